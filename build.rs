@@ -0,0 +1,33 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!(
+        "cargo:rustc-env=PALINGENESIS_FEATURES={}",
+        enabled_features()
+    );
+
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/palingenesis/v1/control.proto");
+        tonic_build::configure()
+            .build_client(true)
+            .build_server(true)
+            .compile_protos(&["proto/palingenesis/v1/control.proto"], &["proto"])
+            .expect("failed to compile gRPC protos");
+    }
+}
+
+/// Enabled cargo features (excluding `default`), e.g. `bots, http-api`.
+fn enabled_features() -> String {
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .filter(|feature| feature != "default")
+        .collect();
+    if features.is_empty() {
+        return "none".to_string();
+    }
+    features.sort();
+    features.join(", ")
+}
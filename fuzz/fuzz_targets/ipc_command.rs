@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use palingenesis::ipc::protocol::IpcCommand;
+use palingenesis::ipc::socket::decode_command_line;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_command_line(data);
+    if let Ok(line) = std::str::from_utf8(data) {
+        let _ = IpcCommand::parse(line);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use std::path::Path;
+
+use libfuzzer_sys::fuzz_target;
+use palingenesis::monitor::frontmatter::parse_session_bytes;
+use palingenesis::resume::ProgressSummary;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(session) = parse_session_bytes(Path::new("fuzz.md"), data) {
+        let summary = ProgressSummary::from_session(&session);
+        let _ = summary.render_next_step("fuzz");
+    }
+});
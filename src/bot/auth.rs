@@ -52,6 +52,7 @@ mod tests {
                 platform: BotPlatform::Discord,
                 user_id: "123".to_string(),
             }],
+            ..BotConfig::default()
         };
 
         let auth = BotAuth::for_platform(&config, BotPlatform::Discord);
@@ -70,6 +71,7 @@ mod tests {
                 platform: BotPlatform::Slack,
                 user_id: "U123".to_string(),
             }],
+            ..BotConfig::default()
         };
 
         let auth = BotAuth::for_platform(&config, BotPlatform::Slack);
@@ -85,6 +87,7 @@ mod tests {
             discord_public_key: None,
             slack_signing_secret: None,
             authorized_users: Vec::new(),
+            ..BotConfig::default()
         };
 
         let auth = BotAuth::for_platform(&config, BotPlatform::Discord);
@@ -100,6 +103,7 @@ mod tests {
             discord_public_key: None,
             slack_signing_secret: None,
             authorized_users: Vec::new(),
+            ..BotConfig::default()
         };
 
         let auth = BotAuth::for_platform(&config, BotPlatform::Discord);
@@ -13,6 +13,7 @@ use crate::bot::auth::BotAuth;
 use crate::bot::commands::{BotCommand, BotCommandResult};
 use crate::bot::executor::CommandExecutor;
 use crate::config::schema::{BotConfig, BotPlatform};
+use crate::daemon::initiator::Initiator;
 use crate::http::server::AppState;
 
 const DISCORD_SIGNATURE_HEADER: &str = "X-Signature-Ed25519";
@@ -94,7 +95,7 @@ pub async fn discord_webhook_handler(
     };
 
     let executor = CommandExecutor::new(Arc::clone(state.daemon_state()), state.events().clone());
-    let result = executor.execute(command);
+    let result = executor.execute(command, &Initiator::Discord { user_id });
     let response = result.to_discord_response();
     (StatusCode::OK, Json(response)).into_response()
 }
@@ -1,13 +1,16 @@
 use std::fs;
 use std::io::{BufRead, BufReader};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::bot::commands::{BotCommand, BotCommandField, BotCommandResult};
 use crate::config::paths::Paths;
+use crate::daemon::initiator::Initiator;
 use crate::daemon::state::DaemonState;
 use crate::http::EventBroadcaster;
 use crate::http::handlers::control::{new_session_daemon, pause_daemon, resume_daemon};
 use crate::http::handlers::status::build_status_snapshot;
+use crate::util::duration;
 
 pub struct CommandExecutor {
     daemon_state: Arc<DaemonState>,
@@ -22,13 +25,14 @@ impl CommandExecutor {
         }
     }
 
-    pub fn execute(&self, command: BotCommand) -> BotCommandResult {
+    /// Run `command` for `initiator`, the chat user who sent it.
+    pub fn execute(&self, command: BotCommand, initiator: &Initiator) -> BotCommandResult {
         match command {
             BotCommand::Status => self.execute_status(),
-            BotCommand::Pause => self.execute_pause(),
-            BotCommand::Resume => self.execute_resume(),
+            BotCommand::Pause => self.execute_pause(initiator),
+            BotCommand::Resume => self.execute_resume(initiator),
             BotCommand::Logs { tail } => self.execute_logs(tail),
-            BotCommand::NewSession => self.execute_new_session(),
+            BotCommand::NewSession => self.execute_new_session(initiator),
             BotCommand::Help => self.execute_help(),
         }
     }
@@ -77,22 +81,22 @@ impl CommandExecutor {
         BotCommandResult::success("Daemon status").with_fields(fields)
     }
 
-    fn execute_pause(&self) -> BotCommandResult {
-        match pause_daemon(&self.daemon_state) {
+    fn execute_pause(&self, initiator: &Initiator) -> BotCommandResult {
+        match pause_daemon(&self.daemon_state, initiator) {
             Ok(()) => BotCommandResult::success("Daemon paused successfully."),
             Err(err) => BotCommandResult::error(err.message),
         }
     }
 
-    fn execute_resume(&self) -> BotCommandResult {
-        match resume_daemon(&self.daemon_state) {
+    fn execute_resume(&self, initiator: &Initiator) -> BotCommandResult {
+        match resume_daemon(&self.daemon_state, initiator) {
             Ok(()) => BotCommandResult::success("Daemon resumed successfully."),
             Err(err) => BotCommandResult::error(err.message),
         }
     }
 
-    fn execute_new_session(&self) -> BotCommandResult {
-        match new_session_daemon(&self.daemon_state) {
+    fn execute_new_session(&self, initiator: &Initiator) -> BotCommandResult {
+        match new_session_daemon(&self.daemon_state, initiator) {
             Ok(session_id) => BotCommandResult::success("New session started")
                 .with_body(format!("Session ID: {session_id}")),
             Err(err) => BotCommandResult::error(err.message),
@@ -100,7 +104,7 @@ impl CommandExecutor {
     }
 
     fn execute_logs(&self, tail: usize) -> BotCommandResult {
-        let log_path = Paths::state_dir().join("daemon.log");
+        let log_path = Paths::log_file();
         if !log_path.exists() {
             return BotCommandResult::error("No log file found");
         }
@@ -163,29 +167,27 @@ fn truncate_log_lines(lines: &[String], max_chars: usize) -> String {
 }
 
 fn format_duration(seconds: u64) -> String {
-    let hours = seconds / 3600;
-    let minutes = (seconds % 3600) / 60;
-    let secs = seconds % 60;
-    if hours > 0 {
-        format!("{hours}h {minutes}m {secs}s")
-    } else if minutes > 0 {
-        format!("{minutes}m {secs}s")
-    } else {
-        format!("{secs}s")
-    }
+    duration::format_precise(Duration::from_secs(seconds))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::daemon::events::ControlEvent;
     use crate::http::EventBroadcaster;
     use crate::ipc::socket::DaemonStateAccess;
 
+    fn slack_user() -> Initiator {
+        Initiator::Slack {
+            user_id: "U123".to_string(),
+        }
+    }
+
     #[test]
     fn status_command_returns_fields() {
         let executor =
             CommandExecutor::new(Arc::new(DaemonState::new()), EventBroadcaster::default());
-        let result = executor.execute(BotCommand::Status);
+        let result = executor.execute(BotCommand::Status, &slack_user());
         assert!(result.success);
         assert!(!result.fields.is_empty());
     }
@@ -193,10 +195,15 @@ mod tests {
     #[test]
     fn pause_command_updates_state() {
         let state = Arc::new(DaemonState::new());
+        let mut control = state.control_events();
         let executor = CommandExecutor::new(Arc::clone(&state), EventBroadcaster::default());
-        let result = executor.execute(BotCommand::Pause);
+        let result = executor.execute(BotCommand::Pause, &slack_user());
         assert!(result.success);
         assert!(state.is_paused());
+        assert_eq!(
+            control.try_recv().unwrap(),
+            ControlEvent::Paused(slack_user())
+        );
     }
 
     #[test]
@@ -204,7 +211,7 @@ mod tests {
         let state = Arc::new(DaemonState::new());
         state.pause().unwrap();
         let executor = CommandExecutor::new(Arc::clone(&state), EventBroadcaster::default());
-        let result = executor.execute(BotCommand::Resume);
+        let result = executor.execute(BotCommand::Resume, &slack_user());
         assert!(result.success);
         assert!(!state.is_paused());
     }
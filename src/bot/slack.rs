@@ -15,6 +15,7 @@ use crate::bot::auth::BotAuth;
 use crate::bot::commands::{BotCommand, BotCommandResult};
 use crate::bot::executor::CommandExecutor;
 use crate::config::schema::{BotConfig, BotPlatform};
+use crate::daemon::initiator::Initiator;
 use crate::http::server::AppState;
 
 const SLACK_SIGNATURE_HEADER: &str = "X-Slack-Signature";
@@ -84,7 +85,10 @@ pub async fn slack_webhook_handler(
     };
 
     let executor = CommandExecutor::new(Arc::clone(state.daemon_state()), state.events().clone());
-    let result = executor.execute(command);
+    let initiator = Initiator::Slack {
+        user_id: payload.user_id,
+    };
+    let result = executor.execute(command, &initiator);
     (StatusCode::OK, Json(result.to_slack_response())).into_response()
 }
 
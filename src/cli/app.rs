@@ -2,9 +2,37 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
+use crate::config::schema::DaemonMode;
+
+/// Version string with the cargo features this binary was built with.
+pub const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (features: ",
+    env!("PALINGENESIS_FEATURES"),
+    ")"
+);
+
 #[derive(Parser, Debug)]
-#[command(name = "palingenesis", author, version, about, long_about = None)]
+#[command(name = "palingenesis", author, version = VERSION, about, long_about = None)]
 pub struct Cli {
+    /// Daemon instance to run or talk to; each has its own socket, state and config
+    #[arg(
+        long,
+        global = true,
+        env = "PALINGENESIS_INSTANCE",
+        value_name = "NAME"
+    )]
+    pub instance: Option<String>,
+
+    /// State directory; overrides PALINGENESIS_STATE and `daemon.state_dir`
+    #[arg(long, global = true, value_name = "DIR")]
+    pub state_dir: Option<PathBuf>,
+
+    /// Runtime directory for the socket and PID file; overrides
+    /// PALINGENESIS_RUNTIME and `daemon.runtime_dir`
+    #[arg(long, global = true, value_name = "DIR")]
+    pub runtime_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -21,6 +49,35 @@ pub enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Show additional details (OpenCode endpoint, auto-resume exclusions, jobs)
+        #[arg(short, long)]
+        verbose: bool,
+        /// Output format; `minimal` prints one token
+        /// (monitoring|paused|waiting|degraded|stopped) for status lines
+        #[arg(long, value_enum, default_value_t = StatusFormat::Text, conflicts_with = "json")]
+        format: StatusFormat,
+        /// Exit with 0 monitoring, 1 not running, 2 paused, 3 waiting on a backoff,
+        /// 4 degraded or a session needs attention
+        #[arg(long)]
+        exit_code: bool,
+        /// Print nothing; implies --exit-code
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Check daemon and session health; exit 0 when healthy, 1 otherwise
+    Check {
+        /// Longest state changes may go unsaved (e.g., "30m"; default daemon.check.max_stall_secs)
+        #[arg(long, value_name = "DURATION")]
+        max_stall: Option<String>,
+        /// Oldest an open incident may be (e.g., "2h"; default daemon.check.max_incident_age_secs)
+        #[arg(long, value_name = "DURATION")]
+        max_incident_age: Option<String>,
+        /// GET this URL when healthy and `<URL>/fail` otherwise (e.g., a healthchecks.io check)
+        #[arg(long, value_name = "URL")]
+        ping_url: Option<String>,
+        /// Output each condition as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// View daemon logs
     Logs {
@@ -33,32 +90,245 @@ pub enum Commands {
         /// Show logs since duration (e.g., "1h", "30m", "1d")
         #[arg(short, long)]
         since: Option<String>,
+        /// Only records at this level or more severe (error, warn, info, debug, trace)
+        #[arg(long, value_name = "LEVEL")]
+        level: Option<tracing::Level>,
+    },
+    /// List queued and running daemon jobs
+    Jobs {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Pause monitoring
     Pause,
     /// Resume monitoring
     Resume,
+    /// Switch between acting on stops (`active`) and only reporting them (`observe`)
+    Mode {
+        /// `active` or `observe`
+        mode: DaemonMode,
+    },
+    /// Acknowledge an incident, silencing its further non-critical alerts
+    Ack {
+        /// Incident id from the notification
+        incident_id: String,
+    },
     /// Start a new session
-    NewSession,
+    NewSession {
+        /// Start even if the session hit the resume rate limit
+        #[arg(long, conflicts_with = "prompt")]
+        force: bool,
+        /// Start a session with this prompt as an HTTP API job and wait for it
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Directory under `monitoring.session_dir` to start the session in
+        #[arg(long, requires = "prompt")]
+        workdir: Option<PathBuf>,
+        /// Model to start the session on
+        #[arg(long, requires = "prompt")]
+        model: Option<String>,
+        /// Print the job ID instead of waiting for the session to start
+        #[arg(long, requires = "prompt")]
+        no_wait: bool,
+    },
     /// Configuration management
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
     /// MCP server operations
+    #[cfg(feature = "mcp")]
     Mcp {
         #[command(subcommand)]
         command: McpCommands,
     },
+    /// Inspect auto-resume exclusions
+    Exclusions {
+        #[command(subcommand)]
+        action: ExclusionsAction,
+    },
+    /// Validate Next-step.md files
+    NextStep {
+        #[command(subcommand)]
+        action: NextStepAction,
+    },
+    /// Resolve sessions orphaned by a failed resume
+    Orphans {
+        #[command(subcommand)]
+        action: OrphansAction,
+    },
+    /// Daemon instances on this machine
+    Instances {
+        #[command(subcommand)]
+        action: InstancesAction,
+    },
+    /// Inspect postmortems of failed resume commands
+    Failures {
+        #[command(subcommand)]
+        action: FailuresAction,
+    },
+    /// Inspect transcript snapshots saved when stops were classified
+    Incidents {
+        #[command(subcommand)]
+        action: IncidentsAction,
+    },
+    /// Resolve a deleted session held for operator attention
+    Attention {
+        #[command(subcommand)]
+        action: AttentionAction,
+    },
+    /// List sessions known to the state file, watcher, and OpenCode API
+    Sessions {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Only sessions with this frontmatter status (e.g. in-progress)
+        #[arg(long)]
+        status: Option<String>,
+        /// Only sessions with this routing tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Maximum number of sessions to list
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Inspect the daemon's state file
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+    /// Check the state file against the session files and correct it (daemon stopped)
+    Reconcile {
+        /// Print what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete session artifacts past their `[maintenance]` retention
+    Gc {
+        #[command(subcommand)]
+        action: Option<GcAction>,
+        /// Print what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show resume totals, or estimated API cost and value saved (`--costs`)
+    Stats {
+        /// Break down estimated costs per session (`[metrics.model_costs]`)
+        #[arg(long)]
+        costs: bool,
+        /// Only resumes within this window (e.g. 24h, 7d); needs --costs
+        #[arg(long, requires = "costs")]
+        since: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show when each step of the current session completed
+    Timeline {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List `[[schedules]]` entries or fire one now
+    Schedules {
+        #[command(subcommand)]
+        action: SchedulesAction,
+    },
+    /// Manage git worktrees created for new sessions
+    Worktrees {
+        #[command(subcommand)]
+        action: WorktreesAction,
+    },
+    /// Set up a config, start the daemon and wait for the first session event
+    Quickstart {
+        /// Seconds to wait for a session file to change
+        #[arg(long, default_value_t = crate::cli::commands::quickstart::DEFAULT_QUICKSTART_TIMEOUT_SECS)]
+        timeout: u64,
+    },
+    /// Self-test the install in a throwaway directory (run after upgrading)
+    VerifyInstall {
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Also send a test event through the configured notification channels
+        #[arg(long)]
+        with_notifications: bool,
+    },
+    /// Install the daemon as a service
+    Install {
+        #[command(subcommand)]
+        target: InstallTarget,
+    },
+    /// Remove a service installed with `install`
+    Uninstall {
+        #[command(subcommand)]
+        target: UninstallTarget,
+    },
+    /// Metrics export operations
+    #[cfg(feature = "metrics-push")]
+    Metrics {
+        #[command(subcommand)]
+        action: MetricsAction,
+    },
+    /// Inspect notification delivery
+    #[cfg(feature = "notifications")]
+    Notify {
+        #[command(subcommand)]
+        action: NotifyAction,
+    },
+    /// Webhook receiver helpers
+    #[cfg(feature = "notifications")]
+    Webhook {
+        #[command(subcommand)]
+        action: WebhookAction,
+    },
+    /// Manage secrets in the OS keychain for `${keyring:service/key}` placeholders
+    #[cfg(feature = "keyring")]
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+    /// Inspect HTTP API tokens
+    #[cfg(feature = "http-api")]
+    Tokens {
+        #[command(subcommand)]
+        action: TokensAction,
+    },
+}
+
+/// Output format for `palingenesis status`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusFormat {
+    /// Human-readable report
+    #[default]
+    Text,
+    /// A single state token
+    Minimal,
 }
 
 #[derive(clap::Subcommand, Debug)]
 pub enum DaemonAction {
     /// Start the daemon
     Start {
-        /// Run in foreground (don't daemonize)
+        /// Run in foreground (don't daemonize).
+        /// Exit codes: 0 clean, 2 config error, 3 resource conflict, 4 permission error,
+        /// 5 runtime error
         #[arg(short, long)]
         foreground: bool,
+        /// Report what would be resumed without acting (observe mode)
+        #[arg(long)]
+        observe: bool,
+        /// Rewrite a state file written by a newer version, after backing it
+        /// up to state.v<N>.downgraded.bak
+        #[arg(long)]
+        force_downgrade: bool,
     },
     /// Stop the daemon
     Stop,
@@ -84,6 +354,9 @@ pub enum ConfigAction {
         /// Custom path for config file
         #[arg(long)]
         path: Option<PathBuf>,
+        /// Build the config interactively instead of writing the commented template
+        #[arg(long)]
+        wizard: bool,
     },
     /// Show current configuration
     Show {
@@ -96,6 +369,9 @@ pub enum ConfigAction {
         /// Show effective config (including env overrides)
         #[arg(long)]
         effective: bool,
+        /// Annotate every field with its source (default, file, or env)
+        #[arg(long)]
+        provenance: bool,
     },
     /// Validate configuration
     Validate {
@@ -112,8 +388,298 @@ pub enum ConfigAction {
         #[arg(long)]
         no_validate: bool,
     },
+    /// Show how the effective config differs from a baseline.
+    /// Exits 1 when there are differences
+    Diff {
+        /// `defaults`, `backup` (the copy `config edit` saves), or a TOML file path
+        #[arg(long, default_value = "defaults")]
+        against: String,
+        /// Output changed keys as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ExclusionsAction {
+    /// Report whether a session path would be excluded from auto-resume
+    Test {
+        /// Session file path
+        path: PathBuf,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum NextStepAction {
+    /// Print what a new-session resume would extract from Next-step.md
+    Check {
+        /// Session directory or Next-step.md path
+        path: PathBuf,
+        /// Also print the `{context}` a new session continuing this session file would get
+        #[arg(long, value_name = "FILE")]
+        session: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum InstancesAction {
+    /// List instances found in the runtime directory, with their states
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum OrphansAction {
+    /// List orphaned sessions
+    List,
+    /// Make an orphaned session the current session
+    Adopt {
+        /// Orphaned session file path
+        path: PathBuf,
+    },
+    /// Back up and delete an orphaned session file
+    Remove {
+        /// Orphaned session file path
+        path: PathBuf,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum FailuresAction {
+    /// List postmortem bundles, oldest first
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show one bundle: command, exit status, output, prompt and environment
+    Show {
+        /// Bundle id from `failures list`
+        id: String,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum IncidentsAction {
+    /// List incidents, oldest first
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print an incident's transcript tail with the evidence lines highlighted
+    Show {
+        /// Incident id from `incidents list`
+        id: String,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum AttentionAction {
+    /// Resume monitoring if the file is back, otherwise stop tracking it
+    Clear,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum StateAction {
+    /// Print the state file as JSON, whether it is stored as JSON or msgpack
+    Dump,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum GcAction {
+    /// Let the scheduled GC delete under the current `[maintenance]` settings
+    Approve,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum SchedulesAction {
+    /// List schedules with their next and last firing
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Start the schedule's session now, even if it is disabled
+    Run {
+        /// Schedule name from `schedules list`
+        name: String,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum WorktreesAction {
+    /// List recorded worktrees and their sessions
+    List,
+    /// Remove worktrees whose sessions completed
+    Prune {
+        /// Remove worktrees with uncommitted changes too
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum InstallTarget {
+    /// Write a systemd unit for this binary and config, then reload systemd
+    Systemd {
+        /// Install a user unit under ~/.config/systemd/user (the default)
+        #[arg(long, conflicts_with = "system")]
+        user: bool,
+        /// Install a system unit under /etc/systemd/system
+        #[arg(long)]
+        system: bool,
+        /// Print the unit instead of installing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Also run `systemctl enable --now`
+        #[arg(long, conflicts_with = "dry_run")]
+        enable: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum UninstallTarget {
+    /// Stop, disable and remove the systemd unit
+    Systemd {
+        /// Remove the user unit (the default)
+        #[arg(long, conflicts_with = "system")]
+        user: bool,
+        /// Remove the system unit
+        #[arg(long)]
+        system: bool,
+    },
+}
+
+#[cfg(feature = "metrics-push")]
+#[derive(clap::Subcommand, Debug)]
+pub enum MetricsAction {
+    /// Push the running daemon's metrics to a Pushgateway once (for cron)
+    Push {
+        /// Pushgateway URL (defaults to otel.push_endpoint)
+        #[arg(long)]
+        endpoint: Option<String>,
+        /// `job` grouping label (defaults to otel.push_job_name)
+        #[arg(long)]
+        job: Option<String>,
+        /// `instance` grouping label (defaults to the host name)
+        #[arg(long)]
+        instance: Option<String>,
+    },
 }
 
+#[cfg(feature = "notifications")]
+#[derive(clap::Subcommand, Debug)]
+pub enum NotifyAction {
+    /// Show recent delivery decisions (delivered, filtered, disabled, acknowledged, muted, failed)
+    Recent {
+        /// Only this channel
+        #[arg(long)]
+        channel: Option<String>,
+        /// Only this outcome: delivered, filtered, disabled, acknowledged, muted or failed
+        #[arg(long)]
+        outcome: Option<String>,
+        /// Only decisions at or after this RFC3339 time
+        #[arg(long)]
+        since: Option<String>,
+        /// Maximum number of entries
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Re-read a channel's secrets (file, keyring or env placeholders) in the running daemon
+    Rotate {
+        /// Channel name: ntfy, discord, slack, webhook, or a target's `name`
+        channel: String,
+    },
+    /// List active notification mutes with the time each has left
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop notifications to a channel or of an event type
+    Mute {
+        /// Channel name: ntfy, discord, slack, webhook, or a target's `name`
+        #[arg(long, required_unless_present = "event", conflicts_with = "event")]
+        channel: Option<String>,
+        /// Event type, e.g. resume_attempted
+        #[arg(long)]
+        event: Option<String>,
+        /// Lift the mute after this long (e.g. 30m, 2h); without it, until unmuted
+        #[arg(long = "for", value_name = "DURATION")]
+        duration: Option<String>,
+    },
+    /// Lift a mute set with `notify mute`
+    Unmute {
+        /// Channel name
+        #[arg(long, required_unless_present = "event", conflicts_with = "event")]
+        channel: Option<String>,
+        /// Event type
+        #[arg(long)]
+        event: Option<String>,
+    },
+    /// Summarize incident escalations (`[notifications.escalation]`) and resume costs
+    Digest {
+        /// Only escalations within this window (e.g. 24h, 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[cfg(feature = "notifications")]
+#[derive(clap::Subcommand, Debug)]
+pub enum WebhookAction {
+    /// Print a sample signed webhook request for testing a receiver's verification
+    VerifyExample {
+        /// Signing secret (default: the webhook's `signing_secret`, else an example)
+        #[arg(long)]
+        secret: Option<String>,
+        /// Webhook `name` whose URL and secret to use
+        #[arg(long)]
+        channel: Option<String>,
+    },
+}
+
+#[cfg(feature = "keyring")]
+#[derive(clap::Subcommand, Debug)]
+pub enum SecretAction {
+    /// Store a secret; the value is read from stdin (or prompted), never argv
+    Set {
+        /// Entry as service/key
+        reference: String,
+    },
+    /// Delete a secret
+    Rm {
+        /// Entry as service/key
+        reference: String,
+    },
+    /// List secrets stored with `secret set` (names only)
+    Ls,
+}
+
+#[cfg(feature = "http-api")]
+#[derive(clap::Subcommand, Debug)]
+pub enum TokensAction {
+    /// Show which scopes a token would get (read from stdin when omitted)
+    Check {
+        /// Token to check
+        token: Option<String>,
+    },
+}
+
+#[cfg(feature = "mcp")]
 #[derive(clap::Subcommand, Debug)]
 pub enum McpCommands {
     /// Start MCP server using stdio transport
@@ -151,7 +717,7 @@ mod tests {
         let cli = Cli::try_parse_from(["palingenesis", "daemon", "start"]).unwrap();
         match cli.command {
             Some(Commands::Daemon {
-                action: DaemonAction::Start { foreground },
+                action: DaemonAction::Start { foreground, .. },
             }) => {
                 assert!(!foreground);
             }
@@ -164,7 +730,7 @@ mod tests {
         let cli = Cli::try_parse_from(["palingenesis", "daemon", "start", "--foreground"]).unwrap();
         match cli.command {
             Some(Commands::Daemon {
-                action: DaemonAction::Start { foreground },
+                action: DaemonAction::Start { foreground, .. },
             }) => {
                 assert!(foreground);
             }
@@ -235,18 +801,65 @@ mod tests {
     fn test_status_command() {
         let cli = Cli::try_parse_from(["palingenesis", "status"]).unwrap();
         match cli.command {
-            Some(Commands::Status { json }) => {
+            Some(Commands::Status {
+                json,
+                verbose,
+                format,
+                exit_code,
+                quiet,
+            }) => {
                 assert!(!json);
+                assert!(!verbose);
+                assert_eq!(format, StatusFormat::Text);
+                assert!(!exit_code);
+                assert!(!quiet);
             }
             _ => panic!("Expected Status command"),
         }
     }
 
+    #[test]
+    fn test_status_command_for_scripts() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "status",
+            "--format",
+            "minimal",
+            "--exit-code",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Status {
+                format, exit_code, ..
+            }) => {
+                assert_eq!(format, StatusFormat::Minimal);
+                assert!(exit_code);
+            }
+            _ => panic!("Expected Status command for scripts"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["palingenesis", "status", "--json", "--format", "minimal"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_status_command_with_verbose() {
+        let cli = Cli::try_parse_from(["palingenesis", "status", "--verbose"]).unwrap();
+        match cli.command {
+            Some(Commands::Status { verbose, .. }) => {
+                assert!(verbose);
+            }
+            _ => panic!("Expected Status command with verbose flag"),
+        }
+    }
+
     #[test]
     fn test_status_command_with_json() {
         let cli = Cli::try_parse_from(["palingenesis", "status", "--json"]).unwrap();
         match cli.command {
-            Some(Commands::Status { json }) => {
+            Some(Commands::Status { json, .. }) => {
                 assert!(json);
             }
             _ => panic!("Expected Status command with json flag"),
@@ -261,6 +874,7 @@ mod tests {
                 follow,
                 tail,
                 since,
+                ..
             }) => {
                 assert!(!follow);
                 assert_eq!(tail, 20);
@@ -279,6 +893,7 @@ mod tests {
                 follow,
                 tail,
                 since,
+                ..
             }) => {
                 assert!(follow);
                 assert_eq!(tail, 50);
@@ -299,6 +914,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_logs_command_with_level() {
+        let cli = Cli::try_parse_from(["palingenesis", "logs", "--level", "warn"]).unwrap();
+        match cli.command {
+            Some(Commands::Logs { level, .. }) => {
+                assert_eq!(level, Some(tracing::Level::WARN));
+            }
+            _ => panic!("Expected Logs command with level"),
+        }
+    }
+
     #[test]
     fn test_pause_command() {
         let cli = Cli::try_parse_from(["palingenesis", "pause"]).unwrap();
@@ -311,10 +937,89 @@ mod tests {
         assert!(matches!(cli.command, Some(Commands::Resume)));
     }
 
+    #[test]
+    fn test_mode_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "mode", "observe"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Mode {
+                mode: DaemonMode::Observe
+            })
+        ));
+        assert!(Cli::try_parse_from(["palingenesis", "mode", "sleep"]).is_err());
+    }
+
+    #[test]
+    fn test_ack_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "ack", "20261016T120000.000Z-s"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Ack { incident_id }) if incident_id == "20261016T120000.000Z-s"
+        ));
+        assert!(Cli::try_parse_from(["palingenesis", "ack"]).is_err());
+    }
+
+    #[test]
+    fn test_daemon_start_observe() {
+        let cli = Cli::try_parse_from(["palingenesis", "daemon", "start", "--observe"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Daemon {
+                action: DaemonAction::Start { observe: true, .. },
+            })
+        ));
+    }
+
     #[test]
     fn test_new_session_command() {
         let cli = Cli::try_parse_from(["palingenesis", "new-session"]).unwrap();
-        assert!(matches!(cli.command, Some(Commands::NewSession)));
+        assert!(matches!(
+            cli.command,
+            Some(Commands::NewSession {
+                force: false,
+                prompt: None,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["palingenesis", "new-session", "--force"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::NewSession { force: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_new_session_job_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "new-session",
+            "--prompt",
+            "continue the refactor",
+            "--workdir",
+            "project",
+            "--no-wait",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::NewSession {
+                prompt,
+                workdir,
+                model,
+                no_wait,
+                ..
+            }) => {
+                assert_eq!(prompt.as_deref(), Some("continue the refactor"));
+                assert_eq!(workdir, Some(PathBuf::from("project")));
+                assert_eq!(model, None);
+                assert!(no_wait);
+            }
+            _ => panic!("expected new-session command"),
+        }
+        assert!(Cli::try_parse_from(["palingenesis", "new-session", "--no-wait"]).is_err());
+        assert!(
+            Cli::try_parse_from(["palingenesis", "new-session", "--force", "--prompt", "go"])
+                .is_err()
+        );
     }
 
     #[test]
@@ -322,7 +1027,7 @@ mod tests {
         let cli = Cli::try_parse_from(["palingenesis", "config", "init"]).unwrap();
         match cli.command {
             Some(Commands::Config {
-                action: ConfigAction::Init { force, path },
+                action: ConfigAction::Init { force, path, .. },
             }) => {
                 assert!(!force);
                 assert!(path.is_none());
@@ -336,7 +1041,7 @@ mod tests {
         let cli = Cli::try_parse_from(["palingenesis", "config", "init", "--force"]).unwrap();
         match cli.command {
             Some(Commands::Config {
-                action: ConfigAction::Init { force, path },
+                action: ConfigAction::Init { force, path, .. },
             }) => {
                 assert!(force);
                 assert!(path.is_none());
@@ -357,7 +1062,7 @@ mod tests {
         .unwrap();
         match cli.command {
             Some(Commands::Config {
-                action: ConfigAction::Init { force, path },
+                action: ConfigAction::Init { force, path, .. },
             }) => {
                 assert!(!force);
                 assert_eq!(path.as_deref(), Some(Path::new("/tmp/palingenesis.toml")));
@@ -366,6 +1071,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_config_init_command_with_wizard() {
+        let cli = Cli::try_parse_from(["palingenesis", "config", "init", "--wizard"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config {
+                action: ConfigAction::Init { wizard: true, .. },
+            })
+        ));
+    }
+
     #[test]
     fn test_config_show_command() {
         let cli = Cli::try_parse_from(["palingenesis", "config", "show"]).unwrap();
@@ -376,11 +1092,13 @@ mod tests {
                         json,
                         section,
                         effective,
+                        provenance,
                     },
             }) => {
                 assert!(!json);
                 assert!(section.is_none());
                 assert!(!effective);
+                assert!(!provenance);
             }
             _ => panic!("Expected Config Show command"),
         }
@@ -405,6 +1123,7 @@ mod tests {
                         json,
                         section,
                         effective,
+                        ..
                     },
             }) => {
                 assert!(json);
@@ -484,6 +1203,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_config_diff_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "config", "diff"]).unwrap();
+        match cli.command {
+            Some(Commands::Config {
+                action: ConfigAction::Diff { against, json },
+            }) => {
+                assert_eq!(against, "defaults");
+                assert!(!json);
+            }
+            _ => panic!("Expected Config Diff command"),
+        }
+    }
+
     #[test]
     fn test_invalid_command_fails() {
         let result = Cli::try_parse_from(["palingenesis", "invalid"]);
@@ -502,6 +1235,7 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[cfg(feature = "mcp")]
     #[test]
     fn test_mcp_serve_command() {
         let cli = Cli::try_parse_from(["palingenesis", "mcp", "serve"]).unwrap();
@@ -513,6 +1247,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "mcp")]
     #[test]
     fn test_mcp_config_command() {
         let cli = Cli::try_parse_from(["palingenesis", "mcp", "config"]).unwrap();
@@ -523,4 +1258,469 @@ mod tests {
             _ => panic!("Expected Mcp Config command"),
         }
     }
+
+    #[test]
+    fn test_exclusions_test_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "exclusions", "test", "/tmp/s.md"]).unwrap();
+        match cli.command {
+            Some(Commands::Exclusions {
+                action: ExclusionsAction::Test { path },
+            }) => {
+                assert_eq!(path, Path::new("/tmp/s.md"));
+            }
+            _ => panic!("Expected Exclusions Test command"),
+        }
+    }
+
+    #[test]
+    fn test_jobs_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "jobs", "--json"]).unwrap();
+        match cli.command {
+            Some(Commands::Jobs { json }) => assert!(json),
+            _ => panic!("Expected Jobs command"),
+        }
+    }
+
+    #[test]
+    fn test_sessions_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "sessions",
+            "--json",
+            "--status",
+            "in-progress",
+            "--tag",
+            "proj-a",
+            "--limit",
+            "3",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Sessions {
+                json,
+                status,
+                tag,
+                limit,
+            }) => {
+                assert!(json);
+                assert_eq!(status.as_deref(), Some("in-progress"));
+                assert_eq!(tag.as_deref(), Some("proj-a"));
+                assert_eq!(limit, Some(3));
+            }
+            _ => panic!("Expected Sessions command"),
+        }
+    }
+
+    #[test]
+    fn test_stats_costs_command() {
+        let cli =
+            Cli::try_parse_from(["palingenesis", "stats", "--costs", "--since", "7d"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Stats { costs: true, ref since, json: false })
+                if since.as_deref() == Some("7d")
+        ));
+        assert!(Cli::try_parse_from(["palingenesis", "stats", "--since", "7d"]).is_err());
+    }
+
+    #[test]
+    fn test_timeline_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "timeline", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Timeline { json: true })
+        ));
+    }
+
+    #[test]
+    fn test_check_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "check",
+            "--max-stall",
+            "30m",
+            "--ping-url",
+            "https://hc-ping.com/abc",
+            "--json",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Check {
+                max_stall,
+                max_incident_age,
+                ping_url,
+                json,
+            }) => {
+                assert_eq!(max_stall.as_deref(), Some("30m"));
+                assert!(max_incident_age.is_none());
+                assert_eq!(ping_url.as_deref(), Some("https://hc-ping.com/abc"));
+                assert!(json);
+            }
+            _ => panic!("Expected Check command"),
+        }
+    }
+
+    #[test]
+    fn test_quickstart_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "quickstart"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Quickstart { timeout: 60 })
+        ));
+        let cli = Cli::try_parse_from(["palingenesis", "quickstart", "--timeout", "5"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Quickstart { timeout: 5 })
+        ));
+    }
+
+    #[test]
+    fn test_verify_install_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "verify-install"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::VerifyInstall {
+                json: false,
+                with_notifications: false
+            })
+        ));
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "verify-install",
+            "--json",
+            "--with-notifications",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::VerifyInstall {
+                json: true,
+                with_notifications: true
+            })
+        ));
+    }
+
+    #[test]
+    fn test_install_systemd_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "install",
+            "systemd",
+            "--system",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Install {
+                target: InstallTarget::Systemd {
+                    user: false,
+                    system: true,
+                    dry_run: true,
+                    enable: false,
+                }
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["palingenesis", "install", "systemd", "--user", "--system"])
+                .is_err()
+        );
+        assert!(Cli::try_parse_from(["palingenesis", "uninstall", "systemd", "--user"]).is_ok());
+    }
+
+    #[test]
+    fn test_next_step_check_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "next-step", "check", "/w"]).unwrap();
+        match cli.command {
+            Some(Commands::NextStep {
+                action: NextStepAction::Check { path, session },
+            }) => {
+                assert_eq!(path, Path::new("/w"));
+                assert!(session.is_none());
+            }
+            _ => panic!("Expected NextStep Check command"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "next-step",
+            "check",
+            "/w",
+            "--session",
+            "/w/session.md",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::NextStep {
+                action: NextStepAction::Check { session, .. },
+            }) => {
+                assert_eq!(session.as_deref(), Some(Path::new("/w/session.md")));
+            }
+            _ => panic!("Expected NextStep Check command"),
+        }
+    }
+
+    #[test]
+    fn test_orphans_remove_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "orphans", "remove", "/tmp/s.md", "--yes"])
+            .unwrap();
+        match cli.command {
+            Some(Commands::Orphans {
+                action: OrphansAction::Remove { path, yes },
+            }) => {
+                assert_eq!(path, Path::new("/tmp/s.md"));
+                assert!(yes);
+            }
+            _ => panic!("Expected Orphans Remove command"),
+        }
+    }
+
+    #[test]
+    fn test_failures_show_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "failures",
+            "show",
+            "20260101T000000.000Z-session",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Failures {
+                action: FailuresAction::Show { id },
+            }) => assert_eq!(id, "20260101T000000.000Z-session"),
+            _ => panic!("Expected Failures Show command"),
+        }
+    }
+
+    #[test]
+    fn test_incidents_show_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "incidents",
+            "show",
+            "20260101T000000.000Z-session",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Incidents {
+                action: IncidentsAction::Show { id },
+            }) => assert_eq!(id, "20260101T000000.000Z-session"),
+            _ => panic!("Expected Incidents Show command"),
+        }
+    }
+
+    #[test]
+    fn test_state_dump_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "state", "dump"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::State {
+                action: StateAction::Dump
+            })
+        ));
+    }
+
+    #[test]
+    fn test_reconcile_dry_run_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "reconcile", "--dry-run"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Reconcile {
+                dry_run: true,
+                json: false
+            })
+        ));
+    }
+
+    #[test]
+    fn test_gc_commands() {
+        let cli = Cli::try_parse_from(["palingenesis", "gc", "--dry-run"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Gc {
+                action: None,
+                dry_run: true,
+                json: false
+            })
+        ));
+        let cli = Cli::try_parse_from(["palingenesis", "gc", "approve"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Gc {
+                action: Some(GcAction::Approve),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_schedules_run_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "schedules", "run", "ci-triage"]).unwrap();
+        match cli.command {
+            Some(Commands::Schedules {
+                action: SchedulesAction::Run { name },
+            }) => assert_eq!(name, "ci-triage"),
+            _ => panic!("Expected Schedules Run command"),
+        }
+    }
+
+    #[test]
+    fn test_worktrees_prune_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "worktrees", "prune", "--force"]).unwrap();
+        match cli.command {
+            Some(Commands::Worktrees {
+                action: WorktreesAction::Prune { force },
+            }) => assert!(force),
+            _ => panic!("Expected Worktrees Prune command"),
+        }
+    }
+
+    #[cfg(feature = "metrics-push")]
+    #[test]
+    fn test_metrics_push_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "metrics",
+            "push",
+            "--endpoint",
+            "http://gw:9091",
+            "--job",
+            "cron",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Metrics {
+                action:
+                    MetricsAction::Push {
+                        endpoint,
+                        job,
+                        instance,
+                    },
+            }) => {
+                assert_eq!(endpoint.as_deref(), Some("http://gw:9091"));
+                assert_eq!(job.as_deref(), Some("cron"));
+                assert!(instance.is_none());
+            }
+            _ => panic!("Expected Metrics Push command"),
+        }
+    }
+
+    #[cfg(feature = "notifications")]
+    #[test]
+    fn test_notify_recent_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "notify",
+            "recent",
+            "--outcome",
+            "failed",
+            "--channel",
+            "ntfy",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Notify {
+                action:
+                    NotifyAction::Recent {
+                        channel,
+                        outcome,
+                        since,
+                        limit,
+                        json,
+                    },
+            }) => {
+                assert_eq!(channel.as_deref(), Some("ntfy"));
+                assert_eq!(outcome.as_deref(), Some("failed"));
+                assert!(since.is_none());
+                assert_eq!(limit, 20);
+                assert!(!json);
+            }
+            _ => panic!("Expected Notify Recent command"),
+        }
+
+        let cli = Cli::try_parse_from(["palingenesis", "notify", "rotate", "phone"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Notify {
+                action: NotifyAction::Rotate { ref channel },
+            }) if channel == "phone"
+        ));
+
+        let cli =
+            Cli::try_parse_from(["palingenesis", "notify", "digest", "--since", "24h"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Notify {
+                action: NotifyAction::Digest { ref since, json: false },
+            }) if since.as_deref() == Some("24h")
+        ));
+    }
+
+    #[cfg(feature = "notifications")]
+    #[test]
+    fn test_webhook_verify_example_command() {
+        let cli = Cli::try_parse_from([
+            "palingenesis",
+            "webhook",
+            "verify-example",
+            "--secret",
+            "whsec_test",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Webhook {
+                action: WebhookAction::VerifyExample { ref secret, channel: None },
+            }) if secret.as_deref() == Some("whsec_test")
+        ));
+    }
+
+    #[test]
+    fn test_attention_clear_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "attention", "clear"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Attention {
+                action: AttentionAction::Clear
+            })
+        ));
+    }
+
+    #[cfg(feature = "http-api")]
+    #[test]
+    fn test_tokens_check_command() {
+        let cli = Cli::try_parse_from(["palingenesis", "tokens", "check", "s3cret"]).unwrap();
+        match cli.command {
+            Some(Commands::Tokens {
+                action: TokensAction::Check { token },
+            }) => assert_eq!(token.as_deref(), Some("s3cret")),
+            _ => panic!("Expected Tokens Check command"),
+        }
+    }
+
+    #[cfg(feature = "keyring")]
+    #[test]
+    fn test_secret_commands() {
+        let cli =
+            Cli::try_parse_from(["palingenesis", "secret", "set", "palingenesis/slack"]).unwrap();
+        match cli.command {
+            Some(Commands::Secret {
+                action: SecretAction::Set { reference },
+            }) => assert_eq!(reference, "palingenesis/slack"),
+            _ => panic!("Expected Secret Set command"),
+        }
+
+        // Values never come from argv.
+        assert!(
+            Cli::try_parse_from(["palingenesis", "secret", "set", "pal/slack", "hunter2"]).is_err()
+        );
+
+        let cli = Cli::try_parse_from(["palingenesis", "secret", "ls"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Secret {
+                action: SecretAction::Ls
+            })
+        ));
+    }
 }
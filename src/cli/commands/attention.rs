@@ -0,0 +1,30 @@
+use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::state::StateStore;
+
+/// Release the current session held for attention. A running daemon does it
+/// in its own state, which it would otherwise overwrite; without one the
+/// state file is edited directly.
+pub async fn handle_clear() -> anyhow::Result<()> {
+    let message = match IpcClient::clear_attention().await {
+        Ok(message) => message,
+        Err(IpcClientError::NotRunning) => {
+            let store = StateStore::new();
+            let mut state = store.load();
+            let message = state.clear_attention();
+            if message.is_some() {
+                store.save(&state)?;
+            }
+            message
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    match message {
+        Some(message) => println!("{message}"),
+        None => println!("No session needs attention"),
+    }
+    Ok(())
+}
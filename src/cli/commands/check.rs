@@ -0,0 +1,435 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::cli::commands::logs::parse_duration;
+use crate::cli::commands::status::StatusSummary;
+use crate::config::schema::CheckConfig;
+use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::ipc::protocol::DaemonStatus;
+use crate::util::duration;
+
+/// Limits the conditions of `palingenesis check` are held to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckThresholds {
+    /// Longest state changes may wait to be written.
+    pub max_stall: Duration,
+    /// Oldest an open incident may be.
+    pub max_incident_age: Duration,
+}
+
+impl CheckThresholds {
+    pub fn from_config(config: &CheckConfig) -> Self {
+        Self {
+            max_stall: Duration::from_secs(config.max_stall_secs),
+            max_incident_age: Duration::from_secs(config.max_incident_age_secs),
+        }
+    }
+}
+
+/// One evaluated condition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Condition {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Condition {
+    fn new(name: &'static str, ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Outcome of `palingenesis check`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckReport {
+    pub healthy: bool,
+    /// One-line summary: the failed conditions, or the daemon's state.
+    pub reason: String,
+    pub conditions: Vec<Condition>,
+}
+
+impl CheckReport {
+    fn from_conditions(conditions: Vec<Condition>, healthy_reason: String) -> Self {
+        let failed: Vec<&str> = conditions
+            .iter()
+            .filter(|condition| !condition.ok)
+            .map(|condition| condition.detail.as_str())
+            .collect();
+        let healthy = failed.is_empty();
+        let reason = if healthy {
+            healthy_reason
+        } else {
+            failed.join("; ")
+        };
+        Self {
+            healthy,
+            reason,
+            conditions,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.healthy { 0 } else { 1 }
+    }
+
+    /// Line printed without `--json`.
+    pub fn summary_line(&self) -> String {
+        let verdict = if self.healthy { "OK" } else { "FAIL" };
+        format!("{verdict}: {}", self.reason)
+    }
+}
+
+pub async fn handle_check(
+    max_stall: Option<String>,
+    max_incident_age: Option<String>,
+    ping_url: Option<String>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let mut thresholds = CheckThresholds::from_config(&config.daemon.check);
+    if let Some(max_stall) = max_stall {
+        thresholds.max_stall = parse_duration(&max_stall)?;
+    }
+    if let Some(max_incident_age) = max_incident_age {
+        thresholds.max_incident_age = parse_duration(&max_incident_age)?;
+    }
+
+    let report = run_check(&thresholds, Utc::now()).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report.summary_line());
+    }
+
+    if let Some(url) = ping_url.or(config.daemon.check.ping_url) {
+        let target = ping_target(&url, report.healthy);
+        if let Err(err) = ping(&target).await {
+            eprintln!("Failed to ping {target}: {err:#}");
+        }
+    }
+
+    if !report.healthy {
+        std::process::exit(report.exit_code());
+    }
+    Ok(())
+}
+
+/// Ask the daemon for its status and evaluate it.
+pub async fn run_check(thresholds: &CheckThresholds, now: DateTime<Utc>) -> CheckReport {
+    evaluate(IpcClient::status().await, thresholds, now)
+}
+
+/// Evaluate a status reply against `thresholds`.
+///
+/// A daemon that cannot be reached fails on that alone; the remaining
+/// conditions need its status.
+pub fn evaluate(
+    result: Result<DaemonStatus, IpcClientError>,
+    thresholds: &CheckThresholds,
+    now: DateTime<Utc>,
+) -> CheckReport {
+    let status = match result {
+        Ok(status) => status,
+        Err(err) => {
+            let detail = match err {
+                IpcClientError::NotRunning => "daemon not running".to_string(),
+                IpcClientError::Timeout => "daemon unresponsive".to_string(),
+                err => format!("failed to query daemon: {err}"),
+            };
+            return CheckReport::from_conditions(
+                vec![Condition::new("daemon", false, detail)],
+                String::new(),
+            );
+        }
+    };
+
+    let summary = StatusSummary::from_status(&status);
+    let attention = status.needs_attention
+        || status
+            .resume_counters
+            .iter()
+            .any(|counter| counter.loop_suspected_at.is_some());
+    // Attention also makes the summary degraded; report it once, below.
+    let degraded = summary == StatusSummary::Degraded && !attention;
+    let conditions = vec![
+        if degraded {
+            Condition::new(
+                "daemon",
+                false,
+                format!("daemon degraded (state {})", status.state),
+            )
+        } else {
+            Condition::new("daemon", true, format!("daemon {}", status.state))
+        },
+        if attention {
+            Condition::new(
+                "attention",
+                false,
+                "session needs attention (see `palingenesis attention clear`)",
+            )
+        } else {
+            Condition::new("attention", true, "no session needs attention")
+        },
+        age_condition(
+            "incident",
+            status.incident_opened_at,
+            thresholds.max_incident_age,
+            now,
+            "incident open for",
+            "no open incident",
+        ),
+        age_condition(
+            "state_save",
+            status.state_unsaved_since,
+            thresholds.max_stall,
+            now,
+            "state unsaved for",
+            "state saved",
+        ),
+        match status.state_newer_version {
+            Some(version) => Condition::new(
+                "state_file",
+                false,
+                format!("state file is from newer schema v{version}; not writing it"),
+            ),
+            None => Condition::new("state_file", true, "state file writable"),
+        },
+        match status.watcher_alive {
+            Some(true) => Condition::new("watcher", true, "session watcher running"),
+            Some(false) => Condition::new("watcher", false, "session watcher stopped"),
+            None => Condition::new("watcher", true, "session watcher not reported"),
+        },
+    ];
+    CheckReport::from_conditions(conditions, format!("daemon {}", status.state))
+}
+
+fn age_condition(
+    name: &'static str,
+    since: Option<DateTime<Utc>>,
+    limit: Duration,
+    now: DateTime<Utc>,
+    label: &str,
+    none: &str,
+) -> Condition {
+    let Some(since) = since else {
+        return Condition::new(name, true, none);
+    };
+    let age = duration::between(since, now);
+    Condition::new(
+        name,
+        age <= limit,
+        format!(
+            "{label} {} (limit {})",
+            duration::format_compact(age),
+            duration::format_compact(limit)
+        ),
+    )
+}
+
+/// URL to ping for a result: `url` itself on success, healthchecks.io's
+/// `<url>/fail` otherwise.
+pub fn ping_target(url: &str, healthy: bool) -> String {
+    if healthy {
+        url.to_string()
+    } else {
+        format!("{}/fail", url.trim_end_matches('/'))
+    }
+}
+
+#[cfg(feature = "notifications")]
+async fn ping(url: &str) -> anyhow::Result<()> {
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .get(url)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!("server returned {}", response.status());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "notifications"))]
+async fn ping(_url: &str) -> anyhow::Result<()> {
+    anyhow::bail!("--ping-url needs a build with the `notifications` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use tempfile::tempdir;
+    use tokio_util::sync::CancellationToken;
+
+    use crate::ipc::socket::{DaemonStateAccess, IpcServer};
+    use crate::test_utils::ENV_LOCK;
+
+    struct MockState {
+        status: Mutex<DaemonStatus>,
+    }
+
+    impl DaemonStateAccess for MockState {
+        fn get_status(&self) -> DaemonStatus {
+            self.status.lock().unwrap().clone()
+        }
+
+        fn pause(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn resume(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn new_session(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn reload_config(&self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn healthy() -> DaemonStatus {
+        DaemonStatus {
+            state: "monitoring".to_string(),
+            uptime_secs: 60,
+            current_session: Some("/tmp/session.md".to_string()),
+            saves_count: 0,
+            total_resumes: 0,
+            time_saved_seconds: 0.0,
+            time_saved_human: None,
+            opencode_endpoint: None,
+            exclusion_patterns: Vec::new(),
+            watch_filter: None,
+            jobs: Vec::new(),
+            deferred_until: None,
+            deferred_reason: None,
+            quota_resume_at: None,
+            resume_counters: Vec::new(),
+            needs_attention: false,
+            instance: None,
+            clock_skew_secs: None,
+            debounce_window_ms: None,
+            state_newer_version: None,
+            opencode_version: None,
+            restart_correlation: None,
+            mode: Default::default(),
+            incident_opened_at: None,
+            state_unsaved_since: None,
+            watcher_alive: Some(true),
+            http_listeners: None,
+        }
+    }
+
+    fn thresholds() -> CheckThresholds {
+        CheckThresholds {
+            max_stall: Duration::from_secs(30 * 60),
+            max_incident_age: Duration::from_secs(2 * 3600),
+        }
+    }
+
+    fn set_env_var(key: &str, value: impl AsRef<std::ffi::OsStr>) {
+        unsafe {
+            std::env::set_var(key, value);
+        }
+    }
+
+    fn remove_env_var(key: &str) {
+        unsafe {
+            std::env::remove_var(key);
+        }
+    }
+
+    #[tokio::test]
+    async fn check_follows_the_daemon_status() {
+        let _lock = ENV_LOCK.lock().await;
+        let temp = tempdir().unwrap();
+        set_env_var("PALINGENESIS_RUNTIME", temp.path());
+        let now = Utc::now();
+        let url = "https://hc-ping.com/abc";
+
+        let report = run_check(&thresholds(), now).await;
+        assert_eq!(report.reason, "daemon not running");
+        assert_eq!(report.exit_code(), 1);
+
+        let state = Arc::new(MockState {
+            status: Mutex::new(healthy()),
+        });
+        let mut server = IpcServer::new();
+        server.bind().await.unwrap();
+        let cancel = CancellationToken::new();
+        let server_state = Arc::clone(&state);
+        let server_cancel = cancel.clone();
+        tokio::spawn(async move { server.run(server_state, server_cancel).await });
+
+        let report = run_check(&thresholds(), now).await;
+        assert_eq!(report.summary_line(), "OK: daemon monitoring");
+        assert_eq!(report.exit_code(), 0);
+        assert_eq!(ping_target(url, report.healthy), url);
+
+        // Degraded: a session is held for attention and the watcher died.
+        {
+            let mut status = state.status.lock().unwrap();
+            status.needs_attention = true;
+            status.watcher_alive = Some(false);
+        }
+        let report = run_check(&thresholds(), now).await;
+        assert_eq!(
+            report.reason,
+            "session needs attention (see `palingenesis attention clear`); session watcher stopped"
+        );
+        assert_eq!(report.exit_code(), 1);
+        assert_eq!(
+            ping_target(url, report.healthy),
+            "https://hc-ping.com/abc/fail"
+        );
+
+        // Stalled: state has gone unsaved, and an incident stayed open, too long.
+        *state.status.lock().unwrap() = DaemonStatus {
+            state_unsaved_since: Some(now - chrono::TimeDelta::minutes(45)),
+            incident_opened_at: Some(now - chrono::TimeDelta::hours(1)),
+            ..healthy()
+        };
+        let report = run_check(&thresholds(), now).await;
+        assert_eq!(report.reason, "state unsaved for 45m (limit 30m)");
+        assert_eq!(report.exit_code(), 1);
+        let incident = &report.conditions[2];
+        assert_eq!(incident.name, "incident");
+        assert!(incident.ok);
+
+        cancel.cancel();
+        remove_env_var("PALINGENESIS_RUNTIME");
+    }
+
+    #[test]
+    fn state_outside_the_contract_is_degraded() {
+        let status = DaemonStatus {
+            state: "starting".to_string(),
+            watcher_alive: None,
+            http_listeners: None,
+            ..healthy()
+        };
+        let report = evaluate(Ok(status), &thresholds(), Utc::now());
+        assert_eq!(report.reason, "daemon degraded (state starting)");
+        assert!(report.conditions[4].ok);
+    }
+
+    #[test]
+    fn fail_ping_drops_a_trailing_slash() {
+        assert_eq!(
+            ping_target("https://hc-ping.com/abc/", false),
+            "https://hc-ping.com/abc/fail"
+        );
+    }
+}
@@ -1,15 +1,27 @@
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::{self, Command};
 
 use anyhow::Context;
+#[cfg(feature = "notifications")]
+use chrono::Utc;
 use serde::Serialize;
 
+use crate::cli::commands::wizard::{self, TerminalPrompter};
+
 use crate::config::Paths;
+use crate::config::diff::ConfigDiff;
+use crate::config::provenance::{ConfigProvenance, ConfigSource};
 use crate::config::schema::{Config, DiscordConfig, NtfyConfig, SlackConfig, WebhookConfig};
+use crate::config::secrets::{self, SecretError, SecretSource};
 use crate::config::validation::validate_config;
+use crate::monitor::detection::detect_assistants;
+#[cfg(feature = "notifications")]
+use crate::notify::Dispatcher;
+#[cfg(feature = "notifications")]
+use crate::notify::events::NotificationEvent;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ValidationStatus {
@@ -18,7 +30,11 @@ enum ValidationStatus {
     Invalid,
 }
 
-pub async fn handle_init(force: bool, custom_path: Option<PathBuf>) -> anyhow::Result<()> {
+pub async fn handle_init(
+    force: bool,
+    custom_path: Option<PathBuf>,
+    wizard: bool,
+) -> anyhow::Result<()> {
     let config_path = custom_path.unwrap_or_else(Paths::config_file);
 
     if config_path.exists() && !force && !confirm_overwrite(&config_path)? {
@@ -26,18 +42,80 @@ pub async fn handle_init(force: bool, custom_path: Option<PathBuf>) -> anyhow::R
         return Ok(());
     }
 
+    let answers = if wizard && !io::stdin().is_terminal() {
+        eprintln!(
+            "Note: --wizard needs an interactive terminal; writing the commented template instead."
+        );
+        None
+    } else if wizard {
+        let detected = detect_assistants().assistants;
+        Some(wizard::run(&mut TerminalPrompter, &detected).context("Setup wizard aborted")?)
+    } else {
+        None
+    };
+
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)?;
         set_dir_permissions(parent);
     }
 
-    let config_content = generate_default_config_toml();
+    let config_content = match &answers {
+        Some(answers) => answers.to_toml(),
+        None => generate_default_config_toml(),
+    };
     fs::write(&config_path, config_content)?;
     set_file_permissions(&config_path);
 
     println!("\x1b[32mConfig created at {}\x1b[0m", config_path.display());
-    println!("Edit with: palingenesis config edit");
+    let Some(answers) = answers else {
+        println!("Edit with: palingenesis config edit");
+        return Ok(());
+    };
 
+    if validate_config_at_path(&config_path)? == ValidationStatus::Invalid {
+        println!("Fix the errors above with: palingenesis config edit");
+        return Ok(());
+    }
+    if answers.send_test {
+        send_test_notification(&config_path).await?;
+    }
+    println!("\nNext steps:");
+    println!("  palingenesis daemon start    # start monitoring");
+    println!("  palingenesis status          # check the daemon");
+    println!("  palingenesis config edit     # adjust settings later");
+    if let Some(http) = &answers.http {
+        println!(
+            "  curl -H 'Authorization: Bearer <http_token from the config>' http://127.0.0.1:{}/api/v1/status",
+            http.port
+        );
+    }
+    Ok(())
+}
+
+/// Send one test event through every configured channel and report the result.
+#[cfg(feature = "notifications")]
+async fn send_test_notification(config_path: &Path) -> anyhow::Result<()> {
+    let config = load_config_from_path(config_path)?;
+    let summary = Dispatcher::from_config(&config.notifications, config.daemon.locale)
+        .dispatch(NotificationEvent::TestNotification {
+            timestamp: Utc::now(),
+            message: "palingenesis is set up and can reach this channel".to_string(),
+        })
+        .await;
+    if summary.failures == 0 {
+        println!("Test notification sent to {} channel(s)", summary.successes);
+    } else {
+        println!(
+            "Test notification failed for: {} (see `palingenesis notify recent`)",
+            summary.failed_channels.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "notifications"))]
+async fn send_test_notification(_config_path: &Path) -> anyhow::Result<()> {
+    println!("Test notification skipped: built without the notifications feature");
     Ok(())
 }
 
@@ -45,7 +123,12 @@ pub async fn handle_show(
     json: bool,
     section: Option<String>,
     effective: bool,
+    provenance: bool,
 ) -> anyhow::Result<()> {
+    if provenance {
+        return show_provenance(json, section);
+    }
+
     let config_path = Paths::config_file();
     let using_defaults = !config_path.exists();
 
@@ -57,9 +140,9 @@ pub async fn handle_show(
 
     if effective {
         let overrides = apply_env_overrides(&mut config)?;
-        if !overrides.is_empty() {
+        if !overrides.applied.is_empty() {
             eprintln!("Using environment overrides:");
-            for (key, value) in overrides {
+            for (key, value) in overrides.applied {
                 eprintln!("  {key}={value}");
             }
             eprintln!();
@@ -81,6 +164,29 @@ pub async fn handle_show(
     Ok(())
 }
 
+/// Effective config annotated with the source of every field.
+fn show_provenance(json: bool, section: Option<String>) -> anyhow::Result<()> {
+    let section = section.map(|section| section.to_lowercase());
+    if let Some(section) = &section {
+        ensure_known_section(section)?;
+    }
+
+    let (config, provenance) = load_config_with_provenance()?;
+    let output = if json {
+        serde_json::to_string_pretty(&provenance.to_json(&config, section.as_deref()))?
+    } else {
+        let mut output = match provenance.file() {
+            Some(path) => format!("# Config file: {}\n", path.display()),
+            None => "# No config file found; unset fields use built-in defaults\n".to_string(),
+        };
+        output.push_str(&provenance.render_tree(&config, section.as_deref()));
+        output
+    };
+
+    println!("{output}");
+    Ok(())
+}
+
 pub async fn handle_validate(custom_path: Option<PathBuf>) -> anyhow::Result<()> {
     let config_path = custom_path.unwrap_or_else(Paths::config_file);
     match validate_config_at_path(&config_path)? {
@@ -96,9 +202,14 @@ pub async fn handle_edit(custom_path: Option<PathBuf>, no_validate: bool) -> any
 
     if !config_path.exists() {
         println!("No config file found. Creating default config...");
-        handle_init(false, Some(config_path.clone())).await?;
+        handle_init(false, Some(config_path.clone()), false).await?;
     }
 
+    let backup = backup_path(&config_path);
+    fs::copy(&config_path, &backup)
+        .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+    set_file_permissions(&backup);
+
     let editor = find_editor()?;
     println!("Opening {} with {}...", config_path.display(), editor);
 
@@ -124,6 +235,73 @@ pub async fn handle_edit(custom_path: Option<PathBuf>, no_validate: bool) -> any
     Ok(())
 }
 
+/// Compare the effective config with `against`; exits 1 when they differ.
+pub async fn handle_diff(against: &str, json: bool) -> anyhow::Result<()> {
+    let (current, provenance) = load_config_with_provenance()?;
+    let current_label = match provenance.file() {
+        Some(path) => path.display().to_string(),
+        None => "effective (no config file)".to_string(),
+    };
+
+    let (baseline, baseline_label, baseline_secrets) = match against {
+        "defaults" => (Config::default(), "defaults".to_string(), Vec::new()),
+        "backup" => {
+            let path = backup_path(&Paths::config_file());
+            if !path.exists() {
+                anyhow::bail!(
+                    "No backup at {} (`palingenesis config edit` writes one before opening the editor)",
+                    path.display()
+                );
+            }
+            load_baseline(&path)?
+        }
+        path => load_baseline(Path::new(path))?,
+    };
+
+    let diff = ConfigDiff::new(&baseline, &current).with_secret_fields(
+        provenance
+            .secret_fields()
+            .map(str::to_string)
+            .chain(baseline_secrets),
+    );
+
+    if json {
+        let output = serde_json::json!({
+            "baseline": baseline_label,
+            "current": current_label,
+            "changes": diff.changes(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if diff.is_empty() {
+        println!("No differences from {baseline_label}");
+    } else {
+        print!("{}", diff.unified(&baseline_label, &current_label));
+    }
+
+    if !diff.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// A config file to diff against, with the fields it resolved from the keychain.
+fn load_baseline(path: &Path) -> anyhow::Result<(Config, String, Vec<String>)> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let raw: toml::Value = toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+    let (config, secrets) = resolve_config(raw, path)?;
+    let fields = secrets.into_iter().map(|(field, _)| field).collect();
+    Ok((config, path.display().to_string(), fields))
+}
+
+/// Where `config edit` keeps the previous version: `config.toml.bak`.
+fn backup_path(config_path: &Path) -> PathBuf {
+    let mut path = config_path.as_os_str().to_owned();
+    path.push(".bak");
+    PathBuf::from(path)
+}
+
 fn confirm_overwrite(path: &Path) -> anyhow::Result<bool> {
     print!(
         "Config already exists at {}. Overwrite? [y/N] ",
@@ -171,12 +349,73 @@ http_enabled = false
 http_port = 7654
 # HTTP server bind address
 http_bind = "127.0.0.1"
-# Optional: Custom PID file path (uses platform default if not set)
+# Optional: Bearer token the HTTP API requires (all routes except /health)
+# http_token = "${keyring:palingenesis/http}"
+# Optional: Directory overrides; --state-dir/--runtime-dir and
+# PALINGENESIS_STATE/PALINGENESIS_RUNTIME take precedence
+# state_dir = "/var/lib/palingenesis"
+# runtime_dir = "/run/user/1000/palingenesis"
+# Optional: Custom PID file path (in the runtime directory if not set)
 # pid_file = "/run/user/1000/palingenesis/palingenesis.pid"
-# Optional: Custom socket path (uses platform default if not set)
+# Optional: Custom socket path (in the runtime directory if not set)
 # socket_path = "/run/user/1000/palingenesis/palingenesis.sock"
-# Optional: Log to file instead of stderr
+# Optional: Log file (daemon.log in the state directory if not set)
 # log_file = "/path/to/daemon.log"
+# Recent log records kept in memory for `palingenesis logs` (0 disables)
+# log_buffer_entries = 2000
+# log_buffer_bytes = 1048576
+# How often pending state changes are written to disk (milliseconds)
+# state_flush_interval_ms = 1000
+# State file encoding: "json" or the compact "msgpack" (`palingenesis state dump` prints either as JSON)
+# state_format = "json"
+# Language of notifications and `status` output: "en" or "ja" (logs stay English)
+# locale = "en"
+# How paths appear in notifications and CLI output: "absolute", "relative"
+# (to the session directory or git workspace), "basename" or "redacted"
+# path_display = "absolute"
+# Path components replaced by [redacted] in "redacted" mode
+# redact_patterns = ["client-*"]
+# Also apply path_display to JSON output (status --json, SSE, webhooks)
+# redact_json = false
+# How often to check for a system sleep gap (seconds, 0 disables)
+# suspend_check_interval_secs = 30
+# Smallest clock gap reported as a system sleep (seconds)
+# suspend_threshold_secs = 120
+# Concurrent daemon jobs per priority (resumes and restarts are never limited)
+# job_normal_concurrency = 4
+# job_background_concurrency = 2
+# "observe" reports what would have been resumed without acting (default "active")
+# mode = "observe"
+# Longest event detail or error text kept in memory (characters)
+# max_retained_content_chars = 2048
+
+# gRPC control API (requires a build with the `grpc` feature)
+# [daemon.grpc]
+# enabled = true
+# bind = "127.0.0.1:7655"
+# tls_cert = "/etc/palingenesis/grpc.crt"  # TLS when cert and key are set
+# tls_key = "/etc/palingenesis/grpc.key"
+# tls_ca = "/etc/palingenesis/clients-ca.crt"  # require client certificates (mTLS)
+
+# Thresholds of `palingenesis check` (for cron and healthchecks.io)
+# [daemon.check]
+# max_stall_secs = 1800         # longest state changes may go unsaved
+# max_incident_age_secs = 7200  # oldest an open incident may be
+# ping_url = "https://hc-ping.com/<uuid>"  # pinged on success, <url>/fail on failure
+
+# Named HTTP API tokens limited to scopes: read (status, events, metrics, history),
+# control (pause/resume), resume (new sessions), admin (everything, incl. rotate).
+# Check what a token gets with `palingenesis tokens check`.
+# [[daemon.http_tokens]]
+# name = "dashboard"           # recorded as the initiator of its requests
+# token = "${keyring:palingenesis/dashboard}"
+# scopes = ["read"]
+
+# Startup check of the state file against the session files (also `palingenesis reconcile`)
+# [daemon.reconcile]
+# enabled = true
+# missing_session = "flag"     # current session file gone: "flag" for attention or "clear"
+# stale_after_secs = 86400     # close open incidents and resume-loop holds older than this
 
 # Session monitoring configuration
 [monitoring]
@@ -188,17 +427,42 @@ auto_detect_interval_secs = 300
 # assistants = ["opencode"]
 # Debounce time for file events (milliseconds)
 debounce_ms = 100
+# "adaptive" shortens the window while events are sparse and lengthens it
+# during bursts, between debounce_min_ms and debounce_max_ms
+# debounce = "fixed"
+# debounce_min_ms = 50
+# debounce_max_ms = 2000
+# Warn when debouncing delayed an event longer than this (milliseconds, 0 = never)
+# debounce_warn_ms = 2000
 # Optional: Session directory override
 # session_dir = "~/.opencode"
 # Optional: Polling interval fallback (seconds)
 # poll_interval_secs = 5
+# Follow symlinks inside the session directory (targets must stay inside it)
+follow_symlinks = false
+# File extensions whose events reach the monitor (empty forwards all)
+include_extensions = ["md", "jsonl"]
+# Paths under the session directory whose events are dropped (globs)
+ignore_patterns = ["**/.git/**", "*.swp", "*~", "*.tmp"]
+# Recursively watch only subdirectories modified in the last N hours; the
+# session directory itself is still watched for new subdirectories (0 = whole tree)
+active_window_hours = 0
+# Most recent files read when listing sessions
+scan_limit = 200
+# Stop classifications run at once; the tracked session and sessions with an
+# open incident go first
+classify_concurrency = 2
+# Drop other sessions' classifications after waiting this long (seconds, 0 = never)
+classify_stale_secs = 300
 
 # OpenCode process monitoring configuration
 [opencode]
 # Enable OpenCode process monitoring
 enabled = false
-# OpenCode serve port
+# OpenCode serve port (0 = discover from the running process)
 serve_port = 4096
+# Discover the serve port from the running OpenCode process
+auto_discover = false
 # OpenCode serve hostname
 serve_hostname = "localhost"
 # Automatically restart OpenCode on crash
@@ -207,6 +471,12 @@ auto_restart = true
 restart_delay_ms = 1000
 # Health check interval (milliseconds)
 health_check_interval = 1000
+# Hold resumable stops this long in case OpenCode crashed (milliseconds, 0 = off)
+crash_settle_ms = 2000
+# After a crash, give up waiting for a healthy restart after this long (seconds)
+crash_restart_timeout_secs = 120
+# After the restart, let held sessions continue on their own this long (seconds)
+crash_grace_secs = 60
 
 # MCP server configuration
 [mcp]
@@ -231,30 +501,251 @@ max_retries = 10
 jitter = true
 # Number of session backups to keep
 backup_count = 10
+# Write backups to this directory instead of next to the session (relative to the state dir)
+# backup_dir = "backups"
+# Backup path under backup_dir ({stem}, {timestamp}, {date}, {workspace}, {ext})
+# backup_filename_template = "{stem}-backup-{timestamp}.{ext}"
+# Skip backups identical to the session's newest backup
+# backup_dedupe = true
+# Session path globs that are never auto-resumed (stops are still reported)
+# exclude_sessions = ["**/experiments/**", "*scratch*"]
+# Restore the newest backup if the current session file is deleted
+# restore_deleted_from_backup = false
+# Start new sessions on the original session's model (fails the resume if unavailable)
+# enforce_model = false
+# Record git branch, HEAD and dirty state with resume history ({git_branch}, {git_sha})
+# capture_git_context = false
+# Bytes of Next-step.md read into new-session prompts (longer files are truncated)
+# max_next_step_bytes = 65536
+# Defer resumes until these windows end (weekly "<day> HH:MM-HH:MM UTC" or "<start>/<end>" RFC3339)
+# maintenance_windows = ["Tue 02:00-04:00 UTC"]
+# Copy completed sessions into the state directory's archive/
+# archive_on_complete = false
+# Postmortem bundles kept under failures/ when a new-session command fails (0 disables)
+# failure_bundle_count = 20
+# Transcript tails saved under incidents/ when a stop is classified (0 disables)
+# incident_count = 50
+# Wait at least this long between automatic resumes of one session (0 disables)
+# min_resume_interval_secs = 120
+# Stop auto-resuming a session and flag a possible loop after this many resumes in an hour (0 disables)
+# max_resumes_per_hour = 10
+# When a daily/monthly quota runs out, resume once at its reset; wait this many hours if no reset time is reported
+# quota_retry_after_hours = 6
+# Several machines syncing the same session directory: take a lease in the
+# session's .palingenesis/ before resuming so only one of them resumes a stop
+# cross_host_lease = false
+# lease_ttl_secs = 300
+
+# Hold resumes until the machine can take them (no condition is set by default)
+[resume.gates]
+# Wait while the 1-minute load average is above this
+# max_load_average = 4.0
+# Wait while running on battery
+# require_ac_power = false
+# Wait while less than this much memory is available (MiB)
+# min_free_memory_mb = 1024
+# Re-check the conditions this often while waiting (seconds)
+# recheck_interval_secs = 60
+# Stop waiting after this long (seconds)
+# max_deferral_secs = 3600
+# Then "proceed" with the resume anyway or "abandon" it
+# on_max_deferral = "proceed"
+
+# Where new sessions after context exhaustion run
+[resume.new_session]
+# "in_place" (default) or "worktree": start each new session in a fresh git worktree
+# workspace_mode = "in_place"
+# Directory for new worktrees (defaults to the state directory's worktrees/)
+# worktree_root = "/home/user/worktrees"
+# Branch prefix for worktrees (branches are named <prefix>/<timestamp>)
+# branch_prefix = "palingenesis"
+
+# What the {context} placeholder of prompt_template contains, in order; later
+# sections are trimmed first when max_chars (0 = unlimited) is exceeded
+# [resume.new_session.context]
+# max_chars = 4000
+# sections = [
+#   "previous_session_path", "steps_completed", "last_step", "remaining_steps",
+#   "model", "stop_reason", { section = "next_step_content", max_chars = 2000 },
+#   "session_tail:20",  # also "custom:<text>"; { ..., enabled = false } skips one
+# ]
+
+# Resume matching stops with your own command instead of a built-in strategy;
+# the first matching entry wins. The command gets the resume context as JSON on
+# stdin and PALINGENESIS_SESSION_PATH, PALINGENESIS_STOP_REASON, ... in its
+# environment; exit 0 means resumed, and a last stdout line such as
+# {"new_session_path": "...", "message": "..."} reports what it did
+# [[resume.custom_strategies]]
+# name = "tmux-restart"
+# command = "~/bin/restart-in-tmux {session}"  # also {stop_reason}, {attempt}
+# timeout_secs = 300
+# [resume.custom_strategies.match]
+# stop_reasons = ["context_exhausted"]  # rate_limit, quota_exhausted, context_exhausted, unknown
+# session = "**/infra/**"
+
+# Start sessions on a timetable (`palingenesis schedules list|run <name>`).
+# Set one of cron (minute hour day month weekday), daily or weekdays (HH:MM)
+# [[schedules]]
+# name = "ci-triage"
+# weekdays = "06:00"  # or daily = "06:00", or cron = "0 6 * * 1-5"
+# timezone = "local"  # or "utc"
+# workdir = "ci"  # relative to monitoring.session_dir
+# prompt = "Triage the CI failures since {date}"  # also {schedule}, {time}, {workdir}
+# assistant = "claude-code"  # defaults to the first of monitoring.assistants
+# enabled = true
+# Skip a firing while a session in workdir is still active
+# skip_if_active = true
+# Run the most recent firing missed while the daemon was down, once, on startup
+# catch_up = false
+
+# Garbage collection of old session artifacts (also `palingenesis gc`). The
+# first run after the retention settings change only reports what it would
+# delete, until `palingenesis gc approve` (or auto_approve = true)
+# [maintenance]
+# enabled = false
+# interval_secs = 86400
+# auto_approve = false
+# safety_window_secs = 86400        # never delete anything modified more recently
+# backup_retention_days = 30        # completed sessions' backups (0 keeps them)
+# incident_retention_days = 30      # incidents/ snapshots (0 keeps them)
+# failure_bundles_on_complete = true  # failures/ bundles once their session completes
+# orphaned_next_steps = true        # Next-step.md files whose sessions are all gone
 
 # Notification configuration (all optional)
 [notifications]
 # Enable notifications globally
 enabled = false
-
-# Webhook notifications
+# Send urgent events to this channel first: ntfy, discord, slack, webhook, or auto
+# primary_channel = "ntfy"
+# urgent_severities = ["critical"]  # info, warning, error, critical
+# primary_timeout_ms = 3000
+# Allow URLs on localhost/private networks (blocked by default to prevent SSRF)
+# allow_private_networks = false
+# Delivery decisions kept for `palingenesis notify recent` and /api/v1/notifications/recent
+# history_size = 200
+# Also persist them as JSON lines (reloaded when the daemon starts)
+# history_file = "/var/lib/palingenesis/notifications.jsonl"
+# Failure notifications carry a one-time link to acknowledge the incident and
+# silence its further notifications; links point at this HTTP API address
+# ack_base_url = "https://laptop.example.net:7654"
+# Links expire after this long if the incident is not resolved first (seconds)
+# ack_ttl_secs = 86400
+# Acknowledging also pauses auto-resume of the session until `palingenesis attention clear`
+# ack_pauses_resume = false
+# Re-read ${file:...} and ${keyring:...} channel credentials this often so
+# externally rotated secrets take effect (seconds, 0 = only on
+# `palingenesis notify rotate` or after a 401/403)
+# secret_refresh_secs = 0
+# Where events without routing tags (daemon events, sessions whose sidecar
+# sets no `tags`) go: all (every channel) or untargeted (only channels
+# without match_tags)
+# untagged_route = "all"
+# Severities still sent to channels and event types muted with
+# `palingenesis notify mute` (MUTE_CHANNEL / MUTE_EVENT)
+# mute_exempt_severities = ["critical"]
+
+# Webhook notifications (use [[notifications.webhook]] for several endpoints)
 # [notifications.webhook]
 # url = "https://your-webhook.example.com/hook"
 # headers = { "Authorization" = "Bearer token" }
+# Sign requests (X-Palingenesis-Signature: v1=<hmac-sha256 of "<timestamp>.<body>">);
+# see `palingenesis webhook verify-example`
+# signing_secret = "${file:/run/secrets/webhook-signing}"
 
-# ntfy.sh notifications
+# ntfy.sh notifications (use [[notifications.ntfy]] for several topics)
 # [notifications.ntfy]
 # topic = "your-topic"
 # server = "https://ntfy.sh"  # optional, default is ntfy.sh
 # priority = "default"  # min, low, default, high, max
+# token = "tk_..."  # optional, for protected topics
+# token = "${keyring:palingenesis/ntfy}"  # or from the OS keychain (keyring feature)
+# token = "${file:/run/secrets/ntfy-token}"  # or from a file ("${env:NAME}" also works)
+#
+# Each entry may also set a unique name (used by primary_channel and metrics)
+# and filters, e.g. a personal topic for failures only:
+# [[notifications.ntfy]]
+# name = "personal"
+# topic = "my-alerts"
+# severities = ["error", "critical"]
+# events = ["resume_failed"]
+#
+# Route by the `tags` in sessions' .palingenesis/resume.toml: match_tags only
+# takes events of sessions carrying one of them, exclude_tags drops those of
+# sessions carrying any of them (also on webhook, discord and slack)
+# [[notifications.ntfy]]
+# name = "proj-a"
+# topic = "proj-a-alerts"
+# match_tags = ["proj-a"]
+# exclude_tags = ["experiments"]
 
 # Discord notifications
 # [notifications.discord]
 # webhook_url = "https://discord.com/api/webhooks/..."
+# locale = "ja"  # optional, default is daemon.locale
+# path_display = "redacted"  # optional, default is daemon.path_display
 
 # Slack notifications
 # [notifications.slack]
 # webhook_url = "https://hooks.slack.com/services/..."
+# locale = "ja"  # optional, default is daemon.locale
+# path_display = "redacted"  # optional, default is daemon.path_display
+
+# Escalate incidents that stay open after a failed resume: each tier's channels
+# are notified once the incident is that old (seconds since it opened), and
+# every tier reached gets a notice when it resumes, is acknowledged or gives up
+# [notifications.escalation]
+# check_interval_secs = 30
+# [[notifications.escalation.tiers]]
+# after_secs = 0
+# channels = ["ntfy"]
+# [[notifications.escalation.tiers]]
+# after_secs = 1800
+# channels = ["slack"]
+# prefix = "<!here>"  # optional text before the message, e.g. a mention
+# [[notifications.escalation.tiers]]
+# after_secs = 3600
+# channels = ["on-call"]  # a [[notifications.webhook]] entry's name
+
+# Discord/Slack bot commands (optional)
+# [bot]
+# enabled = true
+# slack_signing_secret = "${env:SLACK_SIGNING_SECRET}"
+# Serve /api/v1/bot/* from a listener of its own (defaults to the daemon's
+# http_bind/http_port), e.g. to expose the webhooks but not the control API
+# http_bind = "0.0.0.0"
+# http_port = 8443
+
+# Commands run on lifecycle events (optional)
+# [hooks]
+# Run after a session completes (PALINGENESIS_SESSION_PATH is set)
+# on_complete = "notify-send 'Workflow done'"
+# timeout_secs = 60
+
+# Stop-reason classifier tuning (optional, reloaded on RELOAD)
+# [classifier]
+# default_retry_wait_secs = 30
+# max_lines = 100
+# context_threshold_percent = 0.8  # fraction of the context window
+# default_context_size = 200000
+# extra_rate_limit_patterns = ["(?i)quota window exhausted"]
+# extra_context_patterns = ["(?i)conversation too long"]
+# clock_skew_warn_secs = 60  # warn once when provider Date headers disagree by more
+# max_evidence_chars = 512  # longest evidence line kept per classification
+# reopen_growth_bytes = 512  # growth that turns a completed session back into a live one
+#
+# Model context sizes merged over the built-in table (keys are case-insensitive)
+# [classifier.known_context_sizes]
+# "my-local-model" = 32768
+
+# Time-saved and cost estimates (optional)
+# [metrics]
+# manual_restart_time_seconds = 300  # time a manual restart would take (60-1800)
+# hourly_rate = 90.0  # values time saved in currency; "unknown" without it
+# currency = "USD"
+#
+# Price per 1000 tokens, matched case-insensitively with or without a provider prefix
+# [metrics.model_costs]
+# "claude-sonnet-4" = { input_per_1k = 0.003, output_per_1k = 0.015 }
 
 # OpenTelemetry configuration (optional, for observability)
 # [otel]
@@ -267,18 +758,75 @@ enabled = false
 # logs = false
 # metrics = true
 # metrics_enabled = true
+# push_mode = "off"  # off or pushgateway (for hosts Prometheus can't scrape)
+# push_endpoint = "https://pushgateway.example.com:9091"
+# push_interval_secs = 60
+# push_job_name = "palingenesis"
+# push_username = "metrics"
+# push_password = "secret"
+# push_delete_on_shutdown = true
 "#
     .to_string()
 }
 
+/// Config file (or defaults) with environment overrides applied, as the daemon sees it.
+pub(crate) fn load_effective_config() -> anyhow::Result<Config> {
+    let config_path = Paths::config_file();
+    let mut config = if config_path.exists() {
+        load_config_from_path(&config_path)?
+    } else {
+        Config::default()
+    };
+    apply_env_overrides(&mut config)?;
+    Ok(config)
+}
+
+/// Effective config plus where each field came from (default, file, or env).
+fn load_config_with_provenance() -> anyhow::Result<(Config, ConfigProvenance)> {
+    let config_path = Paths::config_file();
+    let mut provenance = ConfigProvenance::new();
+    let mut config = if config_path.exists() {
+        let contents = fs::read_to_string(&config_path)
+            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let raw: toml::Value = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file: {}", config_path.display()))?;
+        provenance.record_file(&raw, config_path.clone());
+        let (config, secrets) = resolve_config(raw, &config_path)?;
+        for (field, secret) in secrets {
+            provenance.record(field, ConfigSource::Secret(secret.to_string()));
+        }
+        config
+    } else {
+        Config::default()
+    };
+
+    let overrides = apply_env_overrides(&mut config)?;
+    provenance.extend(overrides.provenance);
+    Ok((config, provenance))
+}
+
 fn load_config_from_path(path: &Path) -> anyhow::Result<Config> {
     let contents = fs::read_to_string(path)
         .with_context(|| format!("Failed to read config file: {}", path.display()))?;
-    let config = toml::from_str(&contents)
+    let raw: toml::Value = toml::from_str(&contents)
         .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+    let (config, _) = resolve_config(raw, path)?;
     Ok(config)
 }
 
+/// Resolve secret placeholders in `raw`, then deserialize it.
+fn resolve_config(
+    mut raw: toml::Value,
+    path: &Path,
+) -> anyhow::Result<(Config, Vec<(String, SecretSource)>)> {
+    let secrets = secrets::resolve_with_default_store(&mut raw)
+        .with_context(|| format!("Failed to resolve secrets in {}", path.display()))?;
+    let config = raw
+        .try_into()
+        .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+    Ok((config, secrets))
+}
+
 fn validate_config_at_path(path: &Path) -> anyhow::Result<ValidationStatus> {
     if !path.exists() {
         println!("No config file found, will use defaults");
@@ -288,17 +836,27 @@ fn validate_config_at_path(path: &Path) -> anyhow::Result<ValidationStatus> {
     let contents = fs::read_to_string(path)
         .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-    if let Err(err) = toml::from_str::<toml::Value>(&contents) {
-        eprintln!("\x1b[31mConfiguration syntax error:\x1b[0m");
-        eprintln!("  {err}");
-        if let Some((line, column)) = toml_error_location(&contents, &err) {
-            eprintln!("  at line {line}, column {column}");
-            eprintln!("  Suggestion: check syntax near line {line}");
+    let mut raw = match toml::from_str::<toml::Value>(&contents) {
+        Ok(raw) => raw,
+        Err(err) => {
+            eprintln!("\x1b[31mConfiguration syntax error:\x1b[0m");
+            eprintln!("  {err}");
+            if let Some((line, column)) = toml_error_location(&contents, &err) {
+                eprintln!("  at line {line}, column {column}");
+                eprintln!("  Suggestion: check syntax near line {line}");
+            }
+            return Ok(ValidationStatus::Invalid);
         }
+    };
+
+    if let Err(err) = secrets::resolve_with_default_store(&mut raw) {
+        eprintln!("\x1b[31mConfiguration secret error:\x1b[0m");
+        eprintln!("  {err}");
+        eprintln!("  Suggestion: {}", secret_error_suggestion(&err));
         return Ok(ValidationStatus::Invalid);
     }
 
-    let config: Config = match toml::from_str(&contents) {
+    let config: Config = match raw.try_into() {
         Ok(config) => config,
         Err(err) => {
             eprintln!("\x1b[31mConfiguration value error:\x1b[0m");
@@ -329,6 +887,23 @@ fn validate_config_at_path(path: &Path) -> anyhow::Result<ValidationStatus> {
     Ok(ValidationStatus::Valid)
 }
 
+fn secret_error_suggestion(err: &SecretError) -> &'static str {
+    match err {
+        SecretError::InvalidReference(_) => "write placeholders as ${keyring:service/key}",
+        SecretError::EmptyPlaceholder(_) => {
+            "write placeholders as ${file:/path/to/secret} or ${env:NAME}"
+        }
+        SecretError::NotFound(_) => "store the value with `palingenesis secret set`",
+        SecretError::Unavailable(_) => {
+            "start a Secret Service provider (e.g. gnome-keyring) or use an environment variable instead"
+        }
+        SecretError::FeatureDisabled(_) => "rebuild with `--features keyring`",
+        SecretError::File { .. } => "check that the secret file exists and is readable",
+        SecretError::EnvUnset(_) => "export the variable in the environment that loads the config",
+        SecretError::Index { .. } => "check permissions on the state directory",
+    }
+}
+
 fn toml_error_location(contents: &str, err: &toml::de::Error) -> Option<(usize, usize)> {
     let span = err.span()?;
     Some(line_col_from_offset(contents, span.start))
@@ -402,6 +977,29 @@ fn format_config(config: &Config, json: bool) -> anyhow::Result<String> {
     }
 }
 
+const SECTIONS: &[&str] = &[
+    "daemon",
+    "monitoring",
+    "resume",
+    "notifications",
+    "opencode",
+    "mcp",
+    "otel",
+    "classifier",
+    "maintenance",
+];
+
+fn ensure_known_section(section: &str) -> anyhow::Result<()> {
+    if SECTIONS.contains(&section) {
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "Unknown section: {section}. Valid sections: {}",
+            SECTIONS.join(", ")
+        )
+    }
+}
+
 fn format_section(config: &Config, section: &str, json: bool) -> anyhow::Result<String> {
     let section = section.to_lowercase();
     match section.as_str() {
@@ -415,9 +1013,9 @@ fn format_section(config: &Config, section: &str, json: bool) -> anyhow::Result<
             let otel = config.otel.clone().unwrap_or_default();
             format_value(&otel, json)
         }
-        _ => anyhow::bail!(
-            "Unknown section: {section}. Valid sections: daemon, monitoring, resume, notifications, opencode, mcp, otel"
-        ),
+        "classifier" => format_value(&config.classifier, json),
+        "maintenance" => format_value(&config.maintenance, json),
+        _ => ensure_known_section(&section).map(|()| String::new()),
     }
 }
 
@@ -429,181 +1027,298 @@ fn format_value<T: Serialize>(value: &T, json: bool) -> anyhow::Result<String> {
     }
 }
 
-fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, String)>> {
-    let mut overrides = Vec::new();
+/// Environment variables applied on top of the config file.
+#[derive(Debug, Default)]
+struct EnvOverrides {
+    /// `(variable, raw value)` in the order they were applied.
+    applied: Vec<(String, String)>,
+    /// Fields set by each variable.
+    provenance: ConfigProvenance,
+}
+
+impl EnvOverrides {
+    fn record(&mut self, key: &str, fields: &[&str], value: String) {
+        for field in fields {
+            self.provenance
+                .record(*field, ConfigSource::Env(key.to_string()));
+        }
+        self.applied.push((key.to_string(), value));
+    }
+}
+
+fn apply_env_overrides(config: &mut Config) -> anyhow::Result<EnvOverrides> {
+    let mut overrides = EnvOverrides::default();
 
     apply_string_env(
         "PALINGENESIS_LOG_LEVEL",
+        "daemon.log_level",
         &mut config.daemon.log_level,
         &mut overrides,
     );
     apply_bool_env(
         "PALINGENESIS_HTTP_ENABLED",
+        "daemon.http_enabled",
         &mut config.daemon.http_enabled,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_HTTP_PORT",
+        "daemon.http_port",
         &mut config.daemon.http_port,
         &mut overrides,
     )?;
     apply_string_env(
         "PALINGENESIS_HTTP_BIND",
+        "daemon.http_bind",
         &mut config.daemon.http_bind,
         &mut overrides,
     );
     apply_path_env_option(
         "PALINGENESIS_PID_FILE",
+        "daemon.pid_file",
         &mut config.daemon.pid_file,
         &mut overrides,
     );
     apply_path_env_option(
         "PALINGENESIS_SOCKET_PATH",
+        "daemon.socket_path",
         &mut config.daemon.socket_path,
         &mut overrides,
     );
     apply_path_env_option(
         "PALINGENESIS_LOG_FILE",
+        "daemon.log_file",
         &mut config.daemon.log_file,
         &mut overrides,
     );
+    apply_parse_env(
+        "PALINGENESIS_STATE_FLUSH_INTERVAL_MS",
+        "daemon.state_flush_interval_ms",
+        &mut config.daemon.state_flush_interval_ms,
+        &mut overrides,
+    )?;
 
     apply_path_env_value(
         "PALINGENESIS_SESSION_DIR",
+        "monitoring.session_dir",
         &mut config.monitoring.session_dir,
         &mut overrides,
     );
     apply_list_env(
         "PALINGENESIS_ASSISTANTS",
+        "monitoring.assistants",
         &mut config.monitoring.assistants,
         &mut overrides,
     );
     apply_bool_env(
         "PALINGENESIS_AUTO_DETECT",
+        "monitoring.auto_detect",
         &mut config.monitoring.auto_detect,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_DEBOUNCE_MS",
+        "monitoring.debounce_ms",
         &mut config.monitoring.debounce_ms,
         &mut overrides,
     )?;
     apply_option_parse_env(
         "PALINGENESIS_POLL_INTERVAL_SECS",
+        "monitoring.poll_interval_secs",
         &mut config.monitoring.poll_interval_secs,
         &mut overrides,
     )?;
+    apply_bool_env(
+        "PALINGENESIS_FOLLOW_SYMLINKS",
+        "monitoring.follow_symlinks",
+        &mut config.monitoring.follow_symlinks,
+        &mut overrides,
+    )?;
 
     apply_bool_env(
         "PALINGENESIS_OPENCODE_ENABLED",
+        "opencode.enabled",
         &mut config.opencode.enabled,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_OPENCODE_SERVE_PORT",
+        "opencode.serve_port",
         &mut config.opencode.serve_port,
         &mut overrides,
     )?;
+    apply_bool_env(
+        "PALINGENESIS_OPENCODE_AUTO_DISCOVER",
+        "opencode.auto_discover",
+        &mut config.opencode.auto_discover,
+        &mut overrides,
+    )?;
     apply_string_env(
         "PALINGENESIS_OPENCODE_SERVE_HOSTNAME",
+        "opencode.serve_hostname",
         &mut config.opencode.serve_hostname,
         &mut overrides,
     );
     apply_bool_env(
         "PALINGENESIS_OPENCODE_AUTO_RESTART",
+        "opencode.auto_restart",
         &mut config.opencode.auto_restart,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_OPENCODE_RESTART_DELAY_MS",
+        "opencode.restart_delay_ms",
         &mut config.opencode.restart_delay_ms,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_OPENCODE_HEALTH_CHECK_INTERVAL",
+        "opencode.health_check_interval",
         &mut config.opencode.health_check_interval,
         &mut overrides,
     )?;
 
     apply_bool_env(
         "PALINGENESIS_RESUME_ENABLED",
+        "resume.enabled",
         &mut config.resume.enabled,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_RESUME_BASE_DELAY_SECS",
+        "resume.base_delay_secs",
         &mut config.resume.base_delay_secs,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_RESUME_MAX_DELAY_SECS",
+        "resume.max_delay_secs",
         &mut config.resume.max_delay_secs,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_RESUME_MAX_RETRIES",
+        "resume.max_retries",
         &mut config.resume.max_retries,
         &mut overrides,
     )?;
     apply_bool_env(
         "PALINGENESIS_RESUME_JITTER",
+        "resume.jitter",
         &mut config.resume.jitter,
         &mut overrides,
     )?;
     apply_parse_env(
         "PALINGENESIS_RESUME_BACKUP_COUNT",
+        "resume.backup_count",
         &mut config.resume.backup_count,
         &mut overrides,
     )?;
+    apply_list_env(
+        "PALINGENESIS_RESUME_EXCLUDE_SESSIONS",
+        "resume.exclude_sessions",
+        &mut config.resume.exclude_sessions,
+        &mut overrides,
+    );
+    apply_bool_env(
+        "PALINGENESIS_RESUME_RESTORE_DELETED",
+        "resume.restore_deleted_from_backup",
+        &mut config.resume.restore_deleted_from_backup,
+        &mut overrides,
+    )?;
+    apply_bool_env(
+        "PALINGENESIS_RESUME_ENFORCE_MODEL",
+        "resume.enforce_model",
+        &mut config.resume.enforce_model,
+        &mut overrides,
+    )?;
+    apply_bool_env(
+        "PALINGENESIS_RESUME_CAPTURE_GIT_CONTEXT",
+        "resume.capture_git_context",
+        &mut config.resume.capture_git_context,
+        &mut overrides,
+    )?;
+    apply_parse_env(
+        "PALINGENESIS_RESUME_MAX_NEXT_STEP_BYTES",
+        "resume.max_next_step_bytes",
+        &mut config.resume.max_next_step_bytes,
+        &mut overrides,
+    )?;
 
     apply_bool_env(
         "PALINGENESIS_NOTIFICATIONS_ENABLED",
+        "notifications.enabled",
         &mut config.notifications.enabled,
         &mut overrides,
     )?;
 
     if let Ok(url) = env::var("PALINGENESIS_WEBHOOK_URL") {
-        config.notifications.webhook = Some(WebhookConfig {
-            url: url.clone(),
-            headers: None,
-        });
+        config.notifications.webhook = vec![WebhookConfig::new(url.clone())];
         config.notifications.enabled = true;
-        overrides.push(("PALINGENESIS_WEBHOOK_URL".to_string(), url));
+        overrides.record(
+            "PALINGENESIS_WEBHOOK_URL",
+            &["notifications.webhook", "notifications.enabled"],
+            url,
+        );
     }
 
     if let Ok(topic) = env::var("PALINGENESIS_NTFY_TOPIC") {
-        let mut ntfy = NtfyConfig {
-            topic: topic.clone(),
-            server: None,
-            priority: None,
-        };
+        overrides.record(
+            "PALINGENESIS_NTFY_TOPIC",
+            &["notifications.ntfy", "notifications.enabled"],
+            topic.clone(),
+        );
+        let mut ntfy = NtfyConfig::new(topic);
         if let Ok(server) = env::var("PALINGENESIS_NTFY_SERVER") {
             ntfy.server = Some(server.clone());
-            overrides.push(("PALINGENESIS_NTFY_SERVER".to_string(), server));
+            overrides.record(
+                "PALINGENESIS_NTFY_SERVER",
+                &["notifications.ntfy.server"],
+                server,
+            );
         }
         if let Ok(priority) = env::var("PALINGENESIS_NTFY_PRIORITY") {
             ntfy.priority = Some(priority.clone());
-            overrides.push(("PALINGENESIS_NTFY_PRIORITY".to_string(), priority));
+            overrides.record(
+                "PALINGENESIS_NTFY_PRIORITY",
+                &["notifications.ntfy.priority"],
+                priority,
+            );
         }
-        config.notifications.ntfy = Some(ntfy);
+        config.notifications.ntfy = vec![ntfy];
         config.notifications.enabled = true;
-        overrides.push(("PALINGENESIS_NTFY_TOPIC".to_string(), topic));
     }
 
     if let Ok(url) = env::var("PALINGENESIS_DISCORD_WEBHOOK_URL") {
         config.notifications.discord = Some(DiscordConfig {
             webhook_url: url.clone(),
+            locale: None,
+            path_display: None,
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
         });
         config.notifications.enabled = true;
-        overrides.push(("PALINGENESIS_DISCORD_WEBHOOK_URL".to_string(), url));
+        overrides.record(
+            "PALINGENESIS_DISCORD_WEBHOOK_URL",
+            &["notifications.discord", "notifications.enabled"],
+            url,
+        );
     }
 
     if let Ok(url) = env::var("PALINGENESIS_SLACK_WEBHOOK_URL") {
         config.notifications.slack = Some(SlackConfig {
             webhook_url: url.clone(),
+            locale: None,
+            path_display: None,
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
         });
         config.notifications.enabled = true;
-        overrides.push(("PALINGENESIS_SLACK_WEBHOOK_URL".to_string(), url));
+        overrides.record(
+            "PALINGENESIS_SLACK_WEBHOOK_URL",
+            &["notifications.slack", "notifications.enabled"],
+            url,
+        );
     }
 
     let mut otel_config = config.otel.clone();
@@ -617,7 +1332,7 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.enabled = parsed;
         }
-        overrides.push(("PALINGENESIS_OTEL_ENABLED".to_string(), value));
+        overrides.record("PALINGENESIS_OTEL_ENABLED", &["otel.enabled"], value);
         otel_override = true;
     }
 
@@ -626,7 +1341,7 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.endpoint = endpoint.clone();
         }
-        overrides.push(("PALINGENESIS_OTEL_ENDPOINT".to_string(), endpoint));
+        overrides.record("PALINGENESIS_OTEL_ENDPOINT", &["otel.endpoint"], endpoint);
         otel_override = true;
     }
 
@@ -635,7 +1350,11 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.service_name = name.clone();
         }
-        overrides.push(("PALINGENESIS_OTEL_SERVICE_NAME".to_string(), name));
+        overrides.record(
+            "PALINGENESIS_OTEL_SERVICE_NAME",
+            &["otel.service_name"],
+            name,
+        );
         otel_override = true;
     }
 
@@ -647,7 +1366,7 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.traces = parsed;
         }
-        overrides.push(("PALINGENESIS_OTEL_TRACES".to_string(), value));
+        overrides.record("PALINGENESIS_OTEL_TRACES", &["otel.traces"], value);
         otel_override = true;
     }
 
@@ -659,7 +1378,7 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.metrics = parsed;
         }
-        overrides.push(("PALINGENESIS_OTEL_METRICS".to_string(), value));
+        overrides.record("PALINGENESIS_OTEL_METRICS", &["otel.metrics"], value);
         otel_override = true;
     }
 
@@ -671,7 +1390,11 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.metrics_enabled = parsed;
         }
-        overrides.push(("PALINGENESIS_OTEL_METRICS_ENABLED".to_string(), value));
+        overrides.record(
+            "PALINGENESIS_OTEL_METRICS_ENABLED",
+            &["otel.metrics_enabled"],
+            value,
+        );
         otel_override = true;
     }
 
@@ -680,7 +1403,7 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.protocol = protocol.clone();
         }
-        overrides.push(("PALINGENESIS_OTEL_PROTOCOL".to_string(), protocol));
+        overrides.record("PALINGENESIS_OTEL_PROTOCOL", &["otel.protocol"], protocol);
         otel_override = true;
     }
 
@@ -692,7 +1415,11 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
         if let Some(ref mut otel) = otel_config {
             otel.sampling_ratio = parsed;
         }
-        overrides.push(("PALINGENESIS_OTEL_SAMPLING_RATIO".to_string(), value));
+        overrides.record(
+            "PALINGENESIS_OTEL_SAMPLING_RATIO",
+            &["otel.sampling_ratio"],
+            value,
+        );
         otel_override = true;
     }
 
@@ -703,17 +1430,18 @@ fn apply_env_overrides(config: &mut Config) -> anyhow::Result<Vec<(String, Strin
     Ok(overrides)
 }
 
-fn apply_string_env(key: &str, target: &mut String, overrides: &mut Vec<(String, String)>) {
+fn apply_string_env(key: &str, field: &str, target: &mut String, overrides: &mut EnvOverrides) {
     if let Ok(value) = env::var(key) {
         *target = value.clone();
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
 }
 
 fn apply_parse_env<T>(
     key: &str,
+    field: &str,
     target: &mut T,
-    overrides: &mut Vec<(String, String)>,
+    overrides: &mut EnvOverrides,
 ) -> anyhow::Result<()>
 where
     T: std::str::FromStr,
@@ -723,15 +1451,16 @@ where
         *target = value
             .parse()
             .map_err(|err| anyhow::anyhow!("{key} is invalid: {err}"))?;
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
     Ok(())
 }
 
 fn apply_option_parse_env<T>(
     key: &str,
+    field: &str,
     target: &mut Option<T>,
-    overrides: &mut Vec<(String, String)>,
+    overrides: &mut EnvOverrides,
 ) -> anyhow::Result<()>
 where
     T: std::str::FromStr,
@@ -743,37 +1472,39 @@ where
                 .parse()
                 .map_err(|err| anyhow::anyhow!("{key} is invalid: {err}"))?,
         );
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
     Ok(())
 }
 
 fn apply_bool_env(
     key: &str,
+    field: &str,
     target: &mut bool,
-    overrides: &mut Vec<(String, String)>,
+    overrides: &mut EnvOverrides,
 ) -> anyhow::Result<()> {
     if let Ok(value) = env::var(key) {
         *target = value
             .parse()
             .with_context(|| format!("{key} must be true/false"))?;
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
     Ok(())
 }
 
 fn apply_path_env_option(
     key: &str,
+    field: &str,
     target: &mut Option<PathBuf>,
-    overrides: &mut Vec<(String, String)>,
+    overrides: &mut EnvOverrides,
 ) {
     if let Ok(value) = env::var(key) {
         *target = Some(PathBuf::from(&value));
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
 }
 
-fn apply_list_env(key: &str, target: &mut Vec<String>, overrides: &mut Vec<(String, String)>) {
+fn apply_list_env(key: &str, field: &str, target: &mut Vec<String>, overrides: &mut EnvOverrides) {
     if let Ok(value) = env::var(key) {
         let list = value
             .split(',')
@@ -782,13 +1513,18 @@ fn apply_list_env(key: &str, target: &mut Vec<String>, overrides: &mut Vec<(Stri
             .map(String::from)
             .collect::<Vec<_>>();
         *target = list;
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
 }
 
-fn apply_path_env_value(key: &str, target: &mut PathBuf, overrides: &mut Vec<(String, String)>) {
+fn apply_path_env_value(
+    key: &str,
+    field: &str,
+    target: &mut PathBuf,
+    overrides: &mut EnvOverrides,
+) {
     if let Ok(value) = env::var(key) {
         *target = PathBuf::from(&value);
-        overrides.push((key.to_string(), value));
+        overrides.record(key, &[field], value);
     }
 }
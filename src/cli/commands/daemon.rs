@@ -1,10 +1,19 @@
 use tracing::warn;
 
-use crate::daemon::Daemon;
+use crate::config::schema::DaemonMode;
+use crate::config::{PathFlags, Paths};
+use crate::daemon::last_exit::{self, ExitCause, LastExit, LastExitFile};
+use crate::daemon::state::load_config_from_disk;
+use crate::daemon::{Daemon, ExitReport};
+use crate::telemetry::LogBuffer;
 use crate::telemetry::otel::load_otel_config;
 use crate::telemetry::tracing::{TracingConfig, init_tracing};
 
-pub async fn handle_start(foreground: bool) -> anyhow::Result<()> {
+pub async fn handle_start(
+    foreground: bool,
+    observe: bool,
+    force_downgrade: bool,
+) -> anyhow::Result<()> {
     let otel_config = load_otel_config();
     if !foreground {
         let config = TracingConfig {
@@ -16,19 +25,45 @@ pub async fn handle_start(foreground: bool) -> anyhow::Result<()> {
         warn!("Daemonization not yet implemented (daemonize crate required)");
         return Ok(());
     }
+    last_exit::install_panic_hook(LastExitFile::new());
 
     let config = TracingConfig {
         log_to_file: false,
         log_to_stderr: true,
+        log_buffer: Some(install_log_buffer()),
         ..TracingConfig::default()
     };
     let _guard = init_tracing(&config, otel_config.as_ref())?;
 
-    let mut daemon = Daemon::new();
+    let mut daemon = Daemon::new().with_force_downgrade(force_downgrade);
+    if observe {
+        daemon = daemon.with_mode(DaemonMode::Observe);
+    }
     daemon.run().await?;
     Ok(())
 }
 
+/// Buffer served to `palingenesis logs` over IPC, sized by
+/// `daemon.log_buffer_entries` and `daemon.log_buffer_bytes`.
+fn install_log_buffer() -> LogBuffer {
+    let daemon = load_config_from_disk()
+        .map(|config| config.daemon)
+        .unwrap_or_default();
+    let buffer = LogBuffer::new(daemon.log_buffer_entries, daemon.log_buffer_bytes);
+    LogBuffer::set_global(buffer.clone());
+    buffer
+}
+
+/// Exit with the code for `result`, ending stderr with the JSON exit report.
+pub fn exit_with_report(result: anyhow::Result<()>) -> ! {
+    let report = ExitReport::from_result(&result);
+    if let Err(err) = &result {
+        eprintln!("{err:#}");
+    }
+    eprintln!("{}", report.to_json_line());
+    std::process::exit(report.code())
+}
+
 pub async fn handle_stop() -> anyhow::Result<()> {
     use crate::daemon::pid::PidFile;
     use nix::sys::signal::{Signal, kill};
@@ -65,50 +100,131 @@ pub async fn handle_stop() -> anyhow::Result<()> {
     }
 
     kill(nix_pid, Signal::SIGKILL)?;
+    let exit = LastExit {
+        pid,
+        ..LastExit::new(
+            ExitCause::ForcedStop,
+            "SIGKILL after ignoring SIGTERM for 5 seconds",
+        )
+    };
+    if let Err(err) = LastExitFile::new().write(&exit) {
+        warn!(error = %err, "Failed to write the last exit record");
+    }
     println!("Daemon stopped");
     Ok(())
 }
 
-pub async fn handle_restart() -> anyhow::Result<()> {
-    println!("daemon restart not implemented (Story TBD)");
-    Ok(())
-}
+/// How long to wait for the old daemon to exit and the new one to answer.
+const RESTART_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-pub async fn handle_reload() -> anyhow::Result<()> {
+/// Restart with a runtime-state handoff.
+///
+/// The running daemon writes its in-memory state to the handoff file and
+/// exits; a new daemon is then started, restores the snapshot, and must
+/// answer STATUS before this returns. The PID file and IPC socket are
+/// exclusive, so the old instance has to release them first; if it refuses
+/// the handoff it keeps running and the restart fails.
+pub async fn handle_restart() -> anyhow::Result<()> {
     use crate::daemon::pid::PidFile;
-
-    let pid_file = PidFile::new();
-
-    let pid = match pid_file.read() {
-        Ok(pid) => pid,
-        Err(_) => {
-            println!("Daemon not running");
-            return Ok(());
+    use crate::ipc::client::IpcClient;
+    use std::process::{Command, Stdio};
+    use tokio::time::{Instant, sleep};
+
+    let old_pid = PidFile::new()
+        .read()
+        .ok()
+        .filter(|pid| PidFile::is_process_running(*pid).unwrap_or(false));
+
+    let mut observe = false;
+    if let Some(pid) = old_pid {
+        // A runtime SET_MODE isn't in the config, so carry it over explicitly.
+        observe = IpcClient::status()
+            .await
+            .is_ok_and(|status| status.mode.is_observe());
+        println!("Handing off daemon state (PID: {pid})...");
+        IpcClient::handoff()
+            .await
+            .map_err(|err| anyhow::anyhow!("Daemon refused handoff, still running: {err}"))?;
+
+        let deadline = Instant::now() + RESTART_TIMEOUT;
+        while PidFile::is_process_running(pid)? {
+            if Instant::now() >= deadline {
+                anyhow::bail!("Daemon (PID: {pid}) did not exit after handoff");
+            }
+            sleep(std::time::Duration::from_millis(100)).await;
         }
-    };
-
-    if !PidFile::is_process_running(pid)? {
-        println!("Daemon not running");
-        return Ok(());
     }
 
+    let mut command = Command::new(std::env::current_exe()?);
+    command.args(["daemon", "start", "--foreground"]);
+    if observe {
+        command.arg("--observe");
+    }
+    if let Some(instance) = Paths::instance() {
+        command.args(["--instance", &instance]);
+    }
+    let flags = PathFlags::current();
+    if let Some(dir) = &flags.state_dir {
+        command.arg("--state-dir").arg(dir);
+    }
+    if let Some(dir) = &flags.runtime_dir {
+        command.arg("--runtime-dir").arg(dir);
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
     #[cfg(unix)]
     {
-        use nix::sys::signal::{Signal, kill};
-        use nix::unistd::Pid;
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command.spawn()?;
 
-        kill(Pid::from_raw(pid as i32), Signal::SIGHUP)?;
-        println!("Sent reload signal to daemon (PID: {pid})");
-        Ok(())
+    let deadline = Instant::now() + RESTART_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!("New daemon exited during startup ({status})");
+        }
+        if IpcClient::status().await.is_ok() {
+            println!("Daemon restarted (PID: {})", child.id());
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            anyhow::bail!(
+                "New daemon (PID: {}) did not become ready within {}s",
+                child.id(),
+                RESTART_TIMEOUT.as_secs()
+            );
+        }
+        sleep(std::time::Duration::from_millis(200)).await;
     }
+}
 
-    #[cfg(not(unix))]
-    {
-        let _ = pid;
-        anyhow::bail!("Reload is not supported on this platform");
+/// Reload the daemon's config over IPC and print what changed.
+pub async fn handle_reload() -> anyhow::Result<()> {
+    use crate::ipc::client::{CommandAck, IpcClient, IpcClientError};
+
+    match IpcClient::reload().await {
+        Ok(CommandAck::Applied(changes)) => {
+            println!("Configuration reloaded");
+            for change in changes {
+                println!("  {change}");
+            }
+            Ok(())
+        }
+        Ok(CommandAck::Deferred(message)) => {
+            println!("Reload accepted; {message}");
+            Ok(())
+        }
+        Err(IpcClientError::NotRunning) => {
+            println!("Daemon not running");
+            Ok(())
+        }
+        Err(err) => Err(anyhow::anyhow!("Reload failed: {err}")),
     }
 }
 
 pub async fn handle_status(json: bool) -> anyhow::Result<()> {
-    super::status::handle_status(json).await
+    super::status::handle_status(json, false, crate::cli::StatusFormat::Text, false, false).await
 }
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::commands::config::load_effective_config;
+use crate::resume::{ExclusionMatch, SessionExclusions};
+
+pub async fn handle_test(path: PathBuf) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let exclusions = SessionExclusions::from_config(&config.resume);
+    println!("{}", format_report(&path, &exclusions));
+    Ok(())
+}
+
+fn format_report(path: &Path, exclusions: &SessionExclusions) -> String {
+    if exclusions.is_empty() {
+        return format!(
+            "{}: not excluded (resume.exclude_sessions is empty)",
+            path.display()
+        );
+    }
+
+    let result = exclusions.evaluate(path);
+    let mut report = format!("{}: {}", path.display(), result.describe());
+    if let ExclusionMatch::Excluded { .. } = result {
+        report.push_str("\nStops are still detected and reported, but never auto-resumed.");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_names_matching_pattern() {
+        let exclusions = SessionExclusions::new(&["**/experiments/**"]);
+        let report = format_report(Path::new("/w/experiments/s.md"), &exclusions);
+        assert!(report.starts_with("/w/experiments/s.md: excluded by `**/experiments/**`"));
+        assert!(report.contains("never auto-resumed"));
+    }
+
+    #[test]
+    fn report_explains_empty_list() {
+        let report = format_report(Path::new("/w/s.md"), &SessionExclusions::default());
+        assert_eq!(
+            report,
+            "/w/s.md: not excluded (resume.exclude_sessions is empty)"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::cli::commands::config::load_effective_config;
+use crate::config::Paths;
+use crate::resume::{FailureBundle, FailureRecord, FailureStore};
+use crate::util::path_display::{self, PathDisplay};
+
+pub async fn handle_list(json: bool) -> anyhow::Result<()> {
+    let mut records = store()?.list()?;
+    let paths = path_display::configured();
+    if json {
+        let paths = paths.for_json();
+        for record in &mut records {
+            paths.apply(&mut record.session_path);
+        }
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else {
+        println!("{}", format_records(&records, &paths));
+    }
+    Ok(())
+}
+
+pub async fn handle_show(id: String) -> anyhow::Result<()> {
+    let bundle = store()?.show(&id)?;
+    println!("{}", format_bundle(&bundle, &path_display::configured()));
+    Ok(())
+}
+
+fn store() -> anyhow::Result<FailureStore> {
+    let config = load_effective_config()?;
+    Ok(FailureStore::new(&Paths::state_dir())
+        .with_max_bundles(config.resume.failure_bundle_count as usize))
+}
+
+fn format_records(records: &[FailureRecord], paths: &PathDisplay) -> String {
+    if records.is_empty() {
+        return "No failure postmortems recorded".to_string();
+    }
+    let mut output = String::new();
+    for record in records {
+        output.push_str(&format!(
+            "{} [{}]\n  session: {}\n  command: {}\n",
+            record.id,
+            record.describe_exit(),
+            paths.path(&record.session_path),
+            command_summary(&record.argv),
+        ));
+    }
+    output.trim_end().to_string()
+}
+
+/// Program and subcommand; prompts make full argv too long for a listing.
+fn command_summary(argv: &[String]) -> String {
+    argv.iter().take(2).cloned().collect::<Vec<_>>().join(" ")
+}
+
+fn format_bundle(bundle: &FailureBundle, paths: &PathDisplay) -> String {
+    let record = &bundle.record;
+    let mut output = format!(
+        "Failure {}\n  path: {}\n  captured: {}\n  session: {}\n  status: {}\n",
+        record.id,
+        bundle.path.display(),
+        record.captured_at.to_rfc3339(),
+        paths.path(&record.session_path),
+        record.describe_exit(),
+    );
+    if let Some(reason) = &record.stop_reason {
+        output.push_str(&format!("  stop reason: {reason}\n"));
+    }
+    output.push_str("\nCommand:\n");
+    for arg in &record.argv {
+        output.push_str(&format!("  {arg:?}\n"));
+    }
+    if !record.evidence.is_empty() {
+        output.push_str("\nEvidence:\n");
+        for line in &record.evidence {
+            output.push_str(&format!("  - {line}\n"));
+        }
+    }
+    push_section(
+        &mut output,
+        "Stdout",
+        &bundle.stdout,
+        record.stdout_truncated,
+    );
+    push_section(
+        &mut output,
+        "Stderr",
+        &bundle.stderr,
+        record.stderr_truncated,
+    );
+    if let Some(prompt) = &bundle.prompt {
+        push_section(&mut output, "Prompt", prompt, false);
+    }
+    push_section(&mut output, "Environment", &bundle.env, false);
+    output.trim_end().to_string()
+}
+
+fn push_section(output: &mut String, title: &str, body: &str, truncated: bool) {
+    let note = if truncated {
+        " (truncated, tail kept)"
+    } else {
+        ""
+    };
+    output.push_str(&format!("\n{title}{note}:\n"));
+    if body.trim().is_empty() {
+        output.push_str("  (empty)\n");
+    } else {
+        output.push_str(body.trim_end());
+        output.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use chrono::Utc;
+
+    #[test]
+    fn list_shows_exit_status_without_prompt() {
+        let record = FailureRecord {
+            id: "20260101T000000.000Z-session".to_string(),
+            captured_at: Utc::now(),
+            session_path: PathBuf::from("/work/session.md"),
+            argv: vec![
+                "opencode".to_string(),
+                "new".to_string(),
+                "--prompt".to_string(),
+                "long prompt".to_string(),
+            ],
+            exit_code: Some(7),
+            signal: None,
+            stop_reason: None,
+            evidence: Vec::new(),
+            stopped: None,
+            stdout_truncated: false,
+            stderr_truncated: false,
+        };
+        let output = format_records(&[record], &PathDisplay::default());
+        assert!(output.contains("20260101T000000.000Z-session [exit code 7]"));
+        assert!(output.contains("command: opencode new"));
+        assert!(!output.contains("long prompt"));
+        assert_eq!(
+            format_records(&[], &PathDisplay::default()),
+            "No failure postmortems recorded"
+        );
+    }
+}
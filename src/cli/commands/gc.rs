@@ -0,0 +1,131 @@
+use std::io::ErrorKind;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::ResolvedPaths;
+use crate::daemon::gc::{ArtifactGc, GcReport};
+use crate::state::{StateError, StateFile, StateStore};
+use crate::util::size::format_size;
+
+/// Delete session artifacts past their `[maintenance]` retention, or with
+/// `dry_run` only print what would be deleted.
+pub async fn handle_gc(dry_run: bool, json: bool) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let gc = ArtifactGc::new(&config, ResolvedPaths::current().state_dir());
+    let state = current_state(&StateStore::new())?;
+    let report = if dry_run {
+        gc.plan(&state).await
+    } else {
+        gc.apply(&state).await
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_report(&report));
+    }
+    Ok(())
+}
+
+/// Let the scheduled GC delete under the current retention settings.
+pub async fn handle_approve() -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let paths = ResolvedPaths::current();
+    let state_dir = paths.state_dir();
+    let gc = ArtifactGc::new(&config, state_dir);
+    let approval = gc.approval();
+    approval.save(state_dir)?;
+    println!("Approved artifact GC settings {}", approval.fingerprint);
+    if let Some(plan) = GcReport::load_plan(state_dir)
+        .filter(|plan| plan.fingerprint == approval.fingerprint && !plan.is_empty())
+    {
+        println!(
+            "The next scheduled run deletes up to {} artifacts ({})",
+            plan.artifacts.len(),
+            format_size(plan.bytes())
+        );
+    }
+    if !config.maintenance.enabled {
+        println!("Note: [maintenance] is disabled; run `palingenesis gc` to delete now");
+    }
+    Ok(())
+}
+
+fn current_state(store: &StateStore) -> anyhow::Result<StateFile> {
+    match store.inspect() {
+        Ok((state, _)) => Ok(state),
+        Err(StateError::Io(err)) if err.kind() == ErrorKind::NotFound => Ok(StateFile::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn format_report(report: &GcReport) -> String {
+    let mut output = format!("{:<20} {:>6} {:>10}\n", "ARTIFACT", "COUNT", "SIZE");
+    for (kind, count, bytes) in report.totals() {
+        output.push_str(&format!(
+            "{:<20} {:>6} {:>10}\n",
+            kind.label(),
+            count,
+            format_size(bytes)
+        ));
+    }
+    let verb = if report.dry_run {
+        "Would delete"
+    } else {
+        "Deleted"
+    };
+    output.push_str(&format!(
+        "{verb} {} artifacts ({})\n",
+        report.artifacts.len(),
+        format_size(report.bytes())
+    ));
+    if report.held_back > 0 {
+        output.push_str(&format!(
+            "Kept {} past retention (active session or inside the safety window)\n",
+            report.held_back
+        ));
+    }
+    for failure in &report.failed {
+        output.push_str(&format!("Failed: {failure}\n"));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::daemon::gc::{Artifact, ArtifactKind};
+
+    #[test]
+    fn report_table_lists_every_kind() {
+        let mut report = GcReport {
+            ran_at: chrono::Utc::now(),
+            dry_run: true,
+            fingerprint: "0011223344556677".to_string(),
+            artifacts: vec![Artifact {
+                kind: ArtifactKind::Incident,
+                path: PathBuf::from("/state/incidents/a.json"),
+                companions: Vec::new(),
+                session_path: None,
+                modified: chrono::Utc::now(),
+                bytes: 2048,
+            }],
+            held_back: 2,
+            failed: Vec::new(),
+        };
+        let output = format_report(&report);
+        for kind in ArtifactKind::ALL {
+            assert!(output.contains(kind.label()), "{output}");
+        }
+        assert!(output.contains("Would delete 1 artifacts (2.0 KB)"));
+        assert!(output.contains("Kept 2 past retention"));
+
+        report.dry_run = false;
+        report
+            .failed
+            .push("/state/x: permission denied".to_string());
+        let output = format_report(&report);
+        assert!(output.contains("Deleted 1 artifacts"));
+        assert!(output.contains("Failed: /state/x: permission denied"));
+    }
+}
@@ -0,0 +1,131 @@
+use std::io::{self, IsTerminal};
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::Paths;
+use crate::monitor::incident::{Incident, IncidentRecord, IncidentStore};
+use crate::util::path_display::{self, PathDisplay};
+
+pub async fn handle_list(json: bool) -> anyhow::Result<()> {
+    let mut records = store()?.list()?;
+    let paths = path_display::configured();
+    if json {
+        let paths = paths.for_json();
+        for record in &mut records {
+            paths.apply(&mut record.session_path);
+        }
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else {
+        println!("{}", format_records(&records, &paths));
+    }
+    Ok(())
+}
+
+pub async fn handle_show(id: String) -> anyhow::Result<()> {
+    let incident = store()?.show(&id)?;
+    println!(
+        "{}",
+        format_incident(
+            &incident,
+            io::stdout().is_terminal(),
+            &path_display::configured()
+        )
+    );
+    Ok(())
+}
+
+fn store() -> anyhow::Result<IncidentStore> {
+    let config = load_effective_config()?;
+    Ok(IncidentStore::new(&Paths::state_dir())
+        .with_max_incidents(config.resume.incident_count as usize))
+}
+
+fn format_records(records: &[IncidentRecord], paths: &PathDisplay) -> String {
+    if records.is_empty() {
+        return "No incidents recorded".to_string();
+    }
+    let mut output = String::new();
+    for record in records {
+        output.push_str(&format!(
+            "{} [{} @ {:.0}%]\n  session: {}\n",
+            record.id,
+            record.stop_reason,
+            record.confidence * 100.0,
+            paths.path(&record.session_path),
+        ));
+    }
+    output.trim_end().to_string()
+}
+
+/// Incident header, evidence and the tail; evidence lines are marked with `>`
+/// (and highlighted when `color` is set).
+fn format_incident(incident: &Incident, color: bool, paths: &PathDisplay) -> String {
+    let record = &incident.record;
+    let mut output = format!(
+        "Incident {}\n  path: {}\n  captured: {}\n  session: {}\n  stop reason: {}\n  confidence: {:.0}%\n",
+        record.id,
+        incident.path.display(),
+        record.captured_at.to_rfc3339(),
+        paths.path(&record.session_path),
+        record.stop_reason,
+        record.confidence * 100.0,
+    );
+    if let Some(code) = record.exit_code {
+        output.push_str(&format!("  exit code: {code}\n"));
+    }
+    if !record.evidence.is_empty() {
+        output.push_str("\nEvidence:\n");
+        for line in &record.evidence {
+            output.push_str(&format!("  - {line}\n"));
+        }
+    }
+    let note = if record.tail_truncated {
+        " (truncated, end kept)"
+    } else {
+        ""
+    };
+    output.push_str(&format!("\nTranscript tail{note}:\n"));
+    for line in incident.tail.lines() {
+        if !incident.is_evidence_line(line) {
+            output.push_str(&format!("  {line}\n"));
+        } else if color {
+            output.push_str(&format!("\x1b[1;33m> {line}\x1b[0m\n"));
+        } else {
+            output.push_str(&format!("> {line}\n"));
+        }
+    }
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use chrono::Utc;
+
+    #[test]
+    fn show_marks_evidence_lines() {
+        let incident = Incident {
+            path: PathBuf::from("/state/incidents/20260101T000000.000Z-session.tail.txt"),
+            record: IncidentRecord {
+                id: "20260101T000000.000Z-session".to_string(),
+                captured_at: Utc::now(),
+                session_path: PathBuf::from("/work/session.md"),
+                stop_reason: "RateLimit".to_string(),
+                confidence: 0.9,
+                evidence: vec!["matched pattern: 429".to_string()],
+                exit_code: Some(1),
+                tail_truncated: false,
+            },
+            tail: "working on step 3\nError: 429 Too Many Requests".to_string(),
+        };
+        let output = format_incident(&incident, false, &PathDisplay::default());
+        assert!(output.contains("  working on step 3\n"));
+        assert!(output.ends_with("> Error: 429 Too Many Requests"));
+        assert!(output.contains("exit code: 1"));
+        assert_eq!(
+            format_records(&[], &PathDisplay::default()),
+            "No incidents recorded"
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! `install systemd` / `uninstall systemd`: generate a service unit for the
+//! daemon from the current binary and effective config.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, bail};
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::Paths;
+use crate::config::schema::Config;
+use crate::resume::backup::BackupConfig;
+
+/// Exit code of `daemon start --foreground` for config errors; restarting
+/// would only fail again.
+const CONFIG_ERROR_EXIT_CODE: i32 = 2;
+
+/// Where the unit is installed and which systemd manager runs it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemdScope {
+    /// `systemctl --user`, unit under `~/.config/systemd/user`.
+    User,
+    /// System manager, unit under `/etc/systemd/system`.
+    System,
+}
+
+impl SystemdScope {
+    pub fn from_flags(system: bool) -> Self {
+        if system { Self::System } else { Self::User }
+    }
+
+    fn unit_dir(self) -> anyhow::Result<PathBuf> {
+        match self {
+            Self::User => dirs::config_dir()
+                .map(|dir| dir.join("systemd").join("user"))
+                .context("Cannot locate the user config directory for systemd units"),
+            Self::System => Ok(PathBuf::from("/etc/systemd/system")),
+        }
+    }
+
+    fn wanted_by(self) -> &'static str {
+        match self {
+            Self::User => "default.target",
+            Self::System => "multi-user.target",
+        }
+    }
+}
+
+/// Everything the unit text depends on, resolved from the environment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitSpec {
+    pub scope: SystemdScope,
+    pub binary: PathBuf,
+    pub instance: Option<String>,
+    /// Set when the config file was overridden with `PALINGENESIS_CONFIG`.
+    pub config_file: Option<PathBuf>,
+    /// Set when the state dir was overridden with `PALINGENESIS_STATE`.
+    pub state_override: Option<PathBuf>,
+    /// Account for `User=` in system units.
+    pub user: Option<String>,
+    /// Directories the daemon writes to; everything else under home is read-only.
+    pub writable_paths: Vec<PathBuf>,
+}
+
+impl UnitSpec {
+    /// Spec for this binary, the selected instance and `config`.
+    pub fn resolve(scope: SystemdScope, config: &Config) -> anyhow::Result<Self> {
+        let binary = env::current_exe()
+            .and_then(|path| path.canonicalize())
+            .context("Cannot determine the path of the palingenesis binary")?;
+        let user = match scope {
+            SystemdScope::User => None,
+            SystemdScope::System => Some(
+                env::var("SUDO_USER")
+                    .or_else(|_| env::var("USER"))
+                    .context("Cannot determine the user to run the system unit as")?,
+            ),
+        };
+
+        let mut writable_paths = vec![
+            Paths::state_dir(),
+            Paths::runtime_dir(),
+            config.monitoring.session_dir.clone(),
+        ];
+        if let Some(dir) =
+            BackupConfig::from_resume_config(&config.resume, &config.monitoring).backup_dir
+        {
+            if !writable_paths.contains(&dir) {
+                writable_paths.push(dir);
+            }
+        }
+
+        Ok(Self {
+            scope,
+            binary,
+            instance: Paths::instance(),
+            config_file: env::var_os("PALINGENESIS_CONFIG").map(PathBuf::from),
+            state_override: env::var_os("PALINGENESIS_STATE").map(PathBuf::from),
+            user,
+            writable_paths,
+        })
+    }
+
+    pub fn unit_name(&self) -> String {
+        unit_name(self.instance.as_deref())
+    }
+
+    pub fn unit_path(&self) -> anyhow::Result<PathBuf> {
+        Ok(self.scope.unit_dir()?.join(self.unit_name()))
+    }
+
+    fn command(&self, args: &str) -> String {
+        match self.instance.as_deref() {
+            Some(instance) => format!(
+                "{} --instance {instance} {args}",
+                quote(&self.binary.to_string_lossy())
+            ),
+            None => format!("{} {args}", quote(&self.binary.to_string_lossy())),
+        }
+    }
+}
+
+/// Unit file text for `spec`.
+pub fn render_unit(spec: &UnitSpec) -> String {
+    let description = match spec.instance.as_deref() {
+        Some(instance) => format!("palingenesis agent resurrection daemon ({instance})"),
+        None => "palingenesis agent resurrection daemon".to_string(),
+    };
+
+    let mut unit = vec![
+        "[Unit]".to_string(),
+        format!("Description={description}"),
+        "Documentation=https://github.com/Jack-R-Hong/palingenesis".to_string(),
+        "After=network-online.target".to_string(),
+        "Wants=network-online.target".to_string(),
+        "StartLimitIntervalSec=600".to_string(),
+        "StartLimitBurst=5".to_string(),
+        String::new(),
+        "[Service]".to_string(),
+        // No WatchdogSec: the daemon does not send sd_notify keepalives.
+        "Type=simple".to_string(),
+        format!("ExecStart={}", spec.command("daemon start --foreground")),
+        "ExecReload=/bin/kill -HUP $MAINPID".to_string(),
+        "Restart=on-failure".to_string(),
+        "RestartSec=5s".to_string(),
+        "RestartSteps=5".to_string(),
+        "RestartMaxDelaySec=2min".to_string(),
+        format!("RestartPreventExitStatus={CONFIG_ERROR_EXIT_CODE}"),
+    ];
+    if let Some(user) = &spec.user {
+        unit.push(format!("User={user}"));
+    }
+    if let Some(instance) = &spec.instance {
+        unit.push(format!("Environment=PALINGENESIS_INSTANCE={instance}"));
+    }
+    if let Some(config_file) = &spec.config_file {
+        unit.push(environment("PALINGENESIS_CONFIG", config_file));
+    }
+    if let Some(state) = &spec.state_override {
+        unit.push(environment("PALINGENESIS_STATE", state));
+    }
+
+    unit.extend([
+        "NoNewPrivileges=yes".to_string(),
+        "PrivateTmp=yes".to_string(),
+        "ProtectSystem=strict".to_string(),
+        "ProtectHome=read-only".to_string(),
+    ]);
+    // `-` keeps the unit startable before a directory has been created.
+    let writable: Vec<String> = spec
+        .writable_paths
+        .iter()
+        .map(|path| format!("-{}", quote(&path.to_string_lossy())))
+        .collect();
+    if !writable.is_empty() {
+        unit.push(format!("ReadWritePaths={}", writable.join(" ")));
+    }
+
+    unit.extend([
+        String::new(),
+        "[Install]".to_string(),
+        format!("WantedBy={}", spec.scope.wanted_by()),
+    ]);
+    let mut text = unit.join("\n");
+    text.push('\n');
+    text
+}
+
+pub async fn handle_install_systemd(
+    system: bool,
+    dry_run: bool,
+    enable: bool,
+) -> anyhow::Result<()> {
+    ensure_supported(dry_run)?;
+    let config = load_effective_config()?;
+    let spec = UnitSpec::resolve(SystemdScope::from_flags(system), &config)?;
+    let unit = render_unit(&spec);
+    let path = spec.unit_path()?;
+
+    if dry_run {
+        println!("# {}", path.display());
+        print!("{unit}");
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&path, &unit)
+        .with_context(|| format!("Failed to write unit file: {}", path.display()))?;
+    println!("Wrote {}", path.display());
+
+    let systemctl = Systemctl::new(spec.scope);
+    systemctl.run(&["daemon-reload"])?;
+    if enable {
+        systemctl.run(&["enable", "--now", &spec.unit_name()])?;
+        println!("Enabled and started {}", spec.unit_name());
+    } else {
+        println!(
+            "Start it with: {} enable --now {}",
+            systemctl.display(),
+            spec.unit_name()
+        );
+    }
+    Ok(())
+}
+
+pub async fn handle_uninstall_systemd(system: bool) -> anyhow::Result<()> {
+    ensure_supported(false)?;
+    let scope = SystemdScope::from_flags(system);
+    let name = unit_name(Paths::instance().as_deref());
+    let path = scope.unit_dir()?.join(&name);
+    if !path.exists() {
+        println!("No unit installed at {}", path.display());
+        return Ok(());
+    }
+
+    let systemctl = Systemctl::new(scope);
+    // A unit that was never enabled or started is fine to remove anyway.
+    if let Err(err) = systemctl.run(&["disable", "--now", &name]) {
+        eprintln!("Warning: {err}");
+    }
+    std::fs::remove_file(&path)
+        .with_context(|| format!("Failed to remove unit file: {}", path.display()))?;
+    systemctl.run(&["daemon-reload"])?;
+    println!("Removed {}", path.display());
+    Ok(())
+}
+
+/// `palingenesis.service`, or `palingenesis-<instance>.service`.
+fn unit_name(instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("palingenesis-{instance}.service"),
+        None => "palingenesis.service".to_string(),
+    }
+}
+
+/// Fails on platforms without systemd; a dry run only needs Linux.
+fn ensure_supported(dry_run: bool) -> anyhow::Result<()> {
+    if !cfg!(target_os = "linux") {
+        bail!("systemd units are only supported on Linux; run `palingenesis daemon start` instead");
+    }
+    if !dry_run && !Path::new("/run/systemd/system").exists() {
+        bail!("systemd is not running on this machine; use --dry-run to print the unit");
+    }
+    Ok(())
+}
+
+struct Systemctl {
+    scope: SystemdScope,
+}
+
+impl Systemctl {
+    fn new(scope: SystemdScope) -> Self {
+        Self { scope }
+    }
+
+    fn display(&self) -> &'static str {
+        match self.scope {
+            SystemdScope::User => "systemctl --user",
+            SystemdScope::System => "systemctl",
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> anyhow::Result<()> {
+        let mut command = Command::new("systemctl");
+        if self.scope == SystemdScope::User {
+            command.arg("--user");
+        }
+        let status = command
+            .args(args)
+            .status()
+            .with_context(|| format!("Failed to run {}", self.display()))?;
+        if !status.success() {
+            bail!("`{} {}` failed ({status})", self.display(), args.join(" "));
+        }
+        Ok(())
+    }
+}
+
+fn environment(name: &str, value: &Path) -> String {
+    quote(&format!("Environment={name}={}", value.to_string_lossy()))
+}
+
+/// Quote a unit file word when it contains whitespace or quotes.
+fn quote(value: &str) -> String {
+    if value
+        .chars()
+        .any(|c| c.is_whitespace() || c == '"' || c == '\\')
+    {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(scope: SystemdScope) -> UnitSpec {
+        UnitSpec {
+            scope,
+            binary: PathBuf::from("/opt/palingenesis/bin/palingenesis"),
+            instance: None,
+            config_file: None,
+            state_override: None,
+            user: None,
+            writable_paths: vec![
+                PathBuf::from("/home/dev/.local/state/palingenesis"),
+                PathBuf::from("/run/user/1000/palingenesis"),
+                PathBuf::from("/home/dev/.opencode"),
+            ],
+        }
+    }
+
+    #[test]
+    fn user_unit_runs_current_binary_with_hardening() {
+        let unit = render_unit(&spec(SystemdScope::User));
+        let lines: Vec<&str> = unit.lines().collect();
+
+        assert!(
+            lines.contains(
+                &"ExecStart=/opt/palingenesis/bin/palingenesis daemon start --foreground"
+            )
+        );
+        assert!(lines.contains(&"Restart=on-failure"));
+        assert!(lines.contains(&"RestartPreventExitStatus=2"));
+        assert!(lines.contains(&"ProtectSystem=strict"));
+        assert!(lines.contains(&"ProtectHome=read-only"));
+        assert!(lines.contains(
+            &"ReadWritePaths=-/home/dev/.local/state/palingenesis -/run/user/1000/palingenesis -/home/dev/.opencode"
+        ));
+        assert!(lines.contains(&"WantedBy=default.target"));
+        assert!(!unit.contains("User="));
+        assert!(!unit.contains("Environment="));
+    }
+
+    #[test]
+    fn system_unit_carries_instance_and_overrides() {
+        let mut spec = spec(SystemdScope::System);
+        spec.instance = Some("work".to_string());
+        spec.user = Some("dev".to_string());
+        spec.config_file = Some(PathBuf::from("/etc/palingenesis/my config.toml"));
+        let unit = render_unit(&spec);
+        let lines: Vec<&str> = unit.lines().collect();
+
+        assert_eq!(spec.unit_name(), "palingenesis-work.service");
+        assert!(lines.contains(
+            &"ExecStart=/opt/palingenesis/bin/palingenesis --instance work daemon start --foreground"
+        ));
+        assert!(lines.contains(&"User=dev"));
+        assert!(lines.contains(&"Environment=PALINGENESIS_INSTANCE=work"));
+        assert!(
+            lines.contains(&"\"Environment=PALINGENESIS_CONFIG=/etc/palingenesis/my config.toml\"")
+        );
+        assert!(lines.contains(&"WantedBy=multi-user.target"));
+        assert_eq!(
+            spec.unit_path().unwrap(),
+            PathBuf::from("/etc/systemd/system/palingenesis-work.service")
+        );
+    }
+
+    #[test]
+    fn resolved_spec_allows_writing_session_and_state_dirs() {
+        let mut config = Config::default();
+        config.monitoring.session_dir = PathBuf::from("/srv/sessions");
+        let spec = UnitSpec::resolve(SystemdScope::User, &config).unwrap();
+        let unit = render_unit(&spec);
+
+        let binary = env::current_exe().unwrap().canonicalize().unwrap();
+        assert!(unit.contains(&format!("ExecStart={}", binary.display())));
+        let read_write = unit
+            .lines()
+            .find(|line| line.starts_with("ReadWritePaths="))
+            .unwrap();
+        assert!(read_write.contains("-/srv/sessions"));
+        assert!(read_write.contains(&format!("-{}", Paths::state_dir().display())));
+    }
+}
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::Paths;
+use crate::ipc::client::{IpcClient, IpcClientError};
+
+/// A daemon instance found through its socket in the runtime directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceInfo {
+    pub name: String,
+    /// Daemon state, `stopped` for a stale socket, or `unresponsive`.
+    pub state: String,
+    pub socket: PathBuf,
+}
+
+pub async fn handle_list(json: bool) -> anyhow::Result<()> {
+    let instances = discover_instances(&Paths::runtime_dir()).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&instances)?);
+    } else {
+        println!("{}", format_instances(&instances));
+    }
+    Ok(())
+}
+
+/// Instances with a socket in `runtime_dir`, sorted by name.
+pub async fn discover_instances(runtime_dir: &Path) -> Vec<InstanceInfo> {
+    let Ok(entries) = std::fs::read_dir(runtime_dir) else {
+        return Vec::new();
+    };
+    let mut sockets: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = Paths::instance_from_socket_name(entry.file_name().to_str()?)?;
+            Some((name, entry.path()))
+        })
+        .collect();
+    sockets.sort();
+
+    let mut instances = Vec::with_capacity(sockets.len());
+    for (name, socket) in sockets {
+        let state = match IpcClient::status_at(socket.clone()).await {
+            Ok(status) => status.state,
+            Err(IpcClientError::Timeout) => "unresponsive".to_string(),
+            Err(_) => "stopped".to_string(),
+        };
+        instances.push(InstanceInfo {
+            name,
+            state,
+            socket,
+        });
+    }
+    instances
+}
+
+fn format_instances(instances: &[InstanceInfo]) -> String {
+    if instances.is_empty() {
+        return "No daemon instances found".to_string();
+    }
+    let width = instances
+        .iter()
+        .map(|instance| instance.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("NAME".len());
+    let mut output = format!("{:<width$}  {:<12}  SOCKET", "NAME", "STATE");
+    for instance in instances {
+        output.push_str(&format!(
+            "\n{:<width$}  {:<12}  {}",
+            instance.name,
+            instance.state,
+            instance.socket.display()
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stale_sockets_are_listed_as_stopped() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("palingenesis-work.sock"), "").unwrap();
+        std::fs::write(temp.path().join("palingenesis.pid"), "1").unwrap();
+        std::fs::write(temp.path().join("handoff.json"), "{}").unwrap();
+
+        let instances = discover_instances(temp.path()).await;
+
+        assert_eq!(
+            instances,
+            vec![InstanceInfo {
+                name: "work".to_string(),
+                state: "stopped".to_string(),
+                socket: temp.path().join("palingenesis-work.sock"),
+            }]
+        );
+    }
+
+    #[test]
+    fn format_lists_name_state_and_socket() {
+        let instances = vec![
+            InstanceInfo {
+                name: "default".to_string(),
+                state: "monitoring".to_string(),
+                socket: PathBuf::from("/run/p/palingenesis.sock"),
+            },
+            InstanceInfo {
+                name: "work".to_string(),
+                state: "paused".to_string(),
+                socket: PathBuf::from("/run/p/palingenesis-work.sock"),
+            },
+        ];
+
+        assert_eq!(
+            format_instances(&instances),
+            "NAME     STATE         SOCKET\n\
+             default  monitoring    /run/p/palingenesis.sock\n\
+             work     paused        /run/p/palingenesis-work.sock"
+        );
+        assert_eq!(format_instances(&[]), "No daemon instances found");
+    }
+}
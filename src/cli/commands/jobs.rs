@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+
+use crate::daemon::jobs::{JobState, JobStatus};
+use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::util::duration;
+
+pub async fn handle_jobs(json: bool) -> anyhow::Result<()> {
+    match IpcClient::jobs().await {
+        Ok(jobs) => {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&jobs)?);
+            } else {
+                println!("{}", format_jobs(&jobs, Utc::now()));
+            }
+            Ok(())
+        }
+        Err(IpcClientError::NotRunning) => {
+            eprintln!("Daemon not running");
+            std::process::exit(1);
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// One line per job: state, priority, name and how long it has waited or run.
+pub(crate) fn format_jobs(jobs: &[JobStatus], now: DateTime<Utc>) -> String {
+    if jobs.is_empty() {
+        return "Jobs: none".to_string();
+    }
+    let mut output = "Jobs:".to_string();
+    for job in jobs {
+        let detail = match (job.state, job.started_at) {
+            (JobState::Running, Some(started)) => {
+                format!("running for {}", format_age(now, started))
+            }
+            _ => format!("queued for {}", format_age(now, job.enqueued_at)),
+        };
+        output.push_str(&format!(
+            "\n  #{} {:<10} {} ({detail})",
+            job.id,
+            job.priority.as_str(),
+            job.name
+        ));
+    }
+    output
+}
+
+fn format_age(now: DateTime<Utc>, since: DateTime<Utc>) -> String {
+    duration::format_compact(duration::between(since, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    use crate::daemon::jobs::JobPriority;
+
+    #[test]
+    fn format_jobs_shows_running_and_queued() {
+        let now = Utc::now();
+        let jobs = vec![
+            JobStatus {
+                id: 3,
+                name: "auto_detect".to_string(),
+                priority: JobPriority::Background,
+                state: JobState::Running,
+                enqueued_at: now - Duration::seconds(90),
+                started_at: Some(now - Duration::seconds(75)),
+            },
+            JobStatus {
+                id: 4,
+                name: "prune".to_string(),
+                priority: JobPriority::Background,
+                state: JobState::Queued,
+                enqueued_at: now - Duration::seconds(5),
+                started_at: None,
+            },
+        ];
+        assert_eq!(
+            format_jobs(&jobs, now),
+            "Jobs:\n  #3 background auto_detect (running for 1m 15s)\n  #4 background prune (queued for 5s)"
+        );
+        assert_eq!(format_jobs(&[], now), "Jobs: none");
+    }
+}
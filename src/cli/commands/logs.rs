@@ -1,10 +1,48 @@
 use crate::config::paths::Paths;
+use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::telemetry::log_buffer::LogBatch;
+use crate::telemetry::log_tail::{self, LogTail};
+use crate::util::duration;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
 use std::fs;
 use std::io::{BufRead, BufReader};
 use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+use tracing::Level;
+
+/// Reads the running daemon's in-memory log buffer, and the log file only
+/// when no daemon is running.
+pub async fn handle_logs(
+    follow: bool,
+    tail: u32,
+    since: Option<String>,
+    level: Option<Level>,
+) -> anyhow::Result<()> {
+    let since = since.as_deref().map(parse_duration).transpose()?;
+    let lines = if tail == 0 || since.is_some() {
+        usize::MAX
+    } else {
+        tail as usize
+    };
 
-pub async fn handle_logs(follow: bool, tail: u32, since: Option<String>) -> anyhow::Result<()> {
-    let log_path = Paths::state_dir().join("daemon.log");
+    let result = if follow {
+        IpcClient::follow_logs(lines, level, |record| println!("{record}")).await
+    } else {
+        IpcClient::logs(lines, level).await.map(|batch| {
+            let cutoff = since.map(|since| Utc::now() - since);
+            print!("{}", format_batch(&batch, cutoff));
+        })
+    };
+    match result {
+        Err(IpcClientError::NotRunning) => {}
+        other => return other.map_err(Into::into),
+    }
+
+    if level.is_some() {
+        eprintln!("--level only applies to a running daemon's log buffer");
+    }
+    let log_path = Paths::log_file();
 
     if !log_path.exists() {
         println!("No log file found");
@@ -13,8 +51,8 @@ pub async fn handle_logs(follow: bool, tail: u32, since: Option<String>) -> anyh
 
     if follow {
         handle_follow(&log_path, tail).await?;
-    } else if let Some(duration_str) = since {
-        handle_since(&log_path, &duration_str)?;
+    } else if let Some(duration) = since {
+        handle_since(&log_path, duration)?;
     } else if tail > 0 {
         handle_tail(&log_path, tail)?;
     } else {
@@ -48,8 +86,7 @@ fn handle_tail(log_path: &std::path::Path, tail: u32) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn handle_since(log_path: &std::path::Path, duration_str: &str) -> anyhow::Result<()> {
-    let duration = parse_duration(duration_str)?;
+fn handle_since(log_path: &std::path::Path, duration: Duration) -> anyhow::Result<()> {
     let cutoff_time = SystemTime::now() - duration;
 
     let file = fs::File::open(log_path)?;
@@ -65,60 +102,38 @@ fn handle_since(log_path: &std::path::Path, duration_str: &str) -> anyhow::Resul
     Ok(())
 }
 
+/// Prints the last `tail` records, then each record appended to the log
+/// file as it is written, across truncation and rotation.
 async fn handle_follow(log_path: &std::path::Path, tail: u32) -> anyhow::Result<()> {
-    let file = fs::File::open(log_path)?;
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
-
-    let start = if lines.len() > tail as usize {
-        lines.len() - tail as usize
-    } else {
-        0
-    };
-
-    for line in &lines[start..] {
-        println!("{}", line);
+    let (log, records) = LogTail::open(log_path, tail as usize)?;
+    for record in records {
+        println!("{record}");
     }
+    log_tail::follow(log, CancellationToken::new(), |record| println!("{record}")).await?;
+    Ok(())
+}
 
-    let mut last_size = fs::metadata(log_path)?.len();
-
-    loop {
-        tokio::time::sleep(Duration::from_millis(500)).await;
-
-        if let Ok(metadata) = fs::metadata(log_path) {
-            let current_size = metadata.len();
-            if current_size > last_size {
-                let file = fs::File::open(log_path)?;
-                let reader = BufReader::new(file);
-                for line in reader.lines() {
-                    let line = line?;
-                    println!("{}", line);
-                }
-                last_size = current_size;
-            }
+/// Records at or after `cutoff`, one per line, with a note when the buffer
+/// has dropped records.
+fn format_batch(batch: &LogBatch, cutoff: Option<DateTime<Utc>>) -> String {
+    let mut output = String::new();
+    if batch.dropped > 0 {
+        output.push_str(&format!(
+            "({} older records dropped from the daemon's log buffer)\n",
+            batch.dropped
+        ));
+    }
+    for record in &batch.records {
+        if cutoff.is_none_or(|cutoff| record.timestamp >= cutoff) {
+            output.push_str(&format!("{record}\n"));
         }
     }
+    output
 }
 
-fn parse_duration(duration_str: &str) -> anyhow::Result<Duration> {
-    let duration_str = duration_str.trim();
-    let (num_str, unit) = if let Some(pos) = duration_str.find(|c: char| c.is_alphabetic()) {
-        (&duration_str[..pos], &duration_str[pos..])
-    } else {
-        anyhow::bail!("Invalid duration format: {}", duration_str);
-    };
-
-    let num: u64 = num_str.parse()?;
-
-    let duration = match unit {
-        "s" | "sec" | "second" | "seconds" => Duration::from_secs(num),
-        "m" | "min" | "minute" | "minutes" => Duration::from_secs(num * 60),
-        "h" | "hour" | "hours" => Duration::from_secs(num * 3600),
-        "d" | "day" | "days" => Duration::from_secs(num * 86400),
-        _ => anyhow::bail!("Unknown duration unit: {}", unit),
-    };
-
-    Ok(duration)
+pub(crate) fn parse_duration(duration_str: &str) -> anyhow::Result<Duration> {
+    duration::parse(duration_str)
+        .with_context(|| format!("Invalid duration: {duration_str} (use e.g. 30s, 15m, 2h or 1d)"))
 }
 
 fn should_include_line(line: &str, cutoff_time: SystemTime) -> bool {
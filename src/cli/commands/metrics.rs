@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use anyhow::Context;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::schema::{DaemonConfig, HttpScope};
+use crate::telemetry::push::{MetricsPusher, PushTarget};
+
+/// Read the running daemon's metrics over HTTP and push them once.
+pub async fn handle_push(
+    endpoint: Option<String>,
+    job: Option<String>,
+    instance: Option<String>,
+) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let otel = config.otel.clone().unwrap_or_default();
+
+    let Some(endpoint) = endpoint.or(otel.push_endpoint.clone()) else {
+        anyhow::bail!("No Pushgateway endpoint; pass --endpoint or set otel.push_endpoint");
+    };
+    if !config.daemon.http_enabled {
+        anyhow::bail!(
+            "metrics push reads from the daemon's HTTP API; set daemon.http_enabled = true"
+        );
+    }
+
+    let mut target = PushTarget::new(endpoint, job.unwrap_or(otel.push_job_name));
+    if let Some(instance) = instance {
+        target = target.with_instance(instance);
+    }
+    if let (Some(user), Some(password)) = (otel.push_username, otel.push_password) {
+        target = target.with_basic_auth(user, password);
+    }
+
+    let source = metrics_source_url(&config.daemon);
+    let mut request = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .get(&source);
+    if let Some(token) = config.daemon.http_client_token(HttpScope::Read) {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to read metrics from {source}; is the daemon running?"))?;
+    if !response.status().is_success() {
+        anyhow::bail!("Daemon returned {} for {source}", response.status());
+    }
+    let body = response.text().await?;
+
+    let pusher = MetricsPusher::new(target);
+    pusher.push(body).await?;
+    println!("Pushed metrics to {}", pusher.target().grouping_url());
+    Ok(())
+}
+
+/// Daemon metrics URL; wildcard binds are reached over loopback.
+fn metrics_source_url(daemon: &DaemonConfig) -> String {
+    daemon.http_api_url("/api/v1/metrics")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_url_uses_loopback_for_wildcard_binds() {
+        let mut daemon = DaemonConfig {
+            http_port: 7654,
+            http_bind: "0.0.0.0".to_string(),
+            ..DaemonConfig::default()
+        };
+        assert_eq!(
+            metrics_source_url(&daemon),
+            "http://127.0.0.1:7654/api/v1/metrics"
+        );
+
+        daemon.http_bind = "::".to_string();
+        assert_eq!(
+            metrics_source_url(&daemon),
+            "http://[::1]:7654/api/v1/metrics"
+        );
+
+        daemon.http_bind = "fd00::1".to_string();
+        assert_eq!(
+            metrics_source_url(&daemon),
+            "http://[fd00::1]:7654/api/v1/metrics"
+        );
+    }
+}
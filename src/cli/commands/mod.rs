@@ -1,6 +1,38 @@
+pub mod attention;
+pub mod check;
 pub mod config;
 pub mod daemon;
+pub mod exclusions;
+pub mod failures;
+pub mod gc;
+pub mod incidents;
+pub mod install;
+pub mod instances;
+pub mod jobs;
 pub mod logs;
+#[cfg(feature = "mcp")]
 pub mod mcp;
+#[cfg(feature = "metrics-push")]
+pub mod metrics;
+pub mod next_step;
+#[cfg(feature = "notifications")]
+pub mod notify;
+pub mod orphans;
+pub mod quickstart;
+pub mod reconcile;
+pub mod schedules;
+#[cfg(feature = "keyring")]
+pub mod secret;
 pub mod session;
+pub mod sessions;
+pub mod state;
+pub mod stats;
 pub mod status;
+pub mod timeline;
+#[cfg(feature = "http-api")]
+pub mod tokens;
+pub mod verify_install;
+#[cfg(feature = "notifications")]
+pub mod webhook;
+pub mod wizard;
+pub mod worktrees;
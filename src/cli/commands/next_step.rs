@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::commands::config::load_effective_config;
+use crate::monitor::classifier::StopReason;
+use crate::monitor::frontmatter;
+use crate::resume::next_step::{self, NextStepInfo};
+use crate::resume::prompt_context::{self, AssembledContext};
+use crate::resume::{NewSessionConfig, ResumeContext};
+
+pub async fn handle_check(path: PathBuf, session: Option<PathBuf>) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let max_bytes = config.resume.max_next_step_bytes;
+    let path = if path.is_dir() {
+        path.join(NewSessionConfig::default().next_step_filename)
+    } else {
+        path
+    };
+
+    let (content, truncated) = next_step::read_capped(&path, max_bytes)
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to read {}: {err}", path.display()))?;
+    match next_step::parse(&content, truncated) {
+        Ok(info) => {
+            println!("{}", format_report(&path, &info, max_bytes));
+            if let Some(session_path) = session {
+                let session = frontmatter::parse_session(&session_path).map_err(|err| {
+                    anyhow::anyhow!("Failed to parse {}: {err}", session_path.display())
+                })?;
+                let ctx = ResumeContext::new(session_path, StopReason::ContextExhausted(None))
+                    .with_session(session);
+                let assembled =
+                    prompt_context::assemble(&config.resume.new_session.context, &ctx, &info);
+                println!("\n{}", format_context(&assembled));
+            }
+            Ok(())
+        }
+        Err(err) => Err(anyhow::anyhow!("{}: {}", path.display(), err.describe())),
+    }
+}
+
+fn format_report(path: &Path, info: &NextStepInfo, max_bytes: usize) -> String {
+    let mut report = format!(
+        "{}\nStep: {}\nDescription: {}\n",
+        path.display(),
+        info.step_number,
+        info.description
+    );
+    if info.truncated {
+        report.push_str(&format!(
+            "Context (truncated to resume.max_next_step_bytes = {max_bytes}):\n"
+        ));
+    } else {
+        report.push_str("Context:\n");
+    }
+    report.push_str(info.raw_content.trim());
+    report
+}
+
+/// The assembled `{context}`, as a new session would get it, with what
+/// the budgets cut.
+fn format_context(assembled: &AssembledContext) -> String {
+    let mut report = String::from("{context} (resume.new_session.context):\n");
+    report.push_str(&assembled.text());
+    let trimmed: Vec<&str> = assembled
+        .parts
+        .iter()
+        .filter(|part| part.trimmed)
+        .map(|part| part.section.as_str())
+        .collect();
+    let mut notes = Vec::new();
+    if !trimmed.is_empty() {
+        notes.push(format!("Trimmed: {}", trimmed.join(", ")));
+    }
+    if !assembled.dropped.is_empty() {
+        notes.push(format!("Dropped: {}", assembled.dropped.join(", ")));
+    }
+    if !notes.is_empty() {
+        report.push_str("\n\n");
+        report.push_str(&notes.join("\n"));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::{ContextSection, ContextSectionConfig, PromptContextConfig};
+
+    #[test]
+    fn report_shows_extracted_fields() {
+        let info = next_step::parse("- [ ] Step 3: Wire the CLI\n\nNotes here\n", false).unwrap();
+        let report = format_report(Path::new("/w/Next-step.md"), &info, 65536);
+        assert_eq!(
+            report,
+            "/w/Next-step.md\nStep: 3\nDescription: Wire the CLI\nContext:\n- [ ] Step 3: Wire the CLI\n\nNotes here"
+        );
+    }
+
+    #[test]
+    fn report_flags_truncation() {
+        let (content, truncated) = next_step::truncate("Step 1: a\nlong tail", 12);
+        let info = next_step::parse(&content, truncated).unwrap();
+        let report = format_report(Path::new("/w/Next-step.md"), &info, 12);
+        assert!(report.contains("Context (truncated to resume.max_next_step_bytes = 12):"));
+        assert!(report.ends_with("[... Next-step.md truncated at 12 bytes ...]"));
+    }
+
+    #[test]
+    fn context_report_lists_cut_sections() {
+        let config = PromptContextConfig {
+            sections: [
+                "custom:Carry on with the plan.",
+                "stop_reason",
+                "custom:Mind the tests.",
+            ]
+            .iter()
+            .map(|name| ContextSectionConfig::from(name.parse::<ContextSection>().unwrap()))
+            .collect(),
+            max_chars: 52,
+        };
+        let ctx = ResumeContext::new(
+            PathBuf::from("/w/session.md"),
+            StopReason::ContextExhausted(None),
+        );
+        let info = next_step::parse("Step 1: a\n", false).unwrap();
+
+        let report = format_context(&prompt_context::assemble(&config, &ctx, &info));
+
+        assert_eq!(
+            report,
+            "{context} (resume.new_session.context):\nCarry on with the plan.\n\
+             Stop reason: context_e [...]\n\nTrimmed: stop_reason\nDropped: custom:Mind the tests."
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::cli::commands::logs::parse_duration;
+use crate::config::schema::HttpScope;
+use crate::i18n::configured_locale;
+use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::notify::escalation::{EscalationDigest, digest_since};
+use crate::notify::history::{DeliveryOutcome, DeliveryRecord, HistoryQuery, NotificationHistory};
+use crate::resume::load_metrics_config;
+use crate::state::{MuteKind, NotificationMute, StateHandle};
+use crate::util::duration;
+
+#[derive(Debug, Deserialize)]
+struct RecentEnvelope {
+    data: RecentData,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentData {
+    entries: Vec<DeliveryRecord>,
+}
+
+/// Recent delivery decisions from the running daemon, or from
+/// `notifications.history_file` when the HTTP API is off.
+pub async fn handle_recent(
+    channel: Option<String>,
+    outcome: Option<String>,
+    since: Option<String>,
+    limit: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    let query = HistoryQuery {
+        channel,
+        outcome: outcome
+            .map(|value| {
+                DeliveryOutcome::parse(&value).with_context(|| {
+                    format!(
+                        "Unknown outcome {value:?}; use delivered, filtered, disabled, acknowledged, muted or failed"
+                    )
+                })
+            })
+            .transpose()?,
+        since: since
+            .map(|value| {
+                DateTime::parse_from_rfc3339(&value)
+                    .map(|time| time.with_timezone(&Utc))
+                    .with_context(|| format!("Invalid --since {value:?}; expected RFC3339"))
+            })
+            .transpose()?,
+        limit: Some(limit),
+    };
+
+    let config = load_effective_config()?;
+    let entries = if config.daemon.http_enabled {
+        let url = config.daemon.http_api_url(&format!(
+            "/api/v1/notifications/recent?{}",
+            query_string(&query)
+        ));
+        let mut request = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?
+            .get(&url);
+        if let Some(token) = config.daemon.http_client_token(HttpScope::Read) {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {url}; is the daemon running?"))?;
+        if !response.status().is_success() {
+            anyhow::bail!("Daemon returned {} for {url}", response.status());
+        }
+        response.json::<RecentEnvelope>().await?.data.entries
+    } else if let Some(path) = &config.notifications.history_file {
+        NotificationHistory::new(config.notifications.history_size)
+            .with_file(path.clone())
+            .recent(&query)
+    } else {
+        anyhow::bail!(
+            "Delivery history is served over HTTP; set daemon.http_enabled = true or notifications.history_file"
+        );
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        println!("{}", format_table(&entries));
+    }
+    Ok(())
+}
+
+fn query_string(query: &HistoryQuery) -> String {
+    let mut pairs = Vec::new();
+    if let Some(channel) = &query.channel {
+        pairs.push(format!("channel={}", encode(channel)));
+    }
+    if let Some(outcome) = query.outcome {
+        pairs.push(format!("outcome={}", outcome.as_str()));
+    }
+    if let Some(since) = query.since {
+        pairs.push(format!("since={}", encode(&since.to_rfc3339())));
+    }
+    if let Some(limit) = query.limit {
+        pairs.push(format!("limit={limit}"));
+    }
+    pairs.join("&")
+}
+
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn format_table(entries: &[DeliveryRecord]) -> String {
+    if entries.is_empty() {
+        return "No notification deliveries recorded".to_string();
+    }
+    let mut output = format!(
+        "{:<20} {:<18} {:<8} {:<12} {:<9} {:>6} {:>8}  ERROR\n",
+        "TIME", "EVENT", "SEVERITY", "CHANNEL", "OUTCOME", "STATUS", "LATENCY"
+    );
+    for entry in entries {
+        output.push_str(&format!(
+            "{:<20} {:<18} {:<8} {:<12} {:<9} {:>6} {:>8}  {}\n",
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.event_type,
+            entry.severity.as_str(),
+            entry.channel,
+            entry.outcome.as_str(),
+            entry
+                .http_status
+                .map(|status| status.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            entry
+                .latency_ms
+                .map(|ms| format!("{ms}ms"))
+                .unwrap_or_else(|| "-".to_string()),
+            entry.error.as_deref().unwrap_or(""),
+        ));
+    }
+    output.trim_end().to_string()
+}
+
+/// Ask the daemon to re-resolve one channel's secrets.
+pub async fn handle_rotate(channel: &str) -> anyhow::Result<()> {
+    match IpcClient::rotate_secret(channel).await {
+        Ok(true) => {
+            println!("Credentials of {channel} rotated");
+            Ok(())
+        }
+        Ok(false) => {
+            println!("Credentials of {channel} re-read; unchanged");
+            Ok(())
+        }
+        Err(IpcClientError::NotRunning) => {
+            eprintln!("Daemon not running");
+            std::process::exit(1);
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Active notification mutes, from the running daemon or else the state file.
+pub async fn handle_status(json: bool) -> anyhow::Result<()> {
+    let mutes = match IpcClient::mutes().await {
+        Ok(mutes) => mutes,
+        Err(IpcClientError::NotRunning) => {
+            let now = Utc::now();
+            StateHandle::read_state()
+                .notification_mutes
+                .into_iter()
+                .filter(|mute| !mute.is_expired(now))
+                .collect()
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&mutes)?);
+    } else {
+        println!("{}", format_mutes(&mutes, Utc::now()));
+    }
+    Ok(())
+}
+
+/// Mute a channel or event type in the running daemon.
+pub async fn handle_mute(
+    channel: Option<String>,
+    event: Option<String>,
+    duration: Option<String>,
+) -> anyhow::Result<()> {
+    let (kind, name) = mute_target(channel, event)?;
+    let duration = duration.as_deref().map(parse_duration).transpose()?;
+    if duration.is_some_and(|duration| duration.is_zero()) {
+        anyhow::bail!("--for must be longer than zero");
+    }
+    match IpcClient::mute(kind, &name, duration).await {
+        Ok(_) => {
+            match duration {
+                Some(duration) => println!(
+                    "Muted {kind} {name} for {}",
+                    duration::format_compact(duration)
+                ),
+                None => println!("Muted {kind} {name} until unmuted"),
+            }
+            Ok(())
+        }
+        Err(err) => ipc_failure(err),
+    }
+}
+
+/// Lift a mute in the running daemon.
+pub async fn handle_unmute(channel: Option<String>, event: Option<String>) -> anyhow::Result<()> {
+    let (kind, name) = mute_target(channel, event)?;
+    match IpcClient::unmute(kind, &name).await {
+        Ok(_) => {
+            println!("Unmuted {kind} {name}");
+            Ok(())
+        }
+        Err(err) => ipc_failure(err),
+    }
+}
+
+fn mute_target(
+    channel: Option<String>,
+    event: Option<String>,
+) -> anyhow::Result<(MuteKind, String)> {
+    match (channel, event) {
+        (Some(channel), None) => Ok((MuteKind::Channel, channel)),
+        (None, Some(event)) => Ok((MuteKind::Event, event)),
+        _ => anyhow::bail!("Pass either --channel or --event"),
+    }
+}
+
+fn ipc_failure(err: IpcClientError) -> anyhow::Result<()> {
+    match err {
+        IpcClientError::NotRunning => {
+            eprintln!("Daemon not running");
+            std::process::exit(1);
+        }
+        IpcClientError::Timeout => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        err => Err(err.into()),
+    }
+}
+
+fn format_mutes(mutes: &[NotificationMute], now: DateTime<Utc>) -> String {
+    if mutes.is_empty() {
+        return "No active notification mutes".to_string();
+    }
+    let mut output = format!("{:<8} {:<24} {:<20} REMAINING\n", "KIND", "NAME", "SINCE");
+    for mute in mutes {
+        let remaining = match mute.remaining(now) {
+            Some(remaining) => duration::format_compact(remaining.to_std().unwrap_or_default()),
+            None => "until unmuted".to_string(),
+        };
+        output.push_str(&format!(
+            "{:<8} {:<24} {:<20} {}\n",
+            mute.kind.as_str(),
+            mute.name,
+            mute.muted_at.format("%Y-%m-%d %H:%M:%S"),
+            remaining,
+        ));
+    }
+    output.trim_end().to_string()
+}
+
+/// Escalations and resume costs recorded in the state file, optionally
+/// within `since` (e.g. `24h`).
+pub fn handle_digest(since: Option<String>, json: bool) -> anyhow::Result<()> {
+    let since = since
+        .map(|window| parse_duration(&window).map(|window| digest_since(Utc::now(), window)))
+        .transpose()?;
+    let state = StateHandle::read_state();
+    let digest = EscalationDigest::from_state(&state, since)
+        .with_costs(&state, &load_metrics_config().currency);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&digest)?);
+    } else {
+        println!("{}", digest.format(configured_locale()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::EventSeverity;
+
+    #[test]
+    fn query_string_encodes_filters() {
+        let query = HistoryQuery {
+            channel: Some("my hook".to_string()),
+            outcome: Some(DeliveryOutcome::Failed),
+            since: Some(
+                DateTime::parse_from_rfc3339("2026-01-01T00:00:00+00:00")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            limit: Some(5),
+        };
+        assert_eq!(
+            query_string(&query),
+            "channel=my%20hook&outcome=failed&since=2026-01-01T00%3A00%3A00%2B00%3A00&limit=5"
+        );
+    }
+
+    #[test]
+    fn mutes_list_remaining_time() {
+        let now = Utc::now();
+        let mutes = [
+            NotificationMute {
+                kind: MuteKind::Channel,
+                name: "slack".to_string(),
+                muted_at: now - chrono::Duration::minutes(5),
+                until: Some(now + chrono::Duration::minutes(25)),
+            },
+            NotificationMute {
+                kind: MuteKind::Event,
+                name: "resume_attempted".to_string(),
+                muted_at: now,
+                until: None,
+            },
+        ];
+        let table = format_mutes(&mutes, now);
+        assert!(table.contains("slack"));
+        assert!(table.contains("25m"));
+        assert!(table.contains("until unmuted"));
+        assert_eq!(format_mutes(&[], now), "No active notification mutes");
+    }
+
+    #[test]
+    fn table_shows_outcome_status_and_error() {
+        let entry = DeliveryRecord {
+            timestamp: Utc::now(),
+            event_type: "resume_failed".to_string(),
+            severity: EventSeverity::Error,
+            channel: "ntfy".to_string(),
+            outcome: DeliveryOutcome::Failed,
+            http_status: Some(503),
+            latency_ms: Some(40),
+            error: Some("ntfy returned status 503".to_string()),
+        };
+        let table = format_table(&[entry]);
+        assert!(table.contains("failed"));
+        assert!(table.contains("503"));
+        assert!(table.contains("40ms"));
+        assert!(table.contains("ntfy returned status 503"));
+    }
+}
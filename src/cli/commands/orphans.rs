@@ -0,0 +1,139 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+
+use crate::daemon::pid::PidFile;
+use crate::resume::{BackupConfig, SessionBackup};
+use crate::state::{CurrentSession, OrphanedSession, StateFile, StateStore};
+
+pub async fn handle_list() -> anyhow::Result<()> {
+    let state = StateStore::new().load();
+    println!("{}", format_orphans(&state.orphaned_sessions));
+    Ok(())
+}
+
+pub async fn handle_adopt(path: PathBuf) -> anyhow::Result<()> {
+    if let Some(pid) = PidFile::new().running_pid() {
+        bail!("Daemon is running (PID {pid}); stop it first");
+    }
+    let store = StateStore::new();
+    let mut state = store.load();
+    let orphan = take_orphan(&mut state, &path)?;
+
+    state.current_session = Some(CurrentSession {
+        path: orphan.path.clone(),
+        ..CurrentSession::default()
+    });
+    store.save(&state)?;
+
+    println!("Adopted {} as the current session", orphan.path.display());
+    Ok(())
+}
+
+pub async fn handle_remove(path: PathBuf, yes: bool) -> anyhow::Result<()> {
+    if let Some(pid) = PidFile::new().running_pid() {
+        bail!("Daemon is running (PID {pid}); stop it first");
+    }
+    let store = StateStore::new();
+    let mut state = store.load();
+    let orphan = take_orphan(&mut state, &path)?;
+
+    if orphan.path.exists() {
+        if !yes && !confirm_remove(&orphan.path)? {
+            println!("Aborted.");
+            return Ok(());
+        }
+        let backup = SessionBackup::with_config(BackupConfig::default())
+            .create_backup(&orphan.path)
+            .await
+            .with_context(|| format!("Failed to back up {}", orphan.path.display()))?;
+        std::fs::remove_file(&orphan.path)
+            .with_context(|| format!("Failed to delete {}", orphan.path.display()))?;
+        println!(
+            "Removed {} (backup: {})",
+            orphan.path.display(),
+            backup.display()
+        );
+    } else {
+        println!(
+            "{} no longer exists; dropping the orphan record",
+            orphan.path.display()
+        );
+    }
+
+    store.save(&state)?;
+    Ok(())
+}
+
+/// Remove the orphan record for `path`, accepting relative paths.
+fn take_orphan(state: &mut StateFile, path: &Path) -> anyhow::Result<OrphanedSession> {
+    let orphan = state.remove_orphan(path).or_else(|| {
+        path.canonicalize()
+            .ok()
+            .and_then(|canonical| state.remove_orphan(&canonical))
+    });
+    match orphan {
+        Some(orphan) => Ok(orphan),
+        None => bail!("No orphaned session recorded for {}", path.display()),
+    }
+}
+
+fn confirm_remove(path: &Path) -> anyhow::Result<bool> {
+    print!(
+        "Delete orphaned session {}? A backup is kept. [y/N] ",
+        path.display()
+    );
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let response = input.trim();
+    Ok(response.eq_ignore_ascii_case("y") || response.eq_ignore_ascii_case("yes"))
+}
+
+fn format_orphans(orphans: &[OrphanedSession]) -> String {
+    if orphans.is_empty() {
+        return "No orphaned sessions".to_string();
+    }
+    let mut lines = vec![format!("Orphaned sessions ({}):", orphans.len())];
+    for orphan in orphans {
+        lines.push(format!(
+            "  {} (recorded {}): {}",
+            orphan.path.display(),
+            orphan.recorded_at.to_rfc3339(),
+            orphan.reason
+        ));
+    }
+    lines.push("Resolve with: palingenesis orphans adopt|remove <path>".to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_empty_list() {
+        assert_eq!(format_orphans(&[]), "No orphaned sessions");
+    }
+
+    #[test]
+    fn formats_orphans_with_reason() {
+        let orphan = OrphanedSession::new(PathBuf::from("/w/new.md"), "state store error");
+        let output = format_orphans(&[orphan]);
+        assert!(output.starts_with("Orphaned sessions (1):\n  /w/new.md (recorded "));
+        assert!(output.contains("): state store error\n"));
+    }
+
+    #[test]
+    fn take_orphan_rejects_unknown_path() {
+        let mut state = StateFile::default();
+        state.record_orphan(OrphanedSession::new(PathBuf::from("/w/new.md"), "boom"));
+
+        assert!(take_orphan(&mut state, Path::new("/w/other.md")).is_err());
+        let orphan = take_orphan(&mut state, Path::new("/w/new.md")).unwrap();
+        assert_eq!(orphan.reason, "boom");
+        assert!(state.orphaned_sessions.is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+//! `palingenesis quickstart`: from a fresh install to a monitored session in
+//! one command.
+//!
+//! Creates the default config when there is none, reports the assistants it
+//! can find, runs the daemon in the foreground next to a session watcher, and
+//! waits for the first session file event. Each milestone is printed as a
+//! checklist line with troubleshooting hints when it fails.
+
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::time::{Instant, sleep, timeout};
+use tokio_util::sync::CancellationToken;
+use tracing::Level;
+
+use crate::cli::commands::{config, install};
+use crate::cli::progress::Checklist;
+use crate::config::Paths;
+use crate::config::schema::Config;
+use crate::config::validation::validate_config;
+use crate::daemon::Daemon;
+use crate::daemon::state::load_config_from_disk;
+use crate::ipc::client::IpcClient;
+use crate::monitor::debounce::DebounceSettings;
+use crate::monitor::detection::detect_assistants;
+use crate::monitor::events::WatchEvent;
+use crate::monitor::filter::{SharedWatchFilter, WatchFilter};
+use crate::monitor::watcher::SessionWatcher;
+use crate::telemetry::otel::load_otel_config;
+use crate::telemetry::tracing::{TracingConfig, init_tracing};
+
+/// Default for `quickstart --timeout`.
+pub const DEFAULT_QUICKSTART_TIMEOUT_SECS: u64 = 60;
+
+/// How long the daemon gets to answer on its socket.
+const DAEMON_READY_TIMEOUT: Duration = Duration::from_secs(10);
+const DAEMON_READY_POLL: Duration = Duration::from_millis(100);
+
+pub const CONFIG_LOADED: &str = "Config loaded";
+pub const ASSISTANT_DETECTED: &str = "Assistant detected";
+pub const DAEMON_STARTED: &str = "Daemon started";
+pub const WATCHER_STARTED: &str = "Watcher started";
+pub const SESSION_DETECTED: &str = "Session detected";
+
+pub async fn handle_quickstart(timeout_secs: u64) -> anyhow::Result<()> {
+    // Debug logs for this run only; the installed service keeps its own level.
+    let tracing_config = TracingConfig {
+        level: Level::DEBUG,
+        log_to_file: false,
+        log_to_stderr: true,
+        ..TracingConfig::default()
+    };
+    let _guard = init_tracing(&tracing_config, load_otel_config().as_ref())?;
+
+    println!("palingenesis quickstart\n");
+    let mut checklist = Checklist::stdout();
+    let session = Quickstart::new()
+        .with_timeout(Duration::from_secs(timeout_secs))
+        .run(&mut checklist)
+        .await?;
+    match session {
+        Some(_) => offer_service_install().await,
+        None => anyhow::bail!("Quickstart did not see a session; see the hints above"),
+    }
+}
+
+/// The quickstart steps, separated from terminal setup so they can be driven
+/// against a temporary home.
+#[derive(Debug, Clone)]
+pub struct Quickstart {
+    timeout: Duration,
+}
+
+impl Quickstart {
+    pub fn new() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_QUICKSTART_TIMEOUT_SECS),
+        }
+    }
+
+    /// How long to wait for the first session file event.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run every step, returning the first session file seen; `None` when a
+    /// milestone failed.
+    pub async fn run<W: Write>(
+        &self,
+        checklist: &mut Checklist<W>,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let Some(config) = load_config(checklist).await? else {
+            return Ok(None);
+        };
+        report_assistants(checklist, &config);
+
+        checklist.start(DAEMON_STARTED);
+        let mut daemon = Daemon::new();
+        let shutdown = daemon.shutdown_token();
+        let daemon_run = daemon.run();
+        tokio::pin!(daemon_run);
+
+        let session = tokio::select! {
+            result = &mut daemon_run => {
+                let reason = match result {
+                    Ok(()) => "daemon stopped unexpectedly".to_string(),
+                    Err(err) => err.to_string(),
+                };
+                checklist.fail(DAEMON_STARTED, &reason, &daemon_hints());
+                return Ok(None);
+            }
+            session = self.observe(checklist, &config, shutdown.child_token()) => session,
+        };
+
+        shutdown.cancel();
+        daemon_run
+            .await
+            .context("Daemon failed while shutting down")?;
+        Ok(session)
+    }
+
+    async fn observe<W: Write>(
+        &self,
+        checklist: &mut Checklist<W>,
+        config: &Config,
+        cancel: CancellationToken,
+    ) -> Option<PathBuf> {
+        if let Err(reason) = wait_for_daemon().await {
+            checklist.fail(DAEMON_STARTED, &reason, &daemon_hints());
+            return None;
+        }
+        checklist.done(DAEMON_STARTED, &Paths::socket_file().display().to_string());
+
+        let monitoring = &config.monitoring;
+        let session_dir = monitoring.session_dir.clone();
+        checklist.start(WATCHER_STARTED);
+        let watcher = SessionWatcher::with_path(session_dir.clone())
+            .with_debounce_settings(DebounceSettings::from_config(monitoring))
+            .with_filter(SharedWatchFilter::new(WatchFilter::from_config(monitoring)));
+        let mut events = match watcher.run(cancel.clone()).await {
+            Ok(events) => events,
+            Err(err) => {
+                checklist.fail(
+                    WATCHER_STARTED,
+                    &err.to_string(),
+                    &[format!(
+                        "Check that {} is readable (monitoring.session_dir)",
+                        session_dir.display()
+                    )],
+                );
+                return None;
+            }
+        };
+        checklist.done(WATCHER_STARTED, &session_dir.display().to_string());
+        if !session_dir.exists() {
+            checklist.note("The directory does not exist yet; waiting for it to be created");
+        }
+
+        checklist.start(SESSION_DETECTED);
+        checklist.note(&format!(
+            "Start or continue a session in your assistant (waiting up to {}s)",
+            self.timeout.as_secs()
+        ));
+        let deadline = Instant::now() + self.timeout;
+        let session = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match timeout(remaining, events.recv()).await {
+                Ok(Some(WatchEvent::FileCreated(path) | WatchEvent::FileModified(path))) => {
+                    break Some(path);
+                }
+                Ok(Some(WatchEvent::DirectoryCreated(path))) => {
+                    checklist.note(&format!("{} was created", path.display()));
+                }
+                Ok(Some(WatchEvent::Error(message))) => {
+                    checklist.note(&format!("Watcher reported: {message}"));
+                }
+                Ok(Some(WatchEvent::FileDeleted(_))) => {}
+                Ok(None) | Err(_) => break None,
+            }
+        };
+        cancel.cancel();
+
+        match &session {
+            Some(path) => checklist.done(SESSION_DETECTED, &path.display().to_string()),
+            None => checklist.fail(
+                SESSION_DETECTED,
+                &format!("no session file changed within {}s", self.timeout.as_secs()),
+                &session_hints(config, self.timeout),
+            ),
+        }
+        session
+    }
+}
+
+impl Default for Quickstart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create the default config if there is none, then load it the way the
+/// daemon will.
+async fn load_config<W: Write>(checklist: &mut Checklist<W>) -> anyhow::Result<Option<Config>> {
+    let path = Paths::config_file();
+    checklist.start(CONFIG_LOADED);
+    if !path.exists() {
+        config::handle_init(false, Some(path.clone()), false).await?;
+    }
+
+    let edit_hints = [
+        "Check it with: palingenesis config validate".to_string(),
+        "Fix it with: palingenesis config edit".to_string(),
+    ];
+    let config = match load_config_from_disk() {
+        Ok(config) => config,
+        Err(err) => {
+            checklist.fail(CONFIG_LOADED, &err, &edit_hints);
+            return Ok(None);
+        }
+    };
+    if let Some(error) = validate_config(&config).errors.first() {
+        checklist.fail(
+            CONFIG_LOADED,
+            &format!("{}: {}", error.field, error.message),
+            &edit_hints,
+        );
+        return Ok(None);
+    }
+    checklist.done(CONFIG_LOADED, &path.display().to_string());
+    Ok(Some(config))
+}
+
+/// Print what detection found; finding nothing is reported but not fatal,
+/// the session directory may simply not exist yet.
+fn report_assistants<W: Write>(checklist: &mut Checklist<W>, config: &Config) {
+    checklist.start(ASSISTANT_DETECTED);
+    let detected = detect_assistants().assistants;
+    for assistant in &detected {
+        checklist.note(&format!(
+            "{} at {} (by {}{})",
+            assistant.name,
+            assistant.session_dir.display(),
+            assistant.detected_by.as_str(),
+            if assistant.active { ", running" } else { "" }
+        ));
+    }
+
+    let session_dir = &config.monitoring.session_dir;
+    match detected.first() {
+        Some(first) => {
+            let names: Vec<_> = detected.iter().map(|a| a.name.as_str()).collect();
+            checklist.done(ASSISTANT_DETECTED, &names.join(", "));
+            if !session_dir.exists() && !detected.iter().any(|a| &a.session_dir == session_dir) {
+                checklist.note(&format!(
+                    "Config watches {}; to follow {} set monitoring.session_dir = \"{}\"",
+                    session_dir.display(),
+                    first.name,
+                    first.session_dir.display()
+                ));
+            }
+        }
+        None => checklist.fail(
+            ASSISTANT_DETECTED,
+            "no assistant found",
+            &[
+                "Install opencode and run it once so ~/.opencode exists".to_string(),
+                format!(
+                    "Or point monitoring.session_dir at your assistant's sessions (now {})",
+                    session_dir.display()
+                ),
+            ],
+        ),
+    }
+}
+
+async fn wait_for_daemon() -> Result<(), String> {
+    let deadline = Instant::now() + DAEMON_READY_TIMEOUT;
+    loop {
+        match IpcClient::status().await {
+            Ok(_) => return Ok(()),
+            Err(err) if Instant::now() >= deadline => {
+                return Err(format!(
+                    "no answer within {}s: {err}",
+                    DAEMON_READY_TIMEOUT.as_secs()
+                ));
+            }
+            Err(_) => sleep(DAEMON_READY_POLL).await,
+        }
+    }
+}
+
+fn daemon_hints() -> Vec<String> {
+    vec![
+        "Another daemon may already be running: palingenesis status".to_string(),
+        "Stop it with: palingenesis daemon stop".to_string(),
+        format!(
+            "Check that {} and {} are writable",
+            Paths::state_dir().display(),
+            Paths::runtime_dir().display()
+        ),
+    ]
+}
+
+fn session_hints(config: &Config, waited: Duration) -> Vec<String> {
+    let monitoring = &config.monitoring;
+    let mut hints = vec![format!(
+        "Sessions must be written under {} (monitoring.session_dir)",
+        monitoring.session_dir.display()
+    )];
+    if !monitoring.include_extensions.is_empty() {
+        hints.push(format!(
+            "Only files ending in {} are watched (monitoring.include_extensions)",
+            monitoring.include_extensions.join(", ")
+        ));
+    }
+    hints.push(format!(
+        "Wait longer with: palingenesis quickstart --timeout {}",
+        waited.as_secs().saturating_mul(2).max(1)
+    ));
+    hints
+}
+
+/// Offer to keep the daemon running as a user service.
+async fn offer_service_install() -> anyhow::Result<()> {
+    let systemd = cfg!(target_os = "linux") && Path::new("/run/systemd/system").exists();
+    println!();
+    if !systemd {
+        println!("All set. Keep it running with: palingenesis daemon start --foreground");
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        println!("All set. Run it as a service with: palingenesis install systemd --enable");
+        return Ok(());
+    }
+
+    print!("All set. Install and start palingenesis as a systemd user service? [y/N] ");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let answer = input.trim();
+    if answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes") {
+        install::handle_install_systemd(false, false, true).await
+    } else {
+        println!("Later: palingenesis install systemd --enable");
+        Ok(())
+    }
+}
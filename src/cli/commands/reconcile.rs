@@ -0,0 +1,108 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::bail;
+use tokio_util::sync::CancellationToken;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::ResolvedPaths;
+use crate::daemon::pid::PidFile;
+use crate::daemon::reconcile::{ReconcileReport, Reconciler, live_session_paths};
+use crate::state::{StateError, StateFile, StateStore};
+
+/// Reconcile the state file of a stopped daemon, or with `dry_run` only
+/// print what would change.
+pub async fn handle_reconcile(dry_run: bool, json: bool) -> anyhow::Result<()> {
+    if !dry_run {
+        if let Some(pid) = PidFile::new().running_pid() {
+            bail!("Daemon is running (PID {pid}); stop it first or use --dry-run");
+        }
+    }
+    let config = load_effective_config()?;
+    let live = live_session_paths(&config, None, CancellationToken::new()).await;
+    let reconciler = Reconciler::new(&config.daemon.reconcile).with_live_sessions(live);
+    let paths = ResolvedPaths::current();
+    let report = reconcile(&StateStore::new(), paths.state_dir(), &reconciler, dry_run)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", format_report(&report));
+    }
+    Ok(())
+}
+
+/// Run `reconciler` over the state file in `store`. A dry run reads the
+/// file without migrating it and writes nothing; otherwise corrections are
+/// saved and recorded in `state_dir`.
+pub fn reconcile(
+    store: &StateStore,
+    state_dir: &Path,
+    reconciler: &Reconciler,
+    dry_run: bool,
+) -> anyhow::Result<ReconcileReport> {
+    if dry_run {
+        let state = match store.inspect() {
+            Ok((state, _)) => state,
+            Err(StateError::Io(err)) if err.kind() == ErrorKind::NotFound => StateFile::default(),
+            Err(err) => return Err(err.into()),
+        };
+        return Ok(reconciler.plan(&state));
+    }
+
+    let mut state = store.load();
+    let report = reconciler.apply(&mut state);
+    if !report.is_empty() {
+        store.save(&state)?;
+        report.record(state_dir)?;
+    }
+    Ok(report)
+}
+
+fn format_report(report: &ReconcileReport) -> String {
+    if report.is_empty() {
+        return "State file matches the session files; nothing to reconcile".to_string();
+    }
+    let count = report.corrections.len();
+    let noun = if count == 1 {
+        "correction"
+    } else {
+        "corrections"
+    };
+    let mut output = if report.dry_run {
+        format!("Would make {count} {noun}:")
+    } else {
+        format!("Made {count} {noun}:")
+    };
+    for correction in &report.corrections {
+        output.push_str(&format!("\n  [{}] {}", correction.kind, correction.detail));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::reconcile::{Correction, CorrectionKind};
+
+    #[test]
+    fn dry_run_reports_are_worded_as_plans() {
+        let mut report = ReconcileReport {
+            reconciled_at: chrono::Utc::now(),
+            dry_run: true,
+            corrections: vec![Correction {
+                kind: CorrectionKind::StatsRepaired,
+                session_path: None,
+                incident_id: None,
+                detail: "Stats repaired: value_saved -1 reset to 0".to_string(),
+            }],
+        };
+        assert_eq!(
+            format_report(&report),
+            "Would make 1 correction:\n  [stats_repaired] Stats repaired: value_saved -1 reset to 0"
+        );
+        report.dry_run = false;
+        assert!(format_report(&report).starts_with("Made 1 correction:"));
+        report.corrections.clear();
+        assert!(format_report(&report).contains("nothing to reconcile"));
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::Utc;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::daemon::schedules::{ScheduleStatus, statuses};
+use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::state::{ScheduleRun, ScheduleRunOutcome, StateStore};
+
+pub async fn handle_list(json: bool) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let state = StateStore::new().load();
+    let schedules = statuses(&config, &state, Utc::now());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&schedules)?);
+    } else {
+        println!("{}", format_schedules(&schedules));
+    }
+    Ok(())
+}
+
+/// Ask the daemon to fire `name` now.
+pub async fn handle_run(name: &str) -> anyhow::Result<()> {
+    match IpcClient::run_schedule(name).await {
+        Ok(run) => {
+            println!("{}", format_run(&run));
+            if run.outcome == ScheduleRunOutcome::Failed {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Err(IpcClientError::NotRunning) => {
+            eprintln!("Daemon not running");
+            std::process::exit(1);
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn format_run(run: &ScheduleRun) -> String {
+    match (run.outcome, &run.job_id) {
+        (ScheduleRunOutcome::Started, Some(job_id)) => format!(
+            "Schedule {} started (job {job_id}); see `palingenesis jobs`",
+            run.schedule
+        ),
+        (outcome, _) => format!(
+            "Schedule {} {outcome}: {}",
+            run.schedule,
+            run.detail.as_deref().unwrap_or("-")
+        ),
+    }
+}
+
+fn format_schedules(schedules: &[ScheduleStatus]) -> String {
+    if schedules.is_empty() {
+        return "No schedules configured".to_string();
+    }
+    let mut output = String::new();
+    for schedule in schedules {
+        let mut flags = Vec::new();
+        if !schedule.enabled {
+            flags.push("disabled");
+        }
+        if schedule.catch_up {
+            flags.push("catch-up");
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(", "))
+        };
+        output.push_str(&format!("{} ({}){flags}\n", schedule.name, schedule.when));
+        if let Some(next) = schedule.next_run {
+            output.push_str(&format!("  next: {}\n", next.to_rfc3339()));
+        }
+        match &schedule.last_run {
+            Some(run) => output.push_str(&format!(
+                "  last: {} {} ({})\n",
+                run.fired_at.to_rfc3339(),
+                run.outcome,
+                run.trigger
+            )),
+            None => output.push_str("  last: never\n"),
+        }
+    }
+    output.trim_end().to_string()
+}
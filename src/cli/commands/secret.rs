@@ -0,0 +1,76 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::Context;
+
+use crate::config::secrets::{KeyringStore, SecretIndex, SecretRef, SecretStore};
+
+/// Store a keychain entry, reading the value from stdin so it never lands in
+/// shell history or the process list.
+pub async fn handle_set(reference: &str) -> anyhow::Result<()> {
+    let secret = SecretRef::parse(reference)?;
+    let value = read_value(&secret)?;
+    if value.is_empty() {
+        anyhow::bail!("Refusing to store an empty secret for {secret}");
+    }
+
+    KeyringStore.set(&secret, &value)?;
+    SecretIndex::new(SecretIndex::default_path()).add(&secret)?;
+    println!("Stored {secret}; reference it as ${{keyring:{secret}}}");
+    Ok(())
+}
+
+pub async fn handle_rm(reference: &str) -> anyhow::Result<()> {
+    let secret = SecretRef::parse(reference)?;
+    KeyringStore.delete(&secret)?;
+    SecretIndex::new(SecretIndex::default_path()).remove(&secret)?;
+    println!("Removed {secret}");
+    Ok(())
+}
+
+pub async fn handle_ls() -> anyhow::Result<()> {
+    let index = SecretIndex::new(SecretIndex::default_path());
+    println!("{}", format_secrets(&index.list()));
+    Ok(())
+}
+
+fn read_value(secret: &SecretRef) -> anyhow::Result<String> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Value for {secret}: ");
+        io::stderr().flush()?;
+    }
+    let mut value = String::new();
+    stdin
+        .lock()
+        .read_line(&mut value)
+        .context("Failed to read secret from stdin")?;
+    Ok(value.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn format_secrets(secrets: &[SecretRef]) -> String {
+    if secrets.is_empty() {
+        return "No secrets stored with `palingenesis secret set`".to_string();
+    }
+    let mut lines = vec![format!("Secrets ({}):", secrets.len())];
+    lines.extend(secrets.iter().map(|secret| format!("  {secret}")));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_secret_names() {
+        let secrets = vec![
+            SecretRef::parse("pal/ntfy").unwrap(),
+            SecretRef::parse("pal/slack").unwrap(),
+        ];
+
+        assert_eq!(
+            format_secrets(&secrets),
+            "Secrets (2):\n  pal/ntfy\n  pal/slack"
+        );
+        assert!(format_secrets(&[]).starts_with("No secrets"));
+    }
+}
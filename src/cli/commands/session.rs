@@ -1,11 +1,36 @@
-use crate::ipc::client::{IpcClient, IpcClientError};
+use std::path::PathBuf;
+#[cfg(feature = "http-api")]
+use std::time::Duration;
+
+#[cfg(feature = "http-api")]
+use anyhow::Context;
+
+#[cfg(feature = "http-api")]
+use crate::cli::commands::config::load_effective_config;
+use crate::config::schema::DaemonMode;
+#[cfg(feature = "http-api")]
+use crate::config::schema::{DaemonConfig, HttpScope};
+use crate::daemon::session_jobs::NewSessionRequest;
+#[cfg(feature = "http-api")]
+use crate::daemon::session_jobs::{TrackedJob, TrackedJobState};
+use crate::ipc::client::{CommandAck, IpcClient, IpcClientError};
+#[cfg(feature = "http-api")]
+use crate::resume::ResumeOutcome;
+
+/// How often `new-session --prompt` polls its job.
+#[cfg(feature = "http-api")]
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 pub async fn handle_pause() -> anyhow::Result<()> {
     match IpcClient::pause().await {
-        Ok(()) => {
+        Ok(CommandAck::Applied(())) => {
             println!("Monitoring paused");
             Ok(())
         }
+        Ok(CommandAck::Deferred(message)) => {
+            println!("Pause accepted; {message}");
+            Ok(())
+        }
         Err(IpcClientError::NotRunning) => {
             eprintln!("Daemon not running");
             std::process::exit(1);
@@ -26,12 +51,55 @@ pub async fn handle_pause() -> anyhow::Result<()> {
     }
 }
 
+pub async fn handle_mode(mode: DaemonMode) -> anyhow::Result<()> {
+    match IpcClient::set_mode(mode).await {
+        Ok(()) => {
+            match mode {
+                DaemonMode::Active => println!("Mode: active (stops are resumed)"),
+                DaemonMode::Observe => println!("Mode: observe (stops are reported, not acted on)"),
+            }
+            Ok(())
+        }
+        Err(IpcClientError::NotRunning) => {
+            eprintln!("Daemon not running");
+            std::process::exit(1);
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn handle_ack(incident_id: &str) -> anyhow::Result<()> {
+    match IpcClient::ack(incident_id).await {
+        Ok(()) => {
+            println!("Incident {incident_id} acknowledged; further alerts are silenced");
+            Ok(())
+        }
+        Err(IpcClientError::NotRunning) => {
+            eprintln!("Daemon not running");
+            std::process::exit(1);
+        }
+        Err(IpcClientError::Timeout) => {
+            eprintln!("Daemon unresponsive");
+            std::process::exit(1);
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
 pub async fn handle_resume() -> anyhow::Result<()> {
     match IpcClient::resume().await {
-        Ok(()) => {
+        Ok(CommandAck::Applied(())) => {
             println!("Monitoring resumed");
             Ok(())
         }
+        Ok(CommandAck::Deferred(message)) => {
+            println!("Resume accepted; {message}");
+            Ok(())
+        }
         Err(IpcClientError::NotRunning) => {
             eprintln!("Daemon not running");
             std::process::exit(1);
@@ -52,12 +120,21 @@ pub async fn handle_resume() -> anyhow::Result<()> {
     }
 }
 
-pub async fn handle_new_session() -> anyhow::Result<()> {
-    match IpcClient::new_session().await {
-        Ok(()) => {
+pub async fn handle_new_session(force: bool) -> anyhow::Result<()> {
+    let result = if force {
+        IpcClient::force_new_session().await
+    } else {
+        IpcClient::new_session().await
+    };
+    match result {
+        Ok(CommandAck::Applied(())) => {
             println!("New session started");
             Ok(())
         }
+        Ok(CommandAck::Deferred(message)) => {
+            println!("New session accepted; {message}");
+            Ok(())
+        }
         Err(IpcClientError::NotRunning) => {
             eprintln!("Daemon not running");
             std::process::exit(1);
@@ -77,6 +154,159 @@ pub async fn handle_new_session() -> anyhow::Result<()> {
     }
 }
 
+/// Submit a new-session job to the daemon's HTTP API, then wait for it
+/// unless `no_wait` is set.
+pub async fn handle_new_session_job(
+    prompt: String,
+    workdir: Option<PathBuf>,
+    model: Option<String>,
+    no_wait: bool,
+) -> anyhow::Result<()> {
+    let mut request = NewSessionRequest::new(prompt);
+    if let Some(workdir) = workdir {
+        request = request.with_workdir(workdir);
+    }
+    if let Some(model) = model {
+        request = request.with_model(model);
+    }
+    submit_new_session_job(request, no_wait).await
+}
+
+#[cfg(not(feature = "http-api"))]
+async fn submit_new_session_job(_request: NewSessionRequest, _no_wait: bool) -> anyhow::Result<()> {
+    anyhow::bail!("new-session --prompt needs a build with the http-api feature")
+}
+
+#[cfg(feature = "http-api")]
+async fn submit_new_session_job(request: NewSessionRequest, no_wait: bool) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    if !config.daemon.http_enabled {
+        anyhow::bail!(
+            "new-session --prompt submits a job to the daemon's HTTP API; set daemon.http_enabled = true"
+        );
+    }
+    let client = JobClient::new(&config.daemon)?;
+    let Some(job_id) = client.submit(&request).await? else {
+        return Ok(());
+    };
+    if no_wait {
+        println!("{job_id}");
+        return Ok(());
+    }
+    let job = client.wait(&job_id).await?;
+    match (job.state, job.outcome) {
+        (TrackedJobState::Succeeded, Some(ResumeOutcome::Success { session_path, .. })) => {
+            println!("New session started: {}", session_path.display());
+            Ok(())
+        }
+        _ => anyhow::bail!(
+            "New session failed: {}",
+            job.error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+}
+
+/// Talks to the daemon's `new-session` and `jobs` endpoints.
+#[cfg(feature = "http-api")]
+pub(crate) struct JobClient {
+    client: reqwest::Client,
+    daemon: DaemonConfig,
+}
+
+#[cfg(feature = "http-api")]
+impl JobClient {
+    pub(crate) fn new(daemon: &DaemonConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()?,
+            daemon: daemon.clone(),
+        })
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        scope: HttpScope,
+    ) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, self.daemon.http_api_url(path));
+        match self.daemon.http_client_token(scope) {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Queue `request`, returning the job ID, or `None` when the daemon
+    /// deferred the command (the reason is printed).
+    pub(crate) async fn submit(
+        &self,
+        request: &NewSessionRequest,
+    ) -> anyhow::Result<Option<String>> {
+        let url = self.daemon.http_api_url("/api/v1/new-session");
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                "/api/v1/new-session",
+                HttpScope::Resume,
+            )
+            .json(request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {url}; is the daemon running?"))?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().await?;
+        if !status.is_success() {
+            anyhow::bail!("{}", error_message(&body, status));
+        }
+        if let Some(job_id) = body["job_id"].as_str() {
+            return Ok(Some(job_id.to_string()));
+        }
+        println!(
+            "New session accepted; {}",
+            body["message"].as_str().unwrap_or("deferred")
+        );
+        Ok(None)
+    }
+
+    /// The job's current state.
+    pub(crate) async fn job(&self, job_id: &str) -> anyhow::Result<TrackedJob> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!("/api/v1/jobs/{job_id}"),
+                HttpScope::Read,
+            )
+            .send()
+            .await?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().await?;
+        if !status.is_success() {
+            anyhow::bail!("{}", error_message(&body, status));
+        }
+        Ok(serde_json::from_value(body["job"].clone())?)
+    }
+
+    /// Poll until the job succeeds or fails.
+    pub(crate) async fn wait(&self, job_id: &str) -> anyhow::Result<TrackedJob> {
+        loop {
+            let job = self.job(job_id).await?;
+            if job.state.is_finished() {
+                return Ok(job);
+            }
+            tokio::time::sleep(JOB_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(feature = "http-api")]
+fn error_message(body: &serde_json::Value, status: reqwest::StatusCode) -> String {
+    body["error"]["message"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Daemon returned {status}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +350,26 @@ mod tests {
                 total_resumes: 1,
                 time_saved_seconds: 0.0,
                 time_saved_human: None,
+                opencode_endpoint: None,
+                exclusion_patterns: Vec::new(),
+                watch_filter: None,
+                jobs: Vec::new(),
+                deferred_until: None,
+                deferred_reason: None,
+                quota_resume_at: None,
+                resume_counters: Vec::new(),
+                needs_attention: false,
+                instance: None,
+                clock_skew_secs: None,
+                debounce_window_ms: None,
+                state_newer_version: None,
+                opencode_version: None,
+                restart_correlation: None,
+                mode: Default::default(),
+                incident_opened_at: None,
+                state_unsaved_since: None,
+                watcher_alive: None,
+                http_listeners: None,
             }
         }
 
@@ -175,7 +425,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_handle_pause_resume_new_session() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -186,10 +436,68 @@ mod tests {
         assert!(state.is_paused());
         handle_resume().await.unwrap();
         assert!(!state.is_paused());
-        handle_new_session().await.unwrap();
+        handle_new_session(false).await.unwrap();
         assert_eq!(state.new_session_count(), 1);
+        handle_new_session(true).await.unwrap();
+        assert_eq!(state.new_session_count(), 2);
 
         cancel.cancel();
         remove_env_var("PALINGENESIS_RUNTIME");
     }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn job_client_waits_for_the_session() {
+        use crate::http::{EventBroadcaster, HttpSupervisor};
+        use crate::resume::{ResumeError, SessionCreator};
+
+        struct Created;
+
+        #[async_trait::async_trait]
+        impl SessionCreator for Created {
+            async fn create(
+                &self,
+                _prompt: &str,
+                session_dir: &std::path::Path,
+            ) -> Result<PathBuf, ResumeError> {
+                Ok(session_dir.join("new.md"))
+            }
+        }
+
+        let sessions = tempdir().unwrap();
+        let mut config = crate::config::schema::Config::default();
+        config.daemon.http_enabled = true;
+        config.daemon.http_bind = "127.0.0.1".to_string();
+        config.daemon.http_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        config.monitoring.session_dir = sessions.path().to_path_buf();
+        let state = Arc::new(
+            crate::daemon::DaemonState::new_without_auto_detection().with_config(config.clone()),
+        );
+        state.set_session_creator(Arc::new(Created));
+        let supervisor = HttpSupervisor::new(
+            &state,
+            EventBroadcaster::default(),
+            CancellationToken::new(),
+        );
+        supervisor.start(&config).await.unwrap();
+
+        let client = JobClient::new(&config.daemon).unwrap();
+        let job_id = client
+            .submit(&NewSessionRequest::new("continue"))
+            .await
+            .unwrap()
+            .unwrap();
+        let job = client.wait(&job_id).await.unwrap();
+
+        assert_eq!(job.state, TrackedJobState::Succeeded);
+        assert!(matches!(
+            job.outcome,
+            Some(ResumeOutcome::Success { session_path, .. }) if session_path.ends_with("new.md")
+        ));
+        supervisor.stop().await;
+    }
 }
@@ -0,0 +1,135 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::Paths;
+use crate::ipc::client::IpcClient;
+use crate::monitor::catalog::{self, SessionEntry, SessionQuery, SessionSource};
+use crate::state::StateStore;
+use crate::util::path_display::PathDisplay;
+
+pub async fn handle_sessions(
+    json: bool,
+    status: Option<String>,
+    tag: Option<String>,
+    limit: Option<usize>,
+) -> anyhow::Result<()> {
+    let config = load_effective_config()?;
+    let state = StateStore::new().load();
+    // The pause flag only lives in the daemon; treat an unreachable daemon as not paused.
+    let paused = IpcClient::status()
+        .await
+        .is_ok_and(|status| status.state == "paused");
+    let entries = catalog::collect(
+        &config,
+        &state,
+        &Paths::state_dir(),
+        paused,
+        None,
+        None,
+        CancellationToken::new(),
+    )
+    .await;
+    let mut entries = SessionQuery { status, tag, limit }.apply(entries);
+    let paths = PathDisplay::from_config(&config);
+
+    if json {
+        let paths = paths.for_json();
+        for path in entries.iter_mut().filter_map(|entry| entry.path.as_mut()) {
+            paths.apply(path);
+        }
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        println!("{}", format_sessions(&entries, &paths));
+    }
+    Ok(())
+}
+
+/// One line per session: marker, name, status/progress, sources, tags and flags.
+pub(crate) fn format_sessions(entries: &[SessionEntry], paths: &PathDisplay) -> String {
+    if entries.is_empty() {
+        return "Sessions: none".to_string();
+    }
+    let mut output = "Sessions:".to_string();
+    for entry in entries {
+        let marker = if entry.active { "*" } else { " " };
+        let name = match (&entry.path, &entry.opencode_id) {
+            (Some(path), _) => paths.render(path),
+            (None, Some(id)) => format!("opencode:{id}"),
+            (None, None) => "<unknown>".to_string(),
+        };
+        let progress = match entry.total_steps {
+            Some(total) => format!("{}/{total} steps", entry.steps_completed),
+            None => format!("{} steps", entry.steps_completed),
+        };
+        let sources = entry
+            .sources
+            .iter()
+            .map(|source| match source {
+                SessionSource::State => "state",
+                SessionSource::Watcher => "watcher",
+                SessionSource::Opencode => "opencode",
+            })
+            .collect::<Vec<_>>()
+            .join("+");
+        let mut line = format!(
+            "\n{marker} {name} [{}] {progress} ({sources})",
+            entry.status.as_deref().unwrap_or("unknown")
+        );
+        if !entry.tags.is_empty() {
+            line.push_str(&format!(" #{}", entry.tags.join(" #")));
+        }
+        if let Some(reason) = &entry.last_classification {
+            line.push_str(&format!(" last stop: {reason}"));
+        }
+        if entry.excluded {
+            line.push_str(" excluded");
+        }
+        if entry.paused {
+            line.push_str(" paused");
+        }
+        output.push_str(&line);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn format_sessions_lists_sources_and_flags() {
+        let entries: Vec<SessionEntry> = serde_json::from_value(serde_json::json!([
+            {
+                "path": "/s/run.md",
+                "sources": ["state", "watcher"],
+                "status": "in-progress",
+                "steps_completed": 2,
+                "total_steps": 5,
+                "active": true,
+                "paused": false,
+                "excluded": true,
+                "last_classification": "rate_limit",
+                "tags": ["proj-a", "backend"]
+            },
+            {
+                "opencode_id": "ses_9",
+                "sources": ["opencode"],
+                "active": false,
+                "paused": false,
+                "excluded": false
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            format_sessions(&entries, &PathDisplay::default()),
+            "Sessions:\n* /s/run.md [in-progress] 2/5 steps (state+watcher) #proj-a #backend last stop: rate_limit excluded\n  opencode:ses_9 [unknown] 0 steps (opencode)"
+        );
+        assert_eq!(entries[0].path, Some(PathBuf::from("/s/run.md")));
+        assert_eq!(
+            format_sessions(&[], &PathDisplay::default()),
+            "Sessions: none"
+        );
+    }
+}
@@ -0,0 +1,8 @@
+use crate::state::StateStore;
+
+/// Print the state file as JSON, whatever format it is stored in.
+pub async fn handle_dump() -> anyhow::Result<()> {
+    let (state, _) = StateStore::new().inspect()?;
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
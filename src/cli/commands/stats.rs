@@ -0,0 +1,113 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde_json::json;
+
+use crate::cli::commands::logs::parse_duration;
+use crate::resume::cost::{self, CostSummary};
+use crate::resume::load_metrics_config;
+use crate::state::{StateFile, StateStore};
+use crate::util::duration;
+
+/// Resume totals, or with `costs` the estimated spend and value of recorded
+/// resumes per session, optionally within `since` (e.g. `7d`).
+pub async fn handle_stats(costs: bool, since: Option<String>, json: bool) -> anyhow::Result<()> {
+    let state = StateStore::new().load();
+    let currency = load_metrics_config().currency;
+    if costs {
+        let since = since
+            .map(|window| parse_duration(&window).map(|window| window_start(Utc::now(), window)))
+            .transpose()?;
+        let summary = CostSummary::from_state(&state, since, &currency);
+        if json {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            println!("{}", summary.format());
+        }
+    } else if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "stats": state.stats,
+                "currency": currency,
+            }))?
+        );
+    } else {
+        println!("{}", format_stats(&state, &currency));
+    }
+    Ok(())
+}
+
+/// Lifetime totals; costs read `unknown` until a resume had an estimate.
+fn format_stats(state: &StateFile, currency: &str) -> String {
+    let stats = &state.stats;
+    let recorded = |pick: fn(&crate::state::ResumeCost) -> Option<f64>| {
+        state
+            .resume_history
+            .iter()
+            .flat_map(|history| &history.costs)
+            .any(|cost| pick(cost).is_some())
+    };
+    let known = |total: f64, pick| (total != 0.0 || recorded(pick)).then_some(total);
+    format!(
+        "Resumes: {}\nTime saved: {}\nEstimated API cost: {}\nValue of time saved: {}",
+        stats.total_resumes,
+        duration::format_compact(duration::from_secs_f64(stats.time_saved_seconds)),
+        cost::format_amount(known(stats.estimated_cost, |cost| cost.cost), currency),
+        cost::format_amount(known(stats.value_saved, |cost| cost.value_saved), currency),
+    )
+}
+
+/// Start of the `--since` window; the earliest representable time for a
+/// window reaching past it.
+fn window_start(now: DateTime<Utc>, window: std::time::Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(window)
+        .ok()
+        .and_then(|window| now.checked_sub_signed(window))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ResumeCost;
+    use std::path::Path;
+
+    #[test]
+    fn huge_since_window_saturates() {
+        let now = Utc::now();
+        assert_eq!(
+            window_start(now, std::time::Duration::from_secs(u64::MAX)),
+            DateTime::<Utc>::MIN_UTC
+        );
+        assert_eq!(
+            window_start(now, std::time::Duration::from_secs(3600)),
+            now - TimeDelta::hours(1)
+        );
+    }
+
+    #[test]
+    fn totals_are_unknown_until_estimated() {
+        let mut state = StateFile::default();
+        state.stats.total_resumes = 3;
+        state.stats.time_saved_seconds = 900.0;
+        assert_eq!(
+            format_stats(&state, "USD"),
+            "Resumes: 3\nTime saved: 15m\nEstimated API cost: unknown\nValue of time saved: unknown"
+        );
+
+        state.stats.value_saved = 22.5;
+        state
+            .resume_history_mut(Path::new("/work/session.md"))
+            .record_cost(ResumeCost {
+                at: Utc::now(),
+                model: None,
+                tokens: None,
+                cost: None,
+                time_saved_seconds: 900.0,
+                value_saved: Some(22.5),
+            });
+        assert!(
+            format_stats(&state, "USD")
+                .ends_with("Estimated API cost: unknown\nValue of time saved: 22.50 USD")
+        );
+    }
+}
@@ -1,110 +1,779 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use serde_json::json;
 
+use crate::cli::StatusFormat;
+use crate::cli::commands::jobs::format_jobs;
+use crate::config::schema::DaemonMode;
+use crate::daemon::last_exit::LastExitFile;
 use crate::daemon::pid::PidFile;
+use crate::daemon::restart_correlation::RestartCorrelationStatus;
+use crate::i18n::{Locale, configured_locale, render, text};
 use crate::ipc::client::{IpcClient, IpcClientError};
+use crate::ipc::protocol::{
+    DaemonStatus, OpenCodeEndpointStatus, ResumeCounter, WatchFilterStatus,
+};
+use crate::state::{OpenCodeVersionRecord, STATE_VERSION};
+use crate::util::duration;
+use crate::util::path_display::{self, PathDisplay};
 
-pub async fn handle_status(json: bool) -> anyhow::Result<()> {
-    let pid_file = PidFile::new();
-    let pid = pid_file.read().ok();
+/// Overall daemon health, the contract behind `status --exit-code` and
+/// `status --format minimal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusSummary {
+    /// Monitoring or resuming normally.
+    Monitoring,
+    /// Daemon not running.
+    Stopped,
+    /// Paused by the operator.
+    Paused,
+    /// Waiting on a backoff, maintenance window or resume gate before resuming.
+    Waiting,
+    /// Running but unhealthy: a session needs attention, a resume loop was
+    /// suspected, or the daemon did not answer.
+    Degraded,
+}
+
+impl StatusSummary {
+    /// Map a daemon report onto the contract; problems outrank deliberate pauses.
+    pub fn from_status(status: &DaemonStatus) -> Self {
+        let loop_suspected = status
+            .resume_counters
+            .iter()
+            .any(|counter| counter.loop_suspected_at.is_some());
+        if status.needs_attention || loop_suspected || status.state_newer_version.is_some() {
+            return Self::Degraded;
+        }
+        match status.state.as_str() {
+            "paused" => Self::Paused,
+            "waiting" => Self::Waiting,
+            _ if status.deferred_until.is_some()
+                || status.deferred_reason.is_some()
+                || status.quota_resume_at.is_some() =>
+            {
+                Self::Waiting
+            }
+            "monitoring" | "resuming" => Self::Monitoring,
+            _ => Self::Degraded,
+        }
+    }
 
-    match IpcClient::status().await {
-        Ok(status) => {
-            if json {
-                let output = json!({
-                    "state": status.state,
-                    "pid": pid,
-                    "uptime_secs": status.uptime_secs,
-                    "current_session": status.current_session,
-                    "saves_count": status.saves_count,
-                    "total_resumes": status.total_resumes,
-                    "time_saved_seconds": status.time_saved_seconds,
-                    "time_saved_human": format_time_saved(status.time_saved_seconds),
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Monitoring => 0,
+            Self::Stopped => 1,
+            Self::Paused => 2,
+            Self::Waiting => 3,
+            Self::Degraded => 4,
+        }
+    }
+
+    /// Single token for `--format minimal`.
+    pub fn token(self) -> &'static str {
+        match self {
+            Self::Monitoring => "monitoring",
+            Self::Stopped => "stopped",
+            Self::Paused => "paused",
+            Self::Waiting => "waiting",
+            Self::Degraded => "degraded",
+        }
+    }
+}
+
+pub async fn handle_status(
+    json: bool,
+    verbose: bool,
+    format: StatusFormat,
+    exit_code: bool,
+    quiet: bool,
+) -> anyhow::Result<()> {
+    let exit_code = exit_code || quiet;
+    let result = IpcClient::status().await;
+    let unresponsive = matches!(result, Err(IpcClientError::Timeout));
+    let summary = match &result {
+        Ok(status) => StatusSummary::from_status(status),
+        Err(IpcClientError::NotRunning) => StatusSummary::Stopped,
+        // Unresponsive or unreadable: running, but not healthy.
+        Err(_) => StatusSummary::Degraded,
+    };
+
+    if !quiet {
+        if format == StatusFormat::Minimal {
+            println!("{}", summary.token());
+        } else {
+            let locale = if json {
+                Locale::En
             } else {
-                println!("palingenesis daemon: running");
-                if let Some(p) = pid {
-                    println!("PID: {}", p);
+                configured_locale()
+            };
+            match result {
+                Ok(status) => {
+                    print_status(&status, json, verbose, locale, &path_display::configured())?
                 }
-                println!("State: {}", status.state);
-                println!("Uptime: {}", format_duration(status.uptime_secs));
-                if let Some(session) = &status.current_session {
-                    println!("Current session: {}", session);
-                } else {
-                    println!("Current session: none");
+                Err(IpcClientError::NotRunning) => {
+                    eprintln!("{}", text(locale, "status.not_running"));
+                    if verbose {
+                        print_last_exit(locale);
+                    }
                 }
-                println!("Saves: {}", status.saves_count);
-                println!("Total resumes: {}", status.total_resumes);
-                println!(
-                    "Time saved: {}",
-                    format_time_saved(status.time_saved_seconds)
-                );
+                Err(IpcClientError::Timeout) => {
+                    eprintln!("{}", text(locale, "status.unresponsive"))
+                }
+                Err(err) if !exit_code => return Err(err.into()),
+                Err(err) => eprintln!("Failed to query daemon: {err}"),
             }
-            Ok(())
         }
-        Err(IpcClientError::NotRunning) => {
-            eprintln!("Daemon not running");
-            std::process::exit(1);
+    }
+
+    if exit_code {
+        std::process::exit(summary.exit_code());
+    }
+    if summary == StatusSummary::Stopped || unresponsive {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_status(
+    status: &DaemonStatus,
+    json: bool,
+    verbose: bool,
+    locale: Locale,
+    paths: &PathDisplay,
+) -> anyhow::Result<()> {
+    let pid_file = PidFile::new();
+    let pid = pid_file.read().ok();
+
+    if json {
+        let mut output = json!({
+            "state": status.state,
+            "mode": status.mode,
+            "pid": pid,
+            "uptime_secs": status.uptime_secs,
+            "current_session": status
+                .current_session
+                .as_deref()
+                .map(|session| paths.for_json().render(Path::new(session))),
+            "saves_count": status.saves_count,
+            "total_resumes": status.total_resumes,
+            "time_saved_seconds": status.time_saved_seconds,
+            "time_saved_human": format_time_saved(status.time_saved_seconds),
+            "summary": StatusSummary::from_status(status).token(),
+        });
+        if let Some(instance) = &status.instance {
+            output["instance"] = json!(instance);
+        }
+        if let Some(until) = status.deferred_until {
+            output["deferred_until"] = json!(until);
         }
-        Err(IpcClientError::Timeout) => {
-            eprintln!("Daemon unresponsive");
-            std::process::exit(1);
+        if let Some(reason) = &status.deferred_reason {
+            output["deferred_reason"] = json!(reason);
         }
-        Err(err) => Err(err.into()),
+        if let Some(at) = status.quota_resume_at {
+            output["quota_resume_at"] = json!(at);
+        }
+        if !status.resume_counters.is_empty() {
+            output["resume_counters"] = json!(status.resume_counters);
+        }
+        if status.needs_attention {
+            output["needs_attention"] = json!(true);
+        }
+        if let Some(correlation) = &status.restart_correlation {
+            output["restart_correlation"] = json!(correlation);
+        }
+        if let Some(version) = status.state_newer_version {
+            output["state_newer_version"] = json!(version);
+        }
+        if verbose {
+            output["opencode_endpoint"] = json!(status.opencode_endpoint);
+            output["exclusion_patterns"] = json!(status.exclusion_patterns);
+            output["watch_filter"] = json!(status.watch_filter);
+            output["jobs"] = json!(status.jobs);
+            output["clock_skew_secs"] = json!(status.clock_skew_secs);
+            output["debounce_window_ms"] = json!(status.debounce_window_ms);
+            output["opencode_version"] = json!(status.opencode_version);
+            output["http_listeners"] = json!(status.http_listeners);
+            output["last_exit"] = json!(LastExitFile::new().read().ok().flatten());
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let line = |key: &str, args: &[(&str, &dyn Display)]| println!("{}", render(locale, key, args));
+    println!("{}", text(locale, "status.running"));
+    if let Some(p) = pid {
+        line("status.pid", &[("pid", &p)]);
+    }
+    if let Some(instance) = &status.instance {
+        line("status.instance", &[("instance", instance)]);
     }
+    line("status.state", &[("state", &status.state)]);
+    line("status.mode", &[("mode", &format_mode(status.mode))]);
+    if let Some(until) = status.deferred_until {
+        println!("{}", format_deferral(until, locale));
+    } else if let Some(reason) = &status.deferred_reason {
+        line("status.deferred", &[("reason", reason)]);
+    } else if let Some(at) = status.quota_resume_at {
+        line("status.quota", &[("at", &at.to_rfc3339())]);
+    }
+    if status.needs_attention {
+        println!("{}", text(locale, "status.attention"));
+    }
+    if let Some(version) = status.state_newer_version {
+        line(
+            "status.state_read_only",
+            &[("found", &version), ("supported", &STATE_VERSION)],
+        );
+    }
+    line(
+        "status.uptime",
+        &[("uptime", &format_duration(status.uptime_secs))],
+    );
+    match &status.current_session {
+        Some(session) => line(
+            "status.current_session",
+            &[("session", &paths.path(Path::new(session)))],
+        ),
+        None => println!("{}", text(locale, "status.no_session")),
+    }
+    line("status.saves", &[("count", &status.saves_count)]);
+    line("status.total_resumes", &[("count", &status.total_resumes)]);
+    line(
+        "status.time_saved",
+        &[("saved", &format_time_saved(status.time_saved_seconds))],
+    );
+    if !status.resume_counters.is_empty() {
+        println!("{}", format_resume_counters(&status.resume_counters));
+    }
+    if let Some(correlation) = &status.restart_correlation {
+        println!("{}", format_restart_correlation(correlation));
+    }
+    if let Some(record) = status
+        .opencode_version
+        .as_ref()
+        .filter(|record| !record.problems.is_empty() && !verbose)
+    {
+        line("status.opencode_problems", &[("version", &record.version)]);
+    }
+    if verbose {
+        line(
+            "status.opencode_version",
+            &[(
+                "version",
+                &format_opencode_version(status.opencode_version.as_ref()),
+            )],
+        );
+        line(
+            "status.opencode_endpoint",
+            &[(
+                "endpoint",
+                &format_endpoint(status.opencode_endpoint.as_ref()),
+            )],
+        );
+        println!("{}", format_exclusions(&status.exclusion_patterns));
+        if let Some(filter) = &status.watch_filter {
+            println!("{}", format_watch_filter(filter));
+        }
+        println!("{}", format_jobs(&status.jobs, Utc::now()));
+        line(
+            "status.clock_skew",
+            &[("skew", &format_clock_skew(status.clock_skew_secs))],
+        );
+        if let Some(window_ms) = status.debounce_window_ms {
+            line("status.debounce", &[("window", &window_ms)]);
+        }
+        if let Some(listeners) = &status.http_listeners {
+            line("status.http_api", &[("address", &listeners.api)]);
+            if let Some(bots) = &listeners.bots {
+                line("status.http_bots", &[("address", bots)]);
+            }
+        }
+        print_last_exit(locale);
+    }
+    Ok(())
 }
 
-fn format_duration(secs: u64) -> String {
-    let hours = secs / 3600;
-    let minutes = (secs % 3600) / 60;
-    let seconds = secs % 60;
-
-    if hours > 0 {
-        format!("{}h {}m {}s", hours, minutes, seconds)
-    } else if minutes > 0 {
-        format!("{}m {}s", minutes, seconds)
-    } else {
-        format!("{}s", seconds)
+/// How the previous daemon instance exited, when a record exists.
+fn print_last_exit(locale: Locale) {
+    if let Ok(Some(exit)) = LastExitFile::new().read() {
+        println!(
+            "{}",
+            render(locale, "status.last_exit", &[("exit", &exit.describe())])
+        );
     }
 }
 
-fn format_time_saved(seconds: f64) -> String {
-    if !seconds.is_finite() || seconds <= 0.0 {
-        return "0 seconds".to_string();
+fn format_mode(mode: DaemonMode) -> String {
+    match mode {
+        DaemonMode::Active => "active".to_string(),
+        DaemonMode::Observe => "observe (stops are reported, not acted on)".to_string(),
+    }
+}
+
+fn format_clock_skew(skew_secs: Option<f64>) -> String {
+    match skew_secs {
+        None => "unknown (no provider Date header seen)".to_string(),
+        Some(secs) if secs.abs() < 1.0 => "none".to_string(),
+        Some(secs) if secs > 0.0 => format!("provider {secs:.0}s ahead of local clock"),
+        Some(secs) => format!("provider {:.0}s behind local clock", secs.abs()),
+    }
+}
+
+fn format_deferral(until: DateTime<Utc>, locale: Locale) -> String {
+    render(
+        locale,
+        "status.deferred_until",
+        &[("until", &until.to_rfc3339())],
+    )
+}
+
+fn format_resume_counters(counters: &[ResumeCounter]) -> String {
+    let mut output = "Resumes in the last hour:".to_string();
+    for counter in counters {
+        let limit = if counter.max_per_hour > 0 {
+            format!("{}/{}", counter.resumes_last_hour, counter.max_per_hour)
+        } else {
+            counter.resumes_last_hour.to_string()
+        };
+        output.push_str(&format!("\n  {} {limit}", counter.session));
+        if let Some(at) = counter.loop_suspected_at {
+            output.push_str(&format!(
+                " (loop suspected {}; auto-resume paused)",
+                duration::format_relative(at)
+            ));
+        }
+        if let Some(at) = counter.acknowledged_at {
+            let paused = if counter.resumes_paused {
+                "; auto-resume paused"
+            } else {
+                ""
+            };
+            output.push_str(&format!(
+                " (acknowledged {}{paused})",
+                duration::format_relative(at)
+            ));
+        }
+    }
+    output
+}
+
+fn format_restart_correlation(status: &RestartCorrelationStatus) -> String {
+    let mut output = match (status.server_down_since, status.crashed_pid) {
+        (Some(since), pid) => format!(
+            "OpenCode: crashed {}{}; waiting for restart",
+            duration::format_relative(since),
+            pid.map(|pid| format!(" (pid {pid})")).unwrap_or_default()
+        ),
+        (None, _) => "OpenCode: running".to_string(),
+    };
+    for session in &status.held_sessions {
+        output.push_str(&format!("\n  stop held: {session}"));
+    }
+    if status.resumes_avoided > 0 {
+        output.push_str(&format!(
+            "\n  resumes avoided by restart: {}",
+            status.resumes_avoided
+        ));
+    }
+    output
+}
+
+fn format_opencode_version(record: Option<&OpenCodeVersionRecord>) -> String {
+    let Some(record) = record else {
+        return "unknown (opencode --version not available)".to_string();
+    };
+    let mut output = format!(
+        "{} (checked {})",
+        record.version,
+        record.detected_at.to_rfc3339()
+    );
+    for problem in &record.problems {
+        output.push_str(&format!("\n  compatibility problem: {problem}"));
+    }
+    output
+}
+
+fn format_endpoint(endpoint: Option<&OpenCodeEndpointStatus>) -> String {
+    let Some(endpoint) = endpoint else {
+        return "unknown (OpenCode not tracked)".to_string();
+    };
+
+    match (&endpoint.url, &endpoint.error) {
+        (Some(url), _) => {
+            let mut details = Vec::new();
+            if let Some(source) = &endpoint.source {
+                details.push(source.replace('_', " "));
+            }
+            if let Some(pid) = endpoint.pid {
+                details.push(format!("pid {pid}"));
+            }
+            if let Some(started_at) = endpoint.started_at {
+                details.push(format!("started {}", started_at.to_rfc3339()));
+            }
+            if details.is_empty() {
+                url.clone()
+            } else {
+                format!("{url} ({})", details.join(", "))
+            }
+        }
+        (None, Some(error)) => format!("unavailable: discovery failed ({error})"),
+        (None, None) => "unknown".to_string(),
     }
+}
 
-    if seconds < 60.0 {
-        format!("{:.0} seconds", seconds)
-    } else if seconds < 3600.0 {
-        format!("{:.1} minutes", seconds / 60.0)
-    } else if seconds < 86400.0 {
-        format!("{:.1} hours", seconds / 3600.0)
+fn format_exclusions(patterns: &[String]) -> String {
+    if patterns.is_empty() {
+        return "Auto-resume exclusions: none".to_string();
+    }
+    let mut output = "Auto-resume exclusions:".to_string();
+    for pattern in patterns {
+        output.push_str(&format!("\n  {pattern}"));
+    }
+    output
+}
+
+fn format_watch_filter(filter: &WatchFilterStatus) -> String {
+    let extensions = if filter.include_extensions.is_empty() {
+        "all".to_string()
     } else {
-        format!("{:.1} days", seconds / 86400.0)
+        filter.include_extensions.join(", ")
+    };
+    let mut output = format!(
+        "Watched extensions: {extensions}\nIgnored paths:{}",
+        if filter.ignore_patterns.is_empty() {
+            " none"
+        } else {
+            ""
+        }
+    );
+    for pattern in &filter.ignore_patterns {
+        output.push_str(&format!("\n  {pattern}"));
     }
+    output.push_str(&format!("\nEvents filtered: {}", filter.events_filtered));
+    output
+}
+
+fn format_duration(secs: u64) -> String {
+    duration::format_precise(Duration::from_secs(secs))
+}
+
+fn format_time_saved(seconds: f64) -> String {
+    duration::format_compact(duration::from_secs_f64(seconds))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::format_time_saved;
+    use super::{
+        DaemonMode, DaemonStatus, Locale, OpenCodeEndpointStatus, RestartCorrelationStatus,
+        ResumeCounter, StatusSummary, WatchFilterStatus, format_clock_skew, format_deferral,
+        format_endpoint, format_exclusions, format_mode, format_opencode_version,
+        format_restart_correlation, format_resume_counters, format_time_saved, format_watch_filter,
+    };
+    use crate::state::OpenCodeVersionRecord;
+
+    fn status(state: &str) -> DaemonStatus {
+        DaemonStatus {
+            state: state.to_string(),
+            uptime_secs: 60,
+            current_session: None,
+            saves_count: 0,
+            total_resumes: 0,
+            time_saved_seconds: 0.0,
+            time_saved_human: None,
+            opencode_endpoint: None,
+            exclusion_patterns: Vec::new(),
+            watch_filter: None,
+            jobs: Vec::new(),
+            deferred_until: None,
+            deferred_reason: None,
+            quota_resume_at: None,
+            resume_counters: Vec::new(),
+            needs_attention: false,
+            instance: None,
+            clock_skew_secs: None,
+            debounce_window_ms: None,
+            state_newer_version: None,
+            opencode_version: None,
+            restart_correlation: None,
+            mode: Default::default(),
+            incident_opened_at: None,
+            state_unsaved_since: None,
+            watcher_alive: None,
+            http_listeners: None,
+        }
+    }
+
+    #[test]
+    fn test_summary_maps_daemon_states() {
+        for (state, summary, code, token) in [
+            ("monitoring", StatusSummary::Monitoring, 0, "monitoring"),
+            ("resuming", StatusSummary::Monitoring, 0, "monitoring"),
+            ("paused", StatusSummary::Paused, 2, "paused"),
+            ("waiting", StatusSummary::Waiting, 3, "waiting"),
+            ("mystery", StatusSummary::Degraded, 4, "degraded"),
+        ] {
+            let mapped = StatusSummary::from_status(&status(state));
+            assert_eq!(mapped, summary, "{state}");
+            assert_eq!(mapped.exit_code(), code, "{state}");
+            assert_eq!(mapped.token(), token, "{state}");
+        }
+    }
+
+    #[test]
+    fn test_summary_for_stopped_daemon() {
+        assert_eq!(StatusSummary::Stopped.exit_code(), 1);
+        assert_eq!(StatusSummary::Stopped.token(), "stopped");
+    }
+
+    #[test]
+    fn test_summary_treats_maintenance_deferral_as_waiting() {
+        let mut deferred = status("monitoring");
+        deferred.deferred_until = Some(chrono::Utc::now());
+        assert_eq!(
+            StatusSummary::from_status(&deferred),
+            StatusSummary::Waiting
+        );
+
+        let mut gated = status("monitoring");
+        gated.deferred_reason = Some("waiting for AC power".to_string());
+        assert_eq!(StatusSummary::from_status(&gated), StatusSummary::Waiting);
+
+        let mut quota = status("monitoring");
+        quota.quota_resume_at = Some(chrono::Utc::now());
+        assert_eq!(StatusSummary::from_status(&quota), StatusSummary::Waiting);
+
+        // An explicit pause still wins over a deferral.
+        deferred.state = "paused".to_string();
+        assert_eq!(StatusSummary::from_status(&deferred), StatusSummary::Paused);
+    }
+
+    #[test]
+    fn test_summary_degraded_outranks_pause() {
+        let mut held = status("paused");
+        held.needs_attention = true;
+        assert_eq!(StatusSummary::from_status(&held), StatusSummary::Degraded);
+
+        let mut looping = status("monitoring");
+        looping.resume_counters = vec![ResumeCounter {
+            session: "/tmp/session.md".to_string(),
+            resumes_last_hour: 10,
+            max_per_hour: 10,
+            loop_suspected_at: Some(chrono::Utc::now()),
+            acknowledged_at: None,
+            resumes_paused: false,
+        }];
+        assert_eq!(
+            StatusSummary::from_status(&looping),
+            StatusSummary::Degraded
+        );
+    }
+
+    #[test]
+    fn test_format_endpoint_with_discovered_port() {
+        let endpoint = OpenCodeEndpointStatus {
+            url: Some("http://localhost:38211".to_string()),
+            source: Some("listening_socket".to_string()),
+            pid: Some(42),
+            started_at: None,
+            error: None,
+        };
+        assert_eq!(
+            format_endpoint(Some(&endpoint)),
+            "http://localhost:38211 (listening socket, pid 42)"
+        );
+    }
+
+    #[test]
+    fn test_format_endpoint_includes_process_start_time() {
+        let endpoint = OpenCodeEndpointStatus {
+            url: Some("http://localhost:4096".to_string()),
+            source: Some("configured".to_string()),
+            pid: Some(42),
+            started_at: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+                .ok()
+                .map(|time| time.with_timezone(&chrono::Utc)),
+            error: None,
+        };
+        assert_eq!(
+            format_endpoint(Some(&endpoint)),
+            "http://localhost:4096 (configured, pid 42, started 2026-01-02T03:04:05+00:00)"
+        );
+    }
+
+    #[test]
+    fn test_format_deferral_names_maintenance_window() {
+        let until = chrono::DateTime::parse_from_rfc3339("2026-01-06T04:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(
+            format_deferral(until, Locale::En),
+            "Auto-resume: deferred until 2026-01-06T04:00:00+00:00 (maintenance window)"
+        );
+        assert_eq!(
+            format_deferral(until, Locale::Ja),
+            "自動再開: 2026-01-06T04:00:00+00:00 まで延期（メンテナンス時間帯）"
+        );
+    }
+
+    #[test]
+    fn test_format_endpoint_with_discovery_error() {
+        let endpoint = OpenCodeEndpointStatus {
+            url: None,
+            source: None,
+            pid: None,
+            started_at: None,
+            error: Some("no listening TCP socket found for pid 42".to_string()),
+        };
+        assert!(format_endpoint(Some(&endpoint)).starts_with("unavailable"));
+    }
+
+    #[test]
+    fn test_format_exclusions_lists_patterns() {
+        assert_eq!(format_exclusions(&[]), "Auto-resume exclusions: none");
+        assert_eq!(
+            format_exclusions(&["**/experiments/**".to_string(), "*scratch*".to_string()]),
+            "Auto-resume exclusions:\n  **/experiments/**\n  *scratch*"
+        );
+    }
+
+    #[test]
+    fn test_format_watch_filter() {
+        let filter = WatchFilterStatus {
+            include_extensions: vec!["md".to_string(), "jsonl".to_string()],
+            ignore_patterns: vec!["*.swp".to_string()],
+            events_filtered: 12,
+        };
+        assert_eq!(
+            format_watch_filter(&filter),
+            "Watched extensions: md, jsonl\nIgnored paths:\n  *.swp\nEvents filtered: 12"
+        );
+    }
+
+    #[test]
+    fn test_format_mode() {
+        assert_eq!(format_mode(DaemonMode::Active), "active");
+        assert_eq!(
+            format_mode(DaemonMode::Observe),
+            "observe (stops are reported, not acted on)"
+        );
+    }
+
+    #[test]
+    fn test_format_clock_skew() {
+        assert_eq!(
+            format_clock_skew(None),
+            "unknown (no provider Date header seen)"
+        );
+        assert_eq!(format_clock_skew(Some(0.4)), "none");
+        assert_eq!(
+            format_clock_skew(Some(95.2)),
+            "provider 95s ahead of local clock"
+        );
+        assert_eq!(
+            format_clock_skew(Some(-120.0)),
+            "provider 120s behind local clock"
+        );
+    }
+
+    #[test]
+    fn test_format_resume_counters_flags_loops() {
+        let counters = vec![
+            ResumeCounter {
+                session: "/work/loop.md".to_string(),
+                resumes_last_hour: 10,
+                max_per_hour: 10,
+                loop_suspected_at: Some(chrono::Utc::now() - chrono::TimeDelta::minutes(3)),
+                acknowledged_at: None,
+                resumes_paused: false,
+            },
+            ResumeCounter {
+                session: "/work/ok.md".to_string(),
+                resumes_last_hour: 1,
+                max_per_hour: 0,
+                loop_suspected_at: None,
+                acknowledged_at: None,
+                resumes_paused: false,
+            },
+            ResumeCounter {
+                session: "/work/acked.md".to_string(),
+                resumes_last_hour: 2,
+                max_per_hour: 10,
+                loop_suspected_at: None,
+                acknowledged_at: Some(chrono::Utc::now() - chrono::TimeDelta::minutes(5)),
+                resumes_paused: true,
+            },
+        ];
+        assert_eq!(
+            format_resume_counters(&counters),
+            "Resumes in the last hour:\n  /work/loop.md 10/10 (loop suspected 3 minutes ago; auto-resume paused)\n  /work/ok.md 1\n  /work/acked.md 2/10 (acknowledged 5 minutes ago; auto-resume paused)"
+        );
+    }
+
+    #[test]
+    fn test_format_restart_correlation() {
+        let down = RestartCorrelationStatus {
+            server_down_since: Some(chrono::Utc::now() - chrono::TimeDelta::minutes(2)),
+            crashed_pid: Some(42),
+            held_sessions: vec!["/work/session.md".to_string()],
+            resumes_avoided: 0,
+        };
+        assert_eq!(
+            format_restart_correlation(&down),
+            "OpenCode: crashed 2 minutes ago (pid 42); waiting for restart\n  stop held: /work/session.md"
+        );
+
+        let back = RestartCorrelationStatus {
+            server_down_since: None,
+            crashed_pid: None,
+            held_sessions: Vec::new(),
+            resumes_avoided: 3,
+        };
+        assert_eq!(
+            format_restart_correlation(&back),
+            "OpenCode: running\n  resumes avoided by restart: 3"
+        );
+    }
 
     #[test]
     fn test_format_time_saved_seconds() {
-        assert_eq!(format_time_saved(42.0), "42 seconds");
+        assert_eq!(format_time_saved(42.0), "42s");
     }
 
     #[test]
     fn test_format_time_saved_minutes() {
-        assert_eq!(format_time_saved(90.0), "1.5 minutes");
+        assert_eq!(format_time_saved(90.0), "1m 30s");
     }
 
     #[test]
     fn test_format_time_saved_hours() {
-        assert_eq!(format_time_saved(3600.0), "1.0 hours");
+        assert_eq!(format_time_saved(3600.0), "1h");
     }
 
     #[test]
     fn test_format_time_saved_days() {
-        assert_eq!(format_time_saved(172800.0), "2.0 days");
+        assert_eq!(format_time_saved(172800.0), "2d");
+    }
+
+    #[test]
+    fn test_format_opencode_version() {
+        assert_eq!(
+            format_opencode_version(None),
+            "unknown (opencode --version not available)"
+        );
+        let record = OpenCodeVersionRecord {
+            version: "0.5.0".to_string(),
+            detected_at: chrono::DateTime::parse_from_rfc3339("2026-01-05T10:00:00Z")
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+            problems: vec!["`opencode new` no longer lists --workdir".to_string()],
+        };
+        assert_eq!(
+            format_opencode_version(Some(&record)),
+            "0.5.0 (checked 2026-01-05T10:00:00+00:00)\n  \
+             compatibility problem: `opencode new` no longer lists --workdir"
+        );
     }
 }
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use crate::state::StateStore;
+use crate::state::timeline::{self, TimelineView};
+use crate::util::duration;
+use crate::util::path_display::{self, PathDisplay};
+
+const OFFLINE: &str = "unknown (daemon offline)";
+
+pub async fn handle_timeline(json: bool) -> anyhow::Result<()> {
+    let state = StateStore::new().load();
+    let Some(mut view) = timeline::current(&state) else {
+        println!("No step completions recorded for the current session");
+        return Ok(());
+    };
+    let paths = path_display::configured();
+    if json {
+        paths.for_json().apply(&mut view.session_path);
+        println!("{}", serde_json::to_string_pretty(&view)?);
+    } else {
+        println!("{}", format_timeline(&view, &paths));
+    }
+    Ok(())
+}
+
+/// One row per step with its duration; resumes show up as notes on the step
+/// they interrupted.
+fn format_timeline(view: &TimelineView, paths: &PathDisplay) -> String {
+    let mut output = format!(
+        "Session: {}\n{:<6} {:<26} {:>10}  NOTES\n",
+        paths.path(&view.session_path),
+        "STEP",
+        "COMPLETED",
+        "DURATION"
+    );
+    for row in &view.rows {
+        let completed = row
+            .completed_at
+            .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| OFFLINE.to_string());
+        let took = row
+            .duration_secs
+            .map(|secs| duration::format_compact(Duration::from_secs(secs.max(0) as u64)))
+            .unwrap_or_else(|| "-".to_string());
+        output.push_str(&format!(
+            "{:<6} {:<26} {:>10}  {}\n",
+            row.step,
+            completed,
+            took,
+            format_resumes(row.resumes.len())
+        ));
+    }
+    if !view.resumes_since_last_step.is_empty() {
+        output.push_str(&format!(
+            "{:<6} {:<26} {:>10}  {}\n",
+            "next",
+            "in progress",
+            "-",
+            format_resumes(view.resumes_since_last_step.len())
+        ));
+    }
+    output.trim_end().to_string()
+}
+
+fn format_resumes(count: usize) -> String {
+    match count {
+        0 => String::new(),
+        1 => "stopped, resumed once".to_string(),
+        count => format!("stopped, resumed {count} times"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::state::timeline::TimelineRow;
+
+    #[test]
+    fn renders_offline_steps_durations_and_resumes() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        let view = TimelineView {
+            session_path: PathBuf::from("/w/session.md"),
+            rows: vec![
+                TimelineRow {
+                    step: 1,
+                    completed_at: None,
+                    duration_secs: None,
+                    resumes: Vec::new(),
+                },
+                TimelineRow {
+                    step: 2,
+                    completed_at: Some(at),
+                    duration_secs: Some(3_720),
+                    resumes: vec![at, at],
+                },
+            ],
+            resumes_since_last_step: vec![at],
+        };
+        let output = format_timeline(&view, &PathDisplay::default());
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines[0], "Session: /w/session.md");
+        assert!(lines[2].starts_with("1      unknown (daemon offline)            -"));
+        assert!(lines[3].contains("2026-01-02 03:04:05"));
+        assert!(lines[3].contains("1h 2m  stopped, resumed 2 times"));
+        assert!(lines[4].ends_with("stopped, resumed once"));
+    }
+}
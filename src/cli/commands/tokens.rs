@@ -0,0 +1,93 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Context, bail};
+
+use crate::cli::commands::config::load_effective_config;
+use crate::http::auth::TokenSet;
+
+/// Report the scopes `token` (or a token read from stdin) would get from
+/// the HTTP API. Only the matching token is described.
+pub async fn handle_check(token: Option<String>) -> anyhow::Result<()> {
+    let token = match token {
+        Some(token) => token,
+        None => read_token()?,
+    };
+    let config = load_effective_config()?;
+    println!("{}", check(&TokenSet::from_config(&config.daemon), &token)?);
+    Ok(())
+}
+
+fn check(tokens: &TokenSet, presented: &str) -> anyhow::Result<String> {
+    if tokens.is_empty() {
+        return Ok("The HTTP API has no tokens configured; it accepts every request".to_string());
+    }
+    let Some(grant) = tokens.resolve(presented) else {
+        bail!("Token not recognised; the HTTP API would answer 401");
+    };
+    let name = match &grant.name {
+        Some(name) => format!("Token {name:?}"),
+        None => "daemon.http_token".to_string(),
+    };
+    let scopes: Vec<&str> = grant.scopes.iter().map(|scope| scope.as_str()).collect();
+    Ok(format!("{name} grants: {}", scopes.join(", ")))
+}
+
+fn read_token() -> anyhow::Result<String> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Token: ");
+        io::stderr().flush()?;
+    }
+    let mut token = String::new();
+    stdin
+        .lock()
+        .read_line(&mut token)
+        .context("Failed to read token from stdin")?;
+    Ok(token.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::{HttpScope, HttpTokenConfig};
+
+    #[test]
+    fn reports_only_the_matching_token() {
+        let tokens = TokenSet::default()
+            .with_token(Some("legacy".to_string()))
+            .with_named_tokens(&[
+                HttpTokenConfig {
+                    name: "dashboard".to_string(),
+                    token: "dash".to_string(),
+                    scopes: vec![HttpScope::Read],
+                },
+                HttpTokenConfig {
+                    name: "ops".to_string(),
+                    token: "ops".to_string(),
+                    scopes: vec![HttpScope::Control, HttpScope::Resume],
+                },
+            ]);
+
+        assert_eq!(
+            check(&tokens, "dash").unwrap(),
+            "Token \"dashboard\" grants: read"
+        );
+        assert_eq!(
+            check(&tokens, "ops").unwrap(),
+            "Token \"ops\" grants: control, resume"
+        );
+        assert_eq!(
+            check(&tokens, "legacy").unwrap(),
+            "daemon.http_token grants: admin"
+        );
+        let unknown = check(&tokens, "guess").unwrap_err().to_string();
+        assert!(unknown.contains("not recognised"));
+        assert!(!unknown.contains("dashboard"));
+
+        assert!(
+            check(&TokenSet::default(), "anything")
+                .unwrap()
+                .contains("no tokens")
+        );
+    }
+}
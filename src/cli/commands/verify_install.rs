@@ -0,0 +1,641 @@
+//! `palingenesis verify-install`: post-upgrade self-test.
+//!
+//! Exercises the machinery the daemon relies on overnight inside a throwaway
+//! directory: the classifier on a synthetic rate-limited session, the backoff
+//! schedule from `[resume]`, a backup/verify/prune cycle, a state store round
+//! trip, and STATUS over a sandbox IPC socket through the real client. Each
+//! check is a small function returning a detail line or a failure reason, so
+//! other diagnostics can run them individually.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::Paths;
+use crate::config::schema::{ClassificationConfig, Config, NotificationsConfig, ResumeConfig};
+use crate::config::validation::validate_config;
+use crate::i18n::Locale;
+use crate::ipc::client::IpcClient;
+use crate::ipc::protocol::DaemonStatus;
+use crate::ipc::socket::{DaemonStateAccess, IpcServer};
+use crate::monitor::classifier::{ClassifierConfig, StopReason, StopReasonClassifier};
+use crate::resume::{Backoff, BackoffConfig, BackupConfig, SessionBackup};
+use crate::state::{STATE_VERSION, StateStore};
+use crate::util::duration;
+
+/// Retry-After the synthetic session reports; the classifier must pick it up.
+const SYNTHETIC_RETRY_AFTER_SECS: u64 = 42;
+/// Backups kept by the sandbox cycle; one more is written so one is pruned.
+const SANDBOX_MAX_BACKUPS: usize = 2;
+const IPC_TIMEOUT: Duration = Duration::from_secs(5);
+/// Delays listed in the backoff detail line.
+const SCHEDULE_PREVIEW: usize = 6;
+
+/// How one check ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl CheckOutcome {
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Passed => "✓",
+            Self::Failed => "✗",
+            Self::Skipped => "-",
+        }
+    }
+}
+
+/// One line of the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+    /// What was verified, why it failed, or why it was skipped.
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Every check in the order it ran.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl VerifyReport {
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+
+    pub fn failures(&self) -> usize {
+        self.count(CheckOutcome::Failed)
+    }
+
+    fn count(&self, outcome: CheckOutcome) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.outcome == outcome)
+            .count()
+    }
+
+    fn render(&self) -> String {
+        let mut output = String::new();
+        for check in &self.checks {
+            let timing = match check.outcome {
+                CheckOutcome::Skipped => String::new(),
+                _ => format!(" [{}ms]", check.duration_ms),
+            };
+            output.push_str(&format!(
+                "  {} {}: {}{timing}\n",
+                check.outcome.marker(),
+                check.name,
+                check.detail
+            ));
+        }
+        output.push_str(&format!(
+            "\n{} passed, {} failed, {} skipped",
+            self.count(CheckOutcome::Passed),
+            self.failures(),
+            self.count(CheckOutcome::Skipped)
+        ));
+        output
+    }
+}
+
+pub async fn handle_verify_install(json: bool, with_notifications: bool) -> anyhow::Result<()> {
+    let report = Verification::new()
+        .with_notifications(with_notifications)
+        .run()
+        .await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "palingenesis verify-install {}\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        println!("{}", report.render());
+    }
+    if !report.passed() {
+        anyhow::bail!("{} check(s) failed", report.failures());
+    }
+    Ok(())
+}
+
+/// The self-test, run against the effective config in a temporary directory.
+#[derive(Debug, Clone, Default)]
+pub struct Verification {
+    with_notifications: bool,
+}
+
+impl Verification {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also send one test event through the configured channels.
+    pub fn with_notifications(mut self, enabled: bool) -> Self {
+        self.with_notifications = enabled;
+        self
+    }
+
+    pub async fn run(&self) -> anyhow::Result<VerifyReport> {
+        let sandbox = Sandbox::create()?;
+        let dir = sandbox.path();
+        let mut checks = Vec::new();
+
+        let started = Instant::now();
+        let config = match check_config() {
+            Ok((config, detail)) => {
+                checks.push(finished("config", Ok(detail), started));
+                config
+            }
+            Err(reason) => {
+                checks.push(finished(
+                    "config",
+                    Err(format!("{reason}; remaining checks use defaults")),
+                    started,
+                ));
+                Config::default()
+            }
+        };
+
+        let started = Instant::now();
+        let result = check_classifier(dir, &config.classifier);
+        checks.push(finished("classifier", result, started));
+
+        let started = Instant::now();
+        let result = check_backoff(&config.resume);
+        checks.push(finished("backoff", result, started));
+
+        let started = Instant::now();
+        let result = check_backup(dir, &config).await;
+        checks.push(finished("backup", result, started));
+
+        let started = Instant::now();
+        let result = check_state_store(dir);
+        checks.push(finished("state", result, started));
+
+        let started = Instant::now();
+        let result = check_ipc(dir).await;
+        checks.push(finished("ipc", result, started));
+
+        checks.push(self.notifications(&config).await);
+        Ok(VerifyReport { checks })
+    }
+
+    async fn notifications(&self, config: &Config) -> CheckResult {
+        let locale = config.daemon.locale;
+        let config = &config.notifications;
+        let skip = if !self.with_notifications {
+            Some("pass --with-notifications to send a test event")
+        } else if !config.enabled || configured_channels(config) == 0 {
+            Some("no notification channels configured")
+        } else {
+            None
+        };
+        if let Some(reason) = skip {
+            return CheckResult {
+                name: "notifications",
+                outcome: CheckOutcome::Skipped,
+                detail: reason.to_string(),
+                duration_ms: 0,
+            };
+        }
+        let started = Instant::now();
+        let result = check_notifications(config, locale).await;
+        finished("notifications", result, started)
+    }
+}
+
+fn finished(name: &'static str, result: Result<String, String>, started: Instant) -> CheckResult {
+    let (outcome, detail) = match result {
+        Ok(detail) => (CheckOutcome::Passed, detail),
+        Err(reason) => (CheckOutcome::Failed, reason),
+    };
+    CheckResult {
+        name,
+        outcome,
+        detail,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Load the effective config (file plus environment) and validate it.
+pub fn check_config() -> Result<(Config, String), String> {
+    let config = load_effective_config().map_err(|err| format!("{err:#}"))?;
+    if let Some(error) = validate_config(&config).errors.first() {
+        return Err(format!("{}: {}", error.field, error.message));
+    }
+    let path = Paths::config_file();
+    let source = if path.exists() {
+        path.display().to_string()
+    } else {
+        "defaults (no config file)".to_string()
+    };
+    Ok((config, source))
+}
+
+/// Classify a synthetic session whose tail is a 429 with a Retry-After header.
+pub fn check_classifier(dir: &Path, config: &ClassificationConfig) -> Result<String, String> {
+    let classifier = StopReasonClassifier::with_config(ClassifierConfig::from_config(config))
+        .map_err(|err| format!("classifier did not build: {err}"))?;
+    let session = dir.join("rate-limited-session.md");
+    let content = format!(
+        "---\nstepsCompleted: [1, 2]\nstatus: in-progress\n---\n\n## Step 3\n\n\
+         Working on the next step...\n\n\
+         Error: 429 Too Many Requests\nRetry-After: {SYNTHETIC_RETRY_AFTER_SECS}\n"
+    );
+    std::fs::write(&session, content).map_err(|err| format!("writing session: {err}"))?;
+
+    let result = classifier.classify(&session, None);
+    match result.reason {
+        StopReason::RateLimit(info)
+            if info.retry_after == Duration::from_secs(SYNTHETIC_RETRY_AFTER_SECS) =>
+        {
+            Ok(format!(
+                "rate_limit, retry after {}s (confidence {:.2})",
+                info.retry_after.as_secs(),
+                result.confidence
+            ))
+        }
+        StopReason::RateLimit(info) => Err(format!(
+            "rate_limit, but retry after {}s instead of {SYNTHETIC_RETRY_AFTER_SECS}s",
+            info.retry_after.as_secs()
+        )),
+        other => Err(format!("expected rate_limit, classified as {other:?}")),
+    }
+}
+
+/// Build the backoff from `[resume]` and check its schedule: starts at the
+/// base delay, never shrinks, stays under the cap, and stops at `max_retries`.
+pub fn check_backoff(config: &ResumeConfig) -> Result<String, String> {
+    let backoff_config = BackoffConfig::from_resume_config(config);
+    backoff_config.validate().map_err(|err| err.to_string())?;
+    let schedule_config = BackoffConfig {
+        jitter_enabled: false,
+        ..backoff_config.clone()
+    };
+    let backoff = Backoff::with_config(schedule_config).map_err(|err| err.to_string())?;
+
+    let schedule: Vec<Duration> = (1..=backoff_config.max_retries)
+        .map(|attempt| backoff.delay_for_attempt(attempt))
+        .collect();
+    if schedule.first() != Some(&backoff_config.base_delay) {
+        return Err(format!(
+            "first delay is {:?}, expected the base delay {:?}",
+            schedule.first(),
+            backoff_config.base_delay
+        ));
+    }
+    if schedule.windows(2).any(|pair| pair[1] < pair[0]) {
+        return Err("delays shrink between attempts".to_string());
+    }
+    if schedule
+        .iter()
+        .any(|delay| *delay > backoff_config.max_delay)
+    {
+        return Err(format!(
+            "a delay exceeds max_delay_secs ({}s)",
+            backoff_config.max_delay.as_secs()
+        ));
+    }
+    if backoff
+        .check_retry_limit(backoff_config.max_retries + 1)
+        .is_ok()
+    {
+        return Err("retry limit is not enforced".to_string());
+    }
+
+    let mut preview: Vec<String> = schedule
+        .iter()
+        .take(SCHEDULE_PREVIEW)
+        .map(|delay| duration::format_compact(*delay))
+        .collect();
+    if schedule.len() > SCHEDULE_PREVIEW {
+        preview.push("…".to_string());
+    }
+    Ok(format!(
+        "{} attempts: {}{}",
+        schedule.len(),
+        preview.join(", "),
+        if backoff_config.jitter_enabled {
+            " (± jitter)"
+        } else {
+            ""
+        }
+    ))
+}
+
+/// Back up a changing session three times with the configured filename
+/// template, keeping two: every copy is verified and the oldest is pruned.
+pub async fn check_backup(dir: &Path, config: &Config) -> Result<String, String> {
+    let session_dir = dir.join("sessions");
+    std::fs::create_dir_all(&session_dir).map_err(|err| err.to_string())?;
+    let session = session_dir.join("session.md");
+    let backup = SessionBackup::with_config(BackupConfig {
+        max_backups: SANDBOX_MAX_BACKUPS,
+        // Sub-second timestamps so the three backups get distinct names.
+        timestamp_format: "%Y%m%d-%H%M%S-%6f".to_string(),
+        backup_dir: Some(dir.join("backups")),
+        session_root: Some(session_dir.clone()),
+        ..BackupConfig::from_resume_config(&config.resume, &config.monitoring)
+    });
+
+    let mut newest = None;
+    for revision in 1..=SANDBOX_MAX_BACKUPS + 1 {
+        let content = format!("---\nstepsCompleted: [{revision}]\n---\nrevision {revision}\n");
+        tokio::fs::write(&session, content)
+            .await
+            .map_err(|err| format!("writing session: {err}"))?;
+        let path = backup
+            .create_backup(&session)
+            .await
+            .map_err(|err| format!("backup {revision}: {err}"))?;
+        newest = Some(path);
+    }
+    let newest = newest.expect("at least one backup is written");
+
+    let backups = backup
+        .list_backups(&session)
+        .await
+        .map_err(|err| format!("listing backups: {err}"))?;
+    if backups.len() != SANDBOX_MAX_BACKUPS {
+        return Err(format!(
+            "{} backups kept, expected {SANDBOX_MAX_BACKUPS} after pruning",
+            backups.len()
+        ));
+    }
+    backup
+        .verify_backup(&session, &newest)
+        .await
+        .map_err(|err| err.to_string())?;
+    let restored = tokio::fs::read(&newest)
+        .await
+        .map_err(|err| err.to_string())?;
+    let current = tokio::fs::read(&session)
+        .await
+        .map_err(|err| err.to_string())?;
+    if restored != current {
+        return Err("newest backup does not match the session".to_string());
+    }
+    Ok(format!(
+        "{} written, {SANDBOX_MAX_BACKUPS} kept, newest verified",
+        SANDBOX_MAX_BACKUPS + 1
+    ))
+}
+
+/// Load a minimal version-1 state file, check it migrates to the current
+/// schema without losing data, then save and reload it.
+pub fn check_state_store(dir: &Path) -> Result<String, String> {
+    let path = dir.join("state").join("state.json");
+    std::fs::create_dir_all(path.parent().expect("state path has a parent"))
+        .map_err(|err| err.to_string())?;
+    let legacy = r#"{
+        "version": 1,
+        "daemon_state": "monitoring",
+        "current_session": null,
+        "stats": { "saves_count": 3, "total_resumes": 4 }
+    }"#;
+    std::fs::write(&path, legacy).map_err(|err| format!("writing state: {err}"))?;
+
+    let store = StateStore::with_path(path);
+    let mut state = store.load();
+    if state.stats.saves_count != 3 || state.stats.total_resumes != 4 {
+        return Err("version 1 state file was not read (stats lost)".to_string());
+    }
+    if state.version > STATE_VERSION {
+        return Err(format!(
+            "state version {} is newer than this build ({STATE_VERSION})",
+            state.version
+        ));
+    }
+
+    state.version = STATE_VERSION;
+    state.stats.total_resumes += 1;
+    store
+        .save(&state)
+        .map_err(|err| format!("saving state: {err}"))?;
+    if store.load() != state {
+        return Err("state changed across save and load".to_string());
+    }
+    Ok(format!(
+        "version 1 loaded, saved as version {STATE_VERSION}"
+    ))
+}
+
+/// Bind a socket in `dir` and ask it for STATUS through [`IpcClient`].
+pub async fn check_ipc(dir: &Path) -> Result<String, String> {
+    let path = dir.join("verify.sock");
+    let mut server = IpcServer::with_path(path.clone());
+    server
+        .bind()
+        .await
+        .map_err(|err| format!("binding {}: {err}", path.display()))?;
+    let server = Arc::new(server);
+    let cancel = CancellationToken::new();
+    let handle = {
+        let server = Arc::clone(&server);
+        let cancel = cancel.clone();
+        tokio::spawn(async move { server.run(Arc::new(SandboxState), cancel).await })
+    };
+
+    let status = tokio::time::timeout(IPC_TIMEOUT, IpcClient::status_at(path)).await;
+    cancel.cancel();
+    let _ = handle.await;
+    let _ = server.cleanup();
+
+    match status {
+        Ok(Ok(status)) if status.state == "monitoring" => {
+            Ok(format!("STATUS answered ({})", status.state))
+        }
+        Ok(Ok(status)) => Err(format!("unexpected state {:?}", status.state)),
+        Ok(Err(err)) => Err(format!("STATUS failed: {err}")),
+        Err(_) => Err(format!("no answer within {}s", IPC_TIMEOUT.as_secs())),
+    }
+}
+
+/// Send one test event through every configured channel.
+#[cfg(feature = "notifications")]
+pub async fn check_notifications(
+    config: &NotificationsConfig,
+    locale: Locale,
+) -> Result<String, String> {
+    use crate::notify::Dispatcher;
+    use crate::notify::events::NotificationEvent;
+
+    let summary = Dispatcher::from_config(config, locale)
+        .dispatch(NotificationEvent::TestNotification {
+            timestamp: chrono::Utc::now(),
+            message: "palingenesis verify-install test event".to_string(),
+        })
+        .await;
+    if summary.failures > 0 {
+        return Err(format!(
+            "delivery failed for {}",
+            summary.failed_channels.join(", ")
+        ));
+    }
+    Ok(format!("sent to {} channel(s)", summary.successes))
+}
+
+#[cfg(not(feature = "notifications"))]
+pub async fn check_notifications(
+    _config: &NotificationsConfig,
+    _locale: Locale,
+) -> Result<String, String> {
+    Err("built without the notifications feature".to_string())
+}
+
+fn configured_channels(config: &NotificationsConfig) -> usize {
+    config.webhook.len()
+        + config.ntfy.len()
+        + usize::from(config.discord.is_some())
+        + usize::from(config.slack.is_some())
+}
+
+/// Scratch directory under the system temp dir, removed on drop.
+struct Sandbox(PathBuf);
+
+impl Sandbox {
+    fn create() -> std::io::Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "palingenesis-verify-{}-{nanos}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Fixed daemon state served on the sandbox socket.
+struct SandboxState;
+
+impl DaemonStateAccess for SandboxState {
+    fn get_status(&self) -> DaemonStatus {
+        DaemonStatus {
+            state: "monitoring".to_string(),
+            uptime_secs: 0,
+            current_session: None,
+            saves_count: 0,
+            total_resumes: 0,
+            time_saved_seconds: 0.0,
+            time_saved_human: None,
+            opencode_endpoint: None,
+            exclusion_patterns: Vec::new(),
+            watch_filter: None,
+            jobs: Vec::new(),
+            deferred_until: None,
+            deferred_reason: None,
+            quota_resume_at: None,
+            resume_counters: Vec::new(),
+            needs_attention: false,
+            instance: None,
+            clock_skew_secs: None,
+            debounce_window_ms: None,
+            state_newer_version: None,
+            opencode_version: None,
+            restart_correlation: None,
+            mode: Default::default(),
+            incident_opened_at: None,
+            state_unsaved_since: None,
+            watcher_alive: None,
+            http_listeners: None,
+        }
+    }
+
+    fn pause(&self) -> Result<(), String> {
+        Err("sandbox daemon".to_string())
+    }
+
+    fn resume(&self) -> Result<(), String> {
+        Err("sandbox daemon".to_string())
+    }
+
+    fn new_session(&self) -> Result<(), String> {
+        Err("sandbox daemon".to_string())
+    }
+
+    fn reload_config(&self) -> Result<(), String> {
+        Err("sandbox daemon".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_schedule_follows_resume_config() {
+        let config = ResumeConfig {
+            base_delay_secs: 10,
+            max_delay_secs: 60,
+            max_retries: 8,
+            jitter: false,
+            ..ResumeConfig::default()
+        };
+        assert_eq!(
+            check_backoff(&config).unwrap(),
+            "8 attempts: 10s, 20s, 40s, 1m, 1m, 1m, …"
+        );
+
+        let broken = ResumeConfig {
+            max_delay_secs: 5,
+            ..config
+        };
+        assert!(check_backoff(&broken).is_err());
+    }
+
+    #[test]
+    fn report_lists_checks_and_totals() {
+        let report = VerifyReport {
+            checks: vec![
+                CheckResult {
+                    name: "classifier",
+                    outcome: CheckOutcome::Passed,
+                    detail: "rate_limit".to_string(),
+                    duration_ms: 3,
+                },
+                CheckResult {
+                    name: "ipc",
+                    outcome: CheckOutcome::Failed,
+                    detail: "no answer within 5s".to_string(),
+                    duration_ms: 5000,
+                },
+                CheckResult {
+                    name: "notifications",
+                    outcome: CheckOutcome::Skipped,
+                    detail: "pass --with-notifications to send a test event".to_string(),
+                    duration_ms: 0,
+                },
+            ],
+        };
+        assert!(!report.passed());
+        assert_eq!(
+            report.render(),
+            "  ✓ classifier: rate_limit [3ms]\n  ✗ ipc: no answer within 5s [5000ms]\n  - notifications: pass --with-notifications to send a test event\n\n1 passed, 1 failed, 1 skipped"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chrono::Utc;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::config::schema::WebhookConfig;
+use crate::notify::events::NotificationEvent;
+use crate::notify::signing;
+use crate::notify::webhook::{signed_headers, webhook_body};
+
+const EXAMPLE_URL: &str = "https://receiver.example.com/palingenesis";
+const EXAMPLE_SECRET: &str = "whsec_example";
+
+/// Print a signed sample request, as the webhook channel would send it, and
+/// how to check its signature.
+pub fn handle_verify_example(
+    secret: Option<String>,
+    channel: Option<String>,
+) -> anyhow::Result<()> {
+    let webhook = match &channel {
+        Some(name) => Some(configured_webhook(name)?),
+        None => None,
+    };
+    let secret = secret
+        .or_else(|| {
+            webhook
+                .as_ref()
+                .and_then(|webhook| webhook.signing_secret.clone())
+        })
+        .unwrap_or_else(|| EXAMPLE_SECRET.to_string());
+    let url = webhook.map_or_else(|| EXAMPLE_URL.to_string(), |webhook| webhook.url);
+    print!(
+        "{}",
+        example_request(&url, &secret, Utc::now().timestamp())?
+    );
+    Ok(())
+}
+
+fn configured_webhook(name: &str) -> anyhow::Result<WebhookConfig> {
+    load_effective_config()?
+        .notifications
+        .webhook
+        .into_iter()
+        .find(|webhook| webhook.channel_name() == name)
+        .with_context(|| format!("No [[notifications.webhook]] named {name:?}"))
+}
+
+fn example_request(url: &str, secret: &str, timestamp: i64) -> anyhow::Result<String> {
+    let event = NotificationEvent::SessionStopped {
+        timestamp: Utc::now(),
+        session_path: PathBuf::from("/home/user/project/session.md"),
+        stop_reason: "rate_limit".to_string(),
+        details: Some("Retry after 60 seconds".to_string()),
+        excluded_by: None,
+    };
+    let body = serde_json::to_string(
+        &webhook_body(&event, &["example".to_string()], None).map_err(anyhow::Error::msg)?,
+    )?;
+    let headers = signed_headers(
+        Some(secret),
+        event.event_type(),
+        signing::next_delivery_id(),
+        timestamp,
+        body.as_bytes(),
+    );
+
+    let quoted_body = shell_quote(&body);
+    let mut out = format!("POST {url}\nContent-Type: application/json\n");
+    for (name, value) in &headers {
+        writeln!(out, "{name}: {value}")?;
+    }
+    writeln!(out, "\n{body}\n")?;
+    out.push_str("# Replay it against a receiver:\n");
+    write!(
+        out,
+        "curl -X POST {} -H 'Content-Type: application/json'",
+        shell_quote(url)
+    )?;
+    for (name, value) in &headers {
+        write!(out, " -H '{name}: {value}'")?;
+    }
+    writeln!(out, " --data-binary {quoted_body}\n")?;
+    out.push_str("# The signature is HMAC-SHA256 over \"<timestamp>.<raw body>\":\n");
+    writeln!(
+        out,
+        "printf '%s.%s' '{timestamp}' {quoted_body} | openssl dgst -sha256 -hmac {}",
+        shell_quote(secret)
+    )?;
+    writeln!(
+        out,
+        "# Expect {} to be v1=<that hex>, and reject timestamps older than {} minutes.",
+        signing::SIGNATURE_HEADER,
+        signing::DEFAULT_TOLERANCE.as_secs() / 60
+    )?;
+    Ok(out)
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_request_verifies() {
+        let out = example_request(EXAMPLE_URL, "whsec_test", 1_700_000_000).unwrap();
+        let header = |name: &str| {
+            out.lines()
+                .find_map(|line| line.strip_prefix(&format!("{name}: ")))
+                .unwrap()
+                .to_string()
+        };
+        let body = out.lines().find(|line| line.starts_with('{')).unwrap();
+
+        assert_eq!(header(signing::EVENT_HEADER), "session_stopped");
+        signing::verify(
+            "whsec_test",
+            &header(signing::TIMESTAMP_HEADER),
+            &header(signing::SIGNATURE_HEADER),
+            body.as_bytes(),
+            1_700_000_000,
+            signing::DEFAULT_TOLERANCE,
+        )
+        .unwrap();
+        assert!(out.contains("openssl dgst -sha256 -hmac 'whsec_test'"));
+    }
+}
@@ -0,0 +1,557 @@
+//! Interactive `config init --wizard`.
+//!
+//! The wizard asks which assistants to monitor, whether to expose the HTTP
+//! API, where to send notifications and how eagerly to resume, then renders
+//! a config holding only those sections. Questions go through a
+//! [`Prompter`] so tests can drive the flow from a script.
+
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use rand::Rng;
+use toml::{Table, Value};
+
+use crate::config::schema::{DaemonConfig, ResumeConfig};
+use crate::monitor::detection::DetectedAssistant;
+use crate::resume::assistant::{CLAUDE_CODE, OPENCODE, adapter_for};
+
+/// Notification channels the wizard can configure.
+const CHANNELS: &[&str] = &["webhook", "ntfy", "discord", "slack"];
+const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+
+/// Where the wizard's questions are asked and answered.
+pub trait Prompter {
+    /// Show informational text.
+    fn say(&mut self, text: &str);
+
+    /// Ask `question` and return the trimmed answer; empty when skipped.
+    fn ask(&mut self, question: &str) -> io::Result<String>;
+}
+
+/// Asks on stdout and reads answers from stdin.
+#[derive(Debug, Default)]
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn say(&mut self, text: &str) {
+        println!("{text}");
+    }
+
+    fn ask(&mut self, question: &str) -> io::Result<String> {
+        print!("{question} ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "input closed before the wizard finished",
+            ));
+        }
+        Ok(answer.trim().to_string())
+    }
+}
+
+/// HTTP API settings chosen in the wizard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpAnswers {
+    pub port: u16,
+    pub token: String,
+}
+
+/// ntfy settings chosen in the wizard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NtfyAnswers {
+    pub topic: String,
+    pub server: String,
+}
+
+/// Resume preferences chosen in the wizard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeAnswers {
+    pub enabled: bool,
+    pub max_retries: u32,
+    pub base_delay_secs: u64,
+}
+
+/// Everything the wizard collected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WizardAnswers {
+    pub assistants: Vec<String>,
+    pub http: Option<HttpAnswers>,
+    pub webhook: Option<String>,
+    pub ntfy: Option<NtfyAnswers>,
+    pub discord: Option<String>,
+    pub slack: Option<String>,
+    pub resume: ResumeAnswers,
+    /// Send a test notification once the config is written.
+    pub send_test: bool,
+}
+
+impl WizardAnswers {
+    pub fn has_notifications(&self) -> bool {
+        self.webhook.is_some()
+            || self.ntfy.is_some()
+            || self.discord.is_some()
+            || self.slack.is_some()
+    }
+
+    /// Config file holding only the chosen sections.
+    pub fn to_toml(&self) -> String {
+        let mut root = Table::new();
+
+        let mut monitoring = Table::new();
+        monitoring.insert("assistants".into(), string_array(&self.assistants));
+        root.insert("monitoring".into(), Value::Table(monitoring));
+
+        if let Some(http) = &self.http {
+            let mut daemon = Table::new();
+            daemon.insert("http_enabled".into(), Value::Boolean(true));
+            daemon.insert("http_port".into(), Value::Integer(http.port.into()));
+            daemon.insert("http_token".into(), Value::String(http.token.clone()));
+            root.insert("daemon".into(), Value::Table(daemon));
+        }
+
+        if self.has_notifications() {
+            let mut notifications = Table::new();
+            notifications.insert("enabled".into(), Value::Boolean(true));
+            if let Some(url) = &self.webhook {
+                notifications.insert(
+                    "webhook".into(),
+                    Value::Array(vec![Value::Table(single("url", url))]),
+                );
+            }
+            if let Some(ntfy) = &self.ntfy {
+                let mut entry = single("topic", &ntfy.topic);
+                entry.insert("server".into(), Value::String(ntfy.server.clone()));
+                notifications.insert("ntfy".into(), Value::Array(vec![Value::Table(entry)]));
+            }
+            if let Some(url) = &self.discord {
+                notifications.insert("discord".into(), Value::Table(single("webhook_url", url)));
+            }
+            if let Some(url) = &self.slack {
+                notifications.insert("slack".into(), Value::Table(single("webhook_url", url)));
+            }
+            root.insert("notifications".into(), Value::Table(notifications));
+        }
+
+        let mut resume = Table::new();
+        resume.insert("enabled".into(), Value::Boolean(self.resume.enabled));
+        if self.resume.enabled {
+            resume.insert(
+                "max_retries".into(),
+                Value::Integer(self.resume.max_retries.into()),
+            );
+            resume.insert(
+                "base_delay_secs".into(),
+                Value::Integer(i64::try_from(self.resume.base_delay_secs).unwrap_or(i64::MAX)),
+            );
+        }
+        root.insert("resume".into(), Value::Table(resume));
+
+        format!(
+            "# palingenesis configuration file (written by `config init --wizard`)\n\
+             # https://github.com/Jack-R-Hong/palingenesis\n\n{}",
+            toml::to_string(&root).expect("wizard config serializes")
+        )
+    }
+}
+
+/// Ask every question and collect the answers.
+///
+/// `detected` lists assistants found on this machine; they become the
+/// default selection.
+pub fn run(
+    prompter: &mut dyn Prompter,
+    detected: &[DetectedAssistant],
+) -> io::Result<WizardAnswers> {
+    prompter.say("palingenesis setup. Press Enter to accept the value in brackets.\n");
+
+    let assistants = ask_assistants(prompter, detected)?;
+    let http = ask_http(prompter)?;
+
+    let channels = ask_list(
+        prompter,
+        "Notification channels (webhook, ntfy, discord, slack; blank for none):",
+        "",
+        CHANNELS,
+    )?;
+    let mut webhook = None;
+    let mut ntfy = None;
+    let mut discord = None;
+    let mut slack = None;
+    for channel in &channels {
+        match channel.as_str() {
+            "webhook" => webhook = Some(ask_required(prompter, "Webhook URL:")?),
+            "ntfy" => {
+                let topic = ask_required(prompter, "ntfy topic:")?;
+                let server = ask_default(prompter, "ntfy server", DEFAULT_NTFY_SERVER)?;
+                ntfy = Some(NtfyAnswers { topic, server });
+            }
+            "discord" => discord = Some(ask_required(prompter, "Discord webhook URL:")?),
+            "slack" => slack = Some(ask_required(prompter, "Slack webhook URL:")?),
+            _ => unreachable!("ask_list only returns known channels"),
+        }
+    }
+    let send_test = !channels.is_empty()
+        && confirm(
+            prompter,
+            "Send a test notification after writing the config?",
+            true,
+        )?;
+
+    let defaults = ResumeConfig::default();
+    let enabled = confirm(prompter, "Resume stopped sessions automatically?", true)?;
+    let resume = if enabled {
+        ResumeAnswers {
+            enabled,
+            max_retries: ask_number(prompter, "Maximum resume retries", defaults.max_retries)?,
+            base_delay_secs: ask_number(
+                prompter,
+                "Seconds to wait before the first retry",
+                defaults.base_delay_secs,
+            )?,
+        }
+    } else {
+        ResumeAnswers {
+            enabled,
+            max_retries: defaults.max_retries,
+            base_delay_secs: defaults.base_delay_secs,
+        }
+    };
+
+    Ok(WizardAnswers {
+        assistants,
+        http,
+        webhook,
+        ntfy,
+        discord,
+        slack,
+        resume,
+        send_test,
+    })
+}
+
+fn ask_assistants(
+    prompter: &mut dyn Prompter,
+    detected: &[DetectedAssistant],
+) -> io::Result<Vec<String>> {
+    if detected.is_empty() {
+        prompter.say("No assistants detected on this machine.");
+    } else {
+        let found: Vec<String> = detected
+            .iter()
+            .map(|assistant| format!("{} ({})", assistant.name, assistant.detected_by.as_str()))
+            .collect();
+        prompter.say(&format!("Detected assistants: {}", found.join(", ")));
+    }
+    let default = if detected.is_empty() {
+        OPENCODE.to_string()
+    } else {
+        detected
+            .iter()
+            .map(|assistant| assistant.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    loop {
+        let assistants = ask_list(
+            prompter,
+            &format!("Assistants to monitor ({OPENCODE}, {CLAUDE_CODE})"),
+            &default,
+            &[OPENCODE, CLAUDE_CODE],
+        )?;
+        if !assistants.is_empty() {
+            return Ok(assistants);
+        }
+        prompter.say("Choose at least one assistant.");
+    }
+}
+
+fn ask_http(prompter: &mut dyn Prompter) -> io::Result<Option<HttpAnswers>> {
+    if !confirm(
+        prompter,
+        "Enable the HTTP API (status, metrics, remote pause/resume)?",
+        false,
+    )? {
+        return Ok(None);
+    }
+    let port = ask_number(prompter, "HTTP port", DaemonConfig::default().http_port)?;
+    let token = generate_token();
+    prompter.say("Generated an API token; clients must send `Authorization: Bearer <token>`.");
+    Ok(Some(HttpAnswers { port, token }))
+}
+
+/// Random 256-bit bearer token, hex encoded.
+pub fn generate_token() -> String {
+    hex::encode(rand::thread_rng().r#gen::<[u8; 32]>())
+}
+
+fn ask_default(prompter: &mut dyn Prompter, question: &str, default: &str) -> io::Result<String> {
+    let answer = prompter.ask(&format!("{question} [{default}]:"))?;
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer
+    })
+}
+
+fn ask_required(prompter: &mut dyn Prompter, question: &str) -> io::Result<String> {
+    loop {
+        let answer = prompter.ask(question)?;
+        if !answer.is_empty() {
+            return Ok(answer);
+        }
+        prompter.say("A value is required.");
+    }
+}
+
+fn ask_number<T: FromStr + ToString>(
+    prompter: &mut dyn Prompter,
+    question: &str,
+    default: T,
+) -> io::Result<T> {
+    let default = default.to_string();
+    loop {
+        match ask_default(prompter, question, &default)?.parse() {
+            Ok(value) => return Ok(value),
+            Err(_) => prompter.say("Enter a whole number."),
+        }
+    }
+}
+
+fn confirm(prompter: &mut dyn Prompter, question: &str, default: bool) -> io::Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        let answer = prompter.ask(&format!("{question} {hint}"))?;
+        match answer.to_ascii_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => prompter.say("Answer y or n."),
+        }
+    }
+}
+
+/// Comma-separated choice from `allowed`, asked again until every entry is known.
+fn ask_list(
+    prompter: &mut dyn Prompter,
+    question: &str,
+    default: &str,
+    allowed: &[&str],
+) -> io::Result<Vec<String>> {
+    loop {
+        let answer = if default.is_empty() {
+            prompter.ask(question)?
+        } else {
+            ask_default(prompter, question, default)?
+        };
+        let mut chosen: Vec<String> = Vec::new();
+        let mut unknown = Vec::new();
+        for item in answer
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let item = canonical_choice(item);
+            if !allowed.contains(&item.as_str()) {
+                unknown.push(item);
+            } else if !chosen.contains(&item) {
+                chosen.push(item);
+            }
+        }
+        if unknown.is_empty() {
+            return Ok(chosen);
+        }
+        prompter.say(&format!(
+            "Unknown choice: {}. Pick from {}.",
+            unknown.join(", "),
+            allowed.join(", ")
+        ));
+    }
+}
+
+/// Lowercased choice, with assistant aliases (e.g. `claude`) resolved.
+fn canonical_choice(item: &str) -> String {
+    let item = item.to_ascii_lowercase();
+    match adapter_for(&item) {
+        Some(adapter) => adapter.name().to_string(),
+        None => item,
+    }
+}
+
+fn string_array(items: &[String]) -> Value {
+    Value::Array(items.iter().cloned().map(Value::String).collect())
+}
+
+fn single(key: &str, value: &str) -> Table {
+    let mut table = Table::new();
+    table.insert(key.into(), Value::String(value.to_string()));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+
+    use crate::config::schema::Config;
+    use crate::config::validation::validate_config;
+    use crate::monitor::detection::DetectionMethod;
+
+    /// Answers questions from a script and records the conversation.
+    struct ScriptedPrompter {
+        answers: VecDeque<&'static str>,
+        transcript: Vec<String>,
+    }
+
+    impl ScriptedPrompter {
+        fn new(answers: &[&'static str]) -> Self {
+            Self {
+                answers: answers.iter().copied().collect(),
+                transcript: Vec::new(),
+            }
+        }
+    }
+
+    impl Prompter for ScriptedPrompter {
+        fn say(&mut self, text: &str) {
+            self.transcript.push(text.to_string());
+        }
+
+        fn ask(&mut self, question: &str) -> io::Result<String> {
+            self.transcript.push(question.to_string());
+            self.answers
+                .pop_front()
+                .map(str::to_string)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "script exhausted"))
+        }
+    }
+
+    fn detected(name: &str) -> DetectedAssistant {
+        DetectedAssistant {
+            name: name.to_string(),
+            session_dir: PathBuf::from("/tmp/sessions"),
+            detected_by: DetectionMethod::Process,
+            active: true,
+        }
+    }
+
+    fn parse(answers: &WizardAnswers) -> Config {
+        let config: Config = toml::from_str(&answers.to_toml()).expect("wizard config parses");
+        assert!(
+            validate_config(&config).is_valid(),
+            "wizard config validates"
+        );
+        config
+    }
+
+    #[test]
+    fn minimal_path_accepts_detected_defaults() {
+        let mut prompter = ScriptedPrompter::new(&[
+            "", // assistants: detected default
+            "", // HTTP API: no
+            "", // channels: none
+            "", // resume automatically: yes
+            "", // max retries
+            "", // base delay
+        ]);
+
+        let answers = run(&mut prompter, &[detected("claude-code")]).unwrap();
+
+        assert!(
+            prompter
+                .transcript
+                .contains(&"Detected assistants: claude-code (process)".to_string())
+        );
+        assert_eq!(answers.assistants, vec!["claude-code".to_string()]);
+        assert_eq!(answers.http, None);
+        assert!(!answers.has_notifications());
+        assert!(!answers.send_test);
+
+        let rendered = answers.to_toml();
+        assert!(!rendered.contains("[daemon]"));
+        assert!(!rendered.contains("[notifications]"));
+        assert!(rendered.lines().skip(3).all(|line| !line.starts_with('#')));
+
+        let config = parse(&answers);
+        assert_eq!(
+            config.monitoring.assistants,
+            vec!["claude-code".to_string()]
+        );
+        assert_eq!(
+            config.resume.max_retries,
+            ResumeConfig::default().max_retries
+        );
+    }
+
+    #[test]
+    fn full_path_configures_http_and_channels() {
+        let mut prompter = ScriptedPrompter::new(&[
+            "opencode, claude, bogus", // rejected: unknown assistant
+            "opencode, claude",
+            "y",
+            "8080",
+            "ntfy, slack",
+            "pal-alerts",
+            "",
+            "https://hooks.slack.com/services/T000/B000/XXXX",
+            "yes",
+            "y",
+            "5",
+            "many", // rejected: not a number
+            "60",
+        ]);
+
+        let answers = run(&mut prompter, &[]).unwrap();
+
+        assert!(
+            prompter
+                .transcript
+                .iter()
+                .any(|line| line.starts_with("Unknown choice: bogus"))
+        );
+        assert_eq!(
+            answers.assistants,
+            vec!["opencode".to_string(), "claude-code".to_string()]
+        );
+        let http = answers.http.clone().expect("http enabled");
+        assert_eq!(http.port, 8080);
+        assert_eq!(http.token.len(), 64);
+        assert_eq!(
+            answers.ntfy,
+            Some(NtfyAnswers {
+                topic: "pal-alerts".to_string(),
+                server: DEFAULT_NTFY_SERVER.to_string(),
+            })
+        );
+        assert!(answers.send_test);
+        assert_eq!(
+            answers.resume,
+            ResumeAnswers {
+                enabled: true,
+                max_retries: 5,
+                base_delay_secs: 60,
+            }
+        );
+
+        let config = parse(&answers);
+        assert!(config.daemon.http_enabled);
+        assert_eq!(config.daemon.http_port, 8080);
+        assert_eq!(
+            config.daemon.http_token.as_deref(),
+            Some(http.token.as_str())
+        );
+        assert!(config.notifications.enabled);
+        assert_eq!(config.notifications.ntfy[0].topic, "pal-alerts");
+        assert!(config.notifications.slack.is_some());
+        assert_eq!(config.resume.base_delay_secs, 60);
+    }
+
+    #[test]
+    fn closed_input_aborts() {
+        let mut prompter = ScriptedPrompter::new(&["opencode"]);
+        let err = run(&mut prompter, &[]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
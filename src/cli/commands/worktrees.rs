@@ -0,0 +1,227 @@
+use std::path::Path;
+
+use anyhow::bail;
+
+use crate::cli::commands::config::load_effective_config;
+use crate::daemon::pid::PidFile;
+use crate::monitor::frontmatter::parse_session;
+use crate::resume::{WorktreeError, WorktreeManager};
+use crate::state::{StateFile, StateStore, WorktreeRecord};
+
+pub async fn handle_list() -> anyhow::Result<()> {
+    let state = StateStore::new().load();
+    println!("{}", format_worktrees(&state.worktrees));
+    Ok(())
+}
+
+pub async fn handle_prune(force: bool) -> anyhow::Result<()> {
+    if let Some(pid) = PidFile::new().running_pid() {
+        bail!("Daemon is running (PID {pid}); stop it first");
+    }
+    let config = load_effective_config()?;
+    let manager = WorktreeManager::from_config(&config.resume.new_session);
+    let store = StateStore::new();
+    let mut state = store.load();
+
+    let report = prune(&mut state, &manager, force).await;
+    store.save(&state)?;
+    for line in &report.lines {
+        println!("{line}");
+    }
+    if report.removed == 0 && report.lines.is_empty() {
+        println!("No worktrees to prune");
+    }
+    if report.failed > 0 {
+        bail!("{} worktree(s) could not be removed", report.failed);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct PruneReport {
+    removed: usize,
+    failed: usize,
+    lines: Vec<String>,
+}
+
+/// Remove worktrees whose sessions completed (or are gone) and drop their records.
+async fn prune(state: &mut StateFile, manager: &WorktreeManager, force: bool) -> PruneReport {
+    let mut report = PruneReport::default();
+    for record in state.worktrees.clone() {
+        if !session_finished(&record.session) {
+            continue;
+        }
+        match manager.remove(&record, force).await {
+            Ok(()) => {
+                state.remove_worktree(&record.worktree);
+                report.removed += 1;
+                report.lines.push(format!(
+                    "Removed {} (branch {} kept)",
+                    record.worktree.display(),
+                    record.branch
+                ));
+            }
+            Err(err @ WorktreeError::Dirty { .. }) => {
+                report.failed += 1;
+                report.lines.push(format!("Skipped: {err}"));
+            }
+            Err(err) => {
+                report.failed += 1;
+                report.lines.push(format!(
+                    "Failed to remove {}: {err}",
+                    record.worktree.display()
+                ));
+            }
+        }
+    }
+    report
+}
+
+/// Whether the session is marked complete or its file no longer exists.
+fn session_finished(session: &Path) -> bool {
+    if !session.exists() {
+        return true;
+    }
+    parse_session(session).is_ok_and(|session| session.is_complete())
+}
+
+fn format_worktrees(worktrees: &[WorktreeRecord]) -> String {
+    if worktrees.is_empty() {
+        return "No worktrees recorded".to_string();
+    }
+    let mut output = String::new();
+    for record in worktrees {
+        let status = if session_finished(&record.session) {
+            "completed"
+        } else {
+            "active"
+        };
+        output.push_str(&format!(
+            "{} [{status}]\n  branch: {}\n  workspace: {}\n  session: {}\n  created: {}\n",
+            record.worktree.display(),
+            record.branch,
+            record.workspace.display(),
+            record.session.display(),
+            record.created_at.to_rfc3339()
+        ));
+    }
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+
+    use chrono::{DateTime, Utc};
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .expect("git runs");
+        assert!(status.success(), "git {args:?}");
+    }
+
+    fn init_repo(dir: &Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "-q"]);
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("README.md"), "hello\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-q", "-m", "init"]);
+    }
+
+    async fn add_worktree(
+        manager: &WorktreeManager,
+        repo: &Path,
+        stem: &str,
+        status: &str,
+        now: DateTime<Utc>,
+    ) -> WorktreeRecord {
+        let (worktree, branch) = manager.create(repo, stem, now).await.unwrap();
+        let session = worktree.join("new-session.md");
+        std::fs::write(&session, format!("---\nstatus: {status}\n---\n")).unwrap();
+        WorktreeRecord {
+            workspace: repo.to_path_buf(),
+            worktree,
+            branch,
+            source_session: repo.join(format!("{stem}.md")),
+            session,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn prune_removes_only_completed_clean_worktrees() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        init_repo(&repo);
+        let manager = WorktreeManager::new(temp.path().join("worktrees"), "p");
+
+        // Branch names are per second; keep the two apart.
+        let now = Utc::now();
+        let done = add_worktree(&manager, &repo, "done", "complete", now).await;
+        let active = add_worktree(
+            &manager,
+            &repo,
+            "active",
+            "in_progress",
+            now + chrono::Duration::seconds(1),
+        )
+        .await;
+        let mut state = StateFile::default();
+        state.record_worktree(done.clone());
+        state.record_worktree(active.clone());
+
+        // Untracked session files make both worktrees dirty.
+        let report = prune(&mut state, &manager, false).await;
+        assert_eq!((report.removed, report.failed), (0, 1));
+        assert!(report.lines[0].starts_with("Skipped: Worktree"));
+        assert_eq!(state.worktrees.len(), 2);
+
+        let report = prune(&mut state, &manager, true).await;
+        assert_eq!((report.removed, report.failed), (1, 0));
+        assert!(!done.worktree.exists());
+        assert!(active.worktree.exists());
+        assert_eq!(state.worktrees, vec![active]);
+    }
+
+    #[tokio::test]
+    async fn prune_drops_records_for_deleted_worktrees() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        init_repo(&repo);
+        let manager = WorktreeManager::new(temp.path().join("worktrees"), "p");
+
+        let gone = add_worktree(&manager, &repo, "gone", "complete", Utc::now()).await;
+        std::fs::remove_dir_all(&gone.worktree).unwrap();
+        let mut state = StateFile::default();
+        state.record_worktree(gone);
+
+        let report = prune(&mut state, &manager, false).await;
+        assert_eq!((report.removed, report.failed), (1, 0));
+        assert!(state.worktrees.is_empty());
+    }
+
+    #[test]
+    fn format_lists_worktrees() {
+        assert_eq!(format_worktrees(&[]), "No worktrees recorded");
+        let record = WorktreeRecord {
+            workspace: PathBuf::from("/w/repo"),
+            worktree: PathBuf::from("/w/trees/s-1"),
+            branch: "p/1".to_string(),
+            source_session: PathBuf::from("/w/repo/s.md"),
+            session: PathBuf::from("/w/trees/s-1/missing.md"),
+            created_at: Utc::now(),
+        };
+        let output = format_worktrees(&[record]);
+        assert!(output.starts_with("/w/trees/s-1 [completed]\n  branch: p/1\n"));
+    }
+}
@@ -2,5 +2,22 @@
 
 pub mod app;
 pub mod commands;
+pub mod progress;
 
-pub use app::{Cli, Commands, ConfigAction, DaemonAction, McpCommands};
+#[cfg(feature = "mcp")]
+pub use app::McpCommands;
+#[cfg(feature = "metrics-push")]
+pub use app::MetricsAction;
+#[cfg(feature = "notifications")]
+pub use app::NotifyAction;
+#[cfg(feature = "keyring")]
+pub use app::SecretAction;
+#[cfg(feature = "http-api")]
+pub use app::TokensAction;
+#[cfg(feature = "notifications")]
+pub use app::WebhookAction;
+pub use app::{
+    AttentionAction, Cli, Commands, ConfigAction, DaemonAction, ExclusionsAction, FailuresAction,
+    GcAction, IncidentsAction, InstallTarget, InstancesAction, NextStepAction, OrphansAction,
+    SchedulesAction, StateAction, StatusFormat, UninstallTarget, WorktreesAction,
+};
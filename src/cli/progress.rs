@@ -0,0 +1,140 @@
+//! Checklist-style progress output for multi-step commands.
+//!
+//! Every change of a milestone prints one line, so the output reads as a live
+//! checklist in a terminal and stays a plain log when piped.
+
+use std::io::{self, Write};
+
+/// Where a milestone stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MilestoneState {
+    Pending,
+    Done,
+    Failed,
+}
+
+impl MilestoneState {
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Pending => "…",
+            Self::Done => "✓",
+            Self::Failed => "✗",
+        }
+    }
+}
+
+/// One recorded change of a milestone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub milestone: String,
+    pub state: MilestoneState,
+}
+
+/// Prints milestones as they start, complete or fail, and remembers the
+/// order in which that happened.
+pub struct Checklist<W: Write = io::Stdout> {
+    out: W,
+    transitions: Vec<Transition>,
+}
+
+impl Checklist<io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl<W: Write> Checklist<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            transitions: Vec::new(),
+        }
+    }
+
+    pub fn start(&mut self, milestone: &str) {
+        self.line(milestone, MilestoneState::Pending, milestone);
+    }
+
+    /// Mark `milestone` done; `detail` says what was found.
+    pub fn done(&mut self, milestone: &str, detail: &str) {
+        let text = if detail.is_empty() {
+            milestone.to_string()
+        } else {
+            format!("{milestone} ({detail})")
+        };
+        self.line(milestone, MilestoneState::Done, &text);
+    }
+
+    /// Mark `milestone` failed, followed by one line per troubleshooting hint.
+    pub fn fail(&mut self, milestone: &str, reason: &str, hints: &[String]) {
+        self.line(
+            milestone,
+            MilestoneState::Failed,
+            &format!("{milestone}: {reason}"),
+        );
+        for hint in hints {
+            let _ = writeln!(self.out, "      hint: {hint}");
+        }
+        let _ = self.out.flush();
+    }
+
+    /// Informational line under the current milestone.
+    pub fn note(&mut self, text: &str) {
+        let _ = writeln!(self.out, "    {text}");
+        let _ = self.out.flush();
+    }
+
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    /// Latest state of `milestone`, if it was ever reported.
+    pub fn state(&self, milestone: &str) -> Option<MilestoneState> {
+        self.transitions
+            .iter()
+            .rev()
+            .find(|transition| transition.milestone == milestone)
+            .map(|transition| transition.state)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn line(&mut self, milestone: &str, state: MilestoneState, text: &str) {
+        let _ = writeln!(self.out, "  {} {text}", state.marker());
+        let _ = self.out.flush();
+        self.transitions.push(Transition {
+            milestone: milestone.to_string(),
+            state,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_transitions_and_renders_hints() {
+        let mut checklist = Checklist::new(Vec::new());
+        checklist.start("Watcher started");
+        checklist.done("Watcher started", "/w/sessions");
+        checklist.fail(
+            "Session detected",
+            "nothing changed within 60s",
+            &["Start a session".to_string()],
+        );
+
+        assert_eq!(
+            checklist.state("Watcher started"),
+            Some(MilestoneState::Done)
+        );
+        assert_eq!(checklist.transitions().len(), 3);
+        let output = String::from_utf8(checklist.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            "  … Watcher started\n  ✓ Watcher started (/w/sessions)\n  ✗ Session detected: nothing changed within 60s\n      hint: Start a session\n"
+        );
+    }
+}
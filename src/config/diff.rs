@@ -0,0 +1,304 @@
+//! Differences between two configurations (`config diff`).
+//!
+//! Both sides are flattened to dotted keys, with list elements addressed by
+//! index (`monitoring.assistants[1]`) so a list change shows only the
+//! elements that differ. Secrets are redacted before comparing, so two
+//! credentials only differ when one of them is unset.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::provenance;
+use crate::config::schema::Config;
+
+/// Unchanged lines shown around each hunk of the unified diff.
+const CONTEXT_LINES: usize = 3;
+
+/// One key whose value differs; `None` when the key is absent on that side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChange {
+    pub key: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Flattened, redacted views of a baseline and a current config.
+#[derive(Debug, Clone)]
+pub struct ConfigDiff {
+    baseline: BTreeMap<String, Value>,
+    current: BTreeMap<String, Value>,
+}
+
+impl ConfigDiff {
+    pub fn new(baseline: &Config, current: &Config) -> Self {
+        Self {
+            baseline: flatten(baseline),
+            current: flatten(current),
+        }
+    }
+
+    /// Also redact `fields` (and everything under them), e.g. values that
+    /// were resolved from the keychain.
+    pub fn with_secret_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|field| field.as_ref().to_string())
+            .collect();
+        for leaves in [&mut self.baseline, &mut self.current] {
+            for (key, value) in leaves.iter_mut() {
+                let path = strip_indices(key);
+                let covered = fields
+                    .iter()
+                    .any(|field| path == *field || path.starts_with(&format!("{field}.")));
+                if covered && !value.is_null() {
+                    *value = Value::String(provenance::REDACTED.to_string());
+                }
+            }
+        }
+        self
+    }
+
+    /// Keys whose value differs, in key order.
+    pub fn changes(&self) -> Vec<ConfigChange> {
+        let keys: BTreeSet<&String> = self.baseline.keys().chain(self.current.keys()).collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let old = self.baseline.get(key);
+                let new = self.current.get(key);
+                (old != new).then(|| ConfigChange {
+                    key: key.clone(),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.baseline == self.current
+    }
+
+    /// Unified diff of the `key = value` listings; empty when nothing differs.
+    pub fn unified(&self, from: &str, to: &str) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let old = render_lines(&self.baseline);
+        let new = render_lines(&self.current);
+        let lines = diff_lines(&old, &new);
+
+        let mut output = format!("--- {from}\n+++ {to}\n");
+        let changed: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.kind != ' ')
+            .map(|(index, _)| index)
+            .collect();
+        let mut next = 0;
+        while next < changed.len() {
+            let start = changed[next].saturating_sub(CONTEXT_LINES);
+            let mut last = changed[next];
+            while next + 1 < changed.len() && changed[next + 1] - last <= 2 * CONTEXT_LINES + 1 {
+                next += 1;
+                last = changed[next];
+            }
+            next += 1;
+            let hunk = &lines[start..(last + CONTEXT_LINES + 1).min(lines.len())];
+            output.push_str(&hunk_header(hunk));
+            for line in hunk {
+                output.push(line.kind);
+                output.push_str(line.text);
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
+
+/// A line of the diff: `' '` kept, `'-'` only in the baseline, `'+'` only
+/// in the current config. `old_before`/`new_before` count the lines of each
+/// side that precede it.
+struct DiffLine<'a> {
+    kind: char,
+    text: &'a str,
+    old_before: usize,
+    new_before: usize,
+}
+
+fn hunk_header(hunk: &[DiffLine<'_>]) -> String {
+    let old_count = hunk.iter().filter(|line| line.kind != '+').count();
+    let new_count = hunk.iter().filter(|line| line.kind != '-').count();
+    // An empty side is numbered by the line it follows.
+    let start = |before: usize, count: usize| if count == 0 { before } else { before + 1 };
+    format!(
+        "@@ -{},{old_count} +{},{new_count} @@\n",
+        start(hunk[0].old_before, old_count),
+        start(hunk[0].new_before, new_count)
+    )
+}
+
+/// Longest-common-subsequence line diff; configs are a few hundred lines.
+fn diff_lines<'a>(old: &'a [String], new: &'a [String]) -> Vec<DiffLine<'a>> {
+    let (n, m) = (old.len(), new.len());
+    let mut common = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        let (kind, text) = if i < n && j < m && old[i] == new[j] {
+            (' ', old[i].as_str())
+        } else if i < n && (j == m || common[i + 1][j] >= common[i][j + 1]) {
+            // Removals first on ties, so a changed line reads `-old` then `+new`.
+            ('-', old[i].as_str())
+        } else {
+            ('+', new[j].as_str())
+        };
+        lines.push(DiffLine {
+            kind,
+            text,
+            old_before: i,
+            new_before: j,
+        });
+        if kind != '+' {
+            i += 1;
+        }
+        if kind != '-' {
+            j += 1;
+        }
+    }
+    lines
+}
+
+fn render_lines(leaves: &BTreeMap<String, Value>) -> Vec<String> {
+    leaves
+        .iter()
+        .map(|(key, value)| match value {
+            Value::Null => format!("{key} = (unset)"),
+            value => format!("{key} = {value}"),
+        })
+        .collect()
+}
+
+/// Redacted leaves of `config` keyed by dotted path.
+fn flatten(config: &Config) -> BTreeMap<String, Value> {
+    let value = serde_json::to_value(config).unwrap_or(Value::Null);
+    let mut leaves = BTreeMap::new();
+    collect(value, String::new(), &mut leaves);
+    leaves
+}
+
+fn collect(value: Value, path: String, leaves: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect(child, child_path, leaves);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, item) in items.into_iter().enumerate() {
+                collect(item, format!("{path}[{index}]"), leaves);
+            }
+        }
+        value if !path.is_empty() => {
+            let value = provenance::redact(&strip_indices(&path), value);
+            leaves.insert(path, value);
+        }
+        _ => {}
+    }
+}
+
+/// `notifications.webhooks[0].url` -> `notifications.webhooks.url`.
+fn strip_indices(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut in_index = false;
+    for ch in path.chars() {
+        match ch {
+            '[' => in_index = true,
+            ']' => in_index = false,
+            ch if !in_index => stripped.push(ch),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_changes_are_element_wise() {
+        let baseline = Config::default();
+        let mut current = Config::default();
+        current.monitoring.include_extensions =
+            vec!["md".to_string(), "txt".to_string(), "log".to_string()];
+
+        let changes = ConfigDiff::new(&baseline, &current).changes();
+
+        assert_eq!(
+            changes,
+            vec![
+                ConfigChange {
+                    key: "monitoring.include_extensions[1]".to_string(),
+                    old: Some(Value::from("jsonl")),
+                    new: Some(Value::from("txt")),
+                },
+                ConfigChange {
+                    key: "monitoring.include_extensions[2]".to_string(),
+                    old: None,
+                    new: Some(Value::from("log")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unified_diff_shows_context_and_hunk_header() {
+        let baseline = Config::default();
+        let mut current = Config::default();
+        current.daemon.http_port = 7777;
+
+        let diff = ConfigDiff::new(&baseline, &current).unified("defaults", "config.toml");
+
+        assert!(diff.starts_with("--- defaults\n+++ config.toml\n@@ -"));
+        assert!(diff.contains("\n-daemon.http_port = 7654\n+daemon.http_port = 7777\n"));
+        assert!(diff.contains("\n daemon.http_enabled = false\n"));
+        assert_eq!(diff.matches("@@ -").count(), 1);
+    }
+
+    #[test]
+    fn identical_configs_have_no_diff() {
+        let diff = ConfigDiff::new(&Config::default(), &Config::default());
+        assert!(diff.is_empty());
+        assert!(diff.changes().is_empty());
+        assert_eq!(diff.unified("a", "b"), "");
+    }
+
+    #[test]
+    fn strips_list_indices() {
+        assert_eq!(
+            strip_indices("notifications.webhooks[0].url"),
+            "notifications.webhooks.url"
+        );
+    }
+}
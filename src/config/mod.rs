@@ -1,13 +1,20 @@
 //! Configuration management module.
 
+pub mod diff;
 pub mod paths;
+pub mod provenance;
+pub mod resolved;
 pub mod schema;
+pub mod secrets;
 pub mod validation;
 
-pub use paths::{PathError, Paths};
+pub use paths::{PathError, Paths, UnsafePathError, safe_path};
+pub use provenance::{ConfigProvenance, ConfigSource};
+pub use resolved::{PathEnv, PathFlags, PathSettings, PathSource, ResolvedPath, ResolvedPaths};
 pub use schema::{
-    Config, DaemonConfig, DiscordConfig, McpConfig, MetricsConfig, MonitoringConfig,
+    Config, DaemonConfig, DiscordConfig, GrpcConfig, McpConfig, MetricsConfig, MonitoringConfig,
     NotificationsConfig, NtfyConfig, OpenCodeConfig, OtelConfig, ResumeConfig, SlackConfig,
     WebhookConfig,
 };
+pub use secrets::{SecretError, SecretIndex, SecretRef, SecretSource, SecretStore};
 pub use validation::{ValidationError, ValidationResult, ValidationWarning, validate_config};
@@ -1,7 +1,15 @@
 use std::env;
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::resolved::ResolvedPaths;
+
+/// Instance name used when none is selected; its paths carry no suffix.
+pub const DEFAULT_INSTANCE: &str = "default";
+
+static INSTANCE: OnceLock<String> = OnceLock::new();
 
 /// Platform-specific path resolution for palingenesis.
 pub struct Paths;
@@ -13,6 +21,9 @@ pub enum PathError {
 
     #[error("Failed to create directory {path}: {source}")]
     CreateDirectory { path: PathBuf, source: io::Error },
+
+    #[error("Invalid instance name '{name}': use 1-64 letters, digits, '-' or '_'")]
+    InvalidInstance { name: String },
 }
 
 impl Paths {
@@ -29,7 +40,11 @@ impl Paths {
                 .map(PathBuf::from)
                 .unwrap_or(path);
         }
+        Self::default_config_dir()
+    }
 
+    /// Platform configuration directory, ignoring overrides.
+    pub fn default_config_dir() -> PathBuf {
         #[cfg(target_os = "linux")]
         {
             dirs::config_dir()
@@ -55,22 +70,40 @@ impl Paths {
     }
 
     /// Returns the full config file path.
+    /// - Named instance: `config-<name>.toml` in the config directory
+    /// - Override: PALINGENESIS_CONFIG env var
     pub fn config_file() -> PathBuf {
-        if let Ok(path) = env::var("PALINGENESIS_CONFIG") {
-            return PathBuf::from(path);
-        }
-        Self::config_dir().join("config.toml")
+        ResolvedPaths::current().config_file().to_path_buf()
     }
 
-    /// Returns the state directory path.
+    /// Returns the state directory path, resolved by [`ResolvedPaths`].
     /// - Linux: ~/.local/state/palingenesis/
     /// - macOS: ~/Library/Application Support/palingenesis/
-    /// - Override: PALINGENESIS_STATE env var
+    /// - Named instance: `<name>/` inside the directory above
+    /// - Override: `--state-dir`, PALINGENESIS_STATE env var, `daemon.state_dir`
     pub fn state_dir() -> PathBuf {
+        ResolvedPaths::current().state_dir().to_path_buf()
+    }
+
+    /// State directory of `instance` (`None` for the default instance),
+    /// honoring only the PALINGENESIS_STATE env var.
+    pub fn state_dir_for(instance: Option<&str>) -> PathBuf {
         if let Ok(path) = env::var("PALINGENESIS_STATE") {
             return PathBuf::from(path);
         }
+        Self::default_state_dir_for(instance)
+    }
+
+    /// Platform state directory of `instance`, ignoring overrides.
+    pub fn default_state_dir_for(instance: Option<&str>) -> PathBuf {
+        let dir = Self::default_state_dir();
+        match instance.filter(|name| *name != DEFAULT_INSTANCE) {
+            Some(name) => dir.join(name),
+            None => dir,
+        }
+    }
 
+    fn default_state_dir() -> PathBuf {
         #[cfg(target_os = "linux")]
         {
             dirs::state_dir()
@@ -81,7 +114,7 @@ impl Paths {
 
         #[cfg(target_os = "macos")]
         {
-            Self::config_dir()
+            Self::default_config_dir()
         }
 
         #[cfg(not(any(target_os = "linux", target_os = "macos")))]
@@ -90,15 +123,17 @@ impl Paths {
         }
     }
 
-    /// Returns the runtime directory path (for PID file, Unix socket).
+    /// Returns the runtime directory path (for PID file, Unix socket),
+    /// resolved by [`ResolvedPaths`].
     /// - Linux: /run/user/{uid}/palingenesis/
     /// - macOS: /tmp/palingenesis-{uid}/
-    /// - Override: PALINGENESIS_RUNTIME env var
+    /// - Override: `--runtime-dir`, PALINGENESIS_RUNTIME env var, `daemon.runtime_dir`
     pub fn runtime_dir() -> PathBuf {
-        if let Ok(path) = env::var("PALINGENESIS_RUNTIME") {
-            return PathBuf::from(path);
-        }
+        ResolvedPaths::current().runtime_dir().to_path_buf()
+    }
 
+    /// Platform runtime directory, ignoring overrides.
+    pub fn default_runtime_dir() -> PathBuf {
         #[cfg(target_os = "linux")]
         {
             let runtime_root = dirs::runtime_dir().unwrap_or_else(|| {
@@ -120,6 +155,74 @@ impl Paths {
         }
     }
 
+    /// Select the instance for this process (`--instance`).
+    ///
+    /// The first call wins. [`DEFAULT_INSTANCE`] selects the unsuffixed paths.
+    pub fn set_instance(name: &str) -> Result<(), PathError> {
+        validate_instance_name(name)?;
+        let _ = INSTANCE.set(name.to_string());
+        Ok(())
+    }
+
+    /// The selected named instance, from [`Self::set_instance`] or the
+    /// `PALINGENESIS_INSTANCE` env var; `None` for the default instance.
+    pub fn instance() -> Option<String> {
+        let name = match INSTANCE.get() {
+            Some(name) => name.clone(),
+            None => env::var("PALINGENESIS_INSTANCE").ok()?,
+        };
+        (name != DEFAULT_INSTANCE && validate_instance_name(&name).is_ok()).then_some(name)
+    }
+
+    /// Instance name for labels and messages; [`DEFAULT_INSTANCE`] when none is selected.
+    pub fn instance_label() -> String {
+        Self::instance().unwrap_or_else(|| DEFAULT_INSTANCE.to_string())
+    }
+
+    /// IPC socket of the selected instance.
+    /// - Default: palingenesis.sock in the runtime dir
+    /// - Named instance: palingenesis-<name>.sock
+    /// - Override: PALINGENESIS_SOCKET_PATH env var, `daemon.socket_path`
+    pub fn socket_file() -> PathBuf {
+        ResolvedPaths::current().socket_file().to_path_buf()
+    }
+
+    /// Default IPC socket of `instance` (`None` for the default instance)
+    /// in the runtime dir.
+    pub fn socket_file_for(instance: Option<&str>) -> PathBuf {
+        Self::runtime_dir().join(instance_file_name("palingenesis", "sock", instance))
+    }
+
+    /// PID file of the selected instance (`palingenesis[-<name>].pid`).
+    /// - Override: PALINGENESIS_PID_FILE env var, `daemon.pid_file`
+    pub fn pid_file() -> PathBuf {
+        ResolvedPaths::current().pid_file().to_path_buf()
+    }
+
+    /// Daemon log file (`daemon.log` in the state dir).
+    /// - Override: PALINGENESIS_LOG_FILE env var, `daemon.log_file`
+    pub fn log_file() -> PathBuf {
+        ResolvedPaths::current().log_file().to_path_buf()
+    }
+
+    /// `<stem>.<ext>`, or `<stem>-<name>.<ext>` for a named instance.
+    pub fn instance_file_name(stem: &str, ext: &str) -> String {
+        instance_file_name(stem, ext, Self::instance().as_deref())
+    }
+
+    /// Instance whose socket is `file_name`, if it is an instance socket.
+    ///
+    /// `palingenesis.sock` is [`DEFAULT_INSTANCE`].
+    pub fn instance_from_socket_name(file_name: &str) -> Option<String> {
+        let stem = file_name.strip_suffix(".sock")?;
+        if stem == "palingenesis" {
+            return Some(DEFAULT_INSTANCE.to_string());
+        }
+        let name = stem.strip_prefix("palingenesis-")?;
+        validate_instance_name(name).ok()?;
+        Some(name.to_string())
+    }
+
     /// Ensures the config directory exists, creating it if necessary.
     pub fn ensure_config_dir() -> Result<PathBuf, PathError> {
         let dir = Self::config_dir();
@@ -161,6 +264,143 @@ impl Paths {
     }
 }
 
+/// Check an instance name is usable in file names and metric labels.
+pub fn validate_instance_name(name: &str) -> Result<(), PathError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(PathError::InvalidInstance {
+            name: name.to_string(),
+        })
+    }
+}
+
+pub(crate) fn instance_file_name(stem: &str, ext: &str, instance: Option<&str>) -> String {
+    match instance.filter(|name| *name != DEFAULT_INSTANCE) {
+        Some(name) => format!("{stem}-{name}.{ext}"),
+        None => format!("{stem}.{ext}"),
+    }
+}
+
+/// Reasons a path was refused by [`safe_path`].
+#[derive(Debug, thiserror::Error)]
+pub enum UnsafePathError {
+    #[error("Path {path} contains a parent directory component")]
+    Traversal { path: PathBuf },
+
+    #[error("Path {path} resolves outside {root}")]
+    OutsideRoot { path: PathBuf, root: PathBuf },
+
+    #[error("Path {path} is a symlink and following symlinks is disabled")]
+    Symlink { path: PathBuf },
+
+    #[error("Failed to resolve {path}: {source}")]
+    Resolve { path: PathBuf, source: io::Error },
+}
+
+impl UnsafePathError {
+    /// The offending path.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Traversal { path }
+            | Self::OutsideRoot { path, .. }
+            | Self::Symlink { path }
+            | Self::Resolve { path, .. } => path,
+        }
+    }
+}
+
+/// Resolve `candidate` and ensure it stays inside `root`.
+///
+/// Relative candidates are taken relative to `root`. Any `..` component is
+/// rejected outright. Symlinks along the path are refused unless
+/// `follow_symlinks` is set, and even then only when their target stays
+/// inside `root`. The leaf (and any trailing directories) may not exist yet,
+/// so the result can be used as a write destination.
+pub fn safe_path(
+    candidate: &Path,
+    root: &Path,
+    follow_symlinks: bool,
+) -> Result<PathBuf, UnsafePathError> {
+    if candidate
+        .components()
+        .any(|component| matches!(component, Component::ParentDir))
+    {
+        return Err(UnsafePathError::Traversal {
+            path: candidate.to_path_buf(),
+        });
+    }
+
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|source| UnsafePathError::Resolve {
+            path: root.to_path_buf(),
+            source,
+        })?;
+    let outside = || UnsafePathError::OutsideRoot {
+        path: candidate.to_path_buf(),
+        root: canonical_root.clone(),
+    };
+
+    let relative = if candidate.is_absolute() {
+        candidate
+            .strip_prefix(root)
+            .or_else(|_| candidate.strip_prefix(&canonical_root))
+            .map_err(|_| outside())?
+    } else {
+        candidate
+    };
+
+    let mut resolved = canonical_root.clone();
+    let mut components = relative.components();
+    while let Some(component) = components.next() {
+        let Component::Normal(part) = component else {
+            continue;
+        };
+        resolved.push(part);
+
+        let metadata = match fs::symlink_metadata(&resolved) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                resolved.extend(
+                    components
+                        .by_ref()
+                        .filter(|c| matches!(c, Component::Normal(_))),
+                );
+                break;
+            }
+            Err(source) => {
+                return Err(UnsafePathError::Resolve {
+                    path: resolved,
+                    source,
+                });
+            }
+        };
+
+        if metadata.file_type().is_symlink() {
+            if !follow_symlinks {
+                return Err(UnsafePathError::Symlink { path: resolved });
+            }
+            resolved = resolved
+                .canonicalize()
+                .map_err(|source| UnsafePathError::Resolve {
+                    path: resolved.clone(),
+                    source,
+                })?;
+            if !resolved.starts_with(&canonical_root) {
+                return Err(outside());
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,7 +420,7 @@ mod tests {
 
     #[test]
     fn test_env_override_config_file() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         let config_path = temp.path().join("config.toml");
 
@@ -191,7 +431,7 @@ mod tests {
 
     #[test]
     fn test_env_override_config_dir() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         let config_path = temp.path().join("config.toml");
 
@@ -202,7 +442,7 @@ mod tests {
 
     #[test]
     fn test_env_override_state_dir() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         let state_path = temp.path().join("state");
 
@@ -214,7 +454,7 @@ mod tests {
     #[test]
     #[cfg(target_os = "linux")]
     fn test_linux_paths() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         remove_env_var("PALINGENESIS_CONFIG");
         remove_env_var("PALINGENESIS_STATE");
 
@@ -236,7 +476,7 @@ mod tests {
     #[test]
     #[cfg(target_os = "macos")]
     fn test_macos_paths() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         remove_env_var("PALINGENESIS_CONFIG");
         remove_env_var("PALINGENESIS_STATE");
 
@@ -257,7 +497,7 @@ mod tests {
 
     #[test]
     fn test_directory_creation_helpers() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         let config_path = temp.path().join("config.toml");
         let state_path = temp.path().join("state");
@@ -278,7 +518,7 @@ mod tests {
     #[test]
     #[cfg(target_os = "linux")]
     fn test_runtime_dir_uses_xdg_runtime_dir() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         remove_env_var("PALINGENESIS_RUNTIME");
         let temp = tempfile::tempdir().unwrap();
         set_env_var("XDG_RUNTIME_DIR", temp.path());
@@ -291,7 +531,7 @@ mod tests {
 
     #[test]
     fn test_env_override_runtime_dir() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         let runtime_path = temp.path().join("runtime");
 
@@ -299,4 +539,129 @@ mod tests {
         assert_eq!(Paths::runtime_dir(), runtime_path);
         remove_env_var("PALINGENESIS_RUNTIME");
     }
+
+    #[test]
+    fn test_named_instance_paths() {
+        let _lock = ENV_LOCK.blocking_lock();
+        let temp = tempfile::tempdir().unwrap();
+        remove_env_var("PALINGENESIS_STATE");
+        set_env_var("PALINGENESIS_RUNTIME", temp.path());
+
+        assert_eq!(
+            Paths::socket_file_for(None),
+            temp.path().join("palingenesis.sock")
+        );
+        assert_eq!(
+            Paths::socket_file_for(Some(DEFAULT_INSTANCE)),
+            temp.path().join("palingenesis.sock")
+        );
+        assert_eq!(
+            Paths::socket_file_for(Some("work")),
+            temp.path().join("palingenesis-work.sock")
+        );
+        assert_eq!(
+            Paths::state_dir_for(Some("work")),
+            Paths::state_dir_for(None).join("work")
+        );
+        assert_eq!(
+            instance_file_name("config", "toml", Some("work")),
+            "config-work.toml"
+        );
+        assert_eq!(instance_file_name("config", "toml", None), "config.toml");
+
+        remove_env_var("PALINGENESIS_RUNTIME");
+    }
+
+    #[test]
+    fn test_instance_names() {
+        assert!(validate_instance_name("work_2-personal").is_ok());
+        for name in ["", "../etc", "a b", "x.y", &"a".repeat(65)] {
+            assert!(validate_instance_name(name).is_err(), "{name}");
+        }
+        assert_eq!(
+            Paths::instance_from_socket_name("palingenesis.sock").as_deref(),
+            Some(DEFAULT_INSTANCE)
+        );
+        assert_eq!(
+            Paths::instance_from_socket_name("palingenesis-work.sock").as_deref(),
+            Some("work")
+        );
+        assert_eq!(Paths::instance_from_socket_name("palingenesis.pid"), None);
+        assert_eq!(Paths::instance_from_socket_name("other.sock"), None);
+    }
+
+    #[test]
+    fn test_safe_path_accepts_nested_path() {
+        let temp = tempfile::tempdir().unwrap();
+        let nested = temp.path().join("project").join("sessions");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("session.md"), "---\n---\n").unwrap();
+
+        let resolved = safe_path(&nested.join("session.md"), temp.path(), false).unwrap();
+        assert_eq!(
+            resolved,
+            temp.path()
+                .canonicalize()
+                .unwrap()
+                .join("project/sessions/session.md")
+        );
+
+        let relative = safe_path(Path::new("project/new.md"), temp.path(), false).unwrap();
+        assert!(relative.ends_with("project/new.md"));
+    }
+
+    #[test]
+    fn test_safe_path_rejects_dot_dot() {
+        let temp = tempfile::tempdir().unwrap();
+        let err = safe_path(&temp.path().join("../etc/passwd"), temp.path(), true).unwrap_err();
+        assert!(matches!(err, UnsafePathError::Traversal { .. }));
+
+        let err = safe_path(Path::new("a/../../b"), temp.path(), true).unwrap_err();
+        assert!(matches!(err, UnsafePathError::Traversal { .. }));
+    }
+
+    #[test]
+    fn test_safe_path_rejects_absolute_path_outside_root() {
+        let root = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let err = safe_path(&other.path().join("x.md"), root.path(), true).unwrap_err();
+        assert!(matches!(err, UnsafePathError::OutsideRoot { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_safe_path_rejects_escaping_symlink() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let secret = outside.path().join("passwd");
+        fs::write(&secret, "root:x:0:0").unwrap();
+        let link = root.path().join("session.md");
+        std::os::unix::fs::symlink(&secret, &link).unwrap();
+
+        let err = safe_path(&link, root.path(), false).unwrap_err();
+        assert!(matches!(err, UnsafePathError::Symlink { .. }));
+
+        let err = safe_path(&link, root.path(), true).unwrap_err();
+        assert!(matches!(err, UnsafePathError::OutsideRoot { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_safe_path_follows_internal_symlink_when_enabled() {
+        let root = tempfile::tempdir().unwrap();
+        let real = root.path().join("real");
+        fs::create_dir_all(&real).unwrap();
+        std::os::unix::fs::symlink(&real, root.path().join("alias")).unwrap();
+
+        let candidate = root.path().join("alias/session.md");
+        assert!(matches!(
+            safe_path(&candidate, root.path(), false),
+            Err(UnsafePathError::Symlink { .. })
+        ));
+        let resolved = safe_path(&candidate, root.path(), true).unwrap();
+        assert_eq!(
+            resolved,
+            root.path().canonicalize().unwrap().join("real/session.md")
+        );
+    }
 }
@@ -0,0 +1,374 @@
+//! Where each effective configuration value came from.
+//!
+//! Loading records a [`ConfigSource`] per dotted field path (for example
+//! `daemon.http_port`). Paths without a record fall back to their nearest
+//! recorded parent, and finally to [`ConfigSource::Default`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+
+use crate::config::schema::Config;
+
+pub(crate) const REDACTED: &str = "[redacted]";
+
+/// Origin of a configuration value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default.
+    Default,
+    /// Set in the config file.
+    File(PathBuf),
+    /// Set by an environment variable.
+    Env(String),
+    /// Resolved from a `${keyring:...}`, `${file:...}` or `${env:...}`
+    /// placeholder, e.g. `keyring:service/key`.
+    Secret(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(_) => write!(f, "file"),
+            Self::Env(key) => write!(f, "env:{key}"),
+            Self::Secret(source) => write!(f, "{source}"),
+        }
+    }
+}
+
+/// Source of every field in a loaded [`Config`], keyed by dotted path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigProvenance {
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ConfigProvenance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `source` for `path`, replacing earlier records for it and its children.
+    pub fn record(&mut self, path: impl Into<String>, source: ConfigSource) {
+        let path = path.into();
+        let prefix = format!("{path}.");
+        self.sources.retain(|field, _| !field.starts_with(&prefix));
+        self.sources.insert(path, source);
+    }
+
+    /// Apply records from a later stage (e.g. env overrides over the file).
+    pub fn extend(&mut self, later: ConfigProvenance) {
+        // BTreeMap order visits parents before their children.
+        for (path, source) in later.sources {
+            self.record(path, source);
+        }
+    }
+
+    /// Record every value present in a parsed config file.
+    pub fn record_file(&mut self, file: &toml::Value, path: PathBuf) {
+        let value = serde_json::to_value(file).unwrap_or(Value::Null);
+        let mut leaves = Vec::new();
+        collect_leaves(&value, String::new(), &mut leaves);
+        for (field, _) in leaves {
+            self.record(field, ConfigSource::File(path.clone()));
+        }
+    }
+
+    /// Source of the value at `path`.
+    pub fn source(&self, path: &str) -> &ConfigSource {
+        let mut current = path;
+        loop {
+            if let Some(source) = self.sources.get(current) {
+                return source;
+            }
+            match current.rfind('.') {
+                Some(index) => current = &current[..index],
+                None => return &ConfigSource::Default,
+            }
+        }
+    }
+
+    /// Fields whose value was resolved from a secret placeholder.
+    pub fn secret_fields(&self) -> impl Iterator<Item = &str> {
+        self.sources
+            .iter()
+            .filter(|(_, source)| matches!(source, ConfigSource::Secret(_)))
+            .map(|(field, _)| field.as_str())
+    }
+
+    /// Config file that contributed values, if any.
+    pub fn file(&self) -> Option<&PathBuf> {
+        self.sources.values().find_map(|source| match source {
+            ConfigSource::File(path) => Some(path),
+            _ => None,
+        })
+    }
+
+    /// Every leaf of `config` under `section` (all when `None`), with secrets redacted.
+    pub fn annotate(
+        &self,
+        config: &Config,
+        section: Option<&str>,
+    ) -> Vec<(String, Value, &ConfigSource)> {
+        let value = serde_json::to_value(config).unwrap_or(Value::Null);
+        let mut leaves = Vec::new();
+        collect_leaves(&value, String::new(), &mut leaves);
+        leaves
+            .into_iter()
+            .filter(|(field, _)| {
+                section.is_none_or(|section| {
+                    field == section || field.starts_with(&format!("{section}."))
+                })
+            })
+            .map(|(field, value)| {
+                let source = self.source(&field);
+                let value = match source {
+                    ConfigSource::Secret(_) if !value.is_null() => {
+                        Value::String(REDACTED.to_string())
+                    }
+                    _ => redact(&field, value),
+                };
+                (field, value, source)
+            })
+            .collect()
+    }
+
+    /// Indented tree with one `key = value  # source` line per field.
+    pub fn render_tree(&self, config: &Config, section: Option<&str>) -> String {
+        let mut lines = Vec::new();
+        let mut open: Vec<String> = Vec::new();
+        for (field, value, source) in self.annotate(config, section) {
+            let parts: Vec<&str> = field.split('.').collect();
+            let (parents, key) = parts.split_at(parts.len() - 1);
+
+            let shared = open
+                .iter()
+                .zip(parents)
+                .take_while(|(open, parent)| open.as_str() == **parent)
+                .count();
+            open.truncate(shared);
+            for parent in &parents[shared..] {
+                lines.push(format!("{}{}:", "  ".repeat(open.len()), parent));
+                open.push((*parent).to_string());
+            }
+
+            lines.push(format!(
+                "{}{} = {}  # {}",
+                "  ".repeat(open.len()),
+                key[0],
+                render_value(&value),
+                source
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// Nested JSON mirroring the config, with `{value, source}` at each leaf.
+    pub fn to_json(&self, config: &Config, section: Option<&str>) -> Value {
+        let mut root = Map::new();
+        for (field, value, source) in self.annotate(config, section) {
+            let mut node = &mut root;
+            let parts: Vec<&str> = field.split('.').collect();
+            let (parents, key) = parts.split_at(parts.len() - 1);
+            for parent in parents {
+                node = node
+                    .entry((*parent).to_string())
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .expect("provenance parents are objects");
+            }
+            let mut leaf = Map::new();
+            leaf.insert("value".to_string(), value);
+            leaf.insert("source".to_string(), Value::String(source.to_string()));
+            node.insert(key[0].to_string(), Value::Object(leaf));
+        }
+        Value::Object(root)
+    }
+}
+
+/// Whether the field at `path` holds a credential.
+pub fn is_secret_field(path: &str) -> bool {
+    if path.starts_with("notifications.webhook.headers") {
+        return true;
+    }
+    let key = path.rsplit('.').next().unwrap_or(path);
+    key == "webhook_url"
+        || ["secret", "token", "password", "api_key"]
+            .iter()
+            .any(|marker| key.contains(marker))
+}
+
+pub(crate) fn redact(path: &str, value: Value) -> Value {
+    if value.is_null() {
+        return value;
+    }
+    if is_secret_field(path) {
+        return Value::String(REDACTED.to_string());
+    }
+    match value {
+        // `[[notifications.*]]` entries are array leaves; redact inside them.
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|item| redact(path, item)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, child)| {
+                    let child = redact(&format!("{path}.{key}"), child);
+                    (key, child)
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Flatten objects into dotted paths; arrays and scalars are leaves.
+fn collect_leaves(value: &Value, prefix: String, leaves: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                collect_leaves(child, path, leaves);
+            }
+        }
+        _ if !prefix.is_empty() => leaves.push((prefix, value.clone())),
+        _ => {}
+    }
+}
+
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::Null => "(unset)".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_parent_then_default() {
+        let mut provenance = ConfigProvenance::new();
+        provenance.record("otel", ConfigSource::File(PathBuf::from("/c.toml")));
+
+        assert_eq!(
+            provenance.source("otel.endpoint"),
+            &ConfigSource::File(PathBuf::from("/c.toml"))
+        );
+        assert_eq!(
+            provenance.source("daemon.http_port"),
+            &ConfigSource::Default
+        );
+    }
+
+    #[test]
+    fn later_parent_record_replaces_children() {
+        let mut provenance = ConfigProvenance::new();
+        provenance.record(
+            "notifications.ntfy.server",
+            ConfigSource::File(PathBuf::from("/c.toml")),
+        );
+        let mut env = ConfigProvenance::new();
+        env.record(
+            "notifications.ntfy",
+            ConfigSource::Env("PALINGENESIS_NTFY_TOPIC".to_string()),
+        );
+        provenance.extend(env);
+
+        assert_eq!(
+            provenance.source("notifications.ntfy.server"),
+            &ConfigSource::Env("PALINGENESIS_NTFY_TOPIC".to_string())
+        );
+    }
+
+    #[test]
+    fn records_file_leaves() {
+        let file: toml::Value = toml::from_str("[daemon]\nhttp_port = 9000\n").unwrap();
+        let mut provenance = ConfigProvenance::new();
+        provenance.record_file(&file, PathBuf::from("/c.toml"));
+
+        assert_eq!(
+            provenance.source("daemon.http_port"),
+            &ConfigSource::File(PathBuf::from("/c.toml"))
+        );
+        assert_eq!(
+            provenance.source("daemon.http_bind"),
+            &ConfigSource::Default
+        );
+    }
+
+    #[test]
+    fn redacts_secrets_but_keeps_source() {
+        let mut config = Config::default();
+        config.bot.slack_signing_secret = Some("hunter2".to_string());
+        let mut provenance = ConfigProvenance::new();
+        provenance.record(
+            "bot.slack_signing_secret",
+            ConfigSource::Env("PALINGENESIS_SLACK_SIGNING_SECRET".to_string()),
+        );
+
+        let tree = provenance.render_tree(&config, Some("bot"));
+        assert!(!tree.contains("hunter2"));
+        assert!(tree.contains(
+            "slack_signing_secret = \"[redacted]\"  # env:PALINGENESIS_SLACK_SIGNING_SECRET"
+        ));
+    }
+
+    #[test]
+    fn redacts_keyring_values_in_any_field() {
+        let mut config = Config::default();
+        config.otel = Some(crate::config::schema::OtelConfig {
+            endpoint: "https://user:pw@collector".to_string(),
+            ..Default::default()
+        });
+        let mut provenance = ConfigProvenance::new();
+        provenance.record(
+            "otel.endpoint",
+            ConfigSource::Secret("keyring:pal/otel".to_string()),
+        );
+
+        let tree = provenance.render_tree(&config, Some("otel"));
+        assert!(!tree.contains("user:pw"));
+        assert!(tree.contains("endpoint = \"[redacted]\"  # keyring:pal/otel"));
+    }
+
+    #[test]
+    fn redacts_secrets_inside_target_arrays() {
+        let mut config = Config::default();
+        let mut first = crate::config::schema::NtfyConfig::new("alerts");
+        first.name = Some("phone".to_string());
+        first.token = Some("tk_hunter2".to_string());
+        let mut second = crate::config::schema::NtfyConfig::new("ops");
+        second.name = Some("ops".to_string());
+        config.notifications.ntfy = vec![first, second];
+
+        let tree = ConfigProvenance::new().render_tree(&config, Some("notifications"));
+        assert!(!tree.contains("tk_hunter2"));
+        assert!(tree.contains("alerts"));
+    }
+
+    #[test]
+    fn json_nests_value_and_source() {
+        let provenance = ConfigProvenance::new();
+        let json = provenance.to_json(&Config::default(), Some("daemon"));
+
+        assert_eq!(json["daemon"]["http_port"]["source"], "default");
+        assert!(json["daemon"]["http_port"]["value"].is_u64());
+        assert!(json.get("monitoring").is_none());
+    }
+
+    #[test]
+    fn tree_groups_fields_under_sections() {
+        let tree = ConfigProvenance::new().render_tree(&Config::default(), Some("daemon"));
+        let mut lines = tree.lines();
+        assert_eq!(lines.next(), Some("daemon:"));
+        assert!(lines.all(|line| line.starts_with("  ")));
+    }
+}
@@ -0,0 +1,608 @@
+//! File locations resolved once from every source that can set them.
+//!
+//! Each path kind takes the first of, in order: a command-line flag
+//! (`--state-dir`, `--runtime-dir`), its environment variable, its config
+//! setting, and the platform default from [`Paths`]. Files inside the state
+//! and runtime directories (state file, socket, PID file, log) follow the
+//! resolved directory unless set themselves. The daemon and the CLI build
+//! the same [`ResolvedPaths`] from the same inputs, so both agree on where
+//! the socket, PID file and state live.
+
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use crate::config::paths::{DEFAULT_INSTANCE, Paths, instance_file_name};
+use crate::config::schema::Config;
+use crate::config::validation::ValidationWarning;
+use crate::resume::backup::resolve_backup_dir;
+
+static FLAGS: OnceLock<PathFlags> = OnceLock::new();
+static INSTALLED: OnceLock<Arc<ResolvedPaths>> = OnceLock::new();
+
+/// Which source a resolved path came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSource {
+    /// A command-line flag, e.g. `--state-dir`.
+    Flag(&'static str),
+    /// An environment variable, e.g. `PALINGENESIS_STATE`.
+    Env(&'static str),
+    /// A config setting, e.g. `daemon.state_dir`.
+    Config(&'static str),
+    /// Derived from another resolved path or the platform default.
+    Default,
+}
+
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flag(name) | Self::Env(name) | Self::Config(name) => f.write_str(name),
+            Self::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// A path and where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPath {
+    pub path: PathBuf,
+    pub source: PathSource,
+}
+
+impl ResolvedPath {
+    /// The first candidate that is set, else `default`.
+    fn pick(candidates: &[(Option<&PathBuf>, PathSource)], default: PathBuf) -> Self {
+        candidates
+            .iter()
+            .find_map(|(path, source)| {
+                path.map(|path| Self {
+                    path: path.clone(),
+                    source: *source,
+                })
+            })
+            .unwrap_or(Self {
+                path: default,
+                source: PathSource::Default,
+            })
+    }
+}
+
+/// Path overrides given on the command line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathFlags {
+    pub state_dir: Option<PathBuf>,
+    pub runtime_dir: Option<PathBuf>,
+}
+
+impl PathFlags {
+    /// Record this process's flags. The first call wins.
+    pub fn install(self) {
+        let _ = FLAGS.set(self);
+    }
+
+    /// Flags recorded by [`Self::install`]; none when nothing was installed.
+    pub fn current() -> Self {
+        FLAGS.get().cloned().unwrap_or_default()
+    }
+}
+
+/// Path environment variables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathEnv {
+    /// `PALINGENESIS_CONFIG`: the config file.
+    pub config: Option<PathBuf>,
+    /// `PALINGENESIS_STATE`
+    pub state_dir: Option<PathBuf>,
+    /// `PALINGENESIS_RUNTIME`
+    pub runtime_dir: Option<PathBuf>,
+    /// `PALINGENESIS_PID_FILE`
+    pub pid_file: Option<PathBuf>,
+    /// `PALINGENESIS_SOCKET_PATH`
+    pub socket_path: Option<PathBuf>,
+    /// `PALINGENESIS_LOG_FILE`
+    pub log_file: Option<PathBuf>,
+}
+
+impl PathEnv {
+    pub fn from_process() -> Self {
+        let var = |key: &str| {
+            env::var_os(key)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        Self {
+            config: var("PALINGENESIS_CONFIG"),
+            state_dir: var("PALINGENESIS_STATE"),
+            runtime_dir: var("PALINGENESIS_RUNTIME"),
+            pid_file: var("PALINGENESIS_PID_FILE"),
+            socket_path: var("PALINGENESIS_SOCKET_PATH"),
+            log_file: var("PALINGENESIS_LOG_FILE"),
+        }
+    }
+}
+
+/// Path settings of a config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathSettings {
+    pub state_dir: Option<PathBuf>,
+    pub runtime_dir: Option<PathBuf>,
+    pub pid_file: Option<PathBuf>,
+    pub socket_path: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub backup_dir: Option<PathBuf>,
+}
+
+impl PathSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            state_dir: config.daemon.state_dir.clone(),
+            runtime_dir: config.daemon.runtime_dir.clone(),
+            pid_file: config.daemon.pid_file.clone(),
+            socket_path: config.daemon.socket_path.clone(),
+            log_file: config.daemon.log_file.clone(),
+            backup_dir: config.resume.backup_dir.clone(),
+        }
+    }
+
+    /// Path settings of the config file at `path`. Only these keys are read,
+    /// so a file with unresolved secrets or errors elsewhere still yields
+    /// its paths; an unreadable file yields none.
+    pub fn read(path: &Path) -> Self {
+        let Some(raw) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str::<toml::Value>(&contents).ok())
+        else {
+            return Self::default();
+        };
+        let get = |section: &str, key: &str| {
+            raw.get(section)
+                .and_then(|section| section.get(key))
+                .and_then(toml::Value::as_str)
+                .map(PathBuf::from)
+        };
+        Self {
+            state_dir: get("daemon", "state_dir"),
+            runtime_dir: get("daemon", "runtime_dir"),
+            pid_file: get("daemon", "pid_file"),
+            socket_path: get("daemon", "socket_path"),
+            log_file: get("daemon", "log_file"),
+            backup_dir: get("resume", "backup_dir"),
+        }
+    }
+}
+
+/// Every file location the daemon and CLI use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPaths {
+    instance: Option<String>,
+    config_file: ResolvedPath,
+    state_dir: ResolvedPath,
+    runtime_dir: ResolvedPath,
+    socket_file: ResolvedPath,
+    pid_file: ResolvedPath,
+    log_file: ResolvedPath,
+    backup_dir: Option<PathBuf>,
+}
+
+impl ResolvedPaths {
+    /// Resolve every path from its sources, highest precedence first.
+    pub fn resolve(
+        settings: &PathSettings,
+        env: &PathEnv,
+        flags: &PathFlags,
+        instance: Option<&str>,
+    ) -> Self {
+        let instance = instance
+            .filter(|name| *name != DEFAULT_INSTANCE)
+            .map(str::to_string);
+        let file_name = |stem: &str, ext: &str| instance_file_name(stem, ext, instance.as_deref());
+
+        let config_file = match &env.config {
+            Some(path) => ResolvedPath {
+                path: path.clone(),
+                source: PathSource::Env("PALINGENESIS_CONFIG"),
+            },
+            None => ResolvedPath {
+                path: Paths::default_config_dir().join(file_name("config", "toml")),
+                source: PathSource::Default,
+            },
+        };
+        let state_dir = ResolvedPath::pick(
+            &[
+                (flags.state_dir.as_ref(), PathSource::Flag("--state-dir")),
+                (
+                    env.state_dir.as_ref(),
+                    PathSource::Env("PALINGENESIS_STATE"),
+                ),
+                (
+                    settings.state_dir.as_ref(),
+                    PathSource::Config("daemon.state_dir"),
+                ),
+            ],
+            Paths::default_state_dir_for(instance.as_deref()),
+        );
+        let runtime_dir = ResolvedPath::pick(
+            &[
+                (
+                    flags.runtime_dir.as_ref(),
+                    PathSource::Flag("--runtime-dir"),
+                ),
+                (
+                    env.runtime_dir.as_ref(),
+                    PathSource::Env("PALINGENESIS_RUNTIME"),
+                ),
+                (
+                    settings.runtime_dir.as_ref(),
+                    PathSource::Config("daemon.runtime_dir"),
+                ),
+            ],
+            Paths::default_runtime_dir(),
+        );
+        let socket_file = ResolvedPath::pick(
+            &[
+                (
+                    env.socket_path.as_ref(),
+                    PathSource::Env("PALINGENESIS_SOCKET_PATH"),
+                ),
+                (
+                    settings.socket_path.as_ref(),
+                    PathSource::Config("daemon.socket_path"),
+                ),
+            ],
+            runtime_dir.path.join(file_name("palingenesis", "sock")),
+        );
+        let pid_file = ResolvedPath::pick(
+            &[
+                (
+                    env.pid_file.as_ref(),
+                    PathSource::Env("PALINGENESIS_PID_FILE"),
+                ),
+                (
+                    settings.pid_file.as_ref(),
+                    PathSource::Config("daemon.pid_file"),
+                ),
+            ],
+            runtime_dir.path.join(file_name("palingenesis", "pid")),
+        );
+        let log_file = ResolvedPath::pick(
+            &[
+                (
+                    env.log_file.as_ref(),
+                    PathSource::Env("PALINGENESIS_LOG_FILE"),
+                ),
+                (
+                    settings.log_file.as_ref(),
+                    PathSource::Config("daemon.log_file"),
+                ),
+            ],
+            state_dir.path.join("daemon.log"),
+        );
+        let backup_dir = settings
+            .backup_dir
+            .as_deref()
+            .map(|dir| resolve_backup_dir(dir, &state_dir.path));
+
+        Self {
+            instance,
+            config_file,
+            state_dir,
+            runtime_dir,
+            socket_file,
+            pid_file,
+            log_file,
+            backup_dir,
+        }
+    }
+
+    /// Resolve from this process's flags, environment, selected instance and
+    /// the path settings of its config file.
+    pub fn load() -> Self {
+        let env = PathEnv::from_process();
+        let flags = PathFlags::current();
+        let instance = Paths::instance();
+        let unconfigured =
+            Self::resolve(&PathSettings::default(), &env, &flags, instance.as_deref());
+        let settings = PathSettings::read(&unconfigured.config_file.path);
+        Self::resolve(&settings, &env, &flags, instance.as_deref())
+    }
+
+    /// Use `paths` for the rest of the process. The first call wins; call it
+    /// at startup, once flags and the instance are known.
+    pub fn install(paths: Self) -> Arc<Self> {
+        Arc::clone(INSTALLED.get_or_init(|| Arc::new(paths)))
+    }
+
+    /// The installed paths, or freshly [`Self::load`]ed ones when nothing
+    /// was installed (library use and tests, where the environment changes).
+    pub fn current() -> Arc<Self> {
+        match INSTALLED.get() {
+            Some(paths) => Arc::clone(paths),
+            None => Arc::new(Self::load()),
+        }
+    }
+
+    /// The selected named instance; `None` for the default instance.
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    pub fn config_file(&self) -> &Path {
+        &self.config_file.path
+    }
+
+    pub fn state_dir(&self) -> &Path {
+        &self.state_dir.path
+    }
+
+    pub fn runtime_dir(&self) -> &Path {
+        &self.runtime_dir.path
+    }
+
+    pub fn socket_file(&self) -> &Path {
+        &self.socket_file.path
+    }
+
+    pub fn pid_file(&self) -> &Path {
+        &self.pid_file.path
+    }
+
+    pub fn log_file(&self) -> &Path {
+        &self.log_file.path
+    }
+
+    /// `resume.backup_dir`, relative ones taken under the state directory;
+    /// `None` keeps backups next to each session.
+    pub fn backup_dir(&self) -> Option<&Path> {
+        self.backup_dir.as_deref()
+    }
+
+    /// The state file inside the state directory.
+    pub fn state_file(&self) -> PathBuf {
+        self.state_dir.path.join("state.json")
+    }
+
+    /// Every path with its source, for display.
+    pub fn entries(&self) -> [(&'static str, &ResolvedPath); 6] {
+        [
+            ("config_file", &self.config_file),
+            ("state_dir", &self.state_dir),
+            ("runtime_dir", &self.runtime_dir),
+            ("socket_file", &self.socket_file),
+            ("pid_file", &self.pid_file),
+            ("log_file", &self.log_file),
+        ]
+    }
+
+    /// Conflicts between the sources: config settings that a flag or the
+    /// environment overrides, and a socket or PID file outside the runtime
+    /// directory (where `palingenesis instances` and `daemon stop` of other
+    /// setups will not look).
+    pub fn warnings(&self, settings: &PathSettings) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        let configured = [
+            ("daemon.state_dir", &settings.state_dir, &self.state_dir),
+            (
+                "daemon.runtime_dir",
+                &settings.runtime_dir,
+                &self.runtime_dir,
+            ),
+            (
+                "daemon.socket_path",
+                &settings.socket_path,
+                &self.socket_file,
+            ),
+            ("daemon.pid_file", &settings.pid_file, &self.pid_file),
+            ("daemon.log_file", &settings.log_file, &self.log_file),
+        ];
+        for (field, setting, resolved) in configured {
+            let Some(setting) = setting else {
+                continue;
+            };
+            if *setting != resolved.path {
+                warnings.push(ValidationWarning {
+                    field: field.to_string(),
+                    message: format!(
+                        "{} is overridden by {} ({})",
+                        setting.display(),
+                        resolved.source,
+                        resolved.path.display()
+                    ),
+                });
+            }
+        }
+        for (field, resolved) in [
+            ("daemon.socket_path", &self.socket_file),
+            ("daemon.pid_file", &self.pid_file),
+        ] {
+            if !resolved.path.starts_with(&self.runtime_dir.path) {
+                warnings.push(ValidationWarning {
+                    field: field.to_string(),
+                    message: format!(
+                        "{} (from {}) is outside the runtime directory {} (from {})",
+                        resolved.path.display(),
+                        resolved.source,
+                        self.runtime_dir.path.display(),
+                        self.runtime_dir.source
+                    ),
+                });
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(path))
+    }
+
+    fn all_sources() -> (PathSettings, PathEnv, PathFlags) {
+        let settings = PathSettings {
+            state_dir: path("/config/state"),
+            runtime_dir: path("/config/run"),
+            pid_file: path("/config/run/p.pid"),
+            socket_path: path("/config/run/p.sock"),
+            log_file: path("/config/daemon.log"),
+            backup_dir: path("backups"),
+        };
+        let env = PathEnv {
+            config: path("/env/config.toml"),
+            state_dir: path("/env/state"),
+            runtime_dir: path("/env/run"),
+            pid_file: path("/env/run/p.pid"),
+            socket_path: path("/env/run/p.sock"),
+            log_file: path("/env/daemon.log"),
+        };
+        let flags = PathFlags {
+            state_dir: path("/flag/state"),
+            runtime_dir: path("/flag/run"),
+        };
+        (settings, env, flags)
+    }
+
+    #[test]
+    fn flags_beat_env_beat_config_for_directories() {
+        let (settings, env, flags) = all_sources();
+        let paths = ResolvedPaths::resolve(&settings, &env, &flags, None);
+        assert_eq!(paths.state_dir(), Path::new("/flag/state"));
+        assert_eq!(paths.state_dir.source, PathSource::Flag("--state-dir"));
+        assert_eq!(paths.runtime_dir(), Path::new("/flag/run"));
+
+        let paths = ResolvedPaths::resolve(&settings, &env, &PathFlags::default(), None);
+        assert_eq!(paths.state_dir(), Path::new("/env/state"));
+        assert_eq!(
+            paths.state_dir.source,
+            PathSource::Env("PALINGENESIS_STATE")
+        );
+        assert_eq!(paths.runtime_dir(), Path::new("/env/run"));
+
+        let paths =
+            ResolvedPaths::resolve(&settings, &PathEnv::default(), &PathFlags::default(), None);
+        assert_eq!(paths.state_dir(), Path::new("/config/state"));
+        assert_eq!(
+            paths.runtime_dir.source,
+            PathSource::Config("daemon.runtime_dir")
+        );
+        assert_eq!(paths.backup_dir(), Some(Path::new("/config/state/backups")));
+
+        let paths = ResolvedPaths::resolve(
+            &PathSettings::default(),
+            &PathEnv::default(),
+            &PathFlags::default(),
+            Some("work"),
+        );
+        assert_eq!(
+            paths.state_dir(),
+            Paths::default_state_dir_for(Some("work"))
+        );
+        assert_eq!(paths.runtime_dir(), Paths::default_runtime_dir());
+        assert_eq!(paths.state_dir.source, PathSource::Default);
+    }
+
+    #[test]
+    fn env_beats_config_for_files() {
+        let (settings, env, flags) = all_sources();
+        let paths = ResolvedPaths::resolve(&settings, &env, &flags, None);
+        assert_eq!(paths.config_file(), Path::new("/env/config.toml"));
+        assert_eq!(paths.socket_file(), Path::new("/env/run/p.sock"));
+        assert_eq!(paths.pid_file(), Path::new("/env/run/p.pid"));
+        assert_eq!(paths.log_file(), Path::new("/env/daemon.log"));
+
+        let paths = ResolvedPaths::resolve(&settings, &PathEnv::default(), &flags, None);
+        assert_eq!(paths.socket_file(), Path::new("/config/run/p.sock"));
+        assert_eq!(
+            paths.socket_file.source,
+            PathSource::Config("daemon.socket_path")
+        );
+        assert_eq!(paths.pid_file(), Path::new("/config/run/p.pid"));
+        assert_eq!(paths.log_file(), Path::new("/config/daemon.log"));
+    }
+
+    #[test]
+    fn files_follow_their_resolved_directory_by_default() {
+        let flags = PathFlags {
+            state_dir: path("/flag/state"),
+            runtime_dir: path("/flag/run"),
+        };
+        let paths =
+            ResolvedPaths::resolve(&PathSettings::default(), &PathEnv::default(), &flags, None);
+        assert_eq!(
+            paths.socket_file(),
+            Path::new("/flag/run/palingenesis.sock")
+        );
+        assert_eq!(paths.pid_file(), Path::new("/flag/run/palingenesis.pid"));
+        assert_eq!(paths.log_file(), Path::new("/flag/state/daemon.log"));
+        assert_eq!(paths.state_file(), Path::new("/flag/state/state.json"));
+        assert_eq!(paths.backup_dir(), None);
+
+        let paths = ResolvedPaths::resolve(
+            &PathSettings::default(),
+            &PathEnv::default(),
+            &flags,
+            Some("work"),
+        );
+        assert_eq!(
+            paths.socket_file(),
+            Path::new("/flag/run/palingenesis-work.sock")
+        );
+        assert_eq!(
+            paths.config_file(),
+            Paths::default_config_dir().join("config-work.toml")
+        );
+    }
+
+    #[test]
+    fn conflicts_are_reported() {
+        let settings = PathSettings {
+            state_dir: path("/config/state"),
+            socket_path: path("/elsewhere/p.sock"),
+            ..PathSettings::default()
+        };
+        let env = PathEnv {
+            state_dir: path("/env/state"),
+            runtime_dir: path("/env/run"),
+            ..PathEnv::default()
+        };
+        let paths = ResolvedPaths::resolve(&settings, &env, &PathFlags::default(), None);
+        let warnings = paths.warnings(&settings);
+        let fields: Vec<&str> = warnings.iter().map(|w| w.field.as_str()).collect();
+        assert_eq!(fields, vec!["daemon.state_dir", "daemon.socket_path"]);
+        assert!(
+            warnings[0]
+                .message
+                .contains("overridden by PALINGENESIS_STATE")
+        );
+        assert!(
+            warnings[1]
+                .message
+                .contains("outside the runtime directory /env/run")
+        );
+
+        let quiet =
+            ResolvedPaths::resolve(&PathSettings::default(), &env, &PathFlags::default(), None);
+        assert!(quiet.warnings(&PathSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn settings_are_read_from_the_path_keys_only() {
+        let temp = tempfile::tempdir().unwrap();
+        let file = temp.path().join("config.toml");
+        std::fs::write(
+            &file,
+            "[daemon]\nstate_dir = \"/srv/state\"\nhttp_port = \"${env:PORT}\"\n\n[resume]\nbackup_dir = \"b\"\n",
+        )
+        .unwrap();
+        let settings = PathSettings::read(&file);
+        assert_eq!(settings.state_dir, path("/srv/state"));
+        assert_eq!(settings.backup_dir, path("b"));
+        assert_eq!(settings.socket_path, None);
+        assert_eq!(
+            PathSettings::read(&temp.path().join("missing.toml")),
+            PathSettings::default()
+        );
+    }
+}
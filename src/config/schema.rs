@@ -1,9 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::PathBuf;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::config::Paths;
+use crate::i18n::Locale;
+use crate::resume::backup::DEFAULT_FILENAME_TEMPLATE;
+use crate::state::StateFormat;
+use crate::telemetry::log_buffer;
+use crate::util::content;
 
 /// Root configuration for palingenesis.
 ///
@@ -48,6 +53,19 @@ pub struct Config {
     /// MCP server configuration section.
     /// Example: [mcp]
     pub mcp: McpConfig,
+    /// Commands run on daemon lifecycle events.
+    /// Example: [hooks]
+    pub hooks: HooksConfig,
+    /// Stop-reason classifier tuning; reloaded on RELOAD.
+    /// Example: [classifier]
+    pub classifier: ClassificationConfig,
+    /// Garbage collection of old session artifacts.
+    /// Example: [maintenance]
+    pub maintenance: MaintenanceConfig,
+    /// Sessions started on a recurring schedule.
+    /// Example: [[schedules]]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduleConfig>,
     /// Optional OpenTelemetry configuration section.
     /// Example: [otel]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -61,12 +79,160 @@ pub struct MetricsConfig {
     /// Estimated time for manual session restart (seconds).
     /// Default: 300 (5 minutes)
     pub manual_restart_time_seconds: u64,
+    /// Engineer cost per hour, to value time saved; unset leaves it unknown.
+    /// Example: hourly_rate = 85.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hourly_rate: Option<f64>,
+    /// Currency code shown next to costs and values.
+    /// Example: currency = "EUR"
+    pub currency: String,
+    /// Token prices per model name, used to estimate the cost of each
+    /// resumed session segment.
+    /// Example: model_costs = { "claude-sonnet-4" = { input_per_1k = 0.003, output_per_1k = 0.015 } }
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_costs: BTreeMap<String, ModelCost>,
 }
 
 impl Default for MetricsConfig {
     fn default() -> Self {
         Self {
             manual_restart_time_seconds: default_manual_restart_time_seconds(),
+            hourly_rate: None,
+            currency: default_currency(),
+            model_costs: BTreeMap::new(),
+        }
+    }
+}
+
+/// Price of one model's tokens (`[metrics.model_costs]`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct ModelCost {
+    /// Price per 1,000 input tokens.
+    pub input_per_1k: f64,
+    /// Price per 1,000 output tokens.
+    pub output_per_1k: f64,
+}
+
+/// Stop-reason classifier settings (`[classifier]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ClassificationConfig {
+    /// Wait before resuming a rate-limited session without a Retry-After hint (seconds).
+    /// Example: default_retry_wait_secs = 30
+    pub default_retry_wait_secs: u64,
+    /// Lines read from the end of a session file.
+    /// Example: max_lines = 100
+    pub max_lines: usize,
+    /// Context usage (0.0-1.0) treated as exhausted.
+    /// Example: context_threshold_percent = 0.8
+    pub context_threshold_percent: f32,
+    /// Context window assumed for unknown models (tokens).
+    /// Example: default_context_size = 200000
+    pub default_context_size: u32,
+    /// Context window per model name; merged over the built-in sizes.
+    /// Example: known_context_sizes = { "claude sonnet 4" = 1000000 }
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub known_context_sizes: BTreeMap<String, u32>,
+    /// Extra regexes that mark a rate limit.
+    /// Example: extra_rate_limit_patterns = ["(?i)slow down"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_rate_limit_patterns: Vec<String>,
+    /// Extra regexes that mark context exhaustion.
+    /// Example: extra_context_patterns = ["(?i)conversation too long"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_context_patterns: Vec<String>,
+    /// Provider clock skew that triggers a one-time warning (seconds).
+    /// Example: clock_skew_warn_secs = 60
+    pub clock_skew_warn_secs: u64,
+    /// Longest evidence line kept from a classification (characters).
+    /// Example: max_evidence_chars = 512
+    pub max_evidence_chars: usize,
+    /// Growth of a completed session file that counts as reopening it (bytes).
+    /// Example: reopen_growth_bytes = 512
+    pub reopen_growth_bytes: u64,
+}
+
+impl Default for ClassificationConfig {
+    fn default() -> Self {
+        Self {
+            default_retry_wait_secs: 30,
+            max_lines: 100,
+            context_threshold_percent: 0.80,
+            default_context_size: 200_000,
+            known_context_sizes: BTreeMap::new(),
+            extra_rate_limit_patterns: Vec::new(),
+            extra_context_patterns: Vec::new(),
+            clock_skew_warn_secs: 60,
+            max_evidence_chars: content::DEFAULT_MAX_EVIDENCE_CHARS,
+            reopen_growth_bytes: 512,
+        }
+    }
+}
+
+/// Hook commands (`[hooks]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Shell command run after a session completes; gets PALINGENESIS_SESSION_PATH.
+    /// Example: on_complete = "notify-send 'Workflow done'"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_complete: Option<String>,
+    /// Seconds a hook may run before it is killed.
+    /// Example: timeout_secs = 60
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_complete: None,
+            timeout_secs: 60,
+        }
+    }
+}
+
+/// Garbage collection of session artifacts (`[maintenance]`, `palingenesis gc`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Run the artifact GC as a background job in the daemon.
+    /// Example: enabled = true
+    pub enabled: bool,
+    /// Seconds between scheduled runs.
+    /// Example: interval_secs = 86400
+    pub interval_secs: u64,
+    /// Delete right away after the retention settings change, instead of
+    /// reporting what would be deleted until `palingenesis gc approve`.
+    /// Example: auto_approve = true
+    pub auto_approve: bool,
+    /// Nothing modified more recently than this is deleted (seconds).
+    /// Example: safety_window_secs = 86400
+    pub safety_window_secs: u64,
+    /// Days completed sessions' backups are kept (0 keeps them).
+    /// Example: backup_retention_days = 30
+    pub backup_retention_days: u32,
+    /// Days incident snapshots in `incidents/` are kept (0 keeps them).
+    /// Example: incident_retention_days = 30
+    pub incident_retention_days: u32,
+    /// Delete a session's postmortem bundles in `failures/` once it completes.
+    /// Example: failure_bundles_on_complete = false
+    pub failure_bundles_on_complete: bool,
+    /// Delete Next-step.md files none of whose sessions exist any more.
+    /// Example: orphaned_next_steps = false
+    pub orphaned_next_steps: bool,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 86400,
+            auto_approve: false,
+            safety_window_secs: 86400,
+            backup_retention_days: 30,
+            incident_retention_days: 30,
+            failure_bundles_on_complete: true,
+            orphaned_next_steps: true,
         }
     }
 }
@@ -75,11 +241,21 @@ impl Default for MetricsConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
 pub struct DaemonConfig {
-    /// Path to PID file (platform default if not set).
+    /// State directory (platform default if not set; `--state-dir` and
+    /// PALINGENESIS_STATE take precedence).
+    /// Example: state_dir = "/var/lib/palingenesis"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_dir: Option<PathBuf>,
+    /// Runtime directory for the PID file and socket (platform default if not
+    /// set; `--runtime-dir` and PALINGENESIS_RUNTIME take precedence).
+    /// Example: runtime_dir = "/run/user/1000/palingenesis"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_dir: Option<PathBuf>,
+    /// Path to PID file (in the runtime directory if not set).
     /// Example: pid_file = "/run/user/1000/palingenesis/palingenesis.pid"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pid_file: Option<PathBuf>,
-    /// Path to Unix socket (platform default if not set).
+    /// Path to Unix socket (in the runtime directory if not set).
     /// Example: socket_path = "/run/user/1000/palingenesis/palingenesis.sock"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub socket_path: Option<PathBuf>,
@@ -92,26 +268,350 @@ pub struct DaemonConfig {
     /// HTTP server bind address.
     /// Example: http_bind = "127.0.0.1"
     pub http_bind: String,
+    /// Bearer token required by the HTTP API (all routes except /health).
+    /// Example: http_token = "${keyring:palingenesis/http}"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_token: Option<String>,
+    /// Named HTTP API tokens, each limited to its `scopes`. They are
+    /// accepted alongside `http_token`, which keeps every scope.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub http_tokens: Vec<HttpTokenConfig>,
     /// Log level (trace, debug, info, warn, error).
     /// Example: log_level = "info"
     pub log_level: String,
-    /// Optional log file path.
+    /// Log file path (daemon.log in the state directory if not set).
     /// Example: log_file = "/var/log/palingenesis.log"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub log_file: Option<PathBuf>,
+    /// Recent log records kept in memory for `palingenesis logs` (0 disables).
+    /// Example: log_buffer_entries = 2000
+    pub log_buffer_entries: usize,
+    /// Upper bound on the memory those records use (bytes).
+    /// Example: log_buffer_bytes = 1048576
+    pub log_buffer_bytes: usize,
+    /// How often pending state changes are written to disk (milliseconds).
+    /// Example: state_flush_interval_ms = 1000
+    pub state_flush_interval_ms: u64,
+    /// Encoding of the state file: `json` (readable) or `msgpack` (compact,
+    /// for large states saved often). Either is read back; a change applies
+    /// at the next daemon start. `palingenesis state dump` prints it as JSON.
+    /// Example: state_format = "msgpack"
+    pub state_format: StateFormat,
+    /// How often to check for a system sleep gap (seconds, 0 disables).
+    /// Example: suspend_check_interval_secs = 30
+    pub suspend_check_interval_secs: u64,
+    /// Smallest clock gap reported as a system sleep (seconds).
+    /// Example: suspend_threshold_secs = 120
+    pub suspend_threshold_secs: u64,
+    /// Normal-priority daemon jobs (classification, notifications) run at once.
+    /// Example: job_normal_concurrency = 4
+    pub job_normal_concurrency: usize,
+    /// Background daemon jobs (pruning, scans, digests) run at once.
+    /// Example: job_background_concurrency = 2
+    pub job_background_concurrency: usize,
+    /// gRPC control API (requires the `grpc` build feature).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grpc: Option<GrpcConfig>,
+    /// `observe` detects, classifies and notifies but never resumes; planned
+    /// actions are reported with an `OBSERVE:` prefix instead.
+    /// Example: mode = "observe"
+    pub mode: DaemonMode,
+    /// Longest text (event details, errors) kept in the event buffer and
+    /// notification history (characters); longer text is cut.
+    /// Example: max_retained_content_chars = 2048
+    pub max_retained_content_chars: usize,
+    /// Language of notifications and human CLI output: `en` or `ja`.
+    /// Channels can override it with their own `locale`; logs and JSON
+    /// output stay English.
+    /// Example: locale = "ja"
+    pub locale: Locale,
+    /// How file paths appear in notifications and human CLI output:
+    /// `absolute`, `relative` (to the session directory or git workspace),
+    /// `basename` or `redacted`. Channels can override it.
+    /// Example: path_display = "relative"
+    pub path_display: PathDisplayMode,
+    /// Path components replaced by `[redacted]` under
+    /// `path_display = "redacted"` (`*` and `?` wildcards).
+    /// Example: redact_patterns = ["client-*"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_patterns: Vec<String>,
+    /// Apply `path_display` to JSON output too (`status --json`, the SSE
+    /// stream, webhook payloads); by default JSON keeps full paths.
+    /// Example: redact_json = true
+    pub redact_json: bool,
+    /// Thresholds of `palingenesis check`.
+    pub check: CheckConfig,
+    /// Startup check of the state file against the session files.
+    pub reconcile: ReconcileConfig,
+}
+
+/// How paths are rendered for people (`daemon.path_display`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PathDisplayMode {
+    /// The full path.
+    #[default]
+    Absolute,
+    /// Relative to the session directory or the enclosing git workspace.
+    Relative,
+    /// The file name only.
+    Basename,
+    /// The home directory as `~`, components matching `redact_patterns` as `[redacted]`.
+    Redacted,
+}
+
+/// Whether the daemon acts on the stops it detects.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonMode {
+    /// Resume stopped sessions.
+    #[default]
+    Active,
+    /// Report what would have been done, without doing it.
+    Observe,
+}
+
+impl DaemonMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Observe => "observe",
+        }
+    }
+
+    pub fn is_observe(&self) -> bool {
+        *self == Self::Observe
+    }
+}
+
+impl std::fmt::Display for DaemonMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for DaemonMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "active" => Ok(Self::Active),
+            "observe" => Ok(Self::Observe),
+            other => Err(format!(
+                "unknown mode {other:?} (expected active or observe)"
+            )),
+        }
+    }
 }
 
 impl Default for DaemonConfig {
     fn default() -> Self {
-        let runtime_dir = Paths::runtime_dir();
         Self {
-            pid_file: Some(runtime_dir.join("palingenesis.pid")),
-            socket_path: Some(runtime_dir.join("palingenesis.sock")),
+            state_dir: None,
+            runtime_dir: None,
+            pid_file: None,
+            socket_path: None,
             http_enabled: false,
             http_port: 7654,
             http_bind: "127.0.0.1".to_string(),
+            http_token: None,
+            http_tokens: Vec::new(),
             log_level: "info".to_string(),
             log_file: None,
+            log_buffer_entries: log_buffer::DEFAULT_LOG_BUFFER_ENTRIES,
+            log_buffer_bytes: log_buffer::DEFAULT_LOG_BUFFER_BYTES,
+            state_flush_interval_ms: 1000,
+            state_format: StateFormat::Json,
+            suspend_check_interval_secs: 30,
+            suspend_threshold_secs: 120,
+            job_normal_concurrency: 4,
+            job_background_concurrency: 2,
+            grpc: None,
+            mode: DaemonMode::Active,
+            max_retained_content_chars: content::DEFAULT_MAX_RETAINED_CHARS,
+            locale: Locale::En,
+            path_display: PathDisplayMode::Absolute,
+            redact_patterns: Vec::new(),
+            redact_json: false,
+            check: CheckConfig::default(),
+            reconcile: ReconcileConfig::default(),
+        }
+    }
+}
+
+impl DaemonConfig {
+    /// URL of `path` on the daemon's HTTP API; wildcard binds are reached over loopback.
+    pub fn http_api_url(&self, path: &str) -> String {
+        let host = match self.http_bind.trim() {
+            "" | "0.0.0.0" => "127.0.0.1".to_string(),
+            "::" | "[::]" => "[::1]".to_string(),
+            bind if bind.contains(':') && !bind.starts_with('[') => format!("[{bind}]"),
+            bind => bind.to_string(),
+        };
+        format!("http://{host}:{}{path}", self.http_port)
+    }
+
+    /// Token the CLI presents to the HTTP API for a request needing
+    /// `scope`: `http_token`, else the first named token holding the scope.
+    pub fn http_client_token(&self, scope: HttpScope) -> Option<&str> {
+        self.http_token.as_deref().or_else(|| {
+            self.http_tokens
+                .iter()
+                .find(|token| token.scopes.iter().any(|held| held.grants(scope)))
+                .map(|token| token.token.as_str())
+        })
+    }
+}
+
+/// A named HTTP API token (`[[daemon.http_tokens]]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpTokenConfig {
+    /// Name recorded as the initiator of the token's requests.
+    /// Example: name = "dashboard"
+    pub name: String,
+    /// The bearer token.
+    /// Example: token = "${keyring:palingenesis/dashboard}"
+    pub token: String,
+    /// What the token may do.
+    /// Example: scopes = ["read"]
+    pub scopes: Vec<HttpScope>,
+}
+
+/// Permission an HTTP API route requires.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpScope {
+    /// Status, events, metrics, sessions and notification history.
+    Read,
+    /// Pause and resume monitoring.
+    Control,
+    /// Trigger resumes and start new sessions.
+    Resume,
+    /// Config reloads, mode switches and credential rotation; grants every
+    /// other scope too.
+    Admin,
+}
+
+impl HttpScope {
+    pub const ALL: [Self; 4] = [Self::Read, Self::Control, Self::Resume, Self::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Control => "control",
+            Self::Resume => "resume",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Whether holding this scope allows a route that requires `required`.
+    pub fn grants(self, required: Self) -> bool {
+        self == required || self == Self::Admin
+    }
+}
+
+impl std::fmt::Display for HttpScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Health conditions evaluated by `palingenesis check`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Longest state changes may wait to be written to disk (seconds).
+    /// Example: max_stall_secs = 1800
+    pub max_stall_secs: u64,
+    /// Oldest an open incident may be before the check fails (seconds).
+    /// Example: max_incident_age_secs = 7200
+    pub max_incident_age_secs: u64,
+    /// URL pinged when the check passes; `<url>/fail` when it fails.
+    /// Example: ping_url = "https://hc-ping.com/<uuid>"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ping_url: Option<String>,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            max_stall_secs: 1800,
+            max_incident_age_secs: 7200,
+            ping_url: None,
+        }
+    }
+}
+
+/// Reconciliation of the state file on startup and with `palingenesis reconcile`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ReconcileConfig {
+    /// Reconcile the state file when the daemon starts.
+    /// Example: enabled = true
+    pub enabled: bool,
+    /// What to do when the current session's file is gone: `flag` holds it
+    /// for `palingenesis attention`, `clear` forgets it.
+    /// Example: missing_session = "clear"
+    pub missing_session: MissingSessionPolicy,
+    /// Open incidents and resume-loop holds older than this are closed
+    /// (seconds).
+    /// Example: stale_after_secs = 86400
+    pub stale_after_secs: u64,
+}
+
+impl Default for ReconcileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            missing_session: MissingSessionPolicy::Flag,
+            stale_after_secs: 86400,
+        }
+    }
+}
+
+/// What reconciliation does with a current session whose file is gone.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingSessionPolicy {
+    /// Hold the session for attention.
+    #[default]
+    Flag,
+    /// Drop it as the current session.
+    Clear,
+}
+
+/// gRPC control API configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GrpcConfig {
+    /// Enable the gRPC control API.
+    /// Example: enabled = true
+    pub enabled: bool,
+    /// Listen address.
+    /// Example: bind = "127.0.0.1:7655"
+    pub bind: String,
+    /// Server certificate (PEM); TLS is enabled when set together with `tls_key`.
+    /// Example: tls_cert = "/etc/palingenesis/grpc.crt"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_cert: Option<PathBuf>,
+    /// Server private key (PEM).
+    /// Example: tls_key = "/etc/palingenesis/grpc.key"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_key: Option<PathBuf>,
+    /// CA bundle used to verify client certificates (enables mTLS).
+    /// Example: tls_ca = "/etc/palingenesis/clients-ca.crt"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_ca: Option<PathBuf>,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1:7655".to_string(),
+            tls_cert: None,
+            tls_key: None,
+            tls_ca: None,
         }
     }
 }
@@ -132,13 +632,59 @@ pub struct MonitoringConfig {
     /// Interval for auto-detection re-scan (seconds).
     /// Example: auto_detect_interval_secs = 300
     pub auto_detect_interval_secs: u64,
-    /// Debounce time for file events (milliseconds).
+    /// Debounce time for file events (milliseconds); the starting window in adaptive mode.
     /// Example: debounce_ms = 100
     pub debounce_ms: u64,
+    /// "fixed" keeps `debounce_ms`; "adaptive" moves the window between
+    /// `debounce_min_ms` and `debounce_max_ms` with the event rate.
+    /// Example: debounce = "adaptive"
+    pub debounce: DebounceMode,
+    /// Shortest adaptive debounce window (milliseconds).
+    /// Example: debounce_min_ms = 50
+    pub debounce_min_ms: u64,
+    /// Longest adaptive debounce window (milliseconds).
+    /// Example: debounce_max_ms = 2000
+    pub debounce_max_ms: u64,
+    /// Warn when debouncing held an event back longer than this (milliseconds, 0 = never).
+    /// Example: debounce_warn_ms = 2000
+    pub debounce_warn_ms: u64,
     /// Polling interval fallback (seconds).
     /// Example: poll_interval_secs = 5
     #[serde(skip_serializing_if = "Option::is_none")]
     pub poll_interval_secs: Option<u64>,
+    /// Follow symlinks inside the session directory (targets must stay inside it).
+    /// Example: follow_symlinks = false
+    pub follow_symlinks: bool,
+    /// File extensions whose events reach the monitor (empty forwards all).
+    /// Example: include_extensions = ["md", "jsonl"]
+    pub include_extensions: Vec<String>,
+    /// Globs for paths under the session directory whose events are dropped.
+    /// Example: ignore_patterns = ["**/.git/**", "*.swp", "*~", "*.tmp"]
+    pub ignore_patterns: Vec<String>,
+    /// Recursively watch only subdirectories modified within this many hours (0 = whole tree).
+    /// Example: active_window_hours = 168
+    pub active_window_hours: u64,
+    /// Most recent files read when listing sessions from the session directory.
+    /// Example: scan_limit = 200
+    pub scan_limit: usize,
+    /// Stop classifications run at once.
+    /// Example: classify_concurrency = 2
+    pub classify_concurrency: usize,
+    /// Drop queued classifications of sessions that are neither tracked nor
+    /// in an open incident after this long (seconds, 0 = never).
+    /// Example: classify_stale_secs = 300
+    pub classify_stale_secs: u64,
+}
+
+/// How the session watcher's debounce window is chosen (`monitoring.debounce`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DebounceMode {
+    /// Always `debounce_ms`.
+    #[default]
+    Fixed,
+    /// Short while events are sparse, longer during bursts.
+    Adaptive,
 }
 
 /// OpenCode process monitoring configuration.
@@ -148,9 +694,12 @@ pub struct OpenCodeConfig {
     /// Enable OpenCode process monitoring.
     /// Example: enabled = true
     pub enabled: bool,
-    /// Port for OpenCode serve command and health checks.
+    /// Port for OpenCode serve command and health checks (0 = auto-discover).
     /// Example: serve_port = 4096
     pub serve_port: u16,
+    /// Discover the serve port from the running OpenCode process.
+    /// Example: auto_discover = true
+    pub auto_discover: bool,
     /// Hostname for OpenCode serve command and health checks.
     /// Example: serve_hostname = "localhost"
     pub serve_hostname: String,
@@ -163,6 +712,15 @@ pub struct OpenCodeConfig {
     /// Interval between OpenCode health checks (milliseconds).
     /// Example: health_check_interval = 1000
     pub health_check_interval: u64,
+    /// How long a resumable stop waits for a crash it may belong to (milliseconds, 0 = off).
+    /// Example: crash_settle_ms = 2000
+    pub crash_settle_ms: u64,
+    /// Longest wait for a crashed OpenCode to come back healthy before held stops proceed (seconds).
+    /// Example: crash_restart_timeout_secs = 120
+    pub crash_restart_timeout_secs: u64,
+    /// Time a held session gets to continue on its own after the restart (seconds).
+    /// Example: crash_grace_secs = 60
+    pub crash_grace_secs: u64,
 }
 
 impl Default for OpenCodeConfig {
@@ -170,14 +728,25 @@ impl Default for OpenCodeConfig {
         Self {
             enabled: false,
             serve_port: 4096,
+            auto_discover: false,
             serve_hostname: "localhost".to_string(),
             auto_restart: true,
             restart_delay_ms: 1000,
             health_check_interval: 1000,
+            crash_settle_ms: 2000,
+            crash_restart_timeout_secs: 120,
+            crash_grace_secs: 60,
         }
     }
 }
 
+impl OpenCodeConfig {
+    /// Whether the serve port should be discovered from the running process.
+    pub fn port_discovery_enabled(&self) -> bool {
+        self.auto_discover || self.serve_port == 0
+    }
+}
+
 /// MCP server configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
@@ -215,7 +784,23 @@ impl Default for MonitoringConfig {
             auto_detect: true,
             auto_detect_interval_secs: 300,
             debounce_ms: 100,
+            debounce: DebounceMode::Fixed,
+            debounce_min_ms: 50,
+            debounce_max_ms: 2000,
+            debounce_warn_ms: 2000,
             poll_interval_secs: None,
+            follow_symlinks: false,
+            include_extensions: vec!["md".to_string(), "jsonl".to_string()],
+            ignore_patterns: vec![
+                "**/.git/**".to_string(),
+                "*.swp".to_string(),
+                "*~".to_string(),
+                "*.tmp".to_string(),
+            ],
+            active_window_hours: 0,
+            scan_limit: crate::monitor::scan::DEFAULT_SCAN_LIMIT,
+            classify_concurrency: 2,
+            classify_stale_secs: 300,
         }
     }
 }
@@ -242,6 +827,68 @@ pub struct ResumeConfig {
     /// Number of session backups to keep.
     /// Example: backup_count = 10
     pub backup_count: u32,
+    /// Directory session backups are written to (relative paths are under the state dir).
+    /// Example: backup_dir = "backups"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_dir: Option<PathBuf>,
+    /// Backup path under the backup directory; must contain `{timestamp}` and `{stem}`.
+    /// Example: backup_filename_template = "{workspace}/{date}/{stem}-{timestamp}.{ext}"
+    pub backup_filename_template: String,
+    /// Skip a backup whose content matches the session's newest backup.
+    /// Example: backup_dedupe = false
+    pub backup_dedupe: bool,
+    /// Session path globs that are never auto-resumed (`!pattern` re-includes; last match wins).
+    /// Example: exclude_sessions = ["**/experiments/**", "*scratch*"]
+    pub exclude_sessions: Vec<String>,
+    /// Copy the newest backup back when the current session file is deleted.
+    /// Example: restore_deleted_from_backup = true
+    pub restore_deleted_from_backup: bool,
+    /// Request the original session's model for new sessions (API creator only).
+    /// Example: enforce_model = true
+    pub enforce_model: bool,
+    /// Record the workspace's git branch, HEAD and dirty state around each resume.
+    /// Example: capture_git_context = true
+    pub capture_git_context: bool,
+    /// Maximum bytes of Next-step.md read and embedded in new-session prompts.
+    /// Example: max_next_step_bytes = 65536
+    pub max_next_step_bytes: usize,
+    /// Periods during which resumes wait for the window to end (weekly UTC or RFC3339 intervals).
+    /// Example: maintenance_windows = ["Tue 02:00-04:00 UTC", "2025-12-24T00:00:00Z/2025-12-26T00:00:00Z"]
+    pub maintenance_windows: Vec<String>,
+    /// Copy completed sessions into the state directory's `archive/`.
+    /// Example: archive_on_complete = true
+    pub archive_on_complete: bool,
+    /// Postmortem bundles kept in the state directory's `failures/` (0 disables capture).
+    /// Example: failure_bundle_count = 20
+    pub failure_bundle_count: u32,
+    /// Stop-time transcript snapshots kept in the state directory's `incidents/` (0 disables capture).
+    /// Example: incident_count = 50
+    pub incident_count: u32,
+    /// Minimum seconds between automatic resumes of the same session (0 disables).
+    /// Example: min_resume_interval_secs = 120
+    pub min_resume_interval_secs: u64,
+    /// Automatic resumes of one session per rolling hour before it is flagged as a loop (0 disables).
+    /// Example: max_resumes_per_hour = 10
+    pub max_resumes_per_hour: u32,
+    /// Hours to wait before the single resume after a provider quota runs
+    /// out, when the provider did not say when it resets.
+    /// Example: quota_retry_after_hours = 6
+    pub quota_retry_after_hours: u64,
+    /// Coordinate with daemons on other machines sharing the session
+    /// directory through a lease file in the session's `.palingenesis/`.
+    /// Example: cross_host_lease = true
+    pub cross_host_lease: bool,
+    /// How long a cross-host lease stays valid without renewal (seconds).
+    /// Example: lease_ttl_secs = 300
+    pub lease_ttl_secs: u64,
+    /// System conditions a resume waits for before its strategy runs.
+    pub gates: ResumeGatesConfig,
+    /// Where new sessions after context exhaustion run.
+    pub new_session: NewSessionResumeConfig,
+    /// External commands that resume matching stops instead of the built-in strategies.
+    /// Example: [[resume.custom_strategies]]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom_strategies: Vec<CustomStrategyConfig>,
 }
 
 impl Default for ResumeConfig {
@@ -253,29 +900,586 @@ impl Default for ResumeConfig {
             max_retries: 10,
             jitter: true,
             backup_count: 10,
+            backup_dir: None,
+            backup_filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
+            backup_dedupe: true,
+            exclude_sessions: Vec::new(),
+            restore_deleted_from_backup: false,
+            enforce_model: false,
+            capture_git_context: false,
+            max_next_step_bytes: 64 * 1024,
+            maintenance_windows: Vec::new(),
+            archive_on_complete: false,
+            failure_bundle_count: 20,
+            incident_count: 50,
+            min_resume_interval_secs: 120,
+            max_resumes_per_hour: 10,
+            quota_retry_after_hours: 6,
+            cross_host_lease: false,
+            lease_ttl_secs: 300,
+            gates: ResumeGatesConfig::default(),
+            new_session: NewSessionResumeConfig::default(),
+            custom_strategies: Vec::new(),
+        }
+    }
+}
+
+/// Resume gating conditions (`[resume.gates]`).
+///
+/// No condition is set by default, so resumes are never gated.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ResumeGatesConfig {
+    /// Defer resumes while the 1-minute load average is above this.
+    /// Example: max_load_average = 4.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_load_average: Option<f64>,
+    /// Defer resumes while running on battery.
+    /// Example: require_ac_power = true
+    pub require_ac_power: bool,
+    /// Defer resumes while less than this much memory is available (MiB).
+    /// Example: min_free_memory_mb = 1024
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_free_memory_mb: Option<u64>,
+    /// How often a deferred resume re-checks the conditions (seconds).
+    /// Example: recheck_interval_secs = 60
+    pub recheck_interval_secs: u64,
+    /// Longest a resume waits for the conditions (seconds).
+    /// Example: max_deferral_secs = 3600
+    pub max_deferral_secs: u64,
+    /// What happens once `max_deferral_secs` has passed: "proceed" or "abandon".
+    /// Example: on_max_deferral = "abandon"
+    pub on_max_deferral: MaxDeferralAction,
+}
+
+impl Default for ResumeGatesConfig {
+    fn default() -> Self {
+        Self {
+            max_load_average: None,
+            require_ac_power: false,
+            min_free_memory_mb: None,
+            recheck_interval_secs: 60,
+            max_deferral_secs: 3600,
+            on_max_deferral: MaxDeferralAction::Proceed,
         }
     }
 }
 
+/// What a gated resume does when its conditions are still unmet at `max_deferral_secs`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxDeferralAction {
+    /// Run the resume anyway.
+    #[default]
+    Proceed,
+    /// Skip the resume.
+    Abandon,
+}
+
+/// A resume strategy run as an external command (`[[resume.custom_strategies]]`).
+///
+/// The first entry whose `match` block fits a stop is used instead of the
+/// built-in strategy for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomStrategyConfig {
+    /// Name used in logs, audit entries and metrics; may not be a built-in strategy's.
+    /// Example: name = "tmux-restart"
+    pub name: String,
+    /// Shell command; `{session}`, `{stop_reason}` and `{attempt}` are replaced with quoted values.
+    /// Example: command = "~/bin/restart-in-tmux {session}"
+    pub command: String,
+    /// Seconds the command may run before it is killed.
+    /// Example: timeout_secs = 120
+    #[serde(default = "default_custom_strategy_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Stops this strategy handles.
+    #[serde(default, rename = "match")]
+    pub matches: CustomStrategyMatch,
+}
+
+fn default_custom_strategy_timeout_secs() -> u64 {
+    300
+}
+
+/// Stops a custom strategy handles; every condition that is set must hold.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CustomStrategyMatch {
+    /// Stop reasons: "rate_limit", "quota_exhausted", "context_exhausted" or "unknown" (empty matches all).
+    /// Example: stop_reasons = ["context_exhausted"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_reasons: Vec<String>,
+    /// Session path glob, with the syntax of `resume.exclude_sessions`.
+    /// Example: session = "**/infra/**"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+}
+
+/// A new session started on a recurring schedule (`[[schedules]]`).
+///
+/// Exactly one of `cron` and the `daily`/`weekdays` shorthands sets when it fires.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleConfig {
+    /// Name used by `palingenesis schedules run`, in logs and in audit entries.
+    /// Example: name = "ci-triage"
+    pub name: String,
+    /// Five-field cron expression (minute hour day-of-month month day-of-week).
+    /// Example: cron = "0 6 * * 1-5"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    /// Every day at this time (HH:MM).
+    /// Example: daily = "06:00"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<String>,
+    /// Monday to Friday at this time (HH:MM).
+    /// Example: weekdays = "06:00"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekdays: Option<String>,
+    /// Whether times are local time or UTC.
+    /// Example: timezone = "utc"
+    #[serde(default)]
+    pub timezone: ScheduleTimezone,
+    /// Directory the session starts in; relative paths are under `monitoring.session_dir`.
+    /// Example: workdir = "ci"
+    #[serde(default)]
+    pub workdir: PathBuf,
+    /// Prompt the session starts with; `{schedule}`, `{date}`, `{time}` and
+    /// `{workdir}` are replaced.
+    /// Example: prompt = "Triage the CI failures since {date}"
+    pub prompt: String,
+    /// Assistant that starts the session (defaults to the first of `monitoring.assistants`).
+    /// Example: assistant = "claude-code"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assistant: Option<String>,
+    /// Model to start the session on.
+    /// Example: model = "anthropic/claude-sonnet-4"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Disabled schedules never fire on their own but can still be run by hand.
+    #[serde(default = "default_schedule_enabled")]
+    pub enabled: bool,
+    /// Skip a firing while a session in `workdir` is still active.
+    #[serde(default = "default_schedule_skip_if_active")]
+    pub skip_if_active: bool,
+    /// Run the most recent firing missed while the daemon was down once on startup.
+    #[serde(default)]
+    pub catch_up: bool,
+}
+
+/// Time zone schedule times are read in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleTimezone {
+    #[default]
+    Local,
+    Utc,
+}
+
+/// New-session workspace configuration (`[resume.new_session]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NewSessionResumeConfig {
+    /// Run new sessions in the original workspace or in a fresh git worktree.
+    /// Example: workspace_mode = "worktree"
+    pub workspace_mode: WorkspaceMode,
+    /// Directory new worktrees are created in (defaults to `<state dir>/worktrees`).
+    /// Example: worktree_root = "/home/user/worktrees"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_root: Option<PathBuf>,
+    /// Prefix of the branch created for each worktree.
+    /// Example: branch_prefix = "palingenesis"
+    pub branch_prefix: String,
+    /// What the `{context}` prompt placeholder holds.
+    pub context: PromptContextConfig,
+}
+
+impl Default for NewSessionResumeConfig {
+    fn default() -> Self {
+        Self {
+            workspace_mode: WorkspaceMode::InPlace,
+            worktree_root: None,
+            branch_prefix: "palingenesis".to_string(),
+            context: PromptContextConfig::default(),
+        }
+    }
+}
+
+/// Sections of the `{context}` prompt placeholder (`[resume.new_session.context]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PromptContextConfig {
+    /// Sections in order of priority: over `max_chars`, the last are trimmed
+    /// first. Each is a name or a table with a cap and an on/off switch.
+    /// Example: sections = ["previous_session_path", "session_tail:20", { section = "next_step_content", max_chars = 2000 }]
+    pub sections: Vec<ContextSectionConfig>,
+    /// Characters of the whole context (0 = unlimited).
+    /// Example: max_chars = 4000
+    pub max_chars: usize,
+}
+
+impl Default for PromptContextConfig {
+    fn default() -> Self {
+        Self {
+            sections: [
+                ContextSection::PreviousSessionPath,
+                ContextSection::StepsCompleted,
+                ContextSection::LastStep,
+                ContextSection::RemainingSteps,
+                ContextSection::Model,
+                ContextSection::StopReason,
+                ContextSection::NextStepContent,
+            ]
+            .into_iter()
+            .map(ContextSectionConfig::from)
+            .collect(),
+            max_chars: 0,
+        }
+    }
+}
+
+/// One entry of `resume.new_session.context.sections`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "SectionEntry", into = "SectionEntry")]
+pub struct ContextSectionConfig {
+    pub section: ContextSection,
+    /// Characters this section may take (`None` = no cap of its own).
+    pub max_chars: Option<usize>,
+    /// Left out of the context when false.
+    pub enabled: bool,
+}
+
+impl From<ContextSection> for ContextSectionConfig {
+    fn from(section: ContextSection) -> Self {
+        Self {
+            section,
+            max_chars: None,
+            enabled: true,
+        }
+    }
+}
+
+/// A section name, or a table when a cap or switch is set. Names are parsed
+/// after the untagged match so a bad one reports why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SectionEntry {
+    Name(String),
+    Table {
+        section: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_chars: Option<usize>,
+        #[serde(default = "default_section_enabled")]
+        enabled: bool,
+    },
+}
+
+impl TryFrom<SectionEntry> for ContextSectionConfig {
+    type Error = String;
+
+    fn try_from(entry: SectionEntry) -> Result<Self, Self::Error> {
+        Ok(match entry {
+            SectionEntry::Name(section) => section.parse::<ContextSection>()?.into(),
+            SectionEntry::Table {
+                section,
+                max_chars,
+                enabled,
+            } => Self {
+                section: section.parse()?,
+                max_chars,
+                enabled,
+            },
+        })
+    }
+}
+
+impl From<ContextSectionConfig> for SectionEntry {
+    fn from(config: ContextSectionConfig) -> Self {
+        if config.max_chars.is_none() && config.enabled {
+            return SectionEntry::Name(config.section.to_string());
+        }
+        SectionEntry::Table {
+            section: config.section.to_string(),
+            max_chars: config.max_chars,
+            enabled: config.enabled,
+        }
+    }
+}
+
+/// Lines of the session file in a `session_tail` section without a count.
+pub const DEFAULT_SESSION_TAIL_LINES: usize = 20;
+
+/// Content a `{context}` section renders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ContextSection {
+    /// Path of the session that stopped.
+    PreviousSessionPath,
+    /// Steps the stopped session completed.
+    StepsCompleted,
+    /// Step the stopped session was last on.
+    LastStep,
+    /// Steps left according to Next-step.md.
+    RemainingSteps,
+    /// Model the stopped session ran on.
+    Model,
+    /// Why the session stopped.
+    StopReason,
+    /// Next-step.md content, or the step description without it.
+    NextStepContent,
+    /// Last lines of the session file (`session_tail:N`).
+    SessionTail(usize),
+    /// Fixed text (`custom:<text>`).
+    Custom(String),
+}
+
+impl ContextSection {
+    /// Name without arguments, e.g. `session_tail`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ContextSection::PreviousSessionPath => "previous_session_path",
+            ContextSection::StepsCompleted => "steps_completed",
+            ContextSection::LastStep => "last_step",
+            ContextSection::RemainingSteps => "remaining_steps",
+            ContextSection::Model => "model",
+            ContextSection::StopReason => "stop_reason",
+            ContextSection::NextStepContent => "next_step_content",
+            ContextSection::SessionTail(_) => "session_tail",
+            ContextSection::Custom(_) => "custom",
+        }
+    }
+}
+
+impl std::str::FromStr for ContextSection {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = match value.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument)),
+            None => (value.trim(), None),
+        };
+        let section = match (name, argument) {
+            ("previous_session_path", None) => ContextSection::PreviousSessionPath,
+            ("steps_completed", None) => ContextSection::StepsCompleted,
+            ("last_step", None) => ContextSection::LastStep,
+            ("remaining_steps", None) => ContextSection::RemainingSteps,
+            ("model", None) => ContextSection::Model,
+            ("stop_reason", None) => ContextSection::StopReason,
+            ("next_step_content", None) => ContextSection::NextStepContent,
+            ("session_tail", None) => ContextSection::SessionTail(DEFAULT_SESSION_TAIL_LINES),
+            ("session_tail", Some(lines)) => match lines.trim().parse() {
+                Ok(lines) if lines > 0 => ContextSection::SessionTail(lines),
+                _ => {
+                    return Err(format!(
+                        "invalid line count in {value:?}: expected session_tail:<lines>"
+                    ));
+                }
+            },
+            ("custom", Some(text)) => ContextSection::Custom(text.to_string()),
+            _ => {
+                return Err(format!(
+                    "unknown context section {value:?}: expected previous_session_path, \
+                     steps_completed, last_step, remaining_steps, model, stop_reason, \
+                     next_step_content, session_tail:<lines> or custom:<text>"
+                ));
+            }
+        };
+        Ok(section)
+    }
+}
+
+impl TryFrom<String> for ContextSection {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ContextSection> for String {
+    fn from(section: ContextSection) -> Self {
+        section.to_string()
+    }
+}
+
+impl fmt::Display for ContextSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextSection::SessionTail(lines) => write!(f, "session_tail:{lines}"),
+            ContextSection::Custom(text) => write!(f, "custom:{text}"),
+            section => f.write_str(section.kind()),
+        }
+    }
+}
+
+/// Where a new session runs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceMode {
+    /// In the original workspace.
+    #[default]
+    InPlace,
+    /// In a new git worktree branched from the workspace's HEAD.
+    Worktree,
+}
+
 /// Notification channel configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
 pub struct NotificationsConfig {
     /// Enable notifications globally.
     /// Example: enabled = false
     pub enabled: bool,
-    /// Webhook notification configuration.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub webhook: Option<WebhookConfig>,
-    /// ntfy.sh notification configuration.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ntfy: Option<NtfyConfig>,
+    /// Webhook endpoints: one `[notifications.webhook]` table or several
+    /// `[[notifications.webhook]]` entries.
+    #[serde(
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub webhook: Vec<WebhookConfig>,
+    /// ntfy.sh topics: one `[notifications.ntfy]` table or several
+    /// `[[notifications.ntfy]]` entries.
+    #[serde(
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub ntfy: Vec<NtfyConfig>,
     /// Discord notification configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub discord: Option<DiscordConfig>,
     /// Slack notification configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub slack: Option<SlackConfig>,
+    /// Channel that urgent events are sent to first: a channel name ("ntfy",
+    /// "discord", "slack", "webhook", or a target's `name`), or "auto" to pick
+    /// the lowest-latency channel.
+    /// Example: primary_channel = "ntfy"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_channel: Option<String>,
+    /// Severities that go through the primary channel before the others.
+    /// Example: urgent_severities = ["critical"]
+    pub urgent_severities: Vec<String>,
+    /// How long to wait for the primary channel before promoting the next one (milliseconds).
+    /// Example: primary_timeout_ms = 3000
+    pub primary_timeout_ms: u64,
+    /// Allow notification URLs on loopback, private, or link-local addresses.
+    /// Example: allow_private_networks = false
+    pub allow_private_networks: bool,
+    /// Delivery decisions kept for `GET /api/v1/notifications/recent`.
+    /// Example: history_size = 200
+    pub history_size: usize,
+    /// Also append delivery decisions to this JSON-lines file (reloaded on start).
+    /// Example: history_file = "/var/lib/palingenesis/notifications.jsonl"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_file: Option<PathBuf>,
+    /// Public base URL of the HTTP API, used for one-time acknowledgment links.
+    /// Example: ack_base_url = "https://laptop.example.net:7654"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ack_base_url: Option<String>,
+    /// How long an unresolved incident can be acknowledged (seconds).
+    /// Example: ack_ttl_secs = 86400
+    pub ack_ttl_secs: u64,
+    /// Acknowledging an incident also pauses automatic resumes of its session.
+    /// Example: ack_pauses_resume = true
+    pub ack_pauses_resume: bool,
+    /// Re-resolve `${file:...}` and `${keyring:...}` channel credentials
+    /// this often (seconds); 0 only re-resolves on `ROTATE_SECRET` or after
+    /// an auth failure.
+    /// Example: secret_refresh_secs = 300
+    pub secret_refresh_secs: u64,
+    /// Channels that get events without routing tags (daemon lifecycle,
+    /// untagged sessions): "all", or "untargeted" for channels without
+    /// `match_tags`.
+    /// Example: untagged_route = "untargeted"
+    pub untagged_route: UntaggedRoute,
+    /// Severities sent even to muted channels and event types
+    /// (`MUTE_CHANNEL`, `MUTE_EVENT`).
+    /// Example: mute_exempt_severities = ["critical"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mute_exempt_severities: Vec<String>,
+    /// Tiered escalation of unresolved incidents.
+    #[serde(skip_serializing_if = "EscalationConfig::is_empty")]
+    pub escalation: EscalationConfig,
+}
+
+/// Where events without routing tags go (`notifications.untagged_route`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UntaggedRoute {
+    /// Every channel, whatever its `match_tags`.
+    #[default]
+    All,
+    /// Only channels without `match_tags`.
+    Untargeted,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook: Vec::new(),
+            ntfy: Vec::new(),
+            discord: None,
+            slack: None,
+            primary_channel: None,
+            urgent_severities: vec!["critical".to_string()],
+            primary_timeout_ms: 3000,
+            allow_private_networks: false,
+            history_size: 200,
+            history_file: None,
+            ack_base_url: None,
+            ack_ttl_secs: 86400,
+            ack_pauses_resume: false,
+            secret_refresh_secs: 0,
+            untagged_route: UntaggedRoute::All,
+            mute_exempt_severities: Vec::new(),
+            escalation: EscalationConfig::default(),
+        }
+    }
+}
+
+/// Escalation policy for incidents that stay open
+/// (`[notifications.escalation]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EscalationConfig {
+    /// Tiers in order; each is entered `after_secs` after the incident opened.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<EscalationTier>,
+    /// How often open incidents are checked against the tiers (seconds).
+    /// Example: check_interval_secs = 30
+    pub check_interval_secs: u64,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            tiers: Vec::new(),
+            check_interval_secs: 30,
+        }
+    }
+}
+
+impl EscalationConfig {
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+}
+
+/// One `[[notifications.escalation.tiers]]` entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EscalationTier {
+    /// Time from the start of the incident until this tier is entered (seconds).
+    /// Example: after_secs = 1800
+    #[serde(default)]
+    pub after_secs: u64,
+    /// Channels notified on entering the tier, by name ("ntfy", "slack", a
+    /// target's `name`, ...).
+    /// Example: channels = ["slack"]
+    pub channels: Vec<String>,
+    /// Text put before the tier's message, e.g. a mention.
+    /// Example: prefix = "<!here>"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
 }
 
 /// Bot command configuration.
@@ -302,6 +1506,14 @@ pub struct BotConfig {
     pub slack_signing_secret: Option<String>,
     /// Authorized user list across platforms.
     pub authorized_users: Vec<AuthorizedUser>,
+    /// Address the bot webhooks listen on (defaults to `daemon.http_bind`).
+    /// Example: http_bind = "0.0.0.0"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_bind: Option<String>,
+    /// Port the bot webhooks listen on (defaults to `daemon.http_port`).
+    /// Example: http_port = 8443
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_port: Option<u16>,
 }
 
 impl Default for BotConfig {
@@ -313,7 +1525,22 @@ impl Default for BotConfig {
             discord_public_key: None,
             slack_signing_secret: None,
             authorized_users: Vec::new(),
+            http_bind: None,
+            http_port: None,
+        }
+    }
+}
+
+impl BotConfig {
+    /// Bind address and port of a listener serving only the bot webhooks;
+    /// `None` while bots are disabled or share the HTTP API's address.
+    pub fn separate_listener(&self, daemon: &DaemonConfig) -> Option<(String, u16)> {
+        if !self.enabled {
+            return None;
         }
+        let bind = self.http_bind.as_deref().unwrap_or(&daemon.http_bind);
+        let port = self.http_port.unwrap_or(daemon.http_port);
+        (bind != daemon.http_bind || port != daemon.http_port).then(|| (bind.to_string(), port))
     }
 }
 
@@ -332,9 +1559,39 @@ pub enum BotPlatform {
     Slack,
 }
 
+impl NotificationsConfig {
+    /// Whether any channel uses the `[[...]]` array form.
+    pub fn has_target_arrays(&self) -> bool {
+        self.webhook.len() > 1 || self.ntfy.len() > 1
+    }
+}
+
+/// Accept either a single table or an array of tables.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
 /// Webhook notification configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct WebhookConfig {
+    /// Name used in logs, metrics and `primary_channel` (default: "webhook").
+    /// Example: name = "ops-bridge"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// Webhook URL.
     /// Example: url = "https://example.com/hooks"
     pub url: String,
@@ -342,11 +1599,57 @@ pub struct WebhookConfig {
     /// Example: headers = { Authorization = "Bearer token" }
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
+    /// Sign each request with HMAC-SHA256 of "<timestamp>.<body>" in the
+    /// `X-Palingenesis-Signature` header (unsigned if not set).
+    /// Example: signing_secret = "${file:/run/secrets/webhook-signing}"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    /// Only send events with these severities (default: all).
+    /// Example: severities = ["error", "critical"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub severities: Vec<String>,
+    /// Only send these event types (default: all).
+    /// Example: events = ["resume_failed", "session_stopped"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Only send events whose session has one of these routing tags
+    /// (sidecar `tags`; default: all).
+    /// Example: match_tags = ["proj-a"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_tags: Vec<String>,
+    /// Never send events whose session has one of these routing tags.
+    /// Example: exclude_tags = ["experiments"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+}
+
+impl WebhookConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            url: url.into(),
+            headers: None,
+            signing_secret: None,
+            severities: Vec::new(),
+            events: Vec::new(),
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+        }
+    }
+
+    /// Channel name: `name`, or "webhook".
+    pub fn channel_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("webhook")
+    }
 }
 
 /// ntfy.sh notification configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NtfyConfig {
+    /// Name used in logs, metrics and `primary_channel` (default: "ntfy").
+    /// Example: name = "personal"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// ntfy topic name.
     /// Example: topic = "palingenesis"
     pub topic: String,
@@ -358,6 +1661,58 @@ pub struct NtfyConfig {
     /// Example: priority = "high"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<String>,
+    /// Access token for protected topics.
+    /// Example: token = "tk_..."
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Only send events with these severities (default: all).
+    /// Example: severities = ["critical"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub severities: Vec<String>,
+    /// Only send these event types (default: all).
+    /// Example: events = ["resume_succeeded"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Only send events whose session has one of these routing tags
+    /// (sidecar `tags`; default: all).
+    /// Example: match_tags = ["proj-a"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_tags: Vec<String>,
+    /// Never send events whose session has one of these routing tags.
+    /// Example: exclude_tags = ["experiments"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+    /// Language of this topic's messages (default: `daemon.locale`).
+    /// Example: locale = "en"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+    /// Path rendering for this channel (default: `daemon.path_display`).
+    /// Example: path_display = "redacted"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_display: Option<PathDisplayMode>,
+}
+
+impl NtfyConfig {
+    pub fn new(topic: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            topic: topic.into(),
+            server: None,
+            priority: None,
+            token: None,
+            severities: Vec::new(),
+            events: Vec::new(),
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            locale: None,
+            path_display: None,
+        }
+    }
+
+    /// Channel name: `name`, or "ntfy".
+    pub fn channel_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("ntfy")
+    }
 }
 
 /// Discord webhook notification configuration.
@@ -366,6 +1721,23 @@ pub struct DiscordConfig {
     /// Discord webhook URL.
     /// Example: webhook_url = "https://discord.com/api/webhooks/..."
     pub webhook_url: String,
+    /// Language of Discord messages (default: `daemon.locale`).
+    /// Example: locale = "ja"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+    /// Path rendering for this channel (default: `daemon.path_display`).
+    /// Example: path_display = "redacted"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_display: Option<PathDisplayMode>,
+    /// Only send events whose session has one of these routing tags
+    /// (sidecar `tags`; default: all).
+    /// Example: match_tags = ["proj-a"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_tags: Vec<String>,
+    /// Never send events whose session has one of these routing tags.
+    /// Example: exclude_tags = ["experiments"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
 }
 
 /// Slack webhook notification configuration.
@@ -374,6 +1746,23 @@ pub struct SlackConfig {
     /// Slack webhook URL.
     /// Example: webhook_url = "https://hooks.slack.com/services/..."
     pub webhook_url: String,
+    /// Language of Slack messages (default: `daemon.locale`).
+    /// Example: locale = "ja"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+    /// Path rendering for this channel (default: `daemon.path_display`).
+    /// Example: path_display = "redacted"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_display: Option<PathDisplayMode>,
+    /// Only send events whose session has one of these routing tags
+    /// (sidecar `tags`; default: all).
+    /// Example: match_tags = ["proj-a"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_tags: Vec<String>,
+    /// Never send events whose session has one of these routing tags.
+    /// Example: exclude_tags = ["experiments"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
 }
 
 /// OpenTelemetry configuration.
@@ -412,6 +1801,41 @@ pub struct OtelConfig {
     /// Example: metrics_enabled = true
     #[serde(default = "default_metrics_enabled")]
     pub metrics_enabled: bool,
+    /// Push metrics instead of (or as well as) being scraped: "off" or "pushgateway".
+    /// Example: push_mode = "pushgateway"
+    pub push_mode: MetricsPushMode,
+    /// Pushgateway base URL.
+    /// Example: push_endpoint = "https://pushgateway.example.com:9091"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push_endpoint: Option<String>,
+    /// Seconds between pushes.
+    /// Example: push_interval_secs = 60
+    pub push_interval_secs: u64,
+    /// `job` grouping label; the host name is added as `instance`.
+    /// Example: push_job_name = "palingenesis"
+    pub push_job_name: String,
+    /// Basic auth user for the Pushgateway.
+    /// Example: push_username = "metrics"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push_username: Option<String>,
+    /// Basic auth password for the Pushgateway.
+    /// Example: push_password = "secret"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push_password: Option<String>,
+    /// Delete the pushed group when the daemon stops cleanly.
+    /// Example: push_delete_on_shutdown = true
+    pub push_delete_on_shutdown: bool,
+}
+
+/// How metrics leave the daemon besides the scrape endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsPushMode {
+    /// Only expose `/api/v1/metrics`.
+    #[default]
+    Off,
+    /// Periodically push to a Prometheus Pushgateway.
+    Pushgateway,
 }
 
 impl Default for OtelConfig {
@@ -426,6 +1850,13 @@ impl Default for OtelConfig {
             logs: false,
             metrics: true,
             metrics_enabled: default_metrics_enabled(),
+            push_mode: MetricsPushMode::Off,
+            push_endpoint: None,
+            push_interval_secs: 60,
+            push_job_name: "palingenesis".to_string(),
+            push_username: None,
+            push_password: None,
+            push_delete_on_shutdown: true,
         }
     }
 }
@@ -446,10 +1877,26 @@ fn default_metrics_enabled() -> bool {
     true
 }
 
+fn default_section_enabled() -> bool {
+    true
+}
+
+fn default_schedule_enabled() -> bool {
+    true
+}
+
+fn default_schedule_skip_if_active() -> bool {
+    true
+}
+
 fn default_manual_restart_time_seconds() -> u64 {
     300
 }
 
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::Config;
@@ -468,6 +1915,20 @@ mod tests {
         assert_eq!(config.metrics.manual_restart_time_seconds, 900);
     }
 
+    #[test]
+    fn test_otel_push_settings_parsing() {
+        let config: Config = toml::from_str(
+            "[otel]\npush_mode = \"pushgateway\"\npush_endpoint = \"http://gw:9091\"\npush_interval_secs = 15\n",
+        )
+        .unwrap();
+        let otel = config.otel.expect("otel config");
+        assert_eq!(otel.push_mode, super::MetricsPushMode::Pushgateway);
+        assert_eq!(otel.push_endpoint.as_deref(), Some("http://gw:9091"));
+        assert_eq!(otel.push_interval_secs, 15);
+        assert_eq!(otel.push_job_name, "palingenesis");
+        assert!(otel.push_delete_on_shutdown);
+    }
+
     #[test]
     fn test_otel_defaults_apply() {
         let config: Config = toml::from_str("[otel]\nenabled = true\n").unwrap();
@@ -504,5 +1965,15 @@ auto_restart = false\nrestart_delay_ms = 5000\nhealth_check_interval = 2500\n",
         assert!(!config.opencode.auto_restart);
         assert_eq!(config.opencode.restart_delay_ms, 5000);
         assert_eq!(config.opencode.health_check_interval, 2500);
+        assert!(!config.opencode.auto_discover);
+    }
+
+    #[test]
+    fn test_opencode_auto_discover_parsing() {
+        let config: Config =
+            toml::from_str("[opencode]\nserve_port = 0\nauto_discover = true\n").unwrap();
+        assert_eq!(config.opencode.serve_port, 0);
+        assert!(config.opencode.auto_discover);
+        assert!(config.opencode.port_discovery_enabled());
     }
 }
@@ -0,0 +1,602 @@
+//! Secret placeholders in the config file.
+//!
+//! String values may reference a secret instead of holding it:
+//!
+//! - `${keyring:service/key}`: an entry in the OS keychain (Secret Service on
+//!   Linux, Keychain on macOS, Credential Manager on Windows)
+//! - `${file:/path/to/secret}`: the contents of a file, without the trailing
+//!   newline
+//! - `${env:NAME}`: an environment variable of the process loading the config
+//!
+//! Placeholders are resolved on the raw TOML before it is deserialized, so
+//! every string field supports them. The keychain backend needs the
+//! `keyring` cargo feature; without it, configs that use keyring
+//! placeholders fail to load with a clear error.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::config::Paths;
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$\{(keyring|file|env):([^}]*)\}").expect("valid placeholder regex")
+});
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Invalid keyring reference {0:?}; expected service/key")]
+    InvalidReference(String),
+
+    #[error("Empty {0} placeholder; expected ${{{0}:...}} with a value")]
+    EmptyPlaceholder(&'static str),
+
+    #[error("No keyring entry for {0}; add it with `palingenesis secret set {0}`")]
+    NotFound(SecretRef),
+
+    #[error("Keyring backend unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("{0} references the OS keychain but this build lacks the `keyring` feature")]
+    FeatureDisabled(String),
+
+    #[error("Failed to read secret file {path}: {source}")]
+    File { path: PathBuf, source: io::Error },
+
+    #[error("Environment variable {0} is not set")]
+    EnvUnset(String),
+
+    #[error("Failed to update secret index {path}: {source}")]
+    Index { path: PathBuf, source: io::Error },
+}
+
+/// A `service/key` pair naming one keychain entry.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SecretRef {
+    pub service: String,
+    pub key: String,
+}
+
+impl SecretRef {
+    /// Parse `service/key`; the key may itself contain `/`.
+    pub fn parse(reference: &str) -> Result<Self, SecretError> {
+        let invalid = || SecretError::InvalidReference(reference.to_string());
+        let (service, key) = reference.trim().split_once('/').ok_or_else(invalid)?;
+        if service.is_empty() || key.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            service: service.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.service, self.key)
+    }
+}
+
+/// Where a placeholder's value comes from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SecretSource {
+    Keyring(SecretRef),
+    File(PathBuf),
+    Env(String),
+}
+
+impl SecretSource {
+    fn parse(kind: &str, reference: &str) -> Result<Self, SecretError> {
+        match kind {
+            "keyring" => SecretRef::parse(reference).map(Self::Keyring),
+            "file" if !reference.trim().is_empty() => {
+                Ok(Self::File(PathBuf::from(reference.trim())))
+            }
+            "file" => Err(SecretError::EmptyPlaceholder("file")),
+            _ if !reference.trim().is_empty() => Ok(Self::Env(reference.trim().to_string())),
+            _ => Err(SecretError::EmptyPlaceholder("env")),
+        }
+    }
+
+    /// Whether the value can change without the config file changing, so
+    /// re-resolving it later may pick up a rotated secret.
+    pub fn is_rotatable(&self) -> bool {
+        matches!(self, Self::Keyring(_) | Self::File(_))
+    }
+
+    fn read(&self, store: Option<&dyn SecretStore>) -> Result<String, SecretError> {
+        match self {
+            Self::Keyring(secret) => match store {
+                Some(store) => store.get(secret),
+                None => Err(SecretError::Unavailable(format!(
+                    "no keychain store to read {secret}"
+                ))),
+            },
+            Self::File(path) => fs::read_to_string(path)
+                .map(|contents| contents.trim_end_matches(['\r', '\n']).to_string())
+                .map_err(|source| SecretError::File {
+                    path: path.clone(),
+                    source,
+                }),
+            Self::Env(name) => std::env::var(name).map_err(|_| SecretError::EnvUnset(name.clone())),
+        }
+    }
+}
+
+impl std::fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keyring(secret) => write!(f, "keyring:{secret}"),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Env(name) => write!(f, "env:{name}"),
+        }
+    }
+}
+
+/// Storage for keychain-backed secrets.
+pub trait SecretStore {
+    fn get(&self, secret: &SecretRef) -> Result<String, SecretError>;
+    fn set(&self, secret: &SecretRef, value: &str) -> Result<(), SecretError>;
+    fn delete(&self, secret: &SecretRef) -> Result<(), SecretError>;
+}
+
+/// The platform keychain via the `keyring` crate.
+#[cfg(feature = "keyring")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeyringStore;
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    fn entry(secret: &SecretRef) -> Result<keyring::Entry, SecretError> {
+        keyring::Entry::new(&secret.service, &secret.key).map_err(|err| keyring_error(secret, err))
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretStore for KeyringStore {
+    fn get(&self, secret: &SecretRef) -> Result<String, SecretError> {
+        Self::entry(secret)?
+            .get_password()
+            .map_err(|err| keyring_error(secret, err))
+    }
+
+    fn set(&self, secret: &SecretRef, value: &str) -> Result<(), SecretError> {
+        Self::entry(secret)?
+            .set_password(value)
+            .map_err(|err| keyring_error(secret, err))
+    }
+
+    fn delete(&self, secret: &SecretRef) -> Result<(), SecretError> {
+        Self::entry(secret)?
+            .delete_credential()
+            .map_err(|err| keyring_error(secret, err))
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_error(secret: &SecretRef, err: keyring::Error) -> SecretError {
+    match err {
+        keyring::Error::NoEntry => SecretError::NotFound(secret.clone()),
+        keyring::Error::BadEncoding(_) => {
+            SecretError::Unavailable(format!("entry {secret} is not valid UTF-8"))
+        }
+        other => SecretError::Unavailable(other.to_string()),
+    }
+}
+
+/// The platform keychain, or why it can't be used.
+pub fn default_store(context: &str) -> Result<Box<dyn SecretStore>, SecretError> {
+    #[cfg(feature = "keyring")]
+    {
+        let _ = context;
+        Ok(Box::new(KeyringStore))
+    }
+    #[cfg(not(feature = "keyring"))]
+    {
+        Err(SecretError::FeatureDisabled(context.to_string()))
+    }
+}
+
+/// Every placeholder in `raw`, as `(dotted field path, source)`.
+///
+/// Arrays are reported at the array's own path, matching how provenance
+/// treats them as leaves.
+pub fn find_placeholders(raw: &toml::Value) -> Result<Vec<(String, SecretSource)>, SecretError> {
+    let mut found = Vec::new();
+    collect(raw, "", &mut found)?;
+    Ok(found)
+}
+
+fn collect(
+    value: &toml::Value,
+    path: &str,
+    found: &mut Vec<(String, SecretSource)>,
+) -> Result<(), SecretError> {
+    match value {
+        toml::Value::String(text) => {
+            for capture in PLACEHOLDER.captures_iter(text) {
+                found.push((
+                    path.to_string(),
+                    SecretSource::parse(&capture[1], &capture[2])?,
+                ));
+            }
+        }
+        toml::Value::Array(items) => {
+            let mut nested = Vec::new();
+            for item in items {
+                collect(item, path, &mut nested)?;
+            }
+            found.extend(
+                nested
+                    .into_iter()
+                    .map(|(_, source)| (path.to_string(), source)),
+            );
+        }
+        toml::Value::Table(table) => {
+            for (key, child) in table {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect(child, &child_path, found)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replace every placeholder in `raw` with its value, reading keyring
+/// entries from `store`.
+///
+/// Returns the resolved sources so callers can mark those fields as secret.
+pub fn resolve_placeholders(
+    raw: &mut toml::Value,
+    store: &dyn SecretStore,
+) -> Result<Vec<(String, SecretSource)>, SecretError> {
+    resolve_with(raw, Some(store))
+}
+
+fn resolve_with(
+    raw: &mut toml::Value,
+    store: Option<&dyn SecretStore>,
+) -> Result<Vec<(String, SecretSource)>, SecretError> {
+    let found = find_placeholders(raw)?;
+    if !found.is_empty() {
+        substitute(raw, store)?;
+    }
+    Ok(found)
+}
+
+fn substitute(value: &mut toml::Value, store: Option<&dyn SecretStore>) -> Result<(), SecretError> {
+    match value {
+        toml::Value::String(text) if PLACEHOLDER.is_match(text) => {
+            let mut resolved = String::with_capacity(text.len());
+            let mut last = 0;
+            for capture in PLACEHOLDER.captures_iter(text) {
+                let whole = capture.get(0).expect("capture 0 is the match");
+                resolved.push_str(&text[last..whole.start()]);
+                resolved.push_str(&SecretSource::parse(&capture[1], &capture[2])?.read(store)?);
+                last = whole.end();
+            }
+            resolved.push_str(&text[last..]);
+            *text = resolved;
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                substitute(item, store)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, child) in table.iter_mut() {
+                substitute(child, store)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Resolve placeholders, opening the platform keychain only when `raw`
+/// actually references it.
+pub fn resolve_with_default_store(
+    raw: &mut toml::Value,
+) -> Result<Vec<(String, SecretSource)>, SecretError> {
+    let found = find_placeholders(raw)?;
+    let keyring_field = found
+        .iter()
+        .find(|(_, source)| matches!(source, SecretSource::Keyring(_)))
+        .map(|(field, _)| field.clone());
+    match keyring_field {
+        Some(field) => {
+            let store = default_store(&field)?;
+            resolve_with(raw, Some(store.as_ref()))
+        }
+        None => resolve_with(raw, None),
+    }
+}
+
+/// Names (never values) of secrets stored through `palingenesis secret set`.
+///
+/// Keychains can't be enumerated portably, so `secret ls` reads this list.
+#[derive(Debug, Clone)]
+pub struct SecretIndex {
+    path: PathBuf,
+}
+
+impl SecretIndex {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Index in the state directory.
+    pub fn default_path() -> PathBuf {
+        Paths::state_dir().join("secrets.index")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn list(&self) -> Vec<SecretRef> {
+        let Ok(contents) = fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .filter_map(|line| SecretRef::parse(line).ok())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn add(&self, secret: &SecretRef) -> Result<(), SecretError> {
+        let mut entries: BTreeSet<_> = self.list().into_iter().collect();
+        entries.insert(secret.clone());
+        self.write(entries)
+    }
+
+    pub fn remove(&self, secret: &SecretRef) -> Result<(), SecretError> {
+        let mut entries: BTreeSet<_> = self.list().into_iter().collect();
+        entries.remove(secret);
+        self.write(entries)
+    }
+
+    fn write(&self, entries: BTreeSet<SecretRef>) -> Result<(), SecretError> {
+        let index_error = |source| SecretError::Index {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(index_error)?;
+        }
+        let contents = entries.iter().fold(String::new(), |mut out, entry| {
+            out.push_str(&entry.to_string());
+            out.push('\n');
+            out
+        });
+        fs::write(&self.path, contents).map_err(index_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        entries: RefCell<HashMap<SecretRef, String>>,
+    }
+
+    impl SecretStore for MemoryStore {
+        fn get(&self, secret: &SecretRef) -> Result<String, SecretError> {
+            self.entries
+                .borrow()
+                .get(secret)
+                .cloned()
+                .ok_or_else(|| SecretError::NotFound(secret.clone()))
+        }
+
+        fn set(&self, secret: &SecretRef, value: &str) -> Result<(), SecretError> {
+            self.entries
+                .borrow_mut()
+                .insert(secret.clone(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, secret: &SecretRef) -> Result<(), SecretError> {
+            self.entries.borrow_mut().remove(secret);
+            Ok(())
+        }
+    }
+
+    fn secret(reference: &str) -> SecretRef {
+        SecretRef::parse(reference).unwrap()
+    }
+
+    #[test]
+    fn parses_service_and_key() {
+        assert_eq!(
+            secret("palingenesis/slack/token"),
+            SecretRef {
+                service: "palingenesis".to_string(),
+                key: "slack/token".to_string(),
+            }
+        );
+        assert!(SecretRef::parse("no-slash").is_err());
+        assert!(SecretRef::parse("/key").is_err());
+        assert!(SecretRef::parse("service/").is_err());
+    }
+
+    #[test]
+    fn resolves_whole_and_embedded_placeholders() {
+        let store = MemoryStore::default();
+        store.set(&secret("pal/ntfy"), "tk_123").unwrap();
+        store.set(&secret("pal/hook"), "abc").unwrap();
+        let mut raw: toml::Value = toml::from_str(
+            r#"
+[notifications.ntfy]
+token = "${keyring:pal/ntfy}"
+
+[notifications.webhook]
+url = "https://example.com"
+headers = { Authorization = "Bearer ${keyring:pal/hook}" }
+"#,
+        )
+        .unwrap();
+
+        let resolved = resolve_placeholders(&mut raw, &store).unwrap();
+
+        assert_eq!(
+            raw["notifications"]["ntfy"]["token"].as_str(),
+            Some("tk_123")
+        );
+        assert_eq!(
+            raw["notifications"]["webhook"]["headers"]["Authorization"].as_str(),
+            Some("Bearer abc")
+        );
+        let fields: Vec<_> = resolved.iter().map(|(field, _)| field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "notifications.ntfy.token",
+                "notifications.webhook.headers.Authorization"
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders_in_arrays_report_the_array_path() {
+        let raw: toml::Value = toml::from_str(
+            r#"
+[[notifications.ntfy]]
+topic = "alerts"
+token = "${keyring:pal/ntfy}"
+"#,
+        )
+        .unwrap();
+
+        let found = find_placeholders(&raw).unwrap();
+
+        assert_eq!(
+            found,
+            vec![(
+                "notifications.ntfy".to_string(),
+                SecretSource::Keyring(secret("pal/ntfy"))
+            )]
+        );
+    }
+
+    #[test]
+    fn file_and_env_placeholders_resolve_without_a_keychain() {
+        let dir = tempfile::tempdir().unwrap();
+        let token = dir.path().join("ntfy-token");
+        fs::write(&token, "tk_from_file\n").unwrap();
+        let mut raw: toml::Value = toml::from_str(&format!(
+            "[notifications.ntfy]\ntoken = \"${{file:{}}}\"\ntopic = \"${{env:PATH}}\"\n",
+            token.display()
+        ))
+        .unwrap();
+
+        let resolved = resolve_with_default_store(&mut raw).unwrap();
+
+        assert_eq!(
+            raw["notifications"]["ntfy"]["token"].as_str(),
+            Some("tk_from_file")
+        );
+        assert_eq!(
+            raw["notifications"]["ntfy"]["topic"].as_str(),
+            std::env::var("PATH").ok().as_deref()
+        );
+        assert_eq!(resolved[0].1, SecretSource::File(token));
+        assert!(resolved[0].1.is_rotatable());
+        assert_eq!(resolved[1].1.to_string(), "env:PATH");
+        assert!(!resolved[1].1.is_rotatable());
+    }
+
+    #[test]
+    fn unreadable_file_and_unset_env_are_errors() {
+        let mut raw: toml::Value =
+            toml::from_str("token = \"${file:/nonexistent/palingenesis-secret}\"").unwrap();
+        assert!(matches!(
+            resolve_with_default_store(&mut raw),
+            Err(SecretError::File { .. })
+        ));
+
+        let mut raw: toml::Value =
+            toml::from_str("token = \"${env:PALINGENESIS_TEST_UNSET_SECRET}\"").unwrap();
+        assert!(matches!(
+            resolve_with_default_store(&mut raw),
+            Err(SecretError::EnvUnset(ref name)) if name == "PALINGENESIS_TEST_UNSET_SECRET"
+        ));
+    }
+
+    #[test]
+    fn missing_entry_names_the_reference() {
+        let store = MemoryStore::default();
+        let mut raw: toml::Value = toml::from_str("token = \"${keyring:pal/missing}\"").unwrap();
+
+        let err = resolve_placeholders(&mut raw, &store).unwrap_err();
+
+        assert!(matches!(err, SecretError::NotFound(ref r) if r.key == "missing"));
+        assert!(
+            err.to_string()
+                .contains("palingenesis secret set pal/missing")
+        );
+    }
+
+    #[test]
+    fn configs_without_placeholders_never_touch_the_store() {
+        let mut raw: toml::Value = toml::from_str("[daemon]\nlog_level = \"info\"\n").unwrap();
+
+        let resolved = resolve_with_default_store(&mut raw).unwrap();
+
+        assert!(resolved.is_empty());
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[test]
+    fn placeholders_without_feature_explain_why() {
+        let mut raw: toml::Value =
+            toml::from_str("[bot]\nslack_signing_secret = \"${keyring:pal/slack}\"\n").unwrap();
+
+        let err = resolve_with_default_store(&mut raw).unwrap_err();
+
+        assert!(
+            matches!(err, SecretError::FeatureDisabled(ref field) if field == "bot.slack_signing_secret")
+        );
+    }
+
+    #[cfg(feature = "keyring")]
+    #[test]
+    fn keyring_store_maps_missing_entries() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        let err = KeyringStore.get(&secret("pal/absent")).unwrap_err();
+
+        assert!(matches!(err, SecretError::NotFound(_)));
+    }
+
+    #[test]
+    fn index_lists_names_sorted_and_deduplicated() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = SecretIndex::new(dir.path().join("secrets.index"));
+
+        index.add(&secret("pal/b")).unwrap();
+        index.add(&secret("pal/a")).unwrap();
+        index.add(&secret("pal/b")).unwrap();
+        assert_eq!(index.list(), vec![secret("pal/a"), secret("pal/b")]);
+
+        index.remove(&secret("pal/a")).unwrap();
+        assert_eq!(index.list(), vec![secret("pal/b")]);
+    }
+}
@@ -1,6 +1,25 @@
+use std::collections::HashSet;
 use std::path::Path;
 
-use crate::config::schema::Config;
+use crate::config::Paths;
+use crate::config::resolved::{PathEnv, PathFlags, PathSettings, ResolvedPaths};
+use crate::config::schema::{
+    ClassificationConfig, Config, ContextSection, DebounceMode, GrpcConfig, McpConfig,
+    MetricsConfig, MetricsPushMode, OtelConfig, PromptContextConfig, WorkspaceMode,
+};
+use crate::daemon::schedules::ScheduleSpec;
+use crate::notify::url_guard::{UrlGuard, UrlGuardError};
+use crate::resume::assistant;
+use crate::resume::backup::validate_filename_template;
+use crate::resume::custom::{BUILTIN_STRATEGY_NAMES, MATCHABLE_STOP_REASONS};
+use crate::resume::maintenance::MaintenanceWindow;
+use crate::resume::sidecar::{known_tags, normalize_tags};
+use crate::util::content;
+
+/// Longest acknowledgment link lifetime (30 days).
+const MAX_ACK_TTL_SECS: u64 = 30 * 86_400;
+/// Longest time resource gates may hold a resume (7 days).
+const MAX_GATE_DEFERRAL_SECS: u64 = 7 * 86_400;
 
 #[derive(Debug, Default)]
 pub struct ValidationResult {
@@ -69,6 +88,22 @@ pub fn validate_config(config: &Config) -> ValidationResult {
         &mut errors,
         &mut warnings,
     );
+    for (field, dir) in [
+        ("daemon.state_dir", config.daemon.state_dir.as_deref()),
+        ("daemon.runtime_dir", config.daemon.runtime_dir.as_deref()),
+    ] {
+        if let Some(dir) = dir {
+            validate_dir_path(field, dir, &mut errors, &mut warnings);
+        }
+    }
+    let path_settings = PathSettings::from_config(config);
+    let resolved = ResolvedPaths::resolve(
+        &path_settings,
+        &PathEnv::from_process(),
+        &PathFlags::current(),
+        Paths::instance().as_deref(),
+    );
+    warnings.extend(resolved.warnings(&path_settings));
 
     validate_dir_path(
         "monitoring.session_dir",
@@ -85,6 +120,27 @@ pub fn validate_config(config: &Config) -> ValidationResult {
         });
     }
 
+    let monitoring = &config.monitoring;
+    if monitoring.debounce == DebounceMode::Adaptive {
+        if monitoring.debounce_min_ms == 0 {
+            errors.push(ValidationError {
+                field: "monitoring.debounce_min_ms".to_string(),
+                message: "Minimum debounce window must be positive".to_string(),
+                suggestion: Some("Use e.g. 50".to_string()),
+            });
+        }
+        if monitoring.debounce_max_ms < monitoring.debounce_min_ms {
+            errors.push(ValidationError {
+                field: "monitoring.debounce_max_ms".to_string(),
+                message: format!(
+                    "Maximum debounce window ({} ms) is below the minimum ({} ms)",
+                    monitoring.debounce_max_ms, monitoring.debounce_min_ms
+                ),
+                suggestion: None,
+            });
+        }
+    }
+
     if config.monitoring.auto_detect_interval_secs == 0 {
         errors.push(ValidationError {
             field: "monitoring.auto_detect_interval_secs".to_string(),
@@ -103,6 +159,22 @@ pub fn validate_config(config: &Config) -> ValidationResult {
         }
     }
 
+    if config.monitoring.scan_limit == 0 {
+        errors.push(ValidationError {
+            field: "monitoring.scan_limit".to_string(),
+            message: "Scan limit must be positive".to_string(),
+            suggestion: Some("Use the default of 200".to_string()),
+        });
+    }
+
+    if config.monitoring.classify_concurrency == 0 {
+        errors.push(ValidationError {
+            field: "monitoring.classify_concurrency".to_string(),
+            message: "Classification concurrency must be positive".to_string(),
+            suggestion: Some("Use the default of 2".to_string()),
+        });
+    }
+
     if config.opencode.health_check_interval == 0 {
         errors.push(ValidationError {
             field: "opencode.health_check_interval".to_string(),
@@ -119,16 +191,64 @@ pub fn validate_config(config: &Config) -> ValidationResult {
         });
     }
 
-    if config.opencode.serve_port == 0 {
-        errors.push(ValidationError {
+    if config.opencode.serve_port == 0 && !config.opencode.enabled {
+        warnings.push(ValidationWarning {
             field: "opencode.serve_port".to_string(),
-            message: "OpenCode serve port must be between 1 and 65535".to_string(),
-            suggestion: Some("Use a port between 1 and 65535".to_string()),
+            message: "serve_port = 0 requests port discovery but OpenCode monitoring is disabled"
+                .to_string(),
         });
     }
 
     validate_opencode_hostname(&config.opencode.serve_hostname, &mut errors);
 
+    for (field, value) in [
+        (
+            "daemon.job_normal_concurrency",
+            config.daemon.job_normal_concurrency,
+        ),
+        (
+            "daemon.job_background_concurrency",
+            config.daemon.job_background_concurrency,
+        ),
+    ] {
+        if value == 0 {
+            errors.push(ValidationError {
+                field: field.to_string(),
+                message: "Job concurrency cannot be zero (jobs would never run)".to_string(),
+                suggestion: Some("Use a value of at least 1".to_string()),
+            });
+        }
+    }
+
+    validate_http_tokens(config, &mut errors);
+
+    if config.daemon.reconcile.stale_after_secs == 0 {
+        errors.push(ValidationError {
+            field: "daemon.reconcile.stale_after_secs".to_string(),
+            message:
+                "Stale threshold cannot be zero (every open incident would be closed on startup)"
+                    .to_string(),
+            suggestion: Some("Use the default of 86400 seconds (one day)".to_string()),
+        });
+    }
+
+    if config.maintenance.interval_secs == 0 {
+        errors.push(ValidationError {
+            field: "maintenance.interval_secs".to_string(),
+            message: "GC interval cannot be zero".to_string(),
+            suggestion: Some("Use the default of 86400 seconds (one day)".to_string()),
+        });
+    }
+
+    if config.maintenance.safety_window_secs == 0 {
+        warnings.push(ValidationWarning {
+            field: "maintenance.safety_window_secs".to_string(),
+            message:
+                "Without a safety window the GC may delete artifacts that are still being written"
+                    .to_string(),
+        });
+    }
+
     if config.resume.base_delay_secs == 0 {
         errors.push(ValidationError {
             field: "resume.base_delay_secs".to_string(),
@@ -160,20 +280,172 @@ pub fn validate_config(config: &Config) -> ValidationResult {
         });
     }
 
-    if let Some(ref webhook) = config.notifications.webhook {
+    for (field, value, default) in [
+        (
+            "daemon.max_retained_content_chars",
+            config.daemon.max_retained_content_chars,
+            content::DEFAULT_MAX_RETAINED_CHARS,
+        ),
+        (
+            "classifier.max_evidence_chars",
+            config.classifier.max_evidence_chars,
+            content::DEFAULT_MAX_EVIDENCE_CHARS,
+        ),
+    ] {
+        if value == 0 {
+            errors.push(ValidationError {
+                field: field.to_string(),
+                message: "Content limit cannot be zero (all text would be cut)".to_string(),
+                suggestion: Some(format!("Use the default of {default} characters")),
+            });
+        }
+    }
+
+    if config.resume.quota_retry_after_hours == 0 {
+        errors.push(ValidationError {
+            field: "resume.quota_retry_after_hours".to_string(),
+            message: "Quota retry wait cannot be zero".to_string(),
+            suggestion: Some("Use the default of 6 hours".to_string()),
+        });
+    }
+
+    if config.resume.cross_host_lease && config.resume.lease_ttl_secs == 0 {
+        errors.push(ValidationError {
+            field: "resume.lease_ttl_secs".to_string(),
+            message: "Cross-host lease TTL cannot be zero".to_string(),
+            suggestion: Some("Use the default of 300 seconds".to_string()),
+        });
+    }
+
+    if config.resume.max_next_step_bytes == 0 {
+        errors.push(ValidationError {
+            field: "resume.max_next_step_bytes".to_string(),
+            message: "Next-step size limit cannot be zero".to_string(),
+            suggestion: Some("Use the default of 65536 bytes".to_string()),
+        });
+    }
+
+    if let Err(reason) = validate_filename_template(&config.resume.backup_filename_template) {
+        errors.push(ValidationError {
+            field: "resume.backup_filename_template".to_string(),
+            message: format!(
+                "Invalid backup filename template {:?}: {reason}",
+                config.resume.backup_filename_template
+            ),
+            suggestion: Some(
+                "Use e.g. \"{workspace}/{date}/{stem}-{timestamp}.{ext}\"".to_string(),
+            ),
+        });
+    }
+
+    for (index, spec) in config.resume.maintenance_windows.iter().enumerate() {
+        if let Err(err) = spec.parse::<MaintenanceWindow>() {
+            errors.push(ValidationError {
+                field: format!("resume.maintenance_windows[{index}]"),
+                message: format!("Invalid maintenance window {spec:?}: {err}"),
+                suggestion: Some(
+                    "Use \"Tue 02:00-04:00 UTC\" or \"2025-12-24T00:00:00Z/2025-12-26T00:00:00Z\""
+                        .to_string(),
+                ),
+            });
+        }
+    }
+
+    let gates = &config.resume.gates;
+    if let Some(load) = gates.max_load_average {
+        if !load.is_finite() || load <= 0.0 {
+            errors.push(ValidationError {
+                field: "resume.gates.max_load_average".to_string(),
+                message: format!("Load average threshold must be positive, got {load}"),
+                suggestion: Some("Use e.g. the number of CPU cores".to_string()),
+            });
+        }
+    }
+    if gates.recheck_interval_secs == 0 {
+        errors.push(ValidationError {
+            field: "resume.gates.recheck_interval_secs".to_string(),
+            message: "Recheck interval must be at least 1 second".to_string(),
+            suggestion: Some("Use e.g. 60".to_string()),
+        });
+    }
+    if gates.max_deferral_secs == 0 || gates.max_deferral_secs > MAX_GATE_DEFERRAL_SECS {
+        errors.push(ValidationError {
+            field: "resume.gates.max_deferral_secs".to_string(),
+            message: format!(
+                "Maximum deferral must be between 1 and {MAX_GATE_DEFERRAL_SECS} seconds"
+            ),
+            suggestion: Some("Use e.g. 3600".to_string()),
+        });
+    }
+
+    let new_session = &config.resume.new_session;
+    if new_session.workspace_mode == WorkspaceMode::Worktree {
+        let prefix = new_session.branch_prefix.trim_matches('/');
+        if prefix.is_empty() || prefix.contains(char::is_whitespace) || prefix.contains("..") {
+            errors.push(ValidationError {
+                field: "resume.new_session.branch_prefix".to_string(),
+                message: format!("Invalid branch prefix {:?}", new_session.branch_prefix),
+                suggestion: Some("Use a simple name such as \"palingenesis\"".to_string()),
+            });
+        }
+        if let Some(root) = &new_session.worktree_root {
+            if root.is_relative() {
+                errors.push(ValidationError {
+                    field: "resume.new_session.worktree_root".to_string(),
+                    message: "Worktree root must be an absolute path".to_string(),
+                    suggestion: None,
+                });
+            }
+        }
+    }
+
+    validate_prompt_context(&new_session.context, &mut errors, &mut warnings);
+
+    for pattern in &config.resume.exclude_sessions {
+        if pattern.trim().trim_start_matches('!').trim().is_empty() {
+            warnings.push(ValidationWarning {
+                field: "resume.exclude_sessions".to_string(),
+                message: "Empty exclusion pattern is ignored".to_string(),
+            });
+        }
+    }
+
+    validate_custom_strategies(config, &mut errors);
+    validate_schedules(config, &mut errors);
+
+    let guard = UrlGuard::from_config(&config.notifications);
+    let webhooks = &config.notifications.webhook;
+    for (index, webhook) in webhooks.iter().enumerate() {
+        let field = |key: &str| target_field("webhook", index, webhooks.len(), key);
         if !is_http_url(&webhook.url) {
             errors.push(ValidationError {
-                field: "notifications.webhook.url".to_string(),
+                field: field("url"),
                 message: "Webhook URL must start with http:// or https://".to_string(),
                 suggestion: None,
             });
+        } else {
+            validate_notification_target(&guard, &webhook.url, field("url"), &mut errors);
+        }
+        validate_target_filters(&webhook.severities, field("severities"), &mut errors);
+        if webhook
+            .signing_secret
+            .as_deref()
+            .is_some_and(|secret| secret.trim().is_empty())
+        {
+            errors.push(ValidationError {
+                field: field("signing_secret"),
+                message: "Signing secret is empty".to_string(),
+                suggestion: Some("Remove it to send unsigned requests".to_string()),
+            });
         }
     }
 
-    if let Some(ref ntfy) = config.notifications.ntfy {
+    let topics = &config.notifications.ntfy;
+    for (index, ntfy) in topics.iter().enumerate() {
+        let field = |key: &str| target_field("ntfy", index, topics.len(), key);
         if ntfy.topic.trim().is_empty() {
             errors.push(ValidationError {
-                field: "notifications.ntfy.topic".to_string(),
+                field: field("topic"),
                 message: "ntfy topic cannot be empty".to_string(),
                 suggestion: None,
             });
@@ -181,12 +453,41 @@ pub fn validate_config(config: &Config) -> ValidationResult {
         if let Some(ref server) = ntfy.server {
             if !is_http_url(server) {
                 errors.push(ValidationError {
-                    field: "notifications.ntfy.server".to_string(),
+                    field: field("server"),
                     message: "ntfy server must start with http:// or https://".to_string(),
                     suggestion: None,
                 });
+            } else {
+                validate_notification_target(&guard, server, field("server"), &mut errors);
             }
         }
+        validate_target_filters(&ntfy.severities, field("severities"), &mut errors);
+    }
+
+    if let Some(ref discord) = config.notifications.discord {
+        validate_notification_target(
+            &guard,
+            &discord.webhook_url,
+            "notifications.discord.webhook_url".to_string(),
+            &mut errors,
+        );
+    }
+    if let Some(ref slack) = config.notifications.slack {
+        validate_notification_target(
+            &guard,
+            &slack.webhook_url,
+            "notifications.slack.webhook_url".to_string(),
+            &mut errors,
+        );
+    }
+
+    validate_channel_names(config, &mut errors);
+    validate_tag_routes(config, &mut warnings);
+    validate_notification_priority(config, &mut errors);
+    validate_escalation(config, &mut errors);
+
+    if let Some(ref grpc) = config.daemon.grpc {
+        validate_grpc_config(grpc, &mut errors, &mut warnings);
     }
 
     if let Some(ref otel) = config.otel {
@@ -221,8 +522,14 @@ pub fn validate_config(config: &Config) -> ValidationResult {
                 suggestion: Some("Set sampling_ratio between 0.0 and 1.0".to_string()),
             });
         }
+
+        if otel.push_mode == MetricsPushMode::Pushgateway {
+            validate_push_settings(otel, &mut errors);
+        }
     }
 
+    validate_classifier(&config.classifier, &mut errors);
+
     if !(60..=1800).contains(&config.metrics.manual_restart_time_seconds) {
         errors.push(ValidationError {
             field: "metrics.manual_restart_time_seconds".to_string(),
@@ -230,129 +537,732 @@ pub fn validate_config(config: &Config) -> ValidationResult {
             suggestion: Some("Set a value between 60 and 1800 seconds".to_string()),
         });
     }
+    validate_cost_settings(&config.metrics, &mut errors);
 
     validate_bot_config(config, &mut errors, &mut warnings);
+    validate_feature_sections(config, &mut warnings);
 
     ValidationResult { errors, warnings }
 }
 
-fn validate_bot_config(
-    config: &Config,
+/// Warn about sections that are configured but compiled out of this binary.
+fn validate_feature_sections(config: &Config, warnings: &mut Vec<ValidationWarning>) {
+    let sections = [
+        (
+            config.daemon.http_enabled,
+            cfg!(feature = "http-api"),
+            "daemon.http_enabled",
+            "HTTP API",
+            "http-api",
+        ),
+        (
+            config.bot.enabled,
+            cfg!(feature = "bots"),
+            "bot.enabled",
+            "bots",
+            "bots",
+        ),
+        (
+            config.notifications.enabled,
+            cfg!(feature = "notifications"),
+            "notifications.enabled",
+            "notifications",
+            "notifications",
+        ),
+        (
+            config.opencode.enabled,
+            cfg!(feature = "opencode-api"),
+            "opencode.enabled",
+            "OpenCode health checks",
+            "opencode-api",
+        ),
+        (
+            config
+                .otel
+                .as_ref()
+                .is_some_and(|otel| otel.push_mode == MetricsPushMode::Pushgateway),
+            cfg!(feature = "metrics-push"),
+            "otel.push_mode",
+            "Metrics push",
+            "metrics-push",
+        ),
+        (
+            // MCP is enabled by default, so only a customized section counts.
+            config.mcp != McpConfig::default(),
+            cfg!(feature = "mcp"),
+            "mcp",
+            "MCP",
+            "mcp",
+        ),
+    ];
+
+    for (configured, compiled, field, name, feature) in sections {
+        if configured && !compiled {
+            warnings.push(ValidationWarning {
+                field: field.to_string(),
+                message: format!(
+                    "{name} configured but binary built without the `{feature}` feature"
+                ),
+            });
+        }
+    }
+}
+
+fn validate_grpc_config(
+    grpc: &GrpcConfig,
     errors: &mut Vec<ValidationError>,
     warnings: &mut Vec<ValidationWarning>,
 ) {
-    let bot = &config.bot;
-    if !bot.enabled {
-        return;
+    if grpc.bind.parse::<std::net::SocketAddr>().is_err() {
+        errors.push(ValidationError {
+            field: "daemon.grpc.bind".to_string(),
+            message: format!("Invalid gRPC bind address: {}", grpc.bind),
+            suggestion: Some("Use host:port, e.g. 127.0.0.1:7655".to_string()),
+        });
     }
 
-    if bot.discord_public_key.is_none() && bot.slack_signing_secret.is_none() {
+    if grpc.tls_cert.is_some() != grpc.tls_key.is_some() {
         errors.push(ValidationError {
-            field: "bot.enabled".to_string(),
-            message: "Bot enabled but no signing keys configured".to_string(),
-            suggestion: Some("Set bot.discord_public_key or bot.slack_signing_secret".to_string()),
+            field: "daemon.grpc.tls_cert".to_string(),
+            message: "tls_cert and tls_key must be set together".to_string(),
+            suggestion: None,
         });
     }
 
-    if let Some(ref key) = bot.discord_public_key {
-        let trimmed = key.trim();
-        if trimmed.is_empty() {
+    if grpc.tls_ca.is_some() && grpc.tls_cert.is_none() {
+        errors.push(ValidationError {
+            field: "daemon.grpc.tls_ca".to_string(),
+            message: "tls_ca requires tls_cert and tls_key".to_string(),
+            suggestion: None,
+        });
+    }
+
+    if grpc.enabled && !cfg!(feature = "grpc") {
+        warnings.push(ValidationWarning {
+            field: "daemon.grpc.enabled".to_string(),
+            message: "gRPC is enabled but this build does not include the `grpc` feature"
+                .to_string(),
+        });
+    }
+}
+
+/// `notifications.<kind>.<key>` for the table form, `notifications.<kind>[i].<key>` for arrays.
+fn target_field(kind: &str, index: usize, len: usize, key: &str) -> String {
+    if len > 1 {
+        format!("notifications.{kind}[{index}].{key}")
+    } else {
+        format!("notifications.{kind}.{key}")
+    }
+}
+
+fn validate_target_filters(
+    severities: &[String],
+    field: String,
+    errors: &mut Vec<ValidationError>,
+) {
+    for severity in severities {
+        if !SEVERITIES.contains(&severity.trim().to_ascii_lowercase().as_str()) {
             errors.push(ValidationError {
-                field: "bot.discord_public_key".to_string(),
-                message: "Discord public key cannot be empty".to_string(),
-                suggestion: None,
+                field: field.clone(),
+                message: format!("Unknown severity: {severity}"),
+                suggestion: Some("Use info, warning, error, or critical".to_string()),
             });
-        } else if trimmed.len() != 64 {
+        }
+    }
+}
+
+/// Warn about channels whose `match_tags` name no tag any sidecar under
+/// `monitoring.session_dir` declares, so they would never get a session's events.
+fn validate_tag_routes(config: &Config, warnings: &mut Vec<ValidationWarning>) {
+    let notifications = &config.notifications;
+    let mut routes: Vec<(String, &[String])> = Vec::new();
+    for (index, webhook) in notifications.webhook.iter().enumerate() {
+        let field = target_field("webhook", index, notifications.webhook.len(), "match_tags");
+        routes.push((field, &webhook.match_tags));
+    }
+    for (index, ntfy) in notifications.ntfy.iter().enumerate() {
+        let field = target_field("ntfy", index, notifications.ntfy.len(), "match_tags");
+        routes.push((field, &ntfy.match_tags));
+    }
+    if let Some(discord) = &notifications.discord {
+        routes.push((
+            "notifications.discord.match_tags".to_string(),
+            &discord.match_tags,
+        ));
+    }
+    if let Some(slack) = &notifications.slack {
+        routes.push((
+            "notifications.slack.match_tags".to_string(),
+            &slack.match_tags,
+        ));
+    }
+    routes.retain(|(_, tags)| !tags.is_empty());
+    if routes.is_empty() {
+        return;
+    }
+
+    let known = known_tags(&config.monitoring.session_dir);
+    for (field, tags) in routes {
+        if normalize_tags(tags).iter().any(|tag| known.contains(tag)) {
+            continue;
+        }
+        warnings.push(ValidationWarning {
+            field,
+            message: format!(
+                "No session sidecar or workspace overlay declares any of the tags {}; this channel will never get a session's events",
+                tags.join(", ")
+            ),
+        });
+    }
+}
+
+/// With `[[notifications.*]]` arrays, every channel needs a distinct name.
+fn validate_channel_names(config: &Config, errors: &mut Vec<ValidationError>) {
+    let notifications = &config.notifications;
+    if !notifications.has_target_arrays() {
+        return;
+    }
+    let webhooks = notifications
+        .webhook
+        .iter()
+        .enumerate()
+        .map(|(index, webhook)| {
+            (
+                target_field("webhook", index, notifications.webhook.len(), "name"),
+                webhook.channel_name(),
+            )
+        });
+    let topics = notifications.ntfy.iter().enumerate().map(|(index, ntfy)| {
+        (
+            target_field("ntfy", index, notifications.ntfy.len(), "name"),
+            ntfy.channel_name(),
+        )
+    });
+    let mut seen = HashSet::new();
+    for (field, name) in webhooks.chain(topics) {
+        if !seen.insert(name.to_ascii_lowercase()) {
             errors.push(ValidationError {
-                field: "bot.discord_public_key".to_string(),
-                message: format!(
-                    "Discord public key must be 64 hex characters (got {})",
-                    trimmed.len()
-                ),
-                suggestion: Some(
-                    "Ed25519 public keys are 32 bytes = 64 hex characters".to_string(),
-                ),
+                field,
+                message: format!("Duplicate notification channel name: {name}"),
+                suggestion: Some("Give each [[notifications.*]] entry a unique name".to_string()),
             });
-        } else if hex::decode(trimmed).is_err() {
+        }
+    }
+}
+
+const SEVERITIES: [&str; 4] = ["info", "warning", "error", "critical"];
+const CHANNELS: [&str; 4] = ["ntfy", "discord", "slack", "webhook"];
+
+/// Whether `name` is a built-in channel name or a configured target's `name`.
+fn is_channel_name(config: &Config, name: &str) -> bool {
+    let notifications = &config.notifications;
+    CHANNELS.contains(&name.to_ascii_lowercase().as_str())
+        || notifications
+            .webhook
+            .iter()
+            .map(|webhook| webhook.channel_name())
+            .chain(notifications.ntfy.iter().map(|ntfy| ntfy.channel_name()))
+            .any(|target| target.eq_ignore_ascii_case(name))
+}
+
+fn validate_notification_priority(config: &Config, errors: &mut Vec<ValidationError>) {
+    let notifications = &config.notifications;
+    if let Some(ref primary) = notifications.primary_channel {
+        let primary = primary.trim().to_ascii_lowercase();
+        if primary != "auto" && !is_channel_name(config, &primary) {
             errors.push(ValidationError {
-                field: "bot.discord_public_key".to_string(),
-                message: "Discord public key must be hex-encoded".to_string(),
+                field: "notifications.primary_channel".to_string(),
+                message: format!("Unknown notification channel: {primary}"),
                 suggestion: Some(
-                    "Use the hex public key from the Discord developer portal".to_string(),
+                    "Use ntfy, discord, slack, webhook, auto, or a configured target name"
+                        .to_string(),
                 ),
             });
         }
     }
 
-    if let Some(ref secret) = bot.slack_signing_secret {
-        if secret.trim().is_empty() {
+    for severity in &notifications.urgent_severities {
+        if !SEVERITIES.contains(&severity.trim().to_ascii_lowercase().as_str()) {
             errors.push(ValidationError {
-                field: "bot.slack_signing_secret".to_string(),
-                message: "Slack signing secret cannot be empty".to_string(),
-                suggestion: None,
+                field: "notifications.urgent_severities".to_string(),
+                message: format!("Unknown severity: {severity}"),
+                suggestion: Some("Use info, warning, error, or critical".to_string()),
             });
         }
     }
 
-    if bot.authorized_users.is_empty() && !bot.allow_all_users {
-        warnings.push(ValidationWarning {
-            field: "bot.authorized_users".to_string(),
-            message: "No authorized users configured; commands will be rejected".to_string(),
-        });
-    }
-
-    for (index, user) in bot.authorized_users.iter().enumerate() {
-        if user.user_id.trim().is_empty() {
+    for severity in &notifications.mute_exempt_severities {
+        if !SEVERITIES.contains(&severity.trim().to_ascii_lowercase().as_str()) {
             errors.push(ValidationError {
-                field: format!("bot.authorized_users[{index}].user_id"),
-                message: "Authorized user ID cannot be empty".to_string(),
-                suggestion: None,
+                field: "notifications.mute_exempt_severities".to_string(),
+                message: format!("Unknown severity: {severity}"),
+                suggestion: Some("Use info, warning, error, or critical".to_string()),
             });
         }
     }
-}
 
-fn validate_log_level(level: &str, errors: &mut Vec<ValidationError>) {
-    let level = level.trim().to_lowercase();
-    let valid = ["trace", "debug", "info", "warn", "error"];
-    if !valid.iter().any(|value| *value == level) {
+    if notifications.primary_channel.is_some() && notifications.primary_timeout_ms == 0 {
         errors.push(ValidationError {
-            field: "daemon.log_level".to_string(),
-            message: format!("Invalid log level: {level}"),
-            suggestion: Some(format!("Valid levels: {}", valid.join(", "))),
+            field: "notifications.primary_timeout_ms".to_string(),
+            message: "primary_timeout_ms must be greater than 0".to_string(),
+            suggestion: None,
         });
     }
-}
 
-fn validate_opencode_hostname(hostname: &str, errors: &mut Vec<ValidationError>) {
-    let trimmed = hostname.trim();
-    if trimmed.is_empty() {
+    if notifications.history_size == 0 {
         errors.push(ValidationError {
-            field: "opencode.serve_hostname".to_string(),
-            message: "OpenCode hostname cannot be empty".to_string(),
-            suggestion: Some("Use a hostname like localhost or 0.0.0.0".to_string()),
+            field: "notifications.history_size".to_string(),
+            message: "history_size must be greater than 0".to_string(),
+            suggestion: Some("Use the default of 200".to_string()),
         });
-        return;
     }
 
-    let has_whitespace = trimmed.chars().any(char::is_whitespace);
-    if has_whitespace || trimmed.contains("//") || trimmed.contains('/') {
+    if let Some(base) = &notifications.ack_base_url {
+        if !(base.starts_with("http://") || base.starts_with("https://")) {
+            errors.push(ValidationError {
+                field: "notifications.ack_base_url".to_string(),
+                message: format!("Acknowledgment base URL must be http(s), got {base:?}"),
+                suggestion: Some("Use e.g. \"https://laptop.example.net:7654\"".to_string()),
+            });
+        }
+    }
+
+    if notifications.ack_ttl_secs == 0 || notifications.ack_ttl_secs > MAX_ACK_TTL_SECS {
         errors.push(ValidationError {
-            field: "opencode.serve_hostname".to_string(),
-            message: "OpenCode hostname must be a hostname without scheme or path".to_string(),
-            suggestion: Some("Use a hostname like localhost or 0.0.0.0".to_string()),
+            field: "notifications.ack_ttl_secs".to_string(),
+            message: format!("ack_ttl_secs must be between 1 and {MAX_ACK_TTL_SECS}"),
+            suggestion: Some("Use the default of 86400".to_string()),
         });
     }
 }
 
-fn validate_dir_path(
-    field: &str,
-    path: &Path,
-    errors: &mut Vec<ValidationError>,
-    warnings: &mut Vec<ValidationWarning>,
-) {
-    if path.exists() {
-        if !path.is_dir() {
+fn validate_http_tokens(config: &Config, errors: &mut Vec<ValidationError>) {
+    let mut names = HashSet::new();
+    let mut secrets = HashSet::new();
+    secrets.extend(config.daemon.http_token.as_deref());
+    for (index, token) in config.daemon.http_tokens.iter().enumerate() {
+        let field = |name: &str| format!("daemon.http_tokens[{index}].{name}");
+        let name = token.name.trim();
+        if name.is_empty() {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: "HTTP token name cannot be empty".to_string(),
+                suggestion: Some("Use e.g. \"dashboard\"".to_string()),
+            });
+        } else if !names.insert(name) {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: format!("Duplicate HTTP token name: {name}"),
+                suggestion: None,
+            });
+        }
+        if token.token.is_empty() {
+            errors.push(ValidationError {
+                field: field("token"),
+                message: format!("HTTP token {name:?} is empty"),
+                suggestion: Some(
+                    "Reference a secret, e.g. token = \"${keyring:palingenesis/dashboard}\""
+                        .to_string(),
+                ),
+            });
+        } else if !secrets.insert(token.token.as_str()) {
+            errors.push(ValidationError {
+                field: field("token"),
+                message: format!("HTTP token {name:?} reuses another token's value"),
+                suggestion: Some("Give every token a value of its own".to_string()),
+            });
+        }
+        if token.scopes.is_empty() {
+            errors.push(ValidationError {
+                field: field("scopes"),
+                message: format!("HTTP token {name:?} has no scopes"),
+                suggestion: Some("Use e.g. scopes = [\"read\"]".to_string()),
+            });
+        }
+    }
+}
+
+fn validate_schedules(config: &Config, errors: &mut Vec<ValidationError>) {
+    let mut names = HashSet::new();
+    for (index, schedule) in config.schedules.iter().enumerate() {
+        let field = |name: &str| format!("schedules[{index}].{name}");
+        let name = schedule.name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: format!("Schedule name {name:?} must be one word"),
+                suggestion: Some("Use e.g. \"ci-triage\"".to_string()),
+            });
+        } else if !names.insert(name) {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: format!("Duplicate schedule name: {name}"),
+                suggestion: None,
+            });
+        }
+        if let Err(err) = ScheduleSpec::from_config(schedule) {
+            errors.push(ValidationError {
+                field: field("cron"),
+                message: format!("Schedule {name:?}: {err}"),
+                suggestion: Some(
+                    "Set one of cron = \"0 6 * * 1-5\", daily = \"06:00\" or weekdays = \"06:00\""
+                        .to_string(),
+                ),
+            });
+        }
+        if schedule.prompt.trim().is_empty() {
+            errors.push(ValidationError {
+                field: field("prompt"),
+                message: "Schedule prompt cannot be empty".to_string(),
+                suggestion: None,
+            });
+        }
+        if let Some(name) = &schedule.assistant {
+            if assistant::adapter_for(name).is_none() {
+                errors.push(ValidationError {
+                    field: field("assistant"),
+                    message: format!("Unknown assistant: {name}"),
+                    suggestion: Some(format!(
+                        "Use {} or {}",
+                        assistant::OPENCODE,
+                        assistant::CLAUDE_CODE
+                    )),
+                });
+            }
+        }
+    }
+}
+
+fn validate_custom_strategies(config: &Config, errors: &mut Vec<ValidationError>) {
+    let mut names = HashSet::new();
+    for (index, strategy) in config.resume.custom_strategies.iter().enumerate() {
+        let field = |name: &str| format!("resume.custom_strategies[{index}].{name}");
+        let name = strategy.name.trim();
+        if name.is_empty() {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: "Custom strategy name cannot be empty".to_string(),
+                suggestion: Some("Use e.g. \"tmux-restart\"".to_string()),
+            });
+        } else if BUILTIN_STRATEGY_NAMES
+            .iter()
+            .any(|builtin| builtin.eq_ignore_ascii_case(name))
+        {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: format!("Custom strategy name {name:?} is taken by a built-in strategy"),
+                suggestion: Some("Pick a name of your own, e.g. \"tmux-restart\"".to_string()),
+            });
+        } else if !names.insert(name.to_lowercase()) {
+            errors.push(ValidationError {
+                field: field("name"),
+                message: format!("Duplicate custom strategy name: {name}"),
+                suggestion: None,
+            });
+        }
+        if strategy.command.trim().is_empty() {
+            errors.push(ValidationError {
+                field: field("command"),
+                message: "Custom strategy command cannot be empty".to_string(),
+                suggestion: None,
+            });
+        }
+        if strategy.timeout_secs == 0 {
+            errors.push(ValidationError {
+                field: field("timeout_secs"),
+                message: "timeout_secs must be greater than 0".to_string(),
+                suggestion: Some("Use the default of 300".to_string()),
+            });
+        }
+        for reason in &strategy.matches.stop_reasons {
+            if !MATCHABLE_STOP_REASONS.contains(&reason.as_str()) {
+                errors.push(ValidationError {
+                    field: field("match.stop_reasons"),
+                    message: format!("Unknown stop reason: {reason}"),
+                    suggestion: Some(format!("Use one of {}", MATCHABLE_STOP_REASONS.join(", "))),
+                });
+            }
+        }
+    }
+}
+
+fn validate_escalation(config: &Config, errors: &mut Vec<ValidationError>) {
+    let escalation = &config.notifications.escalation;
+    let mut previous: Option<u64> = None;
+    for (index, tier) in escalation.tiers.iter().enumerate() {
+        let field = |name: &str| format!("notifications.escalation.tiers[{index}].{name}");
+        if tier.channels.is_empty() {
+            errors.push(ValidationError {
+                field: field("channels"),
+                message: "Escalation tier has no channels".to_string(),
+                suggestion: Some("List at least one channel, e.g. [\"ntfy\"]".to_string()),
+            });
+        }
+        for channel in &tier.channels {
+            if !is_channel_name(config, channel.trim()) {
+                errors.push(ValidationError {
+                    field: field("channels"),
+                    message: format!("Unknown notification channel: {channel}"),
+                    suggestion: Some(
+                        "Use ntfy, discord, slack, webhook, or a configured target name"
+                            .to_string(),
+                    ),
+                });
+            }
+        }
+        if previous.is_some_and(|previous| tier.after_secs <= previous) {
+            errors.push(ValidationError {
+                field: field("after_secs"),
+                message: "Escalation tiers must be ordered by increasing after_secs".to_string(),
+                suggestion: None,
+            });
+        }
+        previous = Some(tier.after_secs);
+    }
+
+    if !escalation.tiers.is_empty() && escalation.check_interval_secs == 0 {
+        errors.push(ValidationError {
+            field: "notifications.escalation.check_interval_secs".to_string(),
+            message: "check_interval_secs must be greater than 0".to_string(),
+            suggestion: Some("Use the default of 30".to_string()),
+        });
+    }
+}
+
+fn validate_bot_config(
+    config: &Config,
+    errors: &mut Vec<ValidationError>,
+    warnings: &mut Vec<ValidationWarning>,
+) {
+    let bot = &config.bot;
+    if !bot.enabled {
+        return;
+    }
+
+    if bot.discord_public_key.is_none() && bot.slack_signing_secret.is_none() {
+        errors.push(ValidationError {
+            field: "bot.enabled".to_string(),
+            message: "Bot enabled but no signing keys configured".to_string(),
+            suggestion: Some("Set bot.discord_public_key or bot.slack_signing_secret".to_string()),
+        });
+    }
+
+    if let Some(ref key) = bot.discord_public_key {
+        let trimmed = key.trim();
+        if trimmed.is_empty() {
+            errors.push(ValidationError {
+                field: "bot.discord_public_key".to_string(),
+                message: "Discord public key cannot be empty".to_string(),
+                suggestion: None,
+            });
+        } else if trimmed.len() != 64 {
+            errors.push(ValidationError {
+                field: "bot.discord_public_key".to_string(),
+                message: format!(
+                    "Discord public key must be 64 hex characters (got {})",
+                    trimmed.len()
+                ),
+                suggestion: Some(
+                    "Ed25519 public keys are 32 bytes = 64 hex characters".to_string(),
+                ),
+            });
+        } else if hex::decode(trimmed).is_err() {
+            errors.push(ValidationError {
+                field: "bot.discord_public_key".to_string(),
+                message: "Discord public key must be hex-encoded".to_string(),
+                suggestion: Some(
+                    "Use the hex public key from the Discord developer portal".to_string(),
+                ),
+            });
+        }
+    }
+
+    if let Some(ref secret) = bot.slack_signing_secret {
+        if secret.trim().is_empty() {
+            errors.push(ValidationError {
+                field: "bot.slack_signing_secret".to_string(),
+                message: "Slack signing secret cannot be empty".to_string(),
+                suggestion: None,
+            });
+        }
+    }
+
+    if bot.authorized_users.is_empty() && !bot.allow_all_users {
+        warnings.push(ValidationWarning {
+            field: "bot.authorized_users".to_string(),
+            message: "No authorized users configured; commands will be rejected".to_string(),
+        });
+    }
+
+    for (index, user) in bot.authorized_users.iter().enumerate() {
+        if user.user_id.trim().is_empty() {
+            errors.push(ValidationError {
+                field: format!("bot.authorized_users[{index}].user_id"),
+                message: "Authorized user ID cannot be empty".to_string(),
+                suggestion: None,
+            });
+        }
+    }
+
+    if let Some(bind) = &bot.http_bind {
+        if bind.parse::<std::net::IpAddr>().is_err() {
+            errors.push(ValidationError {
+                field: "bot.http_bind".to_string(),
+                message: format!("Invalid bot listener address: {bind}"),
+                suggestion: Some(
+                    "Use an IP address such as \"0.0.0.0\" or \"127.0.0.1\"".to_string(),
+                ),
+            });
+        }
+    }
+    if bot.http_port == Some(0) {
+        errors.push(ValidationError {
+            field: "bot.http_port".to_string(),
+            message: "Bot listener port must be between 1 and 65535".to_string(),
+            suggestion: Some("Use a port between 1 and 65535".to_string()),
+        });
+    }
+    let daemon = &config.daemon;
+    let bot_port = bot.http_port.unwrap_or(daemon.http_port);
+    if bot_port == daemon.http_port && bot.separate_listener(daemon).is_some() {
+        errors.push(ValidationError {
+            field: "bot.http_port".to_string(),
+            message: format!(
+                "Bot listener cannot share port {bot_port} with the HTTP API on another address"
+            ),
+            suggestion: Some("Set bot.http_port to a port of its own".to_string()),
+        });
+    }
+}
+
+fn validate_log_level(level: &str, errors: &mut Vec<ValidationError>) {
+    let level = level.trim().to_lowercase();
+    let valid = ["trace", "debug", "info", "warn", "error"];
+    if !valid.iter().any(|value| *value == level) {
+        errors.push(ValidationError {
+            field: "daemon.log_level".to_string(),
+            message: format!("Invalid log level: {level}"),
+            suggestion: Some(format!("Valid levels: {}", valid.join(", "))),
+        });
+    }
+}
+
+fn validate_classifier(classifier: &ClassificationConfig, errors: &mut Vec<ValidationError>) {
+    let threshold = classifier.context_threshold_percent;
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        errors.push(ValidationError {
+            field: "classifier.context_threshold_percent".to_string(),
+            message: "Context threshold must be greater than 0.0 and at most 1.0".to_string(),
+            suggestion: Some("Use a fraction such as 0.8".to_string()),
+        });
+    }
+    if classifier.max_lines == 0 {
+        errors.push(ValidationError {
+            field: "classifier.max_lines".to_string(),
+            message: "max_lines must be positive".to_string(),
+            suggestion: Some("Use the default of 100 lines".to_string()),
+        });
+    }
+    if classifier.default_context_size == 0 {
+        errors.push(ValidationError {
+            field: "classifier.default_context_size".to_string(),
+            message: "default_context_size must be positive".to_string(),
+            suggestion: None,
+        });
+    }
+    for (model, tokens) in &classifier.known_context_sizes {
+        if *tokens == 0 || model.trim().is_empty() {
+            errors.push(ValidationError {
+                field: format!("classifier.known_context_sizes.{model}"),
+                message: "Context sizes need a model name and a positive token count".to_string(),
+                suggestion: None,
+            });
+        }
+    }
+    for (field, patterns) in [
+        (
+            "classifier.extra_rate_limit_patterns",
+            &classifier.extra_rate_limit_patterns,
+        ),
+        (
+            "classifier.extra_context_patterns",
+            &classifier.extra_context_patterns,
+        ),
+    ] {
+        for (index, pattern) in patterns.iter().enumerate() {
+            if let Err(err) = regex::Regex::new(pattern) {
+                errors.push(ValidationError {
+                    field: format!("{field}[{index}]"),
+                    message: format!("Invalid regex {pattern:?}: {err}"),
+                    suggestion: None,
+                });
+            }
+        }
+    }
+}
+
+fn validate_push_settings(otel: &OtelConfig, errors: &mut Vec<ValidationError>) {
+    match otel.push_endpoint.as_deref().map(str::trim) {
+        Some(endpoint) if is_http_url(endpoint) => {}
+        _ => errors.push(ValidationError {
+            field: "otel.push_endpoint".to_string(),
+            message: "push_mode = \"pushgateway\" needs an http:// or https:// push_endpoint"
+                .to_string(),
+            suggestion: Some("Set push_endpoint to the Pushgateway URL".to_string()),
+        }),
+    }
+    if otel.push_interval_secs == 0 {
+        errors.push(ValidationError {
+            field: "otel.push_interval_secs".to_string(),
+            message: "push_interval_secs must be greater than zero".to_string(),
+            suggestion: None,
+        });
+    }
+    if otel.push_job_name.trim().is_empty() {
+        errors.push(ValidationError {
+            field: "otel.push_job_name".to_string(),
+            message: "push_job_name cannot be empty".to_string(),
+            suggestion: None,
+        });
+    }
+    if otel.push_username.is_some() != otel.push_password.is_some() {
+        errors.push(ValidationError {
+            field: "otel.push_username".to_string(),
+            message: "push_username and push_password must be set together".to_string(),
+            suggestion: None,
+        });
+    }
+}
+
+fn validate_opencode_hostname(hostname: &str, errors: &mut Vec<ValidationError>) {
+    let trimmed = hostname.trim();
+    if trimmed.is_empty() {
+        errors.push(ValidationError {
+            field: "opencode.serve_hostname".to_string(),
+            message: "OpenCode hostname cannot be empty".to_string(),
+            suggestion: Some("Use a hostname like localhost or 0.0.0.0".to_string()),
+        });
+        return;
+    }
+
+    let has_whitespace = trimmed.chars().any(char::is_whitespace);
+    if has_whitespace || trimmed.contains("//") || trimmed.contains('/') {
+        errors.push(ValidationError {
+            field: "opencode.serve_hostname".to_string(),
+            message: "OpenCode hostname must be a hostname without scheme or path".to_string(),
+            suggestion: Some("Use a hostname like localhost or 0.0.0.0".to_string()),
+        });
+    }
+}
+
+fn validate_dir_path(
+    field: &str,
+    path: &Path,
+    errors: &mut Vec<ValidationError>,
+    warnings: &mut Vec<ValidationWarning>,
+) {
+    if path.exists() {
+        if !path.is_dir() {
             errors.push(ValidationError {
                 field: field.to_string(),
                 message: format!("Path is not a directory: {}", path.display()),
@@ -422,15 +1332,133 @@ fn validate_file_parent_path(
     }
 }
 
+/// Reject notification URLs aimed at loopback or private addresses (SSRF).
+///
+/// Only IP literals and `localhost` are caught here; hostnames are checked
+/// when they resolve at send time.
+fn validate_notification_target(
+    guard: &UrlGuard,
+    url: &str,
+    field: String,
+    errors: &mut Vec<ValidationError>,
+) {
+    if let Err(err) = guard.check_url(url) {
+        let suggestion = match err {
+            UrlGuardError::InvalidUrl { .. } => None,
+            _ => Some(
+                "Set notifications.allow_private_networks = true for internal receivers"
+                    .to_string(),
+            ),
+        };
+        errors.push(ValidationError {
+            field,
+            message: format!("Notification URL not allowed: {err}"),
+            suggestion,
+        });
+    }
+}
+
 fn is_http_url(value: &str) -> bool {
     let value = value.trim().to_lowercase();
     value.starts_with("http://") || value.starts_with("https://")
 }
 
+fn validate_cost_settings(metrics: &MetricsConfig, errors: &mut Vec<ValidationError>) {
+    let invalid_price = |price: f64| !price.is_finite() || price < 0.0;
+    if metrics.hourly_rate.is_some_and(invalid_price) {
+        errors.push(ValidationError {
+            field: "metrics.hourly_rate".to_string(),
+            message: "hourly rate must be a non-negative number".to_string(),
+            suggestion: Some("Set the cost of an engineer hour, e.g. 90.0".to_string()),
+        });
+    }
+    if metrics.currency.trim().is_empty() {
+        errors.push(ValidationError {
+            field: "metrics.currency".to_string(),
+            message: "currency must not be empty".to_string(),
+            suggestion: Some("Set a currency code, e.g. \"USD\"".to_string()),
+        });
+    }
+    for (model, price) in &metrics.model_costs {
+        if invalid_price(price.input_per_1k) || invalid_price(price.output_per_1k) {
+            errors.push(ValidationError {
+                field: format!("metrics.model_costs.{model}"),
+                message: "token prices must be non-negative numbers".to_string(),
+                suggestion: Some(
+                    "Set input_per_1k and output_per_1k to the price per 1000 tokens".to_string(),
+                ),
+            });
+        }
+    }
+}
+
+fn validate_prompt_context(
+    context: &PromptContextConfig,
+    errors: &mut Vec<ValidationError>,
+    warnings: &mut Vec<ValidationWarning>,
+) {
+    for (index, entry) in context.sections.iter().enumerate() {
+        let field = format!("resume.new_session.context.sections[{index}]");
+        if let ContextSection::Custom(text) = &entry.section {
+            if text.trim().is_empty() {
+                errors.push(ValidationError {
+                    field: field.clone(),
+                    message: "custom context section has no text".to_string(),
+                    suggestion: Some(
+                        "Write the text after the colon, e.g. \"custom:Run the tests first.\""
+                            .to_string(),
+                    ),
+                });
+            }
+        }
+        if entry
+            .max_chars
+            .is_some_and(|max_chars| max_chars <= content::CUT_MARKER.len())
+        {
+            errors.push(ValidationError {
+                field,
+                message: format!(
+                    "max_chars must leave room for the {:?} cut marker",
+                    content::CUT_MARKER
+                ),
+                suggestion: Some(format!(
+                    "Set max_chars above {} or disable the section",
+                    content::CUT_MARKER.len()
+                )),
+            });
+        }
+    }
+    if !context.sections.iter().any(|entry| entry.enabled) {
+        warnings.push(ValidationWarning {
+            field: "resume.new_session.context.sections".to_string(),
+            message: "No context sections are enabled; {context} will be empty".to_string(),
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::schema::Config;
+    use crate::config::schema::{Config, ContextSectionConfig, ModelCost};
+
+    #[test]
+    fn test_validate_config_warns_about_compiled_out_sections() {
+        let mut config = Config::default();
+        config.notifications.enabled = true;
+        config.mcp.instructions = Some("custom".to_string());
+        let result = validate_config(&config);
+
+        let warned = |field: &str| result.warnings.iter().any(|w| w.field == field);
+        assert_eq!(
+            warned("notifications.enabled"),
+            !cfg!(feature = "notifications")
+        );
+        assert_eq!(warned("mcp"), !cfg!(feature = "mcp"));
+        if !cfg!(feature = "notifications") {
+            assert!(result.warnings.iter().any(|w| w.message
+                == "notifications configured but binary built without the `notifications` feature"));
+        }
+    }
 
     #[test]
     fn test_validate_config_reports_invalid_log_level() {
@@ -458,13 +1486,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_config_checks_adaptive_debounce_bounds() {
+        let mut config = Config::default();
+        config.monitoring.debounce_min_ms = 500;
+        config.monitoring.debounce_max_ms = 100;
+        assert!(validate_config(&config).errors.is_empty());
+
+        config.monitoring.debounce = DebounceMode::Adaptive;
+        let result = validate_config(&config);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "monitoring.debounce_max_ms")
+        );
+    }
+
     #[test]
     fn test_validate_config_reports_invalid_webhook_url() {
         let mut config = Config::default();
-        config.notifications.webhook = Some(crate::config::schema::WebhookConfig {
-            url: "ftp://example.com".to_string(),
-            headers: None,
-        });
+        config.notifications.webhook = vec![crate::config::schema::WebhookConfig::new(
+            "ftp://example.com",
+        )];
         let result = validate_config(&config);
         assert!(
             result
@@ -474,6 +1518,116 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_config_rejects_empty_webhook_signing_secret() {
+        let mut config = Config::default();
+        config.notifications.webhook = vec![crate::config::schema::WebhookConfig {
+            signing_secret: Some(" ".to_string()),
+            ..crate::config::schema::WebhookConfig::new("https://example.com/hook")
+        }];
+        let result = validate_config(&config);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "notifications.webhook.signing_secret")
+        );
+    }
+
+    #[test]
+    fn test_validate_config_warns_about_unmatchable_tag_routes() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join(".palingenesis")).unwrap();
+        std::fs::write(
+            temp.path().join(".palingenesis/resume.toml"),
+            "tags = [\"proj-a\"]\n",
+        )
+        .unwrap();
+        let mut config = Config::default();
+        config.monitoring.session_dir = temp.path().to_path_buf();
+        let mut proj_a = crate::config::schema::NtfyConfig::new("a");
+        proj_a.name = Some("proj-a".to_string());
+        proj_a.match_tags = vec!["Proj-A".to_string()];
+        let mut proj_b = crate::config::schema::NtfyConfig::new("b");
+        proj_b.name = Some("proj-b".to_string());
+        proj_b.match_tags = vec!["proj-b".to_string()];
+        config.notifications.ntfy = vec![proj_a, proj_b];
+
+        let warned: Vec<String> = validate_config(&config)
+            .warnings
+            .into_iter()
+            .map(|warning| warning.field)
+            .filter(|field| field.ends_with("match_tags"))
+            .collect();
+        assert_eq!(warned, vec!["notifications.ntfy[1].match_tags"]);
+    }
+
+    #[test]
+    fn test_validate_config_rejects_private_notification_urls() {
+        let mut config = Config::default();
+        config.notifications.webhook = vec![crate::config::schema::WebhookConfig::new(
+            "http://169.254.169.254/latest/meta-data",
+        )];
+        config.notifications.slack = Some(crate::config::schema::SlackConfig {
+            webhook_url: "http://localhost:8080/hook".to_string(),
+            locale: None,
+            path_display: None,
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+        });
+        config.notifications.discord = Some(crate::config::schema::DiscordConfig {
+            webhook_url: "http://0x7f.1/api/webhooks/1".to_string(),
+            locale: None,
+            path_display: None,
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+        });
+        let result = validate_config(&config);
+        let fields: Vec<_> = result.errors.iter().map(|err| err.field.as_str()).collect();
+        assert!(fields.contains(&"notifications.webhook.url"));
+        assert!(fields.contains(&"notifications.slack.webhook_url"));
+        assert!(fields.contains(&"notifications.discord.webhook_url"));
+
+        config.notifications.allow_private_networks = true;
+        let result = validate_config(&config);
+        assert!(
+            !result
+                .errors
+                .iter()
+                .any(|err| err.field.starts_with("notifications."))
+        );
+    }
+
+    #[test]
+    fn test_validate_config_checks_ack_settings() {
+        let mut config = Config::default();
+        config.notifications.ack_base_url = Some("palingenesis.example".to_string());
+        config.notifications.ack_ttl_secs = 0;
+        let result = validate_config(&config);
+        let fields: Vec<_> = result.errors.iter().map(|err| err.field.as_str()).collect();
+        assert!(fields.contains(&"notifications.ack_base_url"));
+        assert!(fields.contains(&"notifications.ack_ttl_secs"));
+
+        config.notifications.ack_ttl_secs = u64::MAX;
+        let result = validate_config(&config);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "notifications.ack_ttl_secs")
+        );
+
+        config.notifications.ack_base_url = Some("https://palingenesis.example".to_string());
+        config.notifications.ack_ttl_secs = 3600;
+        let result = validate_config(&config);
+        assert!(
+            !result
+                .errors
+                .iter()
+                .any(|err| err.field.starts_with("notifications.ack_"))
+        );
+    }
+
     #[test]
     fn test_validate_config_reports_missing_bot_keys() {
         let mut config = Config::default();
@@ -523,21 +1677,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_config_reports_invalid_cost_settings() {
+        let mut config = Config::default();
+        config.metrics.hourly_rate = Some(-1.0);
+        config.metrics.currency = " ".to_string();
+        config.metrics.model_costs.insert(
+            "claude-sonnet-4".to_string(),
+            ModelCost {
+                input_per_1k: 0.003,
+                output_per_1k: f64::NAN,
+            },
+        );
+        let result = validate_config(&config);
+        let fields: Vec<&str> = result.errors.iter().map(|err| err.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "metrics.hourly_rate",
+                "metrics.currency",
+                "metrics.model_costs.claude-sonnet-4"
+            ]
+        );
+    }
+
     #[test]
     fn test_validate_config_reports_invalid_otel_protocol() {
         let mut config = Config::default();
-        let mut otel = crate::config::schema::OtelConfig::default();
-        otel.protocol = "smtp".to_string();
+        let otel = crate::config::schema::OtelConfig {
+            protocol: "smtp".to_string(),
+            ..crate::config::schema::OtelConfig::default()
+        };
         config.otel = Some(otel);
         let result = validate_config(&config);
         assert!(result.errors.iter().any(|err| err.field == "otel.protocol"));
     }
 
+    #[test]
+    fn test_validate_config_checks_classifier_section() {
+        let mut config = Config::default();
+        config.classifier.context_threshold_percent = 1.5;
+        config.classifier.extra_context_patterns = vec!["(unclosed".to_string()];
+        let result = validate_config(&config);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "classifier.context_threshold_percent")
+        );
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "classifier.extra_context_patterns[0]")
+        );
+    }
+
+    #[test]
+    fn test_validate_config_requires_push_endpoint_for_pushgateway() {
+        let mut config = Config::default();
+        let otel = OtelConfig {
+            push_mode: MetricsPushMode::Pushgateway,
+            push_username: Some("metrics".to_string()),
+            ..OtelConfig::default()
+        };
+        config.otel = Some(otel);
+        let result = validate_config(&config);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "otel.push_endpoint")
+        );
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.field == "otel.push_username")
+        );
+    }
+
     #[test]
     fn test_validate_config_reports_invalid_otel_sampling_ratio() {
         let mut config = Config::default();
-        let mut otel = crate::config::schema::OtelConfig::default();
-        otel.sampling_ratio = 1.5;
+        let otel = crate::config::schema::OtelConfig {
+            sampling_ratio: 1.5,
+            ..crate::config::schema::OtelConfig::default()
+        };
         config.otel = Some(otel);
         let result = validate_config(&config);
         assert!(
@@ -549,12 +1775,13 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_config_reports_invalid_opencode_port() {
+    fn test_validate_config_accepts_discovered_opencode_port() {
         let mut config = Config::default();
+        config.opencode.enabled = true;
         config.opencode.serve_port = 0;
         let result = validate_config(&config);
         assert!(
-            result
+            !result
                 .errors
                 .iter()
                 .any(|err| err.field == "opencode.serve_port")
@@ -573,4 +1800,274 @@ mod tests {
                 .any(|err| err.field == "opencode.serve_hostname")
         );
     }
+
+    #[test]
+    fn test_validate_config_reports_unknown_primary_channel_and_severity() {
+        let mut config = Config::default();
+        config.notifications.primary_channel = Some("pager".to_string());
+        config.notifications.urgent_severities = vec!["critical".to_string(), "fatal".to_string()];
+        let result = validate_config(&config);
+        let fields: Vec<&str> = result.errors.iter().map(|err| err.field.as_str()).collect();
+        assert!(fields.contains(&"notifications.primary_channel"));
+        assert_eq!(
+            fields
+                .iter()
+                .filter(|field| **field == "notifications.urgent_severities")
+                .count(),
+            1
+        );
+
+        config.notifications.primary_channel = Some("auto".to_string());
+        config.notifications.urgent_severities = vec!["Critical".to_string()];
+        assert!(
+            !validate_config(&config)
+                .errors
+                .iter()
+                .any(|err| err.field.starts_with("notifications."))
+        );
+    }
+
+    #[test]
+    fn test_validate_config_checks_escalation_tiers() {
+        use crate::config::schema::EscalationTier;
+
+        let tier = |after_secs, channels: &[&str]| EscalationTier {
+            after_secs,
+            channels: channels.iter().map(|name| name.to_string()).collect(),
+            prefix: None,
+        };
+        let mut config = Config::default();
+        config.notifications.escalation.tiers = vec![
+            tier(0, &["ntfy"]),
+            tier(1800, &["Slack"]),
+            tier(3600, &["webhook"]),
+        ];
+        assert!(
+            !validate_config(&config)
+                .errors
+                .iter()
+                .any(|err| err.field.starts_with("notifications.escalation"))
+        );
+
+        config.notifications.escalation.tiers = vec![
+            tier(1800, &["ntfy"]),
+            tier(900, &["pager"]),
+            tier(3600, &[]),
+        ];
+        let result = validate_config(&config);
+        let fields: Vec<&str> = result.errors.iter().map(|err| err.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "notifications.escalation.tiers[1].channels",
+                "notifications.escalation.tiers[1].after_secs",
+                "notifications.escalation.tiers[2].channels",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_config_requires_unique_target_names() {
+        use crate::config::schema::NtfyConfig;
+
+        let mut config = Config::default();
+        let mut phone = NtfyConfig::new("urgent");
+        phone.name = Some("phone".to_string());
+        config.notifications.ntfy = vec![phone.clone(), NtfyConfig::new("")];
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert!(fields.contains(&"notifications.ntfy[1].topic".to_string()));
+        assert!(!fields.iter().any(|field| field.ends_with(".name")));
+
+        let mut team = NtfyConfig::new("team");
+        team.name = Some("Phone".to_string());
+        team.severities = vec!["loud".to_string()];
+        config.notifications.ntfy = vec![phone, team];
+        config.notifications.primary_channel = Some("phone".to_string());
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert!(fields.contains(&"notifications.ntfy[1].name".to_string()));
+        assert!(fields.contains(&"notifications.ntfy[1].severities".to_string()));
+        assert!(!fields.contains(&"notifications.primary_channel".to_string()));
+    }
+
+    #[test]
+    fn test_validate_config_checks_http_tokens() {
+        use crate::config::schema::{HttpScope, HttpTokenConfig};
+
+        let token = |name: &str, token: &str, scopes: &[HttpScope]| HttpTokenConfig {
+            name: name.to_string(),
+            token: token.to_string(),
+            scopes: scopes.to_vec(),
+        };
+        let mut config = Config::default();
+        config.daemon.http_token = Some("legacy".to_string());
+        config.daemon.http_tokens = vec![
+            token("dashboard", "dash", &[HttpScope::Read]),
+            token("dashboard", "legacy", &[]),
+            token("", "", &[HttpScope::Admin]),
+        ];
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert!(!fields.iter().any(|field| field.contains("http_tokens[0]")));
+        assert!(fields.contains(&"daemon.http_tokens[1].name".to_string()));
+        assert!(fields.contains(&"daemon.http_tokens[1].token".to_string()));
+        assert!(fields.contains(&"daemon.http_tokens[1].scopes".to_string()));
+        assert!(fields.contains(&"daemon.http_tokens[2].name".to_string()));
+        assert!(fields.contains(&"daemon.http_tokens[2].token".to_string()));
+    }
+
+    #[test]
+    fn test_validate_config_checks_custom_strategies() {
+        use crate::config::schema::CustomStrategyConfig;
+
+        let strategy = |name: &str| CustomStrategyConfig {
+            name: name.to_string(),
+            command: "~/bin/restart {session}".to_string(),
+            timeout_secs: 60,
+            matches: Default::default(),
+        };
+        let mut config = Config::default();
+        let mut unmatched = strategy("tmux-restart");
+        unmatched.matches.stop_reasons = vec!["user_exit".to_string()];
+        config.resume.custom_strategies = vec![
+            strategy("same_session"),
+            unmatched,
+            strategy("Tmux-Restart"),
+            strategy("ok"),
+        ];
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "resume.custom_strategies[0].name",
+                "resume.custom_strategies[1].match.stop_reasons",
+                "resume.custom_strategies[2].name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_config_checks_resume_gates() {
+        let mut config = Config::default();
+        config.resume.gates.max_load_average = Some(0.0);
+        config.resume.gates.recheck_interval_secs = 0;
+        config.resume.gates.max_deferral_secs = 0;
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert!(fields.contains(&"resume.gates.max_load_average".to_string()));
+        assert!(fields.contains(&"resume.gates.recheck_interval_secs".to_string()));
+        assert!(fields.contains(&"resume.gates.max_deferral_secs".to_string()));
+
+        config.resume.gates = Default::default();
+        config.resume.gates.max_deferral_secs = u64::MAX;
+        assert!(
+            validate_config(&config)
+                .errors
+                .iter()
+                .any(|err| err.field == "resume.gates.max_deferral_secs")
+        );
+
+        config.resume.gates = Default::default();
+        config.resume.gates.max_load_average = Some(4.0);
+        assert!(validate_config(&config).is_valid());
+    }
+
+    #[test]
+    fn test_validate_config_checks_worktree_settings() {
+        let mut config = Config::default();
+        config.resume.new_session.branch_prefix = " ".to_string();
+        assert!(validate_config(&config).is_valid());
+
+        config.resume.new_session.workspace_mode = WorkspaceMode::Worktree;
+        config.resume.new_session.worktree_root = Some(std::path::PathBuf::from("trees"));
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert!(fields.contains(&"resume.new_session.branch_prefix".to_string()));
+        assert!(fields.contains(&"resume.new_session.worktree_root".to_string()));
+    }
+
+    #[test]
+    fn test_validate_config_checks_bot_listener() {
+        let mut config = Config::default();
+        config.bot.enabled = true;
+        config.bot.slack_signing_secret = Some("secret".to_string());
+        config.bot.http_port = Some(8443);
+        assert!(validate_config(&config).is_valid());
+
+        config.bot.http_bind = Some("example.com".to_string());
+        config.bot.http_port = None;
+        let fields: Vec<String> = validate_config(&config)
+            .errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect();
+        assert_eq!(fields, ["bot.http_bind", "bot.http_port"]);
+    }
+
+    #[test]
+    fn test_validate_config_checks_prompt_context_sections() {
+        let mut config = Config::default();
+        config.resume.new_session.context.sections = vec![
+            ContextSection::Custom(" ".to_string()).into(),
+            ContextSectionConfig {
+                section: ContextSection::StopReason,
+                max_chars: Some(4),
+                enabled: false,
+            },
+        ];
+        let result = validate_config(&config);
+        let fields: Vec<&str> = result.errors.iter().map(|err| err.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "resume.new_session.context.sections[0]",
+                "resume.new_session.context.sections[1]"
+            ]
+        );
+        let empty_context = |warnings: &[ValidationWarning]| {
+            warnings
+                .iter()
+                .any(|warning| warning.field == "resume.new_session.context.sections")
+        };
+        assert!(!empty_context(&result.warnings));
+
+        config.resume.new_session.context.sections.remove(0);
+        assert!(empty_context(&validate_config(&config).warnings));
+    }
+
+    #[test]
+    fn test_validate_config_rejects_malformed_maintenance_windows() {
+        let mut config = Config::default();
+        config.resume.maintenance_windows = vec![
+            "Tue 02:00-04:00 UTC".to_string(),
+            "Tue 02:00-04:00 PST".to_string(),
+            "2025-12-26T00:00:00Z/2025-12-24T00:00:00Z".to_string(),
+        ];
+        let errors = validate_config(&config).errors;
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].field, "resume.maintenance_windows[1]");
+        assert!(errors[0].message.contains("unsupported time zone \"PST\""));
+        assert_eq!(errors[1].field, "resume.maintenance_windows[2]");
+        assert!(errors[1].message.contains("is not after start"));
+    }
 }
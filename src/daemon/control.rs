@@ -0,0 +1,392 @@
+//! Safe points for state-changing control commands.
+//!
+//! Reads (STATUS, JOBS) are answered from a snapshot and never wait here.
+//! Commands that change daemon state (PAUSE, RESUME, NEW_SESSION and RELOAD)
+//! must not land halfway through pipeline work such as a resume strategy,
+//! which runs inside an [`Operation`]. A command arriving while an operation
+//! is in flight is queued and applied by [`ControlQueue::run`] once every
+//! in-flight operation has finished; operations starting after that wait
+//! until the queue is applied. A command identical to one already queued
+//! replaces it, so two RELOADs become one. Queued commands keep their
+//! [`Initiator`], so a deferred PAUSE is still attributed to whoever sent it.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Notify, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::daemon::initiator::Initiator;
+use crate::ipc::protocol::{IpcCommand, IpcResponse};
+use crate::ipc::socket::{DaemonStateAccess, apply_command};
+
+/// Where a queued command waits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deferral {
+    /// Pipeline work the command waits for, if any is in flight.
+    pub behind: Option<String>,
+    /// An identical command was already queued; this one replaced it.
+    pub coalesced: bool,
+}
+
+impl fmt::Display for Deferral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.behind {
+            Some(operation) => write!(f, "will apply after {operation} completes")?,
+            None => write!(f, "will apply after queued commands")?,
+        }
+        if self.coalesced {
+            write!(f, " (merged with an identical pending request)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a command may be applied now.
+pub enum Admission {
+    /// Nothing is in flight; hold the guard while applying.
+    Now(ApplyGuard),
+    /// Queued for the next safe point.
+    Deferred(Deferral),
+}
+
+/// Keeps operations from starting while a command is applied.
+pub struct ApplyGuard {
+    _gate: OwnedRwLockWriteGuard<()>,
+}
+
+/// Pipeline work control commands must not interrupt; dropping it is a safe point.
+pub struct Operation {
+    id: u64,
+    inner: Arc<Inner>,
+    _gate: OwnedRwLockReadGuard<()>,
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if let Ok(mut operations) = self.inner.operations.lock() {
+            operations.remove(&self.id);
+        }
+    }
+}
+
+struct Inner {
+    gate: Arc<RwLock<()>>,
+    pending: Mutex<VecDeque<(IpcCommand, Initiator)>>,
+    operations: Mutex<BTreeMap<u64, String>>,
+    next_id: AtomicU64,
+    wake: Notify,
+}
+
+/// Queue shared by the IPC and HTTP control paths; clones share the queue.
+#[derive(Clone)]
+pub struct ControlQueue {
+    inner: Arc<Inner>,
+}
+
+impl ControlQueue {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                gate: Arc::new(RwLock::new(())),
+                pending: Mutex::new(VecDeque::new()),
+                operations: Mutex::new(BTreeMap::new()),
+                next_id: AtomicU64::new(1),
+                wake: Notify::new(),
+            }),
+        }
+    }
+
+    /// Start pipeline work named `name` (e.g. `resume of /path/session.md`).
+    ///
+    /// Waits while queued commands are being applied.
+    pub async fn operation(&self, name: impl Into<String>) -> Operation {
+        let gate = Arc::clone(&self.inner.gate).read_owned().await;
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut operations) = self.inner.operations.lock() {
+            operations.insert(id, name.into());
+        }
+        Operation {
+            id,
+            inner: Arc::clone(&self.inner),
+            _gate: gate,
+        }
+    }
+
+    /// Names of in-flight operations, oldest first.
+    pub fn operations(&self) -> Vec<String> {
+        self.inner
+            .operations
+            .lock()
+            .map(|operations| operations.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Commands waiting for a safe point, oldest first.
+    pub fn pending(&self) -> Vec<IpcCommand> {
+        self.inner
+            .pending
+            .lock()
+            .map(|pending| pending.iter().map(|(command, _)| command.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Apply `command` now if nothing is in flight, otherwise queue it for
+    /// `initiator`.
+    pub fn admit(&self, command: IpcCommand, initiator: &Initiator) -> Admission {
+        match Arc::clone(&self.inner.gate).try_write_owned() {
+            Ok(gate) => Admission::Now(ApplyGuard { _gate: gate }),
+            Err(_) => Admission::Deferred(self.defer(command, initiator)),
+        }
+    }
+
+    fn defer(&self, command: IpcCommand, initiator: &Initiator) -> Deferral {
+        let coalesced = match self.inner.pending.lock() {
+            Ok(mut pending) => {
+                let before = pending.len();
+                pending.retain(|(queued, _)| *queued != command);
+                let coalesced = pending.len() != before;
+                pending.push_back((command.clone(), initiator.clone()));
+                coalesced
+            }
+            Err(_) => false,
+        };
+        self.inner.wake.notify_one();
+        let deferral = Deferral {
+            behind: self.operations().into_iter().next(),
+            coalesced,
+        };
+        info!(?command, %deferral, "Control command deferred to next safe point");
+        deferral
+    }
+
+    /// Apply queued commands at safe points until `cancel` fires.
+    pub async fn run<S: DaemonStateAccess + ?Sized>(
+        &self,
+        state: Arc<S>,
+        cancel: CancellationToken,
+    ) {
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = self.inner.wake.notified() => {}
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = self.apply_pending(&*state) => {}
+            }
+        }
+    }
+
+    /// Wait for the next safe point, then apply every queued command.
+    ///
+    /// Returns how many commands were applied.
+    pub async fn apply_pending<S: DaemonStateAccess + ?Sized>(&self, state: &S) -> usize {
+        let _gate = Arc::clone(&self.inner.gate).write_owned().await;
+        let commands: Vec<(IpcCommand, Initiator)> = match self.inner.pending.lock() {
+            Ok(mut pending) => pending.drain(..).collect(),
+            Err(_) => return 0,
+        };
+        for (command, initiator) in &commands {
+            match apply_command(command.clone(), initiator, state).await {
+                IpcResponse::Error { message } => {
+                    warn!(?command, error = %message, "Deferred control command failed");
+                }
+                response => info!(
+                    ?command,
+                    response = %response.to_text().trim_end(),
+                    "Applied deferred control command"
+                ),
+            }
+        }
+        commands.len()
+    }
+}
+
+impl Default for ControlQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::time::Duration;
+
+    use crate::ipc::protocol::DaemonStatus;
+
+    #[derive(Default)]
+    struct MockState {
+        paused: AtomicBool,
+        paused_by: Mutex<Option<Initiator>>,
+        reloads: AtomicUsize,
+    }
+
+    impl DaemonStateAccess for MockState {
+        fn get_status(&self) -> DaemonStatus {
+            unimplemented!("not used")
+        }
+
+        fn pause(&self) -> Result<(), String> {
+            self.paused.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn pause_by(&self, initiator: &Initiator) -> Result<(), String> {
+            *self.paused_by.lock().unwrap() = Some(initiator.clone());
+            self.pause()
+        }
+
+        fn resume(&self) -> Result<(), String> {
+            self.paused.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn new_session(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn reload_config(&self) -> Result<(), String> {
+            self.reloads.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn cli() -> Initiator {
+        Initiator::Cli {
+            uid: Some(1000),
+            tty: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn commands_apply_immediately_when_idle() {
+        let queue = ControlQueue::new();
+
+        assert!(matches!(
+            queue.admit(IpcCommand::Reload, &cli()),
+            Admission::Now(_)
+        ));
+        assert!(queue.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn commands_wait_for_in_flight_operations() {
+        let queue = ControlQueue::new();
+        let state = MockState::default();
+        let operation = queue.operation("resume of /tmp/session.md").await;
+
+        let Admission::Deferred(deferral) = queue.admit(IpcCommand::Reload, &cli()) else {
+            panic!("expected deferral");
+        };
+        assert_eq!(
+            deferral.to_string(),
+            "will apply after resume of /tmp/session.md completes"
+        );
+
+        let apply = queue.apply_pending(&state);
+        tokio::pin!(apply);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), &mut apply)
+                .await
+                .is_err()
+        );
+        assert_eq!(state.reloads.load(Ordering::SeqCst), 0);
+
+        drop(operation);
+        assert_eq!(apply.await, 1);
+        assert_eq!(state.reloads.load(Ordering::SeqCst), 1);
+        assert!(queue.operations().is_empty());
+    }
+
+    #[tokio::test]
+    async fn identical_pending_commands_coalesce() {
+        let queue = ControlQueue::new();
+        let state = MockState::default();
+        let operation = queue.operation("resume").await;
+
+        let Admission::Deferred(first) = queue.admit(IpcCommand::Reload, &cli()) else {
+            panic!("expected deferral");
+        };
+        queue.admit(IpcCommand::Pause, &cli());
+        let Admission::Deferred(second) = queue.admit(IpcCommand::Reload, &cli()) else {
+            panic!("expected deferral");
+        };
+
+        assert!(!first.coalesced);
+        assert!(second.coalesced);
+        assert_eq!(queue.pending(), vec![IpcCommand::Pause, IpcCommand::Reload]);
+
+        drop(operation);
+        assert_eq!(queue.apply_pending(&state).await, 2);
+        assert_eq!(state.reloads.load(Ordering::SeqCst), 1);
+        assert!(state.paused.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn latest_of_repeated_commands_wins() {
+        let queue = ControlQueue::new();
+        let state = MockState::default();
+        let operation = queue.operation("resume").await;
+
+        queue.admit(IpcCommand::Pause, &cli());
+        queue.admit(IpcCommand::Resume, &cli());
+        queue.admit(IpcCommand::Pause, &cli());
+        drop(operation);
+        queue.apply_pending(&state).await;
+
+        assert!(state.paused.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn deferred_commands_keep_their_initiator() {
+        let queue = ControlQueue::new();
+        let state = MockState::default();
+        let operation = queue.operation("resume").await;
+        let slack = Initiator::Slack {
+            user_id: "U123".to_string(),
+        };
+
+        queue.admit(IpcCommand::Pause, &cli());
+        queue.admit(IpcCommand::Pause, &slack);
+        drop(operation);
+        queue.apply_pending(&state).await;
+
+        assert_eq!(state.paused_by.lock().unwrap().clone(), Some(slack));
+    }
+
+    #[tokio::test]
+    async fn worker_applies_deferred_commands_after_operation() {
+        let queue = ControlQueue::new();
+        let state = Arc::new(MockState::default());
+        let cancel = CancellationToken::new();
+        let worker = tokio::spawn({
+            let queue = queue.clone();
+            let state = Arc::clone(&state);
+            let cancel = cancel.clone();
+            async move { queue.run(state, cancel).await }
+        });
+
+        let operation = queue.operation("resume").await;
+        queue.admit(IpcCommand::Reload, &cli());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(state.reloads.load(Ordering::SeqCst), 0);
+
+        drop(operation);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while state.reloads.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("reload applied");
+
+        cancel.cancel();
+        worker.await.unwrap();
+    }
+}
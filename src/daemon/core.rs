@@ -1,20 +1,58 @@
-use std::sync::Arc;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use tokio::sync::mpsc;
-use tokio::time;
-use tokio_util::sync::CancellationToken;
 use tracing::{Instrument, error, info, info_span, warn};
 
+use crate::config::schema::DaemonMode;
+#[cfg(feature = "notifications")]
+use crate::config::schema::NotificationsConfig;
+use crate::config::{ResolvedPaths, validate_config};
+use crate::daemon::events::{DaemonEventLoop, EventSources};
+use crate::daemon::gc::{self, ARTIFACT_GC_JOB, ArtifactGc};
+use crate::daemon::handoff::HandoffFile;
+use crate::daemon::jobs::JobPriority;
+use crate::daemon::last_exit::{self, ExitCause, LastExit, LastExitFile};
 use crate::daemon::pid::{PidError, PidFile};
-use crate::daemon::shutdown::{SHUTDOWN_TIMEOUT, ShutdownCoordinator, ShutdownResult};
-use crate::daemon::signals::{DaemonSignal, listen_for_signals};
-use crate::daemon::state::DaemonState;
-use crate::http::{EventBroadcaster, HttpServer};
-use crate::ipc::socket::{DaemonStateAccess, IpcError, IpcServer};
+use crate::daemon::reconcile::{self, Reconciler};
+use crate::daemon::schedules::Scheduler;
+use crate::daemon::shutdown::SHUTDOWN_TIMEOUT;
+use crate::daemon::shutdown::{ShutdownCoordinator, ShutdownResult};
+use crate::daemon::signals::listen_for_signals;
+use crate::daemon::startup::{Startup, Subsystem};
+#[cfg(feature = "http-api")]
+use crate::daemon::state::ReloadableService;
+use crate::daemon::state::{DaemonState, load_config_from_disk};
+use crate::daemon::suspend::{self, SuspendDetector, SystemClock};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+#[cfg(feature = "http-api")]
+use crate::http::HttpSupervisor;
+use crate::i18n;
+use crate::ipc::socket::{IpcError, IpcServer};
+#[cfg(feature = "mcp")]
 use crate::mcp::{McpServer, McpServerError};
-use crate::notify::events::NotificationEvent;
-use crate::opencode::{OpenCodeEvent, OpenCodeMonitor, OpenCodeProcessReceiver};
+use crate::monitor::classifier::StopReasonClassifier;
+use crate::notify::CredentialRegistry;
+#[cfg(feature = "notifications")]
+use crate::notify::{AckRegistry, Dispatcher, Escalator, NotificationMutes};
+#[cfg(feature = "opencode-api")]
+use crate::opencode::OpenCodeClient;
+use crate::opencode::{OpenCodeMonitor, VersionCheck};
+use crate::state::{
+    AuditConfig, AuditLogger, AuditWriter, Changefeed, DEFAULT_AUDIT_FLUSH_INTERVAL,
+    DEFAULT_FLUSH_INTERVAL, STATE_VERSION, StateHandle, StateStore,
+};
+use crate::telemetry::Metrics;
+use crate::util::content;
+use crate::util::path_display::{self, PathDisplay};
+use crate::util::process;
+
+type InitHook = Pin<Box<dyn Future<Output = ()> + Send>>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum DaemonError {
@@ -23,55 +61,160 @@ pub enum DaemonError {
 
     #[error("IPC error: {0}")]
     Ipc(#[from] IpcError),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    #[error("State directory {path} is not writable: {source}")]
+    StateDir {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{server} server failed: {source}")]
+    Server {
+        server: &'static str,
+        source: std::io::Error,
+    },
 }
 
 pub struct Daemon {
+    paths: Arc<ResolvedPaths>,
     pid_file: PidFile,
+    last_exit: LastExitFile,
     ipc_server: IpcServer,
     shutdown: ShutdownCoordinator,
     state: Arc<DaemonState>,
-    http_handle: Option<tokio::task::JoinHandle<()>>,
+    #[cfg(feature = "http-api")]
+    http: Option<Arc<HttpSupervisor>>,
     event_broadcaster: EventBroadcaster,
+    /// First fatal error raised by a server task after startup.
+    fatal: Arc<Mutex<Option<DaemonError>>>,
+    /// Rewrite a state file from a newer schema (`--force-downgrade`).
+    force_downgrade: bool,
+    /// Extra setup each background subsystem waits for before it is ready.
+    init_hooks: Mutex<Vec<(Subsystem, InitHook)>>,
 }
 
 impl Daemon {
     pub fn new() -> Self {
+        Self::with_paths(ResolvedPaths::current())
+    }
+
+    /// A daemon whose PID file, socket and state live at `paths`.
+    pub fn with_paths(paths: Arc<ResolvedPaths>) -> Self {
+        let state = Arc::new(DaemonState::new());
         Self {
-            pid_file: PidFile::new(),
-            ipc_server: IpcServer::new(),
-            shutdown: ShutdownCoordinator::new(),
-            state: Arc::new(DaemonState::new()),
-            http_handle: None,
+            pid_file: PidFile::with_path(paths.pid_file().to_path_buf()),
+            last_exit: LastExitFile::in_dir(paths.state_dir()),
+            ipc_server: IpcServer::with_path(paths.socket_file().to_path_buf()),
+            paths,
+            shutdown: ShutdownCoordinator::with_token(state.shutdown_token()),
+            state,
+            #[cfg(feature = "http-api")]
+            http: None,
             event_broadcaster: EventBroadcaster::default(),
+            fatal: Arc::new(Mutex::new(None)),
+            force_downgrade: false,
+            init_hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Run in `mode` instead of the configured `daemon.mode`.
+    pub fn with_mode(self, mode: DaemonMode) -> Self {
+        self.state.override_mode(mode);
+        self
+    }
+
+    /// Back up and rewrite a state file written by a newer version instead
+    /// of running with it read-only.
+    pub fn with_force_downgrade(mut self, force: bool) -> Self {
+        self.force_downgrade = force;
+        self
+    }
+
+    /// Also run `init` before `subsystem` counts as ready, after its own
+    /// initialization.
+    pub fn with_subsystem_init(
+        mut self,
+        subsystem: Subsystem,
+        init: impl Future<Output = ()> + Send + 'static,
+    ) -> Self {
+        if let Ok(hooks) = self.init_hooks.get_mut() {
+            hooks.push((subsystem, Box::pin(init)));
         }
+        self
+    }
+
+    /// File locations this daemon uses.
+    pub fn paths(&self) -> &ResolvedPaths {
+        &self.paths
+    }
+
+    /// Token that stops [`Self::run`] when cancelled, as a signal would.
+    pub fn shutdown_token(&self) -> tokio_util::sync::CancellationToken {
+        self.shutdown.cancel_token()
     }
 
     pub async fn run(&mut self) -> Result<(), DaemonError> {
         let root_span = info_span!("daemon.run");
         let _enter = root_span.enter();
         info!("Starting daemon");
+        let started = Instant::now();
+        let startup = Startup::new();
+        preflight(&self.paths)?;
         self.pid_file.acquire()?;
+        last_exit::attach_state(&self.state);
+        let previous_exit = self.previous_unexpected_exit();
 
         if let Err(err) = self.ipc_server.bind().await {
             if let Err(release_err) = self.pid_file.release() {
                 error!(error = %release_err, "Failed to release PID file after IPC bind failure");
             }
+            self.record_exit(
+                LastExit::new(ExitCause::Error, err.to_string())
+                    .with_uptime(self.state.uptime())
+                    .with_state("starting"),
+            );
             return Err(err.into());
         }
 
-        if let Err(err) = self
-            .event_broadcaster
-            .send(NotificationEvent::DaemonStarted {
+        let audit_writer = install_audit_writer(&self.paths);
+
+        self.event_broadcaster.publish(DomainEvent::DaemonStarted {
+            timestamp: Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        });
+        if let Some(exit) = previous_exit {
+            self.event_broadcaster.publish(DomainEvent::UnexpectedExit {
                 timestamp: Utc::now(),
-                version: env!("CARGO_PKG_VERSION").to_string(),
-            })
-        {
-            tracing::debug!(error = %err, "No SSE subscribers for daemon_started event (expected at startup)");
+                pid: exit.pid,
+                cause: exit.cause.as_str().to_string(),
+                detail: exit.detail,
+            });
         }
 
+        self.restore_handoff();
+        content::set_retained_chars(
+            self.state
+                .config_snapshot()
+                .daemon
+                .max_retained_content_chars,
+        );
+        path_display::set_global(PathDisplay::from_config(&self.state.config_snapshot()));
         let cancel = self.shutdown.cancel_token();
 
-        let (signal_tx, mut signal_rx) = mpsc::channel(4);
+        let state_handle = self.install_state_handle(&cancel);
+        self.spawn_ipc_server(&cancel);
+        self.reconcile_state(&state_handle, &cancel).await;
+        self.spawn_suspend_monitor(&cancel);
+        self.spawn_secret_refresh(&cancel);
+        #[cfg(feature = "notifications")]
+        self.spawn_escalation(&state_handle, &startup, &cancel);
+        self.spawn_scheduler(&state_handle, &cancel);
+        self.spawn_artifact_gc(&state_handle, &cancel);
+
+        let (signal_tx, signal_rx) = mpsc::channel(4);
         let signal_cancel = cancel.clone();
         let signal_span = info_span!("daemon.signals");
         self.shutdown.register_task(tokio::spawn(
@@ -81,154 +224,804 @@ impl Daemon {
             .instrument(signal_span),
         ));
 
-        let signal_state = Arc::clone(&self.state);
-        let signal_cancel = cancel.clone();
-        let handler_span = info_span!("daemon.signal_handler");
+        let event_loop = DaemonEventLoop::new(Arc::clone(&self.state))
+            .with_version_check(self.version_check(&cancel))
+            .with_audit(AuditLogger::new(self.paths.state_dir()))
+            .with_event_broadcaster(self.event_broadcaster.clone())
+            .with_startup(startup.clone());
+        let mut sources = EventSources {
+            signals: signal_rx,
+            opencode: None,
+            monitor: None,
+            control: Some(self.state.control_events()),
+        };
+
+        if let Some(config) = self.state.opencode_config() {
+            if config.enabled {
+                let monitor = OpenCodeMonitor::new(&config)
+                    .with_endpoint(self.state.opencode_endpoint())
+                    .with_pause(event_loop.pause_receiver());
+                match monitor.run(cancel.clone()).await {
+                    Ok(rx) => sources.opencode = Some(rx),
+                    Err(err) => warn!(error = %err, "Failed to start OpenCode monitor"),
+                }
+            }
+        }
+
+        let loop_cancel = cancel.clone();
+        let loop_span = info_span!("daemon.events");
         self.shutdown.register_task(tokio::spawn(
             async move {
-                while let Some(signal) = signal_rx.recv().await {
-                    match signal {
-                        DaemonSignal::Shutdown => {
-                            signal_cancel.cancel();
-                            break;
-                        }
-                        DaemonSignal::Reload => {
-                            if let Err(err) = signal_state.reload_config() {
-                                error!(error = %err, "Failed to reload configuration");
-                            }
-                        }
-                    }
-                }
+                event_loop.run(sources, loop_cancel).await;
             }
-            .instrument(handler_span),
+            .instrument(loop_span),
         ));
 
-        if self.state.auto_detect_active() {
-            let detection_state = Arc::clone(&self.state);
-            let detection_cancel = cancel.clone();
-            let monitor_span = info_span!("daemon.monitor");
-            self.shutdown.register_task(tokio::spawn(
-                async move {
-                    run_auto_detection(detection_state, detection_cancel).await;
-                }
-                .instrument(monitor_span),
-            ));
+        let control = self.state.control();
+        let control_state = Arc::clone(&self.state);
+        let control_cancel = cancel.clone();
+        let control_span = info_span!("daemon.control");
+        self.shutdown.register_task(tokio::spawn(
+            async move {
+                control.run(control_state, control_cancel).await;
+            }
+            .instrument(control_span),
+        ));
+
+        info!(
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "Watching for session events"
+        );
+
+        tokio::select! {
+            _ = self.initialize_subsystems(&startup, &cancel) => startup.report(),
+            _ = cancel.cancelled() => {}
         }
 
-        if let Some(config) = self.state.opencode_config() {
-            if config.enabled {
-                let monitor = OpenCodeMonitor::new(&config);
-                match monitor.run(cancel.clone()).await {
-                    Ok(rx) => self.spawn_opencode_event_handler(rx, cancel.clone()),
-                    Err(err) => warn!(error = %err, "Failed to start OpenCode monitor"),
-                }
+        cancel.cancelled().await;
+        info!("Shutdown requested");
+        let state_at_shutdown = self.state.state_label();
+        self.state.jobs().cancel();
+        stop_child_processes().await;
+
+        // Send DaemonStopped event BEFORE shutting down HTTP server
+        // so SSE clients can receive it
+        self.event_broadcaster.publish(DomainEvent::DaemonStopped {
+            timestamp: Utc::now(),
+            reason: "shutdown".to_string(),
+        });
+
+        // Give SSE clients a brief moment to receive the event
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let shutdown = std::mem::take(&mut self.shutdown);
+        let mut graceful = match shutdown.shutdown().await {
+            ShutdownResult::Graceful => {
+                info!("Shutdown completed");
+                true
+            }
+            ShutdownResult::TimedOut { hung_tasks } => {
+                warn!(hung_tasks, "Shutdown timed out");
+                false
+            }
+        };
+
+        #[cfg(feature = "http-api")]
+        if let Some(http) = self.http.take() {
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, http.stop())
+                .await
+                .is_err()
+            {
+                warn!("HTTP server shutdown timed out");
             }
         }
 
-        if let Some(config) = self.state.daemon_config() {
-            match HttpServer::from_config(
-                &config,
-                cancel.clone(),
-                Arc::clone(&self.state),
-                self.event_broadcaster.clone(),
-            ) {
-                Ok(Some(server)) => {
-                    let server_cancel = cancel.clone();
-                    let http_span = info_span!("daemon.http");
-                    let handle = tokio::spawn(
-                        async move {
-                            if let Err(err) = server.start().await {
-                                error!(error = %err, "HTTP server stopped with error");
-                                server_cancel.cancel();
-                            }
-                        }
-                        .instrument(http_span),
-                    );
-                    self.http_handle = Some(handle);
-                }
-                Ok(None) => {}
-                Err(err) => {
-                    warn!(error = %err, "Failed to configure HTTP server");
-                }
+        if let Err(err) = state_handle.flush() {
+            error!(error = %err, "Failed to flush state on shutdown");
+            graceful = false;
+        }
+        if let Some(writer) = audit_writer {
+            if !writer.shutdown(SHUTDOWN_TIMEOUT).await {
+                warn!("Timed out draining the audit log queue");
+            }
+        }
+
+        let released = self.pid_file.release();
+        let fatal = self.fatal.lock().ok().and_then(|mut fatal| fatal.take());
+        let (cause, detail) = match &fatal {
+            Some(err) => (ExitCause::Error, err.to_string()),
+            None => last_exit::take_shutdown_cause(),
+        };
+        self.record_exit(
+            LastExit::new(cause, detail)
+                .with_uptime(self.state.uptime())
+                .with_state(state_at_shutdown)
+                .with_graceful(graceful && fatal.is_none() && released.is_ok()),
+        );
+
+        released?;
+        match fatal {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// How the previous instance ended, if it did not end on request.
+    ///
+    /// Logged here; a kill inferred from a stale PID file is also recorded,
+    /// so `status --verbose` shows it while this instance runs.
+    fn previous_unexpected_exit(&self) -> Option<LastExit> {
+        let record = self.last_exit.read().unwrap_or_else(|err| {
+            warn!(error = %err, "Failed to read the last exit record");
+            None
+        });
+        let exit = last_exit::unexpected_exit(record, self.pid_file.stale_pid())?;
+        error!(
+            pid = exit.pid,
+            cause = exit.cause.as_str(),
+            detail = %exit.detail,
+            at = %exit.timestamp,
+            "Previous daemon instance terminated unexpectedly"
+        );
+        if exit.inferred {
+            self.record_exit(exit.clone());
+        }
+        Some(exit)
+    }
+
+    fn record_exit(&self, exit: LastExit) {
+        if let Err(err) = self.last_exit.write(&exit) {
+            warn!(error = %err, path = %self.last_exit.path().display(), "Failed to write the last exit record");
+        }
+    }
+}
+
+/// Fail fast on problems a restart cannot fix before taking the PID file.
+fn preflight(paths: &ResolvedPaths) -> Result<(), DaemonError> {
+    let config = load_config_from_disk().map_err(DaemonError::Config)?;
+    let validation = validate_config(&config);
+    if let Some(error) = validation.errors.first() {
+        return Err(DaemonError::Config(format!(
+            "{}: {}",
+            error.field, error.message
+        )));
+    }
+    let notifications = &config.notifications;
+    i18n::warn_missing(
+        std::iter::once(config.daemon.locale)
+            .chain(notifications.ntfy.iter().filter_map(|ntfy| ntfy.locale))
+            .chain(
+                notifications
+                    .discord
+                    .iter()
+                    .filter_map(|discord| discord.locale),
+            )
+            .chain(notifications.slack.iter().filter_map(|slack| slack.locale)),
+    );
+
+    let state_dir = paths.state_dir().to_path_buf();
+    let probe = state_dir.join(".palingenesis-write-test");
+    std::fs::create_dir_all(&state_dir)
+        .and_then(|()| std::fs::write(&probe, b""))
+        .and_then(|()| std::fs::remove_file(&probe))
+        .map_err(|source| DaemonError::StateDir {
+            path: state_dir,
+            source,
+        })
+}
+
+/// Move audit writes for this process onto a background writer.
+fn install_audit_writer(paths: &ResolvedPaths) -> Option<AuditWriter> {
+    let config = AuditConfig {
+        audit_path: paths.state_dir().join("audit.jsonl"),
+        ..AuditConfig::default()
+    };
+    let writer = match AuditWriter::spawn(config, DEFAULT_AUDIT_FLUSH_INTERVAL) {
+        Ok(writer) => writer,
+        Err(err) => {
+            warn!(error = %err, "Failed to start audit writer; audit entries are written inline");
+            return None;
+        }
+    };
+    if !AuditWriter::set_global(writer) {
+        warn!("Audit writer already installed; reusing it");
+    }
+    AuditWriter::global()
+}
+
+/// Tune the shared stop-reason classifier from the `[classifier]` section.
+fn apply_classifier_config(state: &DaemonState) {
+    let config = state.config_snapshot();
+    if let Err(err) = StopReasonClassifier::apply_config(&config.classifier) {
+        warn!(error = %err, "Invalid classifier config, using built-in defaults");
+    }
+}
+
+/// Create the shared metrics registry unless something already did.
+fn install_metrics() {
+    if Metrics::global().is_none() {
+        let _ = Metrics::set_global(Arc::new(Metrics::new()));
+    }
+}
+
+/// Remove the hooks registered for `subsystem` and run them in order.
+fn take_hooks(
+    hooks: &mut Vec<(Subsystem, InitHook)>,
+    subsystem: Subsystem,
+) -> impl Future<Output = ()> + use<> {
+    let (taken, rest) = std::mem::take(hooks)
+        .into_iter()
+        .partition::<Vec<_>, _>(|(owner, _)| *owner == subsystem);
+    *hooks = rest;
+    async move {
+        for (_, hook) in taken {
+            hook.await;
+        }
+    }
+}
+
+/// Stop hooks, strategies and session creators still running, with their
+/// descendants, before the tasks waiting on them are cancelled.
+async fn stop_child_processes() {
+    let stopped = process::terminate_all(process::DEFAULT_GRACE).await;
+    if stopped.is_empty() {
+        return;
+    }
+    let killed = stopped.iter().filter(|stop| stop.escalated()).count();
+    if killed > 0 {
+        warn!(
+            stopped = stopped.len(),
+            killed, "Child processes ignored SIGTERM on shutdown and were killed"
+        );
+    } else {
+        info!(
+            stopped = stopped.len(),
+            "Stopped child processes on shutdown"
+        );
+    }
+}
+
+fn record_fatal(slot: &Mutex<Option<DaemonError>>, err: DaemonError) {
+    if let Ok(mut fatal) = slot.lock() {
+        fatal.get_or_insert(err);
+    }
+}
+
+impl Daemon {
+    /// Restore runtime state left by `daemon restart`, if the snapshot is fresh.
+    fn restore_handoff(&self) {
+        match HandoffFile::new().take(Utc::now()) {
+            Ok(Some(snapshot)) => {
+                self.state.restore_runtime(&snapshot);
+                info!(
+                    from_pid = snapshot.pid,
+                    restored = %snapshot.describe(),
+                    "Restored runtime state from restart handoff"
+                );
             }
-        } else {
-            warn!("Config lock poisoned; skipping HTTP server startup");
+            Ok(None) => {}
+            Err(err) => warn!(error = %err, "Ignoring restart handoff file"),
         }
+    }
 
+    /// Serve IPC requests, so `status` answers while the rest starts up.
+    fn spawn_ipc_server(&mut self, cancel: &tokio_util::sync::CancellationToken) {
         let server = std::mem::take(&mut self.ipc_server);
         let server_state = Arc::clone(&self.state);
         let server_cancel = cancel.clone();
+        let server_fatal = Arc::clone(&self.fatal);
         let ipc_span = info_span!("daemon.ipc");
         self.shutdown.register_task(tokio::spawn(
             async move {
                 let error_cancel = server_cancel.clone();
                 if let Err(err) = server.run(server_state, server_cancel).await {
                     error!(error = %err, "IPC server stopped with error");
+                    record_fatal(&server_fatal, DaemonError::Ipc(err));
                     error_cancel.cancel();
                 }
             }
             .instrument(ipc_span),
         ));
+    }
 
-        cancel.cancelled().await;
-        info!("Shutdown requested");
+    /// Bring up the heavier subsystems concurrently, each marked ready in
+    /// `startup` once done, then the servers that depend on them.
+    async fn initialize_subsystems(
+        &mut self,
+        startup: &Startup,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) {
+        let mut hooks = self
+            .init_hooks
+            .get_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
 
-        // Send DaemonStopped event BEFORE shutting down HTTP server
-        // so SSE clients can receive it
-        if let Err(err) = self
-            .event_broadcaster
-            .send(NotificationEvent::DaemonStopped {
-                timestamp: Utc::now(),
-                reason: "shutdown".to_string(),
+        let metrics_hooks = take_hooks(&mut hooks, Subsystem::Metrics);
+        let metrics = startup.init(Subsystem::Metrics, async {
+            let _ = tokio::task::spawn_blocking(install_metrics).await;
+            metrics_hooks.await;
+        });
+
+        let classifier_hooks = take_hooks(&mut hooks, Subsystem::Classifier);
+        let state = Arc::clone(&self.state);
+        let classifier = startup.init(Subsystem::Classifier, async move {
+            // Compiling the configured patterns is CPU-bound.
+            let _ = tokio::task::spawn_blocking(move || apply_classifier_config(&state)).await;
+            classifier_hooks.await;
+        });
+
+        let notification_hooks = take_hooks(&mut hooks, Subsystem::Notifications);
+        let notifications_config = self.state.notifications_config().unwrap_or_default();
+        let notifications = startup.init(Subsystem::Notifications, async move {
+            // Keychain lookups block.
+            let _ = tokio::task::spawn_blocking(move || {
+                CredentialRegistry::global().register_configured(&notifications_config)
             })
-        {
-            tracing::debug!(error = %err, "No SSE subscribers to receive daemon_stopped event");
+            .await;
+            notification_hooks.await;
+        });
+
+        let http_hooks = take_hooks(&mut hooks, Subsystem::Http);
+        let http = async {
+            // The API serves the shared registry rather than starting its own.
+            startup.wait_ready(Subsystem::Metrics).await;
+            startup
+                .init(Subsystem::Http, async {
+                    self.spawn_http_server(cancel).await;
+                    http_hooks.await;
+                })
+                .await;
+        };
+
+        tokio::join!(metrics, classifier, notifications, http);
+
+        self.spawn_metrics_push(cancel);
+
+        #[cfg(feature = "grpc")]
+        self.spawn_grpc_server(cancel);
+
+        #[cfg(feature = "dbus")]
+        self.spawn_dbus_service(cancel);
+    }
+
+    /// Make a write-behind state handle the single state writer for this process.
+    fn install_state_handle(
+        &mut self,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) -> StateHandle {
+        let config = self.state.daemon_config();
+        let format = config
+            .as_ref()
+            .map(|config| config.state_format)
+            .unwrap_or_default();
+        let store = StateStore::with_path(self.paths.state_file())
+            .with_format(format)
+            .with_force_downgrade(self.force_downgrade);
+        let changefeed = Arc::new(Changefeed::new(
+            self.paths.state_dir().join("changes.jsonl"),
+        ));
+        if !StateHandle::set_global(StateHandle::new(store).with_changefeed(changefeed)) {
+            warn!("State handle already installed; reusing it");
+        }
+        let handle = StateHandle::global_or_default();
+        if let Some(found_version) = handle.newer_version() {
+            self.event_broadcaster.publish(DomainEvent::StateReadOnly {
+                timestamp: Utc::now(),
+                state_file: self.paths.state_file(),
+                found_version,
+                supported_version: STATE_VERSION,
+            });
         }
+        let interval = config
+            .map(|config| Duration::from_millis(config.state_flush_interval_ms))
+            .unwrap_or(DEFAULT_FLUSH_INTERVAL);
+        self.shutdown
+            .register_task(handle.spawn_flusher(interval, cancel.clone()));
+        handle
+    }
 
-        // Give SSE clients a brief moment to receive the event
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    /// Correct what an unclean shutdown left in the state file before
+    /// anything acts on it.
+    async fn reconcile_state(
+        &self,
+        state_handle: &StateHandle,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) {
+        let config = self.state.config_snapshot();
+        if !config.daemon.reconcile.enabled || state_handle.newer_version().is_some() {
+            return;
+        }
+        let live = reconcile::live_session_paths(
+            &config,
+            Some(self.state.opencode_endpoint()),
+            cancel.clone(),
+        )
+        .await;
+        let reconciler = Reconciler::new(&config.daemon.reconcile).with_live_sessions(live);
+        if let Err(err) = reconcile::reconcile_state(
+            state_handle,
+            &reconciler,
+            self.paths.state_dir(),
+            &self.event_broadcaster,
+        ) {
+            warn!(error = %err, "Failed to reconcile the state file");
+        }
+    }
 
-        let shutdown = std::mem::take(&mut self.shutdown);
-        match shutdown.shutdown().await {
-            ShutdownResult::Graceful => info!("Shutdown completed"),
-            ShutdownResult::TimedOut { hung_tasks } => {
-                warn!(hung_tasks, "Shutdown timed out")
-            }
+    fn version_check(&self, cancel: &tokio_util::sync::CancellationToken) -> VersionCheck {
+        let check = VersionCheck::new().with_event_broadcaster(self.event_broadcaster.clone());
+        #[cfg(feature = "opencode-api")]
+        if let Some(config) = self.state.opencode_config().filter(|config| config.enabled) {
+            return check.with_client(
+                OpenCodeClient::new(&config)
+                    .with_endpoint(self.state.opencode_endpoint())
+                    .with_cancellation(cancel.clone()),
+            );
         }
+        #[cfg(not(feature = "opencode-api"))]
+        let _ = cancel;
+        check
+    }
 
-        if let Some(handle) = self.http_handle.take() {
-            match time::timeout(SHUTDOWN_TIMEOUT, handle).await {
-                Ok(Ok(())) => info!("HTTP server stopped"),
-                Ok(Err(err)) => warn!(error = %err, "HTTP server task failed"),
-                Err(_) => warn!("HTTP server shutdown timed out"),
-            }
+    fn spawn_suspend_monitor(&mut self, cancel: &tokio_util::sync::CancellationToken) {
+        let Some(config) = self.state.daemon_config() else {
+            return;
+        };
+        if config.suspend_check_interval_secs == 0 {
+            return;
         }
+        let detector = SuspendDetector::new(
+            Arc::new(SystemClock),
+            Duration::from_secs(config.suspend_threshold_secs),
+        );
+        self.shutdown.register_task(suspend::spawn_monitor(
+            detector,
+            Duration::from_secs(config.suspend_check_interval_secs),
+            self.event_broadcaster.clone(),
+            cancel.clone(),
+        ));
+    }
 
-        self.pid_file.release()?;
-        Ok(())
+    /// Fire `[[schedules]]` entries, catching up missed ones first.
+    fn spawn_scheduler(
+        &mut self,
+        state_handle: &StateHandle,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) {
+        let scheduler = Arc::new(
+            Scheduler::new(state_handle.clone())
+                .with_event_broadcaster(self.event_broadcaster.clone())
+                .with_audit(AuditLogger::new(self.paths.state_dir())),
+        );
+        self.state.set_scheduler(Arc::clone(&scheduler));
+        let state = Arc::clone(&self.state);
+        let cancel = cancel.clone();
+        let span = info_span!("daemon.schedules");
+        self.shutdown
+            .register_task(tokio::spawn(scheduler.run(state, cancel).instrument(span)));
+    }
+
+    /// Queue the `[maintenance]` artifact GC as a background job every
+    /// `interval_secs`, the first time shortly after startup.
+    fn spawn_artifact_gc(
+        &mut self,
+        state_handle: &StateHandle,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) {
+        let state = Arc::clone(&self.state);
+        let store = state_handle.clone();
+        let state_dir = self.paths.state_dir().to_path_buf();
+        let events = self.event_broadcaster.clone();
+        let cancel = cancel.clone();
+        let span = info_span!("daemon.artifact_gc");
+        self.shutdown.register_task(tokio::spawn(
+            async move {
+                let mut wait = gc::STARTUP_DELAY;
+                loop {
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = tokio::time::sleep(wait) => {}
+                    }
+                    let config = state.config_snapshot().maintenance;
+                    // While disabled, look again later in case a reload enables it.
+                    wait = Duration::from_secs(if config.enabled {
+                        config.interval_secs.max(1)
+                    } else {
+                        60
+                    });
+                    let jobs = state.jobs();
+                    if !config.enabled || jobs.contains(ARTIFACT_GC_JOB) {
+                        continue;
+                    }
+                    let gc = ArtifactGc::new(&state.config_snapshot(), &state_dir);
+                    let store = store.clone();
+                    let events = events.clone();
+                    jobs.enqueue(ARTIFACT_GC_JOB, JobPriority::Background, async move {
+                        gc::run_scheduled(&gc, &store.snapshot(), &events).await;
+                    });
+                }
+            }
+            .instrument(span),
+        ));
+    }
+
+    /// Re-resolve the file- and keyring-backed credentials of the configured
+    /// channels every `notifications.secret_refresh_secs`.
+    fn spawn_secret_refresh(&mut self, cancel: &tokio_util::sync::CancellationToken) {
+        let state = Arc::clone(&self.state);
+        let cancel = cancel.clone();
+        let span = info_span!("daemon.secret_refresh");
+        self.shutdown.register_task(tokio::spawn(
+            async move {
+                loop {
+                    let secs = state
+                        .notifications_config()
+                        .map_or(0, |config| config.secret_refresh_secs);
+                    // While disabled, look again later in case a reload enables it.
+                    let wait = Duration::from_secs(if secs == 0 { 60 } else { secs });
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = tokio::time::sleep(wait) => {}
+                    }
+                    if secs > 0 {
+                        // Keychain lookups block.
+                        let _ = tokio::task::spawn_blocking(|| {
+                            CredentialRegistry::global().refresh_rotatable()
+                        })
+                        .await;
+                    }
+                }
+            }
+            .instrument(span),
+        ));
     }
 }
 
-async fn run_auto_detection(state: Arc<DaemonState>, cancel: CancellationToken) {
-    let mut interval = time::interval(state.auto_detect_interval());
-    loop {
-        tokio::select! {
-            _ = cancel.cancelled() => {
-                break;
+#[cfg(feature = "notifications")]
+impl Daemon {
+    /// Walk incidents opened by published resume failures through the
+    /// `notifications.escalation` tiers, rebuilding the policy on reload,
+    /// and lift notification mutes whose duration is up.
+    ///
+    /// Events published before the notification channels are ready wait in
+    /// the subscription until they are.
+    fn spawn_escalation(
+        &mut self,
+        state_handle: &StateHandle,
+        startup: &Startup,
+        cancel: &tokio_util::sync::CancellationToken,
+    ) {
+        use tokio::sync::broadcast::error::RecvError;
+
+        let mut events = self.event_broadcaster.subscribe();
+        let state = Arc::clone(&self.state);
+        let handle = state_handle.clone();
+        let mutes = NotificationMutes::new(state_handle.clone());
+        let startup = startup.clone();
+        let cancel = cancel.clone();
+        let span = info_span!("daemon.escalation");
+        self.shutdown.register_task(tokio::spawn(
+            async move {
+                tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    _ = startup.wait_ready(Subsystem::Notifications) => {}
+                }
+                let mut current: Option<(NotificationsConfig, Option<Escalator>)> = None;
+                loop {
+                    let config = state.config_snapshot();
+                    if current
+                        .as_ref()
+                        .is_none_or(|(notifications, _)| *notifications != config.notifications)
+                    {
+                        let escalator = escalator_for(&config, &handle);
+                        current = Some((config.notifications.clone(), escalator));
+                    }
+                    let escalator = current
+                        .as_ref()
+                        .and_then(|(_, escalator)| escalator.as_ref());
+                    // While disabled, look again later in case a reload enables it.
+                    let interval = Duration::from_secs(
+                        config.notifications.escalation.check_interval_secs.max(1),
+                    );
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = tokio::time::sleep(interval) => {}
+                        received = events.recv() => match received {
+                            Ok(event) => {
+                                if let Some(escalator) = escalator {
+                                    escalator.observe(&event);
+                                }
+                            }
+                            Err(RecvError::Lagged(missed)) => {
+                                warn!(missed, "Escalation fell behind the event stream");
+                            }
+                            Err(RecvError::Closed) => return,
+                        },
+                    }
+                    if let Some(escalator) = escalator {
+                        escalator.tick().await;
+                    }
+                    mutes.expire();
+                }
             }
-            _ = interval.tick() => {
-                state.refresh_auto_detected_assistants();
+            .instrument(span),
+        ));
+    }
+}
+
+/// The escalation policy of `config`, if notifications are on and it has tiers.
+#[cfg(feature = "notifications")]
+fn escalator_for(config: &crate::config::Config, handle: &StateHandle) -> Option<Escalator> {
+    let notifications = &config.notifications;
+    if !notifications.enabled || notifications.escalation.is_empty() {
+        return None;
+    }
+    let escalator = Escalator::new(
+        &notifications.escalation,
+        Dispatcher::from_config(notifications, config.daemon.locale)
+            .with_mutes(NotificationMutes::new(handle.clone())),
+        handle.clone(),
+    )
+    .with_acks(
+        AckRegistry::global_or_init(notifications),
+        notifications.ack_base_url.clone(),
+    );
+    let open = escalator.restore();
+    info!(
+        tiers = notifications.escalation.tiers.len(),
+        open, "Escalation policy active"
+    );
+    Some(escalator)
+}
+
+#[cfg(feature = "http-api")]
+impl Daemon {
+    /// Start the HTTP API under a supervisor that follows config reloads.
+    async fn spawn_http_server(&mut self, cancel: &tokio_util::sync::CancellationToken) {
+        let config = self.state.config_snapshot();
+        let supervisor = Arc::new(HttpSupervisor::new(
+            &self.state,
+            self.event_broadcaster.clone(),
+            cancel.clone(),
+        ));
+        if let Err(err) = supervisor.start(&config).await {
+            error!(error = %format!("{err:#}"), "HTTP server failed to start");
+            record_fatal(&self.fatal, server_error("HTTP", &err));
+            cancel.cancel();
+        }
+        self.state
+            .register_reloadable(Arc::clone(&supervisor) as Arc<dyn ReloadableService>);
+        self.http = Some(supervisor);
+    }
+}
+
+/// Keep the I/O kind of a server failure so the exit code can tell a busy
+/// port from a crash.
+#[cfg(feature = "http-api")]
+fn server_error(server: &'static str, err: &anyhow::Error) -> DaemonError {
+    let kind = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .map(std::io::Error::kind)
+        .unwrap_or(std::io::ErrorKind::Other);
+    DaemonError::Server {
+        server,
+        source: std::io::Error::new(kind, format!("{err:#}")),
+    }
+}
+
+#[cfg(not(feature = "http-api"))]
+impl Daemon {
+    async fn spawn_http_server(&mut self, _cancel: &tokio_util::sync::CancellationToken) {
+        if self
+            .state
+            .daemon_config()
+            .is_some_and(|config| config.http_enabled)
+        {
+            warn!("HTTP API is enabled but this build does not include the `http-api` feature");
+        }
+    }
+}
+
+#[cfg(feature = "metrics-push")]
+impl Daemon {
+    /// Start pushing to the configured Pushgateway, sharing the HTTP API's
+    /// metrics registry when the API is running.
+    fn spawn_metrics_push(&mut self, cancel: &tokio_util::sync::CancellationToken) {
+        let Some(pusher) = self
+            .state
+            .otel_config()
+            .and_then(|otel| crate::telemetry::push::MetricsPusher::from_config(&otel))
+        else {
+            return;
+        };
+        let metrics = crate::telemetry::Metrics::global().unwrap_or_else(|| {
+            let metrics = Arc::new(crate::telemetry::Metrics::new());
+            let _ = crate::telemetry::Metrics::set_global(Arc::clone(&metrics));
+            metrics
+        });
+        let push_state = Arc::clone(&self.state);
+        let push_cancel = cancel.clone();
+        let push_span = info_span!("daemon.metrics_push");
+        self.shutdown.register_task(tokio::spawn(
+            pusher
+                .run(metrics, push_state, push_cancel)
+                .instrument(push_span),
+        ));
+    }
+}
+
+#[cfg(not(feature = "metrics-push"))]
+impl Daemon {
+    fn spawn_metrics_push(&mut self, _cancel: &tokio_util::sync::CancellationToken) {
+        if self.state.otel_config().is_some_and(|otel| {
+            otel.push_mode == crate::config::schema::MetricsPushMode::Pushgateway
+        }) {
+            warn!(
+                "Metrics push is enabled but this build does not include the `metrics-push` feature"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl Daemon {
+    fn spawn_grpc_server(&mut self, cancel: &tokio_util::sync::CancellationToken) {
+        let Some(config) = self
+            .state
+            .daemon_config()
+            .and_then(|config| config.grpc.clone())
+        else {
+            return;
+        };
+
+        match crate::grpc::GrpcServer::from_config(
+            &config,
+            cancel.clone(),
+            Arc::clone(&self.state),
+            self.event_broadcaster.clone(),
+        ) {
+            Ok(Some(server)) => {
+                let server_cancel = cancel.clone();
+                let grpc_span = info_span!("daemon.grpc");
+                self.shutdown.register_task(tokio::spawn(
+                    async move {
+                        if let Err(err) = server.serve().await {
+                            error!(error = %err, "gRPC server stopped with error");
+                            server_cancel.cancel();
+                        }
+                    }
+                    .instrument(grpc_span),
+                ));
             }
+            Ok(None) => {}
+            Err(err) => warn!(error = %err, "Failed to configure gRPC server"),
         }
     }
 }
 
+#[cfg(feature = "dbus")]
+impl Daemon {
+    fn spawn_dbus_service(&mut self, cancel: &tokio_util::sync::CancellationToken) {
+        let server = crate::dbus::DbusServer::new(
+            Arc::clone(&self.state) as Arc<dyn crate::ipc::socket::DaemonStateAccess>,
+            self.event_broadcaster.clone(),
+            cancel.clone(),
+        );
+        let dbus_span = info_span!("daemon.dbus");
+        self.shutdown
+            .register_task(tokio::spawn(server.run().instrument(dbus_span)));
+    }
+}
+
 impl Default for Daemon {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "mcp")]
 pub async fn run_mcp_server(state: Arc<DaemonState>) -> Result<(), McpServerError> {
     let mut shutdown = ShutdownCoordinator::new();
     let cancel = shutdown.cancel_token();
@@ -285,37 +1078,3 @@ pub async fn run_mcp_server(state: Arc<DaemonState>) -> Result<(), McpServerErro
 
     Ok(())
 }
-
-impl Daemon {
-    fn spawn_opencode_event_handler(
-        &mut self,
-        mut rx: OpenCodeProcessReceiver,
-        cancel: CancellationToken,
-    ) {
-        let opencode_span = info_span!("daemon.opencode");
-        self.shutdown.register_task(tokio::spawn(
-            async move {
-                loop {
-                    tokio::select! {
-                        _ = cancel.cancelled() => break,
-                        event = rx.recv() => {
-                            match event {
-                                Some(OpenCodeEvent::OpenCodeStarted(process)) => {
-                                    info!(pid = process.pid, "OpenCode started");
-                                }
-                                Some(OpenCodeEvent::OpenCodeStopped { process, reason }) => {
-                                    warn!(pid = process.pid, reason = ?reason, "OpenCode stopped");
-                                }
-                                Some(OpenCodeEvent::OpenCodeCrashed { process, exit_code }) => {
-                                    warn!(pid = process.pid, exit_code, "OpenCode crashed");
-                                }
-                                None => break,
-                            }
-                        }
-                    }
-                }
-            }
-            .instrument(opencode_span),
-        ));
-    }
-}
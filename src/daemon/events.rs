@@ -0,0 +1,995 @@
+//! Event-driven daemon core loop.
+//!
+//! Every source the daemon reacts to (signals, OpenCode process events,
+//! session monitor events, control requests applied through IPC/HTTP, and
+//! internal timers) is turned into a [`DaemonEvent`] and handled sequentially
+//! by [`DaemonEventLoop`]. Timers are only armed when work is actually
+//! pending, so a paused daemon with nothing scheduled never wakes up on its
+//! own.
+//!
+//! Session stops pass through a [`RestartCorrelator`] before they are
+//! dispatched, so a stop caused by an opencode crash waits for the restart
+//! instead of racing it with a resume. Stops that arrive while the
+//! stop-reason classifier is still initializing queue until it is ready.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Utc;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Instant, sleep_until};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::daemon::control::Admission;
+use crate::daemon::initiator::Initiator;
+use crate::daemon::jobs::JobPriority;
+use crate::daemon::last_exit::{ExitCause, note_shutdown_cause};
+use crate::daemon::restart_correlation::{
+    CorrelationAction, CorrelationSettings, HeldOutcome, RestartCorrelator,
+};
+use crate::daemon::signals::DaemonSignal;
+use crate::daemon::startup::{Startup, Subsystem};
+use crate::daemon::state::DaemonState;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::ipc::protocol::IpcCommand;
+use crate::ipc::socket::DaemonStateAccess;
+use crate::monitor::events::{MonitorEvent, MonitorEventReceiver, MonitorEventSender};
+use crate::opencode::version::VERSION_CHECK_JOB;
+use crate::opencode::{OpenCodeEvent, OpenCodeProcessReceiver, VersionCheck};
+use crate::state::{AuditLogger, StateHandle};
+use crate::telemetry::Metrics;
+use crate::util::encoding::EncodingFixups;
+
+/// Job name of the periodic assistant scan.
+const AUTO_DETECT_JOB: &str = "auto_detect";
+
+/// Control request that has been applied to [`DaemonState`].
+///
+/// IPC and HTTP handlers mutate state synchronously; the state then forwards
+/// one of these so the core loop can re-plan timers and pollers, and audit
+/// and announce who asked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlEvent {
+    Paused(Initiator),
+    Resumed(Initiator),
+    NewSession(Initiator),
+    ConfigReloaded,
+    /// A restart handoff snapshot was written; the daemon should exit.
+    HandoffWritten,
+}
+
+impl ControlEvent {
+    /// The audited, notified form of an operator's action.
+    pub fn applied_event(&self) -> Option<DomainEvent> {
+        let (action, initiator) = match self {
+            Self::Paused(initiator) => ("pause", initiator),
+            Self::Resumed(initiator) => ("resume", initiator),
+            Self::NewSession(initiator) => ("new_session", initiator),
+            Self::ConfigReloaded | Self::HandoffWritten => return None,
+        };
+        Some(DomainEvent::ControlApplied {
+            timestamp: Utc::now(),
+            action: action.to_string(),
+            initiator: initiator.clone(),
+        })
+    }
+}
+
+pub const CONTROL_CHANNEL_CAPACITY: usize = 16;
+
+pub type ControlSender = mpsc::Sender<ControlEvent>;
+pub type ControlReceiver = mpsc::Receiver<ControlEvent>;
+
+/// Timers owned by the core loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonTimer {
+    AutoDetect,
+    /// A stop held by the crash correlation is due for release.
+    CrashCorrelation,
+}
+
+/// Single event type processed by the daemon core loop.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DaemonEvent {
+    Signal(DaemonSignal),
+    OpenCode(OpenCodeEvent),
+    Monitor(Box<MonitorEvent>),
+    Control(ControlEvent),
+    TimerElapsed(DaemonTimer),
+    /// A subsystem finished initializing in the background.
+    Ready(Subsystem),
+}
+
+/// Channels feeding the core loop.
+pub struct EventSources {
+    pub signals: mpsc::Receiver<DaemonSignal>,
+    pub opencode: Option<OpenCodeProcessReceiver>,
+    pub monitor: Option<MonitorEventReceiver>,
+    pub control: Option<ControlReceiver>,
+}
+
+pub struct DaemonEventLoop {
+    state: Arc<DaemonState>,
+    pause_tx: watch::Sender<bool>,
+    auto_detect_at: Option<Instant>,
+    timer_wakeups: Arc<AtomicU64>,
+    version_check: Option<Arc<VersionCheck>>,
+    correlator: RestartCorrelator,
+    monitor_dispatch: Option<MonitorEventSender>,
+    audit: Option<AuditLogger>,
+    events: Option<EventBroadcaster>,
+    startup: Option<Startup>,
+    /// Stops received before the classifier was ready, oldest first.
+    awaiting_classifier: Vec<MonitorEvent>,
+}
+
+impl DaemonEventLoop {
+    pub fn new(state: Arc<DaemonState>) -> Self {
+        let (pause_tx, _) = watch::channel(state.is_paused());
+        let correlator = RestartCorrelator::new(correlation_settings(&state));
+        Self {
+            state,
+            pause_tx,
+            auto_detect_at: None,
+            timer_wakeups: Arc::new(AtomicU64::new(0)),
+            version_check: None,
+            correlator,
+            monitor_dispatch: None,
+            audit: None,
+            events: None,
+            startup: None,
+            awaiting_classifier: Vec::new(),
+        }
+    }
+
+    /// Forward monitor events here once crash correlation lets them through.
+    pub fn with_monitor_dispatch(mut self, tx: MonitorEventSender) -> Self {
+        self.monitor_dispatch = Some(tx);
+        self
+    }
+
+    /// Record server-down windows and held stops in the audit trail.
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Announce control actions (pause, resume, new session) and who asked.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Override the `[opencode]` crash timings (for testing).
+    pub fn with_correlation_settings(mut self, settings: CorrelationSettings) -> Self {
+        self.correlator.set_settings(settings);
+        self
+    }
+
+    /// Hold session stops until `startup` reports the classifier ready.
+    /// Without one, every subsystem counts as ready.
+    pub fn with_startup(mut self, startup: Startup) -> Self {
+        self.startup = Some(startup);
+        self
+    }
+
+    /// Check the opencode version at startup and whenever opencode starts.
+    pub fn with_version_check(mut self, check: VersionCheck) -> Self {
+        self.version_check = Some(Arc::new(check));
+        self
+    }
+
+    /// Pause flag observed by pollers that must stop while the daemon is paused.
+    pub fn pause_receiver(&self) -> watch::Receiver<bool> {
+        self.pause_tx.subscribe()
+    }
+
+    /// Number of times a loop-owned timer has fired.
+    pub fn timer_wakeups(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.timer_wakeups)
+    }
+
+    pub async fn run(mut self, mut sources: EventSources, cancel: CancellationToken) {
+        self.reschedule();
+        self.enqueue_version_check();
+
+        while let Some(event) = self.next_event(&mut sources, &cancel).await {
+            self.handle(event, &cancel);
+            if cancel.is_cancelled() {
+                break;
+            }
+        }
+
+        debug!("Daemon event loop stopped");
+    }
+
+    async fn next_event(
+        &self,
+        sources: &mut EventSources,
+        cancel: &CancellationToken,
+    ) -> Option<DaemonEvent> {
+        loop {
+            tokio::select! {
+                biased;
+                _ = cancel.cancelled() => return None,
+                signal = sources.signals.recv() => match signal {
+                    Some(signal) => return Some(DaemonEvent::Signal(signal)),
+                    // The signal listener only exits once shutdown has started.
+                    None => return None,
+                },
+                control = recv_optional(&mut sources.control) => match control {
+                    Some(control) => return Some(DaemonEvent::Control(control)),
+                    None => sources.control = None,
+                },
+                event = recv_optional(&mut sources.opencode) => match event {
+                    Some(event) => return Some(DaemonEvent::OpenCode(event)),
+                    None => {
+                        debug!("OpenCode event channel closed");
+                        sources.opencode = None;
+                        if !cancel.is_cancelled() {
+                            self.state.mark_watcher_lost();
+                        }
+                    }
+                },
+                event = recv_optional(&mut sources.monitor) => match event {
+                    Some(event) => return Some(DaemonEvent::Monitor(Box::new(event))),
+                    None => {
+                        debug!("Monitor event channel closed");
+                        sources.monitor = None;
+                        if !cancel.is_cancelled() {
+                            self.state.mark_watcher_lost();
+                        }
+                    }
+                },
+                timer = wait_for_timer(self.auto_detect_at, DaemonTimer::AutoDetect) => {
+                    return Some(DaemonEvent::TimerElapsed(timer));
+                }
+                timer = wait_for_timer(
+                    self.correlator.next_deadline(),
+                    DaemonTimer::CrashCorrelation,
+                ) => {
+                    return Some(DaemonEvent::TimerElapsed(timer));
+                }
+                _ = wait_for_ready(self.startup.as_ref(), Subsystem::Classifier),
+                    if !self.awaiting_classifier.is_empty() =>
+                {
+                    return Some(DaemonEvent::Ready(Subsystem::Classifier));
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, event: DaemonEvent, cancel: &CancellationToken) {
+        match event {
+            DaemonEvent::Signal(DaemonSignal::Shutdown) => cancel.cancel(),
+            DaemonEvent::Signal(DaemonSignal::Reload) => {
+                // A successful reload reports back as `ControlEvent::ConfigReloaded`;
+                // services such as the HTTP API restart in the background.
+                let initiator = Initiator::internal("sighup");
+                let guard = match self.state.control().admit(IpcCommand::Reload, &initiator) {
+                    Admission::Now(guard) => guard,
+                    Admission::Deferred(deferral) => {
+                        info!(%deferral, "SIGHUP reload deferred");
+                        return;
+                    }
+                };
+                let state = Arc::clone(&self.state);
+                tokio::spawn(async move {
+                    let _guard = guard;
+                    if let Err(err) = state.reload().await {
+                        error!(error = %err, "Failed to reload configuration");
+                    }
+                });
+            }
+            DaemonEvent::OpenCode(event) => {
+                let now = Instant::now();
+                let actions = match &event {
+                    OpenCodeEvent::OpenCodeCrashed { process, exit_code } => {
+                        self.correlator.on_crash(process.clone(), *exit_code, now)
+                    }
+                    OpenCodeEvent::OpenCodeHealthy(_) => self.correlator.on_healthy(now),
+                    OpenCodeEvent::OpenCodeStarted(_) | OpenCodeEvent::OpenCodeStopped { .. } => {
+                        Vec::new()
+                    }
+                };
+                let started = matches!(event, OpenCodeEvent::OpenCodeStarted(_));
+                handle_opencode_event(event);
+                self.apply_correlation(actions);
+                if started {
+                    self.enqueue_version_check();
+                }
+            }
+            DaemonEvent::Monitor(event) => {
+                let now = Instant::now();
+                let event = *event;
+                match event {
+                    MonitorEvent::SessionStopped { .. } if !self.classifier_ready() => {
+                        debug!(
+                            queued = self.awaiting_classifier.len() + 1,
+                            "Session stop queued until the classifier is ready"
+                        );
+                        self.awaiting_classifier.push(event);
+                    }
+                    MonitorEvent::SessionStopped { .. } => {
+                        let actions = self.correlator.on_stop(event, now);
+                        self.apply_correlation(actions);
+                    }
+                    MonitorEvent::SessionChanged { ref session, .. } => {
+                        let actions = self.correlator.on_session_activity(&session.path, now);
+                        self.dispatch(event);
+                        self.apply_correlation(actions);
+                    }
+                    event => self.dispatch(event),
+                }
+                self.report_encoding_fixups();
+            }
+            DaemonEvent::Control(ControlEvent::HandoffWritten) => {
+                info!("Handoff snapshot written; shutting down for restart");
+                note_shutdown_cause(ExitCause::Handoff, "daemon restart");
+                cancel.cancel();
+            }
+            DaemonEvent::Control(control) => {
+                debug!(control = ?control, "Control request applied");
+                if let Some(event) = control.applied_event() {
+                    self.report_control(&event);
+                }
+                if control == ControlEvent::ConfigReloaded {
+                    self.correlator
+                        .set_settings(correlation_settings(&self.state));
+                }
+                self.reschedule();
+            }
+            DaemonEvent::TimerElapsed(DaemonTimer::AutoDetect) => {
+                self.timer_wakeups.fetch_add(1, Ordering::Relaxed);
+                self.auto_detect_at = None;
+                self.enqueue_auto_detect();
+                self.reschedule();
+            }
+            DaemonEvent::TimerElapsed(DaemonTimer::CrashCorrelation) => {
+                self.timer_wakeups.fetch_add(1, Ordering::Relaxed);
+                let actions = self.correlator.on_deadline(Instant::now());
+                self.apply_correlation(actions);
+            }
+            DaemonEvent::Ready(Subsystem::Classifier) => {
+                let queued = std::mem::take(&mut self.awaiting_classifier);
+                info!(
+                    queued = queued.len(),
+                    "Classifier ready; processing queued session stops"
+                );
+                for event in queued {
+                    let actions = self.correlator.on_stop(event, Instant::now());
+                    self.apply_correlation(actions);
+                }
+            }
+            DaemonEvent::Ready(_) => {}
+        }
+    }
+
+    fn classifier_ready(&self) -> bool {
+        self.startup
+            .as_ref()
+            .is_none_or(|startup| startup.is_ready(Subsystem::Classifier))
+    }
+
+    /// Carry out what the crash correlation decided, and publish its state.
+    fn apply_correlation(&mut self, actions: Vec<CorrelationAction>) {
+        if actions.is_empty() {
+            return;
+        }
+        for action in actions {
+            let event = match action {
+                CorrelationAction::Proceed(event) => {
+                    self.dispatch(*event);
+                    continue;
+                }
+                CorrelationAction::WindowOpened { pid, exit_code } => {
+                    info!(
+                        pid,
+                        exit_code, "Holding session stops until opencode restarts"
+                    );
+                    DomainEvent::ServerDownWindowOpened {
+                        timestamp: Utc::now(),
+                        pid,
+                        exit_code,
+                    }
+                }
+                CorrelationAction::WindowClosed {
+                    pid,
+                    restarted,
+                    down,
+                } => {
+                    if restarted {
+                        info!(pid, down_secs = down.as_secs(), "OpenCode back after crash");
+                    } else {
+                        warn!(
+                            pid,
+                            down_secs = down.as_secs(),
+                            "OpenCode did not restart in time"
+                        );
+                    }
+                    DomainEvent::ServerDownWindowClosed {
+                        timestamp: Utc::now(),
+                        pid,
+                        restarted,
+                        down_secs: down.as_secs(),
+                    }
+                }
+                CorrelationAction::Held {
+                    session_path,
+                    stop_reason,
+                    pid,
+                } => {
+                    info!(session = %session_path.display(), %stop_reason, pid, "Stop held for opencode restart");
+                    DomainEvent::StopHeld {
+                        timestamp: Utc::now(),
+                        session_path,
+                        stop_reason,
+                        pid,
+                    }
+                }
+                CorrelationAction::Released {
+                    session_path,
+                    stop_reason,
+                    outcome,
+                    held,
+                } => {
+                    info!(
+                        session = %session_path.display(),
+                        outcome = outcome.as_str(),
+                        held_secs = held.as_secs(),
+                        "Held stop released"
+                    );
+                    if outcome == HeldOutcome::Avoided {
+                        if let Some(metrics) = Metrics::global() {
+                            metrics.record_resume_avoided_by_restart();
+                        }
+                    }
+                    DomainEvent::HeldStopReleased {
+                        timestamp: Utc::now(),
+                        session_path,
+                        stop_reason,
+                        outcome: outcome.as_str().to_string(),
+                        held_secs: held.as_secs(),
+                    }
+                }
+            };
+            if let Some(audit) = &self.audit {
+                if let Err(err) = audit.record(&event) {
+                    warn!(error = %err, "Failed to audit crash correlation");
+                }
+            }
+        }
+        self.state.set_restart_correlation(self.correlator.status());
+    }
+
+    fn report_control(&self, event: &DomainEvent) {
+        if let Some(audit) = &self.audit {
+            if let Err(err) = audit.record(event) {
+                warn!(error = %err, "Failed to audit control action");
+            }
+        }
+        if let Some(events) = &self.events {
+            events.publish(event.clone());
+        }
+    }
+
+    /// Warn once about each session file that had to be transcoded or have
+    /// its byte order mark stripped.
+    fn report_encoding_fixups(&self) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        for fixup in EncodingFixups::shared().take_pending() {
+            events.publish(DomainEvent::FileEncodingFixed {
+                timestamp: Utc::now(),
+                file: fixup.path,
+                encoding: fixup.encoding.label().to_string(),
+            });
+        }
+    }
+
+    fn dispatch(&self, event: MonitorEvent) {
+        let Some(tx) = &self.monitor_dispatch else {
+            return;
+        };
+        if let Err(err) = tx.try_send(event) {
+            warn!(error = %err, "Monitor event not dispatched");
+        }
+    }
+
+    /// Run the assistant scan as a background job, skipping it while the
+    /// previous scan is still queued or running.
+    fn enqueue_auto_detect(&self) {
+        let jobs = self.state.jobs();
+        if jobs.contains(AUTO_DETECT_JOB) {
+            debug!("Previous auto-detect scan still pending; skipping");
+            return;
+        }
+        let state = Arc::clone(&self.state);
+        jobs.enqueue(AUTO_DETECT_JOB, JobPriority::Background, async move {
+            let scan =
+                tokio::task::spawn_blocking(move || state.refresh_auto_detected_assistants());
+            if let Err(err) = scan.await {
+                warn!(error = %err, "Auto-detect scan failed");
+            }
+        });
+    }
+
+    /// Re-read the opencode version in the background; a check already
+    /// queued covers this request too.
+    fn enqueue_version_check(&self) {
+        let Some(check) = self.version_check.clone() else {
+            return;
+        };
+        let jobs = self.state.jobs();
+        if jobs.contains(VERSION_CHECK_JOB) {
+            return;
+        }
+        jobs.enqueue(VERSION_CHECK_JOB, JobPriority::Background, async move {
+            check.run(&StateHandle::global_or_default()).await;
+        });
+    }
+
+    /// Re-plan timers and pollers from the current daemon state.
+    fn reschedule(&mut self) {
+        let paused = self.state.is_paused();
+        self.pause_tx.send_if_modified(|current| {
+            let changed = *current != paused;
+            *current = paused;
+            changed
+        });
+
+        if paused || !self.state.auto_detect_active() {
+            self.auto_detect_at = None;
+        } else if self.auto_detect_at.is_none() {
+            self.auto_detect_at = Some(Instant::now() + self.state.auto_detect_interval());
+        }
+    }
+}
+
+async fn recv_optional<T>(rx: &mut Option<mpsc::Receiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn wait_for_ready(startup: Option<&Startup>, subsystem: Subsystem) {
+    match startup {
+        Some(startup) => startup.wait_ready(subsystem).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn wait_for_timer(deadline: Option<Instant>, timer: DaemonTimer) -> DaemonTimer {
+    match deadline {
+        Some(deadline) => {
+            sleep_until(deadline).await;
+            timer
+        }
+        None => std::future::pending().await,
+    }
+}
+
+fn handle_opencode_event(event: OpenCodeEvent) {
+    match event {
+        OpenCodeEvent::OpenCodeStarted(process) => {
+            info!(pid = process.pid, started_at = ?process.start_time, "OpenCode started");
+        }
+        OpenCodeEvent::OpenCodeStopped { process, reason } => {
+            warn!(pid = process.pid, reason = ?reason, "OpenCode stopped");
+        }
+        OpenCodeEvent::OpenCodeCrashed { process, exit_code } => {
+            warn!(pid = process.pid, exit_code, "OpenCode crashed");
+        }
+        OpenCodeEvent::OpenCodeHealthy(process) => {
+            debug!(pid = process.pid, "OpenCode healthy");
+        }
+    }
+}
+
+fn correlation_settings(state: &DaemonState) -> CorrelationSettings {
+    state
+        .opencode_config()
+        .map(|config| CorrelationSettings::from_config(&config))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::monitor::classifier::{
+        ClassificationResult, RateLimitInfo, RetryAfterSource, StopReason,
+    };
+    use crate::monitor::session::{Session, SessionState};
+    use crate::opencode::OpenCodeProcess;
+    use crate::state::audit::AuditEventType;
+
+    struct Harness {
+        signal_tx: mpsc::Sender<DaemonSignal>,
+        opencode_tx: mpsc::Sender<OpenCodeEvent>,
+        monitor_tx: mpsc::Sender<MonitorEvent>,
+        dispatched: mpsc::Receiver<MonitorEvent>,
+        wakeups: Arc<AtomicU64>,
+        pause_rx: watch::Receiver<bool>,
+        cancel: CancellationToken,
+        handle: tokio::task::JoinHandle<()>,
+    }
+
+    fn spawn_loop(state: Arc<DaemonState>) -> Harness {
+        spawn_event_loop(DaemonEventLoop::new(Arc::clone(&state)), &state)
+    }
+
+    fn spawn_event_loop(event_loop: DaemonEventLoop, state: &DaemonState) -> Harness {
+        let (signal_tx, signals) = mpsc::channel(4);
+        let (opencode_tx, opencode) = mpsc::channel(4);
+        let (monitor_tx, monitor) = mpsc::channel(4);
+        let (dispatch_tx, dispatched) = mpsc::channel(4);
+        let control = state.control_events();
+        let event_loop = event_loop.with_monitor_dispatch(dispatch_tx);
+        let wakeups = event_loop.timer_wakeups();
+        let pause_rx = event_loop.pause_receiver();
+        let cancel = CancellationToken::new();
+        let sources = EventSources {
+            signals,
+            opencode: Some(opencode),
+            monitor: Some(monitor),
+            control: Some(control),
+        };
+        let handle = tokio::spawn(event_loop.run(sources, cancel.clone()));
+        Harness {
+            signal_tx,
+            opencode_tx,
+            monitor_tx,
+            dispatched,
+            wakeups,
+            pause_rx,
+            cancel,
+            handle,
+        }
+    }
+
+    /// Loop with short, fixed crash timings and an audit trail in `audit_dir`.
+    fn spawn_correlating_loop(state: &Arc<DaemonState>, audit_dir: &std::path::Path) -> Harness {
+        let event_loop = DaemonEventLoop::new(Arc::clone(state))
+            .with_audit(AuditLogger::new(audit_dir))
+            .with_correlation_settings(CorrelationSettings {
+                settle: Duration::from_secs(2),
+                restart_timeout: Duration::from_secs(120),
+                grace: Duration::from_secs(60),
+            });
+        spawn_event_loop(event_loop, state)
+    }
+
+    fn opencode_server() -> OpenCodeProcess {
+        OpenCodeProcess {
+            pid: 3,
+            command_line: vec!["opencode".to_string(), "serve".to_string()],
+            start_time: None,
+            working_dir: None,
+        }
+    }
+
+    fn session(path: &str) -> Session {
+        Session {
+            path: PathBuf::from(path),
+            state: SessionState {
+                steps_completed: Vec::new(),
+                last_step: None,
+                total_steps: None,
+                status: Some("in-progress".to_string()),
+                workflow_type: None,
+                project_name: None,
+                input_documents: Vec::new(),
+                model: None,
+                input_tokens: None,
+                output_tokens: None,
+            },
+        }
+    }
+
+    fn rate_limited_stop(path: &str) -> MonitorEvent {
+        let reason = StopReason::RateLimit(RateLimitInfo {
+            retry_after: Duration::from_secs(30),
+            source: RetryAfterSource::ConfigDefault,
+            message: None,
+        });
+        MonitorEvent::SessionStopped {
+            session: Some(session(path)),
+            classification: ClassificationResult {
+                reason: reason.clone(),
+                confidence: 0.9,
+                evidence: Vec::new(),
+            },
+            reason,
+            process_info: None,
+            incident: None,
+        }
+    }
+
+    fn dispatched_stops(harness: &mut Harness) -> usize {
+        let mut stops = 0;
+        while let Ok(event) = harness.dispatched.try_recv() {
+            if matches!(event, MonitorEvent::SessionStopped { .. }) {
+                stops += 1;
+            }
+        }
+        stops
+    }
+
+    fn correlation_audit(dir: &std::path::Path) -> Vec<String> {
+        AuditLogger::new(dir)
+            .query()
+            .event_types(vec![AuditEventType::RestartCorrelation])
+            .execute()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action_taken)
+            .collect()
+    }
+
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn auto_detect_state() -> Arc<DaemonState> {
+        let state = DaemonState::new_without_auto_detection();
+        state.set_auto_detect_active(true);
+        Arc::new(state)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_signal_stops_loop() {
+        let harness = spawn_loop(Arc::new(DaemonState::new_without_auto_detection()));
+
+        harness
+            .signal_tx
+            .send(DaemonSignal::Shutdown)
+            .await
+            .unwrap();
+        harness.handle.await.unwrap();
+        assert!(harness.cancel.is_cancelled());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handoff_control_event_stops_loop() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(DaemonState::new_without_auto_detection().with_handoff_file(
+            crate::daemon::handoff::HandoffFile::with_path(temp.path().join("handoff.json")),
+        ));
+        let harness = spawn_loop(Arc::clone(&state));
+
+        state.handoff().unwrap();
+        harness.handle.await.unwrap();
+        assert!(harness.cancel.is_cancelled());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keeps_running_after_opencode_channel_closes() {
+        let harness = spawn_loop(Arc::new(DaemonState::new_without_auto_detection()));
+        let process = opencode_server();
+
+        harness
+            .opencode_tx
+            .send(OpenCodeEvent::OpenCodeStarted(process.clone()))
+            .await
+            .unwrap();
+        harness
+            .opencode_tx
+            .send(OpenCodeEvent::OpenCodeCrashed {
+                process,
+                exit_code: 1,
+            })
+            .await
+            .unwrap();
+        drop(harness.opencode_tx);
+
+        settle().await;
+        assert!(!harness.handle.is_finished());
+
+        harness.cancel.cancel();
+        harness.handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_timer_wakeups_while_paused() {
+        let state = auto_detect_state();
+        state.pause().unwrap();
+        let harness = spawn_loop(Arc::clone(&state));
+
+        tokio::time::advance(Duration::from_secs(24 * 60 * 60)).await;
+        settle().await;
+        assert_eq!(harness.wakeups.load(Ordering::Relaxed), 0);
+        assert!(*harness.pause_rx.borrow());
+
+        harness.cancel.cancel();
+        harness.handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pause_disarms_and_resume_rearms_timer() {
+        let state = auto_detect_state();
+        let interval = state.auto_detect_interval();
+        let mut harness = spawn_loop(Arc::clone(&state));
+        settle().await;
+
+        state.pause().unwrap();
+        harness.pause_rx.changed().await.unwrap();
+        assert!(*harness.pause_rx.borrow());
+
+        tokio::time::advance(interval * 3).await;
+        settle().await;
+        assert_eq!(harness.wakeups.load(Ordering::Relaxed), 0);
+
+        state.resume().unwrap();
+        harness.pause_rx.changed().await.unwrap();
+        assert!(!*harness.pause_rx.borrow());
+
+        tokio::time::advance(interval).await;
+        settle().await;
+        assert_eq!(harness.wakeups.load(Ordering::Relaxed), 1);
+
+        harness.cancel.cancel();
+        harness.handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn crash_then_stop_is_dropped_when_session_continues() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let mut harness = spawn_correlating_loop(&state, temp.path());
+
+        harness
+            .opencode_tx
+            .send(OpenCodeEvent::OpenCodeCrashed {
+                process: opencode_server(),
+                exit_code: 1,
+            })
+            .await
+            .unwrap();
+        settle().await;
+        harness
+            .monitor_tx
+            .send(rate_limited_stop("/w/session.md"))
+            .await
+            .unwrap();
+        settle().await;
+        tokio::time::advance(Duration::from_secs(30)).await;
+        settle().await;
+        assert_eq!(
+            dispatched_stops(&mut harness),
+            0,
+            "held while opencode is down"
+        );
+        let status = state.get_status().restart_correlation.unwrap();
+        assert_eq!(status.crashed_pid, Some(3));
+        assert_eq!(status.held_sessions, vec!["/w/session.md".to_string()]);
+
+        // The restarted server picks the session up again.
+        harness
+            .monitor_tx
+            .send(MonitorEvent::SessionChanged {
+                session: session("/w/session.md"),
+                previous: None,
+            })
+            .await
+            .unwrap();
+        harness
+            .opencode_tx
+            .send(OpenCodeEvent::OpenCodeHealthy(opencode_server()))
+            .await
+            .unwrap();
+        settle().await;
+        tokio::time::advance(Duration::from_secs(300)).await;
+        settle().await;
+        assert_eq!(dispatched_stops(&mut harness), 0);
+
+        let status = state.get_status().restart_correlation.unwrap();
+        assert_eq!(status.resumes_avoided, 1);
+        assert!(status.server_down_since.is_none());
+        assert!(status.held_sessions.is_empty());
+        assert_eq!(
+            correlation_audit(temp.path()),
+            vec![
+                "opencode crashed; holding stops until it restarts",
+                "Stop held for opencode restart",
+                "opencode restarted",
+                "Session continued after restart; resume avoided",
+            ]
+        );
+
+        harness.cancel.cancel();
+        harness.handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_then_crash_proceeds_once_after_grace() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let mut harness = spawn_correlating_loop(&state, temp.path());
+
+        // The monitor classifies the stop before the crash is reported.
+        harness
+            .monitor_tx
+            .send(rate_limited_stop("/w/session.md"))
+            .await
+            .unwrap();
+        settle().await;
+        tokio::time::advance(Duration::from_secs(1)).await;
+        harness
+            .opencode_tx
+            .send(OpenCodeEvent::OpenCodeCrashed {
+                process: opencode_server(),
+                exit_code: 1,
+            })
+            .await
+            .unwrap();
+        settle().await;
+        tokio::time::advance(Duration::from_secs(10)).await;
+        settle().await;
+        assert_eq!(
+            dispatched_stops(&mut harness),
+            0,
+            "held while opencode is down"
+        );
+
+        harness
+            .opencode_tx
+            .send(OpenCodeEvent::OpenCodeHealthy(opencode_server()))
+            .await
+            .unwrap();
+        settle().await;
+        tokio::time::advance(Duration::from_secs(59)).await;
+        settle().await;
+        assert_eq!(dispatched_stops(&mut harness), 0, "session still has grace");
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        settle().await;
+        assert_eq!(dispatched_stops(&mut harness), 1);
+        tokio::time::advance(Duration::from_secs(300)).await;
+        settle().await;
+        assert_eq!(
+            dispatched_stops(&mut harness),
+            0,
+            "exactly one corrective action"
+        );
+        assert_eq!(
+            correlation_audit(temp.path()).last().map(String::as_str),
+            Some("Session still stopped after restart; resuming")
+        );
+
+        harness.cancel.cancel();
+        harness.handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_without_crash_proceeds_after_settling() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let mut harness = spawn_correlating_loop(&state, temp.path());
+
+        harness
+            .monitor_tx
+            .send(rate_limited_stop("/w/session.md"))
+            .await
+            .unwrap();
+        settle().await;
+        assert_eq!(dispatched_stops(&mut harness), 0);
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        settle().await;
+        assert_eq!(dispatched_stops(&mut harness), 1);
+        assert!(correlation_audit(temp.path()).is_empty());
+        assert!(state.get_status().restart_correlation.is_none());
+
+        harness.cancel.cancel();
+        harness.handle.await.unwrap();
+    }
+}
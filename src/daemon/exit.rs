@@ -0,0 +1,179 @@
+//! Exit-code contract for `daemon start --foreground`.
+//!
+//! Supervisors restart on some failures and give up on others, so every
+//! foreground exit maps to a stable [`ExitReason`] and ends with one JSON line
+//! on stderr describing it.
+
+use std::io;
+
+use serde::Serialize;
+
+use crate::daemon::core::DaemonError;
+use crate::daemon::pid::PidError;
+use crate::ipc::socket::IpcError;
+
+/// Why the foreground daemon exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    /// Clean shutdown (signal or IPC stop).
+    Clean,
+    /// Invalid configuration; restarting will not help.
+    ConfigError,
+    /// Socket, port or PID file already in use; retrying may help.
+    ResourceConflict,
+    /// A path the daemon needs is not accessible.
+    PermissionError,
+    /// Any other failure.
+    RuntimeError,
+}
+
+impl ExitReason {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitReason::Clean => 0,
+            ExitReason::ConfigError => 2,
+            ExitReason::ResourceConflict => 3,
+            ExitReason::PermissionError => 4,
+            ExitReason::RuntimeError => 5,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExitReason::Clean => "clean",
+            ExitReason::ConfigError => "config_error",
+            ExitReason::ResourceConflict => "resource_conflict",
+            ExitReason::PermissionError => "permission_error",
+            ExitReason::RuntimeError => "runtime_error",
+        }
+    }
+
+    /// Classify an error returned by the start path.
+    ///
+    /// The first daemon or I/O error in the chain decides; anything else is a
+    /// runtime error.
+    pub fn classify(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(err) = cause.downcast_ref::<DaemonError>() {
+                return err.exit_reason();
+            }
+            if let Some(err) = cause.downcast_ref::<io::Error>() {
+                return Self::from_io(err);
+            }
+        }
+        ExitReason::RuntimeError
+    }
+
+    pub(crate) fn from_io(error: &io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => {
+                ExitReason::PermissionError
+            }
+            io::ErrorKind::AddrInUse | io::ErrorKind::AlreadyExists => ExitReason::ResourceConflict,
+            _ => ExitReason::RuntimeError,
+        }
+    }
+}
+
+/// Final line written to stderr before the foreground daemon exits.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitReport {
+    pub exit_reason: ExitReason,
+    pub detail: String,
+}
+
+impl ExitReport {
+    pub fn from_result(result: &anyhow::Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                exit_reason: ExitReason::Clean,
+                detail: "shutdown".to_string(),
+            },
+            Err(err) => Self {
+                exit_reason: ExitReason::classify(err),
+                detail: format!("{err:#}"),
+            },
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        self.exit_reason.code()
+    }
+
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self)
+            .unwrap_or_else(|_| format!("{{\"exit_reason\":\"{}\"}}", self.exit_reason.as_str()))
+    }
+}
+
+impl DaemonError {
+    pub fn exit_reason(&self) -> ExitReason {
+        match self {
+            DaemonError::Config(_) => ExitReason::ConfigError,
+            DaemonError::Pid(PidError::AlreadyRunning { .. }) => ExitReason::ResourceConflict,
+            DaemonError::Pid(PidError::Io(err)) => ExitReason::from_io(err),
+            DaemonError::Pid(_) => ExitReason::RuntimeError,
+            DaemonError::Ipc(IpcError::AlreadyBound | IpcError::InUse { .. }) => {
+                ExitReason::ResourceConflict
+            }
+            DaemonError::Ipc(IpcError::Io(err)) => ExitReason::from_io(err),
+            DaemonError::Ipc(_) => ExitReason::RuntimeError,
+            DaemonError::StateDir { source, .. } | DaemonError::Server { source, .. } => {
+                ExitReason::from_io(source)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn codes_are_stable() {
+        let codes: Vec<i32> = [
+            ExitReason::Clean,
+            ExitReason::ConfigError,
+            ExitReason::ResourceConflict,
+            ExitReason::PermissionError,
+            ExitReason::RuntimeError,
+        ]
+        .iter()
+        .map(|reason| reason.code())
+        .collect();
+        assert_eq!(codes, [0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn classifies_wrapped_errors() {
+        let err = anyhow::Error::from(DaemonError::Pid(PidError::AlreadyRunning { pid: 7 }));
+        assert_eq!(ExitReason::classify(&err), ExitReason::ResourceConflict);
+
+        let err = anyhow::Error::from(io::Error::from(io::ErrorKind::AddrInUse))
+            .context("Failed to bind HTTP API");
+        assert_eq!(ExitReason::classify(&err), ExitReason::ResourceConflict);
+
+        let err = anyhow::Error::from(DaemonError::StateDir {
+            path: PathBuf::from("/state"),
+            source: io::Error::from(io::ErrorKind::PermissionDenied),
+        });
+        assert_eq!(ExitReason::classify(&err), ExitReason::PermissionError);
+
+        assert_eq!(
+            ExitReason::classify(&anyhow::anyhow!("boom")),
+            ExitReason::RuntimeError
+        );
+    }
+
+    #[test]
+    fn report_is_one_json_line() {
+        let result: anyhow::Result<()> = Err(DaemonError::Config("bad port".to_string()).into());
+        let report = ExitReport::from_result(&result);
+        assert_eq!(report.code(), 2);
+        let value: serde_json::Value = serde_json::from_str(&report.to_json_line()).unwrap();
+        assert_eq!(value["exit_reason"], "config_error");
+        assert_eq!(value["detail"], "Invalid configuration: bad port");
+    }
+}
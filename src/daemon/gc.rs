@@ -0,0 +1,621 @@
+//! Garbage collection of session artifacts (`[maintenance]`, `palingenesis gc`).
+//!
+//! Sessions leave files behind that nothing else removes: backups of
+//! completed sessions, incident snapshots in `incidents/`, postmortem
+//! bundles in `failures/`, and Next-step.md files in directories whose
+//! sessions are gone. [`ArtifactGc`] finds the ones past their retention and
+//! deletes them, auditing each deletion. It never touches the active
+//! session's artifacts or anything modified within `safety_window_secs`.
+//!
+//! The daemon runs it as a background job every `interval_secs`. After the
+//! retention settings change, a run only reports what it would delete: one
+//! `artifact_gc_pending` notification, with the plan kept in [`PLAN_FILE`].
+//! Nothing is deleted until `palingenesis gc approve` records the settings
+//! in [`APPROVAL_FILE`], unless `auto_approve` is set.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::config::schema::{Config, MaintenanceConfig};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::monitor::incident::IncidentStore;
+use crate::resume::{BackupConfig, FailureStore, NewSessionConfig, SessionBackup};
+use crate::state::{AuditLogger, StateFile};
+use crate::util::size::format_size;
+
+/// Latest plan that waited for approval, in the state directory.
+pub const PLAN_FILE: &str = "gc-plan.json";
+
+/// Retention settings approved with `palingenesis gc approve`, in the state
+/// directory.
+pub const APPROVAL_FILE: &str = "gc-approval.json";
+
+/// Job name of the scheduled run in the daemon's job queue.
+pub const ARTIFACT_GC_JOB: &str = "artifact_gc";
+
+/// Wait before the daemon's first run, so the scan stays out of startup.
+pub const STARTUP_DELAY: Duration = Duration::from_secs(300);
+
+/// What an artifact is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// A backup of a completed session (`backup_retention_days`).
+    Backup,
+    /// A stop-time transcript snapshot (`incident_retention_days`).
+    Incident,
+    /// A postmortem bundle of a completed session
+    /// (`failure_bundles_on_complete`).
+    FailureBundle,
+    /// A Next-step.md none of whose sessions exist (`orphaned_next_steps`).
+    NextStep,
+}
+
+impl ArtifactKind {
+    pub const ALL: [Self; 4] = [
+        Self::Backup,
+        Self::Incident,
+        Self::FailureBundle,
+        Self::NextStep,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Backup => "backup",
+            Self::Incident => "incident",
+            Self::FailureBundle => "failure_bundle",
+            Self::NextStep => "next_step",
+        }
+    }
+
+    /// Plural, for summaries.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Backup => "backups",
+            Self::Incident => "incident snapshots",
+            Self::FailureBundle => "failure bundles",
+            Self::NextStep => "Next-step files",
+        }
+    }
+}
+
+impl std::fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A file or directory the GC deletes (or, in a dry run, would delete).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: PathBuf,
+    /// Files deleted along with `path` (an incident's classification record).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub companions: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_path: Option<PathBuf>,
+    /// Newest modification time of anything in it.
+    pub modified: DateTime<Utc>,
+    pub bytes: u64,
+}
+
+impl Artifact {
+    /// Stat `path` and `companions`; `None` when `path` is gone.
+    fn scan(
+        kind: ArtifactKind,
+        path: PathBuf,
+        companions: Vec<PathBuf>,
+        session_path: Option<PathBuf>,
+    ) -> Option<Self> {
+        let (mut modified, mut bytes) = usage(&path)?;
+        for companion in &companions {
+            if let Some((companion_modified, companion_bytes)) = usage(companion) {
+                modified = modified.max(companion_modified);
+                bytes += companion_bytes;
+            }
+        }
+        Some(Self {
+            kind,
+            path,
+            companions,
+            session_path,
+            modified,
+            bytes,
+        })
+    }
+
+    fn remove(&self) -> io::Result<()> {
+        match self.kind {
+            ArtifactKind::FailureBundle => fs::remove_dir_all(&self.path)?,
+            _ => fs::remove_file(&self.path)?,
+        }
+        for companion in &self.companions {
+            match fs::remove_file(companion) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The audit event for deleting this artifact at `timestamp`.
+    fn deleted_event(&self, timestamp: DateTime<Utc>) -> DomainEvent {
+        DomainEvent::ArtifactDeleted {
+            timestamp,
+            artifact: self.kind.as_str().to_string(),
+            path: self.path.clone(),
+            bytes: self.bytes,
+            modified: self.modified,
+            session_path: self.session_path.clone(),
+        }
+    }
+}
+
+/// Outcome of one GC pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GcReport {
+    pub ran_at: DateTime<Utc>,
+    /// Nothing was deleted; `artifacts` is what would have been.
+    pub dry_run: bool,
+    /// Retention settings the pass ran under (see [`ArtifactGc::fingerprint`]).
+    pub fingerprint: String,
+    pub artifacts: Vec<Artifact>,
+    /// Past retention but kept: modified within the safety window, or the
+    /// active session's.
+    pub held_back: usize,
+    /// Deletions that failed, one line each.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<String>,
+}
+
+impl GcReport {
+    pub fn is_empty(&self) -> bool {
+        self.artifacts.is_empty()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.bytes).sum()
+    }
+
+    /// Count and size per kind, in [`ArtifactKind::ALL`] order.
+    pub fn totals(&self) -> Vec<(ArtifactKind, usize, u64)> {
+        let mut totals: BTreeMap<ArtifactKind, (usize, u64)> = ArtifactKind::ALL
+            .into_iter()
+            .map(|kind| (kind, (0, 0)))
+            .collect();
+        for artifact in &self.artifacts {
+            let total = totals.entry(artifact.kind).or_default();
+            total.0 += 1;
+            total.1 += artifact.bytes;
+        }
+        totals
+            .into_iter()
+            .map(|(kind, (count, bytes))| (kind, count, bytes))
+            .collect()
+    }
+
+    /// One line per kind with artifacts, e.g. `backups: 3 (12.0 KB)`.
+    pub fn summary(&self) -> Vec<String> {
+        self.totals()
+            .into_iter()
+            .filter(|(_, count, _)| *count > 0)
+            .map(|(kind, count, bytes)| {
+                format!("{}: {count} ({})", kind.label(), format_size(bytes))
+            })
+            .collect()
+    }
+
+    /// The `artifact_gc_pending` event for this plan, written to `plan`.
+    pub fn pending_event(&self, plan: PathBuf) -> DomainEvent {
+        DomainEvent::ArtifactGcPending {
+            timestamp: self.ran_at,
+            artifacts: self.artifacts.len(),
+            bytes: self.bytes(),
+            summary: self.summary(),
+            plan,
+        }
+    }
+
+    /// Plan last written to [`PLAN_FILE`] in `state_dir`.
+    pub fn load_plan(state_dir: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(state_dir.join(PLAN_FILE)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn save_plan(&self, state_dir: &Path) -> io::Result<PathBuf> {
+        let path = state_dir.join(PLAN_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+/// Retention settings someone approved for deletion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approval {
+    pub fingerprint: String,
+    pub approved_at: DateTime<Utc>,
+}
+
+impl Approval {
+    pub fn load(state_dir: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(state_dir.join(APPROVAL_FILE)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Write to [`APPROVAL_FILE`] in `state_dir`, replacing any earlier one.
+    pub fn save(&self, state_dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(state_dir)?;
+        let path = state_dir.join(APPROVAL_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+/// Finds and deletes session artifacts past their retention.
+#[derive(Debug, Clone)]
+pub struct ArtifactGc {
+    config: MaintenanceConfig,
+    state_dir: PathBuf,
+    backups: BackupConfig,
+    next_step_filename: String,
+    now: DateTime<Utc>,
+}
+
+impl ArtifactGc {
+    pub fn new(config: &Config, state_dir: &Path) -> Self {
+        Self {
+            config: config.maintenance.clone(),
+            state_dir: state_dir.to_path_buf(),
+            backups: BackupConfig::from_resume_config(&config.resume, &config.monitoring),
+            next_step_filename: NewSessionConfig::default().next_step_filename,
+            now: Utc::now(),
+        }
+    }
+
+    /// Measure ages against this time (for testing).
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = now;
+        self
+    }
+
+    /// Identifies the settings that decide what is deleted; approval is
+    /// given for one fingerprint. Scheduling settings are left out.
+    pub fn fingerprint(&self) -> String {
+        let config = &self.config;
+        let settings = format!(
+            "safety_window_secs={};backup_retention_days={};incident_retention_days={};\
+             failure_bundles_on_complete={};orphaned_next_steps={}",
+            config.safety_window_secs,
+            config.backup_retention_days,
+            config.incident_retention_days,
+            config.failure_bundles_on_complete,
+            config.orphaned_next_steps,
+        );
+        hex::encode(&Sha256::digest(settings.as_bytes())[..8])
+    }
+
+    /// Whether the current settings may delete without asking.
+    pub fn is_approved(&self) -> bool {
+        self.config.auto_approve
+            || Approval::load(&self.state_dir)
+                .is_some_and(|approval| approval.fingerprint == self.fingerprint())
+    }
+
+    /// The approval `palingenesis gc approve` records for these settings.
+    pub fn approval(&self) -> Approval {
+        Approval {
+            fingerprint: self.fingerprint(),
+            approved_at: self.now,
+        }
+    }
+
+    /// What [`Self::apply`] would delete, deleting nothing.
+    pub async fn plan(&self, state: &StateFile) -> GcReport {
+        let mut candidates = self.expired_backups(state).await;
+        candidates.extend(self.expired_incidents());
+        candidates.extend(self.completed_failure_bundles(state));
+        candidates.extend(self.orphaned_next_steps(state));
+
+        let active = state.current_session.as_ref().map(|session| &session.path);
+        let safe_before = self.now - seconds(self.config.safety_window_secs);
+        let (artifacts, held_back): (Vec<_>, Vec<_>) =
+            candidates.into_iter().partition(|artifact| {
+                artifact.modified < safe_before
+                    && (active.is_none() || artifact.session_path.as_ref() != active)
+            });
+        GcReport {
+            ran_at: self.now,
+            dry_run: true,
+            fingerprint: self.fingerprint(),
+            artifacts,
+            held_back: held_back.len(),
+            failed: Vec::new(),
+        }
+    }
+
+    /// Delete what is past retention, logging and auditing each deletion.
+    pub async fn apply(&self, state: &StateFile) -> GcReport {
+        let mut report = self.plan(state).await;
+        report.dry_run = false;
+        let audit = AuditLogger::new(&self.state_dir);
+        let planned = std::mem::take(&mut report.artifacts);
+        for artifact in planned {
+            match artifact.remove() {
+                Ok(()) => {
+                    info!(
+                        artifact = %artifact.kind,
+                        path = %artifact.path.display(),
+                        bytes = artifact.bytes,
+                        "Artifact deleted"
+                    );
+                    if let Err(err) = audit.record(&artifact.deleted_event(Utc::now())) {
+                        warn!(error = %err, "Failed to audit artifact deletion");
+                    }
+                    report.artifacts.push(artifact);
+                }
+                Err(err) => {
+                    warn!(path = %artifact.path.display(), error = %err, "Failed to delete artifact");
+                    report
+                        .failed
+                        .push(format!("{}: {err}", artifact.path.display()));
+                }
+            }
+        }
+        report
+    }
+
+    /// Whether `modified` is older than `days` (0 keeps forever).
+    fn expired(&self, modified: DateTime<Utc>, days: u32) -> bool {
+        days > 0 && modified < self.now - TimeDelta::days(i64::from(days))
+    }
+
+    async fn expired_backups(&self, state: &StateFile) -> Vec<Artifact> {
+        let days = self.config.backup_retention_days;
+        if days == 0 {
+            return Vec::new();
+        }
+        let backup = SessionBackup::with_config(self.backups.clone());
+        let mut artifacts = Vec::new();
+        for completed in &state.completed_sessions {
+            let backups = match backup.list_backups(&completed.path).await {
+                Ok(backups) => backups,
+                Err(err) => {
+                    debug!(session = %completed.path.display(), error = %err, "Skipping backups");
+                    continue;
+                }
+            };
+            artifacts.extend(
+                backups
+                    .into_iter()
+                    .filter_map(|(path, _)| {
+                        Artifact::scan(
+                            ArtifactKind::Backup,
+                            path,
+                            Vec::new(),
+                            Some(completed.path.clone()),
+                        )
+                    })
+                    .filter(|artifact| self.expired(artifact.modified, days)),
+            );
+        }
+        artifacts
+    }
+
+    fn expired_incidents(&self) -> Vec<Artifact> {
+        let days = self.config.incident_retention_days;
+        if days == 0 {
+            return Vec::new();
+        }
+        let store = IncidentStore::new(&self.state_dir);
+        let records = store.list().unwrap_or_else(|err| {
+            warn!(error = %err, "Failed to list incidents");
+            Vec::new()
+        });
+        records
+            .into_iter()
+            .filter_map(|record| {
+                let [snapshot, classification] = store.files(&record.id);
+                Artifact::scan(
+                    ArtifactKind::Incident,
+                    snapshot,
+                    vec![classification],
+                    Some(record.session_path),
+                )
+            })
+            .filter(|artifact| self.expired(artifact.modified, days))
+            .collect()
+    }
+
+    fn completed_failure_bundles(&self, state: &StateFile) -> Vec<Artifact> {
+        if !self.config.failure_bundles_on_complete {
+            return Vec::new();
+        }
+        let store = FailureStore::new(&self.state_dir);
+        let records = store.list().unwrap_or_else(|err| {
+            warn!(error = %err, "Failed to list failure bundles");
+            Vec::new()
+        });
+        records
+            .into_iter()
+            .filter(|record| state.completed_session(&record.session_path).is_some())
+            .filter_map(|record| {
+                Artifact::scan(
+                    ArtifactKind::FailureBundle,
+                    store.dir().join(&record.id),
+                    Vec::new(),
+                    Some(record.session_path),
+                )
+            })
+            .collect()
+    }
+
+    /// Next-step.md in directories where every session the state file
+    /// knows of is gone. The active session's directory is skipped even
+    /// when its file is missing.
+    fn orphaned_next_steps(&self, state: &StateFile) -> Vec<Artifact> {
+        if !self.config.orphaned_next_steps {
+            return Vec::new();
+        }
+        let active_dir = state
+            .current_session
+            .as_ref()
+            .and_then(|session| session.path.parent());
+        let mut sessions_by_dir: BTreeMap<&Path, Vec<&Path>> = BTreeMap::new();
+        for path in known_sessions(state) {
+            if let Some(dir) = path.parent() {
+                sessions_by_dir.entry(dir).or_default().push(path);
+            }
+        }
+        sessions_by_dir
+            .into_iter()
+            .filter(|(dir, _)| Some(*dir) != active_dir)
+            .filter(|(_, sessions)| !sessions.iter().any(|session| session.exists()))
+            .filter_map(|(dir, _)| {
+                let path = dir.join(&self.next_step_filename);
+                let is_file = fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_file());
+                is_file
+                    .then(|| Artifact::scan(ArtifactKind::NextStep, path, Vec::new(), None))
+                    .flatten()
+            })
+            .collect()
+    }
+}
+
+/// Every session path the state file records.
+fn known_sessions(state: &StateFile) -> HashSet<&Path> {
+    let mut paths: HashSet<&Path> = HashSet::new();
+    paths.extend(state.current_session.iter().map(|s| s.path.as_path()));
+    paths.extend(state.completed_sessions.iter().map(|s| s.path.as_path()));
+    paths.extend(state.orphaned_sessions.iter().map(|s| s.path.as_path()));
+    paths.extend(state.resume_history.iter().map(|s| s.path.as_path()));
+    paths.extend(state.step_timelines.iter().map(|s| s.path.as_path()));
+    paths
+}
+
+/// Newest mtime and total size of `path`, counting a directory's files.
+fn usage(path: &Path) -> Option<(DateTime<Utc>, u64)> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    let mut modified = DateTime::<Utc>::from(metadata.modified().ok()?);
+    let mut bytes = metadata.len();
+    if metadata.is_dir() {
+        bytes = 0;
+        for entry in fs::read_dir(path).ok()?.filter_map(Result::ok) {
+            if let Some((entry_modified, entry_bytes)) = usage(&entry.path()) {
+                modified = modified.max(entry_modified);
+                bytes += entry_bytes;
+            }
+        }
+    }
+    Some((modified, bytes))
+}
+
+fn seconds(secs: u64) -> TimeDelta {
+    i64::try_from(secs)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .unwrap_or(TimeDelta::MAX)
+}
+
+/// One scheduled pass: delete when the settings are approved, otherwise
+/// report the plan, once per settings change.
+pub async fn run_scheduled(
+    gc: &ArtifactGc,
+    state: &StateFile,
+    events: &EventBroadcaster,
+) -> GcReport {
+    if gc.is_approved() {
+        let report = gc.apply(state).await;
+        if !report.is_empty() || !report.failed.is_empty() {
+            info!(
+                deleted = report.artifacts.len(),
+                bytes = report.bytes(),
+                failed = report.failed.len(),
+                "Artifact GC finished"
+            );
+        }
+        return report;
+    }
+
+    let report = gc.plan(state).await;
+    let reported = GcReport::load_plan(&gc.state_dir)
+        .is_some_and(|plan| plan.fingerprint == report.fingerprint);
+    if report.is_empty() || reported {
+        return report;
+    }
+    info!(
+        artifacts = report.artifacts.len(),
+        bytes = report.bytes(),
+        "Artifact GC waiting for approval (palingenesis gc approve)"
+    );
+    let path = report.save_plan(&gc.state_dir).unwrap_or_else(|err| {
+        warn!(error = %err, "Failed to write GC plan");
+        gc.state_dir.join(PLAN_FILE)
+    });
+    events.publish(report.pending_event(path));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_ignores_scheduling_settings() {
+        let mut config = Config::default();
+        let base = ArtifactGc::new(&config, Path::new("/state")).fingerprint();
+
+        config.maintenance.interval_secs = 60;
+        config.maintenance.enabled = true;
+        assert_eq!(
+            ArtifactGc::new(&config, Path::new("/state")).fingerprint(),
+            base
+        );
+
+        config.maintenance.backup_retention_days = 7;
+        assert_ne!(
+            ArtifactGc::new(&config, Path::new("/state")).fingerprint(),
+            base
+        );
+    }
+
+    #[test]
+    fn summary_lists_kinds_with_artifacts() {
+        let artifact = |kind, bytes| Artifact {
+            kind,
+            path: PathBuf::from("/state/x"),
+            companions: Vec::new(),
+            session_path: None,
+            modified: Utc::now(),
+            bytes,
+        };
+        let report = GcReport {
+            ran_at: Utc::now(),
+            dry_run: true,
+            fingerprint: String::new(),
+            artifacts: vec![
+                artifact(ArtifactKind::Backup, 1024),
+                artifact(ArtifactKind::Backup, 1024),
+                artifact(ArtifactKind::NextStep, 100),
+            ],
+            held_back: 0,
+            failed: Vec::new(),
+        };
+        assert_eq!(
+            report.summary(),
+            vec!["backups: 2 (2.0 KB)", "Next-step files: 1 (100 B)"]
+        );
+        assert_eq!(report.bytes(), 2148);
+    }
+}
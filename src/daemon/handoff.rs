@@ -0,0 +1,187 @@
+//! Runtime-state handoff for `daemon restart`.
+//!
+//! The outgoing daemon writes a [`RuntimeSnapshot`] of state that only lives
+//! in memory (the state file covers the rest) to `handoff.json` in the
+//! runtime directory and exits. The incoming daemon restores a fresh snapshot
+//! on startup and deletes the file; snapshots older than [`MAX_HANDOFF_AGE`]
+//! are discarded so a leftover file never resurrects old state.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Paths;
+
+/// Current version of the handoff snapshot format.
+pub const HANDOFF_VERSION: u32 = 1;
+
+/// Snapshots older than this are ignored.
+pub const MAX_HANDOFF_AGE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum HandoffError {
+    #[error("Handoff file I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid handoff file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Handoff file is stale ({age_secs}s old)")]
+    Stale { age_secs: i64 },
+
+    #[error("Unsupported handoff version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// In-memory daemon state carried across a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeSnapshot {
+    pub version: u32,
+    pub written_at: DateTime<Utc>,
+    /// PID of the daemon that wrote the snapshot.
+    pub pid: u32,
+    pub paused: bool,
+    pub sessions_count: u64,
+    pub resumes_count: u64,
+}
+
+impl RuntimeSnapshot {
+    pub fn new(paused: bool, sessions_count: u64, resumes_count: u64) -> Self {
+        Self {
+            version: HANDOFF_VERSION,
+            written_at: Utc::now(),
+            pid: std::process::id(),
+            paused,
+            sessions_count,
+            resumes_count,
+        }
+    }
+
+    /// Human-readable list of restored values, for the startup log.
+    pub fn describe(&self) -> String {
+        format!(
+            "paused={}, sessions_count={}, resumes_count={}",
+            self.paused, self.sessions_count, self.resumes_count
+        )
+    }
+}
+
+/// Location of the handoff snapshot.
+#[derive(Debug, Clone)]
+pub struct HandoffFile {
+    path: PathBuf,
+}
+
+impl HandoffFile {
+    pub fn new() -> Self {
+        Self {
+            path: Paths::runtime_dir().join(Paths::instance_file_name("handoff", "json")),
+        }
+    }
+
+    /// Create with custom path (for testing).
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the snapshot atomically (temp file + rename).
+    pub fn write(&self, snapshot: &RuntimeSnapshot) -> Result<(), HandoffError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(snapshot)?)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// Read and delete the snapshot.
+    ///
+    /// Returns `Ok(None)` when there is no handoff file. The file is removed
+    /// even when it is stale or unreadable, so it is only ever considered once.
+    pub fn take(&self, now: DateTime<Utc>) -> Result<Option<RuntimeSnapshot>, HandoffError> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        fs::remove_file(&self.path)?;
+
+        let snapshot: RuntimeSnapshot = serde_json::from_slice(&contents)?;
+        if snapshot.version != HANDOFF_VERSION {
+            return Err(HandoffError::UnsupportedVersion(snapshot.version));
+        }
+        let age = now - snapshot.written_at;
+        let max_age = chrono::TimeDelta::from_std(MAX_HANDOFF_AGE).unwrap_or_default();
+        if age >= max_age || age < -max_age {
+            return Err(HandoffError::Stale {
+                age_secs: age.num_seconds(),
+            });
+        }
+        Ok(Some(snapshot))
+    }
+}
+
+impl Default for HandoffFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+    use tempfile::tempdir;
+
+    #[test]
+    fn snapshot_round_trips_and_file_is_consumed() {
+        let dir = tempdir().unwrap();
+        let file = HandoffFile::with_path(dir.path().join("handoff.json"));
+        let snapshot = RuntimeSnapshot::new(true, 4, 7);
+
+        file.write(&snapshot).unwrap();
+        let restored = file.take(snapshot.written_at + TimeDelta::seconds(2));
+
+        assert_eq!(restored.unwrap(), Some(snapshot));
+        assert!(!file.path().exists());
+        assert!(file.take(Utc::now()).unwrap().is_none());
+    }
+
+    #[test]
+    fn stale_snapshot_is_rejected_and_removed() {
+        let dir = tempdir().unwrap();
+        let file = HandoffFile::with_path(dir.path().join("handoff.json"));
+        let snapshot = RuntimeSnapshot::new(false, 1, 1);
+        file.write(&snapshot).unwrap();
+
+        let result = file.take(snapshot.written_at + TimeDelta::seconds(60));
+
+        assert!(matches!(result, Err(HandoffError::Stale { age_secs: 60 })));
+        assert!(!file.path().exists());
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let dir = tempdir().unwrap();
+        let file = HandoffFile::with_path(dir.path().join("handoff.json"));
+        let snapshot = RuntimeSnapshot {
+            version: HANDOFF_VERSION + 1,
+            ..RuntimeSnapshot::new(false, 0, 0)
+        };
+        file.write(&snapshot).unwrap();
+
+        assert!(matches!(
+            file.take(snapshot.written_at),
+            Err(HandoffError::UnsupportedVersion(_))
+        ));
+    }
+}
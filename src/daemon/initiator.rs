@@ -0,0 +1,207 @@
+//! Who asked for a control action.
+//!
+//! Every pathway that pauses, resumes or starts a session names its caller
+//! with an [`Initiator`]. The daemon carries it on the control event, the
+//! audit entry and the notification, so "why is it paused?" has an answer.
+
+use std::fmt;
+use std::io::IsTerminal;
+
+use serde::{Deserialize, Serialize};
+
+/// Origin of a control action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "via", rename_all = "snake_case")]
+pub enum Initiator {
+    /// The `palingenesis` CLI over the IPC socket. The daemon fills `uid`
+    /// from the socket's peer credentials.
+    Cli {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uid: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tty: Option<String>,
+    },
+    /// A request to the HTTP API.
+    Http {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        remote_addr: Option<String>,
+        /// Name of the `[[daemon.http_tokens]]` entry the request
+        /// authenticated with.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    /// A gRPC call.
+    Grpc {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        remote_addr: Option<String>,
+    },
+    /// A method call on the D-Bus interface. `uid` is what the bus reports
+    /// for the calling connection.
+    Dbus {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uid: Option<u32>,
+        /// Unique bus name of the caller (`:1.42`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sender: Option<String>,
+    },
+    /// A Discord slash command.
+    Discord { user_id: String },
+    /// A Slack slash command.
+    Slack { user_id: String },
+    /// An MCP tool call.
+    Mcp {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_name: Option<String>,
+    },
+    /// The daemon itself, e.g. a config reload changing the mode.
+    Internal { component: String },
+}
+
+impl Initiator {
+    /// The CLI running in this process, with its controlling terminal.
+    pub fn cli() -> Self {
+        Self::Cli {
+            uid: None,
+            tty: current_tty(),
+        }
+    }
+
+    pub fn internal(component: impl Into<String>) -> Self {
+        Self::Internal {
+            component: component.into(),
+        }
+    }
+
+    /// Fill in the uid the kernel reports for the CLI's socket connection;
+    /// a uid the client claimed is not trusted.
+    pub fn with_peer_uid(self, peer_uid: Option<u32>) -> Self {
+        match self {
+            Self::Cli { tty, .. } => Self::Cli { uid: peer_uid, tty },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Initiator {
+    /// Reads after "paused by", e.g. `@U123 via Slack`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cli { uid, tty } => {
+                write!(f, "CLI")?;
+                match (uid, tty) {
+                    (Some(uid), Some(tty)) => write!(f, " (uid {uid} on {tty})"),
+                    (Some(uid), None) => write!(f, " (uid {uid})"),
+                    (None, Some(tty)) => write!(f, " (on {tty})"),
+                    (None, None) => Ok(()),
+                }
+            }
+            Self::Http { remote_addr, token } => {
+                write!(f, "HTTP API")?;
+                if let Some(addr) = remote_addr {
+                    write!(f, " from {addr}")?;
+                }
+                if let Some(token) = token {
+                    write!(f, " (token {token})")?;
+                }
+                Ok(())
+            }
+            Self::Grpc { remote_addr } => match remote_addr {
+                Some(addr) => write!(f, "gRPC from {addr}"),
+                None => write!(f, "gRPC"),
+            },
+            Self::Dbus { uid, sender } => {
+                write!(f, "D-Bus")?;
+                match (uid, sender) {
+                    (Some(uid), Some(sender)) => write!(f, " (uid {uid}, {sender})"),
+                    (Some(uid), None) => write!(f, " (uid {uid})"),
+                    (None, Some(sender)) => write!(f, " ({sender})"),
+                    (None, None) => Ok(()),
+                }
+            }
+            Self::Discord { user_id } => write!(f, "@{user_id} via Discord"),
+            Self::Slack { user_id } => write!(f, "@{user_id} via Slack"),
+            Self::Mcp { client_name } => match client_name {
+                Some(name) => write!(f, "MCP client {name}"),
+                None => write!(f, "MCP client"),
+            },
+            Self::Internal { component } => write!(f, "daemon ({component})"),
+        }
+    }
+}
+
+/// Terminal on stdin, if any (`/dev/pts/3`).
+fn current_tty() -> Option<String> {
+    if !std::io::stdin().is_terminal() {
+        return None;
+    }
+    std::fs::read_link("/proc/self/fd/0")
+        .ok()
+        .map(|path| path.display().to_string())
+        .or_else(|| std::env::var("TTY").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_each_pathway() {
+        let slack = Initiator::Slack {
+            user_id: "U123".to_string(),
+        };
+        assert_eq!(slack.to_string(), "@U123 via Slack");
+
+        let cli = Initiator::Cli {
+            uid: Some(1000),
+            tty: Some("/dev/pts/3".to_string()),
+        };
+        assert_eq!(cli.to_string(), "CLI (uid 1000 on /dev/pts/3)");
+
+        let http = Initiator::Http {
+            remote_addr: Some("127.0.0.1:5000".to_string()),
+            token: None,
+        };
+        assert_eq!(http.to_string(), "HTTP API from 127.0.0.1:5000");
+        assert_eq!(
+            Initiator::internal("config_reload").to_string(),
+            "daemon (config_reload)"
+        );
+    }
+
+    #[test]
+    fn serializes_with_pathway_tag() {
+        let json = serde_json::to_value(Initiator::Discord {
+            user_id: "42".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json, serde_json::json!({"via": "discord", "user_id": "42"}));
+
+        let back: Initiator = serde_json::from_value(serde_json::json!({"via": "cli"})).unwrap();
+        assert_eq!(
+            back,
+            Initiator::Cli {
+                uid: None,
+                tty: None
+            }
+        );
+    }
+
+    #[test]
+    fn peer_uid_replaces_claimed_uid() {
+        let claimed = Initiator::Cli {
+            uid: Some(0),
+            tty: None,
+        };
+        assert_eq!(
+            claimed.with_peer_uid(Some(1000)),
+            Initiator::Cli {
+                uid: Some(1000),
+                tty: None
+            }
+        );
+        let slack = Initiator::Slack {
+            user_id: "U1".to_string(),
+        };
+        assert_eq!(slack.clone().with_peer_uid(Some(1000)), slack);
+    }
+}
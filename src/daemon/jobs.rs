@@ -0,0 +1,519 @@
+//! Prioritised job queue for daemon work.
+//!
+//! Work the daemon does outside its event loop runs here, in one of three
+//! classes with separate worker limits. Critical jobs (resume execution,
+//! restarts) always start immediately; normal jobs (classification,
+//! notifications) and background jobs (pruning, scans, digests) wait for a
+//! free worker in their own class, so a long background job can never hold
+//! up a resume. With a [`ControlQueue`], critical jobs run as control
+//! operations, so PAUSE or RELOAD never lands in the middle of one.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::config::schema::DaemonConfig;
+use crate::daemon::control::ControlQueue;
+use crate::telemetry::Metrics;
+
+/// Scheduling class of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Resume execution, restarts. Never waits for a worker.
+    Critical,
+    /// Classification, notifications.
+    Normal,
+    /// Pruning, digests, scans.
+    Background,
+}
+
+impl JobPriority {
+    pub const ALL: [Self; 3] = [Self::Critical, Self::Normal, Self::Background];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::Normal => "normal",
+            Self::Background => "background",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Worker limits per class; critical jobs are unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobLimits {
+    pub normal: usize,
+    pub background: usize,
+}
+
+impl JobLimits {
+    pub fn from_config(config: &DaemonConfig) -> Self {
+        Self {
+            normal: config.job_normal_concurrency.max(1),
+            background: config.job_background_concurrency.max(1),
+        }
+    }
+
+    fn limit(&self, priority: JobPriority) -> usize {
+        match priority {
+            JobPriority::Critical => usize::MAX,
+            JobPriority::Normal => self.normal,
+            JobPriority::Background => self.background,
+        }
+    }
+}
+
+impl Default for JobLimits {
+    fn default() -> Self {
+        Self::from_config(&DaemonConfig::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Queued,
+}
+
+/// A queued or running job, as shown by `status --verbose` and `jobs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: u64,
+    pub name: String,
+    pub priority: JobPriority,
+    pub state: JobState,
+    pub enqueued_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+type BoxedJob = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct QueuedJob {
+    status: JobStatus,
+    job: BoxedJob,
+}
+
+struct Queues {
+    pending: [VecDeque<QueuedJob>; 3],
+    running: BTreeMap<u64, JobStatus>,
+    limits: JobLimits,
+}
+
+impl Queues {
+    fn running_in(&self, priority: JobPriority) -> usize {
+        self.running
+            .values()
+            .filter(|status| status.priority == priority)
+            .count()
+    }
+}
+
+struct Inner {
+    queues: Mutex<Queues>,
+    next_id: AtomicU64,
+    cancel: CancellationToken,
+    control: Option<ControlQueue>,
+}
+
+/// Handle to the daemon's job queue; clones share the queue.
+#[derive(Clone)]
+pub struct JobQueue {
+    inner: Arc<Inner>,
+}
+
+impl JobQueue {
+    pub fn new(limits: JobLimits) -> Self {
+        Self::build(limits, None)
+    }
+
+    /// Run critical jobs as operations of `control`, deferring state-changing
+    /// commands until they finish.
+    pub fn with_control(limits: JobLimits, control: ControlQueue) -> Self {
+        Self::build(limits, Some(control))
+    }
+
+    fn build(limits: JobLimits, control: Option<ControlQueue>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                queues: Mutex::new(Queues {
+                    pending: Default::default(),
+                    running: BTreeMap::new(),
+                    limits,
+                }),
+                next_id: AtomicU64::new(1),
+                cancel: CancellationToken::new(),
+                control,
+            }),
+        }
+    }
+
+    /// Apply new worker limits; queued jobs start if the limits grew.
+    pub fn set_limits(&self, limits: JobLimits) {
+        if let Ok(mut queues) = self.inner.queues.lock() {
+            queues.limits = limits;
+        }
+        self.pump();
+    }
+
+    /// Queue `job`; it starts as soon as its class has a free worker.
+    ///
+    /// Must be called from within the Tokio runtime. Jobs enqueued after
+    /// [`JobQueue::cancel`] are dropped.
+    pub fn enqueue<F>(&self, name: impl Into<String>, priority: JobPriority, job: F) -> u64
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let name = name.into();
+        if self.inner.cancel.is_cancelled() {
+            debug!(job = %name, "Job queue is shut down; dropping job");
+            return id;
+        }
+        let status = JobStatus {
+            id,
+            name,
+            priority,
+            state: JobState::Queued,
+            enqueued_at: Utc::now(),
+            started_at: None,
+        };
+        match self.inner.queues.lock() {
+            Ok(mut queues) => queues.pending[priority.index()].push_back(QueuedJob {
+                status,
+                job: Box::pin(job),
+            }),
+            Err(_) => {
+                warn!(job = %status.name, "Job queue lock poisoned; dropping job");
+                return id;
+            }
+        }
+        self.pump();
+        id
+    }
+
+    /// Whether a job with this name is queued or running.
+    pub fn contains(&self, name: &str) -> bool {
+        self.snapshot().iter().any(|status| status.name == name)
+    }
+
+    /// Running jobs, then queued jobs, each by priority and age.
+    pub fn snapshot(&self) -> Vec<JobStatus> {
+        let Ok(queues) = self.inner.queues.lock() else {
+            return Vec::new();
+        };
+        let mut jobs: Vec<JobStatus> = queues
+            .running
+            .values()
+            .cloned()
+            .chain(
+                queues
+                    .pending
+                    .iter()
+                    .flat_map(|pending| pending.iter().map(|queued| queued.status.clone())),
+            )
+            .collect();
+        jobs.sort_by_key(|status| (status.state, status.priority, status.id));
+        jobs
+    }
+
+    /// Stop running jobs and drop queued ones (daemon shutdown).
+    pub fn cancel(&self) {
+        self.inner.cancel.cancel();
+        if let Ok(mut queues) = self.inner.queues.lock() {
+            for pending in &mut queues.pending {
+                pending.clear();
+            }
+        }
+    }
+
+    fn pump(&self) {
+        let mut ready = Vec::new();
+        {
+            let Ok(mut queues) = self.inner.queues.lock() else {
+                return;
+            };
+            for priority in JobPriority::ALL {
+                let limit = queues.limits.limit(priority);
+                let mut running = queues.running_in(priority);
+                while running < limit {
+                    let Some(mut queued) = queues.pending[priority.index()].pop_front() else {
+                        break;
+                    };
+                    queued.status.state = JobState::Running;
+                    queued.status.started_at = Some(Utc::now());
+                    queues
+                        .running
+                        .insert(queued.status.id, queued.status.clone());
+                    running += 1;
+                    ready.push(queued);
+                }
+            }
+        }
+        for queued in ready {
+            self.start(queued);
+        }
+    }
+
+    fn start(&self, queued: QueuedJob) {
+        let QueuedJob { status, job } = queued;
+        let cancel = self.inner.cancel.clone();
+        let control = match status.priority {
+            JobPriority::Critical => self.inner.control.clone(),
+            _ => None,
+        };
+        // Finishing from a drop guard frees the worker even if the job panics.
+        let guard = RunningJob {
+            queue: self.clone(),
+            id: status.id,
+            name: status.name,
+            priority: status.priority,
+            started: Instant::now(),
+        };
+        tokio::spawn(async move {
+            let guard = guard;
+            let _operation = match &control {
+                Some(control) => Some(control.operation(guard.name.clone()).await),
+                None => None,
+            };
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    debug!(job = %guard.name, "Job cancelled by shutdown");
+                }
+                () = job => {}
+            }
+        });
+    }
+
+    fn finish(&self, id: u64) {
+        if let Ok(mut queues) = self.inner.queues.lock() {
+            queues.running.remove(&id);
+        }
+        if !self.inner.cancel.is_cancelled() {
+            self.pump();
+        }
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new(JobLimits::default())
+    }
+}
+
+struct RunningJob {
+    queue: JobQueue,
+    id: u64,
+    name: String,
+    priority: JobPriority,
+    started: Instant,
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        debug!(
+            job = %self.name,
+            priority = self.priority.as_str(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Job finished"
+        );
+        if let Some(metrics) = Metrics::global() {
+            metrics.record_job(&self.name, self.priority.as_str(), elapsed);
+        }
+        self.queue.finish(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    use tokio::sync::oneshot;
+
+    fn limits(normal: usize, background: usize) -> JobLimits {
+        JobLimits { normal, background }
+    }
+
+    async fn settle() {
+        for _ in 0..20 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn critical_jobs_run_while_background_is_saturated() {
+        let queue = JobQueue::new(limits(1, 1));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (release, blocker) = oneshot::channel::<()>();
+
+        queue.enqueue("prune", JobPriority::Background, async move {
+            let _ = blocker.await;
+        });
+        for (name, priority) in [
+            ("scan", JobPriority::Background),
+            ("digest", JobPriority::Background),
+            ("resume", JobPriority::Critical),
+            ("classify", JobPriority::Normal),
+        ] {
+            let order = Arc::clone(&order);
+            queue.enqueue(name, priority, async move {
+                order.lock().unwrap().push(name);
+            });
+        }
+        settle().await;
+        assert_eq!(*order.lock().unwrap(), vec!["resume", "classify"]);
+
+        release.send(()).unwrap();
+        settle().await;
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["resume", "classify", "scan", "digest"]
+        );
+        assert!(queue.snapshot().is_empty());
+    }
+
+    #[tokio::test]
+    async fn background_concurrency_is_bounded() {
+        let queue = JobQueue::new(limits(4, 2));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for index in 0..6 {
+            let (active, peak, done) = (Arc::clone(&active), Arc::clone(&peak), Arc::clone(&done));
+            queue.enqueue(
+                format!("scan-{index}"),
+                JobPriority::Background,
+                async move {
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    done.fetch_add(1, Ordering::SeqCst);
+                },
+            );
+        }
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while done.load(Ordering::SeqCst) < 6 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("jobs finish");
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn snapshot_shows_running_and_queued_jobs() {
+        let queue = JobQueue::new(limits(1, 1));
+        let (release, blocker) = oneshot::channel::<()>();
+        queue.enqueue("prune", JobPriority::Background, async move {
+            let _ = blocker.await;
+        });
+        queue.enqueue("scan", JobPriority::Background, async {});
+        settle().await;
+
+        let jobs = queue.snapshot();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].name, "prune");
+        assert_eq!(jobs[0].state, JobState::Running);
+        assert!(jobs[0].started_at.is_some());
+        assert_eq!(jobs[1].name, "scan");
+        assert_eq!(jobs[1].state, JobState::Queued);
+        assert!(jobs[1].started_at.is_none());
+        assert!(queue.contains("scan"));
+
+        drop(release);
+        settle().await;
+        assert!(!queue.contains("scan"));
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_running_and_drops_queued_jobs() {
+        let queue = JobQueue::new(limits(1, 1));
+        let ran = Arc::new(AtomicUsize::new(0));
+        queue.enqueue("hang", JobPriority::Background, std::future::pending());
+        let counter = Arc::clone(&ran);
+        queue.enqueue("later", JobPriority::Background, async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        settle().await;
+
+        queue.cancel();
+        settle().await;
+        let counter = Arc::clone(&ran);
+        queue.enqueue("after", JobPriority::Critical, async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        settle().await;
+
+        assert_eq!(ran.load(Ordering::SeqCst), 0);
+        assert!(queue.snapshot().is_empty());
+    }
+
+    #[tokio::test]
+    async fn panicking_job_frees_its_worker() {
+        let queue = JobQueue::new(limits(1, 1));
+        queue.enqueue("boom", JobPriority::Background, async {
+            panic!("job failed");
+        });
+        let (tx, rx) = oneshot::channel();
+        queue.enqueue("next", JobPriority::Background, async move {
+            let _ = tx.send(());
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), rx)
+            .await
+            .expect("next job runs")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn critical_jobs_hold_off_control_commands() {
+        use crate::daemon::control::Admission;
+        use crate::daemon::initiator::Initiator;
+        use crate::ipc::protocol::IpcCommand;
+
+        let control = ControlQueue::new();
+        let queue = JobQueue::with_control(limits(1, 1), control.clone());
+        let (release, released) = oneshot::channel::<()>();
+        queue.enqueue("scan", JobPriority::Background, std::future::pending());
+        queue.enqueue("resume", JobPriority::Critical, async move {
+            let _ = released.await;
+        });
+        settle().await;
+
+        assert_eq!(control.operations(), ["resume"]);
+        assert!(matches!(
+            control.admit(IpcCommand::Reload, &Initiator::internal("test")),
+            Admission::Deferred(_)
+        ));
+
+        release.send(()).unwrap();
+        settle().await;
+        assert!(control.operations().is_empty());
+        assert!(matches!(
+            control.admit(IpcCommand::Pause, &Initiator::internal("test")),
+            Admission::Now(_)
+        ));
+        queue.cancel();
+    }
+}
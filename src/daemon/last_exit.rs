@@ -0,0 +1,379 @@
+//! Record of how the daemon last exited, for post-crash diagnosis.
+//!
+//! Each exit path that still runs code writes `last_exit.json` in the state
+//! directory: a normal shutdown after releasing the PID file, a panic from
+//! the panic hook, and `daemon stop` when it had to SIGKILL the daemon. A
+//! SIGKILL or the OOM killer leaves no record but does leave the PID file,
+//! so the next start infers the kill from a stale PID file that no record
+//! accounts for. After an unexpected exit the next daemon logs it, sends a
+//! warning notification and, for inferred kills, writes the record it
+//! reconstructed.
+
+use std::any::Any;
+use std::fs;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Paths;
+use crate::daemon::state::DaemonState;
+
+/// File name of the record in the state directory.
+pub const LAST_EXIT_FILE: &str = "last_exit.json";
+
+/// Panic messages are cut to this many characters.
+const MAX_PANIC_MESSAGE_CHARS: usize = 512;
+
+/// Why the daemon requested its own shutdown; the first cause noted wins.
+static SHUTDOWN_CAUSE: Mutex<Option<(ExitCause, String)>> = Mutex::new(None);
+
+/// State of the running daemon, read by the panic hook.
+static ATTACHED: Mutex<Option<Weak<DaemonState>>> = Mutex::new(None);
+
+#[derive(Debug, thiserror::Error)]
+pub enum LastExitError {
+    #[error("Last exit record I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid last exit record: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Category of a daemon exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCause {
+    /// SIGTERM or SIGINT.
+    Signal,
+    /// `daemon restart` handed the runtime state to a new instance.
+    Handoff,
+    /// A stop request over IPC or HTTP, or the embedding caller.
+    Requested,
+    /// A server task failed.
+    Error,
+    Panic,
+    /// `daemon stop` sent SIGKILL after the daemon ignored SIGTERM.
+    ForcedStop,
+    /// Killed without a chance to write a record (SIGKILL, OOM killer).
+    Killed,
+}
+
+impl ExitCause {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Signal => "signal",
+            Self::Handoff => "handoff",
+            Self::Requested => "requested",
+            Self::Error => "error",
+            Self::Panic => "panic",
+            Self::ForcedStop => "forced_stop",
+            Self::Killed => "killed",
+        }
+    }
+
+    /// Exits nobody asked for.
+    pub fn is_unexpected(self) -> bool {
+        matches!(self, Self::Error | Self::Panic | Self::Killed)
+    }
+}
+
+/// Contents of `last_exit.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastExit {
+    pub timestamp: DateTime<Utc>,
+    /// PID of the daemon that exited.
+    pub pid: u32,
+    /// Daemon version; `None` for inferred exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub cause: ExitCause,
+    /// Signal name, error or panic message.
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+    /// `monitoring`, `paused` or `starting` when the exit began.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon_state: Option<String>,
+    /// Tasks were drained, state flushed and the PID file removed.
+    pub graceful: bool,
+    /// Reconstructed by the next start rather than written on exit.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inferred: bool,
+}
+
+impl LastExit {
+    /// An exit of this process.
+    pub fn new(cause: ExitCause, detail: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            pid: std::process::id(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            cause,
+            detail: detail.into(),
+            uptime_secs: None,
+            daemon_state: None,
+            graceful: false,
+            inferred: false,
+        }
+    }
+
+    /// A kill of `pid`, inferred from its stale PID file.
+    pub fn inferred_kill(pid: u32) -> Self {
+        Self {
+            pid,
+            version: None,
+            inferred: true,
+            ..Self::new(
+                ExitCause::Killed,
+                "PID file left behind without an exit record (SIGKILL or out of memory)",
+            )
+        }
+    }
+
+    pub fn with_uptime(mut self, uptime: Duration) -> Self {
+        self.uptime_secs = Some(uptime.as_secs());
+        self
+    }
+
+    pub fn with_state(mut self, state: impl Into<String>) -> Self {
+        self.daemon_state = Some(state.into());
+        self
+    }
+
+    pub fn with_graceful(mut self, graceful: bool) -> Self {
+        self.graceful = graceful;
+        self
+    }
+
+    /// One line for `status --verbose`.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("PID {}", self.pid)];
+        if let Some(uptime) = self.uptime_secs {
+            parts.push(format!("up {uptime}s"));
+        }
+        if let Some(state) = &self.daemon_state {
+            parts.push(state.clone());
+        }
+        parts.push(
+            if self.graceful {
+                "graceful"
+            } else {
+                "not graceful"
+            }
+            .to_string(),
+        );
+        if self.inferred {
+            parts.push("inferred".to_string());
+        }
+        format!(
+            "{} at {} ({}): {}",
+            self.cause.as_str(),
+            self.timestamp.to_rfc3339(),
+            parts.join(", "),
+            self.detail
+        )
+    }
+}
+
+/// Location of `last_exit.json`.
+#[derive(Debug, Clone)]
+pub struct LastExitFile {
+    path: PathBuf,
+}
+
+impl LastExitFile {
+    pub fn new() -> Self {
+        Self::in_dir(&Paths::state_dir())
+    }
+
+    /// The record in state directory `dir`.
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            path: dir.join(LAST_EXIT_FILE),
+        }
+    }
+
+    /// Create with custom path (for testing).
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the record atomically (temp file + rename).
+    pub fn write(&self, exit: &LastExit) -> Result<(), LastExitError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(exit)?)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// The record, or `Ok(None)` when no daemon has exited here yet.
+    pub fn read(&self) -> Result<Option<LastExit>, LastExitError> {
+        match fs::read(&self.path) {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl Default for LastExitFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The previous exit worth reporting at startup, given the last record and
+/// the PID of a stale PID file the new daemon removed.
+///
+/// A stale PID file means the process died without cleaning up; unless a
+/// record for that PID explains it, it was killed.
+pub fn unexpected_exit(record: Option<LastExit>, stale_pid: Option<u32>) -> Option<LastExit> {
+    if let Some(pid) = stale_pid {
+        if record.as_ref().is_none_or(|record| record.pid != pid) {
+            return Some(LastExit::inferred_kill(pid));
+        }
+    }
+    record.filter(|record| record.cause.is_unexpected())
+}
+
+/// Remember why shutdown was requested; later causes are ignored.
+pub fn note_shutdown_cause(cause: ExitCause, detail: impl Into<String>) {
+    if let Ok(mut slot) = SHUTDOWN_CAUSE.lock() {
+        slot.get_or_insert_with(|| (cause, detail.into()));
+    }
+}
+
+/// The noted shutdown cause, [`ExitCause::Requested`] when none was noted.
+pub fn take_shutdown_cause() -> (ExitCause, String) {
+    SHUTDOWN_CAUSE
+        .lock()
+        .ok()
+        .and_then(|mut slot| slot.take())
+        .unwrap_or_else(|| (ExitCause::Requested, "shutdown requested".to_string()))
+}
+
+/// Let the panic hook report uptime and state from `state`.
+pub fn attach_state(state: &Arc<DaemonState>) {
+    if let Ok(mut attached) = ATTACHED.lock() {
+        *attached = Some(Arc::downgrade(state));
+    }
+}
+
+/// Write a panic record to `file` from any panicking thread, then run the
+/// previously installed hook.
+pub fn install_panic_hook(file: LastExitFile) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        record_panic(&file, info);
+        previous(info);
+    }));
+}
+
+/// Best-effort panic record; never panics itself.
+fn record_panic(file: &LastExitFile, info: &PanicHookInfo<'_>) {
+    let mut detail = truncated_message(info.payload());
+    if let Some(location) = info.location() {
+        detail = format!("{detail} at {}:{}", location.file(), location.line());
+    }
+    let mut exit = LastExit::new(ExitCause::Panic, detail);
+    // try_lock: the panic may have happened while the lock was held.
+    let state = ATTACHED
+        .try_lock()
+        .ok()
+        .and_then(|attached| attached.as_ref().and_then(Weak::upgrade));
+    exit = match state {
+        Some(state) => exit
+            .with_uptime(state.uptime())
+            .with_state(state.state_label()),
+        None => exit.with_state("starting"),
+    };
+    let _ = file.write(&exit);
+}
+
+fn truncated_message(payload: &(dyn Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload");
+    message.chars().take(MAX_PANIC_MESSAGE_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn record_round_trips() {
+        let dir = tempdir().unwrap();
+        let file = LastExitFile::in_dir(dir.path());
+        assert!(file.read().unwrap().is_none());
+
+        let exit = LastExit::new(ExitCause::Signal, "SIGTERM")
+            .with_uptime(Duration::from_secs(90))
+            .with_state("paused")
+            .with_graceful(true);
+        file.write(&exit).unwrap();
+
+        assert_eq!(file.read().unwrap(), Some(exit));
+        let json = fs::read_to_string(file.path()).unwrap();
+        assert!(json.contains("\"cause\": \"signal\""));
+        assert!(!json.contains("inferred"));
+    }
+
+    #[test]
+    fn stale_pid_without_a_matching_record_is_a_kill() {
+        let graceful = LastExit::new(ExitCause::Signal, "SIGTERM").with_graceful(true);
+        let forced = LastExit {
+            pid: 77,
+            ..LastExit::new(ExitCause::ForcedStop, "SIGKILL after 5s")
+        };
+        let panicked = LastExit::new(ExitCause::Panic, "boom");
+
+        let killed = unexpected_exit(None, Some(77)).unwrap();
+        assert_eq!(
+            (killed.cause, killed.pid, killed.inferred),
+            (ExitCause::Killed, 77, true)
+        );
+        // A record from an earlier instance does not explain this PID file.
+        assert_eq!(
+            unexpected_exit(Some(graceful.clone()), Some(77)).map(|exit| exit.cause),
+            Some(ExitCause::Killed)
+        );
+        assert_eq!(unexpected_exit(Some(forced), Some(77)), None);
+        assert_eq!(unexpected_exit(Some(graceful), None), None);
+        assert_eq!(
+            unexpected_exit(Some(panicked), None).map(|exit| exit.cause),
+            Some(ExitCause::Panic)
+        );
+        assert_eq!(unexpected_exit(None, None), None);
+    }
+
+    #[test]
+    fn panic_messages_are_truncated() {
+        let long = "x".repeat(2 * MAX_PANIC_MESSAGE_CHARS);
+        let payload: Box<dyn Any + Send> = Box::new(long);
+        assert_eq!(
+            truncated_message(payload.as_ref()).len(),
+            MAX_PANIC_MESSAGE_CHARS
+        );
+        let payload: Box<dyn Any + Send> = Box::new(7_u8);
+        assert_eq!(
+            truncated_message(payload.as_ref()),
+            "non-string panic payload"
+        );
+    }
+}
@@ -1,10 +1,31 @@
 //! Daemon orchestration module.
 
+pub mod control;
 pub mod core;
+pub mod events;
+pub mod exit;
+pub mod gc;
+pub mod handoff;
+pub mod initiator;
+pub mod jobs;
+pub mod last_exit;
 pub mod pid;
+pub mod reconcile;
+pub mod restart_correlation;
+pub mod schedules;
+pub mod session_jobs;
 pub mod shutdown;
 pub mod signals;
+pub mod startup;
 pub mod state;
+pub mod suspend;
 
-pub use core::Daemon;
+pub use control::{Admission, ControlQueue, Deferral, Operation};
+pub use core::{Daemon, DaemonError};
+pub use exit::{ExitReason, ExitReport};
+pub use initiator::Initiator;
+pub use jobs::{JobLimits, JobPriority, JobQueue, JobState, JobStatus};
+pub use last_exit::{ExitCause, LastExit, LastExitFile};
+pub use session_jobs::{NewSessionRequest, SessionJobs, TrackedJob, TrackedJobState};
+pub use startup::{Startup, Subsystem};
 pub use state::DaemonState;
@@ -26,20 +26,32 @@ pub enum PidError {
 pub struct PidFile {
     path: PathBuf,
     acquired: bool,
+    /// PID found in a stale file removed by [`Self::acquire`].
+    stale_pid: Option<u32>,
 }
 
 impl PidFile {
     /// Create a new PID file handle pointing at the standard runtime location.
     pub fn new() -> Self {
         Self {
-            path: Paths::runtime_dir().join("palingenesis.pid"),
+            path: Paths::pid_file(),
             acquired: false,
+            stale_pid: None,
+        }
+    }
+
+    /// Create a PID file handle at `path`.
+    pub fn with_path(path: PathBuf) -> Self {
+        Self {
+            path,
+            acquired: false,
+            stale_pid: None,
         }
     }
 
     /// Handle an existing PID file: return error if process is running, otherwise remove stale file.
     /// Returns `Ok(())` if file was stale and removed, `Err(AlreadyRunning)` if process is alive.
-    fn handle_existing_pid_file(&self) -> Result<(), PidError> {
+    fn handle_existing_pid_file(&mut self) -> Result<(), PidError> {
         match self.read() {
             Ok(existing_pid) => {
                 if Self::is_process_running(existing_pid)? {
@@ -51,6 +63,7 @@ impl PidFile {
                     "Removing stale PID file"
                 );
                 self.remove()?;
+                self.stale_pid = Some(existing_pid);
             }
             Err(err) => {
                 warn!(error = %err, "Failed to read PID file, removing");
@@ -69,6 +82,9 @@ impl PidFile {
 
         Paths::ensure_runtime_dir()
             .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{err}")))?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
 
         let pid = process::id();
         let mut file = match OpenOptions::new()
@@ -114,6 +130,14 @@ impl PidFile {
             .map_err(|_| PidError::Parse(contents.trim().to_string()))
     }
 
+    /// PID of the running process that holds this file, if any.
+    pub fn running_pid(&self) -> Option<u32> {
+        let pid = self.read().ok()?;
+        Self::is_process_running(pid)
+            .unwrap_or(false)
+            .then_some(pid)
+    }
+
     /// Check if the PID file is stale.
     pub fn check_stale(&self) -> Result<bool, PidError> {
         let pid = self.read()?;
@@ -170,6 +194,12 @@ impl PidFile {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// PID of a process that died without removing its PID file, found by
+    /// [`Self::acquire`].
+    pub fn stale_pid(&self) -> Option<u32> {
+        self.stale_pid
+    }
 }
 
 impl Drop for PidFile {
@@ -208,7 +238,7 @@ mod tests {
 
     #[test]
     fn test_pid_file_creation() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -227,7 +257,7 @@ mod tests {
 
     #[test]
     fn test_stale_pid_detection() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -237,6 +267,7 @@ mod tests {
 
         let mut pid_file = PidFile::new();
         pid_file.acquire().unwrap();
+        assert_eq!(pid_file.stale_pid(), Some(4294967295));
 
         pid_file.release().unwrap();
         remove_env_var("PALINGENESIS_RUNTIME");
@@ -244,7 +275,7 @@ mod tests {
 
     #[test]
     fn test_already_running_error() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -264,7 +295,7 @@ mod tests {
 
     #[test]
     fn test_cleanup_on_release() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -283,7 +314,7 @@ mod tests {
     #[test]
     #[cfg(unix)]
     fn test_file_permissions() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -302,7 +333,7 @@ mod tests {
 
     #[test]
     fn test_check_stale_returns_true_for_nonexistent_process() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -318,7 +349,7 @@ mod tests {
 
     #[test]
     fn test_check_stale_returns_false_for_running_process() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -334,7 +365,7 @@ mod tests {
 
     #[test]
     fn test_read_returns_error_for_invalid_content() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
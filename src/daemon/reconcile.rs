@@ -0,0 +1,529 @@
+//! Reconciliation of the state file against the filesystem.
+//!
+//! An unclean shutdown can leave the state file pointing at a session file
+//! that is gone, an incident or resume-loop hold that was dealt with while
+//! the daemon was down, or stats that counted a resume twice. On startup
+//! (`daemon.reconcile.enabled`) and with `palingenesis reconcile`, every
+//! session the state file references is checked against the session files
+//! and, when `[opencode]` is enabled, the sessions the OpenCode API still
+//! reports. Each correction is logged, audited and kept in [`REPORT_FILE`]
+//! in the state directory; the daemon also sends one `state_reconciled`
+//! notification for the whole pass.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::config::schema::{Config, MissingSessionPolicy, ReconcileConfig};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::monitor::catalog;
+use crate::opencode::SharedEndpoint;
+use crate::state::{
+    AuditLogger, EscalationResolution, OrphanedSession, SessionStatus, StateError, StateFile,
+    StateHandle,
+};
+
+/// Report of the latest reconciliation, in the state directory.
+pub const REPORT_FILE: &str = "reconcile-report.json";
+
+/// Audit note on incidents closed by reconciliation.
+pub const CLOSED_NOTE: &str = "closed on reconciliation";
+
+/// Longest the OpenCode API is given to list its sessions.
+const LIVE_SESSIONS_TIMEOUT: Duration = Duration::from_secs(5);
+
+const ORPHAN_REASON: &str = "session file missing on reconciliation";
+
+/// What a correction changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionKind {
+    /// The current session's file is gone; it is held for attention.
+    MissingSessionFlagged,
+    /// The current session's file is gone; it is no longer tracked.
+    MissingSessionCleared,
+    /// The current session's file, reported missing, is back.
+    SessionReappeared,
+    /// Records of a session whose file is gone moved to the orphan list.
+    RecordOrphaned,
+    /// An incident open longer than `stale_after_secs` was closed.
+    IncidentClosed,
+    /// A resume-loop hold older than `stale_after_secs` was lifted.
+    LoopHoldLifted,
+    /// Stats that cannot be right were repaired.
+    StatsRepaired,
+}
+
+impl CorrectionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingSessionFlagged => "missing_session_flagged",
+            Self::MissingSessionCleared => "missing_session_cleared",
+            Self::SessionReappeared => "session_reappeared",
+            Self::RecordOrphaned => "record_orphaned",
+            Self::IncidentClosed => "incident_closed",
+            Self::LoopHoldLifted => "loop_hold_lifted",
+            Self::StatsRepaired => "stats_repaired",
+        }
+    }
+}
+
+impl std::fmt::Display for CorrectionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One change made (or, in a dry run, planned) to the state file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correction {
+    pub kind: CorrectionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_id: Option<String>,
+    pub detail: String,
+}
+
+impl Correction {
+    fn new(kind: CorrectionKind, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            session_path: None,
+            incident_id: None,
+            detail: detail.into(),
+        }
+    }
+
+    fn for_session(mut self, path: &Path) -> Self {
+        self.session_path = Some(path.to_path_buf());
+        self
+    }
+
+    /// The audit event for this correction, made at `timestamp`.
+    fn event(&self, timestamp: DateTime<Utc>) -> DomainEvent {
+        DomainEvent::StateCorrected {
+            timestamp,
+            correction: self.kind.as_str().to_string(),
+            detail: self.detail.clone(),
+            session_path: self.session_path.clone(),
+            incident_id: self.incident_id.clone(),
+            note: self.incident_id.as_ref().map(|_| CLOSED_NOTE.to_string()),
+        }
+    }
+}
+
+/// Outcome of one reconciliation pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    pub reconciled_at: DateTime<Utc>,
+    /// Nothing was changed; `corrections` is what would have been.
+    pub dry_run: bool,
+    pub corrections: Vec<Correction>,
+}
+
+impl ReconcileReport {
+    pub fn is_empty(&self) -> bool {
+        self.corrections.is_empty()
+    }
+
+    /// Log and audit each correction and write the report to
+    /// [`REPORT_FILE`] in `state_dir`. Returns the report's path.
+    pub fn record(&self, state_dir: &Path) -> std::io::Result<PathBuf> {
+        let audit = AuditLogger::new(state_dir);
+        for correction in &self.corrections {
+            info!(
+                correction = %correction.kind,
+                session = ?correction.session_path,
+                "{}",
+                correction.detail
+            );
+            if let Err(err) = audit.record(&correction.event(self.reconciled_at)) {
+                warn!(error = %err, "Failed to audit reconciliation");
+            }
+        }
+        let path = state_dir.join(REPORT_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// The `state_reconciled` event for this report, written to `report`.
+    pub fn event(&self, report: PathBuf) -> DomainEvent {
+        DomainEvent::StateReconciled {
+            timestamp: self.reconciled_at,
+            corrections: self
+                .corrections
+                .iter()
+                .map(|correction| correction.detail.clone())
+                .collect(),
+            report,
+        }
+    }
+}
+
+/// Checks a state file against the session files.
+#[derive(Debug, Clone)]
+pub struct Reconciler {
+    missing_session: MissingSessionPolicy,
+    stale_after: TimeDelta,
+    live: HashSet<PathBuf>,
+    now: DateTime<Utc>,
+}
+
+impl Reconciler {
+    pub fn new(config: &ReconcileConfig) -> Self {
+        Self {
+            missing_session: config.missing_session,
+            stale_after: i64::try_from(config.stale_after_secs)
+                .ok()
+                .and_then(TimeDelta::try_seconds)
+                .unwrap_or(TimeDelta::MAX),
+            live: HashSet::new(),
+            now: Utc::now(),
+        }
+    }
+
+    /// Session files the OpenCode API still reports; they count as present
+    /// even when the file is not (yet) on disk.
+    pub fn with_live_sessions(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.live.extend(paths);
+        self
+    }
+
+    /// Measure record ages against this time (for testing).
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = now;
+        self
+    }
+
+    /// The corrections [`Self::apply`] would make, leaving `state` as is.
+    pub fn plan(&self, state: &StateFile) -> ReconcileReport {
+        let mut report = self.apply(&mut state.clone());
+        report.dry_run = true;
+        report
+    }
+
+    /// Correct `state` in place.
+    pub fn apply(&self, state: &mut StateFile) -> ReconcileReport {
+        let mut corrections = Vec::new();
+        self.check_current_session(state, &mut corrections);
+        self.orphan_missing_records(state, &mut corrections);
+        self.close_stale_incidents(state, &mut corrections);
+        self.lift_stale_loop_holds(state, &mut corrections);
+        self.repair_stats(state, &mut corrections);
+        ReconcileReport {
+            reconciled_at: self.now,
+            dry_run: false,
+            corrections,
+        }
+    }
+
+    /// Records opened before this are stale.
+    fn stale_before(&self) -> Option<DateTime<Utc>> {
+        self.now.checked_sub_signed(self.stale_after)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists() || self.live.contains(path)
+    }
+
+    fn check_current_session(&self, state: &mut StateFile, corrections: &mut Vec<Correction>) {
+        let Some(session) = state.current_session.as_mut() else {
+            return;
+        };
+        let path = session.path.clone();
+        if self.exists(&path) {
+            if session.status == SessionStatus::Missing {
+                session.status = SessionStatus::Active;
+                corrections.push(
+                    Correction::new(
+                        CorrectionKind::SessionReappeared,
+                        format!(
+                            "Current session {} is back; monitoring it again",
+                            path.display()
+                        ),
+                    )
+                    .for_session(&path),
+                );
+            }
+            return;
+        }
+        // A `missing` session is already waiting for a restore.
+        if session.status != SessionStatus::Active {
+            return;
+        }
+        let correction = match self.missing_session {
+            MissingSessionPolicy::Flag => {
+                session.status = SessionStatus::NeedsAttention;
+                Correction::new(
+                    CorrectionKind::MissingSessionFlagged,
+                    format!(
+                        "Current session {} no longer exists; held for attention",
+                        path.display()
+                    ),
+                )
+            }
+            MissingSessionPolicy::Clear => {
+                state.current_session = None;
+                Correction::new(
+                    CorrectionKind::MissingSessionCleared,
+                    format!(
+                        "Current session {} no longer exists; no longer tracked",
+                        path.display()
+                    ),
+                )
+            }
+        };
+        corrections.push(correction.for_session(&path));
+    }
+
+    /// Move resume history, step timelines and completed determinations of
+    /// sessions whose files are gone to the orphan list.
+    fn orphan_missing_records(&self, state: &mut StateFile, corrections: &mut Vec<Correction>) {
+        let current = state
+            .current_session
+            .as_ref()
+            .map(|session| session.path.clone());
+        let mut missing: Vec<PathBuf> = Vec::new();
+        let recorded = state
+            .resume_history
+            .iter()
+            .map(|history| &history.path)
+            .chain(state.step_timelines.iter().map(|timeline| &timeline.path))
+            .chain(
+                state
+                    .completed_sessions
+                    .iter()
+                    .map(|completed| &completed.path),
+            );
+        for path in recorded {
+            if current.as_ref() != Some(path) && !missing.contains(path) && !self.exists(path) {
+                missing.push(path.clone());
+            }
+        }
+
+        for path in missing {
+            state.remove_resume_history(&path);
+            state
+                .step_timelines
+                .retain(|timeline| timeline.path != path);
+            state.remove_completed_session(&path);
+            if state.orphan(&path).is_none() {
+                state.record_orphan(OrphanedSession {
+                    recorded_at: self.now,
+                    ..OrphanedSession::new(path.clone(), ORPHAN_REASON)
+                });
+            }
+            corrections.push(
+                Correction::new(
+                    CorrectionKind::RecordOrphaned,
+                    format!(
+                        "Session {} no longer exists; its records moved to the orphan list",
+                        path.display()
+                    ),
+                )
+                .for_session(&path),
+            );
+        }
+    }
+
+    fn close_stale_incidents(&self, state: &mut StateFile, corrections: &mut Vec<Correction>) {
+        let Some(cutoff) = self.stale_before() else {
+            return;
+        };
+        for record in state
+            .escalations
+            .iter_mut()
+            .filter(|record| record.resolved_at.is_none() && record.opened_at < cutoff)
+        {
+            record.resolved_at = Some(self.now);
+            record.resolution = Some(EscalationResolution::Reconciled);
+            let mut correction = Correction::new(
+                CorrectionKind::IncidentClosed,
+                format!(
+                    "Incident {} open since {} closed",
+                    record.incident_id,
+                    record.opened_at.to_rfc3339()
+                ),
+            )
+            .for_session(&record.session_path);
+            correction.incident_id = Some(record.incident_id.clone());
+            corrections.push(correction);
+        }
+    }
+
+    fn lift_stale_loop_holds(&self, state: &mut StateFile, corrections: &mut Vec<Correction>) {
+        let Some(cutoff) = self.stale_before() else {
+            return;
+        };
+        let mut lifted = Vec::new();
+        for history in &mut state.resume_history {
+            if let Some(since) = history.loop_suspected_at.filter(|at| *at < cutoff) {
+                history.loop_suspected_at = None;
+                lifted.push((history.path.clone(), since));
+            }
+        }
+        for (path, since) in lifted {
+            if let Some(session) = state.current_session.as_mut().filter(|session| {
+                session.path == path && session.status == SessionStatus::NeedsAttention
+            }) {
+                if self.exists(&path) {
+                    session.status = SessionStatus::Active;
+                }
+            }
+            corrections.push(
+                Correction::new(
+                    CorrectionKind::LoopHoldLifted,
+                    format!(
+                        "Resume-loop hold on {} since {} lifted",
+                        path.display(),
+                        since.to_rfc3339()
+                    ),
+                )
+                .for_session(&path),
+            );
+        }
+    }
+
+    fn repair_stats(&self, state: &mut StateFile, corrections: &mut Vec<Correction>) {
+        let stats = &mut state.stats;
+        let mut repaired = Vec::new();
+        // Both are counted by the same update; more saves than resumes
+        // means a resume was counted twice.
+        if stats.saves_count > stats.total_resumes {
+            repaired.push(format!(
+                "saves_count {} capped at total_resumes {}",
+                stats.saves_count, stats.total_resumes
+            ));
+            stats.saves_count = stats.total_resumes;
+        }
+        for (name, value) in [
+            ("time_saved_seconds", &mut stats.time_saved_seconds),
+            ("estimated_cost", &mut stats.estimated_cost),
+            ("value_saved", &mut stats.value_saved),
+        ] {
+            if !value.is_finite() || *value < 0.0 {
+                repaired.push(format!("{name} {value} reset to 0"));
+                *value = 0.0;
+            }
+        }
+        if let Some(last_resume) = stats.last_resume.filter(|at| *at > self.now) {
+            repaired.push(format!(
+                "last_resume {} is in the future",
+                last_resume.to_rfc3339()
+            ));
+            stats.last_resume = Some(self.now);
+        }
+        if !repaired.is_empty() {
+            corrections.push(Correction::new(
+                CorrectionKind::StatsRepaired,
+                format!("Stats repaired: {}", repaired.join("; ")),
+            ));
+        }
+    }
+}
+
+/// Files of the sessions the OpenCode API reports, when `[opencode]` is
+/// enabled and the API answers within [`LIVE_SESSIONS_TIMEOUT`].
+pub async fn live_session_paths(
+    config: &Config,
+    endpoint: Option<SharedEndpoint>,
+    cancel: CancellationToken,
+) -> HashSet<PathBuf> {
+    if !config.opencode.enabled {
+        return HashSet::new();
+    }
+    let sessions = tokio::time::timeout(
+        LIVE_SESSIONS_TIMEOUT,
+        catalog::live_sessions(config, endpoint, cancel),
+    )
+    .await
+    .unwrap_or_default();
+    sessions
+        .into_iter()
+        .filter_map(|session| session.path)
+        .collect()
+}
+
+/// Reconcile the daemon's state at startup: correct it, write it through,
+/// then record the report and announce it.
+pub fn reconcile_state(
+    store: &StateHandle,
+    reconciler: &Reconciler,
+    state_dir: &Path,
+    events: &EventBroadcaster,
+) -> Result<ReconcileReport, StateError> {
+    let report = store.update_critical(|state| reconciler.apply(state))?;
+    if report.is_empty() {
+        return Ok(report);
+    }
+    info!(
+        corrections = report.corrections.len(),
+        "State file reconciled"
+    );
+    let path = report.record(state_dir).unwrap_or_else(|err| {
+        warn!(error = %err, "Failed to write reconciliation report");
+        state_dir.join(REPORT_FILE)
+    });
+    events.publish(report.event(path));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::audit::AuditEventType;
+    use crate::state::{CurrentSession, EscalationRecord};
+
+    #[test]
+    fn live_sessions_count_as_present() {
+        let path = PathBuf::from("/nonexistent/live.md");
+        let mut state = StateFile {
+            current_session: Some(CurrentSession {
+                path: path.clone(),
+                ..CurrentSession::default()
+            }),
+            ..StateFile::default()
+        };
+        let reconciler = Reconciler::new(&ReconcileConfig::default());
+        assert_eq!(reconciler.plan(&state).corrections.len(), 1);
+
+        let reconciler = reconciler.with_live_sessions([path]);
+        assert!(reconciler.apply(&mut state).is_empty());
+        assert!(state.current_session.unwrap().status.is_active());
+    }
+
+    #[test]
+    fn incidents_within_the_threshold_stay_open() {
+        let now = Utc::now();
+        let mut state = StateFile::default();
+        state.record_escalation(EscalationRecord::new(
+            "inc-1",
+            PathBuf::from("/tmp/a.md"),
+            now - TimeDelta::hours(1),
+        ));
+        let report = Reconciler::new(&ReconcileConfig::default())
+            .with_now(now)
+            .apply(&mut state);
+        assert!(report.is_empty());
+        assert!(state.escalations[0].resolved_at.is_none());
+    }
+
+    #[test]
+    fn closed_incidents_are_audited_with_a_note() {
+        let mut correction =
+            Correction::new(CorrectionKind::IncidentClosed, "Incident inc-1 closed");
+        correction.incident_id = Some("inc-1".to_string());
+        let entry = correction.event(Utc::now()).audit_entry().unwrap();
+        assert_eq!(entry.event_type, AuditEventType::StateReconciled);
+        assert_eq!(entry.metadata["note"], CLOSED_NOTE);
+        assert_eq!(entry.metadata["correction"], "incident_closed");
+    }
+}
@@ -0,0 +1,577 @@
+//! Correlation between opencode crashes and session stops.
+//!
+//! A crashing `opencode serve` takes its sessions down with it, so the stop
+//! the monitor classifies moments later is a symptom of the crash, not
+//! something a resume should fix while the server is still restarting. A
+//! crash opens a server-down window: resumable stops of sessions served by
+//! that process are held until the server passes a health check again, and
+//! each held session then gets `opencode.crash_grace_secs` to continue on
+//! its own. Sessions that do are dropped as resumes avoided; the rest
+//! proceed as classified. Resumable stops that arrive just before the crash
+//! event are held for `opencode.crash_settle_ms` so either order correlates.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::config::schema::OpenCodeConfig;
+use crate::monitor::events::MonitorEvent;
+use crate::monitor::process::ProcessInfo;
+use crate::opencode::OpenCodeProcess;
+
+/// Timing of the correlation, from the `[opencode]` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorrelationSettings {
+    pub settle: Duration,
+    pub restart_timeout: Duration,
+    pub grace: Duration,
+}
+
+impl CorrelationSettings {
+    pub fn from_config(config: &OpenCodeConfig) -> Self {
+        Self {
+            settle: Duration::from_millis(config.crash_settle_ms),
+            restart_timeout: Duration::from_secs(config.crash_restart_timeout_secs),
+            grace: Duration::from_secs(config.crash_grace_secs),
+        }
+    }
+}
+
+impl Default for CorrelationSettings {
+    fn default() -> Self {
+        Self::from_config(&OpenCodeConfig::default())
+    }
+}
+
+/// How a held stop ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeldOutcome {
+    /// The session continued after the restart; no resume needed.
+    Avoided,
+    /// The session stayed stopped; the classification went ahead.
+    Proceeded,
+}
+
+impl HeldOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Avoided => "avoided",
+            Self::Proceeded => "proceeded",
+        }
+    }
+}
+
+/// What the caller should do after feeding an event in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrelationAction {
+    /// Hand the stop on to the resume pipeline.
+    Proceed(Box<MonitorEvent>),
+    WindowOpened {
+        pid: u32,
+        exit_code: i32,
+    },
+    WindowClosed {
+        pid: u32,
+        /// Whether the server came back healthy (otherwise the wait timed out).
+        restarted: bool,
+        down: Duration,
+    },
+    /// A stop is held until the server is back.
+    Held {
+        session_path: PathBuf,
+        stop_reason: String,
+        pid: u32,
+    },
+    Released {
+        session_path: PathBuf,
+        stop_reason: String,
+        outcome: HeldOutcome,
+        held: Duration,
+    },
+}
+
+/// Server-down window and held stops, for `palingenesis status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestartCorrelationStatus {
+    /// When the crash opened the current window; `None` once the server is back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_down_since: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crashed_pid: Option<u32>,
+    /// Sessions whose stop is held for a restart.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub held_sessions: Vec<String>,
+    /// Resumes avoided because the session continued after a restart.
+    pub resumes_avoided: u64,
+}
+
+#[derive(Debug)]
+struct ServerDown {
+    process: OpenCodeProcess,
+    opened: Instant,
+    opened_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+enum Phase {
+    /// Waiting for a crash this stop may belong to.
+    Settling,
+    /// Waiting for the crashed server to come back.
+    ServerDown,
+    /// The server is back; waiting for the session to continue.
+    Grace,
+}
+
+#[derive(Debug)]
+struct HeldStop {
+    event: MonitorEvent,
+    session_path: PathBuf,
+    stop_reason: String,
+    process: Option<ProcessInfo>,
+    held_since: Instant,
+    deadline: Instant,
+    phase: Phase,
+    /// The session changed while the server was down.
+    continued: bool,
+}
+
+/// Holds resumable stops while a crashed opencode server restarts.
+#[derive(Debug, Default)]
+pub struct RestartCorrelator {
+    settings: CorrelationSettings,
+    window: Option<ServerDown>,
+    held: Vec<HeldStop>,
+    resumes_avoided: u64,
+}
+
+impl RestartCorrelator {
+    pub fn new(settings: CorrelationSettings) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// New timings apply to stops held from now on.
+    pub fn set_settings(&mut self, settings: CorrelationSettings) {
+        self.settings = settings;
+    }
+
+    /// A classified stop from the monitor.
+    pub fn on_stop(&mut self, event: MonitorEvent, now: Instant) -> Vec<CorrelationAction> {
+        let MonitorEvent::SessionStopped {
+            session: Some(session),
+            reason,
+            process_info,
+            ..
+        } = &event
+        else {
+            return vec![CorrelationAction::Proceed(Box::new(event))];
+        };
+        if !reason.should_auto_resume() {
+            return vec![CorrelationAction::Proceed(Box::new(event))];
+        }
+        let session_path = session.path.clone();
+        let stop_reason = reason
+            .metrics_reason_label()
+            .unwrap_or("unknown")
+            .to_string();
+        let process = process_info.clone();
+
+        if let Some(window) = &self.window {
+            if serves(&window.process, process.as_ref(), &session_path) {
+                let pid = window.process.pid;
+                let deadline = window.opened + self.settings.restart_timeout;
+                self.held.push(HeldStop {
+                    event,
+                    session_path: session_path.clone(),
+                    stop_reason: stop_reason.clone(),
+                    process,
+                    held_since: now,
+                    deadline,
+                    phase: Phase::ServerDown,
+                    continued: false,
+                });
+                return vec![CorrelationAction::Held {
+                    session_path,
+                    stop_reason,
+                    pid,
+                }];
+            }
+        }
+        if self.settings.settle.is_zero() {
+            return vec![CorrelationAction::Proceed(Box::new(event))];
+        }
+        self.held.push(HeldStop {
+            event,
+            session_path,
+            stop_reason,
+            process,
+            held_since: now,
+            deadline: now + self.settings.settle,
+            phase: Phase::Settling,
+            continued: false,
+        });
+        Vec::new()
+    }
+
+    /// The opencode server crashed: open a window and hold the stops it explains.
+    pub fn on_crash(
+        &mut self,
+        process: OpenCodeProcess,
+        exit_code: i32,
+        now: Instant,
+    ) -> Vec<CorrelationAction> {
+        let mut actions = vec![CorrelationAction::WindowOpened {
+            pid: process.pid,
+            exit_code,
+        }];
+        let deadline = now + self.settings.restart_timeout;
+        for held in &mut self.held {
+            if matches!(held.phase, Phase::Settling)
+                && serves(&process, held.process.as_ref(), &held.session_path)
+            {
+                held.phase = Phase::ServerDown;
+                held.deadline = deadline;
+                actions.push(CorrelationAction::Held {
+                    session_path: held.session_path.clone(),
+                    stop_reason: held.stop_reason.clone(),
+                    pid: process.pid,
+                });
+            }
+        }
+        self.window = Some(ServerDown {
+            process,
+            opened: now,
+            opened_at: Utc::now(),
+        });
+        actions
+    }
+
+    /// The restarted server passed a health check: close the window and
+    /// re-evaluate every held stop.
+    pub fn on_healthy(&mut self, now: Instant) -> Vec<CorrelationAction> {
+        let Some(window) = self.window.take() else {
+            return Vec::new();
+        };
+        let mut actions = vec![CorrelationAction::WindowClosed {
+            pid: window.process.pid,
+            restarted: true,
+            down: now.saturating_duration_since(window.opened),
+        }];
+        let grace = now + self.settings.grace;
+        let mut index = 0;
+        while index < self.held.len() {
+            let held = &mut self.held[index];
+            if !matches!(held.phase, Phase::ServerDown) {
+                index += 1;
+                continue;
+            }
+            if held.continued {
+                let held = self.held.remove(index);
+                actions.push(self.release(held, HeldOutcome::Avoided, now));
+                continue;
+            }
+            held.phase = Phase::Grace;
+            held.deadline = grace;
+            index += 1;
+        }
+        actions
+    }
+
+    /// The session at `path` changed, i.e. it is running again.
+    pub fn on_session_activity(&mut self, path: &Path, now: Instant) -> Vec<CorrelationAction> {
+        let mut actions = Vec::new();
+        let mut index = 0;
+        while index < self.held.len() {
+            let held = &mut self.held[index];
+            if held.session_path != path {
+                index += 1;
+                continue;
+            }
+            match held.phase {
+                Phase::Settling => index += 1,
+                Phase::ServerDown => {
+                    held.continued = true;
+                    index += 1;
+                }
+                Phase::Grace => {
+                    let held = self.held.remove(index);
+                    actions.push(self.release(held, HeldOutcome::Avoided, now));
+                }
+            }
+        }
+        actions
+    }
+
+    /// Earliest time [`Self::on_deadline`] has work to do.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.held.iter().map(|held| held.deadline).min()
+    }
+
+    /// Release everything whose wait is over.
+    pub fn on_deadline(&mut self, now: Instant) -> Vec<CorrelationAction> {
+        let mut actions = Vec::new();
+        if let Some(window) = &self.window {
+            if now >= window.opened + self.settings.restart_timeout {
+                let window = self.window.take().expect("window checked above");
+                actions.push(CorrelationAction::WindowClosed {
+                    pid: window.process.pid,
+                    restarted: false,
+                    down: now.saturating_duration_since(window.opened),
+                });
+            }
+        }
+
+        let mut index = 0;
+        while index < self.held.len() {
+            if self.held[index].deadline > now {
+                index += 1;
+                continue;
+            }
+            let held = self.held.remove(index);
+            if matches!(held.phase, Phase::Settling) {
+                actions.push(CorrelationAction::Proceed(Box::new(held.event)));
+            } else {
+                let event = held.event.clone();
+                actions.push(self.release(held, HeldOutcome::Proceeded, now));
+                actions.push(CorrelationAction::Proceed(Box::new(event)));
+            }
+        }
+        actions
+    }
+
+    /// `None` while there is nothing to report.
+    pub fn status(&self) -> Option<RestartCorrelationStatus> {
+        let held_sessions: Vec<String> = self
+            .held
+            .iter()
+            .filter(|held| !matches!(held.phase, Phase::Settling))
+            .map(|held| held.session_path.display().to_string())
+            .collect();
+        if self.window.is_none() && held_sessions.is_empty() && self.resumes_avoided == 0 {
+            return None;
+        }
+        Some(RestartCorrelationStatus {
+            server_down_since: self.window.as_ref().map(|window| window.opened_at),
+            crashed_pid: self.window.as_ref().map(|window| window.process.pid),
+            held_sessions,
+            resumes_avoided: self.resumes_avoided,
+        })
+    }
+
+    fn release(&mut self, held: HeldStop, outcome: HeldOutcome, now: Instant) -> CorrelationAction {
+        if outcome == HeldOutcome::Avoided {
+            self.resumes_avoided += 1;
+        }
+        CorrelationAction::Released {
+            session_path: held.session_path,
+            stop_reason: held.stop_reason,
+            outcome,
+            held: now.saturating_duration_since(held.held_since),
+        }
+    }
+}
+
+/// Whether the crashed `server` explains a stop of `session_path`.
+///
+/// A stop reported with its process matches by PID or working directory.
+/// Without one, a session under the server's working directory matches, and
+/// when the server's directory is unknown every session does: one `opencode
+/// serve` backs all sessions the daemon watches.
+fn serves(server: &OpenCodeProcess, stopped: Option<&ProcessInfo>, session_path: &Path) -> bool {
+    match (stopped, &server.working_dir) {
+        (Some(stopped), working_dir) => {
+            stopped.pid == server.pid
+                || (working_dir.is_some() && stopped.working_dir == *working_dir)
+        }
+        (None, Some(working_dir)) => session_path.starts_with(working_dir),
+        (None, None) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::classifier::{
+        ClassificationResult, RateLimitInfo, RetryAfterSource, StopReason,
+    };
+    use crate::monitor::session::{Session, SessionState};
+
+    fn settings() -> CorrelationSettings {
+        CorrelationSettings {
+            settle: Duration::from_secs(2),
+            restart_timeout: Duration::from_secs(120),
+            grace: Duration::from_secs(60),
+        }
+    }
+
+    fn stop(path: &str, reason: StopReason) -> MonitorEvent {
+        MonitorEvent::SessionStopped {
+            session: Some(Session {
+                path: PathBuf::from(path),
+                state: SessionState {
+                    steps_completed: Vec::new(),
+                    last_step: None,
+                    total_steps: None,
+                    status: None,
+                    workflow_type: None,
+                    project_name: None,
+                    input_documents: Vec::new(),
+                    model: None,
+                    input_tokens: None,
+                    output_tokens: None,
+                },
+            }),
+            classification: ClassificationResult {
+                reason: reason.clone(),
+                confidence: 0.9,
+                evidence: Vec::new(),
+            },
+            reason,
+            process_info: None,
+            incident: None,
+        }
+    }
+
+    fn rate_limit() -> StopReason {
+        StopReason::RateLimit(RateLimitInfo {
+            retry_after: Duration::from_secs(30),
+            source: RetryAfterSource::ConfigDefault,
+            message: None,
+        })
+    }
+
+    fn server(pid: u32) -> OpenCodeProcess {
+        OpenCodeProcess {
+            pid,
+            command_line: vec!["opencode".to_string(), "serve".to_string()],
+            start_time: None,
+            working_dir: None,
+        }
+    }
+
+    fn proceeded(actions: &[CorrelationAction]) -> usize {
+        actions
+            .iter()
+            .filter(|action| matches!(action, CorrelationAction::Proceed(_)))
+            .count()
+    }
+
+    #[test]
+    fn unrelated_stops_proceed_after_settling() {
+        let start = Instant::now();
+        let mut correlator = RestartCorrelator::new(settings());
+
+        let completed = correlator.on_stop(stop("/w/done.md", StopReason::Completed), start);
+        assert_eq!(proceeded(&completed), 1);
+
+        assert!(
+            correlator
+                .on_stop(stop("/w/a.md", rate_limit()), start)
+                .is_empty()
+        );
+        assert_eq!(
+            correlator.next_deadline(),
+            Some(start + Duration::from_secs(2))
+        );
+        let released = correlator.on_deadline(start + Duration::from_secs(2));
+        assert_eq!(proceeded(&released), 1);
+        assert!(correlator.status().is_none());
+    }
+
+    #[test]
+    fn stop_before_crash_is_held_and_avoided_when_session_continues() {
+        let start = Instant::now();
+        let mut correlator = RestartCorrelator::new(settings());
+        correlator.on_stop(stop("/w/a.md", rate_limit()), start);
+
+        let actions = correlator.on_crash(server(7), 1, start + Duration::from_millis(500));
+        assert!(matches!(actions[1], CorrelationAction::Held { pid: 7, .. }));
+        let status = correlator.status().unwrap();
+        assert_eq!(status.crashed_pid, Some(7));
+        assert_eq!(status.held_sessions, vec!["/w/a.md".to_string()]);
+
+        // Settling would have ended here; the crash hold outlasts it.
+        assert!(
+            correlator
+                .on_deadline(start + Duration::from_secs(3))
+                .is_empty()
+        );
+        correlator.on_session_activity(Path::new("/w/a.md"), start + Duration::from_secs(5));
+        let actions = correlator.on_healthy(start + Duration::from_secs(10));
+        assert!(matches!(
+            actions[1],
+            CorrelationAction::Released {
+                outcome: HeldOutcome::Avoided,
+                ..
+            }
+        ));
+        assert_eq!(proceeded(&actions), 0);
+        assert!(correlator.next_deadline().is_none());
+        assert_eq!(correlator.status().unwrap().resumes_avoided, 1);
+    }
+
+    #[test]
+    fn held_stop_proceeds_when_session_stays_stopped_after_restart() {
+        let start = Instant::now();
+        let mut correlator = RestartCorrelator::new(settings());
+        correlator.on_crash(server(7), 1, start);
+        let held = correlator.on_stop(stop("/w/a.md", rate_limit()), start);
+        assert!(matches!(held[0], CorrelationAction::Held { .. }));
+
+        let healthy = start + Duration::from_secs(10);
+        assert_eq!(proceeded(&correlator.on_healthy(healthy)), 0);
+        assert_eq!(
+            correlator.next_deadline(),
+            Some(healthy + Duration::from_secs(60))
+        );
+        let actions = correlator.on_deadline(healthy + Duration::from_secs(60));
+        assert!(matches!(
+            actions[0],
+            CorrelationAction::Released {
+                outcome: HeldOutcome::Proceeded,
+                ..
+            }
+        ));
+        assert_eq!(proceeded(&actions), 1);
+    }
+
+    #[test]
+    fn server_that_never_recovers_releases_held_stops() {
+        let start = Instant::now();
+        let mut correlator = RestartCorrelator::new(settings());
+        correlator.on_crash(server(7), 1, start);
+        correlator.on_stop(stop("/w/a.md", rate_limit()), start);
+
+        let actions = correlator.on_deadline(start + Duration::from_secs(120));
+        assert!(matches!(
+            actions[0],
+            CorrelationAction::WindowClosed {
+                restarted: false,
+                ..
+            }
+        ));
+        assert_eq!(proceeded(&actions), 1);
+        assert!(correlator.status().is_none());
+    }
+
+    #[test]
+    fn stops_of_other_processes_are_not_held() {
+        let mut crashed = server(7);
+        crashed.working_dir = Some(PathBuf::from("/w/project"));
+        let other = ProcessInfo {
+            pid: 9,
+            command_line: Vec::new(),
+            start_time: None,
+            working_dir: Some(PathBuf::from("/w/other")),
+        };
+        assert!(!serves(&crashed, Some(&other), Path::new("/w/other/s.md")));
+        assert!(serves(&crashed, None, Path::new("/w/project/s.md")));
+        assert!(!serves(&crashed, None, Path::new("/w/other/s.md")));
+    }
+}
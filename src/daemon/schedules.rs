@@ -0,0 +1,667 @@
+//! Sessions started on a timetable (`[[schedules]]`).
+//!
+//! Each entry fires on a cron expression (or the `daily`/`weekdays`
+//! shorthands) and starts a session through the same tracked job queue as
+//! `POST /api/v1/new-session`, as a [`SCHEDULED_JOB_KIND`] job. Firings are
+//! recorded in the state file (`schedule_runs`), the audit trail and a
+//! `scheduled_run` event once the session has started, failed or been
+//! skipped.
+//!
+//! Firings missed while the daemon was down are dropped, unless the entry
+//! sets `catch_up`: then only the most recent miss runs, once, on startup.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveTime, SubsecRound, Timelike, Utc};
+use croner::Cron;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::config::schema::{Config, DaemonMode, ScheduleConfig, ScheduleTimezone};
+use crate::daemon::initiator::Initiator;
+use crate::daemon::session_jobs::{NewSessionRequest, TrackedJobState};
+use crate::daemon::state::DaemonState;
+use crate::daemon::suspend::{Clock, SystemClock};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::ResumeOutcome;
+use crate::state::{AuditLogger, ScheduleRun, ScheduleRunOutcome, StateFile, StateHandle};
+
+/// Tracked job kind of scheduled sessions.
+pub const SCHEDULED_JOB_KIND: &str = "scheduled_session";
+
+/// Longest sleep between checks, so clock changes are noticed.
+const MAX_TICK: Duration = Duration::from_secs(60);
+
+/// How far back the most recent occurrence is looked for, widening until
+/// one is found.
+const LOOKBACK: [chrono::Duration; 5] = [
+    chrono::Duration::hours(1),
+    chrono::Duration::days(1),
+    chrono::Duration::days(7),
+    chrono::Duration::days(31),
+    chrono::Duration::days(366),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ScheduleSpecError {
+    #[error("one of cron, daily or weekdays must be set")]
+    Missing,
+
+    #[error("only one of cron, daily or weekdays may be set")]
+    Ambiguous,
+
+    #[error("invalid time {value:?} (expected HH:MM)")]
+    InvalidTime { value: String },
+
+    #[error("invalid cron expression {expression:?}: {message}")]
+    InvalidCron { expression: String, message: String },
+}
+
+/// When a schedule fires.
+#[derive(Debug, Clone)]
+pub struct ScheduleSpec {
+    cron: Cron,
+    timezone: ScheduleTimezone,
+}
+
+impl ScheduleSpec {
+    pub fn from_config(schedule: &ScheduleConfig) -> Result<Self, ScheduleSpecError> {
+        let expression = match (&schedule.cron, &schedule.daily, &schedule.weekdays) {
+            (Some(cron), None, None) => cron.trim().to_string(),
+            (None, Some(time), None) => {
+                let (hour, minute) = parse_time(time)?;
+                format!("{minute} {hour} * * *")
+            }
+            (None, None, Some(time)) => {
+                let (hour, minute) = parse_time(time)?;
+                format!("{minute} {hour} * * 1-5")
+            }
+            (None, None, None) => return Err(ScheduleSpecError::Missing),
+            _ => return Err(ScheduleSpecError::Ambiguous),
+        };
+        let cron =
+            Cron::new(&expression)
+                .parse()
+                .map_err(|err| ScheduleSpecError::InvalidCron {
+                    expression: expression.clone(),
+                    message: err.to_string(),
+                })?;
+        Ok(Self {
+            cron,
+            timezone: schedule.timezone,
+        })
+    }
+
+    /// The cron expression, with shorthands expanded.
+    pub fn expression(&self) -> &str {
+        self.cron.pattern.as_str()
+    }
+
+    /// First occurrence strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let after = after.trunc_subsecs(0);
+        match self.timezone {
+            ScheduleTimezone::Utc => self.cron.find_next_occurrence(&after, false).ok(),
+            ScheduleTimezone::Local => self
+                .cron
+                .find_next_occurrence(&after.with_timezone(&Local), false)
+                .ok()
+                .map(|next| next.with_timezone(&Utc)),
+        }
+    }
+
+    /// Latest occurrence in `(after, until]`.
+    pub fn last_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if until <= after {
+            return None;
+        }
+        for window in LOOKBACK {
+            let start = (until - window).max(after);
+            let mut last = None;
+            let mut cursor = start;
+            while let Some(next) = self.next_after(cursor) {
+                if next > until {
+                    break;
+                }
+                last = Some(next);
+                cursor = next;
+            }
+            if last.is_some() || start == after {
+                return last;
+            }
+        }
+        None
+    }
+
+    /// `at` in the schedule's time zone, formatted with `format`.
+    fn format(&self, at: DateTime<Utc>, format: &str) -> String {
+        match self.timezone {
+            ScheduleTimezone::Utc => at.format(format).to_string(),
+            ScheduleTimezone::Local => at.with_timezone(&Local).format(format).to_string(),
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<(u32, u32), ScheduleSpecError> {
+    let time = NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| {
+        ScheduleSpecError::InvalidTime {
+            value: value.to_string(),
+        }
+    })?;
+    Ok((time.hour(), time.minute()))
+}
+
+/// Why a schedule fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Scheduled,
+    /// The most recent firing missed while the daemon was down.
+    CatchUp,
+    /// `palingenesis schedules run`.
+    Manual,
+}
+
+impl Trigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::CatchUp => "catch_up",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// The schedule's prompt with `{schedule}`, `{date}`, `{time}` and
+/// `{workdir}` filled in.
+pub fn render_prompt(
+    schedule: &ScheduleConfig,
+    spec: &ScheduleSpec,
+    fired_at: DateTime<Utc>,
+    workdir: &Path,
+) -> String {
+    schedule
+        .prompt
+        .replace("{schedule}", &schedule.name)
+        .replace("{date}", &spec.format(fired_at, "%Y-%m-%d"))
+        .replace("{time}", &spec.format(fired_at, "%H:%M"))
+        .replace("{workdir}", &workdir.display().to_string())
+}
+
+/// The current session, if it is still active and lives under `workdir`.
+pub fn active_session_in(state: &StateFile, workdir: &Path) -> Option<PathBuf> {
+    let current = state.current_session.as_ref()?;
+    if !current.status.is_active()
+        || state
+            .completed_sessions
+            .iter()
+            .any(|completed| completed.path == current.path)
+    {
+        return None;
+    }
+    let path = current
+        .path
+        .canonicalize()
+        .unwrap_or_else(|_| current.path.clone());
+    path.starts_with(workdir).then(|| current.path.clone())
+}
+
+/// A schedule as shown by `palingenesis schedules list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub name: String,
+    /// Cron expression, or the config error that keeps it from firing.
+    pub when: String,
+    pub enabled: bool,
+    pub catch_up: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<ScheduleRun>,
+}
+
+/// Every configured schedule with its next and last firing.
+pub fn statuses(config: &Config, state: &StateFile, now: DateTime<Utc>) -> Vec<ScheduleStatus> {
+    config
+        .schedules
+        .iter()
+        .map(|schedule| {
+            let spec = ScheduleSpec::from_config(schedule);
+            ScheduleStatus {
+                name: schedule.name.clone(),
+                when: match &spec {
+                    Ok(spec) => spec.expression().to_string(),
+                    Err(err) => format!("invalid: {err}"),
+                },
+                enabled: schedule.enabled,
+                catch_up: schedule.catch_up,
+                next_run: spec
+                    .ok()
+                    .filter(|_| schedule.enabled)
+                    .and_then(|spec| spec.next_after(now)),
+                last_run: state.last_schedule_run(&schedule.name).cloned(),
+            }
+        })
+        .collect()
+}
+
+/// Fires `[[schedules]]` entries.
+pub struct Scheduler {
+    store: StateHandle,
+    clock: Arc<dyn Clock>,
+    events: Option<EventBroadcaster>,
+    audit: Option<AuditLogger>,
+    /// Occurrences up to here have been handled.
+    checked_until: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl Scheduler {
+    pub fn new(store: StateHandle) -> Self {
+        Self {
+            store,
+            clock: Arc::new(SystemClock),
+            events: None,
+            audit: None,
+            checked_until: Mutex::new(None),
+        }
+    }
+
+    /// Read the time from `clock` (for testing).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.clock.wall())
+    }
+
+    /// Run, once each, the most recent firing that `catch_up` schedules
+    /// missed since they last ran. Later ticks only consider firings after
+    /// this call.
+    pub fn catch_up(self: &Arc<Self>, state: &DaemonState) -> Vec<ScheduleRun> {
+        let now = self.now();
+        let snapshot = self.store.snapshot();
+        let mut runs = Vec::new();
+        for schedule in state.config_snapshot().schedules {
+            if !schedule.enabled || !schedule.catch_up {
+                continue;
+            }
+            let Some(last) = snapshot.last_schedule_run(&schedule.name) else {
+                continue;
+            };
+            let Ok(spec) = ScheduleSpec::from_config(&schedule) else {
+                continue;
+            };
+            if let Some(missed) = spec.last_between(last.fired_at, now) {
+                info!(schedule = %schedule.name, %missed, "Catching up missed schedule");
+                runs.push(self.fire(state, &schedule, &spec, missed, Trigger::CatchUp, None));
+            }
+        }
+        if let Ok(mut checked) = self.checked_until.lock() {
+            *checked = Some(now);
+        }
+        runs
+    }
+
+    /// Fire the enabled schedules due since the last tick.
+    pub fn tick(self: &Arc<Self>, state: &DaemonState) -> Vec<ScheduleRun> {
+        let now = self.now();
+        let since = {
+            let Ok(mut checked) = self.checked_until.lock() else {
+                return Vec::new();
+            };
+            let since = checked.unwrap_or(now);
+            // A clock set backwards must not fire the same occurrence again.
+            *checked = Some(now.max(since));
+            since
+        };
+        let mut runs = Vec::new();
+        for schedule in state.config_snapshot().schedules {
+            if !schedule.enabled {
+                continue;
+            }
+            let spec = match ScheduleSpec::from_config(&schedule) {
+                Ok(spec) => spec,
+                Err(err) => {
+                    debug!(schedule = %schedule.name, error = %err, "Schedule not evaluated");
+                    continue;
+                }
+            };
+            if let Some(due) = spec.last_between(since, now) {
+                runs.push(self.fire(state, &schedule, &spec, due, Trigger::Scheduled, None));
+            }
+        }
+        runs
+    }
+
+    /// Fire `name` now, even when it is disabled.
+    pub fn run_now(
+        self: &Arc<Self>,
+        state: &DaemonState,
+        name: &str,
+        initiator: &Initiator,
+    ) -> Result<ScheduleRun, String> {
+        let config = state.config_snapshot();
+        let schedule = config
+            .schedules
+            .iter()
+            .find(|schedule| schedule.name == name)
+            .ok_or_else(|| format!("No schedule named {name:?}"))?;
+        let spec = ScheduleSpec::from_config(schedule)
+            .map_err(|err| format!("Schedule {name:?}: {err}"))?;
+        Ok(self.fire(
+            state,
+            schedule,
+            &spec,
+            self.now(),
+            Trigger::Manual,
+            Some(initiator.clone()),
+        ))
+    }
+
+    /// Earliest next firing of the enabled schedules.
+    fn next_due(&self, state: &DaemonState, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        state
+            .config_snapshot()
+            .schedules
+            .iter()
+            .filter(|schedule| schedule.enabled)
+            .filter_map(|schedule| ScheduleSpec::from_config(schedule).ok())
+            .filter_map(|spec| spec.next_after(now))
+            .min()
+    }
+
+    /// Catch up, then fire schedules as they come due until `cancel`.
+    pub async fn run(self: Arc<Self>, state: Arc<DaemonState>, cancel: CancellationToken) {
+        self.catch_up(&state);
+        loop {
+            let now = self.now();
+            let wait = self
+                .next_due(&state, now)
+                .and_then(|due| (due - now).to_std().ok())
+                .map_or(MAX_TICK, |wait| wait.min(MAX_TICK));
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(wait) => {}
+            }
+            self.tick(&state);
+        }
+    }
+
+    fn fire(
+        self: &Arc<Self>,
+        state: &DaemonState,
+        schedule: &ScheduleConfig,
+        spec: &ScheduleSpec,
+        fired_at: DateTime<Utc>,
+        trigger: Trigger,
+        initiator: Option<Initiator>,
+    ) -> ScheduleRun {
+        let mut run = ScheduleRun {
+            schedule: schedule.name.clone(),
+            fired_at,
+            trigger: trigger.as_str().to_string(),
+            outcome: ScheduleRunOutcome::Skipped,
+            job_id: None,
+            session_path: None,
+            detail: None,
+        };
+        let session_dir = state
+            .monitoring_config()
+            .map(|monitoring| monitoring.session_dir)
+            .unwrap_or_default();
+
+        if trigger != Trigger::Manual {
+            let held = if state.is_paused() {
+                Some("daemon is paused")
+            } else if state.mode() == DaemonMode::Observe {
+                Some("daemon is in observe mode")
+            } else {
+                None
+            };
+            if let Some(reason) = held {
+                run.detail = Some(reason.to_string());
+                return self.finish(run, &schedule.workdir, initiator);
+            }
+        }
+
+        let mut request =
+            NewSessionRequest::new(schedule.prompt.clone()).with_workdir(schedule.workdir.clone());
+        if let Some(model) = &schedule.model {
+            request = request.with_model(model.clone());
+        }
+        if let Some(assistant) = &schedule.assistant {
+            request = request.with_assistant(assistant.clone());
+        }
+        let workdir = match request.validate(&session_dir) {
+            Ok(workdir) => workdir,
+            Err(err) => {
+                run.outcome = ScheduleRunOutcome::Failed;
+                run.detail = Some(err.to_string());
+                return self.finish(run, &schedule.workdir, initiator);
+            }
+        };
+
+        if schedule.skip_if_active {
+            if let Some(reason) = self.busy_reason(state, &schedule.name, &workdir) {
+                run.detail = Some(reason);
+                return self.finish(run, &workdir, initiator);
+            }
+        }
+
+        request.prompt = render_prompt(schedule, spec, fired_at, &workdir);
+        let job_id = state.submit_session_job(SCHEDULED_JOB_KIND, request, workdir.clone());
+        info!(schedule = %schedule.name, trigger = trigger.as_str(), job = %job_id, "Scheduled session queued");
+        run.outcome = ScheduleRunOutcome::Started;
+        run.job_id = Some(job_id.clone());
+        self.store
+            .update(|file| file.record_schedule_run(run.clone()));
+
+        let scheduler = Arc::clone(self);
+        let jobs = state.session_jobs();
+        let started = run.clone();
+        tokio::spawn(async move {
+            let mut run = started;
+            match jobs.wait(&job_id).await {
+                Some(job) if job.state == TrackedJobState::Succeeded => {
+                    run.outcome = ScheduleRunOutcome::Succeeded;
+                    if let Some(ResumeOutcome::Success { session_path, .. }) = job.outcome {
+                        run.session_path = Some(session_path);
+                    }
+                }
+                Some(job) => {
+                    run.outcome = ScheduleRunOutcome::Failed;
+                    run.detail = job.error;
+                }
+                None => {
+                    run.outcome = ScheduleRunOutcome::Failed;
+                    run.detail = Some("session job result expired".to_string());
+                }
+            }
+            scheduler.finish(run, &workdir, initiator);
+        });
+        run
+    }
+
+    /// Why `workdir` should not get another session yet, if it should not.
+    fn busy_reason(&self, state: &DaemonState, schedule: &str, workdir: &Path) -> Option<String> {
+        let snapshot = self.store.snapshot();
+        if let Some(session) = active_session_in(&snapshot, workdir) {
+            return Some(format!("session {} is still active", session.display()));
+        }
+        let last = snapshot.last_schedule_run(schedule)?;
+        let job = state.session_jobs().get(last.job_id.as_deref()?)?;
+        (last.outcome == ScheduleRunOutcome::Started && !job.state.is_finished())
+            .then(|| "the previous scheduled session is still starting".to_string())
+    }
+
+    /// Store the final state of `run` and report it.
+    fn finish(
+        &self,
+        run: ScheduleRun,
+        workdir: &Path,
+        initiator: Option<Initiator>,
+    ) -> ScheduleRun {
+        self.store.update(|file| {
+            let started = run.job_id.as_ref().and_then(|job_id| {
+                file.schedule_runs
+                    .iter_mut()
+                    .rev()
+                    .find(|recorded| recorded.job_id.as_ref() == Some(job_id))
+            });
+            match started {
+                Some(recorded) => *recorded = run.clone(),
+                None => file.record_schedule_run(run.clone()),
+            }
+        });
+        match run.outcome {
+            ScheduleRunOutcome::Failed => {
+                warn!(schedule = %run.schedule, detail = ?run.detail, "Scheduled session failed")
+            }
+            ScheduleRunOutcome::Skipped => {
+                info!(schedule = %run.schedule, detail = ?run.detail, "Scheduled session skipped")
+            }
+            _ => {
+                info!(schedule = %run.schedule, session = ?run.session_path, "Scheduled session started")
+            }
+        }
+        let event = DomainEvent::ScheduledRun {
+            timestamp: self.now(),
+            schedule: run.schedule.clone(),
+            trigger: run.trigger.clone(),
+            outcome: run.outcome.as_str().to_string(),
+            workdir: workdir.to_path_buf(),
+            session_path: run.session_path.clone(),
+            detail: run.detail.clone(),
+            initiator,
+        };
+        if let Some(audit) = &self.audit {
+            if let Err(err) = audit.record(&event) {
+                warn!(error = %err, "Failed to audit scheduled session");
+            }
+        }
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(cron: Option<&str>, daily: Option<&str>, weekdays: Option<&str>) -> ScheduleConfig {
+        ScheduleConfig {
+            name: "triage".to_string(),
+            cron: cron.map(str::to_string),
+            daily: daily.map(str::to_string),
+            weekdays: weekdays.map(str::to_string),
+            timezone: ScheduleTimezone::Utc,
+            workdir: PathBuf::new(),
+            prompt: "Triage {schedule} on {date} at {time}".to_string(),
+            assistant: None,
+            model: None,
+            enabled: true,
+            skip_if_active: true,
+            catch_up: false,
+        }
+    }
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn shorthands_expand_to_cron() {
+        let daily = ScheduleSpec::from_config(&schedule(None, Some("06:30"), None)).unwrap();
+        assert_eq!(daily.expression(), "30 6 * * *");
+        let weekdays = ScheduleSpec::from_config(&schedule(None, None, Some("6:05"))).unwrap();
+        assert_eq!(weekdays.expression(), "5 6 * * 1-5");
+
+        assert_eq!(
+            ScheduleSpec::from_config(&schedule(None, None, None)).unwrap_err(),
+            ScheduleSpecError::Missing
+        );
+        assert_eq!(
+            ScheduleSpec::from_config(&schedule(Some("0 6 * * *"), Some("06:00"), None))
+                .unwrap_err(),
+            ScheduleSpecError::Ambiguous
+        );
+        assert!(matches!(
+            ScheduleSpec::from_config(&schedule(None, Some("25:00"), None)),
+            Err(ScheduleSpecError::InvalidTime { .. })
+        ));
+        assert!(matches!(
+            ScheduleSpec::from_config(&schedule(Some("61 * * * *"), None, None)),
+            Err(ScheduleSpecError::InvalidCron { .. })
+        ));
+    }
+
+    #[test]
+    fn weekday_schedules_skip_weekends() {
+        let spec = ScheduleSpec::from_config(&schedule(None, None, Some("06:00"))).unwrap();
+        // Friday 2026-03-06 after six, so the next firing is on Monday.
+        assert_eq!(
+            spec.next_after(at("2026-03-06T07:00:00Z")),
+            Some(at("2026-03-09T06:00:00Z"))
+        );
+        assert_eq!(
+            spec.next_after(at("2026-03-09T06:00:00Z")),
+            Some(at("2026-03-10T06:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn last_between_finds_the_most_recent_occurrence() {
+        let spec = ScheduleSpec::from_config(&schedule(None, Some("06:00"), None)).unwrap();
+        assert_eq!(
+            spec.last_between(at("2026-03-01T06:00:00Z"), at("2026-03-20T12:00:00Z")),
+            Some(at("2026-03-20T06:00:00Z"))
+        );
+        assert_eq!(
+            spec.last_between(at("2026-03-20T05:00:00Z"), at("2026-03-20T06:00:00Z")),
+            Some(at("2026-03-20T06:00:00Z"))
+        );
+        assert_eq!(
+            spec.last_between(at("2026-03-20T06:00:00Z"), at("2026-03-21T05:59:00Z")),
+            None
+        );
+
+        let yearly = ScheduleSpec::from_config(&schedule(Some("0 0 1 1 *"), None, None)).unwrap();
+        assert_eq!(
+            yearly.last_between(at("2020-01-01T00:00:00Z"), at("2026-03-20T00:00:00Z")),
+            Some(at("2026-01-01T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn prompts_are_rendered_in_the_schedule_time_zone() {
+        let config = schedule(None, Some("06:00"), None);
+        let spec = ScheduleSpec::from_config(&config).unwrap();
+        assert_eq!(
+            render_prompt(&config, &spec, at("2026-03-20T06:00:00Z"), Path::new("/w")),
+            "Triage triage on 2026-03-20 at 06:00"
+        );
+    }
+}
@@ -0,0 +1,531 @@
+//! Tracked new-session requests.
+//!
+//! `POST /api/v1/new-session` does not wait for the assistant: it validates
+//! a [`NewSessionRequest`], queues the work as a critical job and answers
+//! with a job ID. [`SessionJobs`] records each job's progress and keeps the
+//! result for [`RESULT_RETENTION`] after it finishes, so clients can poll
+//! `GET /api/v1/jobs/<id>` and SSE subscribers see every state change.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::config::paths::{UnsafePathError, safe_path};
+use crate::resume::{ResumeError, ResumeOutcome, SessionCreator, assistant};
+
+/// Version of the new-session request payload this daemon accepts.
+pub const NEW_SESSION_REQUEST_VERSION: u32 = 1;
+
+/// How long a finished job's result can still be fetched.
+pub const RESULT_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Finished jobs kept at most; the oldest are dropped first.
+pub const MAX_RETAINED_JOBS: usize = 256;
+
+const UPDATE_CHANNEL_CAPACITY: usize = 64;
+
+/// Body of `POST /api/v1/new-session`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewSessionRequest {
+    /// Payload version; defaults to the current one when omitted.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Prompt the new session starts with.
+    pub prompt: String,
+    /// Directory to start in, inside `monitoring.session_dir`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "NewSessionOptions::is_empty")]
+    pub options: NewSessionOptions,
+}
+
+/// Strategy options for a requested session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewSessionOptions {
+    /// Model to start the session on; fails the job if it cannot be selected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Assistant from `monitoring.assistants` to start the session with,
+    /// instead of the first configured one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assistant: Option<String>,
+}
+
+impl NewSessionOptions {
+    fn is_empty(&self) -> bool {
+        self.model.is_none() && self.assistant.is_none()
+    }
+}
+
+fn default_version() -> u32 {
+    NEW_SESSION_REQUEST_VERSION
+}
+
+/// Why a new-session request was refused before queueing.
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    #[error("Unsupported request version {version} (expected {NEW_SESSION_REQUEST_VERSION})")]
+    UnsupportedVersion { version: u32 },
+
+    #[error("Prompt must not be empty")]
+    EmptyPrompt,
+
+    #[error("Invalid workdir: {0}")]
+    Workdir(#[from] UnsafePathError),
+
+    #[error("Workdir {path} is not a directory")]
+    WorkdirMissing { path: PathBuf },
+
+    #[error("Unknown assistant {name:?}")]
+    UnknownAssistant { name: String },
+}
+
+impl RequestError {
+    /// Machine-readable code for API error responses.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnsupportedVersion { .. } => "UNSUPPORTED_VERSION",
+            Self::EmptyPrompt => "INVALID_PROMPT",
+            Self::Workdir(_) | Self::WorkdirMissing { .. } => "INVALID_WORKDIR",
+            Self::UnknownAssistant { .. } => "INVALID_OPTIONS",
+        }
+    }
+}
+
+impl NewSessionRequest {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            version: NEW_SESSION_REQUEST_VERSION,
+            prompt: prompt.into(),
+            workdir: None,
+            options: NewSessionOptions::default(),
+        }
+    }
+
+    pub fn with_workdir(mut self, workdir: impl Into<PathBuf>) -> Self {
+        self.workdir = Some(workdir.into());
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.options.model = Some(model.into());
+        self
+    }
+
+    pub fn with_assistant(mut self, assistant: impl Into<String>) -> Self {
+        self.options.assistant = Some(assistant.into());
+        self
+    }
+
+    /// Check the request against `session_dir`, returning the directory the
+    /// session starts in.
+    pub fn validate(&self, session_dir: &Path) -> Result<PathBuf, RequestError> {
+        if self.version != NEW_SESSION_REQUEST_VERSION {
+            return Err(RequestError::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        if self.prompt.trim().is_empty() {
+            return Err(RequestError::EmptyPrompt);
+        }
+        if let Some(name) = &self.options.assistant {
+            if assistant::adapter_for(name).is_none() {
+                return Err(RequestError::UnknownAssistant { name: name.clone() });
+            }
+        }
+        let workdir = match &self.workdir {
+            Some(workdir) => safe_path(workdir, session_dir, false)?,
+            None => safe_path(Path::new(""), session_dir, false)?,
+        };
+        if !workdir.is_dir() {
+            return Err(RequestError::WorkdirMissing { path: workdir });
+        }
+        Ok(workdir)
+    }
+}
+
+/// Progress of a tracked job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackedJobState {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl TrackedJobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A job as reported by `GET /api/v1/jobs/<id>` and the `job` SSE event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackedJob {
+    pub job_id: String,
+    pub kind: String,
+    pub state: TrackedJobState,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<ResumeOutcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct Registry {
+    jobs: HashMap<String, TrackedJob>,
+    retention: Duration,
+    max_retained: usize,
+}
+
+impl Registry {
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let retention = chrono::Duration::from_std(self.retention).unwrap_or(chrono::Duration::MAX);
+        self.jobs.retain(|_, job| match job.finished_at {
+            Some(finished_at) => now - finished_at < retention,
+            None => true,
+        });
+        let mut finished: Vec<(DateTime<Utc>, String)> = self
+            .jobs
+            .values()
+            .filter_map(|job| job.finished_at.map(|at| (at, job.job_id.clone())))
+            .collect();
+        if finished.len() <= self.max_retained {
+            return;
+        }
+        finished.sort();
+        let excess = finished.len() - self.max_retained;
+        for (_, id) in finished.into_iter().take(excess) {
+            self.jobs.remove(&id);
+        }
+    }
+}
+
+/// Registry of tracked jobs; clones share it.
+#[derive(Clone)]
+pub struct SessionJobs {
+    registry: Arc<Mutex<Registry>>,
+    updates: broadcast::Sender<TrackedJob>,
+}
+
+impl Default for SessionJobs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionJobs {
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            registry: Arc::new(Mutex::new(Registry {
+                jobs: HashMap::new(),
+                retention: RESULT_RETENTION,
+                max_retained: MAX_RETAINED_JOBS,
+            })),
+            updates,
+        }
+    }
+
+    /// Keep finished results for `retention` instead of [`RESULT_RETENTION`].
+    pub fn with_retention(self, retention: Duration) -> Self {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.retention = retention;
+        }
+        self
+    }
+
+    /// Keep at most `max_retained` finished results.
+    pub fn with_max_retained(self, max_retained: usize) -> Self {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.max_retained = max_retained;
+        }
+        self
+    }
+
+    /// Job state changes, for the SSE stream.
+    pub fn subscribe(&self) -> broadcast::Receiver<TrackedJob> {
+        self.updates.subscribe()
+    }
+
+    /// Record a new pending job and return its ID.
+    pub fn submit(&self, kind: &str) -> String {
+        let job = TrackedJob {
+            job_id: Uuid::new_v4().to_string(),
+            kind: kind.to_string(),
+            state: TrackedJobState::Pending,
+            submitted_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            outcome: None,
+            error: None,
+        };
+        let id = job.job_id.clone();
+        self.store(job);
+        id
+    }
+
+    pub fn start(&self, id: &str) {
+        self.update(id, |job| {
+            job.state = TrackedJobState::Running;
+            job.started_at = Some(Utc::now());
+        });
+    }
+
+    /// Record how the job ended; an `Err` fails it with that message.
+    pub fn finish(&self, id: &str, result: Result<ResumeOutcome, String>) {
+        self.update(id, |job| {
+            job.finished_at = Some(Utc::now());
+            match result {
+                Ok(outcome) if outcome.is_success() => {
+                    job.state = TrackedJobState::Succeeded;
+                    job.outcome = Some(outcome);
+                }
+                Ok(outcome) => {
+                    job.state = TrackedJobState::Failed;
+                    job.error = Some(outcome_error(&outcome));
+                    job.outcome = Some(outcome);
+                }
+                Err(message) => {
+                    job.state = TrackedJobState::Failed;
+                    job.error = Some(message);
+                }
+            }
+        });
+    }
+
+    /// The job, unless it is unknown or its result has expired.
+    pub fn get(&self, id: &str) -> Option<TrackedJob> {
+        self.get_at(id, Utc::now())
+    }
+
+    /// Wait for the job to finish; `None` when it is unknown or its result
+    /// expired before it was seen.
+    pub async fn wait(&self, id: &str) -> Option<TrackedJob> {
+        let mut updates = self.subscribe();
+        loop {
+            let job = self.get(id)?;
+            if job.state.is_finished() {
+                return Some(job);
+            }
+            match updates.recv().await {
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return self.get(id),
+            }
+        }
+    }
+
+    pub(crate) fn get_at(&self, id: &str, now: DateTime<Utc>) -> Option<TrackedJob> {
+        let mut registry = self.registry.lock().ok()?;
+        registry.prune(now);
+        registry.jobs.get(id).cloned()
+    }
+
+    fn store(&self, job: TrackedJob) {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.prune(Utc::now());
+            registry.jobs.insert(job.job_id.clone(), job.clone());
+        }
+        let _ = self.updates.send(job);
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut TrackedJob)) {
+        let updated = {
+            let Ok(mut registry) = self.registry.lock() else {
+                return;
+            };
+            let Some(job) = registry.jobs.get_mut(id) else {
+                return;
+            };
+            apply(job);
+            job.clone()
+        };
+        let _ = self.updates.send(updated);
+    }
+}
+
+fn outcome_error(outcome: &ResumeOutcome) -> String {
+    match outcome {
+        ResumeOutcome::Failure { message, .. } => message.clone(),
+        ResumeOutcome::Skipped { reason } | ResumeOutcome::Delayed { reason, .. } => reason.clone(),
+        ResumeOutcome::Success { action, .. } => action.clone(),
+    }
+}
+
+/// Start the requested session in `workdir`, as a job outcome.
+pub async fn create_session(
+    creator: &dyn SessionCreator,
+    request: &NewSessionRequest,
+    workdir: &Path,
+) -> Result<ResumeOutcome, String> {
+    let created = match &request.options.model {
+        Some(model) => {
+            creator
+                .create_with_model(&request.prompt, workdir, model)
+                .await
+        }
+        None => creator.create(&request.prompt, workdir).await,
+    };
+    created
+        .map(|session_path| ResumeOutcome::success(session_path, "new session created"))
+        .map_err(|err| match &err {
+            ResumeError::CommandFailed { stderr, .. } if !stderr.trim().is_empty() => {
+                format!("{err}: {}", stderr.trim())
+            }
+            _ => err.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn request_defaults_to_current_version() {
+        let request: NewSessionRequest = serde_json::from_str(r#"{"prompt":"go"}"#).unwrap();
+        assert_eq!(request, NewSessionRequest::new("go"));
+        assert!(serde_json::from_str::<NewSessionRequest>(r#"{"prompt":"go","extra":1}"#).is_err());
+    }
+
+    #[test]
+    fn validate_keeps_workdir_inside_session_dir() {
+        let root = tempdir().unwrap();
+        std::fs::create_dir(root.path().join("project")).unwrap();
+        let root_dir = root.path().canonicalize().unwrap();
+
+        assert_eq!(
+            NewSessionRequest::new("go").validate(root.path()).unwrap(),
+            root_dir
+        );
+        assert_eq!(
+            NewSessionRequest::new("go")
+                .with_workdir("project")
+                .validate(root.path())
+                .unwrap(),
+            root_dir.join("project")
+        );
+
+        let outside = NewSessionRequest::new("go").with_workdir("/etc");
+        assert_eq!(
+            outside.validate(root.path()).unwrap_err().code(),
+            "INVALID_WORKDIR"
+        );
+        let missing = NewSessionRequest::new("go").with_workdir("nope");
+        assert!(matches!(
+            missing.validate(root.path()),
+            Err(RequestError::WorkdirMissing { .. })
+        ));
+        assert!(matches!(
+            NewSessionRequest::new("  ").validate(root.path()),
+            Err(RequestError::EmptyPrompt)
+        ));
+        assert_eq!(
+            NewSessionRequest::new("go")
+                .with_assistant("emacs")
+                .validate(root.path())
+                .unwrap_err()
+                .code(),
+            "INVALID_OPTIONS"
+        );
+        let mut future = NewSessionRequest::new("go");
+        future.version = 2;
+        assert_eq!(
+            future.validate(root.path()).unwrap_err().code(),
+            "UNSUPPORTED_VERSION"
+        );
+    }
+
+    #[test]
+    fn jobs_move_through_states_and_broadcast() {
+        let jobs = SessionJobs::new();
+        let mut updates = jobs.subscribe();
+        let id = jobs.submit("new_session");
+        assert_eq!(jobs.get(&id).unwrap().state, TrackedJobState::Pending);
+
+        jobs.start(&id);
+        jobs.finish(&id, Err("boom".to_string()));
+
+        let states: Vec<TrackedJobState> = std::iter::from_fn(|| updates.try_recv().ok())
+            .map(|job| job.state)
+            .collect();
+        assert_eq!(
+            states,
+            [
+                TrackedJobState::Pending,
+                TrackedJobState::Running,
+                TrackedJobState::Failed
+            ]
+        );
+        let job = jobs.get(&id).unwrap();
+        assert_eq!(job.error.as_deref(), Some("boom"));
+        assert!(job.started_at.is_some() && job.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn wait_returns_the_finished_job() {
+        let jobs = SessionJobs::new();
+        let id = jobs.submit("new_session");
+        let waiter = tokio::spawn({
+            let jobs = jobs.clone();
+            let id = id.clone();
+            async move { jobs.wait(&id).await }
+        });
+        tokio::task::yield_now().await;
+        jobs.start(&id);
+        jobs.finish(&id, Err("boom".to_string()));
+
+        let job = waiter.await.unwrap().unwrap();
+        assert_eq!(job.state, TrackedJobState::Failed);
+        assert!(jobs.wait("unknown").await.is_none());
+    }
+
+    #[test]
+    fn finished_results_expire() {
+        let jobs = SessionJobs::new().with_retention(Duration::from_secs(60));
+        let done = jobs.submit("new_session");
+        jobs.finish(
+            &done,
+            Ok(ResumeOutcome::success(PathBuf::from("/s.md"), "created")),
+        );
+        let pending = jobs.submit("new_session");
+
+        let later = Utc::now() + chrono::Duration::seconds(61);
+        assert!(jobs.get_at(&done, later).is_none());
+        assert!(jobs.get_at(&pending, later).is_some());
+    }
+
+    #[test]
+    fn oldest_results_are_dropped_past_the_cap() {
+        let jobs = SessionJobs::new().with_max_retained(1);
+        let first = jobs.submit("new_session");
+        jobs.finish(&first, Err("first".to_string()));
+        let second = jobs.submit("new_session");
+        jobs.finish(&second, Err("second".to_string()));
+
+        assert!(jobs.get(&first).is_none());
+        assert!(jobs.get(&second).is_some());
+    }
+}
@@ -18,6 +18,15 @@ impl ShutdownCoordinator {
         }
     }
 
+    /// Cancel `cancel` on shutdown, for a token handed out before the
+    /// coordinator existed.
+    pub fn with_token(cancel: CancellationToken) -> Self {
+        Self {
+            cancel,
+            tasks: Vec::new(),
+        }
+    }
+
     pub fn cancel_token(&self) -> CancellationToken {
         self.cancel.clone()
     }
@@ -100,6 +109,16 @@ mod tests {
         assert!(progress.load(Ordering::SeqCst) > 0);
     }
 
+    #[tokio::test]
+    async fn test_shutdown_cancels_shared_token() {
+        let shared = CancellationToken::new();
+        let coordinator = ShutdownCoordinator::with_token(shared.clone());
+
+        let result = coordinator.shutdown().await;
+        assert!(matches!(result, ShutdownResult::Graceful));
+        assert!(shared.is_cancelled());
+    }
+
     #[tokio::test(start_paused = true)]
     async fn test_shutdown_timeout_aborts_tasks() {
         let mut coordinator = ShutdownCoordinator::new();
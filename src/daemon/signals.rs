@@ -4,6 +4,9 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
+#[cfg(unix)]
+use crate::daemon::last_exit::{ExitCause, note_shutdown_cause};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DaemonSignal {
     Shutdown,
@@ -42,12 +45,14 @@ pub async fn listen_for_signals(tx: mpsc::Sender<DaemonSignal>, cancel: Cancella
             tokio::select! {
                 _ = sigterm.recv() => {
                     info!("Received SIGTERM; initiating shutdown");
+                    note_shutdown_cause(ExitCause::Signal, "SIGTERM");
                     let _ = tx.send(DaemonSignal::Shutdown).await;
                     cancel.cancel();
                     break;
                 }
                 _ = sigint.recv() => {
                     info!("Received SIGINT; initiating shutdown");
+                    note_shutdown_cause(ExitCause::Signal, "SIGINT");
                     let _ = tx.send(DaemonSignal::Shutdown).await;
                     cancel.cancel();
                     break;
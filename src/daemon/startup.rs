@@ -0,0 +1,202 @@
+//! Staged daemon startup.
+//!
+//! The IPC socket, event loop and watchers come up first so `status` and
+//! detection work right away. The heavier subsystems (metrics registry,
+//! stop-reason classifier, notification channels, HTTP API) then initialize
+//! concurrently in the background. [`Startup`] tracks when each one became
+//! ready so work that needs it can wait, and logs "Startup complete" with
+//! every init duration once all of them are.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+use crate::telemetry::Metrics;
+
+/// A subsystem initialized in the background after the watchers start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Subsystem {
+    Metrics,
+    Classifier,
+    Notifications,
+    Http,
+}
+
+impl Subsystem {
+    pub const ALL: [Self; 4] = [
+        Self::Metrics,
+        Self::Classifier,
+        Self::Notifications,
+        Self::Http,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Metrics => "metrics",
+            Self::Classifier => "classifier",
+            Self::Notifications => "notifications",
+            Self::Http => "http",
+        }
+    }
+}
+
+impl std::fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Readiness of the background subsystems. Clones share it.
+#[derive(Clone)]
+pub struct Startup {
+    began: Instant,
+    ready: Arc<watch::Sender<BTreeMap<Subsystem, Duration>>>,
+}
+
+impl Startup {
+    pub fn new() -> Self {
+        Self {
+            began: Instant::now(),
+            ready: Arc::new(watch::Sender::new(BTreeMap::new())),
+        }
+    }
+
+    /// A tracker with every subsystem already ready, for event loops and
+    /// servers run outside the daemon.
+    pub fn ready() -> Self {
+        let startup = Self::new();
+        for subsystem in Subsystem::ALL {
+            startup.mark_ready(subsystem, Duration::ZERO);
+        }
+        startup
+    }
+
+    pub fn is_ready(&self, subsystem: Subsystem) -> bool {
+        self.ready.borrow().contains_key(&subsystem)
+    }
+
+    /// Whether every subsystem has finished initializing.
+    pub fn is_complete(&self) -> bool {
+        self.ready.borrow().len() == Subsystem::ALL.len()
+    }
+
+    /// Subsystems still initializing.
+    pub fn pending(&self) -> Vec<Subsystem> {
+        let ready = self.ready.borrow();
+        Subsystem::ALL
+            .into_iter()
+            .filter(|subsystem| !ready.contains_key(subsystem))
+            .collect()
+    }
+
+    /// Wait until `subsystem` is ready.
+    pub async fn wait_ready(&self, subsystem: Subsystem) {
+        let mut ready = self.ready.subscribe();
+        // The sender lives as long as `self`, so this cannot fail.
+        let _ = ready.wait_for(|ready| ready.contains_key(&subsystem)).await;
+    }
+
+    /// Run `init`, then mark `subsystem` ready with the time it took.
+    pub async fn init<F: Future>(&self, subsystem: Subsystem, init: F) -> F::Output {
+        let started = Instant::now();
+        let output = init.await;
+        self.mark_ready(subsystem, started.elapsed());
+        output
+    }
+
+    pub fn mark_ready(&self, subsystem: Subsystem, took: Duration) {
+        let newly_ready = self.ready.send_if_modified(|ready| {
+            if ready.contains_key(&subsystem) {
+                return false;
+            }
+            ready.insert(subsystem, took);
+            true
+        });
+        if newly_ready {
+            debug!(
+                subsystem = subsystem.as_str(),
+                took_ms = took.as_millis() as u64,
+                "Subsystem ready"
+            );
+        }
+    }
+
+    /// Init duration of each ready subsystem.
+    pub fn durations(&self) -> BTreeMap<Subsystem, Duration> {
+        self.ready.borrow().clone()
+    }
+
+    /// Log "Startup complete" with each subsystem's init duration and record
+    /// them in the metrics registry.
+    pub fn report(&self) {
+        let durations = self.durations();
+        if let Some(metrics) = Metrics::global() {
+            for (subsystem, took) in &durations {
+                metrics.record_startup(subsystem.as_str(), *took);
+            }
+        }
+        let ms = |subsystem: Subsystem| {
+            durations
+                .get(&subsystem)
+                .map_or(0, |took| took.as_millis() as u64)
+        };
+        info!(
+            total_ms = self.began.elapsed().as_millis() as u64,
+            metrics_ms = ms(Subsystem::Metrics),
+            classifier_ms = ms(Subsystem::Classifier),
+            notifications_ms = ms(Subsystem::Notifications),
+            http_ms = ms(Subsystem::Http),
+            "Startup complete"
+        );
+    }
+}
+
+impl Default for Startup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tracks_readiness_and_durations() {
+        let startup = Startup::new();
+        assert!(!startup.is_ready(Subsystem::Classifier));
+        assert_eq!(startup.pending(), Subsystem::ALL.to_vec());
+
+        let waiter = {
+            let startup = startup.clone();
+            tokio::spawn(async move { startup.wait_ready(Subsystem::Classifier).await })
+        };
+        let value = startup
+            .init(Subsystem::Classifier, async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                7
+            })
+            .await;
+        assert_eq!(value, 7);
+        waiter.await.unwrap();
+
+        assert!(startup.is_ready(Subsystem::Classifier));
+        assert!(startup.durations()[&Subsystem::Classifier] >= Duration::from_millis(20));
+        assert!(!startup.is_complete());
+
+        // A second report for the same subsystem keeps the first duration.
+        startup.mark_ready(Subsystem::Classifier, Duration::ZERO);
+        assert!(startup.durations()[&Subsystem::Classifier] >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn ready_tracker_has_nothing_pending() {
+        let startup = Startup::ready();
+        assert!(startup.is_complete());
+        assert!(startup.pending().is_empty());
+    }
+}
@@ -1,24 +1,89 @@
-use std::sync::RwLock;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
 use crate::config::Paths;
-use crate::config::schema::Config;
+use crate::config::schema::{Config, DaemonMode, NewSessionResumeConfig, WorkspaceMode};
 use crate::config::validation::validate_config;
-use crate::ipc::protocol::DaemonStatus;
+use crate::daemon::control::ControlQueue;
+use crate::daemon::events::{
+    CONTROL_CHANNEL_CAPACITY, ControlEvent, ControlReceiver, ControlSender,
+};
+use crate::daemon::handoff::{HandoffFile, RuntimeSnapshot};
+use crate::daemon::initiator::Initiator;
+use crate::daemon::jobs::{JobLimits, JobPriority, JobQueue, JobStatus};
+use crate::daemon::restart_correlation::RestartCorrelationStatus;
+use crate::daemon::schedules::Scheduler;
+use crate::daemon::session_jobs::{self, NewSessionRequest, SessionJobs};
+use crate::ipc::protocol::{
+    DaemonStatus, HttpListenerStatus, OpenCodeEndpointStatus, ResumeCounter, WatchFilterStatus,
+};
 use crate::ipc::socket::DaemonStateAccess;
+use crate::monitor::classifier::StopReasonClassifier;
+use crate::monitor::clock_skew::ClockSkew;
+use crate::monitor::debounce;
 use crate::monitor::detection::detect_assistants;
-use crate::state::StateStore;
+use crate::monitor::filter::{SharedWatchFilter, WatchFilter};
+use crate::monitor::scan::DirCache;
+use crate::notify::ack::{AckError, AckIncident, AckRegistry, record_acknowledgment};
+use crate::notify::{CredentialRegistry, NotificationMutes};
+#[cfg(feature = "opencode-api")]
+use crate::opencode::OpenCodeClient;
+use crate::opencode::SharedEndpoint;
+#[cfg(feature = "opencode-api")]
+use crate::resume::ApiSessionCreator;
+use crate::resume::next_step::DEFAULT_MAX_NEXT_STEP_BYTES;
+use crate::resume::postmortem::DEFAULT_MAX_BUNDLES;
+use crate::resume::{
+    AdapterSessionCreator, BackupConfig, DEFAULT_QUOTA_RETRY_AFTER, GateStatus, GitCollector,
+    LeaseManager, MaintenanceSchedule, ModeSwitch, NewSessionConfig, OpenCodeAdapter,
+    QuotaSchedule, QuotaWait, RESUME_WINDOW, ResumeGates, ResumeRateLimit, SessionCreator,
+    SessionExclusions, StrategySelector, WorktreeManager, assistant,
+};
+use crate::state::{AuditLogger, MuteKind, NotificationMute, ScheduleRun, StateFile, StateHandle};
+use crate::telemetry::{LogBuffer, Metrics};
+use crate::util::path_display::{self, PathDisplay};
+use crate::util::{content, duration};
 
 pub struct DaemonState {
     start_time: Instant,
     paused: AtomicBool,
     sessions_count: AtomicU64,
     resumes_count: AtomicU64,
+    mode: ModeSwitch,
+    gate_status: GateStatus,
+    quota_schedule: QuotaSchedule,
     config: RwLock<Config>,
     auto_detect_active: AtomicBool,
+    opencode_endpoint: SharedEndpoint,
+    watch_filter: SharedWatchFilter,
+    session_dir_cache: Arc<DirCache>,
+    jobs: JobQueue,
+    session_jobs: SessionJobs,
+    session_creator: RwLock<Option<Arc<dyn SessionCreator>>>,
+    scheduler: RwLock<Option<Arc<Scheduler>>>,
+    control: ControlQueue,
+    control_tx: Mutex<Option<ControlSender>>,
+    handoff_file: HandoffFile,
+    restart_correlation: Mutex<Option<RestartCorrelationStatus>>,
+    http_listeners: Mutex<Option<HttpListenerStatus>>,
+    watcher_lost: AtomicBool,
+    reloadable: Mutex<Vec<Arc<dyn ReloadableService>>>,
+    shutdown: CancellationToken,
+}
+
+/// A daemon-owned service that reconfigures itself when the config is reloaded.
+#[async_trait]
+pub trait ReloadableService: Send + Sync {
+    /// Bring the service in line with `config`, describing any change made
+    /// (e.g. `http: restarted on 127.0.0.1:8080`).
+    async fn apply(&self, config: &Config) -> Option<String>;
 }
 
 impl DaemonState {
@@ -28,13 +93,34 @@ impl DaemonState {
             Config::default()
         });
         let auto_detect_active = apply_auto_detection(&mut config);
+        let watch_filter = SharedWatchFilter::new(WatchFilter::from_config(&config.monitoring));
+        let control = ControlQueue::new();
+        let jobs = JobQueue::with_control(JobLimits::from_config(&config.daemon), control.clone());
         Self {
             start_time: Instant::now(),
             paused: AtomicBool::new(false),
             sessions_count: AtomicU64::new(0),
             resumes_count: AtomicU64::new(0),
+            mode: ModeSwitch::new(config.daemon.mode),
+            gate_status: GateStatus::new(),
+            quota_schedule: QuotaSchedule::new(),
             config: RwLock::new(config),
             auto_detect_active: AtomicBool::new(auto_detect_active),
+            opencode_endpoint: SharedEndpoint::new(),
+            watch_filter,
+            session_dir_cache: Arc::new(DirCache::new()),
+            jobs,
+            session_jobs: SessionJobs::new(),
+            session_creator: RwLock::new(None),
+            scheduler: RwLock::new(None),
+            control,
+            control_tx: Mutex::new(None),
+            handoff_file: HandoffFile::new(),
+            restart_correlation: Mutex::new(None),
+            http_listeners: Mutex::new(None),
+            watcher_lost: AtomicBool::new(false),
+            reloadable: Mutex::new(Vec::new()),
+            shutdown: CancellationToken::new(),
         }
     }
 
@@ -43,14 +129,93 @@ impl DaemonState {
             warn!(error = %err, "Failed to load config; using defaults");
             Config::default()
         });
+        let watch_filter = SharedWatchFilter::new(WatchFilter::from_config(&config.monitoring));
+        let control = ControlQueue::new();
+        let jobs = JobQueue::with_control(JobLimits::from_config(&config.daemon), control.clone());
         Self {
             start_time: Instant::now(),
             paused: AtomicBool::new(false),
             sessions_count: AtomicU64::new(0),
             resumes_count: AtomicU64::new(0),
+            mode: ModeSwitch::new(config.daemon.mode),
+            gate_status: GateStatus::new(),
+            quota_schedule: QuotaSchedule::new(),
             config: RwLock::new(config),
             auto_detect_active: AtomicBool::new(false),
+            opencode_endpoint: SharedEndpoint::new(),
+            watch_filter,
+            session_dir_cache: Arc::new(DirCache::new()),
+            jobs,
+            session_jobs: SessionJobs::new(),
+            session_creator: RwLock::new(None),
+            scheduler: RwLock::new(None),
+            control,
+            control_tx: Mutex::new(None),
+            handoff_file: HandoffFile::new(),
+            restart_correlation: Mutex::new(None),
+            http_listeners: Mutex::new(None),
+            watcher_lost: AtomicBool::new(false),
+            reloadable: Mutex::new(Vec::new()),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Have `service` follow config reloads (see [`DaemonStateAccess::reload`]).
+    pub fn register_reloadable(&self, service: Arc<dyn ReloadableService>) {
+        if let Ok(mut services) = self.reloadable.lock() {
+            services.push(service);
+        }
+    }
+
+    /// Bring registered services in line with the current config, returning
+    /// what changed. The second half of [`DaemonStateAccess::reload`].
+    pub async fn reconfigure_services(&self) -> Vec<String> {
+        let config = self.config_snapshot();
+        let services = self
+            .reloadable
+            .lock()
+            .map(|services| services.clone())
+            .unwrap_or_default();
+        let mut changes = Vec::new();
+        for service in services {
+            if let Some(change) = service.apply(&config).await {
+                info!(change = %change, "Service reconfigured on reload");
+                changes.push(change);
+            }
+        }
+        changes
+    }
+
+    /// Use a custom handoff file location (for testing).
+    pub fn with_handoff_file(mut self, handoff_file: HandoffFile) -> Self {
+        self.handoff_file = handoff_file;
+        self
+    }
+
+    /// Use `config` instead of the file on disk (for testing).
+    pub fn with_config(self, config: Config) -> Self {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
         }
+        self
+    }
+
+    /// In-memory state worth carrying across `daemon restart`.
+    pub fn runtime_snapshot(&self) -> RuntimeSnapshot {
+        RuntimeSnapshot::new(
+            self.is_paused(),
+            self.sessions_count.load(Ordering::SeqCst),
+            self.resumes_count.load(Ordering::SeqCst),
+        )
+    }
+
+    /// Apply a snapshot written by the previous daemon.
+    pub fn restore_runtime(&self, snapshot: &RuntimeSnapshot) {
+        self.paused.store(snapshot.paused, Ordering::SeqCst);
+        self.sessions_count
+            .store(snapshot.sessions_count, Ordering::SeqCst);
+        self.resumes_count
+            .store(snapshot.resumes_count, Ordering::SeqCst);
     }
 
     pub fn uptime(&self) -> Duration {
@@ -61,6 +226,15 @@ impl DaemonState {
         self.paused.load(Ordering::SeqCst)
     }
 
+    /// `paused` or `monitoring`, as reported by STATUS.
+    pub fn state_label(&self) -> &'static str {
+        if self.is_paused() {
+            "paused"
+        } else {
+            "monitoring"
+        }
+    }
+
     pub fn daemon_config(&self) -> Option<crate::config::schema::DaemonConfig> {
         match self.config.read() {
             Ok(guard) => Some(guard.daemon.clone()),
@@ -89,12 +263,356 @@ impl DaemonState {
         }
     }
 
+    pub fn notifications_config(&self) -> Option<crate::config::schema::NotificationsConfig> {
+        match self.config.read() {
+            Ok(guard) => Some(guard.notifications.clone()),
+            Err(_) => None,
+        }
+    }
+
     pub fn opencode_config(&self) -> Option<crate::config::schema::OpenCodeConfig> {
         match self.config.read() {
             Ok(guard) => Some(guard.opencode.clone()),
             Err(_) => None,
         }
     }
+
+    /// Published by the core loop whenever the crash correlation changes.
+    pub fn set_restart_correlation(&self, status: Option<RestartCorrelationStatus>) {
+        if let Ok(mut current) = self.restart_correlation.lock() {
+            *current = status;
+        }
+    }
+
+    /// Published by the HTTP supervisor as its listeners start and stop.
+    pub fn set_http_listeners(&self, listeners: Option<HttpListenerStatus>) {
+        if let Ok(mut current) = self.http_listeners.lock() {
+            *current = listeners;
+        }
+    }
+
+    /// Called by the core loop when a session event source stops delivering.
+    pub fn mark_watcher_lost(&self) {
+        self.watcher_lost.store(true, Ordering::SeqCst);
+    }
+
+    /// Watcher event filter; `RELOAD` swaps its rules in place.
+    pub fn watch_filter(&self) -> SharedWatchFilter {
+        self.watch_filter.clone()
+    }
+
+    /// Session directory listings shared by session listings and the monitor.
+    pub fn session_dir_cache(&self) -> Arc<DirCache> {
+        Arc::clone(&self.session_dir_cache)
+    }
+
+    /// Queue for daemon work outside the event loop.
+    pub fn jobs(&self) -> JobQueue {
+        self.jobs.clone()
+    }
+
+    /// New-session requests submitted over HTTP and their results.
+    pub fn session_jobs(&self) -> SessionJobs {
+        self.session_jobs.clone()
+    }
+
+    /// Start requested sessions with `creator` instead of the configured
+    /// assistant (for testing).
+    pub fn set_session_creator(&self, creator: Arc<dyn SessionCreator>) {
+        if let Ok(mut current) = self.session_creator.write() {
+            *current = Some(creator);
+        }
+    }
+
+    /// Fire `[[schedules]]` entries run by hand through `scheduler`.
+    pub fn set_scheduler(&self, scheduler: Arc<Scheduler>) {
+        if let Ok(mut current) = self.scheduler.write() {
+            *current = Some(scheduler);
+        }
+    }
+
+    /// Queue `request` (already validated to start in `workdir`) as a
+    /// critical job, returning the tracked job ID.
+    pub fn submit_new_session_job(&self, request: NewSessionRequest, workdir: PathBuf) -> String {
+        self.submit_session_job("new_session", request, workdir)
+    }
+
+    /// [`Self::submit_new_session_job`], tracked and queued as a `kind` job
+    /// (e.g. `scheduled_session`).
+    pub fn submit_session_job(
+        &self,
+        kind: &str,
+        request: NewSessionRequest,
+        workdir: PathBuf,
+    ) -> String {
+        let id = self.session_jobs.submit(kind);
+        let creator = self.session_creator_for(&request);
+        let tracked = self.session_jobs.clone();
+        let job_id = id.clone();
+        self.jobs.enqueue(kind, JobPriority::Critical, async move {
+            tracked.start(&job_id);
+            let result = session_jobs::create_session(&*creator, &request, &workdir).await;
+            if let Err(err) = &result {
+                warn!(job = %job_id, error = %err, "Requested session failed to start");
+            }
+            tracked.finish(&job_id, result);
+        });
+        id
+    }
+
+    /// Creator for a requested session: the OpenCode API when a model is
+    /// pinned and available (and no other assistant is asked for), otherwise
+    /// the requested or first configured assistant's command.
+    fn session_creator_for(&self, request: &NewSessionRequest) -> Arc<dyn SessionCreator> {
+        if let Some(creator) = self
+            .session_creator
+            .read()
+            .ok()
+            .and_then(|current| current.clone())
+        {
+            return creator;
+        }
+        let requested = request.options.assistant.as_deref();
+        #[cfg(feature = "opencode-api")]
+        if request.options.model.is_some()
+            && requested.is_none_or(|name| name == assistant::OPENCODE)
+        {
+            if let Some(opencode) = self.opencode_config() {
+                return Arc::new(ApiSessionCreator::new(
+                    OpenCodeClient::new(&opencode)
+                        .with_endpoint(self.opencode_endpoint())
+                        .with_cancellation(self.shutdown_token()),
+                ));
+            }
+        }
+        if let Some(adapter) = requested.and_then(assistant::adapter_for) {
+            return Arc::new(AdapterSessionCreator::new(adapter));
+        }
+        let adapter = self
+            .monitoring_config()
+            .and_then(|monitoring| {
+                monitoring
+                    .assistants
+                    .iter()
+                    .find_map(|name| assistant::adapter_for(name))
+            })
+            .unwrap_or_else(|| Arc::new(OpenCodeAdapter::new()));
+        Arc::new(AdapterSessionCreator::new(adapter))
+    }
+
+    /// Holds state-changing control commands until pipeline safe points.
+    pub fn control(&self) -> ControlQueue {
+        self.control.clone()
+    }
+
+    /// Strategy selector built from the current config.
+    ///
+    /// Built per stop rather than cached, so `resume.exclude_sessions`
+    /// changes apply as soon as a reload succeeds.
+    pub fn strategy_selector(&self) -> StrategySelector {
+        let (
+            exclusions,
+            enforce_model,
+            capture_git,
+            max_next_step_bytes,
+            max_failure_bundles,
+            new_session,
+            assistants,
+            backup,
+            custom_strategies,
+        ) = match self.config.read() {
+            Ok(guard) => (
+                SessionExclusions::from_config(&guard.resume),
+                guard.resume.enforce_model,
+                guard.resume.capture_git_context,
+                guard.resume.max_next_step_bytes,
+                guard.resume.failure_bundle_count as usize,
+                guard.resume.new_session.clone(),
+                guard.monitoring.assistants.clone(),
+                BackupConfig::from_resume_config(&guard.resume, &guard.monitoring),
+                guard.resume.custom_strategies.clone(),
+            ),
+            Err(_) => (
+                SessionExclusions::default(),
+                false,
+                false,
+                DEFAULT_MAX_NEXT_STEP_BYTES,
+                DEFAULT_MAX_BUNDLES,
+                NewSessionResumeConfig::default(),
+                Vec::new(),
+                BackupConfig::default(),
+                Vec::new(),
+            ),
+        };
+        let mut selector = StrategySelector::new()
+            .with_mode(self.mode.clone())
+            .with_cancellation(self.shutdown_token())
+            .with_audit(AuditLogger::new(&Paths::state_dir()))
+            .with_exclusions(exclusions)
+            .with_maintenance(self.maintenance_schedule())
+            .with_gates(self.resume_gates(), self.gate_status.clone())
+            .with_rate_limit(self.resume_rate_limit())
+            .with_quota(self.quota_retry_after(), self.quota_schedule.clone())
+            .with_assistants(&assistants)
+            .with_custom_strategy_configs(&custom_strategies)
+            .with_new_session_config(NewSessionConfig {
+                enforce_model,
+                max_next_step_bytes,
+                max_failure_bundles,
+                max_backups: backup.max_backups,
+                follow_symlinks: backup.follow_symlinks,
+                session_root: backup.session_root,
+                backup_dir: backup.backup_dir,
+                backup_filename_template: backup.filename_template,
+                backup_dedupe: backup.dedupe,
+                context: new_session.context.clone(),
+                ..NewSessionConfig::default()
+            });
+        if capture_git {
+            selector = selector.with_git_collector(GitCollector::new());
+        }
+        if let Some(lease) = self.cross_host_lease() {
+            selector = selector.with_cross_host_lease(lease);
+        }
+        if new_session.workspace_mode == WorkspaceMode::Worktree {
+            selector = selector.with_worktree_manager(WorktreeManager::from_config(&new_session));
+        }
+        #[cfg(feature = "opencode-api")]
+        if let Some(opencode) = self.opencode_config().filter(|_| enforce_model) {
+            return selector.with_opencode_client(
+                OpenCodeClient::new(&opencode)
+                    .with_endpoint(self.opencode_endpoint())
+                    .with_cancellation(self.shutdown_token()),
+            );
+        }
+        selector
+    }
+
+    /// Whether stops are acted on or only reported (`daemon.mode`, `SET_MODE`).
+    pub fn mode(&self) -> DaemonMode {
+        self.mode.get()
+    }
+
+    /// Start in `mode` regardless of `daemon.mode` (`daemon start --observe`).
+    pub fn override_mode(&self, mode: DaemonMode) {
+        self.mode.set(mode);
+    }
+
+    /// Copy of the active configuration.
+    pub fn config_snapshot(&self) -> Config {
+        self.config
+            .read()
+            .map(|guard| guard.clone())
+            .unwrap_or_default()
+    }
+
+    /// Parsed `resume.maintenance_windows`.
+    pub fn maintenance_schedule(&self) -> MaintenanceSchedule {
+        self.config
+            .read()
+            .map(|guard| MaintenanceSchedule::from_config(&guard.resume.maintenance_windows))
+            .unwrap_or_default()
+    }
+
+    /// Parsed `[resume.gates]`.
+    pub fn resume_gates(&self) -> ResumeGates {
+        self.config
+            .read()
+            .map(|guard| ResumeGates::from_config(&guard.resume.gates))
+            .unwrap_or_default()
+    }
+
+    /// Lease manager for coordinating resumes with daemons on other hosts,
+    /// or `None` unless `resume.cross_host_lease` is on.
+    pub fn cross_host_lease(&self) -> Option<LeaseManager> {
+        self.config
+            .read()
+            .ok()
+            .filter(|guard| guard.resume.cross_host_lease)
+            .map(|guard| LeaseManager::from_config(&guard.resume))
+    }
+
+    /// Per-session resume spacing and hourly cap from `[resume]`.
+    pub fn resume_rate_limit(&self) -> ResumeRateLimit {
+        self.config
+            .read()
+            .map(|guard| ResumeRateLimit::from_config(&guard.resume))
+            .unwrap_or_default()
+    }
+
+    /// Fallback wait for quota resumes (`resume.quota_retry_after_hours`).
+    pub fn quota_retry_after(&self) -> Duration {
+        self.config
+            .read()
+            .map(|guard| QuotaWait::retry_after_from_config(&guard.resume))
+            .unwrap_or(DEFAULT_QUOTA_RETRY_AFTER)
+    }
+
+    /// Endpoint resolved by the OpenCode monitor (shared with API clients).
+    pub fn opencode_endpoint(&self) -> SharedEndpoint {
+        self.opencode_endpoint.clone()
+    }
+
+    /// Cancelled when the daemon begins shutting down, so outgoing requests
+    /// can be abandoned instead of delaying it.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Subscribe the daemon core loop to applied control requests.
+    ///
+    /// Only one subscriber is kept; a later call replaces the earlier one.
+    pub fn control_events(&self) -> ControlReceiver {
+        let (tx, rx) = tokio::sync::mpsc::channel(CONTROL_CHANNEL_CAPACITY);
+        if let Ok(mut guard) = self.control_tx.lock() {
+            *guard = Some(tx);
+        }
+        rx
+    }
+
+    fn notify_control(&self, event: ControlEvent) {
+        let Ok(guard) = self.control_tx.lock() else {
+            return;
+        };
+        if let Some(tx) = guard.as_ref() {
+            // The loop re-reads state on every control event, so a dropped
+            // notification under backlog is caught up by the next one.
+            if let Err(err) = tx.try_send(event) {
+                tracing::debug!(error = %err, "Control event not delivered to core loop");
+            }
+        }
+    }
+
+    fn watch_filter_status(&self) -> WatchFilterStatus {
+        let filter = self.watch_filter.snapshot();
+        WatchFilterStatus {
+            include_extensions: filter.include_extensions().to_vec(),
+            ignore_patterns: filter.ignore_patterns(),
+            events_filtered: self.watch_filter.filtered_count(),
+        }
+    }
+
+    fn opencode_endpoint_status(&self) -> Option<OpenCodeEndpointStatus> {
+        if let Some(endpoint) = self.opencode_endpoint.get() {
+            return Some(OpenCodeEndpointStatus {
+                url: Some(endpoint.base_url()),
+                source: Some(endpoint.source.as_str().to_string()),
+                pid: endpoint.pid,
+                started_at: endpoint.started_at.map(DateTime::<Utc>::from),
+                error: None,
+            });
+        }
+
+        self.opencode_endpoint
+            .last_error()
+            .map(|error| OpenCodeEndpointStatus {
+                url: None,
+                source: None,
+                pid: None,
+                started_at: None,
+                error: Some(error),
+            })
+    }
 }
 
 impl Default for DaemonState {
@@ -103,42 +621,106 @@ impl Default for DaemonState {
     }
 }
 
+#[async_trait]
 impl DaemonStateAccess for DaemonState {
     fn get_status(&self) -> DaemonStatus {
-        let stats = StateStore::new().load().stats;
+        let state = StateHandle::read_state();
+        let stats = state.stats.clone();
         DaemonStatus {
-            state: if self.paused.load(Ordering::SeqCst) {
-                "paused".to_string()
-            } else {
-                "monitoring".to_string()
-            },
+            state: self.state_label().to_string(),
             uptime_secs: self.uptime().as_secs(),
             current_session: None,
             saves_count: stats.saves_count,
             total_resumes: self.resumes_count.load(Ordering::SeqCst),
             time_saved_seconds: stats.time_saved_seconds,
             time_saved_human: Some(format_time_saved(stats.time_saved_seconds)),
+            opencode_endpoint: self.opencode_endpoint_status(),
+            exclusion_patterns: self.strategy_selector().exclusions().patterns(),
+            watch_filter: Some(self.watch_filter_status()),
+            jobs: self.jobs.snapshot(),
+            deferred_until: self.maintenance_schedule().active_until(Utc::now()),
+            deferred_reason: self.gate_status.reason(),
+            quota_resume_at: self.quota_schedule.next(),
+            resume_counters: resume_counters(&state, self.resume_rate_limit(), Utc::now()),
+            needs_attention: state
+                .current_session
+                .as_ref()
+                .is_some_and(|session| !session.status.is_active()),
+            instance: Paths::instance(),
+            clock_skew_secs: ClockSkew::shared().estimate(),
+            debounce_window_ms: debounce::effective_window()
+                .map(|window| window.as_millis() as u64),
+            opencode_version: state.opencode_version.clone(),
+            restart_correlation: self
+                .restart_correlation
+                .lock()
+                .ok()
+                .and_then(|status| status.clone()),
+            mode: self.mode(),
+            incident_opened_at: AckRegistry::global()
+                .and_then(|registry| registry.oldest_open(Utc::now())),
+            state_unsaved_since: StateHandle::global().and_then(|handle| handle.unsaved_since()),
+            state_newer_version: StateHandle::global().and_then(|handle| handle.newer_version()),
+            watcher_alive: Some(!self.watcher_lost.load(Ordering::SeqCst)),
+            http_listeners: self
+                .http_listeners
+                .lock()
+                .ok()
+                .and_then(|listeners| listeners.clone()),
         }
     }
 
     fn pause(&self) -> Result<(), String> {
+        self.pause_by(&Initiator::internal("daemon"))
+    }
+
+    fn pause_by(&self, initiator: &Initiator) -> Result<(), String> {
         if self.paused.swap(true, Ordering::SeqCst) {
             return Err("Daemon already paused".to_string());
         }
+        info!(by = %initiator, "Daemon paused");
+        self.notify_control(ControlEvent::Paused(initiator.clone()));
         Ok(())
     }
 
     fn resume(&self) -> Result<(), String> {
+        self.resume_by(&Initiator::internal("daemon"))
+    }
+
+    fn resume_by(&self, initiator: &Initiator) -> Result<(), String> {
         let was_paused = self.paused.swap(false, Ordering::SeqCst);
         if !was_paused {
             return Err("Daemon is not paused".to_string());
         }
         self.resumes_count.fetch_add(1, Ordering::SeqCst);
+        info!(by = %initiator, "Daemon resumed");
+        self.notify_control(ControlEvent::Resumed(initiator.clone()));
         Ok(())
     }
 
     fn new_session(&self) -> Result<(), String> {
+        self.new_session_by(&Initiator::internal("daemon"))
+    }
+
+    fn new_session_by(&self, initiator: &Initiator) -> Result<(), String> {
+        let limit = self.resume_rate_limit();
+        if let Some(message) = limit.manual_trigger_error(&StateHandle::read_state(), Utc::now()) {
+            return Err(message);
+        }
+        self.force_new_session_by(initiator)
+    }
+
+    fn force_new_session(&self) -> Result<(), String> {
+        self.force_new_session_by(&Initiator::internal("daemon"))
+    }
+
+    fn force_new_session_by(&self, initiator: &Initiator) -> Result<(), String> {
         self.sessions_count.fetch_add(1, Ordering::SeqCst);
+        if let Some(metrics) = Metrics::global() {
+            metrics.end_all_retry_cycles();
+        }
+        info!(by = %initiator, "New session requested");
+        self.notify_control(ControlEvent::NewSession(initiator.clone()));
         Ok(())
     }
 
@@ -174,8 +756,32 @@ impl DaemonStateAccess for DaemonState {
 
         log_non_reloadable_changes(&current_config, &new_config);
 
+        if current_config.classifier != new_config.classifier {
+            if let Err(err) = StopReasonClassifier::apply_config(&new_config.classifier) {
+                error!(error = %err, "Invalid classifier config, keeping current");
+                return Err(format!("Classifier configuration failed: {err}"));
+            }
+            info!("Classifier configuration reloaded");
+        }
+
+        if current_config.daemon.mode != new_config.daemon.mode {
+            self.set_mode_by(
+                new_config.daemon.mode,
+                &Initiator::internal("config_reload"),
+            )?;
+        }
+        content::set_retained_chars(new_config.daemon.max_retained_content_chars);
+        path_display::set_global(PathDisplay::from_config(&new_config));
+
         let mut new_config = new_config;
         let auto_detect_active = apply_auto_detection(&mut new_config);
+        self.watch_filter
+            .replace(WatchFilter::from_config(&new_config.monitoring));
+        self.jobs
+            .set_limits(JobLimits::from_config(&new_config.daemon));
+        if current_config.notifications != new_config.notifications {
+            CredentialRegistry::global().register_configured(&new_config.notifications);
+        }
 
         let mut guard = self
             .config
@@ -184,17 +790,154 @@ impl DaemonStateAccess for DaemonState {
         *guard = new_config;
         self.auto_detect_active
             .store(auto_detect_active, Ordering::SeqCst);
+        drop(guard);
 
         info!("Configuration reloaded");
+        self.notify_control(ControlEvent::ConfigReloaded);
         Ok(())
     }
+
+    async fn reload(&self) -> Result<Vec<String>, String> {
+        self.reload_config()?;
+        Ok(self.reconfigure_services().await)
+    }
+
+    fn handoff(&self) -> Result<(), String> {
+        let snapshot = self.runtime_snapshot();
+        self.handoff_file
+            .write(&snapshot)
+            .map_err(|err| err.to_string())?;
+        info!(
+            path = %self.handoff_file.path().display(),
+            restored = %snapshot.describe(),
+            "Wrote restart handoff snapshot"
+        );
+        self.notify_control(ControlEvent::HandoffWritten);
+        Ok(())
+    }
+
+    fn control_queue(&self) -> Option<ControlQueue> {
+        Some(self.control.clone())
+    }
+
+    fn set_mode(&self, mode: DaemonMode) -> Result<(), String> {
+        self.set_mode_by(mode, &Initiator::internal("daemon"))
+    }
+
+    fn set_mode_by(&self, mode: DaemonMode, initiator: &Initiator) -> Result<(), String> {
+        let previous = self.mode.set(mode);
+        if previous == mode {
+            return Ok(());
+        }
+        info!(%previous, %mode, by = %initiator, "Daemon mode changed");
+        let audited = Paths::ensure_state_dir()
+            .map_err(|err| err.to_string())
+            .and_then(|state_dir| {
+                AuditLogger::new(&state_dir)
+                    .log_mode_changed(previous.as_str(), mode.as_str(), initiator)
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = audited {
+            warn!(error = %err, "Failed to audit daemon mode change");
+        }
+        Ok(())
+    }
+
+    fn jobs(&self) -> Vec<JobStatus> {
+        self.jobs.snapshot()
+    }
+
+    fn log_buffer(&self) -> Option<LogBuffer> {
+        LogBuffer::global()
+    }
+
+    fn acknowledge(&self, incident_id: &str) -> Result<(), String> {
+        let config = self.notifications_config().unwrap_or_default();
+        let incident = AckRegistry::global_or_init(&config)
+            .acknowledge(incident_id, Utc::now())
+            .map_err(|err| err.to_string())?;
+        record_acknowledgment(
+            &StateHandle::global_or_default(),
+            &incident,
+            config.ack_pauses_resume,
+        );
+        Ok(())
+    }
+
+    fn clear_attention(&self) -> Result<Option<String>, String> {
+        StateHandle::global_or_default()
+            .update_critical(StateFile::clear_attention)
+            .map_err(|err| err.to_string())
+    }
+
+    fn rotate_secret(&self, channel: &str) -> Result<bool, String> {
+        CredentialRegistry::global()
+            .rotate(channel)
+            .map_err(|err| err.to_string())
+    }
+
+    fn run_schedule(&self, name: &str, initiator: &Initiator) -> Result<ScheduleRun, String> {
+        let scheduler = self
+            .scheduler
+            .read()
+            .ok()
+            .and_then(|current| current.clone())
+            .ok_or_else(|| "Scheduler not running".to_string())?;
+        scheduler.run_now(self, name, initiator)
+    }
+
+    fn mute(
+        &self,
+        kind: MuteKind,
+        name: &str,
+        duration: Option<Duration>,
+    ) -> Result<Vec<NotificationMute>, String> {
+        let mutes = NotificationMutes::new(StateHandle::global_or_default());
+        mutes
+            .mute(kind, name, duration)
+            .map_err(|err| format!("Failed to save mute: {err}"))?;
+        Ok(mutes.active())
+    }
+
+    fn unmute(&self, kind: MuteKind, name: &str) -> Result<Vec<NotificationMute>, String> {
+        let mutes = NotificationMutes::new(StateHandle::global_or_default());
+        let removed = mutes
+            .unmute(kind, name)
+            .map_err(|err| format!("Failed to save mute: {err}"))?;
+        if !removed {
+            return Err(format!("{kind} {name} is not muted"));
+        }
+        Ok(mutes.active())
+    }
+
+    fn mutes(&self) -> Vec<NotificationMute> {
+        NotificationMutes::new(StateHandle::global_or_default()).active()
+    }
 }
 
 impl DaemonState {
+    /// Acknowledge the incident an ack link token was issued for
+    /// (`GET /api/v1/ack/<token>`).
+    pub fn acknowledge_token(&self, token: &str) -> Result<AckIncident, AckError> {
+        let config = self.notifications_config().unwrap_or_default();
+        let incident = AckRegistry::global_or_init(&config).acknowledge_token(token, Utc::now())?;
+        record_acknowledgment(
+            &StateHandle::global_or_default(),
+            &incident,
+            config.ack_pauses_resume,
+        );
+        Ok(incident)
+    }
+
     pub fn auto_detect_active(&self) -> bool {
         self.auto_detect_active.load(Ordering::SeqCst)
     }
 
+    #[cfg(test)]
+    pub(crate) fn set_auto_detect_active(&self, active: bool) {
+        self.auto_detect_active.store(active, Ordering::SeqCst);
+    }
+
     pub fn auto_detect_interval(&self) -> Duration {
         let guard = match self.config.read() {
             Ok(guard) => guard,
@@ -246,7 +989,7 @@ impl DaemonState {
     }
 }
 
-fn load_config_from_disk() -> Result<Config, String> {
+pub(crate) fn load_config_from_disk() -> Result<Config, String> {
     let path = Paths::config_file();
     if !path.exists() {
         return Ok(Config::default());
@@ -254,26 +997,27 @@ fn load_config_from_disk() -> Result<Config, String> {
 
     let contents = std::fs::read_to_string(&path)
         .map_err(|err| format!("Failed to read config file {}: {err}", path.display()))?;
-    toml::from_str(&contents)
+    let mut raw: toml::Value = toml::from_str(&contents)
+        .map_err(|err| format!("Failed to parse config file {}: {err}", path.display()))?;
+    crate::config::secrets::resolve_with_default_store(&mut raw)
+        .map_err(|err| format!("Failed to resolve secrets in {}: {err}", path.display()))?;
+    raw.try_into()
         .map_err(|err| format!("Failed to parse config file {}: {err}", path.display()))
 }
 
 fn log_non_reloadable_changes(old: &Config, new: &Config) {
+    if old.daemon.state_dir != new.daemon.state_dir {
+        warn!("Setting daemon.state_dir requires restart to take effect");
+    }
+    if old.daemon.runtime_dir != new.daemon.runtime_dir {
+        warn!("Setting daemon.runtime_dir requires restart to take effect");
+    }
     if old.daemon.pid_file != new.daemon.pid_file {
         warn!("Setting daemon.pid_file requires restart to take effect");
     }
     if old.daemon.socket_path != new.daemon.socket_path {
         warn!("Setting daemon.socket_path requires restart to take effect");
     }
-    if old.daemon.http_enabled != new.daemon.http_enabled {
-        warn!("Setting daemon.http_enabled requires restart to take effect");
-    }
-    if old.daemon.http_bind != new.daemon.http_bind {
-        warn!("Setting daemon.http_bind requires restart to take effect");
-    }
-    if old.daemon.http_port != new.daemon.http_port {
-        warn!("Setting daemon.http_port requires restart to take effect");
-    }
     if old.daemon.log_file != new.daemon.log_file {
         warn!("Setting daemon.log_file requires restart to take effect");
     }
@@ -282,20 +1026,36 @@ fn log_non_reloadable_changes(old: &Config, new: &Config) {
     }
 }
 
-fn format_time_saved(seconds: f64) -> String {
-    if !seconds.is_finite() || seconds <= 0.0 {
-        return "0 seconds".to_string();
-    }
+/// Sessions with automatic resumes in the last hour, busiest first.
+fn resume_counters(
+    state: &StateFile,
+    limit: ResumeRateLimit,
+    now: DateTime<Utc>,
+) -> Vec<ResumeCounter> {
+    let since = now - chrono::TimeDelta::from_std(RESUME_WINDOW).unwrap_or_default();
+    let mut counters: Vec<ResumeCounter> = state
+        .resume_history
+        .iter()
+        .map(|history| ResumeCounter {
+            session: history.path.display().to_string(),
+            resumes_last_hour: history.count_since(since) as u32,
+            max_per_hour: limit.max_per_hour,
+            loop_suspected_at: history.loop_suspected_at,
+            acknowledged_at: history.acknowledged_at,
+            resumes_paused: history.resumes_paused_at.is_some(),
+        })
+        .filter(|counter| {
+            counter.resumes_last_hour > 0
+                || counter.loop_suspected_at.is_some()
+                || counter.acknowledged_at.is_some()
+        })
+        .collect();
+    counters.sort_by(|a, b| b.resumes_last_hour.cmp(&a.resumes_last_hour));
+    counters
+}
 
-    if seconds < 60.0 {
-        format!("{:.0} seconds", seconds)
-    } else if seconds < 3600.0 {
-        format!("{:.1} minutes", seconds / 60.0)
-    } else if seconds < 86400.0 {
-        format!("{:.1} hours", seconds / 3600.0)
-    } else {
-        format!("{:.1} days", seconds / 86400.0)
-    }
+fn format_time_saved(seconds: f64) -> String {
+    duration::format_compact(duration::from_secs_f64(seconds))
 }
 
 fn apply_auto_detection(config: &mut Config) -> bool {
@@ -359,9 +1119,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resume_counters_report_recent_resumes() {
+        let now = Utc::now();
+        let mut state = StateFile::default();
+        let busy = std::path::Path::new("/tmp/busy.md");
+        state.resume_history_mut(busy).resumed_at = vec![
+            now - chrono::TimeDelta::minutes(90),
+            now - chrono::TimeDelta::minutes(20),
+            now - chrono::TimeDelta::minutes(5),
+        ];
+        state.resume_history_mut(busy).loop_suspected_at = Some(now);
+        state
+            .resume_history_mut(std::path::Path::new("/tmp/quiet.md"))
+            .resumed_at = vec![now - chrono::TimeDelta::hours(3)];
+
+        let counters = resume_counters(&state, ResumeRateLimit::default(), now);
+        assert_eq!(counters.len(), 1);
+        assert_eq!(counters[0].session, "/tmp/busy.md");
+        assert_eq!(counters[0].resumes_last_hour, 2);
+        assert_eq!(counters[0].max_per_hour, 10);
+        assert_eq!(counters[0].loop_suspected_at, Some(now));
+    }
+
     #[test]
     fn test_reload_config_valid_updates_config() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempdir().unwrap();
         let config_path = temp.path().join("config.toml");
         set_env_var("PALINGENESIS_CONFIG", &config_path);
@@ -379,7 +1162,7 @@ mod tests {
 
     #[test]
     fn test_reload_config_invalid_keeps_existing() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempdir().unwrap();
         let config_path = temp.path().join("config.toml");
         set_env_var("PALINGENESIS_CONFIG", &config_path);
@@ -395,4 +1178,87 @@ mod tests {
 
         remove_env_var("PALINGENESIS_CONFIG");
     }
+
+    #[test]
+    fn test_reload_config_rebuilds_shared_classifier() {
+        let _lock = ENV_LOCK.blocking_lock();
+        let temp = tempdir().unwrap();
+        let config_path = temp.path().join("config.toml");
+        set_env_var("PALINGENESIS_CONFIG", &config_path);
+        let borderline = "used 170000 of 200000 tokens";
+
+        std::fs::write(
+            &config_path,
+            "[classifier]\ncontext_threshold_percent = 0.8\n",
+        )
+        .unwrap();
+        let state = DaemonState::new();
+        StopReasonClassifier::apply_config(&state.config_snapshot().classifier).unwrap();
+        let before = StopReasonClassifier::shared().classify_content(borderline, None);
+        assert!(matches!(
+            before.reason,
+            crate::monitor::classifier::StopReason::ContextExhausted(_)
+        ));
+
+        std::fs::write(
+            &config_path,
+            "[classifier]\ncontext_threshold_percent = 0.9\n",
+        )
+        .unwrap();
+        assert!(state.reload_config().is_ok());
+        let after = StopReasonClassifier::shared().classify_content(borderline, None);
+        assert!(!matches!(
+            after.reason,
+            crate::monitor::classifier::StopReason::ContextExhausted(_)
+        ));
+
+        StopReasonClassifier::apply_config(&Default::default()).unwrap();
+        remove_env_var("PALINGENESIS_CONFIG");
+    }
+
+    #[tokio::test]
+    async fn test_control_requests_are_forwarded_to_core_loop() {
+        let state = DaemonState::new_without_auto_detection();
+        let mut rx = state.control_events();
+
+        let cli = Initiator::Cli {
+            uid: Some(1000),
+            tty: None,
+        };
+        state.pause_by(&cli).unwrap();
+        assert!(state.pause().is_err());
+        state.resume().unwrap();
+
+        assert_eq!(rx.recv().await, Some(ControlEvent::Paused(cli)));
+        assert_eq!(
+            rx.recv().await,
+            Some(ControlEvent::Resumed(Initiator::internal("daemon")))
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handoff_snapshot_restores_into_new_state() {
+        let temp = tempdir().unwrap();
+        let handoff_file = HandoffFile::with_path(temp.path().join("handoff.json"));
+        let old = DaemonState::new_without_auto_detection().with_handoff_file(handoff_file.clone());
+        let mut rx = old.control_events();
+        old.new_session().unwrap();
+        old.pause().unwrap();
+        old.handoff().unwrap();
+
+        let daemon = Initiator::internal("daemon");
+        assert_eq!(
+            rx.recv().await,
+            Some(ControlEvent::NewSession(daemon.clone()))
+        );
+        assert_eq!(rx.recv().await, Some(ControlEvent::Paused(daemon)));
+        assert_eq!(rx.recv().await, Some(ControlEvent::HandoffWritten));
+
+        let snapshot = handoff_file.take(Utc::now()).unwrap().unwrap();
+        let new = DaemonState::new_without_auto_detection();
+        new.restore_runtime(&snapshot);
+        assert!(new.is_paused());
+        assert_eq!(new.runtime_snapshot().sessions_count, 1);
+    }
 }
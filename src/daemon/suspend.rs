@@ -0,0 +1,228 @@
+//! System sleep detection.
+//!
+//! The monotonic clock stops while the host is suspended but the wall clock
+//! keeps going, so a tick where wall time advanced much further than
+//! monotonic time means the machine slept. `Instant`-based uptime and waits
+//! already leave the gap out; the detector records it so resumes that were
+//! pending across it get re-checked instead of firing blindly.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::monitor::clock_skew::ClockSkew;
+use crate::notify::events::format_sleep_gap;
+use crate::telemetry::Metrics;
+
+/// Sleeps detected since the process started.
+static SUSPEND_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Number of sleeps detected so far.
+///
+/// Code that waits across long periods compares this before and after the
+/// wait to learn whether the host slept in between.
+pub fn generation() -> u64 {
+    SUSPEND_GENERATION.load(Ordering::SeqCst)
+}
+
+/// Source of monotonic and wall-clock time.
+pub trait Clock: Send + Sync {
+    fn monotonic(&self) -> Instant;
+    fn wall(&self) -> SystemTime;
+}
+
+/// The real clocks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn monotonic(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Compares clock deltas between checks.
+pub struct SuspendDetector {
+    clock: Arc<dyn Clock>,
+    threshold: Duration,
+    last_monotonic: Instant,
+    last_wall: SystemTime,
+}
+
+impl SuspendDetector {
+    pub fn new(clock: Arc<dyn Clock>, threshold: Duration) -> Self {
+        let last_monotonic = clock.monotonic();
+        let last_wall = clock.wall();
+        Self {
+            clock,
+            threshold,
+            last_monotonic,
+            last_wall,
+        }
+    }
+
+    /// Returns the sleep gap since the previous check, if it exceeds the threshold.
+    ///
+    /// Wall-clock jumps backwards (NTP corrections) never count as sleep.
+    pub fn check(&mut self) -> Option<Duration> {
+        let monotonic = self.clock.monotonic();
+        let wall = self.clock.wall();
+        let monotonic_delta = monotonic.saturating_duration_since(self.last_monotonic);
+        let wall_delta = wall
+            .duration_since(self.last_wall)
+            .unwrap_or(Duration::ZERO);
+        self.last_monotonic = monotonic;
+        self.last_wall = wall;
+
+        let gap = wall_delta.saturating_sub(monotonic_delta);
+        (gap >= self.threshold).then_some(gap)
+    }
+}
+
+/// Log, count and announce a detected sleep.
+pub fn record_suspend(gap: Duration, events: Option<&EventBroadcaster>) {
+    SUSPEND_GENERATION.fetch_add(1, Ordering::SeqCst);
+    // The host clock is often stepped on wake; start the skew estimate over.
+    ClockSkew::shared().reset();
+    info!(
+        slept = %format_sleep_gap(gap.as_secs()),
+        slept_secs = gap.as_secs(),
+        "System sleep detected; pending resumes will be re-checked"
+    );
+    if let Some(metrics) = Metrics::global() {
+        metrics.record_suspend(gap);
+    }
+    if let Some(events) = events {
+        let event = DomainEvent::SystemResumed {
+            timestamp: Utc::now(),
+            slept_secs: gap.as_secs(),
+        };
+        events.publish(event);
+    }
+}
+
+/// Check for sleep gaps every `interval` until `cancel` fires.
+pub fn spawn_monitor(
+    mut detector: SuspendDetector,
+    interval: Duration,
+    events: EventBroadcaster,
+    cancel: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            if let Some(gap) = detector.check() {
+                record_suspend(gap, Some(&events));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use std::sync::Mutex;
+
+    struct FakeClock {
+        now: Mutex<(Instant, SystemTime)>,
+    }
+
+    impl FakeClock {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                now: Mutex::new((Instant::now(), SystemTime::now())),
+            })
+        }
+
+        /// Both clocks advance (the host is awake).
+        fn run(&self, duration: Duration) {
+            let mut now = self.now.lock().unwrap();
+            now.0 += duration;
+            now.1 += duration;
+        }
+
+        /// Only the wall clock advances (the host is asleep).
+        fn sleep(&self, duration: Duration) {
+            self.now.lock().unwrap().1 += duration;
+        }
+
+        fn set_wall_back(&self, duration: Duration) {
+            self.now.lock().unwrap().1 -= duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn monotonic(&self) -> Instant {
+            self.now.lock().unwrap().0
+        }
+
+        fn wall(&self) -> SystemTime {
+            self.now.lock().unwrap().1
+        }
+    }
+
+    #[test]
+    fn detects_gap_beyond_threshold() {
+        let clock = FakeClock::new();
+        let mut detector = SuspendDetector::new(clock.clone(), Duration::from_secs(120));
+
+        clock.run(Duration::from_secs(30));
+        assert_eq!(detector.check(), None);
+
+        clock.run(Duration::from_secs(10));
+        clock.sleep(Duration::from_secs(7 * 3600 + 52 * 60));
+        assert_eq!(
+            detector.check(),
+            Some(Duration::from_secs(7 * 3600 + 52 * 60))
+        );
+
+        clock.run(Duration::from_secs(30));
+        assert_eq!(detector.check(), None);
+    }
+
+    #[test]
+    fn ignores_small_drift_and_backward_jumps() {
+        let clock = FakeClock::new();
+        let mut detector = SuspendDetector::new(clock.clone(), Duration::from_secs(120));
+
+        clock.run(Duration::from_secs(30));
+        clock.sleep(Duration::from_secs(5));
+        assert_eq!(detector.check(), None);
+
+        clock.run(Duration::from_secs(30));
+        clock.set_wall_back(Duration::from_secs(3600));
+        assert_eq!(detector.check(), None);
+    }
+
+    #[tokio::test]
+    async fn recorded_suspend_bumps_generation_and_notifies() {
+        let events = EventBroadcaster::new(4);
+        let mut rx = events.subscribe();
+        let before = generation();
+
+        record_suspend(Duration::from_secs(28_320), Some(&events));
+
+        assert!(generation() > before);
+        match rx.recv().await.unwrap() {
+            NotificationEvent::SystemResumed { slept_secs, .. } => assert_eq!(slept_secs, 28_320),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! D-Bus interface for desktop integration (enabled with the `dbus` feature).
+//!
+//! The daemon owns `dev.palingenesis.Daemon1` on the session bus and exports
+//! one object at [`OBJECT_PATH`]. Its properties mirror the daemon status, its
+//! methods go through the same control queue and initiator attribution as the
+//! IPC socket, and its signals are driven by the event broadcaster. Without a
+//! session bus (headless servers) the service is skipped.
+//!
+//! `dbus/dev.palingenesis.Daemon1.xml` holds the introspection data generated
+//! from [`DaemonObject`].
+
+pub mod server;
+pub mod service;
+
+/// Well-known bus name the daemon requests.
+pub const BUS_NAME: &str = "dev.palingenesis.Daemon1";
+
+/// Object exporting the `dev.palingenesis.Daemon1` interface.
+pub const OBJECT_PATH: &str = "/dev/palingenesis/Daemon1";
+
+pub use server::{DbusError, DbusServer};
+pub use service::{DaemonObject, DaemonObjectSignals, introspection_xml};
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use zbus::connection::Builder;
+use zbus::object_server::InterfaceRef;
+use zbus::{Connection, fdo};
+
+use crate::dbus::service::{DaemonObject, DaemonObjectSignals, Snapshot};
+use crate::dbus::{BUS_NAME, OBJECT_PATH};
+use crate::http::EventBroadcaster;
+use crate::ipc::socket::DaemonStateAccess;
+use crate::notify::events::NotificationEvent;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbusError {
+    #[error("D-Bus name {BUS_NAME} is already owned by another process")]
+    NameTaken,
+
+    #[error("D-Bus error: {0}")]
+    Bus(#[from] zbus::Error),
+}
+
+/// Exports [`DaemonObject`] and turns broadcast events into its signals.
+///
+/// Events are subscribed to and properties read on creation, so nothing that
+/// happens while the bus connection is set up is missed.
+pub struct DbusServer {
+    state: Arc<dyn DaemonStateAccess>,
+    events: broadcast::Receiver<NotificationEvent>,
+    published: Snapshot,
+    shutdown: CancellationToken,
+}
+
+impl DbusServer {
+    pub fn new(
+        state: Arc<dyn DaemonStateAccess>,
+        events: EventBroadcaster,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            published: Snapshot::from_status(&state.get_status()),
+            events: events.subscribe(),
+            state,
+            shutdown,
+        }
+    }
+
+    /// Serve on the session bus until shutdown; returns at once when there is
+    /// no session bus.
+    pub async fn run(self) {
+        let builder = match Builder::session() {
+            Ok(builder) => builder,
+            Err(err) => {
+                debug!(error = %err, "No D-Bus session bus; D-Bus interface disabled");
+                return;
+            }
+        };
+        match self.serve(builder).await {
+            Ok(()) => {}
+            Err(err @ DbusError::NameTaken) => warn!(error = %err, "D-Bus interface disabled"),
+            Err(err) => debug!(error = %err, "D-Bus interface disabled"),
+        }
+    }
+
+    /// Connect with `builder`, claim [`BUS_NAME`] and serve until shutdown.
+    pub async fn serve(mut self, builder: Builder<'_>) -> Result<(), DbusError> {
+        let connection = self.connect(builder).await?;
+        info!(
+            name = BUS_NAME,
+            path = OBJECT_PATH,
+            "D-Bus interface exported"
+        );
+        self.forward_events(&connection).await
+    }
+
+    /// Connect with `builder` and export the object; signals are only sent
+    /// once [`Self::forward_events`] runs.
+    pub async fn connect(&self, builder: Builder<'_>) -> Result<Connection, DbusError> {
+        let object = DaemonObject::new(Arc::clone(&self.state));
+        let connection = builder.serve_at(OBJECT_PATH, object)?.build().await?;
+        match connection.request_name(BUS_NAME).await {
+            Ok(()) => Ok(connection),
+            Err(zbus::Error::NameTaken) => Err(DbusError::NameTaken),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Emit signals for broadcast events until shutdown.
+    pub async fn forward_events(&mut self, connection: &Connection) -> Result<(), DbusError> {
+        let object = connection
+            .object_server()
+            .interface::<_, DaemonObject>(OBJECT_PATH)
+            .await?;
+        loop {
+            let event = tokio::select! {
+                _ = self.shutdown.cancelled() => return Ok(()),
+                event = self.events.recv() => match event {
+                    Ok(event) => Some(event),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "D-Bus signal forwarder lagged behind broadcast channel");
+                        None
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            };
+            if let Some(event) = &event {
+                if let Err(err) = emit_event_signal(&object, event).await {
+                    debug!(error = %err, "Failed to emit D-Bus signal");
+                }
+            }
+            let current = Snapshot::from_status(&self.state.get_status());
+            if let Err(err) = emit_property_changes(&object, &self.published, &current).await {
+                debug!(error = %err, "Failed to emit D-Bus property change");
+            }
+            self.published = current;
+        }
+    }
+}
+
+async fn emit_event_signal(
+    object: &InterfaceRef<DaemonObject>,
+    event: &NotificationEvent,
+) -> zbus::Result<()> {
+    let session = event
+        .session_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    match event {
+        NotificationEvent::ResumeSucceeded { .. } => {
+            object.emit_resume_completed(&session, true).await
+        }
+        NotificationEvent::ResumeFailed { .. } => {
+            object.emit_resume_completed(&session, false).await
+        }
+        NotificationEvent::SessionDeleted { .. }
+        | NotificationEvent::ResumeLoopSuspected { .. }
+        | NotificationEvent::IncidentEscalated { .. } => {
+            object
+                .emit_needs_attention(&session, event.event_type())
+                .await
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn emit_property_changes(
+    object: &InterfaceRef<DaemonObject>,
+    published: &Snapshot,
+    current: &Snapshot,
+) -> fdo::Result<()> {
+    let emitter = object.signal_emitter();
+    let daemon = object.get().await;
+    if current.state != published.state {
+        object.emit_state_changed(&current.state).await?;
+        daemon.state_changed(emitter).await?;
+    }
+    if current.current_session != published.current_session {
+        daemon.current_session_changed(emitter).await?;
+    }
+    if current.paused_until != published.paused_until {
+        daemon.paused_until_changed(emitter).await?;
+    }
+    if current.incident_active != published.incident_active {
+        daemon.incident_active_changed(emitter).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use zbus::fdo::DBusProxy;
+use zbus::message::Header;
+use zbus::object_server::{Interface, SignalEmitter};
+use zbus::{Connection, fdo, interface};
+
+use crate::daemon::initiator::Initiator;
+use crate::ipc::protocol::{DaemonStatus, IpcCommand, IpcResponse};
+use crate::ipc::socket::{DaemonStateAccess, handle_command};
+
+/// Property values as published on the bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub state: String,
+    pub current_session: String,
+    pub paused_until: String,
+    pub incident_active: bool,
+}
+
+impl Snapshot {
+    pub fn from_status(status: &DaemonStatus) -> Self {
+        let held_until = [status.deferred_until, status.quota_resume_at]
+            .into_iter()
+            .flatten()
+            .min();
+        Self {
+            state: status.state.clone(),
+            current_session: status.current_session.clone().unwrap_or_default(),
+            paused_until: held_until
+                .map(|until| until.to_rfc3339())
+                .unwrap_or_default(),
+            incident_active: status.needs_attention || status.incident_opened_at.is_some(),
+        }
+    }
+}
+
+/// The object served at [`super::OBJECT_PATH`].
+pub struct DaemonObject {
+    state: Arc<dyn DaemonStateAccess>,
+    /// Callers must run as this user (or root), like the owner-only IPC socket.
+    owner_uid: u32,
+}
+
+impl DaemonObject {
+    pub fn new(state: Arc<dyn DaemonStateAccess>) -> Self {
+        Self {
+            state,
+            owner_uid: unsafe { libc::getuid() },
+        }
+    }
+
+    pub fn with_owner_uid(mut self, uid: u32) -> Self {
+        self.owner_uid = uid;
+        self
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::from_status(&self.state.get_status())
+    }
+
+    async fn control(
+        &self,
+        command: IpcCommand,
+        header: &Header<'_>,
+        connection: &Connection,
+    ) -> fdo::Result<bool> {
+        let initiator = caller(header, connection).await;
+        self.authorize(&initiator)?;
+        match handle_command(command, &initiator, &*self.state).await {
+            IpcResponse::Ok => Ok(true),
+            IpcResponse::Deferred { .. } => Ok(false),
+            IpcResponse::Error { message } => Err(fdo::Error::Failed(message)),
+            other => Err(fdo::Error::Failed(format!(
+                "Unexpected response: {other:?}"
+            ))),
+        }
+    }
+
+    fn authorize(&self, initiator: &Initiator) -> fdo::Result<()> {
+        match initiator {
+            Initiator::Dbus { uid: Some(uid), .. } if *uid == self.owner_uid || *uid == 0 => Ok(()),
+            Initiator::Dbus { uid: Some(uid), .. } => Err(fdo::Error::AccessDenied(format!(
+                "uid {uid} may not control a daemon owned by uid {}",
+                self.owner_uid
+            ))),
+            _ => Err(fdo::Error::AccessDenied(
+                "caller's uid could not be determined".to_string(),
+            )),
+        }
+    }
+}
+
+/// Identify the caller from what the bus (or, on a direct connection, the
+/// socket) reports; nothing the caller sends is trusted.
+async fn caller(header: &Header<'_>, connection: &Connection) -> Initiator {
+    let sender = header.sender().map(|name| name.to_owned());
+    let uid = match &sender {
+        Some(sender) => match DBusProxy::new(connection).await {
+            Ok(proxy) => proxy
+                .get_connection_unix_user(sender.as_ref().into())
+                .await
+                .ok(),
+            Err(_) => None,
+        },
+        None => connection
+            .peer_creds()
+            .await
+            .ok()
+            .and_then(|creds| creds.unix_user_id()),
+    };
+    Initiator::Dbus {
+        uid,
+        sender: sender.map(|name| name.to_string()),
+    }
+}
+
+#[interface(name = "dev.palingenesis.Daemon1")]
+impl DaemonObject {
+    /// Daemon state as shown by `palingenesis status`, e.g. `monitoring`
+    /// or `paused`.
+    #[zbus(property)]
+    async fn state(&self) -> String {
+        self.snapshot().state
+    }
+
+    /// Path of the session being tracked; empty when there is none.
+    #[zbus(property)]
+    async fn current_session(&self) -> String {
+        self.snapshot().current_session
+    }
+
+    /// RFC 3339 time until which resumes are held (maintenance window or
+    /// quota reset); empty when they are not.
+    #[zbus(property)]
+    async fn paused_until(&self) -> String {
+        self.snapshot().paused_until
+    }
+
+    /// An incident is open or the current session needs attention.
+    #[zbus(property)]
+    async fn incident_active(&self) -> bool {
+        self.snapshot().incident_active
+    }
+
+    /// Pause automatic resumes. Returns false when the request was queued
+    /// behind a running resume.
+    #[zbus(out_args("applied"))]
+    async fn pause(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<bool> {
+        self.control(IpcCommand::Pause, &header, connection).await
+    }
+
+    /// Resume monitoring after a pause. Returns false when queued.
+    #[zbus(out_args("applied"))]
+    async fn resume(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<bool> {
+        self.control(IpcCommand::Resume, &header, connection).await
+    }
+
+    /// Start a new session now, like `palingenesis new-session`. Returns
+    /// false when queued.
+    #[zbus(out_args("applied"))]
+    async fn trigger_resume(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> fdo::Result<bool> {
+        self.control(IpcCommand::NewSession, &header, connection)
+            .await
+    }
+
+    /// The `State` property changed.
+    #[zbus(signal, name = "StateChanged")]
+    async fn emit_state_changed(emitter: &SignalEmitter<'_>, state: &str) -> zbus::Result<()>;
+
+    /// A resume of `session` finished.
+    #[zbus(signal, name = "ResumeCompleted")]
+    async fn emit_resume_completed(
+        emitter: &SignalEmitter<'_>,
+        session: &str,
+        success: bool,
+    ) -> zbus::Result<()>;
+
+    /// `session` is held until someone looks at it; `reason` is the event
+    /// type, e.g. `resume_loop_suspected`.
+    #[zbus(signal, name = "NeedsAttention")]
+    async fn emit_needs_attention(
+        emitter: &SignalEmitter<'_>,
+        session: &str,
+        reason: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Introspection data for `object`, as a bus would return it for
+/// [`super::OBJECT_PATH`] (without the standard interfaces).
+pub fn introspection_xml(object: &DaemonObject) -> String {
+    let mut xml = String::from(
+        "<!DOCTYPE node PUBLIC \"-//freedesktop//DTD D-BUS Object Introspection 1.0//EN\"\n \
+         \"http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd\">\n<node>\n",
+    );
+    object.introspect_to_writer(&mut xml, 2);
+    xml.push_str("</node>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::state::DaemonState;
+    use chrono::{Duration, Utc};
+
+    fn object() -> DaemonObject {
+        DaemonObject::new(Arc::new(DaemonState::new_without_auto_detection())).with_owner_uid(1000)
+    }
+
+    #[test]
+    fn only_the_owner_and_root_may_control() {
+        let object = object();
+        let caller = |uid| Initiator::Dbus {
+            uid,
+            sender: Some(":1.7".to_string()),
+        };
+        assert!(object.authorize(&caller(Some(1000))).is_ok());
+        assert!(object.authorize(&caller(Some(0))).is_ok());
+        assert!(matches!(
+            object.authorize(&caller(Some(1001))),
+            Err(fdo::Error::AccessDenied(_))
+        ));
+        assert!(object.authorize(&caller(None)).is_err());
+    }
+
+    #[test]
+    fn paused_until_is_the_earliest_hold() {
+        let mut status = object().state.get_status();
+        assert_eq!(Snapshot::from_status(&status).paused_until, "");
+
+        let quota = Utc::now() + Duration::minutes(5);
+        status.deferred_until = Some(quota + Duration::hours(1));
+        status.quota_resume_at = Some(quota);
+        assert_eq!(
+            Snapshot::from_status(&status).paused_until,
+            quota.to_rfc3339()
+        );
+    }
+
+    #[test]
+    fn introspection_lists_the_interface() {
+        let xml = introspection_xml(&object());
+        assert!(xml.contains("<interface name=\"dev.palingenesis.Daemon1\">"));
+        for member in [
+            "State",
+            "CurrentSession",
+            "PausedUntil",
+            "IncidentActive",
+            "Pause",
+            "Resume",
+            "TriggerResume",
+            "StateChanged",
+            "ResumeCompleted",
+            "NeedsAttention",
+        ] {
+            assert!(xml.contains(&format!("name=\"{member}\"")), "{member}");
+        }
+    }
+}
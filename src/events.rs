@@ -0,0 +1,1590 @@
+//! The daemon's event vocabulary.
+//!
+//! Everything the daemon reports is a [`DomainEvent`]. Consumers derive their
+//! own representation through the mappings here:
+//! [`DomainEvent::notification`] feeds the channels, the SSE stream and the
+//! notification history, and [`DomainEvent::audit_entry`] feeds the audit
+//! trail. Both are exhaustive, so adding a variant is one place to name the
+//! event and one arm per consumer, and the compiler lists the consumers that
+//! still need a decision.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::daemon::initiator::Initiator;
+use crate::i18n::Locale;
+use crate::notify::NotificationEvent;
+use crate::notify::events::format_control_action;
+use crate::resume::git_context::GitContext;
+use crate::state::audit::{AuditEntry, AuditEventType, AuditOutcome};
+
+/// Something that happened in the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// A session stopped; the resume decision follows.
+    StopDetected {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        details: Option<String>,
+        /// `resume.exclude_sessions` pattern that kept this session from auto-resume.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        excluded_by: Option<String>,
+    },
+    /// The classifier's verdict on a stop.
+    StopClassified {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        confidence: f32,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        evidence: Vec<String>,
+        /// Stop-time transcript snapshot.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        incident: Option<PathBuf>,
+    },
+    /// A resume is waiting out a backoff or rate-limit delay.
+    WaitStarted {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        wait_secs: u64,
+        attempt: u32,
+    },
+    /// The wait before a resume ended.
+    WaitEnded {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        waited_secs: u64,
+    },
+    /// A resume was postponed, e.g. until a maintenance window ends.
+    ResumeDeferred {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        until: DateTime<Utc>,
+        reason: String,
+    },
+    ResumeStarted {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        strategy: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+    },
+    ResumeSucceeded {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        strategy: String,
+        wait_time_secs: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        git: Option<GitContext>,
+    },
+    ResumeFailed {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        strategy: String,
+        error: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        incident: Option<PathBuf>,
+    },
+    /// The daemon gave up on resuming a session (e.g. retry limit reached).
+    ResumeAbandoned {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        attempts: u32,
+        reason: String,
+    },
+    /// A session hit `resume.max_resumes_per_hour`.
+    ResumeLoopSuspected {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        resumes: u32,
+        span_secs: u64,
+    },
+    /// A per-session resume sidecar could not be parsed.
+    ResumeSidecarInvalid {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        sidecar_path: PathBuf,
+        error: String,
+    },
+    /// Observe mode: a resume the daemon would have run.
+    ActionObserved {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        action: String,
+    },
+    /// A resume started a new session.
+    SessionCreated {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        /// Session that was continued.
+        source_session: PathBuf,
+    },
+    /// A tracked session's status changed, e.g. `active` to `needs_attention`.
+    SessionStateChanged {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        from: String,
+        to: String,
+    },
+    SessionCompleted {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        duration_secs: Option<u64>,
+        resumes: u64,
+        time_saved_seconds: f64,
+    },
+    SessionBackedUp {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        backup_path: PathBuf,
+    },
+    /// The current session's file was deleted.
+    SessionDeleted {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restored_from: Option<PathBuf>,
+    },
+    /// A resume created a session but failed before the daemon could track it.
+    SessionOrphaned {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        source_session: PathBuf,
+        reason: String,
+    },
+    ModelChanged {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        previous_model: String,
+        current_model: String,
+    },
+    NextStepInvalid {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        next_step_path: PathBuf,
+        unmatched_lines: Vec<String>,
+    },
+    WorktreeCreated {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        workspace: PathBuf,
+        worktree_path: PathBuf,
+        branch: String,
+    },
+    /// A path was refused by the safe-path check.
+    PathRejected {
+        timestamp: DateTime<Utc>,
+        path: PathBuf,
+        reason: String,
+    },
+    DaemonStarted {
+        timestamp: DateTime<Utc>,
+        version: String,
+    },
+    DaemonStopped {
+        timestamp: DateTime<Utc>,
+        reason: String,
+    },
+    /// The daemon switched between active and observe mode.
+    ModeChanged {
+        timestamp: DateTime<Utc>,
+        previous: String,
+        current: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        initiator: Option<Initiator>,
+    },
+    /// Someone paused or resumed the daemon, or started a new session.
+    ControlApplied {
+        timestamp: DateTime<Utc>,
+        /// `pause`, `resume` or `new_session`.
+        action: String,
+        initiator: Initiator,
+    },
+    ConfigReloaded {
+        timestamp: DateTime<Utc>,
+        /// Top-level sections that changed.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sections: Vec<String>,
+    },
+    /// The host slept; pending resumes are re-checked.
+    SystemResumed {
+        timestamp: DateTime<Utc>,
+        slept_secs: u64,
+    },
+    ClockSkewDetected {
+        timestamp: DateTime<Utc>,
+        skew_secs: f64,
+    },
+    /// A session file had a byte order mark or was saved as UTF-16.
+    FileEncodingFixed {
+        timestamp: DateTime<Utc>,
+        file: PathBuf,
+        encoding: String,
+    },
+    /// A `[[schedules]]` entry fired; `outcome` is `succeeded`, `failed`
+    /// or `skipped`.
+    ScheduledRun {
+        timestamp: DateTime<Utc>,
+        schedule: String,
+        /// `scheduled`, `catch_up` or `manual`.
+        trigger: String,
+        outcome: String,
+        workdir: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_path: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+        /// Who ran the schedule by hand; `None` for timed firings.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        initiator: Option<Initiator>,
+    },
+    /// Startup reconciliation corrected the state file; each correction is
+    /// audited on its own.
+    StateReconciled {
+        timestamp: DateTime<Utc>,
+        corrections: Vec<String>,
+        report: PathBuf,
+    },
+    /// One change reconciliation made to the state file (audit only; the
+    /// summary goes out as [`DomainEvent::StateReconciled`]).
+    StateCorrected {
+        timestamp: DateTime<Utc>,
+        /// The correction's kind, e.g. `incident_closed`.
+        correction: String,
+        detail: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_path: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        incident_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+    },
+    /// The artifact GC's first run under new retention settings found
+    /// artifacts to delete and waits for `palingenesis gc approve`.
+    ArtifactGcPending {
+        timestamp: DateTime<Utc>,
+        artifacts: usize,
+        bytes: u64,
+        summary: Vec<String>,
+        plan: PathBuf,
+    },
+    /// The artifact GC deleted a file or directory past its retention.
+    ArtifactDeleted {
+        timestamp: DateTime<Utc>,
+        /// The artifact's kind, e.g. `backup`.
+        artifact: String,
+        path: PathBuf,
+        bytes: u64,
+        modified: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_path: Option<PathBuf>,
+    },
+    /// The state file comes from a newer schema and is not written.
+    StateReadOnly {
+        timestamp: DateTime<Utc>,
+        state_file: PathBuf,
+        found_version: u32,
+        supported_version: u32,
+    },
+    #[serde(rename = "opencode_started")]
+    OpenCodeStarted { timestamp: DateTime<Utc>, pid: u32 },
+    #[serde(rename = "opencode_stopped")]
+    OpenCodeStopped {
+        timestamp: DateTime<Utc>,
+        pid: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        /// Exited unexpectedly rather than on request.
+        crashed: bool,
+    },
+    /// opencode crashed; stops of the sessions it served are held until it is back.
+    ServerDownWindowOpened {
+        timestamp: DateTime<Utc>,
+        pid: u32,
+        exit_code: i32,
+    },
+    ServerDownWindowClosed {
+        timestamp: DateTime<Utc>,
+        pid: u32,
+        /// The server came back healthy rather than the wait timing out.
+        restarted: bool,
+        down_secs: u64,
+    },
+    /// A resumable stop is held while the server that served it restarts.
+    StopHeld {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        pid: u32,
+    },
+    /// A held stop was dropped (`avoided`) or handed on (`proceeded`).
+    HeldStopReleased {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        outcome: String,
+        held_secs: u64,
+    },
+    #[serde(rename = "opencode_version_changed")]
+    OpenCodeVersionChanged {
+        timestamp: DateTime<Utc>,
+        previous: Option<String>,
+        current: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        problems: Vec<String>,
+    },
+    /// A component kept running with reduced function, e.g. the HTTP API
+    /// failed to bind.
+    Degraded {
+        timestamp: DateTime<Utc>,
+        component: String,
+        message: String,
+    },
+    /// Sent on request to check that a channel is configured correctly.
+    TestNotification {
+        timestamp: DateTime<Utc>,
+        message: String,
+    },
+    /// The previous daemon instance crashed, panicked or was killed.
+    UnexpectedExit {
+        timestamp: DateTime<Utc>,
+        pid: u32,
+        /// `error`, `panic` or `killed`.
+        cause: String,
+        detail: String,
+    },
+}
+
+impl DomainEvent {
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            Self::StopDetected { timestamp, .. }
+            | Self::StopClassified { timestamp, .. }
+            | Self::WaitStarted { timestamp, .. }
+            | Self::WaitEnded { timestamp, .. }
+            | Self::ResumeDeferred { timestamp, .. }
+            | Self::ResumeStarted { timestamp, .. }
+            | Self::ResumeSucceeded { timestamp, .. }
+            | Self::ResumeFailed { timestamp, .. }
+            | Self::ResumeAbandoned { timestamp, .. }
+            | Self::ResumeLoopSuspected { timestamp, .. }
+            | Self::ResumeSidecarInvalid { timestamp, .. }
+            | Self::ActionObserved { timestamp, .. }
+            | Self::SessionCreated { timestamp, .. }
+            | Self::SessionStateChanged { timestamp, .. }
+            | Self::SessionCompleted { timestamp, .. }
+            | Self::SessionBackedUp { timestamp, .. }
+            | Self::SessionDeleted { timestamp, .. }
+            | Self::SessionOrphaned { timestamp, .. }
+            | Self::ModelChanged { timestamp, .. }
+            | Self::NextStepInvalid { timestamp, .. }
+            | Self::WorktreeCreated { timestamp, .. }
+            | Self::PathRejected { timestamp, .. }
+            | Self::DaemonStarted { timestamp, .. }
+            | Self::DaemonStopped { timestamp, .. }
+            | Self::ModeChanged { timestamp, .. }
+            | Self::ControlApplied { timestamp, .. }
+            | Self::ConfigReloaded { timestamp, .. }
+            | Self::SystemResumed { timestamp, .. }
+            | Self::ClockSkewDetected { timestamp, .. }
+            | Self::FileEncodingFixed { timestamp, .. }
+            | Self::ScheduledRun { timestamp, .. }
+            | Self::StateReconciled { timestamp, .. }
+            | Self::StateCorrected { timestamp, .. }
+            | Self::ArtifactGcPending { timestamp, .. }
+            | Self::ArtifactDeleted { timestamp, .. }
+            | Self::StateReadOnly { timestamp, .. }
+            | Self::OpenCodeStarted { timestamp, .. }
+            | Self::OpenCodeStopped { timestamp, .. }
+            | Self::ServerDownWindowOpened { timestamp, .. }
+            | Self::ServerDownWindowClosed { timestamp, .. }
+            | Self::StopHeld { timestamp, .. }
+            | Self::HeldStopReleased { timestamp, .. }
+            | Self::OpenCodeVersionChanged { timestamp, .. }
+            | Self::Degraded { timestamp, .. }
+            | Self::TestNotification { timestamp, .. }
+            | Self::UnexpectedExit { timestamp, .. } => *timestamp,
+        }
+    }
+
+    /// Notification for channels, the SSE stream and notification history;
+    /// `None` for internal bookkeeping nobody is paged about.
+    pub fn notification(&self) -> Option<NotificationEvent> {
+        let event = match self.clone() {
+            Self::StopDetected {
+                timestamp,
+                session_path,
+                stop_reason,
+                details,
+                excluded_by,
+            } => NotificationEvent::SessionStopped {
+                timestamp,
+                session_path,
+                stop_reason,
+                details,
+                excluded_by,
+            },
+            Self::ResumeDeferred {
+                timestamp,
+                session_path,
+                stop_reason,
+                until,
+                reason,
+            } => NotificationEvent::ResumeDeferred {
+                timestamp,
+                session_path,
+                stop_reason,
+                until,
+                reason,
+            },
+            Self::ResumeStarted {
+                timestamp,
+                session_path,
+                strategy,
+                progress,
+                percent,
+            } => NotificationEvent::ResumeAttempted {
+                timestamp,
+                session_path,
+                strategy,
+                progress,
+                percent,
+            },
+            Self::ResumeSucceeded {
+                timestamp,
+                session_path,
+                strategy,
+                wait_time_secs,
+                progress,
+                percent,
+                git,
+            } => NotificationEvent::ResumeSucceeded {
+                timestamp,
+                session_path,
+                strategy,
+                wait_time_secs,
+                progress,
+                percent,
+                git,
+            },
+            Self::ResumeFailed {
+                timestamp,
+                session_path,
+                strategy,
+                error,
+                progress,
+                percent,
+                incident,
+            } => NotificationEvent::ResumeFailed {
+                timestamp,
+                session_path,
+                strategy,
+                error,
+                progress,
+                percent,
+                incident,
+                ack: None,
+            },
+            Self::ResumeLoopSuspected {
+                timestamp,
+                session_path,
+                resumes,
+                span_secs,
+            } => NotificationEvent::ResumeLoopSuspected {
+                timestamp,
+                session_path,
+                resumes,
+                span_secs,
+                ack: None,
+            },
+            Self::ResumeSidecarInvalid {
+                timestamp,
+                session_path,
+                sidecar_path,
+                error,
+            } => NotificationEvent::ResumeSidecarInvalid {
+                timestamp,
+                session_path,
+                sidecar_path,
+                error,
+            },
+            Self::ActionObserved {
+                timestamp,
+                session_path,
+                stop_reason,
+                action,
+            } => NotificationEvent::ActionObserved {
+                timestamp,
+                session_path,
+                stop_reason,
+                action,
+            },
+            Self::SessionCompleted {
+                timestamp,
+                session_path,
+                duration_secs,
+                resumes,
+                time_saved_seconds,
+            } => NotificationEvent::SessionCompleted {
+                timestamp,
+                session_path,
+                duration_secs,
+                resumes,
+                time_saved_seconds,
+            },
+            Self::SessionDeleted {
+                timestamp,
+                session_path,
+                restored_from,
+            } => NotificationEvent::SessionDeleted {
+                timestamp,
+                session_path,
+                restored_from,
+            },
+            Self::SessionOrphaned {
+                timestamp,
+                session_path,
+                source_session,
+                reason,
+            } => NotificationEvent::SessionOrphaned {
+                timestamp,
+                session_path,
+                source_session,
+                reason,
+            },
+            Self::ModelChanged {
+                timestamp,
+                session_path,
+                previous_model,
+                current_model,
+            } => NotificationEvent::ModelChanged {
+                timestamp,
+                session_path,
+                previous_model,
+                current_model,
+            },
+            Self::NextStepInvalid {
+                timestamp,
+                session_path,
+                next_step_path,
+                unmatched_lines,
+            } => NotificationEvent::NextStepInvalid {
+                timestamp,
+                session_path,
+                next_step_path,
+                unmatched_lines,
+            },
+            Self::WorktreeCreated {
+                timestamp,
+                session_path,
+                workspace,
+                worktree_path,
+                branch,
+            } => NotificationEvent::WorktreeCreated {
+                timestamp,
+                session_path,
+                workspace,
+                worktree_path,
+                branch,
+            },
+            Self::DaemonStarted { timestamp, version } => {
+                NotificationEvent::DaemonStarted { timestamp, version }
+            }
+            Self::DaemonStopped { timestamp, reason } => {
+                NotificationEvent::DaemonStopped { timestamp, reason }
+            }
+            Self::SystemResumed {
+                timestamp,
+                slept_secs,
+            } => NotificationEvent::SystemResumed {
+                timestamp,
+                slept_secs,
+            },
+            Self::ClockSkewDetected {
+                timestamp,
+                skew_secs,
+            } => NotificationEvent::ClockSkewDetected {
+                timestamp,
+                skew_secs,
+            },
+            Self::FileEncodingFixed {
+                timestamp,
+                file,
+                encoding,
+            } => NotificationEvent::FileEncodingFixed {
+                timestamp,
+                file,
+                encoding,
+            },
+            Self::ScheduledRun {
+                timestamp,
+                schedule,
+                trigger,
+                outcome,
+                workdir,
+                session_path,
+                detail,
+                ..
+            } => NotificationEvent::ScheduledRun {
+                timestamp,
+                schedule,
+                trigger,
+                outcome,
+                workdir,
+                session_path,
+                detail,
+            },
+            Self::StateReconciled {
+                timestamp,
+                corrections,
+                report,
+            } => NotificationEvent::StateReconciled {
+                timestamp,
+                corrections,
+                report,
+            },
+            Self::ArtifactGcPending {
+                timestamp,
+                artifacts,
+                bytes,
+                summary,
+                plan,
+            } => NotificationEvent::ArtifactGcPending {
+                timestamp,
+                artifacts,
+                bytes,
+                summary,
+                plan,
+            },
+            Self::StateReadOnly {
+                timestamp,
+                state_file,
+                found_version,
+                supported_version,
+            } => NotificationEvent::StateReadOnly {
+                timestamp,
+                state_file,
+                found_version,
+                supported_version,
+            },
+            Self::OpenCodeVersionChanged {
+                timestamp,
+                previous,
+                current,
+                problems,
+            } => NotificationEvent::OpenCodeVersionChanged {
+                timestamp,
+                previous,
+                current,
+                problems,
+            },
+            Self::ControlApplied {
+                timestamp,
+                action,
+                initiator,
+            } => NotificationEvent::ControlApplied {
+                timestamp,
+                action,
+                initiator,
+            },
+            Self::TestNotification { timestamp, message } => {
+                NotificationEvent::TestNotification { timestamp, message }
+            }
+            Self::UnexpectedExit {
+                timestamp,
+                pid,
+                cause,
+                detail,
+            } => NotificationEvent::UnexpectedExit {
+                timestamp,
+                pid,
+                cause,
+                detail,
+            },
+            Self::StopClassified { .. }
+            | Self::WaitStarted { .. }
+            | Self::WaitEnded { .. }
+            | Self::ResumeAbandoned { .. }
+            | Self::SessionCreated { .. }
+            | Self::SessionStateChanged { .. }
+            | Self::SessionBackedUp { .. }
+            | Self::PathRejected { .. }
+            | Self::ModeChanged { .. }
+            | Self::ConfigReloaded { .. }
+            | Self::StateCorrected { .. }
+            | Self::ArtifactDeleted { .. }
+            | Self::OpenCodeStarted { .. }
+            | Self::OpenCodeStopped { .. }
+            | Self::ServerDownWindowOpened { .. }
+            | Self::ServerDownWindowClosed { .. }
+            | Self::StopHeld { .. }
+            | Self::HeldStopReleased { .. }
+            | Self::Degraded { .. } => return None,
+        };
+        Some(event)
+    }
+
+    /// Audit trail entry; `None` for events the audit trail does not record.
+    pub fn audit_entry(&self) -> Option<AuditEntry> {
+        let mut entry = match self {
+            Self::ResumeStarted {
+                session_path,
+                strategy,
+                ..
+            } => AuditEntry::new(AuditEventType::ResumeStarted, "Starting resume")
+                .with_session(session_path.clone())
+                .with_outcome(AuditOutcome::Pending)
+                .with_metadata("strategy", strategy.as_str()),
+            Self::ResumeSucceeded {
+                session_path,
+                strategy,
+                git,
+                ..
+            } => {
+                let mut entry = AuditEntry::new(AuditEventType::ResumeCompleted, strategy.as_str())
+                    .with_session(session_path.clone())
+                    .with_outcome(AuditOutcome::Success);
+                if let Some(value) = git.as_ref().and_then(|git| serde_json::to_value(git).ok()) {
+                    entry = entry.with_metadata("git_at_resume", value);
+                }
+                entry
+            }
+            Self::ResumeFailed {
+                session_path,
+                error,
+                incident,
+                ..
+            } => {
+                let mut entry = AuditEntry::new(AuditEventType::ResumeFailed, "Resume failed")
+                    .with_session(session_path.clone())
+                    .with_outcome(AuditOutcome::Failure)
+                    .with_metadata("error", error.as_str());
+                if let Some(incident) = incident {
+                    entry = entry.with_metadata("incident", incident.display().to_string());
+                }
+                entry
+            }
+            Self::ResumeAbandoned {
+                session_path,
+                attempts,
+                reason,
+                ..
+            } => AuditEntry::new(AuditEventType::ResumeFailed, "Resume abandoned")
+                .with_session(session_path.clone())
+                .with_outcome(AuditOutcome::Skipped)
+                .with_metadata("error", reason.as_str())
+                .with_metadata("attempts", *attempts),
+            Self::ActionObserved {
+                session_path,
+                stop_reason,
+                action,
+                ..
+            } => AuditEntry::new(
+                AuditEventType::ActionObserved,
+                format!("OBSERVE: would run {action}"),
+            )
+            .with_session(session_path.clone())
+            .with_stop_reason(stop_reason.as_str())
+            .with_outcome(AuditOutcome::Skipped),
+            Self::SessionCreated {
+                session_path,
+                source_session,
+                ..
+            } => AuditEntry::new(AuditEventType::SessionCreated, "Session created")
+                .with_session(session_path.clone())
+                .with_outcome(AuditOutcome::Success)
+                .with_metadata("source_session", source_session.display().to_string()),
+            Self::SessionStateChanged {
+                session_path,
+                from,
+                to,
+                ..
+            } => AuditEntry::new(
+                AuditEventType::StateChanged,
+                format!("Session status set to {to}"),
+            )
+            .with_session(session_path.clone())
+            .with_outcome(AuditOutcome::Success)
+            .with_metadata("previous_status", from.as_str())
+            .with_metadata("status", to.as_str()),
+            Self::SessionCompleted {
+                session_path,
+                duration_secs,
+                resumes,
+                time_saved_seconds,
+                ..
+            } => {
+                let mut entry =
+                    AuditEntry::new(AuditEventType::SessionCompleted, "Session completed")
+                        .with_session(session_path.clone())
+                        .with_outcome(AuditOutcome::Success)
+                        .with_metadata("resumes", *resumes)
+                        .with_metadata("time_saved_seconds", *time_saved_seconds);
+                if let Some(duration) = duration_secs {
+                    entry = entry.with_metadata("duration_secs", *duration);
+                }
+                entry
+            }
+            Self::SessionBackedUp {
+                session_path,
+                backup_path,
+                ..
+            } => AuditEntry::new(AuditEventType::SessionBackedUp, "Session backed up")
+                .with_session(session_path.clone())
+                .with_outcome(AuditOutcome::Success)
+                .with_metadata("backup_path", backup_path.display().to_string()),
+            Self::SessionDeleted {
+                session_path,
+                restored_from: Some(backup),
+                ..
+            } => AuditEntry::new(
+                AuditEventType::SessionRestored,
+                "Session restored from backup",
+            )
+            .with_session(session_path.clone())
+            .with_outcome(AuditOutcome::Success)
+            .with_metadata("backup_path", backup.display().to_string()),
+            Self::ModelChanged {
+                session_path,
+                previous_model,
+                current_model,
+                ..
+            } => AuditEntry::new(AuditEventType::ModelMismatch, "Session model changed")
+                .with_session(session_path.clone())
+                .with_outcome(AuditOutcome::Success)
+                .with_metadata("previous_model", previous_model.as_str())
+                .with_metadata("current_model", current_model.as_str()),
+            Self::PathRejected { path, reason, .. } => {
+                AuditEntry::new(AuditEventType::PathRejected, "Path rejected")
+                    .with_session(path.clone())
+                    .with_outcome(AuditOutcome::Skipped)
+                    .with_metadata("reason", reason.as_str())
+            }
+            Self::DaemonStarted { version, .. } => {
+                AuditEntry::new(AuditEventType::DaemonStarted, "Daemon started")
+                    .with_outcome(AuditOutcome::Success)
+                    .with_metadata("version", version.as_str())
+            }
+            Self::DaemonStopped { reason, .. } => {
+                AuditEntry::new(AuditEventType::DaemonStopped, "Daemon stopped")
+                    .with_outcome(AuditOutcome::Success)
+                    .with_metadata("reason", reason.as_str())
+            }
+            Self::ModeChanged {
+                previous,
+                current,
+                initiator,
+                ..
+            } => {
+                let entry = AuditEntry::new(
+                    AuditEventType::ModeChanged,
+                    format!("Daemon mode set to {current}"),
+                )
+                .with_outcome(AuditOutcome::Success)
+                .with_metadata("previous_mode", previous.as_str())
+                .with_metadata("mode", current.as_str());
+                match initiator {
+                    Some(initiator) => entry.with_initiator(initiator),
+                    None => entry,
+                }
+            }
+            Self::ControlApplied {
+                action, initiator, ..
+            } => AuditEntry::new(
+                AuditEventType::ControlAction,
+                format_control_action(action, initiator, Locale::En),
+            )
+            .with_outcome(AuditOutcome::Success)
+            .with_metadata("action", action.as_str())
+            .with_initiator(initiator),
+            Self::ScheduledRun {
+                schedule,
+                trigger,
+                outcome,
+                workdir,
+                session_path,
+                detail,
+                initiator,
+                ..
+            } => {
+                let (audit_outcome, detail_key) = match outcome.as_str() {
+                    "succeeded" => (AuditOutcome::Success, "error"),
+                    "skipped" => (AuditOutcome::Skipped, "reason"),
+                    _ => (AuditOutcome::Failure, "error"),
+                };
+                let mut entry = AuditEntry::new(
+                    AuditEventType::ScheduledRun,
+                    format!("Scheduled session {schedule}"),
+                )
+                .with_outcome(audit_outcome)
+                .with_metadata("schedule", schedule.as_str())
+                .with_metadata("trigger", trigger.as_str())
+                .with_metadata("workdir", workdir.display().to_string());
+                if let Some(session_path) = session_path {
+                    entry = entry.with_session(session_path.clone());
+                }
+                if let Some(detail) = detail {
+                    entry = entry.with_metadata(detail_key, detail.as_str());
+                }
+                match initiator {
+                    Some(initiator) => entry.with_initiator(initiator),
+                    None => entry,
+                }
+            }
+            Self::ConfigReloaded { sections, .. } => {
+                AuditEntry::new(AuditEventType::ConfigChanged, "Config reloaded")
+                    .with_outcome(AuditOutcome::Success)
+                    .with_metadata("sections", sections.clone())
+            }
+            Self::StateCorrected {
+                correction,
+                detail,
+                session_path,
+                incident_id,
+                note,
+                ..
+            } => {
+                let mut entry = AuditEntry::new(AuditEventType::StateReconciled, detail.as_str())
+                    .with_outcome(AuditOutcome::Success)
+                    .with_metadata("correction", correction.as_str());
+                if let Some(path) = session_path {
+                    entry = entry.with_session(path.clone());
+                }
+                if let Some(id) = incident_id {
+                    entry = entry.with_metadata("incident_id", id.as_str());
+                }
+                if let Some(note) = note {
+                    entry = entry.with_metadata("note", note.as_str());
+                }
+                entry
+            }
+            Self::ArtifactDeleted {
+                artifact,
+                path,
+                bytes,
+                modified,
+                session_path,
+                ..
+            } => {
+                let entry = AuditEntry::new(
+                    AuditEventType::ArtifactDeleted,
+                    format!("Deleted {artifact} {}", path.display()),
+                )
+                .with_outcome(AuditOutcome::Success)
+                .with_metadata("artifact", artifact.as_str())
+                .with_metadata("path", path.display().to_string())
+                .with_metadata("bytes", *bytes)
+                .with_metadata("modified", modified.to_rfc3339());
+                match session_path {
+                    Some(path) => entry.with_session(path.clone()),
+                    None => entry,
+                }
+            }
+            Self::ServerDownWindowOpened { pid, exit_code, .. } => AuditEntry::new(
+                AuditEventType::RestartCorrelation,
+                "opencode crashed; holding stops until it restarts",
+            )
+            .with_outcome(AuditOutcome::Pending)
+            .with_metadata("pid", *pid)
+            .with_metadata("exit_code", *exit_code),
+            Self::ServerDownWindowClosed {
+                pid,
+                restarted,
+                down_secs,
+                ..
+            } => AuditEntry::new(
+                AuditEventType::RestartCorrelation,
+                if *restarted {
+                    "opencode restarted"
+                } else {
+                    "opencode did not restart in time"
+                },
+            )
+            .with_outcome(if *restarted {
+                AuditOutcome::Success
+            } else {
+                AuditOutcome::Failure
+            })
+            .with_metadata("pid", *pid)
+            .with_metadata("down_secs", *down_secs),
+            Self::StopHeld {
+                session_path,
+                stop_reason,
+                pid,
+                ..
+            } => AuditEntry::new(
+                AuditEventType::RestartCorrelation,
+                "Stop held for opencode restart",
+            )
+            .with_session(session_path.clone())
+            .with_stop_reason(stop_reason.as_str())
+            .with_outcome(AuditOutcome::Pending)
+            .with_metadata("pid", *pid),
+            Self::HeldStopReleased {
+                session_path,
+                stop_reason,
+                outcome,
+                held_secs,
+                ..
+            } => AuditEntry::new(
+                AuditEventType::RestartCorrelation,
+                if outcome == "avoided" {
+                    "Session continued after restart; resume avoided"
+                } else {
+                    "Session still stopped after restart; resuming"
+                },
+            )
+            .with_session(session_path.clone())
+            .with_stop_reason(stop_reason.as_str())
+            .with_outcome(if outcome == "avoided" {
+                AuditOutcome::Skipped
+            } else {
+                AuditOutcome::Success
+            })
+            .with_metadata("outcome", outcome.as_str())
+            .with_metadata("held_secs", *held_secs),
+            Self::Degraded {
+                component, message, ..
+            } => AuditEntry::new(AuditEventType::Error, format!("{component} degraded"))
+                .with_outcome(AuditOutcome::Failure)
+                .with_metadata("error", message.as_str()),
+            Self::UnexpectedExit {
+                pid, cause, detail, ..
+            } => AuditEntry::new(
+                AuditEventType::Error,
+                format!("Previous daemon (PID {pid}) exited unexpectedly"),
+            )
+            .with_outcome(AuditOutcome::Failure)
+            .with_metadata("cause", cause.as_str())
+            .with_metadata("error", detail.as_str()),
+            Self::StopDetected { .. }
+            | Self::StopClassified { .. }
+            | Self::WaitStarted { .. }
+            | Self::WaitEnded { .. }
+            | Self::ResumeDeferred { .. }
+            | Self::ResumeLoopSuspected { .. }
+            | Self::ResumeSidecarInvalid { .. }
+            | Self::SessionDeleted { .. }
+            | Self::SessionOrphaned { .. }
+            | Self::NextStepInvalid { .. }
+            | Self::WorktreeCreated { .. }
+            | Self::SystemResumed { .. }
+            | Self::ClockSkewDetected { .. }
+            | Self::FileEncodingFixed { .. }
+            | Self::StateReconciled { .. }
+            | Self::ArtifactGcPending { .. }
+            | Self::StateReadOnly { .. }
+            | Self::OpenCodeStarted { .. }
+            | Self::OpenCodeStopped { .. }
+            | Self::OpenCodeVersionChanged { .. }
+            | Self::TestNotification { .. } => return None,
+        };
+        entry.timestamp = self.timestamp();
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ts() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 4, 5, 6, 7)
+            .single()
+            .expect("valid timestamp")
+    }
+
+    /// One sample per variant. The match makes a new variant fail to compile
+    /// here until it has a sample, and the test then checks both mappings.
+    fn samples() -> Vec<DomainEvent> {
+        let path = PathBuf::from("/work/session.md");
+        let timestamp = ts();
+        let all = vec![
+            DomainEvent::StopDetected {
+                timestamp,
+                session_path: path.clone(),
+                stop_reason: "rate_limit".into(),
+                details: None,
+                excluded_by: None,
+            },
+            DomainEvent::StopClassified {
+                timestamp,
+                session_path: path.clone(),
+                stop_reason: "rate_limit".into(),
+                confidence: 0.9,
+                evidence: vec!["matched pattern: 429".into()],
+                incident: None,
+            },
+            DomainEvent::WaitStarted {
+                timestamp,
+                session_path: path.clone(),
+                wait_secs: 30,
+                attempt: 1,
+            },
+            DomainEvent::WaitEnded {
+                timestamp,
+                session_path: path.clone(),
+                waited_secs: 30,
+            },
+            DomainEvent::ResumeDeferred {
+                timestamp,
+                session_path: path.clone(),
+                stop_reason: "rate_limit".into(),
+                until: timestamp,
+                reason: "maintenance window".into(),
+            },
+            DomainEvent::ResumeStarted {
+                timestamp,
+                session_path: path.clone(),
+                strategy: "same_session".into(),
+                progress: None,
+                percent: None,
+            },
+            DomainEvent::ResumeSucceeded {
+                timestamp,
+                session_path: path.clone(),
+                strategy: "same_session".into(),
+                wait_time_secs: 30,
+                progress: None,
+                percent: None,
+                git: None,
+            },
+            DomainEvent::ResumeFailed {
+                timestamp,
+                session_path: path.clone(),
+                strategy: "new_session".into(),
+                error: "boom".into(),
+                progress: None,
+                percent: None,
+                incident: Some("/state/incidents/x.tail.txt".into()),
+            },
+            DomainEvent::ResumeAbandoned {
+                timestamp,
+                session_path: path.clone(),
+                attempts: 10,
+                reason: "retry limit".into(),
+            },
+            DomainEvent::ResumeLoopSuspected {
+                timestamp,
+                session_path: path.clone(),
+                resumes: 5,
+                span_secs: 600,
+            },
+            DomainEvent::ResumeSidecarInvalid {
+                timestamp,
+                session_path: path.clone(),
+                sidecar_path: "/work/session.resume.toml".into(),
+                error: "bad toml".into(),
+            },
+            DomainEvent::ActionObserved {
+                timestamp,
+                session_path: path.clone(),
+                stop_reason: "context_exhausted".into(),
+                action: "new_session resume".into(),
+            },
+            DomainEvent::SessionCreated {
+                timestamp,
+                session_path: "/work/next.md".into(),
+                source_session: path.clone(),
+            },
+            DomainEvent::SessionStateChanged {
+                timestamp,
+                session_path: path.clone(),
+                from: "active".into(),
+                to: "needs_attention".into(),
+            },
+            DomainEvent::SessionCompleted {
+                timestamp,
+                session_path: path.clone(),
+                duration_secs: Some(3600),
+                resumes: 2,
+                time_saved_seconds: 600.0,
+            },
+            DomainEvent::SessionBackedUp {
+                timestamp,
+                session_path: path.clone(),
+                backup_path: "/work/session.md.bak".into(),
+            },
+            DomainEvent::SessionDeleted {
+                timestamp,
+                session_path: path.clone(),
+                restored_from: Some("/work/session.md.bak".into()),
+            },
+            DomainEvent::SessionOrphaned {
+                timestamp,
+                session_path: "/work/next.md".into(),
+                source_session: path.clone(),
+                reason: "state store error".into(),
+            },
+            DomainEvent::ModelChanged {
+                timestamp,
+                session_path: path.clone(),
+                previous_model: "a".into(),
+                current_model: "b".into(),
+            },
+            DomainEvent::NextStepInvalid {
+                timestamp,
+                session_path: path.clone(),
+                next_step_path: "/work/Next-step.md".into(),
+                unmatched_lines: vec!["???".into()],
+            },
+            DomainEvent::WorktreeCreated {
+                timestamp,
+                session_path: path.clone(),
+                workspace: "/work".into(),
+                worktree_path: "/work-wt".into(),
+                branch: "palingenesis/session".into(),
+            },
+            DomainEvent::PathRejected {
+                timestamp,
+                path: "/etc/passwd".into(),
+                reason: "outside session dir".into(),
+            },
+            DomainEvent::DaemonStarted {
+                timestamp,
+                version: "1.0.0".into(),
+            },
+            DomainEvent::DaemonStopped {
+                timestamp,
+                reason: "signal".into(),
+            },
+            DomainEvent::ControlApplied {
+                timestamp,
+                action: "pause".into(),
+                initiator: Initiator::Slack {
+                    user_id: "U123".into(),
+                },
+            },
+            DomainEvent::ModeChanged {
+                timestamp,
+                previous: "active".into(),
+                current: "observe".into(),
+                initiator: Some(Initiator::Cli {
+                    uid: Some(1000),
+                    tty: None,
+                }),
+            },
+            DomainEvent::ConfigReloaded {
+                timestamp,
+                sections: vec!["resume".into()],
+            },
+            DomainEvent::SystemResumed {
+                timestamp,
+                slept_secs: 60,
+            },
+            DomainEvent::ClockSkewDetected {
+                timestamp,
+                skew_secs: 90.0,
+            },
+            DomainEvent::FileEncodingFixed {
+                timestamp,
+                file: "/tmp/session.md".into(),
+                encoding: "UTF-16LE".into(),
+            },
+            DomainEvent::ScheduledRun {
+                timestamp,
+                schedule: "nightly".into(),
+                trigger: "scheduled".into(),
+                outcome: "succeeded".into(),
+                workdir: "/work".into(),
+                session_path: Some(path.clone()),
+                detail: None,
+                initiator: None,
+            },
+            DomainEvent::StateReconciled {
+                timestamp,
+                corrections: vec!["Incident inc-1 closed".into()],
+                report: "/tmp/reconcile-report.json".into(),
+            },
+            DomainEvent::StateCorrected {
+                timestamp,
+                correction: "incident_closed".into(),
+                detail: "Incident inc-1 closed".into(),
+                session_path: Some(path.clone()),
+                incident_id: Some("inc-1".into()),
+                note: Some("closed on reconciliation".into()),
+            },
+            DomainEvent::ArtifactGcPending {
+                timestamp,
+                artifacts: 3,
+                bytes: 4096,
+                summary: vec!["backups: 3 (4.0 KB)".into()],
+                plan: "/tmp/gc-plan.json".into(),
+            },
+            DomainEvent::ArtifactDeleted {
+                timestamp,
+                artifact: "backup".into(),
+                path: "/work/session-backup-20260101-000000.md".into(),
+                bytes: 4096,
+                modified: timestamp,
+                session_path: Some(path.clone()),
+            },
+            DomainEvent::StateReadOnly {
+                timestamp,
+                state_file: "/tmp/state.json".into(),
+                found_version: 2,
+                supported_version: 1,
+            },
+            DomainEvent::OpenCodeStarted { timestamp, pid: 42 },
+            DomainEvent::OpenCodeStopped {
+                timestamp,
+                pid: 42,
+                exit_code: Some(1),
+                crashed: true,
+            },
+            DomainEvent::ServerDownWindowOpened {
+                timestamp,
+                pid: 42,
+                exit_code: 1,
+            },
+            DomainEvent::ServerDownWindowClosed {
+                timestamp,
+                pid: 42,
+                restarted: true,
+                down_secs: 8,
+            },
+            DomainEvent::StopHeld {
+                timestamp,
+                session_path: path.clone(),
+                stop_reason: "rate_limit".into(),
+                pid: 42,
+            },
+            DomainEvent::HeldStopReleased {
+                timestamp,
+                session_path: path.clone(),
+                stop_reason: "rate_limit".into(),
+                outcome: "avoided".into(),
+                held_secs: 12,
+            },
+            DomainEvent::OpenCodeVersionChanged {
+                timestamp,
+                previous: None,
+                current: "1.2.3".into(),
+                problems: Vec::new(),
+            },
+            DomainEvent::Degraded {
+                timestamp,
+                component: "http".into(),
+                message: "address in use".into(),
+            },
+            DomainEvent::TestNotification {
+                timestamp,
+                message: "hello".into(),
+            },
+            DomainEvent::UnexpectedExit {
+                timestamp,
+                pid: 4242,
+                cause: "panic".into(),
+                detail: "index out of bounds".into(),
+            },
+        ];
+        for event in &all {
+            match event {
+                DomainEvent::StopDetected { .. }
+                | DomainEvent::StopClassified { .. }
+                | DomainEvent::WaitStarted { .. }
+                | DomainEvent::WaitEnded { .. }
+                | DomainEvent::ResumeDeferred { .. }
+                | DomainEvent::ResumeStarted { .. }
+                | DomainEvent::ResumeSucceeded { .. }
+                | DomainEvent::ResumeFailed { .. }
+                | DomainEvent::ResumeAbandoned { .. }
+                | DomainEvent::ResumeLoopSuspected { .. }
+                | DomainEvent::ResumeSidecarInvalid { .. }
+                | DomainEvent::ActionObserved { .. }
+                | DomainEvent::SessionCreated { .. }
+                | DomainEvent::SessionStateChanged { .. }
+                | DomainEvent::SessionCompleted { .. }
+                | DomainEvent::SessionBackedUp { .. }
+                | DomainEvent::SessionDeleted { .. }
+                | DomainEvent::SessionOrphaned { .. }
+                | DomainEvent::ModelChanged { .. }
+                | DomainEvent::NextStepInvalid { .. }
+                | DomainEvent::WorktreeCreated { .. }
+                | DomainEvent::PathRejected { .. }
+                | DomainEvent::DaemonStarted { .. }
+                | DomainEvent::DaemonStopped { .. }
+                | DomainEvent::ModeChanged { .. }
+                | DomainEvent::ControlApplied { .. }
+                | DomainEvent::ConfigReloaded { .. }
+                | DomainEvent::SystemResumed { .. }
+                | DomainEvent::ClockSkewDetected { .. }
+                | DomainEvent::FileEncodingFixed { .. }
+                | DomainEvent::ScheduledRun { .. }
+                | DomainEvent::StateReconciled { .. }
+                | DomainEvent::StateCorrected { .. }
+                | DomainEvent::ArtifactGcPending { .. }
+                | DomainEvent::ArtifactDeleted { .. }
+                | DomainEvent::StateReadOnly { .. }
+                | DomainEvent::OpenCodeStarted { .. }
+                | DomainEvent::OpenCodeStopped { .. }
+                | DomainEvent::ServerDownWindowOpened { .. }
+                | DomainEvent::ServerDownWindowClosed { .. }
+                | DomainEvent::StopHeld { .. }
+                | DomainEvent::HeldStopReleased { .. }
+                | DomainEvent::OpenCodeVersionChanged { .. }
+                | DomainEvent::Degraded { .. }
+                | DomainEvent::TestNotification { .. }
+                | DomainEvent::UnexpectedExit { .. } => {}
+            }
+        }
+        all
+    }
+
+    fn name(event: &DomainEvent) -> String {
+        serde_json::to_value(event).unwrap()["event"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn every_event_maps_for_each_consumer() {
+        let samples = samples();
+        let mut names: Vec<String> = samples.iter().map(name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), samples.len(), "one sample per variant");
+
+        for event in &samples {
+            let name = name(event);
+            let notification = event.notification().map(|n| n.event_type());
+            let audit = event.audit_entry().map(|entry| entry.event_type);
+            let expected: (Option<&str>, Option<AuditEventType>) = match name.as_str() {
+                "stop_detected" => (Some("session_stopped"), None),
+                "stop_classified" | "wait_started" | "wait_ended" => (None, None),
+                "resume_deferred" => (Some("resume_deferred"), None),
+                "resume_started" => (
+                    Some("resume_attempted"),
+                    Some(AuditEventType::ResumeStarted),
+                ),
+                "resume_succeeded" => (
+                    Some("resume_succeeded"),
+                    Some(AuditEventType::ResumeCompleted),
+                ),
+                "resume_failed" => (Some("resume_failed"), Some(AuditEventType::ResumeFailed)),
+                "resume_abandoned" => (None, Some(AuditEventType::ResumeFailed)),
+                "resume_loop_suspected" => (Some("resume_loop_suspected"), None),
+                "resume_sidecar_invalid" => (Some("resume_sidecar_invalid"), None),
+                "action_observed" => (
+                    Some("action_observed"),
+                    Some(AuditEventType::ActionObserved),
+                ),
+                "session_created" => (None, Some(AuditEventType::SessionCreated)),
+                "session_state_changed" => (None, Some(AuditEventType::StateChanged)),
+                "session_completed" => (
+                    Some("session_completed"),
+                    Some(AuditEventType::SessionCompleted),
+                ),
+                "session_backed_up" => (None, Some(AuditEventType::SessionBackedUp)),
+                "session_deleted" => (
+                    Some("session_deleted"),
+                    Some(AuditEventType::SessionRestored),
+                ),
+                "session_orphaned" => (Some("session_orphaned"), None),
+                "model_changed" => (Some("model_changed"), Some(AuditEventType::ModelMismatch)),
+                "next_step_invalid" => (Some("next_step_invalid"), None),
+                "worktree_created" => (Some("worktree_created"), None),
+                "path_rejected" => (None, Some(AuditEventType::PathRejected)),
+                "daemon_started" => (Some("daemon_started"), Some(AuditEventType::DaemonStarted)),
+                "daemon_stopped" => (Some("daemon_stopped"), Some(AuditEventType::DaemonStopped)),
+                "mode_changed" => (None, Some(AuditEventType::ModeChanged)),
+                "control_applied" => (Some("control_applied"), Some(AuditEventType::ControlAction)),
+                "config_reloaded" => (None, Some(AuditEventType::ConfigChanged)),
+                "system_resumed" => (Some("system_resumed"), None),
+                "clock_skew_detected" => (Some("clock_skew_detected"), None),
+                "file_encoding_fixed" => (Some("file_encoding_fixed"), None),
+                "scheduled_run" => (Some("scheduled_run"), Some(AuditEventType::ScheduledRun)),
+                "state_reconciled" => (Some("state_reconciled"), None),
+                "state_corrected" => (None, Some(AuditEventType::StateReconciled)),
+                "artifact_gc_pending" => (Some("artifact_gc_pending"), None),
+                "artifact_deleted" => (None, Some(AuditEventType::ArtifactDeleted)),
+                "state_read_only" => (Some("state_read_only"), None),
+                "opencode_started" | "opencode_stopped" => (None, None),
+                "server_down_window_opened"
+                | "server_down_window_closed"
+                | "stop_held"
+                | "held_stop_released" => (None, Some(AuditEventType::RestartCorrelation)),
+                "opencode_version_changed" => (Some("opencode_version_changed"), None),
+                "degraded" => (None, Some(AuditEventType::Error)),
+                "test_notification" => (Some("test_notification"), None),
+                "unexpected_exit" => (Some("unexpected_exit"), Some(AuditEventType::Error)),
+                other => panic!("no expectation for {other}"),
+            };
+            assert_eq!((notification, audit), expected, "{name}");
+            if let Some(notification) = event.notification() {
+                assert_eq!(notification.timestamp(), event.timestamp());
+            }
+            if let Some(entry) = event.audit_entry() {
+                assert_eq!(entry.timestamp, event.timestamp());
+            }
+        }
+    }
+
+    #[test]
+    fn events_round_trip_through_serde() {
+        for event in samples() {
+            let json = serde_json::to_string(&event).unwrap();
+            let back: DomainEvent = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, event);
+        }
+    }
+
+    #[test]
+    fn audit_entries_keep_the_existing_wording() {
+        let entry = DomainEvent::ActionObserved {
+            timestamp: ts(),
+            session_path: "/work/session.md".into(),
+            stop_reason: "context_exhausted".into(),
+            action: "new_session resume".into(),
+        }
+        .audit_entry()
+        .unwrap();
+        assert_eq!(entry.action_taken, "OBSERVE: would run new_session resume");
+        assert_eq!(entry.outcome, AuditOutcome::Skipped);
+        assert_eq!(entry.stop_reason.as_deref(), Some("context_exhausted"));
+
+        let deleted = DomainEvent::SessionDeleted {
+            timestamp: ts(),
+            session_path: "/work/session.md".into(),
+            restored_from: None,
+        };
+        assert!(deleted.audit_entry().is_none(), "only restores are audited");
+    }
+
+    #[test]
+    fn control_actions_audit_their_initiator() {
+        let entry = DomainEvent::ControlApplied {
+            timestamp: ts(),
+            action: "pause".into(),
+            initiator: Initiator::Slack {
+                user_id: "U123".into(),
+            },
+        }
+        .audit_entry()
+        .unwrap();
+        assert_eq!(entry.action_taken, "Daemon paused by @U123 via Slack");
+        assert_eq!(entry.metadata["action"], "pause");
+        assert_eq!(
+            entry.metadata["initiator"],
+            serde_json::json!({"via": "slack", "user_id": "U123"})
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! gRPC control API (enabled with the `grpc` feature).
+//!
+//! Every RPC delegates to the same [`DaemonStateAccess`] operations, event
+//! broadcaster, and audit trail that back the IPC socket and HTTP API.
+//!
+//! [`DaemonStateAccess`]: crate::ipc::socket::DaemonStateAccess
+
+pub mod server;
+pub mod service;
+
+/// Generated from `proto/palingenesis/v1/control.proto`.
+pub mod proto {
+    tonic::include_proto!("palingenesis.v1");
+}
+
+pub use server::{GrpcError, GrpcServer};
+pub use service::ControlService;
@@ -0,0 +1,202 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tracing::{info, warn};
+
+use crate::config::schema::GrpcConfig;
+use crate::grpc::proto::daemon_control_server::DaemonControlServer;
+use crate::grpc::service::ControlService;
+use crate::http::EventBroadcaster;
+use crate::ipc::socket::DaemonStateAccess;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GrpcError {
+    #[error("Invalid gRPC bind address {bind}: {source}")]
+    InvalidBind {
+        bind: String,
+        source: std::net::AddrParseError,
+    },
+
+    #[error("Failed to read gRPC TLS file {path}: {source}")]
+    Tls {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to bind gRPC API to {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        source: std::io::Error,
+    },
+
+    #[error("gRPC transport error: {0}")]
+    Transport(#[from] tonic::transport::Error),
+}
+
+/// gRPC control API server.
+pub struct GrpcServer<S> {
+    bind_addr: SocketAddr,
+    tls: Option<ServerTlsConfig>,
+    service: ControlService<S>,
+    shutdown: CancellationToken,
+}
+
+impl<S: DaemonStateAccess + 'static> GrpcServer<S> {
+    /// Create a server from `[daemon.grpc]`, or `None` when it is disabled.
+    pub fn from_config(
+        config: &GrpcConfig,
+        shutdown: CancellationToken,
+        state: Arc<S>,
+        events: EventBroadcaster,
+    ) -> Result<Option<Self>, GrpcError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(
+            config,
+            shutdown,
+            ControlService::new(state, events),
+        )?))
+    }
+
+    pub fn new(
+        config: &GrpcConfig,
+        shutdown: CancellationToken,
+        service: ControlService<S>,
+    ) -> Result<Self, GrpcError> {
+        let bind_addr: SocketAddr =
+            config
+                .bind
+                .parse()
+                .map_err(|source| GrpcError::InvalidBind {
+                    bind: config.bind.clone(),
+                    source,
+                })?;
+        if bind_addr.ip().is_unspecified() && config.tls_cert.is_none() {
+            warn!(
+                address = %bind_addr,
+                "gRPC API binding to all interfaces without TLS. This exposes daemon control to the network."
+            );
+        }
+
+        Ok(Self {
+            bind_addr,
+            tls: tls_config(config)?,
+            service,
+            shutdown,
+        })
+    }
+
+    pub fn bind_addr(&self) -> SocketAddr {
+        self.bind_addr
+    }
+
+    /// Bind the configured address and serve until shutdown.
+    pub async fn serve(self) -> Result<(), GrpcError> {
+        let listener =
+            TcpListener::bind(self.bind_addr)
+                .await
+                .map_err(|source| GrpcError::Bind {
+                    addr: self.bind_addr,
+                    source,
+                })?;
+        self.serve_with_listener(listener).await
+    }
+
+    /// Serve on an already bound listener until shutdown.
+    pub async fn serve_with_listener(self, listener: TcpListener) -> Result<(), GrpcError> {
+        if let Ok(addr) = listener.local_addr() {
+            info!(address = %addr, tls = self.tls.is_some(), "gRPC API server listening");
+        }
+
+        let mut builder = Server::builder();
+        if let Some(tls) = self.tls {
+            builder = builder.tls_config(tls)?;
+        }
+        builder
+            .add_service(DaemonControlServer::new(self.service))
+            .serve_with_incoming_shutdown(
+                TcpListenerStream::new(listener),
+                self.shutdown.cancelled_owned(),
+            )
+            .await?;
+
+        info!("gRPC API server stopped");
+        Ok(())
+    }
+}
+
+fn tls_config(config: &GrpcConfig) -> Result<Option<ServerTlsConfig>, GrpcError> {
+    let (Some(cert), Some(key)) = (&config.tls_cert, &config.tls_key) else {
+        return Ok(None);
+    };
+    let identity = Identity::from_pem(read_pem(cert)?, read_pem(key)?);
+    let mut tls = ServerTlsConfig::new().identity(identity);
+    if let Some(ca) = &config.tls_ca {
+        tls = tls.client_ca_root(Certificate::from_pem(read_pem(ca)?));
+    }
+    Ok(Some(tls))
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, GrpcError> {
+    std::fs::read(path).map_err(|source| GrpcError::Tls {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::state::DaemonState;
+
+    fn config(bind: &str) -> GrpcConfig {
+        GrpcConfig {
+            enabled: true,
+            bind: bind.to_string(),
+            ..GrpcConfig::default()
+        }
+    }
+
+    #[test]
+    fn disabled_config_yields_no_server() {
+        let server = GrpcServer::from_config(
+            &GrpcConfig::default(),
+            CancellationToken::new(),
+            Arc::new(DaemonState::new_without_auto_detection()),
+            EventBroadcaster::new(8),
+        )
+        .unwrap();
+        assert!(server.is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_bind() {
+        let result = GrpcServer::from_config(
+            &config("localhost"),
+            CancellationToken::new(),
+            Arc::new(DaemonState::new_without_auto_detection()),
+            EventBroadcaster::new(8),
+        );
+        assert!(matches!(result, Err(GrpcError::InvalidBind { .. })));
+    }
+
+    #[test]
+    fn missing_tls_file_is_reported() {
+        let mut config = config("127.0.0.1:0");
+        config.tls_cert = Some("/nonexistent/cert.pem".into());
+        config.tls_key = Some("/nonexistent/key.pem".into());
+        let result = GrpcServer::from_config(
+            &config,
+            CancellationToken::new(),
+            Arc::new(DaemonState::new_without_auto_detection()),
+            EventBroadcaster::new(8),
+        );
+        assert!(matches!(result, Err(GrpcError::Tls { .. })));
+    }
+}
@@ -0,0 +1,186 @@
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::warn;
+
+use crate::config::Paths;
+use crate::daemon::initiator::Initiator;
+use crate::grpc::proto;
+use crate::grpc::proto::daemon_control_server::DaemonControl;
+use crate::http::EventBroadcaster;
+use crate::ipc::protocol::DaemonStatus;
+use crate::ipc::socket::DaemonStateAccess;
+use crate::notify::events::NotificationEvent;
+use crate::state::audit::{AuditEntry, AuditLogger};
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<proto::Event, Status>> + Send>>;
+
+/// `DaemonControl` implementation backed by daemon state.
+pub struct ControlService<S> {
+    state: Arc<S>,
+    events: EventBroadcaster,
+    audit: AuditLogger,
+}
+
+impl<S: DaemonStateAccess + 'static> ControlService<S> {
+    pub fn new(state: Arc<S>, events: EventBroadcaster) -> Self {
+        Self {
+            state,
+            events,
+            audit: AuditLogger::new(&Paths::state_dir()),
+        }
+    }
+
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = audit;
+        self
+    }
+}
+
+#[tonic::async_trait]
+impl<S: DaemonStateAccess + 'static> DaemonControl for ControlService<S> {
+    async fn get_status(
+        &self,
+        _request: Request<proto::GetStatusRequest>,
+    ) -> Result<Response<proto::StatusResponse>, Status> {
+        Ok(Response::new(status_response(self.state.get_status())))
+    }
+
+    async fn pause(
+        &self,
+        request: Request<proto::PauseRequest>,
+    ) -> Result<Response<proto::ControlResponse>, Status> {
+        control_response(self.state.pause_by(&initiator(&request)))
+    }
+
+    async fn resume(
+        &self,
+        request: Request<proto::ResumeRequest>,
+    ) -> Result<Response<proto::ControlResponse>, Status> {
+        control_response(self.state.resume_by(&initiator(&request)))
+    }
+
+    async fn reload(
+        &self,
+        _request: Request<proto::ReloadRequest>,
+    ) -> Result<Response<proto::ControlResponse>, Status> {
+        self.state
+            .reload()
+            .await
+            .map(|_| Response::new(proto::ControlResponse { success: true }))
+            .map_err(Status::internal)
+    }
+
+    async fn trigger_resume(
+        &self,
+        request: Request<proto::TriggerResumeRequest>,
+    ) -> Result<Response<proto::ControlResponse>, Status> {
+        control_response(self.state.new_session_by(&initiator(&request)))
+    }
+
+    type StreamEventsStream = EventStream;
+
+    async fn stream_events(
+        &self,
+        _request: Request<proto::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let stream =
+            BroadcastStream::new(self.events.subscribe()).filter_map(|message| match message {
+                Ok(event) => Some(Ok(event_message(&event))),
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    warn!(
+                        skipped,
+                        "gRPC event subscriber lagged behind broadcast channel"
+                    );
+                    None
+                }
+            });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn get_history(
+        &self,
+        request: Request<proto::GetHistoryRequest>,
+    ) -> Result<Response<proto::HistoryResponse>, Status> {
+        let request = request.into_inner();
+        let mut query = self.audit.query();
+        if let Some(path) = request.session_path {
+            query = query.for_session(path.into());
+        }
+        let mut entries = query
+            .execute()
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        let limit = request.limit as usize;
+        if limit > 0 && entries.len() > limit {
+            entries.drain(..entries.len() - limit);
+        }
+
+        Ok(Response::new(proto::HistoryResponse {
+            entries: entries.iter().map(history_entry).collect(),
+        }))
+    }
+}
+
+/// The gRPC peer behind `request`.
+fn initiator<T>(request: &Request<T>) -> Initiator {
+    Initiator::Grpc {
+        remote_addr: request.remote_addr().map(|addr| addr.to_string()),
+    }
+}
+
+fn control_response(
+    result: Result<(), String>,
+) -> Result<Response<proto::ControlResponse>, Status> {
+    result
+        .map(|()| Response::new(proto::ControlResponse { success: true }))
+        .map_err(Status::failed_precondition)
+}
+
+fn status_response(status: DaemonStatus) -> proto::StatusResponse {
+    proto::StatusResponse {
+        state: status.state,
+        uptime_secs: status.uptime_secs,
+        current_session: status.current_session,
+        saves_count: status.saves_count,
+        total_resumes: status.total_resumes,
+        time_saved_seconds: status.time_saved_seconds,
+        exclusion_patterns: status.exclusion_patterns,
+    }
+}
+
+fn event_message(event: &NotificationEvent) -> proto::Event {
+    proto::Event {
+        event_type: event.event_type().to_string(),
+        severity: event.severity().as_str().to_string(),
+        timestamp: event.timestamp().to_rfc3339(),
+        payload_json: serde_json::to_string(event).unwrap_or_default(),
+    }
+}
+
+fn history_entry(entry: &AuditEntry) -> proto::HistoryEntry {
+    proto::HistoryEntry {
+        timestamp: entry.timestamp.to_rfc3339(),
+        event_type: serde_label(&entry.event_type),
+        session_path: entry
+            .session_path
+            .as_ref()
+            .map(|path| path.display().to_string()),
+        stop_reason: entry.stop_reason.clone(),
+        action_taken: entry.action_taken.clone(),
+        outcome: serde_label(&entry.outcome),
+        metadata_json: serde_json::to_string(&entry.metadata).unwrap_or_default(),
+    }
+}
+
+/// Serialized name of a unit enum variant (e.g. `resume_started`).
+fn serde_label<T: serde::Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(label)) => label,
+        _ => "unknown".to_string(),
+    }
+}
@@ -0,0 +1,253 @@
+//! Bearer token checks for the HTTP API (`daemon.http_token` and
+//! `[[daemon.http_tokens]]`).
+//!
+//! [`require_bearer`] resolves the presented token to a [`Grant`]; routes
+//! then name the scope they need with [`scoped`]. `/health` stays open for
+//! load balancers and supervisors, the bot webhooks verify their own
+//! platform signatures instead, and ack links carry their own single-use
+//! token.
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{HeaderValue, Request, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::MethodRouter;
+use serde_json::json;
+
+use crate::config::schema::{DaemonConfig, HttpScope, HttpTokenConfig};
+
+/// Paths served without a token.
+const PUBLIC_PATHS: &[&str] = &["/health"];
+/// Path prefixes that authenticate requests themselves.
+const SELF_AUTHENTICATED_PREFIXES: &[&str] = &["/api/v1/bot/", "/api/v1/ack/"];
+
+/// What an accepted token may do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    /// The token's name; `None` for the unnamed `daemon.http_token`.
+    pub name: Option<String>,
+    pub scopes: Vec<HttpScope>,
+}
+
+impl Grant {
+    pub fn allows(&self, required: HttpScope) -> bool {
+        self.scopes.iter().any(|scope| scope.grants(required))
+    }
+}
+
+struct ApiToken {
+    secret: String,
+    grant: Grant,
+}
+
+/// The tokens the API accepts. Empty means the API is unauthenticated.
+#[derive(Clone, Default)]
+pub struct TokenSet {
+    tokens: Vec<Arc<ApiToken>>,
+}
+
+impl TokenSet {
+    /// `daemon.http_token` with every scope, then the named tokens.
+    pub fn from_config(config: &DaemonConfig) -> Self {
+        Self::default()
+            .with_token(config.http_token.clone())
+            .with_named_tokens(&config.http_tokens)
+    }
+
+    /// Accept `token` with every scope.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        if let Some(secret) = token.filter(|token| !token.is_empty()) {
+            self.tokens.push(Arc::new(ApiToken {
+                secret,
+                grant: Grant {
+                    name: None,
+                    scopes: vec![HttpScope::Admin],
+                },
+            }));
+        }
+        self
+    }
+
+    /// Accept each named token with its own scopes.
+    pub fn with_named_tokens(mut self, tokens: &[HttpTokenConfig]) -> Self {
+        self.tokens.extend(
+            tokens
+                .iter()
+                .filter(|token| !token.token.is_empty())
+                .map(|token| {
+                    Arc::new(ApiToken {
+                        secret: token.token.clone(),
+                        grant: Grant {
+                            name: Some(token.name.clone()),
+                            scopes: token.scopes.clone(),
+                        },
+                    })
+                }),
+        );
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// The grant of the token matching `presented`. Every candidate is
+    /// compared in constant time, so the answer does not reveal which one
+    /// came close.
+    pub fn resolve(&self, presented: &str) -> Option<Grant> {
+        let mut matched = None;
+        for token in &self.tokens {
+            if constant_time_eq(presented.as_bytes(), token.secret.as_bytes()) && matched.is_none()
+            {
+                matched = Some(&token.grant);
+            }
+        }
+        matched.cloned()
+    }
+}
+
+/// Reject requests that do not carry `Authorization: Bearer <token>` with a
+/// known token, and attach the token's [`Grant`] to the rest.
+pub async fn require_bearer(
+    State(tokens): State<Arc<TokenSet>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if PUBLIC_PATHS.contains(&path)
+        || SELF_AUTHENTICATED_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    {
+        return next.run(request).await;
+    }
+
+    let grant = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|presented| tokens.resolve(presented));
+    match grant {
+        Some(grant) => {
+            request.extensions_mut().insert(grant);
+            next.run(request).await
+        }
+        None => unauthorized(),
+    }
+}
+
+/// Require `scope` for `route`. Requests without a [`Grant`] only reach it
+/// when the API has no tokens, so they pass.
+pub fn scoped<S>(scope: HttpScope, route: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    route.route_layer(middleware::from_fn_with_state(scope, require_scope))
+}
+
+async fn require_scope(
+    State(scope): State<HttpScope>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    match request.extensions().get::<Grant>() {
+        Some(grant) if !grant.allows(scope) => forbidden(scope),
+        _ => next.run(request).await,
+    }
+}
+
+fn unauthorized() -> Response {
+    let mut response = (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({
+            "success": false,
+            "error": {
+                "code": "UNAUTHORIZED",
+                "message": "Missing or invalid bearer token"
+            }
+        })),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+fn forbidden(scope: HttpScope) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "success": false,
+            "error": {
+                "code": "FORBIDDEN",
+                "message": format!("This token lacks the {scope} scope"),
+                "required_scope": scope.as_str(),
+            }
+        })),
+    )
+        .into_response()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (&x, &y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, token: &str, scopes: &[HttpScope]) -> HttpTokenConfig {
+        HttpTokenConfig {
+            name: name.to_string(),
+            token: token.to_string(),
+            scopes: scopes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn tokens_resolve_to_their_own_scopes() {
+        let tokens = TokenSet::default()
+            .with_token(Some("legacy".to_string()))
+            .with_named_tokens(&[
+                named("dashboard", "dash", &[HttpScope::Read]),
+                named("ops", "ops", &[HttpScope::Read, HttpScope::Control]),
+            ]);
+
+        let dashboard = tokens.resolve("dash").unwrap();
+        assert_eq!(dashboard.name.as_deref(), Some("dashboard"));
+        assert!(dashboard.allows(HttpScope::Read));
+        assert!(!dashboard.allows(HttpScope::Control));
+
+        let ops = tokens.resolve("ops").unwrap();
+        assert!(ops.allows(HttpScope::Control));
+        assert!(!ops.allows(HttpScope::Resume));
+
+        let legacy = tokens.resolve("legacy").unwrap();
+        assert_eq!(legacy.name, None);
+        assert!(HttpScope::ALL.iter().all(|scope| legacy.allows(*scope)));
+
+        assert_eq!(tokens.resolve("unknown"), None);
+        assert_eq!(tokens.resolve(""), None);
+    }
+
+    #[test]
+    fn empty_tokens_are_ignored() {
+        let tokens = TokenSet::default()
+            .with_token(Some(String::new()))
+            .with_named_tokens(&[named("blank", "", &[HttpScope::Admin])]);
+        assert!(tokens.is_empty());
+    }
+}
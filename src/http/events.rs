@@ -2,16 +2,24 @@ use std::sync::{Arc, RwLock};
 
 use chrono::{DateTime, Utc};
 use tokio::sync::broadcast;
+use tracing::debug;
 
+use crate::events::DomainEvent;
 use crate::notify::events::NotificationEvent;
+use crate::util::content::{self, RetainedContent};
 
 const DEFAULT_CAPACITY: usize = 1024;
 
 /// Broadcasts daemon events to multiple SSE subscribers.
+///
+/// The channel keeps the last `capacity` events for lagging subscribers, so
+/// free text is capped at `daemon.max_retained_content_chars` on the way in.
 #[derive(Clone, Debug)]
 pub struct EventBroadcaster {
     sender: broadcast::Sender<NotificationEvent>,
     last_event: Arc<RwLock<Option<DateTime<Utc>>>>,
+    content_limit: Option<usize>,
+    retained: Arc<RetainedContent>,
 }
 
 impl EventBroadcaster {
@@ -22,9 +30,18 @@ impl EventBroadcaster {
         Self {
             sender,
             last_event: Arc::new(RwLock::new(None)),
+            content_limit: None,
+            // The channel rounds its buffer up to a power of two.
+            retained: Arc::new(RetainedContent::new("events", capacity.next_power_of_two())),
         }
     }
 
+    /// Cap event free text at `max_chars` instead of the configured limit.
+    pub fn with_content_limit(mut self, max_chars: usize) -> Self {
+        self.content_limit = Some(max_chars);
+        self
+    }
+
     /// Subscribe to notification events.
     pub fn subscribe(&self) -> broadcast::Receiver<NotificationEvent> {
         self.sender.subscribe()
@@ -33,17 +50,44 @@ impl EventBroadcaster {
     /// Send a notification event to all subscribers.
     pub fn send(
         &self,
-        event: NotificationEvent,
-    ) -> Result<usize, broadcast::error::SendError<NotificationEvent>> {
+        mut event: NotificationEvent,
+    ) -> Result<usize, broadcast::error::SendError<Box<NotificationEvent>>> {
         if let Ok(mut guard) = self.last_event.write() {
             *guard = Some(event.timestamp());
         }
-        self.sender.send(event)
+        event.bound_content(self.content_limit.unwrap_or_else(content::retained_chars));
+        let bytes = event.content_bytes();
+        let sent = self
+            .sender
+            .send(event)
+            .map_err(|err| broadcast::error::SendError(Box::new(err.0)))?;
+        // Without subscribers nothing is buffered.
+        self.retained.push(bytes);
+        Ok(sent)
+    }
+
+    /// Send the notification form of `event`, if it has one.
+    ///
+    /// Returns the number of subscribers reached; having none is not an error.
+    pub fn publish(&self, event: DomainEvent) -> usize {
+        let Some(notification) = event.notification() else {
+            return 0;
+        };
+        let event_type = notification.event_type();
+        self.send(notification).unwrap_or_else(|_| {
+            debug!(event_type, "No subscribers for event");
+            0
+        })
     }
 
     pub fn last_event_timestamp(&self) -> Option<DateTime<Utc>> {
         self.last_event.read().ok().and_then(|guard| *guard)
     }
+
+    /// Estimated free-text bytes held in the channel buffer.
+    pub fn retained_content_bytes(&self) -> usize {
+        self.retained.bytes()
+    }
 }
 
 impl Default for EventBroadcaster {
@@ -68,6 +112,7 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             stop_reason: "rate_limit".to_string(),
             details: None,
+            excluded_by: None,
         }
     }
 
@@ -100,6 +145,67 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_large_events_stay_within_content_bound() {
+        const CAPACITY: usize = 16;
+        const LIMIT: usize = 256;
+        let broadcaster = EventBroadcaster::new(CAPACITY).with_content_limit(LIMIT);
+        let mut receiver = broadcaster.subscribe();
+        let details = "x".repeat(1024 * 1024);
+
+        for _ in 0..500 {
+            let mut event = sample_event();
+            if let NotificationEvent::SessionStopped { details: d, .. } = &mut event {
+                *d = Some(details.clone());
+            }
+            broadcaster.send(event).expect("send event");
+        }
+
+        // One capped field per event, plus the cut marker.
+        let bound = CAPACITY * (LIMIT + 16);
+        assert!(broadcaster.retained_content_bytes() <= bound);
+        let Err(broadcast::error::RecvError::Lagged(_)) = receiver.recv().await else {
+            panic!("expected the receiver to lag");
+        };
+        match receiver.recv().await.expect("recv event") {
+            NotificationEvent::SessionStopped {
+                details: Some(details),
+                ..
+            } => assert!(details.len() <= LIMIT + 16),
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_sends_only_notifiable_events() {
+        let broadcaster = EventBroadcaster::new(8);
+        let mut receiver = broadcaster.subscribe();
+        let timestamp = chrono::Utc::now();
+
+        let reached = broadcaster.publish(DomainEvent::WaitEnded {
+            timestamp,
+            session_path: PathBuf::from("/tmp/session"),
+            waited_secs: 30,
+        });
+        assert_eq!(reached, 0);
+        let reached = broadcaster.publish(DomainEvent::DaemonStarted {
+            timestamp,
+            version: "1.0.0".to_string(),
+        });
+        assert_eq!(reached, 1);
+
+        let event = receiver.recv().await.expect("recv event");
+        assert_eq!(event.event_type(), "daemon_started");
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(
+            EventBroadcaster::new(1).publish(DomainEvent::DaemonStarted {
+                timestamp,
+                version: "1.0.0".to_string(),
+            }),
+            0
+        );
+    }
+
     #[test]
     fn test_last_event_timestamp_updates() {
         let broadcaster = EventBroadcaster::default();
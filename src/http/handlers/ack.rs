@@ -0,0 +1,64 @@
+//! `GET /api/v1/ack/<token>`: acknowledge an incident from a notification.
+//!
+//! The link is opened from a phone, so the token in the path is the only
+//! credential and the reply is a line of plain text rather than JSON.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+
+use crate::http::server::AppState;
+use crate::notify::AckError;
+
+pub async fn ack_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    match state.daemon_state().acknowledge_token(&token) {
+        Ok(incident) => (
+            StatusCode::OK,
+            format!(
+                "Acknowledged incident {} for {}. Further alerts are silenced until it resolves.\n",
+                incident.id,
+                incident.session_path.display()
+            ),
+        ),
+        Err(err @ AckError::Expired { .. }) => (StatusCode::GONE, format!("{err}\n")),
+        Err(err) => (StatusCode::NOT_FOUND, format!("{err}\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::daemon::state::DaemonState;
+    use crate::telemetry::Metrics;
+    use axum::Router;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_unknown_token_is_not_found() {
+        let router = Router::new()
+            .route("/api/v1/ack/{token}", get(ack_handler))
+            .with_state(AppState::new(
+                Arc::new(DaemonState::new()),
+                crate::http::EventBroadcaster::default(),
+                Arc::new(Metrics::new()),
+            ));
+
+        let response = router
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/v1/ack/not-a-token")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
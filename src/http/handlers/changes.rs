@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use axum::Json;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::http::handlers::control::error_response;
+use crate::state::{Changefeed, ChangesSince, StateHandle};
+
+/// Longest a request may wait for a change.
+pub const MAX_WAIT_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct ChangesQuery {
+    /// Return records with a larger sequence number.
+    #[serde(default)]
+    pub since_seq: u64,
+    /// Seconds to wait when there is nothing newer yet (at most 60).
+    #[serde(default)]
+    pub wait_secs: u64,
+}
+
+/// Handles GET /api/v1/changes: state changes after `?since_seq=N`,
+/// long-polling for up to `?wait_secs=S` when there are none yet.
+pub async fn changes_handler(Query(query): Query<ChangesQuery>) -> Response {
+    match StateHandle::global().and_then(|handle| handle.changefeed()) {
+        Some(changefeed) => changes_response(&changefeed, query).await,
+        None => error_response(
+            "CHANGEFEED_UNAVAILABLE",
+            "The daemon is not recording state changes",
+            StatusCode::SERVICE_UNAVAILABLE,
+        )
+        .into_response(),
+    }
+}
+
+/// Response for `query` against `changefeed`.
+pub async fn changes_response(changefeed: &Changefeed, query: ChangesQuery) -> Response {
+    let wait = Duration::from_secs(query.wait_secs.min(MAX_WAIT_SECS));
+    match changefeed.wait_since(query.since_seq, wait).await {
+        ChangesSince::Changes { changes, last_seq } => (
+            StatusCode::OK,
+            Json(json!({
+                "success": true,
+                "data": { "changes": changes, "last_seq": last_seq }
+            })),
+        )
+            .into_response(),
+        ChangesSince::Compacted { oldest_seq } => (
+            StatusCode::GONE,
+            Json(json!({
+                "success": false,
+                "error": {
+                    "code": "COMPACTED",
+                    "message": "Changes after since_seq were compacted; resnapshot from /api/v1/status and /api/v1/sessions",
+                    "oldest_seq": oldest_seq
+                }
+            })),
+        )
+            .into_response(),
+    }
+}
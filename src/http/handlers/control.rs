@@ -1,12 +1,22 @@
+use std::sync::Arc;
+
 use axum::Json;
-use axum::extract::State;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
+use axum::http::header::LOCATION;
 use axum::response::IntoResponse;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::config::schema::DaemonMode;
+use crate::daemon::control::{Admission, Deferral};
+use crate::daemon::initiator::Initiator;
+use crate::daemon::session_jobs::{NewSessionRequest, TrackedJob};
 use crate::daemon::state::DaemonState;
+use crate::http::initiator::RequestInitiator;
 use crate::http::server::AppState;
+use crate::ipc::protocol::IpcCommand;
 use crate::ipc::socket::DaemonStateAccess;
 #[cfg(test)]
 use crate::telemetry::Metrics;
@@ -32,25 +42,48 @@ impl ControlResponse {
     }
 }
 
-/// Success response payload for new-session endpoint (ARCH23 compliant).
+/// Response for a queued new-session job (ARCH23 compliant).
 ///
-/// Returns `{ "success": true, "session_id": "..." }` with a UUID identifier.
+/// Returns `{ "success": true, "job_id": "..." }` with 202 Accepted; the job
+/// is polled at `/api/v1/jobs/<job_id>`.
 #[derive(Debug, Serialize, PartialEq, Eq)]
-pub struct ControlResponseWithId {
+pub struct JobAcceptedResponse {
+    success: bool,
+    job_id: String,
+}
+
+/// Job status payload: `{ "success": true, "job": { ... } }`.
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
     success: bool,
-    /// Unique session identifier (UUID v4 format).
-    session_id: String,
+    job: TrackedJob,
 }
 
-impl ControlResponseWithId {
-    fn success(session_id: String) -> Self {
+/// Response for a command queued until the daemon reaches a safe point.
+///
+/// Returns `{ "success": true, "deferred": true, "message": "..." }` with 202 Accepted.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct DeferredResponse {
+    success: bool,
+    deferred: bool,
+    /// What the command waits for, e.g. "will apply after resume completes".
+    message: String,
+}
+
+impl DeferredResponse {
+    fn new(deferral: &Deferral) -> Self {
         Self {
             success: true,
-            session_id,
+            deferred: true,
+            message: deferral.to_string(),
         }
     }
 }
 
+fn deferred_response(deferral: &Deferral) -> axum::response::Response {
+    (StatusCode::ACCEPTED, Json(DeferredResponse::new(deferral))).into_response()
+}
+
 /// Error detail payload for control endpoint failures (ARCH23 compliant).
 ///
 /// Contains machine-readable `code` and human-readable `message`.
@@ -83,7 +116,7 @@ impl ControlErrorResponse {
     }
 }
 
-fn error_response(
+pub(crate) fn error_response(
     code: &str,
     message: &str,
     status: StatusCode,
@@ -108,8 +141,8 @@ impl ControlError {
     }
 }
 
-pub fn pause_daemon(daemon_state: &DaemonState) -> Result<(), ControlError> {
-    match daemon_state.pause() {
+pub fn pause_daemon(daemon_state: &DaemonState, initiator: &Initiator) -> Result<(), ControlError> {
+    match daemon_state.pause_by(initiator) {
         Ok(()) => Ok(()),
         Err(message) if message == error_messages::ALREADY_PAUSED => Err(ControlError::new(
             "ALREADY_PAUSED",
@@ -124,8 +157,11 @@ pub fn pause_daemon(daemon_state: &DaemonState) -> Result<(), ControlError> {
     }
 }
 
-pub fn resume_daemon(daemon_state: &DaemonState) -> Result<(), ControlError> {
-    match daemon_state.resume() {
+pub fn resume_daemon(
+    daemon_state: &DaemonState,
+    initiator: &Initiator,
+) -> Result<(), ControlError> {
+    match daemon_state.resume_by(initiator) {
         Ok(()) => Ok(()),
         Err(message) if message == error_messages::NOT_PAUSED => Err(ControlError::new(
             "NOT_PAUSED",
@@ -140,8 +176,11 @@ pub fn resume_daemon(daemon_state: &DaemonState) -> Result<(), ControlError> {
     }
 }
 
-pub fn new_session_daemon(daemon_state: &DaemonState) -> Result<String, ControlError> {
-    match daemon_state.new_session() {
+pub fn new_session_daemon(
+    daemon_state: &DaemonState,
+    initiator: &Initiator,
+) -> Result<String, ControlError> {
+    match daemon_state.new_session_by(initiator) {
         Ok(()) => Ok(Uuid::new_v4().to_string()),
         Err(message) => Err(ControlError::new(
             "SESSION_ERROR",
@@ -151,36 +190,180 @@ pub fn new_session_daemon(daemon_state: &DaemonState) -> Result<String, ControlE
     }
 }
 
+/// Validate `request`, record the new-session request and queue the session
+/// as a tracked job, returning its ID.
+pub fn submit_new_session(
+    daemon_state: &DaemonState,
+    request: NewSessionRequest,
+    initiator: &Initiator,
+) -> Result<String, ControlError> {
+    let session_dir = daemon_state
+        .monitoring_config()
+        .map(|monitoring| monitoring.session_dir)
+        .unwrap_or_default();
+    let workdir = request
+        .validate(&session_dir)
+        .map_err(|err| ControlError::new(err.code(), &err.to_string(), StatusCode::BAD_REQUEST))?;
+    daemon_state.new_session_by(initiator).map_err(|message| {
+        ControlError::new("SESSION_ERROR", &message, StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    Ok(daemon_state.submit_new_session_job(request, workdir))
+}
+
 /// Handles POST /api/v1/pause requests to pause daemon monitoring.
-pub async fn pause_handler(State(state): State<AppState>) -> impl IntoResponse {
+pub async fn pause_handler(
+    State(state): State<AppState>,
+    RequestInitiator(initiator): RequestInitiator,
+) -> impl IntoResponse {
     let daemon_state = state.daemon_state();
-    match pause_daemon(daemon_state) {
+    let _guard = match daemon_state.control().admit(IpcCommand::Pause, &initiator) {
+        Admission::Now(guard) => guard,
+        Admission::Deferred(deferral) => return deferred_response(&deferral),
+    };
+    match pause_daemon(daemon_state, &initiator) {
         Ok(()) => (StatusCode::OK, Json(ControlResponse::success())).into_response(),
         Err(err) => error_response(&err.code, &err.message, err.status).into_response(),
     }
 }
 
 /// Handles POST /api/v1/resume requests to resume daemon monitoring.
-pub async fn resume_handler(State(state): State<AppState>) -> impl IntoResponse {
+pub async fn resume_handler(
+    State(state): State<AppState>,
+    RequestInitiator(initiator): RequestInitiator,
+) -> impl IntoResponse {
     let daemon_state = state.daemon_state();
-    match resume_daemon(daemon_state) {
+    let _guard = match daemon_state.control().admit(IpcCommand::Resume, &initiator) {
+        Admission::Now(guard) => guard,
+        Admission::Deferred(deferral) => return deferred_response(&deferral),
+    };
+    match resume_daemon(daemon_state, &initiator) {
         Ok(()) => (StatusCode::OK, Json(ControlResponse::success())).into_response(),
         Err(err) => error_response(&err.code, &err.message, err.status).into_response(),
     }
 }
 
 /// Handles POST /api/v1/new-session requests to start a new session.
-pub async fn new_session_handler(State(state): State<AppState>) -> impl IntoResponse {
+///
+/// The body is a versioned [`NewSessionRequest`]. The session starts in the
+/// background; the 202 response carries the job ID and a `Location` header
+/// pointing at its status.
+pub async fn new_session_handler(
+    State(state): State<AppState>,
+    RequestInitiator(initiator): RequestInitiator,
+    body: Bytes,
+) -> impl IntoResponse {
+    let request: NewSessionRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            return error_response(
+                "INVALID_REQUEST",
+                &format!("Invalid new-session request: {err}"),
+                StatusCode::BAD_REQUEST,
+            )
+            .into_response();
+        }
+    };
     let daemon_state = state.daemon_state();
-    match new_session_daemon(daemon_state) {
-        Ok(session_id) => {
-            let response = ControlResponseWithId::success(session_id);
-            (StatusCode::OK, Json(response)).into_response()
+    let _guard = match daemon_state
+        .control()
+        .admit(IpcCommand::NewSession, &initiator)
+    {
+        Admission::Now(guard) => guard,
+        Admission::Deferred(deferral) => return deferred_response(&deferral),
+    };
+    match submit_new_session(daemon_state, request, &initiator) {
+        Ok(job_id) => {
+            let location = format!("/api/v1/jobs/{job_id}");
+            let response = JobAcceptedResponse {
+                success: true,
+                job_id,
+            };
+            (StatusCode::ACCEPTED, [(LOCATION, location)], Json(response)).into_response()
         }
         Err(err) => error_response(&err.code, &err.message, err.status).into_response(),
     }
 }
 
+/// Body of POST /api/v1/mode: `{ "mode": "observe" }`.
+#[derive(Debug, Deserialize)]
+pub struct ModeRequest {
+    pub mode: DaemonMode,
+}
+
+/// Handles POST /api/v1/reload requests to reload the configuration.
+///
+/// A config that fails to load is reported in the response. Services whose
+/// settings changed, this API included, are then restarted in the
+/// background, so the response does not wait on its own listener.
+pub async fn reload_handler(
+    State(state): State<AppState>,
+    RequestInitiator(initiator): RequestInitiator,
+) -> impl IntoResponse {
+    let daemon_state = state.daemon_state();
+    let _guard = match daemon_state.control().admit(IpcCommand::Reload, &initiator) {
+        Admission::Now(guard) => guard,
+        Admission::Deferred(deferral) => return deferred_response(&deferral),
+    };
+    if let Err(message) = daemon_state.reload_config() {
+        return error_response("RELOAD_ERROR", &message, StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response();
+    }
+    let daemon_state = Arc::clone(daemon_state);
+    tokio::spawn(async move {
+        daemon_state.reconfigure_services().await;
+    });
+    (StatusCode::OK, Json(ControlResponse::success())).into_response()
+}
+
+/// Handles POST /api/v1/mode requests to switch between acting on stops
+/// and only reporting them.
+pub async fn mode_handler(
+    State(state): State<AppState>,
+    RequestInitiator(initiator): RequestInitiator,
+    body: Bytes,
+) -> impl IntoResponse {
+    let request: ModeRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            return error_response(
+                "INVALID_REQUEST",
+                &format!("Invalid mode request: {err}"),
+                StatusCode::BAD_REQUEST,
+            )
+            .into_response();
+        }
+    };
+    let daemon_state = state.daemon_state();
+    let _guard = match daemon_state
+        .control()
+        .admit(IpcCommand::SetMode(request.mode), &initiator)
+    {
+        Admission::Now(guard) => guard,
+        Admission::Deferred(deferral) => return deferred_response(&deferral),
+    };
+    match daemon_state.set_mode_by(request.mode, &initiator) {
+        Ok(()) => (StatusCode::OK, Json(ControlResponse::success())).into_response(),
+        Err(message) => error_response("MODE_ERROR", &message, StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response(),
+    }
+}
+
+/// Handles GET /api/v1/jobs/{id} requests for a tracked job's status.
+pub async fn job_handler(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    match state.daemon_state().session_jobs().get(&job_id) {
+        Some(job) => (StatusCode::OK, Json(JobResponse { success: true, job })).into_response(),
+        None => error_response(
+            "JOB_NOT_FOUND",
+            &format!("No job {job_id} (unknown or expired)"),
+            StatusCode::NOT_FOUND,
+        )
+        .into_response(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -196,6 +379,7 @@ mod tests {
             .route("/api/v1/pause", post(pause_handler))
             .route("/api/v1/resume", post(resume_handler))
             .route("/api/v1/new-session", post(new_session_handler))
+            .route("/api/v1/mode", post(mode_handler))
             .with_state(AppState::new(
                 state,
                 crate::http::EventBroadcaster::default(),
@@ -227,6 +411,37 @@ mod tests {
         assert_eq!(payload["success"], true);
     }
 
+    #[tokio::test]
+    async fn test_pause_is_attributed_to_the_http_peer() {
+        let state = Arc::new(DaemonState::new());
+        let mut control = state.control_events();
+        let peer: std::net::SocketAddr = "127.0.0.1:5555".parse().unwrap();
+
+        let response = test_router(Arc::clone(&state))
+            .layer(axum::middleware::from_fn(
+                crate::http::initiator::attach_initiator,
+            ))
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/pause")
+                    .extension(axum::extract::ConnectInfo(peer))
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            control.try_recv().unwrap(),
+            crate::daemon::events::ControlEvent::Paused(Initiator::Http {
+                remote_addr: Some("127.0.0.1:5555".to_string()),
+                token: None,
+            })
+        );
+    }
+
     #[tokio::test]
     async fn test_pause_already_paused_returns_error() {
         let state = Arc::new(DaemonState::new());
@@ -250,6 +465,36 @@ mod tests {
         assert_eq!(payload["error"]["message"], "Daemon already paused");
     }
 
+    #[tokio::test]
+    async fn test_pause_during_operation_is_deferred() {
+        let state = Arc::new(DaemonState::new());
+        let operation = state.control().operation("resume of /tmp/session.md").await;
+
+        let response = test_router(Arc::clone(&state))
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/pause")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let payload = read_json(response).await;
+        assert_eq!(payload["deferred"], true);
+        assert_eq!(
+            payload["message"],
+            "will apply after resume of /tmp/session.md completes"
+        );
+        assert!(!state.is_paused());
+
+        drop(operation);
+        state.control().apply_pending(&*state).await;
+        assert!(state.is_paused());
+    }
+
     #[tokio::test]
     async fn test_resume_success() {
         let state = Arc::new(DaemonState::new());
@@ -293,30 +538,105 @@ mod tests {
         assert_eq!(payload["error"]["message"], "Daemon is not paused");
     }
 
+    struct CreatedAt(std::path::PathBuf);
+
+    #[async_trait::async_trait]
+    impl crate::resume::SessionCreator for CreatedAt {
+        async fn create(
+            &self,
+            _prompt: &str,
+            _session_dir: &std::path::Path,
+        ) -> Result<std::path::PathBuf, crate::resume::ResumeError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn state_with_session_dir(session_dir: &std::path::Path) -> Arc<DaemonState> {
+        let mut config = crate::config::schema::Config::default();
+        config.monitoring.session_dir = session_dir.to_path_buf();
+        Arc::new(DaemonState::new_without_auto_detection().with_config(config))
+    }
+
+    fn new_session_request(body: &str) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/v1/new-session")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
+
     #[tokio::test]
-    async fn test_new_session_success_includes_session_id() {
-        let state = Arc::new(DaemonState::new());
+    async fn test_new_session_is_accepted_as_a_job() {
+        let sessions = tempfile::tempdir().unwrap();
+        let state = state_with_session_dir(sessions.path());
+        state.set_session_creator(Arc::new(CreatedAt(sessions.path().join("new.md"))));
 
-        let response = test_router(state)
-            .oneshot(
-                axum::http::Request::builder()
-                    .method("POST")
-                    .uri("/api/v1/new-session")
-                    .body(axum::body::Body::empty())
-                    .unwrap(),
-            )
+        let response = test_router(Arc::clone(&state))
+            .oneshot(new_session_request(r#"{"version":1,"prompt":"continue"}"#))
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let location = response.headers()[LOCATION].to_str().unwrap().to_string();
         let payload = read_json(response).await;
         assert_eq!(payload["success"], true);
-        assert!(
-            payload["session_id"]
-                .as_str()
-                .is_some_and(|id| !id.is_empty()),
-            "session_id should be a non-empty string"
-        );
+        let job_id = payload["job_id"].as_str().unwrap();
+        assert_eq!(location, format!("/api/v1/jobs/{job_id}"));
+        assert!(state.session_jobs().get(job_id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_new_session_rejects_invalid_payloads() {
+        let sessions = tempfile::tempdir().unwrap();
+        let state = state_with_session_dir(sessions.path());
+
+        for (body, code) in [
+            ("", "INVALID_REQUEST"),
+            (r#"{"prompt":"go","unknown":true}"#, "INVALID_REQUEST"),
+            (r#"{"version":9,"prompt":"go"}"#, "UNSUPPORTED_VERSION"),
+            (r#"{"prompt":" "}"#, "INVALID_PROMPT"),
+            (r#"{"prompt":"go","workdir":"/etc"}"#, "INVALID_WORKDIR"),
+        ] {
+            let response = test_router(Arc::clone(&state))
+                .oneshot(new_session_request(body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+            assert_eq!(read_json(response).await["error"]["code"], code, "{body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mode_validates_the_requested_mode() {
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let mode_request = |body: &'static str| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/api/v1/mode")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body))
+                .unwrap()
+        };
+
+        for body in ["", r#"{"mode":"sleepy"}"#] {
+            let response = test_router(Arc::clone(&state))
+                .oneshot(mode_request(body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+            assert_eq!(
+                read_json(response).await["error"]["code"],
+                "INVALID_REQUEST",
+                "{body}"
+            );
+        }
+
+        let response = test_router(Arc::clone(&state))
+            .oneshot(mode_request(r#"{"mode":"active"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.mode(), DaemonMode::Active);
     }
 
     #[tokio::test]
@@ -12,10 +12,13 @@ use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tracing::warn;
 
+use crate::daemon::session_jobs::TrackedJob;
 use crate::http::server::AppState;
 use crate::notify::events::NotificationEvent;
+use crate::notify::routing;
 #[cfg(test)]
 use crate::telemetry::Metrics;
+use crate::util::path_display;
 
 #[derive(Debug, Serialize)]
 struct ConnectedEvent {
@@ -24,9 +27,13 @@ struct ConnectedEvent {
 }
 
 /// Handles GET /api/v1/events SSE streaming requests.
+///
+/// Notification events are interleaved with `job` events for tracked
+/// new-session jobs.
 pub async fn events_handler(State(state): State<AppState>) -> impl IntoResponse {
     let receiver = state.events().subscribe();
-    let stream = connected_stream().chain(broadcast_stream(receiver));
+    let jobs = state.daemon_state().session_jobs().subscribe();
+    let stream = connected_stream().chain(broadcast_stream(receiver).merge(job_stream(jobs)));
     Sse::new(stream).keep_alive(
         KeepAlive::new()
             .interval(Duration::from_secs(30))
@@ -63,8 +70,44 @@ fn broadcast_stream(
     })
 }
 
+fn job_stream(
+    receiver: broadcast::Receiver<TrackedJob>,
+) -> impl tokio_stream::Stream<Item = Result<Event, Infallible>> {
+    BroadcastStream::new(receiver).filter_map(|message| match message {
+        Ok(job) => Some(Ok(job_event(&job))),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            warn!(skipped, "SSE subscriber lagged behind job updates");
+            None
+        }
+    })
+}
+
+fn job_event(job: &TrackedJob) -> Event {
+    match Event::default().event("job").json_data(job) {
+        Ok(event) => event,
+        Err(err) => {
+            warn!(error = %err, job = %job.job_id, "Failed to serialize SSE job event");
+            Event::default()
+                .event("job")
+                .data("{\"error\":\"serialization_failed\"}")
+        }
+    }
+}
+
 fn notification_event(event: NotificationEvent) -> Event {
-    match Event::default().event(event.event_type()).json_data(&event) {
+    let tags = routing::event_tags(&event);
+    let display = path_display::global().for_json();
+    let event = event.with_display_paths(&display);
+    let payload = serde_json::to_value(event.as_ref()).map(|mut payload| {
+        routing::insert_tags(&mut payload, &tags);
+        payload
+    });
+    match payload.and_then(|payload| {
+        Event::default()
+            .event(event.event_type())
+            .json_data(payload)
+            .map_err(serde::ser::Error::custom)
+    }) {
         Ok(event) => event,
         Err(err) => {
             warn!(error = %err, event_type = event.event_type(), "Failed to serialize SSE event");
@@ -115,6 +158,7 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             stop_reason: "rate_limit".to_string(),
             details: None,
+            excluded_by: None,
         }
     }
 
@@ -194,6 +238,34 @@ mod tests {
         assert!(text.contains("\"event\":\"session_stopped\""));
     }
 
+    #[tokio::test]
+    async fn test_job_updates_are_streamed() {
+        let daemon_state = Arc::new(DaemonState::new());
+        let state = AppState::new(
+            Arc::clone(&daemon_state),
+            EventBroadcaster::default(),
+            Arc::new(Metrics::new()),
+        );
+        let response = test_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/events")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut body = response.into_body();
+        let _ = read_frame_text(&mut body).await;
+
+        let job_id = daemon_state.session_jobs().submit("new_session");
+        let text = read_frame_text(&mut body).await;
+        assert!(text.contains("event: job"));
+        assert!(text.contains(&format!("\"job_id\":\"{job_id}\"")));
+        assert!(text.contains("\"state\":\"pending\""));
+    }
+
     #[tokio::test]
     async fn test_multiple_clients_receive_same_event() {
         let broadcaster = EventBroadcaster::default();
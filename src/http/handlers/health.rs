@@ -10,8 +10,10 @@ use serde::Serialize;
 
 use crate::daemon::state::DaemonState;
 use crate::http::server::AppState;
+use crate::state::AuditWriter;
 #[cfg(test)]
 use crate::telemetry::Metrics;
+use crate::util::duration;
 
 #[derive(Debug, Serialize, PartialEq, Eq)]
 pub struct HealthEnvelope {
@@ -72,6 +74,7 @@ pub async fn health_handler(State(state): State<AppState>) -> (StatusCode, Json<
 /// Returns a list of issue identifiers for any detected problems:
 /// - `paused`: Daemon is currently paused
 /// - `config_unavailable`: Configuration lock is poisoned or inaccessible
+/// - `audit_degraded`: The last audit log write failed
 fn collect_health_issues(state: &DaemonState) -> Vec<String> {
     let mut issues = Vec::new();
     if state.is_paused() {
@@ -80,32 +83,15 @@ fn collect_health_issues(state: &DaemonState) -> Vec<String> {
     if state.daemon_config().is_none() {
         issues.push("config_unavailable".to_string());
     }
+    if AuditWriter::global().is_some_and(|writer| writer.is_degraded()) {
+        issues.push("audit_degraded".to_string());
+    }
     issues
 }
 
-/// Formats a duration as a human-readable uptime string.
-///
-/// Output format examples:
-/// - `"45s"` for durations under 1 minute
-/// - `"15m"` for durations under 1 hour
-/// - `"2h30m"` for durations under 1 day
-/// - `"3d2h"` for durations of 1 day or more
+/// Formats a duration as a human-readable uptime string, e.g. `2h 30m` or `3d 2h`.
 fn format_uptime(duration: Duration) -> String {
-    let total_secs = duration.as_secs();
-    let days = total_secs / 86400;
-    let hours = (total_secs % 86400) / 3600;
-    let minutes = (total_secs % 3600) / 60;
-    let seconds = total_secs % 60;
-
-    if days > 0 {
-        format!("{days}d{hours}h")
-    } else if hours > 0 {
-        format!("{hours}h{minutes}m")
-    } else if minutes > 0 {
-        format!("{minutes}m")
-    } else {
-        format!("{seconds}s")
-    }
+    duration::format_compact(duration)
 }
 
 #[cfg(test)]
@@ -160,15 +146,15 @@ mod tests {
     fn test_format_uptime() {
         assert_eq!(
             format_uptime(Duration::from_secs(2 * 3600 + 30 * 60)),
-            "2h30m"
+            "2h 30m"
         );
         assert_eq!(format_uptime(Duration::from_secs(15 * 60)), "15m");
         assert_eq!(format_uptime(Duration::from_secs(45)), "45s");
         assert_eq!(
             format_uptime(Duration::from_secs(3 * 86400 + 2 * 3600)),
-            "3d2h"
+            "3d 2h"
         );
-        assert_eq!(format_uptime(Duration::from_secs(86400)), "1d0h");
+        assert_eq!(format_uptime(Duration::from_secs(86400)), "1d");
     }
 
     #[tokio::test]
@@ -228,12 +214,12 @@ mod tests {
     fn test_health_response_serialization() {
         let response = HealthResponse::new(
             HealthStatus::Degraded,
-            "1h30m".to_string(),
+            "1h 30m".to_string(),
             vec!["paused".to_string(), "config_unavailable".to_string()],
         );
         let json = serde_json::to_value(&response).unwrap();
         assert_eq!(json["status"], "degraded");
-        assert_eq!(json["uptime"], "1h30m");
+        assert_eq!(json["uptime"], "1h 30m");
         assert_eq!(
             json["issues"],
             serde_json::json!(["paused", "config_unavailable"])
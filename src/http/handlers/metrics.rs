@@ -86,7 +86,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_metrics_response_content_type() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = enable_metrics_for_test();
         let state = Arc::new(DaemonState::new());
         let response = test_router(state)
@@ -113,7 +113,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_metrics_output_contains_expected_names() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = enable_metrics_for_test();
         let state = Arc::new(DaemonState::new());
         let response = test_router(state)
@@ -154,7 +154,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_metrics_disabled_returns_not_found() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = tempdir().unwrap();
         let config_path = temp.path().join("config.toml");
         set_env_var("PALINGENESIS_CONFIG", &config_path);
@@ -177,7 +177,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_metrics_endpoint_handles_burst_quickly() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = enable_metrics_for_test();
         let state = Arc::new(DaemonState::new());
         let router = test_router(state);
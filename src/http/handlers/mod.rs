@@ -1,9 +1,15 @@
 //! HTTP request handlers.
 
+pub mod ack;
+#[cfg(feature = "bots")]
 pub mod bot_discord;
+#[cfg(feature = "bots")]
 pub mod bot_slack;
+pub mod changes;
 pub mod control;
 pub mod events;
 pub mod health;
 pub mod metrics;
+pub mod notifications;
+pub mod sessions;
 pub mod status;
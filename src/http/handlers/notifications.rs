@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use std::sync::Arc;
+
+use crate::http::handlers::control::error_response;
+use crate::http::server::AppState;
+use crate::ipc::socket::DaemonStateAccess;
+use crate::notify::credentials::{CredentialRegistry, CredentialStatus};
+use crate::notify::history::{DeliveryOutcome, DeliveryRecord, HistoryQuery, NotificationHistory};
+use crate::state::{MuteKind, NotificationMute};
+#[cfg(test)]
+use crate::telemetry::Metrics;
+use crate::util::duration;
+
+/// Envelope for recent deliveries: `{ "success": true, "data": {...} }`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RecentEnvelope {
+    success: bool,
+    data: RecentDeliveries,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RecentDeliveries {
+    /// Matching decisions, newest first.
+    entries: Vec<DeliveryRecord>,
+    /// Retained decisions per outcome, honoring `since`.
+    counts: BTreeMap<DeliveryOutcome, usize>,
+}
+
+/// Handles GET /api/v1/notifications/recent.
+///
+/// Supports `?channel=ntfy`, `?outcome=failed`, `?since=<RFC3339>` and `?limit=N`.
+pub async fn recent_handler(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> (StatusCode, Json<RecentEnvelope>) {
+    let config = state.daemon_state().config_snapshot();
+    let history = NotificationHistory::global_or_init(&config.notifications);
+    let envelope = RecentEnvelope {
+        success: true,
+        data: RecentDeliveries {
+            entries: history.recent(&query),
+            counts: history.outcome_counts(query.since),
+        },
+    };
+    (StatusCode::OK, Json(envelope))
+}
+
+/// Envelope for channel health: `{ "success": true, "data": {...} }`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HealthEnvelope {
+    success: bool,
+    data: ChannelHealth,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChannelHealth {
+    /// Configured channels by name, with when their credentials were last resolved.
+    channels: Vec<CredentialStatus>,
+}
+
+/// Handles GET /api/v1/notifications/health.
+pub async fn health_handler() -> (StatusCode, Json<HealthEnvelope>) {
+    let envelope = HealthEnvelope {
+        success: true,
+        data: ChannelHealth {
+            channels: CredentialRegistry::global().statuses(),
+        },
+    };
+    (StatusCode::OK, Json(envelope))
+}
+
+/// Envelope for a rotation: `{ "success": true, "data": {...} }`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RotateEnvelope {
+    success: bool,
+    data: Rotation,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Rotation {
+    channel: String,
+    /// Whether the re-resolved credentials differ from the previous ones.
+    changed: bool,
+    credentials_refreshed_at: DateTime<Utc>,
+}
+
+/// Handles POST /api/v1/notifications/{channel}/rotate, the HTTP form of
+/// `ROTATE_SECRET <channel>`.
+pub async fn rotate_handler(Path(channel): Path<String>) -> Response {
+    let Some(entry) = CredentialRegistry::global().get(&channel) else {
+        return error_response(
+            "UNKNOWN_CHANNEL",
+            &format!("No notification channel named {channel:?}"),
+            StatusCode::NOT_FOUND,
+        )
+        .into_response();
+    };
+    // Keychain lookups block.
+    let refresh = {
+        let entry = std::sync::Arc::clone(&entry);
+        tokio::task::spawn_blocking(move || entry.refresh())
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|result| result.map_err(|err| err.to_string()))
+    };
+    match refresh {
+        Ok(changed) => (
+            StatusCode::OK,
+            Json(RotateEnvelope {
+                success: true,
+                data: Rotation {
+                    channel,
+                    changed,
+                    credentials_refreshed_at: entry.refreshed_at(),
+                },
+            }),
+        )
+            .into_response(),
+        Err(message) => {
+            error_response("ROTATE_FAILED", &message, StatusCode::INTERNAL_SERVER_ERROR)
+                .into_response()
+        }
+    }
+}
+
+/// Envelope for mutes: `{ "success": true, "data": {...} }`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct MutesEnvelope {
+    success: bool,
+    data: ActiveMutes,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ActiveMutes {
+    mutes: Vec<NotificationMute>,
+}
+
+/// Query of a mute request: `?duration=30m`; without it the mute lasts
+/// until lifted.
+#[derive(Debug, Default, Deserialize)]
+pub struct MuteQuery {
+    #[serde(default)]
+    duration: Option<String>,
+}
+
+fn mutes_response(mutes: Vec<NotificationMute>) -> Response {
+    (
+        StatusCode::OK,
+        Json(MutesEnvelope {
+            success: true,
+            data: ActiveMutes { mutes },
+        }),
+    )
+        .into_response()
+}
+
+/// Handles GET /api/v1/notifications/mutes.
+pub async fn mutes_handler(State(state): State<AppState>) -> Response {
+    mutes_response(state.daemon_state().mutes())
+}
+
+/// Handles POST /api/v1/notifications/{channels|events}/{name}/mute, the
+/// HTTP form of `MUTE_CHANNEL` and `MUTE_EVENT`.
+pub async fn mute_handler(
+    State(state): State<AppState>,
+    Path((kind, name)): Path<(String, String)>,
+    Query(query): Query<MuteQuery>,
+) -> Response {
+    let Some(kind) = mute_kind(&kind) else {
+        return unknown_kind(&kind);
+    };
+    let duration = match query.duration.as_deref().map(duration::parse) {
+        None => None,
+        Some(Some(duration)) if !duration.is_zero() => Some(duration),
+        Some(_) => {
+            return error_response(
+                "INVALID_DURATION",
+                "duration must look like 30s, 15m, 2h or 1d",
+                StatusCode::BAD_REQUEST,
+            )
+            .into_response();
+        }
+    };
+    match state.daemon_state().mute(kind, &name, duration) {
+        Ok(mutes) => mutes_response(mutes),
+        Err(message) => error_response("MUTE_FAILED", &message, StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response(),
+    }
+}
+
+/// Handles DELETE /api/v1/notifications/{channels|events}/{name}/mute, the
+/// HTTP form of `UNMUTE_CHANNEL` and `UNMUTE_EVENT`.
+pub async fn unmute_handler(
+    State(state): State<AppState>,
+    Path((kind, name)): Path<(String, String)>,
+) -> Response {
+    let Some(kind) = mute_kind(&kind) else {
+        return unknown_kind(&kind);
+    };
+    let daemon_state = state.daemon_state();
+    if !daemon_state
+        .mutes()
+        .iter()
+        .any(|mute| mute.kind == kind && mute.name.eq_ignore_ascii_case(name.trim()))
+    {
+        return error_response(
+            "NOT_MUTED",
+            &format!("{kind} {name} is not muted"),
+            StatusCode::NOT_FOUND,
+        )
+        .into_response();
+    }
+    match daemon_state.unmute(kind, &name) {
+        Ok(mutes) => mutes_response(mutes),
+        Err(message) => error_response("MUTE_FAILED", &message, StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response(),
+    }
+}
+
+fn mute_kind(segment: &str) -> Option<MuteKind> {
+    match segment {
+        "channels" => Some(MuteKind::Channel),
+        "events" => Some(MuteKind::Event),
+        _ => None,
+    }
+}
+
+fn unknown_kind(segment: &str) -> Response {
+    error_response(
+        "NOT_FOUND",
+        &format!("Unknown mute target {segment:?}; use channels or events"),
+        StatusCode::NOT_FOUND,
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::to_bytes;
+    use axum::routing::{get, post};
+    use tower::ServiceExt;
+
+    use crate::daemon::state::DaemonState;
+    use crate::notify::events::EventSeverity;
+
+    fn test_router() -> Router {
+        Router::new()
+            .route("/api/v1/notifications/recent", get(recent_handler))
+            .route("/api/v1/notifications/health", get(health_handler))
+            .route(
+                "/api/v1/notifications/{channel}/rotate",
+                post(rotate_handler),
+            )
+            .route(
+                "/api/v1/notifications/{kind}/{name}/mute",
+                post(mute_handler).delete(unmute_handler),
+            )
+            .with_state(AppState::new(
+                Arc::new(DaemonState::new_without_auto_detection()),
+                crate::http::EventBroadcaster::default(),
+                Arc::new(Metrics::new()),
+            ))
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, serde_json::Value) {
+        request_json("GET", uri).await
+    }
+
+    async fn request_json(method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = test_router()
+            .oneshot(
+                axum::http::Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_recent_filters_by_channel_and_outcome() {
+        let history = NotificationHistory::global_or_init(
+            &crate::config::schema::NotificationsConfig::default(),
+        );
+        history.record(DeliveryRecord {
+            timestamp: Utc::now(),
+            event_type: "resume_failed".to_string(),
+            severity: EventSeverity::Error,
+            channel: "http-test-channel".to_string(),
+            outcome: DeliveryOutcome::Failed,
+            http_status: Some(503),
+            latency_ms: Some(12),
+            error: Some("webhook returned status 503".to_string()),
+        });
+
+        let (status, payload) =
+            get_json("/api/v1/notifications/recent?channel=http-test-channel&outcome=failed").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["success"], true);
+        let entries = payload["data"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["http_status"], 503);
+        assert!(payload["data"]["counts"]["failed"].as_u64().unwrap() >= 1);
+
+        let (status, payload) =
+            get_json("/api/v1/notifications/recent?channel=http-test-channel&outcome=delivered")
+                .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(payload["data"]["entries"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recent_rejects_unknown_outcome() {
+        let (status, _) = get_json("/api/v1/notifications/recent?outcome=lost").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_mute_rejects_bad_duration_and_target() {
+        let (status, payload) = request_json(
+            "POST",
+            "/api/v1/notifications/channels/slack/mute?duration=soon",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(payload["error"]["code"], "INVALID_DURATION");
+
+        let (status, _) = request_json("DELETE", "/api/v1/notifications/pagers/slack/mute").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_re_resolves_a_file_secret() {
+        use crate::notify::credentials::{ChannelKind, Credentials};
+
+        let _lock = crate::test_utils::ENV_LOCK.lock().await;
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("hook-url");
+        std::fs::write(&secret, "https://hooks.example.com/new\n").unwrap();
+        let config = dir.path().join("config.toml");
+        std::fs::write(
+            &config,
+            format!(
+                "[[notifications.webhook]]\nname = \"http-rotate-test\"\nurl = \"${{file:{}}}\"\n",
+                secret.display()
+            ),
+        )
+        .unwrap();
+        unsafe { std::env::set_var("PALINGENESIS_CONFIG", &config) };
+        CredentialRegistry::global().register(
+            "http-rotate-test",
+            ChannelKind::Webhook,
+            Credentials {
+                url: "https://hooks.example.com/old".to_string(),
+                ..Credentials::default()
+            },
+        );
+
+        let (status, payload) =
+            request_json("POST", "/api/v1/notifications/http-rotate-test/rotate").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["data"]["changed"], true);
+
+        let (status, payload) = get_json("/api/v1/notifications/health").await;
+        assert_eq!(status, StatusCode::OK);
+        let channel = payload["data"]["channels"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|channel| channel["channel"] == "http-rotate-test")
+            .unwrap()
+            .clone();
+        assert_eq!(channel["kind"], "webhook");
+        assert_eq!(channel["sources"][0], format!("file:{}", secret.display()));
+        assert!(channel["credentials_refreshed_at"].is_string());
+
+        let (status, payload) =
+            request_json("POST", "/api/v1/notifications/no-such-channel/rotate").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(payload["error"]["code"], "UNKNOWN_CHANNEL");
+
+        unsafe { std::env::remove_var("PALINGENESIS_CONFIG") };
+    }
+}
@@ -0,0 +1,129 @@
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde_json::json;
+
+#[cfg(test)]
+use std::sync::Arc;
+
+use crate::config::Paths;
+use crate::http::server::AppState;
+use crate::monitor::catalog::{self, SessionEntry, SessionQuery};
+use crate::state::{StateHandle, timeline};
+#[cfg(test)]
+use crate::telemetry::Metrics;
+
+/// Envelope for the session listing: `{ "success": true, "data": [...] }`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SessionsEnvelope {
+    success: bool,
+    data: Vec<SessionEntry>,
+}
+
+/// Handles GET /api/v1/sessions, merging state, watcher, and OpenCode views.
+///
+/// Supports `?status=in-progress` and `?limit=N`.
+pub async fn sessions_handler(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+) -> (StatusCode, Json<SessionsEnvelope>) {
+    let daemon_state = state.daemon_state();
+    let config = daemon_state.config_snapshot();
+    let cache = daemon_state.session_dir_cache();
+    let entries = catalog::collect(
+        &config,
+        &StateHandle::read_state(),
+        &Paths::state_dir(),
+        daemon_state.is_paused(),
+        Some(cache.as_ref()),
+        Some(daemon_state.opencode_endpoint()),
+        daemon_state.shutdown_token(),
+    )
+    .await;
+    let envelope = SessionsEnvelope {
+        success: true,
+        data: query.apply(entries),
+    };
+    (StatusCode::OK, Json(envelope))
+}
+
+/// Handles GET /api/v1/sessions/current/timeline: when each step of the
+/// current session completed, with durations and the resumes in between.
+pub async fn timeline_handler() -> (StatusCode, Json<serde_json::Value>) {
+    match timeline::current(&StateHandle::read_state()) {
+        Some(view) => (
+            StatusCode::OK,
+            Json(json!({ "success": true, "data": view })),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "error": {
+                    "code": "NO_TIMELINE",
+                    "message": "No step completions recorded for the current session"
+                }
+            })),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::to_bytes;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    use crate::daemon::state::DaemonState;
+
+    fn test_router(state: Arc<DaemonState>) -> Router {
+        Router::new()
+            .route("/api/v1/sessions", get(sessions_handler))
+            .with_state(AppState::new(
+                state,
+                crate::http::EventBroadcaster::default(),
+                Arc::new(Metrics::new()),
+            ))
+    }
+
+    #[tokio::test]
+    async fn test_sessions_response_is_enveloped_list() {
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let response = test_router(state)
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/v1/sessions?status=in-progress&limit=5")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["success"], true);
+        let data = payload["data"].as_array().unwrap();
+        assert!(data.len() <= 5);
+        assert!(data.iter().all(|entry| entry["status"] == "in-progress"));
+    }
+
+    #[tokio::test]
+    async fn test_sessions_rejects_invalid_limit() {
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let response = test_router(state)
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/v1/sessions?limit=many")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
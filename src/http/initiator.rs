@@ -0,0 +1,55 @@
+//! Who sent an HTTP control request.
+//!
+//! [`attach_initiator`] runs on every request and records the peer address
+//! and the name of the token it authenticated with as an
+//! [`Initiator::Http`]; control handlers take it with the
+//! [`RequestInitiator`] extractor.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::Request;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::daemon::initiator::Initiator;
+use crate::http::auth::Grant;
+
+/// Attach an [`Initiator::Http`] naming the peer and token to the request.
+pub async fn attach_initiator(mut request: Request<Body>, next: Next) -> Response {
+    let remote_addr = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.to_string());
+    let token = request
+        .extensions()
+        .get::<Grant>()
+        .and_then(|grant| grant.name.clone());
+    request
+        .extensions_mut()
+        .insert(Initiator::Http { remote_addr, token });
+    next.run(request).await
+}
+
+/// The request's [`Initiator`]; an address-less HTTP initiator when
+/// [`attach_initiator`] did not run.
+pub struct RequestInitiator(pub Initiator);
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestInitiator {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let initiator = parts
+            .extensions
+            .get::<Initiator>()
+            .cloned()
+            .unwrap_or(Initiator::Http {
+                remote_addr: None,
+                token: None,
+            });
+        Ok(Self(initiator))
+    }
+}
@@ -1,8 +1,22 @@
 //! Axum HTTP server module.
+//!
+//! [`EventBroadcaster`] is always available; the server and its handlers
+//! need the `http-api` feature.
 
+#[cfg(feature = "http-api")]
+pub mod auth;
 pub mod events;
+#[cfg(feature = "http-api")]
 pub mod handlers;
+#[cfg(feature = "http-api")]
+pub mod initiator;
+#[cfg(feature = "http-api")]
 pub mod server;
+#[cfg(feature = "http-api")]
+pub mod supervisor;
 
 pub use events::EventBroadcaster;
+#[cfg(feature = "http-api")]
 pub use server::{AppState, HttpServer};
+#[cfg(feature = "http-api")]
+pub use supervisor::{HttpChange, HttpSupervisor};
@@ -5,27 +5,49 @@ use std::time::Duration;
 use anyhow::{Context, Result};
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
-use axum::{Json, Router};
+use axum::{Json, Router, middleware};
 use serde_json::json;
 use tokio::net::TcpListener;
 use tokio_util::sync::CancellationToken;
 use tower_http::trace::TraceLayer;
 use tracing::{info, warn};
 
-use crate::config::schema::DaemonConfig;
+use crate::config::schema::{DaemonConfig, HttpScope, HttpTokenConfig};
 use crate::daemon::state::DaemonState;
+use crate::http::auth::{self, TokenSet, scoped};
 use crate::http::events::EventBroadcaster;
-use crate::http::handlers;
+use crate::http::{handlers, initiator};
 use crate::telemetry::Metrics;
 
 /// HTTP API server for external integrations.
 pub struct HttpServer {
     bind_addr: SocketAddr,
-    router: Router,
+    routes: Routes,
+    app_state: AppState,
+    tokens: TokenSet,
     shutdown: CancellationToken,
     events: EventBroadcaster,
 }
 
+/// Which routes a listener serves; `/health` is on every listener.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Routes {
+    All,
+    /// Everything but the bot webhooks.
+    Control,
+    /// Only the bot webhooks.
+    Bots,
+}
+
+impl Routes {
+    fn label(self) -> &'static str {
+        match self {
+            Routes::All | Routes::Control => "HTTP API",
+            Routes::Bots => "Bot webhooks",
+        }
+    }
+}
+
 /// Shared application state for HTTP handlers.
 #[derive(Clone)]
 pub struct AppState {
@@ -72,13 +94,11 @@ impl HttpServer {
             return Ok(None);
         }
 
-        Ok(Some(Self::new(
-            &config.http_bind,
-            config.http_port,
-            shutdown,
-            state,
-            events,
-        )?))
+        Ok(Some(
+            Self::new(&config.http_bind, config.http_port, shutdown, state, events)?
+                .with_auth_token(config.http_token.clone())
+                .with_api_tokens(&config.http_tokens),
+        ))
     }
 
     /// Create a new HTTP server with bind address and shutdown token.
@@ -89,25 +109,74 @@ impl HttpServer {
         state: Arc<DaemonState>,
         events: EventBroadcaster,
     ) -> Result<Self> {
+        let bind_addr = Self::parse_bind_addr(bind, port, Routes::All)?;
+
+        // Reuse the registry across server restarts so metric families are
+        // registered once and counters keep their values.
+        let metrics = Metrics::global().unwrap_or_else(|| {
+            let metrics = Arc::new(Metrics::new());
+            let _ = Metrics::set_global(Arc::clone(&metrics));
+            metrics
+        });
+
+        Ok(Self {
+            bind_addr,
+            routes: Routes::All,
+            app_state: AppState::new(state, events.clone(), metrics),
+            tokens: TokenSet::default(),
+            shutdown,
+            events,
+        })
+    }
+
+    /// Require `Authorization: Bearer <token>` on every route except
+    /// `/health`, the ack links and the bot webhooks. `token` holds every
+    /// scope.
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.tokens = self.tokens.with_token(token);
+        self
+    }
+
+    /// Also accept the named `tokens`, each limited to its scopes.
+    pub fn with_api_tokens(mut self, tokens: &[HttpTokenConfig]) -> Self {
+        self.tokens = self.tokens.with_named_tokens(tokens);
+        self
+    }
+
+    /// Move the bot webhooks to their own listener on `bind:port`, returning
+    /// this server without them and the bot server. Both share the handler
+    /// state and the shutdown token; the bot server carries only
+    /// `/api/v1/bot/*` and `/health`, so none of the control routes can be
+    /// reached through it.
+    pub fn split_bot_listener(self, bind: &str, port: u16) -> Result<(Self, Self)> {
+        let bots = Self {
+            bind_addr: Self::parse_bind_addr(bind, port, Routes::Bots)?,
+            routes: Routes::Bots,
+            app_state: self.app_state.clone(),
+            tokens: TokenSet::default(),
+            shutdown: self.shutdown.clone(),
+            events: self.events.clone(),
+        };
+        let control = Self {
+            routes: Routes::Control,
+            ..self
+        };
+        Ok((control, bots))
+    }
+
+    fn parse_bind_addr(bind: &str, port: u16, routes: Routes) -> Result<SocketAddr> {
         let bind_addr: SocketAddr = format!("{bind}:{port}")
             .parse()
             .with_context(|| format!("Invalid HTTP bind address: {bind}:{port}"))?;
 
         if bind == "0.0.0.0" {
+            let label = routes.label();
             warn!(
                 port,
-                "HTTP API binding to all interfaces (0.0.0.0). This exposes the API to the network."
+                "{label} binding to all interfaces (0.0.0.0). This exposes it to the network."
             );
         }
-
-        let router = Self::create_router(state, events.clone());
-
-        Ok(Self {
-            bind_addr,
-            router,
-            shutdown,
-            events,
-        })
+        Ok(bind_addr)
     }
 
     pub fn bind_addr(&self) -> SocketAddr {
@@ -124,67 +193,207 @@ impl HttpServer {
 
     /// Start the HTTP server and wait for shutdown.
     pub async fn start(&self) -> Result<()> {
-        let listener = TcpListener::bind(self.bind_addr)
-            .await
-            .with_context(|| format!("Failed to bind HTTP API to {}", self.bind_addr))?;
+        let listener = self.bind().await?;
+        self.serve(listener).await
+    }
+
+    /// Bind the listening socket without serving, so bind failures can be
+    /// reported before the server task is spawned.
+    pub async fn bind(&self) -> Result<TcpListener> {
+        TcpListener::bind(self.bind_addr).await.with_context(|| {
+            format!(
+                "Failed to bind {} to {}",
+                self.routes.label(),
+                self.bind_addr
+            )
+        })
+    }
+
+    /// Serve on a listener from [`HttpServer::bind`] until shutdown, letting
+    /// in-flight requests finish.
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        let label = self.routes.label();
         let local_addr = listener
             .local_addr()
             .context("Failed to read bound HTTP address")?;
-        info!(address = %local_addr, "HTTP API server listening");
+        info!(address = %local_addr, "{label} server listening");
 
         let shutdown = self.shutdown.clone();
-        axum::serve(listener, self.router.clone())
+        let service = self
+            .router()
+            .into_make_service_with_connect_info::<SocketAddr>();
+        axum::serve(listener, service)
             .with_graceful_shutdown(async move {
                 shutdown.cancelled().await;
-                info!("HTTP API server shutting down");
+                info!("{label} server shutting down");
             })
             .await
-            .context("HTTP API server failed")?;
+            .with_context(|| format!("{label} server failed"))?;
 
-        info!("HTTP API server stopped");
+        info!("{label} server stopped");
         Ok(())
     }
 
-    fn create_router(state: Arc<DaemonState>, events: EventBroadcaster) -> Router {
-        let metrics = Arc::new(Metrics::new());
-        let _ = Metrics::set_global(Arc::clone(&metrics));
-        let app_state = AppState::new(state, events, metrics);
+    /// The listener's routes, wrapped in the request layers and, with
+    /// tokens set, the bearer check.
+    pub(crate) fn router(&self) -> Router {
+        let mut routes = Router::new().route(
+            "/health",
+            axum::routing::get(handlers::health::health_handler),
+        );
+        if self.routes != Routes::Bots {
+            routes = routes.merge(Self::control_routes());
+        }
+        if self.routes != Routes::Control {
+            routes = routes.merge(Self::bot_routes());
+        }
+        let router = Self::with_layers(
+            routes
+                .fallback(Self::fallback_handler)
+                .with_state(self.app_state.clone()),
+        );
+        if self.tokens.is_empty() {
+            return router;
+        }
+        router.layer(middleware::from_fn_with_state(
+            Arc::new(self.tokens.clone()),
+            auth::require_bearer,
+        ))
+    }
+
+    /// Everything but the bot webhooks, each route with the scope a token
+    /// needs to reach it.
+    fn control_routes() -> Router<AppState> {
         Router::new()
-            .route("/health", axum::routing::get(handlers::health::health_handler))
             .route(
                 "/api/v1/status",
-                axum::routing::get(handlers::status::status_handler),
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::status::status_handler),
+                ),
+            )
+            .route(
+                "/api/v1/sessions",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::sessions::sessions_handler),
+                ),
+            )
+            .route(
+                "/api/v1/sessions/current/timeline",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::sessions::timeline_handler),
+                ),
             )
             .route(
                 "/api/v1/metrics",
-                axum::routing::get(handlers::metrics::metrics_handler),
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::metrics::metrics_handler),
+                ),
+            )
+            .route(
+                "/api/v1/notifications/recent",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::notifications::recent_handler),
+                ),
+            )
+            .route(
+                "/api/v1/notifications/health",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::notifications::health_handler),
+                ),
+            )
+            .route(
+                "/api/v1/notifications/{channel}/rotate",
+                scoped(
+                    HttpScope::Admin,
+                    axum::routing::post(handlers::notifications::rotate_handler),
+                ),
+            )
+            .route(
+                "/api/v1/notifications/mutes",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::notifications::mutes_handler),
+                ),
+            )
+            .route(
+                "/api/v1/notifications/{kind}/{name}/mute",
+                scoped(
+                    HttpScope::Control,
+                    axum::routing::post(handlers::notifications::mute_handler)
+                        .delete(handlers::notifications::unmute_handler),
+                ),
+            )
+            .route(
+                "/api/v1/changes",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::changes::changes_handler),
+                ),
             )
             .route(
                 "/api/v1/events",
-                axum::routing::get(handlers::events::events_handler),
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::events::events_handler),
+                ),
             )
             .route(
                 "/api/v1/pause",
-                axum::routing::post(handlers::control::pause_handler),
+                scoped(
+                    HttpScope::Control,
+                    axum::routing::post(handlers::control::pause_handler),
+                ),
             )
             .route(
                 "/api/v1/resume",
-                axum::routing::post(handlers::control::resume_handler),
+                scoped(
+                    HttpScope::Control,
+                    axum::routing::post(handlers::control::resume_handler),
+                ),
             )
             .route(
                 "/api/v1/new-session",
-                axum::routing::post(handlers::control::new_session_handler),
+                scoped(
+                    HttpScope::Resume,
+                    axum::routing::post(handlers::control::new_session_handler),
+                ),
             )
             .route(
-                "/api/v1/bot/discord",
-                axum::routing::post(handlers::bot_discord::discord_webhook_handler),
+                "/api/v1/reload",
+                scoped(
+                    HttpScope::Admin,
+                    axum::routing::post(handlers::control::reload_handler),
+                ),
             )
             .route(
-                "/api/v1/bot/slack",
-                axum::routing::post(handlers::bot_slack::slack_webhook_handler),
+                "/api/v1/mode",
+                scoped(
+                    HttpScope::Admin,
+                    axum::routing::post(handlers::control::mode_handler),
+                ),
+            )
+            .route(
+                "/api/v1/jobs/{id}",
+                scoped(
+                    HttpScope::Read,
+                    axum::routing::get(handlers::control::job_handler),
+                ),
             )
-            .fallback(Self::fallback_handler)
-            .with_state(app_state)
+            .route(
+                "/api/v1/ack/{token}",
+                axum::routing::get(handlers::ack::ack_handler),
+            )
+    }
+
+    fn with_layers(router: Router) -> Router {
+        router
+            .layer(middleware::from_fn(initiator::attach_initiator))
             .layer(
                 TraceLayer::new_for_http()
                     .make_span_with(|request: &Request<Body>| {
@@ -215,6 +424,24 @@ impl HttpServer {
             )
     }
 
+    #[cfg(feature = "bots")]
+    fn bot_routes() -> Router<AppState> {
+        Router::new()
+            .route(
+                "/api/v1/bot/discord",
+                axum::routing::post(handlers::bot_discord::discord_webhook_handler),
+            )
+            .route(
+                "/api/v1/bot/slack",
+                axum::routing::post(handlers::bot_slack::slack_webhook_handler),
+            )
+    }
+
+    #[cfg(not(feature = "bots"))]
+    fn bot_routes() -> Router<AppState> {
+        Router::new()
+    }
+
     async fn fallback_handler() -> (StatusCode, Json<serde_json::Value>) {
         (
             StatusCode::NOT_FOUND,
@@ -227,11 +454,6 @@ impl HttpServer {
             })),
         )
     }
-
-    #[cfg(test)]
-    pub(crate) fn router(&self) -> Router {
-        self.router.clone()
-    }
 }
 
 #[cfg(test)]
@@ -344,7 +566,7 @@ mod tests {
 
     #[test]
     fn test_binding_all_interfaces_warns() {
-        let _tracing = TRACING_LOCK.lock().unwrap();
+        let _tracing = TRACING_LOCK.blocking_lock();
         let (buffer, _guard) = capture_logs();
         let _server = HttpServer::new(
             "0.0.0.0",
@@ -358,10 +580,31 @@ mod tests {
         assert!(output.contains("HTTP API binding to all interfaces"));
     }
 
+    #[test]
+    fn test_bot_listener_on_all_interfaces_warns() {
+        let _tracing = TRACING_LOCK.blocking_lock();
+        let (buffer, _guard) = capture_logs();
+        let _servers = HttpServer::new(
+            "127.0.0.1",
+            7654,
+            CancellationToken::new(),
+            Arc::new(DaemonState::new()),
+            EventBroadcaster::default(),
+        )
+        .unwrap()
+        .split_bot_listener("0.0.0.0", 8443)
+        .unwrap();
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Bot webhooks binding to all interfaces"));
+        assert!(!output.contains("HTTP API binding to all interfaces"));
+    }
+
     #[test]
     fn test_http_disabled_returns_none() {
-        let mut config = DaemonConfig::default();
-        config.http_enabled = false;
+        let config = DaemonConfig {
+            http_enabled: false,
+            ..DaemonConfig::default()
+        };
         let result = HttpServer::from_config(
             &config,
             CancellationToken::new(),
@@ -402,7 +645,7 @@ mod tests {
     #[tokio::test]
     #[ignore = "Flaky under parallel test execution due to global tracing subscriber"]
     async fn test_request_logging() {
-        let _tracing = TRACING_LOCK.lock().unwrap();
+        let _tracing = TRACING_LOCK.lock().await;
         let (buffer, _guard) = capture_logs();
         let server = HttpServer::new(
             "127.0.0.1",
@@ -468,4 +711,82 @@ mod tests {
         shutdown.cancel();
         handle.await.unwrap();
     }
+
+    async fn get(router: Router, uri: &str, token: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {token}"));
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_guards_api_routes() {
+        let server = HttpServer::new(
+            "127.0.0.1",
+            7654,
+            CancellationToken::new(),
+            Arc::new(DaemonState::new()),
+            EventBroadcaster::default(),
+        )
+        .unwrap()
+        .with_auth_token(Some("s3cret".to_string()));
+        let router = server.router();
+
+        assert_eq!(
+            get(router.clone(), "/api/v1/status", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get(router.clone(), "/api/v1/status", Some("wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get(router.clone(), "/api/v1/status", Some("s3cret")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            get(router.clone(), "/api/v1/ack/unknown", None).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(get(router, "/health", None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_split_listeners_keep_their_own_routes() {
+        let (control, bots) = HttpServer::new(
+            "127.0.0.1",
+            7654,
+            CancellationToken::new(),
+            Arc::new(DaemonState::new()),
+            EventBroadcaster::default(),
+        )
+        .unwrap()
+        .with_auth_token(Some("s3cret".to_string()))
+        .split_bot_listener("127.0.0.1", 8443)
+        .unwrap();
+        assert_eq!(bots.bind_addr().port(), 8443);
+
+        assert_eq!(
+            get(control.router(), "/api/v1/status", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get(bots.router(), "/api/v1/status", Some("s3cret")).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            get(control.router(), "/api/v1/pause", Some("s3cret")).await,
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            get(bots.router(), "/api/v1/pause", Some("s3cret")).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(get(bots.router(), "/health", None).await, StatusCode::OK);
+    }
 }
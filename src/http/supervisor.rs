@@ -0,0 +1,386 @@
+//! HTTP API lifecycle that follows config reloads.
+//!
+//! The daemon owns one [`HttpSupervisor`]. On reload it compares the running
+//! server's `[daemon]` HTTP settings (and `[bot]` listener address) with the
+//! new ones and starts, stops or restarts the listeners to match.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span, error, info, info_span, warn};
+
+use crate::config::schema::{Config, HttpTokenConfig};
+use crate::daemon::state::{DaemonState, ReloadableService};
+use crate::http::events::EventBroadcaster;
+use crate::http::server::HttpServer;
+use crate::ipc::protocol::HttpListenerStatus;
+
+/// How long a stopping server may spend finishing in-flight requests.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The settings that require new listeners when they change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HttpSettings {
+    enabled: bool,
+    bind: String,
+    port: u16,
+    token: Option<String>,
+    tokens: Vec<HttpTokenConfig>,
+    /// Bind address and port of a separate bot webhook listener.
+    bots: Option<(String, u16)>,
+}
+
+impl HttpSettings {
+    fn from_config(config: &Config) -> Self {
+        let daemon = &config.daemon;
+        Self {
+            enabled: daemon.http_enabled,
+            bind: daemon.http_bind.clone(),
+            port: daemon.http_port,
+            token: daemon.http_token.clone(),
+            tokens: daemon.http_tokens.clone(),
+            bots: config.bot.separate_listener(daemon),
+        }
+    }
+}
+
+/// What a reload did to the HTTP server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpChange {
+    Started(SocketAddr),
+    Stopped,
+    Restarted(SocketAddr),
+    /// The new settings could not be served; the API is off.
+    Failed(String),
+}
+
+impl fmt::Display for HttpChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Started(addr) => write!(f, "http: started on {addr}"),
+            Self::Stopped => write!(f, "http: stopped"),
+            Self::Restarted(addr) => write!(f, "http: restarted on {addr}"),
+            Self::Failed(err) => write!(f, "http: failed to start: {err}"),
+        }
+    }
+}
+
+struct RunningServer {
+    settings: HttpSettings,
+    addr: SocketAddr,
+    bot_addr: Option<SocketAddr>,
+    /// Shared by both listeners.
+    shutdown: CancellationToken,
+    handles: Vec<JoinHandle<()>>,
+}
+
+/// Owns the HTTP server task and restarts it as settings change.
+pub struct HttpSupervisor {
+    state: Weak<DaemonState>,
+    events: EventBroadcaster,
+    cancel: CancellationToken,
+    drain_timeout: Duration,
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl HttpSupervisor {
+    /// Servers stop when `cancel` (the daemon's shutdown token) fires.
+    pub fn new(
+        state: &Arc<DaemonState>,
+        events: EventBroadcaster,
+        cancel: CancellationToken,
+    ) -> Self {
+        Self {
+            state: Arc::downgrade(state),
+            events,
+            cancel,
+            drain_timeout: DRAIN_TIMEOUT,
+            running: Mutex::new(None),
+        }
+    }
+
+    pub fn with_drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    /// Address of the running server, if any.
+    pub async fn local_addr(&self) -> Option<SocketAddr> {
+        self.running.lock().await.as_ref().map(|server| server.addr)
+    }
+
+    /// Address of the separate bot webhook listener, if one is running.
+    pub async fn bot_addr(&self) -> Option<SocketAddr> {
+        self.running
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|server| server.bot_addr)
+    }
+
+    /// Start serving at daemon startup; bind failures are returned so the
+    /// daemon can exit with the right code.
+    pub async fn start(&self, config: &Config) -> anyhow::Result<Option<SocketAddr>> {
+        let settings = HttpSettings::from_config(config);
+        if !settings.enabled {
+            return Ok(None);
+        }
+        let mut running = self.running.lock().await;
+        if let Some(server) = running.take() {
+            self.stop_server(server).await;
+        }
+        let server = self.spawn(settings).await?;
+        let addr = server.addr;
+        *running = Some(server);
+        Ok(Some(addr))
+    }
+
+    /// Match the running server to `config`, returning what changed.
+    pub async fn reconcile(&self, config: &Config) -> Option<HttpChange> {
+        let desired = HttpSettings::from_config(config);
+        let mut running = self.running.lock().await;
+        let unchanged = match running.as_ref() {
+            Some(server) => desired.enabled && server.settings == desired,
+            None => !desired.enabled,
+        };
+        if unchanged {
+            return None;
+        }
+
+        let restarting = match running.take() {
+            Some(server) => {
+                self.stop_server(server).await;
+                if !desired.enabled {
+                    return Some(HttpChange::Stopped);
+                }
+                true
+            }
+            None => false,
+        };
+
+        match self.spawn(desired).await {
+            Ok(server) => {
+                let addr = server.addr;
+                *running = Some(server);
+                Some(if restarting {
+                    HttpChange::Restarted(addr)
+                } else {
+                    HttpChange::Started(addr)
+                })
+            }
+            Err(err) => {
+                error!(error = %format!("{err:#}"), "Failed to start HTTP API after reload");
+                Some(HttpChange::Failed(format!("{err:#}")))
+            }
+        }
+    }
+
+    /// Stop the server, draining in-flight requests.
+    pub async fn stop(&self) {
+        if let Some(server) = self.running.lock().await.take() {
+            self.stop_server(server).await;
+        }
+    }
+
+    async fn spawn(&self, settings: HttpSettings) -> anyhow::Result<RunningServer> {
+        let state = self
+            .state
+            .upgrade()
+            .context("Daemon state dropped; not starting HTTP API")?;
+        let shutdown = self.cancel.child_token();
+        let server = HttpServer::new(
+            &settings.bind,
+            settings.port,
+            shutdown.clone(),
+            Arc::clone(&state),
+            self.events.clone(),
+        )?
+        .with_auth_token(settings.token.clone())
+        .with_api_tokens(&settings.tokens);
+        let (server, bots) = match &settings.bots {
+            Some((bind, port)) => {
+                let (control, bots) = server.split_bot_listener(bind, *port)?;
+                (control, Some(bots))
+            }
+            None => (server, None),
+        };
+
+        let listener = server.bind().await?;
+        let addr = listener
+            .local_addr()
+            .context("Failed to read bound HTTP address")?;
+        let bots = match bots {
+            Some(bots) => {
+                let listener = bots.bind().await?;
+                let addr = listener
+                    .local_addr()
+                    .context("Failed to read bound bot webhook address")?;
+                Some((bots, listener, addr))
+            }
+            None => None,
+        };
+        let bot_addr = bots.as_ref().map(|(_, _, addr)| *addr);
+
+        let mut handles = vec![serve(
+            server,
+            listener,
+            info_span!("daemon.http", address = %addr),
+        )];
+        if let Some((bots, listener, addr)) = bots {
+            handles.push(serve(
+                bots,
+                listener,
+                info_span!("daemon.http.bots", address = %addr),
+            ));
+        }
+        state.set_http_listeners(Some(HttpListenerStatus {
+            api: addr.to_string(),
+            bots: bot_addr.map(|addr| addr.to_string()),
+        }));
+
+        Ok(RunningServer {
+            settings,
+            addr,
+            bot_addr,
+            shutdown,
+            handles,
+        })
+    }
+
+    async fn stop_server(&self, server: RunningServer) {
+        if let Some(state) = self.state.upgrade() {
+            state.set_http_listeners(None);
+        }
+        server.shutdown.cancel();
+        let deadline = time::Instant::now() + self.drain_timeout;
+        for handle in server.handles {
+            let abort = handle.abort_handle();
+            match time::timeout_at(deadline, handle).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => warn!(error = %err, "HTTP server task failed"),
+                Err(_) => {
+                    warn!(address = %server.addr, "HTTP server drain timed out; closing connections");
+                    abort.abort();
+                }
+            }
+        }
+        info!(address = %server.addr, "HTTP server stopped");
+    }
+}
+
+fn serve(server: HttpServer, listener: TcpListener, span: Span) -> JoinHandle<()> {
+    tokio::spawn(
+        async move {
+            if let Err(err) = server.serve(listener).await {
+                error!(error = %err, "HTTP server stopped with error");
+            }
+        }
+        .instrument(span),
+    )
+}
+
+#[async_trait]
+impl ReloadableService for HttpSupervisor {
+    async fn apply(&self, config: &Config) -> Option<String> {
+        self.reconcile(config)
+            .await
+            .map(|change| change.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon_config(enabled: bool, port: u16) -> Config {
+        let mut config = Config::default();
+        config.daemon.http_enabled = enabled;
+        config.daemon.http_bind = "127.0.0.1".to_string();
+        config.daemon.http_port = port;
+        config
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn supervisor(state: &Arc<DaemonState>) -> HttpSupervisor {
+        HttpSupervisor::new(state, EventBroadcaster::default(), CancellationToken::new())
+            .with_drain_timeout(Duration::from_secs(1))
+    }
+
+    #[test]
+    fn describes_changes_for_reload_response() {
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        assert_eq!(
+            HttpChange::Restarted(addr).to_string(),
+            "http: restarted on 127.0.0.1:8080"
+        );
+        assert_eq!(HttpChange::Stopped.to_string(), "http: stopped");
+    }
+
+    #[tokio::test]
+    async fn unchanged_settings_leave_server_alone() {
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let supervisor = supervisor(&state);
+        let config = daemon_config(true, free_port());
+
+        assert!(supervisor.start(&config).await.unwrap().is_some());
+        assert_eq!(supervisor.reconcile(&config).await, None);
+        assert_eq!(
+            supervisor.reconcile(&daemon_config(false, 0)).await,
+            Some(HttpChange::Stopped)
+        );
+        assert_eq!(supervisor.reconcile(&daemon_config(false, 0)).await, None);
+    }
+
+    #[tokio::test]
+    async fn port_change_restarts_on_new_port() {
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let supervisor = supervisor(&state);
+        let first = free_port();
+        supervisor.start(&daemon_config(true, first)).await.unwrap();
+
+        let second = free_port();
+        let change = supervisor.reconcile(&daemon_config(true, second)).await;
+
+        assert!(matches!(change, Some(HttpChange::Restarted(addr)) if addr.port() == second));
+        assert!(
+            tokio::net::TcpStream::connect(("127.0.0.1", first))
+                .await
+                .is_err()
+        );
+        assert!(
+            tokio::net::TcpStream::connect(("127.0.0.1", second))
+                .await
+                .is_ok()
+        );
+        supervisor.stop().await;
+    }
+
+    #[tokio::test]
+    async fn bind_failure_on_reload_is_reported() {
+        let state = Arc::new(DaemonState::new_without_auto_detection());
+        let supervisor = supervisor(&state);
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let change = supervisor.reconcile(&daemon_config(true, port)).await;
+
+        assert!(matches!(change, Some(HttpChange::Failed(_))));
+        assert_eq!(supervisor.local_addr().await, None);
+    }
+}
@@ -0,0 +1,312 @@
+//! English messages; every other catalog is checked against these keys.
+
+use super::Catalog;
+
+pub(crate) static CATALOG: Catalog = Catalog {
+    messages: &[
+        // Notification titles, by event type.
+        ("title.session_stopped", "Session stopped"),
+        (
+            "title.session_stopped_excluded",
+            "Stop detected (excluded from auto-resume)",
+        ),
+        ("title.resume_attempted", "Resume attempted"),
+        ("title.resume_succeeded", "Resume succeeded"),
+        ("title.resume_failed", "Resume failed"),
+        ("title.daemon_started", "Daemon started"),
+        ("title.daemon_stopped", "Daemon stopped"),
+        ("title.session_orphaned", "Orphaned session"),
+        ("title.session_deleted", "Session file deleted"),
+        ("title.model_changed", "Model changed"),
+        ("title.system_resumed", "System resumed"),
+        ("title.next_step_invalid", "Next-step.md not understood"),
+        (
+            "title.worktree_created",
+            "New session started in a worktree",
+        ),
+        ("title.resume_deferred", "Resume deferred"),
+        ("title.session_completed", "Session completed"),
+        ("title.resume_loop_suspected", "Resume loop suspected"),
+        ("title.resume_sidecar_invalid", "Resume sidecar invalid"),
+        ("title.clock_skew_detected", "Clock skew detected"),
+        (
+            "title.file_encoding_fixed",
+            "Session file is not plain UTF-8",
+        ),
+        ("title.scheduled_run", "Scheduled session"),
+        ("title.state_reconciled", "State file reconciled"),
+        (
+            "title.artifact_gc_pending",
+            "Artifact cleanup awaiting approval",
+        ),
+        ("title.state_read_only", "State file is read-only"),
+        ("title.action_observed", "Observed (not executed)"),
+        ("title.opencode_version_changed", "OpenCode version changed"),
+        ("title.control_applied", "Daemon control"),
+        ("title.test_notification", "Test notification"),
+        ("title.incident_escalated", "Incident escalated"),
+        ("title.incident_resolved", "Incident resolved"),
+        (
+            "title.unexpected_exit",
+            "Previous daemon instance terminated unexpectedly",
+        ),
+        // Notification bodies.
+        (
+            "body.session_stopped",
+            "Session stopped at {time}.\nSession: {session}\nReason: {reason}",
+        ),
+        ("body.details", "Details: {details}"),
+        (
+            "body.resume_attempted",
+            "Resume attempted at {time}.\nSession: {session}\nStrategy: {strategy}",
+        ),
+        (
+            "body.resume_succeeded",
+            "Resume succeeded at {time}.\nSession: {session}\nStrategy: {strategy}\nWait time: {wait}s",
+        ),
+        ("body.git", "Git: {git}"),
+        (
+            "body.resume_failed",
+            "Resume failed at {time}.\nSession: {session}\nStrategy: {strategy}\nError: {error}",
+        ),
+        ("body.incident", "Incident: {incident}"),
+        (
+            "body.daemon_started",
+            "Daemon started at {time}.\nVersion: {version}",
+        ),
+        (
+            "body.daemon_stopped",
+            "Daemon stopped at {time}.\nReason: {reason}",
+        ),
+        (
+            "body.session_orphaned",
+            "Orphaned session recorded at {time}.\nSession: {session}\nResumed from: {source}\nReason: {reason}\nResolve with: palingenesis orphans adopt|remove {session}",
+        ),
+        (
+            "body.session_deleted_restored",
+            "Session file deleted at {time}.\nSession: {session}\nRestored from backup: {backup}",
+        ),
+        (
+            "body.session_deleted",
+            "Session file deleted at {time}.\nSession: {session}\nNo backup restored; monitoring is on hold.\nResolve with: palingenesis attention clear",
+        ),
+        (
+            "body.model_changed",
+            "Session model changed at {time}.\nSession: {session}\nPrevious model: {previous}\nCurrent model: {current}",
+        ),
+        (
+            "body.system_resumed",
+            "System slept for {slept} (woke at {time}).\nPending resumes are re-checked before they run.",
+        ),
+        (
+            "body.next_step_invalid",
+            "No step found in {file} at {time}; resumed {session} from session progress instead.\nFirst lines:\n{lines}\nCheck the format with: palingenesis next-step check {file}",
+        ),
+        (
+            "body.worktree_created",
+            "New session for {session} started in worktree {worktree} (branch {branch}, from {workspace}) at {time}",
+        ),
+        (
+            "body.resume_deferred",
+            "Resume of {session} ({stop_reason}) deferred until {until} ({reason}) at {time}",
+        ),
+        (
+            "body.session_completed",
+            "Session {session} completed at {time} after {duration} ({resumes} resumes, {saved} saved)",
+        ),
+        (
+            "body.resume_loop_suspected",
+            "{summary} for {session} at {time}.\nAutomatic resumes are paused; fix the workflow, then run: palingenesis attention clear",
+        ),
+        (
+            "body.resume_sidecar_invalid",
+            "Ignored invalid resume sidecar {sidecar} for {session}: {error} at {time}",
+        ),
+        (
+            "body.clock_behind",
+            "Local clock is {skew}s behind the provider's at {time}; check NTP synchronization",
+        ),
+        (
+            "body.clock_ahead",
+            "Local clock is {skew}s ahead of the provider's at {time}; check NTP synchronization",
+        ),
+        (
+            "body.file_encoding_fixed",
+            "{file} is saved as {encoding}; it was decoded at {time}, but re-save it as UTF-8 without a byte order mark",
+        ),
+        (
+            "body.scheduled_run_succeeded",
+            "Schedule {schedule} ({trigger}) started a session at {time}.\nSession: {session}\nWorkdir: {workdir}",
+        ),
+        (
+            "body.scheduled_run_failed",
+            "Schedule {schedule} ({trigger}) could not start a session at {time}.\nWorkdir: {workdir}\nError: {detail}",
+        ),
+        (
+            "body.scheduled_run_skipped",
+            "Schedule {schedule} ({trigger}) was skipped at {time}: {detail}\nWorkdir: {workdir}",
+        ),
+        ("trigger.scheduled", "scheduled"),
+        ("trigger.catch_up", "catch-up after downtime"),
+        ("trigger.manual", "run by hand"),
+        (
+            "body.state_reconciled",
+            "The state file disagreed with the session files at startup; {count} corrections were made at {time}.\n{corrections}\nReport: {report}",
+        ),
+        (
+            "body.artifact_gc_pending",
+            "Under the new [maintenance] settings, {count} artifacts ({size}) are past their retention as of {time}. Nothing was deleted.\n{summary}\nPlan: {plan}\nRun `palingenesis gc approve` to let the scheduled cleanup delete them",
+        ),
+        (
+            "body.state_read_only",
+            "State file {file} was written by schema version {found}, newer than this build ({supported}), at {time}.\nIt is left untouched and state is kept in memory only; upgrade palingenesis or restart with --force-downgrade",
+        ),
+        (
+            "body.action_observed",
+            "OBSERVE: would run {action} for {session} ({stop_reason}) at {time}",
+        ),
+        ("body.control_applied", "{action} at {time}"),
+        ("body.test_notification", "{message} ({time})"),
+        (
+            "body.incident_escalated",
+            "Incident {incident} still open after {age} (escalation tier {tier}).\nSession: {session}\nOpened: {opened}",
+        ),
+        (
+            "body.incident_resolved",
+            "Incident {incident} ended after {age}: {resolution}.\nSession: {session}",
+        ),
+        ("resolution.resumed", "the session resumed"),
+        ("resolution.acknowledged", "acknowledged"),
+        ("resolution.gave_up", "automatic resumes gave up"),
+        ("resolution.reconciled", "closed on reconciliation"),
+        (
+            "body.unexpected_exit",
+            "The previous daemon (PID {pid}) ended with a {cause}: {detail}\nDetected at {time}",
+        ),
+        ("exit_cause.error", "fatal error"),
+        ("exit_cause.panic", "panic"),
+        ("exit_cause.killed", "kill (SIGKILL or out of memory)"),
+        ("body.progress", "Progress: {progress}"),
+        (
+            "version.changed",
+            "OpenCode changed from {previous} to {current} at {time}",
+        ),
+        ("version.detected", "OpenCode {current} detected at {time}"),
+        ("version.checks_passed", "Compatibility checks passed"),
+        ("version.checks_failed", "Compatibility checks failed:"),
+        (
+            "loop.summary_one",
+            "{resumes} resumes in {minutes} minute — possible workflow loop",
+        ),
+        (
+            "loop.summary",
+            "{resumes} resumes in {minutes} minutes — possible workflow loop",
+        ),
+        ("control.pause", "Daemon paused by {initiator}"),
+        ("control.resume", "Daemon resumed by {initiator}"),
+        ("control.new_session", "New session started by {initiator}"),
+        ("control.other", "{action} applied by {initiator}"),
+        (
+            "notice.excluded",
+            "Stop detected (excluded from auto-resume by `{pattern}`)",
+        ),
+        (
+            "notice.ack_link",
+            "Acknowledge: {url} (or `palingenesis ack {incident}`)",
+        ),
+        ("notice.ack", "Acknowledge: `palingenesis ack {incident}`"),
+        ("notice.instance", "Instance: {instance}"),
+        ("common.unknown", "unknown"),
+        ("common.not_restored", "not restored"),
+        // Field labels of Slack and Discord messages.
+        ("label.branch", "Branch"),
+        ("label.by", "By"),
+        ("label.compatibility_problems", "Compatibility problems"),
+        ("label.current", "Current"),
+        ("label.deferred_until", "Deferred until"),
+        ("label.details", "Details"),
+        ("label.duration", "Duration"),
+        ("label.encoding", "Encoding"),
+        ("label.error", "Error"),
+        ("label.file", "File"),
+        ("label.first_lines", "First lines"),
+        ("label.incident", "Incident"),
+        ("label.message", "Message"),
+        ("label.model", "Model"),
+        ("label.note", "Note"),
+        ("label.previous", "Previous"),
+        ("label.reason", "Reason"),
+        ("label.resolution", "Resolution"),
+        ("label.restored_from", "Restored from"),
+        ("label.resumed_from", "Resumed from"),
+        ("label.resumes", "Resumes"),
+        ("label.schedule", "Schedule"),
+        ("label.session", "Session"),
+        ("label.sidecar", "Sidecar"),
+        ("label.skew", "Skew"),
+        ("label.slept_for", "Slept for"),
+        ("label.strategy", "Strategy"),
+        ("label.tier", "Escalation tier"),
+        ("label.time_saved", "Time saved"),
+        ("label.version", "Version"),
+        ("label.wait_time", "Wait time"),
+        ("label.workdir", "Workdir"),
+        ("label.worktree", "Worktree"),
+        ("label.would_run", "Would run"),
+        // `palingenesis notify digest`.
+        ("digest.none", "No escalations recorded"),
+        (
+            "digest.summary",
+            "{escalations} escalation(s) across {incidents} incident(s), {open} still open",
+        ),
+        ("digest.session", "session: {session}"),
+        ("digest.opened", "opened: {opened}"),
+        ("digest.tier", "tier {tier} after {age}: {channels}"),
+        ("digest.resolved", "ended after {age}: {resolution}"),
+        ("digest.open", "still open"),
+        ("digest.costs", "Resume costs: {costs}"),
+        // `palingenesis status`.
+        ("status.not_running", "Daemon not running"),
+        ("status.unresponsive", "Daemon unresponsive"),
+        ("status.running", "palingenesis daemon: running"),
+        ("status.pid", "PID: {pid}"),
+        ("status.instance", "Instance: {instance}"),
+        ("status.state", "State: {state}"),
+        ("status.mode", "Mode: {mode}"),
+        (
+            "status.deferred_until",
+            "Auto-resume: deferred until {until} (maintenance window)",
+        ),
+        ("status.deferred", "Auto-resume: deferred: {reason}"),
+        (
+            "status.quota",
+            "Auto-resume: provider quota exhausted; retrying once at {at}",
+        ),
+        (
+            "status.attention",
+            "Attention: current session needs attention (see `palingenesis attention clear`)",
+        ),
+        ("status.uptime", "Uptime: {uptime}"),
+        ("status.current_session", "Current session: {session}"),
+        ("status.no_session", "Current session: none"),
+        ("status.saves", "Saves: {count}"),
+        ("status.total_resumes", "Total resumes: {count}"),
+        ("status.time_saved", "Time saved: {saved}"),
+        (
+            "status.opencode_problems",
+            "OpenCode: {version} failed compatibility checks (see `palingenesis status --verbose`)",
+        ),
+        ("status.opencode_version", "OpenCode version: {version}"),
+        ("status.opencode_endpoint", "OpenCode endpoint: {endpoint}"),
+        ("status.clock_skew", "Provider clock skew: {skew}"),
+        ("status.debounce", "Debounce window: {window} ms"),
+        ("status.http_api", "HTTP API: {address}"),
+        ("status.http_bots", "Bot webhooks: {address}"),
+        ("status.last_exit", "Last exit: {exit}"),
+        (
+            "status.state_read_only",
+            "State: read-only (file has schema v{found}, this build v{supported}; restart with --force-downgrade to rewrite it)",
+        ),
+    ],
+    fallback: &[],
+};
@@ -0,0 +1,325 @@
+//! Japanese messages.
+//!
+//! Command lines (`palingenesis ...`), product names and the raw values
+//! filled into placeholders are left as they are.
+
+use super::Catalog;
+
+pub(crate) static CATALOG: Catalog = Catalog {
+    messages: &[
+        ("title.session_stopped", "セッションが停止しました"),
+        (
+            "title.session_stopped_excluded",
+            "停止を検出しました（自動再開の対象外）",
+        ),
+        ("title.resume_attempted", "再開を試行しました"),
+        ("title.resume_succeeded", "再開に成功しました"),
+        ("title.resume_failed", "再開に失敗しました"),
+        ("title.daemon_started", "デーモンが起動しました"),
+        ("title.daemon_stopped", "デーモンが停止しました"),
+        ("title.session_orphaned", "孤立したセッション"),
+        (
+            "title.session_deleted",
+            "セッションファイルが削除されました",
+        ),
+        ("title.model_changed", "モデルが変更されました"),
+        ("title.system_resumed", "システムがスリープから復帰しました"),
+        ("title.next_step_invalid", "Next-step.md を解釈できません"),
+        (
+            "title.worktree_created",
+            "ワークツリーで新しいセッションを開始しました",
+        ),
+        ("title.resume_deferred", "再開を延期しました"),
+        ("title.session_completed", "セッションが完了しました"),
+        ("title.resume_loop_suspected", "再開ループの疑い"),
+        ("title.resume_sidecar_invalid", "再開サイドカーが無効です"),
+        ("title.clock_skew_detected", "時刻のずれを検出しました"),
+        (
+            "title.file_encoding_fixed",
+            "セッションファイルが UTF-8 ではありません",
+        ),
+        ("title.scheduled_run", "スケジュール実行"),
+        ("title.state_reconciled", "状態ファイルを整合しました"),
+        ("title.artifact_gc_pending", "成果物の削除が承認待ちです"),
+        ("title.state_read_only", "状態ファイルは読み取り専用です"),
+        ("title.action_observed", "観測のみ（未実行）"),
+        (
+            "title.opencode_version_changed",
+            "OpenCode のバージョンが変わりました",
+        ),
+        ("title.control_applied", "デーモン操作"),
+        ("title.test_notification", "テスト通知"),
+        (
+            "title.incident_escalated",
+            "インシデントをエスカレーションしました",
+        ),
+        ("title.incident_resolved", "インシデントが終了しました"),
+        (
+            "title.unexpected_exit",
+            "前回のデーモンが予期せず終了しました",
+        ),
+        (
+            "body.session_stopped",
+            "{time} にセッションが停止しました。\nセッション: {session}\n理由: {reason}",
+        ),
+        ("body.details", "詳細: {details}"),
+        (
+            "body.resume_attempted",
+            "{time} に再開を試行しました。\nセッション: {session}\n戦略: {strategy}",
+        ),
+        (
+            "body.resume_succeeded",
+            "{time} に再開に成功しました。\nセッション: {session}\n戦略: {strategy}\n待機時間: {wait}秒",
+        ),
+        ("body.git", "Git: {git}"),
+        (
+            "body.resume_failed",
+            "{time} に再開に失敗しました。\nセッション: {session}\n戦略: {strategy}\nエラー: {error}",
+        ),
+        ("body.incident", "インシデント: {incident}"),
+        (
+            "body.daemon_started",
+            "{time} にデーモンが起動しました。\nバージョン: {version}",
+        ),
+        (
+            "body.daemon_stopped",
+            "{time} にデーモンが停止しました。\n理由: {reason}",
+        ),
+        (
+            "body.session_orphaned",
+            "{time} に孤立したセッションを記録しました。\nセッション: {session}\n再開元: {source}\n理由: {reason}\n対処: palingenesis orphans adopt|remove {session}",
+        ),
+        (
+            "body.session_deleted_restored",
+            "{time} にセッションファイルが削除されました。\nセッション: {session}\nバックアップから復元: {backup}",
+        ),
+        (
+            "body.session_deleted",
+            "{time} にセッションファイルが削除されました。\nセッション: {session}\nバックアップは復元されていないため、監視を保留しています。\n対処: palingenesis attention clear",
+        ),
+        (
+            "body.model_changed",
+            "{time} にセッションのモデルが変更されました。\nセッション: {session}\n変更前のモデル: {previous}\n現在のモデル: {current}",
+        ),
+        (
+            "body.system_resumed",
+            "システムは {slept} スリープしていました（{time} に復帰）。\n保留中の再開は実行前に再確認されます。",
+        ),
+        (
+            "body.next_step_invalid",
+            "{time} に {file} でステップが見つからなかったため、{session} をセッションの進捗から再開しました。\n先頭の行:\n{lines}\n形式の確認: palingenesis next-step check {file}",
+        ),
+        (
+            "body.worktree_created",
+            "{time} に {session} の新しいセッションをワークツリー {worktree} で開始しました（ブランチ {branch}、元: {workspace}）",
+        ),
+        (
+            "body.resume_deferred",
+            "{time} に {session}（{stop_reason}）の再開を {until} まで延期しました（{reason}）",
+        ),
+        (
+            "body.session_completed",
+            "セッション {session} が {time} に完了しました。所要時間 {duration}（再開 {resumes} 回、{saved} 節約）",
+        ),
+        (
+            "body.resume_loop_suspected",
+            "{time} に {session} で {summary}。\n自動再開は停止中です。ワークフローを修正してから実行してください: palingenesis attention clear",
+        ),
+        (
+            "body.resume_sidecar_invalid",
+            "{time} に {session} の無効な再開サイドカー {sidecar} を無視しました: {error}",
+        ),
+        (
+            "body.clock_behind",
+            "{time} 時点でローカル時計がプロバイダーより {skew} 秒遅れています。NTP の同期を確認してください",
+        ),
+        (
+            "body.clock_ahead",
+            "{time} 時点でローカル時計がプロバイダーより {skew} 秒進んでいます。NTP の同期を確認してください",
+        ),
+        (
+            "body.file_encoding_fixed",
+            "{file} は {encoding} で保存されています。{time} に変換して読み込みましたが、BOM なしの UTF-8 で保存し直してください",
+        ),
+        (
+            "body.scheduled_run_succeeded",
+            "{time} にスケジュール {schedule}（{trigger}）がセッションを開始しました。\nセッション: {session}\n作業ディレクトリ: {workdir}",
+        ),
+        (
+            "body.scheduled_run_failed",
+            "{time} にスケジュール {schedule}（{trigger}）のセッションを開始できませんでした。\n作業ディレクトリ: {workdir}\nエラー: {detail}",
+        ),
+        (
+            "body.scheduled_run_skipped",
+            "{time} にスケジュール {schedule}（{trigger}）をスキップしました: {detail}\n作業ディレクトリ: {workdir}",
+        ),
+        ("trigger.scheduled", "定期実行"),
+        ("trigger.catch_up", "停止中の分の実行"),
+        ("trigger.manual", "手動実行"),
+        (
+            "body.state_reconciled",
+            "起動時に状態ファイルとセッションファイルの食い違いが見つかり、{time} に {count} 件を修正しました。\n{corrections}\nレポート: {report}",
+        ),
+        (
+            "body.artifact_gc_pending",
+            "{time} 時点で、新しい [maintenance] 設定では {count} 件（{size}）が保持期間を過ぎています。まだ何も削除していません。\n{summary}\n計画: {plan}\n`palingenesis gc approve` を実行すると定期クリーンアップで削除されます",
+        ),
+        (
+            "body.state_read_only",
+            "{time} 時点で状態ファイル {file} はスキーマバージョン {found} で書かれており、このビルド（{supported}）より新しいため変更しません。\n状態はメモリ上にのみ保持されます。palingenesis を更新するか、--force-downgrade を付けて再起動してください",
+        ),
+        (
+            "body.action_observed",
+            "観測モード: {time} に {session}（{stop_reason}）に対して {action} を実行するところでした",
+        ),
+        ("body.control_applied", "{time} に{action}"),
+        ("body.test_notification", "{message}（{time}）"),
+        (
+            "body.incident_escalated",
+            "インシデント {incident} が {age} 未解決のままです（エスカレーション段階 {tier}）。\nセッション: {session}\n発生: {opened}",
+        ),
+        (
+            "body.incident_resolved",
+            "インシデント {incident} は {age} 後に終了しました: {resolution}。\nセッション: {session}",
+        ),
+        ("resolution.resumed", "セッションが再開されました"),
+        ("resolution.acknowledged", "確認済み"),
+        ("resolution.gave_up", "自動再開を断念しました"),
+        ("resolution.reconciled", "起動時の整合処理で終了しました"),
+        (
+            "body.unexpected_exit",
+            "前回のデーモン（PID {pid}）は{cause}で終了しました: {detail}\n検出: {time}",
+        ),
+        ("exit_cause.error", "致命的なエラー"),
+        ("exit_cause.panic", "パニック"),
+        ("exit_cause.killed", "強制終了（SIGKILL またはメモリ不足）"),
+        ("body.progress", "進捗: {progress}"),
+        (
+            "version.changed",
+            "{time} に OpenCode が {previous} から {current} に変わりました",
+        ),
+        (
+            "version.detected",
+            "{time} に OpenCode {current} を検出しました",
+        ),
+        ("version.checks_passed", "互換性チェックに合格しました"),
+        ("version.checks_failed", "互換性チェックに失敗しました:"),
+        (
+            "loop.summary_one",
+            "{minutes} 分間に {resumes} 回の再開 — ワークフローがループしている可能性があります",
+        ),
+        (
+            "loop.summary",
+            "{minutes} 分間に {resumes} 回の再開 — ワークフローがループしている可能性があります",
+        ),
+        ("control.pause", "{initiator} がデーモンを一時停止しました"),
+        ("control.resume", "{initiator} がデーモンを再開しました"),
+        (
+            "control.new_session",
+            "{initiator} が新しいセッションを開始しました",
+        ),
+        ("control.other", "{initiator} が {action} を適用しました"),
+        (
+            "notice.excluded",
+            "停止を検出しました（`{pattern}` により自動再開の対象外）",
+        ),
+        (
+            "notice.ack_link",
+            "確認: {url}（または `palingenesis ack {incident}`）",
+        ),
+        ("notice.ack", "確認: `palingenesis ack {incident}`"),
+        ("notice.instance", "インスタンス: {instance}"),
+        ("common.unknown", "不明"),
+        ("common.not_restored", "未復元"),
+        ("label.branch", "ブランチ"),
+        ("label.by", "実行者"),
+        ("label.compatibility_problems", "互換性の問題"),
+        ("label.current", "現在"),
+        ("label.deferred_until", "延期先"),
+        ("label.details", "詳細"),
+        ("label.duration", "所要時間"),
+        ("label.encoding", "エンコーディング"),
+        ("label.error", "エラー"),
+        ("label.file", "ファイル"),
+        ("label.first_lines", "先頭の行"),
+        ("label.incident", "インシデント"),
+        ("label.message", "メッセージ"),
+        ("label.model", "モデル"),
+        ("label.note", "注記"),
+        ("label.previous", "変更前"),
+        ("label.reason", "理由"),
+        ("label.resolution", "結果"),
+        ("label.restored_from", "復元元"),
+        ("label.resumed_from", "再開元"),
+        ("label.resumes", "再開回数"),
+        ("label.schedule", "スケジュール"),
+        ("label.session", "セッション"),
+        ("label.sidecar", "サイドカー"),
+        ("label.skew", "ずれ"),
+        ("label.slept_for", "スリープ時間"),
+        ("label.strategy", "戦略"),
+        ("label.tier", "エスカレーション段階"),
+        ("label.time_saved", "節約時間"),
+        ("label.version", "バージョン"),
+        ("label.wait_time", "待機時間"),
+        ("label.workdir", "作業ディレクトリ"),
+        ("label.worktree", "ワークツリー"),
+        ("label.would_run", "実行予定"),
+        ("digest.none", "エスカレーションの記録はありません"),
+        (
+            "digest.summary",
+            "{incidents} 件のインシデントで {escalations} 回エスカレーション、うち {open} 件が未解決",
+        ),
+        ("digest.session", "セッション: {session}"),
+        ("digest.opened", "発生: {opened}"),
+        ("digest.tier", "{age} 後に段階 {tier}: {channels}"),
+        ("digest.resolved", "{age} 後に終了: {resolution}"),
+        ("digest.open", "未解決"),
+        ("digest.costs", "再開コスト: {costs}"),
+        ("status.not_running", "デーモンは起動していません"),
+        ("status.unresponsive", "デーモンが応答しません"),
+        ("status.running", "palingenesis デーモン: 実行中"),
+        ("status.pid", "PID: {pid}"),
+        ("status.instance", "インスタンス: {instance}"),
+        ("status.state", "状態: {state}"),
+        ("status.mode", "モード: {mode}"),
+        (
+            "status.deferred_until",
+            "自動再開: {until} まで延期（メンテナンス時間帯）",
+        ),
+        ("status.deferred", "自動再開: 延期中: {reason}"),
+        (
+            "status.quota",
+            "自動再開: プロバイダーの利用枠を使い切りました。{at} に一度だけ再試行します",
+        ),
+        (
+            "status.attention",
+            "要対応: 現在のセッションに対応が必要です（`palingenesis attention clear` を参照）",
+        ),
+        ("status.uptime", "稼働時間: {uptime}"),
+        ("status.current_session", "現在のセッション: {session}"),
+        ("status.no_session", "現在のセッション: なし"),
+        ("status.saves", "保存回数: {count}"),
+        ("status.total_resumes", "再開回数の合計: {count}"),
+        ("status.time_saved", "節約時間: {saved}"),
+        (
+            "status.opencode_problems",
+            "OpenCode: {version} は互換性チェックに失敗しました（`palingenesis status --verbose` を参照）",
+        ),
+        ("status.opencode_version", "OpenCode バージョン: {version}"),
+        (
+            "status.opencode_endpoint",
+            "OpenCode エンドポイント: {endpoint}",
+        ),
+        ("status.clock_skew", "プロバイダーとの時刻のずれ: {skew}"),
+        ("status.debounce", "デバウンス間隔: {window} ms"),
+        ("status.http_api", "HTTP API: {address}"),
+        ("status.http_bots", "ボット Webhook: {address}"),
+        ("status.last_exit", "前回の終了: {exit}"),
+        (
+            "status.state_read_only",
+            "状態: 読み取り専用（ファイルはスキーマ v{found}、このビルドは v{supported}。書き直すには --force-downgrade を付けて再起動）",
+        ),
+    ],
+    fallback: &[],
+};
@@ -0,0 +1,224 @@
+//! Translated user-facing text.
+//!
+//! Notification titles, bodies and field labels and the human `status`
+//! output are looked up here by key, in the locale chosen by
+//! `daemon.locale` or a notification channel's `locale`. Log and tracing
+//! messages, JSON output and webhook payload fields stay English so they
+//! can be searched and parsed the same way everywhere.
+//!
+//! Each locale is a key → template table compiled into the binary.
+//! Templates use `{name}` placeholders, filled by [`render`] the same way
+//! in every locale. A key a locale lacks falls back to English; the daemon
+//! lists such keys at startup unless the catalog marks them as deliberate
+//! fallbacks.
+
+mod en;
+mod ja;
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Language of user-facing text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Locale {
+    /// Every locale with a shipped catalog.
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Ja];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Ja => "ja",
+        }
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Self::En => &en::CATALOG,
+            Self::Ja => &ja::CATALOG,
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One locale's messages.
+pub(crate) struct Catalog {
+    pub(crate) messages: &'static [(&'static str, &'static str)],
+    /// Keys deliberately left to the English text.
+    pub(crate) fallback: &'static [&'static str],
+}
+
+impl Catalog {
+    fn get(&self, key: &str) -> Option<&'static str> {
+        self.messages
+            .iter()
+            .find(|(candidate, _)| *candidate == key)
+            .map(|(_, message)| *message)
+    }
+}
+
+/// Message for `key` in `locale`, falling back to English and then to the
+/// key itself.
+pub fn text(locale: Locale, key: &str) -> &str {
+    locale
+        .catalog()
+        .get(key)
+        .or_else(|| en::CATALOG.get(key))
+        .unwrap_or(key)
+}
+
+/// Message for `key` with its `{name}` placeholders filled from `args`.
+///
+/// Placeholders without a matching argument are left as written.
+pub fn render(locale: Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    fill(text(locale, key), args)
+}
+
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| (value.to_string(), end))
+        });
+        match value {
+            Some((value, end)) => {
+                output.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// English keys `locale` neither translates nor marks as a fallback.
+pub fn missing_keys(locale: Locale) -> Vec<&'static str> {
+    let catalog = locale.catalog();
+    en::CATALOG
+        .messages
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| catalog.get(key).is_none() && !catalog.fallback.contains(key))
+        .collect()
+}
+
+/// Warn once per locale in use that still lacks translations.
+pub fn warn_missing(locales: impl IntoIterator<Item = Locale>) {
+    let mut seen = Vec::new();
+    for locale in locales {
+        if seen.contains(&locale) {
+            continue;
+        }
+        seen.push(locale);
+        let missing = missing_keys(locale);
+        if !missing.is_empty() {
+            warn!(
+                locale = %locale,
+                keys = %missing.join(", "),
+                "Messages missing from the locale's catalog fall back to English"
+            );
+        }
+    }
+}
+
+/// `daemon.locale` from the config file, for CLI output; English if the
+/// config cannot be read.
+pub fn configured_locale() -> Locale {
+    crate::daemon::state::load_config_from_disk()
+        .map(|config| config.daemon.locale)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(template: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn every_locale_covers_the_english_keys() {
+        for locale in Locale::ALL {
+            assert_eq!(missing_keys(locale), Vec::<&str>::new(), "{locale}");
+        }
+    }
+
+    #[test]
+    fn translations_use_the_english_placeholders() {
+        for locale in Locale::ALL {
+            for (key, message) in locale.catalog().messages {
+                let english = en::CATALOG
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{locale} has unknown key {key}"));
+                assert_eq!(
+                    placeholders(message),
+                    placeholders(english),
+                    "{locale} {key}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn catalogs_have_no_duplicate_keys() {
+        for locale in Locale::ALL {
+            let mut keys: Vec<_> = locale.catalog().messages.iter().map(|(k, _)| k).collect();
+            let total = keys.len();
+            keys.sort_unstable();
+            keys.dedup();
+            assert_eq!(keys.len(), total, "{locale}");
+        }
+    }
+
+    #[test]
+    fn render_fills_placeholders_in_any_locale() {
+        let args: &[(&str, &dyn Display)] = &[("instance", &"work")];
+        assert_eq!(
+            render(Locale::En, "notice.instance", args),
+            "Instance: work"
+        );
+        assert_eq!(
+            render(Locale::Ja, "notice.instance", args),
+            "インスタンス: work"
+        );
+        assert_eq!(fill("{a} {missing} {", &[("a", &1)]), "1 {missing} {");
+    }
+
+    #[test]
+    fn locale_parses_from_config_names() {
+        let locale: Locale = serde_json::from_str("\"ja\"").unwrap();
+        assert_eq!(locale, Locale::Ja);
+        assert_eq!(Locale::default(), Locale::En);
+    }
+}
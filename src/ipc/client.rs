@@ -1,11 +1,18 @@
+use std::borrow::Cow;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixStream;
-use tracing::debug;
+use tracing::{Level, debug};
 
 use crate::config::Paths;
-use crate::ipc::protocol::{DaemonStatus, IpcCommand, IpcResponse};
+use crate::config::schema::DaemonMode;
+use crate::daemon::initiator::Initiator;
+use crate::daemon::jobs::JobStatus;
+use crate::ipc::protocol::{DaemonStatus, IpcCommand, IpcFrame, IpcResponse};
+use crate::state::{MuteKind, NotificationMute, ScheduleRun};
+use crate::telemetry::log_buffer::{LogBatch, LogRecord};
 
 #[cfg(test)]
 const CONNECTION_TIMEOUT_SECS: u64 = 1;
@@ -28,6 +35,15 @@ pub enum IpcClientError {
     Protocol(String),
 }
 
+/// How the daemon handled a state-changing command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandAck<T = ()> {
+    /// The command took effect.
+    Applied(T),
+    /// The command waits for a pipeline safe point; says what it waits for.
+    Deferred(String),
+}
+
 pub struct IpcClient {
     path: PathBuf,
     reader: BufReader<tokio::net::unix::OwnedReadHalf>,
@@ -37,7 +53,7 @@ pub struct IpcClient {
 impl IpcClient {
     /// Connect to the daemon's IPC socket.
     pub async fn connect() -> Result<Self, IpcClientError> {
-        let path = Paths::runtime_dir().join("palingenesis.sock");
+        let path = Paths::socket_file();
         Self::connect_with_path(path).await
     }
 
@@ -70,7 +86,7 @@ impl IpcClient {
 
     /// Send a command to the daemon and read the response.
     pub async fn send_command(&mut self, cmd: IpcCommand) -> Result<IpcResponse, IpcClientError> {
-        let command = Self::command_text(&cmd);
+        let command = Self::request_text(&cmd);
         debug!(
             path = %self.path.display(),
             command = %command.trim_end(),
@@ -108,46 +124,287 @@ impl IpcClient {
 
     /// Request daemon status.
     pub async fn status() -> Result<DaemonStatus, IpcClientError> {
-        let mut client = Self::connect().await?;
+        Self::status_at(Paths::socket_file()).await
+    }
+
+    /// Request status from the daemon listening on `path` (another instance's socket).
+    pub async fn status_at(path: PathBuf) -> Result<DaemonStatus, IpcClientError> {
+        let mut client = Self::connect_with_path(path).await?;
         let response = client.send_command(IpcCommand::Status).await?;
         Self::expect_status(response)
     }
 
+    /// List queued and running daemon jobs.
+    pub async fn jobs() -> Result<Vec<JobStatus>, IpcClientError> {
+        let mut client = Self::connect().await?;
+        match client.send_command(IpcCommand::Jobs).await? {
+            IpcResponse::Jobs(jobs) => Ok(jobs),
+            IpcResponse::Error { message } => Err(IpcClientError::Protocol(message)),
+            _ => Err(IpcClientError::Protocol(
+                "Unexpected response to JOBS".to_string(),
+            )),
+        }
+    }
+
+    /// Ask the daemon to save its runtime state for a restart and shut down.
+    pub async fn handoff() -> Result<(), IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client.send_command(IpcCommand::Handoff).await?;
+        Self::expect_ok(response)
+    }
+
     /// Pause daemon monitoring.
-    pub async fn pause() -> Result<(), IpcClientError> {
+    pub async fn pause() -> Result<CommandAck, IpcClientError> {
         let mut client = Self::connect().await?;
         let response = client.send_command(IpcCommand::Pause).await?;
-        Self::expect_ok(response)
+        Self::expect_ack(response)
     }
 
     /// Resume daemon monitoring.
-    pub async fn resume() -> Result<(), IpcClientError> {
+    pub async fn resume() -> Result<CommandAck, IpcClientError> {
         let mut client = Self::connect().await?;
         let response = client.send_command(IpcCommand::Resume).await?;
+        Self::expect_ack(response)
+    }
+
+    /// Acknowledge an open incident, silencing its non-critical notifications.
+    pub async fn ack(incident_id: &str) -> Result<(), IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client
+            .send_command(IpcCommand::Ack(incident_id.to_string()))
+            .await?;
         Self::expect_ok(response)
     }
 
-    /// Reload daemon configuration.
-    pub async fn reload() -> Result<(), IpcClientError> {
+    /// Release the current session held for attention, returning what was
+    /// done, or `None` if no session needed attention.
+    pub async fn clear_attention() -> Result<Option<String>, IpcClientError> {
+        let mut client = Self::connect().await?;
+        match client.send_command(IpcCommand::ClearAttention).await? {
+            IpcResponse::Reloaded { changes } => Ok(changes.into_iter().next()),
+            other => Self::expect_ok(other).map(|()| None),
+        }
+    }
+
+    /// Re-resolve one notification channel's secrets, returning whether
+    /// they changed.
+    pub async fn rotate_secret(channel: &str) -> Result<bool, IpcClientError> {
+        let mut client = Self::connect().await?;
+        match client
+            .send_command(IpcCommand::RotateSecret(channel.to_string()))
+            .await?
+        {
+            IpcResponse::Reloaded { changes } => Ok(!changes.is_empty()),
+            other => Self::expect_ok(other).map(|()| false),
+        }
+    }
+
+    /// Fire a `[[schedules]]` entry now.
+    pub async fn run_schedule(name: &str) -> Result<ScheduleRun, IpcClientError> {
+        let mut client = Self::connect().await?;
+        match client
+            .send_command(IpcCommand::RunSchedule(name.to_string()))
+            .await?
+        {
+            IpcResponse::ScheduleRun(run) => Ok(*run),
+            IpcResponse::Error { message } => Err(IpcClientError::Protocol(message)),
+            _ => Err(IpcClientError::Protocol(
+                "Unexpected response to RUN_SCHEDULE".to_string(),
+            )),
+        }
+    }
+
+    /// Mute a notification channel or event type for `duration`, or until
+    /// unmuted, returning the active mutes.
+    pub async fn mute(
+        kind: MuteKind,
+        name: &str,
+        duration: Option<Duration>,
+    ) -> Result<Vec<NotificationMute>, IpcClientError> {
         let mut client = Self::connect().await?;
-        let response = client.send_command(IpcCommand::Reload).await?;
+        let response = client
+            .send_command(IpcCommand::Mute {
+                kind,
+                name: name.to_string(),
+                duration,
+            })
+            .await?;
+        Self::expect_mutes(response)
+    }
+
+    /// Lift a mute, returning the ones still active.
+    pub async fn unmute(
+        kind: MuteKind,
+        name: &str,
+    ) -> Result<Vec<NotificationMute>, IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client
+            .send_command(IpcCommand::Unmute {
+                kind,
+                name: name.to_string(),
+            })
+            .await?;
+        Self::expect_mutes(response)
+    }
+
+    /// Active notification mutes.
+    pub async fn mutes() -> Result<Vec<NotificationMute>, IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client.send_command(IpcCommand::Mutes).await?;
+        Self::expect_mutes(response)
+    }
+
+    /// Switch the daemon between active and observe mode.
+    pub async fn set_mode(mode: DaemonMode) -> Result<(), IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client.send_command(IpcCommand::SetMode(mode)).await?;
         Self::expect_ok(response)
     }
 
+    /// Reload daemon configuration, returning what was done to running
+    /// services (e.g. `http: restarted on 127.0.0.1:8080`).
+    pub async fn reload() -> Result<CommandAck<Vec<String>>, IpcClientError> {
+        let mut client = Self::connect().await?;
+        match client.send_command(IpcCommand::Reload).await? {
+            IpcResponse::Reloaded { changes } => Ok(CommandAck::Applied(changes)),
+            other => Self::expect_ack(other).map(|ack| match ack {
+                CommandAck::Applied(()) => CommandAck::Applied(Vec::new()),
+                CommandAck::Deferred(message) => CommandAck::Deferred(message),
+            }),
+        }
+    }
+
     /// Force a new session.
-    pub async fn new_session() -> Result<(), IpcClientError> {
+    pub async fn new_session() -> Result<CommandAck, IpcClientError> {
         let mut client = Self::connect().await?;
         let response = client.send_command(IpcCommand::NewSession).await?;
-        Self::expect_ok(response)
+        Self::expect_ack(response)
+    }
+
+    /// Start a new session, bypassing the resume rate limit.
+    pub async fn force_new_session() -> Result<CommandAck, IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client.send_command(IpcCommand::ForceNewSession).await?;
+        Self::expect_ack(response)
+    }
+
+    /// The newest `lines` buffered log records at `level` or more severe.
+    pub async fn logs(lines: usize, level: Option<Level>) -> Result<LogBatch, IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client
+            .send_command(IpcCommand::Logs {
+                lines,
+                level,
+                follow: false,
+            })
+            .await?;
+        Self::expect_logs(response)
     }
 
-    fn command_text(cmd: &IpcCommand) -> &'static str {
+    /// Like [`Self::logs`], then hand each new record to `on_record` until
+    /// the daemon closes the connection.
+    pub async fn follow_logs(
+        lines: usize,
+        level: Option<Level>,
+        mut on_record: impl FnMut(LogRecord),
+    ) -> Result<(), IpcClientError> {
+        let mut client = Self::connect().await?;
+        let response = client
+            .send_command(IpcCommand::Logs {
+                lines,
+                level,
+                follow: true,
+            })
+            .await?;
+        for record in Self::expect_logs(response)?.records {
+            on_record(record);
+        }
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if client.reader.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+            let record: LogRecord = serde_json::from_str(line.trim_end()).map_err(|error| {
+                IpcClientError::Protocol(format!("Invalid log record: {error}"))
+            })?;
+            on_record(record);
+        }
+    }
+
+    /// Commands that act on the daemon go out as a version 2 frame naming
+    /// this CLI as the initiator; queries stay bare lines.
+    fn request_text(cmd: &IpcCommand) -> Cow<'static, str> {
+        let text = Self::command_text(cmd);
+        if !matches!(
+            cmd,
+            IpcCommand::Pause
+                | IpcCommand::Resume
+                | IpcCommand::NewSession
+                | IpcCommand::ForceNewSession
+                | IpcCommand::SetMode(_)
+                | IpcCommand::Ack(_)
+                | IpcCommand::RotateSecret(_)
+                | IpcCommand::RunSchedule(_)
+                | IpcCommand::Mute { .. }
+                | IpcCommand::Unmute { .. }
+                | IpcCommand::ClearAttention
+        ) {
+            return text;
+        }
+        let frame = IpcFrame::new(text.trim_end(), Initiator::cli());
+        match serde_json::to_string(&frame) {
+            Ok(json) => format!("{json}\n").into(),
+            Err(_) => text,
+        }
+    }
+
+    fn command_text(cmd: &IpcCommand) -> Cow<'static, str> {
         match cmd {
-            IpcCommand::Status => "STATUS\n",
-            IpcCommand::Pause => "PAUSE\n",
-            IpcCommand::Resume => "RESUME\n",
-            IpcCommand::NewSession => "NEW_SESSION\n",
-            IpcCommand::Reload => "RELOAD\n",
+            IpcCommand::Status => "STATUS\n".into(),
+            IpcCommand::Pause => "PAUSE\n".into(),
+            IpcCommand::Resume => "RESUME\n".into(),
+            IpcCommand::NewSession => "NEW_SESSION\n".into(),
+            IpcCommand::ForceNewSession => "NEW_SESSION FORCE\n".into(),
+            IpcCommand::Reload => "RELOAD\n".into(),
+            IpcCommand::Jobs => "JOBS\n".into(),
+            IpcCommand::Handoff => "HANDOFF\n".into(),
+            IpcCommand::SetMode(mode) => format!("SET_MODE {mode}\n").into(),
+            IpcCommand::Ack(id) => format!("ACK {id}\n").into(),
+            IpcCommand::RotateSecret(channel) => format!("ROTATE_SECRET {channel}\n").into(),
+            IpcCommand::RunSchedule(name) => format!("RUN_SCHEDULE {name}\n").into(),
+            IpcCommand::Mute {
+                kind,
+                name,
+                duration,
+            } => {
+                let mut text = format!("MUTE_{} {name}", kind.as_str().to_ascii_uppercase());
+                if let Some(duration) = duration {
+                    text.push_str(&format!(" {}s", duration.as_secs()));
+                }
+                text.push('\n');
+                text.into()
+            }
+            IpcCommand::Unmute { kind, name } => {
+                format!("UNMUTE_{} {name}\n", kind.as_str().to_ascii_uppercase()).into()
+            }
+            IpcCommand::Mutes => "MUTES\n".into(),
+            IpcCommand::ClearAttention => "CLEAR_ATTENTION\n".into(),
+            IpcCommand::Logs {
+                lines,
+                level,
+                follow,
+            } => {
+                let mut text = format!("LOGS {lines}");
+                if let Some(level) = level {
+                    text.push_str(&format!(" {level}"));
+                }
+                if *follow {
+                    text.push_str(" FOLLOW");
+                }
+                text.push('\n');
+                text.into()
+            }
         }
     }
 
@@ -161,15 +418,58 @@ impl IpcClient {
             return Ok(IpcResponse::Ok);
         }
 
+        if let Some(changes) = trimmed.strip_prefix("OK:") {
+            return Ok(IpcResponse::Reloaded {
+                changes: changes
+                    .split("; ")
+                    .map(|change| change.trim().to_string())
+                    .collect(),
+            });
+        }
+
+        if let Some(message) = trimmed.strip_prefix("ACCEPTED:") {
+            return Ok(IpcResponse::Deferred {
+                message: message.trim().to_string(),
+            });
+        }
+
         if let Some(message) = trimmed.strip_prefix("ERR:") {
             return Ok(IpcResponse::Error {
                 message: message.trim().to_string(),
             });
         }
 
+        if trimmed.starts_with(r#"{"schedule_run":"#) {
+            let mut value: serde_json::Value = serde_json::from_str(trimmed)
+                .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
+            let run: ScheduleRun = serde_json::from_value(value["schedule_run"].take())
+                .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
+            return Ok(IpcResponse::ScheduleRun(Box::new(run)));
+        }
+
+        if trimmed.starts_with(r#"{"mutes":"#) {
+            let mut value: serde_json::Value = serde_json::from_str(trimmed)
+                .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
+            let mutes: Vec<NotificationMute> = serde_json::from_value(value["mutes"].take())
+                .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
+            return Ok(IpcResponse::Mutes(mutes));
+        }
+
+        if trimmed.starts_with(r#"{"records":"#) {
+            let batch: LogBatch = serde_json::from_str(trimmed)
+                .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
+            return Ok(IpcResponse::Logs(batch));
+        }
+
+        if trimmed.starts_with('[') {
+            let jobs: Vec<JobStatus> = serde_json::from_str(trimmed)
+                .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
+            return Ok(IpcResponse::Jobs(jobs));
+        }
+
         let status: DaemonStatus = serde_json::from_str(trimmed)
             .map_err(|error| IpcClientError::Protocol(format!("Invalid response: {error}")))?;
-        Ok(IpcResponse::Status(status))
+        Ok(IpcResponse::Status(Box::new(status)))
     }
 
     fn expect_ok(response: IpcResponse) -> Result<(), IpcClientError> {
@@ -179,16 +479,77 @@ impl IpcClient {
             IpcResponse::Status(_) => Err(IpcClientError::Protocol(
                 "Unexpected status response".to_string(),
             )),
+            IpcResponse::Jobs(_) => Err(IpcClientError::Protocol(
+                "Unexpected jobs response".to_string(),
+            )),
+            IpcResponse::Reloaded { .. } => Ok(()),
+            IpcResponse::Deferred { .. } => Err(IpcClientError::Protocol(
+                "Unexpected deferred response".to_string(),
+            )),
+            IpcResponse::Logs(_) => Err(IpcClientError::Protocol(
+                "Unexpected logs response".to_string(),
+            )),
+            IpcResponse::ScheduleRun(_) => Err(IpcClientError::Protocol(
+                "Unexpected schedule run response".to_string(),
+            )),
+            IpcResponse::Mutes(_) => Err(IpcClientError::Protocol(
+                "Unexpected mutes response".to_string(),
+            )),
+        }
+    }
+
+    fn expect_logs(response: IpcResponse) -> Result<LogBatch, IpcClientError> {
+        match response {
+            IpcResponse::Logs(batch) => Ok(batch),
+            IpcResponse::Error { message } => Err(IpcClientError::Protocol(message)),
+            _ => Err(IpcClientError::Protocol(
+                "Unexpected response to LOGS".to_string(),
+            )),
+        }
+    }
+
+    fn expect_mutes(response: IpcResponse) -> Result<Vec<NotificationMute>, IpcClientError> {
+        match response {
+            IpcResponse::Mutes(mutes) => Ok(mutes),
+            IpcResponse::Error { message } => Err(IpcClientError::Protocol(message)),
+            _ => Err(IpcClientError::Protocol(
+                "Unexpected response to a mute command".to_string(),
+            )),
+        }
+    }
+
+    fn expect_ack(response: IpcResponse) -> Result<CommandAck, IpcClientError> {
+        match response {
+            IpcResponse::Deferred { message } => Ok(CommandAck::Deferred(message)),
+            other => Self::expect_ok(other).map(CommandAck::Applied),
         }
     }
 
     fn expect_status(response: IpcResponse) -> Result<DaemonStatus, IpcClientError> {
         match response {
-            IpcResponse::Status(status) => Ok(status),
+            IpcResponse::Status(status) => Ok(*status),
             IpcResponse::Error { message } => Err(IpcClientError::Protocol(message)),
             IpcResponse::Ok => Err(IpcClientError::Protocol(
                 "Unexpected OK response".to_string(),
             )),
+            IpcResponse::Jobs(_) => Err(IpcClientError::Protocol(
+                "Unexpected jobs response".to_string(),
+            )),
+            IpcResponse::Reloaded { .. } => Err(IpcClientError::Protocol(
+                "Unexpected reload response".to_string(),
+            )),
+            IpcResponse::Deferred { .. } => Err(IpcClientError::Protocol(
+                "Unexpected deferred response".to_string(),
+            )),
+            IpcResponse::Logs(_) => Err(IpcClientError::Protocol(
+                "Unexpected logs response".to_string(),
+            )),
+            IpcResponse::ScheduleRun(_) => Err(IpcClientError::Protocol(
+                "Unexpected schedule run response".to_string(),
+            )),
+            IpcResponse::Mutes(_) => Err(IpcClientError::Protocol(
+                "Unexpected mutes response".to_string(),
+            )),
         }
     }
 
@@ -251,6 +612,26 @@ mod tests {
                 total_resumes: 10,
                 time_saved_seconds: 1800.0,
                 time_saved_human: None,
+                opencode_endpoint: None,
+                exclusion_patterns: Vec::new(),
+                watch_filter: None,
+                jobs: Vec::new(),
+                deferred_until: None,
+                deferred_reason: None,
+                quota_resume_at: None,
+                resume_counters: Vec::new(),
+                needs_attention: false,
+                instance: None,
+                clock_skew_secs: None,
+                debounce_window_ms: None,
+                state_newer_version: None,
+                opencode_version: None,
+                restart_correlation: None,
+                mode: Default::default(),
+                incident_opened_at: None,
+                state_unsaved_since: None,
+                watcher_alive: None,
+                http_listeners: None,
             }
         }
 
@@ -302,7 +683,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_status_command_success() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -320,7 +701,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_daemon_not_running() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -332,7 +713,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_timeout_handling() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
         let sock_path = temp.path().join("palingenesis.sock");
@@ -361,7 +742,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_pause_resume_reload_commands() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.lock().await;
         let temp = tempdir().unwrap();
         set_env_var("PALINGENESIS_RUNTIME", temp.path());
 
@@ -384,4 +765,28 @@ mod tests {
         cancel.cancel();
         remove_env_var("PALINGENESIS_RUNTIME");
     }
+
+    #[test]
+    fn test_parse_reload_changes() {
+        match IpcClient::parse_response("OK: http: stopped; http: started on 127.0.0.1:9000\n")
+            .unwrap()
+        {
+            IpcResponse::Reloaded { changes } => assert_eq!(
+                changes,
+                ["http: stopped", "http: started on 127.0.0.1:9000"]
+            ),
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_deferred_response() {
+        let response =
+            IpcClient::parse_response("ACCEPTED: will apply after resume completes\n").unwrap();
+
+        assert_eq!(
+            IpcClient::expect_ack(response).unwrap(),
+            CommandAck::Deferred("will apply after resume completes".to_string())
+        );
+    }
 }
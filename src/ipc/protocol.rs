@@ -1,4 +1,16 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tracing::Level;
+
+use crate::config::schema::DaemonMode;
+use crate::daemon::initiator::Initiator;
+use crate::daemon::jobs::JobStatus;
+use crate::daemon::restart_correlation::RestartCorrelationStatus;
+use crate::state::{MuteKind, NotificationMute, OpenCodeVersionRecord, ScheduleRun};
+use crate::telemetry::log_buffer::LogBatch;
+use crate::util::duration;
 
 /// Commands that can be sent to the daemon via Unix socket.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,22 +23,186 @@ pub enum IpcCommand {
     Resume,
     /// Force a new session.
     NewSession,
+    /// Start a new session, bypassing the resume rate limit (`new-session --force`).
+    ForceNewSession,
     /// Reload configuration file.
     Reload,
+    /// List queued and running daemon jobs.
+    Jobs,
+    /// Write a runtime-state handoff snapshot and shut down (`daemon restart`).
+    Handoff,
+    /// Switch between active and observe mode (`SET_MODE observe|active`).
+    SetMode(DaemonMode),
+    /// Newest buffered log records (`LOGS <n> [level] [FOLLOW]`); with
+    /// `follow`, new records keep streaming until the client disconnects.
+    Logs {
+        lines: usize,
+        level: Option<Level>,
+        follow: bool,
+    },
+    /// Acknowledge an open incident by id (`ACK <incident-id>`).
+    Ack(String),
+    /// Re-resolve one notification channel's secrets
+    /// (`ROTATE_SECRET <channel-name>`).
+    RotateSecret(String),
+    /// Fire a `[[schedules]]` entry now (`RUN_SCHEDULE <name>`).
+    RunSchedule(String),
+    /// Hold back notifications to a channel or of an event type, for a
+    /// while or until unmuted (`MUTE_CHANNEL <name> [duration]`,
+    /// `MUTE_EVENT <event-type> [duration]`).
+    Mute {
+        kind: MuteKind,
+        name: String,
+        duration: Option<Duration>,
+    },
+    /// Lift a mute (`UNMUTE_CHANNEL <name>`, `UNMUTE_EVENT <event-type>`).
+    Unmute { kind: MuteKind, name: String },
+    /// List active mutes (`MUTES`).
+    Mutes,
+    /// Release the current session held for attention (`CLEAR_ATTENTION`).
+    ClearAttention,
 }
 
 impl IpcCommand {
     /// Parse command from text line (without newline).
     pub fn parse(line: &str) -> Option<Self> {
+        // Incident ids, channel and schedule names are case-sensitive, so
+        // ACK, ROTATE_SECRET, RUN_SCHEDULE and the mute commands keep their
+        // argument as sent.
+        if let Some((command, arg)) = line.trim().split_once(' ') {
+            let arg = arg.trim();
+            let valid = !arg.is_empty() && !arg.contains(char::is_whitespace);
+            if command.eq_ignore_ascii_case("ACK") {
+                return valid.then(|| Self::Ack(arg.to_string()));
+            }
+            if command.eq_ignore_ascii_case("ROTATE_SECRET")
+                || command.eq_ignore_ascii_case("ROTATE-SECRET")
+            {
+                return valid.then(|| Self::RotateSecret(arg.to_string()));
+            }
+            if command.eq_ignore_ascii_case("RUN_SCHEDULE")
+                || command.eq_ignore_ascii_case("RUN-SCHEDULE")
+            {
+                return valid.then(|| Self::RunSchedule(arg.to_string()));
+            }
+            if let Some((mute, kind)) = Self::mute_command(command) {
+                return Self::parse_mute(mute, kind, arg);
+            }
+        }
         match line.trim().to_ascii_uppercase().as_str() {
             "STATUS" => Some(Self::Status),
             "PAUSE" => Some(Self::Pause),
             "RESUME" => Some(Self::Resume),
             "NEW_SESSION" | "NEW-SESSION" => Some(Self::NewSession),
+            "NEW_SESSION FORCE" | "NEW-SESSION FORCE" => Some(Self::ForceNewSession),
             "RELOAD" => Some(Self::Reload),
+            "JOBS" => Some(Self::Jobs),
+            "HANDOFF" => Some(Self::Handoff),
+            "MUTES" => Some(Self::Mutes),
+            "CLEAR_ATTENTION" | "CLEAR-ATTENTION" => Some(Self::ClearAttention),
+            other => {
+                let (command, args) = other.split_once(' ')?;
+                match command {
+                    "SET_MODE" | "SET-MODE" => args.parse().ok().map(Self::SetMode),
+                    "LOGS" => Self::parse_logs(args),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn parse_logs(args: &str) -> Option<Self> {
+        let mut words = args.split_whitespace();
+        let lines = words.next()?.parse().ok()?;
+        let mut level = None;
+        let mut follow = false;
+        for word in words {
+            match word {
+                "FOLLOW" if !follow => follow = true,
+                word if level.is_none() && !follow => level = Some(word.parse().ok()?),
+                _ => return None,
+            }
+        }
+        Some(Self::Logs {
+            lines,
+            level,
+            follow,
+        })
+    }
+
+    /// Whether `command` mutes or unmutes, and what kind of target.
+    fn mute_command(command: &str) -> Option<(bool, MuteKind)> {
+        match command.to_ascii_uppercase().replace('-', "_").as_str() {
+            "MUTE_CHANNEL" => Some((true, MuteKind::Channel)),
+            "MUTE_EVENT" => Some((true, MuteKind::Event)),
+            "UNMUTE_CHANNEL" => Some((false, MuteKind::Channel)),
+            "UNMUTE_EVENT" => Some((false, MuteKind::Event)),
             _ => None,
         }
     }
+
+    fn parse_mute(mute: bool, kind: MuteKind, args: &str) -> Option<Self> {
+        let mut words = args.split_whitespace();
+        let name = words.next()?.to_string();
+        let duration = match words.next() {
+            Some(duration) if mute => Some(duration::parse(duration).filter(|d| !d.is_zero())?),
+            Some(_) => return None,
+            None => None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+        Some(if mute {
+            Self::Mute {
+                kind,
+                name,
+                duration,
+            }
+        } else {
+            Self::Unmute { kind, name }
+        })
+    }
+
+    /// Whether the command changes daemon state, and so waits for a
+    /// pipeline safe point (see [`crate::daemon::control`]).
+    pub fn changes_state(&self) -> bool {
+        matches!(
+            self,
+            Self::Pause | Self::Resume | Self::NewSession | Self::ForceNewSession | Self::Reload
+        )
+    }
+}
+
+/// Version 2 request line: a JSON object carrying the command text and who
+/// sent it, e.g. `{"v":2,"command":"PAUSE","initiator":{"via":"cli"}}`.
+///
+/// Bare command lines (version 1) are still accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpcFrame {
+    pub v: u8,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initiator: Option<Initiator>,
+}
+
+impl IpcFrame {
+    pub const VERSION: u8 = 2;
+
+    pub fn new(command: impl Into<String>, initiator: Initiator) -> Self {
+        Self {
+            v: Self::VERSION,
+            command: command.into(),
+            initiator: Some(initiator),
+        }
+    }
+}
+
+/// A decoded request line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpcRequest {
+    pub command: IpcCommand,
+    /// Who sent the command; only version 2 frames say.
+    pub initiator: Option<Initiator>,
 }
 
 /// Response types from the daemon.
@@ -38,7 +214,21 @@ pub enum IpcResponse {
     /// Error response with message.
     Error { message: String },
     /// Status response with JSON data.
-    Status(DaemonStatus),
+    Status(Box<DaemonStatus>),
+    /// Queued and running jobs, running first.
+    Jobs(Vec<JobStatus>),
+    /// Configuration reloaded; what was done to running services, if anything.
+    /// Also answers `ROTATE_SECRET`, naming the channel if its credentials changed,
+    /// and `CLEAR_ATTENTION`, describing what was released.
+    Reloaded { changes: Vec<String> },
+    /// The command was queued and will apply at the next safe point.
+    Deferred { message: String },
+    /// Buffered log records, oldest first.
+    Logs(LogBatch),
+    /// How a `RUN_SCHEDULE` firing started (or why it was skipped).
+    ScheduleRun(Box<ScheduleRun>),
+    /// Active notification mutes; also answers `MUTE_*` and `UNMUTE_*`.
+    Mutes(Vec<NotificationMute>),
 }
 
 /// Daemon status for STATUS command response.
@@ -52,6 +242,118 @@ pub struct DaemonStatus {
     pub time_saved_seconds: f64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub time_saved_human: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode_endpoint: Option<OpenCodeEndpointStatus>,
+    /// Active `resume.exclude_sessions` patterns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclusion_patterns: Vec<String>,
+    /// Watcher event filter (`monitoring.include_extensions`, `monitoring.ignore_patterns`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch_filter: Option<WatchFilterStatus>,
+    /// Queued and running daemon jobs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jobs: Vec<JobStatus>,
+    /// End of the current `resume.maintenance_windows` period; resumes wait until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deferred_until: Option<DateTime<Utc>>,
+    /// Why a resume is held by `[resume.gates]` (`waiting for AC power`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deferred_reason: Option<String>,
+    /// Next single resume scheduled for a provider quota reset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_resume_at: Option<DateTime<Utc>>,
+    /// Sessions automatically resumed within the last hour.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resume_counters: Vec<ResumeCounter>,
+    /// The current session is held for attention (deleted, or a suspected resume loop).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub needs_attention: bool,
+    /// Name of the daemon instance (`--instance`); `None` for the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Smoothed provider clock minus local clock (seconds), once a provider
+    /// `Date:` header has been seen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_skew_secs: Option<f64>,
+    /// Debounce window the session watcher currently uses (milliseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debounce_window_ms: Option<u64>,
+    /// Installed opencode version and its compatibility check result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode_version: Option<OpenCodeVersionRecord>,
+    /// Server-down window after an opencode crash and the stops it holds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_correlation: Option<RestartCorrelationStatus>,
+    /// `observe` while stops are only reported, not acted on.
+    #[serde(default)]
+    pub mode: DaemonMode,
+    /// When the oldest incident still open (not resolved or expired) began.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_opened_at: Option<DateTime<Utc>>,
+    /// Since when state changes have been waiting to be written to disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_unsaved_since: Option<DateTime<Utc>>,
+    /// Schema version of a state file written by a newer build; while set,
+    /// the daemon keeps state in memory and does not write the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_newer_version: Option<u32>,
+    /// Whether the daemon's session event sources are still delivering;
+    /// `None` from daemons that do not report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watcher_alive: Option<bool>,
+    /// Addresses the HTTP API is serving on, while it runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_listeners: Option<HttpListenerStatus>,
+}
+
+/// Automatic resumes of one session against `resume.max_resumes_per_hour`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResumeCounter {
+    pub session: String,
+    pub resumes_last_hour: u32,
+    /// Hourly cap; 0 when disabled.
+    pub max_per_hour: u32,
+    /// When automatic resumes were paused as a suspected loop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_suspected_at: Option<DateTime<Utc>>,
+    /// When the operator acknowledged the session's open incident.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    /// Whether the acknowledgment also paused automatic resumes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resumes_paused: bool,
+}
+
+/// Bound HTTP addresses; `bots` is set when `[bot] http_bind`/`http_port`
+/// moved the bot webhooks to their own listener.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpListenerStatus {
+    pub api: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bots: Option<String>,
+}
+
+/// Which watcher events the daemon forwards, and how many it dropped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchFilterStatus {
+    pub include_extensions: Vec<String>,
+    pub ignore_patterns: Vec<String>,
+    pub events_filtered: u64,
+}
+
+/// OpenCode API endpoint the daemon is talking to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenCodeEndpointStatus {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 impl IpcResponse {
@@ -64,6 +366,13 @@ impl IpcResponse {
             // which are guaranteed to serialize successfully. unwrap_or_default() is a
             // defensive fallback that should never trigger in practice.
             Self::Status(status) => serde_json::to_string(status).unwrap_or_default() + "\n",
+            Self::Jobs(jobs) => serde_json::to_string(jobs).unwrap_or_default() + "\n",
+            Self::Reloaded { changes } if changes.is_empty() => "OK\n".to_string(),
+            Self::Reloaded { changes } => format!("OK: {}\n", changes.join("; ")),
+            Self::Deferred { message } => format!("ACCEPTED: {message}\n"),
+            Self::Logs(batch) => serde_json::to_string(batch).unwrap_or_default() + "\n",
+            Self::ScheduleRun(run) => serde_json::json!({ "schedule_run": run }).to_string() + "\n",
+            Self::Mutes(mutes) => serde_json::json!({ "mutes": mutes }).to_string() + "\n",
         }
     }
 }
@@ -86,7 +395,86 @@ mod tests {
             IpcCommand::parse("NEW-SESSION"),
             Some(IpcCommand::NewSession)
         );
+        assert_eq!(
+            IpcCommand::parse("new_session force"),
+            Some(IpcCommand::ForceNewSession)
+        );
         assert_eq!(IpcCommand::parse("RELOAD"), Some(IpcCommand::Reload));
+        assert_eq!(IpcCommand::parse("jobs"), Some(IpcCommand::Jobs));
+        assert_eq!(IpcCommand::parse("handoff"), Some(IpcCommand::Handoff));
+        assert_eq!(
+            IpcCommand::parse("set_mode observe"),
+            Some(IpcCommand::SetMode(DaemonMode::Observe))
+        );
+        assert_eq!(
+            IpcCommand::parse("SET_MODE ACTIVE\n"),
+            Some(IpcCommand::SetMode(DaemonMode::Active))
+        );
+        assert_eq!(IpcCommand::parse("SET_MODE passive"), None);
+        assert_eq!(
+            IpcCommand::parse("logs 50 warn follow"),
+            Some(IpcCommand::Logs {
+                lines: 50,
+                level: Some(Level::WARN),
+                follow: true,
+            })
+        );
+        assert_eq!(
+            IpcCommand::parse("LOGS 20 FOLLOW"),
+            Some(IpcCommand::Logs {
+                lines: 20,
+                level: None,
+                follow: true,
+            })
+        );
+        assert_eq!(IpcCommand::parse("LOGS many"), None);
+        assert_eq!(IpcCommand::parse("LOGS 5 FOLLOW WARN"), None);
+        assert_eq!(
+            IpcCommand::parse("ack 20261016T120000.000Z-Session\n"),
+            Some(IpcCommand::Ack("20261016T120000.000Z-Session".to_string()))
+        );
+        assert_eq!(IpcCommand::parse("ACK a b"), None);
+        assert_eq!(
+            IpcCommand::parse("rotate_secret Phone\n"),
+            Some(IpcCommand::RotateSecret("Phone".to_string()))
+        );
+        assert_eq!(IpcCommand::parse("ROTATE_SECRET"), None);
+        assert_eq!(
+            IpcCommand::parse("run_schedule CI-triage\n"),
+            Some(IpcCommand::RunSchedule("CI-triage".to_string()))
+        );
+        assert_eq!(IpcCommand::parse("RUN_SCHEDULE"), None);
+        assert_eq!(
+            IpcCommand::parse("mute_channel Phone 30m\n"),
+            Some(IpcCommand::Mute {
+                kind: MuteKind::Channel,
+                name: "Phone".to_string(),
+                duration: Some(Duration::from_secs(1800)),
+            })
+        );
+        assert_eq!(
+            IpcCommand::parse("MUTE_EVENT resume_attempted"),
+            Some(IpcCommand::Mute {
+                kind: MuteKind::Event,
+                name: "resume_attempted".to_string(),
+                duration: None,
+            })
+        );
+        assert_eq!(
+            IpcCommand::parse("UNMUTE_CHANNEL slack"),
+            Some(IpcCommand::Unmute {
+                kind: MuteKind::Channel,
+                name: "slack".to_string(),
+            })
+        );
+        assert_eq!(IpcCommand::parse("MUTE_CHANNEL slack soon"), None);
+        assert_eq!(IpcCommand::parse("MUTE_CHANNEL slack 0s"), None);
+        assert_eq!(IpcCommand::parse("UNMUTE_EVENT resume_failed 1h"), None);
+        assert_eq!(IpcCommand::parse("mutes"), Some(IpcCommand::Mutes));
+        assert_eq!(
+            IpcCommand::parse("clear-attention"),
+            Some(IpcCommand::ClearAttention)
+        );
         assert_eq!(IpcCommand::parse("UNKNOWN"), None);
     }
 
@@ -100,6 +488,27 @@ mod tests {
             .to_text(),
             "ERR: test\n"
         );
+        assert_eq!(
+            IpcResponse::Reloaded {
+                changes: Vec::new()
+            }
+            .to_text(),
+            "OK\n"
+        );
+        assert_eq!(
+            IpcResponse::Reloaded {
+                changes: vec!["http: restarted on 127.0.0.1:8080".to_string()]
+            }
+            .to_text(),
+            "OK: http: restarted on 127.0.0.1:8080\n"
+        );
+        assert_eq!(
+            IpcResponse::Deferred {
+                message: "will apply after resume completes".to_string()
+            }
+            .to_text(),
+            "ACCEPTED: will apply after resume completes\n"
+        );
 
         let status = DaemonStatus {
             state: "monitoring".to_string(),
@@ -108,9 +517,29 @@ mod tests {
             saves_count: 7,
             total_resumes: 3,
             time_saved_seconds: 360.0,
-            time_saved_human: Some("6.0 minutes".to_string()),
+            time_saved_human: Some("6m".to_string()),
+            opencode_endpoint: None,
+            exclusion_patterns: Vec::new(),
+            watch_filter: None,
+            jobs: Vec::new(),
+            deferred_until: None,
+            deferred_reason: None,
+            quota_resume_at: None,
+            resume_counters: Vec::new(),
+            needs_attention: false,
+            instance: None,
+            clock_skew_secs: None,
+            debounce_window_ms: None,
+            state_newer_version: None,
+            opencode_version: None,
+            restart_correlation: None,
+            mode: Default::default(),
+            incident_opened_at: None,
+            state_unsaved_since: None,
+            watcher_alive: None,
+            http_listeners: None,
         };
-        let text = IpcResponse::Status(status.clone()).to_text();
+        let text = IpcResponse::Status(Box::new(status.clone())).to_text();
         let json = text.trim_end();
         let parsed: DaemonStatus = serde_json::from_str(json).unwrap();
         assert_eq!(parsed, status);
@@ -1,13 +1,23 @@
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use async_trait::async_trait;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
 use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, warn};
+use tracing::{Level, debug, error, info, warn};
 
 use crate::config::Paths;
-use crate::ipc::protocol::{DaemonStatus, IpcCommand, IpcResponse};
+use crate::config::schema::DaemonMode;
+use crate::daemon::control::{Admission, ControlQueue};
+use crate::daemon::initiator::Initiator;
+use crate::daemon::jobs::JobStatus;
+use crate::ipc::protocol::{DaemonStatus, IpcCommand, IpcFrame, IpcRequest, IpcResponse};
+use crate::state::{MuteKind, NotificationMute, ScheduleRun};
+use crate::telemetry::log_buffer::{LogBuffer, LogRecord};
 
 #[cfg(test)]
 const CONNECTION_TIMEOUT_SECS: u64 = 1;
@@ -15,6 +25,9 @@ const CONNECTION_TIMEOUT_SECS: u64 = 1;
 #[cfg(not(test))]
 const CONNECTION_TIMEOUT_SECS: u64 = 5;
 
+/// Longest command line accepted; commands are short words or a small JSON frame.
+pub const MAX_COMMAND_LINE_BYTES: usize = 1024;
+
 #[derive(Debug, thiserror::Error)]
 pub enum IpcError {
     #[error("I/O error: {0}")]
@@ -23,17 +36,107 @@ pub enum IpcError {
     #[error("Socket already bound")]
     AlreadyBound,
 
+    #[error("Socket {path} is in use by another process")]
+    InUse { path: PathBuf },
+
     #[error("Socket path does not exist")]
     NotBound,
 }
 
 /// Shared state that the IPC server can access.
+#[async_trait]
 pub trait DaemonStateAccess: Send + Sync {
     fn get_status(&self) -> DaemonStatus;
     fn pause(&self) -> Result<(), String>;
     fn resume(&self) -> Result<(), String>;
     fn new_session(&self) -> Result<(), String>;
+    /// Start a new session even if the resume rate limit would refuse it.
+    fn force_new_session(&self) -> Result<(), String> {
+        self.new_session()
+    }
     fn reload_config(&self) -> Result<(), String>;
+    /// Reload configuration, then restart services whose settings changed.
+    /// Returns what was done, e.g. `http: restarted on 127.0.0.1:8080`.
+    async fn reload(&self) -> Result<Vec<String>, String> {
+        self.reload_config().map(|()| Vec::new())
+    }
+    /// Queued and running daemon jobs.
+    fn jobs(&self) -> Vec<JobStatus> {
+        self.get_status().jobs
+    }
+    /// Save in-memory runtime state for the next daemon and begin shutdown.
+    fn handoff(&self) -> Result<(), String> {
+        Err("Handoff not supported".to_string())
+    }
+    /// Queue that holds state-changing commands until a pipeline safe point.
+    /// Without one, commands apply as they arrive.
+    fn control_queue(&self) -> Option<ControlQueue> {
+        None
+    }
+    /// Switch between acting on stops and only reporting them (audited).
+    fn set_mode(&self, _mode: DaemonMode) -> Result<(), String> {
+        Err("Mode switching not supported".to_string())
+    }
+    /// [`Self::pause`] on behalf of `initiator`, who is recorded with the action.
+    fn pause_by(&self, _initiator: &Initiator) -> Result<(), String> {
+        self.pause()
+    }
+    /// [`Self::resume`] on behalf of `initiator`.
+    fn resume_by(&self, _initiator: &Initiator) -> Result<(), String> {
+        self.resume()
+    }
+    /// [`Self::new_session`] on behalf of `initiator`.
+    fn new_session_by(&self, _initiator: &Initiator) -> Result<(), String> {
+        self.new_session()
+    }
+    /// [`Self::force_new_session`] on behalf of `initiator`.
+    fn force_new_session_by(&self, _initiator: &Initiator) -> Result<(), String> {
+        self.force_new_session()
+    }
+    /// [`Self::set_mode`] on behalf of `initiator`.
+    fn set_mode_by(&self, mode: DaemonMode, _initiator: &Initiator) -> Result<(), String> {
+        self.set_mode(mode)
+    }
+    /// In-memory log records served by `LOGS`.
+    fn log_buffer(&self) -> Option<LogBuffer> {
+        None
+    }
+    /// Acknowledge the open incident `incident_id` (`palingenesis ack`).
+    fn acknowledge(&self, _incident_id: &str) -> Result<(), String> {
+        Err("Acknowledgment not supported".to_string())
+    }
+    /// Re-resolve one notification channel's secrets (`ROTATE_SECRET`),
+    /// returning whether they changed.
+    fn rotate_secret(&self, _channel: &str) -> Result<bool, String> {
+        Err("Secret rotation not supported".to_string())
+    }
+    /// Fire the named `[[schedules]]` entry now on behalf of `initiator`.
+    fn run_schedule(&self, _name: &str, _initiator: &Initiator) -> Result<ScheduleRun, String> {
+        Err("Schedules not supported".to_string())
+    }
+    /// Mute a notification channel or event type for `duration`, or until
+    /// unmuted, returning the active mutes.
+    fn mute(
+        &self,
+        _kind: MuteKind,
+        _name: &str,
+        _duration: Option<Duration>,
+    ) -> Result<Vec<NotificationMute>, String> {
+        Err("Notification mutes not supported".to_string())
+    }
+    /// Lift a mute, returning the ones still active.
+    fn unmute(&self, _kind: MuteKind, _name: &str) -> Result<Vec<NotificationMute>, String> {
+        Err("Notification mutes not supported".to_string())
+    }
+    /// Active notification mutes.
+    fn mutes(&self) -> Vec<NotificationMute> {
+        Vec::new()
+    }
+    /// Release the current session held for attention (`palingenesis
+    /// attention clear`), describing what was done.
+    fn clear_attention(&self) -> Result<Option<String>, String> {
+        Err("Clearing attention not supported".to_string())
+    }
 }
 
 pub struct IpcServer {
@@ -45,7 +148,7 @@ impl IpcServer {
     /// Create a new IpcServer instance pointing to the standard location.
     pub fn new() -> Self {
         Self {
-            path: Paths::runtime_dir().join("palingenesis.sock"),
+            path: Paths::socket_file(),
             listener: None,
         }
     }
@@ -70,6 +173,12 @@ impl IpcServer {
         }
 
         if self.path.exists() {
+            #[cfg(unix)]
+            if std::os::unix::net::UnixStream::connect(&self.path).is_ok() {
+                return Err(IpcError::InUse {
+                    path: self.path.clone(),
+                });
+            }
             warn!(path = %self.path.display(), "Removing stale socket file");
             std::fs::remove_file(&self.path)?;
         }
@@ -164,25 +273,48 @@ async fn handle_connection<S: DaemonStateAccess>(
     stream: UnixStream,
     state: Arc<S>,
 ) -> Result<(), IpcError> {
+    let peer_uid = stream.peer_cred().ok().map(|cred| cred.uid());
     let (reader, mut writer) = stream.into_split();
-    let mut reader = BufReader::new(reader);
-    let mut line = String::new();
+    let mut reader = BufReader::new(reader).take(MAX_COMMAND_LINE_BYTES as u64);
+    let mut line = Vec::new();
 
     let read_result = tokio::time::timeout(
         std::time::Duration::from_secs(CONNECTION_TIMEOUT_SECS),
-        reader.read_line(&mut line),
+        reader.read_until(b'\n', &mut line),
     )
     .await;
 
+    let mut follow = None;
     let response = match read_result {
         Ok(Ok(0)) => {
             return Ok(());
         }
-        Ok(Ok(_)) => match IpcCommand::parse(&line) {
-            Some(cmd) => handle_command(cmd, &*state),
-            None => IpcResponse::Error {
-                message: format!("Unknown command: {}", line.trim()),
-            },
+        Ok(Ok(_)) => match decode_request_line(&line) {
+            Ok(IpcRequest {
+                command: cmd,
+                initiator,
+            }) => {
+                // Old clients send bare command lines; they are still the CLI.
+                let initiator = initiator
+                    .unwrap_or(Initiator::Cli {
+                        uid: None,
+                        tty: None,
+                    })
+                    .with_peer_uid(peer_uid);
+                // Subscribe before the snapshot so no record falls in between.
+                if let IpcCommand::Logs {
+                    level,
+                    follow: true,
+                    ..
+                } = &cmd
+                {
+                    follow = state
+                        .log_buffer()
+                        .map(|buffer| (buffer.subscribe(), *level));
+                }
+                handle_command(cmd, &initiator, &*state).await
+            }
+            Err(message) => IpcResponse::Error { message },
         },
         Ok(Err(e)) => return Err(IpcError::Io(e)),
         Err(_) => IpcResponse::Error {
@@ -193,28 +325,184 @@ async fn handle_connection<S: DaemonStateAccess>(
     writer.write_all(response.to_text().as_bytes()).await?;
     writer.flush().await?;
 
+    if let (Some((records, level)), IpcResponse::Logs(batch)) = (follow, &response) {
+        follow_logs(records, level, batch.latest_seq, &mut reader, &mut writer).await?;
+    }
+
     Ok(())
 }
 
-fn handle_command<S: DaemonStateAccess>(cmd: IpcCommand, state: &S) -> IpcResponse {
+/// Stream records newer than `after_seq` until the client disconnects.
+async fn follow_logs<R: AsyncRead + Unpin>(
+    mut records: broadcast::Receiver<LogRecord>,
+    level: Option<Level>,
+    after_seq: Option<u64>,
+    reader: &mut R,
+    writer: &mut OwnedWriteHalf,
+) -> Result<(), IpcError> {
+    let mut probe = [0u8; 64];
+    loop {
+        tokio::select! {
+            read = reader.read(&mut probe) => match read {
+                Ok(0) | Err(_) => return Ok(()),
+                Ok(_) => {}
+            },
+            record = records.recv() => match record {
+                Ok(record) => {
+                    if after_seq.is_some_and(|seq| record.seq <= seq)
+                        || level.is_some_and(|level| !record.is_at_least(level))
+                    {
+                        continue;
+                    }
+                    let line = serde_json::to_string(&record).unwrap_or_default() + "\n";
+                    writer.write_all(line.as_bytes()).await?;
+                    writer.flush().await?;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "Log follower fell behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Turn one raw request line into a command, or the error message to send back.
+///
+/// A line without a newline that filled [`MAX_COMMAND_LINE_BYTES`] was cut
+/// off by the read limit and is rejected rather than parsed.
+pub fn decode_command_line(line: &[u8]) -> Result<IpcCommand, String> {
+    decode_request_line(line).map(|request| request.command)
+}
+
+/// Like [`decode_command_line`], also accepting a version 2 [`IpcFrame`]
+/// that names who sent the command.
+pub fn decode_request_line(line: &[u8]) -> Result<IpcRequest, String> {
+    if line.len() >= MAX_COMMAND_LINE_BYTES && !line.ends_with(b"\n") {
+        return Err(format!(
+            "Command too long (limit {MAX_COMMAND_LINE_BYTES} bytes)"
+        ));
+    }
+    let text = std::str::from_utf8(line).map_err(|_| "Command is not valid UTF-8".to_string())?;
+    if !text.trim_start().starts_with('{') {
+        let command =
+            IpcCommand::parse(text).ok_or_else(|| format!("Unknown command: {}", text.trim()))?;
+        return Ok(IpcRequest {
+            command,
+            initiator: None,
+        });
+    }
+    let frame: IpcFrame =
+        serde_json::from_str(text.trim()).map_err(|err| format!("Invalid request frame: {err}"))?;
+    let command = IpcCommand::parse(&frame.command)
+        .ok_or_else(|| format!("Unknown command: {}", frame.command.trim()))?;
+    Ok(IpcRequest {
+        command,
+        initiator: frame.initiator,
+    })
+}
+
+/// Run `cmd` once the control queue admits it, or queue it behind in-flight
+/// pipeline work.
+pub(crate) async fn handle_command<S: DaemonStateAccess + ?Sized>(
+    cmd: IpcCommand,
+    initiator: &Initiator,
+    state: &S,
+) -> IpcResponse {
+    if !cmd.changes_state() {
+        return apply_command(cmd, initiator, state).await;
+    }
+    let Some(control) = state.control_queue() else {
+        return apply_command(cmd, initiator, state).await;
+    };
+    match control.admit(cmd.clone(), initiator) {
+        Admission::Now(_guard) => apply_command(cmd, initiator, state).await,
+        Admission::Deferred(deferral) => IpcResponse::Deferred {
+            message: deferral.to_string(),
+        },
+    }
+}
+
+/// Run `cmd` against `state` right away on behalf of `initiator`.
+pub(crate) async fn apply_command<S: DaemonStateAccess + ?Sized>(
+    cmd: IpcCommand,
+    initiator: &Initiator,
+    state: &S,
+) -> IpcResponse {
     match cmd {
-        IpcCommand::Status => IpcResponse::Status(state.get_status()),
-        IpcCommand::Pause => match state.pause() {
+        IpcCommand::Status => IpcResponse::Status(Box::new(state.get_status())),
+        IpcCommand::Pause => match state.pause_by(initiator) {
+            Ok(()) => IpcResponse::Ok,
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::Resume => match state.resume_by(initiator) {
             Ok(()) => IpcResponse::Ok,
             Err(msg) => IpcResponse::Error { message: msg },
         },
-        IpcCommand::Resume => match state.resume() {
+        IpcCommand::NewSession => match state.new_session_by(initiator) {
             Ok(()) => IpcResponse::Ok,
             Err(msg) => IpcResponse::Error { message: msg },
         },
-        IpcCommand::NewSession => match state.new_session() {
+        IpcCommand::ForceNewSession => match state.force_new_session_by(initiator) {
             Ok(()) => IpcResponse::Ok,
             Err(msg) => IpcResponse::Error { message: msg },
         },
-        IpcCommand::Reload => match state.reload_config() {
+        IpcCommand::Reload => match state.reload().await {
+            Ok(changes) => IpcResponse::Reloaded { changes },
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::Jobs => IpcResponse::Jobs(state.jobs()),
+        IpcCommand::Handoff => match state.handoff() {
+            Ok(()) => IpcResponse::Ok,
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::SetMode(mode) => match state.set_mode_by(mode, initiator) {
+            Ok(()) => IpcResponse::Ok,
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::Logs { lines, level, .. } => match state.log_buffer() {
+            Some(buffer) => IpcResponse::Logs(buffer.query(lines, level)),
+            None => IpcResponse::Error {
+                message: "Log buffer not available".to_string(),
+            },
+        },
+        IpcCommand::Ack(incident_id) => match state.acknowledge(&incident_id) {
             Ok(()) => IpcResponse::Ok,
             Err(msg) => IpcResponse::Error { message: msg },
         },
+        IpcCommand::RotateSecret(channel) => match state.rotate_secret(&channel) {
+            Ok(changed) => IpcResponse::Reloaded {
+                changes: if changed {
+                    vec![format!("{channel}: credentials changed")]
+                } else {
+                    Vec::new()
+                },
+            },
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::RunSchedule(name) => match state.run_schedule(&name, initiator) {
+            Ok(run) => IpcResponse::ScheduleRun(Box::new(run)),
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::Mute {
+            kind,
+            name,
+            duration,
+        } => match state.mute(kind, &name, duration) {
+            Ok(mutes) => IpcResponse::Mutes(mutes),
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::Unmute { kind, name } => match state.unmute(kind, &name) {
+            Ok(mutes) => IpcResponse::Mutes(mutes),
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
+        IpcCommand::Mutes => IpcResponse::Mutes(state.mutes()),
+        IpcCommand::ClearAttention => match state.clear_attention() {
+            Ok(message) => IpcResponse::Reloaded {
+                changes: message.into_iter().collect(),
+            },
+            Err(msg) => IpcResponse::Error { message: msg },
+        },
     }
 }
 
@@ -228,8 +516,11 @@ mod tests {
     #[derive(Default)]
     struct MockState {
         paused: AtomicBool,
+        paused_by: std::sync::Mutex<Option<Initiator>>,
         reloads: AtomicUsize,
         new_sessions: AtomicUsize,
+        control: ControlQueue,
+        logs: LogBuffer,
     }
 
     impl MockState {
@@ -260,6 +551,26 @@ mod tests {
                 total_resumes: 10,
                 time_saved_seconds: 7200.0,
                 time_saved_human: None,
+                opencode_endpoint: None,
+                exclusion_patterns: Vec::new(),
+                watch_filter: None,
+                jobs: Vec::new(),
+                deferred_until: None,
+                deferred_reason: None,
+                quota_resume_at: None,
+                resume_counters: Vec::new(),
+                needs_attention: false,
+                instance: None,
+                clock_skew_secs: None,
+                debounce_window_ms: None,
+                state_newer_version: None,
+                opencode_version: None,
+                restart_correlation: None,
+                mode: Default::default(),
+                incident_opened_at: None,
+                state_unsaved_since: None,
+                watcher_alive: None,
+                http_listeners: None,
             }
         }
 
@@ -268,6 +579,11 @@ mod tests {
             Ok(())
         }
 
+        fn pause_by(&self, initiator: &Initiator) -> Result<(), String> {
+            *self.paused_by.lock().unwrap() = Some(initiator.clone());
+            self.pause()
+        }
+
         fn resume(&self) -> Result<(), String> {
             self.paused.store(false, Ordering::SeqCst);
             Ok(())
@@ -282,6 +598,55 @@ mod tests {
             self.new_sessions.fetch_add(1, Ordering::SeqCst);
             Ok(())
         }
+
+        fn control_queue(&self) -> Option<ControlQueue> {
+            Some(self.control.clone())
+        }
+
+        fn log_buffer(&self) -> Option<LogBuffer> {
+            Some(self.logs.clone())
+        }
+    }
+
+    async fn serve(sock_path: &Path, state: Arc<MockState>) -> CancellationToken {
+        let mut server = IpcServer::with_path(sock_path.to_path_buf());
+        server.bind().await.unwrap();
+        let cancel = CancellationToken::new();
+        tokio::spawn({
+            let cancel = cancel.clone();
+            async move { server.run(state, cancel).await }
+        });
+        cancel
+    }
+
+    fn emit_burst(logs: &LogBuffer, count: usize) {
+        use tracing_subscriber::layer::SubscriberExt;
+        let subscriber = tracing_subscriber::registry().with(logs.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            for index in 0..count {
+                if index % 2 == 0 {
+                    tracing::warn!(index, "burst {index}");
+                } else {
+                    tracing::info!(index, "burst {index}");
+                }
+            }
+        });
+    }
+
+    async fn request(sock_path: &Path, command: &str) -> String {
+        let stream = UnixStream::connect(sock_path).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer
+            .write_all(format!("{command}\n").as_bytes())
+            .await
+            .unwrap();
+        writer.flush().await.unwrap();
+        let mut response = String::new();
+        BufReader::new(reader)
+            .read_line(&mut response)
+            .await
+            .unwrap();
+        response
     }
 
     #[tokio::test]
@@ -311,6 +676,86 @@ mod tests {
         assert!(sock_path.exists());
     }
 
+    #[tokio::test]
+    async fn test_live_socket_is_not_replaced() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&sock_path).unwrap();
+
+        let mut server = IpcServer::with_path(sock_path.clone());
+        assert!(matches!(
+            server.bind().await,
+            Err(IpcError::InUse { path }) if path == sock_path
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_state_changes_wait_for_running_resume() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let mut server = IpcServer::with_path(sock_path.clone());
+        server.bind().await.unwrap();
+
+        let server = Arc::new(server);
+        let cancel = CancellationToken::new();
+        let state = Arc::new(MockState::default());
+        let server_task = tokio::spawn({
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+            let cancel = cancel.clone();
+            async move { server.run(state, cancel).await }
+        });
+        let control = state.control.clone();
+        tokio::spawn({
+            let control = control.clone();
+            let state = Arc::clone(&state);
+            let cancel = cancel.clone();
+            async move { control.run(state, cancel).await }
+        });
+
+        // A long-running resume holds the pipeline until released.
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let operation = control.operation("resume of /tmp/session.md").await;
+        let resume = tokio::spawn(async move {
+            let _operation = operation;
+            let _ = released.await;
+        });
+
+        let status = tokio::time::timeout(
+            std::time::Duration::from_millis(500),
+            request(&sock_path, "STATUS"),
+        )
+        .await
+        .expect("STATUS answered during resume");
+        assert!(status.starts_with('{'));
+
+        assert_eq!(
+            request(&sock_path, "RELOAD").await,
+            "ACCEPTED: will apply after resume of /tmp/session.md completes\n"
+        );
+        assert_eq!(
+            request(&sock_path, "RELOAD").await,
+            "ACCEPTED: will apply after resume of /tmp/session.md completes \
+             (merged with an identical pending request)\n"
+        );
+        assert_eq!(state.reload_count(), 0);
+
+        release.send(()).unwrap();
+        resume.await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            while state.reload_count() == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("deferred reload applied");
+        assert_eq!(state.reload_count(), 1);
+
+        cancel.cancel();
+        server_task.await.unwrap().unwrap();
+        server.cleanup().unwrap();
+    }
+
     #[tokio::test]
     async fn test_pause_resume_and_reload_commands() {
         let temp = tempdir().unwrap();
@@ -459,6 +904,37 @@ mod tests {
         server.cleanup().unwrap();
     }
 
+    #[tokio::test]
+    async fn test_jobs_command() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let mut server = IpcServer::with_path(sock_path.clone());
+        server.bind().await.unwrap();
+
+        let server = Arc::new(server);
+        let cancel = CancellationToken::new();
+        let state = Arc::new(MockState::default());
+        let server_ref = Arc::clone(&server);
+        let server_cancel = cancel.clone();
+        let server_task = tokio::spawn(async move { server_ref.run(state, server_cancel).await });
+
+        let stream = tokio::net::UnixStream::connect(&sock_path).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        writer.write_all(b"JOBS\n").await.unwrap();
+        writer.flush().await.unwrap();
+
+        let mut response = String::new();
+        reader.read_line(&mut response).await.unwrap();
+        let jobs: Vec<JobStatus> = serde_json::from_str(response.trim_end()).unwrap();
+        assert!(jobs.is_empty());
+
+        cancel.cancel();
+        server_task.await.unwrap().unwrap();
+        server.cleanup().unwrap();
+    }
+
     #[tokio::test]
     async fn test_unknown_command_returns_error() {
         let temp = tempdir().unwrap();
@@ -490,4 +966,156 @@ mod tests {
         server_task.await.unwrap().unwrap();
         server.cleanup().unwrap();
     }
+
+    #[tokio::test]
+    async fn test_oversized_command_line_is_rejected() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let mut server = IpcServer::with_path(sock_path.clone());
+        server.bind().await.unwrap();
+
+        let server = Arc::new(server);
+        let cancel = CancellationToken::new();
+        let state = Arc::new(MockState::default());
+        let server_ref = Arc::clone(&server);
+        let server_state = Arc::clone(&state);
+        let server_cancel = cancel.clone();
+        let server_task =
+            tokio::spawn(async move { server_ref.run(server_state, server_cancel).await });
+
+        let stream = tokio::net::UnixStream::connect(&sock_path).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        writer
+            .write_all(&vec![b'A'; MAX_COMMAND_LINE_BYTES * 4])
+            .await
+            .unwrap();
+        writer.flush().await.unwrap();
+
+        let mut response = String::new();
+        reader.read_line(&mut response).await.unwrap();
+        assert!(response.starts_with("ERR: Command too long"));
+
+        cancel.cancel();
+        server_task.await.unwrap().unwrap();
+        server.cleanup().unwrap();
+    }
+
+    #[test]
+    fn test_decode_command_line_rejects_bad_input() {
+        assert_eq!(decode_command_line(b"status\n"), Ok(IpcCommand::Status));
+        assert!(decode_command_line(b"PAU\xffSE\n").is_err());
+        assert!(decode_command_line(b"STA\0TUS\n").is_err());
+        assert!(decode_command_line(&[b' '; MAX_COMMAND_LINE_BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_decode_request_line_reads_v2_frames() {
+        let request = decode_request_line(
+            br#"{"v":2,"command":"PAUSE","initiator":{"via":"cli","tty":"/dev/pts/3"}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.command, IpcCommand::Pause);
+        assert_eq!(
+            request.initiator,
+            Some(Initiator::Cli {
+                uid: None,
+                tty: Some("/dev/pts/3".to_string()),
+            })
+        );
+        assert_eq!(decode_request_line(b"PAUSE\n").unwrap().initiator, None);
+        assert!(decode_request_line(br#"{"v":2,"command":"NOPE"}"#).is_err());
+        assert!(decode_request_line(b"{not json}\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commands_are_attributed_to_the_peer_uid() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let state = Arc::new(MockState::default());
+        let cancel = serve(&sock_path, Arc::clone(&state)).await;
+
+        let frame = IpcFrame::new(
+            "PAUSE",
+            Initiator::Cli {
+                uid: Some(0),
+                tty: Some("/dev/pts/3".to_string()),
+            },
+        );
+        let response = request(&sock_path, &serde_json::to_string(&frame).unwrap()).await;
+        assert_eq!(response, "OK\n");
+
+        let uid = unsafe { libc::getuid() };
+        assert_eq!(
+            state.paused_by.lock().unwrap().clone(),
+            Some(Initiator::Cli {
+                uid: Some(uid),
+                tty: Some("/dev/pts/3".to_string()),
+            })
+        );
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_logs_returns_newest_records_filtered_by_level() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let state = Arc::new(MockState::default());
+        emit_burst(&state.logs, 10);
+        let cancel = serve(&sock_path, Arc::clone(&state)).await;
+
+        let response = request(&sock_path, "LOGS 3 WARN").await;
+        let batch: crate::telemetry::log_buffer::LogBatch =
+            serde_json::from_str(response.trim_end()).unwrap();
+        let messages: Vec<_> = batch.records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["burst 4", "burst 6", "burst 8"]);
+        assert!(batch.records.iter().all(|r| r.level == "WARN"));
+        assert_eq!(batch.dropped, 0);
+
+        let response = request(&sock_path, "LOGS 2").await;
+        let batch: crate::telemetry::log_buffer::LogBatch =
+            serde_json::from_str(response.trim_end()).unwrap();
+        let messages: Vec<_> = batch.records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["burst 8", "burst 9"]);
+
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_logs_follow_streams_new_records_until_disconnect() {
+        let temp = tempdir().unwrap();
+        let sock_path = temp.path().join("test.sock");
+        let state = Arc::new(MockState::default());
+        emit_burst(&state.logs, 2);
+        let cancel = serve(&sock_path, Arc::clone(&state)).await;
+
+        let stream = UnixStream::connect(&sock_path).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer.write_all(b"LOGS 1 WARN FOLLOW\n").await.unwrap();
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert!(line.contains("burst 0"));
+
+        emit_burst(&state.logs, 3);
+        let mut streamed = Vec::new();
+        for _ in 0..2 {
+            line.clear();
+            tokio::time::timeout(
+                std::time::Duration::from_secs(1),
+                reader.read_line(&mut line),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            let record: LogRecord = serde_json::from_str(line.trim_end()).unwrap();
+            streamed.push(record.message);
+        }
+        assert_eq!(streamed, vec!["burst 0", "burst 2"]);
+
+        drop(writer);
+        drop(reader);
+        cancel.cancel();
+    }
 }
@@ -1,9 +1,17 @@
+#[cfg(feature = "bots")]
 pub mod bot;
 pub mod cli;
 pub mod config;
 pub mod daemon;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod events;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod http;
+pub mod i18n;
 pub mod ipc;
+#[cfg(feature = "mcp")]
 pub mod mcp;
 pub mod monitor;
 pub mod notify;
@@ -11,6 +19,7 @@ pub mod opencode;
 pub mod resume;
 pub mod state;
 pub mod telemetry;
+pub mod util;
 
 #[cfg(test)]
 mod test_utils;
@@ -1,48 +1,249 @@
 use clap::Parser;
-use palingenesis::cli::{Cli, Commands, ConfigAction, DaemonAction, McpCommands, commands};
+#[cfg(feature = "mcp")]
+use palingenesis::cli::McpCommands;
+#[cfg(feature = "metrics-push")]
+use palingenesis::cli::MetricsAction;
+#[cfg(feature = "notifications")]
+use palingenesis::cli::NotifyAction;
+#[cfg(feature = "keyring")]
+use palingenesis::cli::SecretAction;
+#[cfg(feature = "http-api")]
+use palingenesis::cli::TokensAction;
+#[cfg(feature = "notifications")]
+use palingenesis::cli::WebhookAction;
+use palingenesis::cli::{
+    AttentionAction, Cli, Commands, ConfigAction, DaemonAction, ExclusionsAction, FailuresAction,
+    GcAction, IncidentsAction, InstallTarget, InstancesAction, NextStepAction, OrphansAction,
+    SchedulesAction, StateAction, UninstallTarget, WorktreesAction, commands,
+};
+use palingenesis::config::{PathFlags, Paths, ResolvedPaths};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    if let Some(instance) = &cli.instance {
+        Paths::set_instance(instance)?;
+    }
+    PathFlags {
+        state_dir: cli.state_dir.clone(),
+        runtime_dir: cli.runtime_dir.clone(),
+    }
+    .install();
+    ResolvedPaths::install(ResolvedPaths::load());
 
     let result = match cli.command {
         None => {
             println!("palingenesis - Agent resurrection daemon");
             println!("Use --help to see available commands");
+            if !Paths::config_file().exists() {
+                println!("First time here? Run: palingenesis quickstart");
+            }
             Ok(())
         }
         Some(Commands::Daemon { action }) => match action {
-            DaemonAction::Start { foreground } => commands::daemon::handle_start(foreground).await,
+            DaemonAction::Start {
+                foreground: true,
+                observe,
+                force_downgrade,
+            } => {
+                let result = commands::daemon::handle_start(true, observe, force_downgrade).await;
+                commands::daemon::exit_with_report(result)
+            }
+            DaemonAction::Start {
+                foreground: false,
+                observe,
+                force_downgrade,
+            } => commands::daemon::handle_start(false, observe, force_downgrade).await,
             DaemonAction::Stop => commands::daemon::handle_stop().await,
             DaemonAction::Restart => commands::daemon::handle_restart().await,
             DaemonAction::Reload => commands::daemon::handle_reload().await,
             DaemonAction::Status { json } => commands::daemon::handle_status(json).await,
         },
-        Some(Commands::Status { json }) => commands::status::handle_status(json).await,
+        Some(Commands::Status {
+            json,
+            verbose,
+            format,
+            exit_code,
+            quiet,
+        }) => commands::status::handle_status(json, verbose, format, exit_code, quiet).await,
+        Some(Commands::Check {
+            max_stall,
+            max_incident_age,
+            ping_url,
+            json,
+        }) => commands::check::handle_check(max_stall, max_incident_age, ping_url, json).await,
         Some(Commands::Logs {
             follow,
             tail,
             since,
-        }) => commands::logs::handle_logs(follow, tail, since).await,
+            level,
+        }) => commands::logs::handle_logs(follow, tail, since, level).await,
         Some(Commands::Config { action }) => match action {
-            ConfigAction::Init { force, path } => commands::config::handle_init(force, path).await,
+            ConfigAction::Init {
+                force,
+                path,
+                wizard,
+            } => commands::config::handle_init(force, path, wizard).await,
             ConfigAction::Show {
                 json,
                 section,
                 effective,
-            } => commands::config::handle_show(json, section, effective).await,
+                provenance,
+            } => commands::config::handle_show(json, section, effective, provenance).await,
             ConfigAction::Validate { path } => commands::config::handle_validate(path).await,
             ConfigAction::Edit { path, no_validate } => {
                 commands::config::handle_edit(path, no_validate).await
             }
+            ConfigAction::Diff { against, json } => {
+                commands::config::handle_diff(&against, json).await
+            }
         },
+        #[cfg(feature = "mcp")]
         Some(Commands::Mcp { command }) => match command {
             McpCommands::Serve => commands::mcp::handle_serve().await,
             McpCommands::Config => commands::mcp::handle_config().await,
         },
+        Some(Commands::Exclusions { action }) => match action {
+            ExclusionsAction::Test { path } => commands::exclusions::handle_test(path).await,
+        },
+        Some(Commands::NextStep { action }) => match action {
+            NextStepAction::Check { path, session } => {
+                commands::next_step::handle_check(path, session).await
+            }
+        },
+        Some(Commands::Instances { action }) => match action {
+            InstancesAction::List { json } => commands::instances::handle_list(json).await,
+        },
+        Some(Commands::Orphans { action }) => match action {
+            OrphansAction::List => commands::orphans::handle_list().await,
+            OrphansAction::Adopt { path } => commands::orphans::handle_adopt(path).await,
+            OrphansAction::Remove { path, yes } => {
+                commands::orphans::handle_remove(path, yes).await
+            }
+        },
+        Some(Commands::Failures { action }) => match action {
+            FailuresAction::List { json } => commands::failures::handle_list(json).await,
+            FailuresAction::Show { id } => commands::failures::handle_show(id).await,
+        },
+        Some(Commands::Incidents { action }) => match action {
+            IncidentsAction::List { json } => commands::incidents::handle_list(json).await,
+            IncidentsAction::Show { id } => commands::incidents::handle_show(id).await,
+        },
+        Some(Commands::Attention { action }) => match action {
+            AttentionAction::Clear => commands::attention::handle_clear().await,
+        },
+        Some(Commands::Sessions {
+            json,
+            status,
+            tag,
+            limit,
+        }) => commands::sessions::handle_sessions(json, status, tag, limit).await,
+        Some(Commands::State { action }) => match action {
+            StateAction::Dump => commands::state::handle_dump().await,
+        },
+        Some(Commands::Reconcile { dry_run, json }) => {
+            commands::reconcile::handle_reconcile(dry_run, json).await
+        }
+        Some(Commands::Gc {
+            action,
+            dry_run,
+            json,
+        }) => match action {
+            Some(GcAction::Approve) => commands::gc::handle_approve().await,
+            None => commands::gc::handle_gc(dry_run, json).await,
+        },
+        Some(Commands::Stats { costs, since, json }) => {
+            commands::stats::handle_stats(costs, since, json).await
+        }
+        Some(Commands::Timeline { json }) => commands::timeline::handle_timeline(json).await,
+        Some(Commands::Schedules { action }) => match action {
+            SchedulesAction::List { json } => commands::schedules::handle_list(json).await,
+            SchedulesAction::Run { name } => commands::schedules::handle_run(&name).await,
+        },
+        Some(Commands::Worktrees { action }) => match action {
+            WorktreesAction::List => commands::worktrees::handle_list().await,
+            WorktreesAction::Prune { force } => commands::worktrees::handle_prune(force).await,
+        },
+        Some(Commands::Quickstart { timeout }) => {
+            commands::quickstart::handle_quickstart(timeout).await
+        }
+        Some(Commands::VerifyInstall {
+            json,
+            with_notifications,
+        }) => commands::verify_install::handle_verify_install(json, with_notifications).await,
+        Some(Commands::Install { target }) => match target {
+            InstallTarget::Systemd {
+                system,
+                dry_run,
+                enable,
+                ..
+            } => commands::install::handle_install_systemd(system, dry_run, enable).await,
+        },
+        Some(Commands::Uninstall { target }) => match target {
+            UninstallTarget::Systemd { system, .. } => {
+                commands::install::handle_uninstall_systemd(system).await
+            }
+        },
+        #[cfg(feature = "metrics-push")]
+        Some(Commands::Metrics { action }) => match action {
+            MetricsAction::Push {
+                endpoint,
+                job,
+                instance,
+            } => commands::metrics::handle_push(endpoint, job, instance).await,
+        },
+        #[cfg(feature = "notifications")]
+        Some(Commands::Notify { action }) => match action {
+            NotifyAction::Recent {
+                channel,
+                outcome,
+                since,
+                limit,
+                json,
+            } => commands::notify::handle_recent(channel, outcome, since, limit, json).await,
+            NotifyAction::Rotate { channel } => commands::notify::handle_rotate(&channel).await,
+            NotifyAction::Status { json } => commands::notify::handle_status(json).await,
+            NotifyAction::Mute {
+                channel,
+                event,
+                duration,
+            } => commands::notify::handle_mute(channel, event, duration).await,
+            NotifyAction::Unmute { channel, event } => {
+                commands::notify::handle_unmute(channel, event).await
+            }
+            NotifyAction::Digest { since, json } => commands::notify::handle_digest(since, json),
+        },
+        #[cfg(feature = "notifications")]
+        Some(Commands::Webhook { action }) => match action {
+            WebhookAction::VerifyExample { secret, channel } => {
+                commands::webhook::handle_verify_example(secret, channel)
+            }
+        },
+        #[cfg(feature = "keyring")]
+        Some(Commands::Secret { action }) => match action {
+            SecretAction::Set { reference } => commands::secret::handle_set(&reference).await,
+            SecretAction::Rm { reference } => commands::secret::handle_rm(&reference).await,
+            SecretAction::Ls => commands::secret::handle_ls().await,
+        },
+        #[cfg(feature = "http-api")]
+        Some(Commands::Tokens { action }) => match action {
+            TokensAction::Check { token } => commands::tokens::handle_check(token).await,
+        },
+        Some(Commands::Jobs { json }) => commands::jobs::handle_jobs(json).await,
         Some(Commands::Pause) => commands::session::handle_pause().await,
         Some(Commands::Resume) => commands::session::handle_resume().await,
-        Some(Commands::NewSession) => commands::session::handle_new_session().await,
+        Some(Commands::Mode { mode }) => commands::session::handle_mode(mode).await,
+        Some(Commands::Ack { incident_id }) => commands::session::handle_ack(&incident_id).await,
+        Some(Commands::NewSession {
+            prompt: Some(prompt),
+            workdir,
+            model,
+            no_wait,
+            ..
+        }) => commands::session::handle_new_session_job(prompt, workdir, model, no_wait).await,
+        Some(Commands::NewSession { force, .. }) => {
+            commands::session::handle_new_session(force).await
+        }
     };
 
     if let Err(error) = result {
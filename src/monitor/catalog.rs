@@ -0,0 +1,628 @@
+//! Merged view of every session palingenesis knows about.
+//!
+//! Three sources are combined into one list: session records in the state
+//! file, session files found under `monitoring.session_dir`, and live
+//! sessions reported by the OpenCode API (correlated to files through the
+//! [`SessionIndex`](crate::monitor::session_index::SessionIndex)). Backs `GET /api/v1/sessions` and `palingenesis sessions`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::config::schema::Config;
+use crate::monitor::filter::WatchFilter;
+use crate::monitor::frontmatter::parse_session;
+use crate::monitor::scan::{DirCache, RecentScan};
+use crate::monitor::session::SessionState;
+use crate::opencode::SharedEndpoint;
+use crate::resume::SessionExclusions;
+use crate::resume::sidecar::session_tags;
+use crate::state::audit::AuditEntry;
+use crate::state::{AuditLogger, StateFile};
+use crate::telemetry::Metrics;
+
+/// Where a session entry was seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSource {
+    /// Recorded in the state file (current session, orphans, worktrees).
+    State,
+    /// Found under `monitoring.session_dir`.
+    Watcher,
+    /// Listed by the OpenCode API.
+    Opencode,
+}
+
+/// A session file found under the monitored directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedSession {
+    pub path: PathBuf,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub state: SessionState,
+}
+
+/// A session reported by the OpenCode API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveSession {
+    pub id: String,
+    pub title: Option<String>,
+    /// Session file, when the session index knows the ID.
+    pub path: Option<PathBuf>,
+}
+
+/// One merged session entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub sources: Vec<SessionSource>,
+    /// Frontmatter `status` (e.g. "in-progress", "complete").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub steps_completed: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_steps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+    /// The daemon's current session.
+    pub active: bool,
+    /// Monitoring is paused.
+    pub paused: bool,
+    /// Matches `resume.exclude_sessions`.
+    pub excluded: bool,
+    /// Stop reason of the most recent resume for this session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_classification: Option<String>,
+    /// Routing tags from the session's sidecar files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl SessionEntry {
+    fn new() -> Self {
+        Self {
+            path: None,
+            opencode_id: None,
+            title: None,
+            sources: Vec::new(),
+            status: None,
+            steps_completed: 0,
+            total_steps: None,
+            modified_at: None,
+            active: false,
+            paused: false,
+            excluded: false,
+            last_classification: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn add_source(&mut self, source: SessionSource) {
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+            self.sources.sort();
+        }
+    }
+}
+
+/// Everything the merge needs, already gathered.
+#[derive(Debug, Default)]
+pub struct CatalogSources<'a> {
+    pub state: Option<&'a StateFile>,
+    pub watched: &'a [WatchedSession],
+    pub live: &'a [LiveSession],
+    /// Last stop reason per session path.
+    pub classifications: HashMap<PathBuf, String>,
+    pub exclusions: SessionExclusions,
+    pub paused: bool,
+}
+
+/// Merge the sources into one entry per session.
+///
+/// Entries are keyed by session path; live sessions the index can't map to a
+/// file are listed on their own. The active session sorts first, then the
+/// most recently modified.
+pub fn merge(sources: CatalogSources<'_>) -> Vec<SessionEntry> {
+    let mut entries: Vec<SessionEntry> = Vec::new();
+    let mut by_path: HashMap<PathBuf, usize> = HashMap::new();
+    let mut entry_for = |entries: &mut Vec<SessionEntry>, path: &Path| -> usize {
+        *by_path.entry(path.to_path_buf()).or_insert_with(|| {
+            let mut entry = SessionEntry::new();
+            entry.path = Some(path.to_path_buf());
+            entries.push(entry);
+            entries.len() - 1
+        })
+    };
+
+    if let Some(state) = sources.state {
+        if let Some(current) = &state.current_session {
+            let index = entry_for(&mut entries, &current.path);
+            let entry = &mut entries[index];
+            entry.add_source(SessionSource::State);
+            entry.active = true;
+            entry.steps_completed = current.steps_completed.len();
+            if current.total_steps > 0 {
+                entry.total_steps = Some(current.total_steps);
+            }
+        }
+        let recorded = state
+            .orphaned_sessions
+            .iter()
+            .map(|orphan| &orphan.path)
+            .chain(state.worktrees.iter().map(|record| &record.session));
+        for path in recorded {
+            let index = entry_for(&mut entries, path);
+            entries[index].add_source(SessionSource::State);
+        }
+    }
+
+    for watched in sources.watched {
+        let index = entry_for(&mut entries, &watched.path);
+        let entry = &mut entries[index];
+        entry.add_source(SessionSource::Watcher);
+        entry.status = watched.state.status.clone();
+        entry.steps_completed = watched.state.steps_completed.len();
+        entry.total_steps = watched
+            .state
+            .total_steps
+            .and_then(|total| u32::try_from(total).ok())
+            .or(entry.total_steps);
+        entry.modified_at = watched.modified_at;
+    }
+
+    for live in sources.live {
+        let index = match &live.path {
+            Some(path) => entry_for(&mut entries, path),
+            None => {
+                entries.push(SessionEntry::new());
+                entries.len() - 1
+            }
+        };
+        let entry = &mut entries[index];
+        entry.add_source(SessionSource::Opencode);
+        entry.opencode_id = Some(live.id.clone());
+        entry.title = live.title.clone();
+    }
+
+    for entry in &mut entries {
+        entry.paused = sources.paused;
+        if let Some(path) = &entry.path {
+            entry.excluded = sources.exclusions.is_excluded(path);
+            entry.last_classification = sources.classifications.get(path).cloned();
+            entry.tags = session_tags(path);
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        b.active
+            .cmp(&a.active)
+            .then_with(|| b.modified_at.cmp(&a.modified_at))
+    });
+    entries
+}
+
+/// `?status=`, `?tag=` and `?limit=` filters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SessionQuery {
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl SessionQuery {
+    /// Keep entries whose status matches (ignoring case and `-`/`_`) and
+    /// that carry the tag, then truncate.
+    pub fn apply(&self, entries: Vec<SessionEntry>) -> Vec<SessionEntry> {
+        let wanted = self.status.as_deref().map(normalize_status);
+        let tag = self.tag.as_deref().map(|tag| tag.trim().to_lowercase());
+        let matching = entries.into_iter().filter(|entry| {
+            let status_matches = match &wanted {
+                Some(wanted) => entry
+                    .status
+                    .as_deref()
+                    .is_some_and(|status| normalize_status(status) == *wanted),
+                None => true,
+            };
+            status_matches && tag.as_ref().is_none_or(|tag| entry.tags.contains(tag))
+        });
+        match self.limit {
+            Some(limit) => matching.take(limit).collect(),
+            None => matching.collect(),
+        }
+    }
+}
+
+fn normalize_status(status: &str) -> String {
+    status.trim().to_lowercase().replace('_', "-")
+}
+
+/// Session files (files with frontmatter) among the `limit` most recently
+/// modified files under `session_dir`.
+///
+/// Honours the watcher filter and skips symlinks. Traversal is bounded (see
+/// [`RecentScan`]) and reuses listings from `cache` when given.
+pub fn scan_session_dir(
+    session_dir: &Path,
+    filter: &WatchFilter,
+    limit: usize,
+    cache: Option<&DirCache>,
+) -> Vec<WatchedSession> {
+    let started = Instant::now();
+    let mut scan = RecentScan::new(session_dir, filter).with_limit(limit);
+    if let Some(cache) = cache {
+        scan = scan.with_cache(cache);
+    }
+    let outcome = scan.run();
+    let sessions: Vec<WatchedSession> = outcome
+        .files
+        .into_iter()
+        .filter_map(|file| {
+            let session = parse_session(&file.path).ok()?;
+            Some(WatchedSession {
+                path: file.path,
+                modified_at: Some(DateTime::<Utc>::from(file.modified)),
+                state: session.state,
+            })
+        })
+        .collect();
+    debug!(
+        dir = %session_dir.display(),
+        dirs = outcome.dirs_visited,
+        files = outcome.files_seen,
+        exhaustive = outcome.exhaustive,
+        "Scanned session directory"
+    );
+    if let Some(metrics) = Metrics::global() {
+        metrics.record_session_scan("catalog", started.elapsed());
+    }
+    sessions
+}
+
+/// Stop reason of the latest audited resume per session.
+pub fn last_classifications(entries: &[AuditEntry]) -> HashMap<PathBuf, String> {
+    let mut latest: HashMap<PathBuf, (DateTime<Utc>, String)> = HashMap::new();
+    for entry in entries {
+        let (Some(path), Some(reason)) = (&entry.session_path, &entry.stop_reason) else {
+            continue;
+        };
+        let newer = latest
+            .get(path)
+            .is_none_or(|(seen, _)| entry.timestamp >= *seen);
+        if newer {
+            latest.insert(path.clone(), (entry.timestamp, reason.clone()));
+        }
+    }
+    latest
+        .into_iter()
+        .map(|(path, (_, reason))| (path, reason))
+        .collect()
+}
+
+/// Gather all sources for `config` and merge them.
+///
+/// `cache` holds session directory listings kept between calls. `endpoint` is the daemon's discovered OpenCode endpoint, if any; live
+/// sessions are only fetched when `[opencode].enabled`, and the fetch is
+/// abandoned when `cancel` fires.
+pub async fn collect(
+    config: &Config,
+    state: &StateFile,
+    state_dir: &Path,
+    paused: bool,
+    cache: Option<&DirCache>,
+    endpoint: Option<SharedEndpoint>,
+    cancel: CancellationToken,
+) -> Vec<SessionEntry> {
+    let watched = scan_session_dir(
+        &config.monitoring.session_dir,
+        &WatchFilter::from_config(&config.monitoring),
+        config.monitoring.scan_limit,
+        cache,
+    );
+    let live = if config.opencode.enabled {
+        live_sessions(config, endpoint, cancel).await
+    } else {
+        Vec::new()
+    };
+    let classifications = AuditLogger::new(state_dir)
+        .query()
+        .execute()
+        .map(|entries| last_classifications(&entries))
+        .unwrap_or_default();
+
+    merge(CatalogSources {
+        state: Some(state),
+        watched: &watched,
+        live: &live,
+        classifications,
+        exclusions: SessionExclusions::from_config(&config.resume),
+        paused,
+    })
+}
+
+/// Sessions the OpenCode API reports, with their files when the session
+/// index knows them; empty when the API cannot be reached.
+#[cfg(feature = "opencode-api")]
+pub async fn live_sessions(
+    config: &Config,
+    endpoint: Option<SharedEndpoint>,
+    cancel: CancellationToken,
+) -> Vec<LiveSession> {
+    let mut client =
+        crate::opencode::OpenCodeClient::new(&config.opencode).with_cancellation(cancel);
+    if let Some(endpoint) = endpoint {
+        client = client.with_endpoint(endpoint);
+    }
+    let sessions = match client.list_sessions().await {
+        Ok(sessions) => sessions,
+        Err(err) => {
+            debug!(error = %err, "OpenCode session listing unavailable");
+            return Vec::new();
+        }
+    };
+    let index = crate::monitor::session_index::SessionIndex::default_location();
+    sessions
+        .into_iter()
+        .map(|session| {
+            let indexed = index
+                .as_ref()
+                .and_then(|index| index.lookup_by_id(&session.id));
+            let title = session
+                .metadata
+                .get("title")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+                .or_else(|| indexed.as_ref().and_then(|found| found.title.clone()));
+            LiveSession {
+                id: session.id,
+                title,
+                path: indexed.map(|found| found.path),
+            }
+        })
+        .collect()
+}
+
+/// Live sessions come from the OpenCode API and need the `opencode-api` feature.
+#[cfg(not(feature = "opencode-api"))]
+pub async fn live_sessions(
+    _config: &Config,
+    _endpoint: Option<SharedEndpoint>,
+    _cancel: CancellationToken,
+) -> Vec<LiveSession> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::state::{CurrentSession, OrphanedSession};
+
+    fn watched(path: &str, status: &str, modified_secs: i64) -> WatchedSession {
+        WatchedSession {
+            path: PathBuf::from(path),
+            modified_at: DateTime::from_timestamp(modified_secs, 0),
+            state: serde_yaml::from_str(&format!("status: {status}\nstepsCompleted: [1, 2]\n"))
+                .unwrap(),
+        }
+    }
+
+    fn live(id: &str, path: Option<&str>) -> LiveSession {
+        LiveSession {
+            id: id.to_string(),
+            title: Some(format!("title {id}")),
+            path: path.map(PathBuf::from),
+        }
+    }
+
+    fn state_with_current(path: &str) -> StateFile {
+        StateFile {
+            current_session: Some(CurrentSession {
+                path: PathBuf::from(path),
+                total_steps: 5,
+                ..CurrentSession::default()
+            }),
+            ..StateFile::default()
+        }
+    }
+
+    #[test]
+    fn entries_from_a_single_source_stand_alone() {
+        let mut state = StateFile::default();
+        state.record_orphan(OrphanedSession::new(PathBuf::from("/s/orphan.md"), "boom"));
+        let files = [watched("/s/file.md", "in-progress", 10)];
+        let api = [live("ses_1", None)];
+
+        let entries = merge(CatalogSources {
+            state: Some(&state),
+            watched: &files,
+            live: &api,
+            ..CatalogSources::default()
+        });
+
+        assert_eq!(entries.len(), 3);
+        let sources: Vec<_> = entries.iter().map(|entry| entry.sources.clone()).collect();
+        assert!(sources.contains(&vec![SessionSource::State]));
+        assert!(sources.contains(&vec![SessionSource::Watcher]));
+        assert!(sources.contains(&vec![SessionSource::Opencode]));
+        let unmapped = entries
+            .iter()
+            .find(|entry| entry.opencode_id.as_deref() == Some("ses_1"))
+            .unwrap();
+        assert_eq!(unmapped.path, None);
+        assert!(entries.iter().all(|entry| !entry.active));
+    }
+
+    #[test]
+    fn entries_known_to_two_sources_merge() {
+        let state = state_with_current("/s/current.md");
+        let files = [
+            watched("/s/current.md", "in-progress", 20),
+            watched("/s/other.md", "complete", 30),
+        ];
+        let api = [live("ses_2", Some("/s/other.md"))];
+
+        let entries = merge(CatalogSources {
+            state: Some(&state),
+            watched: &files,
+            live: &api,
+            ..CatalogSources::default()
+        });
+
+        assert_eq!(entries.len(), 2);
+        let current = &entries[0];
+        assert_eq!(current.path, Some(PathBuf::from("/s/current.md")));
+        assert!(current.active);
+        assert_eq!(
+            current.sources,
+            vec![SessionSource::State, SessionSource::Watcher]
+        );
+        assert_eq!(current.total_steps, Some(5));
+        assert_eq!(current.steps_completed, 2);
+
+        let other = &entries[1];
+        assert_eq!(
+            other.sources,
+            vec![SessionSource::Watcher, SessionSource::Opencode]
+        );
+        assert_eq!(other.opencode_id.as_deref(), Some("ses_2"));
+        assert_eq!(other.status.as_deref(), Some("complete"));
+    }
+
+    #[test]
+    fn entries_known_to_all_sources_carry_flags() {
+        let state = state_with_current("/s/experiments/run.md");
+        let files = [watched("/s/experiments/run.md", "in-progress", 40)];
+        let api = [live("ses_3", Some("/s/experiments/run.md"))];
+        let classifications =
+            HashMap::from([(PathBuf::from("/s/experiments/run.md"), "rate_limit".into())]);
+
+        let entries = merge(CatalogSources {
+            state: Some(&state),
+            watched: &files,
+            live: &api,
+            classifications,
+            exclusions: SessionExclusions::new(&["**/experiments/**"]),
+            paused: true,
+        });
+
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(
+            entry.sources,
+            vec![
+                SessionSource::State,
+                SessionSource::Watcher,
+                SessionSource::Opencode
+            ]
+        );
+        assert!(entry.active && entry.paused && entry.excluded);
+        assert_eq!(entry.last_classification.as_deref(), Some("rate_limit"));
+        assert_eq!(entry.title.as_deref(), Some("title ses_3"));
+    }
+
+    #[test]
+    fn query_filters_status_and_limits() {
+        let files = [
+            watched("/s/a.md", "in_progress", 1),
+            watched("/s/b.md", "complete", 2),
+            watched("/s/c.md", "In-Progress", 3),
+        ];
+        let entries = merge(CatalogSources {
+            watched: &files,
+            ..CatalogSources::default()
+        });
+
+        let query = SessionQuery {
+            status: Some("in-progress".to_string()),
+            ..SessionQuery::default()
+        };
+        let paths: Vec<_> = query
+            .apply(entries.clone())
+            .into_iter()
+            .filter_map(|entry| entry.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/s/c.md"), PathBuf::from("/s/a.md")]
+        );
+
+        let query = SessionQuery {
+            limit: Some(1),
+            ..SessionQuery::default()
+        };
+        assert_eq!(query.apply(entries.clone()).len(), 1);
+
+        let mut tagged = entries;
+        tagged[1].tags = vec!["proj-a".to_string()];
+        let query = SessionQuery {
+            tag: Some("Proj-A".to_string()),
+            ..SessionQuery::default()
+        };
+        let paths: Vec<_> = query
+            .apply(tagged)
+            .into_iter()
+            .filter_map(|entry| entry.path)
+            .collect();
+        assert_eq!(paths.len(), 1);
+    }
+
+    #[test]
+    fn last_classification_prefers_newest_entry() {
+        use crate::state::audit::AuditEventType;
+
+        let path = PathBuf::from("/s/a.md");
+        let older = AuditEntry::new(AuditEventType::ResumeStarted, "resume")
+            .with_session(path.clone())
+            .with_stop_reason("rate_limit");
+        let mut newer = AuditEntry::new(AuditEventType::ResumeStarted, "resume")
+            .with_session(path.clone())
+            .with_stop_reason("context_exhausted");
+        newer.timestamp = older.timestamp + chrono::TimeDelta::seconds(5);
+
+        let latest = last_classifications(&[newer, older]);
+        assert_eq!(
+            latest.get(&path).map(String::as_str),
+            Some("context_exhausted")
+        );
+    }
+
+    #[test]
+    fn scan_finds_frontmatter_sessions_and_honours_filter() {
+        let temp = tempfile::tempdir().unwrap();
+        let nested = temp.path().join("project");
+        fs::create_dir_all(nested.join("skip")).unwrap();
+        fs::write(
+            nested.join("session.md"),
+            "---\nstatus: in-progress\nstepsCompleted: [1]\n---\nbody\n",
+        )
+        .unwrap();
+        fs::write(nested.join("notes.md"), "no frontmatter\n").unwrap();
+        fs::write(
+            nested.join("skip/ignored.md"),
+            "---\nstatus: complete\n---\n",
+        )
+        .unwrap();
+
+        let filter = WatchFilter::new(&["md"], &["**/skip/**"]);
+        let sessions = scan_session_dir(temp.path(), &filter, 10, None);
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].path, nested.join("session.md"));
+        assert_eq!(sessions[0].state.status.as_deref(), Some("in-progress"));
+        assert!(sessions[0].modified_at.is_some());
+    }
+}
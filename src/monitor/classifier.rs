@@ -1,22 +1,42 @@
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use tracing::{debug, info, warn};
 
+use crate::config::schema::ClassificationConfig;
+use crate::monitor::clock_skew::ClockSkew;
+use crate::monitor::reopen::PriorCompletion;
+use crate::util::content::{DEFAULT_MAX_EVIDENCE_CHARS, cap_in_place};
+use crate::util::encoding;
+
 const DEFAULT_RETRY_WAIT_SECS: u64 = 30;
 const DEFAULT_MAX_LINES: usize = 100;
+const DEFAULT_REOPEN_GROWTH_BYTES: u64 = 512;
 const EXIT_CODE_SIGHUP: i32 = 129;
 const EXIT_CODE_SIGINT: i32 = 130;
 const EXIT_CODE_SIGTERM: i32 = 143;
+/// RFC 7231 IMF-fixdate, e.g. `Wed, 21 Oct 2026 07:28:00 GMT`.
+const HTTP_DATE: &str =
+    r"[a-z]{3},\s+\d{1,2}\s+[a-z]{3}\s+\d{4}\s+\d{2}:\d{2}:\d{2}\s+(?:gmt|utc|[+-]\d{4})";
+
+/// Line a workflow writes to declare itself finished.
+pub const COMPLETION_MARKER: &str = "<!-- palingenesis: complete -->";
 
 /// Reason why a session stopped.
 #[derive(Debug, Clone, PartialEq)]
 pub enum StopReason {
     /// Session hit rate limit (HTTP 429 or equivalent).
     RateLimit(RateLimitInfo),
+    /// Provider quota for the current window (day, month) is used up.
+    ///
+    /// Retrying before `resets_at` cannot succeed; `None` when the transcript
+    /// did not say when the window resets.
+    QuotaExhausted { resets_at: Option<DateTime<Utc>> },
     /// Session exhausted context window.
     ContextExhausted(Option<ContextExhaustionInfo>),
     /// User explicitly exited (Ctrl+C, exit command).
@@ -32,6 +52,7 @@ impl StopReason {
     pub fn should_auto_resume(&self) -> bool {
         match self {
             StopReason::RateLimit(_) => true,
+            StopReason::QuotaExhausted { .. } => true,
             StopReason::ContextExhausted(_) => true,
             StopReason::UserExit(_) => false,
             StopReason::Completed => false,
@@ -39,9 +60,22 @@ impl StopReason {
         }
     }
 
+    /// Snake-case name, as matched by `resume.custom_strategies`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StopReason::RateLimit(_) => "rate_limit",
+            StopReason::QuotaExhausted { .. } => "quota_exhausted",
+            StopReason::ContextExhausted(_) => "context_exhausted",
+            StopReason::UserExit(_) => "user_exit",
+            StopReason::Completed => "completed",
+            StopReason::Unknown(_) => "unknown",
+        }
+    }
+
     pub fn metrics_reason_label(&self) -> Option<&'static str> {
         match self {
             StopReason::RateLimit(_) => Some("rate_limit"),
+            StopReason::QuotaExhausted { .. } => Some("quota_exhausted"),
             StopReason::ContextExhausted(_) => Some("context_exhausted"),
             StopReason::UserExit(_) | StopReason::Completed => Some("manual"),
             StopReason::Unknown(_) => None,
@@ -120,6 +154,14 @@ pub enum ClassifierError {
 
     #[error("Pattern compilation error: {0}")]
     Pattern(#[from] regex::Error),
+
+    #[error("Invalid {kind} pattern {pattern:?}: {source}")]
+    InvalidPattern {
+        kind: &'static str,
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
 }
 
 /// Configuration for the classifier.
@@ -139,6 +181,10 @@ pub struct ClassifierConfig {
     pub extra_rate_limit_patterns: Vec<String>,
     /// Extra context exhaustion patterns for future extensibility.
     pub extra_context_patterns: Vec<String>,
+    /// Longest evidence line or matched message kept in a result (characters).
+    pub max_evidence_chars: usize,
+    /// Growth of a completed session file that counts as reopening it (bytes).
+    pub reopen_growth_bytes: u64,
 }
 
 impl Default for ClassifierConfig {
@@ -157,16 +203,115 @@ impl Default for ClassifierConfig {
             known_context_sizes,
             extra_rate_limit_patterns: Vec::new(),
             extra_context_patterns: Vec::new(),
+            max_evidence_chars: DEFAULT_MAX_EVIDENCE_CHARS,
+            reopen_growth_bytes: DEFAULT_REOPEN_GROWTH_BYTES,
+        }
+    }
+}
+
+impl ClassifierConfig {
+    /// Build from the `[classifier]` config section.
+    ///
+    /// `known_context_sizes` entries are merged over the built-in sizes (model
+    /// names compared case-insensitively), so adding one model keeps the rest.
+    pub fn from_config(config: &ClassificationConfig) -> Self {
+        let mut known_context_sizes = Self::default().known_context_sizes;
+        for (model, tokens) in &config.known_context_sizes {
+            known_context_sizes.insert(model.trim().to_lowercase(), *tokens);
+        }
+        Self {
+            default_retry_wait: Duration::from_secs(config.default_retry_wait_secs),
+            max_lines: config.max_lines,
+            context_threshold_percent: config.context_threshold_percent,
+            default_context_size: config.default_context_size,
+            known_context_sizes,
+            extra_rate_limit_patterns: config.extra_rate_limit_patterns.clone(),
+            extra_context_patterns: config.extra_context_patterns.clone(),
+            max_evidence_chars: config.max_evidence_chars,
+            reopen_growth_bytes: config.reopen_growth_bytes,
         }
     }
 }
 
+thread_local! {
+    static PATTERNS_COMPILED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Regexes compiled by classifiers on the current thread.
+///
+/// All compilation happens in the constructors; classification never adds to it.
+pub fn compiled_pattern_count() -> usize {
+    PATTERNS_COMPILED.with(Cell::get)
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    PATTERNS_COMPILED.with(|count| count.set(count.get() + 1));
+    Regex::new(pattern)
+}
+
+/// Compile a user-supplied pattern, naming it in the error.
+fn compile_extra(kind: &'static str, pattern: &str) -> Result<Regex, ClassifierError> {
+    compile(pattern).map_err(|source| ClassifierError::InvalidPattern {
+        kind,
+        pattern: pattern.to_string(),
+        source,
+    })
+}
+
+/// Patterns that pull values (token counts, retry delays) out of content.
+struct ValuePatterns {
+    tokens_used_of: Regex,
+    tokens_fraction: Regex,
+    tokens_used_count: Regex,
+    retry_after_header: Regex,
+    retry_after_date: Regex,
+    date_header: Regex,
+    retry_after_json: Regex,
+    retry_after_text: Regex,
+    quota_reset_at: Regex,
+    quota_reset_date: Regex,
+    quota_reset_in: Regex,
+    ratelimit_reset_header: Regex,
+}
+
+impl ValuePatterns {
+    fn build() -> Result<Self, ClassifierError> {
+        Ok(Self {
+            tokens_used_of: compile(r"(?i)used\s+(\d{2,})\s+of\s+(\d{2,})\s+tokens")?,
+            tokens_fraction: compile(r"(?i)(\d{2,})\s*/\s*(\d{2,})\s*tokens?")?,
+            tokens_used_count: compile(r"(?i)(\d{2,})\s*tokens?\s*(?:used|consumed|spent)")?,
+            retry_after_header: compile(r"(?i)retry-after[:\s]+(\d+)")?,
+            retry_after_date: compile(&format!(r"(?i)retry-after:\s*({HTTP_DATE})"))?,
+            date_header: compile(&format!(r"(?i)\bdate:\s*({HTTP_DATE})"))?,
+            retry_after_json: compile(r#"\"retry_after\"\s*:\s*\"?(\d+)\"?"#)?,
+            retry_after_text: compile(r"(?i)try\s+again\s+in\s+(\d+)\s*(?:seconds|second|sec|s)")?,
+            quota_reset_at: compile(
+                r"(?i)(?:resets?|try\s+again|available)\s+(?:at|on|after)\s+(\d{4}-\d{2}-\d{2}[t ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?\s*(?:z|utc|gmt|[+-]\d{2}:?\d{2})?)",
+            )?,
+            quota_reset_date: compile(&format!(
+                r"(?i)(?:resets?|try\s+again|available)\s+(?:at|on|after)\s+({HTTP_DATE})"
+            ))?,
+            quota_reset_in: compile(
+                r"(?i)resets?\s+in\s+(\d+)\s*(days?|d|hours?|hrs?|h|minutes?|mins?|m)\b",
+            )?,
+            ratelimit_reset_header: compile(r"(?i)ratelimit(?:-[a-z]+)*-reset:\s*(\S+)")?,
+        })
+    }
+}
+
+fn shared_slot() -> &'static RwLock<Arc<StopReasonClassifier>> {
+    static SHARED: OnceLock<RwLock<Arc<StopReasonClassifier>>> = OnceLock::new();
+    SHARED.get_or_init(|| RwLock::new(Arc::new(StopReasonClassifier::default())))
+}
+
 /// Stop reason classifier implementation.
 pub struct StopReasonClassifier {
     config: ClassifierConfig,
     rate_limit_patterns: Vec<Regex>,
+    quota_patterns: Vec<Regex>,
     context_patterns: Vec<Regex>,
     user_exit_patterns: Vec<Regex>,
+    values: ValuePatterns,
 }
 
 impl StopReasonClassifier {
@@ -175,67 +320,193 @@ impl StopReasonClassifier {
         Self::with_config(ClassifierConfig::default())
     }
 
+    /// Process-wide classifier with the effective `[classifier]` configuration.
+    ///
+    /// Starts with the defaults and is swapped by [`Self::install_shared`] when
+    /// the daemon loads or reloads config, so callers outside the monitor use
+    /// the same settings without recompiling every pattern per classification.
+    pub fn shared() -> Arc<Self> {
+        match shared_slot().read() {
+            Ok(current) => Arc::clone(&current),
+            Err(poisoned) => Arc::clone(&poisoned.into_inner()),
+        }
+    }
+
+    /// Replace the process-wide classifier returned by [`Self::shared`].
+    pub fn install_shared(classifier: Self) {
+        let classifier = Arc::new(classifier);
+        match shared_slot().write() {
+            Ok(mut current) => *current = classifier,
+            Err(poisoned) => *poisoned.into_inner() = classifier,
+        }
+    }
+
+    /// Build a classifier from `[classifier]` and make it the shared one.
+    ///
+    /// Also applies `clock_skew_warn_secs` to [`ClockSkew::shared`].
+    pub fn apply_config(config: &ClassificationConfig) -> Result<(), ClassifierError> {
+        let classifier = Self::with_config(ClassifierConfig::from_config(config))?;
+        Self::install_shared(classifier);
+        ClockSkew::shared().set_warn_threshold(Duration::from_secs(config.clock_skew_warn_secs));
+        Ok(())
+    }
+
     /// Create with custom configuration.
+    ///
+    /// Extra patterns are compiled here, so an invalid one fails construction.
     pub fn with_config(config: ClassifierConfig) -> Result<Self, ClassifierError> {
         let mut rate_limit_patterns = vec![
-            Regex::new(r"(?i)rate[_-]?limit[_-]?error")?,
-            Regex::new(r"(?i)rate\s+limit\s+reached")?,
-            Regex::new(r"(?i)\b429\b")?,
-            Regex::new(r"(?i)too\s+many\s+requests")?,
-            Regex::new(r"(?i)quota\s+exceeded")?,
-            Regex::new(r"(?i)overloaded[_-]?error|overloaded")?,
-            Regex::new(r"(?i)throttl")?,
+            compile(r"(?i)rate[_-]?limit[_-]?error")?,
+            compile(r"(?i)rate\s+limit\s+reached")?,
+            compile(r"(?i)\b429\b")?,
+            compile(r"(?i)too\s+many\s+requests")?,
+            compile(r"(?i)quota\s+exceeded")?,
+            compile(r"(?i)overloaded[_-]?error|overloaded")?,
+            compile(r"(?i)throttl")?,
         ];
 
         for pattern in &config.extra_rate_limit_patterns {
-            rate_limit_patterns.push(Regex::new(pattern)?);
+            rate_limit_patterns.push(compile_extra("rate limit", pattern)?);
         }
 
+        let quota_patterns = vec![
+            compile(
+                r"(?i)\b(?:daily|weekly|monthly)\s+(?:token\s+|request\s+|usage\s+)?(?:quota|limit)",
+            )?,
+            compile(r"(?i)quota\s+(?:will\s+)?resets?")?,
+            compile(r"(?i)quota\s+(?:has\s+been\s+)?exhausted")?,
+            compile(r"(?i)limit\s+will\s+reset")?,
+            compile(r"(?i)insufficient[_\s]quota")?,
+        ];
+
         let mut context_patterns = Self::build_context_patterns()?;
         for pattern in &config.extra_context_patterns {
-            context_patterns.push(Regex::new(pattern)?);
+            context_patterns.push(compile_extra("context exhaustion", pattern)?);
         }
 
         let user_exit_patterns = Self::build_user_exit_patterns()?;
+        let values = ValuePatterns::build()?;
 
         Ok(Self {
             config,
             rate_limit_patterns,
+            quota_patterns,
             context_patterns,
             user_exit_patterns,
+            values,
         })
     }
 
     /// Classify the stop reason from session file content.
     pub fn classify(&self, session_path: &Path, exit_code: Option<i32>) -> ClassificationResult {
+        self.classify_with_tail(session_path, exit_code).0
+    }
+
+    /// Like [`Self::classify`], also returning the tail that was analyzed
+    /// (`None` when the file could not be read).
+    pub fn classify_with_tail(
+        &self,
+        session_path: &Path,
+        exit_code: Option<i32>,
+    ) -> (ClassificationResult, Option<String>) {
+        self.classify_with_prior(session_path, exit_code, None)
+    }
+
+    /// Like [`Self::classify_with_tail`], for a session whose last stop was
+    /// classified as completed: unchanged it stays completed, reopened its old
+    /// completion signals no longer count.
+    pub fn classify_with_prior(
+        &self,
+        session_path: &Path,
+        exit_code: Option<i32>,
+        prior: Option<&PriorCompletion>,
+    ) -> (ClassificationResult, Option<String>) {
         let content = match self.read_file_tail(session_path, self.config.max_lines) {
             Ok(content) => content,
             Err(err) => {
                 warn!(error = %err, "Failed to read session file");
-                return ClassificationResult {
+                let result = ClassificationResult {
                     reason: StopReason::Unknown(format!("Read error: {err}")),
                     confidence: 0.0,
                     evidence: vec![format!("error: {err}")],
                 };
+                return (result, None);
             }
         };
 
-        self.classify_with_session(&content, Some(session_path), exit_code)
+        let result = self.classify_with_session(&content, Some(session_path), exit_code, prior);
+        (result, Some(content))
     }
 
     /// Classify from raw content (for log analysis).
     pub fn classify_content(&self, content: &str, exit_code: Option<i32>) -> ClassificationResult {
-        self.classify_with_session(content, None, exit_code)
+        self.classify_with_session(content, None, exit_code, None)
     }
 
+    /// Classify, then cap evidence and matched messages so a huge match
+    /// (e.g. from a broad extra pattern) is not carried through the resume.
     fn classify_with_session(
         &self,
         content: &str,
         session_path: Option<&Path>,
         exit_code: Option<i32>,
+        prior: Option<&PriorCompletion>,
+    ) -> ClassificationResult {
+        let mut result = self.classify_uncapped(content, session_path, exit_code, prior);
+        let max_chars = self.config.max_evidence_chars;
+        for line in &mut result.evidence {
+            cap_in_place(line, max_chars);
+        }
+        let message = match &mut result.reason {
+            StopReason::RateLimit(info) => info.message.as_mut(),
+            StopReason::ContextExhausted(info) => {
+                info.as_mut().and_then(|info| info.message.as_mut())
+            }
+            StopReason::UserExit(info) => info.message.as_mut(),
+            StopReason::Unknown(message) => Some(message),
+            StopReason::QuotaExhausted { .. } | StopReason::Completed => None,
+        };
+        if let Some(message) = message {
+            cap_in_place(message, max_chars);
+        }
+        result
+    }
+
+    fn classify_uncapped(
+        &self,
+        content: &str,
+        session_path: Option<&Path>,
+        exit_code: Option<i32>,
+        prior: Option<&PriorCompletion>,
     ) -> ClassificationResult {
         let mut evidence = Vec::new();
 
+        // An unchanged completed session stays completed whatever its
+        // transcript says; only reopening it brings classification back.
+        if let (Some(path), Some(PriorCompletion::Unchanged)) = (session_path, prior) {
+            if let Some(reason) = self.check_completed(path, prior, &mut evidence) {
+                debug!("Session still completed");
+                return ClassificationResult {
+                    reason,
+                    confidence: 0.95,
+                    evidence,
+                };
+            }
+        }
+
+        // Checked before rate limits: "daily quota exceeded" also matches the
+        // generic "quota exceeded" rate limit pattern, but backing off for
+        // minutes cannot help until the window resets.
+        if let Some(resets_at) = self.detect_quota_exhausted(content, Utc::now(), &mut evidence) {
+            let confidence = Self::confidence_from_evidence(&evidence, 0.85);
+            debug!(confidence, ?resets_at, "Classified stop as quota exhausted");
+            return ClassificationResult {
+                reason: StopReason::QuotaExhausted { resets_at },
+                confidence,
+                evidence,
+            };
+        }
+
         if let Some(info) = self.detect_rate_limit(content, &mut evidence) {
             let confidence = Self::confidence_from_evidence(&evidence, 0.85);
             debug!(confidence, "Classified stop as rate limit");
@@ -247,7 +518,7 @@ impl StopReasonClassifier {
         }
 
         if let Some(path) = session_path {
-            if let Some(reason) = self.check_completed(path, &mut evidence) {
+            if let Some(reason) = self.check_completed(path, prior, &mut evidence) {
                 debug!("Classified stop as completed");
                 return ClassificationResult {
                     reason,
@@ -286,28 +557,28 @@ impl StopReasonClassifier {
 
     fn build_context_patterns() -> Result<Vec<Regex>, ClassifierError> {
         Ok(vec![
-            Regex::new(r"(?i)context[_-]?length[_-]?exceeded")?,
-            Regex::new(r"(?i)maximum\s+context\s+length")?,
-            Regex::new(r"(?i)token\s+limit\s+exceeded")?,
-            Regex::new(r"(?i)conversation\s+too\s+long")?,
-            Regex::new(r"(?i)context\s+window\s+(?:full|exceeded|limit)")?,
-            Regex::new(r"(?i)max\s*tokens?\s+reached")?,
-            Regex::new(r"(?i)prompt\s+is\s+too\s+long")?,
-            Regex::new(r"(?i)context\s+(?:truncated|reset)")?,
-            Regex::new(r"(?i)conversation\s+reset")?,
+            compile(r"(?i)context[_-]?length[_-]?exceeded")?,
+            compile(r"(?i)maximum\s+context\s+length")?,
+            compile(r"(?i)token\s+limit\s+exceeded")?,
+            compile(r"(?i)conversation\s+too\s+long")?,
+            compile(r"(?i)context\s+window\s+(?:full|exceeded|limit)")?,
+            compile(r"(?i)max\s*tokens?\s+reached")?,
+            compile(r"(?i)prompt\s+is\s+too\s+long")?,
+            compile(r"(?i)context\s+(?:truncated|reset)")?,
+            compile(r"(?i)conversation\s+reset")?,
         ])
     }
 
     fn build_user_exit_patterns() -> Result<Vec<Regex>, ClassifierError> {
         Ok(vec![
-            Regex::new(r"(?im)^\s*exit\s*$")?,
-            Regex::new(r"(?im)^\s*quit\s*$")?,
-            Regex::new(r"(?im)^\s*/bye\s*$")?,
-            Regex::new(r"(?im)^\s*goodbye\s*$")?,
-            Regex::new(r"(?im)^\s*done\s*$")?,
-            Regex::new(r"(?i)keyboard\s+interrupt")?,
-            Regex::new(r"(?i)interrupted\s+by\s+user")?,
-            Regex::new(r"(?i)sigint\s+received")?,
+            compile(r"(?im)^\s*exit\s*$")?,
+            compile(r"(?im)^\s*quit\s*$")?,
+            compile(r"(?im)^\s*/bye\s*$")?,
+            compile(r"(?im)^\s*goodbye\s*$")?,
+            compile(r"(?im)^\s*done\s*$")?,
+            compile(r"(?i)keyboard\s+interrupt")?,
+            compile(r"(?i)interrupted\s+by\s+user")?,
+            compile(r"(?i)sigint\s+received")?,
         ])
     }
 
@@ -419,8 +690,7 @@ impl StopReasonClassifier {
     }
 
     fn extract_token_usage(&self, content: &str) -> Option<(f32, u32)> {
-        let used_of_pattern = Regex::new(r"(?i)used\s+(\d{2,})\s+of\s+(\d{2,})\s+tokens").ok();
-        if let Some(re) = used_of_pattern {
+        for re in [&self.values.tokens_used_of, &self.values.tokens_fraction] {
             if let Some(caps) = re.captures(content) {
                 let used = caps.get(1).and_then(|m| m.as_str().parse::<u32>().ok());
                 let total = caps.get(2).and_then(|m| m.as_str().parse::<u32>().ok());
@@ -430,25 +700,10 @@ impl StopReasonClassifier {
             }
         }
 
-        let fraction_pattern = Regex::new(r"(?i)(\d{2,})\s*/\s*(\d{2,})\s*tokens?").ok();
-        if let Some(re) = fraction_pattern {
-            if let Some(caps) = re.captures(content) {
-                let used = caps.get(1).and_then(|m| m.as_str().parse::<u32>().ok());
-                let total = caps.get(2).and_then(|m| m.as_str().parse::<u32>().ok());
-                if let (Some(used), Some(total)) = (used, total) {
-                    return Some((used as f32 / total as f32, total));
-                }
-            }
-        }
-
-        let used_count_pattern =
-            Regex::new(r"(?i)(\d{2,})\s*tokens?\s*(?:used|consumed|spent)").ok();
-        if let Some(re) = used_count_pattern {
-            if let Some(caps) = re.captures(content) {
-                if let Some(used) = caps.get(1).and_then(|m| m.as_str().parse::<u32>().ok()) {
-                    let context_size = self.infer_context_size(content);
-                    return Some((used as f32 / context_size as f32, context_size));
-                }
+        if let Some(caps) = self.values.tokens_used_count.captures(content) {
+            if let Some(used) = caps.get(1).and_then(|m| m.as_str().parse::<u32>().ok()) {
+                let context_size = self.infer_context_size(content);
+                return Some((used as f32 / context_size as f32, context_size));
             }
         }
 
@@ -473,13 +728,36 @@ impl StopReasonClassifier {
         self.config.default_context_size
     }
 
+    /// Completion from the marker or frontmatter, unless `prior` already
+    /// settled it: an unchanged session is completed, and a reopened one's
+    /// leftover marker and status are stale.
     fn check_completed(
         &self,
         session_path: &Path,
+        prior: Option<&PriorCompletion>,
         evidence: &mut Vec<String>,
     ) -> Option<StopReason> {
         use crate::monitor::frontmatter::parse_session;
 
+        match prior {
+            Some(PriorCompletion::Unchanged) => {
+                evidence.push("completed earlier, no substantive changes since".to_string());
+                return Some(StopReason::Completed);
+            }
+            Some(PriorCompletion::Reopened(changes)) => {
+                evidence.push(format!("session reopened: {}", changes.join(", ")));
+                return None;
+            }
+            None => {}
+        }
+
+        if encoding::read_text(session_path)
+            .is_ok_and(|decoded| has_completion_marker(&decoded.text))
+        {
+            evidence.push("completion marker found".to_string());
+            return Some(StopReason::Completed);
+        }
+
         match parse_session(session_path) {
             Ok(session) => {
                 if session.is_complete() {
@@ -527,31 +805,86 @@ impl StopReasonClassifier {
         None
     }
 
-    fn extract_retry_after(&self, content: &str) -> (Duration, RetryAfterSource) {
-        let header_pattern = Regex::new(r"(?i)retry-after[:\s]+(\d+)").ok();
-        if let Some(re) = header_pattern {
-            if let Some(caps) = re.captures(content) {
-                if let Some(secs) = Self::capture_seconds(&caps, 1) {
-                    return (Duration::from_secs(secs), RetryAfterSource::Header);
-                }
-            }
+    /// `Some(resets_at)` when `content` reports an exhausted quota window.
+    fn detect_quota_exhausted(
+        &self,
+        content: &str,
+        now: DateTime<Utc>,
+        evidence: &mut Vec<String>,
+    ) -> Option<Option<DateTime<Utc>>> {
+        let matched = self
+            .quota_patterns
+            .iter()
+            .find_map(|pattern| pattern.find(content))?;
+        evidence.push(format!("matched quota pattern: {}", matched.as_str()));
+        let resets_at = self.quota_reset_time(content, now);
+        if let Some(resets_at) = resets_at {
+            evidence.push(format!("quota resets at {}", resets_at.to_rfc3339()));
         }
+        Some(resets_at)
+    }
 
-        let json_pattern = Regex::new(r#"\"retry_after\"\s*:\s*\"?(\d+)\"?"#).ok();
-        if let Some(re) = json_pattern {
-            if let Some(caps) = re.captures(content) {
-                if let Some(secs) = Self::capture_seconds(&caps, 1) {
-                    return (Duration::from_secs(secs), RetryAfterSource::ResponseBody);
-                }
-            }
-        }
+    /// When the exhausted quota window resets, if `content` says.
+    ///
+    /// Absolute times (ISO 8601, HTTP dates, `*-ratelimit-*-reset` headers
+    /// with a timestamp or epoch seconds) win over relative phrasings such
+    /// as "resets in 3 hours", which are taken from `now`. Times in the past
+    /// are ignored.
+    fn quota_reset_time(&self, content: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let values = &self.values;
+        let absolute = values
+            .quota_reset_at
+            .captures(content)
+            .and_then(|caps| parse_reset_timestamp(caps.get(1)?.as_str()))
+            .or_else(|| {
+                values
+                    .quota_reset_date
+                    .captures(content)
+                    .and_then(|caps| parse_http_date(caps.get(1)?.as_str()))
+            })
+            .or_else(|| {
+                values
+                    .ratelimit_reset_header
+                    .captures_iter(content)
+                    .filter_map(|caps| parse_reset_header(caps.get(1)?.as_str()))
+                    .max()
+            });
+        let relative = || {
+            let caps = values.quota_reset_in.captures(content)?;
+            let amount = caps.get(1)?.as_str().parse::<i64>().ok()?;
+            let unit = caps.get(2)?.as_str().to_ascii_lowercase();
+            let delta = match unit.chars().next()? {
+                'd' => chrono::Duration::try_days(amount)?,
+                'h' => chrono::Duration::try_hours(amount)?,
+                _ => chrono::Duration::try_minutes(amount)?,
+            };
+            now.checked_add_signed(delta)
+        };
+        absolute
+            .or_else(relative)
+            .filter(|resets_at| *resets_at > now)
+    }
 
-        let text_pattern =
-            Regex::new(r"(?i)try\s+again\s+in\s+(\d+)\s*(?:seconds|second|sec|s)").ok();
-        if let Some(re) = text_pattern {
-            if let Some(caps) = re.captures(content) {
-                if let Some(secs) = Self::capture_seconds(&caps, 1) {
-                    return (Duration::from_secs(secs), RetryAfterSource::TextParsed);
+    fn extract_retry_after(&self, content: &str) -> (Duration, RetryAfterSource) {
+        let sources = [
+            (&self.values.retry_after_header, RetryAfterSource::Header),
+            (
+                &self.values.retry_after_json,
+                RetryAfterSource::ResponseBody,
+            ),
+            (&self.values.retry_after_text, RetryAfterSource::TextParsed),
+        ];
+        for (re, source) in sources {
+            if let Some(secs) = re
+                .captures(content)
+                .and_then(|caps| Self::capture_seconds(&caps, 1))
+            {
+                return (Duration::from_secs(secs), source);
+            }
+            if source == RetryAfterSource::Header {
+                if let Some(delay) = self.retry_after_date(content, Utc::now(), ClockSkew::shared())
+                {
+                    return (delay, source);
                 }
             }
         }
@@ -562,13 +895,39 @@ impl StopReasonClassifier {
         )
     }
 
+    /// Delay for a `Retry-After` given as an HTTP date.
+    ///
+    /// A `Date:` header in the same content updates `skew`, which then
+    /// corrects the conversion from the provider's clock to a local wait.
+    fn retry_after_date(
+        &self,
+        content: &str,
+        now: DateTime<Utc>,
+        skew: &ClockSkew,
+    ) -> Option<Duration> {
+        let retry_at = self
+            .values
+            .retry_after_date
+            .captures(content)
+            .and_then(|caps| parse_http_date(caps.get(1)?.as_str()))?;
+        if let Some(provider_now) = self
+            .values
+            .date_header
+            .captures(content)
+            .and_then(|caps| parse_http_date(caps.get(1)?.as_str()))
+        {
+            skew.observe(provider_now, now);
+        }
+        Some(skew.delay_until(retry_at, now))
+    }
+
     fn capture_seconds(caps: &regex::Captures<'_>, index: usize) -> Option<u64> {
         caps.get(index).and_then(|m| m.as_str().parse::<u64>().ok())
     }
 
     fn read_file_tail(&self, path: &Path, max_lines: usize) -> Result<String, std::io::Error> {
-        let content = fs::read_to_string(path)?;
-        let lines: Vec<&str> = content.lines().collect();
+        let decoded = encoding::read_text(path)?;
+        let lines: Vec<&str> = decoded.text.lines().collect();
         let start = lines.len().saturating_sub(max_lines);
         Ok(lines[start..].join("\n"))
     }
@@ -584,3 +943,88 @@ impl Default for StopReasonClassifier {
         Self::new().expect("Failed to create default classifier")
     }
 }
+
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    let normalized = match value.len().checked_sub(4) {
+        Some(split) if value[split..].eq_ignore_ascii_case(" utc") => {
+            format!("{} GMT", &value[..split])
+        }
+        _ => value.to_string(),
+    };
+    DateTime::parse_from_rfc2822(&normalized)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Parse an ISO 8601 reset time; a missing offset means UTC.
+fn parse_reset_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(&value.replacen(' ', "T", 1)) {
+        return Some(date.with_timezone(&Utc));
+    }
+    let lower = value.to_ascii_lowercase();
+    let naive = lower
+        .strip_suffix("utc")
+        .or_else(|| lower.strip_suffix("gmt"))
+        .or_else(|| lower.strip_suffix('z'))
+        .unwrap_or(&lower)
+        .trim()
+        .replacen('t', " ", 1);
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(&naive, format).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// Parse a `*-ratelimit-*-reset` header value given as a timestamp or epoch
+/// seconds. Relative values (`6m0s`, `30`) describe per-minute buckets, not
+/// quota windows, and are ignored.
+fn parse_reset_header(value: &str) -> Option<DateTime<Utc>> {
+    const MIN_EPOCH_SECS: i64 = 1_000_000_000;
+    match value.parse::<i64>() {
+        Ok(secs) if secs >= MIN_EPOCH_SECS => DateTime::from_timestamp(secs, 0),
+        Ok(_) => None,
+        Err(_) => parse_reset_timestamp(value),
+    }
+}
+
+/// Whether `content` has a [`COMPLETION_MARKER`] line outside fenced code blocks.
+///
+/// The marker must stand on its own line; spacing inside the comment and case
+/// are ignored.
+pub fn has_completion_marker(content: &str) -> bool {
+    let mut fence: Option<char> = None;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(c) = trimmed.chars().next().filter(|c| *c == '`' || *c == '~') {
+            if trimmed.chars().take_while(|ch| *ch == c).count() >= 3 {
+                match fence {
+                    Some(open) if open == c => fence = None,
+                    None => fence = Some(c),
+                    Some(_) => {}
+                }
+                continue;
+            }
+        }
+        if fence.is_none() && is_marker_line(trimmed) {
+            return true;
+        }
+    }
+    false
+}
+
+fn is_marker_line(line: &str) -> bool {
+    let Some(inner) = line
+        .strip_prefix("<!--")
+        .and_then(|rest| rest.strip_suffix("-->"))
+    else {
+        return false;
+    };
+    let normalized = inner
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_lowercase();
+    normalized == "palingenesis: complete" || normalized == "palingenesis:complete"
+}
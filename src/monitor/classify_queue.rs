@@ -0,0 +1,370 @@
+//! Bounded stop-classification queue.
+//!
+//! Classifying a stop reads the session tail and runs the pattern set, which
+//! is cheap for one file but adds up when a burst of stops lands at once.
+//! Requests wait here for one of `monitoring.classify_concurrency` workers in
+//! one of two lanes: the tracked session and sessions with an open incident
+//! go to the priority lane and are always taken first; everything else waits
+//! in the bulk lane, where requests older than `monitoring.classify_stale_secs`
+//! are dropped instead of classified. A file is queued at most once: a newer
+//! request for it replaces the queued one in place.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::config::schema::MonitoringConfig;
+use crate::telemetry::Metrics;
+
+/// Queue lane of a classification request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClassifyLane {
+    /// The tracked session and sessions with an open incident.
+    Priority,
+    /// Everything else; dropped once stale.
+    Bulk,
+}
+
+impl ClassifyLane {
+    pub const ALL: [Self; 2] = [Self::Priority, Self::Bulk];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Priority => "priority",
+            Self::Bulk => "bulk",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Worker count and stale threshold (`monitoring.classify_*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassifySettings {
+    pub workers: usize,
+    /// Bulk requests waiting longer than this are dropped.
+    pub stale_after: Option<Duration>,
+}
+
+impl ClassifySettings {
+    pub fn from_config(config: &MonitoringConfig) -> Self {
+        Self {
+            workers: config.classify_concurrency.max(1),
+            stale_after: (config.classify_stale_secs > 0)
+                .then(|| Duration::from_secs(config.classify_stale_secs)),
+        }
+    }
+}
+
+impl Default for ClassifySettings {
+    fn default() -> Self {
+        Self::from_config(&MonitoringConfig::default())
+    }
+}
+
+/// A classification taken off the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassifyRequest<T> {
+    pub path: PathBuf,
+    pub lane: ClassifyLane,
+    pub payload: T,
+    /// Time between the latest submission for the file and its dequeue.
+    pub waited: Duration,
+}
+
+/// What [`ClassificationQueue::submit`] did with a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submitted {
+    Queued,
+    /// The file was already queued; its request was replaced.
+    Superseded,
+}
+
+struct Pending<T> {
+    lane: ClassifyLane,
+    payload: T,
+    submitted_at: Instant,
+}
+
+struct Lanes<T> {
+    order: [VecDeque<PathBuf>; 2],
+    pending: HashMap<PathBuf, Pending<T>>,
+}
+
+struct Shared<T> {
+    settings: ClassifySettings,
+    lanes: Mutex<Lanes<T>>,
+    ready: Notify,
+    dropped: AtomicU64,
+}
+
+/// Classification requests waiting for a worker; cheap to clone.
+pub struct ClassificationQueue<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for ClassificationQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T: Send + 'static> ClassificationQueue<T> {
+    pub fn new(settings: ClassifySettings) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                settings,
+                lanes: Mutex::new(Lanes {
+                    order: [VecDeque::new(), VecDeque::new()],
+                    pending: HashMap::new(),
+                }),
+                ready: Notify::new(),
+                dropped: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn settings(&self) -> ClassifySettings {
+        self.shared.settings
+    }
+
+    /// Queue `payload` for `path`. A request already queued for the file is
+    /// replaced, keeping its place unless this one moves it up a lane.
+    pub fn submit(&self, path: PathBuf, lane: ClassifyLane, payload: T) -> Submitted {
+        let submitted = {
+            let mut lanes = self.shared.lanes.lock().unwrap();
+            let Lanes { order, pending } = &mut *lanes;
+            let submitted = match pending.get_mut(&path) {
+                Some(queued) => {
+                    if lane < queued.lane {
+                        order[queued.lane.index()].retain(|queued| queued != &path);
+                        order[lane.index()].push_back(path.clone());
+                        queued.lane = lane;
+                    }
+                    queued.payload = payload;
+                    queued.submitted_at = Instant::now();
+                    Submitted::Superseded
+                }
+                None => {
+                    order[lane.index()].push_back(path.clone());
+                    pending.insert(
+                        path,
+                        Pending {
+                            lane,
+                            payload,
+                            submitted_at: Instant::now(),
+                        },
+                    );
+                    Submitted::Queued
+                }
+            };
+            publish_depth(order);
+            submitted
+        };
+        self.shared.ready.notify_one();
+        submitted
+    }
+
+    /// The next request, priority lane first; stale bulk requests are
+    /// dropped on the way.
+    pub fn next(&self) -> Option<ClassifyRequest<T>> {
+        let mut lanes = self.shared.lanes.lock().unwrap();
+        let Lanes { order, pending } = &mut *lanes;
+        let request = loop {
+            let Some(path) = order.iter_mut().find_map(VecDeque::pop_front) else {
+                break None;
+            };
+            let Some(queued) = pending.remove(&path) else {
+                continue;
+            };
+            let waited = queued.submitted_at.elapsed();
+            let stale = queued.lane == ClassifyLane::Bulk
+                && self
+                    .shared
+                    .settings
+                    .stale_after
+                    .is_some_and(|limit| waited > limit);
+            if stale {
+                self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                if let Some(metrics) = Metrics::global() {
+                    metrics.record_classification_dropped();
+                }
+                debug!(path = %path.display(), waited_ms = waited.as_millis() as u64, "Dropped stale classification");
+                continue;
+            }
+            if let Some(metrics) = Metrics::global() {
+                metrics.record_classification_wait(queued.lane.as_str(), waited);
+            }
+            break Some(ClassifyRequest {
+                path,
+                lane: queued.lane,
+                payload: queued.payload,
+                waited,
+            });
+        };
+        publish_depth(order);
+        request
+    }
+
+    /// Requests waiting in `lane`.
+    pub fn depth(&self, lane: ClassifyLane) -> usize {
+        self.shared.lanes.lock().unwrap().order[lane.index()].len()
+    }
+
+    /// Stale requests dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Start the workers; each runs `classify` on a blocking thread for one
+    /// request at a time until `cancel` fires.
+    pub fn spawn<F>(&self, cancel: CancellationToken, classify: F)
+    where
+        F: Fn(ClassifyRequest<T>) + Send + Sync + 'static,
+    {
+        let classify = Arc::new(classify);
+        for _ in 0..self.shared.settings.workers {
+            let queue = self.clone();
+            let classify = Arc::clone(&classify);
+            let cancel = cancel.clone();
+            tokio::spawn(async move {
+                loop {
+                    if let Some(request) = queue.next() {
+                        let classify = Arc::clone(&classify);
+                        let _ = tokio::task::spawn_blocking(move || classify(request)).await;
+                        continue;
+                    }
+                    tokio::select! {
+                        _ = cancel.cancelled() => break,
+                        _ = queue.shared.ready.notified() => {}
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn publish_depth(order: &[VecDeque<PathBuf>; 2]) {
+    if let Some(metrics) = Metrics::global() {
+        for lane in ClassifyLane::ALL {
+            metrics.set_classification_queue_depth(lane.as_str(), order[lane.index()].len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(stale_after: Option<Duration>) -> ClassificationQueue<u32> {
+        ClassificationQueue::new(ClassifySettings {
+            workers: 1,
+            stale_after,
+        })
+    }
+
+    fn drain(queue: &ClassificationQueue<u32>) -> Vec<(String, u32)> {
+        std::iter::from_fn(|| queue.next())
+            .map(|request| (request.path.display().to_string(), request.payload))
+            .collect()
+    }
+
+    #[test]
+    fn priority_lane_goes_first_and_duplicates_collapse() {
+        let queue = queue(None);
+        for (index, file) in ["old-1.md", "old-2.md", "old-3.md"].iter().enumerate() {
+            queue.submit(PathBuf::from(file), ClassifyLane::Bulk, index as u32);
+        }
+        queue.submit(PathBuf::from("active.md"), ClassifyLane::Priority, 10);
+        assert_eq!(
+            queue.submit(PathBuf::from("old-2.md"), ClassifyLane::Bulk, 20),
+            Submitted::Superseded
+        );
+        assert_eq!(queue.depth(ClassifyLane::Bulk), 3);
+
+        assert_eq!(
+            drain(&queue),
+            vec![
+                ("active.md".to_string(), 10),
+                ("old-1.md".to_string(), 0),
+                ("old-2.md".to_string(), 20),
+                ("old-3.md".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn resubmitting_in_a_higher_lane_moves_the_request_up() {
+        let queue = queue(None);
+        queue.submit(PathBuf::from("a.md"), ClassifyLane::Bulk, 1);
+        queue.submit(PathBuf::from("b.md"), ClassifyLane::Bulk, 2);
+        queue.submit(PathBuf::from("b.md"), ClassifyLane::Priority, 3);
+        assert_eq!(queue.depth(ClassifyLane::Bulk), 1);
+
+        let first = queue.next().unwrap();
+        assert_eq!(first.path, PathBuf::from("b.md"));
+        assert_eq!(first.lane, ClassifyLane::Priority);
+        assert_eq!(first.payload, 3);
+    }
+
+    #[test]
+    fn stale_bulk_requests_are_dropped() {
+        let queue = queue(Some(Duration::from_millis(20)));
+        queue.submit(PathBuf::from("old.md"), ClassifyLane::Bulk, 1);
+        queue.submit(PathBuf::from("incident.md"), ClassifyLane::Priority, 2);
+        std::thread::sleep(Duration::from_millis(40));
+        queue.submit(PathBuf::from("fresh.md"), ClassifyLane::Bulk, 3);
+
+        assert_eq!(
+            drain(&queue),
+            vec![("incident.md".to_string(), 2), ("fresh.md".to_string(), 3)]
+        );
+        assert_eq!(queue.dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn workers_process_the_active_session_before_the_backlog() {
+        let queue = queue(None);
+        for index in 0..5 {
+            queue.submit(
+                PathBuf::from(format!("bulk-{index}.md")),
+                ClassifyLane::Bulk,
+                index,
+            );
+        }
+        queue.submit(PathBuf::from("active.md"), ClassifyLane::Priority, 99);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        queue.spawn(cancel.clone(), move |request| {
+            let _ = tx.send(request.payload);
+        });
+
+        let mut order = Vec::new();
+        while order.len() < 6 {
+            let payload = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("classified")
+                .unwrap();
+            order.push(payload);
+        }
+        assert_eq!(order, vec![99, 0, 1, 2, 3, 4]);
+
+        queue.submit(PathBuf::from("later.md"), ClassifyLane::Bulk, 7);
+        let later = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("woken by the submission");
+        assert_eq!(later, Some(7));
+        cancel.cancel();
+    }
+}
@@ -0,0 +1,215 @@
+//! Clock skew between the daemon and AI providers.
+//!
+//! A `Retry-After` given as an HTTP date is an absolute time on the
+//! provider's clock. When the same response carries a `Date:` header, the
+//! difference between that and the local clock is the skew, and waiting until
+//! the Retry-After date on an unsynchronized local clock would resume too
+//! early (or far too late). Each observation updates a smoothed estimate that
+//! converts absolute retry times into durations; a sleep/wake cycle resets
+//! it, since the host clock is often corrected on wake.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::telemetry::Metrics;
+
+/// Default skew (seconds) above which a warning is sent.
+pub const DEFAULT_WARN_THRESHOLD_SECS: u64 = 60;
+/// Offsets larger than this are treated as a bogus `Date:` header.
+const MAX_PLAUSIBLE_SKEW_SECS: f64 = 24.0 * 60.0 * 60.0;
+/// Weight of a new observation in the moving average.
+const SMOOTHING: f64 = 0.3;
+
+#[derive(Debug, Default)]
+struct Estimate {
+    /// Provider clock minus local clock, in seconds.
+    skew_secs: Option<f64>,
+    samples: u64,
+    warned: bool,
+}
+
+/// Smoothed provider-vs-local clock offset.
+#[derive(Debug)]
+pub struct ClockSkew {
+    estimate: Mutex<Estimate>,
+    warn_threshold: Mutex<Duration>,
+}
+
+impl ClockSkew {
+    pub fn new() -> Self {
+        Self {
+            estimate: Mutex::new(Estimate::default()),
+            warn_threshold: Mutex::new(Duration::from_secs(DEFAULT_WARN_THRESHOLD_SECS)),
+        }
+    }
+
+    /// Process-wide estimate fed by the classifier and shown in `status`.
+    pub fn shared() -> &'static Self {
+        static SHARED: OnceLock<ClockSkew> = OnceLock::new();
+        SHARED.get_or_init(Self::new)
+    }
+
+    /// Set the skew above which [`Self::take_warning`] fires.
+    pub fn set_warn_threshold(&self, threshold: Duration) {
+        if let Ok(mut current) = self.warn_threshold.lock() {
+            *current = threshold;
+        }
+    }
+
+    /// Record a provider `Date:` seen at local time `local`.
+    ///
+    /// Returns the updated estimate, or `None` when the offset is implausible
+    /// and was ignored.
+    pub fn observe(&self, provider: DateTime<Utc>, local: DateTime<Utc>) -> Option<f64> {
+        let offset = (provider - local).num_milliseconds() as f64 / 1000.0;
+        if !offset.is_finite() || offset.abs() > MAX_PLAUSIBLE_SKEW_SECS {
+            debug!(
+                offset_secs = offset,
+                "Ignoring implausible provider Date header"
+            );
+            return None;
+        }
+        let mut estimate = self.estimate.lock().ok()?;
+        let smoothed = match estimate.skew_secs {
+            Some(previous) => previous + SMOOTHING * (offset - previous),
+            None => offset,
+        };
+        estimate.skew_secs = Some(smoothed);
+        estimate.samples += 1;
+        drop(estimate);
+        if let Some(metrics) = Metrics::global() {
+            metrics.set_clock_skew(smoothed);
+        }
+        Some(smoothed)
+    }
+
+    /// Current estimate in seconds (positive: provider clock is ahead).
+    pub fn estimate(&self) -> Option<f64> {
+        self.estimate.lock().ok()?.skew_secs
+    }
+
+    /// Forget the estimate, e.g. after the host slept.
+    pub fn reset(&self) {
+        if let Ok(mut estimate) = self.estimate.lock() {
+            estimate.skew_secs = None;
+            estimate.samples = 0;
+        }
+        if let Some(metrics) = Metrics::global() {
+            metrics.set_clock_skew(0.0);
+        }
+    }
+
+    /// Wait until the provider's `retry_at`, measured from local time `now`.
+    pub fn delay_until(&self, retry_at: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+        let skew_ms = self
+            .estimate()
+            .map(|secs| (secs * 1000.0).round() as i64)
+            .unwrap_or(0);
+        let provider_now = now + chrono::Duration::milliseconds(skew_ms);
+        (retry_at - provider_now).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    /// The estimate, the first time it exceeds the warning threshold.
+    ///
+    /// Returns `None` on every later call, so the warning is sent once per
+    /// daemon run.
+    pub fn take_warning(&self) -> Option<f64> {
+        let threshold = self.warn_threshold.lock().ok()?.as_secs_f64();
+        let mut estimate = self.estimate.lock().ok()?;
+        let skew = estimate.skew_secs?;
+        if estimate.warned || skew.abs() <= threshold {
+            return None;
+        }
+        estimate.warned = true;
+        warn!(
+            skew_secs = skew,
+            "Local clock differs from the provider's; check NTP synchronization"
+        );
+        Some(skew)
+    }
+}
+
+impl Default for ClockSkew {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(1_760_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn provider_ahead_shortens_the_wait() {
+        let skew = ClockSkew::new();
+
+        assert_eq!(skew.observe(at(120), at(0)), Some(120.0));
+
+        // Provider says retry at its 180; its clock reads 120 now.
+        assert_eq!(skew.delay_until(at(180), at(0)), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn provider_behind_lengthens_the_wait() {
+        let skew = ClockSkew::new();
+
+        assert_eq!(skew.observe(at(-90), at(0)), Some(-90.0));
+
+        assert_eq!(skew.delay_until(at(-30), at(0)), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn absurd_offsets_are_ignored() {
+        let skew = ClockSkew::new();
+        skew.observe(at(5), at(0));
+
+        assert_eq!(skew.observe(at(400 * 86_400), at(0)), None);
+        assert_eq!(skew.observe(at(-3 * 86_400), at(0)), None);
+        assert_eq!(skew.estimate(), Some(5.0));
+    }
+
+    #[test]
+    fn estimate_is_smoothed_and_reset() {
+        let skew = ClockSkew::new();
+        skew.observe(at(100), at(0));
+        let smoothed = skew.observe(at(0), at(0)).unwrap();
+
+        assert!(smoothed > 0.0 && smoothed < 100.0);
+
+        skew.reset();
+        assert_eq!(skew.estimate(), None);
+        assert_eq!(skew.delay_until(at(30), at(0)), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn past_retry_time_means_no_wait() {
+        let skew = ClockSkew::new();
+
+        assert_eq!(skew.delay_until(at(-10), at(0)), Duration::ZERO);
+    }
+
+    #[test]
+    fn warning_fires_once_above_threshold() {
+        let skew = ClockSkew::new();
+        skew.observe(at(30), at(0));
+        assert_eq!(skew.take_warning(), None);
+
+        skew.reset();
+        skew.observe(at(-300), at(0));
+        assert_eq!(skew.take_warning(), Some(-300.0));
+        assert_eq!(skew.take_warning(), None);
+
+        skew.set_warn_threshold(Duration::from_secs(10));
+        skew.reset();
+        skew.observe(at(30), at(0));
+        assert_eq!(skew.take_warning(), None);
+    }
+}
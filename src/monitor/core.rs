@@ -1,19 +1,37 @@
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, SystemTime};
 
+use chrono::Utc;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
-use crate::monitor::classifier::{ClassifierConfig, ClassifierError, StopReasonClassifier};
+use crate::config::schema::MonitoringConfig;
+use crate::monitor::classifier::{
+    ClassificationResult, ClassifierConfig, ClassifierError, StopReason, StopReasonClassifier,
+};
+use crate::monitor::classify_queue::{ClassificationQueue, ClassifyLane, ClassifySettings};
+use crate::monitor::debounce::DebounceSettings;
+use crate::monitor::deletion::DeletionHandler;
 use crate::monitor::events::{
     MonitorEvent, MonitorEventReceiver, MonitorEventSender, WatchEvent, WatchEventReceiver,
 };
+use crate::monitor::filter::{SharedWatchFilter, WatchFilter};
 use crate::monitor::frontmatter::SessionParser;
-use crate::monitor::process::{ProcessError, ProcessEvent, ProcessEventReceiver, ProcessMonitor};
+use crate::monitor::incident::IncidentStore;
+use crate::monitor::process::{
+    ProcessError, ProcessEvent, ProcessEventReceiver, ProcessInfo, ProcessMonitor,
+};
+use crate::monitor::reopen::{self, PriorCompletion};
+use crate::monitor::scan::DirCache;
 use crate::monitor::session::Session;
-use crate::monitor::watcher::{SessionWatcher, WatcherError};
+use crate::monitor::watcher::{DEFAULT_POLL_INTERVAL_SECS, SessionWatcher, WatcherError};
+use crate::state::StateBackend;
+use crate::state::orphans;
 use crate::telemetry::Metrics;
 
 const DEFAULT_CHANNEL_CAPACITY: usize = 100;
@@ -26,6 +44,41 @@ pub struct MonitorConfig {
     pub classifier_config: ClassifierConfig,
     pub enable_process_detection: bool,
     pub health_check_interval: Duration,
+    /// Follow symlinks inside `session_dir` when validating event paths.
+    pub follow_symlinks: bool,
+    /// Which watcher events are forwarded (`monitoring.include_extensions`,
+    /// `monitoring.ignore_patterns`).
+    pub watch_filter: SharedWatchFilter,
+    /// Recursively watch only subdirectories active within this window
+    /// (`monitoring.active_window_hours`).
+    pub active_window: Option<Duration>,
+    /// Poll interval if the watch limit forces the polling backend.
+    pub poll_interval: Duration,
+    /// Debounce window and delay warning (`monitoring.debounce*`).
+    pub debounce: DebounceSettings,
+    /// Stop classification workers (`monitoring.classify_*`).
+    pub classify: ClassifySettings,
+}
+
+impl MonitorConfig {
+    /// Monitor settings from `[monitoring]`.
+    pub fn from_monitoring(config: &MonitoringConfig) -> Self {
+        Self {
+            session_dir: config.session_dir.clone(),
+            follow_symlinks: config.follow_symlinks,
+            watch_filter: SharedWatchFilter::new(WatchFilter::from_config(config)),
+            active_window: (config.active_window_hours > 0)
+                .then(|| Duration::from_secs(config.active_window_hours * 3600)),
+            poll_interval: Duration::from_secs(
+                config
+                    .poll_interval_secs
+                    .unwrap_or(DEFAULT_POLL_INTERVAL_SECS),
+            ),
+            debounce: DebounceSettings::from_config(config),
+            classify: ClassifySettings::from_config(config),
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for MonitorConfig {
@@ -39,17 +92,49 @@ impl Default for MonitorConfig {
             classifier_config: ClassifierConfig::default(),
             enable_process_detection: true,
             health_check_interval: Duration::from_secs(DEFAULT_HEALTH_CHECK_INTERVAL_SECS),
+            follow_symlinks: false,
+            watch_filter: SharedWatchFilter::default(),
+            active_window: None,
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+            debounce: DebounceSettings::default(),
+            classify: ClassifySettings::default(),
         }
     }
 }
 
 pub struct Monitor {
     config: MonitorConfig,
-    classifier: StopReasonClassifier,
+    classifier: Arc<StopReasonClassifier>,
     parser: SessionParser,
     current_session: Option<Session>,
+    state_store: Option<Arc<dyn StateBackend>>,
+    deletion: Option<Arc<DeletionHandler>>,
+    incidents: Option<IncidentStore>,
+    dir_cache: Option<Arc<DirCache>>,
+    /// Sessions whose steps were seen since the monitor started; earlier
+    /// completions are recorded without a time.
+    timeline_seen: HashSet<PathBuf>,
+    /// Stops waiting to be classified; set once the monitor runs.
+    stops: Option<ClassificationQueue<PendingStop>>,
     errors_count: u64,
-    dropped_events: u64,
+    dropped_events: Arc<AtomicU64>,
+}
+
+/// A process stop waiting in the classification queue.
+struct PendingStop {
+    session: Session,
+    info: ProcessInfo,
+    exit_code: Option<i32>,
+}
+
+/// Classifies queued stops off the event loop and reports them.
+struct StopClassifier {
+    classifier: Arc<StopReasonClassifier>,
+    state_store: Option<Arc<dyn StateBackend>>,
+    incidents: Option<IncidentStore>,
+    reopen_growth_bytes: u64,
+    tx: MonitorEventSender,
+    dropped_events: Arc<AtomicU64>,
 }
 
 impl Monitor {
@@ -58,22 +143,62 @@ impl Monitor {
     }
 
     pub fn with_config(config: MonitorConfig) -> Result<Self, MonitorError> {
-        let classifier = StopReasonClassifier::with_config(config.classifier_config.clone())?;
+        let classifier = Arc::new(StopReasonClassifier::with_config(
+            config.classifier_config.clone(),
+        )?);
+        let parser =
+            SessionParser::new().with_root(config.session_dir.clone(), config.follow_symlinks);
         Ok(Self {
             config,
             classifier,
-            parser: SessionParser::new(),
+            parser,
             current_session: None,
+            state_store: None,
+            deletion: None,
+            incidents: None,
+            dir_cache: None,
+            timeline_seen: HashSet::new(),
+            stops: None,
             errors_count: 0,
-            dropped_events: 0,
+            dropped_events: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// Skip sessions recorded as orphaned in this state store when tracking
+    /// the current session, and record step completions and completed
+    /// determinations in it.
+    pub fn with_state_store<T: StateBackend + 'static>(mut self, store: T) -> Self {
+        self.state_store = Some(Arc::new(store));
+        self
+    }
+
+    /// React to the current session's file being deleted.
+    pub fn with_deletion_handler(mut self, handler: DeletionHandler) -> Self {
+        self.deletion = Some(Arc::new(handler));
+        self
+    }
+
+    /// Save the transcript tail behind each classified stop.
+    pub fn with_incident_store(mut self, store: IncidentStore) -> Self {
+        self.incidents = Some(store);
+        self
+    }
+
+    /// Keep `cache` in step with watch events so session listings can reuse it.
+    pub fn with_dir_cache(mut self, cache: Arc<DirCache>) -> Self {
+        self.dir_cache = Some(cache);
+        self
+    }
+
     pub async fn run(
         self,
         cancel: CancellationToken,
     ) -> Result<MonitorEventReceiver, MonitorError> {
-        let watcher = SessionWatcher::with_path(self.config.session_dir.clone());
+        let watcher = SessionWatcher::with_path(self.config.session_dir.clone())
+            .with_filter(self.config.watch_filter.clone())
+            .with_active_window(self.config.active_window)
+            .with_poll_interval(self.config.poll_interval)
+            .with_debounce_settings(self.config.debounce);
         let watcher_rx = watcher.run(cancel.clone()).await?;
 
         let process_rx = if self.config.enable_process_detection {
@@ -96,6 +221,20 @@ impl Monitor {
     ) -> MonitorEventReceiver {
         let (tx, rx) = mpsc::channel(self.config.channel_capacity);
 
+        let stops = ClassificationQueue::new(self.config.classify);
+        let classifier = StopClassifier {
+            classifier: Arc::clone(&self.classifier),
+            state_store: self.state_store.clone(),
+            incidents: self.incidents.clone(),
+            reopen_growth_bytes: self.config.classifier_config.reopen_growth_bytes,
+            tx: tx.clone(),
+            dropped_events: Arc::clone(&self.dropped_events),
+        };
+        stops.spawn(cancel.clone(), move |request| {
+            classifier.classify(request.payload)
+        });
+        self.stops = Some(stops);
+
         tokio::spawn(async move {
             self.event_loop(tx, watcher_rx, process_rx, cancel).await;
         });
@@ -144,19 +283,35 @@ impl Monitor {
     }
 
     async fn handle_watch_event(&mut self, event: WatchEvent, tx: &MonitorEventSender) {
-        if let WatchEvent::FileDeleted(path) = &event {
-            if self
-                .current_session
-                .as_ref()
-                .is_some_and(|session| session.path == *path)
+        if let Some(cache) = &self.dir_cache {
+            cache.handle_watch_event(&event);
+        }
+        match &event {
+            WatchEvent::FileDeleted(path)
+                if self
+                    .current_session
+                    .as_ref()
+                    .is_some_and(|session| session.path == *path) =>
             {
                 self.current_session = None;
+                self.spawn_deletion_handler(path.clone(), tx);
+            }
+            WatchEvent::FileCreated(path) | WatchEvent::FileModified(path) => {
+                if let Some(handler) = &self.deletion {
+                    handler.note_reappeared(path);
+                }
             }
+            _ => {}
         }
 
         if let Some(event) = self.parser.handle_event(event) {
             if let MonitorEvent::SessionChanged { session, .. } = &event {
-                self.current_session = Some(session.clone());
+                if self.is_orphaned(&session.path) {
+                    debug!(path = %session.path.display(), "Ignoring orphaned session for current-session tracking");
+                } else {
+                    self.record_step_timeline(session);
+                    self.current_session = Some(session.clone());
+                }
             }
 
             if let MonitorEvent::Error {
@@ -191,51 +346,227 @@ impl Monitor {
                     )
                     .await;
 
-                let classification = if let Some(session) = &self.current_session {
-                    self.classifier.classify(&session.path, exit_code)
-                } else {
-                    self.classifier.classify_content("", exit_code)
-                };
-
-                if let Some(metrics) = Metrics::global() {
-                    let reason = classification
-                        .reason
-                        .metrics_reason_label()
-                        .unwrap_or("unknown");
-                    if let Some(latency) = estimate_detection_latency(self.current_session.as_ref())
-                    {
-                        metrics.record_detection(latency, reason);
+                match self.current_session.clone() {
+                    Some(session) => self.submit_stop(session, info, exit_code),
+                    None => {
+                        let classification = self.classifier.classify_content("", exit_code);
+                        let _ = self
+                            .try_send(
+                                tx,
+                                MonitorEvent::SessionStopped {
+                                    session: None,
+                                    reason: classification.reason.clone(),
+                                    classification,
+                                    process_info: Some(info),
+                                    incident: None,
+                                },
+                            )
+                            .await;
                     }
                 }
+            }
+        }
+    }
 
-                let _ = self
-                    .try_send(
-                        tx,
-                        MonitorEvent::SessionStopped {
-                            session: self.current_session.clone(),
-                            reason: classification.reason.clone(),
-                            classification,
-                            process_info: Some(info),
-                        },
-                    )
+    /// Queue a stop for classification; the tracked session and sessions
+    /// with an open incident skip ahead of other files.
+    fn submit_stop(&self, session: Session, info: ProcessInfo, exit_code: Option<i32>) {
+        let Some(stops) = &self.stops else {
+            return;
+        };
+        let tracked = self
+            .current_session
+            .as_ref()
+            .is_some_and(|current| current.path == session.path);
+        let lane = if tracked || self.has_open_incident(&session.path) {
+            ClassifyLane::Priority
+        } else {
+            ClassifyLane::Bulk
+        };
+        stops.submit(
+            session.path.clone(),
+            lane,
+            PendingStop {
+                session,
+                info,
+                exit_code,
+            },
+        );
+    }
+
+    fn has_open_incident(&self, path: &std::path::Path) -> bool {
+        self.state_store.as_deref().is_some_and(|store| {
+            store
+                .load()
+                .escalations
+                .iter()
+                .any(|record| record.session_path == path && record.resolved_at.is_none())
+        })
+    }
+
+    /// Timestamp steps that completed since the last parse. On the first parse
+    /// of a session after startup, steps missing from its timeline completed
+    /// while the daemon was offline.
+    fn record_step_timeline(&mut self, session: &Session) {
+        let Some(store) = self.state_store.as_deref() else {
+            return;
+        };
+        let completed = session.completed_step_numbers();
+        let at = if self.timeline_seen.insert(session.path.clone()) {
+            None
+        } else {
+            Some(Utc::now())
+        };
+        let mut state = store.load();
+        if state.record_completed_steps(&session.path, &completed, at) == 0 {
+            return;
+        }
+        if let Err(err) = store.save(&state) {
+            warn!(path = %session.path.display(), error = %err, "Failed to record step timeline");
+        }
+    }
+
+    /// Resolve a deletion off the event loop so the grace period does not block it.
+    fn spawn_deletion_handler(&self, path: PathBuf, tx: &MonitorEventSender) {
+        let Some(handler) = self.deletion.clone() else {
+            return;
+        };
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Some(outcome) = handler.handle(&path).await {
+                let _ = tx
+                    .send(MonitorEvent::SessionDeleted { path, outcome })
                     .await;
             }
-        }
+        });
+    }
+
+    fn is_orphaned(&self, path: &std::path::Path) -> bool {
+        self.state_store
+            .as_deref()
+            .is_some_and(|store| orphans::skip_for_selection(store, path))
     }
 
     async fn try_send(&mut self, tx: &MonitorEventSender, event: MonitorEvent) -> bool {
-        match tx.try_send(event) {
-            Ok(_) => true,
-            Err(mpsc::error::TrySendError::Full(event)) => {
-                self.dropped_events += 1;
-                warn!(dropped_events = self.dropped_events, event = ?event, "Monitor event channel full, dropping event");
-                false
+        send_event(tx, event, &self.dropped_events)
+    }
+}
+
+impl StopClassifier {
+    /// Classify a queued stop and report it, along with a reopening of a
+    /// session that was classified as completed before.
+    fn classify(&self, stop: PendingStop) {
+        let PendingStop {
+            session,
+            info,
+            exit_code,
+        } = stop;
+        let prior = self.prior_completion(&session);
+        if let Some(PriorCompletion::Reopened(changes)) = &prior {
+            info!(path = %session.path.display(), changes = ?changes, "Session reopened");
+            self.send(MonitorEvent::SessionReopened {
+                path: session.path.clone(),
+                changes: changes.clone(),
+            });
+        }
+        let (classification, tail) =
+            self.classifier
+                .classify_with_prior(&session.path, exit_code, prior.as_ref());
+        if classification.reason == StopReason::Completed
+            && prior != Some(PriorCompletion::Unchanged)
+        {
+            self.record_completion(&session);
+        }
+        let incident = tail.and_then(|tail| {
+            self.record_incident(&session.path, &tail, &classification, exit_code)
+        });
+
+        if let Some(metrics) = Metrics::global() {
+            let reason = classification
+                .reason
+                .metrics_reason_label()
+                .unwrap_or("unknown");
+            if let Some(latency) = estimate_detection_latency(Some(&session)) {
+                metrics.record_detection(latency, reason);
             }
-            Err(mpsc::error::TrySendError::Closed(_)) => {
-                debug!("Monitor event channel closed");
-                false
+        }
+
+        self.send(MonitorEvent::SessionStopped {
+            session: Some(session),
+            reason: classification.reason.clone(),
+            classification,
+            process_info: Some(info),
+            incident,
+        });
+    }
+
+    fn send(&self, event: MonitorEvent) -> bool {
+        send_event(&self.tx, event, &self.dropped_events)
+    }
+
+    /// Save the analyzed tail; a failed write is logged and the stop still reported.
+    fn record_incident(
+        &self,
+        session_path: &std::path::Path,
+        tail: &str,
+        classification: &ClassificationResult,
+        exit_code: Option<i32>,
+    ) -> Option<PathBuf> {
+        let store = self.incidents.as_ref()?;
+        match store.record(session_path, tail, classification, exit_code) {
+            Ok(path) => path,
+            Err(err) => {
+                warn!(error = %err, "Failed to save stop-time transcript snapshot");
+                None
+            }
+        }
+    }
+
+    /// Compare the session with its stored completed determination, if any.
+    /// A reopened session's determination is cleared from the state store.
+    fn prior_completion(&self, session: &Session) -> Option<PriorCompletion> {
+        let store = self.state_store.as_deref()?;
+        let mut state = store.load();
+        let recorded = state.completed_session(&session.path)?;
+        let current = reopen::snapshot(session, Utc::now())?;
+        let prior = PriorCompletion::assess(recorded, &current, self.reopen_growth_bytes);
+        if matches!(prior, PriorCompletion::Reopened(_)) {
+            state.remove_completed_session(&session.path);
+            if let Err(err) = store.save(&state) {
+                warn!(path = %session.path.display(), error = %err, "Failed to clear completed determination");
             }
         }
+        Some(prior)
+    }
+
+    /// Remember what the session looked like when it was classified as completed.
+    fn record_completion(&self, session: &Session) {
+        let Some(store) = self.state_store.as_deref() else {
+            return;
+        };
+        let Some(completed) = reopen::snapshot(session, Utc::now()) else {
+            return;
+        };
+        let mut state = store.load();
+        state.record_completed_session(completed);
+        if let Err(err) = store.save(&state) {
+            warn!(path = %session.path.display(), error = %err, "Failed to record completed determination");
+        }
+    }
+}
+
+fn send_event(tx: &MonitorEventSender, event: MonitorEvent, dropped_events: &AtomicU64) -> bool {
+    match tx.try_send(event) {
+        Ok(_) => true,
+        Err(mpsc::error::TrySendError::Full(event)) => {
+            let dropped_events = dropped_events.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!(dropped_events, event = ?event, "Monitor event channel full, dropping event");
+            false
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            debug!("Monitor event channel closed");
+            false
+        }
     }
 }
 
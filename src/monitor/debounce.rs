@@ -0,0 +1,234 @@
+//! Debounce window of the session watcher.
+//!
+//! File events wait in the watcher's buffer for up to one window before they
+//! are forwarded, so the window is a floor on detection latency. A fixed
+//! window is either too short for bursty writers (one classification per
+//! write) or too long for quiet ones (slow detection). In adaptive mode the
+//! window follows the recent event rate: it doubles while events arrive in
+//! bursts and halves while they are sparse, staying within the configured
+//! bounds. The controller is a plain value so it can be driven by synthetic
+//! rates in tests.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::config::schema::{DebounceMode, MonitoringConfig};
+use crate::telemetry::Metrics;
+
+/// Mean rate (events/second) above which the window grows.
+pub const BURST_RATE: f64 = 20.0;
+/// Mean rate (events/second) below which the window shrinks.
+pub const QUIET_RATE: f64 = 2.0;
+/// Rate samples averaged per adjustment.
+const RATE_SAMPLES: usize = 5;
+/// How often the adaptive window is re-evaluated.
+pub const ADJUST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Debounce behavior of one watcher (`monitoring.debounce*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebounceSettings {
+    pub mode: DebounceMode,
+    /// Fixed window, or the starting window in adaptive mode.
+    pub window: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// Warn when an event was held back longer than this.
+    pub warn_after: Option<Duration>,
+}
+
+impl DebounceSettings {
+    /// A fixed window with the default warning threshold.
+    pub fn fixed(window: Duration) -> Self {
+        Self {
+            mode: DebounceMode::Fixed,
+            window,
+            min: window,
+            max: window,
+            warn_after: Some(Duration::from_millis(2000)),
+        }
+    }
+
+    pub fn from_config(config: &MonitoringConfig) -> Self {
+        let window = Duration::from_millis(config.debounce_ms);
+        let (min, max) = match config.debounce {
+            DebounceMode::Fixed => (window, window),
+            DebounceMode::Adaptive => (
+                Duration::from_millis(config.debounce_min_ms),
+                Duration::from_millis(config.debounce_max_ms.max(config.debounce_min_ms)),
+            ),
+        };
+        Self {
+            mode: config.debounce,
+            window: window.clamp(min, max),
+            min,
+            max,
+            warn_after: (config.debounce_warn_ms > 0)
+                .then(|| Duration::from_millis(config.debounce_warn_ms)),
+        }
+    }
+
+    /// Quiet period of the notify debouncer underneath the buffer.
+    ///
+    /// It cannot change without recreating the watch, so adaptive mode pins
+    /// it to the minimum and moves only the buffer flush interval.
+    pub fn notify_timeout(&self) -> Duration {
+        match self.mode {
+            DebounceMode::Fixed => self.window,
+            DebounceMode::Adaptive => self.min,
+        }
+    }
+
+    /// Controller for adaptive mode.
+    pub fn controller(&self) -> Option<AdaptiveDebounce> {
+        (self.mode == DebounceMode::Adaptive).then(|| AdaptiveDebounce::new(self.min, self.max))
+    }
+}
+
+impl Default for DebounceSettings {
+    fn default() -> Self {
+        Self::fixed(Duration::from_millis(100))
+    }
+}
+
+/// Chooses the next window from recent event rates.
+#[derive(Debug, Clone)]
+pub struct AdaptiveDebounce {
+    min: Duration,
+    max: Duration,
+    rates: VecDeque<f64>,
+}
+
+impl AdaptiveDebounce {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            rates: VecDeque::with_capacity(RATE_SAMPLES),
+        }
+    }
+
+    /// Record `events` seen over `elapsed`.
+    pub fn observe(&mut self, events: usize, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return;
+        }
+        if self.rates.len() == RATE_SAMPLES {
+            self.rates.pop_front();
+        }
+        self.rates.push_back(events as f64 / secs);
+    }
+
+    /// Mean of the recorded rates (events/second).
+    pub fn rate(&self) -> f64 {
+        if self.rates.is_empty() {
+            return 0.0;
+        }
+        self.rates.iter().sum::<f64>() / self.rates.len() as f64
+    }
+
+    /// Window to use after `current`, given the recorded rates.
+    pub fn next_window(&self, current: Duration) -> Duration {
+        let rate = self.rate();
+        let next = if rate >= BURST_RATE {
+            current.saturating_mul(2)
+        } else if rate <= QUIET_RATE {
+            current / 2
+        } else {
+            current
+        };
+        next.clamp(self.min, self.max)
+    }
+}
+
+fn window_ms() -> &'static AtomicU64 {
+    static WINDOW_MS: AtomicU64 = AtomicU64::new(0);
+    &WINDOW_MS
+}
+
+/// Record the window the running watcher uses, for `status` and metrics.
+pub fn publish_window(window: Duration) {
+    window_ms().store(window.as_millis() as u64, Ordering::Relaxed);
+    if let Some(metrics) = Metrics::global() {
+        metrics.set_debounce_window(window);
+    }
+}
+
+/// Window of the running watcher, if one has started.
+pub fn effective_window() -> Option<Duration> {
+    match window_ms().load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    fn drive(controller: &mut AdaptiveDebounce, start: Duration, rates: &[usize]) -> Duration {
+        rates.iter().fold(start, |window, events| {
+            controller.observe(*events, ADJUST_INTERVAL);
+            controller.next_window(window)
+        })
+    }
+
+    #[test]
+    fn burst_grows_window_up_to_max() {
+        let mut controller = AdaptiveDebounce::new(ms(50), ms(2000));
+        let window = drive(&mut controller, ms(100), &[40; 3]);
+        assert_eq!(window, ms(800));
+
+        let window = drive(&mut controller, window, &[40; 10]);
+        assert_eq!(window, ms(2000));
+    }
+
+    #[test]
+    fn quiet_period_shrinks_window_down_to_min() {
+        let mut controller = AdaptiveDebounce::new(ms(50), ms(2000));
+        let window = drive(&mut controller, ms(1600), &[1; 2]);
+        assert_eq!(window, ms(400));
+
+        let window = drive(&mut controller, window, &[0; 10]);
+        assert_eq!(window, ms(50));
+    }
+
+    #[test]
+    fn moderate_rate_holds_window() {
+        let mut controller = AdaptiveDebounce::new(ms(50), ms(2000));
+        assert_eq!(drive(&mut controller, ms(300), &[8; 6]), ms(300));
+    }
+
+    #[test]
+    fn single_spike_is_averaged_out() {
+        let mut controller = AdaptiveDebounce::new(ms(50), ms(2000));
+        let window = drive(&mut controller, ms(200), &[5, 5, 5, 5]);
+        assert_eq!(window, ms(200));
+
+        controller.observe(60, ADJUST_INTERVAL);
+        assert_eq!(controller.rate(), 16.0);
+        assert_eq!(controller.next_window(window), ms(200));
+    }
+
+    #[test]
+    fn settings_from_config() {
+        let mut config = MonitoringConfig::default();
+        let fixed = DebounceSettings::from_config(&config);
+        assert_eq!(fixed.notify_timeout(), ms(100));
+        assert!(fixed.controller().is_none());
+
+        config.debounce = DebounceMode::Adaptive;
+        config.debounce_ms = 5000;
+        config.debounce_warn_ms = 0;
+        let adaptive = DebounceSettings::from_config(&config);
+        assert_eq!(adaptive.window, ms(2000));
+        assert_eq!(adaptive.notify_timeout(), ms(50));
+        assert_eq!(adaptive.warn_after, None);
+        assert!(adaptive.controller().is_some());
+    }
+}
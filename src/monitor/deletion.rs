@@ -0,0 +1,363 @@
+//! Handling for the current session's file being deleted.
+//!
+//! A deletion is only acted on once it has outlasted a short grace period, so
+//! editors that save by deleting and recreating the file are ignored. After
+//! that the session is marked [`SessionStatus::Missing`] and a Critical
+//! notification is published. With `resume.restore_deleted_from_backup` the
+//! newest backup is copied back into place; otherwise the session is held in
+//! [`SessionStatus::NeedsAttention`] until the file reappears or the operator
+//! runs `palingenesis attention clear`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::{debug, info, warn};
+
+use crate::config::schema::Config;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::backup::{BackupConfig, SessionBackup};
+use crate::state::{AuditLogger, SessionStatus, StateBackend};
+
+/// How long a deleted file may take to reappear before it counts as deleted.
+pub const DEFAULT_DELETION_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// What happened after the current session's file was deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeletionOutcome {
+    /// The file came back within the grace period.
+    Recreated,
+    /// The file was restored from a backup.
+    Restored { backup: PathBuf },
+    /// Nothing brought the file back; waiting for the operator.
+    NeedsAttention,
+}
+
+pub struct DeletionHandler {
+    grace_period: Duration,
+    restore_from_backup: bool,
+    backup: SessionBackup,
+    store: Arc<dyn StateBackend>,
+    events: Option<EventBroadcaster>,
+    audit: Option<AuditLogger>,
+    pending: Mutex<HashSet<PathBuf>>,
+    held: Mutex<HashSet<PathBuf>>,
+}
+
+impl DeletionHandler {
+    pub fn new<T: StateBackend + 'static>(store: T) -> Self {
+        Self {
+            grace_period: DEFAULT_DELETION_GRACE_PERIOD,
+            restore_from_backup: false,
+            backup: SessionBackup::default(),
+            store: Arc::new(store),
+            events: None,
+            audit: None,
+            pending: Mutex::new(HashSet::new()),
+            held: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn from_config<T: StateBackend + 'static>(config: &Config, store: T) -> Self {
+        Self::new(store)
+            .with_restore_from_backup(config.resume.restore_deleted_from_backup)
+            .with_backup(SessionBackup::with_config(
+                BackupConfig::from_resume_config(&config.resume, &config.monitoring),
+            ))
+    }
+
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    pub fn with_restore_from_backup(mut self, enabled: bool) -> Self {
+        self.restore_from_backup = enabled;
+        self
+    }
+
+    pub fn with_backup(mut self, backup: SessionBackup) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// React to `path` being deleted while it was the current session.
+    ///
+    /// Returns `None` if a deletion of the same path is already being handled.
+    pub async fn handle(&self, path: &Path) -> Option<DeletionOutcome> {
+        if !self
+            .pending
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(path.to_path_buf())
+        {
+            return None;
+        }
+        let outcome = self.resolve(path).await;
+        self.pending
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(path);
+        Some(outcome)
+    }
+
+    async fn resolve(&self, path: &Path) -> DeletionOutcome {
+        tokio::time::sleep(self.grace_period).await;
+        if path.exists() {
+            debug!(path = %path.display(), "Session file recreated within grace period");
+            return DeletionOutcome::Recreated;
+        }
+
+        warn!(path = %path.display(), "Current session file deleted");
+        self.set_status(path, SessionStatus::Missing);
+
+        let restored_from = if self.restore_from_backup {
+            self.restore(path).await
+        } else {
+            None
+        };
+        if restored_from.is_none() && path.exists() {
+            debug!(path = %path.display(), "Session file recreated during restore");
+            self.set_status(path, SessionStatus::Active);
+            return DeletionOutcome::Recreated;
+        }
+        self.notify(path, restored_from.clone());
+
+        match restored_from {
+            Some(backup) => {
+                self.set_status(path, SessionStatus::Active);
+                DeletionOutcome::Restored { backup }
+            }
+            None => {
+                self.held
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .insert(path.to_path_buf());
+                self.set_status(path, SessionStatus::NeedsAttention);
+                DeletionOutcome::NeedsAttention
+            }
+        }
+    }
+
+    /// Release a session held for attention once its file exists again.
+    ///
+    /// Returns whether `path` was being held.
+    pub fn note_reappeared(&self, path: &Path) -> bool {
+        if !self
+            .held
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(path)
+        {
+            return false;
+        }
+        info!(path = %path.display(), "Deleted session file reappeared");
+        self.set_status(path, SessionStatus::Active);
+        true
+    }
+
+    async fn restore(&self, path: &Path) -> Option<PathBuf> {
+        match self.backup.restore_latest(path).await {
+            Ok(Some(backup)) => {
+                if let Some(audit) = &self.audit {
+                    if let Err(err) = audit.log_session_restored(path, &backup) {
+                        warn!(error = %err, "Failed to audit session restore");
+                    }
+                }
+                Some(backup)
+            }
+            Ok(None) => {
+                info!(path = %path.display(), "No backup available to restore deleted session");
+                None
+            }
+            Err(err) => {
+                warn!(path = %path.display(), error = %err, "Failed to restore deleted session");
+                None
+            }
+        }
+    }
+
+    /// Update the stored current session's status, keeping its stats.
+    fn set_status(&self, path: &Path, status: SessionStatus) {
+        let mut state = self.store.load();
+        let Some(session) = state
+            .current_session
+            .as_mut()
+            .filter(|session| session.path == path)
+        else {
+            return;
+        };
+        if session.status == status {
+            return;
+        }
+        session.status = status;
+        if let Err(err) = self.store.save(&state) {
+            warn!(path = %path.display(), error = %err, "Failed to record session status");
+        }
+    }
+
+    fn notify(&self, path: &Path, restored_from: Option<PathBuf>) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let event = DomainEvent::SessionDeleted {
+            timestamp: Utc::now(),
+            session_path: path.to_path_buf(),
+            restored_from,
+        };
+        events.publish(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use crate::state::{CurrentSession, StateError, StateFile};
+
+    #[derive(Default)]
+    struct MemoryStore(Mutex<StateFile>);
+
+    impl StateBackend for MemoryStore {
+        fn load(&self) -> StateFile {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn save(&self, state: &StateFile) -> Result<(), StateError> {
+            *self.0.lock().unwrap() = state.clone();
+            Ok(())
+        }
+    }
+
+    fn tracked(path: &Path) -> Arc<MemoryStore> {
+        let store = Arc::new(MemoryStore::default());
+        let mut state = StateFile {
+            current_session: Some(CurrentSession {
+                path: path.to_path_buf(),
+                steps_completed: vec![1, 2],
+                last_step: 2,
+                ..CurrentSession::default()
+            }),
+            ..StateFile::default()
+        };
+        state.stats.total_resumes = 4;
+        store.save(&state).unwrap();
+        store
+    }
+
+    fn status(store: &MemoryStore) -> SessionStatus {
+        store.load().current_session.unwrap().status
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn recreate_within_grace_period_is_ignored() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.md");
+        let store = tracked(&path);
+        let events = EventBroadcaster::new(4);
+        let mut rx = events.subscribe();
+        let handler = DeletionHandler::new(Arc::clone(&store)).with_event_broadcaster(events);
+
+        let recreate = {
+            let path = path.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                std::fs::write(&path, b"saved again").unwrap();
+            }
+        };
+        let (outcome, ()) = tokio::join!(handler.handle(&path), recreate);
+
+        assert_eq!(outcome, Some(DeletionOutcome::Recreated));
+        assert_eq!(status(&store), SessionStatus::Active);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn deletion_without_backup_needs_attention() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.md");
+        let store = tracked(&path);
+        let events = EventBroadcaster::new(4);
+        let mut rx = events.subscribe();
+        let handler = DeletionHandler::new(Arc::clone(&store))
+            .with_restore_from_backup(true)
+            .with_event_broadcaster(events);
+
+        let outcome = handler.handle(&path).await;
+
+        assert_eq!(outcome, Some(DeletionOutcome::NeedsAttention));
+        let state = store.load();
+        assert_eq!(
+            state.current_session.as_ref().unwrap().status,
+            SessionStatus::NeedsAttention
+        );
+        assert_eq!(state.current_session.unwrap().steps_completed, vec![1, 2]);
+        assert_eq!(state.stats.total_resumes, 4);
+        match rx.try_recv().unwrap() {
+            NotificationEvent::SessionDeleted { restored_from, .. } => {
+                assert!(restored_from.is_none())
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        std::fs::write(&path, b"back").unwrap();
+        assert!(handler.note_reappeared(&path));
+        assert_eq!(status(&store), SessionStatus::Active);
+        assert!(!handler.note_reappeared(&path));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn deletion_with_backup_restores_and_audits() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.md");
+        let backup = temp.path().join("session-backup-20260101-120000.md");
+        std::fs::write(&backup, b"backed up").unwrap();
+        let store = tracked(&path);
+        let audit_dir = temp.path().join("state");
+        std::fs::create_dir(&audit_dir).unwrap();
+        let handler = DeletionHandler::new(Arc::clone(&store))
+            .with_restore_from_backup(true)
+            .with_audit(AuditLogger::new(&audit_dir));
+
+        let outcome = handler.handle(&path).await;
+
+        assert_eq!(
+            outcome,
+            Some(DeletionOutcome::Restored {
+                backup: backup.clone()
+            })
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"backed up");
+        assert_eq!(status(&store), SessionStatus::Active);
+        let audit = std::fs::read_to_string(audit_dir.join("audit.jsonl")).unwrap();
+        assert!(audit.contains("session_restored"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn restore_disabled_leaves_backup_untouched() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.md");
+        std::fs::write(temp.path().join("session-backup-20260101-120000.md"), b"x").unwrap();
+        let store = tracked(&path);
+        let handler = DeletionHandler::new(Arc::clone(&store));
+
+        assert_eq!(
+            handler.handle(&path).await,
+            Some(DeletionOutcome::NeedsAttention)
+        );
+        assert!(!path.exists());
+    }
+}
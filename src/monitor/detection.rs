@@ -41,11 +41,18 @@ pub struct DetectionResult {
 
 pub fn known_assistants() -> Vec<AssistantDefinition> {
     let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-    vec![AssistantDefinition {
-        name: "opencode".to_string(),
-        session_dir: home.join(".opencode"),
-        process_name: Some("opencode".to_string()),
-    }]
+    vec![
+        AssistantDefinition {
+            name: "opencode".to_string(),
+            session_dir: home.join(".opencode"),
+            process_name: Some("opencode".to_string()),
+        },
+        AssistantDefinition {
+            name: "claude-code".to_string(),
+            session_dir: home.join(".claude").join("projects"),
+            process_name: Some("claude".to_string()),
+        },
+    ]
 }
 
 pub fn detect_assistants() -> DetectionResult {
@@ -133,7 +140,7 @@ fn has_session_files(dir: &Path) -> bool {
 fn is_session_artifact(path: &Path) -> bool {
     matches!(
         path.extension().and_then(|ext| ext.to_str()),
-        Some("md") | Some("lock") | Some("sock")
+        Some("md") | Some("jsonl") | Some("lock") | Some("sock")
     )
 }
 
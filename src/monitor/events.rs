@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use tokio::sync::mpsc;
 
 use crate::monitor::classifier::{ClassificationResult, StopReason};
+use crate::monitor::deletion::DeletionOutcome;
 use crate::monitor::process::ProcessInfo;
 use crate::monitor::session::Session;
 
@@ -42,6 +43,20 @@ pub enum MonitorEvent {
         reason: StopReason,
         classification: ClassificationResult,
         process_info: Option<ProcessInfo>,
+        /// Stop-time transcript snapshot, when one was saved.
+        incident: Option<PathBuf>,
+    },
+    /// A session classified as completed was picked up again (informational);
+    /// its stops are classified normally from here on.
+    SessionReopened {
+        path: PathBuf,
+        /// What changed since the completed determination.
+        changes: Vec<String>,
+    },
+    /// The current session's file was deleted and the deletion was handled.
+    SessionDeleted {
+        path: PathBuf,
+        outcome: DeletionOutcome,
     },
     /// Monitor encountered an error.
     Error {
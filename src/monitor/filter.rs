@@ -0,0 +1,199 @@
+//! Which watcher events reach the monitor (`monitoring.include_extensions`,
+//! `monitoring.ignore_patterns`).
+//!
+//! Ignore patterns use the `resume.exclude_sessions` glob syntax and are
+//! matched against the path relative to the session directory. The extension
+//! allowlist only applies to files; events for the session directory itself
+//! are always forwarded.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use notify::EventKind;
+use notify::event::{CreateKind, RemoveKind};
+
+use crate::config::schema::MonitoringConfig;
+use crate::resume::exclusions::SessionExclusions;
+use crate::telemetry::Metrics;
+
+/// Compiled watcher filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchFilter {
+    include_extensions: Vec<String>,
+    ignore: SessionExclusions,
+}
+
+impl WatchFilter {
+    pub fn new<S: AsRef<str>>(include_extensions: &[S], ignore_patterns: &[S]) -> Self {
+        Self {
+            include_extensions: include_extensions
+                .iter()
+                .map(|ext| ext.as_ref().trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            ignore: SessionExclusions::new(ignore_patterns),
+        }
+    }
+
+    pub fn from_config(config: &MonitoringConfig) -> Self {
+        Self::new(&config.include_extensions, &config.ignore_patterns)
+    }
+
+    pub fn include_extensions(&self) -> &[String] {
+        &self.include_extensions
+    }
+
+    pub fn ignore_patterns(&self) -> Vec<String> {
+        self.ignore.patterns()
+    }
+
+    /// Whether an event for `path` should be forwarded.
+    pub fn allows(&self, session_dir: &Path, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(session_dir) else {
+            return true;
+        };
+        if relative.as_os_str().is_empty() {
+            return true;
+        }
+        if self.ignore.is_excluded(relative) {
+            return false;
+        }
+        if is_dir || self.include_extensions.is_empty() {
+            return true;
+        }
+        relative
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.include_extensions
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+            })
+    }
+}
+
+impl Default for WatchFilter {
+    fn default() -> Self {
+        Self::from_config(&MonitoringConfig::default())
+    }
+}
+
+/// Watcher filter shared with the daemon so `RELOAD` can swap it in place.
+#[derive(Debug, Clone, Default)]
+pub struct SharedWatchFilter {
+    filter: Arc<RwLock<WatchFilter>>,
+    filtered: Arc<AtomicU64>,
+}
+
+impl SharedWatchFilter {
+    pub fn new(filter: WatchFilter) -> Self {
+        Self {
+            filter: Arc::new(RwLock::new(filter)),
+            filtered: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn replace(&self, filter: WatchFilter) {
+        if let Ok(mut current) = self.filter.write() {
+            *current = filter;
+        }
+    }
+
+    pub fn snapshot(&self) -> WatchFilter {
+        self.filter
+            .read()
+            .map(|filter| filter.clone())
+            .unwrap_or_default()
+    }
+
+    /// Events dropped by the filter since the daemon started.
+    pub fn filtered_count(&self) -> u64 {
+        self.filtered.load(Ordering::Relaxed)
+    }
+
+    /// Check an event, counting it when it is dropped.
+    pub fn forwards(&self, session_dir: &Path, path: &Path, kind: &EventKind) -> bool {
+        let is_dir = matches!(
+            kind,
+            EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder)
+        ) || path.is_dir();
+        let allowed = match self.filter.read() {
+            Ok(filter) => filter.allows(session_dir, path, is_dir),
+            Err(_) => true,
+        };
+        if !allowed {
+            self.filtered.fetch_add(1, Ordering::Relaxed);
+            if let Some(metrics) = Metrics::global() {
+                metrics.record_event_filtered();
+            }
+        }
+        allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn session_dir() -> PathBuf {
+        PathBuf::from("/home/u/.opencode")
+    }
+
+    fn allows(filter: &WatchFilter, relative: &str) -> bool {
+        filter.allows(&session_dir(), &session_dir().join(relative), false)
+    }
+
+    #[test]
+    fn defaults_forward_sessions_and_drop_noise() {
+        let filter = WatchFilter::default();
+        assert!(allows(&filter, "session.md"));
+        assert!(allows(&filter, "projects/app/abc.jsonl"));
+        assert!(!allows(&filter, "session.md.swp"));
+        assert!(!allows(&filter, ".session.md.swp"));
+        assert!(!allows(&filter, "session.md~"));
+        assert!(!allows(&filter, "notes/draft.tmp"));
+        assert!(!allows(&filter, "repo/.git/objects/ab/cdef.md"));
+        assert!(!allows(&filter, "build/output.bin"));
+    }
+
+    #[test]
+    fn globs_match_nested_paths() {
+        let filter = WatchFilter::new(&["md"], &["**/cache/**", "scratch-*"]);
+        assert!(!allows(&filter, "a/b/cache/c/session.md"));
+        assert!(!allows(&filter, "cache/session.md"));
+        assert!(!allows(&filter, "deep/scratch-1/session.md"));
+        assert!(allows(&filter, "a/caches/session.md"));
+        assert!(allows(&filter, "A/Session.MD"));
+    }
+
+    #[test]
+    fn patterns_ignore_parents_of_the_session_dir() {
+        let filter = WatchFilter::new(&["md"], &["*.tmp"]);
+        let dir = PathBuf::from("/tmp/work.tmp/sessions");
+        assert!(filter.allows(&dir, &dir.join("session.md"), false));
+    }
+
+    #[test]
+    fn session_dir_itself_is_never_filtered() {
+        let filter = SharedWatchFilter::new(WatchFilter::new(&["md"], &["*", "**"]));
+        let dir = session_dir();
+        assert!(filter.forwards(&dir, &dir, &EventKind::Create(CreateKind::Folder)));
+        assert_eq!(filter.filtered_count(), 0);
+    }
+
+    #[test]
+    fn replace_applies_new_rules_and_counts_drops() {
+        let filter = SharedWatchFilter::default();
+        let dir = session_dir();
+        let path = dir.join("log.txt");
+        let modify = EventKind::Modify(notify::event::ModifyKind::Any);
+        assert!(!filter.forwards(&dir, &path, &modify));
+        assert_eq!(filter.filtered_count(), 1);
+
+        filter.replace(WatchFilter::new::<&str>(&[], &[]));
+        assert!(filter.forwards(&dir, &path, &modify));
+        assert_eq!(filter.filtered_count(), 1);
+    }
+}
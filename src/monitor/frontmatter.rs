@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 
+use crate::config::paths::{UnsafePathError, safe_path};
 use crate::monitor::events::{MonitorEvent, WatchEvent};
 use crate::monitor::session::{Session, SessionState};
+use crate::state::audit::record_rejected_path;
+use crate::util::encoding::{self, EncodingError, EncodingFixups, SourceEncoding};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ParseError {
@@ -19,12 +22,32 @@ pub enum ParseError {
 
     #[error("Session file not found: {path}")]
     FileNotFound { path: PathBuf },
+
+    #[error("Frontmatter exceeds {limit} bytes")]
+    TooLarge { limit: usize },
+
+    #[error("Frontmatter uses more than {limit} YAML aliases")]
+    TooManyAliases { limit: usize },
+
+    #[error("Unreadable session file: {0}")]
+    Encoding(#[from] EncodingError),
 }
 
+/// Largest frontmatter block that is parsed; real sessions use a few hundred bytes.
+pub const MAX_FRONTMATTER_BYTES: usize = 64 * 1024;
+
+/// YAML alias references (`*name`) allowed in one frontmatter block.
+///
+/// serde_yaml bounds alias expansion internally but its limit isn't
+/// configurable; session frontmatter never needs aliases, so a low cap
+/// rejects alias bombs before they reach the parser.
+pub const MAX_YAML_ALIASES: usize = 16;
+
 /// Extract YAML frontmatter from a markdown file.
 ///
 /// Efficiently reads only the frontmatter section, stopping
-/// after the closing `---` delimiter.
+/// after the closing `---` delimiter. Byte order marks, UTF-16 and CRLF
+/// line endings are handled (see [`crate::util::encoding`]).
 pub fn extract_frontmatter(path: &Path) -> Result<String, ParseError> {
     let file = File::open(path).map_err(|err| {
         if err.kind() == std::io::ErrorKind::NotFound {
@@ -36,35 +59,130 @@ pub fn extract_frontmatter(path: &Path) -> Result<String, ParseError> {
         }
     })?;
 
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
+    let (frontmatter, encoding) = read_frontmatter(BufReader::new(file))?;
+    EncodingFixups::shared().record(path, encoding);
+    Ok(frontmatter)
+}
+
+/// Extract frontmatter from any reader, reading at most
+/// [`MAX_FRONTMATTER_BYTES`] plus the delimiters.
+pub fn frontmatter_from_reader<R: BufRead>(reader: R) -> Result<String, ParseError> {
+    read_frontmatter(reader).map(|(frontmatter, _)| frontmatter)
+}
+
+fn read_frontmatter<R: BufRead>(mut reader: R) -> Result<(String, SourceEncoding), ParseError> {
+    let (encoding, bom_len) = SourceEncoding::detect(reader.fill_buf()?);
+    if encoding.is_utf16() {
+        // Two bytes per code unit; transcode, then parse the UTF-8 text.
+        let mut bytes = Vec::new();
+        reader
+            .take(FRONTMATTER_READ_CAP * 2 + bom_len as u64)
+            .read_to_end(&mut bytes)?;
+        let decoded = encoding::decode_prefix(&bytes)?;
+        let frontmatter = frontmatter_from_lines(decoded.text.as_bytes())?;
+        return Ok((frontmatter, encoding));
+    }
+    reader.consume(bom_len);
+    Ok((frontmatter_from_lines(reader)?, encoding))
+}
 
-    let first_line = lines.next().ok_or(ParseError::NoFrontmatter)??;
+// Room for both `---` lines (with optional `\r`) around the limit.
+const FRONTMATTER_READ_CAP: u64 = (MAX_FRONTMATTER_BYTES + 16) as u64;
+
+fn frontmatter_from_lines<R: BufRead>(reader: R) -> Result<String, ParseError> {
+    let mut reader = reader.take(FRONTMATTER_READ_CAP);
+    let mut consumed = 0;
+    let mut next_line = || -> Result<Option<String>, ParseError> {
+        let mut line = Vec::new();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        consumed += read;
+        let line = String::from_utf8(line)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+    };
+
+    let first_line = next_line()?.ok_or(ParseError::NoFrontmatter)?;
     if first_line.trim() != "---" {
         return Err(ParseError::NoFrontmatter);
     }
 
     let mut frontmatter = String::new();
-    for line in lines {
-        let line = line?;
+    while let Some(line) = next_line()? {
         if line.trim() == "---" {
             return Ok(frontmatter);
         }
         frontmatter.push_str(&line);
         frontmatter.push('\n');
+        if frontmatter.len() > MAX_FRONTMATTER_BYTES {
+            return Err(ParseError::TooLarge {
+                limit: MAX_FRONTMATTER_BYTES,
+            });
+        }
     }
 
+    if consumed as u64 >= FRONTMATTER_READ_CAP {
+        return Err(ParseError::TooLarge {
+            limit: MAX_FRONTMATTER_BYTES,
+        });
+    }
     Err(ParseError::NoFrontmatter)
 }
 
+/// Parse extracted frontmatter into session state.
+pub fn parse_frontmatter(frontmatter: &str) -> Result<SessionState, ParseError> {
+    if frontmatter.len() > MAX_FRONTMATTER_BYTES {
+        return Err(ParseError::TooLarge {
+            limit: MAX_FRONTMATTER_BYTES,
+        });
+    }
+    if count_aliases(frontmatter) > MAX_YAML_ALIASES {
+        return Err(ParseError::TooManyAliases {
+            limit: MAX_YAML_ALIASES,
+        });
+    }
+    Ok(serde_yaml::from_str(frontmatter)?)
+}
+
+/// Parse session file contents that were already read into memory.
+pub fn parse_session_bytes(path: &Path, contents: &[u8]) -> Result<Session, ParseError> {
+    let (frontmatter, encoding) = read_frontmatter(contents)?;
+    EncodingFixups::shared().record(path, encoding);
+    Ok(Session {
+        path: path.to_path_buf(),
+        state: parse_frontmatter(&frontmatter)?,
+    })
+}
+
+/// Count `*alias` tokens: a `*` at a token start followed by an anchor name.
+fn count_aliases(yaml: &str) -> usize {
+    let bytes = yaml.as_bytes();
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|&(idx, &byte)| {
+            byte == b'*'
+                && (idx == 0
+                    || matches!(
+                        bytes[idx - 1],
+                        b' ' | b'\t' | b'\n' | b'[' | b'{' | b',' | b':'
+                    ))
+                && bytes
+                    .get(idx + 1)
+                    .is_some_and(|next| next.is_ascii_alphanumeric() || matches!(next, b'_' | b'-'))
+        })
+        .count()
+}
+
 /// Parse a session file and extract its state.
 pub fn parse_session(path: &Path) -> Result<Session, ParseError> {
     let frontmatter = extract_frontmatter(path)?;
-    let state: SessionState = serde_yaml::from_str(&frontmatter)?;
 
     Ok(Session {
         path: path.to_path_buf(),
-        state,
+        state: parse_frontmatter(&frontmatter)?,
     })
 }
 
@@ -72,18 +190,44 @@ pub fn parse_session(path: &Path) -> Result<Session, ParseError> {
 #[derive(Debug, Default)]
 pub struct SessionParser {
     sessions: HashMap<PathBuf, Session>,
+    root: Option<PathBuf>,
+    follow_symlinks: bool,
 }
 
 impl SessionParser {
     pub fn new() -> Self {
         Self {
             sessions: HashMap::new(),
+            root: None,
+            follow_symlinks: false,
+        }
+    }
+
+    /// Only parse files that resolve inside `root`.
+    pub fn with_root(mut self, root: PathBuf, follow_symlinks: bool) -> Self {
+        self.root = Some(root);
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    fn check_path(&self, path: &Path) -> Result<(), UnsafePathError> {
+        match &self.root {
+            Some(root) => safe_path(path, root, self.follow_symlinks).map(|_| ()),
+            None => Ok(()),
         }
     }
 
     pub fn handle_event(&mut self, event: WatchEvent) -> Option<MonitorEvent> {
         match event {
             WatchEvent::FileModified(path) | WatchEvent::FileCreated(path) => {
+                if let Err(err) = self.check_path(&path) {
+                    record_rejected_path(&path, &err);
+                    return Some(MonitorEvent::Error {
+                        source: "path_guard".to_string(),
+                        message: err.to_string(),
+                        recoverable: true,
+                    });
+                }
                 match parse_session(&path) {
                     Ok(session) => {
                         let previous = self.sessions.insert(path, session.clone());
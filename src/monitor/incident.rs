@@ -0,0 +1,385 @@
+//! Stop-time transcript snapshots.
+//!
+//! When a stop is classified, [`IncidentStore::record`] saves the session tail
+//! the classifier analyzed to `incidents/<timestamp>-<session-stem>.tail.txt`
+//! under the state directory, next to a `.classification.json` record. The
+//! tail is the one already read for classification, so a later resume or edit
+//! of the session file does not change what the postmortem shows. Only the
+//! newest incidents are kept, oldest pruned first.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{debug, info};
+
+use crate::monitor::classifier::ClassificationResult;
+use crate::resume::postmortem::sanitize_stem;
+
+/// Directory under the state dir holding incident snapshots.
+pub const INCIDENTS_DIR: &str = "incidents";
+/// Incidents kept before the oldest are pruned.
+pub const DEFAULT_MAX_INCIDENTS: usize = 50;
+/// Bytes of transcript tail kept per incident; the end is kept.
+pub const DEFAULT_MAX_TAIL_BYTES: usize = 256 * 1024;
+
+const TAIL_SUFFIX: &str = ".tail.txt";
+const RECORD_SUFFIX: &str = ".classification.json";
+
+#[derive(Debug, Error)]
+pub enum IncidentError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid incident record: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("No incident named {id}")]
+    NotFound { id: String },
+}
+
+/// Classification saved next to each snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentRecord {
+    /// `<timestamp>-<session-stem>`, shared by the snapshot and record files.
+    pub id: String,
+    pub captured_at: DateTime<Utc>,
+    pub session_path: PathBuf,
+    /// Classified stop reason.
+    pub stop_reason: String,
+    pub confidence: f32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// The tail was longer than the byte cap and its start was dropped.
+    #[serde(default)]
+    pub tail_truncated: bool,
+}
+
+/// An incident read back from disk.
+#[derive(Debug, Clone)]
+pub struct Incident {
+    /// Path of the tail snapshot.
+    pub path: PathBuf,
+    pub record: IncidentRecord,
+    pub tail: String,
+}
+
+impl Incident {
+    /// Tail lines that produced the classification evidence.
+    pub fn is_evidence_line(&self, line: &str) -> bool {
+        self.record
+            .evidence
+            .iter()
+            .filter_map(|evidence| matched_text(evidence))
+            .any(|matched| line.contains(matched))
+    }
+}
+
+/// Writes, lists and prunes incident snapshots.
+#[derive(Debug, Clone)]
+pub struct IncidentStore {
+    dir: PathBuf,
+    max_incidents: usize,
+    max_tail_bytes: usize,
+}
+
+impl IncidentStore {
+    /// Incidents under `<state_dir>/incidents`.
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            dir: state_dir.join(INCIDENTS_DIR),
+            max_incidents: DEFAULT_MAX_INCIDENTS,
+            max_tail_bytes: DEFAULT_MAX_TAIL_BYTES,
+        }
+    }
+
+    /// Keep at most `max_incidents` incidents; 0 disables capture.
+    pub fn with_max_incidents(mut self, max_incidents: usize) -> Self {
+        self.max_incidents = max_incidents;
+        self
+    }
+
+    pub fn with_max_tail_bytes(mut self, max_tail_bytes: usize) -> Self {
+        self.max_tail_bytes = max_tail_bytes;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save `tail` and `classification` for a stop of `session_path`, then
+    /// prune old incidents. Returns the snapshot path, or `Ok(None)` when
+    /// capture is disabled.
+    pub fn record(
+        &self,
+        session_path: &Path,
+        tail: &str,
+        classification: &ClassificationResult,
+        exit_code: Option<i32>,
+    ) -> Result<Option<PathBuf>, IncidentError> {
+        if self.max_incidents == 0 {
+            return Ok(None);
+        }
+        fs::create_dir_all(&self.dir)?;
+
+        let captured_at = Utc::now();
+        let (id, tail_path) = self.reserve(captured_at, session_path)?;
+        let (tail, tail_truncated) = keep_tail(tail, self.max_tail_bytes);
+        let record = IncidentRecord {
+            id: id.clone(),
+            captured_at,
+            session_path: session_path.to_path_buf(),
+            stop_reason: format!("{:?}", classification.reason),
+            confidence: classification.confidence,
+            evidence: classification.evidence.clone(),
+            exit_code,
+            tail_truncated,
+        };
+        fs::write(&tail_path, tail)?;
+        fs::write(
+            self.dir.join(format!("{id}{RECORD_SUFFIX}")),
+            serde_json::to_string_pretty(&record)?,
+        )?;
+        info!(path = %tail_path.display(), "Saved stop-time transcript snapshot");
+
+        if let Err(err) = self.prune() {
+            debug!(error = %err, "Failed to prune old incidents");
+        }
+        Ok(Some(tail_path))
+    }
+
+    /// Incident records, oldest first. Unreadable records are skipped.
+    ///
+    /// A record's `id` is the name of its snapshot file, whatever the record
+    /// says.
+    pub fn list(&self) -> Result<Vec<IncidentRecord>, IncidentError> {
+        let mut records = Vec::new();
+        for id in self.incident_ids()? {
+            match self.read_record(&id) {
+                Ok(record) => records.push(IncidentRecord { id, ..record }),
+                Err(err) => debug!(id = %id, error = %err, "Skipping unreadable incident"),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Read the incident named `id`.
+    pub fn show(&self, id: &str) -> Result<Incident, IncidentError> {
+        let valid = !id.is_empty() && !id.contains(['/', '\\']) && id != "." && id != "..";
+        let path = self.dir.join(format!("{id}{TAIL_SUFFIX}"));
+        if !valid || !path.is_file() {
+            return Err(IncidentError::NotFound { id: id.to_string() });
+        }
+        Ok(Incident {
+            record: self.read_record(id)?,
+            tail: String::from_utf8_lossy(&fs::read(&path)?).into_owned(),
+            path,
+        })
+    }
+
+    /// Remove the oldest incidents beyond `max_incidents`.
+    pub fn prune(&self) -> Result<usize, IncidentError> {
+        let ids = self.incident_ids()?;
+        let excess = ids.len().saturating_sub(self.max_incidents);
+        for id in &ids[..excess] {
+            debug!(id = %id, "Pruning old incident");
+            for path in self.files(id) {
+                match fs::remove_file(path) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(excess)
+    }
+
+    /// The snapshot and record files of incident `id`.
+    pub fn files(&self, id: &str) -> [PathBuf; 2] {
+        [TAIL_SUFFIX, RECORD_SUFFIX].map(|suffix| self.dir.join(format!("{id}{suffix}")))
+    }
+
+    /// Incident ids, oldest first (ids start with a UTC timestamp).
+    fn incident_ids(&self) -> Result<Vec<String>, IncidentError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut ids: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_suffix(TAIL_SUFFIX).map(str::to_string)
+            })
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn read_record(&self, id: &str) -> Result<IncidentRecord, IncidentError> {
+        let contents = fs::read_to_string(self.dir.join(format!("{id}{RECORD_SUFFIX}")))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Create an empty snapshot file, suffixing the id on collision.
+    fn reserve(
+        &self,
+        captured_at: DateTime<Utc>,
+        session_path: &Path,
+    ) -> Result<(String, PathBuf), IncidentError> {
+        let stem = session_path
+            .file_stem()
+            .map(|stem| sanitize_stem(&stem.to_string_lossy()))
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or_else(|| "session".to_string());
+        let base = format!("{}-{stem}", captured_at.format("%Y%m%dT%H%M%S%.3fZ"));
+        let mut id = base.clone();
+        for suffix in 1.. {
+            let path = self.dir.join(format!("{id}{TAIL_SUFFIX}"));
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok((id, path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    id = format!("{base}-{suffix}");
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        unreachable!("suffixes are unbounded")
+    }
+}
+
+/// Audit metadata pointing at an incident snapshot, if there is one.
+pub fn audit_metadata(incident: Option<&Path>) -> HashMap<String, Value> {
+    incident
+        .map(|path| ("incident".to_string(), path.display().to_string().into()))
+        .into_iter()
+        .collect()
+}
+
+/// Text a `matched ... pattern: <text>` evidence line matched in the tail.
+fn matched_text(evidence: &str) -> Option<&str> {
+    let (label, text) = evidence.split_once(": ")?;
+    (label.starts_with("matched") && !text.trim().is_empty()).then(|| text.trim())
+}
+
+/// End of `text` within `limit` bytes (on a char boundary), and whether
+/// anything was dropped.
+fn keep_tail(text: &str, limit: usize) -> (&str, bool) {
+    if text.len() <= limit {
+        return (text, false);
+    }
+    let mut start = text.len() - limit;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    (&text[start..], true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::classifier::StopReasonClassifier;
+    use tempfile::tempdir;
+
+    #[test]
+    fn snapshot_matches_what_the_classifier_saw() {
+        let temp = tempdir().unwrap();
+        let session = temp.path().join("my session.md");
+        let mut content = (0..300)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        content.push('\n');
+        content.push_str("Error: 429 Too Many Requests\nretry-after: 60\n");
+        fs::write(&session, &content).unwrap();
+
+        let (classification, tail) =
+            StopReasonClassifier::default().classify_with_tail(&session, None);
+        let tail = tail.expect("tail read");
+        let store = IncidentStore::new(temp.path());
+        let path = store
+            .record(&session, &tail, &classification, Some(1))
+            .unwrap()
+            .expect("capture enabled");
+
+        fs::write(&session, "rewritten by the resume\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), tail);
+        assert!(!tail.contains("line 0\n"), "only the analyzed tail is kept");
+
+        let records = store.list().unwrap();
+        assert_eq!(records.len(), 1);
+        let incident = store.show(&records[0].id).unwrap();
+        assert_eq!(incident.tail, tail);
+        assert_eq!(incident.record.evidence, classification.evidence);
+        assert_eq!(incident.record.exit_code, Some(1));
+        assert!(incident.record.id.ends_with("-my_session"));
+        assert!(incident.is_evidence_line("Error: 429 Too Many Requests"));
+        assert!(!incident.is_evidence_line("line 299"));
+    }
+
+    #[test]
+    fn prunes_oldest_incidents_first() {
+        let temp = tempdir().unwrap();
+        let store = IncidentStore::new(temp.path()).with_max_incidents(2);
+        let classification = StopReasonClassifier::default().classify_content("", None);
+
+        let mut paths = Vec::new();
+        for i in 0..3 {
+            let session = PathBuf::from(format!("/work/session-{i}.md"));
+            paths.push(
+                store
+                    .record(&session, "tail", &classification, None)
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+
+        assert!(!paths[0].exists(), "oldest incident pruned");
+        assert!(paths[1].exists() && paths[2].exists());
+        let ids: Vec<String> = store.list().unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids[0].ends_with("session-1") && ids[1].ends_with("session-2"));
+        assert_eq!(
+            fs::read_dir(store.dir()).unwrap().count(),
+            4,
+            "pruned record removed with its snapshot"
+        );
+    }
+
+    #[test]
+    fn caps_tail_and_disables_at_zero() {
+        let temp = tempdir().unwrap();
+        let classification = StopReasonClassifier::default().classify_content("", None);
+        let store = IncidentStore::new(temp.path()).with_max_tail_bytes(4);
+        let path = store
+            .record(Path::new("/work/s.md"), "abcdefgh", &classification, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "efgh");
+        assert!(store.list().unwrap()[0].tail_truncated);
+
+        let disabled = IncidentStore::new(temp.path()).with_max_incidents(0);
+        assert!(
+            disabled
+                .record(Path::new("/work/s.md"), "tail", &classification, None)
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            store.show("../etc"),
+            Err(IncidentError::NotFound { .. })
+        ));
+    }
+}
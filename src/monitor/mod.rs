@@ -1,10 +1,20 @@
 //! File watcher and session parsing module.
 
+pub mod catalog;
 pub mod classifier;
+pub mod classify_queue;
+pub mod clock_skew;
 pub mod core;
+pub mod debounce;
+pub mod deletion;
 pub mod detection;
 pub mod events;
+pub mod filter;
 pub mod frontmatter;
+pub mod incident;
 pub mod process;
+pub mod reopen;
+pub mod scan;
 pub mod session;
+pub mod session_index;
 pub mod watcher;
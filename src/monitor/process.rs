@@ -25,6 +25,22 @@ pub struct ProcessInfo {
     pub working_dir: Option<PathBuf>,
 }
 
+impl ProcessInfo {
+    /// Whether `other` is a later observation of this same process.
+    ///
+    /// A matching PID with a different start time means the PID was reused.
+    /// When either start time is unknown only the PID is compared.
+    pub fn is_same_process(&self, other: &ProcessInfo) -> bool {
+        if self.pid != other.pid {
+            return false;
+        }
+        match (self.start_time, other.start_time) {
+            (Some(ours), Some(theirs)) => ours == theirs,
+            _ => true,
+        }
+    }
+}
+
 /// Events emitted by the process monitor.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProcessEvent {
@@ -62,6 +78,12 @@ pub trait ProcessEnumerator: Send + Sync {
     fn try_get_exit_code(&self, _pid: u32) -> Option<i32> {
         None
     }
+    /// List TCP ports the process is listening on.
+    fn listening_ports(&self, _pid: u32) -> Result<Vec<u16>, ProcessError> {
+        Err(ProcessError::EnumerationFailed(
+            "listening socket inspection not supported".to_string(),
+        ))
+    }
 }
 
 #[derive(Clone)]
@@ -186,16 +208,31 @@ impl ProcessMonitorState {
         let current_pids: HashSet<u32> = current.iter().map(|process| process.pid).collect();
 
         for process in &current {
-            if let std::collections::hash_map::Entry::Vacant(e) =
-                self.tracked_processes.entry(process.pid)
-            {
-                info!(pid = process.pid, "New opencode process detected");
-                e.insert(process.clone());
+            if let Some(previous) = self.tracked_processes.get(&process.pid) {
+                if previous.is_same_process(process) {
+                    continue;
+                }
+                // The PID now belongs to a different process; the old one is gone
+                // and its exit status can no longer be read.
+                let info = previous.clone();
+                info!(pid = info.pid, "opencode process replaced (PID reused)");
                 if cancel.is_cancelled() {
                     return Ok(());
                 }
-                let _ = tx.send(ProcessEvent::ProcessStarted(process.clone())).await;
+                let _ = tx
+                    .send(ProcessEvent::ProcessStopped {
+                        info,
+                        exit_code: None,
+                    })
+                    .await;
+            } else {
+                info!(pid = process.pid, "New opencode process detected");
+            }
+            self.tracked_processes.insert(process.pid, process.clone());
+            if cancel.is_cancelled() {
+                return Ok(());
             }
+            let _ = tx.send(ProcessEvent::ProcessStarted(process.clone())).await;
         }
 
         let stopped: Vec<u32> = self
@@ -232,6 +269,10 @@ impl ProcessEnumerator for DefaultProcessEnumerator {
     fn try_get_exit_code(&self, pid: u32) -> Option<i32> {
         try_get_exit_code(pid)
     }
+
+    fn listening_ports(&self, pid: u32) -> Result<Vec<u16>, ProcessError> {
+        crate::opencode::discovery::listening_ports_for_pid(pid)
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -239,6 +280,7 @@ fn enumerate_opencode_processes() -> Result<Vec<ProcessInfo>, ProcessError> {
     use std::fs;
 
     let mut processes = Vec::new();
+    let boot_time = boot_time();
 
     for entry in fs::read_dir("/proc")? {
         let entry = match entry {
@@ -275,11 +317,16 @@ fn enumerate_opencode_processes() -> Result<Vec<ProcessInfo>, ProcessError> {
         }
 
         let working_dir = fs::read_link(path.join("cwd")).ok();
+        let start_time = boot_time.and_then(|boot_time| {
+            let stat = fs::read_to_string(path.join("stat")).ok()?;
+            let ticks = parse_start_ticks_from_stat(&stat)?;
+            start_time_from_ticks(boot_time, ticks, clock_ticks_per_second())
+        });
 
         processes.push(ProcessInfo {
             pid,
             command_line,
-            start_time: None,
+            start_time,
             working_dir,
         });
     }
@@ -369,6 +416,50 @@ fn parse_exit_code_from_stat(stat: &str) -> Option<i32> {
     fields[49].parse::<i32>().ok()
 }
 
+/// Process start time in clock ticks since boot (`/proc/<pid>/stat` field 22).
+#[cfg(target_os = "linux")]
+fn parse_start_ticks_from_stat(stat: &str) -> Option<u64> {
+    let close_paren = stat.rfind(')')?;
+    let rest = stat.get(close_paren + 1..)?.trim();
+    // Fields after the command name start at field 3 (state).
+    rest.split_whitespace().nth(19)?.parse::<u64>().ok()
+}
+
+/// System boot time in seconds since the epoch (`btime` line of `/proc/stat`).
+#[cfg(target_os = "linux")]
+fn parse_boot_time(proc_stat: &str) -> Option<u64> {
+    proc_stat
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+#[cfg(target_os = "linux")]
+fn boot_time() -> Option<u64> {
+    static BOOT_TIME: std::sync::OnceLock<Option<u64>> = std::sync::OnceLock::new();
+    *BOOT_TIME.get_or_init(|| {
+        let proc_stat = std::fs::read_to_string("/proc/stat").ok()?;
+        parse_boot_time(&proc_stat)
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn clock_ticks_per_second() -> u64 {
+    // SAFETY: sysconf has no preconditions and only reads system configuration.
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 { ticks as u64 } else { 100 }
+}
+
+#[cfg(target_os = "linux")]
+fn start_time_from_ticks(boot_time: u64, ticks: u64, ticks_per_second: u64) -> Option<SystemTime> {
+    if ticks_per_second == 0 {
+        return None;
+    }
+    let since_boot = Duration::from_secs(ticks / ticks_per_second)
+        + Duration::from_nanos((ticks % ticks_per_second) * 1_000_000_000 / ticks_per_second);
+    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(boot_time) + since_boot)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -548,4 +639,81 @@ mod tests {
 
         assert!(closed);
     }
+
+    fn process_started_at(pid: u32, secs: u64) -> ProcessInfo {
+        ProcessInfo {
+            start_time: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ..process(pid, "opencode")
+        }
+    }
+
+    #[tokio::test]
+    async fn reused_pid_emits_stop_then_start() {
+        let enumerator = Arc::new(MockEnumerator::with_sequences(vec![
+            Ok(vec![process_started_at(50, 1_000)]),
+            Ok(vec![process_started_at(50, 1_000)]),
+            Ok(vec![process_started_at(50, 2_000)]),
+        ]));
+        let monitor = ProcessMonitor::new()
+            .with_poll_interval(Duration::from_millis(5))
+            .with_enumerator(enumerator);
+        let cancel = CancellationToken::new();
+
+        let mut rx = monitor.run(cancel.clone()).await.expect("run monitor");
+        let _ = timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .expect("start event");
+
+        let event = timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .expect("stop event")
+            .expect("event value");
+        assert_eq!(
+            event,
+            ProcessEvent::ProcessStopped {
+                info: process_started_at(50, 1_000),
+                exit_code: None,
+            }
+        );
+
+        let event = timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .expect("start event")
+            .expect("event value");
+        assert_eq!(
+            event,
+            ProcessEvent::ProcessStarted(process_started_at(50, 2_000))
+        );
+
+        cancel.cancel();
+    }
+
+    #[test]
+    fn same_process_ignores_unknown_start_times() {
+        let known = process_started_at(7, 10);
+        assert!(known.is_same_process(&process(7, "opencode")));
+        assert!(known.is_same_process(&process_started_at(7, 10)));
+        assert!(!known.is_same_process(&process_started_at(7, 11)));
+        assert!(!known.is_same_process(&process_started_at(8, 10)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_start_ticks_and_boot_time_fixtures() {
+        let stat = "4242 (open code) S 1 4242 4242 0 -1 4194560 1203 0 0 0 \
+                    15 4 0 0 20 0 12 0 987654 123456789 4321 18446744073709551615";
+        assert_eq!(parse_start_ticks_from_stat(stat), Some(987654));
+        assert_eq!(parse_start_ticks_from_stat("4242 (opencode) S 1"), None);
+
+        let proc_stat = "cpu  1 2 3 4\nintr 0\nctxt 99\nbtime 1700000000\nprocesses 12\n";
+        assert_eq!(parse_boot_time(proc_stat), Some(1_700_000_000));
+        assert_eq!(parse_boot_time("cpu 1 2 3\n"), None);
+
+        let start = start_time_from_ticks(1_700_000_000, 987_654, 100).expect("start time");
+        assert_eq!(
+            start,
+            SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000 + 9_876_540)
+        );
+        assert_eq!(start_time_from_ticks(1, 1, 0), None);
+    }
 }
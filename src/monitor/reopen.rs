@@ -0,0 +1,127 @@
+//! Completion that holds until a session is reopened.
+//!
+//! A stop classified as completed is recorded in the state file as a
+//! [`CompletedSession`]. The next stop compares the session with that record:
+//! if the file grew by more than `classifier.reopen_growth_bytes`, its status
+//! went back to `in-progress`, or it gained steps, the session was reopened and
+//! is classified like any other. Otherwise it stays completed.
+
+use std::fs;
+
+use chrono::{DateTime, Utc};
+
+use crate::monitor::session::Session;
+use crate::state::CompletedSession;
+
+/// How a session relates to its earlier completed determination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriorCompletion {
+    /// Nothing substantive changed; the session is still completed.
+    Unchanged,
+    /// The session was picked up again; one line per change that shows it.
+    Reopened(Vec<String>),
+}
+
+impl PriorCompletion {
+    /// Compare `current` with the `prior` determination.
+    pub fn assess(prior: &CompletedSession, current: &CompletedSession, growth_bytes: u64) -> Self {
+        let mut changes = Vec::new();
+
+        let grown = current.content_len.saturating_sub(prior.content_len);
+        if grown > growth_bytes {
+            changes.push(format!("content grew by {grown} bytes"));
+        }
+        if current.status.as_deref() == Some("in-progress")
+            && prior.status.as_deref() != Some("in-progress")
+        {
+            changes.push(format!(
+                "status changed from {} to in-progress",
+                prior.status.as_deref().unwrap_or("none")
+            ));
+        }
+        if current.steps_completed > prior.steps_completed {
+            changes.push(format!(
+                "{} new completed step(s)",
+                current.steps_completed - prior.steps_completed
+            ));
+        } else if current.last_step > prior.last_step && prior.last_step.is_some() {
+            changes.push(format!(
+                "lastStep raised from {} to {}",
+                prior.last_step.unwrap_or_default(),
+                current.last_step.unwrap_or_default()
+            ));
+        }
+
+        if changes.is_empty() {
+            Self::Unchanged
+        } else {
+            Self::Reopened(changes)
+        }
+    }
+}
+
+/// The session as it is on disk now; `None` when the file cannot be read.
+pub fn snapshot(session: &Session, at: DateTime<Utc>) -> Option<CompletedSession> {
+    let content_len = fs::metadata(&session.path).ok()?.len();
+    Some(CompletedSession {
+        path: session.path.clone(),
+        determined_at: at,
+        content_len,
+        steps_completed: session.steps_completed_count(),
+        last_step: session.state.last_step,
+        status: session.state.status.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn completed(content_len: u64, steps: usize, status: &str) -> CompletedSession {
+        CompletedSession {
+            path: PathBuf::from("/w/session.md"),
+            determined_at: Utc::now(),
+            content_len,
+            steps_completed: steps,
+            last_step: Some(3),
+            status: Some(status.to_string()),
+        }
+    }
+
+    #[test]
+    fn small_edits_keep_session_completed() {
+        let prior = completed(1_000, 3, "complete");
+        let touched = completed(1_200, 3, "complete");
+        assert_eq!(
+            PriorCompletion::assess(&prior, &touched, 512),
+            PriorCompletion::Unchanged
+        );
+    }
+
+    #[test]
+    fn growth_status_and_steps_each_reopen() {
+        let prior = completed(1_000, 3, "complete");
+
+        let grown = completed(2_000, 3, "complete");
+        assert_eq!(
+            PriorCompletion::assess(&prior, &grown, 512),
+            PriorCompletion::Reopened(vec!["content grew by 1000 bytes".to_string()])
+        );
+
+        let flipped = completed(1_000, 3, "in-progress");
+        assert_eq!(
+            PriorCompletion::assess(&prior, &flipped, 512),
+            PriorCompletion::Reopened(vec![
+                "status changed from complete to in-progress".to_string()
+            ])
+        );
+
+        let mut extended = completed(1_000, 3, "complete");
+        extended.last_step = Some(5);
+        assert_eq!(
+            PriorCompletion::assess(&prior, &extended, 512),
+            PriorCompletion::Reopened(vec!["lastStep raised from 3 to 5".to_string()])
+        );
+    }
+}
@@ -0,0 +1,457 @@
+//! Bounded traversal of large session directories.
+//!
+//! A session directory can accumulate tens of thousands of historical files.
+//! [`RecentScan`] visits directories newest-first by directory mtime and
+//! stops once it holds enough recent files and every directory left is older
+//! than the oldest file kept. A directory's mtime moves when entries are
+//! created, renamed or removed, not when a file is rewritten in place, so the
+//! cut-off is a heuristic: sessions written with a create or rename (OpenCode,
+//! most editors) are always found.
+//!
+//! [`DirCache`] keeps directory listings between scans. A listing is reused
+//! while the directory's mtime is unchanged and dropped on watch events for
+//! its entries, so repeated selections do not re-read the tree.
+//!
+//! [`WatchPlan`] decides which directories get recursive watches when
+//! `monitoring.active_window_hours` is set.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tracing::debug;
+
+use crate::monitor::events::WatchEvent;
+use crate::monitor::filter::WatchFilter;
+
+/// Recent files a scan collects by default (`monitoring.scan_limit`).
+pub const DEFAULT_SCAN_LIMIT: usize = 200;
+/// Directory levels below the root a scan descends.
+pub const MAX_SCAN_DEPTH: usize = 6;
+/// Files a single scan looks at before giving up on finding older ones.
+const MAX_SEEN_FILES: usize = 100_000;
+/// Listings kept by a [`DirCache`]; past this the cache starts over.
+const MAX_CACHED_DIRS: usize = 4_096;
+
+#[derive(Debug, Clone)]
+struct DirEntryInfo {
+    path: PathBuf,
+    is_dir: bool,
+    modified: SystemTime,
+}
+
+#[derive(Debug)]
+struct Listing {
+    dir_modified: SystemTime,
+    entries: Arc<Vec<DirEntryInfo>>,
+}
+
+/// Directory listings shared between scans.
+#[derive(Debug, Default)]
+pub struct DirCache {
+    listings: Mutex<HashMap<PathBuf, Listing>>,
+}
+
+impl DirCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the listings `event` makes stale: the directory holding the
+    /// path, the path itself, and anything below a deleted path.
+    pub fn handle_watch_event(&self, event: &WatchEvent) {
+        let path = match event {
+            WatchEvent::FileCreated(path)
+            | WatchEvent::FileModified(path)
+            | WatchEvent::FileDeleted(path)
+            | WatchEvent::DirectoryCreated(path) => path,
+            WatchEvent::Error(_) => return,
+        };
+        let mut listings = self.lock();
+        if let Some(parent) = path.parent() {
+            listings.remove(parent);
+        }
+        if matches!(event, WatchEvent::FileDeleted(_)) {
+            listings.retain(|dir, _| !dir.starts_with(path));
+        } else {
+            listings.remove(path);
+        }
+    }
+
+    pub fn invalidate(&self) {
+        self.lock().clear();
+    }
+
+    /// Directories currently cached.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entries(&self, dir: &Path) -> Option<Arc<Vec<DirEntryInfo>>> {
+        let dir_modified = fs::metadata(dir).and_then(|meta| meta.modified()).ok()?;
+        {
+            let listings = self.lock();
+            if let Some(listing) = listings.get(dir) {
+                if listing.dir_modified == dir_modified {
+                    return Some(Arc::clone(&listing.entries));
+                }
+            }
+        }
+        let entries = Arc::new(read_entries(dir)?);
+        let mut listings = self.lock();
+        if listings.len() >= MAX_CACHED_DIRS {
+            debug!(
+                cached = listings.len(),
+                "Directory listing cache full; clearing"
+            );
+            listings.clear();
+        }
+        listings.insert(
+            dir.to_path_buf(),
+            Listing {
+                dir_modified,
+                entries: Arc::clone(&entries),
+            },
+        );
+        Some(entries)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, Listing>> {
+        self.listings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Entries of `dir`, skipping symlinks.
+fn read_entries(dir: &Path) -> Option<Vec<DirEntryInfo>> {
+    let entries = fs::read_dir(dir).ok()?;
+    Some(
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_type = entry.file_type().ok()?;
+                if file_type.is_symlink() {
+                    return None;
+                }
+                let modified = entry
+                    .metadata()
+                    .and_then(|meta| meta.modified())
+                    .unwrap_or(UNIX_EPOCH);
+                Some(DirEntryInfo {
+                    path: entry.path(),
+                    is_dir: file_type.is_dir(),
+                    modified,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// A file kept by a [`RecentScan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+/// What a [`RecentScan`] found.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    /// Newest first.
+    pub files: Vec<RecentFile>,
+    pub dirs_visited: usize,
+    pub files_seen: usize,
+    /// Every directory within the depth limit was read.
+    pub exhaustive: bool,
+    pub elapsed: Duration,
+}
+
+/// Newest-first traversal that stops once it has enough recent files.
+#[derive(Debug, Clone, Copy)]
+pub struct RecentScan<'a> {
+    root: &'a Path,
+    filter: &'a WatchFilter,
+    limit: usize,
+    max_depth: usize,
+    cache: Option<&'a DirCache>,
+}
+
+impl<'a> RecentScan<'a> {
+    pub fn new(root: &'a Path, filter: &'a WatchFilter) -> Self {
+        Self {
+            root,
+            filter,
+            limit: DEFAULT_SCAN_LIMIT,
+            max_depth: MAX_SCAN_DEPTH,
+            cache: None,
+        }
+    }
+
+    /// Files to collect before older directories are skipped.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Reuse listings from `cache` and store the ones read.
+    pub fn with_cache(mut self, cache: &'a DirCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn run(&self) -> ScanOutcome {
+        let started = Instant::now();
+        let mut pending = BinaryHeap::new();
+        pending.push((SystemTime::now(), 0usize, self.root.to_path_buf()));
+        let mut kept: BinaryHeap<Reverse<(SystemTime, PathBuf)>> = BinaryHeap::new();
+        let mut outcome = ScanOutcome {
+            exhaustive: true,
+            ..ScanOutcome::default()
+        };
+
+        'dirs: while let Some((dir_modified, depth, dir)) = pending.pop() {
+            let full = kept.len() >= self.limit;
+            if full
+                && kept
+                    .peek()
+                    .is_some_and(|Reverse((oldest, _))| dir_modified < *oldest)
+            {
+                outcome.exhaustive = false;
+                break;
+            }
+            let Some(entries) = self.entries(&dir) else {
+                continue;
+            };
+            outcome.dirs_visited += 1;
+            for entry in entries.iter() {
+                if !self.filter.allows(self.root, &entry.path, entry.is_dir) {
+                    continue;
+                }
+                if entry.is_dir {
+                    if depth < self.max_depth {
+                        pending.push((entry.modified, depth + 1, entry.path.clone()));
+                    }
+                    continue;
+                }
+                outcome.files_seen += 1;
+                if outcome.files_seen > MAX_SEEN_FILES {
+                    debug!(root = %self.root.display(), "Session scan file limit reached");
+                    outcome.exhaustive = false;
+                    break 'dirs;
+                }
+                kept.push(Reverse((entry.modified, entry.path.clone())));
+                if kept.len() > self.limit {
+                    kept.pop();
+                }
+            }
+        }
+
+        outcome.files = kept
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((modified, path))| RecentFile { path, modified })
+            .collect();
+        outcome.elapsed = started.elapsed();
+        outcome
+    }
+
+    fn entries(&self, dir: &Path) -> Option<Arc<Vec<DirEntryInfo>>> {
+        match self.cache {
+            Some(cache) => cache.entries(dir),
+            None => read_entries(dir).map(Arc::new),
+        }
+    }
+}
+
+/// Which directories the session watcher watches, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchPlan {
+    /// Watched together with their whole subtree.
+    pub recursive: Vec<PathBuf>,
+    /// Watched for their own entries only, to notice new subdirectories.
+    pub shallow: Vec<PathBuf>,
+}
+
+impl WatchPlan {
+    /// Plan for `root`.
+    ///
+    /// Without an activity window the whole tree is watched recursively.
+    /// With one, `root` is watched shallowly and only its subdirectories
+    /// modified within the window (and not ignored by `filter`) recursively.
+    pub fn new(
+        root: &Path,
+        filter: &WatchFilter,
+        active_window: Option<Duration>,
+        now: SystemTime,
+    ) -> Self {
+        let Some(window) = active_window else {
+            return Self {
+                recursive: vec![root.to_path_buf()],
+                shallow: Vec::new(),
+            };
+        };
+        let cutoff = now.checked_sub(window).unwrap_or(UNIX_EPOCH);
+        let mut recursive: Vec<PathBuf> = read_entries(root)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| entry.is_dir && entry.modified >= cutoff)
+            .filter(|entry| filter.allows(root, &entry.path, true))
+            .map(|entry| entry.path)
+            .collect();
+        recursive.sort();
+        Self {
+            recursive,
+            shallow: vec![root.to_path_buf()],
+        }
+    }
+
+    /// Only part of the tree is watched recursively.
+    pub fn is_windowed(&self) -> bool {
+        !self.shallow.is_empty()
+    }
+
+    /// Inotify watches the plan needs: one per directory in each recursive
+    /// subtree plus one per shallow watch. Reads directories only.
+    pub fn descriptor_count(&self) -> usize {
+        self.shallow.len()
+            + self
+                .recursive
+                .iter()
+                .map(|dir| count_dirs(dir))
+                .sum::<usize>()
+    }
+}
+
+/// `dir` and every directory below it, not following symlinks.
+fn count_dirs(dir: &Path) -> usize {
+    let mut count = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        count += 1;
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                pending.push(entry.path());
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn age(path: &Path, secs_ago: u64) {
+        let at = SystemTime::now() - Duration::from_secs(secs_ago);
+        fs::File::open(path).unwrap().set_modified(at).unwrap();
+    }
+
+    fn write(path: &Path, secs_ago: u64) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "---\nstepsCompleted: [1]\n---\n").unwrap();
+        age(path, secs_ago);
+    }
+
+    #[test]
+    fn scan_keeps_newest_files_and_skips_older_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        for (dir, secs_ago) in [("old", 9_000), ("older", 10_000), ("new", 10)] {
+            for n in 0..5 {
+                write(&temp.path().join(dir).join(format!("{n}.md")), secs_ago + n);
+            }
+            age(&temp.path().join(dir), secs_ago);
+        }
+
+        let filter = WatchFilter::new(&["md"], &[] as &[&str]);
+        let outcome = RecentScan::new(temp.path(), &filter).with_limit(3).run();
+
+        let names: Vec<_> = outcome
+            .files
+            .iter()
+            .map(|file| file.path.strip_prefix(temp.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                PathBuf::from("new/0.md"),
+                PathBuf::from("new/1.md"),
+                PathBuf::from("new/2.md")
+            ]
+        );
+        assert!(!outcome.exhaustive);
+        assert_eq!(outcome.dirs_visited, 2, "root and new/ only");
+    }
+
+    #[test]
+    fn cache_reuses_listings_until_a_watch_event() {
+        let temp = tempfile::tempdir().unwrap();
+        let session = temp.path().join("project").join("a.md");
+        write(&session, 100);
+        let filter = WatchFilter::new(&["md"], &[] as &[&str]);
+        let cache = DirCache::new();
+
+        let first = RecentScan::new(temp.path(), &filter)
+            .with_cache(&cache)
+            .run();
+        assert_eq!(first.files.len(), 1);
+        assert_eq!(cache.len(), 2);
+
+        // Rewritten in place: the directory mtime stays put, so only the
+        // watch event makes the new file time visible.
+        fs::write(&session, "---\nstepsCompleted: [1, 2]\n---\n").unwrap();
+        let stale = RecentScan::new(temp.path(), &filter)
+            .with_cache(&cache)
+            .run();
+        assert_eq!(stale.files[0].modified, first.files[0].modified);
+
+        cache.handle_watch_event(&WatchEvent::FileModified(session.clone()));
+        assert_eq!(cache.len(), 1);
+        let fresh = RecentScan::new(temp.path(), &filter)
+            .with_cache(&cache)
+            .run();
+        assert!(fresh.files[0].modified > first.files[0].modified);
+
+        cache.handle_watch_event(&WatchEvent::FileDeleted(temp.path().join("project")));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn plan_watches_only_recently_active_subdirectories() {
+        let temp = tempfile::tempdir().unwrap();
+        for (dir, secs_ago) in [("active", 60), ("stale", 30 * 86_400), (".git", 60)] {
+            fs::create_dir_all(temp.path().join(dir).join("nested")).unwrap();
+            age(&temp.path().join(dir), secs_ago);
+        }
+        let filter = WatchFilter::new(&["md"], &["**/.git/**", ".git"]);
+
+        let plan = WatchPlan::new(
+            temp.path(),
+            &filter,
+            Some(Duration::from_secs(86_400)),
+            SystemTime::now(),
+        );
+        assert_eq!(plan.recursive, vec![temp.path().join("active")]);
+        assert_eq!(plan.shallow, vec![temp.path().to_path_buf()]);
+        assert_eq!(plan.descriptor_count(), 3);
+
+        let whole = WatchPlan::new(temp.path(), &filter, None, SystemTime::now());
+        assert!(!whole.is_windowed());
+        assert_eq!(whole.descriptor_count(), 7);
+    }
+}
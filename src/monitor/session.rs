@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use serde::Deserialize;
 
+use crate::state::TokenUsage;
+
 /// Represents a step identifier (integer or string).
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(untagged)]
@@ -21,6 +23,10 @@ pub struct SessionState {
     #[serde(default, rename = "lastStep", alias = "last_step")]
     pub last_step: Option<i64>,
 
+    /// Total number of steps in the workflow (if declared).
+    #[serde(default, rename = "totalSteps", alias = "total_steps")]
+    pub total_steps: Option<i64>,
+
     /// Workflow status (e.g., "complete", "in-progress").
     #[serde(default)]
     pub status: Option<String>,
@@ -36,6 +42,18 @@ pub struct SessionState {
     /// Input documents used.
     #[serde(default, rename = "inputDocuments", alias = "input_documents")]
     pub input_documents: Vec<String>,
+
+    /// Assistant model the session runs on (e.g., "anthropic/claude-sonnet-4").
+    #[serde(default, alias = "modelID", alias = "model_id")]
+    pub model: Option<String>,
+
+    /// Input tokens the session has used so far, when the assistant reports it.
+    #[serde(default, rename = "inputTokens", alias = "input_tokens")]
+    pub input_tokens: Option<u64>,
+
+    /// Output tokens the session has used so far.
+    #[serde(default, rename = "outputTokens", alias = "output_tokens")]
+    pub output_tokens: Option<u64>,
 }
 
 /// A parsed session file with path and state.
@@ -58,4 +76,27 @@ impl Session {
     pub fn steps_completed_count(&self) -> usize {
         self.state.steps_completed.len()
     }
+
+    /// Cumulative token usage, when the frontmatter reports any.
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        if self.state.input_tokens.is_none() && self.state.output_tokens.is_none() {
+            return None;
+        }
+        Some(TokenUsage {
+            input: self.state.input_tokens.unwrap_or(0),
+            output: self.state.output_tokens.unwrap_or(0),
+        })
+    }
+
+    /// Completed steps that are plain step numbers.
+    pub fn completed_step_numbers(&self) -> Vec<u32> {
+        self.state
+            .steps_completed
+            .iter()
+            .filter_map(|value| match value {
+                StepValue::Integer(num) => u32::try_from(*num).ok(),
+                StepValue::String(value) => value.parse::<u32>().ok(),
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,454 @@
+//! Mapping between OpenCode session IDs and on-disk session files.
+//!
+//! OpenCode keeps one JSON metadata file per session under
+//! `<data_dir>/opencode/storage/session/<projectID>/<sessionID>.json`, and one
+//! per project under `storage/project/<projectID>.json`. The index reads those
+//! files lazily, refreshes on lookup misses and on watch events under the
+//! storage directory, and briefly caches misses so a burst of events for an
+//! unknown session does not rescan storage every time.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::monitor::events::WatchEvent;
+
+const SESSION_DIR: &str = "session";
+const PROJECT_DIR: &str = "project";
+const MESSAGE_DIRS: [&str; 2] = ["message", "part"];
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(5);
+
+/// A session known to OpenCode's storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionRef {
+    /// OpenCode server-side session ID.
+    pub id: String,
+    /// Session metadata file.
+    pub path: PathBuf,
+    pub project_id: Option<String>,
+    /// Working directory of the session's project.
+    pub project_dir: Option<PathBuf>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionMetadata {
+    id: String,
+    #[serde(default, rename = "projectID")]
+    project_id: Option<String>,
+    #[serde(default)]
+    directory: Option<PathBuf>,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectMetadata {
+    id: String,
+    #[serde(default)]
+    worktree: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum LookupKey {
+    Id(String),
+    Path(PathBuf),
+}
+
+#[derive(Debug, Default)]
+struct IndexState {
+    loaded: bool,
+    by_id: HashMap<String, SessionRef>,
+    by_path: HashMap<PathBuf, String>,
+    misses: HashMap<LookupKey, Instant>,
+    /// Storage problems already logged, so a broken file warns once.
+    reported: Vec<PathBuf>,
+}
+
+/// Session ID ↔ file path ↔ project index over OpenCode storage.
+#[derive(Debug)]
+pub struct SessionIndex {
+    storage_dir: PathBuf,
+    negative_ttl: Duration,
+    state: Mutex<IndexState>,
+}
+
+impl SessionIndex {
+    pub fn new(storage_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            storage_dir: storage_dir.into(),
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            state: Mutex::new(IndexState::default()),
+        }
+    }
+
+    /// Index over OpenCode's default storage directory, if the platform has one.
+    pub fn default_location() -> Option<Self> {
+        dirs::data_dir().map(|dir| Self::new(dir.join("opencode").join("storage")))
+    }
+
+    /// How long a lookup miss is remembered before storage is re-read.
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    pub fn storage_dir(&self) -> &Path {
+        &self.storage_dir
+    }
+
+    pub fn lookup_by_id(&self, id: &str) -> Option<SessionRef> {
+        self.lookup(LookupKey::Id(id.to_string()))
+    }
+
+    /// Find the session owning `path`.
+    ///
+    /// Accepts the session metadata file itself or any file stored under the
+    /// session's message/part directories.
+    pub fn lookup_by_path(&self, path: &Path) -> Option<SessionRef> {
+        self.lookup(LookupKey::Path(path.to_path_buf()))
+    }
+
+    /// Refresh the index if `event` touches OpenCode storage metadata.
+    pub fn handle_watch_event(&self, event: &WatchEvent) {
+        let path = match event {
+            WatchEvent::FileCreated(path)
+            | WatchEvent::FileModified(path)
+            | WatchEvent::FileDeleted(path)
+            | WatchEvent::DirectoryCreated(path) => path,
+            WatchEvent::Error(_) => return,
+        };
+        if !self.is_metadata_path(path) {
+            return;
+        }
+
+        debug!(path = %path.display(), "OpenCode storage changed; refreshing session index");
+        let mut state = self.lock_state();
+        self.reload(&mut state);
+    }
+
+    /// Drop everything and re-read storage on the next lookup.
+    pub fn invalidate(&self) {
+        let mut state = self.lock_state();
+        state.loaded = false;
+        state.misses.clear();
+    }
+
+    fn lookup(&self, key: LookupKey) -> Option<SessionRef> {
+        let mut state = self.lock_state();
+        if !state.loaded {
+            self.reload(&mut state);
+        }
+        if let Some(found) = self.resolve(&state, &key) {
+            return Some(found);
+        }
+
+        if state
+            .misses
+            .get(&key)
+            .is_some_and(|missed_at| missed_at.elapsed() < self.negative_ttl)
+        {
+            return None;
+        }
+
+        self.reload(&mut state);
+        let found = self.resolve(&state, &key);
+        if found.is_none() {
+            state.misses.insert(key, Instant::now());
+        }
+        found
+    }
+
+    fn resolve(&self, state: &IndexState, key: &LookupKey) -> Option<SessionRef> {
+        match key {
+            LookupKey::Id(id) => state.by_id.get(id).cloned(),
+            LookupKey::Path(path) => {
+                let id = state
+                    .by_path
+                    .get(path)
+                    .cloned()
+                    .or_else(|| self.session_id_from_message_path(path))?;
+                state.by_id.get(&id).cloned()
+            }
+        }
+    }
+
+    /// `storage/message/<sessionID>/...` and `storage/part/<sessionID>/...`.
+    fn session_id_from_message_path(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.storage_dir).ok()?;
+        let mut components = relative.components();
+        let kind = components.next()?.as_os_str().to_str()?;
+        if !MESSAGE_DIRS.contains(&kind) {
+            return None;
+        }
+        let id = components.next()?.as_os_str().to_str()?;
+        Some(id.to_string())
+    }
+
+    fn is_metadata_path(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.storage_dir) else {
+            return false;
+        };
+        match relative.components().next() {
+            Some(first) => first.as_os_str() == SESSION_DIR || first.as_os_str() == PROJECT_DIR,
+            // The storage directory itself appeared or was replaced.
+            None => true,
+        }
+    }
+
+    fn reload(&self, state: &mut IndexState) {
+        let projects = self.read_projects(state);
+        let mut by_id = HashMap::new();
+        let mut by_path = HashMap::new();
+
+        for path in json_files(&self.storage_dir.join(SESSION_DIR), 2) {
+            let Some(metadata) = self.read_json::<SessionMetadata>(&path, state) else {
+                continue;
+            };
+            let project_dir = metadata.directory.or_else(|| {
+                metadata
+                    .project_id
+                    .as_ref()
+                    .and_then(|id| projects.get(id).cloned())
+            });
+            by_path.insert(path.clone(), metadata.id.clone());
+            by_id.insert(
+                metadata.id.clone(),
+                SessionRef {
+                    id: metadata.id,
+                    path,
+                    project_id: metadata.project_id,
+                    project_dir,
+                    title: metadata.title,
+                },
+            );
+        }
+
+        debug!(
+            storage = %self.storage_dir.display(),
+            sessions = by_id.len(),
+            "Session index loaded"
+        );
+        state.by_id = by_id;
+        state.by_path = by_path;
+        state.misses.clear();
+        state.loaded = true;
+    }
+
+    fn read_projects(&self, state: &mut IndexState) -> HashMap<String, PathBuf> {
+        json_files(&self.storage_dir.join(PROJECT_DIR), 1)
+            .into_iter()
+            .filter_map(|path| self.read_json::<ProjectMetadata>(&path, state))
+            .filter_map(|project| project.worktree.map(|worktree| (project.id, worktree)))
+            .collect()
+    }
+
+    fn read_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &Path,
+        state: &mut IndexState,
+    ) -> Option<T> {
+        let result = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|err| err.to_string()));
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                if !state.reported.iter().any(|reported| reported == path) {
+                    warn!(
+                        path = %path.display(),
+                        error = %err,
+                        "Skipping unreadable OpenCode session metadata"
+                    );
+                    state.reported.push(path.to_path_buf());
+                }
+                None
+            }
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, IndexState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// `*.json` files up to `depth` directory levels below `dir`.
+fn json_files(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() && depth > 1 => files.extend(json_files(&path, depth - 1)),
+            Ok(kind) if kind.is_file() => {
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    files.push(path);
+                }
+            }
+            _ => {}
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn fixture() -> (TempDir, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let storage = temp.path().join("storage");
+        write(
+            &storage.join("project/proj_1.json"),
+            r#"{"id":"proj_1","worktree":"/work/app"}"#,
+        );
+        write(
+            &storage.join("session/proj_1/ses_a.json"),
+            r#"{"id":"ses_a","projectID":"proj_1","title":"Refactor"}"#,
+        );
+        write(
+            &storage.join("session/proj_1/ses_b.json"),
+            r#"{"id":"ses_b","projectID":"proj_1","directory":"/work/app/sub"}"#,
+        );
+        (temp, storage)
+    }
+
+    #[test]
+    fn looks_up_by_id_and_path() {
+        let (_temp, storage) = fixture();
+        let index = SessionIndex::new(&storage);
+
+        let by_id = index.lookup_by_id("ses_a").expect("session by id");
+        assert_eq!(by_id.path, storage.join("session/proj_1/ses_a.json"));
+        assert_eq!(by_id.project_id.as_deref(), Some("proj_1"));
+        assert_eq!(by_id.project_dir, Some(PathBuf::from("/work/app")));
+        assert_eq!(by_id.title.as_deref(), Some("Refactor"));
+
+        let by_path = index
+            .lookup_by_path(&storage.join("session/proj_1/ses_b.json"))
+            .expect("session by path");
+        assert_eq!(by_path.id, "ses_b");
+        assert_eq!(by_path.project_dir, Some(PathBuf::from("/work/app/sub")));
+    }
+
+    #[test]
+    fn maps_message_files_to_their_session() {
+        let (_temp, storage) = fixture();
+        let index = SessionIndex::new(&storage);
+
+        let found = index
+            .lookup_by_path(&storage.join("message/ses_a/msg_1.json"))
+            .expect("session for message");
+        assert_eq!(found.id, "ses_a");
+        assert!(
+            index
+                .lookup_by_path(Path::new("/elsewhere/ses_a.json"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn tolerates_missing_and_unparseable_storage() {
+        let temp = TempDir::new().unwrap();
+        let missing = SessionIndex::new(temp.path().join("absent"));
+        assert!(missing.lookup_by_id("ses_a").is_none());
+
+        let storage = temp.path().join("storage");
+        write(&storage.join("session/proj_1/broken.json"), "{not json");
+        write(
+            &storage.join("session/proj_1/ses_ok.json"),
+            r#"{"id":"ses_ok"}"#,
+        );
+        let index = SessionIndex::new(&storage);
+        assert!(index.lookup_by_id("broken").is_none());
+        assert_eq!(index.lookup_by_id("ses_ok").unwrap().project_dir, None);
+    }
+
+    #[test]
+    fn negative_lookups_are_cached_until_ttl_or_watch_event() {
+        let (_temp, storage) = fixture();
+        let index = SessionIndex::new(&storage).with_negative_ttl(Duration::from_secs(60));
+        assert!(index.lookup_by_id("ses_new").is_none());
+
+        let new_file = storage.join("session/proj_1/ses_new.json");
+        write(&new_file, r#"{"id":"ses_new","projectID":"proj_1"}"#);
+        assert!(
+            index.lookup_by_id("ses_new").is_none(),
+            "miss is cached within TTL"
+        );
+
+        index.handle_watch_event(&WatchEvent::FileCreated(new_file));
+        assert_eq!(index.lookup_by_id("ses_new").unwrap().id, "ses_new");
+    }
+
+    #[test]
+    fn expired_negative_lookup_rereads_storage() {
+        let (_temp, storage) = fixture();
+        let index = SessionIndex::new(&storage).with_negative_ttl(Duration::ZERO);
+        assert!(index.lookup_by_id("ses_late").is_none());
+
+        write(
+            &storage.join("session/proj_1/ses_late.json"),
+            r#"{"id":"ses_late"}"#,
+        );
+        assert!(index.lookup_by_id("ses_late").is_some());
+    }
+
+    #[test]
+    fn watch_event_invalidates_stale_entries() {
+        let (_temp, storage) = fixture();
+        let index = SessionIndex::new(&storage);
+        let session_file = storage.join("session/proj_1/ses_a.json");
+        assert_eq!(
+            index.lookup_by_id("ses_a").unwrap().title.as_deref(),
+            Some("Refactor")
+        );
+
+        write(
+            &session_file,
+            r#"{"id":"ses_a","projectID":"proj_1","title":"Renamed"}"#,
+        );
+        index.handle_watch_event(&WatchEvent::FileModified(session_file.clone()));
+        assert_eq!(
+            index.lookup_by_id("ses_a").unwrap().title.as_deref(),
+            Some("Renamed")
+        );
+
+        fs::remove_file(&session_file).unwrap();
+        index.handle_watch_event(&WatchEvent::FileDeleted(session_file.clone()));
+        assert!(index.lookup_by_path(&session_file).is_none());
+    }
+
+    #[test]
+    fn ignores_watch_events_outside_metadata() {
+        let (_temp, storage) = fixture();
+        let index = SessionIndex::new(&storage);
+        assert!(index.lookup_by_id("ses_a").is_some());
+
+        let session_file = storage.join("session/proj_1/ses_a.json");
+        write(&session_file, r#"{"id":"ses_a","title":"Changed"}"#);
+        index.handle_watch_event(&WatchEvent::FileModified(
+            storage.join("message/ses_a/msg_1.json"),
+        ));
+
+        assert_eq!(
+            index.lookup_by_id("ses_a").unwrap().title.as_deref(),
+            Some("Refactor")
+        );
+    }
+}
@@ -1,28 +1,40 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{
     Arc,
     atomic::{AtomicBool, Ordering},
 };
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
+use notify::event::CreateKind;
 use notify::{
-    Config as NotifyConfig, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+    Config as NotifyConfig, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode,
+    Watcher,
 };
 use notify_debouncer_full::{
-    DebounceEventResult, DebouncedEvent, Debouncer, FileIdCache, new_debouncer,
+    DebounceEventResult, DebouncedEvent, Debouncer, FileIdCache, RecommendedCache, new_debouncer,
+    new_debouncer_opt,
 };
 use tokio::sync::mpsc;
 use tokio::time::MissedTickBehavior;
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, trace, warn};
 
+use crate::monitor::debounce::{self, ADJUST_INTERVAL, AdaptiveDebounce, DebounceSettings};
 use crate::monitor::events::{WatchEvent, WatchEventReceiver, WatchEventSender};
+use crate::monitor::filter::SharedWatchFilter;
+use crate::monitor::scan::{RecentScan, WatchPlan};
+use crate::telemetry::Metrics;
 
 const DEFAULT_SESSION_DIR: &str = ".opencode";
 const DEFAULT_DEBOUNCE_MS: u64 = 100;
 const WATCH_RETRY_ATTEMPTS: usize = 3;
 const WATCH_RETRY_DELAY_MS: u64 = 200;
+/// Poll interval after falling back from native watches, unless
+/// `monitoring.poll_interval_secs` is set.
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+/// How often an activity-window watch re-checks which subdirectories are active.
+const REPLAN_INTERVAL: Duration = Duration::from_secs(300);
 
 #[derive(Debug, thiserror::Error)]
 pub enum WatcherError {
@@ -39,6 +51,17 @@ pub enum WatcherError {
     AlreadyRunning,
 }
 
+impl WatcherError {
+    /// The OS ran out of watch descriptors (inotify `max_user_watches`).
+    pub fn is_watch_limit(&self) -> bool {
+        matches!(self, Self::Notify(err) if is_watch_limit(err))
+    }
+}
+
+fn is_watch_limit(err: &notify::Error) -> bool {
+    matches!(err.kind, notify::ErrorKind::MaxFilesWatch)
+}
+
 /// Access to watcher configuration from daemon state.
 pub trait WatcherStateAccess: Send + Sync {
     fn session_dir(&self) -> PathBuf;
@@ -47,17 +70,37 @@ pub trait WatcherStateAccess: Send + Sync {
 
 pub struct SessionWatcher {
     session_dir: PathBuf,
-    debounce: Duration,
+    settings: WatchSettings,
+    filter: SharedWatchFilter,
     running: Arc<AtomicBool>,
 }
 
+/// Timing and coverage of the watch, fixed when the watcher starts.
+#[derive(Debug, Clone, Copy)]
+struct WatchSettings {
+    debounce: DebounceSettings,
+    active_window: Option<Duration>,
+    poll_interval: Duration,
+}
+
+impl WatchSettings {
+    fn new(debounce: Duration) -> Self {
+        Self {
+            debounce: DebounceSettings::fixed(debounce),
+            active_window: None,
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+        }
+    }
+}
+
 impl SessionWatcher {
     /// Create a new SessionWatcher with the default session directory (~/.opencode/).
     pub fn new() -> Self {
         let session_dir = default_session_dir();
         Self {
             session_dir,
-            debounce: Duration::from_millis(DEFAULT_DEBOUNCE_MS),
+            settings: WatchSettings::new(Duration::from_millis(DEFAULT_DEBOUNCE_MS)),
+            filter: SharedWatchFilter::default(),
             running: Arc::new(AtomicBool::new(false)),
         }
     }
@@ -66,7 +109,8 @@ impl SessionWatcher {
     pub fn with_path(path: PathBuf) -> Self {
         Self {
             session_dir: path,
-            debounce: Duration::from_millis(DEFAULT_DEBOUNCE_MS),
+            settings: WatchSettings::new(Duration::from_millis(DEFAULT_DEBOUNCE_MS)),
+            filter: SharedWatchFilter::default(),
             running: Arc::new(AtomicBool::new(false)),
         }
     }
@@ -75,14 +119,41 @@ impl SessionWatcher {
     pub fn from_state<S: WatcherStateAccess>(state: &S) -> Self {
         Self {
             session_dir: state.session_dir(),
-            debounce: state.debounce_duration(),
+            settings: WatchSettings::new(state.debounce_duration()),
+            filter: SharedWatchFilter::default(),
             running: Arc::new(AtomicBool::new(false)),
         }
     }
 
     /// Set custom debounce duration.
     pub fn with_debounce(mut self, duration: Duration) -> Self {
-        self.debounce = duration;
+        self.settings.debounce = DebounceSettings::fixed(duration);
+        self
+    }
+
+    /// Set the debounce mode, bounds and delay warning.
+    pub fn with_debounce_settings(mut self, settings: DebounceSettings) -> Self {
+        self.settings.debounce = settings;
+        self
+    }
+
+    /// Recursively watch only subdirectories modified within `window`; the
+    /// session directory itself is watched for new subdirectories. `None`
+    /// watches the whole tree.
+    pub fn with_active_window(mut self, window: Option<Duration>) -> Self {
+        self.settings.active_window = window;
+        self
+    }
+
+    /// Poll interval used if the OS watch limit forces the polling backend.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.settings.poll_interval = interval;
+        self
+    }
+
+    /// Filter deciding which events are forwarded; shared so it can be swapped on reload.
+    pub fn with_filter(mut self, filter: SharedWatchFilter) -> Self {
+        self.filter = filter;
         self
     }
 
@@ -99,12 +170,13 @@ impl SessionWatcher {
 
         let (tx, rx) = mpsc::channel(100);
         let session_dir = self.session_dir.clone();
-        let debounce = self.debounce;
+        let settings = self.settings;
+        let filter = self.filter.clone();
         let running = Arc::clone(&self.running);
 
         tokio::spawn(async move {
             let _guard = RunningGuard::new(running);
-            if let Err(err) = run_watcher_task(session_dir, debounce, tx, cancel).await {
+            if let Err(err) = run_watcher_task(session_dir, settings, filter, tx, cancel).await {
                 error!(error = %err, "Watcher task failed");
             }
         });
@@ -143,7 +215,8 @@ fn default_session_dir() -> PathBuf {
 
 async fn run_watcher_task(
     session_dir: PathBuf,
-    debounce: Duration,
+    settings: WatchSettings,
+    filter: SharedWatchFilter,
     tx: WatchEventSender,
     cancel: CancellationToken,
 ) -> Result<(), WatcherError> {
@@ -152,7 +225,7 @@ async fn run_watcher_task(
         wait_for_directory_creation(&session_dir, &tx, cancel.clone()).await?;
     }
 
-    start_watching(session_dir, debounce, tx, cancel).await
+    start_watching(session_dir, settings, filter, tx, cancel).await
 }
 
 async fn wait_for_directory_creation(
@@ -223,34 +296,173 @@ async fn wait_for_directory_creation(
 
 async fn start_watching(
     session_dir: PathBuf,
-    debounce: Duration,
+    settings: WatchSettings,
+    filter: SharedWatchFilter,
     tx: WatchEventSender,
     cancel: CancellationToken,
+) -> Result<(), WatcherError> {
+    match watch_natively(&session_dir, &settings, &filter, &tx, &cancel).await {
+        Err(err) if err.is_watch_limit() => {
+            warn!(
+                path = %session_dir.display(),
+                poll_interval_secs = settings.poll_interval.as_secs(),
+                "File watch limit reached; falling back to polling. Raise \
+                 fs.inotify.max_user_watches or set monitoring.active_window_hours"
+            );
+            watch_by_polling(&session_dir, &settings, &filter, &tx, &cancel).await
+        }
+        result => result,
+    }
+}
+
+/// Watch with the platform backend, following the [`WatchPlan`].
+async fn watch_natively(
+    session_dir: &Path,
+    settings: &WatchSettings,
+    filter: &SharedWatchFilter,
+    tx: &WatchEventSender,
+    cancel: &CancellationToken,
 ) -> Result<(), WatcherError> {
     let (debounce_tx, mut debounce_rx) = mpsc::channel(128);
-    let mut debouncer = new_debouncer(debounce, None, move |result: DebounceEventResult| {
+    let mut debouncer = new_debouncer(settings.debounce.notify_timeout(), None, move |result| {
         let _ = debounce_tx.blocking_send(result);
     })?;
 
-    watch_debouncer_with_retry(&mut debouncer, &session_dir, RecursiveMode::Recursive).await?;
-    info!(path = %session_dir.display(), "Started watching session directory");
+    let started = Instant::now();
+    let plan = WatchPlan::new(
+        session_dir,
+        &filter.snapshot(),
+        settings.active_window,
+        SystemTime::now(),
+    );
+    let mut watched = HashSet::new();
+    for dir in &plan.shallow {
+        watch_debouncer_with_retry(&mut debouncer, dir, RecursiveMode::NonRecursive).await?;
+    }
+    for dir in &plan.recursive {
+        watch_debouncer_with_retry(&mut debouncer, dir, RecursiveMode::Recursive).await?;
+        watched.insert(dir.clone());
+    }
+    publish_watch_setup(&plan, started.elapsed());
+    info!(
+        path = %session_dir.display(),
+        recursive = plan.recursive.len(),
+        windowed = plan.is_windowed(),
+        "Started watching session directory"
+    );
+
+    let mut debounce_buffer = EventBuffer::default();
+    let mut flush = FlushSchedule::new(&settings.debounce);
+    let mut replan = tokio::time::interval(REPLAN_INTERVAL);
+    replan.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    replan.reset();
 
-    let mut debounce_buffer: HashMap<PathBuf, EventKind> = HashMap::new();
-    let mut interval = tokio::time::interval(debounce);
-    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("File watcher shutting down");
+                flush_buffer(&mut debounce_buffer, &settings.debounce, tx).await;
+                break;
+            }
+            Some(result) = debounce_rx.recv() => {
+                if let Err(errors) = &result {
+                    if errors.iter().any(is_watch_limit) {
+                        flush_buffer(&mut debounce_buffer, &settings.debounce, tx).await;
+                        return Err(notify::Error::new(notify::ErrorKind::MaxFilesWatch).into());
+                    }
+                }
+                if plan.is_windowed() {
+                    for dir in new_subdirectories(&result, session_dir) {
+                        if watched.contains(&dir) {
+                            continue;
+                        }
+                        if let Err(err) = debouncer.watch(&dir, RecursiveMode::Recursive) {
+                            flush_buffer(&mut debounce_buffer, &settings.debounce, tx).await;
+                            return Err(err.into());
+                        }
+                        debug!(path = %dir.display(), "Watching new session subdirectory");
+                        buffer_existing_files(&mut debounce_buffer, &dir, filter, session_dir);
+                        watched.insert(dir);
+                    }
+                }
+                handle_debounce_result(result, &mut debounce_buffer, filter, session_dir, tx).await;
+            }
+            _ = flush.interval.tick() => {
+                flush_buffer(&mut debounce_buffer, &settings.debounce, tx).await;
+            }
+            _ = flush.adjust.tick(), if flush.controller.is_some() => {
+                flush.adjust(&mut debounce_buffer);
+            }
+            _ = replan.tick(), if plan.is_windowed() => {
+                let started = Instant::now();
+                let current = WatchPlan::new(
+                    session_dir,
+                    &filter.snapshot(),
+                    settings.active_window,
+                    SystemTime::now(),
+                );
+                for dir in current.recursive.iter().filter(|dir| !watched.contains(*dir)) {
+                    debouncer.watch(dir, RecursiveMode::Recursive)?;
+                    debug!(path = %dir.display(), "Session subdirectory became active; watching");
+                }
+                for dir in watched.iter().filter(|dir| !current.recursive.contains(*dir)) {
+                    let _ = debouncer.unwatch(dir);
+                    debug!(path = %dir.display(), "Session subdirectory went quiet; unwatching");
+                }
+                watched = current.recursive.iter().cloned().collect();
+                publish_watch_setup(&current, started.elapsed());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Watch the whole tree by polling, for when watch descriptors ran out.
+async fn watch_by_polling(
+    session_dir: &Path,
+    settings: &WatchSettings,
+    filter: &SharedWatchFilter,
+    tx: &WatchEventSender,
+    cancel: &CancellationToken,
+) -> Result<(), WatcherError> {
+    let (debounce_tx, mut debounce_rx) = mpsc::channel(128);
+    let mut debouncer = new_debouncer_opt::<_, PollWatcher, _>(
+        settings.debounce.notify_timeout(),
+        None,
+        move |result| {
+            let _ = debounce_tx.blocking_send(result);
+        },
+        RecommendedCache::new(),
+        NotifyConfig::default().with_poll_interval(settings.poll_interval),
+    )?;
+    let started = Instant::now();
+    watch_debouncer_with_retry(&mut debouncer, session_dir, RecursiveMode::Recursive).await?;
+    if let Some(metrics) = Metrics::global() {
+        metrics.record_session_scan("watch_setup", started.elapsed());
+        metrics.set_watched_directories(0);
+        metrics.set_watch_polling(true);
+    }
+    info!(path = %session_dir.display(), "Started polling session directory");
+
+    let mut debounce_buffer = EventBuffer::default();
+    let mut flush = FlushSchedule::new(&settings.debounce);
 
     loop {
         tokio::select! {
             _ = cancel.cancelled() => {
                 info!("File watcher shutting down");
-                flush_buffer(&mut debounce_buffer, &tx).await;
+                flush_buffer(&mut debounce_buffer, &settings.debounce, tx).await;
                 break;
             }
             Some(result) = debounce_rx.recv() => {
-                handle_debounce_result(result, &mut debounce_buffer, &tx).await;
+                handle_debounce_result(result, &mut debounce_buffer, filter, session_dir, tx).await;
+            }
+            _ = flush.interval.tick() => {
+                flush_buffer(&mut debounce_buffer, &settings.debounce, tx).await;
             }
-            _ = interval.tick() => {
-                flush_buffer(&mut debounce_buffer, &tx).await;
+            _ = flush.adjust.tick(), if flush.controller.is_some() => {
+                flush.adjust(&mut debounce_buffer);
             }
         }
     }
@@ -258,15 +470,61 @@ async fn start_watching(
     Ok(())
 }
 
+fn publish_watch_setup(plan: &WatchPlan, elapsed: Duration) {
+    let Some(metrics) = Metrics::global() else {
+        return;
+    };
+    metrics.record_session_scan("watch_setup", elapsed);
+    metrics.set_watched_directories(plan.descriptor_count());
+    metrics.set_watch_polling(false);
+}
+
+/// Directories created directly under `session_dir`.
+fn new_subdirectories(result: &DebounceEventResult, session_dir: &Path) -> Vec<PathBuf> {
+    let Ok(events) = result else {
+        return Vec::new();
+    };
+    events
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::Create(CreateKind::Folder)))
+        .flat_map(|event| event.paths.iter())
+        .filter(|path| path.parent() == Some(session_dir))
+        .cloned()
+        .collect()
+}
+
+/// Queue creation events for files written to a new subdirectory before its
+/// watch was in place.
+fn buffer_existing_files(
+    buffer: &mut EventBuffer,
+    dir: &Path,
+    filter: &SharedWatchFilter,
+    session_dir: &Path,
+) {
+    let snapshot = filter.snapshot();
+    let outcome = RecentScan::new(dir, &snapshot).run();
+    for file in outcome.files {
+        if snapshot.allows(session_dir, &file.path, false) {
+            buffer.insert(
+                file.path,
+                EventKind::Create(CreateKind::File),
+                Instant::now(),
+            );
+        }
+    }
+}
+
 async fn handle_debounce_result(
     result: DebounceEventResult,
-    buffer: &mut HashMap<PathBuf, EventKind>,
+    buffer: &mut EventBuffer,
+    filter: &SharedWatchFilter,
+    session_dir: &Path,
     tx: &WatchEventSender,
 ) {
     match result {
         Ok(events) => {
             for event in events {
-                buffer_event(buffer, &event);
+                buffer_event(buffer, &event, filter, session_dir);
             }
         }
         Err(errors) => {
@@ -278,20 +536,149 @@ async fn handle_debounce_result(
     }
 }
 
-fn buffer_event(buffer: &mut HashMap<PathBuf, EventKind>, event: &DebouncedEvent) {
+fn buffer_event(
+    buffer: &mut EventBuffer,
+    event: &DebouncedEvent,
+    filter: &SharedWatchFilter,
+    session_dir: &Path,
+) {
     if !is_core_event(&event.kind) {
         return;
     }
 
     for path in &event.paths {
-        buffer.insert(path.clone(), event.kind);
+        if !filter.forwards(session_dir, path, &event.kind) {
+            trace!(path = %path.display(), "Watcher event filtered");
+            continue;
+        }
+        buffer.insert(path.clone(), event.kind, event.time);
     }
 }
 
-async fn flush_buffer(buffer: &mut HashMap<PathBuf, EventKind>, tx: &WatchEventSender) {
-    if buffer.is_empty() {
-        return;
+/// Events waiting for the next flush, keyed by path.
+#[derive(Debug, Default)]
+struct EventBuffer {
+    events: HashMap<PathBuf, Buffered>,
+    /// Raw events buffered since the last rate sample.
+    received: usize,
+}
+
+#[derive(Debug)]
+struct Buffered {
+    kind: EventKind,
+    /// When the first event for the path since the last flush happened.
+    since: Instant,
+}
+
+impl EventBuffer {
+    /// Keep the latest kind for `path` and the time of its earliest event.
+    fn insert(&mut self, path: PathBuf, kind: EventKind, at: Instant) {
+        self.received += 1;
+        self.events
+            .entry(path)
+            .and_modify(|buffered| {
+                buffered.kind = kind;
+                buffered.since = buffered.since.min(at);
+            })
+            .or_insert(Buffered { kind, since: at });
+    }
+
+    /// Age of the oldest buffered event at `now`.
+    fn oldest_age(&self, now: Instant) -> Option<Duration> {
+        self.events
+            .values()
+            .map(|buffered| now.saturating_duration_since(buffered.since))
+            .max()
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = (PathBuf, EventKind)> + '_ {
+        self.events
+            .drain()
+            .map(|(path, buffered)| (path, buffered.kind))
+    }
+
+    /// Raw event count since the previous call.
+    fn take_received(&mut self) -> usize {
+        std::mem::take(&mut self.received)
+    }
+}
+
+/// Flush timer, retuned by the adaptive controller without touching the
+/// notify debouncer.
+struct FlushSchedule {
+    window: Duration,
+    interval: tokio::time::Interval,
+    adjust: tokio::time::Interval,
+    controller: Option<AdaptiveDebounce>,
+}
+
+impl FlushSchedule {
+    fn new(settings: &DebounceSettings) -> Self {
+        let mut adjust = tokio::time::interval(ADJUST_INTERVAL);
+        adjust.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        adjust.reset();
+        debounce::publish_window(settings.window);
+        Self {
+            window: settings.window,
+            interval: flush_interval(settings.window),
+            adjust,
+            controller: settings.controller(),
+        }
+    }
+
+    /// Feed the last period's event count to the controller and move the
+    /// flush interval if it picks a new window.
+    fn adjust(&mut self, buffer: &mut EventBuffer) {
+        let Some(controller) = self.controller.as_mut() else {
+            return;
+        };
+        controller.observe(buffer.take_received(), ADJUST_INTERVAL);
+        let next = controller.next_window(self.window);
+        if next == self.window {
+            return;
+        }
+        debug!(
+            from_ms = self.window.as_millis() as u64,
+            to_ms = next.as_millis() as u64,
+            rate = controller.rate(),
+            "Adjusted debounce window"
+        );
+        self.window = next;
+        self.interval = flush_interval(next);
+        self.interval.reset();
+        debounce::publish_window(next);
+    }
+}
+
+fn flush_interval(window: Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(window);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+/// Record how long the oldest flushed event waited, warning past the
+/// configured threshold.
+fn record_flush_delay(metrics: Option<&Metrics>, delay: Duration, warn_after: Option<Duration>) {
+    if let Some(metrics) = metrics {
+        metrics.record_debounce_delay(delay);
+    }
+    if warn_after.is_some_and(|limit| delay > limit) {
+        warn!(
+            delay_ms = delay.as_millis() as u64,
+            "Debouncing delayed a file event; consider a shorter monitoring.debounce_max_ms"
+        );
     }
+}
+
+async fn flush_buffer(
+    buffer: &mut EventBuffer,
+    settings: &DebounceSettings,
+    tx: &WatchEventSender,
+) {
+    let Some(delay) = buffer.oldest_age(Instant::now()) else {
+        return;
+    };
+    record_flush_delay(Metrics::global().as_deref(), delay, settings.warn_after);
 
     for (path, kind) in buffer.drain() {
         if let Some(event) = map_event(kind, path) {
@@ -336,6 +723,7 @@ async fn watch_with_retry<W: Watcher>(
     for attempt in 0..=WATCH_RETRY_ATTEMPTS {
         match watcher.watch(path, mode) {
             Ok(()) => return Ok(()),
+            Err(err) if is_watch_limit(&err) => return Err(err.into()),
             Err(err) => {
                 last_error = Some(err);
                 if attempt < WATCH_RETRY_ATTEMPTS {
@@ -362,6 +750,7 @@ async fn watch_debouncer_with_retry<T: Watcher, C: FileIdCache>(
     for attempt in 0..=WATCH_RETRY_ATTEMPTS {
         match debouncer.watch(path, mode) {
             Ok(()) => return Ok(()),
+            Err(err) if is_watch_limit(&err) => return Err(err.into()),
             Err(err) => {
                 last_error = Some(err);
                 if attempt < WATCH_RETRY_ATTEMPTS {
@@ -382,6 +771,7 @@ async fn watch_debouncer_with_retry<T: Watcher, C: FileIdCache>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::monitor::filter::WatchFilter;
 
     #[test]
     fn test_map_event_skips_access_events() {
@@ -412,15 +802,128 @@ mod tests {
 
     #[test]
     fn test_buffer_event_tracks_latest_kind() {
-        let mut buffer = HashMap::new();
+        let mut buffer = EventBuffer::default();
         let event = DebouncedEvent::new(
             Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
                 .add_path(PathBuf::from("/tmp/file.txt")),
             std::time::Instant::now(),
         );
 
-        buffer_event(&mut buffer, &event);
-        assert_eq!(buffer.len(), 1);
-        assert!(matches!(buffer.values().next(), Some(EventKind::Modify(_))));
+        let filter = SharedWatchFilter::new(WatchFilter::new::<&str>(&[], &[]));
+        buffer_event(&mut buffer, &event, &filter, Path::new("/tmp"));
+        assert_eq!(buffer.events.len(), 1);
+        assert!(matches!(
+            buffer.events.values().next(),
+            Some(Buffered {
+                kind: EventKind::Modify(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_buffer_event_drops_filtered_paths() {
+        let mut buffer = EventBuffer::default();
+        let session_dir = Path::new("/home/u/.opencode");
+        let event = DebouncedEvent::new(
+            Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+                .add_path(session_dir.join("session.md"))
+                .add_path(session_dir.join("session.md.swp"))
+                .add_path(session_dir.join("repo/.git/index"))
+                .add_path(session_dir.join("notes.txt")),
+            std::time::Instant::now(),
+        );
+        let filter = SharedWatchFilter::default();
+
+        buffer_event(&mut buffer, &event, &filter, session_dir);
+        assert_eq!(buffer.events.len(), 1);
+        assert!(buffer.events.contains_key(&session_dir.join("session.md")));
+        assert_eq!(filter.filtered_count(), 3);
+    }
+
+    #[test]
+    fn test_buffer_event_keeps_session_dir_creation() {
+        let mut buffer = EventBuffer::default();
+        let session_dir = Path::new("/home/u/.opencode");
+        let event = DebouncedEvent::new(
+            Event::new(EventKind::Create(notify::event::CreateKind::Folder))
+                .add_path(session_dir.to_path_buf()),
+            std::time::Instant::now(),
+        );
+        let filter = SharedWatchFilter::new(WatchFilter::new(&["md"], &["**", "*.opencode"]));
+
+        buffer_event(&mut buffer, &event, &filter, session_dir);
+        let (path, kind) = buffer.drain().next().expect("session dir event");
+        assert!(matches!(
+            map_event(kind, path),
+            Some(WatchEvent::DirectoryCreated(dir)) if dir == session_dir
+        ));
+    }
+
+    #[test]
+    fn test_buffer_keeps_earliest_event_time() {
+        let start = Instant::now();
+        let path = PathBuf::from("/tmp/session.md");
+        let mut buffer = EventBuffer::default();
+        buffer.insert(path.clone(), EventKind::Create(CreateKind::File), start);
+        buffer.insert(
+            path.clone(),
+            EventKind::Modify(notify::event::ModifyKind::Any),
+            start + Duration::from_millis(300),
+        );
+
+        assert_eq!(
+            buffer.oldest_age(start + Duration::from_millis(500)),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(buffer.take_received(), 2);
+        assert_eq!(buffer.take_received(), 0);
+        let (drained, kind) = buffer.drain().next().expect("event");
+        assert_eq!(drained, path);
+        assert!(matches!(kind, EventKind::Modify(_)));
+        assert_eq!(buffer.oldest_age(start), None);
+    }
+
+    #[test]
+    fn test_flush_delays_fill_histogram() {
+        let metrics = Metrics::new();
+        let start = Instant::now();
+        for (offset_ms, flushed_ms) in [(0, 80), (0, 120), (40, 400), (0, 3000)] {
+            let mut buffer = EventBuffer::default();
+            buffer.insert(
+                PathBuf::from("/tmp/a.md"),
+                EventKind::Create(CreateKind::File),
+                start + Duration::from_millis(offset_ms),
+            );
+            buffer.insert(
+                PathBuf::from("/tmp/b.md"),
+                EventKind::Create(CreateKind::File),
+                start + Duration::from_millis(offset_ms + 20),
+            );
+            let delay = buffer
+                .oldest_age(start + Duration::from_millis(flushed_ms))
+                .expect("delay");
+            record_flush_delay(Some(&metrics), delay, Some(Duration::from_secs(2)));
+        }
+
+        let output = metrics.encode().expect("encode");
+        let bucket = |le: &str| {
+            let label = format!("le=\"{le}\"");
+            output
+                .lines()
+                .find(|line| {
+                    line.starts_with("palingenesis_debounce_delay_seconds_bucket{")
+                        && line.contains(&label)
+                })
+                .and_then(|line| line.rsplit(' ').next())
+                .unwrap_or_else(|| panic!("missing bucket {le}"))
+                .to_string()
+        };
+        assert_eq!(bucket("0.1"), "1");
+        assert_eq!(bucket("0.25"), "2");
+        assert_eq!(bucket("0.5"), "3");
+        assert_eq!(bucket("2.0"), "3");
+        assert_eq!(bucket("5.0"), "4");
+        assert!(output.contains("palingenesis_debounce_delay_seconds_count"));
     }
 }
@@ -0,0 +1,449 @@
+//! Incident acknowledgment (`palingenesis ack`, `GET /api/v1/ack/<token>`).
+//!
+//! Failure and attention notifications carry an [`AckLink`] for the session's
+//! open incident. Acknowledging it silences further non-critical
+//! notifications for that incident; the resume-loop give-up and critical
+//! events still go out. The incident closes when the session resumes
+//! successfully or completes, or after `notifications.ack_ttl_secs`, and its
+//! link stops working then.
+//!
+//! Link tokens are 256 random bits and the only credential the ack endpoint
+//! takes, so each one works once.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::config::schema::NotificationsConfig;
+use crate::i18n::{Locale, render};
+use crate::resume::postmortem::sanitize_stem;
+use crate::state::{EscalationResolution, SessionStatus, StateHandle};
+
+/// Route of the ack endpoint; the token follows as the last segment.
+pub const ACK_ROUTE: &str = "/api/v1/ack";
+/// How long an unresolved incident can be acknowledged.
+pub const DEFAULT_ACK_TTL: Duration = Duration::from_secs(24 * 3600);
+
+const TAIL_SUFFIX: &str = ".tail.txt";
+
+static GLOBAL_REGISTRY: OnceLock<Arc<AckRegistry>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AckError {
+    #[error("Acknowledgment link is invalid or was already used")]
+    UnknownToken,
+
+    #[error("No open incident {id}")]
+    UnknownIncident { id: String },
+
+    #[error("Incident {id} has expired")]
+    Expired { id: String },
+}
+
+/// Acknowledgment handle attached to a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AckLink {
+    /// Argument for `palingenesis ack`.
+    pub incident_id: String,
+    /// One-time link to the ack endpoint, when `notifications.ack_base_url` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl AckLink {
+    /// Line appended to text notifications.
+    pub fn notice(&self, locale: Locale) -> String {
+        match &self.url {
+            Some(url) => render(
+                locale,
+                "notice.ack_link",
+                &[("url", url), ("incident", &self.incident_id)],
+            ),
+            None => render(locale, "notice.ack", &[("incident", &self.incident_id)]),
+        }
+    }
+}
+
+/// An incident that can be, or has been, acknowledged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AckIncident {
+    pub id: String,
+    pub session_path: PathBuf,
+    pub opened_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+struct OpenIncident {
+    incident: AckIncident,
+    /// Unused link token; taken by the first acknowledgment.
+    token: Option<String>,
+}
+
+/// Open incidents, one per session.
+#[derive(Debug)]
+pub struct AckRegistry {
+    ttl: Duration,
+    incidents: Mutex<HashMap<PathBuf, OpenIncident>>,
+}
+
+impl Default for AckRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACK_TTL)
+    }
+}
+
+impl AckRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            incidents: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &NotificationsConfig) -> Self {
+        Self::new(Duration::from_secs(config.ack_ttl_secs))
+    }
+
+    /// When an incident opened at `opened_at` expires; never, for a TTL
+    /// past the end of time.
+    fn expiry(&self, opened_at: DateTime<Utc>) -> DateTime<Utc> {
+        TimeDelta::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| opened_at.checked_add_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// The process-wide registry, created from `config` on first use.
+    pub fn global_or_init(config: &NotificationsConfig) -> Arc<AckRegistry> {
+        Arc::clone(GLOBAL_REGISTRY.get_or_init(|| Arc::new(Self::from_config(config))))
+    }
+
+    /// The process-wide registry, if notifications created one.
+    pub fn global() -> Option<Arc<AckRegistry>> {
+        GLOBAL_REGISTRY.get().cloned()
+    }
+
+    /// The session's open incident and its unused link token, opening an
+    /// incident if none is open.
+    ///
+    /// `snapshot` is the stop's transcript tail (`incidents/<id>.tail.txt`);
+    /// its id names the incident when present. Returns `None` once the
+    /// incident is acknowledged: there is nothing left to link to.
+    pub fn issue(
+        &self,
+        session_path: &Path,
+        snapshot: Option<&Path>,
+        now: DateTime<Utc>,
+    ) -> Option<(AckIncident, String)> {
+        let mut incidents = self.incidents.lock().ok()?;
+        let expired = incidents
+            .get(session_path)
+            .is_some_and(|open| open.incident.expires_at <= now);
+        if expired {
+            incidents.remove(session_path);
+        }
+        let open = incidents
+            .entry(session_path.to_path_buf())
+            .or_insert_with(|| OpenIncident {
+                incident: AckIncident {
+                    id: incident_id(session_path, snapshot, now),
+                    session_path: session_path.to_path_buf(),
+                    opened_at: now,
+                    expires_at: self.expiry(now),
+                    acknowledged_at: None,
+                },
+                token: Some(new_token()),
+            });
+        let token = open.token.clone()?;
+        Some((open.incident.clone(), token))
+    }
+
+    /// Track an incident opened before a restart under its original id, with
+    /// a fresh link token. Does nothing if the session already has one open.
+    pub fn reopen(&self, id: &str, session_path: &Path, opened_at: DateTime<Utc>) {
+        let Ok(mut incidents) = self.incidents.lock() else {
+            return;
+        };
+        incidents
+            .entry(session_path.to_path_buf())
+            .or_insert_with(|| OpenIncident {
+                incident: AckIncident {
+                    id: id.to_string(),
+                    session_path: session_path.to_path_buf(),
+                    opened_at,
+                    expires_at: self.expiry(opened_at),
+                    acknowledged_at: None,
+                },
+                token: Some(new_token()),
+            });
+    }
+
+    /// Acknowledge the incident a link token was issued for; the token is
+    /// spent either way.
+    pub fn acknowledge_token(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<AckIncident, AckError> {
+        let mut incidents = self.incidents.lock().map_err(|_| AckError::UnknownToken)?;
+        let session = incidents
+            .iter()
+            .find(|(_, open)| open.token.as_deref() == Some(token))
+            .map(|(session, _)| session.clone())
+            .ok_or(AckError::UnknownToken)?;
+        Self::acknowledge_open(&mut incidents, &session, now)
+    }
+
+    /// Acknowledge an incident by id (`palingenesis ack <incident-id>`).
+    pub fn acknowledge(&self, id: &str, now: DateTime<Utc>) -> Result<AckIncident, AckError> {
+        let unknown = || AckError::UnknownIncident { id: id.to_string() };
+        let mut incidents = self.incidents.lock().map_err(|_| unknown())?;
+        let session = incidents
+            .iter()
+            .find(|(_, open)| open.incident.id == id)
+            .map(|(session, _)| session.clone())
+            .ok_or_else(unknown)?;
+        Self::acknowledge_open(&mut incidents, &session, now)
+    }
+
+    fn acknowledge_open(
+        incidents: &mut HashMap<PathBuf, OpenIncident>,
+        session: &Path,
+        now: DateTime<Utc>,
+    ) -> Result<AckIncident, AckError> {
+        let Some(open) = incidents.get_mut(session) else {
+            return Err(AckError::UnknownToken);
+        };
+        if open.incident.expires_at <= now {
+            let id = open.incident.id.clone();
+            incidents.remove(session);
+            return Err(AckError::Expired { id });
+        }
+        open.token = None;
+        open.incident.acknowledged_at.get_or_insert(now);
+        Ok(open.incident.clone())
+    }
+
+    /// Whether the session's open incident has been acknowledged.
+    pub fn is_acknowledged(&self, session_path: &Path, now: DateTime<Utc>) -> bool {
+        self.incidents.lock().is_ok_and(|incidents| {
+            incidents.get(session_path).is_some_and(|open| {
+                open.incident.acknowledged_at.is_some() && open.incident.expires_at > now
+            })
+        })
+    }
+
+    /// Close the session's incident; its link stops working.
+    pub fn resolve(&self, session_path: &Path) -> Option<AckIncident> {
+        let mut incidents = self.incidents.lock().ok()?;
+        incidents.remove(session_path).map(|open| open.incident)
+    }
+
+    /// When the oldest incident still open at `now` began.
+    pub fn oldest_open(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let incidents = self.incidents.lock().ok()?;
+        incidents
+            .values()
+            .filter(|open| open.incident.expires_at > now)
+            .map(|open| open.incident.opened_at)
+            .min()
+    }
+}
+
+/// Record an acknowledgment in the session's resume history and end its
+/// escalation; with `pause_resume` the session is also held for attention,
+/// which stops automatic resumes until `palingenesis attention clear`.
+pub fn record_acknowledgment(state: &StateHandle, incident: &AckIncident, pause_resume: bool) {
+    let acknowledged_at = incident.acknowledged_at.unwrap_or_else(Utc::now);
+    let path = incident.session_path.clone();
+    let result = state.update_critical(|state| {
+        state.resolve_escalation(&path, EscalationResolution::Acknowledged, acknowledged_at);
+        let history = state.resume_history_mut(&path);
+        history.acknowledged_at = Some(acknowledged_at);
+        if pause_resume {
+            history.resumes_paused_at.get_or_insert(acknowledged_at);
+            if let Some(session) = state
+                .current_session
+                .as_mut()
+                .filter(|session| session.path == path)
+            {
+                session.status = SessionStatus::NeedsAttention;
+            }
+        }
+    });
+    match result {
+        Ok(()) => info!(
+            incident = %incident.id,
+            session = %incident.session_path.display(),
+            pause_resume,
+            "Incident acknowledged"
+        ),
+        Err(err) => warn!(error = %err, "Failed to record incident acknowledgment"),
+    }
+}
+
+/// `<timestamp>-<stem>`, matching the transcript snapshot's id when there is one.
+pub(crate) fn incident_id(
+    session_path: &Path,
+    snapshot: Option<&Path>,
+    now: DateTime<Utc>,
+) -> String {
+    let from_snapshot = snapshot
+        .and_then(|path| path.file_name())
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(TAIL_SUFFIX))
+        .filter(|id| !id.is_empty());
+    if let Some(id) = from_snapshot {
+        return id.to_string();
+    }
+    let stem = session_path
+        .file_stem()
+        .map(|stem| sanitize_stem(&stem.to_string_lossy()))
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| "session".to_string());
+    format!("{}-{stem}", now.format("%Y%m%dT%H%M%S%.3fZ"))
+}
+
+fn new_token() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_767_225_600 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn huge_ttl_never_expires() {
+        let registry = AckRegistry::new(Duration::from_secs(u64::MAX));
+        let session = Path::new("/work/session.md");
+
+        let (incident, _) = registry.issue(session, None, at(0)).unwrap();
+        assert_eq!(incident.expires_at, DateTime::<Utc>::MAX_UTC);
+
+        let other = Path::new("/work/other.md");
+        registry.reopen("20260101T000000.000Z-other", other, at(0));
+        assert_eq!(
+            registry.resolve(other).map(|incident| incident.expires_at),
+            Some(DateTime::<Utc>::MAX_UTC)
+        );
+    }
+
+    #[test]
+    fn issues_one_incident_per_session() {
+        let registry = AckRegistry::default();
+        let session = Path::new("/work/session.md");
+        let snapshot = Path::new("/state/incidents/20260101T000000.000Z-session.tail.txt");
+
+        let (incident, token) = registry.issue(session, Some(snapshot), at(0)).unwrap();
+        assert_eq!(incident.id, "20260101T000000.000Z-session");
+        assert_eq!(token.len(), 64);
+
+        // Later failures of the same incident reuse the link.
+        let (again, same) = registry.issue(session, None, at(60)).unwrap();
+        assert_eq!(again.id, incident.id);
+        assert_eq!(same, token);
+
+        let (other, other_token) = registry
+            .issue(Path::new("/work/other.md"), None, at(0))
+            .unwrap();
+        assert_eq!(other.id, "20260101T000000.000Z-other");
+        assert_ne!(other_token, token);
+    }
+
+    #[test]
+    fn tokens_are_single_use() {
+        let registry = AckRegistry::default();
+        let session = Path::new("/work/session.md");
+        let (_, token) = registry.issue(session, None, at(0)).unwrap();
+
+        let incident = registry.acknowledge_token(&token, at(10)).unwrap();
+        assert_eq!(incident.acknowledged_at, Some(at(10)));
+        assert!(registry.is_acknowledged(session, at(20)));
+        assert_eq!(
+            registry.acknowledge_token(&token, at(20)),
+            Err(AckError::UnknownToken)
+        );
+        // Nothing left to link to once acknowledged.
+        assert!(registry.issue(session, None, at(30)).is_none());
+    }
+
+    #[test]
+    fn links_expire_with_the_incident() {
+        let registry = AckRegistry::new(Duration::from_secs(3600));
+        let session = Path::new("/work/session.md");
+        let (incident, token) = registry.issue(session, None, at(0)).unwrap();
+
+        assert_eq!(
+            registry.acknowledge_token(&token, at(3600)),
+            Err(AckError::Expired { id: incident.id })
+        );
+
+        let (_, token) = registry.issue(session, None, at(3700)).unwrap();
+        registry.resolve(session);
+        assert_eq!(
+            registry.acknowledge_token(&token, at(3710)),
+            Err(AckError::UnknownToken)
+        );
+    }
+
+    #[test]
+    fn oldest_open_ignores_resolved_and_expired_incidents() {
+        let registry = AckRegistry::new(Duration::from_secs(3600));
+        let first = Path::new("/work/first.md");
+        registry.issue(first, None, at(0)).unwrap();
+        registry
+            .issue(Path::new("/work/second.md"), None, at(600))
+            .unwrap();
+
+        assert_eq!(registry.oldest_open(at(900)), Some(at(0)));
+        assert_eq!(registry.oldest_open(at(3600)), Some(at(600)));
+        registry.resolve(first);
+        assert_eq!(registry.oldest_open(at(900)), Some(at(600)));
+        assert_eq!(registry.oldest_open(at(4200)), None);
+    }
+
+    #[test]
+    fn acknowledges_by_incident_id() {
+        let registry = AckRegistry::default();
+        let session = Path::new("/work/session.md");
+        let (incident, token) = registry.issue(session, None, at(0)).unwrap();
+
+        registry.acknowledge(&incident.id, at(5)).unwrap();
+        assert!(registry.is_acknowledged(session, at(5)));
+        assert_eq!(
+            registry.acknowledge_token(&token, at(6)),
+            Err(AckError::UnknownToken)
+        );
+        assert_eq!(
+            registry.acknowledge("nope", at(6)),
+            Err(AckError::UnknownIncident {
+                id: "nope".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn notice_offers_link_and_cli() {
+        let link = AckLink {
+            incident_id: "20260101T000000.000Z-session".to_string(),
+            url: Some("https://host/api/v1/ack/abc".to_string()),
+        };
+        assert_eq!(
+            link.notice(Locale::En),
+            "Acknowledge: https://host/api/v1/ack/abc (or `palingenesis ack 20260101T000000.000Z-session`)"
+        );
+    }
+}
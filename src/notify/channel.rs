@@ -1,11 +1,72 @@
 use async_trait::async_trait;
 
 use crate::notify::error::NotifyError;
-use crate::notify::events::NotificationEvent;
+use crate::notify::events::{EventSeverity, NotificationEvent};
+use crate::notify::routing::TagRoute;
 
 #[async_trait]
 pub trait NotificationChannel: Send + Sync {
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
     async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError>;
     fn is_enabled(&self) -> bool;
+
+    /// Whether this channel wants `event` (per-target severity/event filters).
+    fn accepts(&self, event: &NotificationEvent) -> bool {
+        let _ = event;
+        true
+    }
+
+    /// Per-target `match_tags`/`exclude_tags`; `None` takes every event.
+    fn tag_route(&self) -> Option<&TagRoute> {
+        None
+    }
+
+    /// Send `event` with an extra note appended to the message.
+    ///
+    /// Used when this channel stands in for a failed primary channel.
+    /// Channels that cannot carry free text fall back to a plain send.
+    async fn send_annotated(
+        &self,
+        event: &NotificationEvent,
+        note: &str,
+    ) -> Result<(), NotifyError> {
+        let _ = note;
+        self.send(event).await
+    }
+
+    /// Re-resolve this channel's secrets (webhook URL, token, headers)
+    /// from their placeholders, returning whether they changed.
+    ///
+    /// Channels without re-resolvable credentials keep what they have.
+    fn refresh_credentials(&self) -> Result<bool, NotifyError> {
+        Ok(false)
+    }
+}
+
+/// Per-target `severities`/`events` filter; empty lists allow everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    severities: Vec<EventSeverity>,
+    events: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn new(severities: &[String], events: &[String]) -> Self {
+        Self {
+            severities: severities
+                .iter()
+                .filter_map(|severity| EventSeverity::parse(severity))
+                .collect(),
+            events: events
+                .iter()
+                .map(|event| event.trim().to_ascii_lowercase())
+                .filter(|event| !event.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn matches(&self, event: &NotificationEvent) -> bool {
+        (self.severities.is_empty() || self.severities.contains(&event.severity()))
+            && (self.events.is_empty() || self.events.iter().any(|name| name == event.event_type()))
+    }
 }
@@ -0,0 +1,474 @@
+//! Notification channel credentials that can be re-resolved while running.
+//!
+//! A channel's webhook URL, ntfy token, webhook headers or webhook signing
+//! secret may come from `${file:...}`, `${keyring:...}` or `${env:...}`
+//! placeholders. Each channel keeps its resolved values in a
+//! [`ChannelCredentials`] registered under the channel's name, and reads
+//! them on every send. Re-resolving a channel (`ROTATE_SECRET <channel>`,
+//! the periodic `notifications.secret_refresh_secs` task, or a 401/403 from
+//! the provider) re-reads only that channel's entry of the config file, so
+//! the other channels and the rest of the config are left alone.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::Paths;
+use crate::config::schema::{
+    DiscordConfig, NotificationsConfig, NtfyConfig, SlackConfig, WebhookConfig,
+};
+use crate::config::secrets::{self, SecretSource};
+use crate::notify::error::NotifyError;
+
+static REGISTRY: LazyLock<CredentialRegistry> = LazyLock::new(CredentialRegistry::default);
+
+/// Which `[notifications.*]` section a channel is configured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Webhook,
+    Ntfy,
+    Discord,
+    Slack,
+}
+
+impl ChannelKind {
+    /// Section key under `[notifications]`, also the default channel name.
+    pub fn section(&self) -> &'static str {
+        match self {
+            Self::Webhook => "webhook",
+            Self::Ntfy => "ntfy",
+            Self::Discord => "discord",
+            Self::Slack => "slack",
+        }
+    }
+}
+
+/// The secret-bearing parts of a channel's config, as resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Credentials {
+    /// Where deliveries are posted: the webhook URL, or ntfy's topic URL.
+    pub url: String,
+    /// ntfy access token.
+    pub token: Option<String>,
+    /// Extra webhook headers.
+    pub headers: Option<HashMap<String, String>>,
+    /// Webhook request signing secret.
+    pub signing_secret: Option<String>,
+}
+
+impl Credentials {
+    pub fn webhook(config: &WebhookConfig) -> Self {
+        Self {
+            url: config.url.clone(),
+            token: None,
+            headers: config.headers.clone(),
+            signing_secret: config.signing_secret.clone(),
+        }
+    }
+
+    pub fn ntfy(config: &NtfyConfig) -> Self {
+        let server = config.server.as_deref().unwrap_or("https://ntfy.sh");
+        Self {
+            url: format!(
+                "{}/{}",
+                server.trim_end_matches('/'),
+                config.topic.trim_start_matches('/')
+            ),
+            token: config.token.clone(),
+            ..Self::default()
+        }
+    }
+
+    pub fn discord(config: &DiscordConfig) -> Self {
+        Self {
+            url: config.webhook_url.clone(),
+            ..Self::default()
+        }
+    }
+
+    pub fn slack(config: &SlackConfig) -> Self {
+        Self {
+            url: config.webhook_url.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Credentials of one channel, with when they were last resolved.
+#[derive(Debug)]
+pub struct ChannelCredentials {
+    name: String,
+    kind: ChannelKind,
+    state: RwLock<Resolved>,
+}
+
+#[derive(Debug)]
+struct Resolved {
+    credentials: Credentials,
+    refreshed_at: DateTime<Utc>,
+    /// Placeholders the last re-resolution found; `None` until the channel
+    /// has been re-resolved from the config file once.
+    sources: Option<Vec<SecretSource>>,
+}
+
+impl ChannelCredentials {
+    pub fn new(name: impl Into<String>, kind: ChannelKind, credentials: Credentials) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            state: RwLock::new(Resolved {
+                credentials,
+                refreshed_at: Utc::now(),
+                sources: None,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Values to use for the next request.
+    pub fn current(&self) -> Credentials {
+        self.read().credentials.clone()
+    }
+
+    pub fn refreshed_at(&self) -> DateTime<Utc> {
+        self.read().refreshed_at
+    }
+
+    /// Whether a periodic refresh could pick up a new value: the channel
+    /// uses a file or keyring placeholder, or hasn't been checked yet.
+    pub fn is_rotatable(&self) -> bool {
+        self.read()
+            .sources
+            .as_ref()
+            .is_none_or(|sources| sources.iter().any(SecretSource::is_rotatable))
+    }
+
+    /// Re-resolve this channel's entry of the config file.
+    ///
+    /// Returns whether the credentials changed.
+    pub fn refresh(&self) -> Result<bool, NotifyError> {
+        self.refresh_from(&Paths::config_file())
+    }
+
+    fn refresh_from(&self, path: &Path) -> Result<bool, NotifyError> {
+        let (credentials, sources) = resolve_entry(path, self.kind, &self.name)?;
+        let mut state = self.state.write().unwrap_or_else(|err| err.into_inner());
+        let changed = state.credentials != credentials;
+        state.credentials = credentials;
+        state.refreshed_at = Utc::now();
+        state.sources = Some(sources);
+        debug!(channel = %self.name, changed, "Notification credentials re-resolved");
+        Ok(changed)
+    }
+
+    pub fn status(&self) -> CredentialStatus {
+        let state = self.read();
+        CredentialStatus {
+            channel: self.name.clone(),
+            kind: self.kind,
+            credentials_refreshed_at: state.refreshed_at,
+            sources: state
+                .sources
+                .iter()
+                .flatten()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Resolved> {
+        self.state.read().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// One channel in `GET /api/v1/notifications/health`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CredentialStatus {
+    pub channel: String,
+    pub kind: ChannelKind,
+    pub credentials_refreshed_at: DateTime<Utc>,
+    /// Placeholders behind the credentials, e.g. `file:/run/secrets/ntfy`;
+    /// empty for inline values or before the first re-resolution.
+    pub sources: Vec<String>,
+}
+
+/// Every channel's credentials in this process, by channel name.
+#[derive(Debug, Default)]
+pub struct CredentialRegistry {
+    channels: Mutex<BTreeMap<String, Arc<ChannelCredentials>>>,
+}
+
+impl CredentialRegistry {
+    pub fn global() -> &'static CredentialRegistry {
+        &REGISTRY
+    }
+
+    /// Track `credentials` under `name`, replacing an earlier channel of
+    /// that name.
+    pub fn register(
+        &self,
+        name: &str,
+        kind: ChannelKind,
+        credentials: Credentials,
+    ) -> Arc<ChannelCredentials> {
+        let entry = Arc::new(ChannelCredentials::new(name, kind, credentials));
+        self.lock().insert(name.to_string(), Arc::clone(&entry));
+        entry
+    }
+
+    /// Track exactly the channels `config` defines (at startup and reload).
+    pub fn register_configured(&self, config: &NotificationsConfig) {
+        let mut entries = Vec::new();
+        for webhook in &config.webhook {
+            entries.push((
+                webhook.channel_name(),
+                ChannelKind::Webhook,
+                Credentials::webhook(webhook),
+            ));
+        }
+        for ntfy in &config.ntfy {
+            entries.push((
+                ntfy.channel_name(),
+                ChannelKind::Ntfy,
+                Credentials::ntfy(ntfy),
+            ));
+        }
+        if let Some(discord) = &config.discord {
+            entries.push((
+                "discord",
+                ChannelKind::Discord,
+                Credentials::discord(discord),
+            ));
+        }
+        if let Some(slack) = &config.slack {
+            entries.push(("slack", ChannelKind::Slack, Credentials::slack(slack)));
+        }
+
+        self.lock()
+            .retain(|name, _| entries.iter().any(|(configured, _, _)| configured == name));
+        for (name, kind, credentials) in entries {
+            self.register(name, kind, credentials);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<ChannelCredentials>> {
+        self.lock().get(name).cloned()
+    }
+
+    /// Re-resolve one channel's credentials (`ROTATE_SECRET <channel>`).
+    pub fn rotate(&self, name: &str) -> Result<bool, NotifyError> {
+        let entry = self.get(name).ok_or_else(|| NotifyError::ConfigError {
+            message: format!("unknown notification channel {name:?}"),
+        })?;
+        let changed = entry.refresh()?;
+        info!(channel = name, changed, "Notification credentials rotated");
+        Ok(changed)
+    }
+
+    /// Re-resolve every channel backed by a file or keyring secret,
+    /// returning how many changed.
+    pub fn refresh_rotatable(&self) -> usize {
+        let entries: Vec<_> = self.lock().values().cloned().collect();
+        let mut changed = 0;
+        for entry in entries.iter().filter(|entry| entry.is_rotatable()) {
+            match entry.refresh() {
+                Ok(true) => {
+                    info!(channel = entry.name(), "Notification credentials changed");
+                    changed += 1;
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    warn!(channel = entry.name(), error = %err, "Failed to re-resolve notification credentials");
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn statuses(&self) -> Vec<CredentialStatus> {
+        self.lock().values().map(|entry| entry.status()).collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Arc<ChannelCredentials>>> {
+        self.channels.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Resolve the placeholders of channel `name`'s entry in the config file
+/// at `path`, leaving every other entry unresolved.
+fn resolve_entry(
+    path: &Path,
+    kind: ChannelKind,
+    name: &str,
+) -> Result<(Credentials, Vec<SecretSource>), NotifyError> {
+    let config_error = |message: String| NotifyError::ConfigError { message };
+    let contents = std::fs::read_to_string(path).map_err(|err| {
+        config_error(format!(
+            "failed to read config file {}: {err}",
+            path.display()
+        ))
+    })?;
+    let raw: toml::Value = toml::from_str(&contents).map_err(|err| {
+        config_error(format!(
+            "failed to parse config file {}: {err}",
+            path.display()
+        ))
+    })?;
+    let mut entry = raw
+        .get("notifications")
+        .and_then(|notifications| notifications.get(kind.section()))
+        .and_then(|section| find_entry(section, kind, name))
+        .ok_or_else(|| {
+            config_error(format!(
+                "channel {name:?} is no longer in [notifications.{}] of {}",
+                kind.section(),
+                path.display()
+            ))
+        })?;
+
+    let sources = secrets::resolve_with_default_store(&mut entry)
+        .map_err(|err| config_error(format!("failed to resolve secrets for {name:?}: {err}")))?
+        .into_iter()
+        .map(|(_, source)| source)
+        .collect();
+    let credentials = match kind {
+        ChannelKind::Webhook => Credentials::webhook(&parse_entry(entry, name)?),
+        ChannelKind::Ntfy => Credentials::ntfy(&parse_entry(entry, name)?),
+        ChannelKind::Discord => Credentials::discord(&parse_entry(entry, name)?),
+        ChannelKind::Slack => Credentials::slack(&parse_entry(entry, name)?),
+    };
+    Ok((credentials, sources))
+}
+
+/// The table for channel `name` in a section holding one table or an array.
+fn find_entry(section: &toml::Value, kind: ChannelKind, name: &str) -> Option<toml::Value> {
+    let is_named = |entry: &toml::Value| {
+        entry
+            .get("name")
+            .and_then(toml::Value::as_str)
+            .unwrap_or(kind.section())
+            == name
+    };
+    match section {
+        toml::Value::Array(entries) => entries.iter().find(|entry| is_named(entry)).cloned(),
+        entry if is_named(entry) => Some(entry.clone()),
+        _ => None,
+    }
+}
+
+fn parse_entry<T: for<'de> Deserialize<'de>>(
+    entry: toml::Value,
+    name: &str,
+) -> Result<T, NotifyError> {
+    entry.try_into().map_err(|err| NotifyError::ConfigError {
+        message: format!("invalid config for channel {name:?}: {err}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(dir: &Path, token_file: &Path) -> std::path::PathBuf {
+        let path = dir.join("config.toml");
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+[[notifications.ntfy]]
+name = "phone"
+topic = "alerts"
+token = "${{file:{}}}"
+
+[[notifications.ntfy]]
+name = "team"
+topic = "team"
+token = "${{env:PALINGENESIS_TEST_UNSET_TEAM_TOKEN}}"
+"#,
+                token_file.display()
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn refresh_reads_only_the_named_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("phone-token");
+        std::fs::write(&token_file, "tk_old\n").unwrap();
+        let config = write_config(dir.path(), &token_file);
+        let entry = ChannelCredentials::new(
+            "phone",
+            ChannelKind::Ntfy,
+            Credentials {
+                url: "https://ntfy.sh/alerts".to_string(),
+                token: Some("tk_old".to_string()),
+                ..Credentials::default()
+            },
+        );
+        assert!(entry.is_rotatable());
+
+        // The unset variable of the "team" entry is never looked up.
+        assert!(!entry.refresh_from(&config).unwrap());
+        std::fs::write(&token_file, "tk_new\n").unwrap();
+        assert!(entry.refresh_from(&config).unwrap());
+
+        assert_eq!(entry.current().token.as_deref(), Some("tk_new"));
+        assert!(entry.is_rotatable());
+        let status = entry.status();
+        assert_eq!(
+            status.sources,
+            vec![format!("file:{}", token_file.display())]
+        );
+    }
+
+    #[test]
+    fn refresh_fails_for_a_removed_channel() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("phone-token");
+        let config = write_config(dir.path(), &token_file);
+        let entry = ChannelCredentials::new("gone", ChannelKind::Ntfy, Credentials::default());
+
+        let err = entry.refresh_from(&config).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("no longer in [notifications.ntfy]")
+        );
+    }
+
+    #[test]
+    fn register_configured_drops_removed_channels() {
+        let registry = CredentialRegistry::default();
+        registry.register("old", ChannelKind::Webhook, Credentials::default());
+        let config = NotificationsConfig {
+            slack: Some(SlackConfig {
+                webhook_url: "https://hooks.slack.com/services/x".to_string(),
+                locale: None,
+                path_display: None,
+                match_tags: Vec::new(),
+                exclude_tags: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        registry.register_configured(&config);
+
+        let channels: Vec<_> = registry
+            .statuses()
+            .into_iter()
+            .map(|status| status.channel)
+            .collect();
+        assert_eq!(channels, ["slack"]);
+        assert!(registry.rotate("old").is_err());
+    }
+}
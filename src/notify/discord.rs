@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -5,68 +6,134 @@ use reqwest::Client;
 use serde::Serialize;
 use tracing::debug;
 
-use crate::config::schema::DiscordConfig;
+use crate::config::schema::{DiscordConfig, PathDisplayMode};
+use crate::i18n::{Locale, text};
 use crate::notify::channel::NotificationChannel;
+use crate::notify::credentials::{
+    ChannelCredentials, ChannelKind, CredentialRegistry, Credentials,
+};
 use crate::notify::error::NotifyError;
-use crate::notify::events::{EventSeverity, NotificationEvent};
+use crate::notify::events::{
+    EventSeverity, NotificationEvent, format_completion_duration, format_loop_summary,
+    format_sleep_gap, format_time_saved,
+};
+use crate::notify::message::{event_title, format_event_message, label};
+use crate::notify::routing::TagRoute;
+use crate::notify::truncate::{
+    Limit, detail_pointer, fit, is_payload_rejection, truncate, with_pointer,
+};
+use crate::notify::url_guard::UrlGuard;
+use crate::util::path_display;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Discord embed limits.
+const TITLE_LIMIT: Limit = Limit::chars(256);
+const DESCRIPTION_LIMIT: Limit = Limit::chars(4096);
+const FIELD_VALUE_LIMIT: Limit = Limit::chars(1024);
+const EMBED_TOTAL_CHARS: usize = 6000;
+const MAX_FIELDS: usize = 25;
 
 pub struct DiscordChannel {
-    webhook_url: String,
-    client: Client,
+    credentials: Arc<ChannelCredentials>,
+    /// Guarded HTTP client; if it could not be built, every send fails.
+    client: Result<Client, NotifyError>,
+    guard: UrlGuard,
+    locale: Locale,
+    /// `path_display` override; the daemon setting applies otherwise.
+    path_display: Option<PathDisplayMode>,
+    /// `match_tags`/`exclude_tags`.
+    route: TagRoute,
     enabled: bool,
 }
 
 impl DiscordChannel {
     pub fn new(config: &DiscordConfig) -> Self {
-        let client = Client::builder()
-            .timeout(REQUEST_TIMEOUT)
-            .build()
-            .unwrap_or_else(|err| {
-                tracing::warn!(error = %err, "Failed to build discord client; using defaults");
-                Client::new()
-            });
-
+        let guard = UrlGuard::default();
         Self {
-            webhook_url: config.webhook_url.clone(),
-            client,
+            credentials: CredentialRegistry::global().register(
+                "discord",
+                ChannelKind::Discord,
+                Credentials::discord(config),
+            ),
+            client: guard.build_client(REQUEST_TIMEOUT),
+            guard,
+            locale: config.locale.unwrap_or_default(),
+            path_display: config.path_display,
+            route: TagRoute::new(&config.match_tags, &config.exclude_tags),
             enabled: true,
         }
     }
+
+    /// Apply `notifications.allow_private_networks`.
+    pub fn with_url_guard(mut self, guard: UrlGuard) -> Self {
+        self.client = guard.build_client(REQUEST_TIMEOUT);
+        self.guard = guard;
+        self
+    }
+
+    /// Language of titles, field names and text.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
 }
 
 #[async_trait]
 impl NotificationChannel for DiscordChannel {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "discord"
     }
 
     async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
-        let payload = DiscordWebhookPayload {
-            embeds: vec![DiscordEmbed {
-                title: event_title(event).to_string(),
-                description: format_event_message(event),
-                color: severity_color(event.severity()),
-                timestamp: event_timestamp(event).to_rfc3339(),
-                fields: event_fields(event),
-            }],
-        };
+        self.deliver(event, None).await
+    }
 
-        let response = self
-            .client
-            .post(&self.webhook_url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|err| NotifyError::SendFailed {
-                message: format!("discord request error: {err}"),
-            })?;
+    async fn send_annotated(
+        &self,
+        event: &NotificationEvent,
+        note: &str,
+    ) -> Result<(), NotifyError> {
+        self.deliver(event, Some(note)).await
+    }
 
-        if !response.status().is_success() {
-            return Err(NotifyError::SendFailed {
-                message: format!("discord returned status {}", response.status()),
-            });
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn tag_route(&self) -> Option<&TagRoute> {
+        Some(&self.route)
+    }
+
+    fn refresh_credentials(&self) -> Result<bool, NotifyError> {
+        self.credentials.refresh()
+    }
+}
+
+impl DiscordChannel {
+    async fn deliver(
+        &self,
+        event: &NotificationEvent,
+        note: Option<&str>,
+    ) -> Result<(), NotifyError> {
+        let display = path_display::global().overridden(self.path_display);
+        let event = &*event.with_display_paths(&display);
+        let url = self.credentials.current().url;
+        self.guard.check_url(&url)?;
+        match self
+            .post(&url, &build_payload(event, note, false, self.locale))
+            .await
+        {
+            Err(err) if is_payload_rejection(&err) => {
+                debug!(
+                    channel = self.name(),
+                    event_type = event.event_type(),
+                    error = %err,
+                    "Discord rejected the payload; retrying with a shorter one"
+                );
+                self.post(&url, &build_payload(event, note, true, self.locale))
+                    .await?;
+            }
+            result => result?,
         }
 
         debug!(
@@ -77,13 +144,88 @@ impl NotificationChannel for DiscordChannel {
         Ok(())
     }
 
-    fn is_enabled(&self) -> bool {
-        self.enabled
+    async fn post(&self, url: &str, payload: &DiscordWebhookPayload) -> Result<(), NotifyError> {
+        let client = self.client.as_ref().map_err(Clone::clone)?;
+        let response =
+            client
+                .post(url)
+                .json(payload)
+                .send()
+                .await
+                .map_err(|err| NotifyError::SendFailed {
+                    message: format!("discord request error: {err}"),
+                })?;
+
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                service: "discord".to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Render `event` within Discord's embed limits; `aggressive` cuts deeper
+/// after Discord rejected the full-size payload.
+fn build_payload(
+    event: &NotificationEvent,
+    note: Option<&str>,
+    aggressive: bool,
+    locale: Locale,
+) -> DiscordWebhookPayload {
+    let shrink = |limit: Limit| {
+        if aggressive {
+            limit.aggressive()
+        } else {
+            limit
+        }
+    };
+    let title = truncate(&event_title(event, locale), TITLE_LIMIT).text;
+
+    let mut fields_cut = false;
+    let mut fields = event_fields(event, locale);
+    fields.truncate(MAX_FIELDS);
+    for field in &mut fields {
+        let cut = truncate(&field.value, shrink(FIELD_VALUE_LIMIT));
+        fields_cut |= cut.removed > 0;
+        field.value = cut.text;
+    }
+    let fields_size: usize = fields
+        .iter()
+        .map(|field| field.name.chars().count() + field.value.chars().count())
+        .sum();
+
+    let mut description = format_event_message(event, locale);
+    if let Some(note) = note {
+        description.push_str(&format!("\n\n{note}"));
+    }
+    let pointer = detail_pointer(event);
+    let room = EMBED_TOTAL_CHARS.saturating_sub(title.chars().count() + fields_size);
+    let limit = shrink(DESCRIPTION_LIMIT.at_most(room));
+    let description = if fields_cut {
+        with_pointer(&description, limit, &pointer)
+    } else {
+        fit(&description, limit, &pointer)
+    };
+
+    DiscordWebhookPayload {
+        // Mentions only notify from the message content, not from embeds.
+        content: event.prefix().map(str::to_string),
+        embeds: vec![DiscordEmbed {
+            title,
+            description,
+            color: severity_color(event.severity()),
+            timestamp: event_timestamp(event).to_rfc3339(),
+            fields,
+        }],
     }
 }
 
 #[derive(Debug, Serialize)]
 struct DiscordWebhookPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
     embeds: Vec<DiscordEmbed>,
 }
 
@@ -108,17 +250,7 @@ fn severity_color(severity: EventSeverity) -> u32 {
         EventSeverity::Info => 0x00FF00,
         EventSeverity::Warning => 0xFFFF00,
         EventSeverity::Error => 0xFF0000,
-    }
-}
-
-fn event_title(event: &NotificationEvent) -> &'static str {
-    match event {
-        NotificationEvent::SessionStopped { .. } => "Session stopped",
-        NotificationEvent::ResumeAttempted { .. } => "Resume attempted",
-        NotificationEvent::ResumeSucceeded { .. } => "Resume succeeded",
-        NotificationEvent::ResumeFailed { .. } => "Resume failed",
-        NotificationEvent::DaemonStarted { .. } => "Daemon started",
-        NotificationEvent::DaemonStopped { .. } => "Daemon stopped",
+        EventSeverity::Critical => 0x8B0000,
     }
 }
 
@@ -130,10 +262,33 @@ fn event_timestamp(event: &NotificationEvent) -> chrono::DateTime<chrono::Utc> {
         NotificationEvent::ResumeFailed { timestamp, .. } => *timestamp,
         NotificationEvent::DaemonStarted { timestamp, .. } => *timestamp,
         NotificationEvent::DaemonStopped { timestamp, .. } => *timestamp,
+        NotificationEvent::SessionOrphaned { timestamp, .. } => *timestamp,
+        NotificationEvent::SessionDeleted { timestamp, .. } => *timestamp,
+        NotificationEvent::ModelChanged { timestamp, .. } => *timestamp,
+        NotificationEvent::SystemResumed { timestamp, .. } => *timestamp,
+        NotificationEvent::NextStepInvalid { timestamp, .. } => *timestamp,
+        NotificationEvent::WorktreeCreated { timestamp, .. } => *timestamp,
+        NotificationEvent::ResumeDeferred { timestamp, .. } => *timestamp,
+        NotificationEvent::SessionCompleted { timestamp, .. } => *timestamp,
+        NotificationEvent::ResumeLoopSuspected { timestamp, .. } => *timestamp,
+        NotificationEvent::ResumeSidecarInvalid { timestamp, .. } => *timestamp,
+        NotificationEvent::ClockSkewDetected { timestamp, .. } => *timestamp,
+        NotificationEvent::FileEncodingFixed { timestamp, .. } => *timestamp,
+        NotificationEvent::ScheduledRun { timestamp, .. } => *timestamp,
+        NotificationEvent::StateReconciled { timestamp, .. } => *timestamp,
+        NotificationEvent::ArtifactGcPending { timestamp, .. } => *timestamp,
+        NotificationEvent::StateReadOnly { timestamp, .. } => *timestamp,
+        NotificationEvent::ActionObserved { timestamp, .. } => *timestamp,
+        NotificationEvent::OpenCodeVersionChanged { timestamp, .. } => *timestamp,
+        NotificationEvent::ControlApplied { timestamp, .. } => *timestamp,
+        NotificationEvent::TestNotification { timestamp, .. } => *timestamp,
+        NotificationEvent::IncidentEscalated { timestamp, .. } => *timestamp,
+        NotificationEvent::IncidentResolved { timestamp, .. } => *timestamp,
+        NotificationEvent::UnexpectedExit { timestamp, .. } => *timestamp,
     }
 }
 
-fn event_fields(event: &NotificationEvent) -> Vec<DiscordEmbedField> {
+fn event_fields(event: &NotificationEvent, locale: Locale) -> Vec<DiscordEmbedField> {
     match event {
         NotificationEvent::SessionStopped {
             session_path,
@@ -143,19 +298,19 @@ fn event_fields(event: &NotificationEvent) -> Vec<DiscordEmbedField> {
         } => {
             let mut fields = vec![
                 DiscordEmbedField {
-                    name: "Session".to_string(),
+                    name: label(locale, "session"),
                     value: session_path.display().to_string(),
                     inline: true,
                 },
                 DiscordEmbedField {
-                    name: "Reason".to_string(),
+                    name: label(locale, "reason"),
                     value: stop_reason.clone(),
                     inline: true,
                 },
             ];
             if let Some(details) = details {
                 fields.push(DiscordEmbedField {
-                    name: "Details".to_string(),
+                    name: label(locale, "details"),
                     value: details.clone(),
                     inline: false,
                 });
@@ -168,12 +323,12 @@ fn event_fields(event: &NotificationEvent) -> Vec<DiscordEmbedField> {
             ..
         } => vec![
             DiscordEmbedField {
-                name: "Session".to_string(),
+                name: label(locale, "session"),
                 value: session_path.display().to_string(),
                 inline: true,
             },
             DiscordEmbedField {
-                name: "Strategy".to_string(),
+                name: label(locale, "strategy"),
                 value: strategy.clone(),
                 inline: true,
             },
@@ -185,17 +340,17 @@ fn event_fields(event: &NotificationEvent) -> Vec<DiscordEmbedField> {
             ..
         } => vec![
             DiscordEmbedField {
-                name: "Session".to_string(),
+                name: label(locale, "session"),
                 value: session_path.display().to_string(),
                 inline: true,
             },
             DiscordEmbedField {
-                name: "Strategy".to_string(),
+                name: label(locale, "strategy"),
                 value: strategy.clone(),
                 inline: true,
             },
             DiscordEmbedField {
-                name: "Wait time".to_string(),
+                name: label(locale, "wait_time"),
                 value: format!("{wait_time_secs}s"),
                 inline: true,
             },
@@ -207,103 +362,401 @@ fn event_fields(event: &NotificationEvent) -> Vec<DiscordEmbedField> {
             ..
         } => vec![
             DiscordEmbedField {
-                name: "Session".to_string(),
+                name: label(locale, "session"),
                 value: session_path.display().to_string(),
                 inline: true,
             },
             DiscordEmbedField {
-                name: "Strategy".to_string(),
+                name: label(locale, "strategy"),
                 value: strategy.clone(),
                 inline: true,
             },
             DiscordEmbedField {
-                name: "Error".to_string(),
+                name: label(locale, "error"),
                 value: error.clone(),
                 inline: false,
             },
         ],
         NotificationEvent::DaemonStarted { version, .. } => vec![DiscordEmbedField {
-            name: "Version".to_string(),
+            name: label(locale, "version"),
             value: version.clone(),
             inline: true,
         }],
         NotificationEvent::DaemonStopped { reason, .. } => vec![DiscordEmbedField {
-            name: "Reason".to_string(),
+            name: label(locale, "reason"),
             value: reason.clone(),
             inline: true,
         }],
-    }
-}
-
-fn format_event_message(event: &NotificationEvent) -> String {
-    match event {
-        NotificationEvent::SessionStopped {
-            timestamp,
+        NotificationEvent::SessionOrphaned {
             session_path,
-            stop_reason,
-            details,
+            source_session,
+            reason,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "session"),
+                value: session_path.display().to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "resumed_from"),
+                value: source_session.display().to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "reason"),
+                value: reason.clone(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::SessionDeleted {
+            session_path,
+            restored_from,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "session"),
+                value: session_path.display().to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "restored_from"),
+                value: restored_from
+                    .as_ref()
+                    .map(|backup| backup.display().to_string())
+                    .unwrap_or_else(|| text(locale, "common.not_restored").to_string()),
+                inline: true,
+            },
+        ],
+        NotificationEvent::ModelChanged {
+            session_path,
+            previous_model,
+            current_model,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "session"),
+                value: session_path.display().to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "model"),
+                value: format!("{previous_model} → {current_model}"),
+                inline: true,
+            },
+        ],
+        NotificationEvent::SystemResumed { slept_secs, .. } => vec![DiscordEmbedField {
+            name: label(locale, "slept_for"),
+            value: format_sleep_gap(*slept_secs),
+            inline: true,
+        }],
+        NotificationEvent::NextStepInvalid {
+            next_step_path,
+            unmatched_lines,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "file"),
+                value: next_step_path.display().to_string(),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "first_lines"),
+                value: unmatched_lines.join("\n"),
+                inline: false,
+            },
+        ],
+        NotificationEvent::WorktreeCreated {
+            worktree_path,
+            branch,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "worktree"),
+                value: worktree_path.display().to_string(),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "branch"),
+                value: branch.clone(),
+                inline: true,
+            },
+        ],
+        NotificationEvent::ResumeDeferred { until, reason, .. } => vec![
+            DiscordEmbedField {
+                name: label(locale, "deferred_until"),
+                value: until.to_rfc3339(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "reason"),
+                value: reason.clone(),
+                inline: true,
+            },
+        ],
+        NotificationEvent::SessionCompleted {
+            duration_secs,
+            resumes,
+            time_saved_seconds,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "duration"),
+                value: format_completion_duration(*duration_secs, locale),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "resumes"),
+                value: resumes.to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "time_saved"),
+                value: format_time_saved(*time_saved_seconds),
+                inline: true,
+            },
+        ],
+        NotificationEvent::ResumeLoopSuspected {
+            resumes, span_secs, ..
+        } => vec![DiscordEmbedField {
+            name: label(locale, "resumes"),
+            value: format_loop_summary(*resumes, *span_secs, locale),
+            inline: false,
+        }],
+        NotificationEvent::ResumeSidecarInvalid {
+            sidecar_path,
+            error,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "sidecar"),
+                value: sidecar_path.display().to_string(),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "error"),
+                value: error.clone(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::ClockSkewDetected { skew_secs, .. } => vec![DiscordEmbedField {
+            name: label(locale, "skew"),
+            value: format!("{skew_secs:+.0}s"),
+            inline: true,
+        }],
+        NotificationEvent::FileEncodingFixed { file, encoding, .. } => vec![
+            DiscordEmbedField {
+                name: label(locale, "file"),
+                value: file.display().to_string(),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "encoding"),
+                value: encoding.clone(),
+                inline: true,
+            },
+        ],
+        NotificationEvent::ScheduledRun {
+            schedule,
+            workdir,
+            session_path,
+            ..
         } => {
-            let mut message = format!(
-                "Session stopped at {}.\nSession: {}\nReason: {}",
-                timestamp.to_rfc3339(),
-                session_path.display(),
-                stop_reason
-            );
-            if let Some(details) = details {
-                message.push_str(&format!("\nDetails: {details}"));
+            let mut fields = vec![
+                DiscordEmbedField {
+                    name: label(locale, "schedule"),
+                    value: schedule.clone(),
+                    inline: true,
+                },
+                DiscordEmbedField {
+                    name: label(locale, "workdir"),
+                    value: workdir.display().to_string(),
+                    inline: false,
+                },
+            ];
+            if let Some(session_path) = session_path {
+                fields.push(DiscordEmbedField {
+                    name: label(locale, "session"),
+                    value: session_path.display().to_string(),
+                    inline: false,
+                });
             }
-            message
+            fields
         }
-        NotificationEvent::ResumeAttempted {
-            timestamp,
+        NotificationEvent::StateReconciled {
+            corrections,
+            report,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "details"),
+                value: corrections.join("\n"),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "file"),
+                value: report.display().to_string(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::ArtifactGcPending { summary, plan, .. } => vec![
+            DiscordEmbedField {
+                name: label(locale, "details"),
+                value: summary.join("\n"),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "file"),
+                value: plan.display().to_string(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::StateReadOnly {
+            state_file,
+            found_version,
+            supported_version,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "file"),
+                value: state_file.display().to_string(),
+                inline: false,
+            },
+            DiscordEmbedField {
+                name: label(locale, "version"),
+                value: format!("v{found_version} > v{supported_version}"),
+                inline: true,
+            },
+        ],
+        NotificationEvent::ActionObserved {
             session_path,
-            strategy,
-        } => format!(
-            "Resume attempted at {}.\nSession: {}\nStrategy: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy
-        ),
-        NotificationEvent::ResumeSucceeded {
-            timestamp,
+            stop_reason,
+            action,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "session"),
+                value: session_path.display().to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "reason"),
+                value: stop_reason.clone(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "would_run"),
+                value: action.clone(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::OpenCodeVersionChanged {
+            previous,
+            current,
+            problems,
+            ..
+        } => {
+            let mut fields = vec![
+                DiscordEmbedField {
+                    name: label(locale, "previous"),
+                    value: previous
+                        .clone()
+                        .unwrap_or_else(|| text(locale, "common.unknown").to_string()),
+                    inline: true,
+                },
+                DiscordEmbedField {
+                    name: label(locale, "current"),
+                    value: current.clone(),
+                    inline: true,
+                },
+            ];
+            if !problems.is_empty() {
+                fields.push(DiscordEmbedField {
+                    name: label(locale, "compatibility_problems"),
+                    value: problems.join("\n"),
+                    inline: false,
+                });
+            }
+            fields
+        }
+        NotificationEvent::ControlApplied { initiator, .. } => vec![DiscordEmbedField {
+            name: label(locale, "by"),
+            value: initiator.to_string(),
+            inline: true,
+        }],
+        NotificationEvent::TestNotification { message, .. } => vec![DiscordEmbedField {
+            name: label(locale, "message"),
+            value: message.clone(),
+            inline: false,
+        }],
+        NotificationEvent::IncidentEscalated {
             session_path,
-            strategy,
-            wait_time_secs,
-        } => format!(
-            "Resume succeeded at {}.\nSession: {}\nStrategy: {}\nWait time: {}s",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            wait_time_secs
-        ),
-        NotificationEvent::ResumeFailed {
-            timestamp,
+            incident_id,
+            tier,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "incident"),
+                value: incident_id.clone(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "tier"),
+                value: tier.to_string(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "session"),
+                value: session_path.display().to_string(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::IncidentResolved {
             session_path,
-            strategy,
-            error,
-        } => format!(
-            "Resume failed at {}.\nSession: {}\nStrategy: {}\nError: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            error
-        ),
-        NotificationEvent::DaemonStarted { timestamp, version } => format!(
-            "Daemon started at {}.\nVersion: {}",
-            timestamp.to_rfc3339(),
-            version
-        ),
-        NotificationEvent::DaemonStopped { timestamp, reason } => format!(
-            "Daemon stopped at {}.\nReason: {}",
-            timestamp.to_rfc3339(),
-            reason
-        ),
+            incident_id,
+            resolution,
+            ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "incident"),
+                value: incident_id.clone(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "resolution"),
+                value: resolution.clone(),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "session"),
+                value: session_path.display().to_string(),
+                inline: false,
+            },
+        ],
+        NotificationEvent::UnexpectedExit {
+            pid, cause, detail, ..
+        } => vec![
+            DiscordEmbedField {
+                name: label(locale, "reason"),
+                value: format!(
+                    "{} (PID {pid})",
+                    text(locale, &format!("exit_cause.{cause}"))
+                ),
+                inline: true,
+            },
+            DiscordEmbedField {
+                name: label(locale, "details"),
+                value: detail.clone(),
+                inline: false,
+            },
+        ],
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::resume::git_context::GitContext;
     use chrono::TimeZone;
     use std::path::PathBuf;
 
@@ -318,13 +771,160 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             strategy: "same_session".to_string(),
             wait_time_secs: 120,
+            progress: None,
+            percent: None,
+            git: Some(GitContext::Repo {
+                branch: "main".to_string(),
+                sha: "1a2b3c4".to_string(),
+                dirty: false,
+            }),
         };
 
-        let message = format_event_message(&event);
+        let message = format_event_message(&event, Locale::En);
 
         assert!(message.contains("Resume succeeded at 2025-01-02T03:04:05+00:00"));
         assert!(message.contains("Session: /tmp/session"));
         assert!(message.contains("Strategy: same_session"));
         assert!(message.contains("Wait time: 120s"));
+        assert!(message.contains("Git: main@1a2b3c4 (clean)"));
+    }
+
+    #[test]
+    fn formats_durations_compactly() {
+        let event = NotificationEvent::SessionCompleted {
+            timestamp: chrono::Utc
+                .with_ymd_and_hms(2025, 1, 2, 3, 4, 5)
+                .single()
+                .expect("timestamp"),
+            session_path: PathBuf::from("/tmp/session.md"),
+            duration_secs: Some(3_661),
+            resumes: 2,
+            time_saved_seconds: 600.0,
+        };
+        assert!(
+            format_event_message(&event, Locale::En).contains("after 1h 1m (2 resumes, 10m saved)")
+        );
+
+        let event = NotificationEvent::SystemResumed {
+            timestamp: chrono::Utc::now(),
+            slept_secs: 28_320,
+        };
+        assert!(format_event_message(&event, Locale::En).contains("7h 52m"));
+    }
+
+    fn oversized_failure() -> NotificationEvent {
+        NotificationEvent::ResumeFailed {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session.md"),
+            strategy: "new_session".to_string(),
+            error: format!("```\n{}```", "stderr: connection reset\n".repeat(2_000)),
+            progress: None,
+            percent: None,
+            incident: None,
+            ack: None,
+        }
+    }
+
+    fn embed_size(embed: &DiscordEmbed) -> usize {
+        embed.title.chars().count()
+            + embed.description.chars().count()
+            + embed
+                .fields
+                .iter()
+                .map(|field| field.name.chars().count() + field.value.chars().count())
+                .sum::<usize>()
+    }
+
+    #[test]
+    fn oversized_event_fits_embed_limits() {
+        let event = oversized_failure();
+        let note = "n".repeat(10_000);
+
+        let payload = build_payload(&event, Some(&note), false, Locale::En);
+        let embed = &payload.embeds[0];
+
+        assert!(DESCRIPTION_LIMIT.fits(&embed.description));
+        assert!(
+            embed
+                .fields
+                .iter()
+                .all(|field| FIELD_VALUE_LIMIT.fits(&field.value))
+        );
+        assert!(embed_size(embed) <= EMBED_TOTAL_CHARS);
+        assert_eq!(embed.title, "Resume failed");
+        assert_eq!(embed.fields[0].value, "/tmp/session.md");
+        assert_eq!(embed.fields[1].value, "new_session");
+        assert!(embed.fields[2].value.contains("… truncated "));
+        assert!(embed.description.ends_with(&detail_pointer(&event)));
+    }
+
+    #[test]
+    fn stop_reason_survives_oversized_details() {
+        let event = NotificationEvent::SessionStopped {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session.md"),
+            stop_reason: "rate_limit".to_string(),
+            details: Some("d".repeat(50_000)),
+            excluded_by: None,
+        };
+
+        let embed = &build_payload(&event, None, false, Locale::En).embeds[0];
+
+        assert!(embed_size(embed) <= EMBED_TOTAL_CHARS);
+        assert_eq!(embed.fields[1].value, "rate_limit");
+        assert!(embed.description.ends_with("Full details: /tmp/session.md"));
+    }
+
+    #[test]
+    fn aggressive_payload_is_smaller() {
+        let event = oversized_failure();
+
+        let full = embed_size(&build_payload(&event, None, false, Locale::En).embeds[0]);
+        let aggressive = embed_size(&build_payload(&event, None, true, Locale::En).embeds[0]);
+
+        assert!(aggressive < full / 2);
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn retries_once_with_shorter_payload_after_400() {
+        use axum::Router;
+        use axum::http::StatusCode;
+        use std::sync::{Arc, Mutex};
+
+        let bodies = Arc::new(Mutex::new(Vec::<String>::new()));
+        let sink = Arc::clone(&bodies);
+        let app = Router::new().fallback(move |body: String| {
+            let sink = Arc::clone(&sink);
+            async move {
+                let mut bodies = sink.lock().unwrap();
+                bodies.push(body);
+                if bodies.len() == 1 {
+                    StatusCode::BAD_REQUEST
+                } else {
+                    StatusCode::NO_CONTENT
+                }
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let channel = DiscordChannel::new(&DiscordConfig {
+            webhook_url: format!("http://{addr}/webhook"),
+            locale: None,
+            path_display: None,
+            match_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+        })
+        .with_url_guard(UrlGuard::new(true));
+
+        channel.send(&oversized_failure()).await.unwrap();
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies[1].len() < bodies[0].len());
     }
 }
@@ -1,8 +1,32 @@
-use tracing::error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use chrono::Utc;
+use tokio::time::Instant;
+use tracing::{debug, error, warn};
+
+use crate::config::schema::{NotificationsConfig, UntaggedRoute};
+use crate::events::DomainEvent;
+use crate::i18n::Locale;
+use crate::notify::ack::{ACK_ROUTE, AckLink, AckRegistry};
 use crate::notify::channel::NotificationChannel;
+use crate::notify::discord::DiscordChannel;
 use crate::notify::error::NotifyError;
-use crate::notify::events::NotificationEvent;
+use crate::notify::events::{EventSeverity, NotificationEvent};
+use crate::notify::history::{DeliveryOutcome, DeliveryRecord, NotificationHistory};
+use crate::notify::mute::NotificationMutes;
+use crate::notify::ntfy::NtfyChannel;
+use crate::notify::routing;
+use crate::notify::slack::SlackChannel;
+use crate::notify::url_guard::UrlGuard;
+use crate::notify::webhook::WebhookChannel;
+use crate::state::{MuteKind, NotificationMute};
+use crate::telemetry::Metrics;
+
+/// Weight of the newest sample in the per-channel latency average.
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+const DEFAULT_PRIMARY_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DispatchSummary {
@@ -10,105 +34,655 @@ pub struct DispatchSummary {
     pub successes: usize,
     pub failures: usize,
     pub failed_channels: Vec<String>,
+    /// Channel that delivered an urgent event first, if the primary path was used.
+    pub primary: Option<String>,
 }
 
 impl DispatchSummary {
-    fn new(total: usize, failures: Vec<String>) -> Self {
+    fn new(total: usize, failures: Vec<String>, primary: Option<String>) -> Self {
         let failures_count = failures.len();
         Self {
             total,
             successes: total.saturating_sub(failures_count),
             failures: failures_count,
             failed_channels: failures,
+            primary,
+        }
+    }
+}
+
+/// Which channel urgent events go to first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrimaryChannel {
+    Named(String),
+    /// The channel with the lowest rolling delivery latency.
+    Auto,
+}
+
+/// How urgent events are ordered across channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchPolicy {
+    pub primary: Option<PrimaryChannel>,
+    pub urgent_severities: Vec<EventSeverity>,
+    pub primary_timeout: Duration,
+    /// Channels that get events without routing tags.
+    pub untagged_route: UntaggedRoute,
+    /// Severities sent past runtime mutes.
+    pub mute_exempt_severities: Vec<EventSeverity>,
+}
+
+impl Default for DispatchPolicy {
+    fn default() -> Self {
+        Self {
+            primary: None,
+            urgent_severities: vec![EventSeverity::Critical],
+            primary_timeout: DEFAULT_PRIMARY_TIMEOUT,
+            untagged_route: UntaggedRoute::default(),
+            mute_exempt_severities: Vec::new(),
+        }
+    }
+}
+
+impl DispatchPolicy {
+    pub fn from_config(config: &NotificationsConfig) -> Self {
+        let primary = config
+            .primary_channel
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                if name.eq_ignore_ascii_case("auto") {
+                    PrimaryChannel::Auto
+                } else {
+                    PrimaryChannel::Named(name.to_ascii_lowercase())
+                }
+            });
+        let urgent_severities = config
+            .urgent_severities
+            .iter()
+            .filter_map(|severity| EventSeverity::parse(severity))
+            .collect();
+        let mute_exempt_severities = config
+            .mute_exempt_severities
+            .iter()
+            .filter_map(|severity| EventSeverity::parse(severity))
+            .collect();
+
+        Self {
+            primary,
+            urgent_severities,
+            primary_timeout: Duration::from_millis(config.primary_timeout_ms),
+            untagged_route: config.untagged_route,
+            mute_exempt_severities,
         }
     }
+
+    fn is_urgent(&self, severity: EventSeverity) -> bool {
+        self.primary.is_some() && self.urgent_severities.contains(&severity)
+    }
 }
 
 pub struct Dispatcher {
     channels: Vec<Box<dyn NotificationChannel>>,
+    policy: DispatchPolicy,
+    latency: Mutex<HashMap<String, f64>>,
+    history: Option<Arc<NotificationHistory>>,
+    acks: Option<AckLinks>,
+    mutes: Option<NotificationMutes>,
+}
+
+/// Incident acknowledgment for dispatched events.
+struct AckLinks {
+    registry: Arc<AckRegistry>,
+    /// `notifications.ack_base_url`, without a trailing slash.
+    base_url: Option<String>,
 }
 
 impl Dispatcher {
     pub fn new(channels: Vec<Box<dyn NotificationChannel>>) -> Self {
-        Self { channels }
+        Self {
+            channels,
+            policy: DispatchPolicy::default(),
+            latency: Mutex::new(HashMap::new()),
+            history: None,
+            acks: None,
+            mutes: None,
+        }
     }
 
-    pub async fn dispatch(&self, event: NotificationEvent) -> DispatchSummary {
-        let enabled: Vec<&dyn NotificationChannel> = self
-            .channels
-            .iter()
-            .map(|channel| channel.as_ref())
-            .filter(|channel| channel.is_enabled())
-            .collect();
+    /// One channel per configured target; `[[notifications.*]]` arrays
+    /// produce one channel per entry. Channels without their own `locale`
+    /// write in `locale` (`daemon.locale`).
+    pub fn from_config(config: &NotificationsConfig, locale: Locale) -> Self {
+        let guard = UrlGuard::from_config(config);
+        let mut channels: Vec<Box<dyn NotificationChannel>> = Vec::new();
+        for webhook in &config.webhook {
+            channels.push(Box::new(WebhookChannel::new(webhook).with_url_guard(guard)));
+        }
+        for ntfy in &config.ntfy {
+            channels.push(Box::new(
+                NtfyChannel::new(ntfy)
+                    .with_url_guard(guard)
+                    .with_locale(ntfy.locale.unwrap_or(locale)),
+            ));
+        }
+        if let Some(discord) = &config.discord {
+            channels.push(Box::new(
+                DiscordChannel::new(discord)
+                    .with_url_guard(guard)
+                    .with_locale(discord.locale.unwrap_or(locale)),
+            ));
+        }
+        if let Some(slack) = &config.slack {
+            channels.push(Box::new(
+                SlackChannel::new(slack)
+                    .with_url_guard(guard)
+                    .with_locale(slack.locale.unwrap_or(locale)),
+            ));
+        }
+        Self::new(channels)
+            .with_policy(DispatchPolicy::from_config(config))
+            .with_history(NotificationHistory::global_or_init(config))
+            .with_acks(
+                AckRegistry::global_or_init(config),
+                config.ack_base_url.clone(),
+            )
+    }
+
+    pub fn with_policy(mut self, policy: DispatchPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Record every delivery decision in `history`.
+    pub fn with_history(mut self, history: Arc<NotificationHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Attach acknowledgment links from `registry` to failure and attention
+    /// events, linking to the ack endpoint under `base_url`, and hold back
+    /// the non-critical events of acknowledged incidents.
+    pub fn with_acks(mut self, registry: Arc<AckRegistry>, base_url: Option<String>) -> Self {
+        self.acks = Some(AckLinks {
+            registry,
+            base_url: base_url.map(|url| url.trim_end_matches('/').to_string()),
+        });
+        self
+    }
+
+    /// Hold back what `mutes` silences, unless its severity is in
+    /// `mute_exempt_severities`.
+    pub fn with_mutes(mut self, mutes: NotificationMutes) -> Self {
+        self.mutes = Some(mutes);
+        self
+    }
+
+    /// Rolling delivery latency for a channel, once it has delivered at least once.
+    pub fn latency(&self, channel: &str) -> Option<Duration> {
+        let latency = self.latency.lock().expect("latency lock poisoned");
+        latency
+            .get(channel)
+            .map(|seconds| Duration::from_secs_f64(*seconds))
+    }
+
+    /// Dispatch the notification form of `event`; `None` when it has none.
+    pub async fn dispatch_event(&self, event: &DomainEvent) -> Option<DispatchSummary> {
+        let notification = event.notification()?;
+        Some(self.dispatch(notification).await)
+    }
+
+    pub async fn dispatch(&self, mut event: NotificationEvent) -> DispatchSummary {
+        let mutes = self.mutes_for(&event);
+        if mutes.iter().any(|mute| mute.kind == MuteKind::Event) {
+            debug!(
+                event_type = event.event_type(),
+                "Event type muted; notification not sent"
+            );
+            for channel in &self.channels {
+                self.record_skipped(channel.name(), &event, DeliveryOutcome::Muted);
+            }
+            return DispatchSummary::new(0, Vec::new(), None);
+        }
+        if self.acknowledged(&event) {
+            debug!(
+                event_type = event.event_type(),
+                "Incident acknowledged; notification not sent"
+            );
+            for channel in &self.channels {
+                self.record_skipped(channel.name(), &event, DeliveryOutcome::Acknowledged);
+            }
+            return DispatchSummary::new(0, Vec::new(), None);
+        }
+        self.attach_ack(&mut event);
+        let summary = self.deliver(&event, &mutes).await;
+        if event.resolves_incident() {
+            if let (Some(acks), Some(session)) = (&self.acks, event.session_path()) {
+                acks.registry.resolve(session);
+            }
+        }
+        summary
+    }
+
+    /// Send `event` to the named channels only, past their event and
+    /// severity filters and acknowledgment holds but not mutes; escalation
+    /// tiers route this way. Names matching no channel are ignored.
+    pub async fn dispatch_to(
+        &self,
+        names: &[String],
+        event: &NotificationEvent,
+    ) -> DispatchSummary {
+        let mutes = self.mutes_for(event);
+        let mut failures = Vec::new();
+        let mut total = 0;
+        let selected = self.channels.iter().filter(|channel| {
+            names
+                .iter()
+                .any(|name| channel.name().eq_ignore_ascii_case(name.trim()))
+        });
+        for channel in selected {
+            if is_muted(&mutes, channel.name(), event) {
+                self.record_skipped(channel.name(), event, DeliveryOutcome::Muted);
+                continue;
+            }
+            if !channel.is_enabled() {
+                self.record_skipped(channel.name(), event, DeliveryOutcome::Disabled);
+                continue;
+            }
+            total += 1;
+            let outcome = self.send_one(channel.as_ref(), event).await;
+            self.record_outcome(&outcome, event);
+            if let Err(err) = outcome.result {
+                error!(
+                    channel = %outcome.name,
+                    event_type = event.event_type(),
+                    error = %err,
+                    "Notification channel send failed"
+                );
+                failures.push(outcome.name);
+            }
+        }
+        DispatchSummary::new(total, failures, None)
+    }
+
+    /// Active mutes that could hold back `event`; none for exempt severities.
+    fn mutes_for(&self, event: &NotificationEvent) -> Vec<NotificationMute> {
+        let Some(mutes) = &self.mutes else {
+            return Vec::new();
+        };
+        if self
+            .policy
+            .mute_exempt_severities
+            .contains(&event.severity())
+        {
+            return Vec::new();
+        }
+        mutes
+            .active()
+            .into_iter()
+            .filter(|mute| mute.kind == MuteKind::Channel || mute.silences("", event.event_type()))
+            .collect()
+    }
+
+    /// Whether `event` belongs to an acknowledged incident and may be held back.
+    fn acknowledged(&self, event: &NotificationEvent) -> bool {
+        let (Some(acks), Some(session)) = (&self.acks, event.session_path()) else {
+            return false;
+        };
+        event.severity() != EventSeverity::Critical
+            && !event.is_give_up()
+            && !event.resolves_incident()
+            && acks.registry.is_acknowledged(session, Utc::now())
+    }
+
+    fn attach_ack(&self, event: &mut NotificationEvent) {
+        let Some(acks) = &self.acks else {
+            return;
+        };
+        let Some(session) = event.session_path().map(|path| path.to_path_buf()) else {
+            return;
+        };
+        let snapshot = match &*event {
+            NotificationEvent::ResumeFailed { incident, .. } => incident.clone(),
+            _ => None,
+        };
+        let Some(slot) = event.ack_mut() else {
+            return;
+        };
+        if let Some((incident, token)) =
+            acks.registry
+                .issue(&session, snapshot.as_deref(), Utc::now())
+        {
+            *slot = Some(AckLink {
+                incident_id: incident.id,
+                url: acks
+                    .base_url
+                    .as_ref()
+                    .map(|base| format!("{base}{ACK_ROUTE}/{token}")),
+            });
+        }
+    }
+
+    async fn deliver(
+        &self,
+        event: &NotificationEvent,
+        mutes: &[NotificationMute],
+    ) -> DispatchSummary {
+        let event = event.clone();
+        let tags = routing::event_tags(&event);
+        let mut enabled: Vec<&dyn NotificationChannel> = Vec::new();
+        for channel in &self.channels {
+            let routed = channel
+                .tag_route()
+                .is_none_or(|route| route.matches(&tags, self.policy.untagged_route));
+            if is_muted(mutes, channel.name(), &event) {
+                self.record_skipped(channel.name(), &event, DeliveryOutcome::Muted);
+            } else if !channel.is_enabled() {
+                self.record_skipped(channel.name(), &event, DeliveryOutcome::Disabled);
+            } else if !channel.accepts(&event) || !routed {
+                self.record_skipped(channel.name(), &event, DeliveryOutcome::Filtered);
+            } else {
+                enabled.push(channel.as_ref());
+            }
+        }
 
         let mut failures = Vec::new();
+        let mut primary = None;
         let total = enabled.len();
 
-        for chunk in enabled.chunks(4) {
+        let remaining = if self.policy.is_urgent(event.severity()) {
+            let candidates = self.primary_candidates(enabled);
+            let (attempted, delivered) = self
+                .dispatch_primary(&candidates, &event, &mut failures)
+                .await;
+            primary = delivered;
+            candidates[attempted..].to_vec()
+        } else {
+            enabled
+        };
+
+        for chunk in remaining.chunks(4) {
             let mut outcomes = Vec::new();
             match chunk.len() {
                 0 => {}
                 1 => {
-                    outcomes.push(send_one(chunk[0], &event).await);
+                    outcomes.push(self.send_one(chunk[0], &event).await);
                 }
                 2 => {
-                    let fut1 = send_one(chunk[0], &event);
-                    let fut2 = send_one(chunk[1], &event);
+                    let fut1 = self.send_one(chunk[0], &event);
+                    let fut2 = self.send_one(chunk[1], &event);
                     let (res1, res2) = tokio::join!(fut1, fut2);
                     outcomes.extend([res1, res2]);
                 }
                 3 => {
-                    let fut1 = send_one(chunk[0], &event);
-                    let fut2 = send_one(chunk[1], &event);
-                    let fut3 = send_one(chunk[2], &event);
+                    let fut1 = self.send_one(chunk[0], &event);
+                    let fut2 = self.send_one(chunk[1], &event);
+                    let fut3 = self.send_one(chunk[2], &event);
                     let (res1, res2, res3) = tokio::join!(fut1, fut2, fut3);
                     outcomes.extend([res1, res2, res3]);
                 }
                 _ => {
-                    let fut1 = send_one(chunk[0], &event);
-                    let fut2 = send_one(chunk[1], &event);
-                    let fut3 = send_one(chunk[2], &event);
-                    let fut4 = send_one(chunk[3], &event);
+                    let fut1 = self.send_one(chunk[0], &event);
+                    let fut2 = self.send_one(chunk[1], &event);
+                    let fut3 = self.send_one(chunk[2], &event);
+                    let fut4 = self.send_one(chunk[3], &event);
                     let (res1, res2, res3, res4) = tokio::join!(fut1, fut2, fut3, fut4);
                     outcomes.extend([res1, res2, res3, res4]);
                 }
             }
 
             for outcome in outcomes {
+                self.record_outcome(&outcome, &event);
                 if let Err(err) = outcome.result {
                     error!(
-                        channel = outcome.name,
+                        channel = %outcome.name,
                         event_type = event.event_type(),
                         error = %err,
                         "Notification channel send failed"
                     );
-                    failures.push(outcome.name.to_string());
+                    failures.push(outcome.name);
+                }
+            }
+        }
+
+        DispatchSummary::new(total, failures, primary)
+    }
+
+    /// Order enabled channels so the primary comes first and the rest follow
+    /// in fallback order.
+    fn primary_candidates<'a>(
+        &self,
+        mut enabled: Vec<&'a dyn NotificationChannel>,
+    ) -> Vec<&'a dyn NotificationChannel> {
+        match &self.policy.primary {
+            Some(PrimaryChannel::Named(name)) => {
+                match enabled
+                    .iter()
+                    .position(|channel| channel.name().eq_ignore_ascii_case(name))
+                {
+                    Some(index) => {
+                        let channel = enabled.remove(index);
+                        enabled.insert(0, channel);
+                    }
+                    None => {
+                        debug!(
+                            channel = %name,
+                            "Primary channel not enabled; using configured order"
+                        );
+                    }
+                }
+            }
+            Some(PrimaryChannel::Auto) => {
+                let latency = self.latency.lock().expect("latency lock poisoned");
+                // Channels without samples yet go last, keeping configured order.
+                enabled.sort_by(|a, b| {
+                    let a = latency.get(a.name()).copied().unwrap_or(f64::INFINITY);
+                    let b = latency.get(b.name()).copied().unwrap_or(f64::INFINITY);
+                    a.total_cmp(&b)
+                });
+            }
+            None => {}
+        }
+        enabled
+    }
+
+    /// Try candidates one at a time until one delivers, annotating fallbacks
+    /// with the primary's failure.
+    ///
+    /// Returns how many candidates were attempted and which one delivered.
+    async fn dispatch_primary(
+        &self,
+        candidates: &[&dyn NotificationChannel],
+        event: &NotificationEvent,
+        failures: &mut Vec<String>,
+    ) -> (usize, Option<String>) {
+        let mut note: Option<String> = None;
+
+        for (index, channel) in candidates.iter().enumerate() {
+            let outcome = self.send_primary(*channel, event, note.as_deref()).await;
+            self.record_outcome(&outcome, event);
+            match outcome.result {
+                Ok(()) => return (index + 1, Some(outcome.name)),
+                Err(err) => {
+                    warn!(
+                        channel = %outcome.name,
+                        event_type = event.event_type(),
+                        error = %err,
+                        "Primary notification channel failed; promoting next channel"
+                    );
+                    note.get_or_insert_with(|| {
+                        format!("Note: primary channel {} failed ({err})", outcome.name)
+                    });
+                    failures.push(outcome.name);
                 }
             }
         }
 
-        DispatchSummary::new(total, failures)
+        (candidates.len(), None)
+    }
+
+    async fn send_primary(
+        &self,
+        channel: &dyn NotificationChannel,
+        event: &NotificationEvent,
+        note: Option<&str>,
+    ) -> ChannelOutcome {
+        let name = channel.name().to_string();
+        let timeout = self.policy.primary_timeout;
+        let started = Instant::now();
+        let send = send_with_refresh(channel, event, note);
+        let result = match tokio::time::timeout(timeout, send).await {
+            Ok(result) => result,
+            Err(_) => Err(NotifyError::Timeout { duration: timeout }),
+        };
+        let elapsed = started.elapsed();
+        if result.is_ok() {
+            self.record_latency(&name, elapsed);
+        }
+        ChannelOutcome {
+            name,
+            result,
+            elapsed,
+        }
+    }
+
+    async fn send_one(
+        &self,
+        channel: &dyn NotificationChannel,
+        event: &NotificationEvent,
+    ) -> ChannelOutcome {
+        let name = channel.name().to_string();
+        let started = Instant::now();
+        let result = send_with_refresh(channel, event, None).await;
+        let elapsed = started.elapsed();
+        if result.is_ok() {
+            self.record_latency(&name, elapsed);
+        }
+        ChannelOutcome {
+            name,
+            result,
+            elapsed,
+        }
+    }
+
+    fn record_skipped(&self, channel: &str, event: &NotificationEvent, outcome: DeliveryOutcome) {
+        if let Some(history) = &self.history {
+            history.record(DeliveryRecord {
+                timestamp: Utc::now(),
+                event_type: event.event_type().to_string(),
+                severity: event.severity(),
+                channel: channel.to_string(),
+                outcome,
+                http_status: None,
+                latency_ms: None,
+                error: None,
+            });
+        }
+    }
+
+    fn record_outcome(&self, outcome: &ChannelOutcome, event: &NotificationEvent) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let (delivery, http_status, error) = match &outcome.result {
+            Ok(()) => (DeliveryOutcome::Delivered, None, None),
+            Err(err) => (
+                DeliveryOutcome::Failed,
+                err.http_status(),
+                Some(err.to_string()),
+            ),
+        };
+        history.record(DeliveryRecord {
+            timestamp: Utc::now(),
+            event_type: event.event_type().to_string(),
+            severity: event.severity(),
+            channel: outcome.name.clone(),
+            outcome: delivery,
+            http_status,
+            latency_ms: Some(outcome.elapsed.as_millis() as u64),
+            error,
+        });
+    }
+
+    fn record_latency(&self, name: &str, elapsed: Duration) {
+        let sample = elapsed.as_secs_f64();
+        let value = {
+            let mut latency = self.latency.lock().expect("latency lock poisoned");
+            let value = match latency.get(name) {
+                Some(previous) => {
+                    LATENCY_EWMA_ALPHA * sample + (1.0 - LATENCY_EWMA_ALPHA) * previous
+                }
+                None => sample,
+            };
+            latency.insert(name.to_string(), value);
+            value
+        };
+        if let Some(metrics) = Metrics::global() {
+            metrics.set_notification_latency(name, value);
+        }
     }
 }
 
+fn is_muted(mutes: &[NotificationMute], channel: &str, event: &NotificationEvent) -> bool {
+    mutes
+        .iter()
+        .any(|mute| mute.silences(channel, event.event_type()))
+}
+
 struct ChannelOutcome {
-    name: &'static str,
+    name: String,
     result: Result<(), NotifyError>,
+    elapsed: Duration,
 }
 
-async fn send_one(channel: &dyn NotificationChannel, event: &NotificationEvent) -> ChannelOutcome {
-    let name = channel.name();
-    let result = channel.send(event).await;
-    ChannelOutcome { name, result }
+/// Send `event`; after a 401/403, re-resolve the channel's credentials
+/// and, if they changed, send once more before reporting the failure.
+async fn send_with_refresh(
+    channel: &dyn NotificationChannel,
+    event: &NotificationEvent,
+    note: Option<&str>,
+) -> Result<(), NotifyError> {
+    let send = || async {
+        match note {
+            Some(note) => channel.send_annotated(event, note).await,
+            None => channel.send(event).await,
+        }
+    };
+    match send().await {
+        Err(err) if err.is_auth_failure() => match channel.refresh_credentials() {
+            Ok(true) => {
+                debug!(
+                    channel = channel.name(),
+                    "Credentials changed after an auth failure; retrying"
+                );
+                send().await
+            }
+            Ok(false) => Err(err),
+            Err(refresh_err) => {
+                warn!(
+                    channel = channel.name(),
+                    error = %refresh_err,
+                    "Failed to re-resolve credentials after an auth failure"
+                );
+                Err(err)
+            }
+        },
+        result => result,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::schema::{NtfyConfig, WebhookConfig};
+    use crate::notify::channel::EventFilter;
     use crate::notify::events::EventSeverity;
+    use crate::notify::history::HistoryQuery;
+    use crate::notify::routing::TagRoute;
     use async_trait::async_trait;
     use chrono::TimeZone;
     use std::path::PathBuf;
+    use std::sync::Arc;
 
     struct MockChannel {
         name: &'static str,
@@ -118,7 +692,7 @@ mod tests {
 
     #[async_trait]
     impl NotificationChannel for MockChannel {
-        fn name(&self) -> &'static str {
+        fn name(&self) -> &str {
             self.name
         }
 
@@ -146,6 +720,8 @@ mod tests {
             timestamp,
             session_path: PathBuf::from("/tmp/session"),
             strategy: "same_session".to_string(),
+            progress: None,
+            percent: None,
         }
     }
 
@@ -225,4 +801,719 @@ mod tests {
         assert_eq!(summary.failures, 0);
         assert_eq!(EventSeverity::Info, sample_event().severity());
     }
+
+    type SendLog = Arc<Mutex<Vec<(&'static str, Option<String>)>>>;
+
+    struct RecordingChannel {
+        name: &'static str,
+        fail: bool,
+        delay: Duration,
+        log: SendLog,
+    }
+
+    impl RecordingChannel {
+        fn boxed(name: &'static str, log: &SendLog) -> Box<dyn NotificationChannel> {
+            Self::boxed_with(name, false, Duration::ZERO, log)
+        }
+
+        fn boxed_with(
+            name: &'static str,
+            fail: bool,
+            delay: Duration,
+            log: &SendLog,
+        ) -> Box<dyn NotificationChannel> {
+            Box::new(Self {
+                name,
+                fail,
+                delay,
+                log: Arc::clone(log),
+            })
+        }
+
+        async fn deliver(&self, note: Option<&str>) -> Result<(), NotifyError> {
+            self.log
+                .lock()
+                .unwrap()
+                .push((self.name, note.map(str::to_string)));
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                return Err(NotifyError::SendFailed {
+                    message: format!("{} unreachable", self.name),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _event: &NotificationEvent) -> Result<(), NotifyError> {
+            self.deliver(None).await
+        }
+
+        async fn send_annotated(
+            &self,
+            _event: &NotificationEvent,
+            note: &str,
+        ) -> Result<(), NotifyError> {
+            self.deliver(Some(note)).await
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    fn failed_event() -> NotificationEvent {
+        NotificationEvent::ResumeFailed {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session"),
+            strategy: "same_session".to_string(),
+            error: "retries exhausted".to_string(),
+            progress: None,
+            percent: None,
+            incident: None,
+            ack: None,
+        }
+    }
+
+    fn urgent_policy(primary: PrimaryChannel) -> DispatchPolicy {
+        DispatchPolicy {
+            primary: Some(primary),
+            urgent_severities: vec![EventSeverity::Error],
+            ..DispatchPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_primary_promotes_next_channel_with_note() {
+        let log = SendLog::default();
+        let dispatcher = Dispatcher::new(vec![
+            RecordingChannel::boxed("slack", &log),
+            RecordingChannel::boxed_with("ntfy", true, Duration::ZERO, &log),
+            RecordingChannel::boxed("discord", &log),
+        ])
+        .with_policy(urgent_policy(PrimaryChannel::Named("ntfy".to_string())));
+
+        let summary = dispatcher.dispatch(failed_event()).await;
+
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.successes, 2);
+        assert_eq!(summary.failed_channels, vec!["ntfy".to_string()]);
+        assert_eq!(summary.primary.as_deref(), Some("slack"));
+
+        let log = log.lock().unwrap();
+        assert_eq!(log[0], ("ntfy", None));
+        assert_eq!(log[1].0, "slack");
+        let note = log[1].1.as_deref().expect("promoted channel is annotated");
+        assert!(note.contains("primary channel ntfy failed"));
+        assert!(note.contains("ntfy unreachable"));
+        assert_eq!(log[2], ("discord", None));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_primary_times_out_and_is_promoted_past() {
+        let log = SendLog::default();
+        let dispatcher = Dispatcher::new(vec![
+            RecordingChannel::boxed_with("ntfy", false, Duration::from_secs(30), &log),
+            RecordingChannel::boxed("discord", &log),
+        ])
+        .with_policy(urgent_policy(PrimaryChannel::Named("ntfy".to_string())));
+
+        let summary = dispatcher.dispatch(failed_event()).await;
+
+        assert_eq!(summary.primary.as_deref(), Some("discord"));
+        assert_eq!(summary.failed_channels, vec!["ntfy".to_string()]);
+        let log = log.lock().unwrap();
+        let note = log[1].1.as_deref().expect("promoted channel is annotated");
+        assert!(note.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn non_urgent_events_skip_primary_ordering() {
+        let log = SendLog::default();
+        let dispatcher = Dispatcher::new(vec![
+            RecordingChannel::boxed("slack", &log),
+            RecordingChannel::boxed_with("ntfy", true, Duration::ZERO, &log),
+        ])
+        .with_policy(urgent_policy(PrimaryChannel::Named("ntfy".to_string())));
+
+        let summary = dispatcher.dispatch(sample_event()).await;
+
+        assert_eq!(summary.primary, None);
+        assert_eq!(summary.failed_channels, vec!["ntfy".to_string()]);
+        assert!(log.lock().unwrap().iter().all(|(_, note)| note.is_none()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn auto_primary_prefers_lowest_latency() {
+        let log = SendLog::default();
+        let dispatcher = Dispatcher::new(vec![
+            RecordingChannel::boxed_with("slack", false, Duration::from_millis(800), &log),
+            RecordingChannel::boxed_with("ntfy", false, Duration::from_millis(100), &log),
+        ])
+        .with_policy(urgent_policy(PrimaryChannel::Auto));
+
+        dispatcher.dispatch(sample_event()).await;
+        assert_eq!(dispatcher.latency("ntfy"), Some(Duration::from_millis(100)));
+        assert_eq!(
+            dispatcher.latency("slack"),
+            Some(Duration::from_millis(800))
+        );
+
+        log.lock().unwrap().clear();
+        let summary = dispatcher.dispatch(failed_event()).await;
+
+        assert_eq!(summary.primary.as_deref(), Some("ntfy"));
+        assert_eq!(log.lock().unwrap()[0].0, "ntfy");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn latency_is_a_rolling_average() {
+        let log = SendLog::default();
+        let dispatcher = Dispatcher::new(vec![RecordingChannel::boxed_with(
+            "ntfy",
+            false,
+            Duration::from_secs(1),
+            &log,
+        )]);
+
+        dispatcher.dispatch(sample_event()).await;
+        dispatcher.record_latency("ntfy", Duration::from_secs(2));
+
+        let latency = dispatcher.latency("ntfy").unwrap().as_secs_f64();
+        assert!((latency - 1.3).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn filtered_channels_are_skipped() {
+        let dispatcher = Dispatcher::new(vec![
+            Box::new(FilteredChannel {
+                name: "oncall",
+                filter: EventFilter::new(&["critical".to_string()], &[]),
+            }),
+            Box::new(FilteredChannel {
+                name: "resumes",
+                filter: EventFilter::new(&[], &["resume_attempted".to_string()]),
+            }),
+        ]);
+
+        let summary = dispatcher.dispatch(sample_event()).await;
+
+        assert_eq!(summary.total, 1);
+        assert_eq!(summary.failed_channels, vec!["resumes".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn history_tags_filtered_and_failed_deliveries() {
+        let history = Arc::new(NotificationHistory::new(16));
+        let dispatcher = Dispatcher::new(vec![
+            Box::new(FilteredChannel {
+                name: "oncall",
+                filter: EventFilter::new(&["critical".to_string()], &[]),
+            }),
+            Box::new(FilteredChannel {
+                name: "resumes",
+                filter: EventFilter::new(&[], &[]),
+            }),
+        ])
+        .with_history(Arc::clone(&history));
+
+        dispatcher.dispatch(sample_event()).await;
+
+        let records = history.recent(&HistoryQuery::default());
+        assert_eq!(records.len(), 2);
+        let oncall = records.iter().find(|r| r.channel == "oncall").unwrap();
+        assert_eq!(oncall.outcome, DeliveryOutcome::Filtered);
+        assert_eq!(oncall.error, None);
+        let resumes = records.iter().find(|r| r.channel == "resumes").unwrap();
+        assert_eq!(resumes.outcome, DeliveryOutcome::Failed);
+        assert_eq!(resumes.event_type, sample_event().event_type());
+        assert!(
+            resumes
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("resumes reached")
+        );
+        assert!(resumes.latency_ms.is_some());
+    }
+
+    struct FilteredChannel {
+        name: &'static str,
+        filter: EventFilter,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for FilteredChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _event: &NotificationEvent) -> Result<(), NotifyError> {
+            Err(NotifyError::SendFailed {
+                message: format!("{} reached", self.name),
+            })
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn accepts(&self, event: &NotificationEvent) -> bool {
+            self.filter.matches(event)
+        }
+    }
+
+    struct RoutedChannel {
+        name: &'static str,
+        route: TagRoute,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RoutedChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _event: &NotificationEvent) -> Result<(), NotifyError> {
+            Err(NotifyError::SendFailed {
+                message: format!("{} reached", self.name),
+            })
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn tag_route(&self) -> Option<&TagRoute> {
+            Some(&self.route)
+        }
+    }
+
+    fn routed_dispatcher(untagged_route: UntaggedRoute) -> Dispatcher {
+        let route = |tag: &str| TagRoute::new(&[tag.to_string()], &[]);
+        Dispatcher::new(vec![
+            Box::new(RoutedChannel {
+                name: "proj-a",
+                route: route("proj-a"),
+            }),
+            Box::new(RoutedChannel {
+                name: "proj-b",
+                route: route("proj-b"),
+            }),
+            Box::new(RoutedChannel {
+                name: "everything",
+                route: TagRoute::default(),
+            }),
+        ])
+        .with_policy(DispatchPolicy {
+            untagged_route,
+            ..DispatchPolicy::default()
+        })
+    }
+
+    fn tagged_session(dir: &std::path::Path, name: &str, tags: &str) -> NotificationEvent {
+        let session_path = dir.join(format!("{name}.md"));
+        std::fs::write(
+            dir.join(format!("{name}.palingenesis.toml")),
+            format!("tags = [{tags}]\n"),
+        )
+        .unwrap();
+        NotificationEvent::ResumeAttempted {
+            timestamp: chrono::Utc::now(),
+            session_path,
+            strategy: "same_session".to_string(),
+            progress: None,
+            percent: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn tagged_sessions_reach_only_their_channels() {
+        let temp = tempfile::tempdir().unwrap();
+        let dispatcher = routed_dispatcher(UntaggedRoute::All);
+
+        let proj_a = tagged_session(temp.path(), "a", "\"Proj-A\"");
+        let summary = dispatcher.dispatch(proj_a).await;
+        assert_eq!(summary.failed_channels, vec!["proj-a", "everything"]);
+
+        let proj_b = tagged_session(temp.path(), "b", "\"proj-b\", \"backend\"");
+        let summary = dispatcher.dispatch(proj_b).await;
+        assert_eq!(summary.failed_channels, vec!["proj-b", "everything"]);
+    }
+
+    #[tokio::test]
+    async fn untagged_events_follow_the_untagged_route() {
+        let started = NotificationEvent::DaemonStarted {
+            timestamp: chrono::Utc::now(),
+            version: "1.0.0".to_string(),
+        };
+
+        let summary = routed_dispatcher(UntaggedRoute::All)
+            .dispatch(started.clone())
+            .await;
+        assert_eq!(
+            summary.failed_channels,
+            vec!["proj-a", "proj-b", "everything"]
+        );
+
+        let summary = routed_dispatcher(UntaggedRoute::Untargeted)
+            .dispatch(started)
+            .await;
+        assert_eq!(summary.failed_channels, vec!["everything"]);
+    }
+
+    #[test]
+    fn from_config_builds_one_channel_per_target() {
+        let mut ops = WebhookConfig::new("http://127.0.0.1:9/ops");
+        ops.name = Some("ops".to_string());
+        let mut audit = WebhookConfig::new("http://127.0.0.1:9/audit");
+        audit.name = Some("audit".to_string());
+        let config = NotificationsConfig {
+            webhook: vec![ops, audit],
+            ntfy: vec![NtfyConfig::new("alerts")],
+            ..NotificationsConfig::default()
+        };
+
+        let dispatcher = Dispatcher::from_config(&config, Locale::En);
+        let names: Vec<&str> = dispatcher
+            .channels
+            .iter()
+            .map(|channel| channel.name())
+            .collect();
+
+        assert_eq!(names, vec!["ops", "audit", "ntfy"]);
+    }
+
+    #[test]
+    fn policy_from_config_parses_primary_and_severities() {
+        let config = NotificationsConfig {
+            primary_channel: Some("Auto".to_string()),
+            urgent_severities: vec!["critical".to_string(), "error".to_string()],
+            primary_timeout_ms: 1500,
+            ..NotificationsConfig::default()
+        };
+
+        let policy = DispatchPolicy::from_config(&config);
+
+        assert_eq!(policy.primary, Some(PrimaryChannel::Auto));
+        assert_eq!(
+            policy.urgent_severities,
+            vec![EventSeverity::Critical, EventSeverity::Error]
+        );
+        assert_eq!(policy.primary_timeout, Duration::from_millis(1500));
+        assert_eq!(
+            DispatchPolicy::from_config(&NotificationsConfig::default()).primary,
+            None
+        );
+    }
+
+    type Captured = Arc<std::sync::Mutex<Vec<NotificationEvent>>>;
+
+    struct CapturingChannel {
+        sent: Captured,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for CapturingChannel {
+        fn name(&self) -> &str {
+            "capture"
+        }
+
+        async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
+            self.sent.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    fn ack_dispatcher(
+        registry: &Arc<AckRegistry>,
+        history: &Arc<NotificationHistory>,
+    ) -> (Dispatcher, Captured) {
+        let sent = Captured::default();
+        let dispatcher = Dispatcher::new(vec![Box::new(CapturingChannel {
+            sent: Arc::clone(&sent),
+        })])
+        .with_history(Arc::clone(history))
+        .with_acks(
+            Arc::clone(registry),
+            Some("https://palingenesis.example/".to_string()),
+        );
+        (dispatcher, sent)
+    }
+
+    fn sent_ack(sent: &Captured, index: usize) -> Option<AckLink> {
+        match &sent.lock().unwrap()[index] {
+            NotificationEvent::ResumeFailed { ack, .. }
+            | NotificationEvent::ResumeLoopSuspected { ack, .. } => ack.clone(),
+            _ => None,
+        }
+    }
+
+    fn loop_event() -> NotificationEvent {
+        NotificationEvent::ResumeLoopSuspected {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session"),
+            resumes: 10,
+            span_secs: 600,
+            ack: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn failure_notifications_carry_an_ack_link() {
+        let registry = Arc::new(AckRegistry::default());
+        let history = Arc::new(NotificationHistory::new(16));
+        let (dispatcher, sent) = ack_dispatcher(&registry, &history);
+
+        dispatcher.dispatch(failed_event()).await;
+        dispatcher.dispatch(failed_event()).await;
+
+        let first = sent_ack(&sent, 0).expect("failure carries an ack link");
+        let url = first.url.as_deref().expect("base url configured");
+        let token = url
+            .strip_prefix("https://palingenesis.example/api/v1/ack/")
+            .expect("link points at the ack route");
+        assert_eq!(token.len(), 64);
+        assert!(first.notice(Locale::En).contains(&first.incident_id));
+        // Same incident, same link, until it is used.
+        assert_eq!(sent_ack(&sent, 1), Some(first));
+    }
+
+    #[tokio::test]
+    async fn acknowledged_incident_is_silenced_except_for_give_up() {
+        let registry = Arc::new(AckRegistry::default());
+        let history = Arc::new(NotificationHistory::new(16));
+        let (dispatcher, sent) = ack_dispatcher(&registry, &history);
+
+        dispatcher.dispatch(failed_event()).await;
+        let link = sent_ack(&sent, 0).unwrap();
+        let token = link.url.unwrap().rsplit('/').next().unwrap().to_string();
+        registry
+            .acknowledge_token(&token, chrono::Utc::now())
+            .unwrap();
+
+        let summary = dispatcher.dispatch(failed_event()).await;
+        assert_eq!(summary.total, 0);
+        assert_eq!(sent.lock().unwrap().len(), 1);
+        let skipped = history.recent(&HistoryQuery {
+            outcome: Some(DeliveryOutcome::Acknowledged),
+            ..HistoryQuery::default()
+        });
+        assert_eq!(skipped.len(), 1);
+
+        // The give-up still goes out, with nothing left to acknowledge.
+        let summary = dispatcher.dispatch(loop_event()).await;
+        assert_eq!(summary.successes, 1);
+        assert_eq!(sent_ack(&sent, 1), None);
+
+        // A successful resume closes the incident; the next failure opens a new one.
+        dispatcher
+            .dispatch(NotificationEvent::ResumeSucceeded {
+                timestamp: chrono::Utc::now(),
+                session_path: PathBuf::from("/tmp/session"),
+                strategy: "same_session".to_string(),
+                wait_time_secs: 0,
+                progress: None,
+                percent: None,
+                git: None,
+            })
+            .await;
+        dispatcher.dispatch(failed_event()).await;
+        assert_eq!(sent.lock().unwrap().len(), 4);
+        assert!(sent_ack(&sent, 3).is_some());
+    }
+
+    /// Wall clock moved by hand.
+    struct FakeClock(Mutex<std::time::SystemTime>);
+
+    impl crate::daemon::suspend::Clock for FakeClock {
+        fn monotonic(&self) -> std::time::Instant {
+            std::time::Instant::now()
+        }
+
+        fn wall(&self) -> std::time::SystemTime {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn muted_channel_is_skipped_until_the_mute_lapses() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = crate::state::StateHandle::new(crate::state::StateStore::with_path(
+            dir.path().join("state.json"),
+        ));
+        let clock = Arc::new(FakeClock(Mutex::new(std::time::SystemTime::now())));
+        let mutes = NotificationMutes::new(state).with_clock(clock.clone());
+        let history = Arc::new(NotificationHistory::new(32));
+        let log = SendLog::default();
+        let dispatcher = Dispatcher::new(vec![
+            RecordingChannel::boxed("slack", &log),
+            RecordingChannel::boxed("ntfy", &log),
+        ])
+        .with_policy(DispatchPolicy {
+            mute_exempt_severities: vec![EventSeverity::Critical],
+            ..DispatchPolicy::default()
+        })
+        .with_history(Arc::clone(&history))
+        .with_mutes(mutes.clone());
+        let sent = |log: &SendLog| {
+            let mut names: Vec<_> = log
+                .lock()
+                .unwrap()
+                .drain(..)
+                .map(|(name, _)| name)
+                .collect();
+            names.sort_unstable();
+            names
+        };
+
+        mutes
+            .mute(MuteKind::Channel, "slack", Some(Duration::from_secs(1800)))
+            .unwrap();
+        let summary = dispatcher.dispatch(failed_event()).await;
+        assert_eq!(summary.total, 1);
+        assert_eq!(sent(&log), vec!["ntfy"]);
+        let muted = history.recent(&HistoryQuery {
+            outcome: Some(DeliveryOutcome::Muted),
+            ..HistoryQuery::default()
+        });
+        assert_eq!(muted.len(), 1);
+        assert_eq!(muted[0].channel, "slack");
+
+        // Escalation tiers name their channels, and still honor the mute.
+        dispatcher
+            .dispatch_to(&["slack".to_string()], &failed_event())
+            .await;
+        assert!(sent(&log).is_empty());
+
+        // Exempt severities go out regardless.
+        dispatcher
+            .dispatch(NotificationEvent::SessionDeleted {
+                timestamp: chrono::Utc::now(),
+                session_path: PathBuf::from("/tmp/session"),
+                restored_from: None,
+            })
+            .await;
+        assert_eq!(sent(&log), vec!["ntfy", "slack"]);
+
+        // A muted event type goes nowhere.
+        mutes
+            .mute(MuteKind::Event, "resume_attempted", None)
+            .unwrap();
+        let summary = dispatcher.dispatch(sample_event()).await;
+        assert_eq!(summary.total, 0);
+        assert!(sent(&log).is_empty());
+        mutes.unmute(MuteKind::Event, "resume_attempted").unwrap();
+
+        *clock.0.lock().unwrap() += Duration::from_secs(1801);
+        let summary = dispatcher.dispatch(failed_event()).await;
+        assert_eq!(summary.successes, 2);
+        assert_eq!(sent(&log), vec!["ntfy", "slack"]);
+        assert!(mutes.active().is_empty());
+    }
+
+    /// ntfy stub that accepts only `Bearer <accepted>` and records every
+    /// Authorization header it sees.
+    #[cfg(feature = "http-api")]
+    async fn auth_stub(accepted: Arc<Mutex<String>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        use axum::Router;
+        use axum::http::{HeaderMap, StatusCode};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let app = Router::new().fallback(move |headers: HeaderMap| {
+            let sink = Arc::clone(&sink);
+            let accepted = Arc::clone(&accepted);
+            async move {
+                let auth = headers
+                    .get("authorization")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                let ok = auth == format!("Bearer {}", accepted.lock().unwrap());
+                sink.lock().unwrap().push(auth);
+                if ok {
+                    StatusCode::OK
+                } else {
+                    StatusCode::UNAUTHORIZED
+                }
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}"), seen)
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn rotated_file_secret_reaches_the_next_delivery() {
+        use crate::config::schema::NtfyConfig;
+        use crate::notify::credentials::CredentialRegistry;
+
+        let _lock = crate::test_utils::ENV_LOCK.lock().await;
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("ntfy-token");
+        std::fs::write(&token_file, "tk_old\n").unwrap();
+        let accepted = Arc::new(Mutex::new("tk_old".to_string()));
+        let (server, seen) = auth_stub(Arc::clone(&accepted)).await;
+        let config_file = dir.path().join("config.toml");
+        std::fs::write(
+            &config_file,
+            format!(
+                "[[notifications.ntfy]]\nname = \"rotation-test\"\ntopic = \"alerts\"\nserver = \"{server}\"\ntoken = \"${{file:{}}}\"\n",
+                token_file.display()
+            ),
+        )
+        .unwrap();
+        unsafe { std::env::set_var("PALINGENESIS_CONFIG", &config_file) };
+
+        let mut ntfy = NtfyConfig::new("alerts");
+        ntfy.name = Some("rotation-test".to_string());
+        ntfy.server = Some(server);
+        ntfy.token = Some("tk_old".to_string());
+        let dispatcher = Dispatcher::new(vec![Box::new(
+            NtfyChannel::new(&ntfy).with_url_guard(UrlGuard::new(true)),
+        )]);
+
+        // Rotated on disk and at the provider: the 401 triggers one
+        // re-resolution and a retry with the new token.
+        std::fs::write(&token_file, "tk_new\n").unwrap();
+        *accepted.lock().unwrap() = "tk_new".to_string();
+        let summary = dispatcher.dispatch(sample_event()).await;
+        assert_eq!(summary.successes, 1);
+        assert_eq!(*seen.lock().unwrap(), ["Bearer tk_old", "Bearer tk_new"]);
+
+        // An explicit rotation takes effect before the next send.
+        std::fs::write(&token_file, "tk_newer\n").unwrap();
+        *accepted.lock().unwrap() = "tk_newer".to_string();
+        assert!(
+            CredentialRegistry::global()
+                .rotate("rotation-test")
+                .unwrap()
+        );
+        let summary = dispatcher.dispatch(sample_event()).await;
+        assert_eq!(summary.successes, 1);
+        assert_eq!(seen.lock().unwrap().last().unwrap(), "Bearer tk_newer");
+
+        // A rejection the secret source can't fix fails after one attempt.
+        *accepted.lock().unwrap() = "tk_revoked".to_string();
+        let summary = dispatcher.dispatch(sample_event()).await;
+        assert_eq!(summary.failures, 1);
+        assert_eq!(seen.lock().unwrap().len(), 4);
+
+        unsafe { std::env::remove_var("PALINGENESIS_CONFIG") };
+    }
 }
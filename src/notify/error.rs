@@ -2,12 +2,33 @@ use std::time::Duration;
 
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+use crate::notify::url_guard::UrlGuardError;
+
+#[derive(Debug, Clone, Error)]
 pub enum NotifyError {
     #[error("Notification send failed: {message}")]
     SendFailed { message: String },
+    #[error("Notification send failed: {service} returned status {status}")]
+    Status { service: String, status: u16 },
     #[error("Notification timed out after {duration:?}")]
     Timeout { duration: Duration },
     #[error("Notification configuration error: {message}")]
     ConfigError { message: String },
+    #[error("Notification target blocked: {0}")]
+    Blocked(#[from] UrlGuardError),
+}
+
+impl NotifyError {
+    /// HTTP status the target answered with, if it answered at all.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            NotifyError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Whether the target rejected the credentials (401 or 403).
+    pub fn is_auth_failure(&self) -> bool {
+        matches!(self.http_status(), Some(401 | 403))
+    }
 }
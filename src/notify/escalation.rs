@@ -0,0 +1,703 @@
+//! Time-boxed escalation of unresolved incidents (`[notifications.escalation]`).
+//!
+//! A failed resume opens an incident for its session. The scheduler checks
+//! open incidents every `check_interval_secs`; once an incident has been
+//! open for a tier's `after_secs`, that tier's channels are notified even if
+//! nothing else happened. A successful resume, an acknowledgment or the
+//! resume-loop give-up resolves the incident: escalation stops and every
+//! channel of the tiers it reached gets a resolution notice.
+//!
+//! Incidents and the tiers they reached are kept in the state file, so a
+//! restarted daemon carries on where it left off, and `palingenesis notify
+//! digest` summarizes them afterwards.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::config::schema::{EscalationConfig, EscalationTier};
+use crate::daemon::suspend::{Clock, SystemClock};
+use crate::i18n::{Locale, render, text};
+use crate::notify::ack::{ACK_ROUTE, AckLink, AckRegistry, incident_id};
+use crate::notify::dispatcher::Dispatcher;
+use crate::notify::events::NotificationEvent;
+use crate::resume::cost::CostSummary;
+use crate::state::{EngagedTier, EscalationRecord, EscalationResolution, StateFile, StateHandle};
+use crate::util::duration;
+
+/// Walks open incidents through the configured tiers.
+pub struct Escalator {
+    tiers: Vec<EscalationTier>,
+    dispatcher: Dispatcher,
+    state: StateHandle,
+    acks: Option<(Arc<AckRegistry>, Option<String>)>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Escalator {
+    pub fn new(config: &EscalationConfig, dispatcher: Dispatcher, state: StateHandle) -> Self {
+        Self {
+            tiers: config.tiers.clone(),
+            dispatcher,
+            state,
+            acks: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Open incidents in `registry`, so escalations carry an acknowledgment
+    /// link under `base_url` and `palingenesis ack` ends them.
+    pub fn with_acks(mut self, registry: Arc<AckRegistry>, base_url: Option<String>) -> Self {
+        let base_url = base_url.map(|url| url.trim_end_matches('/').to_string());
+        self.acks = Some((registry, base_url));
+        self
+    }
+
+    /// Measure incident age against this clock (for testing).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.clock.wall())
+    }
+
+    /// Re-register incidents still open in the state file with the ack
+    /// registry, after a restart.
+    pub fn restore(&self) -> usize {
+        let open: Vec<EscalationRecord> = self
+            .state
+            .snapshot()
+            .escalations
+            .into_iter()
+            .filter(|record| record.resolved_at.is_none())
+            .collect();
+        if let Some((registry, _)) = &self.acks {
+            for record in &open {
+                registry.reopen(&record.incident_id, &record.session_path, record.opened_at);
+            }
+        }
+        open.len()
+    }
+
+    /// Open or resolve the session's incident from a published event.
+    pub fn observe(&self, event: &NotificationEvent) {
+        match event {
+            NotificationEvent::ResumeFailed {
+                session_path,
+                incident,
+                ..
+            } => {
+                self.open(session_path, incident.as_deref());
+            }
+            NotificationEvent::ResumeSucceeded { session_path, .. }
+            | NotificationEvent::SessionCompleted { session_path, .. } => {
+                self.resolve(session_path, EscalationResolution::Resumed);
+            }
+            NotificationEvent::ResumeLoopSuspected { session_path, .. } => {
+                self.resolve(session_path, EscalationResolution::GaveUp);
+            }
+            _ => {}
+        }
+    }
+
+    /// Start escalating the session's incident unless one is already open;
+    /// returns the incident id of a newly opened one.
+    ///
+    /// `snapshot` is the stop's transcript tail, which names the incident.
+    /// An incident the ack registry already has acknowledged is left alone.
+    pub fn open(&self, session_path: &Path, snapshot: Option<&Path>) -> Option<String> {
+        if self.tiers.is_empty() {
+            return None;
+        }
+        let now = self.now();
+        if self
+            .state
+            .snapshot()
+            .escalations
+            .iter()
+            .any(|record| record.session_path == session_path && record.resolved_at.is_none())
+        {
+            return None;
+        }
+        let (id, opened_at) = match &self.acks {
+            Some((registry, _)) => {
+                let (incident, _) = registry.issue(session_path, snapshot, now)?;
+                (incident.id, incident.opened_at)
+            }
+            None => (incident_id(session_path, snapshot, now), now),
+        };
+        let record = EscalationRecord::new(id.clone(), session_path.to_path_buf(), opened_at);
+        if let Err(err) = self
+            .state
+            .update_critical(|state| state.record_escalation(record))
+        {
+            warn!(error = %err, incident = %id, "Failed to record escalation");
+            return None;
+        }
+        info!(incident = %id, session = %session_path.display(), "Escalation started");
+        Some(id)
+    }
+
+    /// End the session's escalation; its resolution notices go out on the
+    /// next [`Self::tick`].
+    pub fn resolve(&self, session_path: &Path, resolution: EscalationResolution) -> bool {
+        let now = self.now();
+        let resolved = self
+            .state
+            .update_critical(|state| state.resolve_escalation(session_path, resolution, now))
+            .unwrap_or_else(|err| {
+                warn!(error = %err, "Failed to record escalation resolution");
+                false
+            });
+        if resolved && resolution == EscalationResolution::Resumed {
+            if let Some((registry, _)) = &self.acks {
+                registry.resolve(session_path);
+            }
+        }
+        resolved
+    }
+
+    /// Send what is due now: the tiers open incidents have newly reached,
+    /// and the resolution notices of incidents that ended. Returns how many
+    /// notifications went out.
+    pub async fn tick(&self) -> usize {
+        let now = self.now();
+        let mut sent = 0;
+        for record in self.state.snapshot().escalations {
+            if record.resolved_at.is_some() {
+                if !record.is_settled() {
+                    self.send_resolution(&record, now).await;
+                    sent += 1;
+                }
+                continue;
+            }
+            let age = duration::between(record.opened_at, now).as_secs();
+            let due = self
+                .tiers
+                .iter()
+                .enumerate()
+                .skip(record.tiers.len())
+                .take_while(|(_, tier)| tier.after_secs <= age);
+            let mut entered = Vec::new();
+            for (index, tier) in due {
+                self.send_tier(&record, index, tier, now).await;
+                entered.push(EngagedTier {
+                    tier: index,
+                    entered_at: now,
+                    channels: tier.channels.clone(),
+                });
+            }
+            if entered.is_empty() {
+                continue;
+            }
+            sent += entered.len();
+            let id = record.incident_id.clone();
+            let saved = self.state.update_critical_async(|state| {
+                if let Some(record) = state
+                    .escalations
+                    .iter_mut()
+                    .find(|candidate| candidate.incident_id == id)
+                {
+                    record.tiers.extend(entered);
+                }
+            });
+            if let Err(err) = saved.await {
+                warn!(error = %err, incident = %id, "Failed to record escalation tier");
+            }
+        }
+        sent
+    }
+
+    async fn send_tier(
+        &self,
+        record: &EscalationRecord,
+        index: usize,
+        tier: &EscalationTier,
+        now: DateTime<Utc>,
+    ) {
+        let event = NotificationEvent::IncidentEscalated {
+            timestamp: now,
+            session_path: record.session_path.clone(),
+            incident_id: record.incident_id.clone(),
+            tier: index + 1,
+            opened_at: record.opened_at,
+            prefix: tier.prefix.clone(),
+            ack: self.ack_link(record, now),
+        };
+        let summary = self.dispatcher.dispatch_to(&tier.channels, &event).await;
+        info!(
+            incident = %record.incident_id,
+            tier = index + 1,
+            delivered = summary.successes,
+            failed = summary.failures,
+            "Incident escalated"
+        );
+    }
+
+    async fn send_resolution(&self, record: &EscalationRecord, now: DateTime<Utc>) {
+        let mut channels: Vec<String> = Vec::new();
+        for channel in record.tiers.iter().flat_map(|tier| &tier.channels) {
+            if !channels
+                .iter()
+                .any(|seen| seen.eq_ignore_ascii_case(channel))
+            {
+                channels.push(channel.clone());
+            }
+        }
+        let event = NotificationEvent::IncidentResolved {
+            timestamp: record.resolved_at.unwrap_or(now),
+            session_path: record.session_path.clone(),
+            incident_id: record.incident_id.clone(),
+            opened_at: record.opened_at,
+            resolution: record
+                .resolution
+                .unwrap_or(EscalationResolution::Resumed)
+                .to_string(),
+        };
+        self.dispatcher.dispatch_to(&channels, &event).await;
+
+        let id = record.incident_id.clone();
+        let saved = self.state.update_critical_async(|state| {
+            if let Some(record) = state
+                .escalations
+                .iter_mut()
+                .find(|candidate| candidate.incident_id == id)
+            {
+                record.resolution_sent = true;
+            }
+        });
+        if let Err(err) = saved.await {
+            warn!(error = %err, incident = %id, "Failed to record escalation resolution notice");
+        }
+    }
+
+    fn ack_link(&self, record: &EscalationRecord, now: DateTime<Utc>) -> Option<AckLink> {
+        let (registry, base_url) = self.acks.as_ref()?;
+        let (incident, token) = registry.issue(&record.session_path, None, now)?;
+        Some(AckLink {
+            incident_id: incident.id,
+            url: base_url
+                .as_ref()
+                .map(|base| format!("{base}{ACK_ROUTE}/{token}")),
+        })
+    }
+}
+
+/// Escalations recorded in the state file, for `palingenesis notify digest`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EscalationDigest {
+    pub since: Option<DateTime<Utc>>,
+    /// Tiers entered across all incidents.
+    pub escalations: usize,
+    pub open: usize,
+    pub incidents: Vec<EscalationRecord>,
+    /// Resume costs over the same window, when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub costs: Option<CostSummary>,
+}
+
+impl EscalationDigest {
+    /// Incidents that escalated at least once at or after `since`.
+    pub fn from_state(state: &StateFile, since: Option<DateTime<Utc>>) -> Self {
+        let incidents: Vec<EscalationRecord> = state
+            .escalations
+            .iter()
+            .filter(|record| {
+                record
+                    .tiers
+                    .iter()
+                    .any(|tier| since.is_none_or(|since| tier.entered_at >= since))
+            })
+            .cloned()
+            .collect();
+        Self {
+            since,
+            escalations: incidents.iter().map(|record| record.tiers.len()).sum(),
+            open: incidents
+                .iter()
+                .filter(|record| record.resolved_at.is_none())
+                .count(),
+            incidents,
+            costs: None,
+        }
+    }
+
+    /// Include the costs of resumes recorded since the digest's `since`.
+    pub fn with_costs(mut self, state: &StateFile, currency: &str) -> Self {
+        self.costs = Some(CostSummary::from_state(state, self.since, currency));
+        self
+    }
+
+    /// One paragraph per incident: the tiers it reached and how it ended.
+    pub fn format(&self, locale: Locale) -> String {
+        let mut output = self.format_escalations(locale);
+        if let Some(costs) = &self.costs {
+            output.push_str("\n\n");
+            output.push_str(&render(
+                locale,
+                "digest.costs",
+                &[("costs", &costs.format())],
+            ));
+        }
+        output
+    }
+
+    fn format_escalations(&self, locale: Locale) -> String {
+        if self.incidents.is_empty() {
+            return text(locale, "digest.none").to_string();
+        }
+        let mut output = render(
+            locale,
+            "digest.summary",
+            &[
+                ("escalations", &self.escalations),
+                ("incidents", &self.incidents.len()),
+                ("open", &self.open),
+            ],
+        );
+        output.push('\n');
+        for record in &self.incidents {
+            let session = record.session_path.display();
+            let opened = record.opened_at.to_rfc3339();
+            output.push_str(&format!(
+                "\n{}\n  {}\n  {}\n",
+                record.incident_id,
+                render(locale, "digest.session", &[("session", &session)]),
+                render(locale, "digest.opened", &[("opened", &opened)]),
+            ));
+            for tier in &record.tiers {
+                let line = render(
+                    locale,
+                    "digest.tier",
+                    &[
+                        ("tier", &(tier.tier + 1)),
+                        ("age", &format_age(record.opened_at, tier.entered_at)),
+                        ("channels", &tier.channels.join(", ")),
+                    ],
+                );
+                output.push_str(&format!("  {line}\n"));
+            }
+            let ending = match (record.resolved_at, record.resolution) {
+                (Some(at), Some(resolution)) => render(
+                    locale,
+                    "digest.resolved",
+                    &[
+                        ("age", &format_age(record.opened_at, at)),
+                        (
+                            "resolution",
+                            &text(locale, &format!("resolution.{resolution}")),
+                        ),
+                    ],
+                ),
+                _ => text(locale, "digest.open").to_string(),
+            };
+            output.push_str(&format!("  {ending}\n"));
+        }
+        output.trim_end().to_string()
+    }
+}
+
+fn format_age(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    duration::format_compact(duration::between(from, to))
+}
+
+/// Start of the digest window `window` back from `now`; the earliest
+/// representable time for a window reaching past it.
+pub fn digest_since(now: DateTime<Utc>, window: std::time::Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(window)
+        .ok()
+        .and_then(|window| now.checked_sub_signed(window))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::{Instant, SystemTime};
+
+    use async_trait::async_trait;
+    use tempfile::tempdir;
+
+    use crate::notify::channel::NotificationChannel;
+    use crate::notify::error::NotifyError;
+    use crate::notify::message::format_event_message;
+    use crate::state::StateStore;
+
+    /// Wall clock moved by hand.
+    struct FakeClock {
+        wall: Mutex<SystemTime>,
+    }
+
+    impl FakeClock {
+        fn at(wall: DateTime<Utc>) -> Arc<Self> {
+            Arc::new(Self {
+                wall: Mutex::new(wall.into()),
+            })
+        }
+
+        fn advance_mins(&self, minutes: u64) {
+            *self.wall.lock().unwrap() += std::time::Duration::from_secs(minutes * 60);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn monotonic(&self) -> Instant {
+            Instant::now()
+        }
+
+        fn wall(&self) -> SystemTime {
+            *self.wall.lock().unwrap()
+        }
+    }
+
+    type Deliveries = Arc<Mutex<Vec<(&'static str, String)>>>;
+
+    struct RecordingChannel {
+        name: &'static str,
+        log: Deliveries,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
+            self.log
+                .lock()
+                .unwrap()
+                .push((self.name, format_event_message(event, Locale::En)));
+            Ok(())
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    fn tier(after_mins: u64, channels: &[&str], prefix: Option<&str>) -> EscalationTier {
+        EscalationTier {
+            after_secs: after_mins * 60,
+            channels: channels.iter().map(|name| name.to_string()).collect(),
+            prefix: prefix.map(str::to_string),
+        }
+    }
+
+    fn config() -> EscalationConfig {
+        EscalationConfig {
+            tiers: vec![
+                tier(0, &["ntfy"], None),
+                tier(30, &["slack"], Some("<!here>")),
+                tier(60, &["on-call"], None),
+            ],
+            ..EscalationConfig::default()
+        }
+    }
+
+    fn escalator(state: &StateHandle, clock: &Arc<FakeClock>, log: &Deliveries) -> Escalator {
+        let channels: Vec<Box<dyn NotificationChannel>> = ["ntfy", "slack", "on-call"]
+            .into_iter()
+            .map(|name| {
+                Box::new(RecordingChannel {
+                    name,
+                    log: Arc::clone(log),
+                }) as Box<dyn NotificationChannel>
+            })
+            .collect();
+        Escalator::new(&config(), Dispatcher::new(channels), state.clone())
+            .with_acks(Arc::new(AckRegistry::default()), None)
+            .with_clock(Arc::clone(clock) as Arc<dyn Clock>)
+    }
+
+    fn failed(session: &str, at: DateTime<Utc>) -> NotificationEvent {
+        NotificationEvent::ResumeFailed {
+            timestamp: at,
+            session_path: session.into(),
+            strategy: "same_session".to_string(),
+            error: "rate limited".to_string(),
+            progress: None,
+            percent: None,
+            incident: None,
+            ack: None,
+        }
+    }
+
+    fn drain(log: &Deliveries) -> Vec<(&'static str, String)> {
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    fn channels(deliveries: &[(&'static str, String)]) -> Vec<&'static str> {
+        deliveries.iter().map(|(name, _)| *name).collect()
+    }
+
+    #[tokio::test]
+    async fn walks_an_incident_through_the_tiers_across_a_restart() {
+        let dir = tempdir().unwrap();
+        let state_file = dir.path().join("state.json");
+        let start: DateTime<Utc> = "2026-03-01T09:00:00Z".parse().unwrap();
+        let clock = FakeClock::at(start);
+        let log = Deliveries::default();
+
+        let state = StateHandle::new(StateStore::with_path(state_file.clone()));
+        let first = escalator(&state, &clock, &log);
+        first.observe(&failed("/work/session.md", start));
+        // A second failure while the incident is open changes nothing.
+        first.observe(&failed("/work/session.md", start));
+
+        assert_eq!(first.tick().await, 1);
+        let tier1 = drain(&log);
+        assert_eq!(channels(&tier1), ["ntfy"]);
+        assert!(tier1[0].1.contains("escalation tier 1"));
+        assert!(tier1[0].1.contains("Acknowledge: `palingenesis ack"));
+
+        // Nothing new happens before the next tier is due.
+        clock.advance_mins(15);
+        assert_eq!(first.tick().await, 0);
+
+        clock.advance_mins(16);
+        assert_eq!(first.tick().await, 1);
+        let tier2 = drain(&log);
+        assert_eq!(channels(&tier2), ["slack"]);
+        assert!(tier2[0].1.starts_with("<!here> Incident "));
+        assert!(tier2[0].1.contains("still open after 31m"));
+        drop(first);
+        drop(state);
+
+        // The daemon restarts; the engaged tiers come back from disk.
+        let state = StateHandle::new(StateStore::with_path(state_file.clone()));
+        let second = escalator(&state, &clock, &log);
+        assert_eq!(second.restore(), 1);
+        assert_eq!(second.tick().await, 0);
+
+        clock.advance_mins(30);
+        assert_eq!(second.tick().await, 1);
+        let tier3 = drain(&log);
+        assert_eq!(channels(&tier3), ["on-call"]);
+        assert!(tier3[0].1.contains("escalation tier 3"));
+
+        clock.advance_mins(5);
+        second.observe(&NotificationEvent::ResumeSucceeded {
+            timestamp: start,
+            session_path: "/work/session.md".into(),
+            strategy: "same_session".to_string(),
+            wait_time_secs: 0,
+            progress: None,
+            percent: None,
+            git: None,
+        });
+        assert_eq!(second.tick().await, 1);
+        let resolved = drain(&log);
+        assert_eq!(channels(&resolved), ["ntfy", "slack", "on-call"]);
+        assert!(
+            resolved
+                .iter()
+                .all(|(_, message)| message.contains("ended after 1h 6m: the session resumed"))
+        );
+
+        // Escalation has stopped.
+        clock.advance_mins(120);
+        assert_eq!(second.tick().await, 0);
+        assert!(log.lock().unwrap().is_empty());
+
+        let digest = EscalationDigest::from_state(&state.snapshot(), None);
+        assert_eq!((digest.escalations, digest.open), (3, 0));
+        let text = digest.format(Locale::En);
+        assert!(text.starts_with("3 escalation(s) across 1 incident(s), 0 still open"));
+        assert!(text.contains("tier 2 after 31m: slack"));
+        assert!(text.contains("ended after 1h 6m: the session resumed"));
+        let ja = digest.format(Locale::Ja);
+        assert!(ja.contains("31m 後に段階 2: slack"));
+        assert!(ja.contains("1h 6m 後に終了: セッションが再開されました"));
+    }
+
+    #[tokio::test]
+    async fn acknowledgment_and_give_up_stop_the_escalation() {
+        let start: DateTime<Utc> = "2026-03-01T09:00:00Z".parse().unwrap();
+        let clock = FakeClock::at(start);
+        let log = Deliveries::default();
+        let dir = tempdir().unwrap();
+        let state = StateHandle::new(StateStore::with_path(dir.path().join("state.json")));
+        let escalator = escalator(&state, &clock, &log);
+
+        let id = escalator.open(Path::new("/work/a.md"), None).unwrap();
+        escalator.open(Path::new("/work/b.md"), None).unwrap();
+        escalator.tick().await;
+        drain(&log);
+
+        // `palingenesis ack` records the acknowledgment, which resolves.
+        let (registry, _) = escalator.acks.as_ref().unwrap();
+        let incident = registry.acknowledge(&id, start).unwrap();
+        crate::notify::ack::record_acknowledgment(&state, &incident, false);
+        escalator.observe(&NotificationEvent::ResumeLoopSuspected {
+            timestamp: start,
+            session_path: "/work/b.md".into(),
+            resumes: 5,
+            span_secs: 600,
+            ack: None,
+        });
+
+        clock.advance_mins(45);
+        assert_eq!(escalator.tick().await, 2);
+        let notices = drain(&log);
+        assert_eq!(channels(&notices), ["ntfy", "ntfy"]);
+        assert!(notices[0].1.contains(": acknowledged."));
+        assert!(notices[1].1.contains(": automatic resumes gave up."));
+
+        let snapshot = state.snapshot();
+        let digest = EscalationDigest::from_state(&snapshot, Some(start));
+        assert_eq!((digest.escalations, digest.open), (2, 0));
+        let later = digest_since(
+            start + TimeDelta::hours(1),
+            std::time::Duration::from_secs(1800),
+        );
+        assert!(
+            EscalationDigest::from_state(&snapshot, Some(later))
+                .incidents
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn huge_digest_window_covers_everything() {
+        let now = Utc::now();
+        assert_eq!(
+            digest_since(now, std::time::Duration::from_secs(u64::MAX)),
+            DateTime::<Utc>::MIN_UTC
+        );
+        assert_eq!(
+            digest_since(now, std::time::Duration::from_secs(60)),
+            now - TimeDelta::minutes(1)
+        );
+    }
+
+    #[test]
+    fn digest_renders_resume_costs() {
+        let mut state = StateFile::default();
+        state
+            .resume_history_mut(Path::new("/work/session.md"))
+            .record_cost(crate::state::ResumeCost {
+                at: Utc::now(),
+                model: Some("claude-sonnet-4".to_string()),
+                tokens: None,
+                cost: Some(1.5),
+                time_saved_seconds: 600.0,
+                value_saved: None,
+            });
+
+        let digest = EscalationDigest::from_state(&state, None).with_costs(&state, "USD");
+        assert_eq!(
+            digest.format(Locale::En),
+            "No escalations recorded\n\nResume costs: 1 resume(s) across 1 session(s)\n  \
+             estimated API cost: 1.50 USD\n  value of time saved: unknown (10m)\n\
+             \n/work/session.md\n  1 resume(s), 0 input / 0 output tokens\n  cost 1.50 USD, value unknown"
+        );
+        assert!(EscalationDigest::from_state(&state, None).costs.is_none());
+    }
+}
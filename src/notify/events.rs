@@ -1,14 +1,48 @@
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+use crate::config::Paths;
+use crate::config::schema::PathDisplayMode;
+use crate::daemon::initiator::Initiator;
+use crate::i18n::{Locale, render, text};
+use crate::notify::ack::AckLink;
+use crate::resume::git_context::GitContext;
+use crate::util::path_display::PathDisplay;
+use crate::util::{content, duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventSeverity {
     Info,
     Warning,
     Error,
+    Critical,
+}
+
+impl EventSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// Parse a severity name as written in config (case-insensitive).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "info" => Some(Self::Info),
+            "warning" | "warn" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
 }
 
 /// Events emitted by the notification system.
@@ -20,23 +54,54 @@ pub enum NotificationEvent {
         session_path: PathBuf,
         stop_reason: String,
         details: Option<String>,
+        /// `resume.exclude_sessions` pattern that kept this session from auto-resume.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        excluded_by: Option<String>,
     },
     ResumeAttempted {
         timestamp: DateTime<Utc>,
         session_path: PathBuf,
         strategy: String,
+        /// Workflow progress, e.g. "step 7 of 12 (58%), steps 1-6 complete".
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<String>,
+        /// Completion percentage when the total step count is known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
     },
     ResumeSucceeded {
         timestamp: DateTime<Utc>,
         session_path: PathBuf,
         strategy: String,
         wait_time_secs: u64,
+        /// Workflow progress, e.g. "step 7 of 12 (58%), steps 1-6 complete".
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<String>,
+        /// Completion percentage when the total step count is known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+
+        /// Workspace git state after the resume (`resume.capture_git_context`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        git: Option<GitContext>,
     },
     ResumeFailed {
         timestamp: DateTime<Utc>,
         session_path: PathBuf,
         strategy: String,
         error: String,
+        /// Workflow progress, e.g. "step 7 of 12 (58%), steps 1-6 complete".
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<String>,
+        /// Completion percentage when the total step count is known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        /// Stop-time transcript snapshot for the stop that led to the resume.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        incident: Option<PathBuf>,
+        /// Acknowledgment handle for the incident, added at dispatch.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ack: Option<AckLink>,
     },
     DaemonStarted {
         timestamp: DateTime<Utc>,
@@ -46,6 +111,217 @@ pub enum NotificationEvent {
         timestamp: DateTime<Utc>,
         reason: String,
     },
+    /// A resume created a session but failed before the daemon could track it.
+    SessionOrphaned {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        /// Session the failed resume started from.
+        source_session: PathBuf,
+        reason: String,
+    },
+    /// The current session's file was deleted.
+    SessionDeleted {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        /// Backup copied back into place, if the session was restored.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restored_from: Option<PathBuf>,
+    },
+    /// A resumed session runs on a different model than the session it continues.
+    ModelChanged {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        previous_model: String,
+        current_model: String,
+    },
+    /// The host was suspended; pending resumes are re-checked before firing.
+    SystemResumed {
+        timestamp: DateTime<Utc>,
+        /// Length of the sleep gap.
+        slept_secs: u64,
+    },
+    /// Next-step.md had no recognizable step; the resume fell back to session progress.
+    NextStepInvalid {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        next_step_path: PathBuf,
+        /// First lines that matched no step syntax.
+        unmatched_lines: Vec<String>,
+    },
+    /// A new session was started in a fresh git worktree (`resume.new_session.workspace_mode`).
+    WorktreeCreated {
+        timestamp: DateTime<Utc>,
+        /// Session that ran out of context.
+        session_path: PathBuf,
+        workspace: PathBuf,
+        worktree_path: PathBuf,
+        branch: String,
+    },
+    /// A resume was postponed, e.g. until a maintenance window ends.
+    ResumeDeferred {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        /// When the resume will run.
+        until: DateTime<Utc>,
+        /// Why it was postponed (e.g. "maintenance window").
+        reason: String,
+    },
+    /// A workflow finished; sent once by the completion wrap-up.
+    SessionCompleted {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        /// Time from the first recorded activity to completion, when known.
+        duration_secs: Option<u64>,
+        /// Successful resumes of this session.
+        resumes: u64,
+        /// Estimated time saved by those resumes.
+        time_saved_seconds: f64,
+    },
+    /// A session hit `resume.max_resumes_per_hour`; automatic resumes stop
+    /// until the window passes or the operator clears it.
+    ResumeLoopSuspected {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        /// Automatic resumes within the window.
+        resumes: u32,
+        /// Time between the first counted resume and detection.
+        span_secs: u64,
+        /// Acknowledgment handle for the incident, added at dispatch.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ack: Option<AckLink>,
+    },
+    /// A per-session resume sidecar could not be parsed; the resume used the
+    /// inherited configuration instead.
+    ResumeSidecarInvalid {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        sidecar_path: PathBuf,
+        error: String,
+    },
+    /// The local clock disagrees with provider `Date:` headers by more than
+    /// `classifier.clock_skew_warn_secs`; sent once per daemon run.
+    ClockSkewDetected {
+        timestamp: DateTime<Utc>,
+        /// Provider clock minus local clock (seconds).
+        skew_secs: f64,
+    },
+    /// A session file was read despite a byte order mark or UTF-16
+    /// encoding; sent once per file and daemon run.
+    FileEncodingFixed {
+        timestamp: DateTime<Utc>,
+        file: PathBuf,
+        /// Encoding the file was saved in, e.g. `UTF-16LE`.
+        encoding: String,
+    },
+    /// A `[[schedules]]` firing finished or was skipped.
+    ScheduledRun {
+        timestamp: DateTime<Utc>,
+        schedule: String,
+        /// `scheduled`, `catch_up` or `manual`.
+        trigger: String,
+        /// `succeeded`, `failed` or `skipped`.
+        outcome: String,
+        workdir: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_path: Option<PathBuf>,
+        /// Why the firing was skipped or failed.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    /// Startup reconciliation corrected the state file after an unclean
+    /// shutdown.
+    StateReconciled {
+        timestamp: DateTime<Utc>,
+        /// One line per correction.
+        corrections: Vec<String>,
+        /// Reconciliation report in the state directory.
+        report: PathBuf,
+    },
+    /// The artifact GC found artifacts past their retention under settings
+    /// that are not approved yet; nothing was deleted.
+    ArtifactGcPending {
+        timestamp: DateTime<Utc>,
+        /// Artifacts that would be deleted.
+        artifacts: usize,
+        /// Their total size.
+        bytes: u64,
+        /// One line per artifact kind.
+        summary: Vec<String>,
+        /// Dry-run plan in the state directory.
+        plan: PathBuf,
+    },
+    /// The state file was written by a newer schema; the daemon leaves it
+    /// untouched and keeps state in memory only.
+    StateReadOnly {
+        timestamp: DateTime<Utc>,
+        state_file: PathBuf,
+        found_version: u32,
+        supported_version: u32,
+    },
+    /// Observe mode: a resume the daemon would have run, reported instead
+    /// of executed.
+    ActionObserved {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        stop_reason: String,
+        /// The planned action, e.g. `new_session resume`.
+        action: String,
+    },
+    /// The installed opencode reports a different version than last recorded.
+    OpenCodeVersionChanged {
+        timestamp: DateTime<Utc>,
+        /// Last recorded version; `None` when none was recorded yet.
+        previous: Option<String>,
+        current: String,
+        /// Integration assumptions the new version fails, e.g. a missing flag.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        problems: Vec<String>,
+    },
+    /// Someone paused, resumed or started a session (e.g. "paused by @U123 via Slack").
+    ControlApplied {
+        timestamp: DateTime<Utc>,
+        /// `pause`, `resume` or `new_session`.
+        action: String,
+        initiator: Initiator,
+    },
+    /// Sent on request to check that a channel is configured correctly.
+    TestNotification {
+        timestamp: DateTime<Utc>,
+        message: String,
+    },
+    /// An open incident entered a `notifications.escalation` tier.
+    IncidentEscalated {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        incident_id: String,
+        /// Tier number, from 1.
+        tier: usize,
+        opened_at: DateTime<Utc>,
+        /// The tier's `prefix`, e.g. a mention.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prefix: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ack: Option<AckLink>,
+    },
+    /// The previous daemon instance crashed, panicked or was killed.
+    UnexpectedExit {
+        timestamp: DateTime<Utc>,
+        /// PID of the previous instance.
+        pid: u32,
+        /// `error`, `panic` or `killed`.
+        cause: String,
+        detail: String,
+    },
+    /// An escalated incident ended; sent to every tier it reached.
+    IncidentResolved {
+        timestamp: DateTime<Utc>,
+        session_path: PathBuf,
+        incident_id: String,
+        opened_at: DateTime<Utc>,
+        /// `resumed`, `acknowledged`, `gave_up` or `reconciled`.
+        resolution: String,
+    },
 }
 
 impl NotificationEvent {
@@ -57,6 +333,29 @@ impl NotificationEvent {
             Self::ResumeFailed { timestamp, .. } => *timestamp,
             Self::DaemonStarted { timestamp, .. } => *timestamp,
             Self::DaemonStopped { timestamp, .. } => *timestamp,
+            Self::SessionOrphaned { timestamp, .. } => *timestamp,
+            Self::SessionDeleted { timestamp, .. } => *timestamp,
+            Self::ModelChanged { timestamp, .. } => *timestamp,
+            Self::SystemResumed { timestamp, .. } => *timestamp,
+            Self::NextStepInvalid { timestamp, .. } => *timestamp,
+            Self::WorktreeCreated { timestamp, .. } => *timestamp,
+            Self::ResumeDeferred { timestamp, .. } => *timestamp,
+            Self::SessionCompleted { timestamp, .. } => *timestamp,
+            Self::ResumeLoopSuspected { timestamp, .. } => *timestamp,
+            Self::ResumeSidecarInvalid { timestamp, .. } => *timestamp,
+            Self::ClockSkewDetected { timestamp, .. } => *timestamp,
+            Self::FileEncodingFixed { timestamp, .. } => *timestamp,
+            Self::ScheduledRun { timestamp, .. } => *timestamp,
+            Self::StateReconciled { timestamp, .. } => *timestamp,
+            Self::ArtifactGcPending { timestamp, .. } => *timestamp,
+            Self::StateReadOnly { timestamp, .. } => *timestamp,
+            Self::ActionObserved { timestamp, .. } => *timestamp,
+            Self::OpenCodeVersionChanged { timestamp, .. } => *timestamp,
+            Self::ControlApplied { timestamp, .. } => *timestamp,
+            Self::TestNotification { timestamp, .. } => *timestamp,
+            Self::IncidentEscalated { timestamp, .. } => *timestamp,
+            Self::IncidentResolved { timestamp, .. } => *timestamp,
+            Self::UnexpectedExit { timestamp, .. } => *timestamp,
         }
     }
 
@@ -68,6 +367,135 @@ impl NotificationEvent {
             Self::ResumeFailed { .. } => "resume_failed",
             Self::DaemonStarted { .. } => "daemon_started",
             Self::DaemonStopped { .. } => "daemon_stopped",
+            Self::SessionOrphaned { .. } => "session_orphaned",
+            Self::SessionDeleted { .. } => "session_deleted",
+            Self::ModelChanged { .. } => "model_changed",
+            Self::SystemResumed { .. } => "system_resumed",
+            Self::NextStepInvalid { .. } => "next_step_invalid",
+            Self::WorktreeCreated { .. } => "worktree_created",
+            Self::ResumeDeferred { .. } => "resume_deferred",
+            Self::SessionCompleted { .. } => "session_completed",
+            Self::ResumeLoopSuspected { .. } => "resume_loop_suspected",
+            Self::ResumeSidecarInvalid { .. } => "resume_sidecar_invalid",
+            Self::ClockSkewDetected { .. } => "clock_skew_detected",
+            Self::FileEncodingFixed { .. } => "file_encoding_fixed",
+            Self::ScheduledRun { .. } => "scheduled_run",
+            Self::StateReconciled { .. } => "state_reconciled",
+            Self::ArtifactGcPending { .. } => "artifact_gc_pending",
+            Self::StateReadOnly { .. } => "state_read_only",
+            Self::ActionObserved { .. } => "action_observed",
+            Self::OpenCodeVersionChanged { .. } => "opencode_version_changed",
+            Self::ControlApplied { .. } => "control_applied",
+            Self::TestNotification { .. } => "test_notification",
+            Self::IncidentEscalated { .. } => "incident_escalated",
+            Self::IncidentResolved { .. } => "incident_resolved",
+            Self::UnexpectedExit { .. } => "unexpected_exit",
+        }
+    }
+
+    /// Workflow progress summary attached to resume events.
+    pub fn progress(&self) -> Option<&str> {
+        match self {
+            Self::ResumeAttempted { progress, .. }
+            | Self::ResumeSucceeded { progress, .. }
+            | Self::ResumeFailed { progress, .. } => progress.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Completion percentage attached to resume events.
+    pub fn percent(&self) -> Option<u8> {
+        match self {
+            Self::ResumeAttempted { percent, .. }
+            | Self::ResumeSucceeded { percent, .. }
+            | Self::ResumeFailed { percent, .. } => *percent,
+            _ => None,
+        }
+    }
+
+    /// Session the event is about, if any.
+    pub fn session_path(&self) -> Option<&Path> {
+        match self {
+            Self::SessionStopped { session_path, .. }
+            | Self::ResumeAttempted { session_path, .. }
+            | Self::ResumeSucceeded { session_path, .. }
+            | Self::ResumeFailed { session_path, .. }
+            | Self::SessionOrphaned { session_path, .. }
+            | Self::SessionDeleted { session_path, .. }
+            | Self::ModelChanged { session_path, .. }
+            | Self::NextStepInvalid { session_path, .. }
+            | Self::WorktreeCreated { session_path, .. }
+            | Self::ResumeDeferred { session_path, .. }
+            | Self::SessionCompleted { session_path, .. }
+            | Self::ResumeLoopSuspected { session_path, .. }
+            | Self::ResumeSidecarInvalid { session_path, .. }
+            | Self::ActionObserved { session_path, .. }
+            | Self::IncidentEscalated { session_path, .. }
+            | Self::IncidentResolved { session_path, .. } => Some(session_path),
+            Self::ScheduledRun { session_path, .. } => session_path.as_deref(),
+            Self::DaemonStarted { .. }
+            | Self::DaemonStopped { .. }
+            | Self::SystemResumed { .. }
+            | Self::ClockSkewDetected { .. }
+            | Self::FileEncodingFixed { .. }
+            | Self::StateReconciled { .. }
+            | Self::ArtifactGcPending { .. }
+            | Self::StateReadOnly { .. }
+            | Self::OpenCodeVersionChanged { .. }
+            | Self::ControlApplied { .. }
+            | Self::TestNotification { .. }
+            | Self::UnexpectedExit { .. } => None,
+        }
+    }
+
+    /// Acknowledgment slot of failure and attention events.
+    pub fn ack_mut(&mut self) -> Option<&mut Option<AckLink>> {
+        match self {
+            Self::ResumeFailed { ack, .. } | Self::ResumeLoopSuspected { ack, .. } => Some(ack),
+            _ => None,
+        }
+    }
+
+    /// Line inviting the reader to acknowledge the incident.
+    pub fn ack_notice(&self, locale: Locale) -> Option<String> {
+        match self {
+            Self::ResumeFailed { ack: Some(ack), .. }
+            | Self::ResumeLoopSuspected { ack: Some(ack), .. }
+            | Self::IncidentEscalated { ack: Some(ack), .. } => Some(ack.notice(locale)),
+            _ => None,
+        }
+    }
+
+    /// Still sent for an acknowledged incident: automatic resumes have
+    /// stopped for the session.
+    pub fn is_give_up(&self) -> bool {
+        matches!(self, Self::ResumeLoopSuspected { .. })
+    }
+
+    /// The session's incident is over.
+    pub fn resolves_incident(&self) -> bool {
+        matches!(
+            self,
+            Self::ResumeSucceeded { .. } | Self::SessionCompleted { .. }
+        )
+    }
+
+    /// Text to put before the message, such as an escalation tier's mention.
+    pub fn prefix(&self) -> Option<&str> {
+        match self {
+            Self::IncidentEscalated { prefix, .. } => prefix.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Status line for a stop that will not be auto-resumed.
+    pub fn exclusion_notice(&self, locale: Locale) -> Option<String> {
+        match self {
+            Self::SessionStopped {
+                excluded_by: Some(pattern),
+                ..
+            } => Some(render(locale, "notice.excluded", &[("pattern", pattern)])),
+            _ => None,
         }
     }
 
@@ -79,8 +507,264 @@ impl NotificationEvent {
             Self::ResumeFailed { .. } => EventSeverity::Error,
             Self::DaemonStarted { .. } => EventSeverity::Info,
             Self::DaemonStopped { .. } => EventSeverity::Warning,
+            Self::SessionOrphaned { .. } => EventSeverity::Warning,
+            Self::SessionDeleted { .. } => EventSeverity::Critical,
+            Self::ModelChanged { .. } => EventSeverity::Warning,
+            Self::SystemResumed { .. } => EventSeverity::Info,
+            Self::NextStepInvalid { .. } => EventSeverity::Warning,
+            Self::WorktreeCreated { .. } => EventSeverity::Info,
+            Self::ResumeDeferred { .. } => EventSeverity::Info,
+            Self::SessionCompleted { .. } => EventSeverity::Info,
+            Self::ResumeLoopSuspected { .. } => EventSeverity::Error,
+            Self::ResumeSidecarInvalid { .. } => EventSeverity::Warning,
+            Self::ClockSkewDetected { .. } => EventSeverity::Warning,
+            Self::FileEncodingFixed { .. } => EventSeverity::Warning,
+            Self::ScheduledRun { outcome, .. } if outcome == "failed" => EventSeverity::Error,
+            Self::ScheduledRun { .. } => EventSeverity::Info,
+            Self::StateReconciled { .. } => EventSeverity::Info,
+            Self::ArtifactGcPending { .. } => EventSeverity::Info,
+            Self::StateReadOnly { .. } => EventSeverity::Error,
+            Self::ActionObserved { .. } => EventSeverity::Info,
+            Self::OpenCodeVersionChanged { problems, .. } if problems.is_empty() => {
+                EventSeverity::Info
+            }
+            Self::OpenCodeVersionChanged { .. } => EventSeverity::Warning,
+            Self::ControlApplied { .. } => EventSeverity::Info,
+            Self::TestNotification { .. } => EventSeverity::Info,
+            Self::IncidentEscalated { .. } => EventSeverity::Error,
+            Self::IncidentResolved { .. } => EventSeverity::Info,
+            Self::UnexpectedExit { .. } => EventSeverity::Warning,
+        }
+    }
+
+    /// Cut free-text fields (details, errors, quoted lines) to `max_chars`
+    /// before the event is kept beyond its delivery.
+    pub fn bound_content(&mut self, max_chars: usize) {
+        for text in self.free_text_mut() {
+            content::cap_in_place(text, max_chars);
+        }
+    }
+
+    /// Bytes of free text the event carries; what [`Self::bound_content`] limits.
+    pub fn content_bytes(&self) -> usize {
+        match self {
+            Self::SessionStopped { details, .. } => details.as_ref().map_or(0, String::len),
+            Self::ResumeAttempted { progress, .. } | Self::ResumeSucceeded { progress, .. } => {
+                progress.as_ref().map_or(0, String::len)
+            }
+            Self::ResumeFailed {
+                error, progress, ..
+            } => error.len() + progress.as_ref().map_or(0, String::len),
+            Self::DaemonStopped { reason, .. }
+            | Self::SessionOrphaned { reason, .. }
+            | Self::ResumeDeferred { reason, .. } => reason.len(),
+            Self::NextStepInvalid {
+                unmatched_lines, ..
+            } => unmatched_lines.iter().map(String::len).sum(),
+            Self::ResumeSidecarInvalid { error, .. } => error.len(),
+            Self::ScheduledRun { detail, .. } => detail.as_ref().map_or(0, String::len),
+            Self::OpenCodeVersionChanged { problems, .. } => problems.iter().map(String::len).sum(),
+            Self::StateReconciled { corrections, .. } => corrections.iter().map(String::len).sum(),
+            Self::ArtifactGcPending { summary, .. } => summary.iter().map(String::len).sum(),
+            _ => 0,
+        }
+    }
+
+    /// The event with its paths rendered by `display`, for channels that
+    /// show them; history keeps the original.
+    pub fn with_display_paths(&self, display: &PathDisplay) -> Cow<'_, Self> {
+        if display.mode() == PathDisplayMode::Absolute {
+            return Cow::Borrowed(self);
+        }
+        let mut event = self.clone();
+        for path in event.paths_mut() {
+            display.apply(path);
+        }
+        Cow::Owned(event)
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut PathBuf> {
+        match self {
+            Self::ResumeFailed {
+                session_path,
+                incident,
+                ..
+            } => std::iter::once(session_path)
+                .chain(incident.iter_mut())
+                .collect(),
+            Self::SessionOrphaned {
+                session_path,
+                source_session,
+                ..
+            } => vec![session_path, source_session],
+            Self::SessionDeleted {
+                session_path,
+                restored_from,
+                ..
+            } => std::iter::once(session_path)
+                .chain(restored_from.iter_mut())
+                .collect(),
+            Self::NextStepInvalid {
+                session_path,
+                next_step_path,
+                ..
+            } => vec![session_path, next_step_path],
+            Self::WorktreeCreated {
+                session_path,
+                workspace,
+                worktree_path,
+                ..
+            } => vec![session_path, workspace, worktree_path],
+            Self::ResumeSidecarInvalid {
+                session_path,
+                sidecar_path,
+                ..
+            } => vec![session_path, sidecar_path],
+            Self::StateReadOnly { state_file, .. } => vec![state_file],
+            Self::StateReconciled { report, .. } => vec![report],
+            Self::ArtifactGcPending { plan, .. } => vec![plan],
+            Self::FileEncodingFixed { file, .. } => vec![file],
+            Self::ScheduledRun {
+                workdir,
+                session_path,
+                ..
+            } => std::iter::once(workdir)
+                .chain(session_path.iter_mut())
+                .collect(),
+            Self::SessionStopped { session_path, .. }
+            | Self::ResumeAttempted { session_path, .. }
+            | Self::ResumeSucceeded { session_path, .. }
+            | Self::ModelChanged { session_path, .. }
+            | Self::ResumeDeferred { session_path, .. }
+            | Self::SessionCompleted { session_path, .. }
+            | Self::ResumeLoopSuspected { session_path, .. }
+            | Self::ActionObserved { session_path, .. }
+            | Self::IncidentEscalated { session_path, .. }
+            | Self::IncidentResolved { session_path, .. } => vec![session_path],
+            Self::DaemonStarted { .. }
+            | Self::DaemonStopped { .. }
+            | Self::SystemResumed { .. }
+            | Self::ClockSkewDetected { .. }
+            | Self::OpenCodeVersionChanged { .. }
+            | Self::ControlApplied { .. }
+            | Self::TestNotification { .. }
+            | Self::UnexpectedExit { .. } => Vec::new(),
+        }
+    }
+
+    fn free_text_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Self::SessionStopped { details, .. } => details.iter_mut().collect(),
+            Self::ResumeAttempted { progress, .. } | Self::ResumeSucceeded { progress, .. } => {
+                progress.iter_mut().collect()
+            }
+            Self::ResumeFailed {
+                error, progress, ..
+            } => std::iter::once(error).chain(progress.iter_mut()).collect(),
+            Self::DaemonStopped { reason, .. }
+            | Self::SessionOrphaned { reason, .. }
+            | Self::ResumeDeferred { reason, .. } => vec![reason],
+            Self::NextStepInvalid {
+                unmatched_lines, ..
+            } => unmatched_lines.iter_mut().collect(),
+            Self::ResumeSidecarInvalid { error, .. } => vec![error],
+            Self::ScheduledRun { detail, .. } => detail.iter_mut().collect(),
+            Self::UnexpectedExit { detail, .. } => vec![detail],
+            Self::OpenCodeVersionChanged { problems, .. } => problems.iter_mut().collect(),
+            Self::StateReconciled { corrections, .. } => corrections.iter_mut().collect(),
+            Self::ArtifactGcPending { summary, .. } => summary.iter_mut().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// [`format_sleep_gap`] for an optional duration, "unknown" when missing.
+/// Line naming the daemon instance; only named instances get one.
+pub fn instance_notice(locale: Locale) -> Option<String> {
+    Paths::instance().map(|name| render(locale, "notice.instance", &[("instance", &name)]))
+}
+
+pub fn format_completion_duration(secs: Option<u64>, locale: Locale) -> String {
+    secs.map(format_sleep_gap)
+        .unwrap_or_else(|| text(locale, "common.unknown").to_string())
+}
+
+/// How long an incident has been open at `at`, e.g. `1h 5m`.
+pub fn format_incident_age(opened_at: DateTime<Utc>, at: DateTime<Utc>) -> String {
+    duration::format_compact(duration::between(opened_at, at))
+}
+
+/// Compact time saved such as `10m`; negative or non-finite values read `0s`.
+pub fn format_time_saved(secs: f64) -> String {
+    duration::format_compact(duration::from_secs_f64(secs))
+}
+
+/// Version change message, listing any failed compatibility checks.
+pub fn format_version_change(
+    previous: Option<&str>,
+    current: &str,
+    problems: &[String],
+    timestamp: DateTime<Utc>,
+    locale: Locale,
+) -> String {
+    let time = timestamp.to_rfc3339();
+    let mut message = match previous {
+        Some(previous) => render(
+            locale,
+            "version.changed",
+            &[
+                ("previous", &previous),
+                ("current", &current),
+                ("time", &time),
+            ],
+        ),
+        None => render(
+            locale,
+            "version.detected",
+            &[("current", &current), ("time", &time)],
+        ),
+    };
+    message.push('\n');
+    if problems.is_empty() {
+        message.push_str(text(locale, "version.checks_passed"));
+    } else {
+        message.push_str(text(locale, "version.checks_failed"));
+        for problem in problems {
+            message.push_str(&format!("\n- {problem}"));
         }
     }
+    message
+}
+
+/// Loop warning headline, e.g. `5 resumes in 20 minutes — possible workflow loop`.
+pub fn format_loop_summary(resumes: u32, span_secs: u64, locale: Locale) -> String {
+    let minutes = span_secs.div_ceil(60).max(1);
+    let key = if minutes == 1 {
+        "loop.summary_one"
+    } else {
+        "loop.summary"
+    };
+    render(locale, key, &[("resumes", &resumes), ("minutes", &minutes)])
+}
+
+/// Control action headline, e.g. `Daemon paused by @U123 via Slack`.
+pub fn format_control_action(action: &str, initiator: &Initiator, locale: Locale) -> String {
+    let key = match action {
+        "pause" => "control.pause",
+        "resume" => "control.resume",
+        "new_session" => "control.new_session",
+        _ => "control.other",
+    };
+    render(
+        locale,
+        key,
+        &[("action", &action), ("initiator", initiator)],
+    )
+}
+
+/// Compact sleep gap such as `7h 52m` or `45s`.
+pub fn format_sleep_gap(secs: u64) -> String {
+    duration::format_compact(Duration::from_secs(secs))
 }
 
 #[cfg(test)]
@@ -107,6 +791,7 @@ mod tests {
                     session_path: session_path.clone(),
                     stop_reason: "rate_limit".to_string(),
                     details: None,
+                    excluded_by: None,
                 },
                 "session_stopped",
                 EventSeverity::Warning,
@@ -116,6 +801,8 @@ mod tests {
                     timestamp: ts,
                     session_path: session_path.clone(),
                     strategy: "same_session".to_string(),
+                    progress: None,
+                    percent: None,
                 },
                 "resume_attempted",
                 EventSeverity::Info,
@@ -126,6 +813,9 @@ mod tests {
                     session_path: session_path.clone(),
                     strategy: "same_session".to_string(),
                     wait_time_secs: 42,
+                    progress: None,
+                    percent: None,
+                    git: None,
                 },
                 "resume_succeeded",
                 EventSeverity::Info,
@@ -136,6 +826,10 @@ mod tests {
                     session_path: session_path.clone(),
                     strategy: "same_session".to_string(),
                     error: "boom".to_string(),
+                    progress: None,
+                    percent: None,
+                    incident: None,
+                    ack: None,
                 },
                 "resume_failed",
                 EventSeverity::Error,
@@ -156,14 +850,302 @@ mod tests {
                 "daemon_stopped",
                 EventSeverity::Warning,
             ),
+            (
+                NotificationEvent::SessionOrphaned {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/new-session"),
+                    source_session: session_path.clone(),
+                    reason: "state store error".to_string(),
+                },
+                "session_orphaned",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::SessionDeleted {
+                    timestamp: ts,
+                    session_path: session_path.clone(),
+                    restored_from: None,
+                },
+                "session_deleted",
+                EventSeverity::Critical,
+            ),
+            (
+                NotificationEvent::ModelChanged {
+                    timestamp: ts,
+                    session_path: session_path.clone(),
+                    previous_model: "anthropic/claude-opus".to_string(),
+                    current_model: "openai/gpt-5".to_string(),
+                },
+                "model_changed",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::SystemResumed {
+                    timestamp: ts,
+                    slept_secs: 28_320,
+                },
+                "system_resumed",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::NextStepInvalid {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    next_step_path: PathBuf::from("/tmp/Next-step.md"),
+                    unmatched_lines: vec!["# Plan".to_string()],
+                },
+                "next_step_invalid",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::WorktreeCreated {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    workspace: PathBuf::from("/tmp/repo"),
+                    worktree_path: PathBuf::from("/tmp/worktrees/session-1"),
+                    branch: "palingenesis/1".to_string(),
+                },
+                "worktree_created",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::ResumeDeferred {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    stop_reason: "rate_limit".to_string(),
+                    until: ts,
+                    reason: "maintenance window".to_string(),
+                },
+                "resume_deferred",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::SessionCompleted {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    duration_secs: Some(3600),
+                    resumes: 2,
+                    time_saved_seconds: 600.0,
+                },
+                "session_completed",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::ResumeLoopSuspected {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    resumes: 5,
+                    span_secs: 1_200,
+                    ack: None,
+                },
+                "resume_loop_suspected",
+                EventSeverity::Error,
+            ),
+            (
+                NotificationEvent::ResumeSidecarInvalid {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    sidecar_path: PathBuf::from("/tmp/session.palingenesis.toml"),
+                    error: "expected `=`".to_string(),
+                },
+                "resume_sidecar_invalid",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::ClockSkewDetected {
+                    timestamp: ts,
+                    skew_secs: -95.0,
+                },
+                "clock_skew_detected",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::FileEncodingFixed {
+                    timestamp: ts,
+                    file: PathBuf::from("/tmp/session.md"),
+                    encoding: "UTF-8 with BOM".to_string(),
+                },
+                "file_encoding_fixed",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::ScheduledRun {
+                    timestamp: ts,
+                    schedule: "ci-triage".to_string(),
+                    trigger: "scheduled".to_string(),
+                    outcome: "failed".to_string(),
+                    workdir: PathBuf::from("/tmp/ci"),
+                    session_path: None,
+                    detail: Some("opencode exited with status 1".to_string()),
+                },
+                "scheduled_run",
+                EventSeverity::Error,
+            ),
+            (
+                NotificationEvent::StateReconciled {
+                    timestamp: ts,
+                    corrections: vec!["Incident inc-1 closed".to_string()],
+                    report: PathBuf::from("/tmp/reconcile-report.json"),
+                },
+                "state_reconciled",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::ArtifactGcPending {
+                    timestamp: ts,
+                    artifacts: 3,
+                    bytes: 4096,
+                    summary: vec!["backups: 3 (4.0 KB)".to_string()],
+                    plan: PathBuf::from("/tmp/gc-plan.json"),
+                },
+                "artifact_gc_pending",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::StateReadOnly {
+                    timestamp: ts,
+                    state_file: PathBuf::from("/tmp/state.json"),
+                    found_version: 2,
+                    supported_version: 1,
+                },
+                "state_read_only",
+                EventSeverity::Error,
+            ),
+            (
+                NotificationEvent::ActionObserved {
+                    timestamp: ts,
+                    session_path: PathBuf::from("/tmp/session.md"),
+                    stop_reason: "rate_limit".to_string(),
+                    action: "same_session resume".to_string(),
+                },
+                "action_observed",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::OpenCodeVersionChanged {
+                    timestamp: ts,
+                    previous: Some("0.3.1".to_string()),
+                    current: "0.4.0".to_string(),
+                    problems: Vec::new(),
+                },
+                "opencode_version_changed",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::OpenCodeVersionChanged {
+                    timestamp: ts,
+                    previous: Some("0.3.1".to_string()),
+                    current: "0.4.0".to_string(),
+                    problems: vec!["`opencode new` no longer accepts --workdir".to_string()],
+                },
+                "opencode_version_changed",
+                EventSeverity::Warning,
+            ),
+            (
+                NotificationEvent::ControlApplied {
+                    timestamp: ts,
+                    action: "pause".to_string(),
+                    initiator: Initiator::Slack {
+                        user_id: "U123".to_string(),
+                    },
+                },
+                "control_applied",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::TestNotification {
+                    timestamp: ts,
+                    message: "hello".to_string(),
+                },
+                "test_notification",
+                EventSeverity::Info,
+            ),
+            (
+                NotificationEvent::UnexpectedExit {
+                    timestamp: ts,
+                    pid: 4242,
+                    cause: "killed".to_string(),
+                    detail: "stale PID file without an exit record".to_string(),
+                },
+                "unexpected_exit",
+                EventSeverity::Warning,
+            ),
         ];
 
         for (event, event_type, severity) in cases {
             assert_eq!(event.event_type(), event_type);
             assert_eq!(event.severity(), severity);
+            for locale in Locale::ALL {
+                let title = crate::notify::message::event_title(&event, locale);
+                assert!(!title.starts_with("title."), "{locale} {event_type}");
+            }
         }
     }
 
+    #[test]
+    fn formats_version_change_with_problems() {
+        let message = format_version_change(
+            Some("0.3.1"),
+            "0.4.0",
+            &["`opencode new` no longer accepts --workdir".to_string()],
+            timestamp(),
+            Locale::En,
+        );
+        assert_eq!(
+            message,
+            "OpenCode changed from 0.3.1 to 0.4.0 at 2025-01-02T03:04:05+00:00\n\
+             Compatibility checks failed:\n\
+             - `opencode new` no longer accepts --workdir"
+        );
+        assert!(
+            format_version_change(None, "0.4.0", &[], timestamp(), Locale::En)
+                .ends_with("checks passed")
+        );
+    }
+
+    #[test]
+    fn formats_control_actions_with_initiator() {
+        let slack = Initiator::Slack {
+            user_id: "U123".to_string(),
+        };
+        assert_eq!(
+            format_control_action("pause", &slack, Locale::En),
+            "Daemon paused by @U123 via Slack"
+        );
+        assert_eq!(
+            format_control_action(
+                "new_session",
+                &Initiator::Cli {
+                    uid: None,
+                    tty: None
+                },
+                Locale::En
+            ),
+            "New session started by CLI"
+        );
+    }
+
+    #[test]
+    fn formats_sleep_gaps() {
+        assert_eq!(format_sleep_gap(45), "45s");
+        assert_eq!(format_sleep_gap(600), "10m");
+        assert_eq!(format_sleep_gap(28_320), "7h 52m");
+        assert_eq!(format_time_saved(600.4), "10m");
+        assert_eq!(format_time_saved(-5.0), "0s");
+    }
+
+    #[test]
+    fn formats_loop_summary() {
+        assert_eq!(
+            format_loop_summary(5, 1_200, Locale::En),
+            "5 resumes in 20 minutes — possible workflow loop"
+        );
+        assert_eq!(
+            format_loop_summary(3, 20, Locale::En),
+            "3 resumes in 1 minute — possible workflow loop"
+        );
+    }
+
     #[test]
     fn serializes_session_stopped() {
         let event = NotificationEvent::SessionStopped {
@@ -171,6 +1153,7 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             stop_reason: "rate_limit".to_string(),
             details: None,
+            excluded_by: None,
         };
 
         let value = serde_json::to_value(&event).expect("serialize event");
@@ -192,6 +1175,9 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             strategy: "same_session".to_string(),
             wait_time_secs: 120,
+            progress: None,
+            percent: None,
+            git: None,
         };
 
         let value = serde_json::to_value(&event).expect("serialize event");
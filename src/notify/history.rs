@@ -0,0 +1,332 @@
+//! Recent notification delivery decisions.
+//!
+//! The dispatcher records one [`DeliveryRecord`] per channel for every event,
+//! including the negative outcomes (filtered out, channel disabled, send
+//! failed), so a missing ping can be traced to its cause. Records live in a
+//! fixed-size ring buffer; `notifications.history_file` additionally appends
+//! them as JSON lines and reloads the newest on startup. Error text is
+//! capped at `daemon.max_retained_content_chars` before it is kept.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::schema::NotificationsConfig;
+use crate::notify::events::EventSeverity;
+use crate::util::content::{self, RetainedContent};
+
+/// Records kept when `notifications.history_size` is not set.
+pub const DEFAULT_HISTORY_SIZE: usize = 200;
+
+static GLOBAL_HISTORY: OnceLock<Arc<NotificationHistory>> = OnceLock::new();
+
+/// What happened to one event on one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryOutcome {
+    Delivered,
+    /// The channel's `severities`/`events` filter or routing tags rejected the event.
+    Filtered,
+    /// The channel is configured but disabled.
+    Disabled,
+    /// The event's incident was acknowledged, so it was not sent.
+    Acknowledged,
+    /// The channel or event type was muted (`MUTE_CHANNEL`, `MUTE_EVENT`).
+    Muted,
+    Failed,
+}
+
+impl DeliveryOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delivered => "delivered",
+            Self::Filtered => "filtered",
+            Self::Disabled => "disabled",
+            Self::Acknowledged => "acknowledged",
+            Self::Muted => "muted",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "delivered" => Some(Self::Delivered),
+            "filtered" => Some(Self::Filtered),
+            "disabled" => Some(Self::Disabled),
+            "acknowledged" => Some(Self::Acknowledged),
+            "muted" => Some(Self::Muted),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// One dispatch decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    pub timestamp: DateTime<Utc>,
+    pub event_type: String,
+    pub severity: EventSeverity,
+    pub channel: String,
+    pub outcome: DeliveryOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Filters for [`NotificationHistory::recent`]; also the HTTP query string.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct HistoryQuery {
+    pub channel: Option<String>,
+    pub outcome: Option<DeliveryOutcome>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    fn matches(&self, record: &DeliveryRecord) -> bool {
+        self.channel
+            .as_deref()
+            .is_none_or(|channel| record.channel.eq_ignore_ascii_case(channel))
+            && self.outcome.is_none_or(|outcome| record.outcome == outcome)
+            && self.since.is_none_or(|since| record.timestamp >= since)
+    }
+}
+
+/// Bounded log of recent delivery decisions.
+#[derive(Debug)]
+pub struct NotificationHistory {
+    capacity: usize,
+    records: Mutex<VecDeque<DeliveryRecord>>,
+    file: Option<PathBuf>,
+    content_limit: Option<usize>,
+    retained: RetainedContent,
+}
+
+impl NotificationHistory {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::new()),
+            file: None,
+            content_limit: None,
+            retained: RetainedContent::new("notification_history", capacity),
+        }
+    }
+
+    /// Cap record error text at `max_chars` instead of the configured limit.
+    pub fn with_content_limit(mut self, max_chars: usize) -> Self {
+        self.content_limit = Some(max_chars);
+        self
+    }
+
+    /// Sized and persisted per `[notifications]`.
+    pub fn from_config(config: &NotificationsConfig) -> Self {
+        let history = Self::new(config.history_size);
+        match &config.history_file {
+            Some(path) => history.with_file(path.clone()),
+            None => history,
+        }
+    }
+
+    /// Append records to `path` as JSON lines, loading the newest existing ones.
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        let mut loaded = load_tail(&path, self.capacity);
+        for record in &mut loaded {
+            self.bound(record);
+        }
+        *self.records.get_mut().expect("history lock poisoned") = loaded;
+        self.file = Some(path);
+        self
+    }
+
+    /// Install `history` as the process-wide log; returns false if one is already set.
+    pub fn set_global(history: Arc<NotificationHistory>) -> bool {
+        GLOBAL_HISTORY.set(history).is_ok()
+    }
+
+    pub fn global() -> Option<Arc<NotificationHistory>> {
+        GLOBAL_HISTORY.get().cloned()
+    }
+
+    /// The process-wide log, created from `config` on first use.
+    pub fn global_or_init(config: &NotificationsConfig) -> Arc<NotificationHistory> {
+        Arc::clone(GLOBAL_HISTORY.get_or_init(|| Arc::new(Self::from_config(config))))
+    }
+
+    pub fn record(&self, mut record: DeliveryRecord) {
+        self.bound(&mut record);
+        if let Some(path) = &self.file {
+            if let Err(err) = append_line(path, &record) {
+                warn!(path = %path.display(), error = %err, "Failed to persist notification history");
+            }
+        }
+        let mut records = self.records.lock().expect("history lock poisoned");
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Matching records, newest first.
+    pub fn recent(&self, query: &HistoryQuery) -> Vec<DeliveryRecord> {
+        let records = self.records.lock().expect("history lock poisoned");
+        records
+            .iter()
+            .rev()
+            .filter(|record| query.matches(record))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// Records per outcome since `since` (all retained records when `None`).
+    pub fn outcome_counts(&self, since: Option<DateTime<Utc>>) -> BTreeMap<DeliveryOutcome, usize> {
+        let records = self.records.lock().expect("history lock poisoned");
+        let mut counts = BTreeMap::new();
+        for record in records
+            .iter()
+            .filter(|record| since.is_none_or(|since| record.timestamp >= since))
+        {
+            *counts.entry(record.outcome).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Estimated error-text bytes held by the retained records.
+    pub fn retained_content_bytes(&self) -> usize {
+        self.retained.bytes()
+    }
+
+    fn bound(&self, record: &mut DeliveryRecord) {
+        if let Some(error) = &mut record.error {
+            content::cap_in_place(
+                error,
+                self.content_limit.unwrap_or_else(content::retained_chars),
+            );
+        }
+        self.retained
+            .push(record.error.as_ref().map_or(0, String::len));
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.lock().expect("history lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn append_line(path: &Path, record: &DeliveryRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record).map_err(std::io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Newest `capacity` parseable records from a history file.
+pub fn load_tail(path: &Path, capacity: usize) -> VecDeque<DeliveryRecord> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return VecDeque::new();
+    };
+    let mut records = VecDeque::with_capacity(capacity);
+    for line in contents.lines() {
+        let Ok(record) = serde_json::from_str::<DeliveryRecord>(line) else {
+            continue;
+        };
+        if records.len() == capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(channel: &str, outcome: DeliveryOutcome) -> DeliveryRecord {
+        DeliveryRecord {
+            timestamp: Utc::now(),
+            event_type: "resume_failed".to_string(),
+            severity: EventSeverity::Error,
+            channel: channel.to_string(),
+            outcome,
+            http_status: None,
+            latency_ms: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn ring_buffer_keeps_newest_and_filters() {
+        let history = NotificationHistory::new(2);
+        history.record(record("ntfy", DeliveryOutcome::Delivered));
+        history.record(record("slack", DeliveryOutcome::Failed));
+        history.record(record("ntfy", DeliveryOutcome::Filtered));
+
+        let all = history.recent(&HistoryQuery::default());
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].outcome, DeliveryOutcome::Filtered);
+
+        let query = HistoryQuery {
+            channel: Some("NTFY".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(history.recent(&query).len(), 1);
+
+        let counts = history.outcome_counts(None);
+        assert_eq!(counts.get(&DeliveryOutcome::Failed), Some(&1));
+        assert_eq!(counts.get(&DeliveryOutcome::Delivered), None);
+    }
+
+    #[test]
+    fn large_errors_stay_within_content_bound() {
+        let history = NotificationHistory::new(32).with_content_limit(128);
+        let error = "e".repeat(256 * 1024);
+        for _ in 0..1000 {
+            let mut failed = record("webhook", DeliveryOutcome::Failed);
+            failed.error = Some(error.clone());
+            history.record(failed);
+        }
+
+        assert_eq!(history.len(), 32);
+        assert!(history.retained_content_bytes() <= 32 * (128 + 16));
+        let newest = &history.recent(&HistoryQuery::default())[0];
+        assert!(newest.error.as_ref().unwrap().len() <= 128 + 16);
+    }
+
+    #[test]
+    fn history_file_survives_restart() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("history.jsonl");
+
+        let history = NotificationHistory::new(10).with_file(path.clone());
+        history.record(record("webhook", DeliveryOutcome::Failed));
+        history.record(record("ntfy", DeliveryOutcome::Delivered));
+
+        let reloaded = NotificationHistory::new(1).with_file(path);
+        let records = reloaded.recent(&HistoryQuery::default());
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].channel, "ntfy");
+    }
+}
@@ -0,0 +1,516 @@
+//! Localized notification text shared by every channel.
+
+use std::fmt::Display;
+
+use crate::i18n::{Locale, render, text};
+use crate::notify::events::{
+    NotificationEvent, format_completion_duration, format_control_action, format_incident_age,
+    format_loop_summary, format_sleep_gap, format_time_saved, format_version_change,
+    instance_notice,
+};
+use crate::util::size::format_size;
+
+/// Headline for `event`.
+pub fn event_title(event: &NotificationEvent, locale: Locale) -> String {
+    let key = match event {
+        NotificationEvent::SessionStopped {
+            excluded_by: Some(_),
+            ..
+        } => "title.session_stopped_excluded".to_string(),
+        event => format!("title.{}", event.event_type()),
+    };
+    text(locale, &key).to_string()
+}
+
+/// Field label such as "Session", from its `label.*` key suffix.
+pub fn label(locale: Locale, name: &str) -> String {
+    text(locale, &format!("label.{name}")).to_string()
+}
+
+/// Plain-text body for `event`, with the progress, exclusion, acknowledgment
+/// and instance lines that apply.
+pub fn format_event_message(event: &NotificationEvent, locale: Locale) -> String {
+    let line = |key: &str, args: &[(&str, &dyn Display)]| render(locale, key, args);
+    let mut message = match event {
+        NotificationEvent::SessionStopped {
+            timestamp,
+            session_path,
+            stop_reason,
+            details,
+            ..
+        } => {
+            let mut message = line(
+                "body.session_stopped",
+                &[
+                    ("time", &timestamp.to_rfc3339()),
+                    ("session", &session_path.display()),
+                    ("reason", stop_reason),
+                ],
+            );
+            if let Some(details) = details {
+                message.push('\n');
+                message.push_str(&line("body.details", &[("details", details)]));
+            }
+            message
+        }
+        NotificationEvent::ResumeAttempted {
+            timestamp,
+            session_path,
+            strategy,
+            ..
+        } => line(
+            "body.resume_attempted",
+            &[
+                ("time", &timestamp.to_rfc3339()),
+                ("session", &session_path.display()),
+                ("strategy", strategy),
+            ],
+        ),
+        NotificationEvent::ResumeSucceeded {
+            timestamp,
+            session_path,
+            strategy,
+            wait_time_secs,
+            git,
+            ..
+        } => {
+            let mut message = line(
+                "body.resume_succeeded",
+                &[
+                    ("time", &timestamp.to_rfc3339()),
+                    ("session", &session_path.display()),
+                    ("strategy", strategy),
+                    ("wait", wait_time_secs),
+                ],
+            );
+            if let Some(git) = git {
+                message.push('\n');
+                message.push_str(&line("body.git", &[("git", &git.describe())]));
+            }
+            message
+        }
+        NotificationEvent::ResumeFailed {
+            timestamp,
+            session_path,
+            strategy,
+            error,
+            incident,
+            ..
+        } => {
+            let mut message = line(
+                "body.resume_failed",
+                &[
+                    ("time", &timestamp.to_rfc3339()),
+                    ("session", &session_path.display()),
+                    ("strategy", strategy),
+                    ("error", error),
+                ],
+            );
+            if let Some(incident) = incident {
+                message.push('\n');
+                message.push_str(&line("body.incident", &[("incident", &incident.display())]));
+            }
+            message
+        }
+        NotificationEvent::DaemonStarted { timestamp, version } => line(
+            "body.daemon_started",
+            &[("time", &timestamp.to_rfc3339()), ("version", version)],
+        ),
+        NotificationEvent::DaemonStopped { timestamp, reason } => line(
+            "body.daemon_stopped",
+            &[("time", &timestamp.to_rfc3339()), ("reason", reason)],
+        ),
+        NotificationEvent::SessionOrphaned {
+            timestamp,
+            session_path,
+            source_session,
+            reason,
+        } => line(
+            "body.session_orphaned",
+            &[
+                ("time", &timestamp.to_rfc3339()),
+                ("session", &session_path.display()),
+                ("source", &source_session.display()),
+                ("reason", reason),
+            ],
+        ),
+        NotificationEvent::SessionDeleted {
+            timestamp,
+            session_path,
+            restored_from,
+        } => match restored_from {
+            Some(backup) => line(
+                "body.session_deleted_restored",
+                &[
+                    ("time", &timestamp.to_rfc3339()),
+                    ("session", &session_path.display()),
+                    ("backup", &backup.display()),
+                ],
+            ),
+            None => line(
+                "body.session_deleted",
+                &[
+                    ("time", &timestamp.to_rfc3339()),
+                    ("session", &session_path.display()),
+                ],
+            ),
+        },
+        NotificationEvent::ModelChanged {
+            timestamp,
+            session_path,
+            previous_model,
+            current_model,
+        } => line(
+            "body.model_changed",
+            &[
+                ("time", &timestamp.to_rfc3339()),
+                ("session", &session_path.display()),
+                ("previous", previous_model),
+                ("current", current_model),
+            ],
+        ),
+        NotificationEvent::SystemResumed {
+            timestamp,
+            slept_secs,
+        } => line(
+            "body.system_resumed",
+            &[
+                ("slept", &format_sleep_gap(*slept_secs)),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::NextStepInvalid {
+            timestamp,
+            session_path,
+            next_step_path,
+            unmatched_lines,
+        } => line(
+            "body.next_step_invalid",
+            &[
+                ("file", &next_step_path.display()),
+                ("time", &timestamp.to_rfc3339()),
+                ("session", &session_path.display()),
+                ("lines", &unmatched_lines.join("\n")),
+            ],
+        ),
+        NotificationEvent::WorktreeCreated {
+            timestamp,
+            session_path,
+            workspace,
+            worktree_path,
+            branch,
+        } => line(
+            "body.worktree_created",
+            &[
+                ("session", &session_path.display()),
+                ("worktree", &worktree_path.display()),
+                ("branch", branch),
+                ("workspace", &workspace.display()),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::ResumeDeferred {
+            timestamp,
+            session_path,
+            stop_reason,
+            until,
+            reason,
+        } => line(
+            "body.resume_deferred",
+            &[
+                ("session", &session_path.display()),
+                ("stop_reason", stop_reason),
+                ("until", &until.to_rfc3339()),
+                ("reason", reason),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::SessionCompleted {
+            timestamp,
+            session_path,
+            duration_secs,
+            resumes,
+            time_saved_seconds,
+        } => line(
+            "body.session_completed",
+            &[
+                ("session", &session_path.display()),
+                ("time", &timestamp.to_rfc3339()),
+                (
+                    "duration",
+                    &format_completion_duration(*duration_secs, locale),
+                ),
+                ("resumes", resumes),
+                ("saved", &format_time_saved(*time_saved_seconds)),
+            ],
+        ),
+        NotificationEvent::ResumeLoopSuspected {
+            timestamp,
+            session_path,
+            resumes,
+            span_secs,
+            ..
+        } => line(
+            "body.resume_loop_suspected",
+            &[
+                (
+                    "summary",
+                    &format_loop_summary(*resumes, *span_secs, locale),
+                ),
+                ("session", &session_path.display()),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::ResumeSidecarInvalid {
+            timestamp,
+            session_path,
+            sidecar_path,
+            error,
+        } => line(
+            "body.resume_sidecar_invalid",
+            &[
+                ("sidecar", &sidecar_path.display()),
+                ("session", &session_path.display()),
+                ("error", error),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::ClockSkewDetected {
+            timestamp,
+            skew_secs,
+        } => line(
+            if *skew_secs > 0.0 {
+                "body.clock_behind"
+            } else {
+                "body.clock_ahead"
+            },
+            &[
+                ("skew", &format!("{:.0}", skew_secs.abs())),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::FileEncodingFixed {
+            timestamp,
+            file,
+            encoding,
+        } => line(
+            "body.file_encoding_fixed",
+            &[
+                ("file", &file.display()),
+                ("encoding", encoding),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::ScheduledRun {
+            timestamp,
+            schedule,
+            trigger,
+            outcome,
+            workdir,
+            session_path,
+            detail,
+        } => {
+            let trigger = text(locale, &format!("trigger.{trigger}")).to_string();
+            let session = session_path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "-".to_string());
+            line(
+                &format!("body.scheduled_run_{outcome}"),
+                &[
+                    ("schedule", schedule),
+                    ("trigger", &trigger),
+                    ("session", &session),
+                    ("workdir", &workdir.display()),
+                    ("detail", &detail.as_deref().unwrap_or("-")),
+                    ("time", &timestamp.to_rfc3339()),
+                ],
+            )
+        }
+        NotificationEvent::StateReconciled {
+            timestamp,
+            corrections,
+            report,
+        } => line(
+            "body.state_reconciled",
+            &[
+                ("count", &corrections.len()),
+                ("corrections", &corrections.join("\n")),
+                ("report", &report.display()),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::ArtifactGcPending {
+            timestamp,
+            artifacts,
+            bytes,
+            summary,
+            plan,
+        } => line(
+            "body.artifact_gc_pending",
+            &[
+                ("count", artifacts),
+                ("size", &format_size(*bytes)),
+                ("summary", &summary.join("\n")),
+                ("plan", &plan.display()),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::StateReadOnly {
+            timestamp,
+            state_file,
+            found_version,
+            supported_version,
+        } => line(
+            "body.state_read_only",
+            &[
+                ("file", &state_file.display()),
+                ("found", found_version),
+                ("supported", supported_version),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::ActionObserved {
+            timestamp,
+            session_path,
+            stop_reason,
+            action,
+        } => line(
+            "body.action_observed",
+            &[
+                ("action", action),
+                ("session", &session_path.display()),
+                ("stop_reason", stop_reason),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::OpenCodeVersionChanged {
+            timestamp,
+            previous,
+            current,
+            problems,
+        } => format_version_change(previous.as_deref(), current, problems, *timestamp, locale),
+        NotificationEvent::ControlApplied {
+            timestamp,
+            action,
+            initiator,
+        } => line(
+            "body.control_applied",
+            &[
+                ("action", &format_control_action(action, initiator, locale)),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+        NotificationEvent::TestNotification { timestamp, message } => line(
+            "body.test_notification",
+            &[("message", message), ("time", &timestamp.to_rfc3339())],
+        ),
+        NotificationEvent::IncidentEscalated {
+            timestamp,
+            session_path,
+            incident_id,
+            tier,
+            opened_at,
+            prefix,
+            ..
+        } => {
+            let body = line(
+                "body.incident_escalated",
+                &[
+                    ("incident", incident_id),
+                    ("age", &format_incident_age(*opened_at, *timestamp)),
+                    ("tier", tier),
+                    ("session", &session_path.display()),
+                    ("opened", &opened_at.to_rfc3339()),
+                ],
+            );
+            match prefix {
+                Some(prefix) => format!("{prefix} {body}"),
+                None => body,
+            }
+        }
+        NotificationEvent::IncidentResolved {
+            timestamp,
+            session_path,
+            incident_id,
+            opened_at,
+            resolution,
+        } => line(
+            "body.incident_resolved",
+            &[
+                ("incident", incident_id),
+                ("age", &format_incident_age(*opened_at, *timestamp)),
+                (
+                    "resolution",
+                    &text(locale, &format!("resolution.{resolution}")),
+                ),
+                ("session", &session_path.display()),
+            ],
+        ),
+        NotificationEvent::UnexpectedExit {
+            timestamp,
+            pid,
+            cause,
+            detail,
+        } => line(
+            "body.unexpected_exit",
+            &[
+                ("pid", pid),
+                ("cause", &text(locale, &format!("exit_cause.{cause}"))),
+                ("detail", detail),
+                ("time", &timestamp.to_rfc3339()),
+            ],
+        ),
+    };
+    if let Some(progress) = event.progress() {
+        message.push('\n');
+        message.push_str(&line("body.progress", &[("progress", &progress)]));
+    }
+    let notices = [
+        event.exclusion_notice(locale),
+        event.ack_notice(locale),
+        instance_notice(locale),
+    ];
+    for notice in notices.into_iter().flatten() {
+        message.push('\n');
+        message.push_str(&notice);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn stopped() -> NotificationEvent {
+        NotificationEvent::SessionStopped {
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap(),
+            session_path: "/tmp/session.md".into(),
+            stop_reason: "rate_limit".to_string(),
+            details: Some("429".to_string()),
+            excluded_by: None,
+        }
+    }
+
+    #[test]
+    fn english_body_is_unchanged_by_the_catalog() {
+        assert_eq!(
+            format_event_message(&stopped(), Locale::En),
+            "Session stopped at 2025-01-02T03:04:05+00:00.\nSession: /tmp/session.md\nReason: rate_limit\nDetails: 429"
+        );
+        assert_eq!(event_title(&stopped(), Locale::En), "Session stopped");
+    }
+
+    #[test]
+    fn japanese_body_keeps_the_values() {
+        let message = format_event_message(&stopped(), Locale::Ja);
+        assert!(message.starts_with("2025-01-02T03:04:05+00:00 にセッションが停止しました。"));
+        assert!(message.contains("セッション: /tmp/session.md"));
+        assert!(message.contains("理由: rate_limit"));
+        assert_eq!(
+            event_title(&stopped(), Locale::Ja),
+            "セッションが停止しました"
+        );
+    }
+}
@@ -1,15 +1,44 @@
 //! Notification dispatcher module.
+//!
+//! Event types are always available; the channels that deliver them need the
+//! `notifications` feature.
 
+pub mod ack;
 pub mod channel;
+pub mod credentials;
+#[cfg(feature = "notifications")]
 pub mod discord;
+#[cfg(feature = "notifications")]
 pub mod dispatcher;
 pub mod error;
+#[cfg(feature = "notifications")]
+pub mod escalation;
 pub mod events;
+pub mod history;
+pub mod message;
+pub mod mute;
+#[cfg(feature = "notifications")]
 pub mod ntfy;
+pub mod routing;
+#[cfg(feature = "notifications")]
+pub mod signing;
+#[cfg(feature = "notifications")]
 pub mod slack;
+pub mod truncate;
+pub mod url_guard;
+#[cfg(feature = "notifications")]
 pub mod webhook;
 
-pub use channel::NotificationChannel;
-pub use dispatcher::{DispatchSummary, Dispatcher};
+pub use ack::{AckError, AckIncident, AckLink, AckRegistry};
+pub use channel::{EventFilter, NotificationChannel};
+pub use credentials::{ChannelCredentials, CredentialRegistry, CredentialStatus, Credentials};
+#[cfg(feature = "notifications")]
+pub use dispatcher::{DispatchPolicy, DispatchSummary, Dispatcher, PrimaryChannel};
 pub use error::NotifyError;
+#[cfg(feature = "notifications")]
+pub use escalation::{EscalationDigest, Escalator};
 pub use events::{EventSeverity, NotificationEvent};
+pub use history::{DeliveryOutcome, DeliveryRecord, HistoryQuery, NotificationHistory};
+pub use mute::NotificationMutes;
+pub use routing::TagRoute;
+pub use url_guard::{UrlGuard, UrlGuardError};
@@ -0,0 +1,194 @@
+//! Runtime notification mutes (`MUTE_CHANNEL`, `MUTE_EVENT`).
+//!
+//! A muted channel gets nothing; a muted event type goes to no channel. The
+//! dispatcher checks mutes before any filtering and records
+//! [`DeliveryOutcome::Muted`](crate::notify::DeliveryOutcome::Muted) for what
+//! they hold back. Mutes live in the state file so a restart does not lift
+//! them, and lapse on their own once their duration is up. Severities in
+//! `notifications.mute_exempt_severities` go out regardless.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+use crate::daemon::suspend::{Clock, SystemClock};
+use crate::state::{MuteKind, NotificationMute, StateError, StateHandle};
+
+/// Mutes recorded in a state file.
+#[derive(Clone)]
+pub struct NotificationMutes {
+    state: StateHandle,
+    clock: Arc<dyn Clock>,
+}
+
+impl NotificationMutes {
+    pub fn new(state: StateHandle) -> Self {
+        Self {
+            state,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Measure expiry against this clock (for testing).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.clock.wall())
+    }
+
+    /// Mute `name` for `duration`, or until unmuted, persisting it right
+    /// away. Muting again replaces the previous duration.
+    pub fn mute(
+        &self,
+        kind: MuteKind,
+        name: &str,
+        duration: Option<Duration>,
+    ) -> Result<NotificationMute, StateError> {
+        let now = self.now();
+        let mute = NotificationMute {
+            kind,
+            name: normalize(name),
+            muted_at: now,
+            until: duration.map(|duration| {
+                now + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+            }),
+        };
+        self.state.update_critical(|state| {
+            state
+                .notification_mutes
+                .retain(|existing| !(existing.kind == kind && existing.name == mute.name));
+            state.notification_mutes.push(mute.clone());
+        })?;
+        info!(
+            kind = kind.as_str(),
+            name = %mute.name,
+            until = ?mute.until,
+            "Notification mute added"
+        );
+        Ok(mute)
+    }
+
+    /// Lift the mute on `name`; `false` when it was not muted.
+    pub fn unmute(&self, kind: MuteKind, name: &str) -> Result<bool, StateError> {
+        let name = normalize(name);
+        let removed = self.state.update_critical(|state| {
+            let before = state.notification_mutes.len();
+            state
+                .notification_mutes
+                .retain(|existing| !(existing.kind == kind && existing.name == name));
+            state.notification_mutes.len() != before
+        })?;
+        if removed {
+            info!(kind = kind.as_str(), name = %name, "Notification mute lifted");
+        }
+        Ok(removed)
+    }
+
+    /// Mutes still in effect, dropping the lapsed ones.
+    pub fn active(&self) -> Vec<NotificationMute> {
+        self.expire();
+        self.state.snapshot().notification_mutes
+    }
+
+    /// Drop lapsed mutes, logging each; returns how many there were.
+    pub fn expire(&self) -> usize {
+        let now = self.now();
+        if !self
+            .state
+            .snapshot()
+            .notification_mutes
+            .iter()
+            .any(|mute| mute.is_expired(now))
+        {
+            return 0;
+        }
+        let expired = self.state.update(|state| {
+            let (expired, kept) = state
+                .notification_mutes
+                .drain(..)
+                .partition::<Vec<_>, _>(|mute| mute.is_expired(now));
+            state.notification_mutes = kept;
+            expired
+        });
+        for mute in &expired {
+            info!(
+                kind = mute.kind.as_str(),
+                name = %mute.name,
+                "Notification mute expired"
+            );
+        }
+        expired.len()
+    }
+
+    /// Whether `event_type` is muted, or muted on `channel`.
+    pub fn is_muted(&self, channel: &str, event_type: &str) -> bool {
+        self.active()
+            .iter()
+            .any(|mute| mute.silences(channel, event_type))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::{Instant, SystemTime};
+
+    use chrono::TimeZone;
+
+    use crate::state::StateStore;
+
+    struct FakeClock(Mutex<SystemTime>);
+
+    impl Clock for FakeClock {
+        fn monotonic(&self) -> Instant {
+            Instant::now()
+        }
+
+        fn wall(&self) -> SystemTime {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn mutes_persist_and_lapse() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("state.json");
+        let state = StateHandle::new(StateStore::with_path(path.clone()));
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FakeClock(Mutex::new(start.into())));
+        let mutes = NotificationMutes::new(state.clone()).with_clock(clock.clone());
+
+        mutes
+            .mute(MuteKind::Channel, "Slack", Some(Duration::from_secs(600)))
+            .unwrap();
+        mutes
+            .mute(MuteKind::Event, "resume_attempted", None)
+            .unwrap();
+        assert!(mutes.is_muted("slack", "resume_failed"));
+        assert!(mutes.is_muted("ntfy", "resume_attempted"));
+        assert!(!mutes.is_muted("ntfy", "resume_failed"));
+
+        // Saved right away, so a restart keeps them.
+        let reopened = StateHandle::new(StateStore::with_path(path));
+        let reopened = NotificationMutes::new(reopened).with_clock(clock.clone());
+        assert_eq!(reopened.active().len(), 2);
+
+        *clock.0.lock().unwrap() = (start + chrono::Duration::minutes(11)).into();
+        assert!(!mutes.is_muted("slack", "resume_failed"));
+        assert_eq!(mutes.active().len(), 1);
+
+        assert!(mutes.unmute(MuteKind::Event, "RESUME_ATTEMPTED").unwrap());
+        assert!(!mutes.unmute(MuteKind::Event, "resume_attempted").unwrap());
+        assert!(state.snapshot().notification_mutes.is_empty());
+    }
+}
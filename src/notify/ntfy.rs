@@ -1,73 +1,194 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use reqwest::Client;
 use tracing::debug;
 
-use crate::config::schema::NtfyConfig;
-use crate::notify::channel::NotificationChannel;
+use crate::config::schema::{NtfyConfig, PathDisplayMode};
+use crate::i18n::Locale;
+use crate::notify::channel::{EventFilter, NotificationChannel};
+use crate::notify::credentials::{
+    ChannelCredentials, ChannelKind, CredentialRegistry, Credentials,
+};
 use crate::notify::error::NotifyError;
 use crate::notify::events::{EventSeverity, NotificationEvent};
+use crate::notify::message::{event_title, format_event_message};
+use crate::notify::routing::{self, TagRoute};
+use crate::notify::truncate::{Limit, detail_pointer, fit, is_payload_rejection};
+use crate::notify::url_guard::UrlGuard;
+use crate::util::path_display;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// ntfy's default `message-size-limit`.
+const MESSAGE_LIMIT: Limit = Limit::bytes(4096);
 
 pub struct NtfyChannel {
-    topic: String,
-    server: String,
+    name: String,
+    /// Topic URL and access token.
+    credentials: Arc<ChannelCredentials>,
     priority: Option<String>,
-    client: Client,
+    filter: EventFilter,
+    /// Guarded HTTP client; if it could not be built, every send fails.
+    client: Result<Client, NotifyError>,
+    guard: UrlGuard,
+    locale: Locale,
+    /// `path_display` override; the daemon setting applies otherwise.
+    path_display: Option<PathDisplayMode>,
+    /// `match_tags`/`exclude_tags`.
+    route: TagRoute,
     enabled: bool,
 }
 
 impl NtfyChannel {
     pub fn new(config: &NtfyConfig) -> Self {
-        let client = Client::builder()
-            .timeout(REQUEST_TIMEOUT)
-            .build()
-            .unwrap_or_else(|err| {
-                tracing::warn!(error = %err, "Failed to build ntfy client; using defaults");
-                Client::new()
-            });
-
+        let guard = UrlGuard::default();
         Self {
-            topic: config.topic.clone(),
-            server: config
-                .server
-                .clone()
-                .unwrap_or_else(|| "https://ntfy.sh".to_string()),
+            name: config.channel_name().to_string(),
+            credentials: CredentialRegistry::global().register(
+                config.channel_name(),
+                ChannelKind::Ntfy,
+                Credentials::ntfy(config),
+            ),
             priority: config.priority.clone(),
-            client,
+            filter: EventFilter::new(&config.severities, &config.events),
+            client: guard.build_client(REQUEST_TIMEOUT),
+            guard,
+            locale: config.locale.unwrap_or_default(),
+            path_display: config.path_display,
+            route: TagRoute::new(&config.match_tags, &config.exclude_tags),
             enabled: true,
         }
     }
+
+    /// Apply `notifications.allow_private_networks`.
+    pub fn with_url_guard(mut self, guard: UrlGuard) -> Self {
+        self.client = guard.build_client(REQUEST_TIMEOUT);
+        self.guard = guard;
+        self
+    }
+
+    /// Language of titles and bodies.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
 }
 
 #[async_trait]
 impl NotificationChannel for NtfyChannel {
-    fn name(&self) -> &'static str {
-        "ntfy"
+    fn name(&self) -> &str {
+        &self.name
     }
 
     async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
-        let url = format!(
-            "{}/{}",
-            self.server.trim_end_matches('/'),
-            self.topic.trim_start_matches('/')
+        self.deliver(event, None).await
+    }
+
+    async fn send_annotated(
+        &self,
+        event: &NotificationEvent,
+        note: &str,
+    ) -> Result<(), NotifyError> {
+        self.deliver(event, Some(note)).await
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn tag_route(&self) -> Option<&TagRoute> {
+        Some(&self.route)
+    }
+
+    fn accepts(&self, event: &NotificationEvent) -> bool {
+        self.filter.matches(event)
+    }
+
+    fn refresh_credentials(&self) -> Result<bool, NotifyError> {
+        self.credentials.refresh()
+    }
+}
+
+impl NtfyChannel {
+    async fn deliver(
+        &self,
+        event: &NotificationEvent,
+        note: Option<&str>,
+    ) -> Result<(), NotifyError> {
+        let tags = tags_header(event.severity(), &routing::event_tags(event));
+        let display = path_display::global().overridden(self.path_display);
+        let event = &*event.with_display_paths(&display);
+        let credentials = self.credentials.current();
+        self.guard.check_url(&credentials.url)?;
+        match self
+            .post(
+                &credentials,
+                event,
+                &tags,
+                render_body(event, note, false, self.locale),
+            )
+            .await
+        {
+            Err(err) if is_payload_rejection(&err) => {
+                debug!(
+                    channel = self.name(),
+                    event_type = event.event_type(),
+                    error = %err,
+                    "ntfy rejected the message; retrying with a shorter one"
+                );
+                self.post(
+                    &credentials,
+                    event,
+                    &tags,
+                    render_body(event, note, true, self.locale),
+                )
+                .await?;
+            }
+            result => result?,
+        }
+
+        debug!(
+            channel = self.name(),
+            event_type = event.event_type(),
+            "ntfy notification sent"
         );
-        let title = event_title(event);
-        let message = format_event_message(event);
-        let tags = severity_tag(event.severity());
+        Ok(())
+    }
 
-        let mut request = self
-            .client
-            .post(url)
-            .header("Title", title)
-            .header("Tags", tags)
-            .body(message);
+    async fn post(
+        &self,
+        credentials: &Credentials,
+        event: &NotificationEvent,
+        tags: &str,
+        body: String,
+    ) -> Result<(), NotifyError> {
+        let client = self.client.as_ref().map_err(Clone::clone)?;
+        let url = credentials.url.as_str();
+        let title = event_title(event, self.locale);
+        let mut request = if title.is_ascii() {
+            client.post(url).header("Title", title)
+        } else {
+            // Header values are ASCII; ntfy takes the same parameter from
+            // the query string, which carries translated titles intact.
+            let mut url = reqwest::Url::parse(url).map_err(|err| NotifyError::SendFailed {
+                message: format!("invalid ntfy URL: {err}"),
+            })?;
+            url.query_pairs_mut().append_pair("title", &title);
+            client.post(url)
+        }
+        .header("Tags", tags)
+        .body(body);
 
         if let Some(priority) = &self.priority {
             request = request.header("Priority", priority);
         }
+        if let Some(actions) = ack_action(event) {
+            request = request.header("Actions", actions);
+        }
+        if let Some(token) = &credentials.token {
+            request = request.bearer_auth(token);
+        }
 
         let response = request
             .send()
@@ -77,22 +198,33 @@ impl NotificationChannel for NtfyChannel {
             })?;
 
         if !response.status().is_success() {
-            return Err(NotifyError::SendFailed {
-                message: format!("ntfy returned status {}", response.status()),
+            return Err(NotifyError::Status {
+                service: "ntfy".to_string(),
+                status: response.status().as_u16(),
             });
         }
-
-        debug!(
-            channel = self.name(),
-            event_type = event.event_type(),
-            "ntfy notification sent"
-        );
         Ok(())
     }
+}
 
-    fn is_enabled(&self) -> bool {
-        self.enabled
+/// Message body within ntfy's limit; longer bodies would be turned into an
+/// attachment that most clients never show.
+fn render_body(
+    event: &NotificationEvent,
+    note: Option<&str>,
+    aggressive: bool,
+    locale: Locale,
+) -> String {
+    let mut message = format_event_message(event, locale);
+    if let Some(note) = note {
+        message.push_str(&format!("\n\n{note}"));
     }
+    let limit = if aggressive {
+        MESSAGE_LIMIT.aggressive()
+    } else {
+        MESSAGE_LIMIT
+    };
+    fit(&message, limit, &detail_pointer(event))
 }
 
 fn severity_tag(severity: EventSeverity) -> &'static str {
@@ -100,83 +232,28 @@ fn severity_tag(severity: EventSeverity) -> &'static str {
         EventSeverity::Info => "ℹ️",
         EventSeverity::Warning => "⚠️",
         EventSeverity::Error => "🔴",
+        EventSeverity::Critical => "🚨",
     }
 }
 
-fn event_title(event: &NotificationEvent) -> &'static str {
-    match event {
-        NotificationEvent::SessionStopped { .. } => "Session stopped",
-        NotificationEvent::ResumeAttempted { .. } => "Resume attempted",
-        NotificationEvent::ResumeSucceeded { .. } => "Resume succeeded",
-        NotificationEvent::ResumeFailed { .. } => "Resume failed",
-        NotificationEvent::DaemonStarted { .. } => "Daemon started",
-        NotificationEvent::DaemonStopped { .. } => "Daemon stopped",
-    }
+/// `Tags` header: the severity marker, then the session's routing tags
+/// (ASCII only, as header values must be).
+fn tags_header(severity: EventSeverity, tags: &[String]) -> String {
+    std::iter::once(severity_tag(severity))
+        .chain(tags.iter().map(String::as_str).filter(|tag| tag.is_ascii()))
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
-fn format_event_message(event: &NotificationEvent) -> String {
+/// ntfy action button opening the incident's acknowledgment link.
+fn ack_action(event: &NotificationEvent) -> Option<String> {
     match event {
-        NotificationEvent::SessionStopped {
-            timestamp,
-            session_path,
-            stop_reason,
-            details,
-        } => {
-            let mut message = format!(
-                "Session stopped at {}.\nSession: {}\nReason: {}",
-                timestamp.to_rfc3339(),
-                session_path.display(),
-                stop_reason
-            );
-            if let Some(details) = details {
-                message.push_str(&format!("\nDetails: {details}"));
-            }
-            message
-        }
-        NotificationEvent::ResumeAttempted {
-            timestamp,
-            session_path,
-            strategy,
-        } => format!(
-            "Resume attempted at {}.\nSession: {}\nStrategy: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy
-        ),
-        NotificationEvent::ResumeSucceeded {
-            timestamp,
-            session_path,
-            strategy,
-            wait_time_secs,
-        } => format!(
-            "Resume succeeded at {}.\nSession: {}\nStrategy: {}\nWait time: {}s",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            wait_time_secs
-        ),
-        NotificationEvent::ResumeFailed {
-            timestamp,
-            session_path,
-            strategy,
-            error,
-        } => format!(
-            "Resume failed at {}.\nSession: {}\nStrategy: {}\nError: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            error
-        ),
-        NotificationEvent::DaemonStarted { timestamp, version } => format!(
-            "Daemon started at {}.\nVersion: {}",
-            timestamp.to_rfc3339(),
-            version
-        ),
-        NotificationEvent::DaemonStopped { timestamp, reason } => format!(
-            "Daemon stopped at {}.\nReason: {}",
-            timestamp.to_rfc3339(),
-            reason
-        ),
+        NotificationEvent::ResumeFailed { ack: Some(ack), .. }
+        | NotificationEvent::ResumeLoopSuspected { ack: Some(ack), .. } => ack
+            .url
+            .as_ref()
+            .map(|url| format!("view, Acknowledge, {url}, clear=true")),
+        _ => None,
     }
 }
 
@@ -197,13 +274,108 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             strategy: "same_session".to_string(),
             error: "timeout".to_string(),
+            progress: None,
+            percent: None,
+            incident: Some(PathBuf::from(
+                "/state/incidents/20250102T030400.000Z-session.tail.txt",
+            )),
+            ack: None,
         };
 
-        let message = format_event_message(&event);
+        let message = format_event_message(&event, Locale::En);
 
         assert!(message.contains("Resume failed at 2025-01-02T03:04:05+00:00"));
         assert!(message.contains("Session: /tmp/session"));
         assert!(message.contains("Strategy: same_session"));
         assert!(message.contains("Error: timeout"));
+        assert!(
+            message.contains("Incident: /state/incidents/20250102T030400.000Z-session.tail.txt")
+        );
+    }
+
+    #[test]
+    fn appends_progress_to_resume_message() {
+        let event = NotificationEvent::ResumeAttempted {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session"),
+            strategy: "new_session".to_string(),
+            progress: Some("step 7 of 12 (58%), steps 1-6 complete".to_string()),
+            percent: Some(58),
+        };
+
+        let message = format_event_message(&event, Locale::En);
+
+        assert!(message.ends_with("\nProgress: step 7 of 12 (58%), steps 1-6 complete"));
+    }
+
+    #[test]
+    fn reports_excluded_stop() {
+        let event = NotificationEvent::SessionStopped {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/work/experiments/session.md"),
+            stop_reason: "rate_limit".to_string(),
+            details: None,
+            excluded_by: Some("**/experiments/**".to_string()),
+        };
+
+        assert_eq!(
+            event_title(&event, Locale::En),
+            "Stop detected (excluded from auto-resume)"
+        );
+        assert!(
+            format_event_message(&event, Locale::En)
+                .ends_with("\nStop detected (excluded from auto-resume by `**/experiments/**`)")
+        );
+    }
+
+    #[test]
+    fn formats_durations_compactly() {
+        let event = NotificationEvent::SessionCompleted {
+            timestamp: chrono::Utc
+                .with_ymd_and_hms(2025, 1, 2, 3, 4, 5)
+                .single()
+                .expect("timestamp"),
+            session_path: PathBuf::from("/tmp/session.md"),
+            duration_secs: Some(3_661),
+            resumes: 2,
+            time_saved_seconds: 600.0,
+        };
+        assert!(
+            format_event_message(&event, Locale::En).contains("after 1h 1m (2 resumes, 10m saved)")
+        );
+
+        let event = NotificationEvent::SystemResumed {
+            timestamp: chrono::Utc::now(),
+            slept_secs: 28_320,
+        };
+        assert!(format_event_message(&event, Locale::En).contains("7h 52m"));
+    }
+
+    #[test]
+    fn oversized_body_fits_message_limit() {
+        let event = NotificationEvent::ResumeFailed {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session"),
+            strategy: "same_session".to_string(),
+            error: "é".repeat(10_000),
+            progress: Some("step 7 of 12".to_string()),
+            percent: Some(58),
+            incident: None,
+            ack: None,
+        };
+
+        let body = render_body(&event, Some("retrying"), false, Locale::En);
+
+        assert!(MESSAGE_LIMIT.fits(&body));
+        assert!(body.contains("Session: /tmp/session"));
+        assert!(body.contains("Strategy: same_session"));
+        assert!(body.contains("… truncated "));
+        assert!(body.contains("Progress: step 7 of 12"));
+        assert!(body.ends_with(&detail_pointer(&event)));
+        assert!(
+            MESSAGE_LIMIT
+                .aggressive()
+                .fits(&render_body(&event, None, true, Locale::En))
+        );
     }
 }
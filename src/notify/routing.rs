@@ -0,0 +1,102 @@
+//! Tag-based notification routing.
+//!
+//! Sessions get routing tags from their sidecar files (`tags = [...]`, see
+//! [`sidecar`](crate::resume::sidecar)). A channel with `match_tags` only
+//! receives events whose session carries one of them, and `exclude_tags`
+//! keeps a channel from receiving events whose session carries any of them.
+//! Events without tags — daemon lifecycle events and untagged sessions —
+//! follow `notifications.untagged_route`.
+
+use serde_json::Value;
+
+use crate::config::schema::UntaggedRoute;
+use crate::notify::events::NotificationEvent;
+use crate::resume::sidecar::{normalize_tags, session_tags};
+
+/// A channel's `match_tags`/`exclude_tags`; empty lists route everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagRoute {
+    match_tags: Vec<String>,
+    exclude_tags: Vec<String>,
+}
+
+impl TagRoute {
+    pub fn new(match_tags: &[String], exclude_tags: &[String]) -> Self {
+        Self {
+            match_tags: normalize_tags(match_tags),
+            exclude_tags: normalize_tags(exclude_tags),
+        }
+    }
+
+    /// Whether the channel only takes events of some tags.
+    pub fn is_targeted(&self) -> bool {
+        !self.match_tags.is_empty()
+    }
+
+    pub fn match_tags(&self) -> &[String] {
+        &self.match_tags
+    }
+
+    /// Whether an event with `tags` goes to this channel.
+    pub fn matches(&self, tags: &[String], untagged: UntaggedRoute) -> bool {
+        if tags.is_empty() {
+            return match untagged {
+                UntaggedRoute::All => true,
+                UntaggedRoute::Untargeted => !self.is_targeted(),
+            };
+        }
+        let has = |wanted: &[String]| wanted.iter().any(|tag| tags.contains(tag));
+        (!self.is_targeted() || has(&self.match_tags)) && !has(&self.exclude_tags)
+    }
+}
+
+/// Routing tags of the event's session; none for events without one.
+pub fn event_tags(event: &NotificationEvent) -> Vec<String> {
+    event.session_path().map(session_tags).unwrap_or_default()
+}
+
+/// Add `tags` to an event's JSON payload, when there are any.
+pub fn insert_tags(payload: &mut Value, tags: &[String]) {
+    if let (false, Some(object)) = (tags.is_empty(), payload.as_object_mut()) {
+        object.insert("tags".to_string(), Value::from(tags.to_vec()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn match_and_exclude_tags() {
+        let route = TagRoute::new(&tags(&["Proj-A"]), &tags(&["experiments"]));
+        assert!(route.matches(&tags(&["proj-a", "backend"]), UntaggedRoute::All));
+        assert!(!route.matches(&tags(&["proj-b"]), UntaggedRoute::All));
+        assert!(!route.matches(&tags(&["proj-a", "experiments"]), UntaggedRoute::All));
+
+        let exclude_only = TagRoute::new(&[], &tags(&["experiments"]));
+        assert!(exclude_only.matches(&tags(&["proj-b"]), UntaggedRoute::All));
+        assert!(!exclude_only.matches(&tags(&["experiments"]), UntaggedRoute::All));
+    }
+
+    #[test]
+    fn untagged_events_follow_the_default_route() {
+        let targeted = TagRoute::new(&tags(&["proj-a"]), &[]);
+        let open = TagRoute::default();
+        assert!(targeted.matches(&[], UntaggedRoute::All));
+        assert!(!targeted.matches(&[], UntaggedRoute::Untargeted));
+        assert!(open.matches(&[], UntaggedRoute::Untargeted));
+    }
+
+    #[test]
+    fn tags_are_added_to_json_payloads() {
+        let mut payload = serde_json::json!({"event": "daemon_started"});
+        insert_tags(&mut payload, &[]);
+        assert!(payload.get("tags").is_none());
+        insert_tags(&mut payload, &tags(&["proj-a"]));
+        assert_eq!(payload["tags"], serde_json::json!(["proj-a"]));
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -5,78 +6,131 @@ use reqwest::Client;
 use serde::Serialize;
 use tracing::debug;
 
-use crate::config::schema::SlackConfig;
+use crate::config::schema::{PathDisplayMode, SlackConfig};
+use crate::i18n::{Locale, text};
 use crate::notify::channel::NotificationChannel;
+use crate::notify::credentials::{
+    ChannelCredentials, ChannelKind, CredentialRegistry, Credentials,
+};
 use crate::notify::error::NotifyError;
-use crate::notify::events::{EventSeverity, NotificationEvent};
+use crate::notify::events::{
+    EventSeverity, NotificationEvent, format_completion_duration, format_loop_summary,
+    format_sleep_gap, format_time_saved,
+};
+use crate::notify::message::{event_title, format_event_message, label};
+use crate::notify::routing::TagRoute;
+use crate::notify::truncate::{Limit, detail_pointer, is_payload_rejection, truncate};
+use crate::notify::url_guard::UrlGuard;
+use crate::util::path_display;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Slack Block Kit limits.
+const HEADER_LIMIT: Limit = Limit::chars(150);
+const FIELD_TEXT_LIMIT: Limit = Limit::chars(2000);
+const MAX_SECTION_FIELDS: usize = 10;
 
 pub struct SlackChannel {
-    webhook_url: String,
-    client: Client,
+    credentials: Arc<ChannelCredentials>,
+    /// Guarded HTTP client; if it could not be built, every send fails.
+    client: Result<Client, NotifyError>,
+    guard: UrlGuard,
+    locale: Locale,
+    /// `path_display` override; the daemon setting applies otherwise.
+    path_display: Option<PathDisplayMode>,
+    /// `match_tags`/`exclude_tags`.
+    route: TagRoute,
     enabled: bool,
 }
 
 impl SlackChannel {
     pub fn new(config: &SlackConfig) -> Self {
-        let client = Client::builder()
-            .timeout(REQUEST_TIMEOUT)
-            .build()
-            .unwrap_or_else(|err| {
-                tracing::warn!(error = %err, "Failed to build slack client; using defaults");
-                Client::new()
-            });
-
+        let guard = UrlGuard::default();
         Self {
-            webhook_url: config.webhook_url.clone(),
-            client,
+            credentials: CredentialRegistry::global().register(
+                "slack",
+                ChannelKind::Slack,
+                Credentials::slack(config),
+            ),
+            client: guard.build_client(REQUEST_TIMEOUT),
+            guard,
+            locale: config.locale.unwrap_or_default(),
+            path_display: config.path_display,
+            route: TagRoute::new(&config.match_tags, &config.exclude_tags),
             enabled: true,
         }
     }
+
+    /// Apply `notifications.allow_private_networks`.
+    pub fn with_url_guard(mut self, guard: UrlGuard) -> Self {
+        self.client = guard.build_client(REQUEST_TIMEOUT);
+        self.guard = guard;
+        self
+    }
+
+    /// Language of headers, labels and text.
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
 }
 
 #[async_trait]
 impl NotificationChannel for SlackChannel {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "slack"
     }
 
     async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
-        let message = format_event_message(event);
-        let title = format!(
-            "{} {}",
-            severity_emoji(event.severity()),
-            event_title(event)
-        );
-        let payload = SlackWebhookPayload {
-            blocks: vec![
-                SlackBlock::Header {
-                    text: SlackText {
-                        text_type: "plain_text",
-                        text: title,
-                    },
-                },
-                SlackBlock::Section {
-                    fields: event_fields(event),
-                },
-            ],
-        };
+        self.deliver(event, None).await
+    }
 
-        let response = self
-            .client
-            .post(&self.webhook_url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|err| NotifyError::SendFailed {
-                message: format!("slack request error: {err}"),
-            })?;
+    async fn send_annotated(
+        &self,
+        event: &NotificationEvent,
+        note: &str,
+    ) -> Result<(), NotifyError> {
+        self.deliver(event, Some(note)).await
+    }
 
-        if !response.status().is_success() {
-            return Err(NotifyError::SendFailed {
-                message: format!("slack returned status {}", response.status()),
-            });
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn tag_route(&self) -> Option<&TagRoute> {
+        Some(&self.route)
+    }
+
+    fn refresh_credentials(&self) -> Result<bool, NotifyError> {
+        self.credentials.refresh()
+    }
+}
+
+impl SlackChannel {
+    async fn deliver(
+        &self,
+        event: &NotificationEvent,
+        note: Option<&str>,
+    ) -> Result<(), NotifyError> {
+        let display = path_display::global().overridden(self.path_display);
+        let event = &*event.with_display_paths(&display);
+        let message = format_event_message(event, Locale::En);
+        let url = self.credentials.current().url;
+        self.guard.check_url(&url)?;
+        match self
+            .post(&url, &build_payload(event, note, false, self.locale))
+            .await
+        {
+            Err(err) if is_payload_rejection(&err) => {
+                debug!(
+                    channel = self.name(),
+                    event_type = event.event_type(),
+                    error = %err,
+                    "Slack rejected the payload; retrying with a shorter one"
+                );
+                self.post(&url, &build_payload(event, note, true, self.locale))
+                    .await?;
+            }
+            result => result?,
         }
 
         debug!(
@@ -88,9 +142,101 @@ impl NotificationChannel for SlackChannel {
         Ok(())
     }
 
-    fn is_enabled(&self) -> bool {
-        self.enabled
+    async fn post(&self, url: &str, payload: &SlackWebhookPayload) -> Result<(), NotifyError> {
+        let client = self.client.as_ref().map_err(Clone::clone)?;
+        let response =
+            client
+                .post(url)
+                .json(payload)
+                .send()
+                .await
+                .map_err(|err| NotifyError::SendFailed {
+                    message: format!("slack request error: {err}"),
+                })?;
+
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                service: "slack".to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Render `event` within Slack's Block Kit limits; `aggressive` cuts deeper
+/// after Slack rejected the full-size payload.
+fn build_payload(
+    event: &NotificationEvent,
+    note: Option<&str>,
+    aggressive: bool,
+    locale: Locale,
+) -> SlackWebhookPayload {
+    let shrink = |limit: Limit| {
+        if aggressive {
+            limit.aggressive()
+        } else {
+            limit
+        }
+    };
+    let title = format!(
+        "{} {}",
+        severity_emoji(event.severity()),
+        event_title(event, locale)
+    );
+
+    let mut cut = false;
+    let mut fields = event_fields(event, locale);
+    fields.truncate(MAX_SECTION_FIELDS);
+    for field in &mut fields {
+        let fitted = truncate(&field.text, shrink(FIELD_TEXT_LIMIT));
+        cut |= fitted.removed > 0;
+        field.text = fitted.text;
+    }
+
+    let mut blocks = vec![
+        SlackBlock::Header {
+            text: SlackText {
+                text_type: "plain_text",
+                text: truncate(&title, HEADER_LIMIT).text,
+            },
+        },
+        SlackBlock::Section { fields },
+    ];
+    // Header text is plain, so a mention only notifies from a section.
+    if let Some(prefix) = event.prefix() {
+        blocks.insert(
+            1,
+            SlackBlock::Section {
+                fields: vec![SlackText {
+                    text_type: "mrkdwn",
+                    text: prefix.to_string(),
+                }],
+            },
+        );
     }
+    if let Some(note) = note {
+        let fitted = truncate(
+            &format!("*{}:*\n{note}", label(locale, "note")),
+            shrink(FIELD_TEXT_LIMIT),
+        );
+        cut |= fitted.removed > 0;
+        blocks.push(SlackBlock::Section {
+            fields: vec![SlackText {
+                text_type: "mrkdwn",
+                text: fitted.text,
+            }],
+        });
+    }
+    if cut {
+        blocks.push(SlackBlock::Section {
+            fields: vec![SlackText {
+                text_type: "mrkdwn",
+                text: detail_pointer(event),
+            }],
+        });
+    }
+    SlackWebhookPayload { blocks }
 }
 
 #[derive(Debug, Serialize)]
@@ -119,21 +265,11 @@ fn severity_emoji(severity: EventSeverity) -> &'static str {
         EventSeverity::Info => "ℹ️",
         EventSeverity::Warning => "⚠️",
         EventSeverity::Error => "❌",
+        EventSeverity::Critical => "🚨",
     }
 }
 
-fn event_title(event: &NotificationEvent) -> &'static str {
-    match event {
-        NotificationEvent::SessionStopped { .. } => "Session stopped",
-        NotificationEvent::ResumeAttempted { .. } => "Resume attempted",
-        NotificationEvent::ResumeSucceeded { .. } => "Resume succeeded",
-        NotificationEvent::ResumeFailed { .. } => "Resume failed",
-        NotificationEvent::DaemonStarted { .. } => "Daemon started",
-        NotificationEvent::DaemonStopped { .. } => "Daemon stopped",
-    }
-}
-
-fn event_fields(event: &NotificationEvent) -> Vec<SlackText> {
+fn event_fields(event: &NotificationEvent, locale: Locale) -> Vec<SlackText> {
     match event {
         NotificationEvent::SessionStopped {
             session_path,
@@ -144,17 +280,21 @@ fn event_fields(event: &NotificationEvent) -> Vec<SlackText> {
             let mut fields = vec![
                 SlackText {
                     text_type: "mrkdwn",
-                    text: format!("*Session:*\n{}", session_path.display()),
+                    text: format!(
+                        "*{}:*\n{}",
+                        label(locale, "session"),
+                        session_path.display()
+                    ),
                 },
                 SlackText {
                     text_type: "mrkdwn",
-                    text: format!("*Reason:*\n{stop_reason}"),
+                    text: format!("*{}:*\n{stop_reason}", label(locale, "reason")),
                 },
             ];
             if let Some(details) = details {
                 fields.push(SlackText {
                     text_type: "mrkdwn",
-                    text: format!("*Details:*\n{details}"),
+                    text: format!("*{}:*\n{details}", label(locale, "details")),
                 });
             }
             fields
@@ -166,11 +306,15 @@ fn event_fields(event: &NotificationEvent) -> Vec<SlackText> {
         } => vec![
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Session:*\n{}", session_path.display()),
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
             },
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Strategy:*\n{strategy}"),
+                text: format!("*{}:*\n{strategy}", label(locale, "strategy")),
             },
         ],
         NotificationEvent::ResumeSucceeded {
@@ -181,15 +325,19 @@ fn event_fields(event: &NotificationEvent) -> Vec<SlackText> {
         } => vec![
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Session:*\n{}", session_path.display()),
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
             },
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Strategy:*\n{strategy}"),
+                text: format!("*{}:*\n{strategy}", label(locale, "strategy")),
             },
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Wait time:*\n{wait_time_secs}s"),
+                text: format!("*{}:*\n{wait_time_secs}s", label(locale, "wait_time")),
             },
         ],
         NotificationEvent::ResumeFailed {
@@ -200,91 +348,435 @@ fn event_fields(event: &NotificationEvent) -> Vec<SlackText> {
         } => vec![
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Session:*\n{}", session_path.display()),
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
             },
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Strategy:*\n{strategy}"),
+                text: format!("*{}:*\n{strategy}", label(locale, "strategy")),
             },
             SlackText {
                 text_type: "mrkdwn",
-                text: format!("*Error:*\n{error}"),
+                text: format!("*{}:*\n{error}", label(locale, "error")),
             },
         ],
         NotificationEvent::DaemonStarted { version, .. } => vec![SlackText {
             text_type: "mrkdwn",
-            text: format!("*Version:*\n{version}"),
+            text: format!("*{}:*\n{version}", label(locale, "version")),
         }],
         NotificationEvent::DaemonStopped { reason, .. } => vec![SlackText {
             text_type: "mrkdwn",
-            text: format!("*Reason:*\n{reason}"),
+            text: format!("*{}:*\n{reason}", label(locale, "reason")),
         }],
-    }
-}
-
-fn format_event_message(event: &NotificationEvent) -> String {
-    match event {
-        NotificationEvent::SessionStopped {
-            timestamp,
+        NotificationEvent::SessionOrphaned {
             session_path,
-            stop_reason,
-            details,
+            source_session,
+            reason,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "resumed_from"),
+                    source_session.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{reason}", label(locale, "reason")),
+            },
+        ],
+        NotificationEvent::SessionDeleted {
+            session_path,
+            restored_from,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: match restored_from {
+                    Some(backup) => format!(
+                        "*{}:*\n{}",
+                        label(locale, "restored_from"),
+                        backup.display()
+                    ),
+                    None => format!(
+                        "*{}:*\n{}",
+                        label(locale, "restored_from"),
+                        text(locale, "common.not_restored")
+                    ),
+                },
+            },
+        ],
+        NotificationEvent::ModelChanged {
+            session_path,
+            previous_model,
+            current_model,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{previous_model} → {current_model}",
+                    label(locale, "model")
+                ),
+            },
+        ],
+        NotificationEvent::SystemResumed { slept_secs, .. } => vec![SlackText {
+            text_type: "mrkdwn",
+            text: format!(
+                "*{}:*\n{}",
+                label(locale, "slept_for"),
+                format_sleep_gap(*slept_secs)
+            ),
+        }],
+        NotificationEvent::NextStepInvalid {
+            next_step_path,
+            unmatched_lines,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{}", label(locale, "file"), next_step_path.display()),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "first_lines"),
+                    unmatched_lines.join("\n")
+                ),
+            },
+        ],
+        NotificationEvent::WorktreeCreated {
+            worktree_path,
+            branch,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "worktree"),
+                    worktree_path.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{branch}", label(locale, "branch")),
+            },
+        ],
+        NotificationEvent::ResumeDeferred { until, reason, .. } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "deferred_until"),
+                    until.to_rfc3339()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{reason}", label(locale, "reason")),
+            },
+        ],
+        NotificationEvent::SessionCompleted {
+            duration_secs,
+            resumes,
+            time_saved_seconds,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "duration"),
+                    format_completion_duration(*duration_secs, locale)
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{resumes}", label(locale, "resumes")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "time_saved"),
+                    format_time_saved(*time_saved_seconds)
+                ),
+            },
+        ],
+        NotificationEvent::ResumeLoopSuspected {
+            resumes, span_secs, ..
+        } => vec![SlackText {
+            text_type: "mrkdwn",
+            text: format!(
+                "*{}:*\n{}",
+                label(locale, "resumes"),
+                format_loop_summary(*resumes, *span_secs, locale)
+            ),
+        }],
+        NotificationEvent::ResumeSidecarInvalid {
+            sidecar_path,
+            error,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "sidecar"),
+                    sidecar_path.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{error}", label(locale, "error")),
+            },
+        ],
+        NotificationEvent::ClockSkewDetected { skew_secs, .. } => vec![SlackText {
+            text_type: "mrkdwn",
+            text: format!("*{}:*\n{skew_secs:+.0}s", label(locale, "skew")),
+        }],
+        NotificationEvent::FileEncodingFixed { file, encoding, .. } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{}", label(locale, "file"), file.display()),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{encoding}", label(locale, "encoding")),
+            },
+        ],
+        NotificationEvent::ScheduledRun {
+            schedule,
+            workdir,
+            session_path,
+            ..
         } => {
-            let mut message = format!(
-                "Session stopped at {}.\nSession: {}\nReason: {}",
-                timestamp.to_rfc3339(),
-                session_path.display(),
-                stop_reason
-            );
-            if let Some(details) = details {
-                message.push_str(&format!("\nDetails: {details}"));
+            let mut fields = vec![
+                SlackText {
+                    text_type: "mrkdwn",
+                    text: format!("*{}:*\n{schedule}", label(locale, "schedule")),
+                },
+                SlackText {
+                    text_type: "mrkdwn",
+                    text: format!("*{}:*\n{}", label(locale, "workdir"), workdir.display()),
+                },
+            ];
+            if let Some(session_path) = session_path {
+                fields.push(SlackText {
+                    text_type: "mrkdwn",
+                    text: format!(
+                        "*{}:*\n{}",
+                        label(locale, "session"),
+                        session_path.display()
+                    ),
+                });
             }
-            message
+            fields
         }
-        NotificationEvent::ResumeAttempted {
-            timestamp,
+        NotificationEvent::StateReconciled {
+            corrections,
+            report,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "details"),
+                    corrections.join("\n")
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{}", label(locale, "file"), report.display()),
+            },
+        ],
+        NotificationEvent::ArtifactGcPending { summary, plan, .. } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{}", label(locale, "details"), summary.join("\n")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{}", label(locale, "file"), plan.display()),
+            },
+        ],
+        NotificationEvent::StateReadOnly {
+            state_file,
+            found_version,
+            supported_version,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{}", label(locale, "file"), state_file.display()),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\nv{found_version} > v{supported_version}",
+                    label(locale, "version")
+                ),
+            },
+        ],
+        NotificationEvent::ActionObserved {
             session_path,
-            strategy,
-        } => format!(
-            "Resume attempted at {}.\nSession: {}\nStrategy: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy
-        ),
-        NotificationEvent::ResumeSucceeded {
-            timestamp,
+            stop_reason,
+            action,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{stop_reason}", label(locale, "reason")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{action}", label(locale, "would_run")),
+            },
+        ],
+        NotificationEvent::OpenCodeVersionChanged {
+            previous,
+            current,
+            problems,
+            ..
+        } => {
+            let mut fields = vec![
+                SlackText {
+                    text_type: "mrkdwn",
+                    text: format!(
+                        "*{}:*\n{}",
+                        label(locale, "previous"),
+                        previous
+                            .as_deref()
+                            .unwrap_or(text(locale, "common.unknown"))
+                    ),
+                },
+                SlackText {
+                    text_type: "mrkdwn",
+                    text: format!("*{}:*\n{current}", label(locale, "current")),
+                },
+            ];
+            if !problems.is_empty() {
+                fields.push(SlackText {
+                    text_type: "mrkdwn",
+                    text: format!(
+                        "*{}:*\n{}",
+                        label(locale, "compatibility_problems"),
+                        problems.join("\n")
+                    ),
+                });
+            }
+            fields
+        }
+        NotificationEvent::ControlApplied { initiator, .. } => vec![SlackText {
+            text_type: "mrkdwn",
+            text: format!("*{}:*\n{initiator}", label(locale, "by")),
+        }],
+        NotificationEvent::TestNotification { message, .. } => vec![SlackText {
+            text_type: "mrkdwn",
+            text: format!("*{}:*\n{message}", label(locale, "message")),
+        }],
+        NotificationEvent::IncidentEscalated {
             session_path,
-            strategy,
-            wait_time_secs,
-        } => format!(
-            "Resume succeeded at {}.\nSession: {}\nStrategy: {}\nWait time: {}s",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            wait_time_secs
-        ),
-        NotificationEvent::ResumeFailed {
-            timestamp,
+            incident_id,
+            tier,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{incident_id}", label(locale, "incident")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{tier}", label(locale, "tier")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
+            },
+        ],
+        NotificationEvent::IncidentResolved {
             session_path,
-            strategy,
-            error,
-        } => format!(
-            "Resume failed at {}.\nSession: {}\nStrategy: {}\nError: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            error
-        ),
-        NotificationEvent::DaemonStarted { timestamp, version } => format!(
-            "Daemon started at {}.\nVersion: {}",
-            timestamp.to_rfc3339(),
-            version
-        ),
-        NotificationEvent::DaemonStopped { timestamp, reason } => format!(
-            "Daemon stopped at {}.\nReason: {}",
-            timestamp.to_rfc3339(),
-            reason
-        ),
+            incident_id,
+            resolution,
+            ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{incident_id}", label(locale, "incident")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{resolution}", label(locale, "resolution")),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{}",
+                    label(locale, "session"),
+                    session_path.display()
+                ),
+            },
+        ],
+        NotificationEvent::UnexpectedExit {
+            pid, cause, detail, ..
+        } => vec![
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!(
+                    "*{}:*\n{} (PID {pid})",
+                    label(locale, "reason"),
+                    text(locale, &format!("exit_cause.{cause}"))
+                ),
+            },
+            SlackText {
+                text_type: "mrkdwn",
+                text: format!("*{}:*\n{detail}", label(locale, "details")),
+            },
+        ],
     }
 }
 
@@ -305,16 +797,104 @@ mod tests {
             version: "0.1.0".to_string(),
         };
 
-        let message = format_event_message(&event);
+        let message = format_event_message(&event, Locale::En);
 
         assert!(message.contains("Daemon started at 2025-01-02T03:04:05+00:00"));
         assert!(message.contains("Version: 0.1.0"));
 
-        let fields = event_fields(&NotificationEvent::ResumeAttempted {
-            timestamp,
-            session_path: PathBuf::from("/tmp/session"),
-            strategy: "same_session".to_string(),
-        });
+        let fields = event_fields(
+            &NotificationEvent::ResumeAttempted {
+                timestamp,
+                session_path: PathBuf::from("/tmp/session"),
+                strategy: "same_session".to_string(),
+                progress: None,
+                percent: None,
+            },
+            Locale::En,
+        );
         assert_eq!(fields.len(), 2);
     }
+
+    #[test]
+    fn formats_durations_compactly() {
+        let event = NotificationEvent::SessionCompleted {
+            timestamp: chrono::Utc
+                .with_ymd_and_hms(2025, 1, 2, 3, 4, 5)
+                .single()
+                .expect("timestamp"),
+            session_path: PathBuf::from("/tmp/session.md"),
+            duration_secs: Some(3_661),
+            resumes: 2,
+            time_saved_seconds: 600.0,
+        };
+        assert!(
+            format_event_message(&event, Locale::En).contains("after 1h 1m (2 resumes, 10m saved)")
+        );
+
+        let event = NotificationEvent::SystemResumed {
+            timestamp: chrono::Utc::now(),
+            slept_secs: 28_320,
+        };
+        assert!(format_event_message(&event, Locale::En).contains("7h 52m"));
+    }
+
+    fn texts(payload: &SlackWebhookPayload) -> Vec<&str> {
+        payload
+            .blocks
+            .iter()
+            .flat_map(|block| match block {
+                SlackBlock::Header { text } => vec![text.text.as_str()],
+                SlackBlock::Section { fields } => {
+                    fields.iter().map(|field| field.text.as_str()).collect()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn oversized_event_fits_block_limits() {
+        let event = NotificationEvent::SessionStopped {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session.md"),
+            stop_reason: "rate_limit".to_string(),
+            details: Some(format!(
+                "```\n{}```",
+                "429 Too Many Requests\n".repeat(1_000)
+            )),
+            excluded_by: None,
+        };
+        let note = "n".repeat(5_000);
+
+        let payload = build_payload(&event, Some(&note), false, Locale::En);
+        let lines = texts(&payload);
+
+        assert!(HEADER_LIMIT.fits(lines[0]));
+        assert!(lines.iter().all(|text| FIELD_TEXT_LIMIT.fits(text)));
+        assert!(lines.contains(&"*Session:*\n/tmp/session.md"));
+        assert!(lines.contains(&"*Reason:*\nrate_limit"));
+        assert_eq!(lines.last(), Some(&"Full details: /tmp/session.md"));
+
+        let aggressive = build_payload(&event, Some(&note), true, Locale::En);
+        assert!(
+            texts(&aggressive)
+                .iter()
+                .all(|text| FIELD_TEXT_LIMIT.aggressive().fits(text))
+        );
+    }
+
+    #[test]
+    fn small_event_has_no_pointer() {
+        let event = NotificationEvent::DaemonStarted {
+            timestamp: chrono::Utc::now(),
+            version: "1.0.0".to_string(),
+        };
+
+        let payload = build_payload(&event, None, false, Locale::En);
+
+        assert!(
+            !texts(&payload)
+                .iter()
+                .any(|text| text.starts_with("Full details"))
+        );
+    }
 }
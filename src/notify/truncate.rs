@@ -0,0 +1,377 @@
+//! Fitting rendered notifications into provider payload limits.
+//!
+//! Providers reject (Discord, Slack) or silently cut (ntfy) oversized
+//! messages, and a resume failure carrying full evidence and stderr easily
+//! exceeds them. [`truncate`] keeps the head and tail of a text and replaces
+//! the middle with a `… truncated N chars …` marker, never splitting a code
+//! fence delimiter and re-closing a fence the cut left open. [`fit`] also
+//! appends a pointer to where the full detail can be found.
+
+use std::path::PathBuf;
+
+use crate::config::Paths;
+use crate::notify::error::NotifyError;
+use crate::notify::events::NotificationEvent;
+use crate::resume::postmortem::FAILURES_DIR;
+
+/// Room kept for re-closing and re-opening a fence split by the cut.
+const FENCE_RESERVE: usize = 8;
+/// Share of the kept text taken from the head; the rest is the tail.
+const HEAD_SHARE: usize = 2;
+const HEAD_SHARE_OF: usize = 3;
+
+/// How a provider measures its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Chars,
+    Bytes,
+}
+
+/// Maximum size of one text in a provider payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub max: usize,
+    pub unit: Unit,
+}
+
+impl Limit {
+    pub const fn chars(max: usize) -> Self {
+        Self {
+            max,
+            unit: Unit::Chars,
+        }
+    }
+
+    pub const fn bytes(max: usize) -> Self {
+        Self {
+            max,
+            unit: Unit::Bytes,
+        }
+    }
+
+    /// A quarter of the limit, for the retry after a provider rejected the payload.
+    pub const fn aggressive(self) -> Self {
+        Self {
+            max: self.max / 4,
+            unit: self.unit,
+        }
+    }
+
+    /// The smaller of `self` and `max` in the same unit.
+    pub fn at_most(self, max: usize) -> Self {
+        Self {
+            max: self.max.min(max),
+            unit: self.unit,
+        }
+    }
+
+    /// Size of `text` in this limit's unit.
+    pub fn measure(&self, text: &str) -> usize {
+        match self.unit {
+            Unit::Chars => text.chars().count(),
+            Unit::Bytes => text.len(),
+        }
+    }
+
+    pub fn fits(&self, text: &str) -> bool {
+        self.measure(text) <= self.max
+    }
+
+    /// Byte offset keeping at most `size` units from the start.
+    fn head_end(&self, text: &str, size: usize) -> usize {
+        match self.unit {
+            Unit::Chars => text
+                .char_indices()
+                .nth(size)
+                .map_or(text.len(), |(index, _)| index),
+            Unit::Bytes => {
+                let mut end = size.min(text.len());
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            }
+        }
+    }
+
+    /// Byte offset keeping at most `size` units at the end.
+    fn tail_start(&self, text: &str, size: usize) -> usize {
+        match self.unit {
+            Unit::Chars => {
+                let total = text.chars().count();
+                self.head_end(text, total.saturating_sub(size))
+            }
+            Unit::Bytes => {
+                let mut start = text.len().saturating_sub(size);
+                while !text.is_char_boundary(start) {
+                    start += 1;
+                }
+                start
+            }
+        }
+    }
+}
+
+/// Result of [`truncate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncated {
+    pub text: String,
+    /// Characters removed; 0 when the text already fit.
+    pub removed: usize,
+}
+
+/// Cut the middle of `text` so it fits `limit`.
+pub fn truncate(text: &str, limit: Limit) -> Truncated {
+    if limit.fits(text) {
+        return Truncated {
+            text: text.to_string(),
+            removed: 0,
+        };
+    }
+
+    let total_chars = text.chars().count();
+    let marker_size = limit.measure(&marker(total_chars));
+    let Some(budget) = limit.max.checked_sub(marker_size + FENCE_RESERVE) else {
+        let end = limit.head_end(text, limit.max);
+        let end = fence_safe_end(text, end);
+        return Truncated {
+            text: text[..end].to_string(),
+            removed: text[end..].chars().count(),
+        };
+    };
+
+    let head_size = budget * HEAD_SHARE / HEAD_SHARE_OF;
+    let head_end = fence_safe_end(text, limit.head_end(text, head_size));
+    let tail_start =
+        fence_safe_start(text, limit.tail_start(text, budget - head_size)).max(head_end);
+
+    let mut head = text[..head_end].to_string();
+    let mut tail = text[tail_start..].to_string();
+    if open_fence(&head) {
+        head.push_str("\n```");
+    }
+    if open_fence(&tail) {
+        tail.insert_str(0, "```\n");
+    }
+    let removed = text[head_end..tail_start].chars().count();
+
+    Truncated {
+        text: format!("{head}{}{tail}", marker(removed)),
+        removed,
+    }
+}
+
+/// Fit `text` into `limit`, ending with `pointer` when anything was cut.
+pub fn fit(text: &str, limit: Limit, pointer: &str) -> String {
+    if limit.fits(text) {
+        return text.to_string();
+    }
+    with_pointer(text, limit, pointer)
+}
+
+/// Fit `text` into `limit` and always end with `pointer`, for when another
+/// part of the same payload was cut.
+pub fn with_pointer(text: &str, limit: Limit, pointer: &str) -> String {
+    let suffix = format!("\n{pointer}");
+    let room = limit.max.saturating_sub(limit.measure(&suffix));
+    if room == 0 {
+        return truncate(text, limit).text;
+    }
+    let mut fitted = truncate(text, Limit { max: room, ..limit }).text;
+    fitted.push_str(&suffix);
+    fitted
+}
+
+/// Whether the provider refused the payload itself (too large or malformed),
+/// so a shorter one may get through.
+pub fn is_payload_rejection(err: &NotifyError) -> bool {
+    matches!(err.http_status(), Some(400 | 413))
+}
+
+/// Where the untruncated detail of `event` can be found.
+pub fn detail_pointer(event: &NotificationEvent) -> String {
+    match event {
+        NotificationEvent::ResumeFailed { .. } | NotificationEvent::SessionOrphaned { .. } => {
+            format!(
+                "Full details: {} (palingenesis failures list)",
+                Paths::state_dir().join(FAILURES_DIR).display()
+            )
+        }
+        _ => match session_path(event) {
+            Some(path) => format!("Full details: {}", path.display()),
+            None => "Full details: palingenesis logs".to_string(),
+        },
+    }
+}
+
+fn session_path(event: &NotificationEvent) -> Option<PathBuf> {
+    let value = serde_json::to_value(event).ok()?;
+    value
+        .get("session_path")
+        .and_then(|path| path.as_str())
+        .map(PathBuf::from)
+}
+
+fn marker(removed: usize) -> String {
+    format!("\n… truncated {removed} chars …\n")
+}
+
+fn is_fence_char(c: char) -> bool {
+    c == '`' || c == '~'
+}
+
+/// Move `end` back so it does not fall inside a run of fence characters.
+fn fence_safe_end(text: &str, mut end: usize) -> usize {
+    while end > 0 && end < text.len() {
+        let before = text[..end].chars().next_back();
+        let after = text[end..].chars().next();
+        match (before, after) {
+            (Some(b), Some(a)) if is_fence_char(b) && b == a => end -= b.len_utf8(),
+            _ => break,
+        }
+    }
+    end
+}
+
+/// Move `start` forward so it does not fall inside a run of fence characters.
+fn fence_safe_start(text: &str, mut start: usize) -> usize {
+    while start > 0 && start < text.len() {
+        let before = text[..start].chars().next_back();
+        let after = text[start..].chars().next();
+        match (before, after) {
+            (Some(b), Some(a)) if is_fence_char(a) && b == a => start += a.len_utf8(),
+            _ => break,
+        }
+    }
+    start
+}
+
+fn fence_lines(text: &str) -> usize {
+    text.lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            line.starts_with("```") || line.starts_with("~~~")
+        })
+        .count()
+}
+
+/// Whether a cut left `part` with an unmatched fence: a head that ends
+/// inside a block, or a tail that starts inside one.
+fn open_fence(part: &str) -> bool {
+    fence_lines(part) % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_untouched() {
+        let result = truncate("resume failed", Limit::chars(100));
+
+        assert_eq!(result.text, "resume failed");
+        assert_eq!(result.removed, 0);
+    }
+
+    #[test]
+    fn keeps_head_and_tail_with_marker() {
+        let text = format!("HEAD {} TAIL", "x".repeat(5000));
+
+        let result = truncate(&text, Limit::chars(200));
+
+        assert!(result.text.chars().count() <= 200);
+        assert!(result.text.starts_with("HEAD "));
+        assert!(result.text.ends_with(" TAIL"));
+        assert!(
+            result
+                .text
+                .contains(&format!("… truncated {} chars …", result.removed))
+        );
+        assert_eq!(
+            result.text.chars().count() - marker(result.removed).chars().count() + result.removed,
+            text.chars().count()
+        );
+    }
+
+    #[test]
+    fn byte_limits_respect_char_boundaries() {
+        let text = "é".repeat(3000);
+
+        let result = truncate(&text, Limit::bytes(4096));
+
+        assert!(result.text.len() <= 4096);
+        assert!(result.removed > 0);
+    }
+
+    #[test]
+    fn never_splits_a_fence_delimiter() {
+        let text = "abc```def";
+
+        assert_eq!(fence_safe_end(text, 4), 3);
+        assert_eq!(fence_safe_end(text, 5), 3);
+        assert_eq!(fence_safe_start(text, 4), 6);
+        assert_eq!(fence_safe_end(text, 6), 6);
+    }
+
+    #[test]
+    fn reopens_and_closes_split_code_blocks() {
+        let text = format!(
+            "Error:\n```\n{}\n```\nAttempt 3",
+            "stderr line\n".repeat(500)
+        );
+
+        let result = truncate(&text, Limit::chars(400));
+
+        assert!(result.text.chars().count() <= 400);
+        assert_eq!(fence_lines(&result.text) % 2, 0);
+        assert!(result.text.ends_with("Attempt 3"));
+    }
+
+    #[test]
+    fn fit_points_at_full_detail() {
+        let text = "y".repeat(1000);
+
+        let fitted = fit(&text, Limit::chars(300), "Full details: /tmp/failures");
+
+        assert!(fitted.chars().count() <= 300);
+        assert!(fitted.ends_with("\nFull details: /tmp/failures"));
+        assert_eq!(fit("short", Limit::chars(300), "unused"), "short");
+    }
+
+    #[test]
+    fn with_pointer_appends_even_when_text_fits() {
+        assert_eq!(
+            with_pointer("short", Limit::chars(300), "Full details: x"),
+            "short\nFull details: x"
+        );
+    }
+
+    #[test]
+    fn pointer_names_failures_dir_or_session() {
+        let failed = NotificationEvent::ResumeFailed {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session.md"),
+            strategy: "new_session".to_string(),
+            error: "boom".to_string(),
+            progress: None,
+            percent: None,
+            incident: None,
+            ack: None,
+        };
+        let stopped = NotificationEvent::SessionStopped {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session.md"),
+            stop_reason: "rate_limit".to_string(),
+            details: None,
+            excluded_by: None,
+        };
+        let started = NotificationEvent::DaemonStarted {
+            timestamp: chrono::Utc::now(),
+            version: "1.0".to_string(),
+        };
+
+        assert!(detail_pointer(&failed).contains(FAILURES_DIR));
+        assert_eq!(detail_pointer(&stopped), "Full details: /tmp/session.md");
+        assert_eq!(detail_pointer(&started), "Full details: palingenesis logs");
+    }
+}
@@ -0,0 +1,293 @@
+//! SSRF protection for notification URLs.
+//!
+//! Notification targets can be changed through config reloads, so a bad or
+//! hostile value could aim webhooks at loopback admin ports or cloud metadata
+//! services. [`UrlGuard`] rejects private, loopback, and link-local targets
+//! unless `notifications.allow_private_networks` is set:
+//!
+//! - IP hosts and `localhost` are checked before each request and in
+//!   `validate_config`. URLs are parsed the way reqwest parses them, so
+//!   decimal, hex and shortened IPv4 forms are caught too.
+//! - Hostnames are checked at connect time by a DNS resolver that refuses
+//!   private answers, so every redirect hop is covered too.
+//! - Redirects are limited to [`MAX_REDIRECTS`] and each hop's URL is checked.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use thiserror::Error;
+use url::{Host, Url};
+
+use crate::config::schema::NotificationsConfig;
+
+/// Redirect hops followed before a notification request fails.
+pub const MAX_REDIRECTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UrlGuardError {
+    #[error("Invalid URL: {url}")]
+    InvalidUrl { url: String },
+
+    #[error("{host} resolves to private address {addr}")]
+    PrivateAddress { host: String, addr: IpAddr },
+
+    #[error("{host} is a loopback host")]
+    LoopbackHost { host: String },
+
+    #[error("Too many redirects (limit {limit})")]
+    TooManyRedirects { limit: usize },
+}
+
+/// Address policy for outbound notification requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UrlGuard {
+    allow_private: bool,
+}
+
+impl UrlGuard {
+    pub fn new(allow_private: bool) -> Self {
+        Self { allow_private }
+    }
+
+    pub fn from_config(config: &NotificationsConfig) -> Self {
+        Self::new(config.allow_private_networks)
+    }
+
+    pub fn allows_private(&self) -> bool {
+        self.allow_private
+    }
+
+    /// Check a URL without DNS: IP hosts and `localhost` names.
+    ///
+    /// Other hostnames pass here and are checked when they are resolved.
+    pub fn check_url(&self, url: &str) -> Result<(), UrlGuardError> {
+        let invalid = || UrlGuardError::InvalidUrl {
+            url: url.to_string(),
+        };
+        let parsed = Url::parse(url.trim()).map_err(|_| invalid())?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(invalid());
+        }
+        let host = parsed.host().ok_or_else(invalid)?;
+        if self.allow_private {
+            return Ok(());
+        }
+        match host {
+            Host::Ipv4(addr) => self.check_addr(&addr.to_string(), IpAddr::V4(addr)),
+            Host::Ipv6(addr) => self.check_addr(&addr.to_string(), IpAddr::V6(addr)),
+            Host::Domain(name) => {
+                let name = name.trim_end_matches('.');
+                if name == "localhost" || name.ends_with(".localhost") {
+                    return Err(UrlGuardError::LoopbackHost {
+                        host: name.to_string(),
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check one resolved address for `host`.
+    pub fn check_addr(&self, host: &str, addr: IpAddr) -> Result<(), UrlGuardError> {
+        if self.allow_private || !is_private(addr) {
+            return Ok(());
+        }
+        Err(UrlGuardError::PrivateAddress {
+            host: host.to_string(),
+            addr,
+        })
+    }
+
+    /// Decide whether to follow a redirect to `next` after `previous_hops` redirects.
+    pub fn check_redirect(&self, next: &str, previous_hops: usize) -> Result<(), UrlGuardError> {
+        if previous_hops >= MAX_REDIRECTS {
+            return Err(UrlGuardError::TooManyRedirects {
+                limit: MAX_REDIRECTS,
+            });
+        }
+        self.check_url(next)
+    }
+
+    /// HTTP client that enforces this guard on redirects and DNS answers.
+    ///
+    /// There is no unguarded fallback: if the client cannot be built, the
+    /// channel must not send at all.
+    #[cfg(feature = "notifications")]
+    pub fn build_client(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<reqwest::Client, crate::notify::NotifyError> {
+        let guard = *self;
+        let mut builder = reqwest::Client::builder().timeout(timeout).redirect(
+            reqwest::redirect::Policy::custom(move |attempt| {
+                match guard.check_redirect(attempt.url().as_str(), attempt.previous().len()) {
+                    Ok(()) => attempt.follow(),
+                    Err(err) => attempt.error(err),
+                }
+            }),
+        );
+        if !self.allow_private {
+            builder = builder.dns_resolver(std::sync::Arc::new(GuardedResolver { guard }));
+        }
+        builder.build().map_err(|err| {
+            tracing::warn!(error = %err, "Failed to build notification client; channel disabled");
+            crate::notify::NotifyError::ConfigError {
+                message: format!("failed to build HTTP client: {err}"),
+            }
+        })
+    }
+}
+
+/// Resolver that fails lookups returning any private address.
+#[cfg(feature = "notifications")]
+struct GuardedResolver {
+    guard: UrlGuard,
+}
+
+#[cfg(feature = "notifications")]
+impl reqwest::dns::Resolve for GuardedResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let guard = self.guard;
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<std::net::SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            for addr in &addrs {
+                guard.check_addr(&host, addr.ip())?;
+            }
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Loopback, RFC 1918, link-local, CGNAT, unique-local, and unspecified addresses.
+fn is_private(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => is_private_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_private_v4(v4),
+            None => is_private_v6(v6),
+        },
+    }
+}
+
+fn is_private_v4(addr: Ipv4Addr) -> bool {
+    let [first, second, ..] = addr.octets();
+    addr.is_loopback()
+        || addr.is_private()
+        || addr.is_link_local()
+        || addr.is_unspecified()
+        || addr.is_broadcast()
+        // 100.64.0.0/10 shared address space (carrier-grade NAT).
+        || (first == 100 && (second & 0xc0) == 64)
+}
+
+fn is_private_v6(addr: Ipv6Addr) -> bool {
+    addr.is_loopback()
+        || addr.is_unspecified()
+        || addr.is_unique_local()
+        || addr.is_unicast_link_local()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_loopback_and_metadata_urls() {
+        let guard = UrlGuard::default();
+        for url in [
+            "http://127.0.0.1:8080/admin",
+            "http://[::1]/hook",
+            "http://localhost:9000/",
+            "https://user:pw@169.254.169.254/latest/meta-data",
+            "http://10.1.2.3/hook",
+            "http://[::ffff:192.168.1.1]/hook",
+        ] {
+            assert!(guard.check_url(url).is_err(), "{url} should be rejected");
+        }
+        assert!(matches!(
+            guard.check_url("http://127.0.0.1/"),
+            Err(UrlGuardError::PrivateAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_numeric_ipv4_spellings() {
+        let guard = UrlGuard::default();
+        for url in [
+            "http://2130706433/",
+            "http://127.1/",
+            "http://0x7f.0.0.1/",
+            "http://0177.0.0.1/",
+            "http://0xa9fea9fe/latest/meta-data",
+        ] {
+            assert!(
+                matches!(
+                    guard.check_url(url),
+                    Err(UrlGuardError::PrivateAddress { .. })
+                ),
+                "{url} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn allows_public_hosts() {
+        let guard = UrlGuard::default();
+        assert!(
+            guard
+                .check_url("https://hooks.slack.com/services/1")
+                .is_ok()
+        );
+        assert!(guard.check_url("https://93.184.216.34:8443/hook").is_ok());
+        assert!(
+            guard
+                .check_addr("example.com", "8.8.8.8".parse().unwrap())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn opt_out_allows_private_networks() {
+        let guard = UrlGuard::new(true);
+        assert!(guard.check_url("http://127.0.0.1:8080/hook").is_ok());
+        assert!(guard.check_url("http://localhost/hook").is_ok());
+        assert!(
+            guard
+                .check_addr("internal", "10.0.0.5".parse().unwrap())
+                .is_ok()
+        );
+        assert!(guard.check_url("ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn redirect_from_public_host_to_private_address_is_blocked() {
+        let guard = UrlGuard::default();
+        assert!(guard.check_redirect("https://example.org/next", 0).is_ok());
+        assert!(matches!(
+            guard.check_redirect("http://169.254.169.254/latest", 1),
+            Err(UrlGuardError::PrivateAddress { .. })
+        ));
+        assert_eq!(
+            guard.check_redirect("https://example.org/again", MAX_REDIRECTS),
+            Err(UrlGuardError::TooManyRedirects {
+                limit: MAX_REDIRECTS
+            })
+        );
+    }
+
+    #[test]
+    fn resolved_private_answers_are_rejected() {
+        let guard = UrlGuard::default();
+        assert!(matches!(
+            guard.check_addr("rebind.example", "100.100.1.1".parse().unwrap()),
+            Err(UrlGuardError::PrivateAddress { .. })
+        ));
+        assert!(
+            guard
+                .check_addr("rebind.example", "fe80::1".parse().unwrap())
+                .is_err()
+        );
+    }
+}
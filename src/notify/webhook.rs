@@ -1,16 +1,28 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use reqwest::Client;
-use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::header::{CONTENT_TYPE, HeaderName, HeaderValue};
 use tokio::time::sleep;
 use tracing::{debug, warn};
 
 use crate::config::schema::WebhookConfig;
-use crate::notify::channel::NotificationChannel;
+use crate::i18n::Locale;
+use crate::notify::channel::{EventFilter, NotificationChannel};
+use crate::notify::credentials::{
+    ChannelCredentials, ChannelKind, CredentialRegistry, Credentials,
+};
 use crate::notify::error::NotifyError;
 use crate::notify::events::NotificationEvent;
+use crate::notify::message::format_event_message;
+use crate::notify::routing::{self, TagRoute};
+use crate::notify::signing;
+use crate::notify::truncate::{Limit, is_payload_rejection, truncate};
+use crate::notify::url_guard::UrlGuard;
+use crate::util::path_display;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 const MAX_RETRIES: usize = 3;
@@ -19,42 +31,109 @@ const BACKOFF_DELAYS: [Duration; MAX_RETRIES] = [
     Duration::from_secs(2),
     Duration::from_secs(4),
 ];
+/// String fields are cut to this after the receiver rejects a payload.
+const SHORTENED_STRING_LIMIT: Limit = Limit::chars(2000);
 
 pub struct WebhookChannel {
-    url: String,
-    headers: Option<HashMap<String, String>>,
-    client: Client,
+    name: String,
+    /// URL and headers.
+    credentials: Arc<ChannelCredentials>,
+    filter: EventFilter,
+    /// Guarded HTTP client; if it could not be built, every send fails.
+    client: Result<Client, NotifyError>,
+    guard: UrlGuard,
+    /// `match_tags`/`exclude_tags`.
+    route: TagRoute,
     enabled: bool,
 }
 
 impl WebhookChannel {
     pub fn new(config: &WebhookConfig) -> Self {
-        let client = Client::builder()
-            .timeout(REQUEST_TIMEOUT)
-            .build()
-            .unwrap_or_else(|err| {
-                warn!(error = %err, "Failed to build webhook client; using defaults");
-                Client::new()
-            });
-
+        let guard = UrlGuard::default();
         Self {
-            url: config.url.clone(),
-            headers: config.headers.clone(),
-            client,
+            name: config.channel_name().to_string(),
+            credentials: CredentialRegistry::global().register(
+                config.channel_name(),
+                ChannelKind::Webhook,
+                Credentials::webhook(config),
+            ),
+            filter: EventFilter::new(&config.severities, &config.events),
+            client: guard.build_client(REQUEST_TIMEOUT),
+            guard,
+            route: TagRoute::new(&config.match_tags, &config.exclude_tags),
             enabled: true,
         }
     }
+
+    /// Apply `notifications.allow_private_networks`.
+    pub fn with_url_guard(mut self, guard: UrlGuard) -> Self {
+        self.client = guard.build_client(REQUEST_TIMEOUT);
+        self.guard = guard;
+        self
+    }
 }
 
 #[async_trait]
 impl NotificationChannel for WebhookChannel {
-    fn name(&self) -> &'static str {
-        "webhook"
+    fn name(&self) -> &str {
+        &self.name
     }
 
     async fn send(&self, event: &NotificationEvent) -> Result<(), NotifyError> {
-        let message = format_event_message(event);
-        let mut last_error = match send_once(self, event).await {
+        self.deliver(event, None).await
+    }
+
+    async fn send_annotated(
+        &self,
+        event: &NotificationEvent,
+        note: &str,
+    ) -> Result<(), NotifyError> {
+        self.deliver(event, Some(note)).await
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn tag_route(&self) -> Option<&TagRoute> {
+        Some(&self.route)
+    }
+
+    fn accepts(&self, event: &NotificationEvent) -> bool {
+        self.filter.matches(event)
+    }
+
+    fn refresh_credentials(&self) -> Result<bool, NotifyError> {
+        self.credentials.refresh()
+    }
+}
+
+impl WebhookChannel {
+    async fn deliver(
+        &self,
+        event: &NotificationEvent,
+        note: Option<&str>,
+    ) -> Result<(), NotifyError> {
+        let tags = routing::event_tags(event);
+        let event = &*event.with_display_paths(&path_display::global().for_json());
+        // A blocked target won't become allowed on retry.
+        self.guard.check_url(&self.credentials.current().url)?;
+        let message = format_event_message(event, Locale::En);
+        let mut body = webhook_body(event, &tags, note)
+            .map_err(|message| NotifyError::SendFailed { message })?;
+        let delivery = signing::next_delivery_id();
+
+        let mut result = self.post(event.event_type(), delivery, &body).await;
+        if result.as_ref().is_err_and(is_payload_rejection) {
+            debug!(
+                channel = self.name(),
+                event_type = event.event_type(),
+                "Webhook rejected the payload; retrying with a shorter one"
+            );
+            shorten_strings(&mut body);
+            result = self.post(event.event_type(), delivery, &body).await;
+        }
+        let mut last_error = match result {
             Ok(()) => {
                 debug!(
                     channel = self.name(),
@@ -76,7 +155,7 @@ impl NotificationChannel for WebhookChannel {
                 "Webhook send failed; retrying"
             );
             sleep(*delay).await;
-            match send_once(self, event).await {
+            match self.post(event.event_type(), delivery, &body).await {
                 Ok(()) => {
                     debug!(
                         channel = self.name(),
@@ -91,13 +170,52 @@ impl NotificationChannel for WebhookChannel {
             }
         }
 
-        Err(NotifyError::SendFailed {
-            message: last_error,
-        })
+        Err(last_error)
     }
 
-    fn is_enabled(&self) -> bool {
-        self.enabled
+    /// One attempt of delivery `delivery`, signed over the bytes it sends.
+    async fn post(
+        &self,
+        event_type: &str,
+        delivery: u64,
+        body: &serde_json::Value,
+    ) -> Result<(), NotifyError> {
+        let bytes = serde_json::to_vec(body).map_err(|err| NotifyError::SendFailed {
+            message: format!("Serialization error: {err}"),
+        })?;
+        let client = self.client.as_ref().map_err(Clone::clone)?;
+        let credentials = self.credentials.current();
+        let request = client.post(&credentials.url);
+        let request = apply_headers(request, credentials.headers.as_ref());
+        let request = signed_headers(
+            credentials.signing_secret.as_deref(),
+            event_type,
+            delivery,
+            Utc::now().timestamp(),
+            &bytes,
+        )
+        .into_iter()
+        .fold(request, |request, (name, value)| {
+            request.header(name, value)
+        })
+        .header(CONTENT_TYPE, "application/json")
+        .body(bytes);
+
+        match request.send().await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(NotifyError::Status {
+                        service: "webhook".to_string(),
+                        status: response.status().as_u16(),
+                    })
+                }
+            }
+            Err(err) => Err(NotifyError::SendFailed {
+                message: format!("Request error: {err}"),
+            }),
+        }
     }
 }
 
@@ -125,85 +243,57 @@ fn apply_headers(
     request
 }
 
-async fn send_once(channel: &WebhookChannel, event: &NotificationEvent) -> Result<(), String> {
-    let request = channel.client.post(&channel.url).json(event);
-    let request = apply_headers(request, channel.headers.as_ref());
+/// Event, delivery and timestamp headers, plus the signature when the
+/// channel has a `signing_secret`.
+pub fn signed_headers(
+    secret: Option<&str>,
+    event_type: &str,
+    delivery: u64,
+    timestamp: i64,
+    body: &[u8],
+) -> Vec<(&'static str, String)> {
+    let mut headers = vec![
+        (signing::EVENT_HEADER, event_type.to_string()),
+        (signing::DELIVERY_HEADER, delivery.to_string()),
+        (signing::TIMESTAMP_HEADER, timestamp.to_string()),
+    ];
+    if let Some(secret) = secret {
+        headers.push((
+            signing::SIGNATURE_HEADER,
+            signing::signature(secret, timestamp, body),
+        ));
+    }
+    headers
+}
 
-    match request.send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                Ok(())
-            } else {
-                Err(format!("Unexpected status: {}", response.status()))
-            }
-        }
-        Err(err) => Err(format!("Request error: {err}")),
+/// Event JSON with the session's routing `tags`, and an optional `note`
+/// field for annotated sends.
+pub fn webhook_body(
+    event: &NotificationEvent,
+    tags: &[String],
+    note: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    let mut body =
+        serde_json::to_value(event).map_err(|err| format!("Serialization error: {err}"))?;
+    routing::insert_tags(&mut body, tags);
+    if let (Some(note), Some(object)) = (note, body.as_object_mut()) {
+        object.insert("note".to_string(), serde_json::Value::from(note));
     }
+    Ok(body)
 }
 
-fn format_event_message(event: &NotificationEvent) -> String {
-    match event {
-        NotificationEvent::SessionStopped {
-            timestamp,
-            session_path,
-            stop_reason,
-            details,
-        } => {
-            let mut message = format!(
-                "Session stopped at {}.\nSession: {}\nReason: {}",
-                timestamp.to_rfc3339(),
-                session_path.display(),
-                stop_reason
-            );
-            if let Some(details) = details {
-                message.push_str(&format!("\nDetails: {details}"));
+/// Cut the middle of every long string in `body`.
+fn shorten_strings(body: &mut serde_json::Value) {
+    match body {
+        serde_json::Value::String(text) => {
+            let cut = truncate(text, SHORTENED_STRING_LIMIT);
+            if cut.removed > 0 {
+                *text = cut.text;
             }
-            message
         }
-        NotificationEvent::ResumeAttempted {
-            timestamp,
-            session_path,
-            strategy,
-        } => format!(
-            "Resume attempted at {}.\nSession: {}\nStrategy: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy
-        ),
-        NotificationEvent::ResumeSucceeded {
-            timestamp,
-            session_path,
-            strategy,
-            wait_time_secs,
-        } => format!(
-            "Resume succeeded at {}.\nSession: {}\nStrategy: {}\nWait time: {}s",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            wait_time_secs
-        ),
-        NotificationEvent::ResumeFailed {
-            timestamp,
-            session_path,
-            strategy,
-            error,
-        } => format!(
-            "Resume failed at {}.\nSession: {}\nStrategy: {}\nError: {}",
-            timestamp.to_rfc3339(),
-            session_path.display(),
-            strategy,
-            error
-        ),
-        NotificationEvent::DaemonStarted { timestamp, version } => format!(
-            "Daemon started at {}.\nVersion: {}",
-            timestamp.to_rfc3339(),
-            version
-        ),
-        NotificationEvent::DaemonStopped { timestamp, reason } => format!(
-            "Daemon stopped at {}.\nReason: {}",
-            timestamp.to_rfc3339(),
-            reason
-        ),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(shorten_strings),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(shorten_strings),
+        _ => {}
     }
 }
 
@@ -224,13 +314,158 @@ mod tests {
             session_path: PathBuf::from("/tmp/session"),
             stop_reason: "rate_limit".to_string(),
             details: Some("Retry later".to_string()),
+            excluded_by: None,
         };
 
-        let message = format_event_message(&event);
+        let message = format_event_message(&event, Locale::En);
 
         assert!(message.contains("Session stopped at 2025-01-02T03:04:05+00:00"));
         assert!(message.contains("Session: /tmp/session"));
         assert!(message.contains("Reason: rate_limit"));
         assert!(message.contains("Details: Retry later"));
     }
+
+    #[test]
+    fn annotated_body_includes_note() {
+        let event = NotificationEvent::DaemonStopped {
+            timestamp: chrono::Utc::now(),
+            reason: "signal".to_string(),
+        };
+
+        let plain = webhook_body(&event, &[], None).unwrap();
+        let annotated = webhook_body(&event, &[], Some("Primary channel ntfy failed")).unwrap();
+        let tagged = webhook_body(&event, &["proj-a".to_string()], None).unwrap();
+
+        assert!(plain.get("note").is_none());
+        assert!(plain.get("tags").is_none());
+        assert_eq!(annotated["note"], "Primary channel ntfy failed");
+        assert_eq!(annotated["event"], "daemon_stopped");
+        assert_eq!(tagged["tags"], serde_json::json!(["proj-a"]));
+    }
+
+    #[test]
+    fn formats_durations_compactly() {
+        let event = NotificationEvent::SessionCompleted {
+            timestamp: chrono::Utc
+                .with_ymd_and_hms(2025, 1, 2, 3, 4, 5)
+                .single()
+                .expect("timestamp"),
+            session_path: PathBuf::from("/tmp/session.md"),
+            duration_secs: Some(3_661),
+            resumes: 2,
+            time_saved_seconds: 600.0,
+        };
+        assert!(
+            format_event_message(&event, Locale::En).contains("after 1h 1m (2 resumes, 10m saved)")
+        );
+
+        let event = NotificationEvent::SystemResumed {
+            timestamp: chrono::Utc::now(),
+            slept_secs: 28_320,
+        };
+        assert!(format_event_message(&event, Locale::En).contains("7h 52m"));
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn signatures_verify_against_the_body_sent_on_every_attempt() {
+        use axum::Router;
+        use axum::body::Bytes;
+        use axum::http::{HeaderMap, StatusCode};
+        use std::sync::Mutex;
+
+        let requests = Arc::new(Mutex::new(Vec::<(HeaderMap, Bytes)>::new()));
+        let sink = Arc::clone(&requests);
+        let app = Router::new().fallback(move |headers: HeaderMap, body: Bytes| {
+            let sink = Arc::clone(&sink);
+            async move {
+                let mut requests = sink.lock().unwrap();
+                requests.push((headers, body));
+                match requests.len() {
+                    1 => StatusCode::PAYLOAD_TOO_LARGE,
+                    2 => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::NO_CONTENT,
+                }
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let channel = WebhookChannel::new(&WebhookConfig {
+            name: Some("signed-webhook-test".to_string()),
+            signing_secret: Some("whsec_test".to_string()),
+            ..WebhookConfig::new(format!("http://{addr}/hook"))
+        })
+        .with_url_guard(UrlGuard::new(true));
+        let event = NotificationEvent::SessionStopped {
+            timestamp: chrono::Utc::now(),
+            session_path: PathBuf::from("/tmp/session.md"),
+            stop_reason: "rate_limit".to_string(),
+            details: Some("x".repeat(10_000)),
+            excluded_by: None,
+        };
+        channel.send(&event).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        let header = |headers: &HeaderMap, name: &str| {
+            headers.get(name).unwrap().to_str().unwrap().to_string()
+        };
+        for (headers, body) in requests.iter() {
+            signing::verify(
+                "whsec_test",
+                &header(headers, signing::TIMESTAMP_HEADER),
+                &header(headers, signing::SIGNATURE_HEADER),
+                body,
+                chrono::Utc::now().timestamp(),
+                signing::DEFAULT_TOLERANCE,
+            )
+            .unwrap();
+            assert_eq!(header(headers, signing::EVENT_HEADER), "session_stopped");
+            assert_eq!(
+                header(headers, signing::DELIVERY_HEADER),
+                header(&requests[0].0, signing::DELIVERY_HEADER)
+            );
+        }
+        // The retry after the 413 sent, and signed, a shortened body.
+        assert!(requests[1].1.len() < requests[0].1.len());
+        assert_eq!(requests[2].1, requests[1].1);
+        let shortened: serde_json::Value = serde_json::from_slice(&requests[1].1).unwrap();
+        assert!(shortened["details"].as_str().unwrap().contains("truncated"));
+    }
+
+    #[test]
+    fn unsigned_requests_still_carry_event_and_delivery() {
+        let headers = signed_headers(None, "daemon_started", 7, 1_000, b"{}");
+        let names: Vec<&str> = headers.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            vec![
+                signing::EVENT_HEADER,
+                signing::DELIVERY_HEADER,
+                signing::TIMESTAMP_HEADER
+            ]
+        );
+        let signed = signed_headers(Some("s"), "daemon_started", 7, 1_000, b"{}");
+        assert_eq!(signed[3].1, signing::signature("s", 1_000, b"{}"));
+    }
+
+    #[tokio::test]
+    async fn unbuilt_client_fails_closed() {
+        let mut channel = WebhookChannel::new(&WebhookConfig::new("http://127.0.0.1:9/hook"))
+            .with_url_guard(UrlGuard::new(true));
+        channel.client = Err(NotifyError::ConfigError {
+            message: "failed to build HTTP client".to_string(),
+        });
+        let event = NotificationEvent::DaemonStarted {
+            timestamp: chrono::Utc::now(),
+            version: "1.0.0".to_string(),
+        };
+
+        let err = channel.send(&event).await.unwrap_err();
+        assert!(matches!(err, NotifyError::ConfigError { .. }));
+    }
 }
@@ -6,9 +6,11 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn};
 
 use crate::config::schema::OpenCodeConfig;
+use crate::opencode::discovery::SharedEndpoint;
 
 const DEFAULT_USERNAME: &str = "opencode";
 const DEFAULT_MAX_RETRIES: usize = 3;
@@ -30,6 +32,10 @@ pub enum OpenCodeApiError {
     HttpStatus { status: StatusCode, body: String },
     #[error("Failed to parse response: {0}")]
     ParseError(String),
+    /// The daemon began shutting down while the request was in flight or
+    /// waiting to retry.
+    #[error("Request cancelled")]
+    Cancelled,
 }
 
 impl OpenCodeApiError {
@@ -48,10 +54,23 @@ pub struct Session {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl Session {
+    /// Model identifier from the session metadata, when the server reports one.
+    pub fn model(&self) -> Option<&str> {
+        ["model", "modelID", "model_id"]
+            .iter()
+            .find_map(|key| self.metadata.get(*key))
+            .and_then(serde_json::Value::as_str)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CreateSessionResponse {
     #[serde(alias = "session_id")]
     pub id: String,
+    /// Model the server assigned to the new session, when it reports one.
+    #[serde(default, alias = "modelID", skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -74,8 +93,10 @@ struct BasicAuth {
 pub struct OpenCodeClient {
     client: Client,
     base_url: String,
+    endpoint: Option<SharedEndpoint>,
     auth: Option<BasicAuth>,
     backoff_delays: Vec<Duration>,
+    cancel: CancellationToken,
 }
 
 impl OpenCodeClient {
@@ -94,12 +115,35 @@ impl OpenCodeClient {
         Self {
             client,
             base_url,
+            endpoint: None,
             auth,
             backoff_delays: DEFAULT_BACKOFF_DELAYS.to_vec(),
+            cancel: CancellationToken::new(),
         }
     }
 
-    #[cfg(test)]
+    /// Abandon in-flight requests and pending retries once `cancel` fires.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Follow the endpoint resolved by the OpenCode monitor instead of the configured port.
+    pub fn with_endpoint(mut self, endpoint: SharedEndpoint) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Base URL for API requests, preferring the discovered endpoint when known.
+    pub fn base_url(&self) -> String {
+        self.endpoint
+            .as_ref()
+            .and_then(SharedEndpoint::get)
+            .map(|endpoint| endpoint.base_url())
+            .unwrap_or_else(|| self.base_url.clone())
+    }
+
+    #[cfg(all(test, feature = "http-api"))]
     fn with_base_url(base_url: String, backoff_delays: Vec<Duration>) -> Self {
         Self {
             client: Client::builder()
@@ -107,13 +151,15 @@ impl OpenCodeClient {
                 .build()
                 .expect("build test client"),
             base_url,
+            endpoint: None,
             auth: None,
             backoff_delays,
+            cancel: CancellationToken::new(),
         }
     }
 
     pub async fn health(&self) -> Result<HealthResponse, OpenCodeApiError> {
-        let url = format!("{}/global/health", self.base_url);
+        let url = format!("{}/global/health", self.base_url());
         self.request_with_retry(|| async {
             let response = self
                 .apply_auth(self.client.get(&url))
@@ -126,7 +172,7 @@ impl OpenCodeClient {
     }
 
     pub async fn list_sessions(&self) -> Result<Vec<Session>, OpenCodeApiError> {
-        let url = format!("{}/session", self.base_url);
+        let url = format!("{}/session", self.base_url());
         self.request_with_retry(|| async {
             let response = self
                 .apply_auth(self.client.get(&url))
@@ -141,17 +187,28 @@ impl OpenCodeClient {
     pub async fn create_session(
         &self,
         prompt: &str,
+    ) -> Result<CreateSessionResponse, OpenCodeApiError> {
+        self.create_session_with_model(prompt, None).await
+    }
+
+    /// Create a session, asking the server to use `model` when given.
+    pub async fn create_session_with_model(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
     ) -> Result<CreateSessionResponse, OpenCodeApiError> {
         #[derive(Serialize)]
         struct CreateRequest<'a> {
             prompt: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            model: Option<&'a str>,
         }
 
-        let url = format!("{}/session", self.base_url);
+        let url = format!("{}/session", self.base_url());
         self.request_with_retry(|| async {
             let response = self
                 .apply_auth(self.client.post(&url))
-                .json(&CreateRequest { prompt })
+                .json(&CreateRequest { prompt, model })
                 .send()
                 .await
                 .map_err(map_reqwest_error)?;
@@ -170,7 +227,7 @@ impl OpenCodeClient {
             message: &'a str,
         }
 
-        let url = format!("{}/session/{}/message", self.base_url, session_id);
+        let url = format!("{}/session/{}/message", self.base_url(), session_id);
         self.request_with_retry(|| async {
             let response = self
                 .apply_auth(self.client.post(&url))
@@ -197,12 +254,23 @@ impl OpenCodeClient {
         }
     }
 
+    /// Run `request_fn`, retrying transient failures on the backoff
+    /// schedule. Both the request and the wait before a retry are abandoned
+    /// with [`OpenCodeApiError::Cancelled`] as soon as the token fires.
     async fn request_with_retry<F, Fut, T>(&self, request_fn: F) -> Result<T, OpenCodeApiError>
     where
         F: Fn() -> Fut,
         Fut: std::future::Future<Output = Result<T, OpenCodeApiError>>,
     {
-        let mut last_error = match request_fn().await {
+        let attempt = || async {
+            tokio::select! {
+                biased;
+                _ = self.cancel.cancelled() => Err(OpenCodeApiError::Cancelled),
+                result = request_fn() => result,
+            }
+        };
+
+        let mut last_error = match attempt().await {
             Ok(response) => {
                 debug!("OpenCode API request succeeded");
                 return Ok(response);
@@ -210,18 +278,22 @@ impl OpenCodeClient {
             Err(err) => err,
         };
 
-        for (attempt, delay) in self.backoff_delays.iter().enumerate() {
+        for (retry, delay) in self.backoff_delays.iter().enumerate() {
             if !last_error.is_retryable() {
                 return Err(last_error);
             }
             warn!(
-                attempt = attempt + 1,
+                attempt = retry + 1,
                 delay_secs = delay.as_secs_f64(),
                 error = %last_error,
                 "OpenCode API request failed; retrying"
             );
-            sleep(*delay).await;
-            match request_fn().await {
+            tokio::select! {
+                biased;
+                _ = self.cancel.cancelled() => return Err(OpenCodeApiError::Cancelled),
+                _ = sleep(*delay) => {}
+            }
+            match attempt().await {
                 Ok(response) => {
                     debug!("OpenCode API request succeeded after retry");
                     return Ok(response);
@@ -285,7 +357,7 @@ async fn parse_sessions_response(response: Response) -> Result<Vec<Session>, Ope
         .map_err(|err| OpenCodeApiError::ParseError(err.to_string()))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "http-api"))]
 mod tests {
     use super::*;
 
@@ -335,6 +407,25 @@ mod tests {
         assert_eq!(response.healthy, Some(true));
     }
 
+    #[test]
+    fn base_url_follows_shared_endpoint() {
+        use crate::opencode::discovery::{OpenCodeEndpoint, PortSource};
+
+        let endpoint = SharedEndpoint::new();
+        let client =
+            OpenCodeClient::new(&OpenCodeConfig::default()).with_endpoint(endpoint.clone());
+        assert_eq!(client.base_url(), "http://localhost:4096");
+
+        endpoint.set(OpenCodeEndpoint {
+            hostname: "localhost".to_string(),
+            port: 38211,
+            source: PortSource::ListeningSocket,
+            pid: Some(10),
+            started_at: None,
+        });
+        assert_eq!(client.base_url(), "http://localhost:38211");
+    }
+
     #[tokio::test]
     async fn list_sessions_parses_array_response() {
         async fn handler() -> Json<Vec<Session>> {
@@ -366,6 +457,7 @@ mod tests {
             assert_eq!(payload.prompt, "hello");
             Json(CreateSessionResponse {
                 id: "session-42".to_string(),
+                model: None,
             })
         }
 
@@ -382,6 +474,34 @@ mod tests {
         assert_eq!(response.id, "session-42");
     }
 
+    #[tokio::test]
+    async fn create_session_with_model_sends_model() {
+        #[derive(Deserialize)]
+        struct CreatePayload {
+            model: Option<String>,
+        }
+
+        async fn handler(Json(payload): Json<CreatePayload>) -> Json<serde_json::Value> {
+            assert_eq!(payload.model.as_deref(), Some("anthropic/claude-sonnet"));
+            Json(serde_json::json!({
+                "id": "session-43",
+                "modelID": "anthropic/claude-sonnet",
+            }))
+        }
+
+        let app = Router::new().route("/session", post(handler));
+        let (base_url, handle) = spawn_server(app).await;
+
+        let client = test_client(base_url);
+        let response = client
+            .create_session_with_model("hello", Some("anthropic/claude-sonnet"))
+            .await
+            .expect("create session");
+
+        handle.abort();
+        assert_eq!(response.model.as_deref(), Some("anthropic/claude-sonnet"));
+    }
+
     #[tokio::test]
     async fn send_message_handles_not_found() {
         async fn handler() -> StatusCode {
@@ -401,6 +521,76 @@ mod tests {
         assert!(matches!(err, OpenCodeApiError::NotFound(_)));
     }
 
+    #[tokio::test]
+    async fn cancellation_interrupts_unanswered_request() {
+        async fn handler() -> StatusCode {
+            std::future::pending::<()>().await;
+            StatusCode::OK
+        }
+
+        let app = Router::new().route("/global/health", get(handler));
+        let (base_url, handle) = spawn_server(app).await;
+
+        let cancel = CancellationToken::new();
+        let client = OpenCodeClient::with_base_url(base_url, DEFAULT_BACKOFF_DELAYS.to_vec())
+            .with_cancellation(cancel.clone());
+        let request = tokio::spawn(async move { client.health().await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let cancelled_at = std::time::Instant::now();
+        cancel.cancel();
+        let err = request.await.unwrap().expect_err("expected cancellation");
+
+        handle.abort();
+        assert!(matches!(err, OpenCodeApiError::Cancelled));
+        assert!(!err.is_retryable());
+        assert!(cancelled_at.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn cancellation_interrupts_retry_backoff() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts_handler = Arc::clone(&attempts);
+
+        async fn handler(attempts: Arc<AtomicUsize>) -> StatusCode {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+
+        let app = Router::new().route(
+            "/session",
+            get(move || handler(Arc::clone(&attempts_handler))),
+        );
+        let (base_url, handle) = spawn_server(app).await;
+
+        let cancel = CancellationToken::new();
+        let client = OpenCodeClient::with_base_url(base_url, DEFAULT_BACKOFF_DELAYS.to_vec())
+            .with_cancellation(cancel.clone());
+        let request = tokio::spawn(async move { client.list_sessions().await });
+
+        // The first attempt fails at once; the client is now in its 1s backoff.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let cancelled_at = std::time::Instant::now();
+        cancel.cancel();
+        let err = request.await.unwrap().expect_err("expected cancellation");
+
+        handle.abort();
+        assert!(matches!(err, OpenCodeApiError::Cancelled));
+        assert!(cancelled_at.elapsed() < Duration::from_millis(100));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_client_does_not_send() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        // Nothing listens here; a real attempt would fail with ConnectionFailed.
+        let client = test_client("http://127.0.0.1:9".to_string()).with_cancellation(cancel);
+
+        let err = client.health().await.expect_err("expected cancellation");
+        assert!(matches!(err, OpenCodeApiError::Cancelled));
+    }
+
     #[tokio::test]
     async fn retries_on_server_error() {
         let attempts = Arc::new(AtomicUsize::new(0));
@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use crate::monitor::process::{ProcessEnumerator, ProcessError, ProcessInfo};
+
+const PORT_FLAGS: [&str; 2] = ["--port", "-p"];
+const TCP_LISTEN_STATE: &str = "0A";
+
+/// How the OpenCode serve port was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortSource {
+    /// Port taken from `[opencode] serve_port`.
+    Configured,
+    /// Port parsed from the process command line (`--port 4096`).
+    CommandLine,
+    /// Port found by inspecting the process's listening sockets.
+    ListeningSocket,
+}
+
+impl PortSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortSource::Configured => "configured",
+            PortSource::CommandLine => "command_line",
+            PortSource::ListeningSocket => "listening_socket",
+        }
+    }
+}
+
+/// Resolved address of the OpenCode serve API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenCodeEndpoint {
+    pub hostname: String,
+    pub port: u16,
+    pub source: PortSource,
+    pub pid: Option<u32>,
+    /// Start time of the serving process, when known.
+    pub started_at: Option<SystemTime>,
+}
+
+impl OpenCodeEndpoint {
+    pub fn base_url(&self) -> String {
+        format!("http://{}:{}", self.hostname, self.port)
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DiscoveryError {
+    #[error("no listening TCP socket found for pid {pid}")]
+    NoListeningSocket { pid: u32 },
+
+    #[error("multiple listening ports for pid {pid}: {ports:?}")]
+    Ambiguous { pid: u32, ports: Vec<u16> },
+
+    #[error("failed to inspect sockets for pid {pid}: {message}")]
+    Inspection { pid: u32, message: String },
+}
+
+#[derive(Debug, Default)]
+struct EndpointSlot {
+    endpoint: Option<OpenCodeEndpoint>,
+    last_error: Option<String>,
+}
+
+/// Endpoint shared between the OpenCode monitor, API client, and status reporting.
+#[derive(Debug, Clone, Default)]
+pub struct SharedEndpoint {
+    inner: Arc<RwLock<EndpointSlot>>,
+}
+
+impl SharedEndpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<OpenCodeEndpoint> {
+        self.inner
+            .read()
+            .ok()
+            .and_then(|slot| slot.endpoint.clone())
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.inner
+            .read()
+            .ok()
+            .and_then(|slot| slot.last_error.clone())
+    }
+
+    pub fn set(&self, endpoint: OpenCodeEndpoint) {
+        if let Ok(mut slot) = self.inner.write() {
+            slot.endpoint = Some(endpoint);
+            slot.last_error = None;
+        }
+    }
+
+    pub fn set_error(&self, error: impl Into<String>) {
+        if let Ok(mut slot) = self.inner.write() {
+            slot.endpoint = None;
+            slot.last_error = Some(error.into());
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut slot) = self.inner.write() {
+            slot.endpoint = None;
+            slot.last_error = None;
+        }
+    }
+}
+
+/// Determine the serve port of an OpenCode process.
+///
+/// An explicit non-zero `--port` argument wins; otherwise the process's
+/// listening sockets are inspected through the enumerator.
+pub fn discover_port(
+    process: &ProcessInfo,
+    enumerator: &dyn ProcessEnumerator,
+) -> Result<(u16, PortSource), DiscoveryError> {
+    if let Some(port) = port_from_command_line(&process.command_line) {
+        return Ok((port, PortSource::CommandLine));
+    }
+
+    let mut ports =
+        enumerator
+            .listening_ports(process.pid)
+            .map_err(|err| DiscoveryError::Inspection {
+                pid: process.pid,
+                message: err.to_string(),
+            })?;
+    ports.sort_unstable();
+    ports.dedup();
+
+    match ports.as_slice() {
+        [] => Err(DiscoveryError::NoListeningSocket { pid: process.pid }),
+        [port] => Ok((*port, PortSource::ListeningSocket)),
+        _ => Err(DiscoveryError::Ambiguous {
+            pid: process.pid,
+            ports,
+        }),
+    }
+}
+
+/// Extract an explicit serve port from command line arguments.
+///
+/// Returns `None` when no port is given or the port is `0` (ephemeral).
+pub fn port_from_command_line(args: &[String]) -> Option<u16> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if PORT_FLAGS.contains(&arg.as_str()) {
+            iter.next().map(String::as_str)
+        } else {
+            arg.strip_prefix("--port=")
+        };
+
+        if let Some(port) = value.and_then(|value| value.trim().parse::<u16>().ok()) {
+            return (port != 0).then_some(port);
+        }
+    }
+    None
+}
+
+/// A listening socket parsed from `/proc/<pid>/net/tcp{,6}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListeningSocket {
+    pub port: u16,
+    pub inode: u64,
+}
+
+/// Parse listening sockets from a `/proc/net/tcp`-formatted table.
+pub fn parse_proc_net_tcp(contents: &str) -> Vec<ListeningSocket> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN_STATE {
+                return None;
+            }
+            let (_, port_hex) = fields[1].rsplit_once(':')?;
+            let port = u16::from_str_radix(port_hex, 16).ok()?;
+            let inode = fields[9].parse::<u64>().ok()?;
+            Some(ListeningSocket { port, inode })
+        })
+        .collect()
+}
+
+/// Parse the inode from an fd symlink target such as `socket:[12345]`.
+pub fn parse_socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse::<u64>()
+        .ok()
+}
+
+/// Keep only the ports whose socket inode is owned by the process.
+pub fn ports_for_inodes(sockets: &[ListeningSocket], inodes: &HashSet<u64>) -> Vec<u16> {
+    sockets
+        .iter()
+        .filter(|socket| inodes.contains(&socket.inode))
+        .map(|socket| socket.port)
+        .collect()
+}
+
+/// Parse listening ports from `lsof -Fn` output (lines like `n127.0.0.1:4096`).
+pub fn parse_lsof_output(output: &str) -> Vec<u16> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .filter_map(|name| name.rsplit_once(':'))
+        .filter_map(|(_, port)| port.trim().parse::<u16>().ok())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn listening_ports_for_pid(pid: u32) -> Result<Vec<u16>, ProcessError> {
+    use std::fs;
+    use std::path::Path;
+
+    let proc_dir = Path::new("/proc").join(pid.to_string());
+    let mut inodes = HashSet::new();
+    for entry in fs::read_dir(proc_dir.join("fd")).map_err(map_io_error)? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if let Some(inode) = fs::read_link(entry.path())
+            .ok()
+            .and_then(|target| parse_socket_inode(&target.to_string_lossy()))
+        {
+            inodes.insert(inode);
+        }
+    }
+
+    let mut sockets = Vec::new();
+    for table in ["tcp", "tcp6"] {
+        if let Ok(contents) = fs::read_to_string(proc_dir.join("net").join(table)) {
+            sockets.extend(parse_proc_net_tcp(&contents));
+        }
+    }
+
+    Ok(ports_for_inodes(&sockets, &inodes))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn listening_ports_for_pid(pid: u32) -> Result<Vec<u16>, ProcessError> {
+    let output = std::process::Command::new("lsof")
+        .args([
+            "-Pan",
+            "-p",
+            &pid.to_string(),
+            "-iTCP",
+            "-sTCP:LISTEN",
+            "-Fn",
+        ])
+        .output()?;
+    Ok(parse_lsof_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(target_os = "linux")]
+fn map_io_error(err: std::io::Error) -> ProcessError {
+    if err.kind() == std::io::ErrorKind::PermissionDenied {
+        ProcessError::PermissionDenied
+    } else {
+        ProcessError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_NET_TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1000 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1111 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1000 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 41299 1 0000000000000000 20 4 30 10 -1
+";
+
+    const PROC_NET_TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:A3F1 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 55001 1 0000000000000000 100 0 0 10 0
+";
+
+    struct PortsEnumerator(Result<Vec<u16>, ()>);
+
+    impl ProcessEnumerator for PortsEnumerator {
+        fn list_opencode_processes(&self) -> Result<Vec<ProcessInfo>, ProcessError> {
+            Ok(Vec::new())
+        }
+
+        fn listening_ports(&self, _pid: u32) -> Result<Vec<u16>, ProcessError> {
+            self.0
+                .clone()
+                .map_err(|_| ProcessError::EnumerationFailed("boom".to_string()))
+        }
+    }
+
+    fn process(args: &[&str]) -> ProcessInfo {
+        ProcessInfo {
+            pid: 77,
+            command_line: args.iter().map(|arg| arg.to_string()).collect(),
+            start_time: None,
+            working_dir: None,
+        }
+    }
+
+    #[test]
+    fn parses_listening_sockets_from_proc_net_tcp() {
+        let sockets = parse_proc_net_tcp(PROC_NET_TCP);
+        assert_eq!(
+            sockets,
+            vec![
+                ListeningSocket {
+                    port: 4096,
+                    inode: 41234
+                },
+                ListeningSocket {
+                    port: 22,
+                    inode: 1111
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_listening_sockets_from_proc_net_tcp6() {
+        let sockets = parse_proc_net_tcp(PROC_NET_TCP6);
+        assert_eq!(
+            sockets,
+            vec![ListeningSocket {
+                port: 41969,
+                inode: 55001
+            }]
+        );
+    }
+
+    #[test]
+    fn joins_sockets_with_process_inodes() {
+        let sockets = parse_proc_net_tcp(PROC_NET_TCP);
+        let inodes: HashSet<u64> = ["socket:[41234]", "pipe:[99]", "/dev/null"]
+            .iter()
+            .filter_map(|link| parse_socket_inode(link))
+            .collect();
+        assert_eq!(ports_for_inodes(&sockets, &inodes), vec![4096]);
+    }
+
+    #[test]
+    fn parses_socket_inode_links() {
+        assert_eq!(parse_socket_inode("socket:[12345]"), Some(12345));
+        assert_eq!(parse_socket_inode("anon_inode:[eventpoll]"), None);
+        assert_eq!(parse_socket_inode("/tmp/file"), None);
+    }
+
+    #[test]
+    fn extracts_port_from_command_line() {
+        let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            port_from_command_line(&args(&["opencode", "serve", "--port", "5000"])),
+            Some(5000)
+        );
+        assert_eq!(
+            port_from_command_line(&args(&["opencode", "serve", "--port=5001"])),
+            Some(5001)
+        );
+        assert_eq!(
+            port_from_command_line(&args(&["opencode", "serve", "-p", "5002"])),
+            Some(5002)
+        );
+        assert_eq!(
+            port_from_command_line(&args(&["opencode", "serve", "--port", "0"])),
+            None
+        );
+        assert_eq!(port_from_command_line(&args(&["opencode", "serve"])), None);
+    }
+
+    #[test]
+    fn parses_lsof_field_output() {
+        let output = "p77\nf12\nn127.0.0.1:4096\nf13\nn[::1]:4097\n";
+        assert_eq!(parse_lsof_output(output), vec![4096, 4097]);
+    }
+
+    #[test]
+    fn discover_prefers_command_line_port() {
+        let enumerator = PortsEnumerator(Ok(vec![6000]));
+        let result = discover_port(
+            &process(&["opencode", "serve", "--port", "5000"]),
+            &enumerator,
+        );
+        assert_eq!(result.unwrap(), (5000, PortSource::CommandLine));
+    }
+
+    #[test]
+    fn discover_falls_back_to_listening_socket() {
+        let enumerator = PortsEnumerator(Ok(vec![6000, 6000]));
+        let result = discover_port(&process(&["opencode", "serve", "--port", "0"]), &enumerator);
+        assert_eq!(result.unwrap(), (6000, PortSource::ListeningSocket));
+    }
+
+    #[test]
+    fn discover_reports_missing_and_ambiguous_ports() {
+        let missing = discover_port(
+            &process(&["opencode", "serve"]),
+            &PortsEnumerator(Ok(vec![])),
+        );
+        assert!(matches!(
+            missing,
+            Err(DiscoveryError::NoListeningSocket { pid: 77 })
+        ));
+
+        let ambiguous = discover_port(
+            &process(&["opencode", "serve"]),
+            &PortsEnumerator(Ok(vec![7000, 7001])),
+        );
+        assert!(matches!(ambiguous, Err(DiscoveryError::Ambiguous { .. })));
+
+        let failed = discover_port(&process(&["opencode", "serve"]), &PortsEnumerator(Err(())));
+        assert!(matches!(failed, Err(DiscoveryError::Inspection { .. })));
+    }
+}
@@ -1,11 +1,22 @@
+#[cfg(feature = "opencode-api")]
 mod client;
+pub mod discovery;
 mod process;
+pub mod version;
 
+#[cfg(feature = "opencode-api")]
 pub use client::{
     CreateSessionResponse, HealthResponse, OpenCodeApiError, OpenCodeClient, Session,
 };
 
+pub use discovery::{
+    DiscoveryError, OpenCodeEndpoint, PortSource, SharedEndpoint, discover_port,
+    port_from_command_line,
+};
+
 pub use process::{
     OpenCodeEvent, OpenCodeExitReason, OpenCodeMonitor, OpenCodeProcess, OpenCodeProcessReceiver,
     OpenCodeProcessSender,
 };
+
+pub use version::VersionCheck;
@@ -2,16 +2,18 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
+#[cfg(feature = "opencode-api")]
 use serde::Deserialize;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tokio::time::MissedTickBehavior;
 use tokio_util::sync::CancellationToken;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 use crate::config::schema::OpenCodeConfig;
 use crate::monitor::process::{
     DefaultProcessEnumerator, ProcessEnumerator, ProcessError, ProcessInfo, command_name_matches,
 };
+use crate::opencode::discovery::{OpenCodeEndpoint, PortSource, SharedEndpoint, discover_port};
 
 const EVENT_CHANNEL_CAPACITY: usize = 32;
 const OPENCODE_SERVE_ARG: &str = "serve";
@@ -53,6 +55,8 @@ pub enum OpenCodeEvent {
         process: OpenCodeProcess,
         exit_code: i32,
     },
+    /// First passing health check of a tracked process.
+    OpenCodeHealthy(OpenCodeProcess),
 }
 
 pub type OpenCodeProcessSender = mpsc::Sender<OpenCodeEvent>;
@@ -64,6 +68,9 @@ pub struct OpenCodeMonitor {
     health_hostname: String,
     health_port: u16,
     health_timeout: Duration,
+    auto_discover: bool,
+    endpoint: SharedEndpoint,
+    pause: Option<watch::Receiver<bool>>,
     enumerator: Arc<dyn ProcessEnumerator>,
 }
 
@@ -74,6 +81,9 @@ impl OpenCodeMonitor {
             health_hostname: config.serve_hostname.clone(),
             health_port: config.serve_port,
             health_timeout: Duration::from_millis(config.health_check_interval),
+            auto_discover: config.port_discovery_enabled(),
+            endpoint: SharedEndpoint::new(),
+            pause: None,
             enumerator: Arc::new(DefaultProcessEnumerator),
         }
     }
@@ -83,6 +93,23 @@ impl OpenCodeMonitor {
         self
     }
 
+    /// Publish the resolved endpoint into an existing shared handle.
+    pub fn with_endpoint(mut self, endpoint: SharedEndpoint) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Suspend process polling while the watched flag is `true`.
+    pub fn with_pause(mut self, pause: watch::Receiver<bool>) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    /// Shared handle to the endpoint the monitor is health-checking.
+    pub fn endpoint(&self) -> SharedEndpoint {
+        self.endpoint.clone()
+    }
+
     pub async fn run(
         self,
         cancel: CancellationToken,
@@ -95,6 +122,9 @@ impl OpenCodeMonitor {
             self.health_timeout,
             self.enumerator,
         );
+        state.auto_discover = self.auto_discover;
+        state.endpoint = self.endpoint;
+        state.pause = self.pause;
 
         tokio::spawn(async move {
             state.run_loop(tx, cancel).await;
@@ -108,8 +138,15 @@ struct OpenCodeMonitorState {
     health_check_interval: Duration,
     health_hostname: String,
     health_port: u16,
+    auto_discover: bool,
+    endpoint: SharedEndpoint,
+    discovery_warned: bool,
+    pause: Option<watch::Receiver<bool>>,
     enumerator: Arc<dyn ProcessEnumerator>,
     tracked_process: Option<ProcessInfo>,
+    /// `OpenCodeHealthy` was sent for the tracked process.
+    health_reported: bool,
+    #[cfg(feature = "opencode-api")]
     http_client: reqwest::Client,
 }
 
@@ -121,20 +158,85 @@ impl OpenCodeMonitorState {
         health_timeout: Duration,
         enumerator: Arc<dyn ProcessEnumerator>,
     ) -> Self {
+        #[cfg(feature = "opencode-api")]
         let http_client = reqwest::Client::builder()
             .timeout(health_timeout)
             .build()
             .unwrap_or_default();
+        #[cfg(not(feature = "opencode-api"))]
+        let _ = health_timeout;
         Self {
             health_check_interval,
             health_hostname,
             health_port,
+            auto_discover: false,
+            endpoint: SharedEndpoint::new(),
+            discovery_warned: false,
+            pause: None,
             enumerator,
             tracked_process: None,
+            health_reported: false,
+            #[cfg(feature = "opencode-api")]
             http_client,
         }
     }
 
+    /// Resolve the endpoint for a newly tracked process.
+    fn refresh_endpoint(&mut self, process: &ProcessInfo) {
+        if !self.auto_discover {
+            self.endpoint.set(OpenCodeEndpoint {
+                hostname: self.health_hostname.clone(),
+                port: self.health_port,
+                source: PortSource::Configured,
+                pid: Some(process.pid),
+                started_at: process.start_time,
+            });
+            return;
+        }
+
+        match discover_port(process, self.enumerator.as_ref()) {
+            Ok((port, source)) => {
+                info!(
+                    pid = process.pid,
+                    port,
+                    source = source.as_str(),
+                    "Discovered OpenCode serve port"
+                );
+                self.discovery_warned = false;
+                self.endpoint.set(OpenCodeEndpoint {
+                    hostname: self.health_hostname.clone(),
+                    port,
+                    source,
+                    pid: Some(process.pid),
+                    started_at: process.start_time,
+                });
+            }
+            Err(err) => {
+                if !self.discovery_warned {
+                    warn!(
+                        pid = process.pid,
+                        error = %err,
+                        "OpenCode port discovery failed; health checks skipped until the port is known"
+                    );
+                    self.discovery_warned = true;
+                }
+                self.endpoint.set_error(err.to_string());
+            }
+        }
+    }
+
+    fn track_process(&mut self, process: Option<ProcessInfo>) {
+        match process.as_ref() {
+            Some(process) => {
+                self.discovery_warned = false;
+                self.refresh_endpoint(process);
+            }
+            None => self.endpoint.clear(),
+        }
+        self.health_reported = false;
+        self.tracked_process = process;
+    }
+
     async fn run_loop(&mut self, tx: OpenCodeProcessSender, cancel: CancellationToken) {
         if let Err(err) = self.emit_existing_process(&tx).await {
             warn!(error = %err, "Failed to enumerate existing OpenCode processes");
@@ -144,6 +246,16 @@ impl OpenCodeMonitorState {
         interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         loop {
+            if self.is_paused() {
+                debug!("OpenCode monitor paused; polling suspended");
+                if !self.wait_for_resume(&cancel).await {
+                    info!("OpenCode monitor shutting down");
+                    break;
+                }
+                interval.reset();
+                continue;
+            }
+
             tokio::select! {
                 biased;
                 _ = cancel.cancelled() => {
@@ -159,13 +271,40 @@ impl OpenCodeMonitorState {
         }
     }
 
+    fn is_paused(&self) -> bool {
+        self.pause.as_ref().is_some_and(|pause| *pause.borrow())
+    }
+
+    /// Block without any timers until the daemon resumes.
+    ///
+    /// Returns `false` if cancellation was requested while waiting.
+    async fn wait_for_resume(&mut self, cancel: &CancellationToken) -> bool {
+        let Some(pause) = self.pause.as_mut() else {
+            return true;
+        };
+        let closed = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return false,
+            result = pause.wait_for(|paused| !*paused) => result.is_err(),
+        };
+        if closed {
+            // The daemon dropped its pause flag; keep polling unconditionally.
+            self.pause = None;
+        }
+        true
+    }
+
     async fn emit_existing_process(
         &mut self,
         tx: &OpenCodeProcessSender,
     ) -> Result<(), ProcessError> {
         if let Some(process) = self.find_opencode_process()? {
-            self.tracked_process = Some(process.clone());
-            info!(pid = process.pid, "Detected existing OpenCode process");
+            self.track_process(Some(process.clone()));
+            info!(
+                pid = process.pid,
+                started_at = ?process.start_time,
+                "Detected existing OpenCode process"
+            );
             let _ = tx
                 .send(OpenCodeEvent::OpenCodeStarted(process.into()))
                 .await;
@@ -187,8 +326,12 @@ impl OpenCodeMonitorState {
         match (self.tracked_process.as_ref(), current.as_ref()) {
             (Option::None, Some(process)) => {
                 let process = process.clone();
-                self.tracked_process = Some(process.clone());
-                info!(pid = process.pid, "OpenCode process started");
+                self.track_process(Some(process.clone()));
+                info!(
+                    pid = process.pid,
+                    started_at = ?process.start_time,
+                    "OpenCode process started"
+                );
                 if cancel.is_cancelled() {
                     return Ok(());
                 }
@@ -198,17 +341,34 @@ impl OpenCodeMonitorState {
             }
             (Some(previous), Option::None) => {
                 let previous = previous.clone();
-                self.tracked_process = None;
+                self.track_process(None);
                 self.emit_exit_event(tx, previous, cancel).await;
             }
-            (Some(previous), Some(process)) if previous.pid != process.pid => {
+            (Some(previous), Some(process)) if !previous.is_same_process(process) => {
                 let previous = previous.clone();
-                self.tracked_process = None;
-                self.emit_exit_event(tx, previous, cancel).await;
+                self.track_process(None);
+                if previous.pid == process.pid {
+                    // PID reused: the old process's exit status is no longer readable.
+                    info!(pid = previous.pid, "OpenCode process replaced (PID reused)");
+                    if !cancel.is_cancelled() {
+                        let _ = tx
+                            .send(OpenCodeEvent::OpenCodeStopped {
+                                process: previous.into(),
+                                reason: OpenCodeExitReason::Unknown,
+                            })
+                            .await;
+                    }
+                } else {
+                    self.emit_exit_event(tx, previous, cancel).await;
+                }
 
                 let process = process.clone();
-                self.tracked_process = Some(process.clone());
-                info!(pid = process.pid, "OpenCode process started");
+                self.track_process(Some(process.clone()));
+                info!(
+                    pid = process.pid,
+                    started_at = ?process.start_time,
+                    "OpenCode process started"
+                );
                 if cancel.is_cancelled() {
                     return Ok(());
                 }
@@ -217,8 +377,29 @@ impl OpenCodeMonitorState {
                     .await;
             }
             (Some(process), Some(_)) => {
-                if !check_health(&self.http_client, &self.health_hostname, self.health_port).await {
-                    warn!(pid = process.pid, "OpenCode health check failed");
+                let process = process.clone();
+                if self.endpoint.get().is_none() {
+                    // The serve socket may not be listening yet; retry discovery.
+                    self.refresh_endpoint(&process);
+                }
+                match self.endpoint.get() {
+                    Some(endpoint) => {
+                        if !self.is_healthy(&endpoint).await {
+                            warn!(
+                                pid = process.pid,
+                                port = endpoint.port,
+                                "OpenCode health check failed"
+                            );
+                        } else if !self.health_reported && !cancel.is_cancelled() {
+                            self.health_reported = true;
+                            let _ = tx
+                                .send(OpenCodeEvent::OpenCodeHealthy(process.into()))
+                                .await;
+                        }
+                    }
+                    Option::None => {
+                        debug!(pid = process.pid, "Skipping health check; port unknown");
+                    }
                 }
             }
             (Option::None, Option::None) => {}
@@ -227,6 +408,17 @@ impl OpenCodeMonitorState {
         Ok(())
     }
 
+    #[cfg(feature = "opencode-api")]
+    async fn is_healthy(&self, endpoint: &OpenCodeEndpoint) -> bool {
+        check_health(&self.http_client, &endpoint.hostname, endpoint.port).await
+    }
+
+    /// Health checks call the OpenCode API and need the `opencode-api` feature.
+    #[cfg(not(feature = "opencode-api"))]
+    async fn is_healthy(&self, _endpoint: &OpenCodeEndpoint) -> bool {
+        true
+    }
+
     async fn emit_exit_event(
         &self,
         tx: &OpenCodeProcessSender,
@@ -275,11 +467,13 @@ impl OpenCodeMonitorState {
     }
 }
 
+#[cfg(feature = "opencode-api")]
 #[derive(Debug, Deserialize)]
 struct HealthResponse {
     healthy: bool,
 }
 
+#[cfg(feature = "opencode-api")]
 async fn check_health(client: &reqwest::Client, hostname: &str, health_port: u16) -> bool {
     let url = format!("http://{}:{}/global/health", hostname, health_port);
 
@@ -369,14 +563,21 @@ mod tests {
         }
     }
 
+    fn opencode_process_started_at(pid: u32, secs: u64) -> ProcessInfo {
+        ProcessInfo {
+            start_time: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ..opencode_process(pid)
+        }
+    }
+
     fn config_with_poll(poll_ms: u64) -> OpenCodeConfig {
         OpenCodeConfig {
             enabled: true,
             serve_port: 4096,
+            auto_discover: false,
             serve_hostname: "localhost".to_string(),
-            auto_restart: true,
-            restart_delay_ms: 1000,
             health_check_interval: poll_ms,
+            ..OpenCodeConfig::default()
         }
     }
 
@@ -458,6 +659,136 @@ mod tests {
         cancel.cancel();
     }
 
+    #[tokio::test]
+    async fn reused_pid_emits_stop_then_start() {
+        let enumerator = Arc::new(
+            MockEnumerator::with_sequences(vec![
+                Ok(vec![opencode_process_started_at(12, 1_000)]),
+                Ok(vec![opencode_process_started_at(12, 1_000)]),
+                Ok(vec![opencode_process_started_at(12, 2_000)]),
+            ])
+            .with_exit_code(12, 0),
+        );
+        let monitor = OpenCodeMonitor::new(&config_with_poll(5)).with_enumerator(enumerator);
+        let cancel = CancellationToken::new();
+
+        let mut rx = monitor.run(cancel.clone()).await.expect("run monitor");
+        let _ = timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .expect("start event");
+
+        let event = timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .expect("stop event")
+            .expect("event value");
+        assert_eq!(
+            event,
+            OpenCodeEvent::OpenCodeStopped {
+                process: opencode_process_started_at(12, 1_000).into(),
+                reason: OpenCodeExitReason::Unknown,
+            }
+        );
+
+        let event = timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .expect("restart event")
+            .expect("event value");
+        assert_eq!(
+            event,
+            OpenCodeEvent::OpenCodeStarted(opencode_process_started_at(12, 2_000).into())
+        );
+
+        cancel.cancel();
+    }
+
+    fn repeated(process: ProcessInfo, times: usize) -> Vec<Result<Vec<ProcessInfo>, ProcessError>> {
+        (0..times).map(|_| Ok(vec![process.clone()])).collect()
+    }
+
+    #[tokio::test]
+    async fn publishes_discovered_endpoint_for_tracked_process() {
+        let process = ProcessInfo {
+            pid: 5,
+            command_line: vec![
+                "opencode".to_string(),
+                "serve".to_string(),
+                "--port".to_string(),
+                "5123".to_string(),
+            ],
+            start_time: None,
+            working_dir: None,
+        };
+        let enumerator = Arc::new(MockEnumerator::with_sequences(repeated(process, 100)));
+        let mut config = config_with_poll(5);
+        config.serve_port = 0;
+        let monitor = OpenCodeMonitor::new(&config).with_enumerator(enumerator);
+        let endpoint = monitor.endpoint();
+        let cancel = CancellationToken::new();
+
+        let mut rx = monitor.run(cancel.clone()).await.expect("run monitor");
+        let _ = timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .expect("start event");
+
+        let resolved = endpoint.get().expect("resolved endpoint");
+        assert_eq!(resolved.port, 5123);
+        assert_eq!(resolved.source, PortSource::CommandLine);
+        assert_eq!(resolved.pid, Some(5));
+
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn records_discovery_failure_instead_of_configured_port() {
+        let enumerator = Arc::new(MockEnumerator::with_sequences(repeated(
+            opencode_process(6),
+            100,
+        )));
+        let mut config = config_with_poll(5);
+        config.auto_discover = true;
+        let monitor = OpenCodeMonitor::new(&config).with_enumerator(enumerator);
+        let endpoint = monitor.endpoint();
+        let cancel = CancellationToken::new();
+
+        let mut rx = monitor.run(cancel.clone()).await.expect("run monitor");
+        let _ = timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .expect("start event");
+
+        assert!(endpoint.get().is_none());
+        assert!(endpoint.last_error().is_some());
+
+        cancel.cancel();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn suspends_polling_while_paused() {
+        let enumerator = Arc::new(MockEnumerator::with_sequences(vec![
+            Ok(vec![]),
+            Ok(vec![opencode_process(11)]),
+        ]));
+        let (pause_tx, pause_rx) = watch::channel(true);
+        let monitor = OpenCodeMonitor::new(&config_with_poll(5))
+            .with_enumerator(enumerator)
+            .with_pause(pause_rx);
+        let cancel = CancellationToken::new();
+
+        let mut rx = monitor.run(cancel.clone()).await.expect("run monitor");
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(rx.try_recv().is_err());
+
+        pause_tx.send(false).expect("resume monitor");
+        let event = timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .expect("event")
+            .expect("event value");
+        assert!(matches!(event, OpenCodeEvent::OpenCodeStarted(_)));
+
+        cancel.cancel();
+    }
+
+    #[cfg(all(feature = "opencode-api", feature = "http-api"))]
     #[tokio::test]
     async fn health_check_returns_true_on_healthy_response() {
         use axum::{Json, Router, routing::get};
@@ -495,6 +826,55 @@ mod tests {
         assert!(healthy);
     }
 
+    #[cfg(all(feature = "opencode-api", feature = "http-api"))]
+    #[tokio::test]
+    async fn reports_first_passing_health_check_once() {
+        use axum::{Json, Router, routing::get};
+        use std::future::IntoFuture;
+        use tokio::net::TcpListener;
+
+        let app = Router::new().route(
+            "/global/health",
+            get(|| async { Json(serde_json::json!({ "healthy": true })) }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+        let server = tokio::spawn(axum::serve(listener, app).into_future());
+
+        let enumerator = Arc::new(MockEnumerator::with_sequences(repeated(
+            opencode_process(21),
+            100,
+        )));
+        let mut config = config_with_poll(5);
+        config.serve_hostname = "127.0.0.1".to_string();
+        config.serve_port = port;
+        let monitor = OpenCodeMonitor::new(&config).with_enumerator(enumerator);
+        let cancel = CancellationToken::new();
+        let mut rx = monitor.run(cancel.clone()).await.expect("run monitor");
+
+        let started = timeout(Duration::from_millis(100), rx.recv()).await;
+        assert!(matches!(
+            started,
+            Ok(Some(OpenCodeEvent::OpenCodeStarted(_)))
+        ));
+        let healthy = timeout(Duration::from_secs(2), rx.recv()).await;
+        assert!(matches!(
+            healthy,
+            Ok(Some(OpenCodeEvent::OpenCodeHealthy(OpenCodeProcess {
+                pid: 21,
+                ..
+            })))
+        ));
+        assert!(
+            timeout(Duration::from_millis(100), rx.recv())
+                .await
+                .is_err()
+        );
+
+        cancel.cancel();
+        server.abort();
+    }
+
     #[test]
     fn is_opencode_serve_command_matches_simple() {
         let cmd = vec!["opencode".to_string(), "serve".to_string()];
@@ -0,0 +1,421 @@
+//! OpenCode version tracking.
+//!
+//! palingenesis depends on details opencode does not promise to keep: the
+//! flags of `opencode new` and `opencode continue`, and the serve API routes.
+//! The installed version is read at daemon start and whenever a new opencode
+//! process appears. When it differs from the last recorded version those
+//! assumptions are checked again, and the result is logged, persisted in the
+//! state file and announced as an `opencode_version_changed` event.
+
+use std::io;
+use std::process::{Output, Stdio};
+
+use chrono::Utc;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+#[cfg(feature = "opencode-api")]
+use crate::opencode::{OpenCodeApiError, OpenCodeClient};
+use crate::state::{OpenCodeVersionRecord, StateHandle};
+use crate::telemetry::Metrics;
+use crate::util::process;
+
+/// Job name of the version check.
+pub const VERSION_CHECK_JOB: &str = "opencode_version";
+
+/// Subcommands palingenesis runs and the flags it passes to each.
+const REQUIRED_FLAGS: &[(&str, &[&str])] = &[
+    ("new", &["--prompt", "--workdir"]),
+    ("continue", &["--session"]),
+];
+
+/// Reads the installed opencode version and re-validates it on change.
+#[derive(Debug, Clone)]
+pub struct VersionCheck {
+    program: String,
+    #[cfg(feature = "opencode-api")]
+    client: Option<OpenCodeClient>,
+    events: Option<EventBroadcaster>,
+}
+
+impl VersionCheck {
+    pub fn new() -> Self {
+        Self {
+            program: "opencode".to_string(),
+            #[cfg(feature = "opencode-api")]
+            client: None,
+            events: None,
+        }
+    }
+
+    /// Run this executable instead of `opencode`.
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Also check the serve API, and ask it for the version when the CLI
+    /// does not report one.
+    #[cfg(feature = "opencode-api")]
+    pub fn with_client(mut self, client: OpenCodeClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Installed version from `opencode --version`, falling back to the
+    /// serve API's health response.
+    pub async fn detect_version(&self) -> Option<String> {
+        if let Some(version) = self.cli_version().await {
+            return Some(version);
+        }
+        #[cfg(feature = "opencode-api")]
+        if let Some(client) = &self.client {
+            match client.health().await {
+                Ok(health) => return health.version.as_deref().and_then(parse_version),
+                Err(err) => debug!(error = %err, "OpenCode health check returned no version"),
+            }
+        }
+        None
+    }
+
+    async fn cli_version(&self) -> Option<String> {
+        let output = match self.cli(&["--version"]).await {
+            Ok(output) => output,
+            Err(err) => {
+                debug!(program = %self.program, error = %err, "Could not run opencode --version");
+                return None;
+            }
+        };
+        if !output.status.success() {
+            debug!(status = %output.status, "opencode --version failed");
+            return None;
+        }
+        parse_version(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Integration assumptions the installed opencode no longer satisfies.
+    pub async fn compatibility_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (subcommand, flags) in REQUIRED_FLAGS {
+            let output = match self.cli(&[subcommand, "--help"]).await {
+                Ok(output) => output,
+                Err(err) => {
+                    problems.push(format!(
+                        "`opencode {subcommand} --help` could not run: {err}"
+                    ));
+                    continue;
+                }
+            };
+            // Some CLIs print help on stderr or exit non-zero; only the text matters.
+            let help = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            for flag in *flags {
+                if !advertises_flag(&help, flag) {
+                    problems.push(format!("`opencode {subcommand}` no longer lists {flag}"));
+                }
+            }
+        }
+        #[cfg(feature = "opencode-api")]
+        if let Some(client) = &self.client {
+            problems.extend(api_problems(client).await);
+        }
+        problems
+    }
+
+    /// Compare the installed version with the recorded one, re-validating
+    /// and announcing it when it changed.
+    ///
+    /// Returns the new record when the version changed (or was recorded for
+    /// the first time); `None` when it is unchanged or cannot be read.
+    pub async fn run(&self, state: &StateHandle) -> Option<OpenCodeVersionRecord> {
+        let Some(version) = self.detect_version().await else {
+            debug!("OpenCode version unavailable");
+            return None;
+        };
+        if let Some(metrics) = Metrics::global() {
+            metrics.set_opencode_version(&version);
+        }
+
+        let previous = state
+            .snapshot()
+            .opencode_version
+            .map(|record| record.version);
+        if previous.as_deref() == Some(version.as_str()) {
+            debug!(version = %version, "OpenCode version unchanged");
+            return None;
+        }
+
+        let problems = self.compatibility_problems().await;
+        let record = OpenCodeVersionRecord {
+            version: version.clone(),
+            detected_at: Utc::now(),
+            problems: problems.clone(),
+        };
+        state.update(|file| file.opencode_version = Some(record.clone()));
+
+        match &previous {
+            Some(previous) => warn!(
+                previous = %previous,
+                current = %version,
+                "OpenCode version changed; re-validated integration"
+            ),
+            None => info!(version = %version, "Recorded OpenCode version"),
+        }
+        for problem in &problems {
+            warn!(version = %version, problem = %problem, "OpenCode compatibility check failed");
+        }
+
+        // The first recorded version is only worth a notification if it is broken.
+        if previous.is_some() || !problems.is_empty() {
+            self.announce(DomainEvent::OpenCodeVersionChanged {
+                timestamp: record.detected_at,
+                previous,
+                current: version,
+                problems,
+            });
+        }
+        Some(record)
+    }
+
+    fn announce(&self, event: DomainEvent) {
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
+    async fn cli(&self, args: &[&str]) -> io::Result<Output> {
+        let mut command = Command::new(&self.program);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        Ok(process::output(&mut command, None).await?.output)
+    }
+}
+
+impl Default for VersionCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// First version-like token of `output`, without a leading `v`.
+pub fn parse_version(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|token| token.trim_start_matches('v'))
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.'))
+        .map(str::to_string)
+}
+
+/// Whether `help` mentions `flag` as a whole word (`--session`, not `--session-id`).
+fn advertises_flag(help: &str, flag: &str) -> bool {
+    help.match_indices(flag).any(|(index, _)| {
+        help[index + flag.len()..]
+            .chars()
+            .next()
+            .is_none_or(|next| !(next.is_ascii_alphanumeric() || next == '-' || next == '_'))
+    })
+}
+
+/// Serve API routes that no longer answer as expected.
+///
+/// An unreachable server is not a compatibility problem; those checks are
+/// skipped until the next version change.
+#[cfg(feature = "opencode-api")]
+async fn api_problems(client: &OpenCodeClient) -> Vec<String> {
+    fn unreachable(err: &OpenCodeApiError) -> bool {
+        matches!(
+            err,
+            OpenCodeApiError::ConnectionFailed(_)
+                | OpenCodeApiError::Timeout
+                | OpenCodeApiError::Cancelled
+        )
+    }
+
+    match client.health().await {
+        Ok(_) => {}
+        Err(err) if unreachable(&err) => {
+            debug!(error = %err, "OpenCode serve API unreachable; skipping API checks");
+            return Vec::new();
+        }
+        Err(err) => return vec![format!("`GET /global/health` failed: {err}")],
+    }
+    match client.list_sessions().await {
+        Ok(_) => Vec::new(),
+        Err(err) if unreachable(&err) => Vec::new(),
+        Err(err) => vec![format!("`GET /session` failed: {err}")],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    use crate::state::{StateBackend, StateError, StateFile};
+
+    #[derive(Default)]
+    struct MemoryStore {
+        state: Mutex<StateFile>,
+    }
+
+    impl StateBackend for MemoryStore {
+        fn load(&self) -> StateFile {
+            self.state.lock().unwrap().clone()
+        }
+
+        fn save(&self, state: &StateFile) -> Result<(), StateError> {
+            *self.state.lock().unwrap() = state.clone();
+            Ok(())
+        }
+    }
+
+    /// Fake opencode printing `version`, with `new_help` as `new --help`.
+    fn fake_opencode(dir: &Path, version: &str, new_help: &str) -> String {
+        let program = dir.join("opencode");
+        let script = format!(
+            "#!/bin/sh\ncase \"$1\" in\n  --version) echo 'opencode {version}' ;;\n  new) printf '%b\\n' '{new_help}' ;;\n  continue) echo '--session <id>  Session to continue' ;;\nesac\n"
+        );
+        std::fs::write(&program, script).expect("write fake opencode");
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod fake opencode");
+        program.display().to_string()
+    }
+
+    const FULL_HELP: &str = "--prompt <text>  Initial prompt\\n--workdir <dir>  Working directory";
+
+    #[test]
+    fn parses_version_output() {
+        assert_eq!(parse_version("0.4.0\n").as_deref(), Some("0.4.0"));
+        assert_eq!(parse_version("opencode v0.4.0").as_deref(), Some("0.4.0"));
+        assert_eq!(
+            parse_version("opencode version 1.2.3-beta.1 (abc)").as_deref(),
+            Some("1.2.3-beta.1")
+        );
+        assert_eq!(parse_version("opencode"), None);
+    }
+
+    #[test]
+    fn flag_must_match_whole_word() {
+        assert!(advertises_flag("  --session <id>", "--session"));
+        assert!(advertises_flag("--session=<id>", "--session"));
+        assert!(!advertises_flag("--session-id <id>", "--session"));
+    }
+
+    #[tokio::test]
+    async fn compatible_cli_passes() {
+        let temp = tempfile::tempdir().unwrap();
+        let check =
+            VersionCheck::new().with_program(fake_opencode(temp.path(), "0.4.0", FULL_HELP));
+
+        assert_eq!(check.detect_version().await.as_deref(), Some("0.4.0"));
+        assert!(check.compatibility_problems().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn missing_flag_is_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let check = VersionCheck::new().with_program(fake_opencode(
+            temp.path(),
+            "0.5.0",
+            "--prompt <text>  Initial prompt\\n--cwd <dir>  Working directory",
+        ));
+
+        assert_eq!(
+            check.compatibility_problems().await,
+            vec!["`opencode new` no longer lists --workdir".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_program_has_no_version() {
+        let temp = tempfile::tempdir().unwrap();
+        let check =
+            VersionCheck::new().with_program(temp.path().join("missing").display().to_string());
+
+        assert_eq!(check.detect_version().await, None);
+        let state = StateHandle::new(MemoryStore::default());
+        assert!(check.run(&state).await.is_none());
+        assert!(state.snapshot().opencode_version.is_none());
+    }
+
+    #[tokio::test]
+    async fn version_change_is_recorded_and_announced() {
+        let temp = tempfile::tempdir().unwrap();
+        let events = EventBroadcaster::new(8);
+        let mut rx = events.subscribe();
+        let state = StateHandle::new(MemoryStore::default());
+
+        let program = fake_opencode(temp.path(), "0.4.0", FULL_HELP);
+        let check = VersionCheck::new()
+            .with_program(program)
+            .with_event_broadcaster(events.clone());
+
+        // First sighting: recorded quietly.
+        let record = check.run(&state).await.expect("first version recorded");
+        assert_eq!(record.version, "0.4.0");
+        assert!(rx.try_recv().is_err());
+
+        // Same version again: nothing to do.
+        assert!(check.run(&state).await.is_none());
+        assert!(rx.try_recv().is_err());
+
+        // Upgrade that dropped a flag.
+        let upgraded = temp.path().join("upgraded");
+        std::fs::create_dir(&upgraded).unwrap();
+        let check = check.with_program(fake_opencode(&upgraded, "0.5.0", "--prompt <text>"));
+        let record = check.run(&state).await.expect("version change recorded");
+        assert_eq!(record.version, "0.5.0");
+        assert_eq!(state.snapshot().opencode_version, Some(record.clone()));
+
+        match rx.try_recv().expect("version change event") {
+            NotificationEvent::OpenCodeVersionChanged {
+                previous,
+                current,
+                problems,
+                ..
+            } => {
+                assert_eq!(previous.as_deref(), Some("0.4.0"));
+                assert_eq!(current, "0.5.0");
+                assert_eq!(
+                    problems,
+                    vec!["`opencode new` no longer lists --workdir".to_string()]
+                );
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn broken_first_version_is_announced() {
+        let temp = tempfile::tempdir().unwrap();
+        let events = EventBroadcaster::new(8);
+        let mut rx = events.subscribe();
+        let state = StateHandle::new(MemoryStore::default());
+        let check = VersionCheck::new()
+            .with_program(fake_opencode(temp.path(), "0.5.0", "no flags here"))
+            .with_event_broadcaster(events.clone());
+
+        check.run(&state).await.expect("version recorded");
+        let event = rx.try_recv().expect("warning event");
+        assert_eq!(
+            event.severity(),
+            crate::notify::events::EventSeverity::Warning
+        );
+    }
+}
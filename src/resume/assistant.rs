@@ -0,0 +1,597 @@
+//! Assistant-specific resume behavior.
+//!
+//! An [`AssistantAdapter`] knows where an assistant keeps its sessions, which
+//! extra stop messages it prints, and which commands resume or replace one of
+//! its sessions. [`OpenCodeAdapter`] wraps the original `opencode` commands;
+//! [`ClaudeCodeAdapter`] drives `claude --resume`.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+use crate::monitor::classifier::ClassifierConfig;
+use crate::resume::postmortem::{FailureDetails, FailureStore};
+use crate::resume::{ResumeContext, ResumeError};
+use crate::util::process;
+
+/// Assistant name used in `monitoring.assistants` for opencode.
+pub const OPENCODE: &str = "opencode";
+/// Assistant name used in `monitoring.assistants` for Claude Code.
+pub const CLAUDE_CODE: &str = "claude-code";
+
+/// Message sent to a resumed Claude Code session.
+pub const DEFAULT_CLAUDE_CONTINUATION: &str = "Continue from where you left off.";
+
+/// Transcript lines scanned for Claude Code session metadata.
+const TRANSCRIPT_SCAN_LINES: usize = 50;
+
+/// Assistant-specific patterns added to the stop reason classifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassifyHints {
+    pub rate_limit_patterns: Vec<String>,
+    pub context_patterns: Vec<String>,
+}
+
+impl ClassifyHints {
+    /// Append these patterns to `config`'s extra patterns.
+    pub fn apply(&self, config: &mut ClassifierConfig) {
+        config
+            .extra_rate_limit_patterns
+            .extend(self.rate_limit_patterns.iter().cloned());
+        config
+            .extra_context_patterns
+            .extend(self.context_patterns.iter().cloned());
+    }
+}
+
+/// How palingenesis finds, classifies and restarts one assistant's sessions.
+pub trait AssistantAdapter: Send + Sync + fmt::Debug {
+    /// Name as written in `monitoring.assistants`.
+    fn name(&self) -> &'static str;
+
+    /// Directory holding this assistant's sessions, if it can be located.
+    fn detect_sessions_dir(&self) -> Option<PathBuf>;
+
+    /// Whether `session_path` belongs to this assistant.
+    fn owns_session(&self, session_path: &Path) -> bool {
+        self.detect_sessions_dir()
+            .is_some_and(|dir| session_path.starts_with(dir))
+    }
+
+    /// Extra classifier patterns for messages only this assistant prints.
+    fn classify_hints(&self) -> ClassifyHints {
+        ClassifyHints::default()
+    }
+
+    /// Command that continues the stopped session.
+    fn build_resume_command(&self, ctx: &ResumeContext) -> Result<Command, ResumeError>;
+
+    /// Command that starts a replacement session seeded with `prompt`.
+    fn build_new_session_command(
+        &self,
+        ctx: &ResumeContext,
+        prompt: &str,
+    ) -> Result<Command, ResumeError>;
+
+    /// Locate the session started by the new-session command from its stdout.
+    fn new_session_path(&self, ctx: &ResumeContext, stdout: &str) -> PathBuf;
+}
+
+/// Adapter for an assistant name from `monitoring.assistants`.
+pub fn adapter_for(name: &str) -> Option<Arc<dyn AssistantAdapter>> {
+    match name {
+        OPENCODE => Some(Arc::new(OpenCodeAdapter::new())),
+        CLAUDE_CODE | "claude" => Some(Arc::new(ClaudeCodeAdapter::new())),
+        _ => None,
+    }
+}
+
+/// Run an adapter command to completion, returning its stdout.
+pub async fn run_command(command: Command) -> Result<String, ResumeError> {
+    run_command_with_postmortem(command, None).await
+}
+
+/// Like [`run_command`], writing a postmortem bundle to `store` if the command fails.
+///
+/// A bundle that cannot be written is logged; the command's own error is
+/// returned either way.
+pub async fn run_command_with_postmortem(
+    mut command: Command,
+    postmortem: Option<(&FailureStore, &FailureDetails)>,
+) -> Result<String, ResumeError> {
+    let label = describe(&command);
+    debug!(command = %label, "Running assistant command");
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let finished = process::output(&mut command, None)
+        .await
+        .map_err(ResumeError::Io)?;
+    let output = finished.output;
+    if !output.status.success() {
+        let postmortem = postmortem.and_then(|(store, details)| {
+            let details = FailureDetails {
+                stopped: finished.stopped,
+                ..details.clone()
+            };
+            store
+                .record(&command, &output, &details)
+                .unwrap_or_else(|err| {
+                    warn!(command = %label, error = %err, "Failed to write command postmortem");
+                    None
+                })
+        });
+        return Err(ResumeError::CommandFailed {
+            command: label,
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            postmortem,
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Program and subcommand, without prompts or paths.
+fn describe(command: &Command) -> String {
+    let command = command.as_std();
+    let program = Path::new(command.get_program())
+        .file_name()
+        .unwrap_or(command.get_program())
+        .to_string_lossy()
+        .into_owned();
+    match command.get_args().next() {
+        Some(arg) => format!("{program} {}", arg.to_string_lossy()),
+        None => program,
+    }
+}
+
+fn session_dir(ctx: &ResumeContext) -> Result<&Path, ResumeError> {
+    if let Some(workdir) = &ctx.workdir {
+        return Ok(workdir);
+    }
+    ctx.session_path
+        .parent()
+        .ok_or_else(|| ResumeError::SessionNotFound {
+            path: ctx.session_path.clone(),
+        })
+}
+
+/// Resumes opencode sessions with `opencode continue` and `opencode new`.
+#[derive(Debug, Clone)]
+pub struct OpenCodeAdapter {
+    program: String,
+    resume_command: Option<Vec<String>>,
+    sessions_dir: Option<PathBuf>,
+}
+
+impl OpenCodeAdapter {
+    pub fn new() -> Self {
+        Self {
+            program: "opencode".to_string(),
+            resume_command: None,
+            sessions_dir: dirs::home_dir().map(|home| home.join(".opencode")),
+        }
+    }
+
+    /// Run this executable instead of `opencode`.
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Full resume command; the session path is appended as the last argument.
+    pub fn with_resume_command(mut self, command: Vec<String>) -> Self {
+        self.resume_command = Some(command);
+        self
+    }
+
+    pub fn with_sessions_dir(mut self, dir: PathBuf) -> Self {
+        self.sessions_dir = Some(dir);
+        self
+    }
+}
+
+impl Default for OpenCodeAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AssistantAdapter for OpenCodeAdapter {
+    fn name(&self) -> &'static str {
+        OPENCODE
+    }
+
+    fn detect_sessions_dir(&self) -> Option<PathBuf> {
+        self.sessions_dir.clone()
+    }
+
+    fn build_resume_command(&self, ctx: &ResumeContext) -> Result<Command, ResumeError> {
+        let default_command;
+        let resume_command = match &self.resume_command {
+            Some(command) => command,
+            None => {
+                default_command = vec![
+                    self.program.clone(),
+                    "continue".to_string(),
+                    "--session".to_string(),
+                ];
+                &default_command
+            }
+        };
+        let Some((program, args)) = resume_command.split_first() else {
+            return Err(ResumeError::Config(
+                "resume command cannot be empty".to_string(),
+            ));
+        };
+        let mut command = Command::new(program);
+        command.args(args).arg(&ctx.session_path);
+        Ok(command)
+    }
+
+    fn build_new_session_command(
+        &self,
+        ctx: &ResumeContext,
+        prompt: &str,
+    ) -> Result<Command, ResumeError> {
+        let mut command = Command::new(&self.program);
+        command
+            .arg("new")
+            .arg("--prompt")
+            .arg(prompt)
+            .arg("--workdir")
+            .arg(session_dir(ctx)?);
+        Ok(command)
+    }
+
+    fn new_session_path(&self, ctx: &ResumeContext, stdout: &str) -> PathBuf {
+        stdout
+            .lines()
+            .find(|line| line.contains("session:"))
+            .and_then(|line| line.split("session:").nth(1))
+            .map(|value| PathBuf::from(value.trim()))
+            .unwrap_or_else(|| {
+                ctx.session_path
+                    .parent()
+                    .unwrap_or(Path::new("."))
+                    .join("session.md")
+            })
+    }
+}
+
+/// Resumes Claude Code sessions with `claude --resume <id> --print`.
+#[derive(Debug, Clone)]
+pub struct ClaudeCodeAdapter {
+    program: String,
+    continuation: String,
+    sessions_dir: Option<PathBuf>,
+}
+
+impl ClaudeCodeAdapter {
+    pub fn new() -> Self {
+        Self {
+            program: "claude".to_string(),
+            continuation: DEFAULT_CLAUDE_CONTINUATION.to_string(),
+            sessions_dir: dirs::home_dir().map(|home| home.join(".claude").join("projects")),
+        }
+    }
+
+    /// Run this executable instead of `claude`.
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Message sent when resuming a session.
+    pub fn with_continuation(mut self, continuation: impl Into<String>) -> Self {
+        self.continuation = continuation.into();
+        self
+    }
+
+    pub fn with_sessions_dir(mut self, dir: PathBuf) -> Self {
+        self.sessions_dir = Some(dir);
+        self
+    }
+}
+
+impl Default for ClaudeCodeAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AssistantAdapter for ClaudeCodeAdapter {
+    fn name(&self) -> &'static str {
+        CLAUDE_CODE
+    }
+
+    fn detect_sessions_dir(&self) -> Option<PathBuf> {
+        self.sessions_dir.clone()
+    }
+
+    fn classify_hints(&self) -> ClassifyHints {
+        ClassifyHints {
+            rate_limit_patterns: vec![r"(?i)claude\s+ai\s+usage\s+limit\s+reached".to_string()],
+            context_patterns: vec![
+                r"(?i)prompt\s+is\s+too\s+long".to_string(),
+                r"(?i)context\s+left\s+until\s+auto-compact:\s*0%".to_string(),
+            ],
+        }
+    }
+
+    fn build_resume_command(&self, ctx: &ResumeContext) -> Result<Command, ResumeError> {
+        let transcript = ClaudeTranscript::read(&ctx.session_path);
+        let session_id = transcript.session_id.ok_or_else(|| {
+            ResumeError::Config(format!(
+                "no Claude Code session ID found for {}",
+                ctx.session_path.display()
+            ))
+        })?;
+        let mut command = Command::new(&self.program);
+        command
+            .arg("--resume")
+            .arg(session_id)
+            .arg("--print")
+            .arg(ctx.continuation.as_deref().unwrap_or(&self.continuation));
+        if let Some(cwd) = transcript.cwd.filter(|cwd| cwd.is_dir()) {
+            command.current_dir(cwd);
+        }
+        Ok(command)
+    }
+
+    fn build_new_session_command(
+        &self,
+        ctx: &ResumeContext,
+        prompt: &str,
+    ) -> Result<Command, ResumeError> {
+        let transcript = ClaudeTranscript::read(&ctx.session_path);
+        let mut command = Command::new(&self.program);
+        command
+            .arg("--print")
+            .arg(prompt)
+            .arg("--output-format")
+            .arg("json");
+        let cwd = ctx.workdir.clone().or(transcript.cwd);
+        if let Some(cwd) = cwd.filter(|cwd| cwd.is_dir()) {
+            command.current_dir(cwd);
+        }
+        Ok(command)
+    }
+
+    fn new_session_path(&self, ctx: &ResumeContext, stdout: &str) -> PathBuf {
+        let transcripts = ctx.session_path.parent().unwrap_or(Path::new("."));
+        let session_id = stdout.lines().rev().find_map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+            value
+                .get("session_id")
+                .or_else(|| value.get("sessionId"))
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+        });
+        match session_id {
+            Some(id) => transcripts.join(format!("{id}.jsonl")),
+            None => transcripts.join("session.jsonl"),
+        }
+    }
+}
+
+/// Session metadata recorded in a Claude Code JSONL transcript.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClaudeTranscript {
+    pub session_id: Option<String>,
+    /// Project directory the session ran in.
+    pub cwd: Option<PathBuf>,
+}
+
+impl ClaudeTranscript {
+    /// Read metadata from the first lines of `path`.
+    ///
+    /// The session ID falls back to the file stem, which Claude Code names
+    /// after the session.
+    pub fn read(path: &Path) -> Self {
+        let mut transcript = Self::default();
+        if let Ok(file) = File::open(path) {
+            for line in BufReader::new(file)
+                .lines()
+                .take(TRANSCRIPT_SCAN_LINES)
+                .map_while(Result::ok)
+            {
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+                    continue;
+                };
+                if transcript.session_id.is_none() {
+                    transcript.session_id = value
+                        .get("sessionId")
+                        .and_then(serde_json::Value::as_str)
+                        .map(str::to_string);
+                }
+                if transcript.cwd.is_none() {
+                    transcript.cwd = value
+                        .get("cwd")
+                        .and_then(serde_json::Value::as_str)
+                        .map(PathBuf::from);
+                }
+                if transcript.session_id.is_some() && transcript.cwd.is_some() {
+                    break;
+                }
+            }
+        }
+        if transcript.session_id.is_none()
+            && path.extension().and_then(|ext| ext.to_str()) == Some("jsonl")
+        {
+            transcript.session_id = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(str::to_string);
+        }
+        transcript
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::classifier::{StopReason, StopReasonClassifier};
+
+    fn ctx(path: &Path) -> ResumeContext {
+        ResumeContext::new(path.to_path_buf(), StopReason::Unknown(String::new()))
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn transcript_metadata_from_jsonl() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("file-name.jsonl");
+        std::fs::write(
+            &path,
+            "{\"type\":\"summary\"}\n\
+             {\"sessionId\":\"4f1c\",\"cwd\":\"/work/app\",\"type\":\"user\"}\n",
+        )
+        .unwrap();
+
+        let transcript = ClaudeTranscript::read(&path);
+        assert_eq!(transcript.session_id.as_deref(), Some("4f1c"));
+        assert_eq!(transcript.cwd, Some(PathBuf::from("/work/app")));
+    }
+
+    #[test]
+    fn transcript_id_falls_back_to_file_stem() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("9d2e-77.jsonl");
+        std::fs::write(&path, "not json\n").unwrap();
+
+        assert_eq!(
+            ClaudeTranscript::read(&path).session_id.as_deref(),
+            Some("9d2e-77")
+        );
+    }
+
+    #[test]
+    fn claude_resume_command_uses_session_id() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("abc.jsonl");
+        std::fs::write(&path, "{}\n").unwrap();
+
+        let command = ClaudeCodeAdapter::new()
+            .with_continuation("go on")
+            .build_resume_command(&ctx(&path))
+            .unwrap();
+        assert_eq!(args(&command), ["--resume", "abc", "--print", "go on"]);
+        assert_eq!(describe(&command), "claude --resume");
+    }
+
+    #[test]
+    fn claude_resume_command_prefers_context_continuation() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("abc.jsonl");
+        std::fs::write(&path, "{}\n").unwrap();
+
+        let command = ClaudeCodeAdapter::new()
+            .with_continuation("go on")
+            .build_resume_command(&ctx(&path).with_continuation("keep writing tests"))
+            .unwrap();
+        assert_eq!(
+            args(&command),
+            ["--resume", "abc", "--print", "keep writing tests"]
+        );
+    }
+
+    #[test]
+    fn new_session_commands_use_context_workdir() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.md");
+        let workdir = temp.path().join("worktree");
+        std::fs::create_dir(&workdir).unwrap();
+        let ctx = ctx(&path).with_workdir(workdir.clone());
+
+        let command = OpenCodeAdapter::new()
+            .build_new_session_command(&ctx, "go")
+            .unwrap();
+        assert_eq!(
+            args(&command),
+            [
+                "new",
+                "--prompt",
+                "go",
+                "--workdir",
+                &workdir.to_string_lossy()
+            ]
+        );
+
+        let command = ClaudeCodeAdapter::new()
+            .build_new_session_command(&ctx, "go")
+            .unwrap();
+        assert_eq!(command.as_std().get_current_dir(), Some(workdir.as_path()));
+    }
+
+    #[test]
+    fn claude_new_session_path_from_json_output() {
+        let path = PathBuf::from("/home/u/.claude/projects/-work-app/abc.jsonl");
+        let adapter = ClaudeCodeAdapter::new();
+        assert_eq!(
+            adapter.new_session_path(
+                &ctx(&path),
+                "{\"type\":\"result\",\"session_id\":\"def\"}\n"
+            ),
+            PathBuf::from("/home/u/.claude/projects/-work-app/def.jsonl")
+        );
+    }
+
+    #[test]
+    fn opencode_commands_match_original_behavior() {
+        let path = PathBuf::from("/sessions/session.md");
+        let adapter = OpenCodeAdapter::new();
+        let resume = adapter.build_resume_command(&ctx(&path)).unwrap();
+        assert_eq!(
+            args(&resume),
+            ["continue", "--session", "/sessions/session.md"]
+        );
+        let new = adapter
+            .build_new_session_command(&ctx(&path), "hi")
+            .unwrap();
+        assert_eq!(
+            args(&new),
+            ["new", "--prompt", "hi", "--workdir", "/sessions"]
+        );
+        assert_eq!(
+            adapter.new_session_path(&ctx(&path), "created\nsession: /sessions/next.md\n"),
+            PathBuf::from("/sessions/next.md")
+        );
+
+        let empty = OpenCodeAdapter::new().with_resume_command(Vec::new());
+        assert!(matches!(
+            empty.build_resume_command(&ctx(&path)),
+            Err(ResumeError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn claude_hints_extend_classifier() {
+        let mut config = ClassifierConfig::default();
+        ClaudeCodeAdapter::new().classify_hints().apply(&mut config);
+        let classifier = StopReasonClassifier::with_config(config).unwrap();
+
+        let result = classifier.classify_content("Claude AI usage limit reached|1760000000", None);
+        assert!(matches!(result.reason, StopReason::RateLimit(_)));
+    }
+
+    #[test]
+    fn adapters_by_name() {
+        assert_eq!(adapter_for("opencode").unwrap().name(), OPENCODE);
+        assert_eq!(adapter_for("claude-code").unwrap().name(), CLAUDE_CODE);
+        assert!(adapter_for("sisyphus").is_none());
+    }
+}
@@ -4,6 +4,8 @@ use rand::Rng;
 use thiserror::Error;
 use tracing::debug;
 
+use crate::config::schema::ResumeConfig;
+
 /// Configuration for exponential backoff.
 #[derive(Debug, Clone)]
 pub struct BackoffConfig {
@@ -32,6 +34,17 @@ impl Default for BackoffConfig {
 }
 
 impl BackoffConfig {
+    /// Backoff settings from `[resume]`.
+    pub fn from_resume_config(config: &ResumeConfig) -> Self {
+        Self {
+            base_delay: Duration::from_secs(config.base_delay_secs),
+            max_delay: Duration::from_secs(config.max_delay_secs),
+            max_retries: config.max_retries,
+            jitter_enabled: config.jitter,
+            ..Self::default()
+        }
+    }
+
     /// Validate configuration values.
     pub fn validate(&self) -> Result<(), BackoffError> {
         if self.base_delay.is_zero() {
@@ -1,12 +1,33 @@
-use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tokio::fs;
 use tokio::io::AsyncReadExt;
 use tracing::{debug, info, warn};
 
+use crate::config::paths::{UnsafePathError, safe_path};
+use crate::config::resolved::ResolvedPaths;
+use crate::config::schema::{MonitoringConfig, ResumeConfig};
+
+/// Backup filename used before templates existed; still the default.
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{stem}-backup-{timestamp}.{ext}";
+
+/// Format of the `{date}` placeholder.
+const TEMPLATE_DATE_FORMAT: &str = "%Y-%m-%d";
+
+const PLACEHOLDERS: [&str; 5] = ["stem", "timestamp", "date", "workspace", "ext"];
+
+/// Content hashes of existing backups, kept in the backup directory so older
+/// backups are not rehashed on every resume.
+const BACKUP_INDEX_FILE: &str = ".backup-index.json";
+
 /// Configuration for session backup.
 #[derive(Debug, Clone)]
 pub struct BackupConfig {
@@ -16,6 +37,20 @@ pub struct BackupConfig {
     pub timestamp_format: String,
     /// Verify backup after creation.
     pub verify_backup: bool,
+    /// Allow the session file to be a symlink (target must stay under the session root).
+    pub follow_symlinks: bool,
+    /// Directory session files must stay under; `None` uses the session's own directory.
+    pub session_root: Option<PathBuf>,
+    /// Directory backups are written to; `None` keeps them next to the session file.
+    pub backup_dir: Option<PathBuf>,
+    /// Backup path relative to the backup directory.
+    ///
+    /// Supports `{stem}`, `{timestamp}`, `{date}`, `{workspace}` and `{ext}`;
+    /// `/` separates subdirectories.
+    pub filename_template: String,
+    /// Skip the copy when the session matches its newest backup, returning
+    /// that backup instead.
+    pub dedupe: bool,
 }
 
 impl Default for BackupConfig {
@@ -24,7 +59,145 @@ impl Default for BackupConfig {
             max_backups: 10,
             timestamp_format: "%Y%m%d-%H%M%S".to_string(),
             verify_backup: true,
+            follow_symlinks: false,
+            session_root: None,
+            backup_dir: None,
+            filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
+            dedupe: true,
+        }
+    }
+}
+
+impl BackupConfig {
+    /// Backup settings from `[resume]`; a relative `backup_dir` is under the state dir.
+    ///
+    /// Session files are checked against `monitoring.session_dir`, following
+    /// symlinks only when `monitoring.follow_symlinks` allows it.
+    pub fn from_resume_config(config: &ResumeConfig, monitoring: &MonitoringConfig) -> Self {
+        Self {
+            max_backups: config.backup_count as usize,
+            follow_symlinks: monitoring.follow_symlinks,
+            session_root: Some(monitoring.session_dir.clone()),
+            backup_dir: config
+                .backup_dir
+                .as_deref()
+                .map(|dir| resolve_backup_dir(dir, ResolvedPaths::current().state_dir())),
+            filename_template: config.backup_filename_template.clone(),
+            dedupe: config.backup_dedupe,
+            ..Self::default()
+        }
+    }
+
+    fn uses_legacy_layout(&self) -> bool {
+        self.backup_dir.is_none() && self.filename_template == DEFAULT_FILENAME_TEMPLATE
+    }
+}
+
+/// Resolve a configured backup directory against the state directory.
+pub fn resolve_backup_dir(dir: &Path, state_dir: &Path) -> PathBuf {
+    if dir.is_absolute() {
+        dir.to_path_buf()
+    } else {
+        state_dir.join(dir)
+    }
+}
+
+/// Check that `template` renders a unique, relative path for each session backup.
+pub fn validate_filename_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| "unclosed placeholder".to_string())?;
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!("unknown placeholder {{{name}}}"));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    if !template.contains("{timestamp}") {
+        return Err("must contain {timestamp} so backups do not overwrite each other".to_string());
+    }
+    if !template.contains("{stem}") {
+        return Err("must contain {stem} so sessions do not share backups".to_string());
+    }
+    let path = Path::new(template);
+    if path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err("must be a relative path without `.` or `..`".to_string());
+    }
+    Ok(())
+}
+
+/// Values substituted into a backup filename template for one session.
+struct TemplateVars<'a> {
+    stem: &'a str,
+    workspace: &'a str,
+    ext: Option<&'a str>,
+}
+
+impl<'a> TemplateVars<'a> {
+    fn for_session(session_path: &'a Path) -> Self {
+        Self {
+            stem: session_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("session"),
+            workspace: session_path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .and_then(|s| s.to_str())
+                .unwrap_or("workspace"),
+            ext: session_path.extension().and_then(|s| s.to_str()),
+        }
+    }
+
+    /// `template` with `{ext}` dropped (with its dot) when the session has no extension.
+    fn template(&self, template: &str) -> String {
+        match self.ext {
+            Some(_) => template.to_string(),
+            None => template.replace(".{ext}", "").replace("{ext}", ""),
+        }
+    }
+
+    fn render(&self, template: &str, timestamp: &str, date: &str) -> String {
+        self.template(template)
+            .replace("{stem}", self.stem)
+            .replace("{workspace}", self.workspace)
+            .replace("{ext}", self.ext.unwrap_or_default())
+            .replace("{date}", date)
+            .replace("{timestamp}", timestamp)
+    }
+
+    /// Regex matching this session's backups (relative to the backup dir,
+    /// `/`-separated), capturing the first `{timestamp}` as `ts`.
+    fn matcher(&self, template: &str) -> Option<Regex> {
+        let template = self.template(template);
+        let mut pattern = String::from("^");
+        let mut captured = false;
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            pattern.push_str(&regex::escape(&rest[..start]));
+            let end = start + rest[start..].find('}')?;
+            match &rest[start + 1..end] {
+                "stem" => pattern.push_str(&regex::escape(self.stem)),
+                "workspace" => pattern.push_str(&regex::escape(self.workspace)),
+                "ext" => pattern.push_str(&regex::escape(self.ext.unwrap_or_default())),
+                "date" => pattern.push_str(r"\d{4}-\d{2}-\d{2}"),
+                "timestamp" if !captured => {
+                    pattern.push_str("(?P<ts>[^/]+?)");
+                    captured = true;
+                }
+                "timestamp" => pattern.push_str("[^/]+?"),
+                _ => return None,
+            }
+            rest = &rest[end + 1..];
         }
+        pattern.push_str(&regex::escape(rest));
+        pattern.push('$');
+        Regex::new(&pattern).ok()
     }
 }
 
@@ -45,6 +218,12 @@ pub enum BackupError {
 
     #[error("Failed to parse backup timestamp from filename: {filename}")]
     InvalidBackupFilename { filename: String },
+
+    #[error("Refusing to back up unsafe path: {0}")]
+    UnsafePath(#[from] UnsafePathError),
+
+    #[error("Session file exists again, not overwriting: {path}")]
+    SessionExists { path: PathBuf },
 }
 
 #[async_trait]
@@ -73,6 +252,9 @@ impl SessionBackup {
     }
 
     /// Create a backup of the session file.
+    ///
+    /// With `dedupe`, a session identical to its newest backup is not copied
+    /// again and the existing backup's path is returned.
     pub async fn create_backup(&self, session_path: &Path) -> Result<PathBuf, BackupError> {
         if !session_path.exists() {
             return Err(BackupError::SourceNotFound {
@@ -80,7 +262,29 @@ impl SessionBackup {
             });
         }
 
+        let root = match &self.config.session_root {
+            Some(root) => root.as_path(),
+            None => session_dir(session_path),
+        };
+        safe_path(session_path, root, self.config.follow_symlinks)?;
+
+        if self.config.dedupe {
+            if let Some(existing) = self.identical_backup(session_path).await {
+                info!(
+                    backup = %existing.display(),
+                    "backup skipped (identical to {})",
+                    existing.display()
+                );
+                return Ok(existing);
+            }
+        }
+
         let backup_path = self.generate_backup_path(session_path);
+        if let Some(parent) = backup_path.parent() {
+            if !parent.exists() {
+                create_private_dir(parent).await?;
+            }
+        }
 
         debug!(
             source = %session_path.display(),
@@ -101,27 +305,77 @@ impl SessionBackup {
             warn!(error = %err, "Failed to prune old backups");
         }
 
+        if self.config.dedupe {
+            let mut index = BackupIndex::load(&self.backup_root(session_path)).await;
+            if let Err(err) = index.hash(&backup_path).await {
+                warn!(error = %err, "Failed to hash new backup");
+            }
+            index.save().await;
+        }
+
         Ok(backup_path)
     }
 
-    fn generate_backup_path(&self, session_path: &Path) -> PathBuf {
-        let timestamp = Local::now()
-            .format(&self.config.timestamp_format)
-            .to_string();
-        let stem = session_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("session");
-        let extension = session_path.extension().and_then(|s| s.to_str());
-        let backup_filename = match extension {
-            Some(ext) => format!("{}-backup-{}.{}", stem, timestamp, ext),
-            None => format!("{}-backup-{}", stem, timestamp),
+    /// Newest backup of `session_path` with the same content, if any.
+    ///
+    /// Its hash comes from the index when the file still matches the indexed
+    /// size and mtime, and is recomputed otherwise (missing or stale index).
+    async fn identical_backup(&self, session_path: &Path) -> Option<PathBuf> {
+        let (newest, _) = match self.list_backups(session_path).await {
+            Ok(backups) => backups.into_iter().last()?,
+            Err(err) => {
+                warn!(error = %err, "Failed to list backups for deduplication");
+                return None;
+            }
+        };
+        let source_hash = match hash_file(session_path).await {
+            Ok(hash) => hash,
+            Err(err) => {
+                warn!(error = %err, "Failed to hash session for deduplication");
+                return None;
+            }
+        };
+
+        let mut index = BackupIndex::load(&self.backup_root(session_path)).await;
+        let newest_hash = match index.hash(&newest).await {
+            Ok(hash) => hash,
+            Err(err) => {
+                warn!(backup = %newest.display(), error = %err, "Failed to hash backup");
+                return None;
+            }
         };
+        index.save().await;
+
+        if newest_hash != source_hash {
+            return None;
+        }
+        if self.config.verify_backup {
+            if let Err(err) = self.verify_backup(session_path, &newest).await {
+                warn!(backup = %newest.display(), error = %err, "Identical backup failed verification");
+                return None;
+            }
+        }
+        Some(newest)
+    }
+
+    fn generate_backup_path(&self, session_path: &Path) -> PathBuf {
+        let now = Local::now();
+        let timestamp = now.format(&self.config.timestamp_format).to_string();
+        let date = now.format(TEMPLATE_DATE_FORMAT).to_string();
+        let relative = TemplateVars::for_session(session_path).render(
+            &self.config.filename_template,
+            &timestamp,
+            &date,
+        );
+        self.backup_root(session_path).join(relative)
+    }
 
-        session_path
-            .parent()
-            .map(|p| p.join(&backup_filename))
-            .unwrap_or_else(|| PathBuf::from(&backup_filename))
+    /// Directory the template is rendered under for `session_path`.
+    fn backup_root(&self, session_path: &Path) -> PathBuf {
+        match &self.config.backup_dir {
+            Some(dir) => dir.clone(),
+            None => session_dir(session_path).to_path_buf(),
+        }
     }
 
     async fn copy_metadata(&self, source: &Path, backup: &Path) {
@@ -166,6 +420,164 @@ impl SessionBackup {
         &self,
         session_path: &Path,
     ) -> Result<usize, BackupError> {
+        let mut backups = self.list_backups(session_path).await?;
+
+        let mut removed = 0;
+        while backups.len() > self.config.max_backups {
+            if let Some((path, _)) = backups.first() {
+                debug!(path = %path.display(), "Pruning old backup");
+                fs::remove_file(path).await?;
+                backups.remove(0);
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            info!(count = removed, "Pruned old backups");
+        }
+
+        Ok(removed)
+    }
+
+    /// Copy the newest usable backup back to `session_path`.
+    ///
+    /// Backups are tried newest first; one that cannot be copied intact is
+    /// skipped. Returns the backup used, or `None` when there is none.
+    pub async fn restore_latest(
+        &self,
+        session_path: &Path,
+    ) -> Result<Option<PathBuf>, BackupError> {
+        let backups = self.list_backups(session_path).await?;
+        for (backup, _) in backups.into_iter().rev() {
+            match self.restore_from(&backup, session_path).await {
+                Ok(()) => {
+                    info!(
+                        backup = %backup.display(),
+                        session = %session_path.display(),
+                        "Session restored from backup"
+                    );
+                    return Ok(Some(backup));
+                }
+                Err(BackupError::SessionExists { .. }) => {
+                    info!(session = %session_path.display(), "Session file reappeared; restore skipped");
+                    return Ok(None);
+                }
+                Err(err) => {
+                    warn!(backup = %backup.display(), error = %err, "Skipping unusable backup");
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn restore_from(&self, backup: &Path, session_path: &Path) -> Result<(), BackupError> {
+        let staging = session_path.with_extension("restore.tmp");
+        fs::copy(backup, &staging).await?;
+        if let Err(err) = self.verify_backup(backup, &staging).await {
+            let _ = fs::remove_file(&staging).await;
+            return Err(err);
+        }
+        // Link instead of renaming so a session file that came back while the
+        // backup was copied is never overwritten.
+        let exists = || BackupError::SessionExists {
+            path: session_path.to_path_buf(),
+        };
+        match fs::hard_link(&staging, session_path).await {
+            Ok(()) => {
+                let _ = fs::remove_file(&staging).await;
+                Ok(())
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                let _ = fs::remove_file(&staging).await;
+                Err(exists())
+            }
+            Err(_) if session_path.exists() => {
+                let _ = fs::remove_file(&staging).await;
+                Err(exists())
+            }
+            // No hard links on this filesystem; the check above is the best we can do.
+            Err(_) => {
+                fs::rename(&staging, session_path).await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Backups of `session_path`, oldest first.
+    ///
+    /// Looks for the configured template under the backup directory and, when
+    /// the layout was customised, for legacy `<stem>-backup-<timestamp>` files
+    /// next to the session so older backups are still listed and pruned.
+    pub async fn list_backups(
+        &self,
+        session_path: &Path,
+    ) -> Result<Vec<(PathBuf, DateTime<Local>)>, BackupError> {
+        let mut backups = self.list_templated_backups(session_path).await?;
+        if !self.config.uses_legacy_layout() {
+            backups.extend(self.list_legacy_backups(session_path).await?);
+        }
+
+        backups.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        backups.dedup_by(|a, b| a.0 == b.0);
+        Ok(backups)
+    }
+
+    async fn list_templated_backups(
+        &self,
+        session_path: &Path,
+    ) -> Result<Vec<(PathBuf, DateTime<Local>)>, BackupError> {
+        let root = self.backup_root(session_path);
+        let vars = TemplateVars::for_session(session_path);
+        let Some(matcher) = vars.matcher(&self.config.filename_template) else {
+            warn!(
+                template = %self.config.filename_template,
+                "Invalid backup filename template; no backups listed"
+            );
+            return Ok(Vec::new());
+        };
+        let max_depth = vars
+            .template(&self.config.filename_template)
+            .matches('/')
+            .count();
+
+        let mut backups = Vec::new();
+        let mut pending = vec![(root.clone(), 0usize)];
+        while let Some((dir, depth)) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    if depth < max_depth {
+                        pending.push((path, depth + 1));
+                    }
+                    continue;
+                }
+                let Some(relative) = relative_key(&path, &root) else {
+                    continue;
+                };
+                let Some(captures) = matcher.captures(&relative) else {
+                    continue;
+                };
+                match self.parse_timestamp(&captures["ts"]) {
+                    Some(timestamp) => backups.push((path, timestamp)),
+                    None => warn!(
+                        path = %path.display(),
+                        "Skipping backup with invalid timestamp"
+                    ),
+                }
+            }
+        }
+        Ok(backups)
+    }
+
+    async fn list_legacy_backups(
+        &self,
+        session_path: &Path,
+    ) -> Result<Vec<(PathBuf, DateTime<Local>)>, BackupError> {
         let dir = session_path.parent().ok_or_else(|| {
             BackupError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -192,24 +604,12 @@ impl SessionBackup {
                 }
             }
         }
+        Ok(backups)
+    }
 
-        backups.sort_by(|a, b| a.1.cmp(&b.1));
-
-        let mut removed = 0;
-        while backups.len() > self.config.max_backups {
-            if let Some((path, _)) = backups.first() {
-                debug!(path = %path.display(), "Pruning old backup");
-                fs::remove_file(path).await?;
-                backups.remove(0);
-                removed += 1;
-            }
-        }
-
-        if removed > 0 {
-            info!(count = removed, "Pruned old backups");
-        }
-
-        Ok(removed)
+    fn parse_timestamp(&self, text: &str) -> Option<DateTime<Local>> {
+        let naive = NaiveDateTime::parse_from_str(text, &self.config.timestamp_format).ok()?;
+        Local.from_local_datetime(&naive).single()
     }
 
     fn extract_timestamp(&self, filename: &str) -> Result<DateTime<Local>, BackupError> {
@@ -228,19 +628,137 @@ impl SessionBackup {
                     filename: filename.to_string(),
                 })?;
 
-        let naive = NaiveDateTime::parse_from_str(timestamp_part, &self.config.timestamp_format)
-            .map_err(|_| BackupError::InvalidBackupFilename {
+        self.parse_timestamp(timestamp_part)
+            .ok_or_else(|| BackupError::InvalidBackupFilename {
                 filename: filename.to_string(),
-            })?;
+            })
+    }
+}
 
-        Local.from_local_datetime(&naive).single().ok_or_else(|| {
-            BackupError::InvalidBackupFilename {
-                filename: filename.to_string(),
+/// `.backup-index.json`: SHA-256 of each backup, with the size and mtime it
+/// was computed for so an entry for a changed file is recomputed.
+#[derive(Debug, Default)]
+struct BackupIndex {
+    path: PathBuf,
+    entries: BTreeMap<String, IndexEntry>,
+    changed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexEntry {
+    sha256: String,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl BackupIndex {
+    /// The index in `root`; empty when missing or unreadable.
+    async fn load(root: &Path) -> Self {
+        let path = root.join(BACKUP_INDEX_FILE);
+        let entries = match fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+                warn!(path = %path.display(), error = %err, "Ignoring unreadable backup index");
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self {
+            path,
+            entries,
+            changed: false,
+        }
+    }
+
+    /// Content hash of `backup`, from the index when still current.
+    async fn hash(&mut self, backup: &Path) -> std::io::Result<String> {
+        let metadata = fs::metadata(backup).await?;
+        let key = backup.to_string_lossy().into_owned();
+        let modified = metadata.modified().ok();
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.len == metadata.len() && entry.modified == modified {
+                return Ok(entry.sha256.clone());
+            }
+        }
+        let sha256 = hash_file(backup).await?;
+        self.entries.insert(
+            key,
+            IndexEntry {
+                sha256: sha256.clone(),
+                len: metadata.len(),
+                modified,
+            },
+        );
+        self.changed = true;
+        Ok(sha256)
+    }
+
+    /// Write the index, dropping entries for backups that no longer exist.
+    async fn save(mut self) {
+        let before = self.entries.len();
+        let mut kept = BTreeMap::new();
+        for (key, entry) in std::mem::take(&mut self.entries) {
+            if fs::try_exists(&key).await.unwrap_or(false) {
+                kept.insert(key, entry);
             }
-        })
+        }
+        if !self.changed && kept.len() == before {
+            return;
+        }
+        let Ok(json) = serde_json::to_vec_pretty(&kept) else {
+            return;
+        };
+        let staging = self.path.with_extension("json.tmp");
+        let written = async {
+            fs::write(&staging, json).await?;
+            fs::rename(&staging, &self.path).await
+        }
+        .await;
+        if let Err(err) = written {
+            warn!(path = %self.path.display(), error = %err, "Failed to write backup index");
+        }
     }
 }
 
+async fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn session_dir(session_path: &Path) -> &Path {
+    session_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// `path` relative to `root` with `/` separators, as templates are written.
+fn relative_key(path: &Path, root: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Option<Vec<&str>> = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect();
+    Some(parts?.join("/"))
+}
+
+/// Create `dir` and any missing parents, readable only by the owner.
+async fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(dir).await
+}
+
 #[async_trait]
 impl BackupHandler for SessionBackup {
     async fn backup(&self, session_path: &Path) -> Result<PathBuf, BackupError> {
@@ -317,6 +835,70 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn restore_latest_copies_newest_backup() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let session = temp.path().join("session.md");
+        fs::write(
+            temp.path().join("session-backup-20240101-000000.md"),
+            b"old",
+        )
+        .await
+        .expect("backup write");
+        let newest = temp.path().join("session-backup-20240102-000000.md");
+        fs::write(&newest, b"newest").await.expect("backup write");
+
+        let restored = SessionBackup::default()
+            .restore_latest(&session)
+            .await
+            .expect("restore");
+
+        assert_eq!(restored, Some(newest));
+        assert_eq!(fs::read(&session).await.expect("read session"), b"newest");
+        assert!(!session.with_extension("restore.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn restore_latest_keeps_session_that_reappeared() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let session = temp.path().join("session.md");
+        fs::write(&session, b"recreated")
+            .await
+            .expect("session write");
+        fs::write(
+            temp.path().join("session-backup-20240102-000000.md"),
+            b"backup",
+        )
+        .await
+        .expect("backup write");
+
+        let restored = SessionBackup::default()
+            .restore_latest(&session)
+            .await
+            .expect("restore");
+
+        assert_eq!(restored, None);
+        assert_eq!(
+            fs::read(&session).await.expect("read session"),
+            b"recreated"
+        );
+        assert!(!session.with_extension("restore.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn restore_latest_without_backups_returns_none() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let session = temp.path().join("session.md");
+
+        let restored = SessionBackup::default()
+            .restore_latest(&session)
+            .await
+            .expect("restore");
+
+        assert!(restored.is_none());
+        assert!(!session.exists());
+    }
+
     #[test]
     fn extract_timestamp_parses_format() {
         let backupper = SessionBackup::default();
@@ -329,6 +911,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn template_renders_placeholders() {
+        let session = Path::new("/work/myproj/session.md");
+        let vars = TemplateVars::for_session(session);
+        assert_eq!(
+            vars.render(
+                "{workspace}/{date}/{stem}-{timestamp}.{ext}",
+                "20260205-143022",
+                "2026-02-05"
+            ),
+            "myproj/2026-02-05/session-20260205-143022.md"
+        );
+
+        let bare = TemplateVars::for_session(Path::new("/work/myproj/session"));
+        assert_eq!(
+            bare.render(DEFAULT_FILENAME_TEMPLATE, "20260205-143022", "2026-02-05"),
+            "session-backup-20260205-143022"
+        );
+    }
+
+    #[test]
+    fn template_validation_requires_unique_relative_paths() {
+        assert!(validate_filename_template(DEFAULT_FILENAME_TEMPLATE).is_ok());
+        assert!(validate_filename_template("{workspace}/{date}/{stem}-{timestamp}.{ext}").is_ok());
+        assert!(validate_filename_template("{stem}-{date}.{ext}").is_err());
+        assert!(validate_filename_template("backup-{timestamp}").is_err());
+        assert!(validate_filename_template("{stem}-{timestamp}-{host}").is_err());
+        assert!(validate_filename_template("../{stem}-{timestamp}").is_err());
+        assert!(validate_filename_template("/tmp/{stem}-{timestamp}").is_err());
+    }
+
+    #[tokio::test]
+    async fn prune_in_backup_dir_keeps_other_sessions() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let workspace = temp.path().join("proj");
+        std::fs::create_dir_all(&workspace).expect("workspace");
+        let session = workspace.join("session.md");
+        fs::write(&session, b"session")
+            .await
+            .expect("session write");
+        let backups = temp.path().join("backups");
+        let day = backups.join("proj").join("2024-01-01");
+        std::fs::create_dir_all(&day).expect("backup dir");
+        for ts in ["20240101-000000", "20240101-000001", "20240101-000002"] {
+            fs::write(day.join(format!("session-{ts}.md")), b"b")
+                .await
+                .expect("backup write");
+            fs::write(day.join(format!("session-b-{ts}.md")), b"b")
+                .await
+                .expect("other write");
+        }
+
+        let backupper = SessionBackup::with_config(BackupConfig {
+            max_backups: 1,
+            backup_dir: Some(backups),
+            filename_template: "{workspace}/{date}/{stem}-{timestamp}.{ext}".to_string(),
+            ..BackupConfig::default()
+        });
+        let removed = backupper
+            .prune_old_backups(&session)
+            .await
+            .expect("prune backups");
+
+        assert_eq!(removed, 2);
+        assert!(day.join("session-20240101-000002.md").exists());
+        assert!(!day.join("session-20240101-000000.md").exists());
+        for ts in ["20240101-000000", "20240101-000001", "20240101-000002"] {
+            assert!(day.join(format!("session-b-{ts}.md")).exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_backups_are_listed_and_pruned_with_templated_ones() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let session = temp.path().join("session.md");
+        fs::write(&session, b"session")
+            .await
+            .expect("session write");
+        let legacy = temp.path().join("session-backup-20240101-000000.md");
+        fs::write(&legacy, b"old").await.expect("legacy write");
+        let backups = temp.path().join("backups");
+        std::fs::create_dir_all(&backups).expect("backup dir");
+        let templated = backups.join("session-20240102-000000.md");
+        fs::write(&templated, b"new")
+            .await
+            .expect("templated write");
+
+        let backupper = SessionBackup::with_config(BackupConfig {
+            max_backups: 1,
+            backup_dir: Some(backups),
+            filename_template: "{stem}-{timestamp}.{ext}".to_string(),
+            ..BackupConfig::default()
+        });
+        let listed: Vec<PathBuf> = backupper
+            .list_backups(&session)
+            .await
+            .expect("list backups")
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(listed, vec![legacy.clone(), templated.clone()]);
+
+        assert_eq!(
+            backupper.prune_old_backups(&session).await.expect("prune"),
+            1
+        );
+        assert!(!legacy.exists());
+        assert!(templated.exists());
+    }
+
+    #[test]
+    fn relative_backup_dir_resolves_under_state_dir() {
+        let state = Path::new("/var/lib/palingenesis");
+        assert_eq!(
+            resolve_backup_dir(Path::new("backups"), state),
+            state.join("backups")
+        );
+        assert_eq!(
+            resolve_backup_dir(Path::new("/srv/backups"), state),
+            PathBuf::from("/srv/backups")
+        );
+    }
+
     #[cfg(unix)]
     #[tokio::test]
     async fn backup_fails_when_directory_unwritable() {
@@ -343,6 +1048,12 @@ mod tests {
 
         let permissions = Permissions::from_mode(0o500);
         std::fs::set_permissions(temp.path(), permissions).expect("set permissions");
+        if std::fs::write(temp.path().join("probe"), b"").is_ok() {
+            // Permission bits do not apply to root.
+            std::fs::set_permissions(temp.path(), Permissions::from_mode(0o700))
+                .expect("reset permissions");
+            return;
+        }
 
         let backupper = SessionBackup::default();
         let result = backupper.create_backup(&session).await;
@@ -352,4 +1063,67 @@ mod tests {
         let reset = Permissions::from_mode(0o700);
         std::fs::set_permissions(temp.path(), reset).expect("reset permissions");
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn backup_rejects_symlinked_session() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let outside = tempfile::tempdir().expect("outside");
+        let secret = outside.path().join("passwd");
+        fs::write(&secret, b"root:x:0:0")
+            .await
+            .expect("secret write");
+        let session = temp.path().join("session.md");
+        std::os::unix::fs::symlink(&secret, &session).expect("symlink");
+
+        let backupper = SessionBackup::default();
+        let result = backupper.create_backup(&session).await;
+
+        assert!(matches!(result, Err(BackupError::UnsafePath(_))));
+        let mut entries = fs::read_dir(temp.path()).await.expect("read dir");
+        let mut count = 0;
+        while entries.next_entry().await.expect("entry").is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 1, "no backup should be written");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn backup_follows_symlink_within_session_root() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let shared = root.path().join("shared");
+        let project = root.path().join("project");
+        std::fs::create_dir_all(&shared).expect("shared dir");
+        std::fs::create_dir_all(&project).expect("project dir");
+        fs::write(shared.join("session.md"), b"session")
+            .await
+            .expect("session write");
+        let session = project.join("session.md");
+        std::os::unix::fs::symlink(shared.join("session.md"), &session).expect("symlink");
+
+        let monitoring = MonitoringConfig {
+            session_dir: root.path().to_path_buf(),
+            follow_symlinks: true,
+            ..MonitoringConfig::default()
+        };
+        let config = BackupConfig::from_resume_config(&ResumeConfig::default(), &monitoring);
+        assert_eq!(config.session_root.as_deref(), Some(root.path()));
+        assert!(config.follow_symlinks);
+
+        let backup = SessionBackup::with_config(config.clone())
+            .create_backup(&session)
+            .await
+            .expect("backup");
+        assert!(backup.exists());
+
+        let per_file = SessionBackup::with_config(BackupConfig {
+            session_root: None,
+            ..config
+        });
+        assert!(matches!(
+            per_file.create_backup(&session).await,
+            Err(BackupError::UnsafePath(_))
+        ));
+    }
 }
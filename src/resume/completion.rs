@@ -0,0 +1,449 @@
+//! Wrap-up for sessions that reached an explicit completion marker.
+//!
+//! When the classifier reports [`StopReason::Completed`](crate::monitor::classifier::StopReason)
+//! the daemon runs [`CompletionWorkflow`] once per session: it sends a
+//! `session_completed` notification with final stats, runs the optional
+//! `hooks.on_complete` command, archives the session file when
+//! `resume.archive_on_complete` is set, and clears it from the state file.
+//! A `SessionCompleted` audit entry marks the session as wrapped up, with a
+//! hash of the file: a completed file that is touched again does not trigger
+//! a second run, but one that was reopened and completed with new content does.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::config::schema::{Config, MetricsConfig};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::time_saved::calculate_time_saved;
+use crate::state::{AuditEntry, AuditError, AuditEventType, AuditLogger, StateHandle};
+use crate::util::process::{self, Termination};
+
+/// `SessionCompleted` metadata key holding the wrapped-up content's hash.
+const CONTENT_HASH_KEY: &str = "content_sha256";
+
+#[derive(Debug, Error)]
+pub enum CompletionError {
+    #[error("Audit log error: {0}")]
+    Audit(#[from] AuditError),
+
+    #[error("Failed to archive {path}: {source}")]
+    Archive {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Final stats for a completed session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionSummary {
+    pub session_path: PathBuf,
+    pub duration_secs: Option<u64>,
+    pub resumes: u64,
+    pub time_saved_seconds: f64,
+    /// Where the session was copied, when archiving is enabled.
+    pub archived_to: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionOutcome {
+    WrappedUp(CompletionSummary),
+    /// The audit log already records this session as completed, and its
+    /// content has not changed since.
+    AlreadyWrappedUp,
+}
+
+/// Runs the one-time wrap-up for a completed session.
+pub struct CompletionWorkflow {
+    audit: AuditLogger,
+    metrics: MetricsConfig,
+    state: Option<StateHandle>,
+    events: Option<EventBroadcaster>,
+    on_complete: Option<String>,
+    hook_timeout: Duration,
+    archive_dir: Option<PathBuf>,
+}
+
+impl CompletionWorkflow {
+    pub fn new(audit: AuditLogger) -> Self {
+        Self {
+            audit,
+            metrics: MetricsConfig::default(),
+            state: None,
+            events: None,
+            on_complete: None,
+            hook_timeout: Duration::from_secs(60),
+            archive_dir: None,
+        }
+    }
+
+    /// Build from config; the archive lives in `state_dir/archive`.
+    pub fn from_config(config: &Config, state_dir: &Path) -> Self {
+        let mut workflow = Self::new(AuditLogger::new(state_dir))
+            .with_metrics(config.metrics.clone())
+            .with_hook_timeout(Duration::from_secs(config.hooks.timeout_secs));
+        if let Some(command) = &config.hooks.on_complete {
+            workflow = workflow.with_on_complete(command.clone());
+        }
+        if config.resume.archive_on_complete {
+            workflow = workflow.with_archive_dir(state_dir.join("archive"));
+        }
+        workflow
+    }
+
+    pub fn with_metrics(mut self, metrics: MetricsConfig) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Clear the completed session from this state handle.
+    pub fn with_state_handle(mut self, state: StateHandle) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Publish `session_completed` notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_on_complete(mut self, command: impl Into<String>) -> Self {
+        self.on_complete = Some(command.into());
+        self
+    }
+
+    pub fn with_hook_timeout(mut self, timeout: Duration) -> Self {
+        self.hook_timeout = timeout;
+        self
+    }
+
+    pub fn with_archive_dir(mut self, dir: PathBuf) -> Self {
+        self.archive_dir = Some(dir);
+        self
+    }
+
+    /// Wrap up `session_path` unless that already happened for its current
+    /// content. Stats of a repeated wrap-up cover the time since the last one.
+    ///
+    /// Hook failures are logged and do not fail the workflow; an archive
+    /// failure does, before the session is marked completed, so the next
+    /// completion signal retries it.
+    pub async fn run(&self, session_path: &Path) -> Result<CompletionOutcome, CompletionError> {
+        let mut history = self
+            .audit
+            .query()
+            .for_session(session_path.to_path_buf())
+            .execute()?;
+        let content_sha256 = content_hash(session_path).await;
+        let mut previous_wrap_up = None;
+        if let Some(last) = history
+            .iter()
+            .filter(|entry| entry.event_type == AuditEventType::SessionCompleted)
+            .max_by_key(|entry| entry.timestamp)
+        {
+            let wrapped_hash = last.metadata.get(CONTENT_HASH_KEY).and_then(Value::as_str);
+            let advanced = matches!(
+                (wrapped_hash, content_sha256.as_deref()),
+                (Some(wrapped), Some(current)) if wrapped != current
+            );
+            if !advanced {
+                debug!(path = %session_path.display(), "Session already wrapped up");
+                return Ok(CompletionOutcome::AlreadyWrappedUp);
+            }
+            let since = last.timestamp;
+            history.retain(|entry| entry.timestamp > since);
+            previous_wrap_up = Some(since);
+            debug!(path = %session_path.display(), "Reopened session completed again");
+        }
+
+        let now = Utc::now();
+        let resumes = count_resumes(&history);
+        let per_resume = calculate_time_saved(Duration::ZERO, &self.metrics).total_saved_seconds;
+        let summary = CompletionSummary {
+            session_path: session_path.to_path_buf(),
+            duration_secs: previous_wrap_up
+                .or_else(|| started_at(&history, session_path))
+                .and_then(|start| (now - start).to_std().ok())
+                .map(|duration| duration.as_secs()),
+            resumes,
+            time_saved_seconds: resumes as f64 * per_resume,
+            archived_to: self.archive(session_path)?,
+        };
+
+        self.clear_current_session(session_path);
+        let mut metadata = summary_metadata(&summary);
+        if let Some(hash) = content_sha256 {
+            metadata.insert(CONTENT_HASH_KEY.to_string(), Value::from(hash));
+        }
+        self.audit
+            .log_session_completed_with(session_path, metadata)?;
+        info!(
+            path = %session_path.display(),
+            resumes = summary.resumes,
+            "Session completed"
+        );
+
+        if let Some(events) = &self.events {
+            events.publish(DomainEvent::SessionCompleted {
+                timestamp: now,
+                session_path: session_path.to_path_buf(),
+                duration_secs: summary.duration_secs,
+                resumes: summary.resumes,
+                time_saved_seconds: summary.time_saved_seconds,
+            });
+        }
+        self.run_hook(&summary).await;
+
+        Ok(CompletionOutcome::WrappedUp(summary))
+    }
+
+    fn archive(&self, session_path: &Path) -> Result<Option<PathBuf>, CompletionError> {
+        let Some(dir) = &self.archive_dir else {
+            return Ok(None);
+        };
+        let file_name = session_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "session".to_string());
+        let target = dir.join(format!(
+            "{}-{}",
+            Utc::now().format("%Y%m%dT%H%M%SZ"),
+            file_name
+        ));
+        let result =
+            std::fs::create_dir_all(dir).and_then(|_| std::fs::copy(session_path, &target));
+        match result {
+            Ok(_) => Ok(Some(target)),
+            Err(source) => Err(CompletionError::Archive {
+                path: session_path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    fn clear_current_session(&self, session_path: &Path) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        state.update(|state| {
+            if state
+                .current_session
+                .as_ref()
+                .is_some_and(|current| current.path == session_path)
+            {
+                state.current_session = None;
+            }
+        });
+    }
+
+    async fn run_hook(&self, summary: &CompletionSummary) {
+        let Some(command) = &self.on_complete else {
+            return;
+        };
+        let mut hook = Command::new("sh");
+        hook.arg("-c")
+            .arg(command)
+            .env("PALINGENESIS_SESSION_PATH", &summary.session_path)
+            .env("PALINGENESIS_RESUMES", summary.resumes.to_string())
+            .stdin(Stdio::null());
+        match process::output(&mut hook, Some(self.hook_timeout)).await {
+            Ok(finished) if finished.timed_out => warn!(
+                command = %command,
+                timeout_secs = self.hook_timeout.as_secs(),
+                process_tree = finished.stopped.map(Termination::as_str),
+                "on_complete hook timed out"
+            ),
+            Ok(finished) if finished.output.status.success() => {
+                debug!(command = %command, "on_complete hook finished");
+            }
+            Ok(finished) => {
+                warn!(command = %command, status = %finished.output.status, "on_complete hook failed")
+            }
+            Err(err) => {
+                warn!(command = %command, error = %err, "Failed to run on_complete hook")
+            }
+        }
+    }
+}
+
+/// SHA-256 of the session file; `None` when it cannot be read.
+async fn content_hash(session_path: &Path) -> Option<String> {
+    let content = tokio::fs::read(session_path).await.ok()?;
+    Some(hex::encode(Sha256::digest(&content)))
+}
+
+/// Earliest audit entry for the session, else the file's creation time.
+fn started_at(history: &[AuditEntry], session_path: &Path) -> Option<DateTime<Utc>> {
+    history
+        .iter()
+        .map(|entry| entry.timestamp)
+        .min()
+        .or_else(|| {
+            let metadata = std::fs::metadata(session_path).ok()?;
+            let created = metadata.created().or_else(|_| metadata.modified()).ok()?;
+            Some(DateTime::<Utc>::from(created))
+        })
+}
+
+fn count_resumes(history: &[AuditEntry]) -> u64 {
+    history
+        .iter()
+        .filter(|entry| entry.event_type == AuditEventType::ResumeCompleted)
+        .count() as u64
+}
+
+fn summary_metadata(summary: &CompletionSummary) -> HashMap<String, Value> {
+    let mut metadata = HashMap::from([
+        ("resumes".to_string(), Value::from(summary.resumes)),
+        (
+            "time_saved_seconds".to_string(),
+            Value::from(summary.time_saved_seconds),
+        ),
+    ]);
+    if let Some(duration) = summary.duration_secs {
+        metadata.insert("duration_secs".to_string(), Value::from(duration));
+    }
+    if let Some(archived) = &summary.archived_to {
+        metadata.insert(
+            "archived_to".to_string(),
+            Value::from(archived.display().to_string()),
+        );
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use crate::state::{CurrentSession, StateStore};
+    use tempfile::tempdir;
+
+    fn session_file(dir: &Path) -> PathBuf {
+        let path = dir.join("session.md");
+        std::fs::write(&path, "# Plan\n\nDone.\n\nWORKFLOW COMPLETE\n").unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn wraps_up_once_with_resume_stats() {
+        let dir = tempdir().unwrap();
+        let session = session_file(dir.path());
+        let audit = AuditLogger::new(dir.path());
+        audit.log_resume_started(&session, "same_session").unwrap();
+        audit
+            .log_resume_completed(&session, "same_session")
+            .unwrap();
+        audit
+            .log_resume_completed(&session, "same_session")
+            .unwrap();
+
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let workflow = CompletionWorkflow::new(AuditLogger::new(dir.path()))
+            .with_event_broadcaster(events)
+            .with_archive_dir(dir.path().join("archive"));
+
+        let CompletionOutcome::WrappedUp(summary) = workflow.run(&session).await.unwrap() else {
+            panic!("expected wrap-up");
+        };
+        assert_eq!(summary.resumes, 2);
+        assert_eq!(summary.time_saved_seconds, 600.0);
+        assert!(summary.duration_secs.is_some());
+        assert!(summary.archived_to.as_ref().unwrap().exists());
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            NotificationEvent::SessionCompleted { resumes: 2, .. }
+        ));
+
+        assert_eq!(
+            workflow.run(&session).await.unwrap(),
+            CompletionOutcome::AlreadyWrappedUp
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn reopened_session_wraps_up_again_only_when_content_advanced() {
+        let dir = tempdir().unwrap();
+        let session = session_file(dir.path());
+        let marker = dir.path().join("hook.count");
+        let workflow = CompletionWorkflow::new(AuditLogger::new(dir.path()))
+            .with_on_complete(format!("echo run >> '{}'", marker.display()));
+
+        assert!(matches!(
+            workflow.run(&session).await.unwrap(),
+            CompletionOutcome::WrappedUp(_)
+        ));
+        std::fs::write(&session, std::fs::read(&session).unwrap()).unwrap();
+        assert_eq!(
+            workflow.run(&session).await.unwrap(),
+            CompletionOutcome::AlreadyWrappedUp
+        );
+
+        std::fs::write(&session, "# Plan\n\nFollow-up done.\n\nWORKFLOW COMPLETE\n").unwrap();
+        assert!(matches!(
+            workflow.run(&session).await.unwrap(),
+            CompletionOutcome::WrappedUp(_)
+        ));
+        assert_eq!(
+            workflow.run(&session).await.unwrap(),
+            CompletionOutcome::AlreadyWrappedUp
+        );
+        assert_eq!(std::fs::read_to_string(&marker).unwrap(), "run\nrun\n");
+    }
+
+    #[tokio::test]
+    async fn clears_current_session_and_runs_hook() {
+        let dir = tempdir().unwrap();
+        let session = session_file(dir.path());
+        let marker = dir.path().join("hook.out");
+        let state = StateHandle::new(StateStore::with_path(dir.path().join("state.json")));
+        state.update(|state| {
+            state.current_session = Some(CurrentSession {
+                path: session.clone(),
+                ..CurrentSession::default()
+            });
+        });
+
+        let workflow = CompletionWorkflow::new(AuditLogger::new(dir.path()))
+            .with_state_handle(state.clone())
+            .with_on_complete(format!(
+                "printf '%s' \"$PALINGENESIS_SESSION_PATH\" > '{}'",
+                marker.display()
+            ));
+        workflow.run(&session).await.unwrap();
+
+        assert!(state.snapshot().current_session.is_none());
+        assert_eq!(
+            std::fs::read_to_string(&marker).unwrap(),
+            session.display().to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn hook_timeout_does_not_fail_workflow() {
+        let dir = tempdir().unwrap();
+        let session = session_file(dir.path());
+        let workflow = CompletionWorkflow::new(AuditLogger::new(dir.path()))
+            .with_on_complete("sleep 5")
+            .with_hook_timeout(Duration::from_millis(50));
+
+        assert!(matches!(
+            workflow.run(&session).await.unwrap(),
+            CompletionOutcome::WrappedUp(_)
+        ));
+    }
+}
@@ -1,10 +1,12 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 
 use crate::monitor::classifier::StopReason;
 use crate::monitor::session::Session;
+use crate::resume::git_context::GitContext;
 
 /// Context provided to resume strategies.
 #[derive(Debug, Clone)]
@@ -21,6 +23,17 @@ pub struct ResumeContext {
     pub attempt_number: u32,
     /// When the stop was detected.
     pub timestamp: DateTime<Utc>,
+    /// Workspace git state when the stop was classified.
+    pub git_at_stop: Option<GitContext>,
+    /// Directory a new session should run in instead of the session's own.
+    pub workdir: Option<PathBuf>,
+    /// Classifier evidence for `stop_reason`, kept in failure postmortems.
+    /// Shared between clones of the context.
+    pub evidence: Arc<[String]>,
+    /// Message sent when resuming the same session instead of the adapter's default.
+    pub continuation: Option<String>,
+    /// Transcript tail saved when the stop was classified (`incidents/` in the state dir).
+    pub incident: Option<PathBuf>,
 }
 
 impl ResumeContext {
@@ -32,6 +45,11 @@ impl ResumeContext {
             session_metadata: None,
             attempt_number: 1,
             timestamp: Utc::now(),
+            git_at_stop: None,
+            workdir: None,
+            evidence: Arc::default(),
+            continuation: None,
+            incident: None,
         }
     }
 
@@ -45,6 +63,31 @@ impl ResumeContext {
         self
     }
 
+    pub fn with_git_context(mut self, git: GitContext) -> Self {
+        self.git_at_stop = Some(git);
+        self
+    }
+
+    pub fn with_workdir(mut self, workdir: PathBuf) -> Self {
+        self.workdir = Some(workdir);
+        self
+    }
+
+    pub fn with_evidence(mut self, evidence: Vec<String>) -> Self {
+        self.evidence = evidence.into();
+        self
+    }
+
+    pub fn with_continuation(mut self, continuation: impl Into<String>) -> Self {
+        self.continuation = Some(continuation.into());
+        self
+    }
+
+    pub fn with_incident(mut self, incident: PathBuf) -> Self {
+        self.incident = Some(incident);
+        self
+    }
+
     pub fn increment_attempt(&mut self) {
         self.attempt_number = self.attempt_number.saturating_add(1);
     }
@@ -0,0 +1,411 @@
+//! Cost accounting for automatic resumes.
+//!
+//! Each resume is charged with the API spend of the session segment that
+//! ended in its stop and credited with the engineer time it saved. The
+//! segment's tokens are the session's reported cumulative usage
+//! (`inputTokens`/`outputTokens` in the frontmatter) minus the usage at its
+//! previous resume, priced per `[metrics.model_costs]` entry of its model;
+//! the time saved is valued at `metrics.hourly_rate`. Either estimate is
+//! unknown when usage data, a price or the rate is missing, and estimating
+//! never fails a resume.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::schema::{MetricsConfig, ModelCost};
+use crate::monitor::session::Session;
+use crate::state::{ResumeCost, StateFile, TokenUsage};
+use crate::util::duration;
+
+/// Price of `model`: an exact key, or the name after a provider prefix
+/// (`anthropic/claude-sonnet-4` matches `claude-sonnet-4`). Keys are
+/// case-insensitive.
+pub fn price_for<'a>(config: &'a MetricsConfig, model: &str) -> Option<&'a ModelCost> {
+    let lookup = |name: &str| {
+        config
+            .model_costs
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, price)| price)
+    };
+    lookup(model).or_else(|| model.rsplit_once('/').and_then(|(_, name)| lookup(name)))
+}
+
+/// Tokens used since `previous`; a count lower than before means the
+/// session started over, so all of `current` counts.
+pub fn segment_usage(current: TokenUsage, previous: Option<TokenUsage>) -> TokenUsage {
+    match previous {
+        Some(previous) if current.input >= previous.input && current.output >= previous.output => {
+            TokenUsage {
+                input: current.input - previous.input,
+                output: current.output - previous.output,
+            }
+        }
+        _ => current,
+    }
+}
+
+pub fn token_cost(tokens: TokenUsage, price: &ModelCost) -> f64 {
+    tokens.input as f64 / 1000.0 * price.input_per_1k
+        + tokens.output as f64 / 1000.0 * price.output_per_1k
+}
+
+/// Value of `seconds` of engineer time at `metrics.hourly_rate`.
+pub fn time_value(seconds: f64, config: &MetricsConfig) -> Option<f64> {
+    config.hourly_rate.map(|rate| seconds / 3600.0 * rate)
+}
+
+/// Cost estimate of a resume whose stopped segment used `tokens` on `model`.
+pub fn estimate(
+    config: &MetricsConfig,
+    model: Option<&str>,
+    tokens: Option<TokenUsage>,
+    time_saved_seconds: f64,
+    at: DateTime<Utc>,
+) -> ResumeCost {
+    let price = model.and_then(|model| price_for(config, model));
+    ResumeCost {
+        at,
+        model: model.map(str::to_string),
+        tokens,
+        cost: tokens
+            .zip(price)
+            .map(|(tokens, price)| token_cost(tokens, price)),
+        time_saved_seconds,
+        value_saved: time_value(time_saved_seconds, config),
+    }
+}
+
+/// Estimate a resume of `session_path` and add it to the session's history
+/// and the running totals in `state`.
+pub fn record_resume_cost(
+    state: &mut StateFile,
+    config: &MetricsConfig,
+    session_path: &Path,
+    session: Option<&Session>,
+    time_saved_seconds: f64,
+) -> ResumeCost {
+    let usage = session.and_then(Session::token_usage);
+    let model = session.and_then(|session| session.state.model.as_deref());
+    let history = state.resume_history_mut(session_path);
+    let tokens = usage.map(|usage| segment_usage(usage, history.last_usage));
+    if usage.is_some() {
+        history.last_usage = usage;
+    }
+    let cost = estimate(config, model, tokens, time_saved_seconds, Utc::now());
+    history.record_cost(cost.clone());
+    state.stats.estimated_cost += cost.cost.unwrap_or(0.0);
+    state.stats.value_saved += cost.value_saved.unwrap_or(0.0);
+    cost
+}
+
+/// `12.34 USD`, `<0.01 USD` for fractions of a cent, or `unknown`.
+pub fn format_amount(amount: Option<f64>, currency: &str) -> String {
+    match amount {
+        None => "unknown".to_string(),
+        Some(amount) if amount != 0.0 && amount.abs() < 0.01 => format!("<0.01 {currency}"),
+        Some(amount) => format!("{amount:.2} {currency}"),
+    }
+}
+
+/// A sum of estimates, `None` when none of them was known.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Estimate {
+    /// Sum of the known estimates.
+    pub total: f64,
+    /// Resumes whose estimate was unknown.
+    pub unknown: usize,
+    known: usize,
+}
+
+impl Estimate {
+    fn add(&mut self, value: Option<f64>) {
+        match value {
+            Some(value) => {
+                self.total += value;
+                self.known += 1;
+            }
+            None => self.unknown += 1,
+        }
+    }
+
+    pub fn known_total(&self) -> Option<f64> {
+        (self.known > 0).then_some(self.total)
+    }
+
+    /// The total, noting resumes left out of it.
+    pub fn format(&self, currency: &str) -> String {
+        let total = format_amount(self.known_total(), currency);
+        if self.known > 0 && self.unknown > 0 {
+            format!("{total} ({} resume(s) unknown)", self.unknown)
+        } else {
+            total
+        }
+    }
+}
+
+/// Cost estimates of one session's resumes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionCosts {
+    pub path: PathBuf,
+    pub resumes: usize,
+    pub tokens: TokenUsage,
+    pub cost: Estimate,
+    pub value_saved: Estimate,
+}
+
+/// Cost estimates of recorded resumes, for `stats --costs` and the digest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostSummary {
+    pub since: Option<DateTime<Utc>>,
+    pub currency: String,
+    pub resumes: usize,
+    pub time_saved_seconds: f64,
+    pub cost: Estimate,
+    pub value_saved: Estimate,
+    pub sessions: Vec<SessionCosts>,
+}
+
+impl CostSummary {
+    /// Resumes recorded at or after `since`, most expensive session first.
+    pub fn from_state(state: &StateFile, since: Option<DateTime<Utc>>, currency: &str) -> Self {
+        let mut summary = Self {
+            since,
+            currency: currency.to_string(),
+            resumes: 0,
+            time_saved_seconds: 0.0,
+            cost: Estimate::default(),
+            value_saved: Estimate::default(),
+            sessions: Vec::new(),
+        };
+        for history in &state.resume_history {
+            let mut session = SessionCosts {
+                path: history.path.clone(),
+                resumes: 0,
+                tokens: TokenUsage::default(),
+                cost: Estimate::default(),
+                value_saved: Estimate::default(),
+            };
+            for cost in history
+                .costs
+                .iter()
+                .filter(|cost| since.is_none_or(|since| cost.at >= since))
+            {
+                session.resumes += 1;
+                if let Some(tokens) = cost.tokens {
+                    session.tokens.input += tokens.input;
+                    session.tokens.output += tokens.output;
+                }
+                session.cost.add(cost.cost);
+                session.value_saved.add(cost.value_saved);
+                summary.cost.add(cost.cost);
+                summary.value_saved.add(cost.value_saved);
+                summary.time_saved_seconds += cost.time_saved_seconds;
+            }
+            if session.resumes > 0 {
+                summary.resumes += session.resumes;
+                summary.sessions.push(session);
+            }
+        }
+        summary
+            .sessions
+            .sort_by(|a, b| b.cost.total.total_cmp(&a.cost.total));
+        summary
+    }
+
+    pub fn format(&self) -> String {
+        if self.resumes == 0 {
+            return "No resume costs recorded".to_string();
+        }
+        let currency = &self.currency;
+        let mut output = format!(
+            "{} resume(s) across {} session(s)\n  estimated API cost: {}\n  value of time saved: {} ({})\n",
+            self.resumes,
+            self.sessions.len(),
+            self.cost.format(currency),
+            self.value_saved.format(currency),
+            duration::format_compact(duration::from_secs_f64(self.time_saved_seconds)),
+        );
+        for session in &self.sessions {
+            output.push_str(&format!(
+                "\n{}\n  {} resume(s), {} input / {} output tokens\n  cost {}, value {}\n",
+                session.path.display(),
+                session.resumes,
+                session.tokens.input,
+                session.tokens.output,
+                session.cost.format(currency),
+                session.value_saved.format(currency),
+            ));
+        }
+        output.trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::session::SessionState;
+
+    fn config() -> MetricsConfig {
+        MetricsConfig {
+            hourly_rate: Some(90.0),
+            currency: "EUR".to_string(),
+            model_costs: [(
+                "claude-sonnet-4".to_string(),
+                ModelCost {
+                    input_per_1k: 0.003,
+                    output_per_1k: 0.015,
+                },
+            )]
+            .into(),
+            ..MetricsConfig::default()
+        }
+    }
+
+    fn session(model: &str, input: u64, output: u64) -> Session {
+        Session {
+            path: PathBuf::from("/work/session.md"),
+            state: SessionState {
+                steps_completed: Vec::new(),
+                last_step: None,
+                total_steps: None,
+                status: None,
+                workflow_type: None,
+                project_name: None,
+                input_documents: Vec::new(),
+                model: Some(model.to_string()),
+                input_tokens: Some(input),
+                output_tokens: Some(output),
+            },
+        }
+    }
+
+    #[test]
+    fn segments_are_priced_from_the_previous_resume() {
+        let config = config();
+        let mut state = StateFile::default();
+        let path = Path::new("/work/session.md");
+
+        // 120k input and 10k output tokens: 0.36 + 0.15.
+        let first = record_resume_cost(
+            &mut state,
+            &config,
+            path,
+            Some(&session("anthropic/claude-sonnet-4", 120_000, 10_000)),
+            600.0,
+        );
+        assert!((first.cost.unwrap() - 0.51).abs() < 1e-9);
+        assert_eq!(first.value_saved, Some(15.0));
+
+        // Only the 80k/2k used since the first resume count: 0.24 + 0.03.
+        let second = record_resume_cost(
+            &mut state,
+            &config,
+            path,
+            Some(&session("anthropic/claude-sonnet-4", 200_000, 12_000)),
+            300.0,
+        );
+        assert_eq!(
+            second.tokens,
+            Some(TokenUsage {
+                input: 80_000,
+                output: 2_000
+            })
+        );
+        assert!((second.cost.unwrap() - 0.27).abs() < 1e-9);
+
+        assert!((state.stats.estimated_cost - 0.78).abs() < 1e-9);
+        assert_eq!(state.stats.value_saved, 22.5);
+        assert_eq!(state.resume_history(path).unwrap().costs.len(), 2);
+    }
+
+    #[test]
+    fn missing_prices_and_usage_leave_estimates_unknown() {
+        let mut state = StateFile::default();
+        let path = Path::new("/work/session.md");
+        let unpriced = record_resume_cost(
+            &mut state,
+            &config(),
+            path,
+            Some(&session("local/llama", 5_000, 500)),
+            300.0,
+        );
+        assert_eq!(unpriced.cost, None);
+        assert!(unpriced.tokens.is_some());
+
+        let no_rate = MetricsConfig {
+            hourly_rate: None,
+            ..config()
+        };
+        let no_usage = record_resume_cost(&mut state, &no_rate, path, None, 300.0);
+        assert_eq!((no_usage.cost, no_usage.value_saved), (None, None));
+        assert_eq!(state.stats.estimated_cost, 0.0);
+
+        let summary = CostSummary::from_state(&state, None, "EUR");
+        assert_eq!(summary.cost.known_total(), None);
+        assert_eq!(summary.cost.format("EUR"), "unknown");
+        assert_eq!(
+            summary.value_saved.format("EUR"),
+            "7.50 EUR (1 resume(s) unknown)"
+        );
+    }
+
+    #[test]
+    fn a_session_that_started_over_counts_in_full() {
+        let previous = TokenUsage {
+            input: 50_000,
+            output: 4_000,
+        };
+        let current = TokenUsage {
+            input: 1_000,
+            output: 100,
+        };
+        assert_eq!(segment_usage(current, Some(previous)), current);
+        assert_eq!(segment_usage(current, None), current);
+    }
+
+    #[test]
+    fn amounts_format_with_currency() {
+        assert_eq!(format_amount(Some(12.345), "USD"), "12.35 USD");
+        assert_eq!(format_amount(Some(0.004), "USD"), "<0.01 USD");
+        assert_eq!(format_amount(Some(0.0), "USD"), "0.00 USD");
+        assert_eq!(format_amount(None, "USD"), "unknown");
+    }
+
+    #[test]
+    fn summary_lists_sessions_by_cost() {
+        let config = config();
+        let mut state = StateFile::default();
+        record_resume_cost(
+            &mut state,
+            &config,
+            Path::new("/work/cheap.md"),
+            Some(&session("claude-sonnet-4", 1_000, 0)),
+            300.0,
+        );
+        record_resume_cost(
+            &mut state,
+            &config,
+            Path::new("/work/busy.md"),
+            Some(&session("claude-sonnet-4", 100_000, 20_000)),
+            300.0,
+        );
+
+        let summary = CostSummary::from_state(&state, None, &config.currency);
+        assert_eq!(
+            summary.format(),
+            "2 resume(s) across 2 session(s)\n  estimated API cost: 0.60 EUR\n  value of time saved: 15.00 EUR (10m)\n\
+             \n/work/busy.md\n  1 resume(s), 100000 input / 20000 output tokens\n  cost 0.60 EUR, value 7.50 EUR\n\
+             \n/work/cheap.md\n  1 resume(s), 1000 input / 0 output tokens\n  cost <0.01 EUR, value 7.50 EUR"
+        );
+
+        let later = CostSummary::from_state(
+            &state,
+            Some(Utc::now() + chrono::TimeDelta::hours(1)),
+            "EUR",
+        );
+        assert_eq!(later.format(), "No resume costs recorded");
+    }
+}
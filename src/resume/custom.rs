@@ -0,0 +1,460 @@
+//! Resume strategies run as external commands (`[[resume.custom_strategies]]`).
+//!
+//! The command runs through `sh -c` with the [`ResumeContext`] as JSON on
+//! stdin and its key fields in `PALINGENESIS_*` variables. Exit status 0
+//! means the resume succeeded; any other status, or a command still running
+//! at `timeout_secs`, means it failed. The last non-empty stdout line may be
+//! a JSON result:
+//!
+//! ```json
+//! {"new_session_path": "/work/next.md", "message": "Restarted in tmux"}
+//! ```
+//!
+//! `new_session_path` names the session that continues the work (the
+//! stopped one when absent), `message` describes what happened, and
+//! `"retryable": true` marks a failure worth another attempt. Other stdout
+//! lines are ignored; a last line that starts with `{` but does not parse
+//! fails the resume.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::config::schema::CustomStrategyConfig;
+use crate::monitor::classifier::StopReason;
+use crate::monitor::incident;
+use crate::resume::exclusions::SessionExclusions;
+use crate::resume::git_context::GitContext;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+use crate::state::AuditLogger;
+use crate::telemetry::Metrics;
+use crate::util::content;
+use crate::util::process::ManagedChild;
+
+/// Names custom strategies may not take: the built-in strategies, as
+/// configured (`unknown_stop_default`, sidecars) and as logged.
+pub const BUILTIN_STRATEGY_NAMES: &[&str] = &[
+    "same_session",
+    "new_session",
+    "skip",
+    "SameSessionStrategy",
+    "NewSessionStrategy",
+];
+
+/// Stop reasons a custom strategy can be matched to.
+pub const MATCHABLE_STOP_REASONS: &[&str] = &[
+    "rate_limit",
+    "quota_exhausted",
+    "context_exhausted",
+    "unknown",
+];
+
+/// Captured stderr kept in failure messages and audit entries.
+const MAX_STDERR_CHARS: usize = 2048;
+
+/// What the command gets on stdin.
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    strategy: &'a str,
+    session_path: &'a Path,
+    stop_reason: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_secs: Option<u64>,
+    attempt_number: u32,
+    timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workdir: Option<&'a Path>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    continuation: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    incident: Option<&'a Path>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_at_stop: Option<&'a GitContext>,
+    evidence: &'a [String],
+}
+
+/// The optional result line on stdout.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+struct PluginResult {
+    #[serde(default)]
+    new_session_path: Option<PathBuf>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    retryable: bool,
+}
+
+/// A `[[resume.custom_strategies]]` entry, ready to match and run.
+#[derive(Debug, Clone)]
+pub struct CommandStrategy {
+    name: String,
+    command: String,
+    timeout: Duration,
+    stop_reasons: Vec<String>,
+    session: SessionExclusions,
+    audit: Option<AuditLogger>,
+}
+
+impl CommandStrategy {
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            timeout: Duration::from_secs(300),
+            stop_reasons: Vec::new(),
+            session: SessionExclusions::default(),
+            audit: None,
+        }
+    }
+
+    pub fn from_config(config: &CustomStrategyConfig) -> Self {
+        let strategy = Self::new(config.name.trim(), config.command.clone())
+            .with_timeout(Duration::from_secs(config.timeout_secs))
+            .with_stop_reasons(config.matches.stop_reasons.clone());
+        match &config.matches.session {
+            Some(glob) => strategy.with_session_glob(glob),
+            None => strategy,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Only handle these stop reasons (see [`StopReason::name`]); all when empty.
+    pub fn with_stop_reasons(mut self, stop_reasons: Vec<String>) -> Self {
+        self.stop_reasons = stop_reasons;
+        self
+    }
+
+    /// Only handle sessions matching `glob` (`resume.exclude_sessions` syntax).
+    pub fn with_session_glob(mut self, glob: &str) -> Self {
+        self.session = SessionExclusions::new(&[glob]);
+        self
+    }
+
+    /// Record runs in this audit log.
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Whether this strategy handles `reason` for `session_path`.
+    ///
+    /// User exits and completions are never resumed.
+    pub fn matches(&self, session_path: &Path, reason: &StopReason) -> bool {
+        if matches!(reason, StopReason::UserExit(_) | StopReason::Completed) {
+            return false;
+        }
+        let reason_matches = self.stop_reasons.is_empty()
+            || self
+                .stop_reasons
+                .iter()
+                .any(|name| name.as_str() == reason.name());
+        let session_matches = self.session.is_empty() || self.session.is_excluded(session_path);
+        reason_matches && session_matches
+    }
+
+    /// The configured command with its placeholders filled in.
+    fn render_command(&self, ctx: &ResumeContext) -> String {
+        self.command
+            .replace(
+                "{session}",
+                &shell_quote(&ctx.session_path.to_string_lossy()),
+            )
+            .replace("{stop_reason}", ctx.stop_reason.name())
+            .replace("{attempt}", &ctx.attempt_number.to_string())
+    }
+
+    async fn run(&self, ctx: &ResumeContext) -> Result<Output, ResumeError> {
+        let request = PluginRequest {
+            strategy: &self.name,
+            session_path: &ctx.session_path,
+            stop_reason: ctx.stop_reason.name(),
+            retry_after_secs: ctx.retry_after.map(|after| after.as_secs()),
+            attempt_number: ctx.attempt_number,
+            timestamp: ctx.timestamp,
+            workdir: ctx.workdir.as_deref(),
+            continuation: ctx.continuation.as_deref(),
+            incident: ctx.incident.as_deref(),
+            git_at_stop: ctx.git_at_stop.as_ref(),
+            evidence: &ctx.evidence,
+        };
+        let request = serde_json::to_vec(&request).map_err(|err| {
+            ResumeError::Config(format!("failed to encode resume context: {err}"))
+        })?;
+
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(self.render_command(ctx))
+            .env("PALINGENESIS_STRATEGY", &self.name)
+            .env("PALINGENESIS_SESSION_PATH", &ctx.session_path)
+            .env("PALINGENESIS_STOP_REASON", ctx.stop_reason.name())
+            .env("PALINGENESIS_ATTEMPT", ctx.attempt_number.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(after) = ctx.retry_after {
+            command.env("PALINGENESIS_RETRY_AFTER_SECS", after.as_secs().to_string());
+        }
+        if let Some(incident) = &ctx.incident {
+            command.env("PALINGENESIS_INCIDENT", incident);
+        }
+        if let Some(workdir) = ctx.workdir.as_deref().filter(|dir| dir.is_dir()) {
+            command.current_dir(workdir);
+        }
+
+        debug!(strategy = %self.name, command = %self.command, "Running custom resume strategy");
+        let mut child = ManagedChild::spawn(&mut command).map_err(ResumeError::Io)?;
+        if let Some(mut stdin) = child.take_stdin() {
+            // A command that never reads stdin must not block the timeout.
+            tokio::spawn(async move {
+                let _ = stdin.write_all(&request).await;
+            });
+        }
+        let finished = child
+            .wait_with_output(Some(self.timeout))
+            .await
+            .map_err(ResumeError::Io)?;
+        if finished.timed_out {
+            return Err(ResumeError::Timeout {
+                duration: self.timeout,
+                stopped: finished.stopped,
+            });
+        }
+        Ok(finished.output)
+    }
+
+    /// Outcome for a command that ran to completion.
+    fn interpret(&self, ctx: &ResumeContext, output: &Output) -> ResumeOutcome {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let result = match parse_result(&stdout) {
+            Ok(result) => result,
+            Err(err) => {
+                return ResumeOutcome::failure(
+                    format!("{}: unreadable result line: {err}", self.name),
+                    false,
+                );
+            }
+        };
+        if output.status.success() {
+            let session_path = result
+                .new_session_path
+                .unwrap_or_else(|| ctx.session_path.clone());
+            let action = result
+                .message
+                .unwrap_or_else(|| format!("Resumed by custom strategy {}", self.name));
+            return ResumeOutcome::success(session_path, action);
+        }
+        let message = result.message.unwrap_or_else(|| {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match stderr.trim() {
+                "" => format!("exited with {}", output.status),
+                stderr => format!(
+                    "exited with {}: {}",
+                    output.status,
+                    content::cap(stderr, MAX_STDERR_CHARS)
+                ),
+            }
+        });
+        ResumeOutcome::failure(format!("{}: {message}", self.name), result.retryable)
+    }
+
+    fn audit_metadata(&self, ctx: &ResumeContext) -> HashMap<String, Value> {
+        let mut metadata = incident::audit_metadata(ctx.incident.as_deref());
+        metadata.insert("strategy".to_string(), Value::from(self.name.clone()));
+        metadata
+    }
+}
+
+#[async_trait]
+impl ResumeStrategy for CommandStrategy {
+    #[tracing::instrument(
+        name = "resume.custom",
+        skip(self, ctx),
+        fields(
+            strategy = %self.name,
+            stop_reason = ?ctx.stop_reason,
+            outcome = tracing::field::Empty,
+        )
+    )]
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        let start = Instant::now();
+        let metrics = Metrics::global();
+        let metrics_reason = ctx.stop_reason.metrics_reason_label().unwrap_or("manual");
+        if let Some(metrics) = metrics.as_ref() {
+            metrics.set_retry_attempts(ctx.attempt_number);
+            metrics.record_resume_started(metrics_reason);
+        }
+        if let Some(audit) = &self.audit {
+            let _ = audit.log_resume_started_with(
+                &ctx.session_path,
+                &format!("{:?}", ctx.stop_reason),
+                self.audit_metadata(ctx),
+            );
+        }
+
+        let result = match self.run(ctx).await {
+            Ok(output) => {
+                let outcome = self.interpret(ctx, &output);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if !stderr.trim().is_empty() {
+                    debug!(strategy = %self.name, stderr = %stderr.trim(), "Custom strategy stderr");
+                }
+                Ok(outcome)
+            }
+            Err(err) => Err(err),
+        };
+
+        let label = match &result {
+            Ok(outcome) => outcome.label(),
+            Err(_) => "error",
+        };
+        tracing::Span::current().record("outcome", label);
+        if let Some(metrics) = metrics.as_ref() {
+            let error_type = match &result {
+                Ok(ResumeOutcome::Success { .. }) => None,
+                Ok(_) => Some("command_failed"),
+                Err(err) => Some(err.error_label()),
+            };
+            metrics.record_resume_completed(start.elapsed(), error_type.is_none(), error_type);
+            metrics.record_strategy_run(&self.name, label);
+        }
+        match &result {
+            Ok(ResumeOutcome::Success {
+                session_path,
+                action,
+            }) => {
+                info!(
+                    strategy = %self.name,
+                    session = %session_path.display(),
+                    "Custom strategy resumed session"
+                );
+                if let Some(audit) = &self.audit {
+                    let mut metadata = self.audit_metadata(ctx);
+                    if *session_path != ctx.session_path {
+                        metadata.insert(
+                            "new_session_path".to_string(),
+                            Value::from(session_path.display().to_string()),
+                        );
+                    }
+                    let _ = audit.log_resume_completed_with(&ctx.session_path, action, metadata);
+                }
+            }
+            Ok(ResumeOutcome::Failure { message, .. }) => {
+                warn!(strategy = %self.name, error = %message, "Custom strategy failed");
+                if let Some(audit) = &self.audit {
+                    let _ = audit
+                        .log_resume_failed_with(
+                            &ctx.session_path,
+                            message,
+                            self.audit_metadata(ctx),
+                        )
+                        .await;
+                }
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!(strategy = %self.name, error = %err, "Custom strategy failed");
+                if let Some(audit) = &self.audit {
+                    let mut metadata = self.audit_metadata(ctx);
+                    if let ResumeError::Timeout {
+                        stopped: Some(stopped),
+                        ..
+                    } = err
+                    {
+                        metadata.insert("process_tree".to_string(), Value::from(stopped.as_str()));
+                    }
+                    let _ = audit
+                        .log_resume_failed_with(&ctx.session_path, &err.to_string(), metadata)
+                        .await;
+                }
+            }
+        }
+        result
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The result line: the last non-empty stdout line, when it is a JSON object.
+fn parse_result(stdout: &str) -> Result<PluginResult, serde_json::Error> {
+    match stdout.lines().map(str::trim).rfind(|line| !line.is_empty()) {
+        Some(line) if line.starts_with('{') => serde_json::from_str(line),
+        _ => Ok(PluginResult::default()),
+    }
+}
+
+/// `value` as one single-quoted `sh` word.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::classifier::{RateLimitInfo, RetryAfterSource};
+
+    fn rate_limit() -> StopReason {
+        StopReason::RateLimit(RateLimitInfo {
+            retry_after: Duration::from_secs(30),
+            source: RetryAfterSource::ConfigDefault,
+            message: None,
+        })
+    }
+
+    #[test]
+    fn matches_stop_reason_and_session_glob() {
+        let strategy = CommandStrategy::new("infra", "true")
+            .with_stop_reasons(vec!["rate_limit".to_string()])
+            .with_session_glob("**/infra/**");
+
+        assert!(strategy.matches(Path::new("/work/infra/session.md"), &rate_limit()));
+        assert!(!strategy.matches(Path::new("/work/app/session.md"), &rate_limit()));
+        assert!(!strategy.matches(
+            Path::new("/work/infra/session.md"),
+            &StopReason::ContextExhausted(None)
+        ));
+
+        let any = CommandStrategy::new("any", "true");
+        assert!(any.matches(Path::new("/work/x.md"), &StopReason::Unknown("?".into())));
+        assert!(!any.matches(Path::new("/work/x.md"), &StopReason::Completed));
+    }
+
+    #[test]
+    fn command_placeholders_are_quoted() {
+        let strategy =
+            CommandStrategy::new("tmux", "restart {session} --why {stop_reason}#{attempt}");
+        let ctx = ResumeContext::new(PathBuf::from("/work/it's here.md"), rate_limit());
+
+        assert_eq!(
+            strategy.render_command(&ctx),
+            r"restart '/work/it'\''s here.md' --why rate_limit#1"
+        );
+    }
+
+    #[test]
+    fn result_line_is_optional() {
+        assert_eq!(
+            parse_result("restarting\n\n").unwrap(),
+            PluginResult::default()
+        );
+        let result = parse_result("log\n{\"message\": \"ok\", \"retryable\": true}\n").unwrap();
+        assert_eq!(result.message.as_deref(), Some("ok"));
+        assert!(result.retryable);
+        assert!(parse_result("{\"message\": ").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Waiting shared by the strategy wrappers that hold a resume until a
+//! wall-clock time: maintenance windows, quota resets and resume spacing.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::daemon::suspend::{Clock, SystemClock};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::ResumeContext;
+use crate::util::duration;
+
+/// Clock, shutdown token and notification sink of a deferring wrapper.
+#[derive(Clone)]
+pub struct Deferral {
+    pub clock: Arc<dyn Clock>,
+    pub events: Option<EventBroadcaster>,
+    pub cancel: Option<CancellationToken>,
+}
+
+impl Default for Deferral {
+    fn default() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            events: None,
+            cancel: None,
+        }
+    }
+}
+
+impl Deferral {
+    pub fn now(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.clock.wall())
+    }
+
+    /// Sleep until `until`; `false` if shutdown interrupted the wait.
+    ///
+    /// `waiting_for` names the hold in the debug log.
+    pub async fn wait_until(&self, until: DateTime<Utc>, waiting_for: &str) -> bool {
+        let wait = duration::between(self.now(), until);
+        debug!(
+            duration_secs = wait.as_secs(),
+            waiting_for, "Waiting to resume"
+        );
+        match &self.cancel {
+            Some(cancel) => tokio::select! {
+                _ = cancel.cancelled() => false,
+                _ = tokio::time::sleep(wait) => true,
+            },
+            None => {
+                tokio::time::sleep(wait).await;
+                true
+            }
+        }
+    }
+
+    pub fn publish(&self, event: DomainEvent) {
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
+    /// Announce a `resume_deferred` notification for `ctx`.
+    pub fn report_deferred(&self, ctx: &ResumeContext, until: DateTime<Utc>, reason: &str) {
+        self.publish(DomainEvent::ResumeDeferred {
+            timestamp: self.now(),
+            session_path: ctx.session_path.clone(),
+            stop_reason: ctx
+                .stop_reason
+                .metrics_reason_label()
+                .unwrap_or("unknown")
+                .to_string(),
+            until,
+            reason: reason.to_string(),
+        });
+    }
+}
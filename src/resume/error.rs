@@ -1,8 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use thiserror::Error;
 
+use crate::util::process::Termination;
+
 #[derive(Debug, Error)]
 pub enum ResumeError {
     #[error("I/O error: {0}")]
@@ -11,17 +13,46 @@ pub enum ResumeError {
     #[error("Session not found: {path}")]
     SessionNotFound { path: PathBuf },
 
-    #[error("Command execution failed: {command}")]
-    CommandFailed { command: String, stderr: String },
+    #[error("Command execution failed: {command}{}", postmortem_note(.postmortem.as_deref()))]
+    CommandFailed {
+        command: String,
+        stderr: String,
+        /// Postmortem bundle written for the failure, if capture succeeded.
+        postmortem: Option<PathBuf>,
+    },
 
-    #[error("Operation timed out after {duration:?}")]
-    Timeout { duration: Duration },
+    #[error("Operation timed out after {duration:?}{}", termination_note(*.stopped))]
+    Timeout {
+        duration: Duration,
+        /// How the command's process tree was stopped, when there was one.
+        stopped: Option<Termination>,
+    },
 
     #[error("Configuration error: {0}")]
     Config(String),
 
     #[error("Retry limit exceeded after {attempts} attempts")]
     RetryExceeded { attempts: u32 },
+
+    #[error("Cannot start new session on model {model}: {reason}")]
+    ModelUnavailable { model: String, reason: String },
+
+    /// The daemon is shutting down; not a failure of the resume itself.
+    #[error("Resume cancelled by shutdown")]
+    Cancelled,
+}
+
+fn termination_note(stopped: Option<Termination>) -> String {
+    match stopped {
+        Some(Termination::Killed) => " (killed after ignoring SIGTERM)".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn postmortem_note(postmortem: Option<&Path>) -> String {
+    postmortem
+        .map(|path| format!(" (postmortem: {})", path.display()))
+        .unwrap_or_default()
 }
 
 impl ResumeError {
@@ -33,6 +64,8 @@ impl ResumeError {
             ResumeError::Timeout { .. } => "timeout",
             ResumeError::Config(_) => "config",
             ResumeError::RetryExceeded { .. } => "retry_exceeded",
+            ResumeError::ModelUnavailable { .. } => "model_unavailable",
+            ResumeError::Cancelled => "cancelled",
         }
     }
 }
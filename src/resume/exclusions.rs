@@ -0,0 +1,265 @@
+//! Session paths that are never auto-resumed (`resume.exclude_sessions`).
+//!
+//! Patterns are globs: `*` and `?` stay within one path component, `**`
+//! spans any number of components. A pattern without `/` is matched against
+//! each component of the session path; a pattern with `/` is matched against
+//! the whole path (relative patterns may start anywhere). A leading `!`
+//! re-includes paths excluded by an earlier pattern, and the last matching
+//! pattern wins.
+
+use std::path::Path;
+
+use crate::config::schema::ResumeConfig;
+
+/// Result of checking a session path against the exclusion list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExclusionMatch {
+    /// No pattern matched.
+    NotMatched,
+    /// The last matching pattern excludes the session.
+    Excluded { pattern: String },
+    /// A later `!` pattern re-included a session excluded earlier.
+    Reincluded { pattern: String },
+}
+
+impl ExclusionMatch {
+    pub fn is_excluded(&self) -> bool {
+        matches!(self, Self::Excluded { .. })
+    }
+
+    /// Human-readable reason, e.g. for `palingenesis exclusions test`.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::NotMatched => "not excluded (no pattern matched)".to_string(),
+            Self::Excluded { pattern } => format!("excluded by `{pattern}`"),
+            Self::Reincluded { pattern } => format!("not excluded (re-included by `{pattern}`)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExclusionPattern {
+    raw: String,
+    glob: Vec<char>,
+    negated: bool,
+    whole_path: bool,
+}
+
+impl ExclusionPattern {
+    fn parse(raw: &str) -> Option<Self> {
+        let trimmed = raw.trim();
+        let (negated, body) = match trimmed.strip_prefix('!') {
+            Some(body) => (true, body.trim()),
+            None => (false, trimmed),
+        };
+        if body.is_empty() {
+            return None;
+        }
+
+        let whole_path = body.contains('/');
+        let glob = if whole_path && !body.starts_with('/') && !body.starts_with("**") {
+            format!("**/{body}")
+        } else {
+            body.to_string()
+        };
+
+        Some(Self {
+            raw: trimmed.to_string(),
+            glob: glob.chars().collect(),
+            negated,
+            whole_path,
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        if self.whole_path {
+            let path: Vec<char> = path.chars().collect();
+            return glob_match(&self.glob, &path);
+        }
+        path.split('/')
+            .filter(|component| !component.is_empty())
+            .any(|component| {
+                let component: Vec<char> = component.chars().collect();
+                glob_match(&self.glob, &component)
+            })
+    }
+}
+
+/// Compiled `resume.exclude_sessions` patterns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionExclusions {
+    patterns: Vec<ExclusionPattern>,
+}
+
+impl SessionExclusions {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .filter_map(|pattern| ExclusionPattern::parse(pattern.as_ref()))
+                .collect(),
+        }
+    }
+
+    pub fn from_config(config: &ResumeConfig) -> Self {
+        Self::new(&config.exclude_sessions)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Active patterns, in evaluation order.
+    pub fn patterns(&self) -> Vec<String> {
+        self.patterns
+            .iter()
+            .map(|pattern| pattern.raw.clone())
+            .collect()
+    }
+
+    pub fn evaluate(&self, session_path: &Path) -> ExclusionMatch {
+        let path = session_path.to_string_lossy().replace('\\', "/");
+        let mut result = ExclusionMatch::NotMatched;
+        for pattern in &self.patterns {
+            if !pattern.matches(&path) {
+                continue;
+            }
+            result = if pattern.negated {
+                match result {
+                    ExclusionMatch::NotMatched => ExclusionMatch::NotMatched,
+                    _ => ExclusionMatch::Reincluded {
+                        pattern: pattern.raw.clone(),
+                    },
+                }
+            } else {
+                ExclusionMatch::Excluded {
+                    pattern: pattern.raw.clone(),
+                }
+            };
+        }
+        result
+    }
+
+    pub fn is_excluded(&self, session_path: &Path) -> bool {
+        self.evaluate(session_path).is_excluded()
+    }
+}
+
+pub(crate) fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `**/` also matches zero directories.
+            if rest.first() == Some(&'/') && glob_match(&rest[1..], text) {
+                return true;
+            }
+            (0..=text.len()).any(|skip| glob_match(rest, &text[skip..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for skip in 0..=text.len() {
+                if glob_match(rest, &text[skip..]) {
+                    return true;
+                }
+                if text.get(skip) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            matches!(text.first(), Some(c) if *c != '/') && glob_match(&pattern[1..], &text[1..])
+        }
+        Some(expected) => text.first() == Some(expected) && glob_match(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn exclusions(patterns: &[&str]) -> SessionExclusions {
+        SessionExclusions::new(patterns)
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        let list = exclusions(&["**/experiments/**"]);
+        assert!(list.is_excluded(Path::new("/home/me/experiments/rate/session.md")));
+        assert!(list.is_excluded(Path::new("/home/me/experiments/session.md")));
+        assert!(!list.is_excluded(Path::new("/home/me/work/session.md")));
+    }
+
+    #[test]
+    fn component_pattern_matches_any_component() {
+        let list = exclusions(&["*scratch*"]);
+        assert!(list.is_excluded(Path::new("/home/me/my-scratch-pad/session.md")));
+        assert!(list.is_excluded(Path::new("/home/me/work/scratch.md")));
+        assert!(!list.is_excluded(Path::new("/home/me/work/session.md")));
+    }
+
+    #[test]
+    fn single_star_stays_within_component() {
+        let list = exclusions(&["/home/*/session.md"]);
+        assert!(list.is_excluded(Path::new("/home/me/session.md")));
+        assert!(!list.is_excluded(Path::new("/home/me/nested/session.md")));
+    }
+
+    #[test]
+    fn relative_path_pattern_matches_anywhere() {
+        let list = exclusions(&["experiments/*.md"]);
+        assert!(list.is_excluded(Path::new("/srv/experiments/a.md")));
+        assert!(!list.is_excluded(Path::new("/srv/experiments/deep/a.md")));
+    }
+
+    #[test]
+    fn last_matching_pattern_wins() {
+        let list = exclusions(&["**/experiments/**", "!**/experiments/keep/**"]);
+        assert_eq!(
+            list.evaluate(Path::new("/p/experiments/keep/session.md")),
+            ExclusionMatch::Reincluded {
+                pattern: "!**/experiments/keep/**".to_string()
+            }
+        );
+        assert!(list.is_excluded(Path::new("/p/experiments/drop/session.md")));
+
+        let reordered = exclusions(&["!**/experiments/keep/**", "**/experiments/**"]);
+        assert_eq!(
+            reordered.evaluate(Path::new("/p/experiments/keep/session.md")),
+            ExclusionMatch::Excluded {
+                pattern: "**/experiments/**".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn negation_without_prior_exclusion_is_not_a_match() {
+        let list = exclusions(&["!*keep*"]);
+        assert_eq!(
+            list.evaluate(Path::new("/p/keep/session.md")),
+            ExclusionMatch::NotMatched
+        );
+    }
+
+    #[test]
+    fn blank_patterns_are_ignored() {
+        let list = exclusions(&["", "  ", "!"]);
+        assert!(list.is_empty());
+        assert!(!list.is_excluded(&PathBuf::from("/p/session.md")));
+    }
+
+    #[test]
+    fn describe_reports_reason() {
+        let list = exclusions(&["*scratch*"]);
+        assert_eq!(
+            list.evaluate(Path::new("/p/scratch/session.md")).describe(),
+            "excluded by `*scratch*`"
+        );
+        assert_eq!(
+            list.evaluate(Path::new("/p/session.md")).describe(),
+            "not excluded (no pattern matched)"
+        );
+    }
+}
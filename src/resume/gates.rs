@@ -0,0 +1,634 @@
+//! Resume gates (`[resume.gates]`).
+//!
+//! A resume can wait for the machine to be ready for it: load average below
+//! a threshold, AC power, enough free memory. The conditions are checked just
+//! before the strategy runs and, while unmet, re-checked every
+//! `recheck_interval_secs`. After `max_deferral_secs` the resume proceeds or
+//! is abandoned per `on_max_deferral`.
+//!
+//! Readings come from a [`SystemProbe`]. A reading the platform cannot
+//! provide counts as met, so a desktop without a battery is never held for
+//! AC power.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::config::schema::{MaxDeferralAction, ResumeGatesConfig};
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+
+/// Readings the gates are evaluated against. `None` means unknown.
+pub trait SystemProbe: Send + Sync {
+    /// 1-minute load average.
+    fn load_average(&self) -> Option<f64>;
+
+    /// Whether the machine is on mains power.
+    fn on_ac_power(&self) -> Option<bool>;
+
+    /// Memory available for new processes, in MiB.
+    fn free_memory_mb(&self) -> Option<u64>;
+}
+
+/// The probe for the current platform.
+pub fn platform_probe() -> Arc<dyn SystemProbe> {
+    Arc::new(LinuxProbe::new())
+}
+
+/// Reads `/proc/loadavg`, `/proc/meminfo` and `/sys/class/power_supply`.
+///
+/// On other platforms the files are missing and every reading is unknown.
+#[derive(Debug, Clone)]
+pub struct LinuxProbe {
+    root: PathBuf,
+}
+
+impl LinuxProbe {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+        }
+    }
+
+    /// Read `proc/` and `sys/` under `root` instead of `/` (for testing).
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn read(&self, path: impl AsRef<Path>) -> Option<String> {
+        std::fs::read_to_string(self.root.join(path)).ok()
+    }
+}
+
+impl Default for LinuxProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemProbe for LinuxProbe {
+    fn load_average(&self) -> Option<f64> {
+        self.read("proc/loadavg")?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    fn on_ac_power(&self) -> Option<bool> {
+        let supplies = std::fs::read_dir(self.root.join("sys/class/power_supply")).ok()?;
+        let mut mains = None;
+        for supply in supplies.flatten() {
+            let dir = supply.path();
+            let kind = self.read(dir.join("type")).unwrap_or_default();
+            match kind.trim() {
+                "Mains" | "USB" => {
+                    let online = self.read(dir.join("online")).unwrap_or_default();
+                    if online.trim() == "1" {
+                        return Some(true);
+                    }
+                    mains = Some(false);
+                }
+                "Battery" => {
+                    let status = self.read(dir.join("status")).unwrap_or_default();
+                    if status.trim() == "Discharging" {
+                        mains = Some(false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        mains
+    }
+
+    fn free_memory_mb(&self) -> Option<u64> {
+        let meminfo = self.read("proc/meminfo")?;
+        let line = meminfo
+            .lines()
+            .find(|line| line.starts_with("MemAvailable:"))?;
+        let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kib / 1024)
+    }
+}
+
+/// Parsed `[resume.gates]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumeGates {
+    max_load_average: Option<f64>,
+    require_ac_power: bool,
+    min_free_memory_mb: Option<u64>,
+    recheck_interval: Duration,
+    max_deferral: Duration,
+    on_max_deferral: MaxDeferralAction,
+}
+
+impl ResumeGates {
+    pub fn from_config(config: &ResumeGatesConfig) -> Self {
+        Self {
+            max_load_average: config.max_load_average,
+            require_ac_power: config.require_ac_power,
+            min_free_memory_mb: config.min_free_memory_mb,
+            recheck_interval: Duration::from_secs(config.recheck_interval_secs.max(1)),
+            max_deferral: Duration::from_secs(config.max_deferral_secs),
+            on_max_deferral: config.on_max_deferral,
+        }
+    }
+
+    /// Whether no condition is configured.
+    pub fn is_empty(&self) -> bool {
+        self.max_load_average.is_none()
+            && !self.require_ac_power
+            && self.min_free_memory_mb.is_none()
+    }
+
+    /// The first unmet condition, phrased for `status` (`waiting for AC power`).
+    pub fn unmet(&self, probe: &dyn SystemProbe) -> Option<String> {
+        if self.require_ac_power && probe.on_ac_power() == Some(false) {
+            return Some("waiting for AC power".to_string());
+        }
+        if let Some(max) = self.max_load_average {
+            if let Some(load) = probe.load_average().filter(|load| *load > max) {
+                return Some(format!(
+                    "waiting for load average {load:.2} to drop below {max}"
+                ));
+            }
+        }
+        if let Some(min) = self.min_free_memory_mb {
+            if let Some(free) = probe.free_memory_mb().filter(|free| *free < min) {
+                return Some(format!(
+                    "waiting for {min} MiB of free memory ({free} MiB available)"
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl Default for ResumeGates {
+    fn default() -> Self {
+        Self::from_config(&ResumeGatesConfig::default())
+    }
+}
+
+/// Resumes currently held by their gates, shared between the daemon state
+/// and the strategies it builds.
+#[derive(Debug, Clone, Default)]
+pub struct GateStatus {
+    waiting: Arc<Mutex<HashMap<PathBuf, String>>>,
+}
+
+impl GateStatus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Why a resume is being held, if any is (`waiting for AC power`).
+    pub fn reason(&self) -> Option<String> {
+        let waiting = self.waiting.lock().ok()?;
+        let mut reasons: Vec<&String> = waiting.values().collect();
+        reasons.sort();
+        reasons.first().map(|reason| (*reason).clone())
+    }
+
+    fn set(&self, session: &Path, reason: &str) {
+        if let Ok(mut waiting) = self.waiting.lock() {
+            waiting.insert(session.to_path_buf(), reason.to_string());
+        }
+    }
+
+    fn clear(&self, session: &Path) {
+        if let Ok(mut waiting) = self.waiting.lock() {
+            waiting.remove(session);
+        }
+    }
+}
+
+/// Runs a strategy once the resume gates are met.
+pub struct ResumeGating {
+    inner: Box<dyn ResumeStrategy>,
+    gates: ResumeGates,
+    probe: Arc<dyn SystemProbe>,
+    status: GateStatus,
+    events: Option<EventBroadcaster>,
+    cancel: Option<CancellationToken>,
+}
+
+impl ResumeGating {
+    pub fn new(inner: Box<dyn ResumeStrategy>, gates: ResumeGates) -> Self {
+        Self {
+            inner,
+            gates,
+            probe: platform_probe(),
+            status: GateStatus::new(),
+            events: None,
+            cancel: None,
+        }
+    }
+
+    /// Take readings from this probe (for testing).
+    pub fn with_probe(mut self, probe: Arc<dyn SystemProbe>) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Report held resumes through this status.
+    pub fn with_status(mut self, status: GateStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Publish `resume_deferred` notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Sleep for `duration`; `false` if shutdown interrupted the wait.
+    async fn wait(&self, duration: Duration) -> bool {
+        match &self.cancel {
+            Some(cancel) => tokio::select! {
+                _ = cancel.cancelled() => false,
+                _ = tokio::time::sleep(duration) => true,
+            },
+            None => {
+                tokio::time::sleep(duration).await;
+                true
+            }
+        }
+    }
+
+    fn report_deferred(&self, ctx: &ResumeContext, reason: &str) {
+        if let Some(events) = &self.events {
+            let now = Utc::now();
+            let event = DomainEvent::ResumeDeferred {
+                timestamp: now,
+                session_path: ctx.session_path.clone(),
+                stop_reason: ctx
+                    .stop_reason
+                    .metrics_reason_label()
+                    .unwrap_or("unknown")
+                    .to_string(),
+                until: TimeDelta::from_std(self.gates.max_deferral)
+                    .ok()
+                    .and_then(|max| now.checked_add_signed(max))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+                reason: reason.to_string(),
+            };
+            events.publish(event);
+        }
+    }
+
+    /// Wait for the gates; `Err` carries the outcome when the resume must not run.
+    async fn wait_for_gates(&self, ctx: &ResumeContext) -> Result<(), ResumeOutcome> {
+        let started = Instant::now();
+        let mut reported = false;
+        while let Some(reason) = self.gates.unmet(self.probe.as_ref()) {
+            let elapsed = started.elapsed();
+            if elapsed >= self.gates.max_deferral {
+                return match self.gates.on_max_deferral {
+                    MaxDeferralAction::Proceed => {
+                        warn!(
+                            session = %ctx.session_path.display(),
+                            %reason,
+                            "Resume gates still unmet at max deferral; resuming anyway"
+                        );
+                        Ok(())
+                    }
+                    MaxDeferralAction::Abandon => {
+                        warn!(
+                            session = %ctx.session_path.display(),
+                            %reason,
+                            "Resume gates still unmet at max deferral; abandoning resume"
+                        );
+                        Err(ResumeOutcome::skipped(format!(
+                            "resume gates unmet after {}s ({reason})",
+                            self.gates.max_deferral.as_secs()
+                        )))
+                    }
+                };
+            }
+
+            self.status.set(&ctx.session_path, &reason);
+            if !reported {
+                info!(
+                    session = %ctx.session_path.display(),
+                    %reason,
+                    strategy = self.inner.name(),
+                    "Resume deferred until resume gates are met"
+                );
+                self.report_deferred(ctx, &reason);
+                reported = true;
+            } else {
+                debug!(%reason, "Resume gates still unmet");
+            }
+
+            let wait = self
+                .gates
+                .recheck_interval
+                .min(self.gates.max_deferral - elapsed);
+            if !self.wait(wait).await {
+                info!("Deferred resume cancelled by shutdown");
+                return Err(ResumeOutcome::skipped(
+                    "shutdown while waiting for resume gates",
+                ));
+            }
+        }
+        if reported {
+            info!(
+                session = %ctx.session_path.display(),
+                "Resume gates met"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResumeStrategy for ResumeGating {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        let gated = self.wait_for_gates(ctx).await;
+        self.status.clear(&ctx.session_path);
+        match gated {
+            Ok(()) => self.inner.execute(ctx).await,
+            Err(outcome) => Ok(outcome),
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
+        self.inner.should_retry(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::classifier::StopReason;
+    use crate::notify::events::NotificationEvent;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Readings set by the test.
+    #[derive(Default)]
+    struct MockProbe {
+        load: Mutex<Option<f64>>,
+        ac: Mutex<Option<bool>>,
+        free_mb: Mutex<Option<u64>>,
+    }
+
+    impl MockProbe {
+        fn set_ac(&self, ac: bool) {
+            *self.ac.lock().unwrap() = Some(ac);
+        }
+
+        fn set_load(&self, load: f64) {
+            *self.load.lock().unwrap() = Some(load);
+        }
+    }
+
+    impl SystemProbe for MockProbe {
+        fn load_average(&self) -> Option<f64> {
+            *self.load.lock().unwrap()
+        }
+
+        fn on_ac_power(&self) -> Option<bool> {
+            *self.ac.lock().unwrap()
+        }
+
+        fn free_memory_mb(&self) -> Option<u64> {
+            *self.free_mb.lock().unwrap()
+        }
+    }
+
+    struct CountingStrategy {
+        runs: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ResumeStrategy for CountingStrategy {
+        async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(ResumeOutcome::success(ctx.session_path.clone(), "resumed"))
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    fn gates(config: ResumeGatesConfig) -> ResumeGates {
+        ResumeGates::from_config(&config)
+    }
+
+    fn ac_gate() -> ResumeGatesConfig {
+        ResumeGatesConfig {
+            require_ac_power: true,
+            recheck_interval_secs: 30,
+            max_deferral_secs: 600,
+            ..ResumeGatesConfig::default()
+        }
+    }
+
+    fn gating(
+        config: ResumeGatesConfig,
+        probe: Arc<MockProbe>,
+    ) -> (ResumeGating, Arc<AtomicUsize>, GateStatus) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let status = GateStatus::new();
+        let strategy = ResumeGating::new(
+            Box::new(CountingStrategy {
+                runs: Arc::clone(&runs),
+            }),
+            gates(config),
+        )
+        .with_probe(probe)
+        .with_status(status.clone());
+        (strategy, runs, status)
+    }
+
+    fn ctx() -> ResumeContext {
+        ResumeContext::new("/tmp/session.md".into(), StopReason::ContextExhausted(None))
+    }
+
+    #[test]
+    fn unknown_readings_count_as_met() {
+        let all = gates(ResumeGatesConfig {
+            max_load_average: Some(4.0),
+            require_ac_power: true,
+            min_free_memory_mb: Some(1024),
+            ..ResumeGatesConfig::default()
+        });
+        assert_eq!(all.unmet(&MockProbe::default()), None);
+        assert!(gates(ResumeGatesConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn describes_the_unmet_condition() {
+        let all = gates(ResumeGatesConfig {
+            max_load_average: Some(4.0),
+            require_ac_power: true,
+            min_free_memory_mb: Some(1024),
+            ..ResumeGatesConfig::default()
+        });
+        let probe = MockProbe::default();
+        probe.set_load(6.5);
+        assert_eq!(
+            all.unmet(&probe).as_deref(),
+            Some("waiting for load average 6.50 to drop below 4")
+        );
+        probe.set_ac(false);
+        assert_eq!(all.unmet(&probe).as_deref(), Some("waiting for AC power"));
+        probe.set_ac(true);
+        probe.set_load(1.0);
+        *probe.free_mb.lock().unwrap() = Some(512);
+        assert_eq!(
+            all.unmet(&probe).as_deref(),
+            Some("waiting for 1024 MiB of free memory (512 MiB available)")
+        );
+    }
+
+    #[test]
+    fn linux_probe_reads_proc_and_sys() {
+        let root = tempfile::tempdir().unwrap();
+        let proc_dir = root.path().join("proc");
+        std::fs::create_dir_all(&proc_dir).unwrap();
+        std::fs::write(proc_dir.join("loadavg"), "3.20 2.10 1.00 2/345 6789\n").unwrap();
+        std::fs::write(
+            proc_dir.join("meminfo"),
+            "MemTotal:       16384000 kB\nMemFree:          100000 kB\nMemAvailable:    2097152 kB\n",
+        )
+        .unwrap();
+        let supplies = root.path().join("sys/class/power_supply");
+        for (name, kind) in [("AC", "Mains"), ("BAT0", "Battery")] {
+            std::fs::create_dir_all(supplies.join(name)).unwrap();
+            std::fs::write(supplies.join(name).join("type"), format!("{kind}\n")).unwrap();
+        }
+        std::fs::write(supplies.join("AC/online"), "0\n").unwrap();
+        std::fs::write(supplies.join("BAT0/status"), "Discharging\n").unwrap();
+
+        let probe = LinuxProbe::with_root(root.path());
+        assert_eq!(probe.load_average(), Some(3.2));
+        assert_eq!(probe.free_memory_mb(), Some(2048));
+        assert_eq!(probe.on_ac_power(), Some(false));
+
+        std::fs::write(supplies.join("AC/online"), "1\n").unwrap();
+        assert_eq!(probe.on_ac_power(), Some(true));
+
+        let empty = tempfile::tempdir().unwrap();
+        let probe = LinuxProbe::with_root(empty.path());
+        assert_eq!(probe.load_average(), None);
+        assert_eq!(probe.on_ac_power(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn defers_until_conditions_flip() {
+        let probe = Arc::new(MockProbe::default());
+        probe.set_ac(false);
+        let (strategy, runs, status) = gating(ac_gate(), Arc::clone(&probe));
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let strategy = strategy.with_event_broadcaster(events);
+
+        let task = tokio::spawn(async move { strategy.execute(&ctx()).await });
+        tokio::time::sleep(Duration::from_secs(65)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        assert_eq!(status.reason().as_deref(), Some("waiting for AC power"));
+
+        probe.set_ac(true);
+        let outcome = task.await.unwrap().unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(status.reason(), None);
+
+        // Notified once, not on every re-check.
+        match rx.try_recv().expect("deferral notification") {
+            NotificationEvent::ResumeDeferred { reason, .. } => {
+                assert_eq!(reason, "waiting for AC power");
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn huge_max_deferral_reports_an_open_ended_wait() {
+        let probe = Arc::new(MockProbe::default());
+        probe.set_ac(false);
+        let config = ResumeGatesConfig {
+            max_deferral_secs: u64::MAX,
+            ..ac_gate()
+        };
+        let (strategy, _, _) = gating(config, Arc::clone(&probe));
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let strategy = strategy.with_event_broadcaster(events);
+
+        let task = tokio::spawn(async move { strategy.execute(&ctx()).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        match rx.try_recv().expect("deferral notification") {
+            NotificationEvent::ResumeDeferred { until, .. } => {
+                assert_eq!(until, DateTime::<Utc>::MAX_UTC);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+
+        probe.set_ac(true);
+        assert!(task.await.unwrap().unwrap().is_success());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_deferral_proceeds_by_default() {
+        let probe = Arc::new(MockProbe::default());
+        probe.set_ac(false);
+        let (strategy, runs, _) = gating(ac_gate(), probe);
+
+        let started = Instant::now();
+        let outcome = strategy.execute(&ctx()).await.unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(started.elapsed(), Duration::from_secs(600));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_deferral_can_abandon() {
+        let probe = Arc::new(MockProbe::default());
+        probe.set_load(9.0);
+        let config = ResumeGatesConfig {
+            max_load_average: Some(4.0),
+            on_max_deferral: MaxDeferralAction::Abandon,
+            ..ac_gate()
+        };
+        let (strategy, runs, status) = gating(config, probe);
+
+        let outcome = strategy.execute(&ctx()).await.unwrap();
+        assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        assert_eq!(status.reason(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn met_gates_run_immediately() {
+        let probe = Arc::new(MockProbe::default());
+        probe.set_ac(true);
+        let (strategy, runs, _) = gating(ac_gate(), probe);
+        let started = Instant::now();
+        strategy.execute(&ctx()).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+}
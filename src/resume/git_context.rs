@@ -0,0 +1,258 @@
+//! Workspace git state recorded with resume history (`resume.capture_git_context`).
+//!
+//! Collected by running `git` as a subprocess with a short timeout, so a
+//! hung git on a network filesystem can't stall the resume pipeline.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::resume::ResumeContext;
+use crate::resume::assistant::ClaudeTranscript;
+use crate::util::process;
+
+/// Default bound on all git calls for one capture.
+pub const DEFAULT_GIT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Git state of a session's workspace at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GitContext {
+    Repo {
+        branch: String,
+        sha: String,
+        dirty: bool,
+    },
+    /// The workspace is not inside a git repository.
+    NotARepo,
+    /// git could not be run or did not answer in time.
+    Unavailable { reason: String },
+}
+
+impl GitContext {
+    /// Value for the `{git_branch}` placeholder.
+    pub fn branch(&self) -> &str {
+        match self {
+            Self::Repo { branch, .. } => branch,
+            Self::NotARepo => "not a repo",
+            Self::Unavailable { .. } => "unknown",
+        }
+    }
+
+    /// Value for the `{git_sha}` placeholder.
+    pub fn sha(&self) -> &str {
+        match self {
+            Self::Repo { sha, .. } => sha,
+            Self::NotARepo => "not a repo",
+            Self::Unavailable { .. } => "unknown",
+        }
+    }
+
+    /// Short summary, e.g. "main@1a2b3c4 (dirty)".
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Repo { branch, sha, dirty } => {
+                format!(
+                    "{branch}@{sha} ({})",
+                    if *dirty { "dirty" } else { "clean" }
+                )
+            }
+            Self::NotARepo => "not a repo".to_string(),
+            Self::Unavailable { reason } => format!("unavailable: {reason}"),
+        }
+    }
+
+    /// Replace `{git_branch}` and `{git_sha}` in `template`.
+    pub fn render(context: Option<&Self>, template: &str) -> String {
+        let (branch, sha) = context
+            .map(|context| (context.branch(), context.sha()))
+            .unwrap_or(("unknown", "unknown"));
+        template
+            .replace("{git_branch}", branch)
+            .replace("{git_sha}", sha)
+    }
+}
+
+/// Runs the git queries for a workspace.
+#[derive(Debug, Clone)]
+pub struct GitCollector {
+    program: String,
+    timeout: Duration,
+}
+
+impl GitCollector {
+    pub fn new() -> Self {
+        Self {
+            program: "git".to_string(),
+            timeout: DEFAULT_GIT_TIMEOUT,
+        }
+    }
+
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Capture the git state of `workspace`; never fails.
+    pub async fn capture(&self, workspace: &Path) -> GitContext {
+        if !workspace.is_dir() {
+            return GitContext::NotARepo;
+        }
+        match tokio::time::timeout(self.timeout, self.query(workspace)).await {
+            Ok(context) => context,
+            Err(_) => {
+                debug!(workspace = %workspace.display(), "git timed out");
+                GitContext::Unavailable {
+                    reason: format!("git timed out after {}ms", self.timeout.as_millis()),
+                }
+            }
+        }
+    }
+
+    /// Capture the git state of the workspace a resume runs in.
+    pub async fn capture_for(&self, ctx: &ResumeContext) -> GitContext {
+        self.capture(&workspace_for(ctx)).await
+    }
+
+    async fn query(&self, workspace: &Path) -> GitContext {
+        let branch = match self
+            .git(workspace, &["rev-parse", "--abbrev-ref", "HEAD"])
+            .await
+        {
+            Ok(branch) => branch,
+            Err(GitFailure::NotARepo) => return GitContext::NotARepo,
+            Err(GitFailure::Other(reason)) => return GitContext::Unavailable { reason },
+        };
+        let sha = self.git(workspace, &["rev-parse", "--short", "HEAD"]).await;
+        let status = self.git(workspace, &["status", "--porcelain"]).await;
+        match (sha, status) {
+            (Ok(sha), Ok(status)) => GitContext::Repo {
+                branch,
+                sha,
+                dirty: !status.is_empty(),
+            },
+            (Err(GitFailure::NotARepo), _) | (_, Err(GitFailure::NotARepo)) => GitContext::NotARepo,
+            (Err(GitFailure::Other(reason)), _) | (_, Err(GitFailure::Other(reason))) => {
+                GitContext::Unavailable { reason }
+            }
+        }
+    }
+
+    async fn git(&self, workspace: &Path, args: &[&str]) -> Result<String, GitFailure> {
+        let mut command = Command::new(&self.program);
+        command
+            .arg("-C")
+            .arg(workspace)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let output = process::output(&mut command, None)
+            .await
+            .map_err(|err| GitFailure::Other(format!("failed to run {}: {err}", self.program)))?
+            .output;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitFailure::NotARepo);
+        }
+        Err(GitFailure::Other(stderr.trim().to_string()))
+    }
+}
+
+impl Default for GitCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum GitFailure {
+    NotARepo,
+    Other(String),
+}
+
+/// Audit metadata for the git states around one resume.
+pub fn audit_metadata(
+    at_stop: Option<&GitContext>,
+    at_resume: Option<&GitContext>,
+) -> HashMap<String, Value> {
+    [("git_at_stop", at_stop), ("git_at_resume", at_resume)]
+        .into_iter()
+        .filter_map(|(key, context)| {
+            let value = serde_json::to_value(context?).ok()?;
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// Directory the session's assistant works in.
+///
+/// Claude Code transcripts record it; otherwise the session file's directory is used.
+pub fn workspace_for(ctx: &ResumeContext) -> PathBuf {
+    if ctx
+        .session_path
+        .extension()
+        .is_some_and(|ext| ext == "jsonl")
+    {
+        if let Some(cwd) = ClaudeTranscript::read(&ctx.session_path).cwd {
+            return cwd;
+        }
+    }
+    ctx.session_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fills_placeholders() {
+        let context = GitContext::Repo {
+            branch: "feature/x".to_string(),
+            sha: "1a2b3c4".to_string(),
+            dirty: true,
+        };
+        assert_eq!(
+            GitContext::render(Some(&context), "on {git_branch} at {git_sha}"),
+            "on feature/x at 1a2b3c4"
+        );
+        assert_eq!(context.describe(), "feature/x@1a2b3c4 (dirty)");
+        assert_eq!(
+            GitContext::render(Some(&GitContext::NotARepo), "{git_branch}"),
+            "not a repo"
+        );
+        assert_eq!(GitContext::render(None, "{git_sha}"), "unknown");
+    }
+
+    #[test]
+    fn serializes_with_status_tag() {
+        let value = serde_json::to_value(GitContext::NotARepo).unwrap();
+        assert_eq!(value, serde_json::json!({ "status": "not_a_repo" }));
+    }
+
+    #[tokio::test]
+    async fn missing_git_is_unavailable() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = GitCollector::new()
+            .with_program("palingenesis-no-such-git")
+            .capture(dir.path())
+            .await;
+        assert!(matches!(context, GitContext::Unavailable { .. }));
+    }
+}
@@ -0,0 +1,449 @@
+//! Cross-host resume leases (`resume.cross_host_lease`).
+//!
+//! Daemons on several machines can watch one session directory through a
+//! file-sync layer, and each would resume the same stop. With the lease on,
+//! a daemon claims the resume by creating `.palingenesis/<stem>.lease` next
+//! to the session before running the strategy. A daemon that finds a fresh
+//! lease from another holder defers to it; a lease whose TTL has run out is
+//! broken. The holder renews the lease while the strategy runs and removes
+//! it afterwards.
+//!
+//! Exclusive creation only holds on one machine: two daemons can both create
+//! the file before the sync layer shows either one the other's, and the sync
+//! layer then keeps one of them or a conflict copy of both. After creating
+//! its lease a daemon waits [`DEFAULT_SETTLE_DELAY`], re-reads every lease for
+//! the session, conflict copies included, and yields if a fresh one belongs
+//! to a lexicographically smaller host name.
+
+use std::cmp::Ordering;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::schema::ResumeConfig;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+use crate::util::host::host_name;
+
+/// Directory next to the session that holds its leases.
+pub const LEASE_DIR: &str = ".palingenesis";
+
+/// Default for `resume.lease_ttl_secs`.
+pub const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(300);
+
+/// How long a new lease is left for the sync layer before it is re-read.
+pub const DEFAULT_SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Contents of a lease file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    pub host: String,
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+    pub renewed_at: DateTime<Utc>,
+    pub ttl_secs: u64,
+}
+
+impl LeaseRecord {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        let ttl = TimeDelta::try_seconds(self.ttl_secs as i64).unwrap_or(TimeDelta::MAX);
+        self.renewed_at
+            .checked_add_signed(ttl)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at() > now
+    }
+
+    /// Same holder and acquisition, regardless of renewals.
+    fn same_lease(&self, other: &Self) -> bool {
+        self.host == other.host && self.pid == other.pid && self.acquired_at == other.acquired_at
+    }
+
+    /// Conflict order: the smaller host name wins, then the smaller pid.
+    fn precedence(&self, other: &Self) -> Ordering {
+        self.host.cmp(&other.host).then(self.pid.cmp(&other.pid))
+    }
+}
+
+/// Result of [`LeaseManager::acquire`].
+#[derive(Debug)]
+pub enum LeaseAcquisition {
+    Acquired(Lease),
+    /// Another daemon holds a fresh lease.
+    Held(LeaseRecord),
+}
+
+/// Takes leases for this daemon.
+#[derive(Debug, Clone)]
+pub struct LeaseManager {
+    host: String,
+    pid: u32,
+    ttl: Duration,
+    settle_delay: Duration,
+}
+
+impl LeaseManager {
+    pub fn new() -> Self {
+        Self {
+            host: host_name(),
+            pid: std::process::id(),
+            ttl: DEFAULT_LEASE_TTL,
+            settle_delay: DEFAULT_SETTLE_DELAY,
+        }
+    }
+
+    pub fn from_config(config: &ResumeConfig) -> Self {
+        Self::new().with_ttl(Duration::from_secs(config.lease_ttl_secs))
+    }
+
+    /// Hold leases under this host name instead of the machine's.
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_settle_delay(mut self, delay: Duration) -> Self {
+        self.settle_delay = delay;
+        self
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// How often a held lease is renewed: three times per TTL.
+    pub fn renew_interval(&self) -> Duration {
+        (self.ttl / 3).max(Duration::from_millis(10))
+    }
+
+    /// `.palingenesis/<stem>.lease` next to `session_path`.
+    pub fn lease_path(session_path: &Path) -> PathBuf {
+        let stem = session_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "session".to_string());
+        session_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(LEASE_DIR)
+            .join(format!("{stem}.lease"))
+    }
+
+    /// Claim the resume of `session_path`.
+    pub async fn acquire(&self, session_path: &Path) -> io::Result<LeaseAcquisition> {
+        let path = Self::lease_path(session_path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if let Some(holder) = break_stale(&path) {
+            return Ok(LeaseAcquisition::Held(holder));
+        }
+
+        let record = self.record(Utc::now());
+        match create_new(&path, &record) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                // Created between the scan and now; a stale one was removed above.
+                return match read_record(&path) {
+                    Some(holder) => Ok(LeaseAcquisition::Held(holder)),
+                    None => Err(err),
+                };
+            }
+            Err(err) => return Err(err),
+        }
+
+        tokio::time::sleep(self.settle_delay).await;
+        let now = Utc::now();
+        if let Some(winner) = lease_files(&path)
+            .into_iter()
+            .map(|(_, lease)| lease)
+            .filter(|lease| lease.is_fresh(now))
+            .filter(|lease| !lease.same_lease(&record))
+            .filter(|lease| lease.precedence(&record) == Ordering::Less)
+            .min_by(|a, b| a.precedence(b))
+        {
+            warn!(
+                lease = %path.display(),
+                winner = %winner.host,
+                "Cross-host lease conflict; yielding to the smaller host name"
+            );
+            remove_if_ours(&path, &record);
+            return Ok(LeaseAcquisition::Held(winner));
+        }
+        // The sync layer may have replaced the file with a rival we outrank.
+        if read_record(&path).is_none_or(|current| !current.same_lease(&record)) {
+            write_record(&path, &record)?;
+        }
+        Ok(LeaseAcquisition::Acquired(Lease { path, record }))
+    }
+
+    fn record(&self, now: DateTime<Utc>) -> LeaseRecord {
+        LeaseRecord {
+            host: self.host.clone(),
+            pid: self.pid,
+            acquired_at: now,
+            renewed_at: now,
+            ttl_secs: self.ttl.as_secs().max(1),
+        }
+    }
+}
+
+impl Default for LeaseManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Remove the expired leases for `path`; the first fresh one, if any is left.
+fn break_stale(path: &Path) -> Option<LeaseRecord> {
+    let now = Utc::now();
+    let mut rival = None;
+    for (file, lease) in lease_files(path) {
+        if lease.is_fresh(now) {
+            rival = rival.or(Some(lease));
+            continue;
+        }
+        warn!(
+            lease = %file.display(),
+            holder = %lease.host,
+            expired_at = %lease.expires_at().to_rfc3339(),
+            "Breaking stale cross-host lease"
+        );
+        if let Err(err) = std::fs::remove_file(&file) {
+            warn!(lease = %file.display(), error = %err, "Failed to remove stale lease");
+        }
+    }
+    rival
+}
+
+/// A lease this daemon holds.
+#[derive(Debug)]
+pub struct Lease {
+    path: PathBuf,
+    record: LeaseRecord,
+}
+
+impl Lease {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self) -> &LeaseRecord {
+        &self.record
+    }
+
+    /// Push the expiry one TTL past now.
+    pub fn renew(&mut self) -> io::Result<()> {
+        self.record.renewed_at = Utc::now();
+        write_record(&self.path, &self.record)
+    }
+
+    /// Remove the lease file, unless another holder has replaced it.
+    pub fn release(self) {
+        remove_if_ours(&self.path, &self.record);
+    }
+}
+
+/// The lease at `path` and its sync conflict copies
+/// (`<stem> (conflicted copy).lease`, `<stem>.sync-conflict-….lease`).
+fn lease_files(path: &Path) -> Vec<(PathBuf, LeaseRecord)> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let name = name.to_string_lossy();
+    let stem = name.strip_suffix(".lease").unwrap_or(&name);
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|file| {
+            let Some(file_name) = file.file_name().map(|name| name.to_string_lossy()) else {
+                return false;
+            };
+            file_name == name
+                || (file_name.ends_with(".lease")
+                    && (file_name.starts_with(&format!("{stem}."))
+                        || file_name.starts_with(&format!("{stem} "))))
+        })
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .filter_map(|file| read_record(&file).map(|lease| (file, lease)))
+        .collect()
+}
+
+fn read_record(path: &Path) -> Option<LeaseRecord> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+fn create_new(path: &Path, record: &LeaseRecord) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    file.write_all(&serde_json::to_vec_pretty(record)?)?;
+    file.sync_all()
+}
+
+fn write_record(path: &Path, record: &LeaseRecord) -> io::Result<()> {
+    let temp = path.with_extension("lease.tmp");
+    std::fs::write(&temp, serde_json::to_vec_pretty(record)?)?;
+    std::fs::rename(&temp, path)
+}
+
+fn remove_if_ours(path: &Path, record: &LeaseRecord) {
+    if read_record(path).is_some_and(|current| current.same_lease(record)) {
+        if let Err(err) = std::fs::remove_file(path) {
+            warn!(lease = %path.display(), error = %err, "Failed to remove lease");
+        }
+    }
+}
+
+/// Runs a strategy only while this daemon holds the session's lease.
+pub struct CrossHostLease {
+    inner: Box<dyn ResumeStrategy>,
+    manager: LeaseManager,
+    events: Option<EventBroadcaster>,
+}
+
+impl CrossHostLease {
+    pub fn new(inner: Box<dyn ResumeStrategy>, manager: LeaseManager) -> Self {
+        Self {
+            inner,
+            manager,
+            events: None,
+        }
+    }
+
+    /// Publish `resume_deferred` notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn report_deferred(&self, ctx: &ResumeContext, holder: &LeaseRecord) {
+        if let Some(events) = &self.events {
+            events.publish(DomainEvent::ResumeDeferred {
+                timestamp: Utc::now(),
+                session_path: ctx.session_path.clone(),
+                stop_reason: ctx
+                    .stop_reason
+                    .metrics_reason_label()
+                    .unwrap_or("unknown")
+                    .to_string(),
+                until: holder.expires_at(),
+                reason: handled_elsewhere(holder),
+            });
+        }
+    }
+
+    /// Run the inner strategy, renewing `lease` until it finishes.
+    async fn run_holding(
+        &self,
+        ctx: &ResumeContext,
+        mut lease: Lease,
+    ) -> Result<ResumeOutcome, ResumeError> {
+        let interval = self.manager.renew_interval();
+        let mut renewals =
+            tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        let run = self.inner.execute(ctx);
+        tokio::pin!(run);
+        let result = loop {
+            tokio::select! {
+                result = &mut run => break result,
+                _ = renewals.tick() => {
+                    if let Err(err) = lease.renew() {
+                        warn!(lease = %lease.path().display(), error = %err, "Failed to renew cross-host lease");
+                    }
+                }
+            }
+        };
+        lease.release();
+        result
+    }
+}
+
+fn handled_elsewhere(holder: &LeaseRecord) -> String {
+    format!("being handled by host {}", holder.host)
+}
+
+#[async_trait]
+impl ResumeStrategy for CrossHostLease {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        match self.manager.acquire(&ctx.session_path).await {
+            Ok(LeaseAcquisition::Acquired(lease)) => self.run_holding(ctx, lease).await,
+            Ok(LeaseAcquisition::Held(holder)) => {
+                info!(
+                    session = %ctx.session_path.display(),
+                    holder = %holder.host,
+                    until = %holder.expires_at().to_rfc3339(),
+                    "Resume left to the daemon holding the cross-host lease"
+                );
+                self.report_deferred(ctx, &holder);
+                Ok(ResumeOutcome::skipped(handled_elsewhere(&holder)))
+            }
+            Err(err) => {
+                warn!(
+                    session = %ctx.session_path.display(),
+                    error = %err,
+                    "Failed to take cross-host lease; resuming without it"
+                );
+                self.inner.execute(ctx).await
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
+        self.inner.should_retry(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lease_path_is_next_to_the_session() {
+        assert_eq!(
+            LeaseManager::lease_path(Path::new("/sync/work/session.md")),
+            PathBuf::from("/sync/work/.palingenesis/session.lease")
+        );
+    }
+
+    #[test]
+    fn expiry_follows_the_last_renewal() {
+        let now = Utc::now();
+        let record = LeaseManager::new()
+            .with_ttl(Duration::from_secs(60))
+            .record(now - TimeDelta::seconds(90));
+        assert!(!record.is_fresh(now));
+        let renewed = LeaseRecord {
+            renewed_at: now - TimeDelta::seconds(30),
+            ..record
+        };
+        assert!(renewed.is_fresh(now));
+        assert_eq!(renewed.expires_at(), now + TimeDelta::seconds(30));
+    }
+}
@@ -0,0 +1,532 @@
+//! Maintenance windows (`resume.maintenance_windows`).
+//!
+//! During a window, stops are still classified and reported but strategy
+//! execution waits until the window ends. Waiting does not consume retries;
+//! the resume runs once, at the window boundary.
+//!
+//! Two spec forms are accepted:
+//! - weekly: `"Tue 02:00-04:00 UTC"` (an end before the start crosses midnight)
+//! - absolute: `"2025-12-24T00:00:00Z/2025-12-26T00:00:00Z"` (RFC3339 interval)
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveTime, TimeDelta, Utc, Weekday};
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::daemon::suspend::Clock;
+use crate::http::EventBroadcaster;
+use crate::resume::deferral::Deferral;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+
+/// Upper bound on back-to-back windows followed when looking for the end.
+const MAX_CHAINED_WINDOWS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MaintenanceWindowError {
+    #[error("empty window spec")]
+    Empty,
+
+    #[error("expected \"<day> HH:MM-HH:MM UTC\" or \"<start>/<end>\", got {0:?}")]
+    Malformed(String),
+
+    #[error("unknown weekday {0:?} (use Mon, Tue, ... or the full name)")]
+    InvalidWeekday(String),
+
+    #[error("invalid time {0:?} (expected HH:MM, 24-hour)")]
+    InvalidTime(String),
+
+    #[error("unsupported time zone {0:?} (only UTC is supported)")]
+    UnsupportedTimeZone(String),
+
+    #[error("window {0:?} is empty (start equals end)")]
+    ZeroLength(String),
+
+    #[error("invalid RFC3339 timestamp {value:?}: {message}")]
+    InvalidTimestamp { value: String, message: String },
+
+    #[error("interval end {end} is not after start {start}")]
+    EndNotAfterStart { start: String, end: String },
+}
+
+/// One parsed `resume.maintenance_windows` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaintenanceWindow {
+    /// Repeats every week, starting on `weekday` at `start` UTC.
+    Weekly {
+        weekday: Weekday,
+        start: NaiveTime,
+        length: TimeDelta,
+    },
+    /// A single interval.
+    Absolute {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+}
+
+impl MaintenanceWindow {
+    /// End of the occurrence that contains `at`, if any.
+    fn end_if_active(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Absolute { start, end } => (*start <= at && at < *end).then_some(*end),
+            Self::Weekly {
+                weekday,
+                start,
+                length,
+            } => {
+                // An occurrence is shorter than a week, so only the one that
+                // started this week or last week can contain `at`.
+                let days_since =
+                    (at.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+                let this_week = at.date_naive() - TimeDelta::days(i64::from(days_since));
+                [this_week, this_week - TimeDelta::weeks(1)]
+                    .into_iter()
+                    .map(|date| date.and_time(*start).and_utc())
+                    .find(|occurrence| *occurrence <= at && at < *occurrence + *length)
+                    .map(|occurrence| occurrence + *length)
+            }
+        }
+    }
+}
+
+impl FromStr for MaintenanceWindow {
+    type Err = MaintenanceWindowError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err(MaintenanceWindowError::Empty);
+        }
+        if let Some((start, end)) = spec.split_once('/') {
+            return parse_absolute(start.trim(), end.trim());
+        }
+        parse_weekly(spec)
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Weekly {
+                weekday,
+                start,
+                length,
+            } => {
+                let end = *start + *length;
+                write!(
+                    f,
+                    "{weekday} {}-{} UTC",
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                )
+            }
+            Self::Absolute { start, end } => {
+                write!(f, "{}/{}", start.to_rfc3339(), end.to_rfc3339())
+            }
+        }
+    }
+}
+
+fn parse_absolute(start: &str, end: &str) -> Result<MaintenanceWindow, MaintenanceWindowError> {
+    let start_at = parse_timestamp(start)?;
+    let end_at = parse_timestamp(end)?;
+    if end_at <= start_at {
+        return Err(MaintenanceWindowError::EndNotAfterStart {
+            start: start.to_string(),
+            end: end.to_string(),
+        });
+    }
+    Ok(MaintenanceWindow::Absolute {
+        start: start_at,
+        end: end_at,
+    })
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, MaintenanceWindowError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|err| MaintenanceWindowError::InvalidTimestamp {
+            value: value.to_string(),
+            message: err.to_string(),
+        })
+}
+
+fn parse_weekly(spec: &str) -> Result<MaintenanceWindow, MaintenanceWindowError> {
+    let parts: Vec<&str> = spec.split_whitespace().collect();
+    let (day, range) = match parts.as_slice() {
+        [day, range] => (*day, *range),
+        [day, range, zone] => {
+            if !zone.eq_ignore_ascii_case("UTC") && *zone != "Z" {
+                return Err(MaintenanceWindowError::UnsupportedTimeZone(
+                    zone.to_string(),
+                ));
+            }
+            (*day, *range)
+        }
+        _ => return Err(MaintenanceWindowError::Malformed(spec.to_string())),
+    };
+
+    let weekday = day
+        .parse::<Weekday>()
+        .map_err(|_| MaintenanceWindowError::InvalidWeekday(day.to_string()))?;
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| MaintenanceWindowError::Malformed(spec.to_string()))?;
+    let start = parse_clock_time(start)?;
+    let end = parse_clock_time(end)?;
+    if start == end {
+        return Err(MaintenanceWindowError::ZeroLength(spec.to_string()));
+    }
+
+    let mut length = end - start;
+    if length < TimeDelta::zero() {
+        length += TimeDelta::days(1);
+    }
+    Ok(MaintenanceWindow::Weekly {
+        weekday,
+        start,
+        length,
+    })
+}
+
+fn parse_clock_time(value: &str) -> Result<NaiveTime, MaintenanceWindowError> {
+    let valid = value.len() == 5 && value.as_bytes()[2] == b':';
+    valid
+        .then(|| NaiveTime::parse_from_str(value, "%H:%M").ok())
+        .flatten()
+        .ok_or_else(|| MaintenanceWindowError::InvalidTime(value.to_string()))
+}
+
+/// All configured maintenance windows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceSchedule {
+    windows: Vec<MaintenanceWindow>,
+}
+
+impl MaintenanceSchedule {
+    /// Parse every spec, failing on the first malformed one.
+    pub fn parse<S: AsRef<str>>(specs: &[S]) -> Result<Self, MaintenanceWindowError> {
+        let windows = specs
+            .iter()
+            .map(|spec| spec.as_ref().parse())
+            .collect::<Result<_, _>>()?;
+        Ok(Self { windows })
+    }
+
+    /// Schedule for `resume.maintenance_windows`; invalid entries are skipped.
+    ///
+    /// Validation rejects malformed specs, so this only drops entries when the
+    /// config was loaded without validating.
+    pub fn from_config(specs: &[String]) -> Self {
+        let windows = specs
+            .iter()
+            .filter_map(|spec| match spec.parse() {
+                Ok(window) => Some(window),
+                Err(err) => {
+                    warn!(%spec, error = %err, "Ignoring invalid maintenance window");
+                    None
+                }
+            })
+            .collect();
+        Self { windows }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    pub fn windows(&self) -> &[MaintenanceWindow] {
+        &self.windows
+    }
+
+    /// When the maintenance period containing `now` ends, or `None` outside one.
+    ///
+    /// Overlapping and back-to-back windows merge into one period.
+    pub fn active_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut until = None;
+        let mut at = now;
+        for _ in 0..MAX_CHAINED_WINDOWS {
+            let end = self
+                .windows
+                .iter()
+                .filter_map(|window| window.end_if_active(at))
+                .max();
+            match end {
+                Some(end) => {
+                    until = Some(end);
+                    at = end;
+                }
+                None => break,
+            }
+        }
+        until
+    }
+}
+
+/// Runs a strategy once the current maintenance period is over.
+pub struct MaintenanceDeferral {
+    inner: Box<dyn ResumeStrategy>,
+    schedule: MaintenanceSchedule,
+    deferral: Deferral,
+}
+
+impl MaintenanceDeferral {
+    pub fn new(inner: Box<dyn ResumeStrategy>, schedule: MaintenanceSchedule) -> Self {
+        Self {
+            inner,
+            schedule,
+            deferral: Deferral::default(),
+        }
+    }
+
+    /// Evaluate the schedule against this clock (for testing).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.deferral.clock = clock;
+        self
+    }
+
+    /// Publish `resume_deferred` notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.deferral.events = Some(events);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.deferral.cancel = Some(cancel);
+        self
+    }
+}
+
+#[async_trait]
+impl ResumeStrategy for MaintenanceDeferral {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        let mut reported = false;
+        while let Some(until) = self.schedule.active_until(self.deferral.now()) {
+            if !reported {
+                info!(
+                    session = %ctx.session_path.display(),
+                    until = %until.to_rfc3339(),
+                    strategy = self.inner.name(),
+                    "Resume deferred until maintenance window ends"
+                );
+                self.deferral
+                    .report_deferred(ctx, until, "maintenance window");
+                reported = true;
+            }
+            if !self.deferral.wait_until(until, "maintenance window").await {
+                info!("Deferred resume cancelled by shutdown");
+                return Ok(ResumeOutcome::skipped("shutdown during maintenance window"));
+            }
+        }
+        self.inner.execute(ctx).await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
+        self.inner.should_retry(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use crate::test_utils::TokioClock;
+    use std::sync::Mutex;
+
+    use crate::monitor::classifier::StopReason;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_weekly_and_absolute_specs() {
+        let weekly: MaintenanceWindow = "Tue 02:00-04:00 UTC".parse().unwrap();
+        assert_eq!(weekly.to_string(), "Tue 02:00-04:00 UTC");
+        let overnight: MaintenanceWindow = "sunday 23:30-01:00".parse().unwrap();
+        assert_eq!(overnight.to_string(), "Sun 23:30-01:00 UTC");
+
+        let absolute: MaintenanceWindow =
+            "2025-12-24T00:00:00Z/2025-12-26T00:00:00Z".parse().unwrap();
+        assert_eq!(
+            absolute,
+            MaintenanceWindow::Absolute {
+                start: at("2025-12-24T00:00:00Z"),
+                end: at("2025-12-26T00:00:00Z"),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_specs_precisely() {
+        let err = |spec: &str| spec.parse::<MaintenanceWindow>().unwrap_err();
+        assert_eq!(err("  "), MaintenanceWindowError::Empty);
+        assert_eq!(
+            err("Tue"),
+            MaintenanceWindowError::Malformed("Tue".to_string())
+        );
+        assert_eq!(
+            err("Tus 02:00-04:00 UTC"),
+            MaintenanceWindowError::InvalidWeekday("Tus".to_string())
+        );
+        assert_eq!(
+            err("Tue 2:00-04:00 UTC"),
+            MaintenanceWindowError::InvalidTime("2:00".to_string())
+        );
+        assert_eq!(
+            err("Tue 02:00-24:00 UTC"),
+            MaintenanceWindowError::InvalidTime("24:00".to_string())
+        );
+        assert_eq!(
+            err("Tue 02:00-04:00 CET"),
+            MaintenanceWindowError::UnsupportedTimeZone("CET".to_string())
+        );
+        assert_eq!(
+            err("Tue 02:00-02:00 UTC"),
+            MaintenanceWindowError::ZeroLength("Tue 02:00-02:00 UTC".to_string())
+        );
+        assert!(matches!(
+            err("2025-12-24/2025-12-26T00:00:00Z"),
+            MaintenanceWindowError::InvalidTimestamp { value, .. } if value == "2025-12-24"
+        ));
+        assert!(matches!(
+            err("2025-12-26T00:00:00Z/2025-12-24T00:00:00Z"),
+            MaintenanceWindowError::EndNotAfterStart { .. }
+        ));
+    }
+
+    #[test]
+    fn weekly_window_entry_and_exit() {
+        let schedule = MaintenanceSchedule::parse(&["Tue 02:00-04:00 UTC"]).unwrap();
+        // 2026-01-06 is a Tuesday.
+        assert_eq!(schedule.active_until(at("2026-01-06T01:59:59Z")), None);
+        assert_eq!(
+            schedule.active_until(at("2026-01-06T02:00:00Z")),
+            Some(at("2026-01-06T04:00:00Z"))
+        );
+        assert_eq!(
+            schedule.active_until(at("2026-01-13T03:59:59Z")),
+            Some(at("2026-01-13T04:00:00Z"))
+        );
+        assert_eq!(schedule.active_until(at("2026-01-06T04:00:00Z")), None);
+    }
+
+    #[test]
+    fn overnight_window_spans_midnight() {
+        let schedule = MaintenanceSchedule::parse(&["Sun 23:00-01:00 UTC"]).unwrap();
+        // 2026-01-05 is a Monday.
+        assert_eq!(
+            schedule.active_until(at("2026-01-05T00:30:00Z")),
+            Some(at("2026-01-05T01:00:00Z"))
+        );
+        assert_eq!(schedule.active_until(at("2026-01-05T23:30:00Z")), None);
+    }
+
+    #[test]
+    fn overlapping_and_adjacent_windows_merge() {
+        let schedule = MaintenanceSchedule::parse(&[
+            "Tue 02:00-04:00 UTC",
+            "Tue 03:00-05:00 UTC",
+            "2026-01-06T05:00:00Z/2026-01-06T06:30:00Z",
+        ])
+        .unwrap();
+        assert_eq!(
+            schedule.active_until(at("2026-01-06T02:30:00Z")),
+            Some(at("2026-01-06T06:30:00Z"))
+        );
+        // The absolute window only extends the first week.
+        assert_eq!(
+            schedule.active_until(at("2026-01-13T02:30:00Z")),
+            Some(at("2026-01-13T05:00:00Z"))
+        );
+    }
+
+    struct RecordingStrategy {
+        clock: Arc<dyn Clock>,
+        runs: Arc<Mutex<Vec<DateTime<Utc>>>>,
+    }
+
+    #[async_trait]
+    impl ResumeStrategy for RecordingStrategy {
+        async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+            self.runs.lock().unwrap().push(self.clock.wall().into());
+            Ok(ResumeOutcome::success(ctx.session_path.clone(), "resumed"))
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    fn deferral(
+        now: DateTime<Utc>,
+        specs: &[&str],
+    ) -> (MaintenanceDeferral, Arc<Mutex<Vec<DateTime<Utc>>>>) {
+        let clock = TokioClock::starting_at(now);
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let inner = RecordingStrategy {
+            clock: clock.clone(),
+            runs: Arc::clone(&runs),
+        };
+        let strategy =
+            MaintenanceDeferral::new(Box::new(inner), MaintenanceSchedule::parse(specs).unwrap())
+                .with_clock(clock);
+        (strategy, runs)
+    }
+
+    fn ctx() -> ResumeContext {
+        ResumeContext::new("/tmp/session.md".into(), StopReason::ContextExhausted(None))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_mid_window_resumes_exactly_at_boundary() {
+        let (strategy, runs) = deferral(at("2026-01-06T03:15:00Z"), &["Tue 02:00-04:00 UTC"]);
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let strategy = strategy.with_event_broadcaster(events);
+
+        let outcome = strategy.execute(&ctx()).await.unwrap();
+
+        assert!(outcome.is_success());
+        assert_eq!(*runs.lock().unwrap(), vec![at("2026-01-06T04:00:00Z")]);
+        match rx.try_recv().expect("deferral notification") {
+            NotificationEvent::ResumeDeferred { until, reason, .. } => {
+                assert_eq!(until, at("2026-01-06T04:00:00Z"));
+                assert_eq!(reason, "maintenance window");
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_outside_window_runs_immediately() {
+        let (strategy, runs) = deferral(at("2026-01-06T04:00:00Z"), &["Tue 02:00-04:00 UTC"]);
+        strategy.execute(&ctx()).await.unwrap();
+        assert_eq!(*runs.lock().unwrap(), vec![at("2026-01-06T04:00:00Z")]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_during_window_skips_resume() {
+        let (strategy, runs) = deferral(at("2026-01-06T02:00:00Z"), &["Tue 02:00-04:00 UTC"]);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let outcome = strategy
+            .with_cancellation(cancel)
+            .execute(&ctx())
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+        assert!(runs.lock().unwrap().is_empty());
+    }
+}
@@ -1,23 +1,67 @@
 //! Resume strategies module.
 
+pub mod assistant;
 pub mod backoff;
 pub mod backup;
+pub mod completion;
 pub mod context;
+pub mod cost;
+pub mod custom;
+pub mod deferral;
 pub mod error;
+pub mod exclusions;
+pub mod gates;
+pub mod git_context;
+pub mod lease;
+pub mod maintenance;
+pub mod model;
 pub mod new_session;
+pub mod next_step;
+pub mod observe;
 pub mod outcome;
+pub mod postmortem;
+pub mod progress;
+pub mod prompt_context;
+pub mod quota;
 pub mod same_session;
 pub mod selector;
+pub mod sidecar;
 pub mod strategy;
+pub mod throttle;
 pub mod time_saved;
+pub mod worktree;
 
+pub use assistant::{AssistantAdapter, ClassifyHints, ClaudeCodeAdapter, OpenCodeAdapter};
 pub use backoff::{Backoff, BackoffBuilder, BackoffConfig, BackoffError};
 pub use backup::{BackupConfig, BackupError, BackupHandler, SessionBackup};
+pub use completion::{CompletionError, CompletionOutcome, CompletionSummary, CompletionWorkflow};
 pub use context::ResumeContext;
+pub use cost::CostSummary;
+pub use custom::CommandStrategy;
 pub use error::ResumeError;
-pub use new_session::{NewSessionConfig, NewSessionStrategy, NextStepInfo, SessionCreator};
+pub use exclusions::{ExclusionMatch, SessionExclusions};
+pub use gates::{GateStatus, LinuxProbe, ResumeGates, ResumeGating, SystemProbe};
+pub use git_context::{GitCollector, GitContext};
+pub use lease::{CrossHostLease, Lease, LeaseAcquisition, LeaseManager, LeaseRecord};
+pub use maintenance::{
+    MaintenanceDeferral, MaintenanceSchedule, MaintenanceWindow, MaintenanceWindowError,
+};
+pub use model::ModelMismatch;
+#[cfg(feature = "opencode-api")]
+pub use new_session::ApiSessionCreator;
+pub use new_session::{
+    AdapterSessionCreator, NewSessionConfig, NewSessionStrategy, SessionCreator,
+};
+pub use next_step::{NextStepInfo, NextStepParseError};
+pub use observe::{ModeSwitch, ObserveGate};
 pub use outcome::ResumeOutcome;
+pub use postmortem::{FailureBundle, FailureDetails, FailureError, FailureRecord, FailureStore};
+pub use progress::ProgressSummary;
+pub use quota::{DEFAULT_QUOTA_RETRY_AFTER, QuotaSchedule, QuotaWait};
 pub use same_session::{ResumeTrigger, SameSessionConfig, SameSessionStrategy};
-pub use selector::{StrategySelector, UnknownStrategy};
+pub use selector::{Selection, StrategySelector, UnknownStrategy};
+pub use sidecar::{ResumeOverrides, SessionOverrides, SidecarError, StrategyPreference};
 pub use strategy::ResumeStrategy;
+pub use throttle::{RESUME_WINDOW, ResumeRateLimit, ResumeThrottle, ThrottleDecision};
 pub use time_saved::{TimeSavedCalculation, calculate_time_saved, load_metrics_config};
+pub use worktree::{WorktreeError, WorktreeManager};
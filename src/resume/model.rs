@@ -0,0 +1,70 @@
+//! Detection of assistant model changes across resumes.
+
+use std::fmt;
+
+/// The model a session continues from differs from the model it runs on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelMismatch {
+    /// Model of the session being continued.
+    pub previous: String,
+    /// Model of the new or resumed session.
+    pub current: String,
+}
+
+impl ModelMismatch {
+    /// Compare two model identifiers.
+    ///
+    /// Returns `None` when either side is unknown or both name the same model
+    /// (ignoring case and surrounding whitespace).
+    pub fn detect(previous: Option<&str>, current: Option<&str>) -> Option<Self> {
+        let previous = normalize(previous)?;
+        let current = normalize(current)?;
+        if previous.eq_ignore_ascii_case(current) {
+            return None;
+        }
+        Some(Self {
+            previous: previous.to_string(),
+            current: current.to_string(),
+        })
+    }
+
+    /// Note prepended to the continuation prompt.
+    pub fn prompt_note(&self) -> String {
+        format!(
+            "IMPORTANT: the previous session used model {}, but this session runs on {}. \
+             Ensure consistency with the work done so far.",
+            self.previous, self.current
+        )
+    }
+}
+
+impl fmt::Display for ModelMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.previous, self.current)
+    }
+}
+
+fn normalize(model: Option<&str>) -> Option<&str> {
+    model.map(str::trim).filter(|model| !model.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_different_models() {
+        let mismatch = ModelMismatch::detect(Some("anthropic/claude-opus"), Some("openai/gpt-5"))
+            .expect("mismatch");
+        assert_eq!(mismatch.previous, "anthropic/claude-opus");
+        assert_eq!(mismatch.current, "openai/gpt-5");
+        assert!(mismatch.prompt_note().contains("anthropic/claude-opus"));
+    }
+
+    #[test]
+    fn same_or_unknown_models_match() {
+        assert!(ModelMismatch::detect(Some("Claude-Opus"), Some(" claude-opus ")).is_none());
+        assert!(ModelMismatch::detect(None, Some("openai/gpt-5")).is_none());
+        assert!(ModelMismatch::detect(Some("openai/gpt-5"), Some("")).is_none());
+    }
+}
@@ -1,20 +1,48 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use chrono::Utc;
+use serde_json::Value;
 use tokio::fs;
 use tracing::{Span, debug, info, warn};
 
-use crate::config::paths::Paths;
+use crate::config::paths::{Paths, safe_path};
+use crate::config::schema::PromptContextConfig;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::monitor::classifier::StopReason;
+use crate::monitor::frontmatter::parse_session;
+use crate::monitor::incident;
 use crate::monitor::session::{Session, StepValue};
-use crate::resume::backup::{BackupConfig, BackupHandler, SessionBackup};
+#[cfg(feature = "opencode-api")]
+use crate::monitor::session_index::SessionIndex;
+#[cfg(feature = "opencode-api")]
+use crate::opencode::{OpenCodeApiError, OpenCodeClient};
+use crate::resume::assistant::{AssistantAdapter, OpenCodeAdapter, run_command_with_postmortem};
+use crate::resume::backup::{
+    BackupConfig, BackupError, BackupHandler, DEFAULT_FILENAME_TEMPLATE, SessionBackup,
+};
+use crate::resume::cost;
+use crate::resume::git_context::{self, GitCollector, GitContext};
+use crate::resume::model::ModelMismatch;
+use crate::resume::next_step::{self, DEFAULT_MAX_NEXT_STEP_BYTES, NextStepInfo};
+use crate::resume::postmortem::{DEFAULT_MAX_BUNDLES, FailureDetails, FailureStore};
+use crate::resume::progress::{ProgressSummary, next_step_from_completed};
+use crate::resume::prompt_context;
+use crate::resume::sidecar::SessionOverrides;
+use crate::resume::worktree::WorktreeManager;
 use crate::resume::{
     ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy, calculate_time_saved,
     load_metrics_config,
 };
-use crate::state::{AuditLogger, CurrentSession, StateStore};
+use crate::state::audit::record_rejected_path;
+use crate::state::{
+    AuditLogger, CurrentSession, OrphanedSession, SessionStatus, StateBackend, StateHandle,
+    WorktreeRecord,
+};
 use crate::telemetry::Metrics;
 
 /// Configuration for new-session resume.
@@ -23,6 +51,9 @@ pub struct NewSessionConfig {
     /// Name of Next-step file.
     pub next_step_filename: String,
     /// Prompt template for continuation.
+    ///
+    /// Supports `{step}`, `{description}`, `{context}`, `{progress}`, `{percent}`,
+    /// `{steps_remaining}`, `{git_branch}` and `{git_sha}`.
     pub prompt_template: String,
     /// Enable session backup before new session.
     pub enable_backup: bool,
@@ -30,8 +61,26 @@ pub struct NewSessionConfig {
     pub max_backups: usize,
     /// Timestamp format for backup filenames.
     pub backup_timestamp_format: String,
+    /// Directory backups are written to (`None` keeps them next to the session).
+    pub backup_dir: Option<PathBuf>,
+    /// Backup path template under the backup directory.
+    pub backup_filename_template: String,
+    /// Reuse the newest backup instead of copying an identical session again.
+    pub backup_dedupe: bool,
     /// Verify backup after creation.
     pub verify_backup: bool,
+    /// Follow symlinks for session and Next-step files (targets must stay under the session root).
+    pub follow_symlinks: bool,
+    /// Directory session and Next-step files must stay under; `None` uses the session's own directory.
+    pub session_root: Option<PathBuf>,
+    /// Start the new session on the previous session's model, failing if the creator cannot.
+    pub enforce_model: bool,
+    /// Bytes of Next-step.md read and embedded in the prompt.
+    pub max_next_step_bytes: usize,
+    /// Postmortem bundles kept for failed new-session commands (0 disables capture).
+    pub max_failure_bundles: usize,
+    /// Sections making up the `{context}` placeholder.
+    pub context: PromptContextConfig,
 }
 
 impl Default for NewSessionConfig {
@@ -44,60 +93,180 @@ impl Default for NewSessionConfig {
             enable_backup: true,
             max_backups: 10,
             backup_timestamp_format: "%Y%m%d-%H%M%S".to_string(),
+            backup_dir: None,
+            backup_filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
+            backup_dedupe: true,
             verify_backup: true,
+            follow_symlinks: false,
+            session_root: None,
+            enforce_model: false,
+            max_next_step_bytes: DEFAULT_MAX_NEXT_STEP_BYTES,
+            max_failure_bundles: DEFAULT_MAX_BUNDLES,
+            context: PromptContextConfig::default(),
         }
     }
 }
 
-/// Information extracted from Next-step.md.
-#[derive(Debug, Clone)]
-pub struct NextStepInfo {
-    /// Step number to continue from.
-    pub step_number: u32,
-    /// Description of the step.
-    pub description: String,
-    /// Full content of Next-step.md.
-    pub raw_content: String,
+impl NewSessionConfig {
+    /// Settings for the backup taken before a new session starts.
+    pub fn backup_config(&self) -> BackupConfig {
+        BackupConfig {
+            max_backups: self.max_backups,
+            timestamp_format: self.backup_timestamp_format.clone(),
+            verify_backup: self.verify_backup,
+            follow_symlinks: self.follow_symlinks,
+            session_root: self.session_root.clone(),
+            backup_dir: self.backup_dir.clone(),
+            filename_template: self.backup_filename_template.clone(),
+            dedupe: self.backup_dedupe,
+        }
+    }
 }
 
 #[async_trait]
 pub trait SessionCreator: Send + Sync {
     async fn create(&self, prompt: &str, session_dir: &Path) -> Result<PathBuf, ResumeError>;
+
+    /// Create a session running on `model`.
+    ///
+    /// Creators that cannot choose the model fail with
+    /// [`ResumeError::ModelUnavailable`] rather than silently using a default.
+    async fn create_with_model(
+        &self,
+        prompt: &str,
+        session_dir: &Path,
+        model: &str,
+    ) -> Result<PathBuf, ResumeError> {
+        let _ = (prompt, session_dir);
+        Err(ResumeError::ModelUnavailable {
+            model: model.to_string(),
+            reason: "this session creator cannot select a model".to_string(),
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
-struct CommandSessionCreator;
+/// Creates sessions with an assistant's new-session command.
+///
+/// Used for sessions requested from outside a resume, where there is no
+/// stopped session to derive the command from.
+pub struct AdapterSessionCreator {
+    adapter: Arc<dyn AssistantAdapter>,
+}
+
+impl AdapterSessionCreator {
+    pub fn new(adapter: Arc<dyn AssistantAdapter>) -> Self {
+        Self { adapter }
+    }
+}
 
 #[async_trait]
-impl SessionCreator for CommandSessionCreator {
+impl SessionCreator for AdapterSessionCreator {
     async fn create(&self, prompt: &str, session_dir: &Path) -> Result<PathBuf, ResumeError> {
-        let output = tokio::process::Command::new("opencode")
-            .arg("new")
-            .arg("--prompt")
-            .arg(prompt)
-            .arg("--workdir")
-            .arg(session_dir)
-            .output()
-            .await
-            .map_err(ResumeError::Io)?;
+        let ctx = ResumeContext::new(
+            session_dir.join("session.md"),
+            StopReason::Unknown("new session requested".to_string()),
+        )
+        .with_workdir(session_dir.to_path_buf());
+        let command = self.adapter.build_new_session_command(&ctx, prompt)?;
+        let stdout = run_command_with_postmortem(command, None).await?;
+        Ok(self.adapter.new_session_path(&ctx, &stdout))
+    }
+}
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            return Err(ResumeError::CommandFailed {
-                command: "opencode new".to_string(),
-                stderr,
-            });
+/// Creates sessions through the OpenCode server API, which can pin the model.
+#[cfg(feature = "opencode-api")]
+pub struct ApiSessionCreator {
+    client: OpenCodeClient,
+    index: Option<SessionIndex>,
+}
+
+#[cfg(feature = "opencode-api")]
+impl ApiSessionCreator {
+    pub fn new(client: OpenCodeClient) -> Self {
+        Self {
+            client,
+            index: SessionIndex::default_location(),
         }
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let session_path = stdout
-            .lines()
-            .find(|line| line.contains("session:"))
-            .and_then(|line| line.split("session:").nth(1))
-            .map(|value| PathBuf::from(value.trim()))
-            .unwrap_or_else(|| session_dir.join("session.md"));
+    /// Resolve created session IDs to files through this index.
+    pub fn with_session_index(mut self, index: SessionIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
 
-        Ok(session_path)
+    fn session_path(&self, id: &str, session_dir: &Path) -> PathBuf {
+        self.index
+            .as_ref()
+            .and_then(|index| index.lookup_by_id(id))
+            .map(|session| session.path)
+            .unwrap_or_else(|| session_dir.join(format!("{id}.md")))
+    }
+
+    /// Model the server reports for a session, from the create response or the session list.
+    async fn reported_model(&self, id: &str, response_model: Option<String>) -> Option<String> {
+        if response_model.is_some() {
+            return response_model;
+        }
+        let sessions = self.client.list_sessions().await.ok()?;
+        sessions
+            .into_iter()
+            .find(|session| session.id == id)
+            .and_then(|session| session.model().map(str::to_string))
+    }
+}
+
+#[cfg(feature = "opencode-api")]
+#[async_trait]
+impl SessionCreator for ApiSessionCreator {
+    async fn create(&self, prompt: &str, session_dir: &Path) -> Result<PathBuf, ResumeError> {
+        let response = self
+            .client
+            .create_session(prompt)
+            .await
+            .map_err(|err| match err {
+                OpenCodeApiError::Cancelled => ResumeError::Cancelled,
+                err => ResumeError::CommandFailed {
+                    command: "POST /session".to_string(),
+                    stderr: err.to_string(),
+                    postmortem: None,
+                },
+            })?;
+        Ok(self.session_path(&response.id, session_dir))
+    }
+
+    async fn create_with_model(
+        &self,
+        prompt: &str,
+        session_dir: &Path,
+        model: &str,
+    ) -> Result<PathBuf, ResumeError> {
+        let response = self
+            .client
+            .create_session_with_model(prompt, Some(model))
+            .await
+            .map_err(|err| match err {
+                OpenCodeApiError::Cancelled => ResumeError::Cancelled,
+                err => ResumeError::ModelUnavailable {
+                    model: model.to_string(),
+                    reason: format!("OpenCode rejected the request: {err}"),
+                },
+            })?;
+
+        let reported = self.reported_model(&response.id, response.model).await;
+        if let Some(mismatch) = ModelMismatch::detect(Some(model), reported.as_deref()) {
+            return Err(ResumeError::ModelUnavailable {
+                model: model.to_string(),
+                reason: format!(
+                    "OpenCode started session {} on {} instead",
+                    response.id, mismatch.current
+                ),
+            });
+        }
+        if reported.is_none() {
+            debug!(session = %response.id, "OpenCode did not report the new session's model");
+        }
+        Ok(self.session_path(&response.id, session_dir))
     }
 }
 
@@ -105,33 +274,44 @@ impl SessionCreator for CommandSessionCreator {
 pub struct NewSessionStrategy {
     config: NewSessionConfig,
     backup: Arc<dyn BackupHandler>,
-    creator: Arc<dyn SessionCreator>,
+    adapter: Arc<dyn AssistantAdapter>,
+    creator: Option<Arc<dyn SessionCreator>>,
+    state: Option<StateHandle>,
+    events: Option<EventBroadcaster>,
+    git: Option<GitCollector>,
+    worktrees: Option<WorktreeManager>,
+    failures: Option<FailureStore>,
+    overrides: SessionOverrides,
 }
 
 impl NewSessionStrategy {
     pub fn new() -> Self {
         let config = NewSessionConfig::default();
-        let backup_config = BackupConfig {
-            max_backups: config.max_backups,
-            timestamp_format: config.backup_timestamp_format.clone(),
-            verify_backup: config.verify_backup,
-        };
         Self {
-            backup: Arc::new(SessionBackup::with_config(backup_config)),
-            creator: Arc::new(CommandSessionCreator),
+            backup: Arc::new(SessionBackup::with_config(config.backup_config())),
+            adapter: Arc::new(OpenCodeAdapter::new()),
+            creator: None,
+            state: None,
+            events: None,
+            git: None,
+            worktrees: None,
+            failures: None,
+            overrides: SessionOverrides::default(),
             config,
         }
     }
 
     pub fn with_config(config: NewSessionConfig) -> Self {
-        let backup_config = BackupConfig {
-            max_backups: config.max_backups,
-            timestamp_format: config.backup_timestamp_format.clone(),
-            verify_backup: config.verify_backup,
-        };
         Self {
-            backup: Arc::new(SessionBackup::with_config(backup_config)),
-            creator: Arc::new(CommandSessionCreator),
+            backup: Arc::new(SessionBackup::with_config(config.backup_config())),
+            adapter: Arc::new(OpenCodeAdapter::new()),
+            creator: None,
+            state: None,
+            events: None,
+            git: None,
+            worktrees: None,
+            failures: None,
+            overrides: SessionOverrides::default(),
             config,
         }
     }
@@ -142,23 +322,129 @@ impl NewSessionStrategy {
     }
 
     pub fn with_session_creator<T: SessionCreator + 'static>(mut self, creator: T) -> Self {
-        self.creator = Arc::new(creator);
+        self.creator = Some(Arc::new(creator));
+        self
+    }
+
+    /// Start new sessions with `adapter`'s command when no creator is set.
+    pub fn with_adapter(mut self, adapter: Arc<dyn AssistantAdapter>) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    /// Use a specific state store instead of the default state file.
+    pub fn with_state_store<T: StateBackend + 'static>(mut self, store: T) -> Self {
+        self.state = Some(StateHandle::new(store));
+        self
+    }
+
+    /// Share an existing state handle instead of the global one.
+    pub fn with_state_handle(mut self, handle: StateHandle) -> Self {
+        self.state = Some(handle);
         self
     }
 
+    /// Record the workspace git state in the resume history.
+    pub fn with_git_collector(mut self, collector: GitCollector) -> Self {
+        self.git = Some(collector);
+        self
+    }
+
+    /// Start each new session in a fresh git worktree of the original workspace.
+    pub fn with_worktree_manager(mut self, manager: WorktreeManager) -> Self {
+        self.worktrees = Some(manager);
+        self
+    }
+
+    /// Publish orphaned-session warnings on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Write failure postmortems here instead of the state directory.
+    pub fn with_failure_store(mut self, store: FailureStore) -> Self {
+        self.failures = Some(store);
+        self
+    }
+
+    /// Apply the session's sidecar overrides (see [`crate::resume::sidecar`]).
+    ///
+    /// `prompt_template` and `enable_backup` replace the configured values.
+    pub fn with_overrides(mut self, overrides: SessionOverrides) -> Self {
+        if let Some(template) = &overrides.overrides.prompt_template {
+            self.config.prompt_template = template.clone();
+        }
+        if let Some(enable_backup) = overrides.overrides.enable_backup {
+            self.config.enable_backup = enable_backup;
+        }
+        self.overrides = overrides;
+        self
+    }
+
+    fn failure_store(&self) -> Option<FailureStore> {
+        if let Some(store) = &self.failures {
+            return Some(store.clone());
+        }
+        match Paths::ensure_state_dir() {
+            Ok(state_dir) => Some(
+                FailureStore::new(&state_dir).with_max_bundles(self.config.max_failure_bundles),
+            ),
+            Err(err) => {
+                warn!(error = %err, "Failed to locate failure postmortem directory");
+                None
+            }
+        }
+    }
+
+    async fn capture_git(&self, ctx: &ResumeContext) -> Option<GitContext> {
+        match &self.git {
+            Some(collector) => Some(collector.capture_for(ctx).await),
+            None => None,
+        }
+    }
+
+    fn state(&self) -> StateHandle {
+        self.state
+            .clone()
+            .unwrap_or_else(StateHandle::global_or_default)
+    }
+
     async fn read_next_step(
         &self,
+        ctx: &ResumeContext,
         session_dir: &Path,
     ) -> Result<Option<NextStepInfo>, ResumeError> {
         let next_step_path = session_dir.join(&self.config.next_step_filename);
-        match fs::read_to_string(&next_step_path).await {
-            Ok(content) => {
+        let root = self.config.session_root.as_deref().unwrap_or(session_dir);
+        let next_step_path = match safe_path(&next_step_path, root, self.config.follow_symlinks) {
+            Ok(path) => path,
+            Err(err) => {
+                record_rejected_path(&next_step_path, &err);
+                return Ok(None);
+            }
+        };
+        match next_step::read_capped(&next_step_path, self.config.max_next_step_bytes).await {
+            Ok((content, truncated)) => {
                 debug!(path = %next_step_path.display(), "Found Next-step.md");
-                if let Some(info) = self.parse_next_step(&content) {
-                    Ok(Some(info))
-                } else {
-                    warn!(path = %next_step_path.display(), "Failed to parse Next-step.md");
-                    Ok(None)
+                if truncated {
+                    warn!(
+                        path = %next_step_path.display(),
+                        max_bytes = self.config.max_next_step_bytes,
+                        "Next-step.md exceeds resume.max_next_step_bytes; truncating"
+                    );
+                }
+                match next_step::parse(&content, truncated) {
+                    Ok(info) => Ok(Some(info)),
+                    Err(err) => {
+                        warn!(
+                            path = %next_step_path.display(),
+                            "Failed to parse Next-step.md: {}",
+                            err.describe()
+                        );
+                        self.report_next_step_invalid(ctx, &next_step_path, err);
+                        Ok(None)
+                    }
                 }
             }
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
@@ -169,86 +455,126 @@ impl NewSessionStrategy {
         }
     }
 
-    fn parse_next_step(&self, content: &str) -> Option<NextStepInfo> {
-        let mut step_number = None;
-        let mut description: Option<String> = None;
-
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-
-            if step_number.is_none() {
-                if let Some((step, desc)) = parse_step_line(trimmed) {
-                    step_number = Some(step);
-                    if !desc.is_empty() {
-                        description = Some(desc);
-                    }
-                    continue;
-                }
+    /// Write a Next-step.md describing where the workflow stopped.
+    ///
+    /// Only called when no Next-step.md exists, so user-authored files are
+    /// never overwritten.
+    async fn generate_next_step(
+        &self,
+        session_dir: &Path,
+        progress: &ProgressSummary,
+        info: &mut NextStepInfo,
+    ) {
+        let next_step_path = session_dir.join(&self.config.next_step_filename);
+        let content = progress.render_next_step(&info.description);
+        match fs::write(&next_step_path, &content).await {
+            Ok(()) => {
+                info!(path = %next_step_path.display(), "Generated Next-step.md");
+                info.raw_content = content.into();
             }
-
-            if description.is_none() && !trimmed.starts_with('#') {
-                description = Some(trimmed.to_string());
+            Err(err) => {
+                warn!(path = %next_step_path.display(), error = %err, "Failed to write Next-step.md");
             }
         }
+    }
 
-        let step_number = step_number?;
-        let description =
-            description.unwrap_or_else(|| format!("Continue from step {}", step_number));
-
-        Some(NextStepInfo {
-            step_number,
-            description,
-            raw_content: content.to_string(),
-        })
+    fn report_next_step_invalid(
+        &self,
+        ctx: &ResumeContext,
+        next_step_path: &Path,
+        err: next_step::NextStepParseError,
+    ) {
+        if let Some(events) = &self.events {
+            let event = DomainEvent::NextStepInvalid {
+                timestamp: Utc::now(),
+                session_path: ctx.session_path.clone(),
+                next_step_path: next_step_path.to_path_buf(),
+                unmatched_lines: err.unmatched_lines,
+            };
+            events.publish(event);
+        }
     }
 
-    fn calculate_from_steps_completed(&self, session: &Session) -> u32 {
-        let steps = steps_completed_from_session(session);
-        steps.iter().max().copied().unwrap_or(0).saturating_add(1)
-    }
-
-    fn build_context_summary(&self, ctx: &ResumeContext, info: &NextStepInfo) -> String {
-        let mut lines = vec![format!("Previous session: {}", ctx.session_path.display())];
-
-        if let Some(session) = &ctx.session_metadata {
-            let steps = steps_completed_from_session(session);
-            if !steps.is_empty() {
-                lines.push(format!(
-                    "Steps completed: {}",
-                    steps
-                        .iter()
-                        .map(|step| step.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ));
+    /// Create the worktree a new session runs in.
+    ///
+    /// Returns `None` (run in place) when worktrees are off or creation fails.
+    async fn prepare_worktree(&self, ctx: &ResumeContext) -> Option<WorktreeRecord> {
+        let manager = self.worktrees.as_ref()?;
+        let workspace = git_context::workspace_for(ctx);
+        let stem = ctx
+            .session_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "session".to_string());
+        let now = Utc::now();
+        match manager.create(&workspace, &stem, now).await {
+            Ok((worktree, branch)) => {
+                info!(
+                    workspace = %workspace.display(),
+                    worktree = %worktree.display(),
+                    branch = %branch,
+                    "Created worktree for new session"
+                );
+                Some(WorktreeRecord {
+                    workspace,
+                    worktree,
+                    branch,
+                    source_session: ctx.session_path.clone(),
+                    session: PathBuf::new(),
+                    created_at: now,
+                })
             }
-
-            if let Some(last_step) = session.state.last_step {
-                lines.push(format!("Last step: {}", last_step));
+            Err(err) => {
+                warn!(
+                    workspace = %workspace.display(),
+                    error = %err,
+                    "Failed to create worktree; starting new session in place"
+                );
+                None
             }
         }
+    }
 
-        lines.push(format!("Stop reason: {:?}", ctx.stop_reason));
-
-        if !info.raw_content.trim().is_empty() {
-            lines.push("Next-step details:".to_string());
-            lines.push(info.raw_content.trim().to_string());
-        } else {
-            lines.push(format!("Continuation: {}", info.description));
+    fn report_worktree_created(&self, record: &WorktreeRecord) {
+        if let Some(events) = &self.events {
+            let event = DomainEvent::WorktreeCreated {
+                timestamp: Utc::now(),
+                session_path: record.source_session.clone(),
+                workspace: record.workspace.clone(),
+                worktree_path: record.worktree.clone(),
+                branch: record.branch.clone(),
+            };
+            events.publish(event);
         }
+    }
 
-        lines.join("\n")
+    fn calculate_from_steps_completed(&self, session: &Session) -> u32 {
+        next_step_from_completed(&steps_completed_from_session(session))
     }
 
-    fn generate_prompt(&self, info: &NextStepInfo, ctx: &ResumeContext) -> String {
-        let context = self.build_context_summary(ctx, info);
-        self.config
-            .prompt_template
+    fn generate_prompt(
+        &self,
+        info: &NextStepInfo,
+        ctx: &ResumeContext,
+        progress: &ProgressSummary,
+        git: Option<&GitContext>,
+    ) -> String {
+        let context = prompt_context::assemble(&self.config.context, ctx, info).text();
+        let percent = progress
+            .percent()
+            .map(|percent| format!("{percent}%"))
+            .unwrap_or_else(|| "unknown".to_string());
+        let steps_remaining = if info.remaining_steps.is_empty() {
+            "unknown".to_string()
+        } else {
+            join_steps(&info.remaining_steps)
+        };
+        GitContext::render(git, &self.config.prompt_template)
             .replace("{step}", &info.step_number.to_string())
             .replace("{description}", &info.description)
+            .replace("{progress}", &progress.describe())
+            .replace("{percent}", &percent)
+            .replace("{steps_remaining}", &steps_remaining)
             .replace("{context}", &context)
     }
 
@@ -257,6 +583,7 @@ impl NewSessionStrategy {
         ctx: &ResumeContext,
         new_session_path: PathBuf,
         next_step: &NextStepInfo,
+        model: Option<String>,
     ) -> CurrentSession {
         let steps = ctx
             .session_metadata
@@ -275,49 +602,204 @@ impl NewSessionStrategy {
             steps_completed: steps.clone(),
             last_step,
             total_steps: steps.len() as u32,
+            status: SessionStatus::Active,
+            model,
         }
     }
 
-    fn update_state_on_resume(
+    async fn update_state_on_resume(
         &self,
         ctx: &ResumeContext,
-        new_session_path: PathBuf,
-        next_step: &NextStepInfo,
+        current: CurrentSession,
+        worktree: Option<WorktreeRecord>,
         wait_duration: Duration,
         metrics: Option<&Metrics>,
     ) -> Result<(), ResumeError> {
         let metrics_config = load_metrics_config();
-        let store = StateStore::new();
-        let mut state = store.load();
-
-        state.stats.total_resumes = state.stats.total_resumes.saturating_add(1);
-        state.stats.saves_count = state.stats.saves_count.saturating_add(1);
-        state.stats.last_resume = Some(Utc::now());
-        state.current_session = Some(self.build_current_session(ctx, new_session_path, next_step));
-
         let calculation = calculate_time_saved(wait_duration, &metrics_config);
-        state.stats.time_saved_seconds += calculation.total_saved_seconds;
 
-        store
-            .save(&state)
+        let (cumulative_saved, cost) = self
+            .state()
+            .update_critical_async(|state| {
+                state.stats.total_resumes = state.stats.total_resumes.saturating_add(1);
+                state.stats.saves_count = state.stats.saves_count.saturating_add(1);
+                state.stats.last_resume = Some(Utc::now());
+                state.current_session = Some(current);
+                if let Some(worktree) = worktree {
+                    state.record_worktree(worktree);
+                }
+                state.stats.time_saved_seconds += calculation.total_saved_seconds;
+                // Charged to the stopped session, whose usage ended with it.
+                let cost = cost::record_resume_cost(
+                    state,
+                    &metrics_config,
+                    &ctx.session_path,
+                    ctx.session_metadata.as_ref(),
+                    calculation.total_saved_seconds,
+                );
+                (state.stats.time_saved_seconds, cost)
+            })
+            .await
             .map_err(|err| ResumeError::Config(format!("state store error: {err}")))?;
 
         if let Some(metrics) = metrics {
             metrics.record_save();
             metrics.record_time_saved(calculation.total_saved_seconds);
+            metrics.record_resume_cost(&cost);
         }
 
         info!(
             wait_seconds = calculation.wait_duration_seconds,
             manual_restart_seconds = calculation.manual_restart_seconds,
             total_saved = calculation.total_saved_seconds,
-            cumulative_saved = state.stats.time_saved_seconds,
+            cumulative_saved,
+            cost = %cost::format_amount(cost.cost, &metrics_config.currency),
+            value_saved = %cost::format_amount(cost.value_saved, &metrics_config.currency),
             "Time saved by resume"
         );
 
         Ok(())
     }
 
+    /// Compensate for a failure after `opencode new` succeeded.
+    ///
+    /// The new file is recorded as orphaned so the next detection cycle does
+    /// not pick it as the current session, and a warning is published. A
+    /// failed state write is kept for the flusher, so the orphan is also
+    /// taken back out of `current_session`.
+    async fn record_orphan(&self, ctx: &ResumeContext, orphan_path: &Path, err: &ResumeError) {
+        let reason = err.to_string();
+        warn!(
+            path = %orphan_path.display(),
+            reason = %reason,
+            "New session orphaned after resume failure"
+        );
+
+        let orphan = OrphanedSession::new(orphan_path.to_path_buf(), reason.clone());
+        if let Err(store_err) = self
+            .state()
+            .update_critical_async(|state| {
+                if state
+                    .current_session
+                    .as_ref()
+                    .is_some_and(|session| session.path == orphan_path)
+                {
+                    state.current_session = None;
+                }
+                state.record_orphan(orphan);
+            })
+            .await
+        {
+            warn!(
+                path = %orphan_path.display(),
+                error = %store_err,
+                "Failed to record orphaned session"
+            );
+        }
+
+        if let Some(events) = &self.events {
+            let event = DomainEvent::SessionOrphaned {
+                timestamp: Utc::now(),
+                session_path: orphan_path.to_path_buf(),
+                source_session: ctx.session_path.clone(),
+                reason,
+            };
+            events.publish(event);
+        }
+    }
+
+    /// Publish a `resume_failed` event when the new session could not be started.
+    fn report_resume_failed(
+        &self,
+        ctx: &ResumeContext,
+        err: &ResumeError,
+        progress: &ProgressSummary,
+    ) {
+        if let Some(events) = &self.events {
+            let event = DomainEvent::ResumeFailed {
+                timestamp: Utc::now(),
+                session_path: ctx.session_path.clone(),
+                strategy: self.name().to_string(),
+                error: err.to_string(),
+                progress: Some(progress.describe()),
+                percent: progress.percent(),
+                incident: ctx.incident.clone(),
+            };
+            events.publish(event);
+        }
+    }
+
+    /// Model recorded in state for `session_path` when it was tracked.
+    fn recorded_model(&self, session_path: &Path) -> Option<String> {
+        self.state()
+            .snapshot()
+            .current_session
+            .filter(|session| session.path == session_path)
+            .and_then(|session| session.model)
+    }
+
+    async fn create_session(
+        &self,
+        ctx: &ResumeContext,
+        prompt: &Arc<str>,
+        session_dir: &Path,
+        model: Option<&str>,
+    ) -> Result<PathBuf, ResumeError> {
+        match (model, &self.creator) {
+            (Some(model), Some(creator)) => {
+                creator.create_with_model(prompt, session_dir, model).await
+            }
+            (Some(model), None) => Err(ResumeError::ModelUnavailable {
+                model: model.to_string(),
+                reason: format!("the {} command cannot select a model", self.adapter.name()),
+            }),
+            (None, Some(creator)) => creator.create(prompt, session_dir).await,
+            (None, None) => {
+                let command = self.adapter.build_new_session_command(ctx, prompt)?;
+                let store = self.failure_store();
+                let details = FailureDetails {
+                    session_path: ctx.session_path.clone(),
+                    prompt: Some(Arc::clone(prompt)),
+                    stop_reason: Some(format!("{:?}", ctx.stop_reason)),
+                    evidence: Arc::clone(&ctx.evidence),
+                    ..FailureDetails::default()
+                };
+                let stdout = run_command_with_postmortem(
+                    command,
+                    store.as_ref().map(|store| (store, &details)),
+                )
+                .await?;
+                Ok(self.adapter.new_session_path(ctx, &stdout))
+            }
+        }
+    }
+
+    fn report_model_mismatch(
+        &self,
+        session_path: &Path,
+        mismatch: &ModelMismatch,
+        audit_logger: Option<&AuditLogger>,
+    ) {
+        warn!(
+            session = %session_path.display(),
+            previous_model = %mismatch.previous,
+            current_model = %mismatch.current,
+            "Session model changed across resume"
+        );
+        if let Some(logger) = audit_logger {
+            let _ = logger.log_model_mismatch(session_path, &mismatch.previous, &mismatch.current);
+        }
+        if let Some(events) = &self.events {
+            let event = DomainEvent::ModelChanged {
+                timestamp: Utc::now(),
+                session_path: session_path.to_path_buf(),
+                previous_model: mismatch.previous.clone(),
+                current_model: mismatch.current.clone(),
+            };
+            events.publish(event);
+        }
+    }
+
     fn audit_logger() -> Option<AuditLogger> {
         match Paths::ensure_state_dir() {
             Ok(state_dir) => Some(AuditLogger::new(&state_dir)),
@@ -345,14 +827,25 @@ impl ResumeStrategy for NewSessionStrategy {
         let span = Span::current();
         span.record("wait_duration_ms", 0);
         let metrics = Metrics::global();
+        let metrics_reason = ctx.stop_reason.metrics_reason_label().unwrap_or("manual");
         if let Some(metrics) = metrics.as_ref() {
-            let reason = ctx.stop_reason.metrics_reason_label().unwrap_or("manual");
             metrics.set_retry_attempts(ctx.attempt_number);
-            metrics.record_resume_started(reason);
+            metrics.record_resume_started(metrics_reason);
         }
+        let git_at_stop = match &ctx.git_at_stop {
+            Some(git) => Some(git.clone()),
+            None => self.capture_git(ctx).await,
+        };
         let audit_logger = Self::audit_logger();
         if let Some(logger) = &audit_logger {
-            let _ = logger.log_resume_started(&ctx.session_path, &format!("{:?}", ctx.stop_reason));
+            let mut metadata = git_context::audit_metadata(git_at_stop.as_ref(), None);
+            metadata.extend(self.overrides.audit_metadata());
+            metadata.extend(incident::audit_metadata(ctx.incident.as_deref()));
+            let _ = logger.log_resume_started_with(
+                &ctx.session_path,
+                &format!("{:?}", ctx.stop_reason),
+                metadata,
+            );
         }
 
         let session_dir = match ctx.session_path.parent() {
@@ -367,7 +860,7 @@ impl ResumeStrategy for NewSessionStrategy {
                         false,
                         Some(err.error_label()),
                     );
-                    metrics.set_retry_attempts(0);
+                    metrics.end_retry_cycle(metrics_reason);
                 }
                 span.record("outcome", "error");
                 return Err(err);
@@ -383,25 +876,51 @@ impl ResumeStrategy for NewSessionStrategy {
                     }
                 }
                 Err(err) => {
+                    if let BackupError::UnsafePath(path_err) = &err {
+                        record_rejected_path(&ctx.session_path, path_err);
+                    }
                     warn!("Failed to backup session: {}", err);
                 }
             }
         }
 
-        let next_step = if let Some(info) = self.read_next_step(session_dir).await? {
+        let progress = ctx
+            .session_metadata
+            .as_ref()
+            .map(ProgressSummary::from_session)
+            .unwrap_or_else(|| ProgressSummary::new(Vec::new(), None, None))
+            .with_last_activity(ctx.timestamp);
+
+        let mut next_step = if let Some(info) = self.read_next_step(ctx, session_dir).await? {
             info
         } else if let Some(session) = &ctx.session_metadata {
             let step = self.calculate_from_steps_completed(session);
-            NextStepInfo {
+            let mut info = NextStepInfo {
                 step_number: step,
                 description: format!("Continue from step {}", step),
-                raw_content: String::new(),
+                raw_content: Arc::from(""),
+                truncated: false,
+                remaining_steps: Vec::new(),
+            };
+            let next_step_path = session_dir.join(&self.config.next_step_filename);
+            // symlink_metadata so a dangling symlink is never written through.
+            let missing = matches!(
+                fs::symlink_metadata(&next_step_path).await,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound
+            );
+            if missing {
+                let progress = progress.clone().with_next_step(step);
+                self.generate_next_step(session_dir, &progress, &mut info)
+                    .await;
             }
+            info
         } else {
             NextStepInfo {
                 step_number: 1,
                 description: "Continue workflow".to_string(),
-                raw_content: String::new(),
+                raw_content: Arc::from(""),
+                truncated: false,
+                remaining_steps: Vec::new(),
             }
         };
 
@@ -412,20 +931,79 @@ impl ResumeStrategy for NewSessionStrategy {
             next_step.step_number
         );
 
-        let prompt = self.generate_prompt(&next_step, ctx);
-        let new_session_path = match self.creator.create(&prompt, session_dir).await {
+        let progress = progress.with_next_step(next_step.step_number);
+        info!(progress = %progress.describe(), "Resume progress");
+        next_step.remaining_steps = progress.remaining_steps();
+
+        // The session file may have switched models since the daemon recorded it.
+        let session_model = ctx
+            .session_metadata
+            .as_ref()
+            .and_then(|session| session.state.model.clone());
+        let recorded_model = self.recorded_model(&ctx.session_path);
+        let drift = ModelMismatch::detect(recorded_model.as_deref(), session_model.as_deref());
+        if let Some(mismatch) = &drift {
+            self.report_model_mismatch(&ctx.session_path, mismatch, audit_logger.as_ref());
+        }
+        let previous_model = session_model.or(recorded_model);
+
+        let mut prompt = self.generate_prompt(&next_step, ctx, &progress, git_at_stop.as_ref());
+        if let Some(mismatch) = &drift {
+            prompt = format!("{}\n\n{prompt}", mismatch.prompt_note());
+        }
+        let prompt: Arc<str> = prompt.into();
+
+        let enforced_model = match (&previous_model, self.config.enforce_model) {
+            (Some(model), true) => Some(model.clone()),
+            (None, true) => {
+                warn!("enforce_model is set but the previous session's model is unknown");
+                None
+            }
+            (_, false) => None,
+        };
+        let mut worktree = self.prepare_worktree(ctx).await;
+        let created = match &worktree {
+            Some(record) => {
+                let worktree_ctx = ctx.clone().with_workdir(record.worktree.clone());
+                self.create_session(
+                    &worktree_ctx,
+                    &prompt,
+                    &record.worktree,
+                    enforced_model.as_deref(),
+                )
+                .await
+            }
+            None => {
+                self.create_session(ctx, &prompt, session_dir, enforced_model.as_deref())
+                    .await
+            }
+        };
+        let new_session_path = match created {
             Ok(path) => path,
+            Err(ResumeError::Cancelled) => {
+                info!(session = %ctx.session_path.display(), "Shutdown interrupted new session creation");
+                if let Some(metrics) = metrics.as_ref() {
+                    metrics.end_retry_cycle(metrics_reason);
+                }
+                span.record("outcome", "cancelled");
+                return Err(ResumeError::Cancelled);
+            }
             Err(err) => {
                 if let Some(logger) = &audit_logger {
-                    let _ = logger.log_resume_failed(&ctx.session_path, &err.to_string());
+                    let mut metadata = postmortem_audit_metadata(&err);
+                    metadata.extend(incident::audit_metadata(ctx.incident.as_deref()));
+                    let _ = logger
+                        .log_resume_failed_with(&ctx.session_path, &err.to_string(), metadata)
+                        .await;
                 }
+                self.report_resume_failed(ctx, &err, &progress);
                 if let Some(metrics) = metrics.as_ref() {
                     metrics.record_resume_completed(
                         start.elapsed(),
                         false,
                         Some(err.error_label()),
                     );
-                    metrics.set_retry_attempts(0);
+                    metrics.end_retry_cycle(metrics_reason);
                 }
                 span.record("outcome", "error");
                 return Err(err);
@@ -438,42 +1016,77 @@ impl ResumeStrategy for NewSessionStrategy {
             "Audit: new session transition"
         );
 
+        if let Some(record) = worktree.as_mut() {
+            record.session = new_session_path.clone();
+        }
         if let Some(logger) = &audit_logger {
-            let _ = logger.log_session_created(&new_session_path);
+            let metadata = worktree
+                .as_ref()
+                .map(worktree_audit_metadata)
+                .unwrap_or_default();
+            let _ = logger.log_session_created_with(&new_session_path, metadata);
+        }
+        if let Some(record) = &worktree {
+            self.report_worktree_created(record);
+        }
+
+        let new_model = enforced_model.or_else(|| {
+            parse_session(&new_session_path)
+                .ok()
+                .and_then(|session| session.state.model)
+        });
+        if let Some(mismatch) =
+            ModelMismatch::detect(previous_model.as_deref(), new_model.as_deref())
+        {
+            self.report_model_mismatch(&new_session_path, &mismatch, audit_logger.as_ref());
         }
 
         // New sessions triggered by context exhaustion don't have a wait period
         // since they start immediately. Time saved is still recorded (manual restart
         // time only) to capture the value of automatic session continuation.
-        if let Err(err) = self.update_state_on_resume(
+        let current = self.build_current_session(
             ctx,
             new_session_path.clone(),
             &next_step,
-            Duration::from_secs(0),
-            metrics.as_deref(),
-        ) {
+            new_model.or(previous_model),
+        );
+        if let Err(err) = self
+            .update_state_on_resume(
+                ctx,
+                current,
+                worktree,
+                Duration::from_secs(0),
+                metrics.as_deref(),
+            )
+            .await
+        {
+            self.record_orphan(ctx, &new_session_path, &err).await;
             if let Some(logger) = &audit_logger {
-                let _ = logger.log_resume_failed(&ctx.session_path, &err.to_string());
+                let _ = logger
+                    .log_resume_failed(&ctx.session_path, &err.to_string())
+                    .await;
             }
             if let Some(metrics) = metrics.as_ref() {
                 metrics.record_resume_completed(start.elapsed(), false, Some(err.error_label()));
-                metrics.set_retry_attempts(0);
+                metrics.end_retry_cycle(metrics_reason);
             }
             span.record("outcome", "error");
             return Err(err);
         }
 
+        let git_at_resume = self.capture_git(ctx).await;
         if let Some(logger) = &audit_logger {
-            let _ = logger.log_resume_completed(
+            let _ = logger.log_resume_completed_with(
                 &ctx.session_path,
                 &format!("Started new session from step {}", next_step.step_number),
+                git_context::audit_metadata(git_at_stop.as_ref(), git_at_resume.as_ref()),
             );
         }
 
         if let Some(metrics) = metrics.as_ref() {
             metrics.record_session_started();
             metrics.record_resume_completed(start.elapsed(), true, None);
-            metrics.set_retry_attempts(0);
+            metrics.end_retry_cycle(metrics_reason);
         }
 
         let outcome = ResumeOutcome::success(
@@ -484,7 +1097,7 @@ impl ResumeStrategy for NewSessionStrategy {
         Ok(outcome)
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "NewSessionStrategy"
     }
 }
@@ -495,7 +1108,45 @@ impl Default for NewSessionStrategy {
     }
 }
 
-fn steps_completed_from_session(session: &Session) -> Vec<u32> {
+fn postmortem_audit_metadata(err: &ResumeError) -> HashMap<String, Value> {
+    match err {
+        ResumeError::CommandFailed {
+            postmortem: Some(path),
+            ..
+        } => HashMap::from([(
+            "postmortem".to_string(),
+            Value::String(path.display().to_string()),
+        )]),
+        _ => HashMap::new(),
+    }
+}
+
+fn worktree_audit_metadata(record: &WorktreeRecord) -> HashMap<String, Value> {
+    HashMap::from([
+        (
+            "workspace".to_string(),
+            Value::from(record.workspace.display().to_string()),
+        ),
+        (
+            "worktree_path".to_string(),
+            Value::from(record.worktree.display().to_string()),
+        ),
+        (
+            "worktree_branch".to_string(),
+            Value::from(record.branch.clone()),
+        ),
+    ])
+}
+
+pub(crate) fn join_steps(steps: &[u32]) -> String {
+    steps
+        .iter()
+        .map(|step| step.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(crate) fn steps_completed_from_session(session: &Session) -> Vec<u32> {
     session
         .state
         .steps_completed
@@ -510,45 +1161,3 @@ fn step_value_to_u32(value: &StepValue) -> Option<u32> {
         StepValue::String(value) => value.parse::<u32>().ok(),
     }
 }
-
-fn parse_step_line(line: &str) -> Option<(u32, String)> {
-    let cleaned = line.trim_start_matches('#').trim();
-    let lower = cleaned.to_ascii_lowercase();
-
-    if lower.starts_with("step ") {
-        let remainder = cleaned[4..].trim();
-        return parse_step_number(remainder);
-    }
-
-    parse_leading_number(cleaned)
-}
-
-fn parse_step_number(input: &str) -> Option<(u32, String)> {
-    let (number, remainder) = parse_leading_number(input)?;
-    Some((number, remainder))
-}
-
-fn parse_leading_number(input: &str) -> Option<(u32, String)> {
-    let mut digits = String::new();
-    let mut index = 0;
-
-    for (idx, ch) in input.char_indices() {
-        if ch.is_ascii_digit() {
-            digits.push(ch);
-            index = idx + ch.len_utf8();
-        } else {
-            break;
-        }
-    }
-
-    if digits.is_empty() {
-        return None;
-    }
-
-    let number = digits.parse::<u32>().ok()?;
-    let remainder = input[index..]
-        .trim_start_matches(['.', ':', ')', '-'])
-        .trim();
-
-    Some((number, remainder.to_string()))
-}
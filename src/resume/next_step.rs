@@ -0,0 +1,323 @@
+//! Next-step.md parsing for new-session resumes.
+//!
+//! Agents write the step to continue from in many shapes, so the parser
+//! accepts headings, list items and checkboxes (`## Step 3: ...`,
+//! `- [ ] Step 3: ...`, `Next: step 3`, `3) ...`). Only the first
+//! `resume.max_next_step_bytes` of the file are read, so a runaway file
+//! can't blow up the new-session prompt.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+
+use crate::util::encoding::{self, EncodingFixups};
+
+/// Default for `resume.max_next_step_bytes`.
+pub const DEFAULT_MAX_NEXT_STEP_BYTES: usize = 64 * 1024;
+
+/// Lines quoted in a parse failure.
+const DIAGNOSTIC_LINES: usize = 5;
+
+/// Information extracted from Next-step.md.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NextStepInfo {
+    /// Step number to continue from.
+    pub step_number: u32,
+    /// Description of the step.
+    pub description: String,
+    /// Content of Next-step.md, capped at `resume.max_next_step_bytes`;
+    /// shared rather than copied by the prompt and reports built from it.
+    pub raw_content: Arc<str>,
+    /// Whether `raw_content` was cut short.
+    pub truncated: bool,
+    /// Steps not yet completed, lowest first; empty when unknown.
+    pub remaining_steps: Vec<u32>,
+}
+
+/// Next-step.md had no line naming a step.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("no step number found in Next-step.md")]
+pub struct NextStepParseError {
+    /// First non-empty lines that did not match any step syntax.
+    pub unmatched_lines: Vec<String>,
+    /// Whether the parsed content was truncated.
+    pub truncated: bool,
+}
+
+impl NextStepParseError {
+    /// Multi-line explanation quoting the lines that were tried.
+    pub fn describe(&self) -> String {
+        let mut text = self.to_string();
+        if self.unmatched_lines.is_empty() {
+            text.push_str(" (the file is empty)");
+            return text;
+        }
+        text.push_str("; first lines:");
+        for line in &self.unmatched_lines {
+            text.push_str(&format!("\n  {line}"));
+        }
+        text.push_str(
+            "\nExpected e.g. `Step 3: ...`, `## 3) ...`, `- [ ] Step 3: ...` or `Next: step 3`",
+        );
+        text
+    }
+}
+
+/// Read at most `max_bytes` of `path`.
+///
+/// Returns the text (with a truncation marker when cut) and whether it was cut.
+/// The file is decoded like session files (see [`crate::util::encoding`]).
+pub async fn read_capped(path: &Path, max_bytes: usize) -> std::io::Result<(String, bool)> {
+    let file = File::open(path).await?;
+    let mut bytes = Vec::new();
+    file.take(max_bytes as u64 + 1)
+        .read_to_end(&mut bytes)
+        .await?;
+    let cut = bytes.len() > max_bytes;
+    let decoded = if cut {
+        encoding::decode_prefix(&bytes[..max_bytes])?
+    } else {
+        encoding::decode(&bytes)?
+    };
+    EncodingFixups::shared().record(path, decoded.encoding);
+    let (mut text, truncated) = truncate(&decoded.text, max_bytes);
+    if cut && !truncated {
+        text.push_str(&truncation_marker(max_bytes));
+    }
+    Ok((text, cut || truncated))
+}
+
+/// Cap `content` at `max_bytes` on a character boundary.
+pub fn truncate(content: &str, max_bytes: usize) -> (String, bool) {
+    cap_bytes(content.as_bytes(), max_bytes)
+}
+
+fn cap_bytes(bytes: &[u8], max_bytes: usize) -> (String, bool) {
+    if bytes.len() <= max_bytes {
+        return (String::from_utf8_lossy(bytes).into_owned(), false);
+    }
+    let head = &bytes[..max_bytes];
+    // Drop a character split by the cut rather than emitting a replacement char.
+    let valid = match std::str::from_utf8(head) {
+        Ok(_) => head,
+        Err(err) if err.error_len().is_none() => &head[..err.valid_up_to()],
+        Err(_) => head,
+    };
+    let mut text = String::from_utf8_lossy(valid).into_owned();
+    text.push_str(&truncation_marker(max_bytes));
+    (text, true)
+}
+
+fn truncation_marker(max_bytes: usize) -> String {
+    format!("\n\n[... Next-step.md truncated at {max_bytes} bytes ...]")
+}
+
+/// Parse (already capped) Next-step.md content.
+pub fn parse(content: &str, truncated: bool) -> Result<NextStepInfo, NextStepParseError> {
+    let mut step_number = None;
+    let mut description: Option<String> = None;
+    let mut unmatched = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if step_number.is_none() {
+            if let Some((step, desc)) = parse_step_line(trimmed) {
+                step_number = Some(step);
+                if !desc.is_empty() {
+                    description = Some(desc);
+                }
+                continue;
+            }
+            if unmatched.len() < DIAGNOSTIC_LINES {
+                unmatched.push(trimmed.to_string());
+            }
+        }
+
+        if description.is_none() && !trimmed.starts_with('#') {
+            description = Some(trimmed.to_string());
+        }
+    }
+
+    let Some(step_number) = step_number else {
+        return Err(NextStepParseError {
+            unmatched_lines: unmatched,
+            truncated,
+        });
+    };
+    let description = description.unwrap_or_else(|| format!("Continue from step {step_number}"));
+
+    Ok(NextStepInfo {
+        step_number,
+        description,
+        raw_content: Arc::from(content),
+        truncated,
+        remaining_steps: Vec::new(),
+    })
+}
+
+fn parse_step_line(line: &str) -> Option<(u32, String)> {
+    let cleaned = strip_list_marker(line.trim_start_matches('#').trim());
+    let cleaned = strip_prefix_ignore_case(cleaned, "next:").unwrap_or(cleaned);
+
+    if let Some(remainder) = strip_prefix_ignore_case(cleaned, "step ") {
+        return parse_leading_number(remainder);
+    }
+
+    parse_leading_number(cleaned)
+}
+
+/// Strip `- `, `* `, `+ ` and a `[ ]`/`[x]` checkbox.
+fn strip_list_marker(line: &str) -> &str {
+    let line = match line.strip_prefix(['-', '*', '+']) {
+        Some(rest) if rest.starts_with(' ') => rest.trim_start(),
+        _ => line,
+    };
+    ["[ ]", "[x]", "[X]"]
+        .iter()
+        .find_map(|checkbox| line.strip_prefix(checkbox))
+        .map(str::trim_start)
+        .unwrap_or(line)
+}
+
+fn strip_prefix_ignore_case<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = input.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| input[prefix.len()..].trim_start())
+}
+
+fn parse_leading_number(input: &str) -> Option<(u32, String)> {
+    let end = input
+        .char_indices()
+        .find(|(_, ch)| !ch.is_ascii_digit())
+        .map(|(idx, _)| idx)
+        .unwrap_or(input.len());
+    if end == 0 {
+        return None;
+    }
+
+    let number = input[..end].parse::<u32>().ok()?;
+    let remainder = input[end..]
+        .trim_start()
+        .trim_start_matches(['.', ':', ')', '-'])
+        .trim();
+
+    Some((number, remainder.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(content: &str) -> (u32, String) {
+        let info = parse(content, false).expect("parsed");
+        (info.step_number, info.description)
+    }
+
+    #[test]
+    fn parses_plain_and_heading_steps() {
+        assert_eq!(step("Step 4: Write tests"), (4, "Write tests".to_string()));
+        assert_eq!(
+            step("# Next Step\n\n## Step 2 - Review\n"),
+            (2, "Review".to_string())
+        );
+        assert_eq!(step("## 3) Do the thing"), (3, "Do the thing".to_string()));
+        assert_eq!(step("5. Ship it"), (5, "Ship it".to_string()));
+    }
+
+    #[test]
+    fn parses_checkbox_and_list_steps() {
+        assert_eq!(
+            step("- [ ] Step 3: Wire the CLI"),
+            (3, "Wire the CLI".to_string())
+        );
+        assert_eq!(step("* [x] step 7: Done"), (7, "Done".to_string()));
+        assert_eq!(step("- Step 8"), (8, "Continue from step 8".to_string()));
+    }
+
+    #[test]
+    fn parses_next_prefix() {
+        assert_eq!(
+            step("Next: step 3\nRefactor the parser"),
+            (3, "Refactor the parser".to_string())
+        );
+        assert_eq!(step("NEXT: 9 - Deploy"), (9, "Deploy".to_string()));
+    }
+
+    #[test]
+    fn failure_quotes_first_unmatched_lines() {
+        let content = "# Plan\n\nFigure out the next thing\nthen another\n- a\n- b\n- c\n- d\n";
+        let err = parse(content, false).unwrap_err();
+        assert_eq!(
+            err.unmatched_lines,
+            vec![
+                "# Plan",
+                "Figure out the next thing",
+                "then another",
+                "- a",
+                "- b"
+            ]
+        );
+        let text = err.describe();
+        assert!(text.contains("no step number found"));
+        assert!(text.contains("\n  Figure out the next thing"));
+        assert!(!text.contains("- c"));
+
+        assert!(
+            parse("\n\n", false)
+                .unwrap_err()
+                .describe()
+                .contains("empty")
+        );
+    }
+
+    #[test]
+    fn truncation_caps_bytes_on_char_boundary() {
+        let (text, truncated) = truncate("Step 1: ok", 64);
+        assert_eq!(text, "Step 1: ok");
+        assert!(!truncated);
+
+        let content = format!("Step 2: big\n{}", "é".repeat(100));
+        let (text, truncated) = truncate(&content, 16);
+        assert!(truncated);
+        assert!(text.starts_with("Step 2: big\n"));
+        assert!(!text.contains('\u{FFFD}'));
+        assert!(text.ends_with("[... Next-step.md truncated at 16 bytes ...]"));
+    }
+
+    #[tokio::test]
+    async fn read_capped_stops_at_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Next-step.md");
+        let content = format!("Step 6: huge\n{}", "x".repeat(2 * 1024 * 1024));
+        std::fs::write(&path, content).unwrap();
+
+        let (text, truncated) = read_capped(&path, 1024).await.unwrap();
+        assert!(truncated);
+        assert!(text.len() < 1100);
+        let info = parse(&text, truncated).unwrap();
+        assert_eq!(info.step_number, 6);
+        assert!(info.truncated);
+    }
+
+    #[tokio::test]
+    async fn read_capped_decodes_utf16_and_crlf() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Next-step.md");
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "# Plan\r\nStep 4: wire it up\r\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        std::fs::write(&path, bytes).unwrap();
+
+        let (text, truncated) = read_capped(&path, 1024).await.unwrap();
+        assert!(!truncated);
+        assert_eq!(text, "# Plan\nStep 4: wire it up\n");
+        assert_eq!(parse(&text, truncated).unwrap().step_number, 4);
+    }
+}
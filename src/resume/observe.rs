@@ -0,0 +1,210 @@
+//! Observe mode (`daemon.mode = "observe"`, `daemon start --observe`).
+//!
+//! Detection, classification and notifications run as usual, but the
+//! selected strategy never executes: [`ObserveGate`] records what it would
+//! have done as an `OBSERVE:` audit entry and an `action_observed`
+//! notification, and counts it separately from real resumes. The mode is
+//! checked when the strategy runs, so `SET_MODE` applies to stops already
+//! in flight.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::config::schema::DaemonMode;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+use crate::state::AuditLogger;
+use crate::telemetry::Metrics;
+
+/// Daemon mode shared between the daemon state and the strategies it builds.
+#[derive(Debug, Clone, Default)]
+pub struct ModeSwitch {
+    observe: Arc<AtomicBool>,
+}
+
+impl ModeSwitch {
+    pub fn new(mode: DaemonMode) -> Self {
+        Self {
+            observe: Arc::new(AtomicBool::new(mode.is_observe())),
+        }
+    }
+
+    pub fn get(&self) -> DaemonMode {
+        if self.observe.load(Ordering::SeqCst) {
+            DaemonMode::Observe
+        } else {
+            DaemonMode::Active
+        }
+    }
+
+    /// Switch to `mode`, returning the previous one.
+    pub fn set(&self, mode: DaemonMode) -> DaemonMode {
+        if self.observe.swap(mode.is_observe(), Ordering::SeqCst) {
+            DaemonMode::Observe
+        } else {
+            DaemonMode::Active
+        }
+    }
+}
+
+/// Runs the inner strategy in active mode and only reports it in observe mode.
+pub struct ObserveGate {
+    inner: Box<dyn ResumeStrategy>,
+    mode: ModeSwitch,
+    audit: Option<AuditLogger>,
+    events: Option<EventBroadcaster>,
+}
+
+impl ObserveGate {
+    pub fn new(inner: Box<dyn ResumeStrategy>, mode: ModeSwitch) -> Self {
+        Self {
+            inner,
+            mode,
+            audit: None,
+            events: None,
+        }
+    }
+
+    /// Write `OBSERVE:` entries to this audit log.
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Publish `action_observed` notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn report(&self, ctx: &ResumeContext, action: &str) {
+        let reason = ctx.stop_reason.metrics_reason_label().unwrap_or("unknown");
+        info!(
+            session = %ctx.session_path.display(),
+            stop_reason = reason,
+            "OBSERVE: would run {action}"
+        );
+        if let Some(audit) = &self.audit {
+            if let Err(err) = audit.log_action_observed(&ctx.session_path, reason, action) {
+                warn!(error = %err, "Failed to audit observed action");
+            }
+        }
+        if let Some(metrics) = Metrics::global() {
+            metrics.record_resume_observed(reason);
+        }
+        if let Some(events) = &self.events {
+            let event = DomainEvent::ActionObserved {
+                timestamp: Utc::now(),
+                session_path: ctx.session_path.clone(),
+                stop_reason: reason.to_string(),
+                action: action.to_string(),
+            };
+            events.publish(event);
+        }
+    }
+}
+
+#[async_trait]
+impl ResumeStrategy for ObserveGate {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        if !self.mode.get().is_observe() {
+            return self.inner.execute(ctx).await;
+        }
+        let action = format!("{} resume", self.inner.name());
+        self.report(ctx, &action);
+        Ok(ResumeOutcome::skipped(format!(
+            "OBSERVE: would run {action}"
+        )))
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
+        self.inner.should_retry(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use std::path::PathBuf;
+
+    use crate::monitor::classifier::StopReason;
+    use crate::state::AuditEventType;
+
+    /// Strategy whose only effect is writing a marker file.
+    struct TouchStrategy {
+        marker: PathBuf,
+    }
+
+    #[async_trait]
+    impl ResumeStrategy for TouchStrategy {
+        async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+            std::fs::write(&self.marker, "resumed").unwrap();
+            Ok(ResumeOutcome::success(ctx.session_path.clone(), "resumed"))
+        }
+
+        fn name(&self) -> &str {
+            "touch"
+        }
+    }
+
+    fn ctx() -> ResumeContext {
+        ResumeContext::new("/tmp/session.md".into(), StopReason::ContextExhausted(None))
+    }
+
+    #[test]
+    fn switch_reports_previous_mode() {
+        let mode = ModeSwitch::new(DaemonMode::Active);
+        let shared = mode.clone();
+
+        assert_eq!(mode.set(DaemonMode::Observe), DaemonMode::Active);
+        assert_eq!(shared.get(), DaemonMode::Observe);
+        assert_eq!(shared.set(DaemonMode::Active), DaemonMode::Observe);
+    }
+
+    #[tokio::test]
+    async fn observe_mode_reports_instead_of_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marker");
+        let audit = AuditLogger::new(dir.path());
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let mode = ModeSwitch::new(DaemonMode::Observe);
+        let gate = ObserveGate::new(
+            Box::new(TouchStrategy {
+                marker: marker.clone(),
+            }),
+            mode.clone(),
+        )
+        .with_audit(audit.clone())
+        .with_event_broadcaster(events);
+
+        let outcome = gate.execute(&ctx()).await.unwrap();
+
+        assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+        assert!(!marker.exists());
+        let entries = audit.query().execute().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event_type, AuditEventType::ActionObserved);
+        assert_eq!(entries[0].action_taken, "OBSERVE: would run touch resume");
+        match rx.try_recv().expect("observed notification") {
+            NotificationEvent::ActionObserved { action, .. } => {
+                assert_eq!(action, "touch resume")
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+
+        mode.set(DaemonMode::Active);
+        assert!(gate.execute(&ctx()).await.unwrap().is_success());
+        assert!(marker.exists());
+    }
+}
@@ -0,0 +1,474 @@
+//! Postmortem bundles for failed assistant commands.
+//!
+//! When a spawned resume command (e.g. `opencode new`) exits unsuccessfully,
+//! [`FailureStore::record`] writes `failures/<timestamp>-<session-stem>/` under
+//! the state directory with everything needed to reproduce it: the exact
+//! argv, the environment with secrets redacted, size-capped stdout and
+//! stderr, the exit status, the generated prompt, and the classification
+//! evidence that led to the resume. Only the newest bundles are kept, like
+//! session backups. Capture is best-effort: callers log a failed write and
+//! still return the original command error.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::{debug, info};
+
+use crate::util::process::Termination;
+
+/// Directory under the state dir holding postmortem bundles.
+pub const FAILURES_DIR: &str = "failures";
+/// Bundles kept before the oldest are pruned.
+pub const DEFAULT_MAX_BUNDLES: usize = 20;
+/// Bytes of stdout and of stderr kept per bundle; the tail is kept.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+const RECORD_FILE: &str = "failure.json";
+const ENV_FILE: &str = "env.txt";
+const STDOUT_FILE: &str = "stdout.log";
+const STDERR_FILE: &str = "stderr.log";
+const PROMPT_FILE: &str = "prompt.txt";
+
+/// Environment variable names containing these words have their values redacted.
+const SECRET_MARKERS: &[&str] = &[
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "KEY",
+    "AUTH",
+    "CREDENTIAL",
+    "COOKIE",
+    "SESSION_ID",
+];
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Error)]
+pub enum FailureError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid failure record: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("No failure bundle named {id}")]
+    NotFound { id: String },
+}
+
+/// Summary written to `failure.json` in each bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureRecord {
+    /// Bundle directory name, `<timestamp>-<session-stem>`.
+    pub id: String,
+    pub captured_at: DateTime<Utc>,
+    pub session_path: PathBuf,
+    pub argv: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Terminating signal on Unix, when the command did not exit normally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+    /// Classified stop reason that triggered the resume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    /// Classifier evidence for the stop reason.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
+    /// Set when the daemon stopped the command's process tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped: Option<Termination>,
+    #[serde(default)]
+    pub stdout_truncated: bool,
+    #[serde(default)]
+    pub stderr_truncated: bool,
+}
+
+impl FailureRecord {
+    /// Exit status in words, e.g. "exit code 2" or "killed by signal 9".
+    pub fn describe_exit(&self) -> String {
+        let status = match (self.exit_code, self.signal) {
+            (Some(code), _) => format!("exit code {code}"),
+            (None, Some(signal)) => format!("killed by signal {signal}"),
+            (None, None) => "unknown exit status".to_string(),
+        };
+        match self.stopped {
+            Some(Termination::Terminated) => format!("{status}, stopped by the daemon"),
+            Some(Termination::Killed) => format!("{status}, SIGKILLed by the daemon"),
+            None => status,
+        }
+    }
+}
+
+/// A bundle read back from disk.
+#[derive(Debug, Clone)]
+pub struct FailureBundle {
+    pub path: PathBuf,
+    pub record: FailureRecord,
+    pub env: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub prompt: Option<String>,
+}
+
+/// What led to a failed command, beyond the command itself.
+#[derive(Debug, Clone, Default)]
+pub struct FailureDetails {
+    pub session_path: PathBuf,
+    pub prompt: Option<Arc<str>>,
+    pub stop_reason: Option<String>,
+    pub evidence: Arc<[String]>,
+    /// How the command's process tree was stopped, when it did not exit
+    /// on its own.
+    pub stopped: Option<Termination>,
+}
+
+/// Writes, lists and prunes postmortem bundles.
+#[derive(Debug, Clone)]
+pub struct FailureStore {
+    dir: PathBuf,
+    max_bundles: usize,
+    max_output_bytes: usize,
+}
+
+impl FailureStore {
+    /// Bundles under `<state_dir>/failures`.
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            dir: state_dir.join(FAILURES_DIR),
+            max_bundles: DEFAULT_MAX_BUNDLES,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+        }
+    }
+
+    /// Keep at most `max_bundles` bundles; 0 disables capture.
+    pub fn with_max_bundles(mut self, max_bundles: usize) -> Self {
+        self.max_bundles = max_bundles;
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write a bundle for `command`, which produced `output`, and prune old ones.
+    ///
+    /// Returns `Ok(None)` when capture is disabled.
+    pub fn record(
+        &self,
+        command: &Command,
+        output: &Output,
+        details: &FailureDetails,
+    ) -> Result<Option<PathBuf>, FailureError> {
+        if self.max_bundles == 0 {
+            return Ok(None);
+        }
+        fs::create_dir_all(&self.dir)?;
+
+        let captured_at = Utc::now();
+        let (id, path) = self.reserve_dir(captured_at, &details.session_path)?;
+        let std_command = command.as_std();
+        let argv = std::iter::once(std_command.get_program())
+            .chain(std_command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let (stdout, stdout_truncated) = keep_tail(&output.stdout, self.max_output_bytes);
+        let (stderr, stderr_truncated) = keep_tail(&output.stderr, self.max_output_bytes);
+        let record = FailureRecord {
+            id,
+            captured_at,
+            session_path: details.session_path.clone(),
+            argv,
+            exit_code: output.status.code(),
+            signal: exit_signal(&output.status),
+            stop_reason: details.stop_reason.clone(),
+            evidence: details.evidence.to_vec(),
+            stopped: details.stopped,
+            stdout_truncated,
+            stderr_truncated,
+        };
+
+        fs::write(path.join(STDOUT_FILE), stdout)?;
+        fs::write(path.join(STDERR_FILE), stderr)?;
+        fs::write(path.join(ENV_FILE), render_env(&sanitized_env(command)))?;
+        if let Some(prompt) = &details.prompt {
+            fs::write(path.join(PROMPT_FILE), prompt.as_bytes())?;
+        }
+        fs::write(
+            path.join(RECORD_FILE),
+            serde_json::to_string_pretty(&record)?,
+        )?;
+        info!(path = %path.display(), "Wrote command failure postmortem");
+
+        if let Err(err) = self.prune() {
+            debug!(error = %err, "Failed to prune old failure bundles");
+        }
+        Ok(Some(path))
+    }
+
+    /// Bundle records, oldest first. Directories without a readable record are skipped.
+    ///
+    /// A record's `id` is its directory name, whatever the file says.
+    pub fn list(&self) -> Result<Vec<FailureRecord>, FailureError> {
+        let mut records = Vec::new();
+        for id in self.bundle_ids()? {
+            match read_record(&self.dir.join(&id)) {
+                Ok(record) => records.push(FailureRecord { id, ..record }),
+                Err(err) => debug!(id = %id, error = %err, "Skipping unreadable failure bundle"),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Read the bundle named `id`.
+    pub fn show(&self, id: &str) -> Result<FailureBundle, FailureError> {
+        let valid = !id.is_empty() && !id.contains(['/', '\\']) && id != "." && id != "..";
+        let path = self.dir.join(id);
+        if !valid || !path.join(RECORD_FILE).is_file() {
+            return Err(FailureError::NotFound { id: id.to_string() });
+        }
+        Ok(FailureBundle {
+            record: read_record(&path)?,
+            env: fs::read_to_string(path.join(ENV_FILE)).unwrap_or_default(),
+            stdout: read_lossy(&path.join(STDOUT_FILE)),
+            stderr: read_lossy(&path.join(STDERR_FILE)),
+            prompt: fs::read_to_string(path.join(PROMPT_FILE)).ok(),
+            path,
+        })
+    }
+
+    /// Remove the oldest bundles beyond `max_bundles`.
+    pub fn prune(&self) -> Result<usize, FailureError> {
+        let ids = self.bundle_ids()?;
+        let excess = ids.len().saturating_sub(self.max_bundles);
+        for id in &ids[..excess] {
+            debug!(id = %id, "Pruning old failure bundle");
+            fs::remove_dir_all(self.dir.join(id))?;
+        }
+        Ok(excess)
+    }
+
+    /// Bundle directory names, oldest first (names start with a UTC timestamp).
+    fn bundle_ids(&self) -> Result<Vec<String>, FailureError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut ids: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Create a fresh bundle directory, suffixing the name on collision.
+    fn reserve_dir(
+        &self,
+        captured_at: DateTime<Utc>,
+        session_path: &Path,
+    ) -> Result<(String, PathBuf), FailureError> {
+        let stem = session_path
+            .file_stem()
+            .map(|stem| sanitize_stem(&stem.to_string_lossy()))
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or_else(|| "session".to_string());
+        let base = format!("{}-{stem}", captured_at.format("%Y%m%dT%H%M%S%.3fZ"));
+        let mut id = base.clone();
+        for suffix in 1.. {
+            let path = self.dir.join(&id);
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok((id, path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    id = format!("{base}-{suffix}");
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        unreachable!("suffixes are unbounded")
+    }
+}
+
+/// Environment the command ran with: the daemon's own plus the command's
+/// overrides, with secret-looking values redacted.
+pub fn sanitized_env(command: &Command) -> BTreeMap<String, String> {
+    let mut env: BTreeMap<String, String> = std::env::vars_os()
+        .map(|(key, value)| {
+            (
+                key.to_string_lossy().into_owned(),
+                value.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    for (key, value) in command.as_std().get_envs() {
+        let key = key.to_string_lossy().into_owned();
+        match value {
+            Some(value) => {
+                env.insert(key, value.to_string_lossy().into_owned());
+            }
+            None => {
+                env.remove(&key);
+            }
+        }
+    }
+    for (key, value) in env.iter_mut() {
+        if is_secret_name(key) {
+            *value = REDACTED.to_string();
+        }
+    }
+    env
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+fn render_env(env: &BTreeMap<String, String>) -> String {
+    env.iter().fold(String::new(), |mut out, (key, value)| {
+        out.push_str(key);
+        out.push('=');
+        out.push_str(value);
+        out.push('\n');
+        out
+    })
+}
+
+/// Last `limit` bytes of `bytes`, and whether anything was dropped.
+fn keep_tail(bytes: &[u8], limit: usize) -> (&[u8], bool) {
+    if bytes.len() <= limit {
+        (bytes, false)
+    } else {
+        (&bytes[bytes.len() - limit..], true)
+    }
+}
+
+/// File-name-safe form of a session file stem, at most 64 characters.
+pub(crate) fn sanitize_stem(stem: &str) -> String {
+    stem.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect()
+}
+
+fn read_record(path: &Path) -> Result<FailureRecord, FailureError> {
+    let contents = fs::read_to_string(path.join(RECORD_FILE))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn read_lossy(path: &Path) -> String {
+    fs::read(path)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn failed_output(stdout: &str, stderr: &str) -> Output {
+        let status = std::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .status()
+            .expect("run sh");
+        Output {
+            status,
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn redacts_secret_environment_values() {
+        let mut command = Command::new("opencode");
+        command
+            .env("OPENCODE_API_TOKEN", "tk_live")
+            .env("PALINGENESIS_SESSION_PATH", "/tmp/s.md");
+        let env = sanitized_env(&command);
+        assert_eq!(env["OPENCODE_API_TOKEN"], REDACTED);
+        assert_eq!(env["PALINGENESIS_SESSION_PATH"], "/tmp/s.md");
+    }
+
+    #[test]
+    fn caps_output_and_prunes_oldest_bundles() {
+        let temp = tempdir().unwrap();
+        let store = FailureStore::new(temp.path())
+            .with_max_bundles(2)
+            .with_max_output_bytes(4);
+        let command = Command::new("false");
+        let details = FailureDetails {
+            session_path: PathBuf::from("/work/my session.md"),
+            ..FailureDetails::default()
+        };
+
+        let mut paths = Vec::new();
+        for _ in 0..3 {
+            let path = store
+                .record(&command, &failed_output("", "abcdefgh"), &details)
+                .unwrap()
+                .unwrap();
+            paths.push(path);
+        }
+
+        let records = store.list().unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(!paths[0].exists());
+        assert!(records[0].id.contains("-my_session"));
+
+        let bundle = store.show(&records[1].id).unwrap();
+        assert_eq!(bundle.stderr, "efgh");
+        assert!(bundle.record.stderr_truncated);
+        assert_eq!(bundle.record.exit_code, Some(3));
+        assert!(matches!(
+            store.show("../etc"),
+            Err(FailureError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn zero_bundles_disables_capture() {
+        let temp = tempdir().unwrap();
+        let store = FailureStore::new(temp.path()).with_max_bundles(0);
+        let recorded = store
+            .record(
+                &Command::new("false"),
+                &failed_output("", ""),
+                &FailureDetails::default(),
+            )
+            .unwrap();
+        assert!(recorded.is_none());
+        assert!(!store.dir().exists());
+    }
+}
@@ -0,0 +1,327 @@
+//! Workflow progress summaries for resume notifications and Next-step.md.
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::monitor::session::{Session, StepValue};
+
+/// Remaining steps listed in a rendered Next-step.md; frontmatter can
+/// declare absurd totals, so the list is never proportional to them.
+pub const MAX_LISTED_STEPS: u32 = 100;
+
+/// How far a workflow got before it stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressSummary {
+    /// Step the workflow resumes at.
+    pub next_step: u32,
+    /// Completed step numbers (sorted, deduplicated).
+    pub steps_completed: Vec<u32>,
+    /// Steps below the highest completed one that are not done yet, at most
+    /// [`MAX_LISTED_STEPS`].
+    pub missing_steps: Vec<u32>,
+    /// Total steps in the workflow, if declared.
+    pub total_steps: Option<u32>,
+    /// Last time the session was active.
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+impl ProgressSummary {
+    pub fn new(
+        mut steps_completed: Vec<u32>,
+        last_step: Option<u32>,
+        total_steps: Option<u32>,
+    ) -> Self {
+        steps_completed.sort_unstable();
+        steps_completed.dedup();
+        let highest = steps_completed
+            .last()
+            .copied()
+            .into_iter()
+            .chain(last_step)
+            .max()
+            .unwrap_or(0);
+        let missing_steps = missing_steps(&steps_completed);
+        Self {
+            next_step: missing_steps
+                .first()
+                .copied()
+                .unwrap_or(highest.saturating_add(1)),
+            steps_completed,
+            missing_steps,
+            total_steps: total_steps.filter(|total| *total > 0),
+            last_activity: None,
+        }
+    }
+
+    pub fn from_session(session: &Session) -> Self {
+        let steps = session
+            .state
+            .steps_completed
+            .iter()
+            .filter_map(|value| match value {
+                StepValue::Integer(num) => u32::try_from(*num).ok(),
+                StepValue::String(value) => value.parse::<u32>().ok(),
+            })
+            .collect();
+        let last_step = session
+            .state
+            .last_step
+            .and_then(|step| u32::try_from(step).ok());
+        let total_steps = session
+            .state
+            .total_steps
+            .and_then(|total| u32::try_from(total).ok());
+        Self::new(steps, last_step, total_steps)
+    }
+
+    /// Override the resume step (e.g. from an existing Next-step.md).
+    pub fn with_next_step(mut self, step: u32) -> Self {
+        self.next_step = step.max(1);
+        self
+    }
+
+    pub fn with_last_activity(mut self, at: DateTime<Utc>) -> Self {
+        self.last_activity = Some(at);
+        self
+    }
+
+    /// Position of the resume step within the workflow, rounded to a whole percent.
+    ///
+    /// Returns `None` when the total step count is unknown.
+    pub fn percent(&self) -> Option<u8> {
+        let total = u64::from(self.total_steps?);
+        let position = u64::from(self.next_step).min(total);
+        let percent = (position * 100 + total / 2) / total;
+        Some(percent.min(100) as u8)
+    }
+
+    /// Completed steps collapsed into ranges, e.g. `1-4, 6`.
+    pub fn completed_ranges(&self) -> Option<String> {
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        for &step in &self.steps_completed {
+            match ranges.last_mut() {
+                Some((_, end)) if end.saturating_add(1) == step => *end = step,
+                _ => ranges.push((step, step)),
+            }
+        }
+        if ranges.is_empty() {
+            return None;
+        }
+        Some(
+            ranges
+                .into_iter()
+                .map(|(start, end)| {
+                    if start == end {
+                        start.to_string()
+                    } else {
+                        format!("{start}-{end}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    /// Steps still to run, at most [`MAX_LISTED_STEPS`]: skipped steps below
+    /// the highest completed one, then everything from the resume step to the
+    /// total.
+    ///
+    /// Only the skipped steps are listed when the total step count is unknown.
+    pub fn remaining_steps(&self) -> Vec<u32> {
+        let mut steps = self.missing_steps.clone();
+        if let Some(total) = self.total_steps {
+            let highest_done = self.steps_completed.last().copied().unwrap_or(0);
+            let start = self.next_step.max(highest_done.saturating_add(1));
+            if start <= total {
+                steps.extend((start..=total).take(MAX_LISTED_STEPS as usize));
+            }
+        }
+        steps.sort_unstable();
+        steps.dedup();
+        steps.truncate(MAX_LISTED_STEPS as usize);
+        steps
+    }
+
+    /// One-line summary, e.g. `step 7 of 12 (58%), steps 1-6 complete, last activity 14:32`.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![match (self.total_steps, self.percent()) {
+            (Some(total), Some(percent)) => {
+                format!("step {} of {} ({}%)", self.next_step, total, percent)
+            }
+            _ => format!("step {} (total unknown)", self.next_step),
+        }];
+
+        match self.completed_ranges() {
+            Some(ranges) => parts.push(format!("steps {ranges} complete")),
+            None => parts.push("no steps complete".to_string()),
+        }
+
+        if let Some(at) = self.last_activity {
+            parts.push(format!(
+                "last activity {}",
+                at.with_timezone(&Local).format("%H:%M")
+            ));
+        }
+
+        parts.join(", ")
+    }
+
+    /// Render a Next-step.md that `NewSessionStrategy` can parse back.
+    pub fn render_next_step(&self, description: &str) -> String {
+        let mut content = format!(
+            "# Next Step\n\nStep {}: {}\n\nProgress: {}\n",
+            self.next_step,
+            description,
+            self.describe()
+        );
+
+        let remaining = self.remaining_steps();
+        if !remaining.is_empty() {
+            content.push_str("\n## Remaining steps\n\n");
+            let last_listed = remaining.last().copied();
+            for step in remaining {
+                content.push_str(&format!("- Step {step}\n"));
+            }
+            if let (Some(last), Some(total)) = (last_listed, self.total_steps) {
+                if last < total {
+                    content.push_str(&format!("- ... through step {total}\n"));
+                }
+            }
+        }
+
+        content
+    }
+}
+
+/// Lowest step not yet completed: the first gap in `1..=max(completed)`, or
+/// `max + 1` when the completed steps are contiguous.
+pub fn next_step_from_completed(completed: &[u32]) -> u32 {
+    let mut sorted = completed.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    missing_steps(&sorted)
+        .first()
+        .copied()
+        .unwrap_or_else(|| sorted.last().copied().unwrap_or(0).saturating_add(1))
+}
+
+/// Gaps in `1..=max(completed)`, lowest first, at most [`MAX_LISTED_STEPS`].
+/// `completed` must be sorted and deduplicated.
+fn missing_steps(completed: &[u32]) -> Vec<u32> {
+    let limit = MAX_LISTED_STEPS as usize;
+    let mut missing = Vec::new();
+    let mut expected = 1u32;
+    for &step in completed {
+        while expected < step {
+            if missing.len() == limit {
+                return missing;
+            }
+            missing.push(expected);
+            expected += 1;
+        }
+        expected = expected.max(step.saturating_add(1));
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_uses_resume_position() {
+        let summary = ProgressSummary::new((1..=6).collect(), Some(6), Some(12));
+        assert_eq!(summary.next_step, 7);
+        assert_eq!(summary.percent(), Some(58));
+    }
+
+    #[test]
+    fn huge_totals_list_a_bounded_number_of_steps() {
+        let summary = ProgressSummary::new(Vec::new(), Some(u32::MAX - 1), Some(u32::MAX));
+        assert_eq!(summary.next_step, u32::MAX);
+        assert_eq!(summary.remaining_steps(), vec![u32::MAX]);
+
+        let summary = ProgressSummary::new(vec![u32::MAX - 1], None, Some(u32::MAX));
+        assert_eq!(summary.next_step, 1);
+        assert_eq!(summary.missing_steps.len(), MAX_LISTED_STEPS as usize);
+        assert_eq!(summary.remaining_steps().len(), MAX_LISTED_STEPS as usize);
+
+        let summary = ProgressSummary::new(vec![1], None, Some(u32::MAX));
+        assert_eq!(summary.remaining_steps().len(), MAX_LISTED_STEPS as usize);
+        let rendered = summary.render_next_step("Continue");
+        assert!(rendered.ends_with(&format!("- ... through step {}\n", u32::MAX)));
+    }
+
+    #[test]
+    fn percent_is_none_without_total() {
+        let summary = ProgressSummary::new(vec![1, 2], None, None);
+        assert_eq!(summary.percent(), None);
+        assert!(summary.remaining_steps().is_empty());
+        assert_eq!(
+            summary.describe(),
+            "step 3 (total unknown), steps 1-2 complete"
+        );
+    }
+
+    #[test]
+    fn percent_ignores_zero_total_and_caps_at_hundred() {
+        assert_eq!(ProgressSummary::new(vec![1], None, Some(0)).percent(), None);
+        assert_eq!(
+            ProgressSummary::new(vec![1, 2, 3], None, Some(3)).percent(),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn completed_ranges_collapse_gaps() {
+        let summary = ProgressSummary::new(vec![5, 1, 2, 3, 3, 8, 7], None, None);
+        assert_eq!(summary.completed_ranges().as_deref(), Some("1-3, 5, 7-8"));
+        assert_eq!(summary.next_step, 4);
+    }
+
+    #[test]
+    fn next_step_is_lowest_missing_step() {
+        assert_eq!(next_step_from_completed(&[1, 2, 3]), 4);
+        assert_eq!(next_step_from_completed(&[7, 1, 2, 3, 4, 8]), 5);
+        assert_eq!(next_step_from_completed(&[]), 1);
+        assert_eq!(next_step_from_completed(&[0, 1]), 2);
+    }
+
+    #[test]
+    fn remaining_steps_list_gaps_then_tail() {
+        let summary = ProgressSummary::new(vec![1, 2, 3, 4, 7, 8], Some(8), Some(9));
+        assert_eq!(summary.next_step, 5);
+        assert_eq!(summary.missing_steps, vec![5, 6]);
+        assert_eq!(summary.remaining_steps(), vec![5, 6, 9]);
+        assert_eq!(summary.percent(), Some(56));
+
+        let unknown_total = ProgressSummary::new(vec![1, 4], None, None);
+        assert_eq!(unknown_total.remaining_steps(), vec![2, 3]);
+
+        let contiguous = ProgressSummary::new(vec![1, 2], None, Some(3));
+        assert_eq!(contiguous.remaining_steps(), vec![3]);
+
+        let empty = ProgressSummary::new(Vec::new(), None, Some(2));
+        assert_eq!(empty.next_step, 1);
+        assert_eq!(empty.remaining_steps(), vec![1, 2]);
+    }
+
+    #[test]
+    fn last_step_advances_next_step() {
+        let summary = ProgressSummary::new(Vec::new(), Some(4), Some(10));
+        assert_eq!(summary.next_step, 5);
+        assert!(summary.describe().contains("no steps complete"));
+    }
+
+    #[test]
+    fn renders_next_step_file() {
+        let summary = ProgressSummary::new(vec![1, 2], Some(2), Some(4));
+        let content = summary.render_next_step("Continue from step 3");
+
+        assert_eq!(
+            content,
+            "# Next Step\n\nStep 3: Continue from step 3\n\n\
+             Progress: step 3 of 4 (75%), steps 1-2 complete\n\n\
+             ## Remaining steps\n\n- Step 3\n- Step 4\n"
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! The `{context}` placeholder of new-session prompts.
+//!
+//! `[resume.new_session.context]` lists the sections to include, in order.
+//! Each is rendered, cut to its own `max_chars`, and the sections are joined
+//! by newlines. When the result is over the total `max_chars`, sections are
+//! trimmed from the end of the list: the last one is cut down, and dropped
+//! once too little of it would be left, before any earlier one is touched.
+
+use std::fs;
+
+use serde::Serialize;
+
+use crate::config::schema::{ContextSection, PromptContextConfig};
+use crate::resume::ResumeContext;
+use crate::resume::new_session::{join_steps, steps_completed_from_session};
+use crate::resume::next_step::NextStepInfo;
+use crate::util::content::CUT_MARKER;
+
+/// Characters a trimmed section keeps at least, besides the cut marker.
+const MIN_TRIMMED_CHARS: usize = 16;
+
+/// [`CUT_MARKER`] for text cut at the start.
+const LEADING_CUT_MARKER: &str = "[...] ";
+
+/// A rendered section of the context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContextPart {
+    /// The section's configured name, e.g. `session_tail:20`.
+    pub section: String,
+    pub text: String,
+    /// Cut by its own cap or the total budget.
+    pub trimmed: bool,
+    /// Cuts keep the end of the text rather than the start.
+    #[serde(skip)]
+    keep_end: bool,
+}
+
+/// The sections that made it into the context, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AssembledContext {
+    pub parts: Vec<ContextPart>,
+    /// Sections dropped to fit the total budget, last first.
+    pub dropped: Vec<String>,
+}
+
+impl AssembledContext {
+    /// What `{context}` is replaced with.
+    pub fn text(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| part.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn len(&self) -> usize {
+        let text: usize = self.parts.iter().map(|part| chars(&part.text)).sum();
+        text + self.parts.len().saturating_sub(1)
+    }
+}
+
+/// Assemble the configured sections for a new session continuing `ctx`.
+pub fn assemble(
+    config: &PromptContextConfig,
+    ctx: &ResumeContext,
+    info: &NextStepInfo,
+) -> AssembledContext {
+    let mut assembled = AssembledContext::default();
+    for entry in config.sections.iter().filter(|entry| entry.enabled) {
+        let Some(text) = render(&entry.section, ctx, info) else {
+            continue;
+        };
+        let keep_end = matches!(entry.section, ContextSection::SessionTail(_));
+        let (text, trimmed) = match entry.max_chars {
+            Some(max_chars) => match fit(&text, max_chars, keep_end) {
+                Some(fitted) => (fitted, chars(&text) > max_chars),
+                None => continue,
+            },
+            None => (text, false),
+        };
+        assembled.parts.push(ContextPart {
+            section: entry.section.to_string(),
+            text,
+            trimmed,
+            keep_end,
+        });
+    }
+    if config.max_chars > 0 {
+        trim_to_budget(&mut assembled, config.max_chars);
+    }
+    assembled
+}
+
+/// Cut sections from the end until the context fits `budget`.
+fn trim_to_budget(assembled: &mut AssembledContext, budget: usize) {
+    while assembled.len() > budget {
+        let over = assembled.len() - budget;
+        let Some(part) = assembled.parts.last_mut() else {
+            return;
+        };
+        let keep = chars(&part.text).saturating_sub(over);
+        let fitted = (keep >= MIN_TRIMMED_CHARS + chars(CUT_MARKER))
+            .then(|| fit(&part.text, keep, part.keep_end))
+            .flatten();
+        match fitted {
+            Some(fitted) => {
+                part.text = fitted;
+                part.trimmed = true;
+            }
+            None => {
+                let dropped = assembled.parts.pop().expect("last part exists");
+                assembled.dropped.push(dropped.section);
+            }
+        }
+    }
+}
+
+fn render(section: &ContextSection, ctx: &ResumeContext, info: &NextStepInfo) -> Option<String> {
+    let session = ctx.session_metadata.as_ref();
+    match section {
+        ContextSection::PreviousSessionPath => {
+            Some(format!("Previous session: {}", ctx.session_path.display()))
+        }
+        ContextSection::StepsCompleted => {
+            let steps = steps_completed_from_session(session?);
+            (!steps.is_empty()).then(|| format!("Steps completed: {}", join_steps(&steps)))
+        }
+        ContextSection::LastStep => session?
+            .state
+            .last_step
+            .map(|step| format!("Last step: {step}")),
+        ContextSection::RemainingSteps => (!info.remaining_steps.is_empty())
+            .then(|| format!("Remaining steps: {}", join_steps(&info.remaining_steps))),
+        ContextSection::Model => session?
+            .state
+            .model
+            .as_ref()
+            .map(|model| format!("Model: {model}")),
+        ContextSection::StopReason => Some(format!("Stop reason: {}", ctx.stop_reason.name())),
+        ContextSection::NextStepContent => Some(match info.raw_content.trim() {
+            "" => format!("Continuation: {}", info.description),
+            content => format!("Next-step details:\n{content}"),
+        }),
+        ContextSection::SessionTail(lines) => {
+            let content = fs::read_to_string(&ctx.session_path).ok()?;
+            let all: Vec<&str> = content.lines().collect();
+            let tail = all[all.len().saturating_sub(*lines)..].join("\n");
+            (!tail.trim().is_empty()).then(|| format!("Session tail:\n{tail}"))
+        }
+        ContextSection::Custom(text) => (!text.trim().is_empty()).then(|| text.clone()),
+    }
+}
+
+/// `text` in at most `max_chars` characters, marked where it was cut; the
+/// end is kept with `keep_end`. `None` when not even the marker fits.
+fn fit(text: &str, max_chars: usize, keep_end: bool) -> Option<String> {
+    let len = chars(text);
+    if len <= max_chars {
+        return Some(text.to_string());
+    }
+    let keep = max_chars
+        .checked_sub(chars(CUT_MARKER))
+        .filter(|keep| *keep > 0)?;
+    Some(if keep_end {
+        let start = text
+            .char_indices()
+            .nth(len - keep)
+            .map_or(text.len(), |(index, _)| index);
+        format!("{LEADING_CUT_MARKER}{}", &text[start..])
+    } else {
+        let end = text
+            .char_indices()
+            .nth(keep)
+            .map_or(text.len(), |(index, _)| index);
+        format!("{}{CUT_MARKER}", &text[..end])
+    })
+}
+
+fn chars(text: &str) -> usize {
+    text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::config::schema::ContextSectionConfig;
+    use crate::monitor::classifier::StopReason;
+    use crate::monitor::frontmatter::parse_session_bytes;
+
+    const SESSION: &str = "---\nstepsCompleted: [1, 2]\nlastStep: 2\nmodel: claude\n---\n\
+                           line one\nline two\nline three\n";
+
+    fn fixture() -> (tempfile::TempDir, ResumeContext, NextStepInfo) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.md");
+        fs::write(&path, SESSION).unwrap();
+        let session = parse_session_bytes(&path, SESSION.as_bytes()).unwrap();
+        let ctx =
+            ResumeContext::new(path, StopReason::ContextExhausted(None)).with_session(session);
+        let info = NextStepInfo {
+            step_number: 3,
+            description: "Write tests".to_string(),
+            raw_content: Arc::from("## Step 3\nWrite the tests for the parser."),
+            truncated: false,
+            remaining_steps: vec![3, 4],
+        };
+        (dir, ctx, info)
+    }
+
+    fn config(sections: &[&str], max_chars: usize) -> PromptContextConfig {
+        PromptContextConfig {
+            sections: sections
+                .iter()
+                .map(|name| ContextSectionConfig::from(name.parse::<ContextSection>().unwrap()))
+                .collect(),
+            max_chars,
+        }
+    }
+
+    fn names(assembled: &AssembledContext) -> Vec<&str> {
+        assembled
+            .parts
+            .iter()
+            .map(|part| part.section.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn default_sections_match_the_previous_summary() {
+        let (_dir, ctx, info) = fixture();
+        let text = assemble(&PromptContextConfig::default(), &ctx, &info).text();
+
+        assert_eq!(
+            text,
+            format!(
+                "Previous session: {}\nSteps completed: 1, 2\nLast step: 2\n\
+                 Remaining steps: 3, 4\nModel: claude\nStop reason: context_exhausted\n\
+                 Next-step details:\n## Step 3\nWrite the tests for the parser.",
+                ctx.session_path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn sections_follow_the_configured_order() {
+        let (_dir, ctx, info) = fixture();
+        let assembled = assemble(
+            &config(
+                &[
+                    "custom:Keep going.",
+                    "stop_reason",
+                    "session_tail:2",
+                    "model",
+                ],
+                0,
+            ),
+            &ctx,
+            &info,
+        );
+
+        assert_eq!(
+            names(&assembled),
+            [
+                "custom:Keep going.",
+                "stop_reason",
+                "session_tail:2",
+                "model"
+            ]
+        );
+        assert_eq!(
+            assembled.text(),
+            "Keep going.\nStop reason: context_exhausted\n\
+             Session tail:\nline two\nline three\nModel: claude"
+        );
+    }
+
+    #[test]
+    fn disabled_and_empty_sections_are_left_out() {
+        let (_dir, ctx, mut info) = fixture();
+        info.remaining_steps.clear();
+        let mut config = config(&["model", "remaining_steps", "stop_reason"], 0);
+        config.sections[0].enabled = false;
+
+        assert_eq!(names(&assemble(&config, &ctx, &info)), ["stop_reason"]);
+    }
+
+    #[test]
+    fn section_cap_cuts_its_own_text() {
+        let (_dir, ctx, info) = fixture();
+        let mut config = config(&["next_step_content", "session_tail:3"], 0);
+        config.sections[0].max_chars = Some(24);
+        config.sections[1].max_chars = Some(16);
+
+        let assembled = assemble(&config, &ctx, &info);
+
+        assert_eq!(assembled.parts[0].text, "Next-step details: [...]");
+        assert!(assembled.parts[0].trimmed);
+        assert_eq!(assembled.parts[1].text, "[...] line three");
+        assert!(assembled.parts[1].trimmed);
+    }
+
+    #[test]
+    fn total_budget_trims_the_last_section_first() {
+        let (_dir, ctx, info) = fixture();
+        let full = assemble(
+            &config(&["stop_reason", "next_step_content"], 0),
+            &ctx,
+            &info,
+        );
+        let budget = full.text().chars().count() - 10;
+
+        let assembled = assemble(
+            &config(&["stop_reason", "next_step_content"], budget),
+            &ctx,
+            &info,
+        );
+
+        assert_eq!(assembled.text().chars().count(), budget);
+        assert_eq!(assembled.parts[0].text, "Stop reason: context_exhausted");
+        assert!(!assembled.parts[0].trimmed);
+        assert!(assembled.parts[1].trimmed);
+        assert!(assembled.parts[1].text.ends_with(CUT_MARKER));
+        assert!(assembled.dropped.is_empty());
+    }
+
+    #[test]
+    fn total_budget_drops_sections_too_small_to_keep() {
+        let (_dir, ctx, info) = fixture();
+        let assembled = assemble(
+            &config(&["stop_reason", "model", "last_step"], 40),
+            &ctx,
+            &info,
+        );
+
+        assert_eq!(names(&assembled), ["stop_reason"]);
+        assert_eq!(assembled.dropped, ["last_step", "model"]);
+        assert!(assembled.text().chars().count() <= 40);
+    }
+
+    #[test]
+    fn fit_never_splits_characters() {
+        assert_eq!(fit("ééééééééé", 8, false).as_deref(), Some("éé [...]"));
+        assert_eq!(fit("ééééééééé", 8, true).as_deref(), Some("[...] éé"));
+        assert_eq!(fit("ééééééééé", 6, false), None);
+    }
+}
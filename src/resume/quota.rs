@@ -0,0 +1,322 @@
+//! Single resume at a provider quota reset ([`StopReason::QuotaExhausted`]).
+//!
+//! A daily or monthly quota does not come back after a few minutes of
+//! backoff, so retrying on the usual ladder only burns attempts. Instead the
+//! resume is scheduled once, at the reset time the provider reported (or
+//! `resume.quota_retry_after_hours` from now when it did not say), plus up to
+//! a minute of jitter so several sessions do not hit the provider at the same
+//! second. The scheduled time is shown by `status` and announced with a
+//! `resume_deferred` notification.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use rand::Rng;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::config::schema::ResumeConfig;
+use crate::daemon::suspend::Clock;
+use crate::http::EventBroadcaster;
+use crate::monitor::classifier::StopReason;
+use crate::resume::deferral::Deferral;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+
+/// Fallback wait when the provider did not say when its quota resets.
+pub const DEFAULT_QUOTA_RETRY_AFTER: Duration = Duration::from_secs(6 * 3600);
+/// Upper bound on the random delay added to a scheduled quota resume.
+pub const QUOTA_RESUME_JITTER: Duration = Duration::from_secs(60);
+
+/// Resumes waiting for a quota reset, shared between the daemon state and
+/// the strategies it builds.
+#[derive(Debug, Clone, Default)]
+pub struct QuotaSchedule {
+    scheduled: Arc<Mutex<HashMap<PathBuf, DateTime<Utc>>>>,
+}
+
+impl QuotaSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Earliest scheduled quota resume, if any is waiting.
+    pub fn next(&self) -> Option<DateTime<Utc>> {
+        let scheduled = self.scheduled.lock().ok()?;
+        scheduled.values().min().copied()
+    }
+
+    fn set(&self, session: &Path, at: DateTime<Utc>) {
+        if let Ok(mut scheduled) = self.scheduled.lock() {
+            scheduled.insert(session.to_path_buf(), at);
+        }
+    }
+
+    fn clear(&self, session: &Path) {
+        if let Ok(mut scheduled) = self.scheduled.lock() {
+            scheduled.remove(session);
+        }
+    }
+}
+
+/// Runs a strategy once, when the exhausted quota resets.
+///
+/// Stops for any other reason pass straight through.
+pub struct QuotaWait {
+    inner: Box<dyn ResumeStrategy>,
+    retry_after: Duration,
+    max_jitter: Duration,
+    schedule: QuotaSchedule,
+    deferral: Deferral,
+}
+
+impl QuotaWait {
+    pub fn new(inner: Box<dyn ResumeStrategy>, retry_after: Duration) -> Self {
+        Self {
+            inner,
+            retry_after,
+            max_jitter: QUOTA_RESUME_JITTER,
+            schedule: QuotaSchedule::new(),
+            deferral: Deferral::default(),
+        }
+    }
+
+    /// Fallback wait from `resume.quota_retry_after_hours`.
+    pub fn retry_after_from_config(config: &ResumeConfig) -> Duration {
+        Duration::from_secs(config.quota_retry_after_hours.saturating_mul(3600))
+    }
+
+    /// Report the scheduled resume through `schedule`.
+    pub fn with_schedule(mut self, schedule: QuotaSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Add at most `max_jitter` to the scheduled time.
+    pub fn with_max_jitter(mut self, max_jitter: Duration) -> Self {
+        self.max_jitter = max_jitter;
+        self
+    }
+
+    /// Schedule against this clock (for testing).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.deferral.clock = clock;
+        self
+    }
+
+    /// Publish `resume_deferred` notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.deferral.events = Some(events);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.deferral.cancel = Some(cancel);
+        self
+    }
+
+    /// When to resume: the reported reset (or the fallback) plus jitter.
+    fn resume_at(&self, resets_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DateTime<Utc> {
+        let fallback = TimeDelta::from_std(self.retry_after).unwrap_or(TimeDelta::MAX);
+        let base = resets_at
+            .filter(|resets_at| *resets_at > now)
+            .unwrap_or_else(|| {
+                now.checked_add_signed(fallback)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+            });
+        let max_jitter_ms = self.max_jitter.as_millis() as u64;
+        let jitter = match max_jitter_ms {
+            0 => TimeDelta::zero(),
+            max => TimeDelta::milliseconds(rand::thread_rng().gen_range(0..=max) as i64),
+        };
+        base.checked_add_signed(jitter).unwrap_or(base)
+    }
+}
+
+#[async_trait]
+impl ResumeStrategy for QuotaWait {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        let StopReason::QuotaExhausted { resets_at } = ctx.stop_reason else {
+            return self.inner.execute(ctx).await;
+        };
+        let until = self.resume_at(resets_at, self.deferral.now());
+        info!(
+            session = %ctx.session_path.display(),
+            until = %until.to_rfc3339(),
+            reported_reset = resets_at.is_some(),
+            "Quota exhausted; resume scheduled for quota reset"
+        );
+        self.schedule.set(&ctx.session_path, until);
+        let reason = if resets_at.is_some() {
+            "provider quota exhausted; retrying once at reset"
+        } else {
+            "provider quota exhausted; reset time unknown, retrying once"
+        };
+        self.deferral.report_deferred(ctx, until, reason);
+        let waited = self.deferral.wait_until(until, "quota reset").await;
+        self.schedule.clear(&ctx.session_path);
+        if !waited {
+            info!("Quota resume cancelled by shutdown");
+            return Ok(ResumeOutcome::skipped(
+                "shutdown while waiting for quota reset",
+            ));
+        }
+
+        // The wait already happened here; the inner strategy resumes now, and
+        // a failure is final rather than a step onto the backoff ladder.
+        let scheduled = ctx.clone().with_retry_after(Duration::ZERO);
+        match self.inner.execute(&scheduled).await? {
+            ResumeOutcome::Delayed { reason, .. } => Ok(ResumeOutcome::failure(
+                format!("Resume at quota reset failed: {reason}"),
+                false,
+            )),
+            outcome => Ok(outcome),
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
+        self.inner.should_retry(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use crate::test_utils::TokioClock;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    /// When the inner strategy ran, and the `retry_after` it was given.
+    type Runs = Arc<Mutex<Vec<(DateTime<Utc>, Option<Duration>)>>>;
+
+    /// Records when it ran and fails with a retryable delay.
+    struct FailingStrategy {
+        clock: Arc<dyn Clock>,
+        runs: Runs,
+    }
+
+    #[async_trait]
+    impl ResumeStrategy for FailingStrategy {
+        async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+            self.runs
+                .lock()
+                .unwrap()
+                .push((self.clock.wall().into(), ctx.retry_after));
+            Ok(ResumeOutcome::delayed(
+                Duration::from_secs(30),
+                "Resume failed, will retry: quota",
+            ))
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    fn quota_ctx(resets_at: Option<DateTime<Utc>>) -> ResumeContext {
+        ResumeContext::new(
+            "/tmp/session.md".into(),
+            StopReason::QuotaExhausted { resets_at },
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resumes_once_at_reported_reset_with_jitter() {
+        let clock = TokioClock::starting_at(at("2026-01-06T20:00:00Z"));
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let schedule = QuotaSchedule::new();
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let strategy = QuotaWait::new(
+            Box::new(FailingStrategy {
+                clock: clock.clone(),
+                runs: Arc::clone(&runs),
+            }),
+            DEFAULT_QUOTA_RETRY_AFTER,
+        )
+        .with_clock(clock)
+        .with_schedule(schedule.clone())
+        .with_event_broadcaster(events);
+
+        let ctx = quota_ctx(Some(at("2026-01-07T00:00:00Z")));
+        let task = tokio::spawn(async move { strategy.execute(&ctx).await });
+        tokio::task::yield_now().await;
+
+        let scheduled = schedule.next().expect("resume scheduled");
+        assert!(scheduled >= at("2026-01-07T00:00:00Z"));
+        assert!(scheduled <= at("2026-01-07T00:01:00Z"));
+        match rx.try_recv().expect("deferral notification") {
+            NotificationEvent::ResumeDeferred { until, reason, .. } => {
+                assert_eq!(until, scheduled);
+                assert_eq!(reason, "provider quota exhausted; retrying once at reset");
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+
+        let outcome = task.await.unwrap().unwrap();
+        let runs = runs.lock().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0], (scheduled, Some(Duration::ZERO)));
+        // A failure at the reset is final rather than a backoff retry.
+        assert!(matches!(
+            outcome,
+            ResumeOutcome::Failure {
+                retryable: false,
+                ..
+            }
+        ));
+        assert!(!outcome.should_retry());
+        assert!(schedule.next().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unknown_reset_falls_back_to_configured_wait() {
+        let clock = TokioClock::starting_at(at("2026-01-06T20:00:00Z"));
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let strategy = QuotaWait::new(
+            Box::new(FailingStrategy {
+                clock: clock.clone(),
+                runs: Arc::clone(&runs),
+            }),
+            Duration::from_secs(2 * 3600),
+        )
+        .with_max_jitter(Duration::ZERO)
+        .with_clock(clock);
+
+        strategy.execute(&quota_ctx(None)).await.unwrap();
+
+        assert_eq!(runs.lock().unwrap()[0].0, at("2026-01-06T22:00:00Z"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn other_stop_reasons_pass_through() {
+        let clock = TokioClock::starting_at(at("2026-01-06T20:00:00Z"));
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let strategy = QuotaWait::new(
+            Box::new(FailingStrategy {
+                clock: clock.clone(),
+                runs: Arc::clone(&runs),
+            }),
+            DEFAULT_QUOTA_RETRY_AFTER,
+        )
+        .with_clock(clock);
+
+        let ctx = ResumeContext::new("/tmp/session.md".into(), StopReason::ContextExhausted(None));
+        let outcome = strategy.execute(&ctx).await.unwrap();
+
+        assert!(matches!(outcome, ResumeOutcome::Delayed { .. }));
+        assert_eq!(runs.lock().unwrap()[0], (at("2026-01-06T20:00:00Z"), None));
+    }
+}
@@ -2,18 +2,28 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tokio_util::sync::CancellationToken;
 use tracing::{Span, debug, info, warn};
 
 use crate::config::paths::Paths;
+use crate::daemon::suspend;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::monitor::classifier::{StopReason, StopReasonClassifier};
+use crate::monitor::incident;
 use crate::monitor::session::{Session, StepValue};
+use crate::resume::assistant::{AssistantAdapter, OpenCodeAdapter, run_command};
 use crate::resume::backoff::{Backoff, BackoffConfig};
+use crate::resume::cost;
+use crate::resume::git_context::{self, GitCollector, GitContext};
+use crate::resume::model::ModelMismatch;
+use crate::resume::sidecar::SessionOverrides;
 use crate::resume::{
     ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy, calculate_time_saved,
     load_metrics_config,
 };
-use crate::state::{AuditLogger, CurrentSession, StateStore};
+use crate::state::{AuditLogger, CurrentSession, SessionStatus, StateBackend, StateHandle};
 use crate::telemetry::Metrics;
 
 /// Configuration for same-session resume.
@@ -56,45 +66,25 @@ pub trait ResumeTrigger: Send + Sync {
     async fn trigger(&self, ctx: &ResumeContext) -> Result<(), ResumeError>;
 }
 
+/// Runs the assistant's own resume command.
 #[derive(Debug, Clone)]
-struct CommandResumeTrigger {
-    command: Vec<String>,
+struct AdapterResumeTrigger {
+    adapter: Arc<dyn AssistantAdapter>,
 }
 
 #[async_trait]
-impl ResumeTrigger for CommandResumeTrigger {
+impl ResumeTrigger for AdapterResumeTrigger {
     async fn trigger(&self, ctx: &ResumeContext) -> Result<(), ResumeError> {
-        if self.command.is_empty() {
-            return Err(ResumeError::Config(
-                "resume command cannot be empty".to_string(),
-            ));
-        }
+        let command = self.adapter.build_resume_command(ctx)?;
 
         info!(
             session = %ctx.session_path.display(),
             attempt = ctx.attempt_number,
+            assistant = self.adapter.name(),
             "Resuming session after rate limit"
         );
 
-        let mut command = tokio::process::Command::new(&self.command[0]);
-        if self.command.len() > 1 {
-            command.args(&self.command[1..]);
-        }
-        let output = command
-            .arg(&ctx.session_path)
-            .output()
-            .await
-            .map_err(ResumeError::Io)?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            return Err(ResumeError::CommandFailed {
-                command: self.command.join(" "),
-                stderr,
-            });
-        }
-
-        Ok(())
+        run_command(command).await.map(|_| ())
     }
 }
 
@@ -103,6 +93,10 @@ pub struct SameSessionStrategy {
     config: SameSessionConfig,
     cancel: Option<CancellationToken>,
     trigger: Arc<dyn ResumeTrigger>,
+    events: Option<EventBroadcaster>,
+    state: Option<StateHandle>,
+    git: Option<GitCollector>,
+    overrides: SessionOverrides,
 }
 
 impl SameSessionStrategy {
@@ -111,13 +105,18 @@ impl SameSessionStrategy {
     }
 
     pub fn with_config(config: SameSessionConfig) -> Self {
-        let trigger = CommandResumeTrigger {
-            command: config.resume_command.clone(),
+        let adapter = OpenCodeAdapter::new().with_resume_command(config.resume_command.clone());
+        let trigger = AdapterResumeTrigger {
+            adapter: Arc::new(adapter),
         };
         Self {
             config,
             cancel: None,
             trigger: Arc::new(trigger),
+            events: None,
+            state: None,
+            git: None,
+            overrides: SessionOverrides::default(),
         }
     }
 
@@ -131,6 +130,55 @@ impl SameSessionStrategy {
         self
     }
 
+    /// Resume through `adapter` instead of the configured opencode command.
+    pub fn with_adapter(mut self, adapter: Arc<dyn AssistantAdapter>) -> Self {
+        self.trigger = Arc::new(AdapterResumeTrigger { adapter });
+        self
+    }
+
+    /// Publish model-change warnings on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Use a specific state store instead of the default state file.
+    pub fn with_state_store<T: StateBackend + 'static>(mut self, store: T) -> Self {
+        self.state = Some(StateHandle::new(store));
+        self
+    }
+
+    /// Record the workspace git state in the resume history.
+    pub fn with_git_collector(mut self, collector: GitCollector) -> Self {
+        self.git = Some(collector);
+        self
+    }
+
+    /// Share an existing state handle instead of the global one.
+    pub fn with_state_handle(mut self, handle: StateHandle) -> Self {
+        self.state = Some(handle);
+        self
+    }
+
+    /// Apply the session's sidecar overrides (see [`crate::resume::sidecar`]).
+    pub fn with_overrides(mut self, overrides: SessionOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    async fn capture_git(&self, ctx: &ResumeContext) -> Option<GitContext> {
+        match &self.git {
+            Some(collector) => Some(collector.capture_for(ctx).await),
+            None => None,
+        }
+    }
+
+    fn state(&self) -> StateHandle {
+        self.state
+            .clone()
+            .unwrap_or_else(StateHandle::global_or_default)
+    }
+
     fn wait_duration(&self, ctx: &ResumeContext) -> Duration {
         if let Some(retry_after) = ctx.retry_after {
             return retry_after;
@@ -174,38 +222,60 @@ impl SameSessionStrategy {
         true
     }
 
-    fn update_state_on_resume(
+    async fn update_state_on_resume(
         &self,
         ctx: &ResumeContext,
         wait_duration: Duration,
         metrics: Option<&Metrics>,
     ) -> Result<(), ResumeError> {
         let metrics_config = load_metrics_config();
-        let store = StateStore::new();
-        let mut state = store.load();
-
-        state.stats.total_resumes = state.stats.total_resumes.saturating_add(1);
-        state.stats.saves_count = state.stats.saves_count.saturating_add(1);
-        state.stats.last_resume = Some(Utc::now());
-        state.current_session = Some(self.build_current_session(ctx));
-
         let calculation = calculate_time_saved(wait_duration, &metrics_config);
-        state.stats.time_saved_seconds += calculation.total_saved_seconds;
-
-        store
-            .save(&state)
+        let mut current = self.build_current_session(ctx);
+
+        let (cumulative_saved, mismatch, cost) = self
+            .state()
+            .update_critical_async(|state| {
+                state.stats.total_resumes = state.stats.total_resumes.saturating_add(1);
+                state.stats.saves_count = state.stats.saves_count.saturating_add(1);
+                state.stats.last_resume = Some(Utc::now());
+                let recorded_model = state
+                    .current_session
+                    .as_ref()
+                    .filter(|session| session.path == ctx.session_path)
+                    .and_then(|session| session.model.clone());
+                let mismatch =
+                    ModelMismatch::detect(recorded_model.as_deref(), current.model.as_deref());
+                current.model = current.model.take().or(recorded_model);
+                state.current_session = Some(current);
+                state.stats.time_saved_seconds += calculation.total_saved_seconds;
+                let cost = cost::record_resume_cost(
+                    state,
+                    &metrics_config,
+                    &ctx.session_path,
+                    ctx.session_metadata.as_ref(),
+                    calculation.total_saved_seconds,
+                );
+                (state.stats.time_saved_seconds, mismatch, cost)
+            })
+            .await
             .map_err(|err| ResumeError::Config(format!("state store error: {err}")))?;
+        if let Some(mismatch) = mismatch {
+            self.report_model_mismatch(ctx, &mismatch);
+        }
 
         if let Some(metrics) = metrics {
             metrics.record_save();
             metrics.record_time_saved(calculation.total_saved_seconds);
+            metrics.record_resume_cost(&cost);
         }
 
         info!(
             wait_seconds = calculation.wait_duration_seconds,
             manual_restart_seconds = calculation.manual_restart_seconds,
             total_saved = calculation.total_saved_seconds,
-            cumulative_saved = state.stats.time_saved_seconds,
+            cumulative_saved,
+            cost = %cost::format_amount(cost.cost, &metrics_config.currency),
+            value_saved = %cost::format_amount(cost.value_saved, &metrics_config.currency),
             "Time saved by resume"
         );
 
@@ -223,6 +293,28 @@ impl SameSessionStrategy {
         }
     }
 
+    fn report_model_mismatch(&self, ctx: &ResumeContext, mismatch: &ModelMismatch) {
+        warn!(
+            session = %ctx.session_path.display(),
+            previous_model = %mismatch.previous,
+            current_model = %mismatch.current,
+            "Session model changed across resume"
+        );
+        if let Some(logger) = Self::audit_logger() {
+            let _ =
+                logger.log_model_mismatch(&ctx.session_path, &mismatch.previous, &mismatch.current);
+        }
+        if let Some(events) = &self.events {
+            let event = DomainEvent::ModelChanged {
+                timestamp: Utc::now(),
+                session_path: ctx.session_path.clone(),
+                previous_model: mismatch.previous.clone(),
+                current_model: mismatch.current.clone(),
+            };
+            events.publish(event);
+        }
+    }
+
     fn audit_logger() -> Option<AuditLogger> {
         match Paths::ensure_state_dir() {
             Ok(state_dir) => Some(AuditLogger::new(&state_dir)),
@@ -250,14 +342,25 @@ impl ResumeStrategy for SameSessionStrategy {
         let span = Span::current();
         span.record("wait_duration_ms", 0);
         let metrics = Metrics::global();
+        let metrics_reason = ctx.stop_reason.metrics_reason_label().unwrap_or("manual");
         if let Some(metrics) = metrics.as_ref() {
-            let reason = ctx.stop_reason.metrics_reason_label().unwrap_or("manual");
             metrics.set_retry_attempts(ctx.attempt_number);
-            metrics.record_resume_started(reason);
+            metrics.record_resume_started(metrics_reason);
         }
+        let git_at_stop = match &ctx.git_at_stop {
+            Some(git) => Some(git.clone()),
+            None => self.capture_git(ctx).await,
+        };
         let audit_logger = Self::audit_logger();
         if let Some(logger) = &audit_logger {
-            let _ = logger.log_resume_started(&ctx.session_path, &format!("{:?}", ctx.stop_reason));
+            let mut metadata = git_context::audit_metadata(git_at_stop.as_ref(), None);
+            metadata.extend(self.overrides.audit_metadata());
+            metadata.extend(incident::audit_metadata(ctx.incident.as_deref()));
+            let _ = logger.log_resume_started_with(
+                &ctx.session_path,
+                &format!("{:?}", ctx.stop_reason),
+                metadata,
+            );
         }
 
         if ctx.attempt_number > self.config.max_retries {
@@ -267,14 +370,16 @@ impl ResumeStrategy for SameSessionStrategy {
                 "Retry limit exceeded"
             );
             if let Some(logger) = &audit_logger {
-                let _ = logger.log_resume_failed(
-                    &ctx.session_path,
-                    &format!("Retry limit exceeded after {} attempts", ctx.attempt_number),
-                );
+                let _ = logger
+                    .log_resume_failed(
+                        &ctx.session_path,
+                        &format!("Retry limit exceeded after {} attempts", ctx.attempt_number),
+                    )
+                    .await;
             }
             if let Some(metrics) = metrics.as_ref() {
                 metrics.record_resume_completed(start.elapsed(), false, Some("retry_exceeded"));
-                metrics.set_retry_attempts(0);
+                metrics.end_retry_cycle(metrics_reason);
             }
             let outcome = ResumeOutcome::failure(
                 format!("Retry limit exceeded after {} attempts", ctx.attempt_number),
@@ -286,9 +391,18 @@ impl ResumeStrategy for SameSessionStrategy {
 
         let wait_duration = self.wait_duration(ctx);
         span.record("wait_duration_ms", wait_duration.as_millis() as i64);
+        if let Some(metrics) = metrics.as_ref() {
+            metrics.record_backoff_wait(
+                metrics_reason,
+                wait_duration,
+                self.config.max_retries - ctx.attempt_number + 1,
+                ctx.timestamp,
+            );
+        }
+        let slept_before = suspend::generation();
         if !self.wait_or_cancel(wait_duration).await {
             if let Some(metrics) = metrics.as_ref() {
-                metrics.set_retry_attempts(0);
+                metrics.end_retry_cycle(metrics_reason);
             }
             let outcome = ResumeOutcome::skipped("same-session resume cancelled");
             span.record("outcome", outcome.label());
@@ -297,24 +411,50 @@ impl ResumeStrategy for SameSessionStrategy {
         if let Some(metrics) = metrics.as_ref() {
             metrics.record_wait(wait_duration);
         }
+        if suspend::generation() != slept_before {
+            if let Some(reason) = stale_after_sleep(ctx) {
+                info!(
+                    session = %ctx.session_path.display(),
+                    %reason,
+                    "Dropping resume after system sleep"
+                );
+                if let Some(metrics) = metrics.as_ref() {
+                    metrics.end_retry_cycle(metrics_reason);
+                }
+                let outcome = ResumeOutcome::skipped(reason);
+                span.record("outcome", outcome.label());
+                return Ok(outcome);
+            }
+        }
 
-        match self.trigger.trigger(ctx).await {
+        let overridden;
+        let trigger_ctx = match &self.overrides.overrides.continuation_message {
+            Some(message) => {
+                overridden = ctx.clone().with_continuation(message.clone());
+                &overridden
+            }
+            None => ctx,
+        };
+        match self.trigger.trigger(trigger_ctx).await {
             Ok(()) => {
-                if let Err(err) =
-                    self.update_state_on_resume(ctx, wait_duration, metrics.as_deref())
+                if let Err(err) = self
+                    .update_state_on_resume(ctx, wait_duration, metrics.as_deref())
+                    .await
                 {
                     span.record("outcome", "error");
                     return Err(err);
                 }
+                let git_at_resume = self.capture_git(ctx).await;
                 if let Some(logger) = &audit_logger {
-                    let _ = logger.log_resume_completed(
+                    let _ = logger.log_resume_completed_with(
                         &ctx.session_path,
                         "Resumed same session after rate limit",
+                        git_context::audit_metadata(git_at_stop.as_ref(), git_at_resume.as_ref()),
                     );
                 }
                 if let Some(metrics) = metrics.as_ref() {
                     metrics.record_resume_completed(start.elapsed(), true, None);
-                    metrics.set_retry_attempts(0);
+                    metrics.end_retry_cycle(metrics_reason);
                 }
                 let outcome = ResumeOutcome::success(
                     ctx.session_path.clone(),
@@ -326,7 +466,9 @@ impl ResumeStrategy for SameSessionStrategy {
             Err(err) => {
                 warn!(error = %err, "Resume trigger failed");
                 if let Some(logger) = &audit_logger {
-                    let _ = logger.log_resume_failed(&ctx.session_path, &err.to_string());
+                    let _ = logger
+                        .log_resume_failed(&ctx.session_path, &err.to_string())
+                        .await;
                 }
                 let retryable = ctx.attempt_number < self.config.max_retries;
                 if let Some(metrics) = metrics.as_ref() {
@@ -335,8 +477,15 @@ impl ResumeStrategy for SameSessionStrategy {
                         false,
                         Some(err.error_label()),
                     );
-                    if !retryable {
-                        metrics.set_retry_attempts(0);
+                    if retryable {
+                        metrics.record_backoff_wait(
+                            metrics_reason,
+                            self.backoff_delay(ctx.attempt_number + 1),
+                            self.config.max_retries - ctx.attempt_number,
+                            ctx.timestamp,
+                        );
+                    } else {
+                        metrics.end_retry_cycle(metrics_reason);
                     }
                 }
                 if retryable {
@@ -356,11 +505,34 @@ impl ResumeStrategy for SameSessionStrategy {
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "SameSessionStrategy"
     }
 }
 
+/// Re-check a session when the host slept while its resume was pending.
+///
+/// Returns why the resume should be dropped: the session is gone, was changed
+/// after the stop was detected, or no longer classifies as resumable.
+fn stale_after_sleep(ctx: &ResumeContext) -> Option<String> {
+    let Ok(metadata) = std::fs::metadata(&ctx.session_path) else {
+        return Some("session file no longer exists".to_string());
+    };
+    if let Ok(modified) = metadata.modified() {
+        if DateTime::<Utc>::from(modified) > ctx.timestamp {
+            return Some("session changed while the system slept".to_string());
+        }
+    }
+    match StopReasonClassifier::shared()
+        .classify(&ctx.session_path, None)
+        .reason
+    {
+        StopReason::Completed => Some("session completed while the system slept".to_string()),
+        StopReason::UserExit(_) => Some("session was exited while the system slept".to_string()),
+        _ => None,
+    }
+}
+
 impl Default for SameSessionStrategy {
     fn default() -> Self {
         Self::new()
@@ -388,6 +560,8 @@ fn current_session_from_metadata(
         steps_completed: steps_completed.clone(),
         last_step,
         total_steps: steps_completed.len() as u32,
+        status: SessionStatus::Active,
+        model: session.state.model.clone(),
     }
 }
 
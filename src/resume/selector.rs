@@ -1,9 +1,36 @@
-use tracing::warn;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::Utc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::config::schema::CustomStrategyConfig;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
 use crate::monitor::classifier::StopReason;
-use crate::resume::new_session::NewSessionStrategy;
+use crate::monitor::clock_skew::ClockSkew;
+#[cfg(feature = "opencode-api")]
+use crate::opencode::OpenCodeClient;
+use crate::resume::assistant::{self, AssistantAdapter, OpenCodeAdapter};
+use crate::resume::custom::CommandStrategy;
+use crate::resume::exclusions::{ExclusionMatch, SessionExclusions};
+use crate::resume::gates::{GateStatus, ResumeGates, ResumeGating};
+use crate::resume::git_context::GitCollector;
+use crate::resume::lease::{CrossHostLease, LeaseManager};
+use crate::resume::maintenance::{MaintenanceDeferral, MaintenanceSchedule};
+#[cfg(feature = "opencode-api")]
+use crate::resume::new_session::ApiSessionCreator;
+use crate::resume::new_session::{NewSessionConfig, NewSessionStrategy};
+use crate::resume::observe::{ModeSwitch, ObserveGate};
+use crate::resume::quota::{DEFAULT_QUOTA_RETRY_AFTER, QuotaSchedule, QuotaWait};
 use crate::resume::same_session::SameSessionStrategy;
+use crate::resume::sidecar::{SessionOverrides, SidecarError};
 use crate::resume::strategy::ResumeStrategy;
+use crate::resume::throttle::{ResumeRateLimit, ResumeThrottle};
+use crate::resume::worktree::WorktreeManager;
+use crate::state::{AuditLogger, StateHandle};
 
 #[derive(Debug, Clone, Copy)]
 pub enum UnknownStrategy {
@@ -12,38 +39,429 @@ pub enum UnknownStrategy {
     Skip,
 }
 
+/// Outcome of strategy selection for a stopped session.
+pub enum Selection {
+    Resume(Box<dyn ResumeStrategy>),
+    /// The stop reason does not call for a resume (user exit, completed).
+    Skip,
+    /// The session matches `resume.exclude_sessions`; nothing may run for it.
+    Excluded {
+        pattern: String,
+    },
+}
+
 /// Selects the appropriate resume strategy based on stop reason.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct StrategySelector {
     unknown_default: UnknownStrategy,
+    exclusions: SessionExclusions,
+    new_session: NewSessionConfig,
+    git: Option<GitCollector>,
+    worktrees: Option<WorktreeManager>,
+    maintenance: MaintenanceSchedule,
+    gates: Option<(ResumeGates, GateStatus)>,
+    rate_limit: Option<ResumeRateLimit>,
+    lease: Option<LeaseManager>,
+    quota_retry_after: Duration,
+    quota_schedule: QuotaSchedule,
+    events: Option<EventBroadcaster>,
+    cancel: CancellationToken,
+    mode: Option<ModeSwitch>,
+    audit: Option<AuditLogger>,
+    adapters: Vec<Arc<dyn AssistantAdapter>>,
+    custom: Vec<CommandStrategy>,
+    #[cfg(feature = "opencode-api")]
+    opencode: Option<OpenCodeClient>,
 }
 
 impl StrategySelector {
     pub fn new() -> Self {
+        Self::with_unknown_default(UnknownStrategy::Skip)
+    }
+
+    pub fn with_unknown_default(unknown_default: UnknownStrategy) -> Self {
         Self {
-            unknown_default: UnknownStrategy::Skip,
+            unknown_default,
+            exclusions: SessionExclusions::default(),
+            new_session: NewSessionConfig::default(),
+            git: None,
+            worktrees: None,
+            maintenance: MaintenanceSchedule::default(),
+            gates: None,
+            rate_limit: None,
+            lease: None,
+            quota_retry_after: DEFAULT_QUOTA_RETRY_AFTER,
+            quota_schedule: QuotaSchedule::new(),
+            events: None,
+            cancel: CancellationToken::new(),
+            mode: None,
+            audit: None,
+            adapters: Vec::new(),
+            custom: Vec::new(),
+            #[cfg(feature = "opencode-api")]
+            opencode: None,
         }
     }
 
-    pub fn with_unknown_default(unknown_default: UnknownStrategy) -> Self {
-        Self { unknown_default }
+    pub fn with_new_session_config(mut self, config: NewSessionConfig) -> Self {
+        self.new_session = config;
+        self
+    }
+
+    /// Resume sessions through these adapters, matched by session location.
+    pub fn with_adapters(mut self, adapters: Vec<Arc<dyn AssistantAdapter>>) -> Self {
+        self.adapters = adapters;
+        self
+    }
+
+    /// Adapters for the assistant names in `monitoring.assistants`.
+    pub fn with_assistants(self, assistants: &[String]) -> Self {
+        let adapters = assistants
+            .iter()
+            .filter_map(|name| {
+                let adapter = assistant::adapter_for(name);
+                if adapter.is_none() {
+                    warn!(assistant = %name, "No resume adapter for assistant");
+                }
+                adapter
+            })
+            .collect();
+        self.with_adapters(adapters)
+    }
+
+    /// Adapter for the assistant that owns `session_path`.
+    ///
+    /// Falls back to the only configured adapter, then to opencode.
+    pub fn adapter_for_session(&self, session_path: &Path) -> Arc<dyn AssistantAdapter> {
+        self.adapters
+            .iter()
+            .find(|adapter| adapter.owns_session(session_path))
+            .or(match self.adapters.as_slice() {
+                [only] => Some(only),
+                _ => None,
+            })
+            .cloned()
+            .unwrap_or_else(default_adapter)
+    }
+
+    /// Create new sessions through the OpenCode API when the model is enforced.
+    #[cfg(feature = "opencode-api")]
+    pub fn with_opencode_client(mut self, client: OpenCodeClient) -> Self {
+        self.opencode = Some(client);
+        self
+    }
+
+    /// Record workspace git state around resumes (`resume.capture_git_context`).
+    pub fn with_git_collector(mut self, collector: GitCollector) -> Self {
+        self.git = Some(collector);
+        self
+    }
+
+    /// Start new sessions in fresh git worktrees (`resume.new_session.workspace_mode`).
+    pub fn with_worktree_manager(mut self, manager: WorktreeManager) -> Self {
+        self.worktrees = Some(manager);
+        self
+    }
+
+    /// Hold resumes until maintenance windows end (`resume.maintenance_windows`).
+    pub fn with_maintenance(mut self, schedule: MaintenanceSchedule) -> Self {
+        self.maintenance = schedule;
+        self
+    }
+
+    /// Hold resumes until the system conditions in `[resume.gates]` are met,
+    /// reporting held resumes through `status`.
+    pub fn with_gates(mut self, gates: ResumeGates, status: GateStatus) -> Self {
+        self.gates = Some((gates, status)).filter(|(gates, _)| !gates.is_empty());
+        self
+    }
+
+    /// Space out and cap automatic resumes per session
+    /// (`resume.min_resume_interval_secs`, `resume.max_resumes_per_hour`).
+    pub fn with_rate_limit(mut self, limit: ResumeRateLimit) -> Self {
+        self.rate_limit = Some(limit).filter(|limit| !limit.is_disabled());
+        self
+    }
+
+    /// Take a cross-host lease before each resume (`resume.cross_host_lease`).
+    pub fn with_cross_host_lease(mut self, manager: LeaseManager) -> Self {
+        self.lease = Some(manager);
+        self
+    }
+
+    /// Resume quota-exhausted sessions once at the reset, waiting
+    /// `retry_after` when the provider did not report one
+    /// (`resume.quota_retry_after_hours`), and report them through `schedule`.
+    pub fn with_quota(mut self, retry_after: Duration, schedule: QuotaSchedule) -> Self {
+        self.quota_retry_after = retry_after;
+        self.quota_schedule = schedule;
+        self
+    }
+
+    /// Publish deferral notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Abandon rate-limit, gate, maintenance and quota waits when `cancel` fires.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Report instead of resuming while `mode` is observe (`daemon.mode`).
+    pub fn with_mode(mut self, mode: ModeSwitch) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Audit log for actions held back by observe mode.
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Try these command strategies, in order, before the built-in ones.
+    pub fn with_custom_strategies(mut self, strategies: Vec<CommandStrategy>) -> Self {
+        self.custom = strategies;
+        self
+    }
+
+    /// Command strategies for `resume.custom_strategies`.
+    pub fn with_custom_strategy_configs(self, configs: &[CustomStrategyConfig]) -> Self {
+        let strategies = configs.iter().map(CommandStrategy::from_config).collect();
+        self.with_custom_strategies(strategies)
+    }
+
+    pub fn with_exclusions(mut self, exclusions: SessionExclusions) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    pub fn exclusions(&self) -> &SessionExclusions {
+        &self.exclusions
+    }
+
+    /// Select strategy for a stopped session.
+    ///
+    /// Excluded sessions are rejected before the stop reason is considered,
+    /// so no backoff, retry state, or strategy is ever created for them.
+    /// The session's sidecar overrides are read here and carried by the
+    /// returned strategy.
+    pub fn select_for(&self, session_path: &Path, reason: &StopReason) -> Selection {
+        if let ExclusionMatch::Excluded { pattern } = self.exclusions.evaluate(session_path) {
+            info!(
+                session = %session_path.display(),
+                %pattern,
+                "Stop detected (excluded from auto-resume)"
+            );
+            return Selection::Excluded { pattern };
+        }
+        if let Some(paused_at) = StateHandle::read_state()
+            .resume_history(session_path)
+            .and_then(|history| history.resumes_paused_at)
+        {
+            info!(
+                session = %session_path.display(),
+                paused_at = %paused_at.to_rfc3339(),
+                "Stop detected (resumes paused by acknowledgment; run `palingenesis attention clear`)"
+            );
+            return Selection::Skip;
+        }
+
+        if matches!(reason, StopReason::RateLimit(_)) {
+            self.report_clock_skew();
+        }
+        if let Some(custom) = self.custom_strategy_for(session_path, reason) {
+            return Selection::Resume(self.wrap(custom, reason));
+        }
+        let adapter = self.adapter_for_session(session_path);
+        let overrides = self.discover_overrides(session_path);
+        match self.select_with(adapter, reason, overrides) {
+            Some(strategy) => Selection::Resume(strategy),
+            None => Selection::Skip,
+        }
     }
 
     /// Select strategy based on stop reason.
     /// Returns None if no resume should occur (user exit, completed).
     pub fn select(&self, reason: &StopReason) -> Option<Box<dyn ResumeStrategy>> {
+        let adapter = match self.adapters.as_slice() {
+            [only] => Arc::clone(only),
+            _ => default_adapter(),
+        };
+        self.select_with(adapter, reason, SessionOverrides::default())
+    }
+
+    /// Read the session's sidecars, reporting any that fail to parse.
+    fn discover_overrides(&self, session_path: &Path) -> SessionOverrides {
+        let (overrides, errors) = SessionOverrides::discover(session_path);
+        for SidecarError { path, message } in errors {
+            warn!(
+                session = %session_path.display(),
+                sidecar = %path.display(),
+                error = %message,
+                "Ignoring invalid resume sidecar"
+            );
+            if let Some(events) = &self.events {
+                let event = DomainEvent::ResumeSidecarInvalid {
+                    timestamp: Utc::now(),
+                    session_path: session_path.to_path_buf(),
+                    sidecar_path: path,
+                    error: message,
+                };
+                events.publish(event);
+            }
+        }
+        if !overrides.sources.is_empty() {
+            info!(
+                session = %session_path.display(),
+                fields = ?overrides.overrides.field_names(),
+                "Applying resume sidecar overrides"
+            );
+        }
+        overrides
+    }
+
+    /// Send the one-time warning once the provider clock skew is too large.
+    fn report_clock_skew(&self) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let Some(skew_secs) = ClockSkew::shared().take_warning() else {
+            return;
+        };
+        let event = DomainEvent::ClockSkewDetected {
+            timestamp: Utc::now(),
+            skew_secs,
+        };
+        events.publish(event);
+    }
+
+    /// The first custom strategy that handles this stop.
+    fn custom_strategy_for(
+        &self,
+        session_path: &Path,
+        reason: &StopReason,
+    ) -> Option<Box<dyn ResumeStrategy>> {
+        let custom = self
+            .custom
+            .iter()
+            .find(|custom| custom.matches(session_path, reason))?;
+        info!(
+            session = %session_path.display(),
+            strategy = custom.name(),
+            "Selected custom resume strategy"
+        );
+        let custom = custom.clone();
+        Some(Box::new(match &self.audit {
+            Some(audit) => custom.with_audit(audit.clone()),
+            None => custom,
+        }))
+    }
+
+    fn select_with(
+        &self,
+        adapter: Arc<dyn AssistantAdapter>,
+        reason: &StopReason,
+        overrides: SessionOverrides,
+    ) -> Option<Box<dyn ResumeStrategy>> {
+        let strategy = self.select_strategy(adapter, reason, overrides)?;
+        Some(self.wrap(strategy, reason))
+    }
+
+    /// Put `strategy` behind the configured holds and observe mode.
+    fn wrap(
+        &self,
+        mut strategy: Box<dyn ResumeStrategy>,
+        reason: &StopReason,
+    ) -> Box<dyn ResumeStrategy> {
+        // Innermost, so the lease is only held while the strategy runs.
+        if let Some(manager) = &self.lease {
+            let lease = CrossHostLease::new(strategy, manager.clone());
+            strategy = Box::new(match &self.events {
+                Some(events) => lease.with_event_broadcaster(events.clone()),
+                None => lease,
+            });
+        }
+        if let Some(limit) = self.rate_limit {
+            let throttle =
+                ResumeThrottle::new(strategy, limit).with_cancellation(self.cancel.clone());
+            strategy = Box::new(match &self.events {
+                Some(events) => throttle.with_event_broadcaster(events.clone()),
+                None => throttle,
+            });
+        }
+        // Checked after any maintenance window, just before the strategy runs.
+        if let Some((gates, status)) = &self.gates {
+            let gating = ResumeGating::new(strategy, gates.clone())
+                .with_status(status.clone())
+                .with_cancellation(self.cancel.clone());
+            strategy = Box::new(match &self.events {
+                Some(events) => gating.with_event_broadcaster(events.clone()),
+                None => gating,
+            });
+        }
+        if !self.maintenance.is_empty() {
+            let deferral = MaintenanceDeferral::new(strategy, self.maintenance.clone())
+                .with_cancellation(self.cancel.clone());
+            strategy = Box::new(match &self.events {
+                Some(events) => deferral.with_event_broadcaster(events.clone()),
+                None => deferral,
+            });
+        }
+        // Outside the other holds, so the rate limit, gates and maintenance
+        // windows are checked when the quota resets, not when it ran out.
+        if matches!(reason, StopReason::QuotaExhausted { .. }) {
+            let wait = QuotaWait::new(strategy, self.quota_retry_after)
+                .with_schedule(self.quota_schedule.clone())
+                .with_cancellation(self.cancel.clone());
+            strategy = Box::new(match &self.events {
+                Some(events) => wait.with_event_broadcaster(events.clone()),
+                None => wait,
+            });
+        }
+        // Outermost, so observed resumes neither wait nor count against the rate limit.
+        if let Some(mode) = &self.mode {
+            let mut gate = ObserveGate::new(strategy, mode.clone());
+            if let Some(audit) = &self.audit {
+                gate = gate.with_audit(audit.clone());
+            }
+            if let Some(events) = &self.events {
+                gate = gate.with_event_broadcaster(events.clone());
+            }
+            strategy = Box::new(gate);
+        }
+        strategy
+    }
+
+    fn select_strategy(
+        &self,
+        adapter: Arc<dyn AssistantAdapter>,
+        reason: &StopReason,
+        overrides: SessionOverrides,
+    ) -> Option<Box<dyn ResumeStrategy>> {
+        let unknown_default = overrides
+            .overrides
+            .strategy
+            .map_or(self.unknown_default, UnknownStrategy::from);
         match reason {
-            StopReason::RateLimit(_) => Some(Box::new(SameSessionStrategy::new())),
-            StopReason::ContextExhausted(_) => Some(Box::new(NewSessionStrategy::new())),
+            StopReason::RateLimit(_) | StopReason::QuotaExhausted { .. } => {
+                Some(Box::new(self.same_session_strategy(adapter, overrides)))
+            }
+            StopReason::ContextExhausted(_) => {
+                Some(Box::new(self.new_session_strategy(adapter, overrides)))
+            }
             StopReason::UserExit(_) | StopReason::Completed => None,
-            StopReason::Unknown(details) => match self.unknown_default {
+            StopReason::Unknown(details) => match unknown_default {
                 UnknownStrategy::SameSession => {
                     warn!(%details, "Unknown stop reason, defaulting to same-session resume");
-                    Some(Box::new(SameSessionStrategy::new()))
+                    Some(Box::new(self.same_session_strategy(adapter, overrides)))
                 }
                 UnknownStrategy::NewSession => {
                     warn!(%details, "Unknown stop reason, defaulting to new-session resume");
-                    Some(Box::new(NewSessionStrategy::new()))
+                    Some(Box::new(self.new_session_strategy(adapter, overrides)))
                 }
                 UnknownStrategy::Skip => {
                     warn!(%details, "Unknown stop reason, skipping resume");
@@ -52,6 +470,49 @@ impl StrategySelector {
             },
         }
     }
+
+    fn same_session_strategy(
+        &self,
+        adapter: Arc<dyn AssistantAdapter>,
+        overrides: SessionOverrides,
+    ) -> SameSessionStrategy {
+        let strategy = SameSessionStrategy::new()
+            .with_adapter(adapter)
+            .with_overrides(overrides);
+        match &self.git {
+            Some(collector) => strategy.with_git_collector(collector.clone()),
+            None => strategy,
+        }
+    }
+
+    fn new_session_strategy(
+        &self,
+        adapter: Arc<dyn AssistantAdapter>,
+        overrides: SessionOverrides,
+    ) -> NewSessionStrategy {
+        #[cfg(feature = "opencode-api")]
+        let is_opencode = adapter.name() == assistant::OPENCODE;
+        let mut strategy = NewSessionStrategy::with_config(self.new_session.clone())
+            .with_adapter(adapter)
+            .with_overrides(overrides);
+        if let Some(collector) = &self.git {
+            strategy = strategy.with_git_collector(collector.clone());
+        }
+        if let Some(manager) = &self.worktrees {
+            strategy = strategy.with_worktree_manager(manager.clone());
+        }
+        #[cfg(feature = "opencode-api")]
+        if self.new_session.enforce_model && is_opencode {
+            if let Some(client) = &self.opencode {
+                return strategy.with_session_creator(ApiSessionCreator::new(client.clone()));
+            }
+        }
+        strategy
+    }
+}
+
+fn default_adapter() -> Arc<dyn AssistantAdapter> {
+    Arc::new(OpenCodeAdapter::new())
 }
 
 impl Default for StrategySelector {
@@ -0,0 +1,470 @@
+//! Per-session resume overrides read from sidecar files.
+//!
+//! A `<session-stem>.palingenesis.toml` next to the session file, or a
+//! `.palingenesis/resume.toml` in the session's workspace, changes how that
+//! session is resumed:
+//!
+//! ```toml
+//! prompt_template = "Continue from step {step}; do not modify src/.\n\n{context}"
+//! enable_backup = false
+//! # Strategy for stop reasons the classifier cannot place.
+//! strategy = "new_session"
+//! # Routing tags for notifications (`match_tags` / `exclude_tags`).
+//! tags = ["proj-a", "backend"]
+//!
+//! [same_session]
+//! continuation_message = "continue writing tests, do not modify src/"
+//! ```
+//!
+//! Files are read when a stop is handled, so edits apply to the next resume.
+//! The session sidecar wins over the workspace file, which wins over the
+//! global config; `tags` from both files are combined. The workspace file is
+//! looked up from the session's directory upwards, stopping at the enclosing
+//! git repository root. A file that fails to parse is skipped so the resume
+//! falls back to the inherited settings.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::resume::selector::UnknownStrategy;
+
+/// Suffix of the session sidecar, appended to the session file's stem.
+pub const SIDECAR_SUFFIX: &str = ".palingenesis.toml";
+/// Workspace overlay, relative to the workspace directory.
+pub const WORKSPACE_OVERLAY: &str = ".palingenesis/resume.toml";
+
+/// Strategy preference accepted in sidecar files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyPreference {
+    SameSession,
+    NewSession,
+    Skip,
+}
+
+impl From<StrategyPreference> for UnknownStrategy {
+    fn from(preference: StrategyPreference) -> Self {
+        match preference {
+            StrategyPreference::SameSession => Self::SameSession,
+            StrategyPreference::NewSession => Self::NewSession,
+            StrategyPreference::Skip => Self::Skip,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SameSessionOverrides {
+    continuation_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SidecarFile {
+    prompt_template: Option<String>,
+    enable_backup: Option<bool>,
+    strategy: Option<StrategyPreference>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    same_session: SameSessionOverrides,
+}
+
+/// Settings a sidecar replaces; `None` keeps the inherited value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResumeOverrides {
+    /// Replaces `resume.new_session.prompt_template`.
+    pub prompt_template: Option<String>,
+    /// Message sent when resuming the same session, for assistants that take one.
+    pub continuation_message: Option<String>,
+    /// Replaces `resume.new_session.enable_backup`.
+    pub enable_backup: Option<bool>,
+    /// Strategy used when the stop reason is unknown.
+    pub strategy: Option<StrategyPreference>,
+    /// Notification routing tags, lowercased; combined across files.
+    pub tags: Vec<String>,
+}
+
+impl ResumeOverrides {
+    fn from_file(file: SidecarFile) -> Self {
+        Self {
+            prompt_template: file.prompt_template,
+            continuation_message: file.same_session.continuation_message,
+            enable_backup: file.enable_backup,
+            strategy: file.strategy,
+            tags: normalize_tags(&file.tags),
+        }
+    }
+
+    /// Fill fields `self` leaves unset from `inherited`.
+    fn or(self, inherited: Self) -> Self {
+        let mut tags = self.tags;
+        for tag in inherited.tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        Self {
+            prompt_template: self.prompt_template.or(inherited.prompt_template),
+            continuation_message: self.continuation_message.or(inherited.continuation_message),
+            enable_backup: self.enable_backup.or(inherited.enable_backup),
+            strategy: self.strategy.or(inherited.strategy),
+            tags,
+        }
+    }
+
+    /// Names of the fields this overrides, as written in the sidecar.
+    pub fn field_names(&self) -> Vec<&'static str> {
+        [
+            ("prompt_template", self.prompt_template.is_some()),
+            (
+                "same_session.continuation_message",
+                self.continuation_message.is_some(),
+            ),
+            ("enable_backup", self.enable_backup.is_some()),
+            ("strategy", self.strategy.is_some()),
+            ("tags", !self.tags.is_empty()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.field_names().is_empty()
+    }
+}
+
+/// A sidecar that was found but could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SidecarError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Overrides that apply to one session, with the files they came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionOverrides {
+    pub overrides: ResumeOverrides,
+    /// Sidecar files that were read, highest precedence first.
+    pub sources: Vec<PathBuf>,
+}
+
+impl SessionOverrides {
+    /// Read the sidecar files for `session_path`.
+    ///
+    /// Files that exist but fail to parse are returned as errors and
+    /// contribute nothing.
+    pub fn discover(session_path: &Path) -> (Self, Vec<SidecarError>) {
+        let candidates = [
+            session_sidecar_path(session_path),
+            workspace_overlay_path(session_path),
+        ];
+        let mut found = Self::default();
+        let mut errors = Vec::new();
+        for path in candidates.into_iter().flatten() {
+            match read_sidecar(&path) {
+                Ok(overrides) => {
+                    found.overrides = found.overrides.or(overrides);
+                    found.sources.push(path);
+                }
+                Err(message) => errors.push(SidecarError { path, message }),
+            }
+        }
+        (found, errors)
+    }
+
+    /// Audit metadata recording which sidecars applied and what they changed.
+    pub fn audit_metadata(&self) -> HashMap<String, Value> {
+        if self.sources.is_empty() {
+            return HashMap::new();
+        }
+        let sources = self
+            .sources
+            .iter()
+            .map(|path| Value::String(path.display().to_string()))
+            .collect();
+        let fields = self
+            .overrides
+            .field_names()
+            .into_iter()
+            .map(|name| Value::String(name.to_string()))
+            .collect();
+        let mut metadata = HashMap::from([
+            ("sidecar".to_string(), Value::Array(sources)),
+            ("sidecar_overrides".to_string(), Value::Array(fields)),
+        ]);
+        if !self.overrides.tags.is_empty() {
+            metadata.insert("tags".to_string(), Value::from(self.overrides.tags.clone()));
+        }
+        metadata
+    }
+}
+
+/// Routing tags of `session_path` from its sidecar files; unreadable files
+/// contribute none.
+pub fn session_tags(session_path: &Path) -> Vec<String> {
+    SessionOverrides::discover(session_path).0.overrides.tags
+}
+
+/// How deep [`known_tags`] looks below the session directory.
+const KNOWN_TAGS_DEPTH: usize = 4;
+
+/// Tags declared by sidecar files and workspace overlays under `dir` (a few
+/// levels deep) or in the workspace overlay above it.
+pub fn known_tags(dir: &Path) -> BTreeSet<String> {
+    let mut files: Vec<PathBuf> = workspace_overlay_path(&dir.join(SIDECAR_SUFFIX))
+        .into_iter()
+        .collect();
+    collect_sidecars(dir, KNOWN_TAGS_DEPTH, &mut files);
+    files
+        .iter()
+        .filter_map(|path| read_sidecar(path).ok())
+        .flat_map(|overrides| overrides.tags)
+        .collect()
+}
+
+fn collect_sidecars(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let overlay = dir.join(WORKSPACE_OVERLAY);
+    if overlay.is_file() && !files.contains(&overlay) {
+        files.push(overlay);
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        match entry.file_type() {
+            Ok(kind) if kind.is_file() && name.ends_with(SIDECAR_SUFFIX) => files.push(path),
+            Ok(kind) if kind.is_dir() && depth > 0 && !name.starts_with('.') => {
+                collect_sidecars(&path, depth - 1, files);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tags trimmed, lowercased and deduplicated, in order.
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// `<dir>/<stem>.palingenesis.toml` for `<dir>/<stem>.<ext>`, if it exists.
+pub fn session_sidecar_path(session_path: &Path) -> Option<PathBuf> {
+    let stem = session_path.file_stem()?.to_str()?;
+    let path = session_path.with_file_name(format!("{stem}{SIDECAR_SUFFIX}"));
+    path.is_file().then_some(path)
+}
+
+/// Nearest `.palingenesis/resume.toml` above the session, within its git
+/// repository when it has one.
+pub fn workspace_overlay_path(session_path: &Path) -> Option<PathBuf> {
+    for dir in session_path.parent()?.ancestors() {
+        let path = dir.join(WORKSPACE_OVERLAY);
+        if path.is_file() {
+            return Some(path);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+fn read_sidecar(path: &Path) -> Result<ResumeOverrides, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let file: SidecarFile = toml::from_str(&contents).map_err(|err| err.message().to_string())?;
+    if file.prompt_template.as_deref().is_some_and(str::is_empty) {
+        return Err("prompt_template cannot be empty".to_string());
+    }
+    Ok(ResumeOverrides::from_file(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Workspace {
+        _temp: tempfile::TempDir,
+        root: PathBuf,
+        session: PathBuf,
+    }
+
+    fn workspace() -> Workspace {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("project");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("sessions")).unwrap();
+        let session = root.join("sessions").join("tests.md");
+        std::fs::write(&session, "---\nstepsCompleted: [1]\n---\n").unwrap();
+        Workspace {
+            _temp: temp,
+            root,
+            session,
+        }
+    }
+
+    fn write_overlay(root: &Path, contents: &str) {
+        std::fs::create_dir_all(root.join(".palingenesis")).unwrap();
+        std::fs::write(root.join(WORKSPACE_OVERLAY), contents).unwrap();
+    }
+
+    #[test]
+    fn no_sidecar_means_no_overrides() {
+        let ws = workspace();
+
+        let (found, errors) = SessionOverrides::discover(&ws.session);
+
+        assert_eq!(found, SessionOverrides::default());
+        assert!(errors.is_empty());
+        assert!(found.audit_metadata().is_empty());
+    }
+
+    #[test]
+    fn session_sidecar_wins_over_workspace_overlay() {
+        let ws = workspace();
+        write_overlay(
+            &ws.root,
+            "prompt_template = \"workspace {step}\"\nenable_backup = false\nstrategy = \"skip\"\n",
+        );
+        let sidecar = ws.root.join("sessions").join("tests.palingenesis.toml");
+        std::fs::write(
+            &sidecar,
+            "prompt_template = \"tests {step}\"\n\n[same_session]\ncontinuation_message = \"continue writing tests, do not modify src/\"\n",
+        )
+        .unwrap();
+
+        let (found, errors) = SessionOverrides::discover(&ws.session);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            found.overrides,
+            ResumeOverrides {
+                prompt_template: Some("tests {step}".to_string()),
+                continuation_message: Some(
+                    "continue writing tests, do not modify src/".to_string()
+                ),
+                enable_backup: Some(false),
+                strategy: Some(StrategyPreference::Skip),
+                tags: Vec::new(),
+            }
+        );
+        assert_eq!(
+            found.sources,
+            vec![sidecar, ws.root.join(WORKSPACE_OVERLAY)]
+        );
+    }
+
+    #[test]
+    fn tags_combine_across_files() {
+        let ws = workspace();
+        write_overlay(&ws.root, "tags = [\"proj-a\", \"Backend\"]\n");
+        assert_eq!(session_tags(&ws.session), vec!["proj-a", "backend"]);
+
+        let sidecar = ws.root.join("sessions").join("tests.palingenesis.toml");
+        std::fs::write(&sidecar, "tags = [\"tests\", \"proj-a\", \" \"]\n").unwrap();
+        assert_eq!(
+            session_tags(&ws.session),
+            vec!["tests", "proj-a", "backend"]
+        );
+    }
+
+    #[test]
+    fn known_tags_come_from_sidecars_below_and_the_overlay_above() {
+        let ws = workspace();
+        write_overlay(&ws.root, "tags = [\"proj-a\"]\n");
+        let nested = ws.root.join("sessions").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            nested.join("run.palingenesis.toml"),
+            "tags = [\"backend\"]\n",
+        )
+        .unwrap();
+
+        let tags: Vec<String> = known_tags(&ws.root.join("sessions")).into_iter().collect();
+        assert_eq!(tags, vec!["backend", "proj-a"]);
+    }
+
+    #[test]
+    fn overlay_lookup_stops_at_repository_root() {
+        let ws = workspace();
+        let outer = ws.root.parent().unwrap();
+        write_overlay(outer, "enable_backup = false\n");
+
+        assert_eq!(workspace_overlay_path(&ws.session), None);
+
+        std::fs::remove_dir(ws.root.join(".git")).unwrap();
+        assert_eq!(
+            workspace_overlay_path(&ws.session),
+            Some(outer.join(WORKSPACE_OVERLAY))
+        );
+    }
+
+    #[test]
+    fn malformed_sidecar_falls_back_to_inherited_settings() {
+        let ws = workspace();
+        write_overlay(&ws.root, "enable_backup = false\n");
+        let sidecar = ws.root.join("sessions").join("tests.palingenesis.toml");
+        std::fs::write(&sidecar, "prompt_template = \"unterminated\n").unwrap();
+
+        let (found, errors) = SessionOverrides::discover(&ws.session);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, sidecar);
+        assert_eq!(found.overrides.prompt_template, None);
+        assert_eq!(found.overrides.enable_backup, Some(false));
+        assert_eq!(found.sources, vec![ws.root.join(WORKSPACE_OVERLAY)]);
+    }
+
+    #[test]
+    fn unknown_fields_and_bad_values_are_rejected() {
+        let ws = workspace();
+        let sidecar = ws.root.join("sessions").join("tests.palingenesis.toml");
+
+        for contents in [
+            "prompt_templte = \"typo\"\n",
+            "strategy = \"sideways\"\n",
+            "prompt_template = \"\"\n",
+        ] {
+            std::fs::write(&sidecar, contents).unwrap();
+            let (found, errors) = SessionOverrides::discover(&ws.session);
+            assert_eq!(errors.len(), 1, "{contents}");
+            assert!(found.overrides.is_empty());
+        }
+    }
+
+    #[test]
+    fn audit_metadata_lists_sources_and_fields() {
+        let found = SessionOverrides {
+            overrides: ResumeOverrides {
+                prompt_template: Some("{step}".to_string()),
+                enable_backup: Some(false),
+                ..ResumeOverrides::default()
+            },
+            sources: vec![PathBuf::from("/w/s.palingenesis.toml")],
+        };
+
+        let metadata = found.audit_metadata();
+
+        assert_eq!(
+            metadata["sidecar"],
+            serde_json::json!(["/w/s.palingenesis.toml"])
+        );
+        assert_eq!(
+            metadata["sidecar_overrides"],
+            serde_json::json!(["prompt_template", "enable_backup"])
+        );
+    }
+}
@@ -11,7 +11,7 @@ pub trait ResumeStrategy: Send + Sync {
     async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError>;
 
     /// Name of the strategy for logging.
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
     /// Check if retry should be attempted after this outcome.
     fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
@@ -0,0 +1,535 @@
+//! Session-level resume rate limit (`resume.min_resume_interval_secs`,
+//! `resume.max_resumes_per_hour`).
+//!
+//! Backoff spaces out retries within one incident; this guards against a
+//! session that stops again right after every successful resume. A resume
+//! closer than the minimum interval to the previous one waits out the rest of
+//! it. Once a session reaches the hourly cap, automatic resumes stop, the
+//! session is flagged for attention and a `resume_loop_suspected`
+//! notification goes out. Resumes start again when the oldest counted resume
+//! leaves the hour window or the operator runs `palingenesis attention clear`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::config::schema::ResumeConfig;
+use crate::daemon::suspend::Clock;
+use crate::events::DomainEvent;
+use crate::http::EventBroadcaster;
+use crate::i18n::Locale;
+use crate::notify::events::format_loop_summary;
+use crate::resume::deferral::Deferral;
+use crate::resume::sidecar::session_tags;
+use crate::resume::{ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy};
+use crate::state::{SessionStatus, StateBackend, StateFile, StateHandle};
+use crate::telemetry::Metrics;
+use crate::util::duration;
+
+/// Window `max_resumes_per_hour` is counted over.
+pub const RESUME_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Limits on automatic resumes of one session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResumeRateLimit {
+    /// Minimum gap between consecutive resumes; zero disables.
+    pub min_interval: Duration,
+    /// Resumes allowed per [`RESUME_WINDOW`]; zero disables.
+    pub max_per_hour: u32,
+}
+
+impl Default for ResumeRateLimit {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(120),
+            max_per_hour: 10,
+        }
+    }
+}
+
+/// What [`ResumeRateLimit::check`] allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleDecision {
+    Allow,
+    /// Too soon after the previous resume; wait until `until`.
+    Defer {
+        until: DateTime<Utc>,
+    },
+    /// The hourly cap is reached.
+    LoopSuspected {
+        resumes: u32,
+        span: Duration,
+    },
+}
+
+impl ResumeRateLimit {
+    pub fn from_config(config: &ResumeConfig) -> Self {
+        Self {
+            min_interval: Duration::from_secs(config.min_resume_interval_secs),
+            max_per_hour: config.max_resumes_per_hour,
+        }
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.min_interval.is_zero() && self.max_per_hour == 0
+    }
+
+    /// Decide whether a resume may start at `now` given earlier resume times.
+    pub fn check(&self, resumed_at: &[DateTime<Utc>], now: DateTime<Utc>) -> ThrottleDecision {
+        let recent: Vec<DateTime<Utc>> = resumed_at
+            .iter()
+            .copied()
+            .filter(|at| *at > window_start(now))
+            .collect();
+
+        if self.max_per_hour > 0 && recent.len() >= self.max_per_hour as usize {
+            let first = recent.iter().min().copied().unwrap_or(now);
+            return ThrottleDecision::LoopSuspected {
+                resumes: recent.len() as u32,
+                span: duration::between(first, now),
+            };
+        }
+
+        let interval = TimeDelta::from_std(self.min_interval).unwrap_or(TimeDelta::MAX);
+        match recent.iter().max() {
+            Some(last) if !self.min_interval.is_zero() => {
+                let until = last
+                    .checked_add_signed(interval)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+                if until > now {
+                    ThrottleDecision::Defer { until }
+                } else {
+                    ThrottleDecision::Allow
+                }
+            }
+            _ => ThrottleDecision::Allow,
+        }
+    }
+
+    /// Why a manual trigger for `state`'s current session is refused, if it is.
+    pub fn manual_trigger_error(&self, state: &StateFile, now: DateTime<Utc>) -> Option<String> {
+        let session = state.current_session.as_ref()?;
+        let history = state.resume_history(&session.path)?;
+        match self.check(&history.resumed_at, now) {
+            ThrottleDecision::Allow => None,
+            ThrottleDecision::Defer { until } => Some(format!(
+                "{} was resumed less than {} ago; retry {} or use --force",
+                session.path.display(),
+                duration::format_compact(self.min_interval),
+                duration::format_relative_to(until, now),
+            )),
+            ThrottleDecision::LoopSuspected { resumes, span } => Some(format!(
+                "{} for {}; use --force to start a new session anyway",
+                format_loop_summary(resumes, span.as_secs(), Locale::En),
+                session.path.display(),
+            )),
+        }
+    }
+}
+
+fn window_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - TimeDelta::from_std(RESUME_WINDOW).unwrap_or(TimeDelta::zero())
+}
+
+/// Applies a [`ResumeRateLimit`] around another strategy.
+pub struct ResumeThrottle {
+    inner: Box<dyn ResumeStrategy>,
+    limit: ResumeRateLimit,
+    state: Option<StateHandle>,
+    deferral: Deferral,
+}
+
+impl ResumeThrottle {
+    pub fn new(inner: Box<dyn ResumeStrategy>, limit: ResumeRateLimit) -> Self {
+        Self {
+            inner,
+            limit,
+            state: None,
+            deferral: Deferral::default(),
+        }
+    }
+
+    /// Use a specific state store instead of the default state file.
+    pub fn with_state_store<T: StateBackend + 'static>(mut self, store: T) -> Self {
+        self.state = Some(StateHandle::new(store));
+        self
+    }
+
+    /// Share an existing state handle instead of the global one.
+    pub fn with_state_handle(mut self, handle: StateHandle) -> Self {
+        self.state = Some(handle);
+        self
+    }
+
+    /// Evaluate the limits against this clock (for testing).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.deferral.clock = clock;
+        self
+    }
+
+    /// Publish deferral and loop notifications on this broadcaster.
+    pub fn with_event_broadcaster(mut self, events: EventBroadcaster) -> Self {
+        self.deferral.events = Some(events);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.deferral.cancel = Some(cancel);
+        self
+    }
+
+    fn state(&self) -> StateHandle {
+        self.state
+            .clone()
+            .unwrap_or_else(StateHandle::global_or_default)
+    }
+
+    /// Flag the session on the first detection; later detections only skip.
+    async fn flag_loop(&self, ctx: &ResumeContext, resumes: u32, span: Duration) {
+        let now = self.deferral.now();
+        let path = ctx.session_path.clone();
+        let first = self
+            .state()
+            .update_critical_async(|state| {
+                let history = state.resume_history_mut(&path);
+                if history.loop_suspected_at.is_some() {
+                    return false;
+                }
+                history.loop_suspected_at = Some(now);
+                if let Some(session) = state
+                    .current_session
+                    .as_mut()
+                    .filter(|session| session.path == path)
+                {
+                    session.status = SessionStatus::NeedsAttention;
+                }
+                true
+            })
+            .await;
+        let first = match first {
+            Ok(first) => first,
+            Err(err) => {
+                warn!(error = %err, "Failed to record resume loop");
+                true
+            }
+        };
+        if !first {
+            return;
+        }
+
+        warn!(
+            session = %path.display(),
+            resumes,
+            span_secs = span.as_secs(),
+            "Resume loop suspected; automatic resumes paused for this session"
+        );
+        if let Some(metrics) = Metrics::global() {
+            metrics.record_resume_loop_suspected();
+        }
+        self.deferral.publish(DomainEvent::ResumeLoopSuspected {
+            timestamp: now,
+            session_path: path,
+            resumes,
+            span_secs: span.as_secs(),
+        });
+    }
+
+    /// Count a resume starting now, lifting any earlier loop flag.
+    async fn record_resume(&self, ctx: &ResumeContext) {
+        let now = self.deferral.now();
+        let path = ctx.session_path.clone();
+        let tags = session_tags(&path);
+        let result = self
+            .state()
+            .update_critical_async(|state| {
+                let history = state.resume_history_mut(&path);
+                history.prune_before(window_start(now));
+                history.resumed_at.push(now);
+                history.last_incident.clone_from(&ctx.incident);
+                history.tags = tags;
+                if history.loop_suspected_at.take().is_some() {
+                    if let Some(session) = state.current_session.as_mut().filter(|session| {
+                        session.path == path && session.status == SessionStatus::NeedsAttention
+                    }) {
+                        session.status = SessionStatus::Active;
+                    }
+                }
+            })
+            .await;
+        if let Err(err) = result {
+            warn!(error = %err, "Failed to record resume in rate-limit history");
+        }
+    }
+}
+
+#[async_trait]
+impl ResumeStrategy for ResumeThrottle {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        let mut reported = false;
+        loop {
+            let resumed_at = self
+                .state()
+                .snapshot()
+                .resume_history(&ctx.session_path)
+                .map(|history| history.resumed_at.clone())
+                .unwrap_or_default();
+            match self.limit.check(&resumed_at, self.deferral.now()) {
+                ThrottleDecision::Allow => break,
+                ThrottleDecision::Defer { until } => {
+                    if !reported {
+                        info!(
+                            session = %ctx.session_path.display(),
+                            until = %until.to_rfc3339(),
+                            "Resume deferred by minimum resume interval"
+                        );
+                        self.deferral
+                            .report_deferred(ctx, until, "minimum resume interval");
+                        reported = true;
+                    }
+                    if !self
+                        .deferral
+                        .wait_until(until, "minimum resume interval")
+                        .await
+                    {
+                        info!("Throttled resume cancelled by shutdown");
+                        return Ok(ResumeOutcome::skipped(
+                            "shutdown during minimum resume interval",
+                        ));
+                    }
+                }
+                ThrottleDecision::LoopSuspected { resumes, span } => {
+                    self.flag_loop(ctx, resumes, span).await;
+                    return Ok(ResumeOutcome::skipped(format_loop_summary(
+                        resumes,
+                        span.as_secs(),
+                        Locale::En,
+                    )));
+                }
+            }
+        }
+        self.record_resume(ctx).await;
+        self.inner.execute(ctx).await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn should_retry(&self, outcome: &ResumeOutcome) -> bool {
+        self.inner.should_retry(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::events::NotificationEvent;
+    use crate::test_utils::TokioClock;
+    use std::sync::Mutex;
+
+    use crate::monitor::classifier::StopReason;
+    use crate::state::{CurrentSession, StateError};
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn limit() -> ResumeRateLimit {
+        ResumeRateLimit {
+            min_interval: Duration::from_secs(120),
+            max_per_hour: 5,
+        }
+    }
+
+    #[test]
+    fn check_defers_within_interval_and_flags_loops() {
+        let now = at("2026-01-06T04:00:00Z");
+        let limit = limit();
+        assert_eq!(limit.check(&[], now), ThrottleDecision::Allow);
+        assert_eq!(
+            limit.check(&[at("2026-01-06T03:59:00Z")], now),
+            ThrottleDecision::Defer {
+                until: at("2026-01-06T04:01:00Z")
+            }
+        );
+        assert_eq!(
+            limit.check(&[at("2026-01-06T03:58:00Z")], now),
+            ThrottleDecision::Allow
+        );
+
+        let five = [
+            at("2026-01-06T03:40:00Z"),
+            at("2026-01-06T03:45:00Z"),
+            at("2026-01-06T03:50:00Z"),
+            at("2026-01-06T03:55:00Z"),
+            at("2026-01-06T03:57:00Z"),
+        ];
+        assert_eq!(
+            limit.check(&five, now),
+            ThrottleDecision::LoopSuspected {
+                resumes: 5,
+                span: Duration::from_secs(20 * 60),
+            }
+        );
+        // An hour after the first one, only four still count.
+        assert_eq!(
+            limit.check(&five, at("2026-01-06T04:40:00Z")),
+            ThrottleDecision::Allow
+        );
+    }
+
+    #[test]
+    fn zero_limits_disable_throttling() {
+        let limit = ResumeRateLimit {
+            min_interval: Duration::ZERO,
+            max_per_hour: 0,
+        };
+        let now = at("2026-01-06T04:00:00Z");
+        assert!(limit.is_disabled());
+        assert_eq!(limit.check(&[now; 50], now), ThrottleDecision::Allow);
+    }
+
+    #[derive(Default)]
+    struct MemoryStore {
+        state: Mutex<StateFile>,
+    }
+
+    impl StateBackend for MemoryStore {
+        fn load(&self) -> StateFile {
+            self.state.lock().unwrap().clone()
+        }
+
+        fn save(&self, state: &StateFile) -> Result<(), StateError> {
+            *self.state.lock().unwrap() = state.clone();
+            Ok(())
+        }
+    }
+
+    struct CountingStrategy {
+        runs: Arc<Mutex<usize>>,
+    }
+
+    #[async_trait]
+    impl ResumeStrategy for CountingStrategy {
+        async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+            *self.runs.lock().unwrap() += 1;
+            Ok(ResumeOutcome::success(ctx.session_path.clone(), "resumed"))
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    fn ctx() -> ResumeContext {
+        ResumeContext::new("/tmp/session.md".into(), StopReason::ContextExhausted(None))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn repeated_resumes_are_spaced_then_stopped_then_reset() {
+        let initial = StateFile {
+            current_session: Some(CurrentSession {
+                path: "/tmp/session.md".into(),
+                ..CurrentSession::default()
+            }),
+            ..StateFile::default()
+        };
+        let state = StateHandle::new(MemoryStore {
+            state: Mutex::new(initial),
+        });
+        let start = at("2026-01-06T00:00:00Z");
+        let clock = TokioClock::starting_at(start);
+        let runs = Arc::new(Mutex::new(0));
+        let events = EventBroadcaster::default();
+        let mut rx = events.subscribe();
+        let throttle = ResumeThrottle::new(
+            Box::new(CountingStrategy {
+                runs: Arc::clone(&runs),
+            }),
+            limit(),
+        )
+        .with_state_handle(state.clone())
+        .with_clock(clock.clone())
+        .with_event_broadcaster(events);
+
+        // The session stops again ~40s after every resume.
+        for _ in 0..5 {
+            assert!(throttle.execute(&ctx()).await.unwrap().is_success());
+            tokio::time::advance(Duration::from_secs(40)).await;
+        }
+        assert_eq!(*runs.lock().unwrap(), 5);
+        let waited = DateTime::<Utc>::from(clock.wall()) - start;
+        assert!(waited >= TimeDelta::seconds(4 * 120), "waited {waited}");
+
+        let outcome = throttle.execute(&ctx()).await.unwrap();
+        assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+        assert_eq!(*runs.lock().unwrap(), 5);
+        let snapshot = state.snapshot();
+        assert_eq!(
+            snapshot.current_session.as_ref().unwrap().status,
+            SessionStatus::NeedsAttention
+        );
+
+        let mut escalation = None;
+        while let Ok(event) = rx.try_recv() {
+            if let NotificationEvent::ResumeLoopSuspected {
+                resumes, span_secs, ..
+            } = event
+            {
+                escalation = Some(format_loop_summary(resumes, span_secs, Locale::En));
+            }
+        }
+        assert_eq!(
+            escalation.as_deref(),
+            Some("5 resumes in 9 minutes — possible workflow loop")
+        );
+
+        // Still capped: no second notification.
+        throttle.execute(&ctx()).await.unwrap();
+        assert!(rx.try_recv().is_err());
+
+        tokio::time::advance(RESUME_WINDOW).await;
+        let incident =
+            std::path::PathBuf::from("/state/incidents/20260106T010000.000Z-session.tail.txt");
+        let with_incident = ctx().with_incident(incident.clone());
+        assert!(throttle.execute(&with_incident).await.unwrap().is_success());
+        assert_eq!(*runs.lock().unwrap(), 6);
+        let snapshot = state.snapshot();
+        assert_eq!(
+            snapshot.current_session.as_ref().unwrap().status,
+            SessionStatus::Active
+        );
+        let history = snapshot.resume_history(&ctx().session_path).unwrap();
+        assert_eq!(history.resumed_at.len(), 1);
+        assert!(history.loop_suspected_at.is_none());
+        assert_eq!(history.last_incident.as_ref(), Some(&incident));
+    }
+
+    #[test]
+    fn manual_trigger_is_refused_while_capped() {
+        let now = at("2026-01-06T04:00:00Z");
+        let mut state = StateFile {
+            current_session: Some(CurrentSession {
+                path: "/tmp/session.md".into(),
+                ..CurrentSession::default()
+            }),
+            ..StateFile::default()
+        };
+        assert!(limit().manual_trigger_error(&state, now).is_none());
+
+        state
+            .resume_history_mut(std::path::Path::new("/tmp/session.md"))
+            .resumed_at = vec![now - TimeDelta::minutes(1)];
+        let message = limit().manual_trigger_error(&state, now).unwrap();
+        assert!(
+            message.contains("retry in 1 minute or use --force"),
+            "{message}"
+        );
+    }
+}
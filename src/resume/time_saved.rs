@@ -90,8 +90,10 @@ mod tests {
 
     #[test]
     fn test_calculate_time_saved_custom_manual_restart() {
-        let mut config = MetricsConfig::default();
-        config.manual_restart_time_seconds = 1200;
+        let config = MetricsConfig {
+            manual_restart_time_seconds: 1200,
+            ..MetricsConfig::default()
+        };
         let calculation = calculate_time_saved(Duration::from_secs(90), &config);
         assert_eq!(calculation.wait_duration_seconds, 90.0);
         assert_eq!(calculation.manual_restart_seconds, 1200.0);
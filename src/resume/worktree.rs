@@ -0,0 +1,252 @@
+//! Git worktrees for new sessions (`resume.new_session.workspace_mode = "worktree"`).
+//!
+//! Each new session after context exhaustion gets a fresh worktree branched
+//! from the original workspace's HEAD, so a continuation can't damage the
+//! main checkout. Worktrees are recorded in the state file and removed with
+//! `palingenesis worktrees prune` once their session completes.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::config::Paths;
+use crate::config::schema::NewSessionResumeConfig;
+use crate::state::WorktreeRecord;
+use crate::util::process;
+
+/// Default branch prefix for new-session worktrees.
+pub const DEFAULT_BRANCH_PREFIX: &str = "palingenesis";
+
+/// Bound on one git call; `worktree add` checks out the whole tree.
+const GIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Error)]
+pub enum WorktreeError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("git {command} failed: {stderr}")]
+    Git { command: String, stderr: String },
+
+    #[error("git {command} timed out")]
+    Timeout { command: String },
+
+    #[error("Worktree {path} has uncommitted changes (use --force to remove anyway)")]
+    Dirty { path: PathBuf },
+}
+
+/// Creates and removes new-session worktrees.
+#[derive(Debug, Clone)]
+pub struct WorktreeManager {
+    program: String,
+    root: PathBuf,
+    branch_prefix: String,
+}
+
+impl WorktreeManager {
+    pub fn new(root: PathBuf, branch_prefix: impl Into<String>) -> Self {
+        Self {
+            program: "git".to_string(),
+            root,
+            branch_prefix: branch_prefix.into(),
+        }
+    }
+
+    /// Manager for `[resume.new_session]`; worktrees default to `<state dir>/worktrees`.
+    pub fn from_config(config: &NewSessionResumeConfig) -> Self {
+        let root = config
+            .worktree_root
+            .clone()
+            .unwrap_or_else(|| Paths::state_dir().join("worktrees"));
+        Self::new(root, config.branch_prefix.clone())
+    }
+
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Add a worktree of `workspace`'s HEAD for the session `session_stem`.
+    ///
+    /// Runs `git worktree add <root>/<stem>-<timestamp> -b <prefix>/<timestamp>`.
+    pub async fn create(
+        &self,
+        workspace: &Path,
+        session_stem: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(PathBuf, String), WorktreeError> {
+        let stamp = now.format("%Y%m%d-%H%M%S").to_string();
+        let path = self.root.join(format!("{session_stem}-{stamp}"));
+        let branch = format!("{}/{stamp}", self.branch_prefix.trim_end_matches('/'));
+        tokio::fs::create_dir_all(&self.root).await?;
+        self.git(
+            workspace,
+            &[
+                "worktree".as_ref(),
+                "add".as_ref(),
+                path.as_os_str(),
+                "-b".as_ref(),
+                branch.as_ref(),
+                "HEAD".as_ref(),
+            ],
+        )
+        .await?;
+        Ok((path, branch))
+    }
+
+    /// Whether the worktree has uncommitted or untracked changes.
+    pub async fn is_dirty(&self, worktree: &Path) -> Result<bool, WorktreeError> {
+        let status = self
+            .git(worktree, &["status".as_ref(), "--porcelain".as_ref()])
+            .await?;
+        Ok(!status.trim().is_empty())
+    }
+
+    /// Remove a recorded worktree, refusing when it is dirty unless `force`.
+    ///
+    /// A worktree directory that is already gone only has its git metadata pruned.
+    pub async fn remove(&self, record: &WorktreeRecord, force: bool) -> Result<(), WorktreeError> {
+        if !record.worktree.exists() {
+            self.git(&record.workspace, &["worktree".as_ref(), "prune".as_ref()])
+                .await?;
+            return Ok(());
+        }
+        if !force && self.is_dirty(&record.worktree).await? {
+            return Err(WorktreeError::Dirty {
+                path: record.worktree.clone(),
+            });
+        }
+        let mut args: Vec<&OsStr> = vec!["worktree".as_ref(), "remove".as_ref()];
+        if force {
+            args.push("--force".as_ref());
+        }
+        args.push(record.worktree.as_os_str());
+        self.git(&record.workspace, &args).await?;
+        Ok(())
+    }
+
+    async fn git(&self, dir: &Path, args: &[&OsStr]) -> Result<String, WorktreeError> {
+        let label = args
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        debug!(dir = %dir.display(), command = %label, "Running git");
+        let mut command = Command::new(&self.program);
+        command
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let finished = process::output(&mut command, Some(GIT_TIMEOUT)).await?;
+        if finished.timed_out {
+            return Err(WorktreeError::Timeout { command: label });
+        }
+        let output = finished.output;
+        if !output.status.success() {
+            return Err(WorktreeError::Git {
+                command: label,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .expect("git runs");
+        assert!(status.success(), "git {args:?}");
+    }
+
+    fn init_repo(dir: &Path) {
+        git(dir, &["init", "-q"]);
+        git(dir, &["config", "user.email", "test@example.com"]);
+        git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("README.md"), "hello\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-q", "-m", "init"]);
+    }
+
+    fn record(workspace: &Path, worktree: PathBuf) -> WorktreeRecord {
+        WorktreeRecord {
+            workspace: workspace.to_path_buf(),
+            worktree,
+            branch: "palingenesis/x".to_string(),
+            source_session: workspace.join("session.md"),
+            session: workspace.join("new.md"),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn creates_worktree_on_new_branch() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        init_repo(&repo);
+        let manager = WorktreeManager::new(temp.path().join("worktrees"), "resume/");
+
+        let now = DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (path, branch) = manager.create(&repo, "session", now).await.unwrap();
+
+        assert_eq!(path, temp.path().join("worktrees/session-20260102-030405"));
+        assert_eq!(branch, "resume/20260102-030405");
+        assert!(path.join("README.md").exists());
+        assert!(!manager.is_dirty(&path).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn remove_refuses_dirty_worktree_without_force() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        init_repo(&repo);
+        let manager = WorktreeManager::new(temp.path().join("worktrees"), "p");
+        let (path, _) = manager.create(&repo, "s", Utc::now()).await.unwrap();
+        std::fs::write(path.join("scratch.txt"), "wip").unwrap();
+        let record = record(&repo, path.clone());
+
+        let err = manager.remove(&record, false).await.unwrap_err();
+        assert!(matches!(err, WorktreeError::Dirty { .. }));
+        assert!(path.exists());
+
+        manager.remove(&record, true).await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn create_outside_repo_fails() {
+        let temp = tempfile::tempdir().unwrap();
+        let manager = WorktreeManager::new(temp.path().join("worktrees"), "p");
+        let err = manager
+            .create(temp.path(), "s", Utc::now())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WorktreeError::Git { .. }));
+    }
+}
@@ -9,6 +9,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::{debug, info, warn};
 
+use crate::config::paths::{Paths, UnsafePathError};
+use crate::daemon::initiator::Initiator;
+use crate::events::DomainEvent;
+use crate::state::audit_writer::AuditWriter;
+
 /// Configuration for audit logging.
 #[derive(Debug, Clone)]
 pub struct AuditConfig {
@@ -43,13 +48,42 @@ pub enum AuditEventType {
     ResumeCompleted,
     ResumeFailed,
     SessionCreated,
+    SessionCompleted,
     SessionBackedUp,
+    SessionRestored,
+    ModelMismatch,
     DaemonStarted,
     DaemonStopped,
     ConfigChanged,
+    PathRejected,
+    /// Observe mode: an action that was planned but not executed.
+    ActionObserved,
+    /// The daemon switched between active and observe mode.
+    ModeChanged,
+    /// A tracked session's status changed.
+    StateChanged,
+    /// An opencode crash held or released a session stop.
+    RestartCorrelation,
+    /// Someone paused, resumed or started a session; see the `initiator` metadata.
+    ControlAction,
+    /// A `[[schedules]]` entry started a session, or was skipped or failed.
+    ScheduledRun,
+    /// Startup reconciliation corrected the state file.
+    StateReconciled,
+    /// The artifact GC deleted a file or directory.
+    ArtifactDeleted,
     Error,
 }
 
+impl AuditEventType {
+    /// Entries that must survive a crash: failed or abandoned resumes and
+    /// errors such as state corruption. [`AuditLogger::log`] waits for
+    /// these to reach the disk.
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Self::ResumeFailed | Self::Error)
+    }
+}
+
 /// Outcome of an audited action.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -114,6 +148,12 @@ impl AuditEntry {
         self.metadata.insert(key.into(), value.into());
         self
     }
+
+    /// Record who asked for the action under the `initiator` key.
+    pub fn with_initiator(self, initiator: &Initiator) -> Self {
+        let value = serde_json::to_value(initiator).unwrap_or(Value::Null);
+        self.with_metadata("initiator", value)
+    }
 }
 
 /// Audit trail logger.
@@ -137,17 +177,27 @@ impl AuditLogger {
     }
 
     /// Log an audit entry.
+    ///
+    /// While the daemon's [`AuditWriter`] owns this file the entry is queued
+    /// and the call returns at once; a critical entry (see
+    /// [`AuditEventType::is_critical`]) returns only after it is on disk.
+    /// Without a writer the entry is appended directly.
     pub fn log(&self, entry: &AuditEntry) -> Result<(), AuditError> {
-        self.maybe_rotate()?;
-
         let json =
             serde_json::to_string(entry).map_err(|e| AuditError::Serialization(e.to_string()))?;
-
-        let mut file = self.open_for_append()?;
-        file.lock_exclusive()?;
-        writeln!(file, "{}", json)?;
-        file.flush()?;
-        FileExt::unlock(&file)?;
+        let critical = entry.event_type.is_critical();
+
+        let queued = AuditWriter::global()
+            .filter(|writer| writer.path() == self.config.audit_path)
+            .map(|writer| writer.submit(json.clone(), critical));
+        match queued {
+            Some(Ok(())) => {}
+            // The writer has shut down; append directly like a CLI process would.
+            Some(Err(AuditError::WriterClosed)) | None => {
+                append_lines(&self.config, std::slice::from_ref(&json), critical)?
+            }
+            Some(Err(err)) => return Err(err),
+        }
 
         debug!(
             event_type = ?entry.event_type,
@@ -158,69 +208,38 @@ impl AuditLogger {
         Ok(())
     }
 
-    /// Open audit file for appending, creating if needed.
-    fn open_for_append(&self) -> Result<File, AuditError> {
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.config.audit_path)?;
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let permissions = std::fs::Permissions::from_mode(self.config.file_mode);
-            std::fs::set_permissions(&self.config.audit_path, permissions)?;
-        }
-
-        Ok(file)
-    }
-
-    /// Rotate file if it exceeds max size.
-    fn maybe_rotate(&self) -> Result<(), AuditError> {
-        if !self.config.audit_path.exists() {
-            return Ok(());
-        }
-
-        let metadata = std::fs::metadata(&self.config.audit_path)?;
-        if metadata.len() < self.config.max_size {
-            return Ok(());
-        }
-
-        info!(
-            size = metadata.len(),
-            max = self.config.max_size,
-            "Rotating audit file"
-        );
-
-        for i in (1..self.config.max_files).rev() {
-            let from = self.rotated_path(i);
-            let to = self.rotated_path(i + 1);
-            if from.exists() {
-                std::fs::rename(&from, &to)?;
+    /// [`Self::log`] for async callers: waiting for a critical entry to
+    /// reach the disk does not hold a runtime thread.
+    pub async fn log_async(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        let json =
+            serde_json::to_string(entry).map_err(|e| AuditError::Serialization(e.to_string()))?;
+        let critical = entry.event_type.is_critical();
+
+        let queued =
+            match AuditWriter::global().filter(|writer| writer.path() == self.config.audit_path) {
+                Some(writer) => Some(writer.submit_async(json.clone(), critical).await),
+                None => None,
+            };
+        match queued {
+            Some(Ok(())) => {}
+            Some(Err(AuditError::WriterClosed)) | None => {
+                let config = self.config.clone();
+                tokio::task::spawn_blocking(move || append_lines(&config, &[json], critical))
+                    .await
+                    .map_err(|err| AuditError::WriteFailed(err.to_string()))??
             }
+            Some(Err(err)) => return Err(err),
         }
 
-        let first_rotated = self.rotated_path(1);
-        std::fs::rename(&self.config.audit_path, &first_rotated)?;
-
-        let oldest = self.rotated_path(self.config.max_files + 1);
-        if oldest.exists() {
-            std::fs::remove_file(&oldest)?;
-        }
+        debug!(
+            event_type = ?entry.event_type,
+            outcome = ?entry.outcome,
+            "Audit entry logged"
+        );
 
         Ok(())
     }
 
-    fn rotated_path(&self, index: usize) -> PathBuf {
-        let mut path = self.config.audit_path.clone();
-        let filename = path
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("audit.jsonl");
-        path.set_file_name(format!("{}.{}", filename, index));
-        path
-    }
-
     /// Query audit entries with filters.
     pub fn query(&self) -> AuditQuery {
         AuditQuery::new(&self.config.audit_path)
@@ -231,10 +250,21 @@ impl AuditLogger {
         session_path: &Path,
         stop_reason: &str,
     ) -> Result<(), AuditError> {
-        let entry = AuditEntry::new(AuditEventType::ResumeStarted, "Starting resume")
+        self.log_resume_started_with(session_path, stop_reason, HashMap::new())
+    }
+
+    /// Like [`Self::log_resume_started`], with extra metadata (e.g. `git_at_stop`).
+    pub fn log_resume_started_with(
+        &self,
+        session_path: &Path,
+        stop_reason: &str,
+        metadata: HashMap<String, Value>,
+    ) -> Result<(), AuditError> {
+        let mut entry = AuditEntry::new(AuditEventType::ResumeStarted, "Starting resume")
             .with_session(session_path.to_path_buf())
             .with_stop_reason(stop_reason)
             .with_outcome(AuditOutcome::Pending);
+        entry.metadata.extend(metadata);
         self.log(&entry)
     }
 
@@ -243,34 +273,258 @@ impl AuditLogger {
         session_path: &Path,
         action: &str,
     ) -> Result<(), AuditError> {
-        let entry = AuditEntry::new(AuditEventType::ResumeCompleted, action)
+        self.log_resume_completed_with(session_path, action, HashMap::new())
+    }
+
+    /// Like [`Self::log_resume_completed`], with extra metadata (e.g. `git_at_resume`).
+    pub fn log_resume_completed_with(
+        &self,
+        session_path: &Path,
+        action: &str,
+        metadata: HashMap<String, Value>,
+    ) -> Result<(), AuditError> {
+        let mut entry = AuditEntry::new(AuditEventType::ResumeCompleted, action)
             .with_session(session_path.to_path_buf())
             .with_outcome(AuditOutcome::Success);
+        entry.metadata.extend(metadata);
         self.log(&entry)
     }
 
-    pub fn log_resume_failed(&self, session_path: &Path, error: &str) -> Result<(), AuditError> {
-        let entry = AuditEntry::new(AuditEventType::ResumeFailed, "Resume failed")
+    /// A failed resume is critical; called from resume strategies, so it
+    /// waits for the disk with [`Self::log_async`].
+    pub async fn log_resume_failed(
+        &self,
+        session_path: &Path,
+        error: &str,
+    ) -> Result<(), AuditError> {
+        self.log_resume_failed_with(session_path, error, HashMap::new())
+            .await
+    }
+
+    /// Like [`Self::log_resume_failed`], with extra metadata (e.g. `postmortem`).
+    pub async fn log_resume_failed_with(
+        &self,
+        session_path: &Path,
+        error: &str,
+        metadata: HashMap<String, Value>,
+    ) -> Result<(), AuditError> {
+        let mut entry = AuditEntry::new(AuditEventType::ResumeFailed, "Resume failed")
             .with_session(session_path.to_path_buf())
             .with_outcome(AuditOutcome::Failure)
             .with_metadata("error", error);
-        self.log(&entry)
+        entry.metadata.extend(metadata);
+        self.log_async(&entry).await
     }
 
     pub fn log_session_created(&self, session_path: &Path) -> Result<(), AuditError> {
-        let entry = AuditEntry::new(AuditEventType::SessionCreated, "Session created")
+        self.log_session_created_with(session_path, HashMap::new())
+    }
+
+    /// Like [`Self::log_session_created`], with extra metadata (e.g. `worktree_path`).
+    pub fn log_session_created_with(
+        &self,
+        session_path: &Path,
+        metadata: HashMap<String, Value>,
+    ) -> Result<(), AuditError> {
+        let mut entry = AuditEntry::new(AuditEventType::SessionCreated, "Session created")
             .with_session(session_path.to_path_buf())
             .with_outcome(AuditOutcome::Success);
+        entry.metadata.extend(metadata);
         self.log(&entry)
     }
 
-    pub fn log_session_backed_up(&self, original: &Path, backup: &Path) -> Result<(), AuditError> {
-        let entry = AuditEntry::new(AuditEventType::SessionBackedUp, "Session backed up")
-            .with_session(original.to_path_buf())
-            .with_outcome(AuditOutcome::Success)
-            .with_metadata("backup_path", backup.display().to_string());
+    /// Record a finished workflow with its final stats (resumes, duration, time saved).
+    pub fn log_session_completed_with(
+        &self,
+        session_path: &Path,
+        metadata: HashMap<String, Value>,
+    ) -> Result<(), AuditError> {
+        let mut entry = AuditEntry::new(AuditEventType::SessionCompleted, "Session completed")
+            .with_session(session_path.to_path_buf())
+            .with_outcome(AuditOutcome::Success);
+        entry.metadata.extend(metadata);
         self.log(&entry)
     }
+
+    pub fn log_session_backed_up(&self, original: &Path, backup: &Path) -> Result<(), AuditError> {
+        self.record(&DomainEvent::SessionBackedUp {
+            timestamp: Utc::now(),
+            session_path: original.to_path_buf(),
+            backup_path: backup.to_path_buf(),
+        })
+    }
+
+    pub fn log_session_restored(&self, session: &Path, backup: &Path) -> Result<(), AuditError> {
+        self.record(&DomainEvent::SessionDeleted {
+            timestamp: Utc::now(),
+            session_path: session.to_path_buf(),
+            restored_from: Some(backup.to_path_buf()),
+        })
+    }
+
+    pub fn log_model_mismatch(
+        &self,
+        session: &Path,
+        previous: &str,
+        current: &str,
+    ) -> Result<(), AuditError> {
+        self.record(&DomainEvent::ModelChanged {
+            timestamp: Utc::now(),
+            session_path: session.to_path_buf(),
+            previous_model: previous.to_string(),
+            current_model: current.to_string(),
+        })
+    }
+
+    pub fn log_path_rejected(&self, path: &Path, reason: &str) -> Result<(), AuditError> {
+        self.record(&DomainEvent::PathRejected {
+            timestamp: Utc::now(),
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Record an action observe mode held back; `action` is prefixed `OBSERVE:`.
+    pub fn log_action_observed(
+        &self,
+        session_path: &Path,
+        stop_reason: &str,
+        action: &str,
+    ) -> Result<(), AuditError> {
+        self.record(&DomainEvent::ActionObserved {
+            timestamp: Utc::now(),
+            session_path: session_path.to_path_buf(),
+            stop_reason: stop_reason.to_string(),
+            action: action.to_string(),
+        })
+    }
+
+    pub fn log_mode_changed(
+        &self,
+        previous: &str,
+        current: &str,
+        initiator: &Initiator,
+    ) -> Result<(), AuditError> {
+        self.record(&DomainEvent::ModeChanged {
+            timestamp: Utc::now(),
+            previous: previous.to_string(),
+            current: current.to_string(),
+            initiator: Some(initiator.clone()),
+        })
+    }
+
+    /// Log the audit form of `event`; events the trail does not record are skipped.
+    pub fn record(&self, event: &DomainEvent) -> Result<(), AuditError> {
+        match event.audit_entry() {
+            Some(entry) => self.log(&entry),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Append `lines` to the audit file under an exclusive lock, rotating it
+/// first if it has grown past `max_size`; with `sync` the data is fsynced
+/// before returning.
+pub(crate) fn append_lines(
+    config: &AuditConfig,
+    lines: &[String],
+    sync: bool,
+) -> Result<(), AuditError> {
+    maybe_rotate(config)?;
+
+    let mut file = open_for_append(config)?;
+    file.lock_exclusive()?;
+    let mut written = Ok(());
+    for line in lines {
+        written = writeln!(file, "{}", line);
+        if written.is_err() {
+            break;
+        }
+    }
+    let written = written
+        .and_then(|()| file.flush())
+        .and_then(|()| if sync { file.sync_data() } else { Ok(()) });
+    FileExt::unlock(&file)?;
+    written?;
+    Ok(())
+}
+
+/// Open audit file for appending, creating if needed.
+fn open_for_append(config: &AuditConfig) -> Result<File, AuditError> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&config.audit_path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(config.file_mode);
+        std::fs::set_permissions(&config.audit_path, permissions)?;
+    }
+
+    Ok(file)
+}
+
+/// Rotate file if it exceeds max size.
+fn maybe_rotate(config: &AuditConfig) -> Result<(), AuditError> {
+    if !config.audit_path.exists() {
+        return Ok(());
+    }
+
+    let metadata = std::fs::metadata(&config.audit_path)?;
+    if metadata.len() < config.max_size {
+        return Ok(());
+    }
+
+    info!(
+        size = metadata.len(),
+        max = config.max_size,
+        "Rotating audit file"
+    );
+
+    for i in (1..config.max_files).rev() {
+        let from = rotated_path(config, i);
+        let to = rotated_path(config, i + 1);
+        if from.exists() {
+            std::fs::rename(&from, &to)?;
+        }
+    }
+
+    let first_rotated = rotated_path(config, 1);
+    std::fs::rename(&config.audit_path, &first_rotated)?;
+
+    let oldest = rotated_path(config, config.max_files + 1);
+    if oldest.exists() {
+        std::fs::remove_file(&oldest)?;
+    }
+
+    Ok(())
+}
+
+fn rotated_path(config: &AuditConfig, index: usize) -> PathBuf {
+    let mut path = config.audit_path.clone();
+    let filename = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("audit.jsonl");
+    path.set_file_name(format!("{}.{}", filename, index));
+    path
+}
+
+/// Log and audit a path refused by [`crate::config::paths::safe_path`].
+pub fn record_rejected_path(path: &Path, err: &UnsafePathError) {
+    warn!(path = %path.display(), error = %err, "Rejected unsafe path");
+    let result = Paths::ensure_state_dir()
+        .map_err(|dir_err| dir_err.to_string())
+        .and_then(|state_dir| {
+            AuditLogger::new(&state_dir)
+                .log_path_rejected(path, &err.to_string())
+                .map_err(|audit_err| audit_err.to_string())
+        });
+    if let Err(audit_err) = result {
+        warn!(error = %audit_err, "Failed to audit rejected path");
+    }
 }
 
 /// Query builder for audit entries.
@@ -383,4 +637,10 @@ pub enum AuditError {
 
     #[error("Deserialization error: {0}")]
     Deserialization(String),
+
+    #[error("Audit write failed: {0}")]
+    WriteFailed(String),
+
+    #[error("Audit writer has shut down")]
+    WriterClosed,
 }
@@ -0,0 +1,388 @@
+//! Background writer that owns the daemon's audit file.
+//!
+//! Audit entries are written from the resume path, so a slow disk used to
+//! add its latency to every resume. The daemon now installs one
+//! [`AuditWriter`]: [`crate::state::AuditLogger::log`] queues routine entries
+//! and returns, and the writer thread appends them in batches every
+//! [`DEFAULT_AUDIT_FLUSH_INTERVAL`]. A critical entry (failed or abandoned
+//! resume, state corruption) flushes everything queued before it and is
+//! fsynced before the logging call returns, so a crash cannot lose it.
+//! Entries keep their order either way.
+//!
+//! A failed write is counted and marks the writer degraded (`/health`
+//! reports `audit_degraded`) until a later write succeeds. On shutdown the
+//! daemon drains the queue with a timeout; entries logged after that are
+//! appended directly, as in CLI processes.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+
+use crate::state::audit::{AuditConfig, AuditError, append_lines};
+
+/// How long routine entries may wait in the queue before being written.
+pub const DEFAULT_AUDIT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a critical entry waits for the writer to confirm it is durable.
+pub const CRITICAL_CONFIRM_TIMEOUT: Duration = Duration::from_secs(5);
+/// Queued entries that trigger a write before the interval elapses.
+const MAX_BATCH: usize = 256;
+
+static GLOBAL_WRITER: OnceLock<AuditWriter> = OnceLock::new();
+
+enum Message {
+    Entry {
+        line: String,
+        /// Set for critical entries; answered once the entry is fsynced.
+        confirm: Option<Confirm>,
+    },
+    Drain(SyncSender<()>),
+}
+
+/// Where a critical entry's write result goes.
+enum Confirm {
+    Blocking(SyncSender<Result<(), String>>),
+    Async(oneshot::Sender<Result<(), String>>),
+}
+
+impl Confirm {
+    fn send(self, result: Result<(), String>) {
+        match self {
+            Self::Blocking(sender) => {
+                let _ = sender.send(result);
+            }
+            Self::Async(sender) => {
+                let _ = sender.send(result);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    failures: AtomicU64,
+    degraded: AtomicBool,
+}
+
+/// Handle to the audit writer thread.
+#[derive(Clone)]
+pub struct AuditWriter {
+    path: PathBuf,
+    sender: Sender<Message>,
+    health: Arc<Health>,
+}
+
+impl AuditWriter {
+    /// Start a writer for `config.audit_path` that writes routine entries
+    /// every `flush_interval`.
+    pub fn spawn(config: AuditConfig, flush_interval: Duration) -> std::io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let health = Arc::new(Health::default());
+        let path = config.audit_path.clone();
+        let thread_health = Arc::clone(&health);
+        std::thread::Builder::new()
+            .name("audit-writer".to_string())
+            .spawn(move || {
+                WriterLoop {
+                    config,
+                    pending: Vec::new(),
+                    health: thread_health,
+                }
+                .run(receiver, flush_interval)
+            })?;
+        Ok(Self {
+            path,
+            sender,
+            health,
+        })
+    }
+
+    /// Make `writer` the process-wide writer used by `AuditLogger`.
+    pub fn set_global(writer: AuditWriter) -> bool {
+        GLOBAL_WRITER.set(writer).is_ok()
+    }
+
+    pub fn global() -> Option<AuditWriter> {
+        GLOBAL_WRITER.get().cloned()
+    }
+
+    /// The audit file this writer owns.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue a serialized entry. A critical entry blocks until it is
+    /// durable, or fails after [`CRITICAL_CONFIRM_TIMEOUT`].
+    ///
+    /// Async code uses [`Self::submit_async`], which does not hold a
+    /// runtime thread while a critical entry is fsynced.
+    pub fn submit(&self, line: String, critical: bool) -> Result<(), AuditError> {
+        if !critical {
+            return self.enqueue(line, None);
+        }
+        let (confirm, confirmed) = mpsc::sync_channel(1);
+        self.enqueue(line, Some(Confirm::Blocking(confirm)))?;
+        match confirmed.recv_timeout(CRITICAL_CONFIRM_TIMEOUT) {
+            Ok(result) => result.map_err(AuditError::WriteFailed),
+            Err(RecvTimeoutError::Timeout) => Err(confirm_timed_out()),
+            Err(RecvTimeoutError::Disconnected) => Err(AuditError::WriterClosed),
+        }
+    }
+
+    /// [`Self::submit`] that awaits a critical entry's confirmation.
+    pub async fn submit_async(&self, line: String, critical: bool) -> Result<(), AuditError> {
+        if !critical {
+            return self.enqueue(line, None);
+        }
+        let (confirm, confirmed) = oneshot::channel();
+        self.enqueue(line, Some(Confirm::Async(confirm)))?;
+        match tokio::time::timeout(CRITICAL_CONFIRM_TIMEOUT, confirmed).await {
+            Ok(Ok(result)) => result.map_err(AuditError::WriteFailed),
+            Ok(Err(_)) => Err(AuditError::WriterClosed),
+            Err(_) => Err(confirm_timed_out()),
+        }
+    }
+
+    fn enqueue(&self, line: String, confirm: Option<Confirm>) -> Result<(), AuditError> {
+        self.sender
+            .send(Message::Entry { line, confirm })
+            .map_err(|_| AuditError::WriterClosed)
+    }
+
+    /// Write everything queued and stop the writer, waiting at most
+    /// `timeout`. Returns whether the queue was drained in time.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        let (done, drained) = mpsc::sync_channel(1);
+        if self.sender.send(Message::Drain(done)).is_err() {
+            return true;
+        }
+        tokio::task::spawn_blocking(move || drained.recv_timeout(timeout).is_ok())
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Entries lost to failed writes since the writer started.
+    pub fn write_failures(&self) -> u64 {
+        self.health.failures.load(Ordering::Relaxed)
+    }
+
+    /// Whether the most recent write failed.
+    pub fn is_degraded(&self) -> bool {
+        self.health.degraded.load(Ordering::Relaxed)
+    }
+}
+
+fn confirm_timed_out() -> AuditError {
+    AuditError::WriteFailed("timed out waiting for the audit writer".to_string())
+}
+
+struct WriterLoop {
+    config: AuditConfig,
+    pending: Vec<String>,
+    health: Arc<Health>,
+}
+
+impl WriterLoop {
+    fn run(mut self, receiver: Receiver<Message>, flush_interval: Duration) {
+        // When the oldest pending entry is due; later entries do not push it back.
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let message = match deadline {
+                Some(deadline) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match message {
+                Ok(Message::Entry { line, confirm }) => {
+                    self.pending.push(line);
+                    match confirm {
+                        Some(confirm) => confirm.send(self.flush(true)),
+                        None if self.pending.len() >= MAX_BATCH => {
+                            let _ = self.flush(false);
+                        }
+                        None => {}
+                    }
+                }
+                Ok(Message::Drain(done)) => {
+                    // Take whatever raced the drain request, then close the
+                    // queue so later entries go straight to the file.
+                    let mut confirms = Vec::new();
+                    for message in receiver.try_iter() {
+                        if let Message::Entry { line, confirm } = message {
+                            self.pending.push(line);
+                            confirms.extend(confirm);
+                        }
+                    }
+                    let result = self.flush(!confirms.is_empty());
+                    for confirm in confirms {
+                        confirm.send(result.clone());
+                    }
+                    drop(receiver);
+                    let _ = done.send(());
+                    return;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.flush(false);
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = self.flush(false);
+                    return;
+                }
+            }
+            deadline = match deadline {
+                _ if self.pending.is_empty() => None,
+                Some(deadline) => Some(deadline),
+                None => Some(Instant::now() + flush_interval),
+            };
+        }
+    }
+
+    fn flush(&mut self, sync: bool) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let lines = std::mem::take(&mut self.pending);
+        match append_lines(&self.config, &lines, sync) {
+            Ok(()) => {
+                self.health.degraded.store(false, Ordering::Relaxed);
+                debug!(entries = lines.len(), sync, "Audit entries written");
+                Ok(())
+            }
+            Err(err) => {
+                self.health
+                    .failures
+                    .fetch_add(lines.len() as u64, Ordering::Relaxed);
+                self.health.degraded.store(true, Ordering::Relaxed);
+                warn!(
+                    error = %err,
+                    entries = lines.len(),
+                    path = %self.config.audit_path.display(),
+                    "Failed to write audit entries"
+                );
+                Err(err.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &Path) -> AuditConfig {
+        AuditConfig {
+            audit_path: dir.join("audit.jsonl"),
+            ..AuditConfig::default()
+        }
+    }
+
+    fn lines(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn critical_entry_is_durable_when_submit_returns() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AuditWriter::spawn(config(dir.path()), Duration::from_secs(3600)).unwrap();
+
+        writer.submit("routine".to_string(), false).unwrap();
+        assert!(
+            lines(writer.path()).is_empty(),
+            "routine entries are batched"
+        );
+
+        writer.submit("critical".to_string(), true).unwrap();
+        assert_eq!(lines(writer.path()), ["routine", "critical"]);
+    }
+
+    #[test]
+    fn order_is_kept_across_mixed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AuditWriter::spawn(config(dir.path()), Duration::from_millis(5)).unwrap();
+
+        let mut expected = Vec::new();
+        for i in 0..40 {
+            let line = format!("entry-{i}");
+            writer.submit(line.clone(), i % 7 == 0).unwrap();
+            expected.push(line);
+        }
+        writer.submit("last".to_string(), true).unwrap();
+        expected.push("last".to_string());
+
+        assert_eq!(lines(writer.path()), expected);
+    }
+
+    #[test]
+    fn steady_entries_do_not_postpone_the_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AuditWriter::spawn(config(dir.path()), Duration::from_millis(100)).unwrap();
+
+        // Entries arrive faster than the interval for five intervals.
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(500) {
+            writer.submit("routine".to_string(), false).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        assert!(!lines(writer.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn async_critical_entry_is_durable_when_submit_returns() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AuditWriter::spawn(config(dir.path()), Duration::from_secs(3600)).unwrap();
+
+        writer
+            .submit_async("routine".to_string(), false)
+            .await
+            .unwrap();
+        writer
+            .submit_async("critical".to_string(), true)
+            .await
+            .unwrap();
+
+        assert_eq!(lines(writer.path()), ["routine", "critical"]);
+    }
+
+    #[tokio::test]
+    async fn shutdown_drains_pending_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AuditWriter::spawn(config(dir.path()), Duration::from_secs(3600)).unwrap();
+        for i in 0..3 {
+            writer.submit(format!("entry-{i}"), false).unwrap();
+        }
+
+        assert!(writer.shutdown(Duration::from_secs(5)).await);
+        assert_eq!(lines(writer.path()), ["entry-0", "entry-1", "entry-2"]);
+        assert!(matches!(
+            writer.submit("late".to_string(), false),
+            Err(AuditError::WriterClosed)
+        ));
+    }
+
+    #[test]
+    fn failed_writes_are_counted_and_degrade() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AuditConfig {
+            audit_path: dir.path().join("missing").join("audit.jsonl"),
+            ..AuditConfig::default()
+        };
+        let writer = AuditWriter::spawn(config, Duration::from_secs(3600)).unwrap();
+
+        writer.submit("routine".to_string(), false).unwrap();
+        let result = writer.submit("critical".to_string(), true);
+
+        assert!(matches!(result, Err(AuditError::WriteFailed(_))));
+        assert_eq!(writer.write_failures(), 2);
+        assert!(writer.is_degraded());
+    }
+}
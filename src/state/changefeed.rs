@@ -0,0 +1,446 @@
+//! Changefeed of persisted state mutations, for external sync.
+//!
+//! Each time [`StateHandle`](super::StateHandle) writes the state file, the
+//! written state is compared with the previous write and one
+//! [`ChangeRecord`] is appended per changed entity: the tracked session, the
+//! daemon state, stats, and each added, changed or removed entry of the
+//! history lists. Records carry a sequence number that keeps increasing
+//! across restarts, so consumers can ask for everything after the last one
+//! they saw (`GET /api/v1/changes?since_seq=N`).
+//!
+//! Records go to `changes.jsonl` in the state directory. When that file
+//! holds `max_records` records it replaces `changes.1.jsonl`; a consumer
+//! asking for records older than the oldest one kept is told the feed was
+//! compacted and should take a fresh snapshot.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::sync::watch;
+use tracing::warn;
+
+use super::schema::StateFile;
+
+/// Records per file before the feed rotates.
+pub const DEFAULT_MAX_RECORDS: usize = 10_000;
+
+/// What happened to an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOp {
+    /// A session became the tracked session.
+    Tracked,
+    /// The tracked session was cleared.
+    Untracked,
+    /// A single-valued entity took a new value.
+    Set,
+    /// A single-valued entity was cleared.
+    Clear,
+    /// An entry was added to a list.
+    Append,
+    /// A list entry changed.
+    Update,
+    /// A list entry was removed.
+    Remove,
+}
+
+/// One persisted change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// State field that changed (`session` for the tracked session).
+    pub entity: String,
+    pub op: ChangeOp,
+    /// List entry key (a session path or incident id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// New value; `null` for `untracked`, `clear` and `remove`.
+    pub value: Value,
+}
+
+/// Answer to "what changed after `since_seq`?".
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangesSince {
+    /// Records after `since_seq`, oldest first, and the latest sequence number.
+    Changes {
+        changes: Vec<ChangeRecord>,
+        last_seq: u64,
+    },
+    /// Records after `since_seq` were rotated away; the oldest kept is `oldest_seq`.
+    Compacted { oldest_seq: u64 },
+}
+
+/// Bounded on-disk changefeed; see the module docs.
+pub struct Changefeed {
+    path: PathBuf,
+    max_records: usize,
+    inner: Mutex<Feed>,
+    last_seq: watch::Sender<u64>,
+}
+
+struct Feed {
+    /// Last recorded state, as JSON.
+    baseline: Option<Map<String, Value>>,
+    /// Records in both files, oldest first.
+    retained: VecDeque<ChangeRecord>,
+    /// Records in the current file.
+    current_len: usize,
+    last_seq: u64,
+}
+
+impl Changefeed {
+    /// Open the feed at `path`, resuming its sequence numbers.
+    pub fn new(path: PathBuf) -> Self {
+        let mut retained: VecDeque<ChangeRecord> = read_records(&rotated_path(&path)).into();
+        let current = read_records(&path);
+        let current_len = current.len();
+        retained.extend(current);
+        let last_seq = retained.back().map(|record| record.seq).unwrap_or(0);
+        Self {
+            path,
+            max_records: DEFAULT_MAX_RECORDS,
+            inner: Mutex::new(Feed {
+                baseline: None,
+                retained,
+                current_len,
+                last_seq,
+            }),
+            last_seq: watch::Sender::new(last_seq),
+        }
+    }
+
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = max_records.max(1);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn last_seq(&self) -> u64 {
+        *self.last_seq.borrow()
+    }
+
+    /// Compare later writes against `state` without recording it.
+    pub fn set_baseline(&self, state: &StateFile) {
+        self.feed().baseline = Some(to_map(state));
+    }
+
+    /// Record how `state`, just written, differs from the previous write.
+    pub fn record(&self, state: &StateFile) {
+        let next = to_map(state);
+        let mut feed = self.feed();
+        let Some(previous) = feed.baseline.replace(next.clone()) else {
+            return;
+        };
+        let timestamp = Utc::now();
+        let mut records = Vec::new();
+        for (entity, op, key, value) in diff(&previous, &next) {
+            feed.last_seq += 1;
+            records.push(ChangeRecord {
+                seq: feed.last_seq,
+                timestamp,
+                entity,
+                op,
+                key,
+                value,
+            });
+        }
+        if records.is_empty() {
+            return;
+        }
+        for chunk in records.chunks(self.max_records) {
+            if feed.current_len >= self.max_records {
+                self.rotate(&mut feed);
+            }
+            if let Err(err) = append(&self.path, chunk) {
+                warn!(error = %err, path = %self.path.display(), "Failed to append to changefeed");
+            }
+            feed.current_len += chunk.len();
+            feed.retained.extend(chunk.iter().cloned());
+        }
+        let last_seq = feed.last_seq;
+        drop(feed);
+        self.last_seq.send_replace(last_seq);
+    }
+
+    /// Records after `since_seq`.
+    pub fn since(&self, since_seq: u64) -> ChangesSince {
+        let feed = self.feed();
+        let oldest_seq = feed
+            .retained
+            .front()
+            .map(|record| record.seq)
+            .unwrap_or(feed.last_seq + 1);
+        if since_seq.saturating_add(1) < oldest_seq {
+            return ChangesSince::Compacted { oldest_seq };
+        }
+        ChangesSince::Changes {
+            changes: feed
+                .retained
+                .iter()
+                .filter(|record| record.seq > since_seq)
+                .cloned()
+                .collect(),
+            last_seq: feed.last_seq,
+        }
+    }
+
+    /// Like [`Changefeed::since`], but waits up to `timeout` for a record
+    /// when there is none yet.
+    pub async fn wait_since(&self, since_seq: u64, timeout: Duration) -> ChangesSince {
+        let mut updates = self.last_seq.subscribe();
+        let _ = tokio::time::timeout(timeout, updates.wait_for(|last| *last > since_seq)).await;
+        self.since(since_seq)
+    }
+
+    fn rotate(&self, feed: &mut Feed) {
+        if let Err(err) = std::fs::rename(&self.path, rotated_path(&self.path)) {
+            warn!(error = %err, path = %self.path.display(), "Failed to rotate changefeed");
+        }
+        let dropped = feed.retained.len() - feed.current_len;
+        feed.retained.drain(..dropped);
+        feed.current_len = 0;
+    }
+
+    fn feed(&self) -> MutexGuard<'_, Feed> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// `changes.jsonl` -> `changes.1.jsonl`.
+fn rotated_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}.1.jsonl"))
+}
+
+fn read_records(path: &Path) -> Vec<ChangeRecord> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn append(path: &Path, records: &[ChangeRecord]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut buf = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut buf, record)?;
+        buf.push(b'\n');
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&buf)?;
+    file.sync_data()
+}
+
+fn to_map(state: &StateFile) -> Map<String, Value> {
+    match serde_json::to_value(state) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+type Change = (String, ChangeOp, Option<String>, Value);
+
+/// Changes from `previous` to `next`, field by field.
+fn diff(previous: &Map<String, Value>, next: &Map<String, Value>) -> Vec<Change> {
+    let mut fields: Vec<&String> = previous.keys().chain(next.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    let mut changes = Vec::new();
+    for field in fields {
+        if field == "version" {
+            continue;
+        }
+        let before = previous.get(field).unwrap_or(&Value::Null);
+        let after = next.get(field).unwrap_or(&Value::Null);
+        if before == after {
+            continue;
+        }
+        match (field.as_str(), before, after) {
+            ("current_session", _, Value::Null) => {
+                changes.push((
+                    "session".to_string(),
+                    ChangeOp::Untracked,
+                    None,
+                    Value::Null,
+                ));
+            }
+            ("current_session", _, _) => {
+                let op = if before.get("path") == after.get("path") {
+                    ChangeOp::Update
+                } else {
+                    ChangeOp::Tracked
+                };
+                changes.push(("session".to_string(), op, None, after.clone()));
+            }
+            (_, Value::Array(_), _) | (_, _, Value::Array(_)) => {
+                diff_entries(field, before, after, &mut changes);
+            }
+            (_, _, Value::Null) => {
+                changes.push((field.clone(), ChangeOp::Clear, None, Value::Null));
+            }
+            _ => changes.push((field.clone(), ChangeOp::Set, None, after.clone())),
+        }
+    }
+    changes
+}
+
+/// Per-entry changes of a list field, matching entries by their key.
+fn diff_entries(field: &str, before: &Value, after: &Value, changes: &mut Vec<Change>) {
+    let key_field = match field {
+        "escalations" => "incident_id",
+        "worktrees" => "worktree",
+        _ => "path",
+    };
+    let keyed = |list: &Value| -> Vec<(String, Value)> {
+        list.as_array()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(index, entry)| {
+                        let key = match entry.get(key_field) {
+                            Some(Value::String(key)) => key.clone(),
+                            _ => index.to_string(),
+                        };
+                        (key, entry.clone())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let before = keyed(before);
+    let after = keyed(after);
+
+    for (key, _) in &before {
+        if !after.iter().any(|(other, _)| other == key) {
+            changes.push((
+                field.to_string(),
+                ChangeOp::Remove,
+                Some(key.clone()),
+                Value::Null,
+            ));
+        }
+    }
+    for (key, entry) in &after {
+        match before.iter().find(|(other, _)| other == key) {
+            Some((_, old)) if old == entry => {}
+            Some(_) => changes.push((
+                field.to_string(),
+                ChangeOp::Update,
+                Some(key.clone()),
+                entry.clone(),
+            )),
+            None => changes.push((
+                field.to_string(),
+                ChangeOp::Append,
+                Some(key.clone()),
+                entry.clone(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{CurrentSession, DaemonState, ResumeHistory};
+
+    fn feed(dir: &Path) -> Changefeed {
+        let feed = Changefeed::new(dir.join("changes.jsonl"));
+        feed.set_baseline(&StateFile::default());
+        feed
+    }
+
+    fn changes(feed: &Changefeed, since_seq: u64) -> Vec<ChangeRecord> {
+        match feed.since(since_seq) {
+            ChangesSince::Changes { changes, .. } => changes,
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn diff_names_the_entity_and_operation() {
+        let temp = tempfile::tempdir().unwrap();
+        let feed = feed(temp.path());
+        let mut state = StateFile {
+            current_session: Some(CurrentSession {
+                path: PathBuf::from("/s/a.md"),
+                ..CurrentSession::default()
+            }),
+            resume_history: vec![ResumeHistory::new(PathBuf::from("/s/a.md"))],
+            ..StateFile::default()
+        };
+        feed.record(&state);
+
+        state.daemon_state = DaemonState::Paused;
+        state.current_session = None;
+        state.resume_history.clear();
+        feed.record(&state);
+
+        let ops: Vec<(String, ChangeOp, Option<String>)> = changes(&feed, 0)
+            .into_iter()
+            .map(|record| (record.entity, record.op, record.key))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                ("session".into(), ChangeOp::Tracked, None),
+                (
+                    "resume_history".into(),
+                    ChangeOp::Append,
+                    Some("/s/a.md".into())
+                ),
+                ("session".into(), ChangeOp::Untracked, None),
+                ("daemon_state".into(), ChangeOp::Set, None),
+                (
+                    "resume_history".into(),
+                    ChangeOp::Remove,
+                    Some("/s/a.md".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn first_record_without_a_baseline_only_sets_it() {
+        let temp = tempfile::tempdir().unwrap();
+        let feed = Changefeed::new(temp.path().join("changes.jsonl"));
+        let mut state = StateFile::default();
+        state.stats.total_resumes = 1;
+        feed.record(&state);
+        assert_eq!(feed.last_seq(), 0);
+
+        state.stats.total_resumes = 2;
+        feed.record(&state);
+        assert_eq!(feed.last_seq(), 1);
+    }
+
+    #[test]
+    fn rotated_path_sits_beside_the_feed() {
+        assert_eq!(
+            rotated_path(Path::new("/state/changes.jsonl")),
+            PathBuf::from("/state/changes.1.jsonl")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! On-disk encodings of the state file (`daemon.state_format`).
+//!
+//! JSON stays the default so the file can be read and fixed by hand.
+//! `msgpack` writes MessagePack with field names, which is about half the
+//! size and faster to save and load once resume histories, timelines and
+//! completed sessions pile up (see `tests/state_format_test.rs`). bincode
+//! and postcard were ruled out: they cannot represent omitted fields, and
+//! most state fields are skipped when empty.
+//!
+//! A binary file starts with one JSON header line (magic, version, format),
+//! so [`decode`] recognizes either encoding. Changing the setting therefore
+//! needs no migration step: the old file is read as it is and the next save
+//! writes the new format.
+
+use serde::{Deserialize, Serialize};
+
+use super::schema::StateFile;
+use super::store::StateError;
+
+/// First field of a binary state file's header line.
+pub const STATE_MAGIC: &str = "palingenesis-state";
+/// Header layout version this build reads and writes.
+pub const STATE_HEADER_VERSION: u32 = 1;
+
+/// Encoding of the main state file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StateFormat {
+    /// Pretty-printed JSON.
+    #[default]
+    Json,
+    /// JSON header line followed by MessagePack.
+    Msgpack,
+}
+
+impl StateFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Msgpack => "msgpack",
+        }
+    }
+}
+
+impl std::fmt::Display for StateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    magic: String,
+    version: u32,
+    format: StateFormat,
+}
+
+/// Serialize `state` in `format`, header included.
+pub fn encode(state: &StateFile, format: StateFormat) -> Result<Vec<u8>, StateError> {
+    match format {
+        StateFormat::Json => Ok(serde_json::to_vec_pretty(state)?),
+        StateFormat::Msgpack => {
+            let header = Header {
+                magic: STATE_MAGIC.to_string(),
+                version: STATE_HEADER_VERSION,
+                format,
+            };
+            let mut bytes = serde_json::to_vec(&header)?;
+            bytes.push(b'\n');
+            rmp_serde::encode::write_named(&mut bytes, state)?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// Format of an encoded state file, from its header.
+///
+/// Anything without a header is taken to be JSON.
+pub fn detect(bytes: &[u8]) -> Result<StateFormat, String> {
+    Ok(split_header(bytes)?.0)
+}
+
+/// Parse a state file in either format, returning the format it was in.
+pub fn decode(bytes: &[u8]) -> Result<(StateFile, StateFormat), String> {
+    let (format, body) = split_header(bytes)?;
+    let state = match format {
+        StateFormat::Json => serde_json::from_slice(body).map_err(|err| err.to_string())?,
+        StateFormat::Msgpack => rmp_serde::from_slice(body).map_err(|err| err.to_string())?,
+    };
+    Ok((state, format))
+}
+
+/// Schema version of an encoded state file, read without decoding the rest.
+///
+/// Lets a file this build cannot parse still be recognized as newer.
+pub fn probe_version(bytes: &[u8]) -> Option<u32> {
+    #[derive(Deserialize)]
+    struct Versioned {
+        version: u32,
+    }
+
+    let (format, body) = split_header(bytes).ok()?;
+    let versioned: Versioned = match format {
+        StateFormat::Json => serde_json::from_slice(body).ok()?,
+        StateFormat::Msgpack => rmp_serde::from_slice(body).ok()?,
+    };
+    Some(versioned.version)
+}
+
+fn split_header(bytes: &[u8]) -> Result<(StateFormat, &[u8]), String> {
+    let prefix = format!("{{\"magic\":\"{STATE_MAGIC}\"");
+    if !bytes.starts_with(prefix.as_bytes()) {
+        return Ok((StateFormat::Json, bytes));
+    }
+    let end = bytes
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .ok_or("state header is not terminated")?;
+    let header: Header = serde_json::from_slice(&bytes[..end])
+        .map_err(|err| format!("invalid state header: {err}"))?;
+    if header.version > STATE_HEADER_VERSION {
+        return Err(format!(
+            "state header version {} is newer than supported ({STATE_HEADER_VERSION})",
+            header.version
+        ));
+    }
+    Ok((header.format, &bytes[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ResumeHistory;
+
+    fn sample() -> StateFile {
+        let mut state = StateFile::default();
+        state.stats.saves_count = 3;
+        state
+            .resume_history
+            .push(ResumeHistory::new("/tmp/session.md".into()));
+        state
+    }
+
+    #[test]
+    fn msgpack_file_starts_with_a_json_header() {
+        let bytes = encode(&sample(), StateFormat::Msgpack).unwrap();
+        let header_end = bytes.iter().position(|byte| *byte == b'\n').unwrap();
+        let header: serde_json::Value = serde_json::from_slice(&bytes[..header_end]).unwrap();
+        assert_eq!(
+            header,
+            serde_json::json!({"magic": STATE_MAGIC, "version": 1, "format": "msgpack"})
+        );
+        assert_eq!(detect(&bytes), Ok(StateFormat::Msgpack));
+    }
+
+    #[test]
+    fn both_formats_decode_to_the_same_state() {
+        for format in [StateFormat::Json, StateFormat::Msgpack] {
+            let bytes = encode(&sample(), format).unwrap();
+            assert_eq!(decode(&bytes), Ok((sample(), format)));
+        }
+    }
+
+    #[test]
+    fn newer_header_and_truncated_body_are_rejected() {
+        let newer =
+            format!("{{\"magic\":\"{STATE_MAGIC}\",\"version\":2,\"format\":\"msgpack\"}}\n");
+        assert!(decode(newer.as_bytes()).unwrap_err().contains("newer"));
+
+        let mut bytes = encode(&sample(), StateFormat::Msgpack).unwrap();
+        bytes.truncate(bytes.len() - 4);
+        assert!(decode(&bytes).is_err());
+    }
+}
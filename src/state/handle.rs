@@ -0,0 +1,476 @@
+//! Write-behind access to the state file.
+//!
+//! Mutations go through a shared in-memory [`StateHandle`] that marks the
+//! state dirty; a background flusher persists it at most once per flush
+//! interval, so bursts of small stat bumps cost one write instead of one
+//! fsync each. Critical transitions (resume outcomes, give-up, shutdown) use
+//! [`StateHandle::update_critical`] (or its async form), which writes through
+//! immediately. The last handle to be dropped flushes anything still pending.
+//!
+//! Each successful write is also fed to an attached [`Changefeed`], so the
+//! feed describes exactly what reached disk.
+//!
+//! Over a backend that is read-only because the file on disk comes from a
+//! newer schema, changes are kept in memory only and never written.
+
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use super::changefeed::Changefeed;
+use super::schema::StateFile;
+use super::store::{StateBackend, StateError, StateStore};
+
+/// How long dirty state may stay in memory before it is flushed.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+static GLOBAL_STATE: OnceLock<StateHandle> = OnceLock::new();
+
+/// Shared, write-behind handle to the daemon state.
+#[derive(Clone)]
+pub struct StateHandle {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    backend: Box<dyn StateBackend>,
+    cached: Mutex<Cached>,
+    /// Serializes writes so an older snapshot never lands after a newer one.
+    flush_lock: Mutex<()>,
+    changed: Notify,
+    changefeed: OnceLock<Arc<Changefeed>>,
+}
+
+struct Cached {
+    state: StateFile,
+    dirty: bool,
+    /// When the oldest change not yet on disk was made.
+    dirty_since: Option<DateTime<Utc>>,
+}
+
+impl StateHandle {
+    /// Wrap `backend`, loading its current state.
+    pub fn new<T: StateBackend + 'static>(backend: T) -> Self {
+        let state = backend.load();
+        Self {
+            inner: Arc::new(Inner {
+                backend: Box::new(backend),
+                cached: Mutex::new(Cached {
+                    state,
+                    dirty: false,
+                    dirty_since: None,
+                }),
+                flush_lock: Mutex::new(()),
+                changed: Notify::new(),
+                changefeed: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Record every later write in `changefeed`.
+    pub fn with_changefeed(self, changefeed: Arc<Changefeed>) -> Self {
+        changefeed.set_baseline(&self.snapshot());
+        let _ = self.inner.changefeed.set(changefeed);
+        self
+    }
+
+    pub fn changefeed(&self) -> Option<Arc<Changefeed>> {
+        self.inner.changefeed.get().cloned()
+    }
+
+    /// Install the process-wide handle used by strategies and metrics.
+    pub fn set_global(handle: StateHandle) -> bool {
+        GLOBAL_STATE.set(handle).is_ok()
+    }
+
+    pub fn global() -> Option<StateHandle> {
+        GLOBAL_STATE.get().cloned()
+    }
+
+    /// The global handle, or a handle over the default state file.
+    pub fn global_or_default() -> StateHandle {
+        Self::global().unwrap_or_else(|| Self::new(StateStore::new()))
+    }
+
+    /// Current state, as seen by the global handle or read from disk.
+    pub fn read_state() -> StateFile {
+        match Self::global() {
+            Some(handle) => handle.snapshot(),
+            None => StateStore::new().load(),
+        }
+    }
+
+    pub fn snapshot(&self) -> StateFile {
+        self.inner.cached().state.clone()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.inner.cached().dirty
+    }
+
+    /// When the oldest change still waiting to be written was made; a time
+    /// far in the past means saves are failing.
+    pub fn unsaved_since(&self) -> Option<DateTime<Utc>> {
+        self.inner.cached().dirty_since
+    }
+
+    /// Apply `f` in memory; the change is persisted by the next flush.
+    pub fn update<R>(&self, f: impl FnOnce(&mut StateFile) -> R) -> R {
+        let result = {
+            let mut cached = self.inner.cached();
+            let result = f(&mut cached.state);
+            cached.dirty = true;
+            cached.dirty_since.get_or_insert_with(Utc::now);
+            result
+        };
+        self.inner.changed.notify_one();
+        result
+    }
+
+    /// Apply `f` and persist immediately.
+    ///
+    /// If the write fails the error is returned, and the change stays in
+    /// memory, dirty, for the flusher to retry, as does anything else
+    /// updated meanwhile.
+    pub fn update_critical<R>(&self, f: impl FnOnce(&mut StateFile) -> R) -> Result<R, StateError> {
+        let result = self.update(f);
+        self.inner.flush()?;
+        Ok(result)
+    }
+
+    /// [`Self::update_critical`] for async callers: the write runs on the
+    /// blocking pool instead of holding a runtime thread.
+    pub async fn update_critical_async<R>(
+        &self,
+        f: impl FnOnce(&mut StateFile) -> R,
+    ) -> Result<R, StateError> {
+        let result = self.update(f);
+        let handle = self.clone();
+        tokio::task::spawn_blocking(move || handle.inner.flush())
+            .await
+            .map_err(|err| StateError::Io(std::io::Error::other(err)))??;
+        Ok(result)
+    }
+
+    /// Schema version of the newer state file that keeps the backend read-only.
+    pub fn newer_version(&self) -> Option<u32> {
+        self.inner.backend.newer_version()
+    }
+
+    /// Persist pending changes, if any.
+    pub fn flush(&self) -> Result<(), StateError> {
+        self.inner.flush()
+    }
+
+    /// Flush dirty state at most once per `interval` until `cancel` fires,
+    /// then flush one last time.
+    pub fn spawn_flusher(&self, interval: Duration, cancel: CancellationToken) -> JoinHandle<()> {
+        let handle = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = handle.inner.changed.notified() => {}
+                }
+                // Let further updates pile up before writing.
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+                if let Err(err) = handle.flush() {
+                    warn!(error = %err, "Failed to flush state");
+                }
+            }
+            if let Err(err) = handle.flush() {
+                warn!(error = %err, "Failed to flush state on shutdown");
+            }
+        })
+    }
+}
+
+impl StateBackend for StateHandle {
+    fn load(&self) -> StateFile {
+        self.snapshot()
+    }
+
+    fn save(&self, state: &StateFile) -> Result<(), StateError> {
+        self.update_critical(|current| *current = state.clone())
+    }
+
+    fn newer_version(&self) -> Option<u32> {
+        StateHandle::newer_version(self)
+    }
+}
+
+impl Inner {
+    fn cached(&self) -> MutexGuard<'_, Cached> {
+        self.cached.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn flush_lock(&self) -> MutexGuard<'_, ()> {
+        self.flush_lock
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn record_change(&self, state: &StateFile) {
+        if let Some(changefeed) = self.changefeed.get() {
+            changefeed.record(state);
+        }
+    }
+
+    fn flush(&self) -> Result<(), StateError> {
+        let _flush = self.flush_lock();
+        let snapshot = {
+            let mut cached = self.cached();
+            if !cached.dirty {
+                return Ok(());
+            }
+            if self.backend.newer_version().is_some() {
+                cached.dirty = false;
+                cached.dirty_since = None;
+                return Ok(());
+            }
+            cached.dirty = false;
+            cached.state.clone()
+        };
+        if let Err(err) = self.backend.save(&snapshot) {
+            self.cached().dirty = true;
+            return Err(err);
+        }
+        self.record_change(&snapshot);
+        let mut cached = self.cached();
+        cached.dirty_since = cached.dirty.then(Utc::now);
+        drop(cached);
+        debug!("Flushed pending state");
+        Ok(())
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            warn!(error = %err, "Failed to flush state on drop");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingStore {
+        state: Mutex<StateFile>,
+        saves: AtomicUsize,
+        fail: AtomicBool,
+    }
+
+    impl StateBackend for CountingStore {
+        fn load(&self) -> StateFile {
+            self.state.lock().unwrap().clone()
+        }
+
+        fn save(&self, state: &StateFile) -> Result<(), StateError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(StateError::LockTimeout);
+            }
+            self.saves.fetch_add(1, Ordering::SeqCst);
+            *self.state.lock().unwrap() = state.clone();
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_updates_all_land() {
+        let store = Arc::new(CountingStore::default());
+        let handle = StateHandle::new(Arc::clone(&store));
+        let cancel = CancellationToken::new();
+        let flusher = handle.spawn_flusher(Duration::from_millis(5), cancel.clone());
+
+        let mut tasks = Vec::new();
+        for task in 0..8u64 {
+            let handle = handle.clone();
+            tasks.push(tokio::spawn(async move {
+                for step in 0..250u64 {
+                    handle.update(|state| {
+                        state.stats.total_resumes += 1;
+                        state.stats.saves_count += task * 1000 + step;
+                    });
+                    if step % 50 == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        cancel.cancel();
+        flusher.await.unwrap();
+
+        let persisted = store.load();
+        assert_eq!(persisted.stats.total_resumes, 2000);
+        let expected: u64 = (0..8u64)
+            .flat_map(|task| (0..250u64).map(move |step| task * 1000 + step))
+            .sum();
+        assert_eq!(persisted.stats.saves_count, expected);
+        assert!(!handle.is_dirty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_interval_coalesces_bursts() {
+        let store = Arc::new(CountingStore::default());
+        let handle = StateHandle::new(Arc::clone(&store));
+        let cancel = CancellationToken::new();
+        let flusher = handle.spawn_flusher(DEFAULT_FLUSH_INTERVAL, cancel.clone());
+
+        for _ in 0..100 {
+            handle.update(|state| state.stats.total_resumes += 1);
+        }
+        tokio::time::sleep(DEFAULT_FLUSH_INTERVAL * 2).await;
+        assert_eq!(store.saves.load(Ordering::SeqCst), 1);
+        assert_eq!(store.load().stats.total_resumes, 100);
+
+        cancel.cancel();
+        flusher.await.unwrap();
+        assert_eq!(store.saves.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn critical_update_writes_through_and_keeps_failed_changes_pending() {
+        let store = Arc::new(CountingStore::default());
+        let handle = StateHandle::new(Arc::clone(&store));
+
+        handle
+            .update_critical(|state| state.stats.total_resumes = 3)
+            .unwrap();
+        assert_eq!(store.load().stats.total_resumes, 3);
+
+        store.fail.store(true, Ordering::SeqCst);
+        handle.update(|state| state.stats.saves_count = 7);
+        assert!(
+            handle
+                .update_critical(|state| state.stats.total_resumes = 4)
+                .is_err()
+        );
+        // Neither the failed change nor the earlier update is lost.
+        let snapshot = handle.snapshot();
+        assert_eq!(snapshot.stats.total_resumes, 4);
+        assert_eq!(snapshot.stats.saves_count, 7);
+        assert!(handle.is_dirty());
+
+        store.fail.store(false, Ordering::SeqCst);
+        handle.flush().unwrap();
+        assert_eq!(store.load().stats.total_resumes, 4);
+        assert_eq!(store.load().stats.saves_count, 7);
+    }
+
+    #[tokio::test]
+    async fn async_critical_update_writes_through() {
+        let store = Arc::new(CountingStore::default());
+        let handle = StateHandle::new(Arc::clone(&store));
+
+        let resumes = handle
+            .update_critical_async(|state| {
+                state.stats.total_resumes = 5;
+                state.stats.total_resumes
+            })
+            .await
+            .unwrap();
+        assert_eq!(resumes, 5);
+        assert_eq!(store.load().stats.total_resumes, 5);
+        assert!(!handle.is_dirty());
+
+        store.fail.store(true, Ordering::SeqCst);
+        assert!(
+            handle
+                .update_critical_async(|state| state.stats.total_resumes = 6)
+                .await
+                .is_err()
+        );
+        assert!(handle.is_dirty());
+    }
+
+    #[test]
+    fn critical_write_does_not_block_readers() {
+        struct GatedStore {
+            entered: std::sync::mpsc::SyncSender<()>,
+            release: Mutex<std::sync::mpsc::Receiver<()>>,
+        }
+
+        impl StateBackend for GatedStore {
+            fn load(&self) -> StateFile {
+                StateFile::default()
+            }
+
+            fn save(&self, _state: &StateFile) -> Result<(), StateError> {
+                let _ = self.entered.send(());
+                let _ = self.release.lock().unwrap().recv();
+                Ok(())
+            }
+        }
+
+        let (entered, entered_rx) = std::sync::mpsc::sync_channel(1);
+        let (release, release_rx) = std::sync::mpsc::channel();
+        let handle = StateHandle::new(GatedStore {
+            entered,
+            release: Mutex::new(release_rx),
+        });
+
+        let writer = {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                handle
+                    .update_critical(|state| state.stats.total_resumes = 1)
+                    .unwrap();
+            })
+        };
+        entered_rx.recv().unwrap();
+        // The save is still in progress: reads and updates must not wait for it.
+        assert_eq!(handle.snapshot().stats.total_resumes, 1);
+        handle.update(|state| state.stats.saves_count = 2);
+
+        release.send(()).unwrap();
+        writer.join().unwrap();
+        assert!(handle.is_dirty());
+        assert_eq!(handle.snapshot().stats.saves_count, 2);
+        // Flushed on drop; let it through.
+        release.send(()).unwrap();
+    }
+
+    #[test]
+    fn unsaved_since_stays_until_a_save_succeeds() {
+        let store = Arc::new(CountingStore::default());
+        let handle = StateHandle::new(Arc::clone(&store));
+        assert_eq!(handle.unsaved_since(), None);
+
+        store.fail.store(true, Ordering::SeqCst);
+        handle.update(|state| state.stats.saves_count = 1);
+        let since = handle.unsaved_since().expect("pending change");
+        assert!(handle.flush().is_err());
+        handle.update(|state| state.stats.saves_count = 2);
+        assert_eq!(handle.unsaved_since(), Some(since));
+
+        store.fail.store(false, Ordering::SeqCst);
+        handle.flush().unwrap();
+        assert_eq!(handle.unsaved_since(), None);
+    }
+
+    #[test]
+    fn drop_flushes_pending_changes() {
+        let store = Arc::new(CountingStore::default());
+        let handle = StateHandle::new(Arc::clone(&store));
+        handle.update(|state| state.stats.saves_count = 9);
+        assert_eq!(store.saves.load(Ordering::SeqCst), 0);
+
+        drop(handle);
+        assert_eq!(store.load().stats.saves_count, 9);
+    }
+}
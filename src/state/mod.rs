@@ -1,11 +1,26 @@
 //! State persistence module.
 
 pub mod audit;
+pub mod audit_writer;
+pub mod changefeed;
+pub mod format;
+pub mod handle;
+pub mod orphans;
 pub mod schema;
 pub mod store;
+pub mod timeline;
 
 pub use audit::{
     AuditConfig, AuditEntry, AuditError, AuditEventType, AuditLogger, AuditOutcome, AuditQuery,
 };
-pub use schema::{CurrentSession, DaemonState, STATE_VERSION, StateFile, Stats};
-pub use store::{StateError, StateStore};
+pub use audit_writer::{AuditWriter, DEFAULT_AUDIT_FLUSH_INTERVAL};
+pub use changefeed::{ChangeOp, ChangeRecord, Changefeed, ChangesSince};
+pub use format::StateFormat;
+pub use handle::{DEFAULT_FLUSH_INTERVAL, StateHandle};
+pub use schema::{
+    CompletedSession, CurrentSession, DaemonState, EngagedTier, EscalationRecord,
+    EscalationResolution, MuteKind, NotificationMute, OpenCodeVersionRecord, OrphanedSession,
+    ResumeCost, ResumeHistory, STATE_VERSION, ScheduleRun, ScheduleRunOutcome, SessionStatus,
+    StateFile, Stats, StepCompletion, StepTimeline, TokenUsage, WorktreeRecord,
+};
+pub use store::{StateBackend, StateError, StateStore};
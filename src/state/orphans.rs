@@ -0,0 +1,80 @@
+//! Orphaned sessions: files created by a resume whose follow-up steps failed.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use super::store::StateBackend;
+
+/// Whether `path` must be skipped when picking the current session.
+///
+/// An orphan that was modified after it was recorded is treated as adopted
+/// by whoever is writing to it: the record is dropped and the path becomes
+/// eligible again.
+pub fn skip_for_selection(store: &dyn StateBackend, path: &Path) -> bool {
+    let mut state = store.load();
+    let Some(orphan) = state.orphan(path) else {
+        return false;
+    };
+
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::<Utc>::from);
+    match modified {
+        Ok(modified) if orphan.has_independent_activity(modified) => {
+            state.remove_orphan(path);
+            if let Err(err) = store.save(&state) {
+                warn!(path = %path.display(), error = %err, "Failed to release orphaned session");
+            }
+            info!(path = %path.display(), "Orphaned session shows independent activity; tracking it again");
+            false
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    use crate::state::{OrphanedSession, StateStore};
+
+    #[test]
+    fn untouched_orphan_is_skipped() {
+        let temp = tempfile::tempdir().unwrap();
+        let session = temp.path().join("session.md");
+        fs::write(&session, "---\n---\n").unwrap();
+
+        let store = StateStore::with_path(temp.path().join("state.json"));
+        let mut state = store.load();
+        state.record_orphan(OrphanedSession::new(session.clone(), "state store error"));
+        store.save(&state).unwrap();
+
+        assert!(skip_for_selection(&store, &session));
+        assert!(!skip_for_selection(&store, &temp.path().join("other.md")));
+    }
+
+    #[test]
+    fn modified_orphan_is_released() {
+        let temp = tempfile::tempdir().unwrap();
+        let session = temp.path().join("session.md");
+        fs::write(&session, "---\n---\n").unwrap();
+
+        let store = StateStore::with_path(temp.path().join("state.json"));
+        let mut state = store.load();
+        let mut orphan = OrphanedSession::new(session.clone(), "state store error");
+        orphan.recorded_at = Utc::now() - chrono::Duration::hours(1);
+        state.record_orphan(orphan);
+        store.save(&state).unwrap();
+
+        let file = fs::File::options().write(true).open(&session).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+
+        assert!(!skip_for_selection(&store, &session));
+        assert!(store.load().orphan(&session).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Current version of the state file schema.
 pub const STATE_VERSION: u32 = 1;
@@ -12,6 +12,37 @@ pub struct StateFile {
     pub daemon_state: DaemonState,
     pub current_session: Option<CurrentSession>,
     pub stats: Stats,
+    /// Sessions created by a resume whose follow-up steps failed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orphaned_sessions: Vec<OrphanedSession>,
+    /// Worktrees created for new sessions (`resume.new_session.workspace_mode = "worktree"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub worktrees: Vec<WorktreeRecord>,
+    /// Recent automatic resumes per session (`resume.max_resumes_per_hour`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resume_history: Vec<ResumeHistory>,
+    /// Last opencode version seen and the result of its compatibility check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opencode_version: Option<OpenCodeVersionRecord>,
+    /// When each step of recently monitored sessions completed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub step_timelines: Vec<StepTimeline>,
+    /// Sessions whose last stop was classified as completed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completed_sessions: Vec<CompletedSession>,
+    /// Incidents under `notifications.escalation`, open and recently resolved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub escalations: Vec<EscalationRecord>,
+    /// Recent `[[schedules]]` firings, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule_runs: Vec<ScheduleRun>,
+    /// Notification channels and event types muted at runtime.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notification_mutes: Vec<NotificationMute>,
+    /// Top-level fields this build does not know, kept so that saving does
+    /// not drop what a newer build of the same schema version added.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Default for StateFile {
@@ -21,10 +52,238 @@ impl Default for StateFile {
             daemon_state: DaemonState::Stopped,
             current_session: None,
             stats: Stats::default(),
+            orphaned_sessions: Vec::new(),
+            worktrees: Vec::new(),
+            resume_history: Vec::new(),
+            opencode_version: None,
+            step_timelines: Vec::new(),
+            completed_sessions: Vec::new(),
+            escalations: Vec::new(),
+            schedule_runs: Vec::new(),
+            notification_mutes: Vec::new(),
+            extra: serde_json::Map::new(),
         }
     }
 }
 
+impl StateFile {
+    pub fn orphan(&self, path: &Path) -> Option<&OrphanedSession> {
+        self.orphaned_sessions
+            .iter()
+            .find(|orphan| orphan.path == path)
+    }
+
+    /// Record an orphan, replacing any earlier record for the same path.
+    pub fn record_orphan(&mut self, orphan: OrphanedSession) {
+        self.orphaned_sessions
+            .retain(|existing| existing.path != orphan.path);
+        self.orphaned_sessions.push(orphan);
+    }
+
+    pub fn remove_orphan(&mut self, path: &Path) -> Option<OrphanedSession> {
+        let index = self
+            .orphaned_sessions
+            .iter()
+            .position(|orphan| orphan.path == path)?;
+        Some(self.orphaned_sessions.remove(index))
+    }
+
+    /// Record a new-session worktree, replacing any earlier record for the same directory.
+    pub fn record_worktree(&mut self, record: WorktreeRecord) {
+        self.worktrees
+            .retain(|existing| existing.worktree != record.worktree);
+        self.worktrees.push(record);
+    }
+
+    pub fn resume_history(&self, path: &Path) -> Option<&ResumeHistory> {
+        self.resume_history
+            .iter()
+            .find(|history| history.path == path)
+    }
+
+    /// History for `path`, created empty if the session has none yet.
+    pub fn resume_history_mut(&mut self, path: &Path) -> &mut ResumeHistory {
+        let index = match self
+            .resume_history
+            .iter()
+            .position(|history| history.path == path)
+        {
+            Some(index) => index,
+            None => {
+                self.resume_history
+                    .push(ResumeHistory::new(path.to_path_buf()));
+                self.resume_history.len() - 1
+            }
+        };
+        &mut self.resume_history[index]
+    }
+
+    pub fn remove_resume_history(&mut self, path: &Path) -> Option<ResumeHistory> {
+        let index = self
+            .resume_history
+            .iter()
+            .position(|history| history.path == path)?;
+        Some(self.resume_history.remove(index))
+    }
+
+    /// Release a current session held for attention (deleted, a suspected
+    /// resume loop, or resumes paused by an acknowledgment).
+    ///
+    /// The session goes back to being monitored if its file exists again, and
+    /// stops being tracked otherwise. Its resume-rate history is reset so
+    /// automatic resumes may run again. Stats are kept either way.
+    pub fn clear_attention(&mut self) -> Option<String> {
+        let session = self
+            .current_session
+            .as_mut()
+            .filter(|session| session.status != SessionStatus::Active)?;
+
+        let path = session.path.clone();
+        if path.exists() {
+            session.status = SessionStatus::Active;
+            self.remove_resume_history(&path);
+            return Some(format!("Resumed monitoring {}", path.display()));
+        }
+
+        self.remove_resume_history(&path);
+        self.current_session = None;
+        Some(format!(
+            "Stopped tracking {} (file is still missing)",
+            path.display()
+        ))
+    }
+
+    /// Append `run`, dropping the oldest past [`MAX_SCHEDULE_RUNS`].
+    pub fn record_schedule_run(&mut self, run: ScheduleRun) {
+        self.schedule_runs.push(run);
+        let excess = self.schedule_runs.len().saturating_sub(MAX_SCHEDULE_RUNS);
+        self.schedule_runs.drain(..excess);
+    }
+
+    /// Latest firing of `schedule`.
+    pub fn last_schedule_run(&self, schedule: &str) -> Option<&ScheduleRun> {
+        self.schedule_runs
+            .iter()
+            .rev()
+            .find(|run| run.schedule == schedule)
+    }
+
+    pub fn step_timeline(&self, path: &Path) -> Option<&StepTimeline> {
+        self.step_timelines
+            .iter()
+            .find(|timeline| timeline.path == path)
+    }
+
+    /// Add the steps of `completed` missing from `path`'s timeline, stamped
+    /// with `at` (`None` when they completed while the daemon was not
+    /// watching). Returns how many steps were added.
+    ///
+    /// Keeps the [`MAX_STEP_TIMELINES`] most recently updated sessions.
+    pub fn record_completed_steps(
+        &mut self,
+        path: &Path,
+        completed: &[u32],
+        at: Option<DateTime<Utc>>,
+    ) -> usize {
+        let index = match self
+            .step_timelines
+            .iter()
+            .position(|timeline| timeline.path == path)
+        {
+            Some(index) => index,
+            None => {
+                self.step_timelines
+                    .push(StepTimeline::new(path.to_path_buf()));
+                self.step_timelines.len() - 1
+            }
+        };
+        let added = self.step_timelines[index].record(completed, at);
+        if added > 0 {
+            let timeline = self.step_timelines.remove(index);
+            self.step_timelines.push(timeline);
+            let excess = self.step_timelines.len().saturating_sub(MAX_STEP_TIMELINES);
+            self.step_timelines.drain(..excess);
+        }
+        added
+    }
+
+    pub fn completed_session(&self, path: &Path) -> Option<&CompletedSession> {
+        self.completed_sessions
+            .iter()
+            .find(|completed| completed.path == path)
+    }
+
+    /// Record a completed determination, replacing any earlier one for the
+    /// same path. Keeps the [`MAX_COMPLETED_SESSIONS`] most recent.
+    pub fn record_completed_session(&mut self, completed: CompletedSession) {
+        self.completed_sessions
+            .retain(|existing| existing.path != completed.path);
+        self.completed_sessions.push(completed);
+        let excess = self
+            .completed_sessions
+            .len()
+            .saturating_sub(MAX_COMPLETED_SESSIONS);
+        self.completed_sessions.drain(..excess);
+    }
+
+    pub fn remove_completed_session(&mut self, path: &Path) -> Option<CompletedSession> {
+        let index = self
+            .completed_sessions
+            .iter()
+            .position(|completed| completed.path == path)?;
+        Some(self.completed_sessions.remove(index))
+    }
+
+    /// The session's escalation that has not been resolved yet.
+    pub fn open_escalation_mut(&mut self, path: &Path) -> Option<&mut EscalationRecord> {
+        self.escalations
+            .iter_mut()
+            .find(|record| record.session_path == path && record.resolved_at.is_none())
+    }
+
+    /// Start escalating an incident, dropping the oldest settled records
+    /// beyond [`MAX_ESCALATIONS`].
+    pub fn record_escalation(&mut self, record: EscalationRecord) {
+        self.escalations.push(record);
+        while self.escalations.len() > MAX_ESCALATIONS {
+            let Some(index) = self
+                .escalations
+                .iter()
+                .position(EscalationRecord::is_settled)
+            else {
+                break;
+            };
+            self.escalations.remove(index);
+        }
+    }
+
+    /// Mark the session's open escalation resolved; its resolution notices
+    /// are sent by the escalation scheduler.
+    pub fn resolve_escalation(
+        &mut self,
+        path: &Path,
+        resolution: EscalationResolution,
+        at: DateTime<Utc>,
+    ) -> bool {
+        match self.open_escalation_mut(path) {
+            Some(record) => {
+                record.resolved_at = Some(at);
+                record.resolution = Some(resolution);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_worktree(&mut self, worktree: &Path) -> Option<WorktreeRecord> {
+        let index = self
+            .worktrees
+            .iter()
+            .position(|record| record.worktree == worktree)?;
+        Some(self.worktrees.remove(index))
+    }
+}
+
 /// Daemon operational states.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -47,6 +306,12 @@ pub struct CurrentSession {
     pub steps_completed: Vec<u32>,
     pub last_step: u32,
     pub total_steps: u32,
+    /// Whether the session file is still present on disk.
+    #[serde(default, skip_serializing_if = "SessionStatus::is_active")]
+    pub status: SessionStatus,
+    /// Assistant model the session was started with, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 impl Default for CurrentSession {
@@ -56,8 +321,430 @@ impl Default for CurrentSession {
             steps_completed: Vec::new(),
             last_step: 0,
             total_steps: 0,
+            status: SessionStatus::Active,
+            model: None,
+        }
+    }
+}
+
+/// Health of the current session's file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// File exists and is being monitored.
+    #[default]
+    Active,
+    /// File was deleted; a restore may still bring it back.
+    Missing,
+    /// File is gone with nothing to restore it from; waiting for the operator.
+    NeedsAttention,
+}
+
+impl SessionStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Active)
+    }
+}
+
+/// Session file created by a resume that failed afterwards.
+///
+/// Orphans are skipped when picking the current session until they are
+/// modified after `recorded_at`, or resolved with `palingenesis orphans`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrphanedSession {
+    pub path: PathBuf,
+    pub reason: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl OrphanedSession {
+    pub fn new(path: PathBuf, reason: impl Into<String>) -> Self {
+        Self {
+            path,
+            reason: reason.into(),
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Whether the file changed after the orphan was recorded.
+    pub fn has_independent_activity(&self, modified: DateTime<Utc>) -> bool {
+        modified > self.recorded_at
+    }
+}
+
+/// opencode version recorded by the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenCodeVersionRecord {
+    pub version: String,
+    pub detected_at: DateTime<Utc>,
+    /// Compatibility checks this version failed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+/// Worktree a new session was started in, and the workspace it came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorktreeRecord {
+    /// Original workspace the worktree was branched from.
+    pub workspace: PathBuf,
+    /// Worktree directory the new session runs in.
+    pub worktree: PathBuf,
+    pub branch: String,
+    /// Session that ran out of context.
+    pub source_session: PathBuf,
+    /// Session started in the worktree.
+    pub session: PathBuf,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Automatic resumes of one session, used to spot resume loops.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResumeHistory {
+    pub path: PathBuf,
+    /// Start times of recent automatic resumes, oldest first.
+    #[serde(default)]
+    pub resumed_at: Vec<DateTime<Utc>>,
+    /// When the session was flagged as a possible resume loop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_suspected_at: Option<DateTime<Utc>>,
+    /// Transcript snapshot of the stop behind the latest resume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_incident: Option<PathBuf>,
+    /// When the operator acknowledged the session's open incident.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    /// When an acknowledgment paused automatic resumes (`notifications.ack_pauses_resume`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumes_paused_at: Option<DateTime<Utc>>,
+    /// Routing tags the session had at its latest resume.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Token usage reported at the latest resume; the next resume's
+    /// segment is measured from here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_usage: Option<TokenUsage>,
+    /// Cost estimates of recent resumes, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub costs: Vec<ResumeCost>,
+}
+
+impl ResumeHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            resumed_at: Vec::new(),
+            loop_suspected_at: None,
+            last_incident: None,
+            acknowledged_at: None,
+            resumes_paused_at: None,
+            tags: Vec::new(),
+            last_usage: None,
+            costs: Vec::new(),
+        }
+    }
+
+    /// Resumes at or after `since`.
+    pub fn count_since(&self, since: DateTime<Utc>) -> usize {
+        self.resumed_at.iter().filter(|at| **at >= since).count()
+    }
+
+    /// Forget resumes before `since`.
+    pub fn prune_before(&mut self, since: DateTime<Utc>) {
+        self.resumed_at.retain(|at| *at >= since);
+    }
+
+    /// Add a resume's cost estimate, keeping the [`MAX_RESUME_COSTS`] latest.
+    pub fn record_cost(&mut self, cost: ResumeCost) {
+        self.costs.push(cost);
+        let excess = self.costs.len().saturating_sub(MAX_RESUME_COSTS);
+        self.costs.drain(..excess);
+    }
+}
+
+/// Cost estimates kept per session.
+pub const MAX_RESUME_COSTS: usize = 32;
+
+/// Input and output tokens, as reported by the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input: u64,
+    pub output: u64,
+}
+
+/// Estimated spend and value of one resume; `None` where usage data, a
+/// model price or the hourly rate was missing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResumeCost {
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Tokens used by the segment that ended in this resume's stop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<TokenUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    pub time_saved_seconds: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_saved: Option<f64>,
+}
+
+/// Sessions whose step timeline is kept in the state file.
+pub const MAX_STEP_TIMELINES: usize = 16;
+/// Steps kept per timeline; the oldest are dropped first.
+pub const MAX_TIMELINE_STEPS: usize = 512;
+
+/// Step completions observed for one session, in the order they were seen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepTimeline {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub steps: Vec<StepCompletion>,
+}
+
+impl StepTimeline {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            steps: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, completed: &[u32], at: Option<DateTime<Utc>>) -> usize {
+        let mut new_steps: Vec<u32> = completed
+            .iter()
+            .copied()
+            .filter(|step| !self.steps.iter().any(|entry| entry.step == *step))
+            .collect();
+        new_steps.sort_unstable();
+        new_steps.dedup();
+        let added = new_steps.len();
+        self.steps
+            .extend(new_steps.into_iter().map(|step| StepCompletion {
+                step,
+                completed_at: at,
+            }));
+        let excess = self.steps.len().saturating_sub(MAX_TIMELINE_STEPS);
+        self.steps.drain(..excess);
+        added
+    }
+}
+
+/// One completed step; `completed_at` is `None` when the step completed
+/// while the daemon was offline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepCompletion {
+    pub step: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Sessions whose completed determination is kept in the state file.
+pub const MAX_COMPLETED_SESSIONS: usize = 32;
+
+/// What a session looked like when a stop was classified as completed, so a
+/// later stop can tell whether it was reopened since.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedSession {
+    pub path: PathBuf,
+    pub determined_at: DateTime<Utc>,
+    /// File size in bytes.
+    pub content_len: u64,
+    #[serde(default)]
+    pub steps_completed: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_step: Option<i64>,
+    /// Frontmatter status.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Escalation records kept in the state file.
+pub const MAX_ESCALATIONS: usize = 64;
+
+/// An incident walked through the `notifications.escalation` tiers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationRecord {
+    pub incident_id: String,
+    pub session_path: PathBuf,
+    /// Tier `after_secs` are measured from here.
+    pub opened_at: DateTime<Utc>,
+    /// Tiers entered so far, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<EngagedTier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<EscalationResolution>,
+    /// Resolution notices went out to the engaged tiers.
+    #[serde(default)]
+    pub resolution_sent: bool,
+}
+
+impl EscalationRecord {
+    pub fn new(
+        incident_id: impl Into<String>,
+        session_path: PathBuf,
+        opened_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            incident_id: incident_id.into(),
+            session_path,
+            opened_at,
+            tiers: Vec::new(),
+            resolved_at: None,
+            resolution: None,
+            resolution_sent: false,
         }
     }
+
+    /// Resolved, with nothing left to send.
+    pub fn is_settled(&self) -> bool {
+        self.resolved_at.is_some() && (self.resolution_sent || self.tiers.is_empty())
+    }
+}
+
+/// A tier an incident entered, with the channels it was sent to then.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngagedTier {
+    /// Position in `notifications.escalation.tiers`, from 0.
+    pub tier: usize,
+    pub entered_at: DateTime<Utc>,
+    pub channels: Vec<String>,
+}
+
+/// Why an escalated incident ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationResolution {
+    /// The session resumed or completed.
+    Resumed,
+    /// Someone acknowledged the incident.
+    Acknowledged,
+    /// Automatic resumes gave up on the session.
+    GaveUp,
+    /// Still open long after an unclean shutdown; closed on startup.
+    Reconciled,
+}
+
+impl EscalationResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Resumed => "resumed",
+            Self::Acknowledged => "acknowledged",
+            Self::GaveUp => "gave_up",
+            Self::Reconciled => "reconciled",
+        }
+    }
+}
+
+impl std::fmt::Display for EscalationResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Schedule firings kept in the state file.
+pub const MAX_SCHEDULE_RUNS: usize = 64;
+
+/// One firing of a `[[schedules]]` entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    pub schedule: String,
+    /// The firing time (for manual runs, when it was requested).
+    pub fired_at: DateTime<Utc>,
+    /// `scheduled`, `catch_up` or `manual`.
+    pub trigger: String,
+    pub outcome: ScheduleRunOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_path: Option<PathBuf>,
+    /// Why the firing was skipped or failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// How a schedule firing went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleRunOutcome {
+    /// The session job is queued or running.
+    Started,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+impl ScheduleRunOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+impl std::fmt::Display for ScheduleRunOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A channel or event type muted with `MUTE_CHANNEL`/`MUTE_EVENT`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationMute {
+    pub kind: MuteKind,
+    /// Channel name or event type, lowercase.
+    pub name: String,
+    pub muted_at: DateTime<Utc>,
+    /// When the mute lapses; `None` until unmuted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl NotificationMute {
+    /// Whether this mute holds back `event_type` on `channel`.
+    pub fn silences(&self, channel: &str, event_type: &str) -> bool {
+        match self.kind {
+            MuteKind::Channel => self.name.eq_ignore_ascii_case(channel.trim()),
+            MuteKind::Event => self.name.eq_ignore_ascii_case(event_type),
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_some_and(|until| until <= now)
+    }
+
+    /// Time left at `now`; `None` for mutes without a duration.
+    pub fn remaining(&self, now: DateTime<Utc>) -> Option<chrono::Duration> {
+        self.until
+            .map(|until| (until - now).max(chrono::Duration::zero()))
+    }
+}
+
+/// What a [`NotificationMute`] silences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteKind {
+    Channel,
+    Event,
+}
+
+impl MuteKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Channel => "channel",
+            Self::Event => "event",
+        }
+    }
+}
+
+impl std::fmt::Display for MuteKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Daemon statistics.
@@ -69,6 +756,16 @@ pub struct Stats {
     pub last_resume: Option<DateTime<Utc>>,
     #[serde(default)]
     pub time_saved_seconds: f64,
+    /// Estimated API spend of resumed segments with known usage and price.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub estimated_cost: f64,
+    /// Time saved valued at `metrics.hourly_rate`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub value_saved: f64,
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.0
 }
 
 #[cfg(test)]
@@ -96,4 +793,165 @@ mod tests {
         assert_eq!(parsed.stats.saves_count, 9);
         assert_eq!(parsed.stats.time_saved_seconds, 360.5);
     }
+
+    #[test]
+    fn test_orphans_default_to_empty_for_older_state_files() {
+        let json = r#"{
+            "version": 1,
+            "daemon_state": "monitoring",
+            "current_session": null,
+            "stats": { "saves_count": 0, "total_resumes": 0 }
+        }"#;
+        let parsed: StateFile = serde_json::from_str(json).unwrap();
+        assert!(parsed.orphaned_sessions.is_empty());
+        assert!(
+            !serde_json::to_string(&parsed)
+                .unwrap()
+                .contains("orphaned_sessions")
+        );
+    }
+
+    #[test]
+    fn test_record_orphan_replaces_existing_entry() {
+        let mut state = StateFile::default();
+        let path = PathBuf::from("/tmp/session.md");
+        state.record_orphan(OrphanedSession::new(path.clone(), "first"));
+        state.record_orphan(OrphanedSession::new(path.clone(), "second"));
+
+        assert_eq!(state.orphaned_sessions.len(), 1);
+        assert_eq!(state.orphan(&path).unwrap().reason, "second");
+        assert!(state.remove_orphan(&path).is_some());
+        assert!(state.orphan(&path).is_none());
+    }
+
+    #[test]
+    fn test_record_worktree_replaces_existing_entry() {
+        let mut state = StateFile::default();
+        let record = WorktreeRecord {
+            workspace: PathBuf::from("/w/repo"),
+            worktree: PathBuf::from("/w/trees/session-1"),
+            branch: "palingenesis/1".to_string(),
+            source_session: PathBuf::from("/w/repo/session.md"),
+            session: PathBuf::from("/w/trees/session-1/session.md"),
+            created_at: Utc::now(),
+        };
+        state.record_worktree(record.clone());
+        state.record_worktree(WorktreeRecord {
+            branch: "palingenesis/2".to_string(),
+            ..record.clone()
+        });
+
+        assert_eq!(state.worktrees.len(), 1);
+        assert_eq!(state.worktrees[0].branch, "palingenesis/2");
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: StateFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.worktrees, state.worktrees);
+        assert!(state.remove_worktree(&record.worktree).is_some());
+        assert!(!serde_json::to_string(&state).unwrap().contains("worktrees"));
+    }
+
+    #[test]
+    fn test_record_completed_steps_keeps_first_sighting() {
+        let mut state = StateFile::default();
+        let path = PathBuf::from("/tmp/session.md");
+        let first = Utc::now();
+        assert_eq!(state.record_completed_steps(&path, &[1, 2], None), 2);
+        assert_eq!(
+            state.record_completed_steps(&path, &[2, 1, 3], Some(first)),
+            1
+        );
+        assert_eq!(
+            state.record_completed_steps(&path, &[1, 2, 3], Some(Utc::now())),
+            0
+        );
+
+        let timeline = state.step_timeline(&path).unwrap();
+        let steps: Vec<_> = timeline
+            .steps
+            .iter()
+            .map(|entry| (entry.step, entry.completed_at))
+            .collect();
+        assert_eq!(steps, vec![(1, None), (2, None), (3, Some(first))]);
+
+        for index in 0..MAX_STEP_TIMELINES {
+            let other = PathBuf::from(format!("/tmp/other-{index}.md"));
+            state.record_completed_steps(&other, &[1], None);
+        }
+        assert_eq!(state.step_timelines.len(), MAX_STEP_TIMELINES);
+        assert!(state.step_timeline(&path).is_none());
+    }
+
+    #[test]
+    fn test_session_status_defaults_to_active_and_is_omitted() {
+        let json = r#"{"path":"/tmp/s.md","steps_completed":[1],"last_step":1,"total_steps":3}"#;
+        let mut session: CurrentSession = serde_json::from_str(json).unwrap();
+        assert_eq!(session.status, SessionStatus::Active);
+        assert!(!serde_json::to_string(&session).unwrap().contains("status"));
+
+        session.status = SessionStatus::NeedsAttention;
+        assert!(
+            serde_json::to_string(&session)
+                .unwrap()
+                .contains(r#""status":"needs_attention""#)
+        );
+    }
+
+    fn held(path: std::path::PathBuf) -> StateFile {
+        let mut state = StateFile {
+            current_session: Some(CurrentSession {
+                path,
+                status: SessionStatus::NeedsAttention,
+                ..CurrentSession::default()
+            }),
+            ..StateFile::default()
+        };
+        state.stats.total_resumes = 3;
+        state
+    }
+
+    #[test]
+    fn clear_attention_leaves_active_session_alone() {
+        let mut state = StateFile {
+            current_session: Some(CurrentSession::default()),
+            ..StateFile::default()
+        };
+        assert!(state.clear_attention().is_none());
+        assert!(StateFile::default().clear_attention().is_none());
+    }
+
+    #[test]
+    fn clear_attention_resumes_reappeared_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.md");
+        std::fs::write(&path, b"back").unwrap();
+        let mut state = held(path.clone());
+        state.resume_history_mut(&path).loop_suspected_at = Some(chrono::Utc::now());
+
+        assert!(
+            state
+                .clear_attention()
+                .unwrap()
+                .starts_with("Resumed monitoring")
+        );
+        assert_eq!(
+            state.current_session.as_ref().unwrap().status,
+            SessionStatus::Active
+        );
+        assert!(state.resume_history(&path).is_none());
+    }
+
+    #[test]
+    fn clear_attention_stops_tracking_missing_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut state = held(temp.path().join("gone.md"));
+
+        assert!(
+            state
+                .clear_attention()
+                .unwrap()
+                .starts_with("Stopped tracking")
+        );
+        assert!(state.current_session.is_none());
+        assert_eq!(state.stats.total_resumes, 3);
+    }
 }
@@ -1,16 +1,22 @@
+use std::cmp::Ordering as CmpOrdering;
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
 use tracing::{info, warn};
 
-use crate::config::{PathError, Paths};
+use crate::config::{PathError, ResolvedPaths};
 
-use super::schema::StateFile;
+use super::audit::{AuditEntry, AuditEventType, AuditLogger, AuditOutcome};
+use super::format::{self, StateFormat};
+use super::schema::{STATE_VERSION, StateFile};
 
 const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Enough of the file to read a binary format's header line.
+const MAX_HEADER_BYTES: u64 = 256;
 
 #[derive(Debug, thiserror::Error)]
 pub enum StateError {
@@ -20,26 +26,55 @@ pub enum StateError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Serialization error: {0}")]
+    Encoding(#[from] rmp_serde::encode::Error),
+
     #[error("State file corrupted: {0}")]
     Corrupted(String),
 
     #[error("Lock acquisition timeout")]
     LockTimeout,
 
+    #[error(
+        "State file has schema version {found}, newer than this build ({supported}); \
+         not overwriting it (start the daemon with --force-downgrade to rewrite it)"
+    )]
+    NewerVersion { found: u32, supported: u32 },
+
     #[error("Path error: {0}")]
     Path(#[from] PathError),
 }
 
+/// Load/save access to the state file.
+///
+/// Implemented by [`StateStore`]; callers that must survive persistence
+/// failures take this trait so tests can inject a failing store.
+pub trait StateBackend: Send + Sync {
+    fn load(&self) -> StateFile;
+    fn save(&self, state: &StateFile) -> Result<(), StateError>;
+
+    /// Schema version of a state file written by a newer build, which the
+    /// backend refuses to overwrite.
+    fn newer_version(&self) -> Option<u32> {
+        None
+    }
+}
+
 pub struct StateStore {
     path: PathBuf,
     lock_path: PathBuf,
     lock_timeout: Duration,
+    /// Format to write; `None` keeps whatever format the file is in.
+    format: Option<StateFormat>,
+    /// Rewrite a file from a newer schema instead of going read-only.
+    force_downgrade: bool,
+    /// Schema version of a newer file found on load; 0 when writable.
+    newer_version: AtomicU32,
 }
 
 impl StateStore {
     pub fn new() -> Self {
-        let path = Paths::state_dir().join("state.json");
-        Self::with_path(path)
+        Self::with_path(ResolvedPaths::current().state_file())
     }
 
     pub fn with_path(path: PathBuf) -> Self {
@@ -48,6 +83,9 @@ impl StateStore {
             path,
             lock_path,
             lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            format: None,
+            force_downgrade: false,
+            newer_version: AtomicU32::new(0),
         }
     }
 
@@ -57,9 +95,49 @@ impl StateStore {
             path,
             lock_path,
             lock_timeout,
+            format: None,
+            force_downgrade: false,
+            newer_version: AtomicU32::new(0),
+        }
+    }
+
+    /// Write the state file in `format` (`daemon.state_format`).
+    ///
+    /// A file found in the other format is still read, and rewritten in
+    /// `format` as soon as it is loaded.
+    pub fn with_format(mut self, format: StateFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Rewrite a state file from a newer schema (`--force-downgrade`).
+    ///
+    /// The newer file is first copied to `state.v<N>.downgraded.bak`; fields
+    /// this build does not know are then dropped. Without it such a file is
+    /// left untouched and the store is read-only.
+    pub fn with_force_downgrade(mut self, force: bool) -> Self {
+        self.force_downgrade = force;
+        self
+    }
+
+    /// Schema version of the newer state file that made the store read-only.
+    pub fn newer_version(&self) -> Option<u32> {
+        match self.newer_version.load(Ordering::Relaxed) {
+            0 => None,
+            version => Some(version),
         }
     }
 
+    /// Read the state file without creating, backing up or migrating it.
+    ///
+    /// Returns the format the file is stored in alongside its contents.
+    pub fn inspect(&self) -> Result<(StateFile, StateFormat), StateError> {
+        let lock_file = self.open_lock_file()?;
+        self.lock_shared_with_timeout(&lock_file)?;
+        let bytes = fs::read(&self.path)?;
+        decode(&bytes)
+    }
+
     /// Load state from file, returning default if not exists or corrupted.
     pub fn load(&self) -> StateFile {
         if !self.path.exists() {
@@ -71,7 +149,7 @@ impl StateStore {
         }
 
         match self.load_inner() {
-            Ok(state) => state,
+            Ok((state, on_disk)) => self.reconcile(state, on_disk),
             Err(err) => {
                 warn!(error = %err, "Failed to load state, using defaults");
                 StateFile::default()
@@ -79,25 +157,120 @@ impl StateStore {
         }
     }
 
-    fn load_inner(&self) -> Result<StateFile, StateError> {
+    /// Bring a loaded file up to this build's schema version and format.
+    fn reconcile(&self, mut state: StateFile, on_disk: StateFormat) -> StateFile {
+        let found = state.version;
+        match found.cmp(&STATE_VERSION) {
+            CmpOrdering::Greater if !self.force_downgrade => {
+                self.enter_read_only(found);
+                return state;
+            }
+            CmpOrdering::Greater => match self.backup_newer(found) {
+                Ok(backup) => {
+                    warn!(
+                        found,
+                        supported = STATE_VERSION,
+                        backup = %backup.display(),
+                        "Downgrading state file written by a newer version"
+                    );
+                    state.version = STATE_VERSION;
+                    state.extra.clear();
+                }
+                Err(err) => {
+                    warn!(error = %err, "Failed to back up newer state file; not downgrading");
+                    self.enter_read_only(found);
+                    return state;
+                }
+            },
+            CmpOrdering::Less => {
+                info!(from = found, to = STATE_VERSION, "Migrating state file");
+                state.version = STATE_VERSION;
+            }
+            CmpOrdering::Equal => {}
+        }
+
+        let convert = self.format.filter(|format| *format != on_disk);
+        if let Some(format) = convert {
+            info!(from = %on_disk, to = %format, "Converting state file format");
+        }
+        if found != STATE_VERSION || convert.is_some() {
+            if let Err(err) = self.save(&state) {
+                warn!(error = %err, "Failed to rewrite state file");
+            }
+        }
+        state
+    }
+
+    fn enter_read_only(&self, found: u32) {
+        self.newer_version.store(found, Ordering::Relaxed);
+        warn!(
+            path = %self.path.display(),
+            found,
+            supported = STATE_VERSION,
+            "State file was written by a newer version; running read-only and leaving it \
+             untouched. Upgrade palingenesis, or restart with --force-downgrade to rewrite it"
+        );
+        self.audit(
+            AuditEntry::new(
+                AuditEventType::Error,
+                "State file written by a newer version",
+            )
+            .with_outcome(AuditOutcome::Failure)
+            .with_metadata("state_file", self.path.display().to_string())
+            .with_metadata("found_version", found.to_string())
+            .with_metadata("supported_version", STATE_VERSION.to_string()),
+        );
+    }
+
+    /// Copy a newer-schema file aside before it is rewritten.
+    fn backup_newer(&self, found: u32) -> Result<PathBuf, StateError> {
+        let backup = self.path.with_extension(format!("v{found}.downgraded.bak"));
+        fs::copy(&self.path, &backup)?;
+        self.apply_owner_permissions(&backup)?;
+        self.audit(
+            AuditEntry::new(AuditEventType::Error, "State file downgraded")
+                .with_metadata("state_file", self.path.display().to_string())
+                .with_metadata("backup", backup.display().to_string())
+                .with_metadata("found_version", found.to_string()),
+        );
+        Ok(backup)
+    }
+
+    fn load_inner(&self) -> Result<(StateFile, StateFormat), StateError> {
         let lock_file = self.open_lock_file()?;
         self.lock_shared_with_timeout(&lock_file)?;
 
-        let mut file = File::open(&self.path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-
-        match serde_json::from_str(&contents) {
-            Ok(state) => Ok(state),
+        let bytes = fs::read(&self.path)?;
+        match decode(&bytes) {
+            Ok(loaded) => Ok(loaded),
+            Err(StateError::NewerVersion { found, supported }) if !self.force_downgrade => {
+                self.enter_read_only(found);
+                Err(StateError::NewerVersion { found, supported })
+            }
             Err(err) => {
                 self.backup_corrupted()?;
-                Err(StateError::Corrupted(err.to_string()))
+                Err(err)
             }
         }
     }
 
+    /// Format of the file on disk; JSON when it is missing or unreadable.
+    fn format_on_disk(&self) -> StateFormat {
+        let mut header = Vec::new();
+        if let Ok(file) = File::open(&self.path) {
+            let _ = file.take(MAX_HEADER_BYTES).read_to_end(&mut header);
+        }
+        format::detect(&header).unwrap_or_default()
+    }
+
     /// Save state to file with atomic write.
     pub fn save(&self, state: &StateFile) -> Result<(), StateError> {
+        if let Some(found) = self.newer_version() {
+            return Err(StateError::NewerVersion {
+                found,
+                supported: STATE_VERSION,
+            });
+        }
         if let Some(parent) = self.path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -112,8 +285,9 @@ impl StateStore {
             .truncate(true)
             .open(&temp_path)?;
 
-        let contents = serde_json::to_string_pretty(state)?;
-        temp_file.write_all(contents.as_bytes())?;
+        let format = self.format.unwrap_or_else(|| self.format_on_disk());
+        let contents = format::encode(state, format)?;
+        temp_file.write_all(&contents)?;
         temp_file.sync_all()?;
 
         self.apply_owner_permissions(&temp_path)?;
@@ -171,9 +345,25 @@ impl StateStore {
             "Backing up corrupted state file"
         );
         fs::copy(&self.path, &backup_path)?;
+        self.audit(
+            AuditEntry::new(AuditEventType::Error, "State file corrupted")
+                .with_outcome(AuditOutcome::Failure)
+                .with_metadata("state_file", self.path.display().to_string())
+                .with_metadata("backup", backup_path.display().to_string()),
+        );
         Ok(())
     }
 
+    /// Record `entry` in the audit trail next to the state file.
+    fn audit(&self, entry: AuditEntry) {
+        let Some(state_dir) = self.path.parent() else {
+            return;
+        };
+        if let Err(err) = AuditLogger::new(state_dir).log(&entry) {
+            warn!(error = %err, "Failed to audit state file problem");
+        }
+    }
+
     fn apply_owner_permissions(&self, path: &Path) -> Result<(), StateError> {
         #[cfg(unix)]
         {
@@ -184,6 +374,46 @@ impl StateStore {
     }
 }
 
+impl StateBackend for StateStore {
+    fn load(&self) -> StateFile {
+        StateStore::load(self)
+    }
+
+    fn save(&self, state: &StateFile) -> Result<(), StateError> {
+        StateStore::save(self, state)
+    }
+
+    fn newer_version(&self) -> Option<u32> {
+        StateStore::newer_version(self)
+    }
+}
+
+impl<T: StateBackend + ?Sized> StateBackend for std::sync::Arc<T> {
+    fn load(&self) -> StateFile {
+        (**self).load()
+    }
+
+    fn save(&self, state: &StateFile) -> Result<(), StateError> {
+        (**self).save(state)
+    }
+
+    fn newer_version(&self) -> Option<u32> {
+        (**self).newer_version()
+    }
+}
+/// Decode a state file. One from a newer schema that this build cannot
+/// parse is [`StateError::NewerVersion`], not corrupted.
+fn decode(bytes: &[u8]) -> Result<(StateFile, StateFormat), StateError> {
+    let newer = format::probe_version(bytes).filter(|found| *found > STATE_VERSION);
+    format::decode(bytes).map_err(|err| match newer {
+        Some(found) => StateError::NewerVersion {
+            found,
+            supported: STATE_VERSION,
+        },
+        None => StateError::Corrupted(err),
+    })
+}
+
 impl Default for StateStore {
     fn default() -> Self {
         Self::new()
@@ -238,6 +468,54 @@ mod tests {
         assert!(temp.path().join("state.json.bak").exists());
     }
 
+    #[test]
+    fn test_format_change_converts_file_in_both_directions() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_path = temp.path().join("state.json");
+        let mut state = StateFile::default();
+        state.stats.saves_count = 12;
+        StateStore::with_path(state_path.clone())
+            .save(&state)
+            .unwrap();
+
+        let binary = StateStore::with_path(state_path.clone()).with_format(StateFormat::Msgpack);
+        assert_eq!(binary.load(), state);
+        let bytes = fs::read(&state_path).unwrap();
+        assert_eq!(format::detect(&bytes), Ok(StateFormat::Msgpack));
+
+        // A store without a configured format (the CLI) keeps the file's format.
+        let cli = StateStore::with_path(state_path.clone());
+        state.stats.saves_count = 13;
+        cli.save(&state).unwrap();
+        assert_eq!(
+            cli.inspect().unwrap(),
+            (state.clone(), StateFormat::Msgpack)
+        );
+
+        let json = StateStore::with_path(state_path.clone()).with_format(StateFormat::Json);
+        assert_eq!(json.load(), state);
+        let contents = fs::read_to_string(&state_path).unwrap();
+        assert!(serde_json::from_str::<StateFile>(&contents).is_ok());
+    }
+
+    #[test]
+    fn test_corrupted_binary_file_recovery() {
+        let temp = tempfile::tempdir().unwrap();
+        let state_path = temp.path().join("state.json");
+        let store = StateStore::with_path(state_path.clone()).with_format(StateFormat::Msgpack);
+        store.save(&StateFile::default()).unwrap();
+        let mut bytes = fs::read(&state_path).unwrap();
+        let len = bytes.len();
+        bytes.truncate(len - 3);
+        fs::write(&state_path, &bytes).unwrap();
+
+        assert!(matches!(store.inspect(), Err(StateError::Corrupted(_))));
+        let state = store.load();
+
+        assert_eq!(state, StateFile::default());
+        assert_eq!(fs::read(temp.path().join("state.json.bak")).unwrap(), bytes);
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_state_permissions() {
@@ -0,0 +1,131 @@
+//! Step timeline of a session: when each step completed, how long it took,
+//! and the automatic resumes that happened in between.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::schema::StateFile;
+
+/// Timeline of one session, ready for display.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineView {
+    pub session_path: PathBuf,
+    pub rows: Vec<TimelineRow>,
+    /// Resumes after the last completed step, i.e. while the next one ran.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resumes_since_last_step: Vec<DateTime<Utc>>,
+}
+
+/// One completed step.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineRow {
+    pub step: u32,
+    /// `None` when the step completed while the daemon was offline.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Time since the previous step completed, when both times are known.
+    pub duration_secs: Option<i64>,
+    /// Resumes between the previous step and this one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resumes: Vec<DateTime<Utc>>,
+}
+
+/// Timeline of the current session, or of the most recently updated one when
+/// no session is current.
+pub fn current(state: &StateFile) -> Option<TimelineView> {
+    let path = match &state.current_session {
+        Some(session) => session.path.clone(),
+        None => state.step_timelines.last()?.path.clone(),
+    };
+    build(state, &path)
+}
+
+/// Timeline of `path`, correlated with the resumes still in its resume history.
+pub fn build(state: &StateFile, path: &Path) -> Option<TimelineView> {
+    let timeline = state.step_timeline(path)?;
+    let resumed_at: &[DateTime<Utc>] = state
+        .resume_history(path)
+        .map(|history| history.resumed_at.as_slice())
+        .unwrap_or_default();
+
+    let mut rows = Vec::with_capacity(timeline.steps.len());
+    let mut previous: Option<DateTime<Utc>> = None;
+    for entry in &timeline.steps {
+        let (duration_secs, resumes) = match (previous, entry.completed_at) {
+            (Some(start), Some(end)) => (
+                Some((end - start).num_seconds()),
+                resumes_between(resumed_at, Some(start), end),
+            ),
+            (None, Some(end)) if rows.is_empty() => (None, resumes_between(resumed_at, None, end)),
+            _ => (None, Vec::new()),
+        };
+        rows.push(TimelineRow {
+            step: entry.step,
+            completed_at: entry.completed_at,
+            duration_secs,
+            resumes,
+        });
+        previous = entry.completed_at;
+    }
+
+    let resumes_since_last_step = match timeline.steps.last() {
+        Some(last) => match last.completed_at {
+            Some(at) => resumed_at.iter().copied().filter(|r| *r > at).collect(),
+            None => Vec::new(),
+        },
+        None => resumed_at.to_vec(),
+    };
+
+    Some(TimelineView {
+        session_path: path.to_path_buf(),
+        rows,
+        resumes_since_last_step,
+    })
+}
+
+fn resumes_between(
+    resumed_at: &[DateTime<Utc>],
+    after: Option<DateTime<Utc>>,
+    until: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    resumed_at
+        .iter()
+        .copied()
+        .filter(|at| after.is_none_or(|after| *at > after) && *at <= until)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    use crate::state::CurrentSession;
+
+    #[test]
+    fn durations_and_resumes_fall_between_known_steps() {
+        let path = PathBuf::from("/w/session.md");
+        let t0 = Utc::now();
+        let mut state = StateFile::default();
+        state.record_completed_steps(&path, &[1], None);
+        state.record_completed_steps(&path, &[1, 2], Some(t0));
+        state.record_completed_steps(&path, &[1, 2, 3], Some(t0 + Duration::minutes(10)));
+        state.resume_history_mut(&path).resumed_at =
+            vec![t0 + Duration::minutes(4), t0 + Duration::minutes(12)];
+        state.current_session = Some(CurrentSession {
+            path: path.clone(),
+            ..CurrentSession::default()
+        });
+
+        let view = current(&state).unwrap();
+        let durations: Vec<_> = view.rows.iter().map(|row| row.duration_secs).collect();
+        assert_eq!(durations, vec![None, None, Some(600)]);
+        assert!(view.rows[1].resumes.is_empty());
+        assert_eq!(view.rows[2].resumes, vec![t0 + Duration::minutes(4)]);
+        assert_eq!(
+            view.resumes_since_last_step,
+            vec![t0 + Duration::minutes(12)]
+        );
+    }
+}
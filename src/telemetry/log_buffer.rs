@@ -0,0 +1,296 @@
+//! In-memory ring buffer of recent daemon log records, served over IPC so
+//! `palingenesis logs` works without a log file.
+//!
+//! Recording never waits: when the buffer is busy the record is dropped and
+//! counted instead, and the oldest records make room for new ones once either
+//! the entry or the byte limit is reached.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+/// Default for `daemon.log_buffer_entries`.
+pub const DEFAULT_LOG_BUFFER_ENTRIES: usize = 2000;
+/// Default for `daemon.log_buffer_bytes`.
+pub const DEFAULT_LOG_BUFFER_BYTES: usize = 1024 * 1024;
+
+const FOLLOW_CHANNEL_CAPACITY: usize = 256;
+
+static GLOBAL_LOG_BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+
+/// One captured tracing event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// Position in the daemon's log stream; increases by one per record seen.
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl LogRecord {
+    /// Whether the record is at least as severe as `level`.
+    pub fn is_at_least(&self, level: Level) -> bool {
+        self.level
+            .parse::<Level>()
+            .map_or(true, |record_level| record_level <= level)
+    }
+
+    fn approx_bytes(&self) -> usize {
+        self.level.len()
+            + self.target.len()
+            + self.message.len()
+            + self
+                .fields
+                .iter()
+                .map(|(key, value)| key.len() + value.len())
+                .sum::<usize>()
+            + std::mem::size_of::<Self>()
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:>5} {}: {}",
+            self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            self.level,
+            self.target,
+            self.message
+        )?;
+        for (key, value) in &self.fields {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Newest records matching a `LOGS` query, oldest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogBatch {
+    pub records: Vec<LogRecord>,
+    /// Records dropped since the daemon started (evicted or skipped).
+    pub dropped: u64,
+    /// Newest record in the buffer when queried, matching or not; a follow
+    /// stream starts after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_seq: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Ring {
+    records: VecDeque<LogRecord>,
+    bytes: usize,
+}
+
+#[derive(Debug)]
+struct Inner {
+    ring: Mutex<Ring>,
+    max_entries: usize,
+    max_bytes: usize,
+    next_seq: AtomicU64,
+    dropped: AtomicU64,
+    follow: broadcast::Sender<LogRecord>,
+}
+
+/// Bounded buffer of recent log records; clones share the same buffer.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    inner: Arc<Inner>,
+}
+
+impl LogBuffer {
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        let (follow, _) = broadcast::channel(FOLLOW_CHANNEL_CAPACITY);
+        Self {
+            inner: Arc::new(Inner {
+                ring: Mutex::new(Ring {
+                    records: VecDeque::with_capacity(max_entries),
+                    bytes: 0,
+                }),
+                max_entries,
+                max_bytes,
+                next_seq: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                follow,
+            }),
+        }
+    }
+
+    /// Install the daemon-wide buffer; returns false if one is already set.
+    pub fn set_global(buffer: LogBuffer) -> bool {
+        GLOBAL_LOG_BUFFER.set(buffer).is_ok()
+    }
+
+    pub fn global() -> Option<LogBuffer> {
+        GLOBAL_LOG_BUFFER.get().cloned()
+    }
+
+    /// Tracing layer that records into this buffer.
+    pub fn layer(&self) -> LogBufferLayer {
+        LogBufferLayer {
+            buffer: self.clone(),
+        }
+    }
+
+    /// Records dropped so far, either evicted to make room or skipped
+    /// because the buffer was busy.
+    pub fn dropped(&self) -> u64 {
+        self.inner.dropped.load(Ordering::Relaxed)
+    }
+
+    /// The newest `limit` records at `level` or more severe, oldest first.
+    pub fn query(&self, limit: usize, level: Option<Level>) -> LogBatch {
+        let (records, latest_seq) = match self.inner.ring.lock() {
+            Ok(ring) => {
+                let mut records: Vec<LogRecord> = ring
+                    .records
+                    .iter()
+                    .rev()
+                    .filter(|record| level.is_none_or(|level| record.is_at_least(level)))
+                    .take(limit)
+                    .cloned()
+                    .collect();
+                records.reverse();
+                (records, ring.records.back().map(|record| record.seq))
+            }
+            Err(_) => (Vec::new(), None),
+        };
+        LogBatch {
+            records,
+            dropped: self.dropped(),
+            latest_seq,
+        }
+    }
+
+    /// Records captured from now on, for `LOGS ... FOLLOW`.
+    pub fn subscribe(&self) -> broadcast::Receiver<LogRecord> {
+        self.inner.follow.subscribe()
+    }
+
+    fn push(&self, mut record: LogRecord) {
+        let inner = &self.inner;
+        record.seq = inner.next_seq.fetch_add(1, Ordering::Relaxed);
+        if inner.max_entries == 0 {
+            return;
+        }
+        if inner.follow.receiver_count() > 0 {
+            let _ = inner.follow.send(record.clone());
+        }
+        let Ok(mut ring) = inner.ring.try_lock() else {
+            inner.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        ring.bytes += record.approx_bytes();
+        ring.records.push_back(record);
+        let mut evicted = 0;
+        while ring.records.len() > inner.max_entries
+            || (ring.bytes > inner.max_bytes && ring.records.len() > 1)
+        {
+            if let Some(old) = ring.records.pop_front() {
+                ring.bytes -= old.approx_bytes();
+                evicted += 1;
+            }
+        }
+        if evicted > 0 {
+            inner.dropped.fetch_add(evicted, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_BUFFER_ENTRIES, DEFAULT_LOG_BUFFER_BYTES)
+    }
+}
+
+/// Tracing layer feeding a [`LogBuffer`].
+pub struct LogBufferLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+        self.buffer.push(LogRecord {
+            seq: 0,
+            timestamp: Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn keeps_newest_records_within_limits() {
+        let buffer = LogBuffer::new(3, DEFAULT_LOG_BUFFER_BYTES);
+        let subscriber = tracing_subscriber::registry().with(buffer.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            for index in 0..5 {
+                tracing::info!(index, "event {index}");
+            }
+        });
+
+        let batch = buffer.query(10, None);
+        let messages: Vec<_> = batch.records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["event 2", "event 3", "event 4"]);
+        assert_eq!(batch.records[0].fields["index"], "2");
+        assert_eq!(batch.dropped, 2);
+
+        let small = LogBuffer::new(100, 1);
+        let subscriber = tracing_subscriber::registry().with(small.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::info!("second");
+        });
+        assert_eq!(small.query(10, None).records.len(), 1);
+    }
+}
@@ -0,0 +1,424 @@
+//! Following the daemon log file (`palingenesis logs --follow` while no
+//! daemon is running).
+//!
+//! [`LogTail`] remembers how far into the file it has read and only reads
+//! what was appended since. A file shorter than that offset was truncated:
+//! reading restarts at the top after a [`TRUNCATED_MARKER`] line. A different
+//! file at the same path was rotated in: the rest of the old file is read
+//! first, then the new one from the top. Lines that do not start a record
+//! (panic messages, backtrace frames) are kept with the record before them,
+//! so a backtrace comes out as one block. [`follow`] drives a tail from file
+//! system events rather than polling.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use notify::{Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Emitted in place of the lines lost when the log file is truncated.
+pub const TRUNCATED_MARKER: &str = "--- log truncated ---";
+
+/// How long a record waits for more continuation lines once the file goes
+/// quiet.
+const SETTLE: Duration = Duration::from_millis(150);
+
+/// Reads a log file incrementally, one record at a time.
+pub struct LogTail {
+    path: PathBuf,
+    file: Option<File>,
+    file_id: Option<FileId>,
+    offset: u64,
+    /// Bytes after the last newline read so far.
+    partial: Vec<u8>,
+    /// The newest record, held back while continuation lines may follow.
+    record: Option<String>,
+}
+
+impl LogTail {
+    /// Open `path` at its end, returning its last `records` records.
+    pub fn open(path: &Path, records: usize) -> io::Result<(Self, Vec<String>)> {
+        let mut tail = Self {
+            path: path.to_path_buf(),
+            file: None,
+            file_id: None,
+            offset: 0,
+            partial: Vec::new(),
+            record: None,
+        };
+        let mut existing = tail.read()?;
+        existing.extend(tail.flush());
+        let skip = existing.len().saturating_sub(records);
+        existing.drain(..skip);
+        Ok((tail, existing))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records completed since the last read. The newest one is held back
+    /// until the next record starts or [`Self::flush`] is called.
+    pub fn read(&mut self) -> io::Result<Vec<String>> {
+        let mut records = Vec::new();
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            // Rotated away and not recreated yet.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                self.drain(&mut records)?;
+                return Ok(records);
+            }
+            Err(err) => return Err(err),
+        };
+        let file_id = FileId::of(&metadata);
+        if self.file.is_none() || file_id != self.file_id {
+            if self.file.is_some() {
+                self.drain(&mut records)?;
+                self.finish(&mut records);
+            }
+            self.file = Some(File::open(&self.path)?);
+            self.file_id = file_id;
+            self.offset = 0;
+        } else if metadata.len() < self.offset {
+            self.finish(&mut records);
+            records.push(TRUNCATED_MARKER.to_string());
+            self.offset = 0;
+        }
+        self.drain(&mut records)?;
+        Ok(records)
+    }
+
+    /// The held-back record, once no more lines are expected for it.
+    pub fn flush(&mut self) -> Option<String> {
+        self.record.take()
+    }
+
+    fn has_pending(&self) -> bool {
+        self.record.is_some()
+    }
+
+    /// Read the current file from the offset to its end.
+    fn drain(&mut self, records: &mut Vec<String>) -> io::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        let read = file.read_to_end(&mut appended)?;
+        self.offset += read as u64;
+        self.partial.extend_from_slice(&appended);
+        while let Some(end) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            self.push_line(decode(&line), records);
+        }
+        Ok(())
+    }
+
+    /// Emit everything held, including a last line without its newline.
+    fn finish(&mut self, records: &mut Vec<String>) {
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            self.push_line(decode(&line), records);
+        }
+        records.extend(self.flush());
+    }
+
+    fn push_line(&mut self, line: String, records: &mut Vec<String>) {
+        match &mut self.record {
+            Some(record) if !starts_record(&line) => {
+                record.push('\n');
+                record.push_str(&line);
+            }
+            _ => records.extend(self.record.replace(line)),
+        }
+    }
+}
+
+/// Emit the records appended to `tail`'s file until `cancel` fires, reading
+/// when the file system reports a change to it.
+pub async fn follow(
+    mut tail: LogTail,
+    cancel: CancellationToken,
+    mut emit: impl FnMut(&str),
+) -> io::Result<()> {
+    let path = tail.path().to_path_buf();
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let (changed_tx, mut changed) = mpsc::channel(1);
+    let watched = path.clone();
+    // The directory is watched too, so a rotated-in file is noticed.
+    let mut watcher = RecommendedWatcher::new(
+        move |result: Result<Event, notify::Error>| {
+            if result.is_ok_and(|event| event.paths.iter().any(|path| *path == watched)) {
+                // A pending wake-up already covers this change.
+                let _ = changed_tx.try_send(());
+            }
+        },
+        NotifyConfig::default(),
+    )
+    .map_err(io::Error::other)?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(io::Error::other)?;
+
+    // Pick up anything written between opening the tail and the watch.
+    for record in tail.read()? {
+        emit(&record);
+    }
+    loop {
+        let settle = tail.has_pending();
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            received = changed.recv() => {
+                if received.is_none() {
+                    break;
+                }
+                for record in tail.read()? {
+                    emit(&record);
+                }
+            }
+            _ = tokio::time::sleep(SETTLE), if settle => {
+                if let Some(record) = tail.flush() {
+                    emit(&record);
+                }
+            }
+        }
+    }
+    if let Some(record) = tail.flush() {
+        emit(&record);
+    }
+    Ok(())
+}
+
+/// Whether `line` begins a log record: a timestamped text line, a JSON
+/// line, or the first line of a panic.
+fn starts_record(line: &str) -> bool {
+    let line = strip_ansi(line);
+    line.starts_with('{')
+        || (line.starts_with("thread '") && line.contains("panicked"))
+        || line
+            .get(..19)
+            .is_some_and(|stamp| NaiveDateTime::parse_from_str(stamp, "%Y-%m-%dT%H:%M:%S").is_ok())
+}
+
+/// `line` without leading ANSI color sequences.
+fn strip_ansi(mut line: &str) -> &str {
+    while let Some(rest) = line.strip_prefix("\x1b[") {
+        match rest.find(|c: char| c.is_ascii_alphabetic()) {
+            Some(end) => line = &rest[end + 1..],
+            None => break,
+        }
+    }
+    line
+}
+
+fn decode(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+/// Identity of the file behind a path, to notice when it is replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileId {
+    dev: u64,
+    ino: u64,
+}
+
+impl FileId {
+    #[cfg(unix)]
+    fn of(metadata: &fs::Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+        Some(Self {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+        })
+    }
+
+    /// Without inode numbers a replaced file reads as a truncated one.
+    #[cfg(not(unix))]
+    fn of(_metadata: &fs::Metadata) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn line(second: u32, message: &str) -> String {
+        format!("2026-10-16T12:00:{second:02}.000000Z  INFO palingenesis: {message}\n")
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = File::options()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    /// Everything read plus the held-back record.
+    fn read_all(tail: &mut LogTail) -> Vec<String> {
+        let mut records = tail.read().unwrap();
+        records.extend(tail.flush());
+        records
+    }
+
+    #[test]
+    fn open_returns_the_last_records_and_starts_at_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        append(
+            &path,
+            &(line(1, "one") + &line(2, "two") + &line(3, "three")),
+        );
+
+        let (mut tail, last) = LogTail::open(&path, 2).unwrap();
+        assert_eq!(
+            last,
+            vec![line(2, "two").trim_end(), line(3, "three").trim_end()]
+        );
+        assert!(read_all(&mut tail).is_empty());
+    }
+
+    #[test]
+    fn appends_are_read_once_even_in_pieces() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        append(&path, &line(1, "old"));
+        let (mut tail, _) = LogTail::open(&path, 0).unwrap();
+
+        let second = line(2, "appended");
+        let (head, rest) = second.split_at(20);
+        append(&path, head);
+        assert!(read_all(&mut tail).is_empty());
+        append(&path, rest);
+        append(&path, &line(3, "more"));
+        assert_eq!(
+            read_all(&mut tail),
+            vec![second.trim_end(), line(3, "more").trim_end()]
+        );
+        assert!(read_all(&mut tail).is_empty());
+    }
+
+    #[test]
+    fn truncation_restarts_at_the_top_with_a_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        append(&path, &(line(1, "one") + &line(2, "two")));
+        let (mut tail, _) = LogTail::open(&path, 0).unwrap();
+
+        fs::write(&path, line(3, "fresh")).unwrap();
+        assert_eq!(
+            read_all(&mut tail),
+            vec![
+                TRUNCATED_MARKER.to_string(),
+                line(3, "fresh").trim_end().to_string()
+            ]
+        );
+        append(&path, &line(4, "next"));
+        assert_eq!(read_all(&mut tail), vec![line(4, "next").trim_end()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rotation_finishes_the_old_file_then_reads_the_new_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        append(&path, &line(1, "before"));
+        let (mut tail, _) = LogTail::open(&path, 0).unwrap();
+
+        // Written just before the rename, not yet read.
+        append(&path, &line(2, "last in old file"));
+        fs::rename(&path, dir.path().join("daemon.log.1")).unwrap();
+        assert_eq!(
+            read_all(&mut tail),
+            vec![line(2, "last in old file").trim_end()]
+        );
+
+        append(&path, &(line(3, "first in new file") + &line(4, "second")));
+        assert_eq!(
+            read_all(&mut tail),
+            vec![
+                line(3, "first in new file").trim_end(),
+                line(4, "second").trim_end()
+            ]
+        );
+    }
+
+    #[test]
+    fn backtraces_stay_with_their_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        fs::write(&path, "").unwrap();
+        let (mut tail, _) = LogTail::open(&path, 0).unwrap();
+
+        let panic = "thread 'tokio-runtime-worker' panicked at src/daemon/core.rs:10:5:\n\
+                     boom\n\
+                     stack backtrace:\n   \
+                     0: rust_begin_unwind\n";
+        append(&path, &(line(1, "before") + panic));
+        // The panic may still be growing, so it is held back.
+        assert_eq!(tail.read().unwrap(), vec![line(1, "before").trim_end()]);
+        append(&path, "   1: core::panicking::panic_fmt\n");
+        append(&path, &format!("\x1b[2m{}", line(2, "after")));
+        assert_eq!(
+            tail.read().unwrap(),
+            vec![format!("{}   1: core::panicking::panic_fmt", panic)]
+        );
+        assert_eq!(
+            tail.flush(),
+            Some(format!("\x1b[2m{}", line(2, "after").trim_end()))
+        );
+    }
+
+    async fn next(emitted: &mut mpsc::UnboundedReceiver<String>) -> String {
+        tokio::time::timeout(Duration::from_secs(5), emitted.recv())
+            .await
+            .expect("record emitted")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn follow_emits_appends_truncation_and_rotation_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        append(&path, &line(1, "existing"));
+        let (tail, _) = LogTail::open(&path, 0).unwrap();
+
+        let (emitted_tx, mut emitted) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        let following = tokio::spawn(follow(tail, cancel.clone(), move |record| {
+            let _ = emitted_tx.send(record.to_string());
+        }));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        append(&path, &line(2, "appended"));
+        assert_eq!(next(&mut emitted).await, line(2, "appended").trim_end());
+
+        fs::write(&path, line(3, "after truncate")).unwrap();
+        assert_eq!(next(&mut emitted).await, TRUNCATED_MARKER);
+        assert_eq!(
+            next(&mut emitted).await,
+            line(3, "after truncate").trim_end()
+        );
+
+        fs::rename(&path, dir.path().join("daemon.log.1")).unwrap();
+        append(&path, &line(4, "rotated in"));
+        assert_eq!(next(&mut emitted).await, line(4, "rotated in").trim_end());
+
+        cancel.cancel();
+        following.await.unwrap().unwrap();
+        assert!(emitted.try_recv().is_err(), "nothing emitted twice");
+    }
+}
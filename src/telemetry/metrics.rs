@@ -1,6 +1,10 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use prometheus_client::encoding::EncodeLabelSet;
 use prometheus_client::encoding::text::encode;
 use prometheus_client::metrics::counter::Counter;
@@ -10,9 +14,10 @@ use prometheus_client::metrics::histogram::Histogram;
 use prometheus_client::registry::Registry;
 use tracing::warn;
 
+use crate::config::Paths;
 use crate::daemon::state::DaemonState;
 use crate::ipc::socket::DaemonStateAccess;
-use crate::state::StateStore;
+use crate::state::{ResumeCost, StateHandle};
 
 const METRICS_NAMESPACE: &str = "palingenesis";
 const BUILD_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -40,19 +45,76 @@ struct ResumeFailureLabels {
     error_type: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct StrategyLabels {
+    strategy: String,
+    outcome: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ChannelLabels {
+    channel: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct StoreLabels {
+    store: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ScanLabels {
+    scan: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct LaneLabels {
+    lane: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct SubsystemLabels {
+    subsystem: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct JobLabels {
+    job: String,
+    priority: String,
+}
+
+/// One retry cycle in progress, i.e. the state behind the backoff gauges.
+#[derive(Debug, Clone, Copy)]
+struct BackoffCycle {
+    detected_at: DateTime<Utc>,
+    next_retry_at: DateTime<Utc>,
+    delay: Duration,
+    attempts_remaining: u32,
+}
+
+#[derive(Debug, Default)]
+struct BackoffTracker {
+    cycles: HashMap<String, BackoffCycle>,
+    /// While paused no retry is scheduled, so next-retry and delay read 0.
+    paused: bool,
+}
+
 #[derive(Clone)]
 pub struct Metrics {
     registry: Arc<Mutex<Registry>>,
     info: Family<InfoLabels, Gauge>,
+    opencode_info: Family<InfoLabels, Gauge>,
     build_info: Family<BuildInfoLabels, Gauge>,
     daemon_state: Gauge,
     uptime_seconds: Gauge,
     resumes_total: Family<ResumeReasonLabels, Counter>,
+    resumes_observed_total: Family<ResumeReasonLabels, Counter>,
     resumes_success_total: Counter,
     resumes_failure_total: Family<ResumeFailureLabels, Counter>,
+    strategy_runs_total: Family<StrategyLabels, Counter>,
     saves_total: Counter,
     sessions_started_total: Counter,
     rate_limits_total: Counter,
+    quota_exhaustions_total: Counter,
     context_exhaustions_total: Counter,
     current_session_steps_completed: Gauge,
     current_session_steps_total: Gauge,
@@ -63,11 +125,40 @@ pub struct Metrics {
     wait_duration_seconds: Histogram,
     time_saved_seconds_total: Counter<f64>,
     time_saved_per_resume_seconds: Histogram,
+    estimated_cost_total: Counter<f64>,
+    estimated_value_saved_total: Counter<f64>,
+    notification_latency_seconds: Family<ChannelLabels, Gauge<f64, AtomicU64>>,
+    suspend_seconds_total: Counter<f64>,
+    clock_skew_seconds: Gauge<f64, AtomicU64>,
+    events_filtered_total: Counter,
+    metrics_push_failures_total: Counter,
+    resume_loop_suspected_total: Counter,
+    resumes_avoided_by_restart_total: Counter,
+    job_duration_seconds: Family<JobLabels, Histogram, fn() -> Histogram>,
+    session_scan_duration_seconds: Family<ScanLabels, Histogram, fn() -> Histogram>,
+    startup_duration_seconds: Family<SubsystemLabels, Histogram, fn() -> Histogram>,
+    watched_directories: Gauge,
+    watch_polling: Gauge,
+    debounce_delay_seconds: Histogram,
+    debounce_window_seconds: Gauge<f64, AtomicU64>,
+    classification_queue_depth: Family<LaneLabels, Gauge>,
+    classification_wait_seconds: Family<LaneLabels, Histogram, fn() -> Histogram>,
+    classifications_dropped_total: Counter,
+    content_bytes_retained: Family<StoreLabels, Gauge>,
+    backoff_next_retry_timestamp_seconds: Family<ResumeReasonLabels, Gauge>,
+    backoff_attempts_remaining: Family<ResumeReasonLabels, Gauge>,
+    backoff_current_delay_seconds: Family<ResumeReasonLabels, Gauge<f64, AtomicU64>>,
+    incident_age_seconds: Family<ResumeReasonLabels, Gauge<f64, AtomicU64>>,
+    backoff: Arc<Mutex<BackoffTracker>>,
 }
 
 impl Metrics {
     pub fn new() -> Self {
-        let mut registry = Registry::default();
+        // Every family carries the instance so several daemons can share a scrape target.
+        let mut registry = Registry::with_labels(std::iter::once((
+            Cow::Borrowed("instance"),
+            Cow::Owned(Paths::instance_label()),
+        )));
 
         let info = Family::<InfoLabels, Gauge>::default();
         registry.register(
@@ -76,6 +167,13 @@ impl Metrics {
             info.clone(),
         );
 
+        let opencode_info = Family::<InfoLabels, Gauge>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_opencode_info"),
+            "Version of the installed opencode",
+            opencode_info.clone(),
+        );
+
         let build_info = Family::<BuildInfoLabels, Gauge>::default();
         registry.register(
             format!("{METRICS_NAMESPACE}_build_info"),
@@ -98,20 +196,25 @@ impl Metrics {
         );
 
         let resumes_total = Family::<ResumeReasonLabels, Counter>::default();
-        for reason in ["rate_limit", "context_exhausted", "manual"] {
+        for reason in [
+            "rate_limit",
+            "quota_exhausted",
+            "context_exhausted",
+            "manual",
+        ] {
             let _ = resumes_total.get_or_create(&ResumeReasonLabels {
                 reason: reason.to_string(),
             });
         }
         registry.register(
-            format!("{METRICS_NAMESPACE}_resumes_total"),
+            format!("{METRICS_NAMESPACE}_resumes"),
             "Total number of resume operations attempted",
             resumes_total.clone(),
         );
 
         let resumes_success_total = Counter::default();
         registry.register(
-            format!("{METRICS_NAMESPACE}_resumes_success_total"),
+            format!("{METRICS_NAMESPACE}_resumes_success"),
             "Total number of successful resumes",
             resumes_success_total.clone(),
         );
@@ -132,35 +235,49 @@ impl Metrics {
             });
         }
         registry.register(
-            format!("{METRICS_NAMESPACE}_resumes_failure_total"),
+            format!("{METRICS_NAMESPACE}_resumes_failure"),
             "Total number of failed resume attempts",
             resumes_failure_total.clone(),
         );
 
+        let strategy_runs_total = Family::<StrategyLabels, Counter>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_strategy_runs"),
+            "Runs of custom resume strategies, by strategy name and outcome",
+            strategy_runs_total.clone(),
+        );
+
         let saves_total = Counter::default();
         registry.register(
-            format!("{METRICS_NAMESPACE}_saves_total"),
+            format!("{METRICS_NAMESPACE}_saves"),
             "Total number of times palingenesis saved work by automatically resuming",
             saves_total.clone(),
         );
 
         let sessions_started_total = Counter::default();
         registry.register(
-            format!("{METRICS_NAMESPACE}_sessions_started_total"),
+            format!("{METRICS_NAMESPACE}_sessions_started"),
             "Total number of sessions started",
             sessions_started_total.clone(),
         );
 
         let rate_limits_total = Counter::default();
         registry.register(
-            format!("{METRICS_NAMESPACE}_rate_limits_total"),
+            format!("{METRICS_NAMESPACE}_rate_limits"),
             "Total number of rate limit events detected",
             rate_limits_total.clone(),
         );
 
+        let quota_exhaustions_total = Counter::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_quota_exhaustions"),
+            "Total number of provider quota exhaustion events detected",
+            quota_exhaustions_total.clone(),
+        );
+
         let context_exhaustions_total = Counter::default();
         registry.register(
-            format!("{METRICS_NAMESPACE}_context_exhaustions_total"),
+            format!("{METRICS_NAMESPACE}_context_exhaustions"),
             "Total number of context exhaustion events",
             context_exhaustions_total.clone(),
         );
@@ -217,7 +334,7 @@ impl Metrics {
 
         let time_saved_seconds_total = Counter::<f64>::default();
         registry.register(
-            format!("{METRICS_NAMESPACE}_time_saved_seconds_total"),
+            format!("{METRICS_NAMESPACE}_time_saved_seconds"),
             "Total estimated time saved by automatic resumption",
             time_saved_seconds_total.clone(),
         );
@@ -230,18 +347,212 @@ impl Metrics {
             time_saved_per_resume_seconds.clone(),
         );
 
+        let estimated_cost_total = Counter::<f64>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_estimated_cost"),
+            "Estimated API spend of resumed session segments (metrics.currency)",
+            estimated_cost_total.clone(),
+        );
+
+        let estimated_value_saved_total = Counter::<f64>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_estimated_value_saved"),
+            "Time saved by resumes valued at metrics.hourly_rate (metrics.currency)",
+            estimated_value_saved_total.clone(),
+        );
+
+        let notification_latency_seconds =
+            Family::<ChannelLabels, Gauge<f64, AtomicU64>>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_notification_latency_seconds"),
+            "Rolling (EWMA) delivery latency per notification channel",
+            notification_latency_seconds.clone(),
+        );
+
+        let suspend_seconds_total = Counter::<f64>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_suspend_seconds"),
+            "Total time the host was suspended while the daemon ran",
+            suspend_seconds_total.clone(),
+        );
+
+        let clock_skew_seconds = Gauge::<f64, AtomicU64>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_clock_skew_seconds"),
+            "Smoothed offset of provider Date headers from the local clock",
+            clock_skew_seconds.clone(),
+        );
+
+        let events_filtered_total = Counter::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_events_filtered"),
+            "Watcher events dropped by the extension and ignore filters",
+            events_filtered_total.clone(),
+        );
+
+        let metrics_push_failures_total = Counter::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_metrics_push_failures"),
+            "Failed pushes to the configured Pushgateway",
+            metrics_push_failures_total.clone(),
+        );
+
+        let resumes_observed_total = Family::<ResumeReasonLabels, Counter>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_resumes_observed"),
+            "Resumes planned but not executed because the daemon is in observe mode",
+            resumes_observed_total.clone(),
+        );
+
+        let resume_loop_suspected_total = Counter::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_resume_loop_suspected"),
+            "Sessions that hit resume.max_resumes_per_hour and stopped auto-resuming",
+            resume_loop_suspected_total.clone(),
+        );
+
+        let resumes_avoided_by_restart_total = Counter::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_resumes_avoided_by_restart"),
+            "Held stops dropped because the session continued after an opencode restart",
+            resumes_avoided_by_restart_total.clone(),
+        );
+
+        let job_duration_seconds: Family<JobLabels, Histogram, fn() -> Histogram> =
+            Family::new_with_constructor(|| {
+                Histogram::new([0.01, 0.1, 0.5, 1.0, 5.0, 30.0, 120.0, 600.0])
+            });
+        registry.register(
+            format!("{METRICS_NAMESPACE}_job_duration_seconds"),
+            "Run time of daemon jobs by name and priority",
+            job_duration_seconds.clone(),
+        );
+
+        let session_scan_duration_seconds: Family<ScanLabels, Histogram, fn() -> Histogram> =
+            Family::new_with_constructor(|| {
+                Histogram::new([0.001, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 30.0])
+            });
+        registry.register(
+            format!("{METRICS_NAMESPACE}_session_scan_duration_seconds"),
+            "Time spent walking the session directory (catalog listing, watch setup)",
+            session_scan_duration_seconds.clone(),
+        );
+
+        let startup_duration_seconds: Family<SubsystemLabels, Histogram, fn() -> Histogram> =
+            Family::new_with_constructor(|| {
+                Histogram::new([0.001, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 30.0])
+            });
+        registry.register(
+            format!("{METRICS_NAMESPACE}_startup_duration_seconds"),
+            "Time each subsystem took to initialize at daemon start",
+            startup_duration_seconds.clone(),
+        );
+
+        let watched_directories = Gauge::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_watched_directories"),
+            "Directories holding an inotify watch (0 while polling)",
+            watched_directories.clone(),
+        );
+
+        let watch_polling = Gauge::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_watch_polling"),
+            "1 when the session watcher fell back to polling after hitting the watch limit",
+            watch_polling.clone(),
+        );
+
+        let debounce_delay_seconds =
+            Histogram::new([0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0]);
+        registry.register(
+            format!("{METRICS_NAMESPACE}_debounce_delay_seconds"),
+            "Time file events waited in the watcher's debounce buffer before being forwarded",
+            debounce_delay_seconds.clone(),
+        );
+
+        let debounce_window_seconds = Gauge::<f64, AtomicU64>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_debounce_window_seconds"),
+            "Current debounce window of the session watcher",
+            debounce_window_seconds.clone(),
+        );
+
+        let classification_queue_depth = Family::<LaneLabels, Gauge>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_classification_queue_depth"),
+            "Stop classifications waiting for a worker, by lane",
+            classification_queue_depth.clone(),
+        );
+
+        let classification_wait_seconds: Family<LaneLabels, Histogram, fn() -> Histogram> =
+            Family::new_with_constructor(|| {
+                Histogram::new([0.001, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 30.0, 300.0])
+            });
+        registry.register(
+            format!("{METRICS_NAMESPACE}_classification_wait_seconds"),
+            "Time stop classifications waited in the queue before a worker took them, by lane",
+            classification_wait_seconds.clone(),
+        );
+
+        let classifications_dropped_total = Counter::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_classifications_dropped"),
+            "Bulk-lane classifications dropped for waiting longer than monitoring.classify_stale_secs",
+            classifications_dropped_total.clone(),
+        );
+
+        let content_bytes_retained = Family::<StoreLabels, Gauge>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_content_bytes_retained"),
+            "Estimated session content held by long-lived buffers (event ring, notification history)",
+            content_bytes_retained.clone(),
+        );
+
+        let backoff_next_retry_timestamp_seconds = Family::<ResumeReasonLabels, Gauge>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_backoff_next_retry_timestamp_seconds"),
+            "Unix time of the next scheduled resume attempt (0 if none is scheduled)",
+            backoff_next_retry_timestamp_seconds.clone(),
+        );
+
+        let backoff_attempts_remaining = Family::<ResumeReasonLabels, Gauge>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_backoff_attempts_remaining"),
+            "Resume attempts left in the current retry cycle, including the scheduled one",
+            backoff_attempts_remaining.clone(),
+        );
+
+        let backoff_current_delay_seconds =
+            Family::<ResumeReasonLabels, Gauge<f64, AtomicU64>>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_backoff_current_delay_seconds"),
+            "Delay before the scheduled resume attempt (0 if none is scheduled)",
+            backoff_current_delay_seconds.clone(),
+        );
+
+        let incident_age_seconds = Family::<ResumeReasonLabels, Gauge<f64, AtomicU64>>::default();
+        registry.register(
+            format!("{METRICS_NAMESPACE}_incident_age_seconds"),
+            "Time since the stop that started the current retry cycle",
+            incident_age_seconds.clone(),
+        );
+
         let metrics = Self {
             registry: Arc::new(Mutex::new(registry)),
             info,
+            opencode_info,
             build_info,
             daemon_state,
             uptime_seconds,
             resumes_total,
+            resumes_observed_total,
             resumes_success_total,
             resumes_failure_total,
+            strategy_runs_total,
             saves_total,
             sessions_started_total,
             rate_limits_total,
+            quota_exhaustions_total,
             context_exhaustions_total,
             current_session_steps_completed,
             current_session_steps_total,
@@ -252,11 +563,37 @@ impl Metrics {
             wait_duration_seconds,
             time_saved_seconds_total,
             time_saved_per_resume_seconds,
+            estimated_cost_total,
+            estimated_value_saved_total,
+            notification_latency_seconds,
+            suspend_seconds_total,
+            clock_skew_seconds,
+            events_filtered_total,
+            metrics_push_failures_total,
+            resume_loop_suspected_total,
+            resumes_avoided_by_restart_total,
+            job_duration_seconds,
+            session_scan_duration_seconds,
+            startup_duration_seconds,
+            watched_directories,
+            watch_polling,
+            debounce_delay_seconds,
+            debounce_window_seconds,
+            classification_queue_depth,
+            classification_wait_seconds,
+            classifications_dropped_total,
+            content_bytes_retained,
+            backoff_next_retry_timestamp_seconds,
+            backoff_attempts_remaining,
+            backoff_current_delay_seconds,
+            incident_age_seconds,
+            backoff: Arc::new(Mutex::new(BackoffTracker::default())),
         };
 
         metrics.set_static_info();
         metrics.initialize_saves_total();
         metrics.initialize_time_saved_total();
+        metrics.initialize_cost_totals();
         metrics
     }
 
@@ -283,12 +620,14 @@ impl Metrics {
         self.daemon_state.set(state_value);
         self.uptime_seconds.set(state.uptime().as_secs() as i64);
         self.update_session_gauges();
+        self.set_backoff_paused(state.is_paused());
     }
 
     /// Records the start of a resume operation.
     ///
     /// # Arguments
-    /// * `reason` - The reason for the resume: "rate_limit", "context_exhausted", or "manual"
+    /// * `reason` - The reason for the resume: "rate_limit", "quota_exhausted",
+    ///   "context_exhausted", or "manual"
     pub fn record_resume_started(&self, reason: &str) {
         self.resumes_total
             .get_or_create(&ResumeReasonLabels {
@@ -323,6 +662,17 @@ impl Metrics {
         }
     }
 
+    /// Records a run of the custom strategy `strategy`; `outcome` is the
+    /// [`ResumeOutcome`](crate::resume::ResumeOutcome) label or "error".
+    pub fn record_strategy_run(&self, strategy: &str, outcome: &str) {
+        self.strategy_runs_total
+            .get_or_create(&StrategyLabels {
+                strategy: strategy.to_string(),
+                outcome: outcome.to_string(),
+            })
+            .inc();
+    }
+
     pub fn record_save(&self) {
         self.saves_total.inc();
     }
@@ -338,6 +688,9 @@ impl Metrics {
             "rate_limit" => {
                 self.rate_limits_total.inc();
             }
+            "quota_exhausted" => {
+                self.quota_exhaustions_total.inc();
+            }
             "context_exhausted" => {
                 self.context_exhaustions_total.inc();
             }
@@ -349,6 +702,18 @@ impl Metrics {
         self.wait_duration_seconds.observe(duration.as_secs_f64());
     }
 
+    /// Add the known parts of a resume's cost estimate.
+    pub fn record_resume_cost(&self, cost: &ResumeCost) {
+        for (value, counter) in [
+            (cost.cost, &self.estimated_cost_total),
+            (cost.value_saved, &self.estimated_value_saved_total),
+        ] {
+            if let Some(value) = value.filter(|value| value.is_finite() && *value > 0.0) {
+                counter.inc_by(value);
+            }
+        }
+    }
+
     pub fn record_time_saved(&self, total_saved_seconds: f64) {
         if !total_saved_seconds.is_finite() || total_saved_seconds <= 0.0 {
             return;
@@ -358,13 +723,237 @@ impl Metrics {
             .observe(total_saved_seconds);
     }
 
+    /// Report `version` as the installed opencode, replacing the previous one.
+    pub fn set_opencode_version(&self, version: &str) {
+        self.opencode_info.clear();
+        self.opencode_info
+            .get_or_create(&InfoLabels {
+                version: version.to_string(),
+            })
+            .set(1);
+    }
+
+    pub fn record_suspend(&self, gap: Duration) {
+        self.suspend_seconds_total.inc_by(gap.as_secs_f64());
+    }
+
+    pub fn set_clock_skew(&self, seconds: f64) {
+        self.clock_skew_seconds.set(seconds);
+    }
+
+    pub fn record_event_filtered(&self) {
+        self.events_filtered_total.inc();
+    }
+
+    pub fn record_push_failure(&self) {
+        self.metrics_push_failures_total.inc();
+    }
+
+    /// Records a resume observe mode held back; kept out of `resumes_total`.
+    pub fn record_resume_observed(&self, reason: &str) {
+        self.resumes_observed_total
+            .get_or_create(&ResumeReasonLabels {
+                reason: reason.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn record_resume_loop_suspected(&self) {
+        self.resume_loop_suspected_total.inc();
+    }
+
+    pub fn record_resume_avoided_by_restart(&self) {
+        self.resumes_avoided_by_restart_total.inc();
+    }
+
+    pub fn record_job(&self, job: &str, priority: &str, duration: Duration) {
+        self.job_duration_seconds
+            .get_or_create(&JobLabels {
+                job: job.to_string(),
+                priority: priority.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Records how long a walk of the session directory took; `scan` is
+    /// `catalog` or `watch_setup`.
+    pub fn record_session_scan(&self, scan: &str, duration: Duration) {
+        self.session_scan_duration_seconds
+            .get_or_create(&ScanLabels {
+                scan: scan.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn record_startup(&self, subsystem: &str, duration: Duration) {
+        self.startup_duration_seconds
+            .get_or_create(&SubsystemLabels {
+                subsystem: subsystem.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn set_watched_directories(&self, count: usize) {
+        self.watched_directories
+            .set(i64::try_from(count).unwrap_or(i64::MAX));
+    }
+
+    pub fn set_watch_polling(&self, polling: bool) {
+        self.watch_polling.set(i64::from(polling));
+    }
+
+    /// Records how long a file event waited in the debounce buffer.
+    pub fn record_debounce_delay(&self, delay: Duration) {
+        self.debounce_delay_seconds.observe(delay.as_secs_f64());
+    }
+
+    pub fn set_debounce_window(&self, window: Duration) {
+        self.debounce_window_seconds.set(window.as_secs_f64());
+    }
+
+    pub fn set_classification_queue_depth(&self, lane: &str, depth: usize) {
+        self.classification_queue_depth
+            .get_or_create(&LaneLabels {
+                lane: lane.to_string(),
+            })
+            .set(depth as i64);
+    }
+
+    pub fn record_classification_wait(&self, lane: &str, wait: Duration) {
+        self.classification_wait_seconds
+            .get_or_create(&LaneLabels {
+                lane: lane.to_string(),
+            })
+            .observe(wait.as_secs_f64());
+    }
+
+    pub fn record_classification_dropped(&self) {
+        self.classifications_dropped_total.inc();
+    }
+
+    pub fn set_notification_latency(&self, channel: &str, seconds: f64) {
+        self.notification_latency_seconds
+            .get_or_create(&ChannelLabels {
+                channel: channel.to_string(),
+            })
+            .set(seconds);
+    }
+
+    /// Estimated content bytes held by `store` (see [`crate::util::content`]).
+    pub fn set_content_bytes_retained(&self, store: &str, bytes: usize) {
+        self.content_bytes_retained
+            .get_or_create(&StoreLabels {
+                store: store.to_string(),
+            })
+            .set(i64::try_from(bytes).unwrap_or(i64::MAX));
+    }
+
     pub fn set_retry_attempts(&self, attempt: u32) {
         self.retry_attempts.set(i64::from(attempt));
     }
 
+    /// Records that a resume attempt for `reason` is scheduled after `delay`.
+    ///
+    /// # Arguments
+    /// * `reason` - Same label as [`Self::record_resume_started`]
+    /// * `delay` - Wait before the attempt
+    /// * `attempts_remaining` - Attempts left in the cycle, including this one
+    /// * `detected_at` - When the stop that started the cycle was detected
+    pub fn record_backoff_wait(
+        &self,
+        reason: &str,
+        delay: Duration,
+        attempts_remaining: u32,
+        detected_at: DateTime<Utc>,
+    ) {
+        let now = Utc::now();
+        let next_retry_at = chrono::Duration::from_std(delay)
+            .ok()
+            .and_then(|delay| now.checked_add_signed(delay))
+            .unwrap_or(now);
+        let Ok(mut tracker) = self.backoff.lock() else {
+            return;
+        };
+        let cycle = BackoffCycle {
+            detected_at,
+            next_retry_at,
+            delay,
+            attempts_remaining,
+        };
+        tracker.cycles.insert(reason.to_string(), cycle);
+        self.publish_backoff(&tracker, now);
+    }
+
+    /// Ends the retry cycle for `reason`: the resume succeeded, gave up, or
+    /// was dropped. Next-retry and delay drop to 0, the rest disappear.
+    pub fn end_retry_cycle(&self, reason: &str) {
+        self.retry_attempts.set(0);
+        let Ok(mut tracker) = self.backoff.lock() else {
+            return;
+        };
+        tracker.cycles.remove(reason);
+        self.clear_backoff_labels(reason);
+    }
+
+    /// Ends every retry cycle; a manual trigger replaces whatever was scheduled.
+    pub fn end_all_retry_cycles(&self) {
+        self.retry_attempts.set(0);
+        let Ok(mut tracker) = self.backoff.lock() else {
+            return;
+        };
+        for (reason, _) in tracker.cycles.drain() {
+            self.clear_backoff_labels(&reason);
+        }
+    }
+
+    fn set_backoff_paused(&self, paused: bool) {
+        let Ok(mut tracker) = self.backoff.lock() else {
+            return;
+        };
+        tracker.paused = paused;
+        self.publish_backoff(&tracker, Utc::now());
+    }
+
+    fn publish_backoff(&self, tracker: &BackoffTracker, now: DateTime<Utc>) {
+        for (reason, cycle) in &tracker.cycles {
+            let labels = ResumeReasonLabels {
+                reason: reason.clone(),
+            };
+            let (next_retry, delay) = if tracker.paused {
+                (0, 0.0)
+            } else {
+                (cycle.next_retry_at.timestamp(), cycle.delay.as_secs_f64())
+            };
+            self.backoff_next_retry_timestamp_seconds
+                .get_or_create(&labels)
+                .set(next_retry);
+            self.backoff_current_delay_seconds
+                .get_or_create(&labels)
+                .set(delay);
+            self.backoff_attempts_remaining
+                .get_or_create(&labels)
+                .set(i64::from(cycle.attempts_remaining));
+            let age = (now - cycle.detected_at).num_milliseconds().max(0) as f64 / 1000.0;
+            self.incident_age_seconds.get_or_create(&labels).set(age);
+        }
+    }
+
+    fn clear_backoff_labels(&self, reason: &str) {
+        let labels = ResumeReasonLabels {
+            reason: reason.to_string(),
+        };
+        self.backoff_next_retry_timestamp_seconds
+            .get_or_create(&labels)
+            .set(0);
+        self.backoff_current_delay_seconds
+            .get_or_create(&labels)
+            .set(0.0);
+        self.backoff_attempts_remaining.remove(&labels);
+        self.incident_age_seconds.remove(&labels);
+    }
+
     fn update_session_gauges(&self) {
-        let store = StateStore::new();
-        let state = store.load();
+        let state = StateHandle::read_state();
         if let Some(session) = state.current_session {
             let completed = session.steps_completed.len() as i64;
             let total_steps = if session.total_steps > 0 {
@@ -406,17 +995,25 @@ impl Metrics {
     }
 
     fn initialize_time_saved_total(&self) {
-        let store = StateStore::new();
-        let state = store.load();
+        let state = StateHandle::read_state();
         let total_saved = state.stats.time_saved_seconds;
         if total_saved.is_finite() && total_saved > 0.0 {
             self.time_saved_seconds_total.inc_by(total_saved);
         }
     }
 
+    fn initialize_cost_totals(&self) {
+        let stats = StateHandle::read_state().stats;
+        if stats.estimated_cost.is_finite() && stats.estimated_cost > 0.0 {
+            self.estimated_cost_total.inc_by(stats.estimated_cost);
+        }
+        if stats.value_saved.is_finite() && stats.value_saved > 0.0 {
+            self.estimated_value_saved_total.inc_by(stats.value_saved);
+        }
+    }
+
     fn initialize_saves_total(&self) {
-        let store = StateStore::new();
-        let state = store.load();
+        let state = StateHandle::read_state();
         if state.stats.saves_count > 0 {
             self.saves_total.inc_by(state.stats.saves_count);
         }
@@ -464,7 +1061,7 @@ mod tests {
 
         assert!(output.contains("# HELP palingenesis_info"));
         assert!(output.contains("# TYPE palingenesis_info gauge"));
-        assert!(output.contains("palingenesis_info{version=\""));
+        assert!(output.contains("palingenesis_info{instance=\"default\",version=\""));
         assert!(output.contains("# HELP palingenesis_daemon_state"));
         assert!(output.contains("# TYPE palingenesis_daemon_state gauge"));
         assert!(output.contains("# HELP palingenesis_uptime_seconds"));
@@ -479,10 +1076,36 @@ mod tests {
         metrics.record_resume_started("rate_limit");
         metrics.record_resume_completed(Duration::from_millis(250), true, None);
         metrics.record_detection(Duration::from_millis(50), "rate_limit");
+        metrics.record_detection(Duration::from_millis(50), "quota_exhausted");
         metrics.record_wait(Duration::from_secs(2));
         metrics.record_session_started();
         metrics.record_save();
         metrics.record_time_saved(360.0);
+        metrics.record_resume_cost(&ResumeCost {
+            at: Utc::now(),
+            model: None,
+            tokens: None,
+            cost: Some(0.25),
+            time_saved_seconds: 360.0,
+            value_saved: None,
+        });
+        metrics.record_suspend(Duration::from_secs(90));
+        metrics.set_clock_skew(-75.5);
+        metrics.record_event_filtered();
+        metrics.record_resume_loop_suspected();
+        metrics.record_resume_observed("context_exhausted");
+        metrics.record_strategy_run("notify-oncall", "success");
+        metrics.record_resume_avoided_by_restart();
+        metrics.record_job("auto_detect", "background", Duration::from_millis(40));
+        metrics.set_content_bytes_retained("events", 4096);
+        metrics.record_session_scan("catalog", Duration::from_millis(12));
+        metrics.record_startup("classifier", Duration::from_millis(8));
+        metrics.set_watched_directories(42);
+        metrics.set_watch_polling(true);
+        metrics.set_debounce_window(Duration::from_millis(250));
+        metrics.set_classification_queue_depth("bulk", 7);
+        metrics.record_classification_wait("priority", Duration::from_millis(3));
+        metrics.record_classification_dropped();
         let output = metrics.encode().expect("encode metrics");
 
         assert!(output.contains("palingenesis_resumes_total"));
@@ -491,6 +1114,7 @@ mod tests {
         assert!(output.contains("palingenesis_saves_total"));
         assert!(output.contains("palingenesis_sessions_started_total"));
         assert!(output.contains("palingenesis_rate_limits_total"));
+        assert!(output.contains("palingenesis_quota_exhaustions_total"));
         assert!(output.contains("palingenesis_context_exhaustions_total"));
         assert!(output.contains("palingenesis_current_session_steps_completed"));
         assert!(output.contains("palingenesis_current_session_steps_total"));
@@ -499,26 +1123,71 @@ mod tests {
         assert!(output.contains("palingenesis_resume_duration_seconds"));
         assert!(output.contains("palingenesis_detection_latency_seconds"));
         assert!(output.contains("palingenesis_wait_duration_seconds"));
+        assert!(output.contains("palingenesis_suspend_seconds_total{instance=\"default\"} 90"));
+        assert!(output.contains("palingenesis_clock_skew_seconds{instance=\"default\"} -75.5"));
+        assert!(output.contains("palingenesis_events_filtered_total{instance=\"default\"} 1"));
+        assert!(
+            output.contains("palingenesis_resume_loop_suspected_total{instance=\"default\"} 1")
+        );
+        assert!(
+            output
+                .contains("palingenesis_resumes_avoided_by_restart_total{instance=\"default\"} 1")
+        );
+        assert!(output.contains(
+            "palingenesis_resumes_observed_total{instance=\"default\",reason=\"context_exhausted\"} 1"
+        ));
+        assert!(output.contains(
+            "palingenesis_strategy_runs_total{instance=\"default\",strategy=\"notify-oncall\",outcome=\"success\"} 1"
+        ));
+        assert!(output.contains(
+            "palingenesis_job_duration_seconds_count{instance=\"default\",job=\"auto_detect\",priority=\"background\"} 1"
+        ));
+        assert!(output.contains(
+            "palingenesis_content_bytes_retained{instance=\"default\",store=\"events\"} 4096"
+        ));
+        assert!(output.contains(
+            "palingenesis_session_scan_duration_seconds_count{instance=\"default\",scan=\"catalog\"} 1"
+        ));
+        assert!(output.contains(
+            "palingenesis_startup_duration_seconds_count{instance=\"default\",subsystem=\"classifier\"} 1"
+        ));
+        assert!(output.contains("palingenesis_watched_directories{instance=\"default\"} 42"));
+        assert!(output.contains("palingenesis_watch_polling{instance=\"default\"} 1"));
+        assert!(output.contains("palingenesis_debounce_window_seconds{instance=\"default\"} 0.25"));
+        assert!(output.contains(
+            "palingenesis_classification_queue_depth{instance=\"default\",lane=\"bulk\"} 7"
+        ));
+        assert!(output.contains(
+            "palingenesis_classification_wait_seconds_count{instance=\"default\",lane=\"priority\"} 1"
+        ));
+        assert!(
+            output.contains("palingenesis_classifications_dropped_total{instance=\"default\"} 1")
+        );
         assert!(output.contains("palingenesis_time_saved_seconds_total"));
         assert!(output.contains("palingenesis_time_saved_per_resume_seconds"));
+        assert!(output.contains("palingenesis_estimated_cost_total{instance=\"default\"} 0.25"));
+        assert!(output.contains("palingenesis_estimated_value_saved_total"));
     }
 
     #[test]
     fn test_session_gauges_from_state_store() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempdir().unwrap();
         let state_dir = temp.path().join("state");
         set_env_var("PALINGENESIS_STATE", &state_dir);
 
         let store = StateStore::new();
-        let mut state = StateFile::default();
-        state.current_session = Some(CurrentSession {
-            path: temp.path().join("session.md"),
-            steps_completed: vec![1, 2, 3],
-            last_step: 5,
-            total_steps: 8,
-        });
-        state.stats = Stats::default();
+        let state = StateFile {
+            current_session: Some(CurrentSession {
+                path: temp.path().join("session.md"),
+                steps_completed: vec![1, 2, 3],
+                last_step: 5,
+                total_steps: 8,
+                ..CurrentSession::default()
+            }),
+            stats: Stats::default(),
+            ..StateFile::default()
+        };
         store.save(&state).expect("save state");
 
         let metrics = Metrics::new();
@@ -526,25 +1195,31 @@ mod tests {
         metrics.update_from_state(&daemon_state);
         let output = metrics.encode().expect("encode metrics");
 
-        assert!(output.contains("palingenesis_active_sessions 1"));
-        assert!(output.contains("palingenesis_current_session_steps_completed 3"));
-        assert!(output.contains("palingenesis_current_session_steps_total 8"));
+        assert!(output.contains("palingenesis_active_sessions{instance=\"default\"} 1"));
+        assert!(
+            output.contains("palingenesis_current_session_steps_completed{instance=\"default\"} 3")
+        );
+        assert!(
+            output.contains("palingenesis_current_session_steps_total{instance=\"default\"} 8")
+        );
 
         remove_env_var("PALINGENESIS_STATE");
     }
 
     #[test]
     fn test_metrics_initialize_saves_from_state() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempdir().unwrap();
         let state_dir = temp.path().join("state");
         set_env_var("PALINGENESIS_STATE", &state_dir);
 
         let store = StateStore::new();
-        let mut state = StateFile::default();
-        state.stats = Stats {
-            saves_count: 12,
-            ..Stats::default()
+        let state = StateFile {
+            stats: Stats {
+                saves_count: 12,
+                ..Stats::default()
+            },
+            ..StateFile::default()
         };
         store.save(&state).expect("save state");
 
@@ -603,6 +1278,89 @@ mod tests {
         state.pause().expect("pause daemon");
         metrics.update_from_state(&state);
         let output = metrics.encode().expect("encode metrics");
-        assert!(output.contains("palingenesis_daemon_state 2"));
+        assert!(output.contains("palingenesis_daemon_state{instance=\"default\"} 2"));
+    }
+
+    #[test]
+    fn test_backoff_gauges_follow_retry_cycle() {
+        let metrics = Metrics::new();
+        let state = DaemonState::new();
+        let line = |name: &str| {
+            format!("palingenesis_{name}{{instance=\"default\",reason=\"rate_limit\"}}")
+        };
+        let value = |output: &str, name: &str| -> Option<f64> {
+            let prefix = format!("{} ", line(name));
+            output
+                .lines()
+                .find_map(|l| l.strip_prefix(prefix.as_str()))
+                .map(|v| v.parse().expect("numeric sample"))
+        };
+        let detected_at = Utc::now() - chrono::Duration::seconds(90);
+
+        // First attempt scheduled.
+        metrics.record_backoff_wait("rate_limit", Duration::from_secs(30), 3, detected_at);
+        metrics.update_from_state(&state);
+        let output = metrics.encode().expect("encode metrics");
+        let next_retry = value(&output, "backoff_next_retry_timestamp_seconds").unwrap();
+        assert!((next_retry - (Utc::now().timestamp() + 30) as f64).abs() <= 2.0);
+        assert_eq!(value(&output, "backoff_current_delay_seconds"), Some(30.0));
+        assert_eq!(value(&output, "backoff_attempts_remaining"), Some(3.0));
+        assert!(value(&output, "incident_age_seconds").unwrap() >= 90.0);
+
+        // Attempt failed, the next one backs off further.
+        metrics.record_backoff_wait("rate_limit", Duration::from_secs(60), 2, detected_at);
+        let output = metrics.encode().expect("encode metrics");
+        assert_eq!(value(&output, "backoff_current_delay_seconds"), Some(60.0));
+        assert_eq!(value(&output, "backoff_attempts_remaining"), Some(2.0));
+
+        // Paused: nothing is scheduled until the daemon resumes.
+        state.pause().expect("pause daemon");
+        metrics.update_from_state(&state);
+        let output = metrics.encode().expect("encode metrics");
+        assert_eq!(
+            value(&output, "backoff_next_retry_timestamp_seconds"),
+            Some(0.0)
+        );
+        assert_eq!(value(&output, "backoff_current_delay_seconds"), Some(0.0));
+        assert_eq!(value(&output, "backoff_attempts_remaining"), Some(2.0));
+        state.resume().expect("resume daemon");
+        metrics.update_from_state(&state);
+        let output = metrics.encode().expect("encode metrics");
+        assert_eq!(value(&output, "backoff_current_delay_seconds"), Some(60.0));
+
+        // Resume succeeded: the cycle is over.
+        metrics.end_retry_cycle("rate_limit");
+        metrics.update_from_state(&state);
+        let output = metrics.encode().expect("encode metrics");
+        assert_eq!(
+            value(&output, "backoff_next_retry_timestamp_seconds"),
+            Some(0.0)
+        );
+        assert_eq!(value(&output, "backoff_current_delay_seconds"), Some(0.0));
+        assert_eq!(value(&output, "backoff_attempts_remaining"), None);
+        assert_eq!(value(&output, "incident_age_seconds"), None);
+        assert!(output.contains("palingenesis_retry_attempts{instance=\"default\"} 0"));
+
+        // A manual trigger replaces any scheduled retry.
+        metrics.record_backoff_wait("rate_limit", Duration::from_secs(30), 3, detected_at);
+        metrics.end_all_retry_cycles();
+        let output = metrics.encode().expect("encode metrics");
+        assert_eq!(
+            value(&output, "backoff_next_retry_timestamp_seconds"),
+            Some(0.0)
+        );
+        assert_eq!(value(&output, "backoff_attempts_remaining"), None);
+    }
+
+    #[test]
+    fn test_opencode_info_reports_only_current_version() {
+        let metrics = Metrics::new();
+        metrics.set_opencode_version("0.3.1");
+        metrics.set_opencode_version("0.4.0");
+        let output = metrics.encode().expect("encode metrics");
+        assert!(
+            output.contains("palingenesis_opencode_info{instance=\"default\",version=\"0.4.0\"} 1")
+        );
+        assert!(!output.contains("version=\"0.3.1\""));
     }
 }
@@ -1,6 +1,11 @@
+pub mod log_buffer;
+pub mod log_tail;
 pub mod metrics;
 pub mod otel;
+#[cfg(feature = "metrics-push")]
+pub mod push;
 pub mod tracing;
 
+pub use log_buffer::LogBuffer;
 pub use metrics::Metrics;
 pub use tracing::{TracingConfig, TracingError, TracingGuard, init_tracing};
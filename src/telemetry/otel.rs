@@ -9,8 +9,9 @@ use tracing::info;
 use tracing::warn;
 
 use crate::config::Paths;
-use crate::config::schema::{Config, OtelConfig};
+use crate::config::schema::OtelConfig;
 use crate::config::validation::validate_config;
+use crate::daemon::state::load_config_from_disk;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OtelProtocol {
@@ -29,23 +30,15 @@ impl OtelProtocol {
 }
 
 pub fn load_otel_config() -> Option<OtelConfig> {
-    let path = Paths::config_file();
-    if !path.exists() {
+    if !Paths::config_file().exists() {
         return None;
     }
 
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            warn!(error = %err, "Failed to read config for otel; using defaults");
-            return None;
-        }
-    };
-
-    let config: Config = match toml::from_str(&contents) {
+    // Goes through the daemon loader so `${keyring:...}` push credentials resolve.
+    let config = match load_config_from_disk() {
         Ok(config) => config,
         Err(err) => {
-            warn!(error = %err, "Failed to parse config for otel; using defaults");
+            warn!(error = %err, "Failed to load config for otel; using defaults");
             return None;
         }
     };
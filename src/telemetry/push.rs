@@ -0,0 +1,499 @@
+//! Push metrics to a Prometheus Pushgateway.
+//!
+//! For hosts Prometheus can't scrape (NAT, laptops), `[otel] push_mode =
+//! "pushgateway"` makes the daemon POST [`Metrics::encode`] output every
+//! `push_interval_secs` to the group
+//! `<push_endpoint>/metrics/job/<job>/instance/<host>/palingenesis_instance/<name>`.
+//! The gateway rejects series whose labels disagree with the grouping key,
+//! so the `instance` label every family carries (the daemon instance, not the
+//! host) is pushed as `palingenesis_instance`, which also keeps two daemons
+//! on one host in separate groups. Failed pushes back off up to
+//! [`MAX_BACKOFF_FACTOR`] intervals and count in
+//! `palingenesis_metrics_push_failures_total`. With
+//! `push_delete_on_shutdown` the group is deleted when the daemon stops, so
+//! a stopped daemon doesn't linger in the gateway with its last values.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Client, Method, RequestBuilder};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::config::Paths;
+use crate::config::schema::{MetricsPushMode, OtelConfig};
+use crate::daemon::state::DaemonState;
+use crate::telemetry::Metrics;
+use crate::util::host::host_name;
+
+/// Longest wait between pushes after repeated failures, in push intervals.
+pub const MAX_BACKOFF_FACTOR: u32 = 8;
+
+/// The exposition format is the text format, which Pushgateway parses.
+const PUSH_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Name the daemon's `instance` label is pushed under.
+const PUSHED_INSTANCE_LABEL: &str = "palingenesis_instance";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PushError {
+    #[error("Pushgateway request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Pushgateway returned {status}: {body}")]
+    Status { status: u16, body: String },
+
+    #[error("Failed to encode metrics: {0}")]
+    Encode(#[from] std::fmt::Error),
+}
+
+/// Pushgateway group that receives this host's metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushTarget {
+    endpoint: String,
+    job: String,
+    instance: String,
+    daemon_instance: String,
+    basic_auth: Option<(String, String)>,
+}
+
+impl PushTarget {
+    /// Target `endpoint` with `job`, grouped by this host's name and the
+    /// selected daemon instance, the same value the metrics are labelled with.
+    pub fn new(endpoint: impl Into<String>, job: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            job: job.into(),
+            instance: host_name(),
+            daemon_instance: Paths::instance_label(),
+            basic_auth: None,
+        }
+    }
+
+    /// Target from `[otel]`, when push mode is enabled and an endpoint is set.
+    pub fn from_config(config: &OtelConfig) -> Option<Self> {
+        if config.push_mode != MetricsPushMode::Pushgateway {
+            return None;
+        }
+        let endpoint = config.push_endpoint.as_deref()?.trim();
+        if endpoint.is_empty() {
+            return None;
+        }
+        let mut target = Self::new(endpoint, config.push_job_name.trim());
+        if let (Some(user), Some(password)) = (&config.push_username, &config.push_password) {
+            target = target.with_basic_auth(user.clone(), password.clone());
+        }
+        Some(target)
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = instance.into();
+        self
+    }
+
+    pub fn with_basic_auth(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.basic_auth = Some((user.into(), password.into()));
+        self
+    }
+
+    /// `<endpoint>/metrics/job/<job>/instance/<host>/palingenesis_instance/<name>`.
+    pub fn grouping_url(&self) -> String {
+        format!(
+            "{}/metrics/job/{}/instance/{}/{PUSHED_INSTANCE_LABEL}/{}",
+            self.endpoint.trim_end_matches('/'),
+            encode_segment(&self.job),
+            encode_segment(&self.instance),
+            encode_segment(&self.daemon_instance)
+        )
+    }
+}
+
+/// Sends metrics to one [`PushTarget`].
+pub struct MetricsPusher {
+    client: Client,
+    target: PushTarget,
+    interval: Duration,
+    delete_on_shutdown: bool,
+}
+
+impl MetricsPusher {
+    pub fn new(target: PushTarget) -> Self {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_else(|err| {
+                warn!(error = %err, "Failed to build Pushgateway client; using defaults");
+                Client::new()
+            });
+        Self {
+            client,
+            target,
+            interval: Duration::from_secs(60),
+            delete_on_shutdown: true,
+        }
+    }
+
+    /// Pusher configured from `[otel]`, or `None` when push mode is off.
+    pub fn from_config(config: &OtelConfig) -> Option<Self> {
+        let target = PushTarget::from_config(config)?;
+        Some(
+            Self::new(target)
+                .with_interval(Duration::from_secs(config.push_interval_secs.max(1)))
+                .with_delete_on_shutdown(config.push_delete_on_shutdown),
+        )
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_delete_on_shutdown(mut self, delete: bool) -> Self {
+        self.delete_on_shutdown = delete;
+        self
+    }
+
+    pub fn target(&self) -> &PushTarget {
+        &self.target
+    }
+
+    /// POST an exposition-format body to the grouping URL.
+    pub async fn push(&self, body: String) -> Result<(), PushError> {
+        let request = self
+            .request(Method::POST)
+            .header(reqwest::header::CONTENT_TYPE, PUSH_CONTENT_TYPE)
+            .body(rename_instance_label(&body));
+        send(request).await
+    }
+
+    /// Delete this host's group from the gateway.
+    pub async fn delete(&self) -> Result<(), PushError> {
+        send(self.request(Method::DELETE)).await
+    }
+
+    /// Encode `metrics` and push them, counting failures in `metrics`.
+    pub async fn push_metrics(&self, metrics: &Metrics) -> Result<(), PushError> {
+        let result = match metrics.encode() {
+            Ok(body) => self.push(body).await,
+            Err(err) => Err(err.into()),
+        };
+        if result.is_err() {
+            metrics.record_push_failure();
+        }
+        result
+    }
+
+    /// Push every interval until `cancel` fires, then optionally delete the group.
+    pub async fn run(
+        self,
+        metrics: Arc<Metrics>,
+        state: Arc<DaemonState>,
+        cancel: CancellationToken,
+    ) {
+        info!(url = %self.target.grouping_url(), "Pushing metrics to Pushgateway");
+        let mut failures: u32 = 0;
+        loop {
+            metrics.update_from_state(&state);
+            match self.push_metrics(&metrics).await {
+                Ok(()) => {
+                    if failures > 0 {
+                        info!(failures, "Metrics push recovered");
+                    }
+                    failures = 0;
+                }
+                Err(err) => {
+                    failures = failures.saturating_add(1);
+                    warn!(error = %err, failures, "Metrics push failed");
+                }
+            }
+
+            let wait = backoff_delay(self.interval, failures);
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+
+        if self.delete_on_shutdown {
+            match self.delete().await {
+                Ok(()) => debug!("Deleted Pushgateway metrics group"),
+                Err(err) => warn!(error = %err, "Failed to delete Pushgateway metrics group"),
+            }
+        }
+    }
+
+    fn request(&self, method: Method) -> RequestBuilder {
+        let request = self.client.request(method, self.target.grouping_url());
+        match &self.target.basic_auth {
+            Some((user, password)) => request.basic_auth(user, Some(password)),
+            None => request,
+        }
+    }
+}
+
+async fn send(request: RequestBuilder) -> Result<(), PushError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(PushError::Status {
+        status: status.as_u16(),
+        body: body.trim().chars().take(200).collect(),
+    })
+}
+
+/// Interval doubled per consecutive failure, capped at [`MAX_BACKOFF_FACTOR`].
+fn backoff_delay(interval: Duration, failures: u32) -> Duration {
+    let factor = 1u32
+        .checked_shl(failures)
+        .unwrap_or(u32::MAX)
+        .min(MAX_BACKOFF_FACTOR);
+    interval.saturating_mul(factor)
+}
+
+/// Exposition `body` with the daemon's `instance` label renamed to
+/// [`PUSHED_INSTANCE_LABEL`], so it cannot clash with the grouping key.
+///
+/// Label values escape their quotes, so `{instance="` and `,instance="` only
+/// match label names.
+fn rename_instance_label(body: &str) -> String {
+    body.replace("{instance=\"", &format!("{{{PUSHED_INSTANCE_LABEL}=\""))
+        .replace(",instance=\"", &format!(",{PUSHED_INSTANCE_LABEL}=\""))
+}
+
+/// Percent-encode a grouping label value for use as a path segment.
+fn encode_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "http-api")]
+    use axum::Router;
+    #[cfg(feature = "http-api")]
+    use axum::http::{HeaderMap, Method as HttpMethod, StatusCode, Uri};
+    #[cfg(feature = "http-api")]
+    use std::sync::Mutex;
+
+    #[cfg(feature = "http-api")]
+    #[derive(Debug, Clone)]
+    struct Captured {
+        method: HttpMethod,
+        path: String,
+        auth: Option<String>,
+        body: String,
+    }
+
+    /// Stub gateway recording every request and answering with `status`.
+    #[cfg(feature = "http-api")]
+    async fn stub_gateway(status: StatusCode) -> (String, Arc<Mutex<Vec<Captured>>>) {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&captured);
+        let app = Router::new().fallback(
+            move |method: HttpMethod, uri: Uri, headers: HeaderMap, body: String| {
+                let sink = Arc::clone(&sink);
+                async move {
+                    sink.lock().unwrap().push(Captured {
+                        method,
+                        path: uri.path().to_string(),
+                        auth: headers
+                            .get("authorization")
+                            .and_then(|value| value.to_str().ok())
+                            .map(str::to_string),
+                        body,
+                    });
+                    status
+                }
+            },
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}"), captured)
+    }
+
+    #[test]
+    fn grouping_url_escapes_labels() {
+        let target = PushTarget::new("http://gw:9091/", "palingenesis").with_instance("my host/1");
+        assert_eq!(
+            target.grouping_url(),
+            "http://gw:9091/metrics/job/palingenesis/instance/my%20host%2F1/palingenesis_instance/default"
+        );
+    }
+
+    #[test]
+    fn from_config_requires_pushgateway_mode_and_endpoint() {
+        let mut config = OtelConfig {
+            push_endpoint: Some("http://gw:9091".to_string()),
+            ..OtelConfig::default()
+        };
+        assert!(PushTarget::from_config(&config).is_none());
+
+        config.push_mode = MetricsPushMode::Pushgateway;
+        config.push_username = Some("user".to_string());
+        config.push_password = Some("pw".to_string());
+        let target = PushTarget::from_config(&config).unwrap();
+        assert_eq!(target.basic_auth, Some(("user".into(), "pw".into())));
+
+        config.push_endpoint = None;
+        assert!(PushTarget::from_config(&config).is_none());
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let interval = Duration::from_secs(10);
+        assert_eq!(backoff_delay(interval, 0), interval);
+        assert_eq!(backoff_delay(interval, 2), Duration::from_secs(40));
+        assert_eq!(backoff_delay(interval, 40), interval * MAX_BACKOFF_FACTOR);
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn pushes_exposition_to_grouping_path_with_auth() {
+        let (endpoint, captured) = stub_gateway(StatusCode::OK).await;
+        let pusher = MetricsPusher::new(
+            PushTarget::new(endpoint, "palingenesis")
+                .with_instance("host-1")
+                .with_basic_auth("metrics", "secret"),
+        );
+        let metrics = Metrics::new();
+
+        pusher.push_metrics(&metrics).await.unwrap();
+
+        let requests = captured.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, HttpMethod::POST);
+        assert_eq!(
+            request.path,
+            "/metrics/job/palingenesis/instance/host-1/palingenesis_instance/default"
+        );
+        assert!(request.auth.as_deref().unwrap().starts_with("Basic "));
+        assert!(
+            request
+                .body
+                .contains("# TYPE palingenesis_daemon_state gauge")
+        );
+        assert!(request.body.contains(
+            "palingenesis_metrics_push_failures_total{palingenesis_instance=\"default\"} 0"
+        ));
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn pushed_labels_agree_with_the_grouping_key() {
+        let (endpoint, captured) = stub_gateway(StatusCode::OK).await;
+        let pusher =
+            MetricsPusher::new(PushTarget::new(endpoint, "palingenesis").with_instance("laptop"));
+        let metrics = Metrics::new();
+        metrics.record_push_failure();
+
+        pusher.push(metrics.encode().unwrap()).await.unwrap();
+
+        let request = captured.lock().unwrap()[0].clone();
+        let grouping: Vec<(&str, &str)> = request
+            .path
+            .trim_start_matches("/metrics/")
+            .split('/')
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        assert_eq!(
+            grouping,
+            [
+                ("job", "palingenesis"),
+                ("instance", "laptop"),
+                ("palingenesis_instance", "default"),
+            ]
+        );
+        for line in request.body.lines().filter(|line| !line.starts_with('#')) {
+            let Some((_, labels)) = line.split_once('{') else {
+                continue;
+            };
+            let labels = labels.split_once('}').unwrap().0;
+            for label in labels.split(',') {
+                let (name, value) = label.split_once('=').unwrap();
+                if let Some((_, expected)) = grouping.iter().find(|(key, _)| *key == name) {
+                    assert_eq!(value.trim_matches('"'), *expected, "{line}");
+                }
+            }
+            assert!(
+                !labels.starts_with("instance=") && !labels.contains(",instance="),
+                "{line}"
+            );
+        }
+        assert!(request.body.contains(
+            "palingenesis_metrics_push_failures_total{palingenesis_instance=\"default\"} 1"
+        ));
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn failed_push_is_counted() {
+        let (endpoint, _captured) = stub_gateway(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let pusher = MetricsPusher::new(PushTarget::new(endpoint, "palingenesis"));
+        let metrics = Metrics::new();
+
+        let err = pusher.push_metrics(&metrics).await.unwrap_err();
+
+        assert!(matches!(err, PushError::Status { status: 500, .. }));
+        let output = metrics.encode().unwrap();
+        assert!(
+            output.contains("palingenesis_metrics_push_failures_total{instance=\"default\"} 1")
+        );
+    }
+
+    #[cfg(feature = "http-api")]
+    #[tokio::test]
+    async fn run_deletes_group_on_shutdown() {
+        let (endpoint, captured) = stub_gateway(StatusCode::ACCEPTED).await;
+        let pusher = MetricsPusher::new(PushTarget::new(endpoint, "job").with_instance("h"))
+            .with_interval(Duration::from_secs(3600));
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        pusher
+            .run(
+                Arc::new(Metrics::new()),
+                Arc::new(DaemonState::new()),
+                cancel,
+            )
+            .await;
+
+        let methods: Vec<_> = captured
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| (request.method.clone(), request.path.clone()))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                (
+                    HttpMethod::POST,
+                    "/metrics/job/job/instance/h/palingenesis_instance/default".to_string()
+                ),
+                (
+                    HttpMethod::DELETE,
+                    "/metrics/job/job/instance/h/palingenesis_instance/default".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::config::paths::{PathError, Paths};
 use crate::config::schema::OtelConfig;
+use crate::telemetry::log_buffer::LogBuffer;
 use crate::telemetry::otel;
 use std::fs::File;
 use std::io::{self, Write};
@@ -17,6 +18,8 @@ pub struct TracingConfig {
     pub log_to_file: bool,
     pub log_to_stderr: bool,
     pub json_format: bool,
+    /// Also keep recent records in memory for `palingenesis logs`.
+    pub log_buffer: Option<LogBuffer>,
 }
 
 impl Default for TracingConfig {
@@ -26,6 +29,7 @@ impl Default for TracingConfig {
             log_to_file: false,
             log_to_stderr: true,
             json_format: false,
+            log_buffer: None,
         }
     }
 }
@@ -118,10 +122,16 @@ pub fn init_tracing(
     otel_config: Option<&OtelConfig>,
 ) -> Result<TracingGuard, TracingError> {
     let env_filter = resolve_env_filter(config);
+    let buffer_layer = config.log_buffer.as_ref().map(LogBuffer::layer);
 
     let file = if config.log_to_file {
-        let dir = Paths::ensure_state_dir()?;
-        let path = dir.join("daemon.log");
+        let path = Paths::log_file();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|source| TracingError::LogFileOpen {
+                path: path.clone(),
+                source,
+            })?;
+        }
         let file = File::options()
             .create(true)
             .append(true)
@@ -159,6 +169,7 @@ pub fn init_tracing(
         (true, Some(file_ref), otel_layer, otel_logs_layer) => {
             let file_writer = FileMakeWriter::new(Arc::clone(file_ref));
             let base = tracing_subscriber::registry()
+                .with(buffer_layer)
                 .with(otel_layer)
                 .with(otel_logs_layer)
                 .with(env_filter);
@@ -192,6 +203,7 @@ pub fn init_tracing(
         }
         (true, None, otel_layer, otel_logs_layer) => {
             let base = tracing_subscriber::registry()
+                .with(buffer_layer)
                 .with(otel_layer)
                 .with(otel_logs_layer)
                 .with(env_filter);
@@ -215,6 +227,7 @@ pub fn init_tracing(
         (false, Some(file_ref), otel_layer, otel_logs_layer) => {
             let file_writer = FileMakeWriter::new(Arc::clone(file_ref));
             let base = tracing_subscriber::registry()
+                .with(buffer_layer)
                 .with(otel_layer)
                 .with(otel_logs_layer)
                 .with(env_filter);
@@ -236,6 +249,7 @@ pub fn init_tracing(
             }
         }
         (false, None, otel_layer, otel_logs_layer) => tracing_subscriber::registry()
+            .with(buffer_layer)
             .with(otel_layer)
             .with(otel_logs_layer)
             .with(env_filter)
@@ -260,6 +274,7 @@ pub fn init_tracing(
                     .with_level(true)
                     .with_timer(tracing_subscriber::fmt::time::SystemTime);
                 tracing_subscriber::registry()
+                    .with(buffer_layer)
                     .with(env_filter)
                     .with(stderr_layer)
                     .with(file_layer)
@@ -276,6 +291,7 @@ pub fn init_tracing(
                     .with_level(true)
                     .with_timer(tracing_subscriber::fmt::time::SystemTime);
                 tracing_subscriber::registry()
+                    .with(buffer_layer)
                     .with(env_filter)
                     .with(stderr_layer)
                     .with(file_layer)
@@ -291,6 +307,7 @@ pub fn init_tracing(
                     .with_level(true)
                     .with_timer(tracing_subscriber::fmt::time::SystemTime);
                 tracing_subscriber::registry()
+                    .with(buffer_layer)
                     .with(env_filter)
                     .with(layer)
                     .set_default()
@@ -301,6 +318,7 @@ pub fn init_tracing(
                     .with_level(true)
                     .with_timer(tracing_subscriber::fmt::time::SystemTime);
                 tracing_subscriber::registry()
+                    .with(buffer_layer)
                     .with(env_filter)
                     .with(layer)
                     .set_default()
@@ -316,6 +334,7 @@ pub fn init_tracing(
                     .with_level(true)
                     .with_timer(tracing_subscriber::fmt::time::SystemTime);
                 tracing_subscriber::registry()
+                    .with(buffer_layer)
                     .with(env_filter)
                     .with(layer)
                     .set_default()
@@ -326,12 +345,14 @@ pub fn init_tracing(
                     .with_level(true)
                     .with_timer(tracing_subscriber::fmt::time::SystemTime);
                 tracing_subscriber::registry()
+                    .with(buffer_layer)
                     .with(env_filter)
                     .with(layer)
                     .set_default()
             }
         }
         (false, None) => tracing_subscriber::registry()
+            .with(buffer_layer)
             .with(env_filter)
             .set_default(),
     };
@@ -380,7 +401,7 @@ mod tests {
 
     #[test]
     fn env_filter_uses_rust_log_when_set() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         set_env_var("RUST_LOG", "warn");
         let config = TracingConfig::default();
         let filter = resolve_env_filter(&config);
@@ -390,7 +411,7 @@ mod tests {
 
     #[test]
     fn debug_level_overrides_rust_log() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         set_env_var("RUST_LOG", "error");
         let config = TracingConfig {
             level: Level::DEBUG,
@@ -403,7 +424,7 @@ mod tests {
 
     #[test]
     fn init_tracing_writes_json_log_entry() {
-        let _lock = ENV_LOCK.lock().unwrap();
+        let _lock = ENV_LOCK.blocking_lock();
         let temp = tempfile::tempdir().unwrap();
 
         set_env_var("PALINGENESIS_STATE", temp.path());
@@ -414,12 +435,18 @@ mod tests {
             log_to_file: true,
             log_to_stderr: false,
             json_format: true,
+            log_buffer: Some(LogBuffer::default()),
         };
 
         let guard = init_tracing(&config, None).unwrap();
         tracing::info!(test_field = 42, "telemetry test log");
         drop(guard);
 
+        let buffered = config.log_buffer.as_ref().unwrap().query(10, None);
+        assert!(buffered.records.iter().any(|record| {
+            record.message == "telemetry test log" && record.fields["test_field"] == "42"
+        }));
+
         let log_path = temp.path().join("daemon.log");
         let contents = std::fs::read_to_string(&log_path).unwrap();
         assert!(contents.contains("telemetry test log"));
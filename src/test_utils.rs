@@ -1,8 +1,38 @@
+/// Serializes tests that touch process environment variables.
+///
+/// A tokio mutex so async tests can hold it across awaits; sync tests use
+/// `blocking_lock`.
 #[cfg(test)]
-use std::sync::Mutex;
+pub(crate) static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Serializes tests that install a global tracing subscriber.
+#[cfg(all(test, feature = "http-api"))]
+pub(crate) static TRACING_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Wall clock that advances with tokio's (paused) clock.
+#[cfg(test)]
+pub(crate) struct TokioClock {
+    origin: tokio::time::Instant,
+    wall: std::time::SystemTime,
+}
 
 #[cfg(test)]
-pub(crate) static ENV_LOCK: Mutex<()> = Mutex::new(());
+impl TokioClock {
+    pub(crate) fn starting_at(wall: chrono::DateTime<chrono::Utc>) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Self {
+            origin: tokio::time::Instant::now(),
+            wall: wall.into(),
+        })
+    }
+}
 
 #[cfg(test)]
-pub(crate) static TRACING_LOCK: Mutex<()> = Mutex::new(());
+impl crate::daemon::suspend::Clock for TokioClock {
+    fn monotonic(&self) -> std::time::Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn wall(&self) -> std::time::SystemTime {
+        self.wall + self.origin.elapsed()
+    }
+}
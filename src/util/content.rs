@@ -0,0 +1,125 @@
+//! Bounds on session content kept past the operation that needed it.
+//!
+//! Classifier evidence, Next-step.md text, prompts and provider error
+//! messages grow with the session that produced them. The resume in
+//! progress uses them in full, but copies that outlive it (the event ring
+//! buffer, notification history) are cut with [`cap`], and
+//! [`RetainedContent`] keeps a running estimate of what each such buffer
+//! holds for the `palingenesis_content_bytes_retained` gauge.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::telemetry::Metrics;
+
+/// Default for `classifier.max_evidence_chars`.
+pub const DEFAULT_MAX_EVIDENCE_CHARS: usize = 512;
+
+/// Default for `daemon.max_retained_content_chars`.
+pub const DEFAULT_MAX_RETAINED_CHARS: usize = 2048;
+
+/// Appended to text that was cut.
+pub const CUT_MARKER: &str = " [...]";
+
+static RETAINED_CHARS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_RETAINED_CHARS);
+
+/// Longest string a long-lived buffer keeps (`daemon.max_retained_content_chars`).
+pub fn retained_chars() -> usize {
+    RETAINED_CHARS.load(Ordering::Relaxed)
+}
+
+/// Apply `daemon.max_retained_content_chars` to entries buffered from now on.
+pub fn set_retained_chars(max_chars: usize) {
+    RETAINED_CHARS.store(max_chars, Ordering::Relaxed);
+}
+
+/// `text` cut to at most `max_chars` characters, marked when cut.
+pub fn cap(text: &str, max_chars: usize) -> Cow<'_, str> {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => Cow::Owned(format!("{}{CUT_MARKER}", &text[..end])),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// [`cap`] an owned string without reallocating when it fits.
+pub fn cap_in_place(text: &mut String, max_chars: usize) {
+    if let Some((end, _)) = text.char_indices().nth(max_chars) {
+        text.truncate(end);
+        text.push_str(CUT_MARKER);
+    }
+}
+
+/// Size estimate for a bounded buffer of content-bearing entries.
+///
+/// Mirrors the buffer's eviction (oldest first once `capacity` entries are
+/// held) without holding the entries themselves.
+#[derive(Debug)]
+pub struct RetainedContent {
+    store: &'static str,
+    capacity: usize,
+    sizes: Mutex<VecDeque<usize>>,
+    total: AtomicUsize,
+}
+
+impl RetainedContent {
+    /// `store` labels the gauge, e.g. `events`.
+    pub fn new(store: &'static str, capacity: usize) -> Self {
+        Self {
+            store,
+            capacity: capacity.max(1),
+            sizes: Mutex::new(VecDeque::new()),
+            total: AtomicUsize::new(0),
+        }
+    }
+
+    /// Account for a new entry of `bytes`, evicting the oldest past capacity.
+    pub fn push(&self, bytes: usize) {
+        let total = {
+            let mut sizes = match self.sizes.lock() {
+                Ok(sizes) => sizes,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let mut total = self.total.load(Ordering::Relaxed) + bytes;
+            sizes.push_back(bytes);
+            while sizes.len() > self.capacity {
+                total -= sizes.pop_front().unwrap_or(0);
+            }
+            self.total.store(total, Ordering::Relaxed);
+            total
+        };
+        if let Some(metrics) = Metrics::global() {
+            metrics.set_content_bytes_retained(self.store, total);
+        }
+    }
+
+    /// Estimated bytes currently held.
+    pub fn bytes(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cap_marks_cut_text_on_char_boundaries() {
+        assert_eq!(cap("short", 10), "short");
+        assert_eq!(cap("ééééé", 2), "éé [...]");
+
+        let mut owned = "abcdef".to_string();
+        cap_in_place(&mut owned, 3);
+        assert_eq!(owned, "abc [...]");
+    }
+
+    #[test]
+    fn retained_content_evicts_oldest() {
+        let retained = RetainedContent::new("test", 2);
+        retained.push(10);
+        retained.push(20);
+        retained.push(30);
+        assert_eq!(retained.bytes(), 50);
+    }
+}
@@ -0,0 +1,203 @@
+//! Human-readable durations.
+//!
+//! Every user-facing elapsed time (status output, bot replies, notifications,
+//! log fields, `/health`) goes through these helpers so the same span always
+//! reads the same way. Negative spans, e.g. from a clock that stepped
+//! backwards, are clamped to zero rather than rendered with a minus sign.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+const UNITS: [(u64, &str); 4] = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")];
+
+/// The two most significant units, e.g. `1h 1m`, `3d 2h`, `45s`.
+///
+/// Non-zero spans under a second render as `<1s`.
+pub fn format_compact(duration: Duration) -> String {
+    if duration.is_zero() {
+        return "0s".to_string();
+    }
+    if duration.as_secs() == 0 {
+        return "<1s".to_string();
+    }
+    let parts = parts(duration.as_secs());
+    let first = parts
+        .iter()
+        .position(|(value, _)| *value > 0)
+        .unwrap_or(parts.len() - 1);
+    parts[first..]
+        .iter()
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Every unit from the most significant down to seconds, e.g. `1h 1m 1s`,
+/// `1h 0m 0s`.
+///
+/// Spans under a second render in milliseconds, e.g. `350ms`.
+pub fn format_precise(duration: Duration) -> String {
+    if duration.as_secs() == 0 {
+        return format!("{}ms", duration.subsec_millis());
+    }
+    let parts = parts(duration.as_secs());
+    let first = parts
+        .iter()
+        .position(|(value, _)| *value > 0)
+        .unwrap_or(parts.len() - 1);
+    parts[first..]
+        .iter()
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `at` relative to now, e.g. `3 minutes ago` or `in 2 hours`.
+pub fn format_relative(at: DateTime<Utc>) -> String {
+    format_relative_to(at, Utc::now())
+}
+
+/// `at` relative to `now`; spans under a second read `just now`.
+pub fn format_relative_to(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let (span, future) = if at > now {
+        (between(now, at), true)
+    } else {
+        (between(at, now), false)
+    };
+    let secs = span.as_secs();
+    if secs == 0 {
+        return "just now".to_string();
+    }
+    let (size, name) = [
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ]
+    .into_iter()
+    .find(|(size, _)| secs >= *size)
+    .unwrap_or((1, "second"));
+    let count = secs / size;
+    let plural = if count == 1 { "" } else { "s" };
+    if future {
+        format!("in {count} {name}{plural}")
+    } else {
+        format!("{count} {name}{plural} ago")
+    }
+}
+
+/// A span written as a whole number and a unit, e.g. `30s`, `15m`, `2h`,
+/// `1d` (or `sec`, `min`, `hours`, `days`, ...).
+pub fn parse(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| c.is_alphabetic())?;
+    let (count, unit) = value.split_at(split);
+    let count: u64 = count.trim().parse().ok()?;
+    let size = match unit.to_ascii_lowercase().as_str() {
+        "s" | "sec" | "second" | "seconds" => 1,
+        "m" | "min" | "minute" | "minutes" => 60,
+        "h" | "hour" | "hours" => 3_600,
+        "d" | "day" | "days" => 86_400,
+        _ => return None,
+    };
+    count.checked_mul(size).map(Duration::from_secs)
+}
+
+/// Time from `start` to `end`, zero when `end` is earlier.
+pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Duration {
+    (end - start).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// Seconds as a [`Duration`], zero for negative, NaN or infinite input.
+pub fn from_secs_f64(secs: f64) -> Duration {
+    Duration::try_from_secs_f64(secs).unwrap_or(Duration::ZERO)
+}
+
+fn parts(total: u64) -> Vec<(u64, &'static str)> {
+    let mut rest = total;
+    UNITS
+        .iter()
+        .map(|&(size, unit)| {
+            let value = rest / size;
+            rest %= size;
+            (value, unit)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(value: u64) -> Duration {
+        Duration::from_secs(value)
+    }
+
+    #[test]
+    fn compact_keeps_two_most_significant_units() {
+        assert_eq!(format_compact(secs(3_661)), "1h 1m");
+        assert_eq!(format_compact(secs(3_600)), "1h");
+        assert_eq!(format_compact(secs(28_320)), "7h 52m");
+        assert_eq!(format_compact(secs(3 * 86_400 + 2 * 3_600 + 5)), "3d 2h");
+        assert_eq!(format_compact(secs(90)), "1m 30s");
+        assert_eq!(format_compact(secs(45)), "45s");
+        assert_eq!(format_compact(Duration::ZERO), "0s");
+        assert_eq!(format_compact(Duration::from_millis(350)), "<1s");
+    }
+
+    #[test]
+    fn precise_lists_every_unit_down_to_seconds() {
+        assert_eq!(format_precise(secs(3_661)), "1h 1m 1s");
+        assert_eq!(format_precise(secs(3_600)), "1h 0m 0s");
+        assert_eq!(format_precise(secs(86_401)), "1d 0h 0m 1s");
+        assert_eq!(format_precise(secs(59)), "59s");
+        assert_eq!(format_precise(Duration::from_millis(350)), "350ms");
+        assert_eq!(format_precise(Duration::ZERO), "0ms");
+    }
+
+    #[test]
+    fn relative_reads_past_and_future() {
+        let now = Utc::now();
+        let minutes = chrono::Duration::minutes(3);
+        assert_eq!(format_relative_to(now - minutes, now), "3 minutes ago");
+        assert_eq!(
+            format_relative_to(now + chrono::Duration::hours(2), now),
+            "in 2 hours"
+        );
+        assert_eq!(
+            format_relative_to(now - chrono::Duration::seconds(1), now),
+            "1 second ago"
+        );
+        assert_eq!(
+            format_relative_to(now - chrono::Duration::milliseconds(200), now),
+            "just now"
+        );
+    }
+
+    #[test]
+    fn parses_a_count_and_unit() {
+        assert_eq!(parse("30s"), Some(secs(30)));
+        assert_eq!(parse("15m"), Some(secs(900)));
+        assert_eq!(parse(" 2 hours "), Some(secs(7_200)));
+        assert_eq!(parse("1D"), Some(secs(86_400)));
+        assert_eq!(parse("90"), None);
+        assert_eq!(parse("2w"), None);
+        assert_eq!(parse("-1h"), None);
+    }
+
+    #[test]
+    fn negative_spans_clamp_to_zero() {
+        let now = Utc::now();
+        assert_eq!(
+            between(now, now - chrono::Duration::seconds(5)),
+            Duration::ZERO
+        );
+        assert_eq!(from_secs_f64(-3.0), Duration::ZERO);
+        assert_eq!(from_secs_f64(f64::NAN), Duration::ZERO);
+        assert_eq!(from_secs_f64(f64::INFINITY), Duration::ZERO);
+        assert_eq!(from_secs_f64(1.5), Duration::from_millis(1_500));
+    }
+}
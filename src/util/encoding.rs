@@ -0,0 +1,308 @@
+//! Text decoding for session files, transcripts and Next-step.md.
+//!
+//! Editors on other platforms save these files with a UTF-8 byte order mark,
+//! as UTF-16 or with CRLF line endings, and any of these hides the `---`
+//! frontmatter delimiter from a plain UTF-8 reader. The frontmatter parser,
+//! the classifier tail reader and the Next-step reader all decode through
+//! [`decode`], so they see the same text. Files that needed a BOM stripped or
+//! were transcoded are recorded in [`EncodingFixups`] so the user is warned
+//! once per file.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use tracing::warn;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Encoding a file was saved in, as told by its byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl SourceEncoding {
+    /// Detect the encoding from the first bytes of a file, returning it
+    /// with the length of its byte order mark.
+    pub fn detect(bytes: &[u8]) -> (Self, usize) {
+        if bytes.starts_with(UTF8_BOM) {
+            (Self::Utf8Bom, UTF8_BOM.len())
+        } else if bytes.starts_with(UTF16LE_BOM) {
+            (Self::Utf16Le, UTF16LE_BOM.len())
+        } else if bytes.starts_with(UTF16BE_BOM) {
+            (Self::Utf16Be, UTF16BE_BOM.len())
+        } else {
+            (Self::Utf8, 0)
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf8Bom => "UTF-8 with BOM",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+        }
+    }
+
+    pub fn is_utf16(&self) -> bool {
+        matches!(self, Self::Utf16Le | Self::Utf16Be)
+    }
+
+    /// Whether reading the file needed more than plain UTF-8 decoding.
+    pub fn needs_fixup(&self) -> bool {
+        *self != Self::Utf8
+    }
+}
+
+/// A file's text with line endings normalized to `\n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: SourceEncoding,
+    /// The file used CRLF line endings.
+    pub crlf: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EncodingError {
+    #[error(
+        "file starts with a {encoding} byte order mark but is not valid {encoding}; re-save it as UTF-8"
+    )]
+    InvalidUtf16 { encoding: &'static str },
+}
+
+impl From<EncodingError> for io::Error {
+    fn from(err: EncodingError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Decode a whole file.
+///
+/// UTF-8 (with or without BOM) never fails; invalid sequences become
+/// U+FFFD. UTF-16 is transcoded and fails only when malformed.
+pub fn decode(bytes: &[u8]) -> Result<DecodedText, EncodingError> {
+    decode_inner(bytes, false)
+}
+
+/// Decode the first bytes of a file, which may end mid-character.
+pub fn decode_prefix(bytes: &[u8]) -> Result<DecodedText, EncodingError> {
+    decode_inner(bytes, true)
+}
+
+fn decode_inner(bytes: &[u8], partial: bool) -> Result<DecodedText, EncodingError> {
+    let (encoding, bom_len) = SourceEncoding::detect(bytes);
+    let body = &bytes[bom_len..];
+    let text = if encoding.is_utf16() {
+        decode_utf16(body, encoding, partial)?
+    } else {
+        let body = match std::str::from_utf8(body) {
+            Err(err) if partial && err.error_len().is_none() => &body[..err.valid_up_to()],
+            _ => body,
+        };
+        String::from_utf8_lossy(body).into_owned()
+    };
+    let crlf = text.contains("\r\n");
+    let text = if crlf {
+        text.replace("\r\n", "\n")
+    } else {
+        text
+    };
+    Ok(DecodedText {
+        text,
+        encoding,
+        crlf,
+    })
+}
+
+fn decode_utf16(
+    body: &[u8],
+    encoding: SourceEncoding,
+    partial: bool,
+) -> Result<String, EncodingError> {
+    let invalid = || EncodingError::InvalidUtf16 {
+        encoding: encoding.label(),
+    };
+    if body.len() % 2 != 0 && !partial {
+        return Err(invalid());
+    }
+    let mut units: Vec<u16> = body
+        .chunks_exact(2)
+        .map(|pair| match encoding {
+            SourceEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+            _ => u16::from_le_bytes([pair[0], pair[1]]),
+        })
+        .collect();
+    if partial
+        && units
+            .last()
+            .is_some_and(|unit| (0xD800..0xDC00).contains(unit))
+    {
+        units.pop();
+    }
+    String::from_utf16(&units).map_err(|_| invalid())
+}
+
+/// Read and decode `path`, recording any fixups it needed.
+pub fn read_text(path: &Path) -> io::Result<DecodedText> {
+    let decoded = decode(&std::fs::read(path)?)?;
+    EncodingFixups::shared().record(path, decoded.encoding);
+    Ok(decoded)
+}
+
+/// A file that had to be transcoded or have its BOM stripped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingFixup {
+    pub path: PathBuf,
+    pub encoding: SourceEncoding,
+}
+
+#[derive(Debug, Default)]
+struct FixupState {
+    seen: HashSet<PathBuf>,
+    pending: Vec<EncodingFixup>,
+}
+
+/// Files that needed encoding fixups, each reported once per daemon run.
+#[derive(Debug, Default)]
+pub struct EncodingFixups {
+    state: Mutex<FixupState>,
+}
+
+impl EncodingFixups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide registry used by the session readers.
+    pub fn shared() -> &'static Self {
+        static SHARED: OnceLock<EncodingFixups> = OnceLock::new();
+        SHARED.get_or_init(Self::new)
+    }
+
+    /// Note that `path` was read as `encoding`; the first fixup seen for a
+    /// file is logged and queued for [`Self::take_pending`].
+    pub fn record(&self, path: &Path, encoding: SourceEncoding) {
+        if !encoding.needs_fixup() {
+            return;
+        }
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if !state.seen.insert(path.to_path_buf()) {
+            return;
+        }
+        warn!(
+            path = %path.display(),
+            encoding = encoding.label(),
+            "File is not plain UTF-8; it was read anyway, but save it as UTF-8 without a byte order mark"
+        );
+        state.pending.push(EncodingFixup {
+            path: path.to_path_buf(),
+            encoding,
+        });
+    }
+
+    /// Fixups not yet reported.
+    pub fn take_pending(&self) -> Vec<EncodingFixup> {
+        self.state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.pending))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = if big_endian {
+            UTF16BE_BOM.to_vec()
+        } else {
+            UTF16LE_BOM.to_vec()
+        };
+        for unit in text.encode_utf16() {
+            let pair = if big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            };
+            bytes.extend_from_slice(&pair);
+        }
+        bytes
+    }
+
+    #[test]
+    fn strips_utf8_bom_and_crlf() {
+        let decoded = decode(b"\xEF\xBB\xBF---\r\nphase: x\r\n---\r\n").unwrap();
+        assert_eq!(decoded.text, "---\nphase: x\n---\n");
+        assert_eq!(decoded.encoding, SourceEncoding::Utf8Bom);
+        assert!(decoded.crlf);
+    }
+
+    #[test]
+    fn transcodes_utf16_in_both_byte_orders() {
+        for big_endian in [false, true] {
+            let decoded = decode(&utf16("---\r\nstep: 3 ✓\r\n", big_endian)).unwrap();
+            assert_eq!(decoded.text, "---\nstep: 3 ✓\n");
+            assert!(decoded.encoding.is_utf16());
+        }
+    }
+
+    #[test]
+    fn malformed_utf16_names_the_encoding() {
+        let mut bytes = utf16("abc", false);
+        bytes.push(0x41);
+        let err = decode(&bytes).unwrap_err();
+        assert!(err.to_string().contains("UTF-16LE"));
+
+        let unpaired = [UTF16LE_BOM, &[0x00, 0xDC]].concat();
+        assert!(decode(&unpaired).is_err());
+    }
+
+    #[test]
+    fn prefixes_may_end_mid_character() {
+        let bytes = utf16("a😀", false);
+        let decoded = decode_prefix(&bytes[..bytes.len() - 2]).unwrap();
+        assert_eq!(decoded.text, "a");
+        assert_eq!(
+            decode_prefix("é".as_bytes()[..1].as_ref()).unwrap().text,
+            ""
+        );
+    }
+
+    #[test]
+    fn plain_utf8_is_untouched_and_invalid_bytes_are_replaced() {
+        let decoded = decode(b"step: 1\n").unwrap();
+        assert_eq!(decoded.encoding, SourceEncoding::Utf8);
+        assert!(!decoded.crlf);
+        assert_eq!(decode(b"a\xFFb").unwrap().text, "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn fixups_are_reported_once_per_file() {
+        let fixups = EncodingFixups::new();
+        let path = Path::new("/sessions/a.md");
+        fixups.record(path, SourceEncoding::Utf8);
+        fixups.record(path, SourceEncoding::Utf8Bom);
+        fixups.record(path, SourceEncoding::Utf8Bom);
+
+        assert_eq!(
+            fixups.take_pending(),
+            vec![EncodingFixup {
+                path: path.to_path_buf(),
+                encoding: SourceEncoding::Utf8Bom,
+            }]
+        );
+        assert!(fixups.take_pending().is_empty());
+    }
+}
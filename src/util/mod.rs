@@ -0,0 +1,9 @@
+//! Small helpers shared across the CLI, daemon and notification code.
+
+pub mod content;
+pub mod duration;
+pub mod encoding;
+pub mod host;
+pub mod path_display;
+pub mod process;
+pub mod size;
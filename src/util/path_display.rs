@@ -0,0 +1,334 @@
+//! How file paths are shown to people (`daemon.path_display`).
+//!
+//! Notifications and CLI output name session files by their full path,
+//! which exposes usernames and client directory names wherever the output
+//! lands (a shared Slack channel, a screenshot). [`PathDisplay`] renders a
+//! path for display only: state, history and the audit log keep the full
+//! path, and JSON output keeps it too unless `daemon.redact_json` is set.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::config::schema::{Config, PathDisplayMode};
+use crate::resume::exclusions::glob_match;
+
+/// Replaces each path component matching `daemon.redact_patterns`.
+pub const REDACTED: &str = "[redacted]";
+
+/// Path rendering settings.
+#[derive(Debug, Clone, Default)]
+pub struct PathDisplay {
+    mode: PathDisplayMode,
+    /// Directories `relative` mode strips, tried before the git workspace.
+    roots: Vec<PathBuf>,
+    home: Option<PathBuf>,
+    redact_patterns: Vec<Vec<char>>,
+    redact_json: bool,
+}
+
+impl PathDisplay {
+    pub fn new(mode: PathDisplayMode) -> Self {
+        Self {
+            mode,
+            home: dirs::home_dir(),
+            ..Self::default()
+        }
+    }
+
+    /// Settings from `[daemon]`, relative to `monitoring.session_dir`.
+    pub fn from_config(config: &Config) -> Self {
+        let display = Self::new(config.daemon.path_display)
+            .with_redact_patterns(&config.daemon.redact_patterns)
+            .with_redact_json(config.daemon.redact_json);
+        let session_dir = display.expand_home(&config.monitoring.session_dir);
+        display.with_roots(vec![session_dir])
+    }
+
+    pub fn with_mode(mut self, mode: PathDisplayMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_roots(mut self, roots: Vec<PathBuf>) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// Home directory shown as `~` (default: the current user's).
+    pub fn with_home(mut self, home: Option<PathBuf>) -> Self {
+        self.home = home;
+        self
+    }
+
+    pub fn with_redact_patterns(mut self, patterns: &[String]) -> Self {
+        self.redact_patterns = patterns
+            .iter()
+            .map(|pattern| pattern.chars().collect())
+            .collect();
+        self
+    }
+
+    pub fn with_redact_json(mut self, redact_json: bool) -> Self {
+        self.redact_json = redact_json;
+        self
+    }
+
+    pub fn mode(&self) -> PathDisplayMode {
+        self.mode
+    }
+
+    /// These settings with a channel's `path_display` override applied.
+    pub fn overridden(&self, mode: Option<PathDisplayMode>) -> Self {
+        match mode {
+            Some(mode) => self.clone().with_mode(mode),
+            None => self.clone(),
+        }
+    }
+
+    /// Settings for JSON output: full paths unless `redact_json` is set.
+    pub fn for_json(&self) -> Self {
+        if self.redact_json {
+            self.clone()
+        } else {
+            self.clone().with_mode(PathDisplayMode::Absolute)
+        }
+    }
+
+    /// `path` ready for `{}` formatting.
+    pub fn path<'a>(&'a self, path: &'a Path) -> DisplayPath<'a> {
+        DisplayPath {
+            display: self,
+            path,
+        }
+    }
+
+    /// Replace `path` with its rendering, for output built from owned records.
+    pub fn apply(&self, path: &mut PathBuf) {
+        if self.mode != PathDisplayMode::Absolute {
+            *path = PathBuf::from(self.render(path));
+        }
+    }
+
+    pub fn render(&self, path: &Path) -> String {
+        match self.mode {
+            PathDisplayMode::Absolute => path.display().to_string(),
+            PathDisplayMode::Basename => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+            PathDisplayMode::Relative => self.relative(path),
+            PathDisplayMode::Redacted => self.redacted(path),
+        }
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        let root = self
+            .roots
+            .iter()
+            .find(|root| path.starts_with(root) && path != root.as_path())
+            .cloned()
+            .or_else(|| workspace_root(path));
+        match root
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+        {
+            Some(relative) => relative.display().to_string(),
+            None => self.tilde(path),
+        }
+    }
+
+    fn redacted(&self, path: &Path) -> String {
+        let (prefix, rest) = match self.home_relative(path) {
+            Some(rest) => (Some("~".to_string()), rest),
+            None => (None, path),
+        };
+        let mut parts: Vec<String> = prefix.into_iter().collect();
+        let mut absolute = false;
+        for component in rest.components() {
+            match component {
+                Component::RootDir => absolute = true,
+                Component::Normal(name) => {
+                    let name = name.to_string_lossy();
+                    let chars: Vec<char> = name.chars().collect();
+                    if self
+                        .redact_patterns
+                        .iter()
+                        .any(|pattern| glob_match(pattern, &chars))
+                    {
+                        parts.push(REDACTED.to_string());
+                    } else {
+                        parts.push(name.into_owned());
+                    }
+                }
+                other => parts.push(other.as_os_str().to_string_lossy().into_owned()),
+            }
+        }
+        let joined = parts.join("/");
+        if absolute {
+            format!("/{joined}")
+        } else {
+            joined
+        }
+    }
+
+    fn tilde(&self, path: &Path) -> String {
+        match self.home_relative(path) {
+            Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+            Some(rest) => format!("~/{}", rest.display()),
+            None => path.display().to_string(),
+        }
+    }
+
+    fn home_relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.strip_prefix(self.home.as_deref()?).ok()
+    }
+
+    fn expand_home(&self, path: &Path) -> PathBuf {
+        match (path.strip_prefix("~"), &self.home) {
+            (Ok(rest), Some(home)) => home.join(rest),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+/// A path formatted by [`PathDisplay`]; see [`PathDisplay::path`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayPath<'a> {
+    display: &'a PathDisplay,
+    path: &'a Path,
+}
+
+impl fmt::Display for DisplayPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display.render(self.path))
+    }
+}
+
+/// Nearest ancestor of `path` holding a `.git` entry.
+fn workspace_root(path: &Path) -> Option<PathBuf> {
+    path.parent()?
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+fn global_slot() -> &'static RwLock<Arc<PathDisplay>> {
+    static GLOBAL: OnceLock<RwLock<Arc<PathDisplay>>> = OnceLock::new();
+    GLOBAL.get_or_init(|| RwLock::new(Arc::new(PathDisplay::default())))
+}
+
+/// Apply `[daemon]` path settings to output rendered from now on.
+pub fn set_global(display: PathDisplay) {
+    match global_slot().write() {
+        Ok(mut slot) => *slot = Arc::new(display),
+        Err(poisoned) => *poisoned.into_inner() = Arc::new(display),
+    }
+}
+
+/// Settings of the running daemon (full paths until [`set_global`]).
+pub fn global() -> Arc<PathDisplay> {
+    match global_slot().read() {
+        Ok(slot) => Arc::clone(&slot),
+        Err(poisoned) => Arc::clone(&poisoned.into_inner()),
+    }
+}
+
+/// Settings from the config file on disk, for CLI output.
+pub fn configured() -> PathDisplay {
+    crate::daemon::state::load_config_from_disk()
+        .map(|config| PathDisplay::from_config(&config))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(mode: PathDisplayMode) -> PathDisplay {
+        PathDisplay::new(mode)
+            .with_home(Some(PathBuf::from("/home/alice")))
+            .with_roots(vec![PathBuf::from("/home/alice/.opencode")])
+            .with_redact_patterns(&["client-*".to_string()])
+    }
+
+    const SESSION: &str = "/home/alice/work/client-acme/session.md";
+
+    #[test]
+    fn absolute_keeps_the_path() {
+        let display = display(PathDisplayMode::Absolute);
+        assert_eq!(display.render(Path::new(SESSION)), SESSION);
+        assert_eq!(PathDisplay::default().render(Path::new(SESSION)), SESSION);
+    }
+
+    #[test]
+    fn basename_keeps_the_file_name() {
+        let display = display(PathDisplayMode::Basename);
+        assert_eq!(display.render(Path::new(SESSION)), "session.md");
+        assert_eq!(display.render(Path::new("/")), "/");
+    }
+
+    #[test]
+    fn relative_strips_the_session_dir_then_the_workspace() {
+        let display = display(PathDisplayMode::Relative);
+        assert_eq!(
+            display.render(Path::new("/home/alice/.opencode/ses_1/session.md")),
+            "ses_1/session.md"
+        );
+        assert_eq!(
+            display.render(Path::new(SESSION)),
+            "~/work/client-acme/session.md"
+        );
+        assert_eq!(
+            display.render(Path::new("/srv/session.md")),
+            "/srv/session.md"
+        );
+
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        let session = repo.join("notes").join("session.md");
+        assert_eq!(display.render(&session), "notes/session.md");
+    }
+
+    #[test]
+    fn redacted_hides_home_and_matching_components() {
+        let display = display(PathDisplayMode::Redacted);
+        assert_eq!(
+            display.render(Path::new(SESSION)),
+            "~/work/[redacted]/session.md"
+        );
+        assert_eq!(
+            display.render(Path::new("/srv/client-beta/session.md")),
+            "/srv/[redacted]/session.md"
+        );
+        assert_eq!(format!("{}", display.path(Path::new("/home/alice"))), "~");
+    }
+
+    #[test]
+    fn json_and_channel_overrides() {
+        let display = display(PathDisplayMode::Basename);
+        assert_eq!(display.for_json().mode(), PathDisplayMode::Absolute);
+        assert_eq!(
+            display.clone().with_redact_json(true).for_json().mode(),
+            PathDisplayMode::Basename
+        );
+        assert_eq!(
+            display.overridden(Some(PathDisplayMode::Redacted)).mode(),
+            PathDisplayMode::Redacted
+        );
+        assert_eq!(display.overridden(None).mode(), PathDisplayMode::Basename);
+    }
+
+    #[test]
+    fn from_config_expands_the_session_dir() {
+        let mut config = Config::default();
+        config.daemon.path_display = PathDisplayMode::Relative;
+        config.monitoring.session_dir = PathBuf::from("/data/sessions");
+        let display = PathDisplay::from_config(&config);
+        assert_eq!(
+            display.render(Path::new("/data/sessions/a/session.md")),
+            "a/session.md"
+        );
+    }
+}
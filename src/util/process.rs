@@ -0,0 +1,421 @@
+//! Child processes that take their descendants with them.
+//!
+//! [`ManagedChild`] spawns a command as the leader of a new process group on
+//! Unix, so stopping it also reaches what it started in turn (opencode's
+//! node workers, a hook's ssh). Stopping sends SIGTERM to the group, waits
+//! for a grace period and SIGKILLs whatever is left. Elsewhere only the child
+//! itself is killed.
+//!
+//! A child is stopped when its timeout expires, when it is dropped before it
+//! finished, and by [`terminate_all`] when the daemon shuts down. A child
+//! that exits on its own leaves anything it detached alone.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::process::Output;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Time a process group gets to exit after SIGTERM before it is SIGKILLed.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(5);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How a managed process tree was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    /// Everything exited within the grace period after SIGTERM.
+    Terminated,
+    /// Something outlived the grace period and was SIGKILLed.
+    Killed,
+}
+
+impl Termination {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Termination::Terminated => "terminated",
+            Termination::Killed => "killed",
+        }
+    }
+
+    /// Whether stopping had to escalate to SIGKILL.
+    pub fn escalated(self) -> bool {
+        self == Termination::Killed
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Output of a managed child, and how it was stopped if it had to be.
+#[derive(Debug)]
+pub struct ManagedOutput {
+    pub output: Output,
+    /// The timeout expired before the child exited.
+    pub timed_out: bool,
+    /// Set when the tree was stopped by a timeout or by [`terminate_all`].
+    pub stopped: Option<Termination>,
+}
+
+/// A process group started by [`ManagedChild::spawn`] and not yet finished.
+#[derive(Debug)]
+struct Group {
+    pid: u32,
+    stopped: Mutex<Option<Termination>>,
+}
+
+impl Group {
+    fn stopped(&self) -> Option<Termination> {
+        *self.stopped.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Claim the group for stopping; `false` if it already was claimed.
+    fn mark_stopping(&self) -> bool {
+        let mut stopped = self.stopped.lock().unwrap_or_else(|err| err.into_inner());
+        if stopped.is_some() {
+            return false;
+        }
+        *stopped = Some(Termination::Terminated);
+        true
+    }
+
+    fn set_stopped(&self, termination: Termination) {
+        *self.stopped.lock().unwrap_or_else(|err| err.into_inner()) = Some(termination);
+    }
+}
+
+static LIVE: LazyLock<Mutex<HashMap<u32, Arc<Group>>>> = LazyLock::new(Mutex::default);
+
+fn live() -> std::sync::MutexGuard<'static, HashMap<u32, Arc<Group>>> {
+    LIVE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A spawned command whose whole process tree is stopped together.
+#[derive(Debug)]
+pub struct ManagedChild {
+    child: Child,
+    group: Option<Arc<Group>>,
+    grace: Duration,
+    finished: bool,
+}
+
+impl ManagedChild {
+    /// Spawn `command` in a new process group; it is killed if dropped.
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        #[cfg(unix)]
+        command.process_group(0);
+        command.kill_on_drop(true);
+        let child = command.spawn()?;
+        let group = child.id().map(|pid| {
+            let group = Arc::new(Group {
+                pid,
+                stopped: Mutex::new(None),
+            });
+            live().insert(pid, Arc::clone(&group));
+            group
+        });
+        Ok(Self {
+            child,
+            group,
+            grace: DEFAULT_GRACE,
+            finished: false,
+        })
+    }
+
+    /// Time between SIGTERM and SIGKILL when stopping the tree.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    pub fn id(&self) -> Option<u32> {
+        self.group.as_ref().map(|group| group.pid)
+    }
+
+    /// The child's stdin, when it was piped.
+    pub fn take_stdin(&mut self) -> Option<ChildStdin> {
+        self.child.stdin.take()
+    }
+
+    /// Wait for the child, collecting piped stdout and stderr. When `timeout`
+    /// expires first, the tree is stopped and its partial output returned.
+    pub async fn wait_with_output(
+        mut self,
+        timeout: Option<Duration>,
+    ) -> io::Result<ManagedOutput> {
+        let stdout = self.child.stdout.take().map(read_all);
+        let stderr = self.child.stderr.take().map(read_all);
+        let waited = match timeout {
+            Some(limit) => tokio::time::timeout(limit, self.child.wait()).await.ok(),
+            None => Some(self.child.wait().await),
+        };
+        let timed_out = waited.is_none();
+        let status = match waited {
+            Some(status) => status?,
+            None => {
+                self.stop().await?;
+                self.child.wait().await?
+            }
+        };
+        self.finish();
+
+        let stopped = self.group.as_ref().and_then(|group| group.stopped());
+        // Something that left the group may still hold the pipes open.
+        let drain = (timed_out || stopped.is_some()).then_some(self.grace);
+        Ok(ManagedOutput {
+            output: Output {
+                status,
+                stdout: collect(stdout, drain).await,
+                stderr: collect(stderr, drain).await,
+            },
+            timed_out,
+            stopped,
+        })
+    }
+
+    /// Stop the tree: SIGTERM, then SIGKILL after the grace period.
+    pub async fn stop(&mut self) -> io::Result<Termination> {
+        let Some(group) = self.group.clone() else {
+            // Spawned without a PID: it had already exited.
+            return Ok(Termination::Terminated);
+        };
+        if !group.mark_stopping() {
+            // Already being stopped by `terminate_all`; wait it out.
+            self.child.wait().await?;
+            return Ok(group.stopped().unwrap_or(Termination::Terminated));
+        }
+        #[cfg(unix)]
+        let termination = stop_group(&group, self.grace, Some(&mut self.child)).await;
+        #[cfg(not(unix))]
+        let termination = {
+            group.set_stopped(Termination::Killed);
+            self.child.start_kill()?;
+            self.child.wait().await?;
+            Termination::Killed
+        };
+        Ok(termination)
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        if let Some(group) = &self.group {
+            live().remove(&group.pid);
+        }
+    }
+}
+
+impl Drop for ManagedChild {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finish();
+        // Dropped mid-run, e.g. a cancelled resume: no time for a grace period.
+        #[cfg(unix)]
+        if let (Some(group), Ok(None)) = (&self.group, self.child.try_wait()) {
+            let _ = signal_group(group.pid, Some(nix::sys::signal::Signal::SIGKILL));
+        }
+    }
+}
+
+/// Run `command` as a [`ManagedChild`] and wait for its output.
+pub async fn output(command: &mut Command, timeout: Option<Duration>) -> io::Result<ManagedOutput> {
+    ManagedChild::spawn(command)?
+        .wait_with_output(timeout)
+        .await
+}
+
+/// Stop every running managed process tree, for daemon shutdown.
+///
+/// Returns how each was stopped. Trees already being stopped are left to
+/// their owner. Off Unix, children are only killed when their owner drops
+/// them.
+pub async fn terminate_all(grace: Duration) -> Vec<Termination> {
+    let groups: Vec<Arc<Group>> = live()
+        .values()
+        .filter(|group| group.mark_stopping())
+        .cloned()
+        .collect();
+    let mut stops = tokio::task::JoinSet::new();
+    for group in groups {
+        stops.spawn(async move {
+            #[cfg(unix)]
+            let termination = stop_group(&group, grace, None).await;
+            #[cfg(not(unix))]
+            let termination = {
+                let _ = (group, grace);
+                Termination::Terminated
+            };
+            termination
+        });
+    }
+    stops.join_all().await
+}
+
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: Option<nix::sys::signal::Signal>) -> nix::Result<()> {
+    let pgid = i32::try_from(pid).map_err(|_| nix::errno::Errno::EINVAL)?;
+    nix::sys::signal::killpg(nix::unistd::Pid::from_raw(pgid), signal)
+}
+
+/// Whether any process of the group led by `pid` is still running.
+///
+/// On Linux, zombies are not counted: a PID 1 that never reaps (common in
+/// containers) would otherwise keep every group alive until the SIGKILL.
+#[cfg(unix)]
+fn group_alive(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        return entries.flatten().any(|entry| {
+            let is_pid = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.bytes().all(|byte| byte.is_ascii_digit()));
+            is_pid
+                && std::fs::read_to_string(entry.path().join("stat"))
+                    .is_ok_and(|stat| running_in_group(&stat, pid))
+        });
+    }
+    signal_group(pid, None).is_ok()
+}
+
+/// Whether a `/proc/<pid>/stat` line is a live member of group `pgid`.
+#[cfg(target_os = "linux")]
+fn running_in_group(stat: &str, pgid: u32) -> bool {
+    // The command name may contain spaces; the fields after it are
+    // `state ppid pgrp ...`.
+    let Some((_, rest)) = stat.rsplit_once(')') else {
+        return false;
+    };
+    let mut fields = rest.split_whitespace();
+    let state = fields.next();
+    let pgrp = fields.nth(1).and_then(|pgrp| pgrp.parse::<u32>().ok());
+    state != Some("Z") && pgrp == Some(pgid)
+}
+
+/// SIGTERM `group`, SIGKILL it after `grace`. The leader is reaped through
+/// `child` when given, since a zombie still counts as a member.
+#[cfg(unix)]
+async fn stop_group(group: &Group, grace: Duration, mut child: Option<&mut Child>) -> Termination {
+    use nix::sys::signal::Signal;
+
+    let pid = group.pid;
+    if signal_group(pid, Some(Signal::SIGTERM)).is_err() {
+        return Termination::Terminated;
+    }
+    let deadline = Instant::now() + grace;
+    loop {
+        if let Some(child) = child.as_deref_mut() {
+            let _ = child.try_wait();
+        }
+        if !group_alive(pid) {
+            return Termination::Terminated;
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    // Recorded first, so whoever reaps the leader sees the escalation.
+    group.set_stopped(Termination::Killed);
+    let _ = signal_group(pid, Some(Signal::SIGKILL));
+    if let Some(child) = child {
+        let _ = child.wait().await;
+    }
+    Termination::Killed
+}
+
+fn read_all(mut pipe: impl AsyncRead + Unpin + Send + 'static) -> JoinHandle<Vec<u8>> {
+    tokio::spawn(async move {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf).await;
+        buf
+    })
+}
+
+async fn collect(reader: Option<JoinHandle<Vec<u8>>>, drain: Option<Duration>) -> Vec<u8> {
+    let Some(mut reader) = reader else {
+        return Vec::new();
+    };
+    let Some(drain) = drain else {
+        return reader.await.unwrap_or_default();
+    };
+    match tokio::time::timeout(drain, &mut reader).await {
+        Ok(read) => read.unwrap_or_default(),
+        Err(_) => {
+            reader.abort();
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    #[tokio::test]
+    async fn finished_child_keeps_its_output_and_is_not_stopped() {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "echo out; echo err >&2; exit 3"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let finished = output(&mut command, Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(finished.output.status.code(), Some(3));
+        assert_eq!(finished.output.stdout, b"out\n");
+        assert_eq!(finished.output.stderr, b"err\n");
+        assert!(!finished.timed_out);
+        assert_eq!(finished.stopped, None);
+    }
+
+    #[tokio::test]
+    async fn sigterm_ignoring_child_is_killed_after_the_grace_period() {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "trap '' TERM; echo ready; while :; do sleep 1; done"])
+            .stdout(Stdio::piped());
+        let child = ManagedChild::spawn(&mut command)
+            .unwrap()
+            .with_grace(Duration::from_millis(200));
+        let finished = child
+            .wait_with_output(Some(Duration::from_millis(300)))
+            .await
+            .unwrap();
+        assert!(finished.timed_out);
+        assert_eq!(finished.stopped, Some(Termination::Killed));
+        assert_eq!(finished.output.stdout, b"ready\n");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn stat_lines_match_live_group_members() {
+        assert!(running_in_group("42 (my (odd) name) S 1 42 42 0", 42));
+        assert!(!running_in_group("42 (sleep) Z 1 42 42 0", 42));
+        assert!(!running_in_group("43 (sleep) S 1 7 7 0", 42));
+    }
+
+    #[tokio::test]
+    async fn cooperative_child_is_terminated() {
+        let mut command = Command::new("sleep");
+        command.arg("30");
+        let finished = output(&mut command, Some(Duration::from_millis(100)))
+            .await
+            .unwrap();
+        assert!(finished.timed_out);
+        assert_eq!(finished.stopped, Some(Termination::Terminated));
+    }
+}
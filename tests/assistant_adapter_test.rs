@@ -0,0 +1,231 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use palingenesis::monitor::classifier::{RateLimitInfo, RetryAfterSource, StopReason};
+use palingenesis::resume::{
+    ClaudeCodeAdapter, NewSessionStrategy, OpenCodeAdapter, ResumeContext, ResumeOutcome,
+    ResumeStrategy, SameSessionStrategy, Selection, StrategySelector,
+};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Write an executable that appends its working directory and arguments to
+/// `<name>.log` and prints `stdout`.
+fn fake_program(dir: &Path, name: &str, stdout: &str) -> (PathBuf, PathBuf) {
+    let program = dir.join(name);
+    let log = dir.join(format!("{name}.log"));
+    let script = format!(
+        "#!/bin/sh\nprintf '%s' \"$PWD\" >> '{log}'\nfor arg in \"$@\"; do printf ' [%s]' \"$arg\" >> '{log}'; done\necho >> '{log}'\nprintf '%s\\n' '{stdout}'\n",
+        log = log.display(),
+    );
+    std::fs::write(&program, script).expect("write fake program");
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755))
+        .expect("chmod fake program");
+    (program, log)
+}
+
+fn claude_transcript(dir: &Path, project: &Path) -> PathBuf {
+    let path = dir.join("7c1e-session.jsonl");
+    std::fs::write(
+        &path,
+        format!(
+            "{{\"type\":\"summary\",\"summary\":\"work\"}}\n\
+             {{\"type\":\"user\",\"sessionId\":\"7c1e\",\"cwd\":\"{}\"}}\n",
+            project.display()
+        ),
+    )
+    .expect("write transcript");
+    path
+}
+
+fn rate_limit_now() -> StopReason {
+    StopReason::RateLimit(RateLimitInfo {
+        retry_after: Duration::ZERO,
+        source: RetryAfterSource::Header,
+        message: None,
+    })
+}
+
+struct StateEnv {
+    _lock: std::sync::MutexGuard<'static, ()>,
+    temp: tempfile::TempDir,
+}
+
+impl StateEnv {
+    fn new() -> Self {
+        let lock = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let temp = tempfile::tempdir().expect("tempdir");
+        unsafe {
+            std::env::set_var("PALINGENESIS_STATE", temp.path().join("state"));
+        }
+        Self { _lock: lock, temp }
+    }
+
+    fn path(&self) -> &Path {
+        self.temp.path()
+    }
+}
+
+impl Drop for StateEnv {
+    fn drop(&mut self) {
+        unsafe {
+            std::env::remove_var("PALINGENESIS_STATE");
+        }
+    }
+}
+
+#[tokio::test]
+async fn claude_resume_runs_claude_resume_in_project_dir() {
+    let env = StateEnv::new();
+    let project = env.path().join("project");
+    let transcripts = env.path().join("projects").join("-project");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::create_dir_all(&transcripts).unwrap();
+    let (claude, log) = fake_program(env.path(), "claude", "ok");
+    let session_path = claude_transcript(&transcripts, &project);
+
+    let adapter = ClaudeCodeAdapter::new()
+        .with_program(claude.display().to_string())
+        .with_continuation("keep going");
+    let strategy = SameSessionStrategy::new().with_adapter(Arc::new(adapter));
+    let ctx = ResumeContext::new(session_path, rate_limit_now()).with_retry_after(Duration::ZERO);
+    let outcome = strategy.execute(&ctx).await.expect("resume");
+
+    assert!(outcome.is_success());
+    let invocation = std::fs::read_to_string(log).expect("claude log");
+    assert_eq!(
+        invocation.trim_end(),
+        format!(
+            "{} [--resume] [7c1e] [--print] [keep going]",
+            project.canonicalize().unwrap().display()
+        )
+    );
+}
+
+#[tokio::test]
+async fn claude_new_session_uses_print_and_reported_session_id() {
+    let env = StateEnv::new();
+    let project = env.path().join("project");
+    let transcripts = env.path().join("projects").join("-project");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::create_dir_all(&transcripts).unwrap();
+    let (claude, log) = fake_program(
+        env.path(),
+        "claude",
+        r#"{"type":"result","session_id":"9a0b"}"#,
+    );
+    let session_path = claude_transcript(&transcripts, &project);
+
+    let adapter = ClaudeCodeAdapter::new().with_program(claude.display().to_string());
+    let strategy = NewSessionStrategy::new().with_adapter(Arc::new(adapter));
+    let ctx = ResumeContext::new(session_path, StopReason::ContextExhausted(None));
+    let outcome = strategy.execute(&ctx).await.expect("new session");
+
+    match outcome {
+        ResumeOutcome::Success { session_path, .. } => {
+            assert_eq!(session_path, transcripts.join("9a0b.jsonl"))
+        }
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+    let invocation = std::fs::read_to_string(log).expect("claude log");
+    assert!(invocation.contains(" [--print] ["));
+    assert!(invocation.trim_end().ends_with("[--output-format] [json]"));
+}
+
+#[tokio::test]
+async fn opencode_adapter_keeps_opencode_commands() {
+    let env = StateEnv::new();
+    let sessions = env.path().join("sessions");
+    std::fs::create_dir_all(&sessions).unwrap();
+    let session_path = sessions.join("session.md");
+    std::fs::write(&session_path, "---\nstepsCompleted: [1]\n---\n").unwrap();
+    let next = sessions.join("next.md");
+    let (opencode, log) = fake_program(
+        env.path(),
+        "opencode",
+        &format!("session: {}", next.display()),
+    );
+
+    let adapter = Arc::new(OpenCodeAdapter::new().with_program(opencode.display().to_string()));
+    let resumed = SameSessionStrategy::new()
+        .with_adapter(adapter.clone())
+        .execute(
+            &ResumeContext::new(session_path.clone(), rate_limit_now())
+                .with_retry_after(Duration::ZERO),
+        )
+        .await
+        .expect("resume");
+    assert!(resumed.is_success());
+
+    let outcome = NewSessionStrategy::new()
+        .with_adapter(adapter)
+        .execute(&ResumeContext::new(
+            session_path.clone(),
+            StopReason::ContextExhausted(None),
+        ))
+        .await
+        .expect("new session");
+    match outcome {
+        ResumeOutcome::Success {
+            session_path: created,
+            ..
+        } => assert_eq!(created, next),
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+
+    // The new-session prompt spans several lines, so match on the whole log.
+    let log = std::fs::read_to_string(log).expect("opencode log");
+    let resume_line = log.lines().next().expect("resume invocation");
+    assert!(resume_line.ends_with(&format!(
+        "[continue] [--session] [{}]",
+        session_path.display()
+    )));
+    assert!(log.contains(" [new] [--prompt] ["));
+    assert!(
+        log.trim_end()
+            .ends_with(&format!("[--workdir] [{}]", sessions.display()))
+    );
+}
+
+#[tokio::test]
+async fn selector_picks_adapter_owning_the_session() {
+    let env = StateEnv::new();
+    let project = env.path().join("project");
+    let claude_dir = env.path().join("projects");
+    let transcripts = claude_dir.join("-project");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::create_dir_all(&transcripts).unwrap();
+    let (claude, claude_log) = fake_program(env.path(), "claude", "ok");
+    let (opencode, opencode_log) = fake_program(env.path(), "opencode", "ok");
+    let session_path = claude_transcript(&transcripts, &project);
+
+    let selector = StrategySelector::new().with_adapters(vec![
+        Arc::new(
+            OpenCodeAdapter::new()
+                .with_program(opencode.display().to_string())
+                .with_sessions_dir(env.path().join("opencode")),
+        ),
+        Arc::new(
+            ClaudeCodeAdapter::new()
+                .with_program(claude.display().to_string())
+                .with_sessions_dir(claude_dir),
+        ),
+    ]);
+    assert_eq!(
+        selector.adapter_for_session(&session_path).name(),
+        "claude-code"
+    );
+
+    let Selection::Resume(strategy) = selector.select_for(&session_path, &rate_limit_now()) else {
+        panic!("expected a resume strategy");
+    };
+    let ctx = ResumeContext::new(session_path, rate_limit_now()).with_retry_after(Duration::ZERO);
+    strategy.execute(&ctx).await.expect("resume");
+
+    assert!(claude_log.exists());
+    assert!(!opencode_log.exists());
+}
@@ -7,11 +7,11 @@ use chrono::{Duration as ChronoDuration, Utc};
 use palingenesis::monitor::classifier::{RateLimitInfo, RetryAfterSource, StopReason};
 use palingenesis::resume::{
     ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy, ResumeTrigger, SameSessionConfig,
-    SameSessionStrategy,
+    SameSessionStrategy, SessionOverrides,
 };
 use palingenesis::state::{AuditConfig, AuditEntry, AuditEventType, AuditLogger, AuditOutcome};
 
-static ENV_LOCK: Mutex<()> = Mutex::new(());
+static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
 #[test]
 fn audit_entry_serializes_with_required_fields() {
@@ -222,7 +222,7 @@ fn rate_limit_reason() -> StopReason {
 
 #[tokio::test]
 async fn audit_logs_resume_events_from_strategy() {
-    let _lock = ENV_LOCK.lock().expect("env lock");
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().expect("tempdir");
     let state_dir = temp.path().join("state");
     unsafe {
@@ -231,8 +231,10 @@ async fn audit_logs_resume_events_from_strategy() {
 
     let ctx = ResumeContext::new(PathBuf::from("/tmp/session.md"), rate_limit_reason())
         .with_retry_after(std::time::Duration::from_secs(0));
-    let mut config = SameSessionConfig::default();
-    config.backoff_jitter = false;
+    let config = SameSessionConfig {
+        backoff_jitter: false,
+        ..SameSessionConfig::default()
+    };
     let strategy = SameSessionStrategy::with_config(config).with_trigger(TestTrigger);
 
     let outcome = strategy.execute(&ctx).await.expect("outcome");
@@ -247,3 +249,70 @@ async fn audit_logs_resume_events_from_strategy() {
         std::env::remove_var("PALINGENESIS_STATE");
     }
 }
+
+struct RecordingTrigger {
+    continuation: Arc<Mutex<Option<String>>>,
+}
+
+#[async_trait]
+impl ResumeTrigger for RecordingTrigger {
+    async fn trigger(&self, ctx: &ResumeContext) -> Result<(), ResumeError> {
+        *self.continuation.lock().unwrap() = ctx.continuation.clone();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn audit_records_sidecar_overrides() {
+    let _lock = ENV_LOCK.lock().await;
+    let temp = tempfile::tempdir().expect("tempdir");
+    let state_dir = temp.path().join("state");
+    unsafe {
+        std::env::set_var("PALINGENESIS_STATE", &state_dir);
+    }
+    let session = temp.path().join("tests.md");
+    std::fs::write(&session, "").unwrap();
+    let sidecar = temp.path().join("tests.palingenesis.toml");
+    std::fs::write(
+        &sidecar,
+        "[same_session]\ncontinuation_message = \"continue writing tests, do not modify src/\"\n",
+    )
+    .unwrap();
+
+    let (overrides, errors) = SessionOverrides::discover(&session);
+    assert!(errors.is_empty());
+    let continuation = Arc::new(Mutex::new(None));
+    let ctx = ResumeContext::new(session.clone(), rate_limit_reason())
+        .with_retry_after(std::time::Duration::from_secs(0));
+    let strategy = SameSessionStrategy::with_config(SameSessionConfig::default())
+        .with_trigger(RecordingTrigger {
+            continuation: Arc::clone(&continuation),
+        })
+        .with_overrides(overrides);
+
+    let outcome = strategy.execute(&ctx).await.expect("outcome");
+    assert!(matches!(outcome, ResumeOutcome::Success { .. }));
+    assert_eq!(
+        continuation.lock().unwrap().as_deref(),
+        Some("continue writing tests, do not modify src/")
+    );
+
+    let entries = AuditLogger::new(&state_dir)
+        .query()
+        .event_types(vec![AuditEventType::ResumeStarted])
+        .execute()
+        .expect("query");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].metadata["sidecar"],
+        serde_json::json!([sidecar.display().to_string()])
+    );
+    assert_eq!(
+        entries[0].metadata["sidecar_overrides"],
+        serde_json::json!(["same_session.continuation_message"])
+    );
+
+    unsafe {
+        std::env::remove_var("PALINGENESIS_STATE");
+    }
+}
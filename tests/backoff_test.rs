@@ -7,8 +7,10 @@ use palingenesis::resume::{Backoff, BackoffConfig, BackoffError};
 
 #[test]
 fn backoff_initial_delay_is_base() {
-    let mut config = BackoffConfig::default();
-    config.jitter_enabled = false;
+    let config = BackoffConfig {
+        jitter_enabled: false,
+        ..BackoffConfig::default()
+    };
     let backoff = Backoff::with_config(config).expect("backoff");
 
     let delay = backoff.delay_for_attempt(1);
@@ -17,8 +19,10 @@ fn backoff_initial_delay_is_base() {
 
 #[test]
 fn backoff_caps_at_max_delay() {
-    let mut config = BackoffConfig::default();
-    config.jitter_enabled = false;
+    let config = BackoffConfig {
+        jitter_enabled: false,
+        ..BackoffConfig::default()
+    };
     let backoff = Backoff::with_config(config).expect("backoff");
 
     let delay = backoff.delay_for_attempt(5);
@@ -42,9 +46,11 @@ fn backoff_jitter_stays_within_bounds() {
 
 #[test]
 fn backoff_enforces_max_retries() {
-    let mut config = BackoffConfig::default();
-    config.jitter_enabled = false;
-    config.max_retries = 2;
+    let config = BackoffConfig {
+        jitter_enabled: false,
+        max_retries: 2,
+        ..BackoffConfig::default()
+    };
     let mut backoff = Backoff::with_config(config).expect("backoff");
 
     backoff.next_delay().expect("attempt 1");
@@ -55,8 +61,10 @@ fn backoff_enforces_max_retries() {
 
 #[test]
 fn backoff_reset_clears_attempts() {
-    let mut config = BackoffConfig::default();
-    config.jitter_enabled = false;
+    let config = BackoffConfig {
+        jitter_enabled: false,
+        ..BackoffConfig::default()
+    };
     let mut backoff = Backoff::with_config(config).expect("backoff");
 
     backoff.next_delay().expect("attempt 1");
@@ -68,23 +76,29 @@ fn backoff_reset_clears_attempts() {
 
 #[test]
 fn backoff_config_validation_rejects_invalid_values() {
-    let mut config = BackoffConfig::default();
-    config.base_delay = Duration::from_secs(0);
+    let config = BackoffConfig {
+        base_delay: Duration::from_secs(0),
+        ..BackoffConfig::default()
+    };
     assert!(matches!(
         Backoff::with_config(config),
         Err(BackoffError::InvalidConfig(_))
     ));
 
-    let mut config = BackoffConfig::default();
-    config.max_delay = Duration::from_secs(10);
-    config.base_delay = Duration::from_secs(20);
+    let config = BackoffConfig {
+        max_delay: Duration::from_secs(10),
+        base_delay: Duration::from_secs(20),
+        ..BackoffConfig::default()
+    };
     assert!(matches!(
         Backoff::with_config(config),
         Err(BackoffError::InvalidConfig(_))
     ));
 
-    let mut config = BackoffConfig::default();
-    config.jitter_percent = 2.0;
+    let config = BackoffConfig {
+        jitter_percent: 2.0,
+        ..BackoffConfig::default()
+    };
     assert!(matches!(
         Backoff::with_config(config),
         Err(BackoffError::InvalidConfig(_))
@@ -1,3 +1,5 @@
+#![cfg(feature = "bots")]
+
 use std::env;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -17,7 +19,7 @@ use palingenesis::daemon::state::DaemonState;
 use palingenesis::http::handlers;
 use palingenesis::http::{AppState, EventBroadcaster};
 use palingenesis::telemetry::Metrics;
-static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
 fn set_env_var(key: &str, value: impl AsRef<std::ffi::OsStr>) {
     unsafe {
@@ -80,7 +82,7 @@ fn current_timestamp() -> String {
 
 #[tokio::test]
 async fn test_discord_webhook_accepts_valid_request() {
-    let _lock = ENV_LOCK.lock().unwrap();
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().unwrap();
     let mut seed = [0u8; 32];
     OsRng.fill_bytes(&mut seed);
@@ -118,7 +120,7 @@ async fn test_discord_webhook_accepts_valid_request() {
 
 #[tokio::test]
 async fn test_discord_webhook_rejects_invalid_signature() {
-    let _lock = ENV_LOCK.lock().unwrap();
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().unwrap();
     let mut seed = [0u8; 32];
     OsRng.fill_bytes(&mut seed);
@@ -152,7 +154,7 @@ async fn test_discord_webhook_rejects_invalid_signature() {
 
 #[tokio::test]
 async fn test_slack_webhook_accepts_valid_request() {
-    let _lock = ENV_LOCK.lock().unwrap();
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().unwrap();
     write_bot_config(&temp, "deadbeef", "slack-secret");
 
@@ -183,7 +185,7 @@ async fn test_slack_webhook_accepts_valid_request() {
 
 #[tokio::test]
 async fn test_slack_webhook_rejects_invalid_signature() {
-    let _lock = ENV_LOCK.lock().unwrap();
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().unwrap();
     write_bot_config(&temp, "deadbeef", "slack-secret");
 
@@ -208,7 +210,7 @@ async fn test_slack_webhook_rejects_invalid_signature() {
 
 #[tokio::test]
 async fn test_unauthorized_user_rejected() {
-    let _lock = ENV_LOCK.lock().unwrap();
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().unwrap();
     write_bot_config(&temp, "deadbeef", "slack-secret");
 
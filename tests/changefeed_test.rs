@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use palingenesis::state::{
+    ChangeOp, ChangeRecord, Changefeed, ChangesSince, CurrentSession, DaemonState, ResumeHistory,
+    SessionStatus, StateHandle, StateStore,
+};
+
+const SESSION: &str = "/work/session.md";
+
+fn open(dir: &Path, max_records: usize) -> (StateHandle, Arc<Changefeed>) {
+    let changefeed =
+        Arc::new(Changefeed::new(dir.join("changes.jsonl")).with_max_records(max_records));
+    let handle = StateHandle::new(StateStore::with_path(dir.join("state.json")))
+        .with_changefeed(Arc::clone(&changefeed));
+    (handle, changefeed)
+}
+
+fn changes(changefeed: &Changefeed, since_seq: u64) -> Vec<ChangeRecord> {
+    match changefeed.since(since_seq) {
+        ChangesSince::Changes { changes, .. } => changes,
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+fn summary(records: &[ChangeRecord]) -> Vec<(u64, &str, ChangeOp)> {
+    records
+        .iter()
+        .map(|record| (record.seq, record.entity.as_str(), record.op))
+        .collect()
+}
+
+/// Track a session, resume it once, flag it, pause, then let it go.
+fn drive(handle: &StateHandle) {
+    handle
+        .update_critical(|state| {
+            state.current_session = Some(CurrentSession {
+                path: PathBuf::from(SESSION),
+                ..CurrentSession::default()
+            })
+        })
+        .unwrap();
+    handle.update(|state| {
+        state.stats.total_resumes += 1;
+        state
+            .resume_history
+            .push(ResumeHistory::new(PathBuf::from(SESSION)));
+    });
+    handle.flush().unwrap();
+    handle
+        .update_critical(|state| {
+            if let Some(session) = state.current_session.as_mut() {
+                session.status = SessionStatus::NeedsAttention;
+            }
+            state.daemon_state = DaemonState::Paused;
+        })
+        .unwrap();
+    handle
+        .update_critical(|state| state.current_session = None)
+        .unwrap();
+}
+
+#[test]
+fn persisted_mutations_are_recorded_in_order() {
+    let temp = tempfile::tempdir().unwrap();
+    let (handle, changefeed) = open(temp.path(), 100);
+    drive(&handle);
+
+    let records = changes(&changefeed, 0);
+    assert_eq!(
+        summary(&records),
+        vec![
+            (1, "session", ChangeOp::Tracked),
+            (2, "resume_history", ChangeOp::Append),
+            (3, "stats", ChangeOp::Set),
+            (4, "session", ChangeOp::Update),
+            (5, "daemon_state", ChangeOp::Set),
+            (6, "session", ChangeOp::Untracked),
+        ]
+    );
+    assert_eq!(records[1].key.as_deref(), Some(SESSION));
+    assert_eq!(records[2].value["total_resumes"], 1);
+    assert_eq!(records[3].value["status"], "needs_attention");
+    assert_eq!(records[4].value, "paused");
+
+    let on_disk: Vec<ChangeRecord> = std::fs::read_to_string(changefeed.path())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(on_disk, records);
+
+    // Unflushed changes are not in the feed.
+    handle.update(|state| state.stats.saves_count += 1);
+    assert_eq!(changefeed.last_seq(), 6);
+}
+
+#[test]
+fn since_seq_filters_and_sequence_survives_a_restart() {
+    let temp = tempfile::tempdir().unwrap();
+    {
+        let (handle, changefeed) = open(temp.path(), 100);
+        drive(&handle);
+        assert_eq!(
+            summary(&changes(&changefeed, 4)),
+            vec![
+                (5, "daemon_state", ChangeOp::Set),
+                (6, "session", ChangeOp::Untracked),
+            ]
+        );
+        assert!(changes(&changefeed, 6).is_empty());
+    }
+
+    let (handle, changefeed) = open(temp.path(), 100);
+    assert_eq!(changefeed.last_seq(), 6);
+    handle
+        .update_critical(|state| state.daemon_state = DaemonState::Monitoring)
+        .unwrap();
+    assert_eq!(
+        summary(&changes(&changefeed, 6)),
+        vec![(7, "daemon_state", ChangeOp::Set)]
+    );
+}
+
+#[cfg(feature = "http-api")]
+mod http {
+    use super::*;
+
+    use std::time::Duration;
+
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use palingenesis::http::handlers::changes::{ChangesQuery, changes_response};
+    use serde_json::Value;
+
+    async fn body(response: axum::response::Response) -> (StatusCode, Value) {
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn long_poll_wakes_on_the_next_write() {
+        let temp = tempfile::tempdir().unwrap();
+        let (handle, changefeed) = open(temp.path(), 100);
+
+        let waiting = Arc::clone(&changefeed);
+        let poll = tokio::spawn(async move {
+            changes_response(
+                &waiting,
+                ChangesQuery {
+                    since_seq: 0,
+                    wait_secs: 30,
+                },
+            )
+            .await
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!poll.is_finished(), "nothing to return yet");
+
+        handle
+            .update_critical(|state| state.daemon_state = DaemonState::Monitoring)
+            .unwrap();
+        let response = tokio::time::timeout(Duration::from_secs(5), poll)
+            .await
+            .expect("woken by the write")
+            .unwrap();
+        let (status, payload) = body(response).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["data"]["last_seq"], 1);
+        assert_eq!(payload["data"]["changes"][0]["entity"], "daemon_state");
+        assert_eq!(payload["data"]["changes"][0]["op"], "set");
+
+        // Without a wait the request returns at once.
+        let (status, payload) =
+            body(changes_response(&changefeed, ChangesQuery::default()).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["data"]["changes"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn lagging_consumer_is_told_to_resnapshot_after_rotation() {
+        let temp = tempfile::tempdir().unwrap();
+        let (handle, changefeed) = open(temp.path(), 2);
+        for total in 1..=5 {
+            handle
+                .update_critical(|state| state.stats.total_resumes = total)
+                .unwrap();
+        }
+        assert!(temp.path().join("changes.1.jsonl").exists());
+
+        let query = ChangesQuery {
+            since_seq: 1,
+            wait_secs: 0,
+        };
+        let (status, payload) = body(changes_response(&changefeed, query).await).await;
+        assert_eq!(status, StatusCode::GONE);
+        assert_eq!(payload["error"]["code"], "COMPACTED");
+        assert_eq!(payload["error"]["oldest_seq"], 3);
+
+        let query = ChangesQuery {
+            since_seq: 2,
+            wait_secs: 0,
+        };
+        let (status, payload) = body(changes_response(&changefeed, query).await).await;
+        assert_eq!(status, StatusCode::OK);
+        let seqs: Vec<u64> = payload["data"]["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| record["seq"].as_u64().unwrap())
+            .collect();
+        assert_eq!(seqs, vec![3, 4, 5]);
+    }
+}
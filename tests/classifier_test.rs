@@ -1,10 +1,14 @@
 use std::path::PathBuf;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+
+use palingenesis::config::schema::ClassificationConfig;
 use palingenesis::monitor::classifier::{
-    ClassifierConfig, RetryAfterSource, StopReason, StopReasonClassifier, UserExitInfo,
-    UserExitType,
+    ClassifierConfig, ClassifierError, RetryAfterSource, StopReason, StopReasonClassifier,
+    UserExitInfo, UserExitType, compiled_pattern_count, has_completion_marker,
 };
+use palingenesis::monitor::clock_skew::ClockSkew;
 
 fn fixture_path(name: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -125,6 +129,49 @@ fn classifies_completed_sessions_before_context_exhaustion() {
     assert!(matches!(result.reason, StopReason::Completed));
 }
 
+#[test]
+fn completion_marker_counts_mid_file_and_at_end() {
+    let mid = "# Plan\n<!-- palingenesis: complete -->\nSummary written afterwards.\n";
+    let end = "# Plan\nAll steps done.\n  <!--  Palingenesis:  COMPLETE -->\n";
+    assert!(has_completion_marker(mid));
+    assert!(has_completion_marker(end));
+}
+
+#[test]
+fn completion_marker_inside_code_fence_is_ignored() {
+    let fenced = "Add this when done:\n```markdown\n<!-- palingenesis: complete -->\n```\n";
+    let tilde = "~~~\n<!-- palingenesis: complete -->\n~~~\nstill working\n";
+    let inline = "Write `<!-- palingenesis: complete -->` at the end.\n";
+    assert!(!has_completion_marker(fenced));
+    assert!(!has_completion_marker(tilde));
+    assert!(!has_completion_marker(inline));
+    assert!(has_completion_marker(&format!(
+        "{fenced}<!-- palingenesis: complete -->\n"
+    )));
+}
+
+#[test]
+fn classifies_marker_as_completed_despite_in_progress_status() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("session.md");
+    std::fs::write(
+        &path,
+        "---\nstatus: in-progress\nstepsCompleted: [1]\n---\nDone.\n<!-- palingenesis: complete -->\n",
+    )
+    .expect("write session");
+
+    let classifier = StopReasonClassifier::new().expect("classifier");
+    let result = classifier.classify(&path, None);
+
+    assert!(matches!(result.reason, StopReason::Completed));
+    assert!(
+        result
+            .evidence
+            .iter()
+            .any(|item| item == "completion marker found")
+    );
+}
+
 #[test]
 fn handles_read_errors_without_crashing() {
     let classifier = StopReasonClassifier::new().expect("classifier");
@@ -213,3 +260,255 @@ fn should_auto_resume_respects_user_exit() {
 
     assert!(!reason.should_auto_resume());
 }
+
+#[test]
+fn classification_compiles_no_patterns() {
+    let classifier = StopReasonClassifier::new().expect("classifier");
+    let compiled = compiled_pattern_count();
+    assert!(compiled > 0);
+
+    let samples = [
+        "rate_limit_error: Retry-After: 30",
+        r#"{"error": "429", "retry_after": "12"}"#,
+        "Please try again in 5 seconds",
+        "used 190000 of 200000 tokens",
+        "150000/200000 tokens",
+        "180000 tokens used",
+        "context_length_exceeded",
+        "exit",
+    ];
+    for _ in 0..50 {
+        for sample in samples {
+            classifier.classify_content(sample, None);
+        }
+    }
+
+    assert_eq!(compiled_pattern_count(), compiled);
+}
+
+#[test]
+fn shared_classifier_is_built_once() {
+    let first = StopReasonClassifier::shared();
+    let compiled = compiled_pattern_count();
+    let second = StopReasonClassifier::shared();
+
+    assert!(std::sync::Arc::ptr_eq(&first, &second));
+    assert_eq!(compiled_pattern_count(), compiled);
+}
+
+#[test]
+fn invalid_extra_pattern_names_setting_and_pattern() {
+    let config = ClassifierConfig {
+        extra_context_patterns: vec!["context (full".to_string()],
+        ..Default::default()
+    };
+    let err = match StopReasonClassifier::with_config(config) {
+        Ok(_) => panic!("invalid pattern accepted"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(
+        err,
+        ClassifierError::InvalidPattern {
+            kind: "context exhaustion",
+            ..
+        }
+    ));
+    let message = err.to_string();
+    assert!(message.contains("context exhaustion"), "{message}");
+    assert!(message.contains("context (full"), "{message}");
+}
+
+#[test]
+fn configured_context_sizes_merge_over_builtins() {
+    let mut section = ClassificationConfig::default();
+    section
+        .known_context_sizes
+        .insert("My-Local-Model".to_string(), 32_768);
+    let config = ClassifierConfig::from_config(&section);
+
+    assert_eq!(
+        config.known_context_sizes.get("my-local-model"),
+        Some(&32_768)
+    );
+    assert_eq!(config.known_context_sizes.get("gpt-4"), Some(&8_192));
+    assert_eq!(
+        config.known_context_sizes.get("claude 3 opus"),
+        Some(&200_000)
+    );
+
+    section
+        .known_context_sizes
+        .insert("GPT-4".to_string(), 16_384);
+    let config = ClassifierConfig::from_config(&section);
+    assert_eq!(config.known_context_sizes.get("gpt-4"), Some(&16_384));
+    assert!(!config.known_context_sizes.contains_key("GPT-4"));
+}
+
+#[test]
+fn configured_threshold_changes_borderline_classification() {
+    let content = "Session status: used 170000 of 200000 tokens";
+
+    let default = StopReasonClassifier::with_config(ClassifierConfig::from_config(
+        &ClassificationConfig::default(),
+    ))
+    .expect("classifier");
+    assert!(matches!(
+        default.classify_content(content, None).reason,
+        StopReason::ContextExhausted(_)
+    ));
+
+    let strict = ClassificationConfig {
+        context_threshold_percent: 0.9,
+        ..ClassificationConfig::default()
+    };
+    let strict = StopReasonClassifier::with_config(ClassifierConfig::from_config(&strict))
+        .expect("classifier");
+    assert!(!matches!(
+        strict.classify_content(content, None).reason,
+        StopReason::ContextExhausted(_)
+    ));
+}
+
+fn rate_limit_delay(classifier: &StopReasonClassifier, content: &str) -> Duration {
+    match classifier.classify_content(content, None).reason {
+        StopReason::RateLimit(info) => {
+            assert_eq!(info.source, RetryAfterSource::Header);
+            info.retry_after
+        }
+        other => panic!("expected rate limit, got {other:?}"),
+    }
+}
+
+fn assert_about(actual: Duration, expected_secs: u64) {
+    let diff = actual.as_secs_f64() - expected_secs as f64;
+    assert!(
+        diff.abs() <= 2.0,
+        "expected ~{expected_secs}s, got {actual:?}"
+    );
+}
+
+#[test]
+fn http_date_retry_after_is_corrected_for_provider_clock_skew() {
+    let classifier = StopReasonClassifier::new().expect("classifier");
+    let now = chrono::Utc::now();
+    let http_date = |offset: i64| (now + chrono::Duration::seconds(offset)).to_rfc2822();
+
+    // A bogus Date header is ignored; the retry time is read on the local clock.
+    let bogus = format!(
+        "HTTP/1.1 429 Too Many Requests\nDate: Fri, 01 Jan 1999 00:00:00 GMT\nRetry-After: {}\n",
+        http_date(90)
+    );
+    assert_about(rate_limit_delay(&classifier, &bogus), 90);
+    assert_eq!(ClockSkew::shared().estimate(), None);
+
+    // Provider clock five minutes ahead: its Retry-After is 120s from its now.
+    let skewed = format!(
+        "HTTP/1.1 429 Too Many Requests\nDate: {}\nRetry-After: {}\n",
+        http_date(300),
+        http_date(420)
+    );
+    assert_about(rate_limit_delay(&classifier, &skewed), 120);
+    let estimate = ClockSkew::shared().estimate().expect("skew estimated");
+    assert!((estimate - 300.0).abs() <= 2.0, "{estimate}");
+}
+
+#[test]
+fn caps_evidence_and_matched_message() {
+    let config = ClassifierConfig {
+        extra_rate_limit_patterns: vec!["(?s)slow down.*".to_string()],
+        max_evidence_chars: 64,
+        ..Default::default()
+    };
+    let classifier = StopReasonClassifier::with_config(config).expect("classifier");
+    let content = format!("slow down {}", "x".repeat(100_000));
+    let result = classifier.classify_content(&content, None);
+
+    let StopReason::RateLimit(info) = result.reason else {
+        panic!("expected rate limit, got {:?}", result.reason);
+    };
+    assert!(info.message.expect("message").chars().count() < 80);
+    assert!(!result.evidence.is_empty());
+    assert!(result.evidence.iter().all(|line| line.chars().count() < 80));
+}
+
+fn quota_reset(classifier: &StopReasonClassifier, content: &str) -> Option<DateTime<Utc>> {
+    match classifier.classify_content(content, None).reason {
+        StopReason::QuotaExhausted { resets_at } => resets_at,
+        other => panic!("expected quota exhaustion for {content:?}, got {other:?}"),
+    }
+}
+
+fn utc(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[test]
+fn quota_exhaustion_extracts_absolute_reset_times() {
+    let classifier = StopReasonClassifier::new().expect("classifier");
+    for (content, expected) in [
+        (
+            "Error: daily quota exceeded. Your quota will reset at 2099-03-01T00:00:00Z.",
+            "2099-03-01T00:00:00Z",
+        ),
+        (
+            "You have reached your daily limit. It resets at 2099-03-01 08:30 UTC",
+            "2099-03-01T08:30:00Z",
+        ),
+        (
+            "monthly usage limit reached; try again after 2099-03-01T09:00:00+02:00",
+            "2099-03-01T07:00:00Z",
+        ),
+        (
+            "Weekly quota exhausted, resets on Sun, 01 Mar 2099 00:00:00 GMT",
+            "2099-03-01T00:00:00Z",
+        ),
+        (
+            "HTTP/1.1 429\nanthropic-ratelimit-tokens-reset: 2099-03-01T00:00:00Z\nquota exhausted",
+            "2099-03-01T00:00:00Z",
+        ),
+        (
+            "insufficient_quota\nx-ratelimit-reset: 4076006400",
+            "2099-03-01T00:00:00Z",
+        ),
+    ] {
+        assert_eq!(
+            quota_reset(&classifier, content),
+            Some(utc(expected)),
+            "{content}"
+        );
+    }
+}
+
+#[test]
+fn quota_exhaustion_extracts_relative_reset_and_tolerates_none() {
+    let classifier = StopReasonClassifier::new().expect("classifier");
+
+    let expected = Utc::now() + chrono::Duration::hours(3);
+    let resets_at = quota_reset(
+        &classifier,
+        "Daily token limit reached. Quota resets in 3 hours.",
+    )
+    .expect("relative reset");
+    assert!((resets_at - expected).num_seconds().abs() <= 2);
+
+    assert_eq!(quota_reset(&classifier, "daily quota exceeded"), None);
+    // A reset time already in the past says nothing about the next window.
+    assert_eq!(
+        quota_reset(&classifier, "quota will reset at 2001-01-01T00:00:00Z"),
+        None
+    );
+}
+
+#[test]
+fn per_request_quota_errors_stay_rate_limits() {
+    let classifier = StopReasonClassifier::new().expect("classifier");
+    let result = classifier.classify_content("429: quota exceeded, retry-after: 20", None);
+    assert!(matches!(result.reason, StopReason::RateLimit(_)));
+    assert_eq!(
+        StopReason::QuotaExhausted { resets_at: None }.metrics_reason_label(),
+        Some("quota_exhausted")
+    );
+}
@@ -16,12 +16,22 @@ fn test_help_shows_all_subcommands() {
         .stdout(predicate::str::contains("status"))
         .stdout(predicate::str::contains("logs"))
         .stdout(predicate::str::contains("config"))
-        .stdout(predicate::str::contains("mcp"))
         .stdout(predicate::str::contains("pause"))
         .stdout(predicate::str::contains("resume"))
         .stdout(predicate::str::contains("new-session"));
 }
 
+#[cfg(feature = "mcp")]
+#[test]
+fn test_help_shows_mcp_subcommand() {
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mcp"));
+}
+
 #[test]
 fn test_version_is_semver() {
     Command::cargo_bin("palingenesis")
@@ -0,0 +1,155 @@
+// Allow deprecated cargo_bin - the deprecation is for custom build-dir edge case
+// which doesn't apply to this project. See: https://docs.rs/assert_cmd
+#![allow(deprecated)]
+
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[test]
+fn test_config_diff_against_defaults_reports_changes() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    fs::write(
+        &config_path,
+        r#"
+[daemon]
+http_port = 7777
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "diff"])
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("--- defaults"))
+        .stdout(predicate::str::contains("-daemon.http_port = 7654"))
+        .stdout(predicate::str::contains("+daemon.http_port = 7777"));
+}
+
+#[test]
+fn test_config_diff_identical_files_exits_zero() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    let other_path = temp.path().join("other.toml");
+    let contents = "[daemon]\nlog_level = \"debug\"\n";
+    fs::write(&config_path, contents).unwrap();
+    fs::write(&other_path, contents).unwrap();
+
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "diff", "--against"])
+        .arg(&other_path)
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No differences"));
+}
+
+#[test]
+fn test_config_diff_file_against_file_json() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    let other_path = temp.path().join("other.toml");
+    fs::write(
+        &config_path,
+        r#"
+[daemon]
+log_level = "debug"
+
+[monitoring]
+include_extensions = ["md", "txt"]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &other_path,
+        r#"
+[daemon]
+log_level = "warn"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "diff", "--json", "--against"])
+        .arg(&other_path)
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let changes = json["changes"].as_array().unwrap();
+    let keys: Vec<&str> = changes
+        .iter()
+        .map(|change| change["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        vec!["daemon.log_level", "monitoring.include_extensions[1]"]
+    );
+    assert_eq!(changes[0]["old"], "warn");
+    assert_eq!(changes[0]["new"], "debug");
+    assert_eq!(changes[1]["old"], "jsonl");
+    assert_eq!(changes[1]["new"], "txt");
+}
+
+#[test]
+fn test_config_diff_compares_secrets_by_placeholder() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    let other_path = temp.path().join("other.toml");
+    fs::write(
+        &config_path,
+        "[bot]\nslack_signing_secret = \"current-secret\"\n",
+    )
+    .unwrap();
+    fs::write(
+        &other_path,
+        "[bot]\nslack_signing_secret = \"older-secret\"\n",
+    )
+    .unwrap();
+
+    // Rotated secrets are not drift.
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "diff", "--against"])
+        .arg(&other_path)
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("current-secret").not())
+        .stdout(predicate::str::contains("older-secret").not());
+
+    // Setting one where the baseline has none is, without revealing it.
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "diff", "--json"])
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("bot.slack_signing_secret"))
+        .stdout(predicate::str::contains("[redacted]"))
+        .stdout(predicate::str::contains("current-secret").not());
+}
+
+#[test]
+fn test_config_diff_against_missing_backup_fails() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    fs::write(&config_path, "[daemon]\n").unwrap();
+
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "diff", "--against", "backup"])
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("config edit"));
+}
@@ -1,9 +1,15 @@
 use std::path::PathBuf;
 
 use palingenesis::config::schema::{
-    Config, DaemonConfig, McpConfig, MonitoringConfig, NotificationsConfig, OtelConfig,
-    ResumeConfig,
+    CheckConfig, Config, ContextSection, ContextSectionConfig, DaemonConfig, DaemonMode,
+    DebounceMode, GrpcConfig, HttpScope, HttpTokenConfig, MaxDeferralAction, McpConfig,
+    MissingSessionPolicy, ModelCost, MonitoringConfig, NewSessionResumeConfig, NotificationsConfig,
+    OtelConfig, PathDisplayMode, ReconcileConfig, ResumeConfig, ResumeGatesConfig,
+    ScheduleTimezone, UntaggedRoute, WorkspaceMode,
 };
+use palingenesis::config::validation::validate_config;
+use palingenesis::i18n::Locale;
+use palingenesis::state::StateFormat;
 
 fn expected_session_dir() -> PathBuf {
     dirs::home_dir()
@@ -22,6 +28,13 @@ http_port = 7777
 http_bind = "0.0.0.0"
 log_level = "debug"
 log_file = "/tmp/palingenesis.log"
+locale = "ja"
+path_display = "redacted"
+redact_patterns = ["client-*"]
+
+[daemon.check]
+max_incident_age_secs = 3600
+ping_url = "https://hc-ping.com/abc"
 
 [monitoring]
 session_dir = "/tmp/opencode"
@@ -38,6 +51,11 @@ max_retries = 3
 jitter = false
 backup_count = 2
 
+[resume.gates]
+max_load_average = 4.0
+require_ac_power = true
+on_max_deferral = "abandon"
+
 [notifications]
 enabled = true
 
@@ -74,13 +92,38 @@ metrics = true
     assert_eq!(
         config.daemon,
         DaemonConfig {
+            state_dir: None,
+            runtime_dir: None,
             pid_file: Some(PathBuf::from("/tmp/palingenesis.pid")),
             socket_path: Some(PathBuf::from("/tmp/palingenesis.sock")),
             http_enabled: true,
             http_port: 7777,
             http_bind: "0.0.0.0".to_string(),
+            http_token: None,
+            http_tokens: Vec::new(),
             log_level: "debug".to_string(),
             log_file: Some(PathBuf::from("/tmp/palingenesis.log")),
+            log_buffer_entries: 2000,
+            log_buffer_bytes: 1048576,
+            state_flush_interval_ms: 1000,
+            state_format: StateFormat::Json,
+            suspend_check_interval_secs: 30,
+            suspend_threshold_secs: 120,
+            job_normal_concurrency: 4,
+            job_background_concurrency: 2,
+            grpc: None,
+            mode: DaemonMode::Active,
+            max_retained_content_chars: 2048,
+            locale: Locale::Ja,
+            path_display: PathDisplayMode::Redacted,
+            redact_patterns: vec!["client-*".to_string()],
+            redact_json: false,
+            check: CheckConfig {
+                max_stall_secs: 1800,
+                max_incident_age_secs: 3600,
+                ping_url: Some("https://hc-ping.com/abc".to_string()),
+            },
+            reconcile: ReconcileConfig::default(),
         }
     );
 
@@ -92,7 +135,23 @@ metrics = true
             auto_detect: false,
             auto_detect_interval_secs: 300,
             debounce_ms: 250,
+            debounce: DebounceMode::Fixed,
+            debounce_min_ms: 50,
+            debounce_max_ms: 2000,
+            debounce_warn_ms: 2000,
             poll_interval_secs: Some(5),
+            follow_symlinks: false,
+            include_extensions: vec!["md".to_string(), "jsonl".to_string()],
+            ignore_patterns: vec![
+                "**/.git/**".to_string(),
+                "*.swp".to_string(),
+                "*~".to_string(),
+                "*.tmp".to_string(),
+            ],
+            active_window_hours: 0,
+            scan_limit: 200,
+            classify_concurrency: 2,
+            classify_stale_secs: 300,
         }
     );
 
@@ -105,14 +164,39 @@ metrics = true
             max_retries: 3,
             jitter: false,
             backup_count: 2,
+            backup_dir: None,
+            backup_filename_template: "{stem}-backup-{timestamp}.{ext}".to_string(),
+            backup_dedupe: true,
+            exclude_sessions: Vec::new(),
+            restore_deleted_from_backup: false,
+            enforce_model: false,
+            capture_git_context: false,
+            max_next_step_bytes: 65536,
+            maintenance_windows: Vec::new(),
+            archive_on_complete: false,
+            failure_bundle_count: 20,
+            incident_count: 50,
+            min_resume_interval_secs: 120,
+            max_resumes_per_hour: 10,
+            quota_retry_after_hours: 6,
+            cross_host_lease: false,
+            lease_ttl_secs: 300,
+            gates: ResumeGatesConfig {
+                max_load_average: Some(4.0),
+                require_ac_power: true,
+                on_max_deferral: MaxDeferralAction::Abandon,
+                ..ResumeGatesConfig::default()
+            },
+            new_session: NewSessionResumeConfig::default(),
+            custom_strategies: Vec::new(),
         }
     );
 
-    assert_eq!(config.notifications.enabled, true);
+    assert!(config.notifications.enabled);
     let webhook = config
         .notifications
         .webhook
-        .as_ref()
+        .first()
         .expect("webhook config");
     assert_eq!(webhook.url, "https://example.com/hook");
     let headers = webhook.headers.as_ref().expect("headers");
@@ -134,6 +218,7 @@ metrics = true
             logs: true,
             metrics: true,
             metrics_enabled: true,
+            ..OtelConfig::default()
         }
     );
 }
@@ -149,14 +234,14 @@ fn test_deserialize_minimal_config_uses_defaults() {
 fn test_default_values_applied() {
     let config = Config::default();
 
-    assert_eq!(config.daemon.http_enabled, false);
+    assert!(!config.daemon.http_enabled);
     assert_eq!(config.daemon.http_port, 7654);
     assert_eq!(config.daemon.http_bind, "127.0.0.1");
     assert_eq!(config.daemon.log_level, "info");
 
     assert_eq!(config.monitoring.session_dir, expected_session_dir());
     assert!(config.monitoring.assistants.is_empty());
-    assert_eq!(config.monitoring.auto_detect, true);
+    assert!(config.monitoring.auto_detect);
     assert_eq!(config.monitoring.auto_detect_interval_secs, 300);
     assert_eq!(config.monitoring.debounce_ms, 100);
     assert_eq!(config.monitoring.poll_interval_secs, None);
@@ -164,7 +249,7 @@ fn test_default_values_applied() {
     assert_eq!(config.resume.base_delay_secs, 30);
     assert_eq!(config.resume.max_delay_secs, 300);
     assert_eq!(config.resume.max_retries, 10);
-    assert_eq!(config.resume.jitter, true);
+    assert!(config.resume.jitter);
     assert_eq!(config.resume.backup_count, 10);
 
     assert_eq!(
@@ -180,10 +265,23 @@ fn test_default_values_applied() {
         config.notifications,
         NotificationsConfig {
             enabled: false,
-            webhook: None,
-            ntfy: None,
+            webhook: Vec::new(),
+            ntfy: Vec::new(),
             discord: None,
             slack: None,
+            primary_channel: None,
+            urgent_severities: vec!["critical".to_string()],
+            primary_timeout_ms: 3000,
+            allow_private_networks: false,
+            history_size: 200,
+            history_file: None,
+            ack_base_url: None,
+            ack_ttl_secs: 86400,
+            ack_pauses_resume: false,
+            secret_refresh_secs: 0,
+            untagged_route: UntaggedRoute::All,
+            mute_exempt_severities: Vec::new(),
+            escalation: Default::default(),
         }
     );
 }
@@ -205,7 +303,7 @@ Authorization = "Bearer token"
     let webhook = config
         .notifications
         .webhook
-        .as_ref()
+        .first()
         .expect("webhook config");
     assert_eq!(webhook.url, "https://example.com/hook");
     let headers = webhook.headers.as_ref().expect("headers");
@@ -215,6 +313,84 @@ Authorization = "Bearer token"
     );
 }
 
+#[test]
+fn test_notification_targets_accept_legacy_table() {
+    let toml_str = r#"
+[notifications.ntfy]
+topic = "alerts"
+"#;
+
+    let config: Config = toml::from_str(toml_str).expect("parse legacy ntfy");
+    assert_eq!(config.notifications.ntfy.len(), 1);
+    assert_eq!(config.notifications.ntfy[0].topic, "alerts");
+    assert_eq!(config.notifications.ntfy[0].channel_name(), "ntfy");
+    assert!(config.notifications.webhook.is_empty());
+    assert!(!config.notifications.has_target_arrays());
+}
+
+#[test]
+fn test_notification_targets_accept_arrays() {
+    let toml_str = r#"
+[[notifications.ntfy]]
+name = "phone"
+topic = "urgent"
+token = "tk_phone"
+severities = ["critical"]
+
+[[notifications.ntfy]]
+name = "team"
+topic = "team-feed"
+events = ["resume_succeeded", "resume_failed"]
+
+[[notifications.webhook]]
+name = "audit"
+url = "https://example.com/audit"
+"#;
+
+    let config: Config = toml::from_str(toml_str).expect("parse target arrays");
+    let ntfy = &config.notifications.ntfy;
+    assert_eq!(ntfy.len(), 2);
+    assert_eq!(ntfy[0].channel_name(), "phone");
+    assert_eq!(ntfy[0].token.as_deref(), Some("tk_phone"));
+    assert_eq!(ntfy[0].severities, vec!["critical".to_string()]);
+    assert_eq!(ntfy[1].channel_name(), "team");
+    assert_eq!(ntfy[1].events.len(), 2);
+    assert_eq!(config.notifications.webhook.len(), 1);
+    assert_eq!(config.notifications.webhook[0].channel_name(), "audit");
+    assert!(config.notifications.has_target_arrays());
+}
+
+#[test]
+fn test_notification_targets_mix_table_and_array() {
+    let toml_str = r#"
+[notifications.webhook]
+url = "https://example.com/hook"
+
+[[notifications.ntfy]]
+name = "a"
+topic = "one"
+
+[[notifications.ntfy]]
+name = "b"
+topic = "two"
+"#;
+
+    let config: Config = toml::from_str(toml_str).expect("parse mixed targets");
+    assert_eq!(config.notifications.webhook.len(), 1);
+    assert_eq!(config.notifications.webhook[0].channel_name(), "webhook");
+    let topics: Vec<&str> = config
+        .notifications
+        .ntfy
+        .iter()
+        .map(|ntfy| ntfy.topic.as_str())
+        .collect();
+    assert_eq!(topics, vec!["one", "two"]);
+
+    let reparsed: Config =
+        toml::from_str(&toml::to_string(&config).expect("serialize")).expect("round trip");
+    assert_eq!(reparsed.notifications, config.notifications);
+}
+
 #[test]
 fn test_invalid_config_errors() {
     let toml_str = r#"
@@ -225,3 +401,314 @@ http_port = "not-a-number"
     let result: Result<Config, _> = toml::from_str(toml_str);
     assert!(result.is_err());
 }
+
+#[test]
+fn test_daemon_grpc_section_deserialization() {
+    let toml_str = r#"
+[daemon.grpc]
+enabled = true
+bind = "0.0.0.0:9443"
+tls_cert = "/etc/palingenesis/grpc.crt"
+tls_key = "/etc/palingenesis/grpc.key"
+tls_ca = "/etc/palingenesis/ca.crt"
+"#;
+
+    let config: Config = toml::from_str(toml_str).expect("parse grpc config");
+
+    assert_eq!(
+        config.daemon.grpc,
+        Some(GrpcConfig {
+            enabled: true,
+            bind: "0.0.0.0:9443".to_string(),
+            tls_cert: Some(PathBuf::from("/etc/palingenesis/grpc.crt")),
+            tls_key: Some(PathBuf::from("/etc/palingenesis/grpc.key")),
+            tls_ca: Some(PathBuf::from("/etc/palingenesis/ca.crt")),
+        })
+    );
+}
+
+#[test]
+fn test_resume_new_session_worktree_mode() {
+    let config: Config = toml::from_str(
+        r#"
+[resume.new_session]
+workspace_mode = "worktree"
+worktree_root = "/srv/worktrees"
+"#,
+    )
+    .unwrap();
+    let new_session = &config.resume.new_session;
+    assert_eq!(new_session.workspace_mode, WorkspaceMode::Worktree);
+    assert_eq!(
+        new_session.worktree_root,
+        Some(PathBuf::from("/srv/worktrees"))
+    );
+    assert_eq!(new_session.branch_prefix, "palingenesis");
+
+    let default = Config::default();
+    assert_eq!(
+        default.resume.new_session.workspace_mode,
+        WorkspaceMode::InPlace
+    );
+}
+
+#[test]
+fn test_resume_new_session_context_sections() {
+    let config: Config = toml::from_str(
+        r#"
+[resume.new_session.context]
+max_chars = 4000
+sections = [
+  "custom:Run the tests first.",
+  "stop_reason",
+  { section = "session_tail:40", max_chars = 1500 },
+  { section = "model", enabled = false },
+]
+"#,
+    )
+    .unwrap();
+    let context = &config.resume.new_session.context;
+    assert_eq!(context.max_chars, 4000);
+    assert_eq!(
+        context.sections,
+        vec![
+            ContextSection::Custom("Run the tests first.".to_string()).into(),
+            ContextSection::StopReason.into(),
+            ContextSectionConfig {
+                section: ContextSection::SessionTail(40),
+                max_chars: Some(1500),
+                enabled: true,
+            },
+            ContextSectionConfig {
+                section: ContextSection::Model,
+                max_chars: None,
+                enabled: false,
+            },
+        ]
+    );
+
+    let error = toml::from_str::<Config>(
+        r#"
+[resume.new_session.context]
+sections = ["session_tail:0"]
+"#,
+    )
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("session_tail:<lines>"),
+        "{error}"
+    );
+
+    let default = Config::default().resume.new_session.context;
+    assert_eq!(default.max_chars, 0);
+    assert_eq!(default.sections.len(), 7);
+}
+
+#[test]
+fn test_bot_separate_listener() {
+    let config: Config = toml::from_str(
+        r#"
+[daemon]
+http_bind = "127.0.0.1"
+http_port = 7654
+
+[bot]
+enabled = true
+http_bind = "0.0.0.0"
+http_port = 8443
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.bot.separate_listener(&config.daemon),
+        Some(("0.0.0.0".to_string(), 8443))
+    );
+
+    let shared: Config = toml::from_str("[bot]\nenabled = true\nhttp_port = 7654\n").unwrap();
+    assert_eq!(shared.bot.separate_listener(&shared.daemon), None);
+    let disabled: Config = toml::from_str("[bot]\nhttp_port = 8443\n").unwrap();
+    assert_eq!(disabled.bot.separate_listener(&disabled.daemon), None);
+}
+
+#[test]
+fn test_daemon_observe_mode() {
+    let config: Config = toml::from_str(
+        r#"
+[daemon]
+mode = "observe"
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.daemon.mode, DaemonMode::Observe);
+    assert_eq!(Config::default().daemon.mode, DaemonMode::Active);
+    assert!(toml::from_str::<Config>("[daemon]\nmode = \"passive\"").is_err());
+}
+
+#[test]
+fn test_metrics_cost_settings() {
+    let config: Config = toml::from_str(
+        r#"
+[metrics]
+hourly_rate = 90.0
+currency = "EUR"
+
+[metrics.model_costs]
+"claude-sonnet-4" = { input_per_1k = 0.003, output_per_1k = 0.015 }
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.metrics.hourly_rate, Some(90.0));
+    assert_eq!(config.metrics.currency, "EUR");
+    assert_eq!(config.metrics.manual_restart_time_seconds, 300);
+    assert_eq!(
+        config.metrics.model_costs["claude-sonnet-4"],
+        ModelCost {
+            input_per_1k: 0.003,
+            output_per_1k: 0.015,
+        }
+    );
+
+    let default = Config::default();
+    assert_eq!(default.metrics.hourly_rate, None);
+    assert_eq!(default.metrics.currency, "USD");
+}
+
+#[test]
+fn test_schedules() {
+    let config: Config = toml::from_str(
+        r#"
+[[schedules]]
+name = "ci-triage"
+weekdays = "06:00"
+timezone = "utc"
+workdir = "ci"
+prompt = "Triage the CI failures since {date}"
+
+[[schedules]]
+name = "ci-triage"
+cron = "0 6 * * *"
+daily = "06:00"
+prompt = " "
+assistant = "emacs"
+"#,
+    )
+    .unwrap();
+    let first = &config.schedules[0];
+    assert_eq!(first.weekdays.as_deref(), Some("06:00"));
+    assert_eq!(first.timezone, ScheduleTimezone::Utc);
+    assert_eq!(first.workdir, PathBuf::from("ci"));
+    assert!(first.enabled && first.skip_if_active && !first.catch_up);
+
+    let mut fields: Vec<String> = validate_config(&config)
+        .errors
+        .into_iter()
+        .map(|error| error.field)
+        .filter(|field| field.starts_with("schedules"))
+        .collect();
+    fields.sort();
+    assert_eq!(
+        fields,
+        [
+            "schedules[1].assistant",
+            "schedules[1].cron",
+            "schedules[1].name",
+            "schedules[1].prompt",
+        ]
+    );
+}
+
+#[test]
+fn test_daemon_reconcile() {
+    let config: Config = toml::from_str(
+        r#"
+[daemon.reconcile]
+missing_session = "clear"
+stale_after_secs = 0
+"#,
+    )
+    .unwrap();
+    let reconcile = &config.daemon.reconcile;
+    assert!(reconcile.enabled);
+    assert_eq!(reconcile.missing_session, MissingSessionPolicy::Clear);
+    assert!(
+        validate_config(&config)
+            .errors
+            .iter()
+            .any(|error| error.field == "daemon.reconcile.stale_after_secs")
+    );
+
+    let default = Config::default().daemon.reconcile;
+    assert_eq!(default.missing_session, MissingSessionPolicy::Flag);
+    assert_eq!(default.stale_after_secs, 86400);
+    assert!(toml::from_str::<Config>("[daemon.reconcile]\nmissing_session = \"drop\"").is_err());
+}
+
+#[test]
+fn test_daemon_http_tokens() {
+    let config: Config = toml::from_str(
+        r#"
+[[daemon.http_tokens]]
+name = "dashboard"
+token = "dash"
+scopes = ["read"]
+
+[[daemon.http_tokens]]
+name = "ops"
+token = "ops"
+scopes = ["control", "resume"]
+"#,
+    )
+    .unwrap();
+    let daemon = &config.daemon;
+    assert_eq!(
+        daemon.http_tokens[0],
+        HttpTokenConfig {
+            name: "dashboard".to_string(),
+            token: "dash".to_string(),
+            scopes: vec![HttpScope::Read],
+        }
+    );
+    assert!(validate_config(&config).errors.is_empty());
+
+    assert_eq!(daemon.http_client_token(HttpScope::Read), Some("dash"));
+    assert_eq!(daemon.http_client_token(HttpScope::Resume), Some("ops"));
+    assert_eq!(daemon.http_client_token(HttpScope::Admin), None);
+
+    assert!(
+        toml::from_str::<Config>(
+            "[[daemon.http_tokens]]\nname = \"x\"\ntoken = \"y\"\nscopes = [\"write\"]"
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_maintenance() {
+    let config: Config = toml::from_str(
+        r#"
+[maintenance]
+enabled = true
+backup_retention_days = 7
+failure_bundles_on_complete = false
+interval_secs = 0
+"#,
+    )
+    .unwrap();
+    let maintenance = &config.maintenance;
+    assert!(maintenance.enabled);
+    assert!(!maintenance.auto_approve);
+    assert_eq!(maintenance.backup_retention_days, 7);
+    assert_eq!(maintenance.incident_retention_days, 30);
+    assert!(!maintenance.failure_bundles_on_complete);
+    assert!(
+        validate_config(&config)
+            .errors
+            .iter()
+            .any(|error| error.field == "maintenance.interval_secs")
+    );
+
+    let default = Config::default().maintenance;
+    assert!(!default.enabled);
+    assert_eq!(default.safety_window_secs, 86400);
+    assert!(default.orphaned_next_steps);
+}
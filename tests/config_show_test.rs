@@ -116,3 +116,62 @@ fn test_config_show_invalid_section_fails() {
         .failure()
         .stderr(predicate::str::contains("Unknown section"));
 }
+
+#[test]
+fn test_config_show_provenance_reports_each_source() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    fs::write(
+        &config_path,
+        r#"
+[daemon]
+log_level = "debug"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "show", "--provenance", "--json"])
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .env("PALINGENESIS_HTTP_PORT", "9999")
+        .env(
+            "PALINGENESIS_SLACK_WEBHOOK_URL",
+            "https://hooks.slack.com/secret",
+        )
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let daemon = &json["daemon"];
+    assert_eq!(daemon["http_bind"]["source"], "default");
+    assert_eq!(daemon["log_level"]["value"], "debug");
+    assert_eq!(daemon["log_level"]["source"], "file");
+    assert_eq!(daemon["http_port"]["value"], 9999);
+    assert_eq!(daemon["http_port"]["source"], "env:PALINGENESIS_HTTP_PORT");
+
+    let slack = &json["notifications"]["slack"]["webhook_url"];
+    assert_eq!(slack["value"], "[redacted]");
+    assert_eq!(slack["source"], "env:PALINGENESIS_SLACK_WEBHOOK_URL");
+}
+
+#[test]
+fn test_config_show_provenance_tree() {
+    let temp = tempfile::tempdir().unwrap();
+    let config_path = temp.path().join("config.toml");
+    fs::write(&config_path, "[daemon]\nhttp_port = 7777\n").unwrap();
+
+    Command::cargo_bin("palingenesis")
+        .unwrap()
+        .args(["config", "show", "--provenance", "--section", "daemon"])
+        .env("PALINGENESIS_CONFIG", &config_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("daemon:\n"))
+        .stdout(predicate::str::contains("  http_port = 7777  # file"))
+        .stdout(predicate::str::contains(
+            "  http_bind = \"127.0.0.1\"  # default",
+        ))
+        .stdout(predicate::str::contains("monitoring").not());
+}
@@ -0,0 +1,136 @@
+#![cfg(all(unix, feature = "http-api"))]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+struct DaemonGuard(Child);
+
+impl Drop for DaemonGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn palingenesis(root: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_palingenesis"));
+    command
+        .env("PALINGENESIS_CONFIG", root.join("config.toml"))
+        .env("PALINGENESIS_STATE", root.join("state"))
+        .env("PALINGENESIS_RUNTIME", root.join("run"))
+        .stdin(Stdio::null());
+    command
+}
+
+fn start_daemon(root: &Path, port: u16) -> DaemonGuard {
+    let sessions = root.join("sessions");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::write(
+        root.join("config.toml"),
+        format!(
+            "[daemon]\nhttp_enabled = true\nhttp_bind = \"127.0.0.1\"\nhttp_port = {port}\n\n[monitoring]\nsession_dir = {:?}\n",
+            sessions.display().to_string()
+        ),
+    )
+    .unwrap();
+    let child = palingenesis(root)
+        .args(["daemon", "start", "--foreground"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    DaemonGuard(child)
+}
+
+fn wait_for_socket(root: &Path) {
+    let socket = root.join("run").join("palingenesis.sock");
+    let deadline = Instant::now() + Duration::from_secs(20);
+    loop {
+        if let Ok(mut stream) = UnixStream::connect(&socket) {
+            stream.write_all(b"STATUS\n").unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            return;
+        }
+        assert!(Instant::now() < deadline, "daemon socket not ready");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn post(port: u16, path: &str) -> (String, String) {
+    let deadline = Instant::now() + Duration::from_secs(20);
+    let mut stream = loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => break stream,
+            Err(err) if Instant::now() > deadline => panic!("HTTP API not ready: {err}"),
+            Err(_) => std::thread::sleep(Duration::from_millis(100)),
+        }
+    };
+    let local = stream.local_addr().unwrap().to_string();
+    write!(
+        stream,
+        "POST {path} HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    (response, local)
+}
+
+fn control_initiators(root: &Path, want: usize) -> Vec<Value> {
+    let audit = root.join("state").join("audit.jsonl");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let initiators: Vec<Value> = std::fs::read_to_string(&audit)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter(|entry| entry["event_type"] == "control_action")
+            .map(|entry| entry["metadata"]["initiator"].clone())
+            .collect();
+        if initiators.len() >= want {
+            return initiators;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "expected {want} control_action entries, found {initiators:?}"
+        );
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn control_actions_record_their_initiator() {
+    let temp = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let _daemon = start_daemon(temp.path(), port);
+    wait_for_socket(temp.path());
+
+    let output = palingenesis(temp.path()).arg("pause").output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let (response, local) = post(port, "/api/v1/resume");
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    let initiators = control_initiators(temp.path(), 2);
+    let uid = unsafe { libc::getuid() };
+    assert_eq!(initiators[0], serde_json::json!({"via": "cli", "uid": uid}));
+    assert_eq!(
+        initiators[1],
+        serde_json::json!({"via": "http", "remote_addr": local})
+    );
+}
@@ -0,0 +1,249 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{TimeDelta, Utc};
+use palingenesis::http::EventBroadcaster;
+use palingenesis::monitor::classifier::StopReason;
+use palingenesis::notify::NotificationEvent;
+use palingenesis::resume::{
+    CrossHostLease, LeaseAcquisition, LeaseManager, LeaseRecord, ResumeContext, ResumeError,
+    ResumeOutcome, ResumeStrategy,
+};
+
+struct Workspace {
+    _dir: tempfile::TempDir,
+    session: PathBuf,
+}
+
+impl Workspace {
+    /// A session in a directory both "machines" see.
+    fn new() -> Self {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let session = dir.path().join("session.md");
+        std::fs::write(&session, "# Session\n").unwrap();
+        Self { _dir: dir, session }
+    }
+
+    fn lease_path(&self) -> PathBuf {
+        LeaseManager::lease_path(&self.session)
+    }
+
+    fn lease_dir(&self) -> PathBuf {
+        self.lease_path().parent().unwrap().to_path_buf()
+    }
+}
+
+fn machine(host: &str) -> LeaseManager {
+    LeaseManager::new()
+        .with_host(host)
+        .with_ttl(Duration::from_secs(60))
+        .with_settle_delay(Duration::ZERO)
+}
+
+fn record(host: &str, renewed_ago_secs: i64) -> LeaseRecord {
+    let at = Utc::now() - TimeDelta::seconds(renewed_ago_secs);
+    LeaseRecord {
+        host: host.to_string(),
+        pid: 4242,
+        acquired_at: at,
+        renewed_at: at,
+        ttl_secs: 60,
+    }
+}
+
+fn write_lease(path: &Path, record: &LeaseRecord) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, serde_json::to_vec(record).unwrap()).unwrap();
+}
+
+fn read_lease(path: &Path) -> LeaseRecord {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn fresh_lease_from_another_host_is_deferred_to() {
+    let workspace = Workspace::new();
+
+    let LeaseAcquisition::Acquired(lease) =
+        machine("laptop").acquire(&workspace.session).await.unwrap()
+    else {
+        panic!("first acquirer should get the lease");
+    };
+    assert_eq!(read_lease(&workspace.lease_path()).host, "laptop");
+
+    match machine("desktop")
+        .acquire(&workspace.session)
+        .await
+        .unwrap()
+    {
+        LeaseAcquisition::Held(holder) => assert_eq!(holder.host, "laptop"),
+        LeaseAcquisition::Acquired(_) => panic!("lease taken twice"),
+    }
+
+    lease.release();
+    assert!(!workspace.lease_path().exists());
+    assert!(matches!(
+        machine("desktop")
+            .acquire(&workspace.session)
+            .await
+            .unwrap(),
+        LeaseAcquisition::Acquired(_)
+    ));
+}
+
+#[tokio::test]
+async fn stale_lease_is_broken() {
+    let workspace = Workspace::new();
+    write_lease(&workspace.lease_path(), &record("crashed", 120));
+
+    let LeaseAcquisition::Acquired(lease) = machine("desktop")
+        .acquire(&workspace.session)
+        .await
+        .unwrap()
+    else {
+        panic!("stale lease should be broken");
+    };
+    assert_eq!(read_lease(&workspace.lease_path()).host, "desktop");
+    assert_eq!(lease.record().host, "desktop");
+}
+
+#[tokio::test]
+async fn sync_conflict_yields_to_the_smaller_host_name() {
+    // "beta" creates its lease while "alpha"'s arrives through the sync
+    // layer as a conflict copy.
+    let workspace = Workspace::new();
+    let beta = machine("beta").with_settle_delay(Duration::from_millis(200));
+    let session = workspace.session.clone();
+    let task = tokio::spawn(async move { beta.acquire(&session).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    write_lease(
+        &workspace
+            .lease_dir()
+            .join("session.sync-conflict-20250101-000000-ABC.lease"),
+        &record("alpha", 0),
+    );
+
+    match task.await.unwrap().unwrap() {
+        LeaseAcquisition::Held(winner) => assert_eq!(winner.host, "alpha"),
+        LeaseAcquisition::Acquired(_) => panic!("beta should yield to alpha"),
+    }
+    assert!(!workspace.lease_path().exists(), "beta removed its lease");
+
+    // The other side of the same race keeps the lease.
+    let workspace = Workspace::new();
+    let alpha = machine("alpha").with_settle_delay(Duration::from_millis(200));
+    let session = workspace.session.clone();
+    let task = tokio::spawn(async move { alpha.acquire(&session).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    write_lease(
+        &workspace
+            .lease_dir()
+            .join("session (conflicted copy).lease"),
+        &record("beta", 0),
+    );
+    assert!(matches!(
+        task.await.unwrap().unwrap(),
+        LeaseAcquisition::Acquired(_)
+    ));
+    assert_eq!(read_lease(&workspace.lease_path()).host, "alpha");
+}
+
+#[tokio::test]
+async fn overwritten_lease_is_restored_by_the_winner() {
+    let workspace = Workspace::new();
+    let alpha = machine("alpha").with_settle_delay(Duration::from_millis(200));
+    let session = workspace.session.clone();
+    let task = tokio::spawn(async move { alpha.acquire(&session).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    write_lease(&workspace.lease_path(), &record("beta", 0));
+
+    assert!(matches!(
+        task.await.unwrap().unwrap(),
+        LeaseAcquisition::Acquired(_)
+    ));
+    assert_eq!(read_lease(&workspace.lease_path()).host, "alpha");
+}
+
+/// Records the lease file as seen while it runs.
+struct Probe {
+    lease_path: PathBuf,
+    run_for: Duration,
+    seen: Arc<Mutex<Option<LeaseRecord>>>,
+}
+
+#[async_trait]
+impl ResumeStrategy for Probe {
+    async fn execute(&self, ctx: &ResumeContext) -> Result<ResumeOutcome, ResumeError> {
+        tokio::time::sleep(self.run_for).await;
+        *self.seen.lock().unwrap() = Some(read_lease(&self.lease_path));
+        Ok(ResumeOutcome::success(ctx.session_path.clone(), "resumed"))
+    }
+
+    fn name(&self) -> &str {
+        "probe"
+    }
+}
+
+#[tokio::test]
+async fn strategy_runs_under_a_renewed_lease_and_releases_it() {
+    let workspace = Workspace::new();
+    let seen = Arc::new(Mutex::new(None));
+    let probe = Probe {
+        lease_path: workspace.lease_path(),
+        run_for: Duration::from_millis(800),
+        seen: Arc::clone(&seen),
+    };
+    let strategy = CrossHostLease::new(
+        Box::new(probe),
+        machine("laptop").with_ttl(Duration::from_secs(1)),
+    );
+
+    let ctx = ResumeContext::new(
+        workspace.session.clone(),
+        StopReason::ContextExhausted(None),
+    );
+    let outcome = strategy.execute(&ctx).await.unwrap();
+
+    assert!(outcome.is_success());
+    let seen = seen.lock().unwrap().clone().expect("lease during the run");
+    assert_eq!(seen.host, "laptop");
+    assert!(seen.renewed_at > seen.acquired_at, "renewed while running");
+    assert!(!workspace.lease_path().exists());
+}
+
+#[tokio::test]
+async fn held_lease_skips_the_resume_and_says_where_it_runs() {
+    let workspace = Workspace::new();
+    write_lease(&workspace.lease_path(), &record("desktop", 0));
+    let seen = Arc::new(Mutex::new(None));
+    let probe = Probe {
+        lease_path: workspace.lease_path(),
+        run_for: Duration::ZERO,
+        seen: Arc::clone(&seen),
+    };
+    let events = EventBroadcaster::default();
+    let mut rx = events.subscribe();
+    let strategy =
+        CrossHostLease::new(Box::new(probe), machine("laptop")).with_event_broadcaster(events);
+
+    let ctx = ResumeContext::new(
+        workspace.session.clone(),
+        StopReason::ContextExhausted(None),
+    );
+    let outcome = strategy.execute(&ctx).await.unwrap();
+
+    match outcome {
+        ResumeOutcome::Skipped { reason } => assert_eq!(reason, "being handled by host desktop"),
+        other => panic!("unexpected outcome {other:?}"),
+    }
+    assert!(seen.lock().unwrap().is_none(), "strategy did not run");
+    match rx.try_recv().expect("deferral notification") {
+        NotificationEvent::ResumeDeferred { reason, .. } => {
+            assert_eq!(reason, "being handled by host desktop");
+        }
+        other => panic!("unexpected event {other:?}"),
+    }
+    assert_eq!(read_lease(&workspace.lease_path()).host, "desktop");
+}
@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use palingenesis::config::schema::{CustomStrategyConfig, CustomStrategyMatch, DaemonMode};
+use palingenesis::monitor::classifier::StopReason;
+use palingenesis::resume::{
+    CommandStrategy, ModeSwitch, ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy,
+    Selection, StrategySelector,
+};
+use palingenesis::state::AuditLogger;
+
+/// A plugin whose behavior is picked by its first argument.
+const PLUGIN: &str = r#"#!/bin/sh
+dir=$(dirname "$0")
+case "$1" in
+  ok)
+    cat > "$dir/request.json"
+    echo "restarting $PALINGENESIS_SESSION_PATH"
+    echo "{\"new_session_path\": \"$dir/next.md\", \"message\": \"Restarted by $PALINGENESIS_STRATEGY after $PALINGENESIS_STOP_REASON\"}"
+    ;;
+  fail)
+    echo "tmux: no server running" >&2
+    echo '{"message": "tmux server not running", "retryable": true}'
+    exit 3
+    ;;
+  malformed)
+    echo '{"new_session_path": '
+    ;;
+  slow)
+    exec sleep 5
+    ;;
+esac
+"#;
+
+struct Fixture {
+    dir: tempfile::TempDir,
+    session: PathBuf,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("plugin.sh"), PLUGIN).unwrap();
+        let session = dir.path().join("session.md");
+        std::fs::write(&session, "# Session\n").unwrap();
+        Self { dir, session }
+    }
+
+    fn strategy(&self, mode: &str) -> CommandStrategy {
+        let command = format!(
+            "sh {}/plugin.sh {mode} {{session}}",
+            self.dir.path().display()
+        );
+        CommandStrategy::new("tmux", command).with_timeout(Duration::from_secs(10))
+    }
+
+    fn context(&self) -> ResumeContext {
+        ResumeContext::new(self.session.clone(), StopReason::ContextExhausted(None))
+    }
+}
+
+#[tokio::test]
+async fn plugin_success_reports_the_new_session() {
+    let fixture = Fixture::new();
+    let state = tempfile::tempdir().expect("tempdir");
+    let audit = AuditLogger::new(state.path());
+    let strategy = fixture.strategy("ok").with_audit(audit.clone());
+
+    let outcome = strategy.execute(&fixture.context()).await.expect("outcome");
+
+    match outcome {
+        ResumeOutcome::Success {
+            session_path,
+            action,
+        } => {
+            assert_eq!(session_path, fixture.dir.path().join("next.md"));
+            assert_eq!(action, "Restarted by tmux after context_exhausted");
+        }
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+    let request: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(fixture.dir.path().join("request.json")).unwrap())
+            .unwrap();
+    assert_eq!(request["strategy"], "tmux");
+    assert_eq!(
+        request["session_path"],
+        fixture.session.display().to_string()
+    );
+    assert_eq!(request["stop_reason"], "context_exhausted");
+    assert_eq!(request["attempt_number"], 1);
+
+    let entries = audit.query().execute().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(
+        entries
+            .iter()
+            .all(|entry| entry.metadata.get("strategy") == Some(&"tmux".into()))
+    );
+    assert_eq!(
+        entries[1].action_taken,
+        "Restarted by tmux after context_exhausted"
+    );
+}
+
+#[tokio::test]
+async fn plugin_failure_uses_its_result_line() {
+    let fixture = Fixture::new();
+
+    let outcome = fixture
+        .strategy("fail")
+        .execute(&fixture.context())
+        .await
+        .expect("outcome");
+
+    match outcome {
+        ResumeOutcome::Failure { message, retryable } => {
+            assert_eq!(message, "tmux: tmux server not running");
+            assert!(retryable);
+        }
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn malformed_result_line_fails_the_resume() {
+    let fixture = Fixture::new();
+
+    let outcome = fixture
+        .strategy("malformed")
+        .execute(&fixture.context())
+        .await
+        .expect("outcome");
+
+    match outcome {
+        ResumeOutcome::Failure { message, retryable } => {
+            assert!(
+                message.starts_with("tmux: unreadable result line"),
+                "{message}"
+            );
+            assert!(!retryable);
+        }
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn slow_plugin_times_out() {
+    let fixture = Fixture::new();
+    let strategy = fixture
+        .strategy("slow")
+        .with_timeout(Duration::from_millis(200));
+
+    let started = std::time::Instant::now();
+    let result = strategy.execute(&fixture.context()).await;
+
+    assert!(matches!(result, Err(ResumeError::Timeout { .. })));
+    assert!(started.elapsed() < Duration::from_secs(4));
+}
+
+#[tokio::test]
+async fn selector_prefers_matching_plugins_and_observe_mode_holds_them() {
+    let fixture = Fixture::new();
+    let state = tempfile::tempdir().expect("tempdir");
+    let audit = AuditLogger::new(state.path());
+    let config = CustomStrategyConfig {
+        name: "tmux".to_string(),
+        command: format!(
+            "sh {}/plugin.sh ok {{session}}",
+            fixture.dir.path().display()
+        ),
+        timeout_secs: 10,
+        matches: CustomStrategyMatch {
+            stop_reasons: vec!["context_exhausted".to_string()],
+            session: Some(format!("{}/*.md", fixture.dir.path().display())),
+        },
+    };
+    let selector = StrategySelector::new()
+        .with_mode(ModeSwitch::new(DaemonMode::Observe))
+        .with_audit(audit.clone())
+        .with_custom_strategy_configs(&[config]);
+
+    let Selection::Resume(other) = selector.select_for(
+        Path::new("/work/other.md"),
+        &StopReason::ContextExhausted(None),
+    ) else {
+        panic!("expected a resume");
+    };
+    assert_eq!(other.name(), "NewSessionStrategy");
+
+    let Selection::Resume(strategy) =
+        selector.select_for(&fixture.session, &StopReason::ContextExhausted(None))
+    else {
+        panic!("expected a resume");
+    };
+    assert_eq!(strategy.name(), "tmux");
+    let outcome = strategy
+        .execute(&fixture.context())
+        .await
+        .expect("observed");
+
+    assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+    assert!(!fixture.dir.path().join("request.json").exists());
+    let actions: Vec<String> = audit
+        .query()
+        .execute()
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.action_taken)
+        .collect();
+    assert_eq!(actions, ["OBSERVE: would run tmux resume"]);
+}
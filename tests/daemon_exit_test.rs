@@ -0,0 +1,76 @@
+// Allow deprecated cargo_bin - the deprecation is for custom build-dir edge case
+// which doesn't apply to this project. See: https://docs.rs/assert_cmd
+#![allow(deprecated)]
+#![cfg(unix)]
+
+use std::path::Path;
+
+use assert_cmd::Command;
+
+fn start_foreground(root: &Path) -> Command {
+    let mut command = Command::cargo_bin("palingenesis").unwrap();
+    command
+        .args(["daemon", "start", "--foreground"])
+        .env("PALINGENESIS_CONFIG", root.join("config.toml"))
+        .env("PALINGENESIS_STATE", root.join("state"))
+        .env("PALINGENESIS_RUNTIME", root.join("run"))
+        .timeout(std::time::Duration::from_secs(30));
+    command
+}
+
+fn exit_report(stderr: &[u8]) -> serde_json::Value {
+    let stderr = String::from_utf8_lossy(stderr);
+    let last = stderr.lines().last().expect("stderr output");
+    serde_json::from_str(last).unwrap_or_else(|err| panic!("bad exit line {last:?}: {err}"))
+}
+
+#[test]
+fn invalid_config_exits_with_config_error() {
+    let temp = tempfile::tempdir().unwrap();
+    std::fs::write(temp.path().join("config.toml"), "[daemon\nlog_level = ").unwrap();
+
+    let output = start_foreground(temp.path()).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let report = exit_report(&output.stderr);
+    assert_eq!(report["exit_reason"], "config_error");
+    assert!(report["detail"].as_str().unwrap().contains("config.toml"));
+}
+
+#[test]
+fn bound_socket_exits_with_resource_conflict() {
+    let temp = tempfile::tempdir().unwrap();
+    let runtime = temp.path().join("run");
+    std::fs::create_dir_all(&runtime).unwrap();
+    let _listener = std::os::unix::net::UnixListener::bind(runtime.join("palingenesis.sock"))
+        .expect("pre-bind socket");
+
+    let output = start_foreground(temp.path()).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    let report = exit_report(&output.stderr);
+    assert_eq!(report["exit_reason"], "resource_conflict");
+    assert!(!runtime.join("palingenesis.pid").exists());
+}
+
+#[test]
+fn unwritable_state_dir_exits_with_permission_error() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    let state = temp.path().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    std::fs::set_permissions(&state, std::fs::Permissions::from_mode(0o500)).unwrap();
+    if std::fs::write(state.join("probe"), b"").is_ok() {
+        eprintln!("skipping: directory permissions are not enforced for this user");
+        return;
+    }
+
+    let output = start_foreground(temp.path()).output().unwrap();
+    std::fs::set_permissions(&state, std::fs::Permissions::from_mode(0o700)).unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let report = exit_report(&output.stderr);
+    assert_eq!(report["exit_reason"], "permission_error");
+    assert!(report["detail"].as_str().unwrap().contains("not writable"));
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "dbus")]
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use palingenesis::daemon::events::ControlEvent;
+use palingenesis::daemon::{DaemonState, Initiator};
+use palingenesis::dbus::{DaemonObject, DbusServer, introspection_xml};
+use palingenesis::http::EventBroadcaster;
+use palingenesis::notify::events::NotificationEvent;
+use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+#[zbus::proxy(
+    interface = "dev.palingenesis.Daemon1",
+    default_service = "dev.palingenesis.Daemon1",
+    default_path = "/dev/palingenesis/Daemon1"
+)]
+trait Daemon1 {
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn incident_active(&self) -> zbus::Result<bool>;
+
+    fn pause(&self) -> zbus::Result<bool>;
+
+    #[zbus(signal, name = "StateChanged")]
+    fn state_signal(&self, state: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn resume_completed(&self, session: String, success: bool) -> zbus::Result<()>;
+}
+
+/// A private bus, stopped on drop.
+struct Bus {
+    child: Child,
+    address: String,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Start `dbus-daemon` on a socket in a temporary directory, or `None` when
+/// it is not installed.
+fn private_bus() -> Option<Bus> {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("bus");
+    let address = format!("unix:path={}", socket.display());
+    let child = Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--nopidfile"])
+        .arg(format!("--address={address}"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(child) = child else {
+        eprintln!("dbus-daemon not installed; skipping");
+        return None;
+    };
+    let bus = Bus {
+        child,
+        address,
+        _dir: dir,
+    };
+    for _ in 0..100 {
+        if socket.exists() {
+            return Some(bus);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    panic!("dbus-daemon did not create {}", socket.display());
+}
+
+struct Harness {
+    state: Arc<DaemonState>,
+    events: EventBroadcaster,
+    client: zbus::Connection,
+    shutdown: CancellationToken,
+    _bus: Bus,
+}
+
+async fn serve() -> Option<Harness> {
+    let bus = private_bus()?;
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let events = EventBroadcaster::new(16);
+    let shutdown = CancellationToken::new();
+    let mut server = DbusServer::new(state.clone(), events.clone(), shutdown.clone());
+    let builder = zbus::connection::Builder::address(bus.address.as_str()).unwrap();
+    let connection = server.connect(builder).await.unwrap();
+    tokio::spawn(async move { server.forward_events(&connection).await });
+
+    let client = zbus::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    Some(Harness {
+        state,
+        events,
+        client,
+        shutdown,
+        _bus: bus,
+    })
+}
+
+fn resume_succeeded(session: &Path) -> NotificationEvent {
+    NotificationEvent::ResumeSucceeded {
+        timestamp: Utc::now(),
+        session_path: session.to_path_buf(),
+        strategy: "same_session".to_string(),
+        wait_time_secs: 30,
+        progress: None,
+        percent: None,
+        git: None,
+    }
+}
+
+#[tokio::test]
+async fn properties_reflect_daemon_status() {
+    let Some(harness) = serve().await else {
+        return;
+    };
+    let proxy = Daemon1Proxy::new(&harness.client).await.unwrap();
+    assert_eq!(proxy.state().await.unwrap(), "monitoring");
+    assert!(!proxy.incident_active().await.unwrap());
+    harness.shutdown.cancel();
+}
+
+#[tokio::test]
+async fn pause_is_attributed_to_the_calling_uid() {
+    let Some(harness) = serve().await else {
+        return;
+    };
+    let mut control = harness.state.control_events();
+    let proxy = Daemon1Proxy::new(&harness.client).await.unwrap();
+    let mut changes = proxy.receive_state_signal().await.unwrap();
+
+    assert!(proxy.pause().await.unwrap());
+    assert!(harness.state.is_paused());
+
+    let event = control.recv().await.unwrap();
+    let ControlEvent::Paused(Initiator::Dbus { uid, sender }) = &event else {
+        panic!("unexpected control event {event:?}");
+    };
+    assert_eq!(*uid, Some(unsafe { libc::getuid() }));
+    assert_eq!(
+        sender.as_deref(),
+        harness.client.unique_name().map(|name| name.as_str())
+    );
+
+    // The daemon loop announces applied control requests on the broadcaster.
+    harness
+        .events
+        .publish(event.applied_event().expect("pause is announced"));
+    let signal = tokio::time::timeout(Duration::from_secs(5), changes.next())
+        .await
+        .expect("StateChanged signal")
+        .unwrap();
+    assert_eq!(signal.args().unwrap().state, "paused");
+
+    let err = proxy.pause().await.unwrap_err();
+    assert!(err.to_string().contains("already paused"), "{err}");
+    harness.shutdown.cancel();
+}
+
+#[tokio::test]
+async fn resume_events_are_signalled() {
+    let Some(harness) = serve().await else {
+        return;
+    };
+    let proxy = Daemon1Proxy::new(&harness.client).await.unwrap();
+    let mut completed = proxy.receive_resume_completed().await.unwrap();
+    let session = PathBuf::from("/tmp/session.md");
+
+    harness.events.send(resume_succeeded(&session)).unwrap();
+    let signal = tokio::time::timeout(Duration::from_secs(5), completed.next())
+        .await
+        .expect("ResumeCompleted signal")
+        .unwrap();
+    let args = signal.args().unwrap();
+    assert_eq!(args.session, "/tmp/session.md");
+    assert!(args.success);
+    harness.shutdown.cancel();
+}
+
+#[test]
+fn introspection_xml_is_up_to_date() {
+    let object = DaemonObject::new(Arc::new(DaemonState::new_without_auto_detection()));
+    let generated = introspection_xml(&object);
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("dbus/dev.palingenesis.Daemon1.xml");
+    if std::env::var_os("PALINGENESIS_UPDATE_DBUS_XML").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+    }
+    let committed = std::fs::read_to_string(&path).unwrap_or_default();
+    assert_eq!(
+        committed, generated,
+        "regenerate with PALINGENESIS_UPDATE_DBUS_XML=1 cargo test --features dbus --test dbus_test"
+    );
+}
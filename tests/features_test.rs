@@ -0,0 +1,131 @@
+// Allow deprecated cargo_bin - the deprecation is for custom build-dir edge case
+// which doesn't apply to this project. See: https://docs.rs/assert_cmd
+#![allow(deprecated)]
+
+use std::process::Command;
+
+use assert_cmd::Command as BinCommand;
+use predicates::prelude::*;
+use serde_json::Value;
+
+const OPTIONAL_FEATURES: [&str; 6] = [
+    "bots",
+    "http-api",
+    "mcp",
+    "metrics-push",
+    "notifications",
+    "opencode-api",
+];
+
+fn manifest_package() -> Value {
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "metadata",
+            "--no-deps",
+            "--offline",
+            "--format-version",
+            "1",
+        ])
+        .arg("--manifest-path")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+        .output()
+        .expect("run cargo metadata");
+    assert!(output.status.success(), "cargo metadata failed");
+
+    let metadata: Value = serde_json::from_slice(&output.stdout).expect("metadata json");
+    metadata["packages"]
+        .as_array()
+        .expect("packages")
+        .iter()
+        .find(|package| package["name"] == "palingenesis")
+        .expect("palingenesis package")
+        .clone()
+}
+
+fn manifest_features() -> Value {
+    manifest_package()["features"].clone()
+}
+
+fn feature_list(features: &Value, name: &str) -> Vec<String> {
+    features[name]
+        .as_array()
+        .unwrap_or_else(|| panic!("feature `{name}` is declared"))
+        .iter()
+        .map(|item| item.as_str().expect("feature item").to_string())
+        .collect()
+}
+
+#[test]
+fn default_features_match_full_build() {
+    let features = manifest_features();
+    let mut default = feature_list(&features, "default");
+    default.sort();
+    assert_eq!(default, OPTIONAL_FEATURES);
+}
+
+#[test]
+fn bots_feature_enables_http_api() {
+    let features = manifest_features();
+    assert!(feature_list(&features, "bots").contains(&"http-api".to_string()));
+}
+
+#[test]
+fn optional_dependencies_are_feature_gated() {
+    let features = manifest_features();
+    for (feature, dependency) in [
+        ("http-api", "dep:axum"),
+        ("notifications", "dep:reqwest"),
+        ("opencode-api", "dep:reqwest"),
+        ("metrics-push", "dep:reqwest"),
+        ("mcp", "dep:rmcp"),
+        ("bots", "dep:ed25519-dalek"),
+    ] {
+        assert!(
+            feature_list(&features, feature).contains(&dependency.to_string()),
+            "`{feature}` should enable {dependency}"
+        );
+    }
+}
+
+#[test]
+fn feature_gated_dependencies_are_not_dev_dependencies() {
+    // Tests that need them are gated on the feature instead, so a slim
+    // build's tests compile without them.
+    let package = manifest_package();
+    let dev: Vec<&str> = package["dependencies"]
+        .as_array()
+        .expect("dependencies")
+        .iter()
+        .filter(|dependency| dependency["kind"] == "dev")
+        .filter_map(|dependency| dependency["name"].as_str())
+        .collect();
+    for dependency in ["axum", "tower", "reqwest"] {
+        assert!(
+            !dev.contains(&dependency),
+            "{dependency} is a dev-dependency"
+        );
+    }
+}
+
+#[test]
+fn version_lists_enabled_features() {
+    let mut assert = BinCommand::cargo_bin("palingenesis")
+        .unwrap()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(features: "));
+    for feature in OPTIONAL_FEATURES {
+        let enabled = match feature {
+            "bots" => cfg!(feature = "bots"),
+            "http-api" => cfg!(feature = "http-api"),
+            "mcp" => cfg!(feature = "mcp"),
+            "metrics-push" => cfg!(feature = "metrics-push"),
+            "notifications" => cfg!(feature = "notifications"),
+            _ => cfg!(feature = "opencode-api"),
+        };
+        if enabled {
+            assert = assert.stdout(predicate::str::contains(feature));
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use palingenesis::config::schema::Config;
+use palingenesis::daemon::gc::{ArtifactGc, ArtifactKind, GcReport, PLAN_FILE, run_scheduled};
+use palingenesis::http::EventBroadcaster;
+use palingenesis::monitor::incident::{INCIDENTS_DIR, IncidentRecord};
+use palingenesis::notify::events::NotificationEvent;
+use palingenesis::resume::postmortem::{FAILURES_DIR, FailureRecord};
+use palingenesis::state::{
+    AuditEventType, AuditLogger, CompletedSession, CurrentSession, StateFile,
+};
+
+fn now() -> DateTime<Utc> {
+    Utc::now()
+}
+
+/// Set the mtime of `path` (file or directory) to `age` before now.
+fn age(path: &Path, age: TimeDelta) {
+    let modified = (now() - age).into();
+    File::open(path).unwrap().set_modified(modified).unwrap();
+}
+
+fn write_aged(path: &Path, contents: &str, file_age: TimeDelta) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+    age(path, file_age);
+}
+
+fn incident(state_dir: &Path, id: &str, session: &Path, incident_age: TimeDelta) -> PathBuf {
+    let dir = state_dir.join(INCIDENTS_DIR);
+    let record = IncidentRecord {
+        id: id.to_string(),
+        captured_at: now() - incident_age,
+        session_path: session.to_path_buf(),
+        stop_reason: "RateLimit".to_string(),
+        confidence: 0.9,
+        evidence: Vec::new(),
+        exit_code: None,
+        tail_truncated: false,
+    };
+    let snapshot = dir.join(format!("{id}.tail.txt"));
+    write_aged(&snapshot, "429 Too Many Requests", incident_age);
+    write_aged(
+        &dir.join(format!("{id}.classification.json")),
+        &serde_json::to_string(&record).unwrap(),
+        incident_age,
+    );
+    snapshot
+}
+
+fn failure_bundle(state_dir: &Path, id: &str, session: &Path, bundle_age: TimeDelta) -> PathBuf {
+    let dir = state_dir.join(FAILURES_DIR).join(id);
+    let record = FailureRecord {
+        id: id.to_string(),
+        captured_at: now() - bundle_age,
+        session_path: session.to_path_buf(),
+        argv: vec!["claude".to_string()],
+        exit_code: Some(1),
+        signal: None,
+        stop_reason: None,
+        evidence: Vec::new(),
+        stopped: None,
+        stdout_truncated: false,
+        stderr_truncated: false,
+    };
+    write_aged(
+        &dir.join("failure.json"),
+        &serde_json::to_string(&record).unwrap(),
+        bundle_age,
+    );
+    write_aged(&dir.join("stderr.log"), "error", bundle_age);
+    age(&dir, bundle_age);
+    dir
+}
+
+/// Paths of the artifacts in [`aged_tree`].
+struct Tree {
+    state_dir: PathBuf,
+    old_backup: PathBuf,
+    recent_backup: PathBuf,
+    old_incident: PathBuf,
+    active_incident: PathBuf,
+    old_bundle: PathBuf,
+    fresh_bundle: PathBuf,
+    orphaned_next_step: PathBuf,
+    live_next_step: PathBuf,
+}
+
+/// A completed session with backups 40 and 10 days old and two failure
+/// bundles (one written an hour ago), incidents 40 days old for it and for
+/// the active session, and a Next-step.md in a directory whose only session
+/// is gone.
+fn aged_tree(root: &Path) -> (Tree, StateFile) {
+    let state_dir = root.join("state");
+    let project = root.join("project");
+    let done = project.join("done.md");
+    let active = project.join("active.md");
+    let gone = root.join("old-project").join("gone.md");
+    write_aged(&done, "---\nstatus: complete\n---\n", TimeDelta::days(40));
+    write_aged(
+        &active,
+        "---\nstatus: in-progress\n---\n",
+        TimeDelta::zero(),
+    );
+
+    let tree = Tree {
+        old_backup: project.join("done-backup-20250101-000000.md"),
+        recent_backup: project.join("done-backup-20250201-000000.md"),
+        old_incident: incident(&state_dir, "old-done", &done, TimeDelta::days(40)),
+        active_incident: incident(&state_dir, "old-active", &active, TimeDelta::days(40)),
+        old_bundle: failure_bundle(&state_dir, "old-done", &done, TimeDelta::days(2)),
+        fresh_bundle: failure_bundle(&state_dir, "fresh-done", &done, TimeDelta::hours(1)),
+        orphaned_next_step: root.join("old-project").join("Next-step.md"),
+        live_next_step: project.join("Next-step.md"),
+        state_dir,
+    };
+    write_aged(&tree.old_backup, "backup", TimeDelta::days(40));
+    write_aged(&tree.recent_backup, "backup", TimeDelta::days(10));
+    write_aged(&tree.orphaned_next_step, "next", TimeDelta::days(3));
+    write_aged(&tree.live_next_step, "next", TimeDelta::days(3));
+
+    let mut state = StateFile {
+        current_session: Some(CurrentSession {
+            path: active,
+            ..CurrentSession::default()
+        }),
+        ..StateFile::default()
+    };
+    state.record_completed_session(CompletedSession {
+        path: done,
+        determined_at: now() - TimeDelta::days(40),
+        content_len: 10,
+        steps_completed: 1,
+        last_step: None,
+        status: None,
+    });
+    state.resume_history_mut(&gone).resumed_at.push(now());
+    (tree, state)
+}
+
+fn gc(config: &Config, tree: &Tree) -> ArtifactGc {
+    ArtifactGc::new(config, &tree.state_dir).with_now(now())
+}
+
+fn paths(report: &GcReport) -> Vec<(ArtifactKind, PathBuf)> {
+    let mut paths: Vec<_> = report
+        .artifacts
+        .iter()
+        .map(|artifact| (artifact.kind, artifact.path.clone()))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[tokio::test]
+async fn plan_applies_each_retention_rule() {
+    let root = tempfile::tempdir().unwrap();
+    let (tree, state) = aged_tree(root.path());
+
+    let report = gc(&Config::default(), &tree).plan(&state).await;
+
+    assert!(report.dry_run);
+    let mut expected = vec![
+        (ArtifactKind::Backup, tree.old_backup.clone()),
+        (ArtifactKind::Incident, tree.old_incident.clone()),
+        (ArtifactKind::FailureBundle, tree.old_bundle.clone()),
+        (ArtifactKind::NextStep, tree.orphaned_next_step.clone()),
+    ];
+    expected.sort();
+    assert_eq!(paths(&report), expected);
+    assert!(tree.old_backup.exists(), "a plan deletes nothing");
+
+    let mut config = Config::default();
+    config.maintenance.backup_retention_days = 5;
+    config.maintenance.incident_retention_days = 0;
+    config.maintenance.failure_bundles_on_complete = false;
+    config.maintenance.orphaned_next_steps = false;
+    let report = gc(&config, &tree).plan(&state).await;
+    let mut expected = vec![
+        (ArtifactKind::Backup, tree.old_backup.clone()),
+        (ArtifactKind::Backup, tree.recent_backup.clone()),
+    ];
+    expected.sort();
+    assert_eq!(paths(&report), expected);
+}
+
+#[tokio::test]
+async fn safety_window_and_active_session_are_never_touched() {
+    let root = tempfile::tempdir().unwrap();
+    let (tree, state) = aged_tree(root.path());
+
+    let report = gc(&Config::default(), &tree).apply(&state).await;
+
+    assert_eq!(report.artifacts.len(), 4);
+    assert_eq!(report.held_back, 2);
+    assert!(report.failed.is_empty());
+    assert!(tree.active_incident.exists());
+    assert!(tree.fresh_bundle.exists());
+    assert!(tree.recent_backup.exists());
+    assert!(tree.live_next_step.exists());
+    assert!(!tree.old_backup.exists());
+    assert!(!tree.old_incident.exists());
+    assert!(
+        !tree
+            .state_dir
+            .join(INCIDENTS_DIR)
+            .join("old-done.classification.json")
+            .exists()
+    );
+    assert!(!tree.old_bundle.exists());
+    assert!(!tree.orphaned_next_step.exists());
+
+    // A wider window holds back the 2-day-old bundle and Next-step.md too.
+    let (tree, state) = aged_tree(&root.path().join("again"));
+    let mut config = Config::default();
+    config.maintenance.safety_window_secs = 7 * 24 * 60 * 60;
+    let report = gc(&config, &tree).plan(&state).await;
+    assert_eq!(
+        paths(&report),
+        vec![
+            (ArtifactKind::Backup, tree.old_backup.clone()),
+            (ArtifactKind::Incident, tree.old_incident.clone()),
+        ]
+    );
+    assert_eq!(report.held_back, 4);
+}
+
+#[tokio::test]
+async fn record_ids_cannot_point_outside_the_stores() {
+    let root = tempfile::tempdir().unwrap();
+    let (tree, state) = aged_tree(root.path());
+    let project = root.path().join("project");
+    let decoy = project.join("decoy.tail.txt");
+    write_aged(&decoy, "keep", TimeDelta::days(40));
+
+    let bundle_record = tree.old_bundle.join("failure.json");
+    let mut record: FailureRecord =
+        serde_json::from_str(&fs::read_to_string(&bundle_record).unwrap()).unwrap();
+    record.id = "../../project".to_string();
+    write_aged(
+        &bundle_record,
+        &serde_json::to_string(&record).unwrap(),
+        TimeDelta::days(2),
+    );
+    age(&tree.old_bundle, TimeDelta::days(2));
+    let incident_record = tree
+        .state_dir
+        .join(INCIDENTS_DIR)
+        .join("old-done.classification.json");
+    let mut record: IncidentRecord =
+        serde_json::from_str(&fs::read_to_string(&incident_record).unwrap()).unwrap();
+    record.id = "../../project/decoy".to_string();
+    write_aged(
+        &incident_record,
+        &serde_json::to_string(&record).unwrap(),
+        TimeDelta::days(40),
+    );
+
+    let report = gc(&Config::default(), &tree).apply(&state).await;
+
+    assert!(report.failed.is_empty());
+    assert!(!tree.old_bundle.exists());
+    assert!(!tree.old_incident.exists());
+    assert!(project.join("done.md").exists());
+    assert!(decoy.exists());
+}
+
+#[tokio::test]
+async fn scheduled_runs_wait_for_approval() {
+    let root = tempfile::tempdir().unwrap();
+    let (tree, state) = aged_tree(root.path());
+    let config = Config::default();
+    let broadcaster = EventBroadcaster::new(16);
+    let mut events = broadcaster.subscribe();
+
+    let report = run_scheduled(&gc(&config, &tree), &state, &broadcaster).await;
+    assert!(report.dry_run);
+    assert!(tree.old_backup.exists());
+    match events.try_recv().expect("artifact_gc_pending event") {
+        NotificationEvent::ArtifactGcPending {
+            artifacts,
+            summary,
+            plan,
+            ..
+        } => {
+            assert_eq!(artifacts, 4);
+            assert_eq!(summary.len(), 4);
+            assert_eq!(plan, tree.state_dir.join(PLAN_FILE));
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    assert_eq!(
+        GcReport::load_plan(&tree.state_dir)
+            .unwrap()
+            .artifacts
+            .len(),
+        4
+    );
+
+    // The same settings are reported once.
+    run_scheduled(&gc(&config, &tree), &state, &broadcaster).await;
+    assert!(events.try_recv().is_err());
+    assert!(tree.old_backup.exists());
+
+    gc(&config, &tree).approval().save(&tree.state_dir).unwrap();
+    let report = run_scheduled(&gc(&config, &tree), &state, &broadcaster).await;
+    assert!(!report.dry_run);
+    assert_eq!(report.artifacts.len(), 4);
+    assert!(!tree.old_backup.exists());
+    assert!(events.try_recv().is_err());
+
+    let entries = AuditLogger::new(&tree.state_dir)
+        .query()
+        .event_types(vec![AuditEventType::ArtifactDeleted])
+        .execute()
+        .unwrap();
+    assert_eq!(entries.len(), 4);
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry.metadata["path"] == tree.old_backup.display().to_string())
+    );
+
+    // Changing a retention setting withdraws the approval.
+    let mut changed = config.clone();
+    changed.maintenance.backup_retention_days = 5;
+    assert!(!gc(&changed, &tree).is_approved());
+    let report = run_scheduled(&gc(&changed, &tree), &state, &broadcaster).await;
+    assert!(report.dry_run);
+    assert!(tree.recent_backup.exists());
+    assert!(matches!(
+        events.try_recv(),
+        Ok(NotificationEvent::ArtifactGcPending { artifacts: 1, .. })
+    ));
+}
+
+#[tokio::test]
+async fn auto_approve_deletes_without_asking() {
+    let root = tempfile::tempdir().unwrap();
+    let (tree, state) = aged_tree(root.path());
+    let mut config = Config::default();
+    config.maintenance.auto_approve = true;
+    let broadcaster = EventBroadcaster::new(16);
+    let mut events = broadcaster.subscribe();
+
+    let report = run_scheduled(&gc(&config, &tree), &state, &broadcaster).await;
+
+    assert!(!report.dry_run);
+    assert_eq!(report.artifacts.len(), 4);
+    assert!(!tree.old_backup.exists());
+    assert!(events.try_recv().is_err());
+}
@@ -0,0 +1,163 @@
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use palingenesis::monitor::classifier::StopReason;
+use palingenesis::resume::{
+    GitCollector, GitContext, NewSessionConfig, NewSessionStrategy, ResumeContext, ResumeError,
+    ResumeStrategy, SessionCreator,
+};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn git_available() -> bool {
+    Command::new("git").arg("--version").output().is_ok()
+}
+
+/// Throwaway repository with one commit on `trunk`.
+fn init_repo(dir: &Path) {
+    git(dir, &["init", "-q", "-b", "trunk"]);
+    git(dir, &["config", "user.email", "test@example.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    std::fs::write(dir.join("README.md"), "hello\n").expect("write readme");
+    git(dir, &["add", "README.md"]);
+    git(dir, &["commit", "-q", "-m", "init"]);
+}
+
+#[tokio::test]
+async fn captures_branch_sha_and_dirty_state() {
+    if !git_available() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let repo = tempfile::tempdir().expect("tempdir");
+    init_repo(repo.path());
+    let collector = GitCollector::new();
+
+    let clean = collector.capture(repo.path()).await;
+    let GitContext::Repo { branch, sha, dirty } = &clean else {
+        panic!("unexpected context: {clean:?}");
+    };
+    assert_eq!(branch, "trunk");
+    assert!(sha.len() >= 4 && sha.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(!dirty);
+
+    std::fs::write(repo.path().join("README.md"), "changed\n").expect("edit readme");
+    let edited = collector.capture(repo.path()).await;
+    assert!(matches!(edited, GitContext::Repo { dirty: true, .. }));
+}
+
+#[tokio::test]
+async fn non_repo_workspace_is_recorded_as_such() {
+    if !git_available() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let dir = tempfile::tempdir().expect("tempdir");
+    let context = GitCollector::new().capture(dir.path()).await;
+    assert_eq!(context, GitContext::NotARepo);
+    assert_eq!(context.describe(), "not a repo");
+}
+
+#[tokio::test]
+async fn hung_git_is_bounded_by_timeout() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let script = dir.path().join("git");
+    std::fs::write(&script, "#!/bin/sh\nsleep 30\n").expect("write script");
+    let mut perms = std::fs::metadata(&script).expect("metadata").permissions();
+    std::os::unix::fs::PermissionsExt::set_mode(&mut perms, 0o755);
+    std::fs::set_permissions(&script, perms).expect("chmod");
+
+    let started = Instant::now();
+    let context = GitCollector::new()
+        .with_program(script.display().to_string())
+        .with_timeout(Duration::from_millis(200))
+        .capture(dir.path())
+        .await;
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    match context {
+        GitContext::Unavailable { reason } => assert!(reason.contains("timed out"), "{reason}"),
+        other => panic!("unexpected context: {other:?}"),
+    }
+}
+
+struct PromptCreator {
+    prompt: Arc<Mutex<Option<String>>>,
+    session_path: PathBuf,
+}
+
+#[async_trait]
+impl SessionCreator for PromptCreator {
+    async fn create(&self, prompt: &str, _session_dir: &Path) -> Result<PathBuf, ResumeError> {
+        *self.prompt.lock().expect("prompt lock") = Some(prompt.to_string());
+        Ok(self.session_path.clone())
+    }
+}
+
+#[tokio::test]
+async fn new_session_records_git_context_in_prompt_and_audit_log() {
+    if !git_available() {
+        eprintln!("git not installed; skipping");
+        return;
+    }
+    let temp = tempfile::tempdir().expect("tempdir");
+    let state_dir = temp.path().join("state");
+    unsafe {
+        std::env::set_var("PALINGENESIS_STATE", &state_dir);
+    }
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(&repo).expect("repo dir");
+    init_repo(&repo);
+    let session_path = repo.join("session.md");
+    std::fs::write(&session_path, "session").expect("session file");
+
+    let prompt = Arc::new(Mutex::new(None));
+    let strategy = NewSessionStrategy::with_config(NewSessionConfig {
+        prompt_template: "Continue on {git_branch} at {git_sha}".to_string(),
+        enable_backup: false,
+        ..NewSessionConfig::default()
+    })
+    .with_session_creator(PromptCreator {
+        prompt: Arc::clone(&prompt),
+        session_path: repo.join("new-session.md"),
+    })
+    .with_git_collector(GitCollector::new());
+    let ctx = ResumeContext::new(session_path, StopReason::ContextExhausted(None));
+    let outcome = strategy.execute(&ctx).await.expect("outcome");
+    assert!(outcome.is_success(), "unexpected outcome: {outcome:?}");
+
+    unsafe {
+        std::env::remove_var("PALINGENESIS_STATE");
+    }
+
+    let rendered = prompt.lock().expect("prompt lock").clone().expect("prompt");
+    assert!(rendered.starts_with("Continue on trunk at "), "{rendered}");
+    assert!(!rendered.contains("{git_sha}"));
+
+    let audit = std::fs::read_to_string(state_dir.join("audit.jsonl")).expect("audit log");
+    let completed = audit
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("audit json"))
+        .find(|entry| entry["event_type"] == "resume_completed")
+        .expect("resume_completed entry");
+    assert_eq!(completed["metadata"]["git_at_stop"]["status"], "repo");
+    assert_eq!(completed["metadata"]["git_at_stop"]["branch"], "trunk");
+    // The untracked session file leaves the workspace dirty.
+    assert_eq!(completed["metadata"]["git_at_stop"]["dirty"], true);
+    assert_eq!(completed["metadata"]["git_at_resume"]["branch"], "trunk");
+}
@@ -0,0 +1,278 @@
+#![cfg(feature = "grpc")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use chrono::Utc;
+use tempfile::{TempDir, tempdir};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::Channel;
+
+use palingenesis::config::GrpcConfig;
+use palingenesis::grpc::proto::daemon_control_client::DaemonControlClient;
+use palingenesis::grpc::proto::{
+    GetHistoryRequest, GetStatusRequest, PauseRequest, ReloadRequest, ResumeRequest,
+    StreamEventsRequest, TriggerResumeRequest,
+};
+use palingenesis::grpc::{ControlService, GrpcServer};
+use palingenesis::http::EventBroadcaster;
+use palingenesis::ipc::protocol::DaemonStatus;
+use palingenesis::ipc::socket::DaemonStateAccess;
+use palingenesis::notify::events::NotificationEvent;
+use palingenesis::state::{AuditEntry, AuditEventType, AuditLogger, AuditOutcome};
+
+#[derive(Default)]
+struct MockState {
+    paused: AtomicBool,
+    new_sessions: AtomicUsize,
+    reloads: AtomicUsize,
+}
+
+impl DaemonStateAccess for MockState {
+    fn get_status(&self) -> DaemonStatus {
+        DaemonStatus {
+            state: if self.paused.load(Ordering::SeqCst) {
+                "paused".to_string()
+            } else {
+                "monitoring".to_string()
+            },
+            uptime_secs: 120,
+            current_session: None,
+            saves_count: 2,
+            total_resumes: 3,
+            time_saved_seconds: 90.0,
+            time_saved_human: None,
+            opencode_endpoint: None,
+            exclusion_patterns: vec!["**/scratch/**".to_string()],
+            watch_filter: None,
+            jobs: Vec::new(),
+            deferred_until: None,
+            deferred_reason: None,
+            quota_resume_at: None,
+            resume_counters: Vec::new(),
+            needs_attention: false,
+            instance: None,
+            clock_skew_secs: None,
+            debounce_window_ms: None,
+            state_newer_version: None,
+            opencode_version: None,
+            restart_correlation: None,
+            mode: Default::default(),
+            incident_opened_at: None,
+            state_unsaved_since: None,
+            watcher_alive: None,
+            http_listeners: None,
+        }
+    }
+
+    fn pause(&self) -> Result<(), String> {
+        if self.paused.swap(true, Ordering::SeqCst) {
+            return Err("Daemon already paused".to_string());
+        }
+        Ok(())
+    }
+
+    fn resume(&self) -> Result<(), String> {
+        if !self.paused.swap(false, Ordering::SeqCst) {
+            return Err("Daemon not paused".to_string());
+        }
+        Ok(())
+    }
+
+    fn new_session(&self) -> Result<(), String> {
+        self.new_sessions.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn reload_config(&self) -> Result<(), String> {
+        self.reloads.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+struct Harness {
+    client: DaemonControlClient<Channel>,
+    state: Arc<MockState>,
+    events: EventBroadcaster,
+    cancel: CancellationToken,
+    handle: tokio::task::JoinHandle<()>,
+    _temp: TempDir,
+}
+
+async fn start_server(audit_entries: &[AuditEntry]) -> Harness {
+    let temp = tempdir().unwrap();
+    let audit = AuditLogger::new(temp.path());
+    for entry in audit_entries {
+        audit.log(entry).unwrap();
+    }
+
+    let state = Arc::new(MockState::default());
+    let events = EventBroadcaster::new(16);
+    let cancel = CancellationToken::new();
+    let config = GrpcConfig {
+        enabled: true,
+        bind: "127.0.0.1:0".to_string(),
+        ..GrpcConfig::default()
+    };
+    let service = ControlService::new(Arc::clone(&state), events.clone()).with_audit(audit);
+    let server = GrpcServer::new(&config, cancel.clone(), service).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr: SocketAddr = listener.local_addr().unwrap();
+    let handle = tokio::spawn(async move {
+        server.serve_with_listener(listener).await.unwrap();
+    });
+
+    let client = DaemonControlClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    Harness {
+        client,
+        state,
+        events,
+        cancel,
+        handle,
+        _temp: temp,
+    }
+}
+
+async fn stop(harness: Harness) {
+    harness.cancel.cancel();
+    harness.handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_grpc_get_status() {
+    let mut harness = start_server(&[]).await;
+
+    let status = harness
+        .client
+        .get_status(GetStatusRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(status.state, "monitoring");
+    assert_eq!(status.uptime_secs, 120);
+    assert_eq!(status.total_resumes, 3);
+    assert_eq!(status.exclusion_patterns, vec!["**/scratch/**"]);
+
+    stop(harness).await;
+}
+
+#[tokio::test]
+async fn test_grpc_pause_and_resume() {
+    let mut harness = start_server(&[]).await;
+
+    let response = harness.client.pause(PauseRequest {}).await.unwrap();
+    assert!(response.into_inner().success);
+    assert!(harness.state.paused.load(Ordering::SeqCst));
+
+    let err = harness.client.pause(PauseRequest {}).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+    assert_eq!(err.message(), "Daemon already paused");
+
+    let response = harness.client.resume(ResumeRequest {}).await.unwrap();
+    assert!(response.into_inner().success);
+    assert!(!harness.state.paused.load(Ordering::SeqCst));
+
+    stop(harness).await;
+}
+
+#[tokio::test]
+async fn test_grpc_reload_and_trigger_resume() {
+    let mut harness = start_server(&[]).await;
+
+    harness.client.reload(ReloadRequest {}).await.unwrap();
+    harness
+        .client
+        .trigger_resume(TriggerResumeRequest {})
+        .await
+        .unwrap();
+
+    assert_eq!(harness.state.reloads.load(Ordering::SeqCst), 1);
+    assert_eq!(harness.state.new_sessions.load(Ordering::SeqCst), 1);
+
+    stop(harness).await;
+}
+
+#[tokio::test]
+async fn test_grpc_stream_events() {
+    let mut harness = start_server(&[]).await;
+
+    let mut stream = harness
+        .client
+        .stream_events(StreamEventsRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    harness
+        .events
+        .send(NotificationEvent::DaemonStopped {
+            timestamp: Utc::now(),
+            reason: "shutdown".to_string(),
+        })
+        .unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("event not received")
+        .expect("stream ended")
+        .unwrap();
+    assert_eq!(event.event_type, "daemon_stopped");
+    assert!(event.payload_json.contains("\"reason\":\"shutdown\""));
+
+    drop(stream);
+    stop(harness).await;
+}
+
+#[tokio::test]
+async fn test_grpc_get_history() {
+    let entries = [
+        AuditEntry::new(AuditEventType::ResumeStarted, "continue")
+            .with_session("/tmp/a/session.md".into())
+            .with_stop_reason("rate_limit"),
+        AuditEntry::new(AuditEventType::ResumeCompleted, "continue")
+            .with_session("/tmp/a/session.md".into())
+            .with_outcome(AuditOutcome::Success)
+            .with_metadata("attempt", 1),
+        AuditEntry::new(AuditEventType::SessionCreated, "new_session")
+            .with_session("/tmp/b/session.md".into())
+            .with_outcome(AuditOutcome::Success),
+    ];
+    let mut harness = start_server(&entries).await;
+
+    let history = harness
+        .client
+        .get_history(GetHistoryRequest {
+            limit: 0,
+            session_path: None,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(history.entries.len(), 3);
+
+    let history = harness
+        .client
+        .get_history(GetHistoryRequest {
+            limit: 1,
+            session_path: Some("/tmp/a/session.md".to_string()),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(history.entries.len(), 1);
+    let entry = &history.entries[0];
+    assert_eq!(entry.event_type, "resume_completed");
+    assert_eq!(entry.outcome, "success");
+    assert_eq!(entry.session_path.as_deref(), Some("/tmp/a/session.md"));
+    assert!(entry.metadata_json.contains("\"attempt\":1"));
+
+    stop(harness).await;
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "bots")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use palingenesis::config::schema::Config;
+use palingenesis::daemon::state::DaemonState;
+use palingenesis::http::{EventBroadcaster, HttpChange, HttpSupervisor};
+use palingenesis::ipc::protocol::HttpListenerStatus;
+use palingenesis::ipc::socket::DaemonStateAccess;
+use reqwest::{Method, StatusCode};
+use tokio_util::sync::CancellationToken;
+
+const TOKEN: &str = "s3cret";
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn split_config(api_port: u16, bot_port: u16) -> Config {
+    let mut config = Config::default();
+    config.daemon.http_enabled = true;
+    config.daemon.http_bind = "127.0.0.1".to_string();
+    config.daemon.http_port = api_port;
+    config.daemon.http_token = Some(TOKEN.to_string());
+    config.bot.enabled = true;
+    config.bot.http_port = Some(bot_port);
+    config
+}
+
+async fn started(state: &Arc<DaemonState>, config: &Config) -> HttpSupervisor {
+    let supervisor =
+        HttpSupervisor::new(state, EventBroadcaster::default(), CancellationToken::new())
+            .with_drain_timeout(Duration::from_secs(1));
+    supervisor.start(config).await.unwrap();
+    supervisor
+}
+
+async fn status(method: Method, addr: SocketAddr, path: &str) -> StatusCode {
+    reqwest::Client::new()
+        .request(method, format!("http://{addr}{path}"))
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn bot_routes_and_control_api_are_served_apart() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let supervisor = started(&state, &split_config(free_port(), free_port())).await;
+    let api = supervisor.local_addr().await.unwrap();
+    let bots = supervisor.bot_addr().await.unwrap();
+    assert_ne!(api, bots);
+
+    assert_eq!(status(Method::GET, api, "/health").await, StatusCode::OK);
+    assert_eq!(status(Method::GET, bots, "/health").await, StatusCode::OK);
+
+    assert_eq!(
+        status(Method::GET, api, "/api/v1/status").await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(Method::GET, bots, "/api/v1/status").await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        status(Method::POST, bots, "/api/v1/pause").await,
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(
+        status(Method::POST, api, "/api/v1/bot/slack").await,
+        StatusCode::NOT_FOUND
+    );
+    assert_ne!(
+        status(Method::POST, bots, "/api/v1/bot/slack").await,
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(
+        state.get_status().http_listeners,
+        Some(HttpListenerStatus {
+            api: api.to_string(),
+            bots: Some(bots.to_string()),
+        })
+    );
+    supervisor.stop().await;
+    assert_eq!(state.get_status().http_listeners, None);
+}
+
+#[tokio::test]
+async fn bot_listener_follows_config_reloads() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let api_port = free_port();
+    let mut config = split_config(api_port, api_port);
+    let supervisor = started(&state, &config).await;
+    assert_eq!(supervisor.bot_addr().await, None);
+    let api = supervisor.local_addr().await.unwrap();
+    assert_eq!(
+        status(Method::GET, api, "/api/v1/status").await,
+        StatusCode::OK
+    );
+
+    let bot_port = free_port();
+    config.bot.http_port = Some(bot_port);
+    let change = supervisor.reconcile(&config).await;
+
+    assert!(matches!(change, Some(HttpChange::Restarted(_))));
+    assert_eq!(
+        supervisor.bot_addr().await.map(|addr| addr.port()),
+        Some(bot_port)
+    );
+    supervisor.stop().await;
+}
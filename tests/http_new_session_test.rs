@@ -0,0 +1,183 @@
+#![cfg(feature = "http-api")]
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use palingenesis::config::schema::Config;
+use palingenesis::daemon::state::DaemonState;
+use palingenesis::http::{EventBroadcaster, HttpSupervisor};
+use palingenesis::resume::{ResumeError, SessionCreator};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// Starts sessions at `<dir>/created.md` once released, or fails on request.
+struct MockCreator {
+    release: Arc<Notify>,
+    fail: bool,
+}
+
+#[async_trait]
+impl SessionCreator for MockCreator {
+    async fn create(&self, prompt: &str, session_dir: &Path) -> Result<PathBuf, ResumeError> {
+        self.release.notified().await;
+        if self.fail {
+            return Err(ResumeError::CommandFailed {
+                command: "opencode new".to_string(),
+                stderr: format!("refused {prompt}"),
+                postmortem: None,
+            });
+        }
+        Ok(session_dir.join("created.md"))
+    }
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+struct Daemon {
+    supervisor: HttpSupervisor,
+    addr: SocketAddr,
+    release: Arc<Notify>,
+    sessions: tempfile::TempDir,
+}
+
+async fn start(fail: bool) -> Daemon {
+    let sessions = tempfile::tempdir().unwrap();
+    std::fs::create_dir(sessions.path().join("project")).unwrap();
+    let mut config = Config::default();
+    config.daemon.http_enabled = true;
+    config.daemon.http_bind = "127.0.0.1".to_string();
+    config.daemon.http_port = free_port();
+    config.monitoring.session_dir = sessions.path().to_path_buf();
+
+    let state = Arc::new(DaemonState::new_without_auto_detection().with_config(config.clone()));
+    let release = Arc::new(Notify::new());
+    state.set_session_creator(Arc::new(MockCreator {
+        release: Arc::clone(&release),
+        fail,
+    }));
+    let supervisor = HttpSupervisor::new(
+        &state,
+        EventBroadcaster::default(),
+        CancellationToken::new(),
+    )
+    .with_drain_timeout(Duration::from_secs(1));
+    let addr = SocketAddr::from(([127, 0, 0, 1], config.daemon.http_port));
+    supervisor.start(&config).await.unwrap();
+    Daemon {
+        supervisor,
+        addr,
+        release,
+        sessions,
+    }
+}
+
+async fn submit(addr: SocketAddr, body: Value) -> (StatusCode, Option<String>, Value) {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/api/v1/new-session"))
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    let status = response.status();
+    let location = response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .map(|value| value.to_str().unwrap().to_string());
+    (status, location, response.json().await.unwrap())
+}
+
+async fn get(addr: SocketAddr, path: &str) -> (StatusCode, Value) {
+    let response = reqwest::get(format!("http://{addr}{path}")).await.unwrap();
+    (response.status(), response.json().await.unwrap())
+}
+
+async fn poll_until_finished(addr: SocketAddr, location: &str) -> Value {
+    for _ in 0..100 {
+        let (status, body) = get(addr, location).await;
+        assert_eq!(status, StatusCode::OK);
+        let state = body["job"]["state"].as_str().unwrap();
+        if state == "succeeded" || state == "failed" {
+            return body["job"].clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("job at {location} did not finish");
+}
+
+#[tokio::test]
+async fn new_session_runs_as_a_job_that_can_be_polled() {
+    let daemon = start(false).await;
+
+    let (status, location, body) = submit(
+        daemon.addr,
+        json!({"version": 1, "prompt": "continue", "workdir": "project"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let job_id = body["job_id"].as_str().unwrap();
+    let location = location.unwrap();
+    assert_eq!(location, format!("/api/v1/jobs/{job_id}"));
+
+    let (status, pending) = get(daemon.addr, &location).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(matches!(
+        pending["job"]["state"].as_str(),
+        Some("pending" | "running")
+    ));
+
+    daemon.release.notify_one();
+    let job = poll_until_finished(daemon.addr, &location).await;
+    assert_eq!(job["state"], "succeeded");
+    assert_eq!(job["outcome"]["outcome"], "success");
+    let expected = daemon
+        .sessions
+        .path()
+        .canonicalize()
+        .unwrap()
+        .join("project/created.md");
+    assert_eq!(
+        job["outcome"]["session_path"],
+        expected.display().to_string()
+    );
+    daemon.supervisor.stop().await;
+}
+
+#[tokio::test]
+async fn failed_sessions_report_the_error() {
+    let daemon = start(true).await;
+
+    let (status, location, _) = submit(daemon.addr, json!({"prompt": "continue"})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    daemon.release.notify_one();
+
+    let job = poll_until_finished(daemon.addr, &location.unwrap()).await;
+    assert_eq!(job["state"], "failed");
+    assert!(job["error"].as_str().unwrap().contains("refused continue"));
+    assert!(job.get("outcome").is_none());
+    daemon.supervisor.stop().await;
+}
+
+#[tokio::test]
+async fn unknown_jobs_are_not_found() {
+    let daemon = start(false).await;
+
+    let (status, body) = get(daemon.addr, "/api/v1/jobs/not-a-job").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "JOB_NOT_FOUND");
+
+    let (status, _, body) = submit(daemon.addr, json!({"prompt": "go", "workdir": "../.."})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_WORKDIR");
+    daemon.supervisor.stop().await;
+}
@@ -0,0 +1,96 @@
+#![cfg(all(unix, feature = "http-api"))]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+struct DaemonGuard(Child);
+
+impl Drop for DaemonGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn write_config(root: &Path, http_enabled: bool, port: u16) {
+    let sessions = root.join("sessions");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::write(
+        root.join("config.toml"),
+        format!(
+            "[daemon]\nhttp_enabled = {http_enabled}\nhttp_bind = \"127.0.0.1\"\nhttp_port = {port}\n\n[monitoring]\nsession_dir = {:?}\n",
+            sessions.display().to_string()
+        ),
+    )
+    .unwrap();
+}
+
+fn start_daemon(root: &Path) -> DaemonGuard {
+    let child = Command::new(env!("CARGO_BIN_EXE_palingenesis"))
+        .args(["daemon", "start", "--foreground"])
+        .env("PALINGENESIS_CONFIG", root.join("config.toml"))
+        .env("PALINGENESIS_STATE", root.join("state"))
+        .env("PALINGENESIS_RUNTIME", root.join("run"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    DaemonGuard(child)
+}
+
+fn send(root: &Path, command: &str) -> String {
+    let socket = root.join("run").join("palingenesis.sock");
+    let deadline = Instant::now() + Duration::from_secs(20);
+    let mut stream = loop {
+        match UnixStream::connect(&socket) {
+            Ok(stream) => break stream,
+            Err(err) if Instant::now() > deadline => panic!("daemon socket not ready: {err}"),
+            Err(_) => std::thread::sleep(Duration::from_millis(100)),
+        }
+    };
+    stream.write_all(format!("{command}\n").as_bytes()).unwrap();
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    line.trim_end().to_string()
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn reachable(port: u16) -> bool {
+    TcpStream::connect(("127.0.0.1", port)).is_ok()
+}
+
+#[test]
+fn reload_starts_and_stops_http_api() {
+    let temp = tempfile::tempdir().unwrap();
+    let port = free_port();
+    write_config(temp.path(), false, port);
+    let _daemon = start_daemon(temp.path());
+
+    assert_eq!(send(temp.path(), "STATUS").chars().next(), Some('{'));
+    assert!(!reachable(port));
+
+    write_config(temp.path(), true, port);
+    assert_eq!(
+        send(temp.path(), "RELOAD"),
+        format!("OK: http: started on 127.0.0.1:{port}")
+    );
+    assert!(reachable(port));
+
+    write_config(temp.path(), false, port);
+    assert_eq!(send(temp.path(), "RELOAD"), "OK: http: stopped");
+    assert!(!reachable(port));
+
+    // Nothing to do when the settings are unchanged.
+    assert_eq!(send(temp.path(), "RELOAD"), "OK");
+}
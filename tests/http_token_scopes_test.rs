@@ -0,0 +1,170 @@
+#![cfg(feature = "http-api")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use palingenesis::config::schema::{Config, HttpScope, HttpTokenConfig};
+use palingenesis::daemon::events::ControlEvent;
+use palingenesis::daemon::initiator::Initiator;
+use palingenesis::daemon::state::DaemonState;
+use palingenesis::http::{EventBroadcaster, HttpSupervisor};
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn token(name: &str, scopes: &[HttpScope]) -> HttpTokenConfig {
+    HttpTokenConfig {
+        name: name.to_string(),
+        token: format!("{name}-secret"),
+        scopes: scopes.to_vec(),
+    }
+}
+
+/// An API with one token per scope.
+async fn serve(state: &Arc<DaemonState>) -> (HttpSupervisor, SocketAddr) {
+    let mut config = Config::default();
+    config.daemon.http_enabled = true;
+    config.daemon.http_port = free_port();
+    config.daemon.http_tokens = vec![
+        token("dashboard", &[HttpScope::Read]),
+        token("ops", &[HttpScope::Control]),
+        token("ci", &[HttpScope::Resume]),
+        token("root", &[HttpScope::Admin]),
+    ];
+    let supervisor =
+        HttpSupervisor::new(state, EventBroadcaster::default(), CancellationToken::new())
+            .with_drain_timeout(Duration::from_secs(1));
+    supervisor.start(&config).await.unwrap();
+    let addr = supervisor.local_addr().await.unwrap();
+    (supervisor, addr)
+}
+
+async fn send(addr: SocketAddr, method: Method, path: &str, token: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .request(method, format!("http://{addr}{path}"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn each_scope_reaches_only_its_routes() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let (supervisor, addr) = serve(&state).await;
+
+    let routes = [
+        (Method::GET, "/api/v1/status", HttpScope::Read),
+        (Method::GET, "/api/v1/metrics", HttpScope::Read),
+        (Method::GET, "/api/v1/sessions", HttpScope::Read),
+        (Method::POST, "/api/v1/pause", HttpScope::Control),
+        (Method::POST, "/api/v1/resume", HttpScope::Control),
+        (Method::POST, "/api/v1/new-session", HttpScope::Resume),
+        (
+            Method::POST,
+            "/api/v1/notifications/ntfy/rotate",
+            HttpScope::Admin,
+        ),
+        (Method::POST, "/api/v1/mode", HttpScope::Admin),
+    ];
+    let holders = [
+        ("dashboard", HttpScope::Read),
+        ("ops", HttpScope::Control),
+        ("ci", HttpScope::Resume),
+        ("root", HttpScope::Admin),
+    ];
+    for (method, path, required) in &routes {
+        for (name, held) in holders {
+            let status = send(addr, method.clone(), path, &format!("{name}-secret"))
+                .await
+                .status();
+            if held.grants(*required) {
+                assert_ne!(status, StatusCode::FORBIDDEN, "{name} on {method} {path}");
+            } else {
+                assert_eq!(status, StatusCode::FORBIDDEN, "{name} on {method} {path}");
+            }
+        }
+    }
+
+    assert_eq!(
+        send(addr, Method::GET, "/api/v1/status", "guess")
+            .await
+            .status(),
+        StatusCode::UNAUTHORIZED
+    );
+    supervisor.stop().await;
+}
+
+#[tokio::test]
+async fn insufficient_scope_names_the_required_scope() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let (supervisor, addr) = serve(&state).await;
+
+    let response = send(addr, Method::POST, "/api/v1/pause", "dashboard-secret").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["error"]["code"], "FORBIDDEN");
+    assert_eq!(body["error"]["required_scope"], "control");
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("control")
+    );
+    assert!(!state.is_paused());
+    supervisor.stop().await;
+}
+
+#[tokio::test]
+async fn only_admin_tokens_reload_or_switch_mode() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let (supervisor, addr) = serve(&state).await;
+
+    for name in ["dashboard", "ops", "ci"] {
+        for path in ["/api/v1/reload", "/api/v1/mode"] {
+            let response = send(addr, Method::POST, path, &format!("{name}-secret")).await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{name} on {path}");
+            let body: Value = response.json().await.unwrap();
+            assert_eq!(body["error"]["required_scope"], "admin", "{name} on {path}");
+        }
+    }
+
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/api/v1/mode"))
+        .bearer_auth("root-secret")
+        .json(&serde_json::json!({ "mode": "active" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    supervisor.stop().await;
+}
+
+#[tokio::test]
+async fn control_actions_are_attributed_to_the_token_name() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let mut control = state.control_events();
+    let (supervisor, addr) = serve(&state).await;
+
+    let response = send(addr, Method::POST, "/api/v1/pause", "ops-secret").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let event = tokio::time::timeout(Duration::from_secs(5), control.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let ControlEvent::Paused(Initiator::Http { token, .. }) = event else {
+        panic!("expected an HTTP pause, got {event:?}");
+    };
+    assert_eq!(token.as_deref(), Some("ops"));
+    supervisor.stop().await;
+}
@@ -0,0 +1,134 @@
+// Allow deprecated cargo_bin - the deprecation is for custom build-dir edge case
+// which doesn't apply to this project. See: https://docs.rs/assert_cmd
+#![allow(deprecated)]
+// Relies on dirs honouring XDG_* so each test gets private default paths.
+#![cfg(target_os = "linux")]
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::time::{Duration, Instant};
+
+use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin;
+
+/// The binary with every default path under `root`; instances are told apart only by name.
+fn isolated(root: &Path) -> std::process::Command {
+    let mut command = std::process::Command::new(cargo_bin("palingenesis"));
+    command
+        .env_remove("PALINGENESIS_CONFIG")
+        .env_remove("PALINGENESIS_STATE")
+        .env_remove("PALINGENESIS_INSTANCE")
+        .env("PALINGENESIS_RUNTIME", root.join("run"))
+        .env("XDG_CONFIG_HOME", root.join("config"))
+        .env("XDG_STATE_HOME", root.join("state"))
+        .env("HOME", root);
+    command
+}
+
+struct Daemon(Child);
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn start_daemon(root: &Path, instance: &str) -> Daemon {
+    let child = isolated(root)
+        .args(["--instance", instance, "daemon", "start", "--foreground"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn daemon");
+    Daemon(child)
+}
+
+fn cli(root: &Path, instance: &str) -> Command {
+    let mut command = Command::from_std(isolated(root));
+    command
+        .env("PALINGENESIS_INSTANCE", instance)
+        .timeout(Duration::from_secs(30));
+    command
+}
+
+fn wait_for(path: &Path) {
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while !path.exists() {
+        assert!(
+            Instant::now() < deadline,
+            "{} never appeared",
+            path.display()
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn status(root: &Path, instance: &str) -> serde_json::Value {
+    let output = cli(root, instance)
+        .args(["status", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "status for {instance} failed");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn two_instances_run_side_by_side() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+    let run = root.join("run");
+
+    let _work = start_daemon(root, "work");
+    let _personal = start_daemon(root, "personal");
+    wait_for(&run.join("palingenesis-work.sock"));
+    wait_for(&run.join("palingenesis-personal.sock"));
+
+    assert!(run.join("palingenesis-work.pid").exists());
+    assert!(run.join("palingenesis-personal.pid").exists());
+    assert!(!run.join("palingenesis.sock").exists());
+
+    let work = status(root, "work");
+    let personal = status(root, "personal");
+    assert_eq!(work["instance"], "work");
+    assert_eq!(personal["instance"], "personal");
+    assert_ne!(work["pid"], personal["pid"]);
+
+    cli(root, "personal").arg("pause").assert().success();
+    assert_eq!(status(root, "personal")["state"], "paused");
+    assert_eq!(status(root, "work")["state"], "monitoring");
+
+    let state: PathBuf = root.join("state").join("palingenesis");
+    assert!(state.join("work").is_dir());
+    assert!(state.join("personal").is_dir());
+
+    let output = cli(root, "work")
+        .args(["instances", "list", "--json"])
+        .output()
+        .unwrap();
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let listed: Vec<(&str, &str)> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|instance| {
+            (
+                instance["name"].as_str().unwrap(),
+                instance["state"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(listed, vec![("personal", "paused"), ("work", "monitoring")]);
+}
+
+#[test]
+fn invalid_instance_name_is_rejected() {
+    let temp = tempfile::tempdir().unwrap();
+
+    cli(temp.path(), "../escape")
+        .arg("status")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid instance name"));
+}
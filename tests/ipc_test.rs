@@ -28,6 +28,26 @@ impl DaemonStateAccess for MockState {
             total_resumes: 0,
             time_saved_seconds: 0.0,
             time_saved_human: None,
+            opencode_endpoint: None,
+            exclusion_patterns: Vec::new(),
+            watch_filter: None,
+            jobs: Vec::new(),
+            deferred_until: None,
+            deferred_reason: None,
+            quota_resume_at: None,
+            resume_counters: Vec::new(),
+            needs_attention: false,
+            instance: None,
+            clock_skew_secs: None,
+            debounce_window_ms: None,
+            state_newer_version: None,
+            opencode_version: None,
+            restart_correlation: None,
+            mode: Default::default(),
+            incident_opened_at: None,
+            state_unsaved_since: None,
+            watcher_alive: None,
+            http_listeners: None,
         }
     }
 
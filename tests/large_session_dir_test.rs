@@ -0,0 +1,153 @@
+//! Session directories with thousands of historical files.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use palingenesis::monitor::catalog::scan_session_dir;
+use palingenesis::monitor::events::WatchEvent;
+use palingenesis::monitor::filter::WatchFilter;
+use palingenesis::monitor::scan::{DirCache, WatchPlan};
+use palingenesis::monitor::watcher::SessionWatcher;
+use tokio::time::{sleep, timeout};
+use tokio_util::sync::CancellationToken;
+
+const STALE_DIRS: usize = 77;
+const ACTIVE_DIRS: [&str; 3] = ["active-0", "active-1", "active-2"];
+const FILES_PER_DIR: usize = 50;
+const DAY: u64 = 86_400;
+
+fn set_age(path: &Path, secs_ago: u64) {
+    File::open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(secs_ago))
+        .unwrap();
+}
+
+/// 4,000 session files: 77 directories untouched for a month and three
+/// active in the last hour.
+fn synthetic_tree(root: &Path) {
+    let dirs = (0..STALE_DIRS)
+        .map(|n| (format!("stale-{n:03}"), 30 * DAY + n as u64 * 60))
+        .chain(
+            ACTIVE_DIRS
+                .iter()
+                .enumerate()
+                .map(|(n, name)| (name.to_string(), 60 + n as u64 * 600)),
+        );
+    for (name, secs_ago) in dirs {
+        let dir = root.join(&name);
+        fs::create_dir_all(&dir).unwrap();
+        for file in 0..FILES_PER_DIR {
+            let path = dir.join(format!("session-{file:03}.md"));
+            fs::write(
+                &path,
+                format!("---\nstatus: in-progress\nstepsCompleted: [{file}]\n---\n"),
+            )
+            .unwrap();
+            set_age(&path, secs_ago + file as u64);
+        }
+        set_age(&dir, secs_ago);
+    }
+}
+
+fn filter() -> WatchFilter {
+    WatchFilter::new(&["md"], &["**/.git/**"])
+}
+
+#[test]
+fn selection_reads_only_recent_directories() {
+    let temp = tempfile::tempdir().unwrap();
+    synthetic_tree(temp.path());
+    let cache = DirCache::new();
+
+    let started = Instant::now();
+    let sessions = scan_session_dir(temp.path(), &filter(), 20, Some(&cache));
+    let cold = started.elapsed();
+
+    assert_eq!(sessions.len(), 20);
+    assert!(sessions[0].path.ends_with("active-0/session-000.md"));
+    assert!(
+        sessions
+            .iter()
+            .all(|session| session.path.parent().unwrap().ends_with("active-0")),
+        "the newest files all live in the newest directory"
+    );
+    assert!(
+        cold < Duration::from_secs(2),
+        "cold selection took {cold:?}"
+    );
+    assert!(
+        cache.len() <= 1 + ACTIVE_DIRS.len(),
+        "stale directories are never listed ({} cached)",
+        cache.len()
+    );
+
+    let started = Instant::now();
+    let again = scan_session_dir(temp.path(), &filter(), 20, Some(&cache));
+    let warm = started.elapsed();
+    assert_eq!(again, sessions);
+    assert!(
+        warm < Duration::from_secs(1),
+        "warm selection took {warm:?}"
+    );
+}
+
+#[test]
+fn activity_window_limits_recursive_watches() {
+    let temp = tempfile::tempdir().unwrap();
+    synthetic_tree(temp.path());
+
+    let plan = WatchPlan::new(
+        temp.path(),
+        &filter(),
+        Some(Duration::from_secs(DAY)),
+        SystemTime::now(),
+    );
+    let expected: Vec<PathBuf> = ACTIVE_DIRS
+        .iter()
+        .map(|dir| temp.path().join(dir))
+        .collect();
+    assert_eq!(plan.recursive, expected);
+    assert_eq!(plan.shallow, vec![temp.path().to_path_buf()]);
+    assert_eq!(plan.descriptor_count(), 1 + ACTIVE_DIRS.len());
+
+    let whole = WatchPlan::new(temp.path(), &filter(), None, SystemTime::now());
+    assert_eq!(whole.descriptor_count(), 1 + STALE_DIRS + ACTIVE_DIRS.len());
+}
+
+async fn next_path(receiver: &mut tokio::sync::mpsc::Receiver<WatchEvent>) -> Option<PathBuf> {
+    loop {
+        match timeout(Duration::from_secs(3), receiver.recv()).await {
+            Ok(Some(WatchEvent::FileCreated(path) | WatchEvent::FileModified(path))) => {
+                return Some(path);
+            }
+            Ok(Some(_)) => continue,
+            _ => return None,
+        }
+    }
+}
+
+#[tokio::test]
+async fn windowed_watcher_follows_active_and_new_directories() {
+    let temp = tempfile::tempdir().unwrap();
+    synthetic_tree(temp.path());
+    let watcher = SessionWatcher::with_path(temp.path().to_path_buf())
+        .with_active_window(Some(Duration::from_secs(DAY)));
+    let cancel = CancellationToken::new();
+    let mut receiver = watcher.run(cancel.clone()).await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+
+    // Outside the window: not watched.
+    fs::write(temp.path().join("stale-000/session-000.md"), "---\n---\n").unwrap();
+    let active = temp.path().join("active-1/session-000.md");
+    fs::write(&active, "---\nstepsCompleted: [1, 2]\n---\n").unwrap();
+    assert_eq!(next_path(&mut receiver).await, Some(active));
+
+    let fresh = temp.path().join("fresh");
+    fs::create_dir_all(&fresh).unwrap();
+    fs::write(fresh.join("first.md"), "---\n---\n").unwrap();
+    assert_eq!(next_path(&mut receiver).await, Some(fresh.join("first.md")));
+
+    cancel.cancel();
+}
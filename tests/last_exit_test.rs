@@ -0,0 +1,110 @@
+use std::env;
+use std::path::Path;
+use std::time::Duration;
+
+use palingenesis::daemon::Daemon;
+use palingenesis::daemon::last_exit::{self, ExitCause, LastExit, LastExitFile};
+use tokio::sync::Mutex;
+
+static ENV_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn set_env_var(key: &str, value: impl AsRef<std::ffi::OsStr>) {
+    unsafe {
+        env::set_var(key, value);
+    }
+}
+
+fn remove_env_var(key: &str) {
+    unsafe {
+        env::remove_var(key);
+    }
+}
+
+/// The record once it satisfies `ready`, polling for up to five seconds.
+async fn wait_for_record(file: &LastExitFile, ready: impl Fn(&LastExit) -> bool) -> LastExit {
+    for _ in 0..100 {
+        if let Ok(Some(exit)) = file.read() {
+            if ready(&exit) {
+                return exit;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("no matching record at {}", file.path().display());
+}
+
+fn isolate(root: &Path) {
+    let config = root.join("config.toml");
+    std::fs::write(&config, "").unwrap();
+    set_env_var("PALINGENESIS_CONFIG", &config);
+    set_env_var("PALINGENESIS_STATE", root.join("state"));
+    set_env_var("PALINGENESIS_RUNTIME", root.join("run"));
+}
+
+#[tokio::test]
+async fn restart_after_a_kill_infers_it_and_shutdown_records_the_exit() {
+    let _lock = ENV_LOCK.lock().await;
+    let temp = tempfile::tempdir().unwrap();
+    isolate(temp.path());
+    // A PID file left by a process that no longer exists, and no record.
+    std::fs::create_dir_all(temp.path().join("run")).unwrap();
+    std::fs::write(temp.path().join("run/palingenesis.pid"), "4294967295").unwrap();
+    let file = LastExitFile::in_dir(&temp.path().join("state"));
+
+    let mut daemon = Daemon::new();
+    let shutdown = daemon.shutdown_token();
+    let running = tokio::spawn(async move { daemon.run().await });
+
+    let inferred = wait_for_record(&file, |exit| exit.inferred).await;
+    assert_eq!(inferred.cause, ExitCause::Killed);
+    assert_eq!(inferred.pid, 4294967295);
+    assert!(!inferred.graceful);
+
+    shutdown.cancel();
+    running.await.unwrap().unwrap();
+
+    let exit = file.read().unwrap().expect("record written on shutdown");
+    assert_eq!(exit.cause, ExitCause::Requested);
+    assert_eq!(exit.pid, std::process::id());
+    assert_eq!(exit.daemon_state.as_deref(), Some("monitoring"));
+    assert!(exit.graceful);
+    assert!(!exit.inferred);
+    assert!(exit.uptime_secs.is_some());
+    // A graceful exit is not reported on the next start.
+    assert_eq!(last_exit::unexpected_exit(Some(exit), None), None);
+
+    for key in [
+        "PALINGENESIS_CONFIG",
+        "PALINGENESIS_STATE",
+        "PALINGENESIS_RUNTIME",
+    ] {
+        remove_env_var(key);
+    }
+}
+
+#[test]
+fn panic_hook_records_the_panic() {
+    let _lock = ENV_LOCK.blocking_lock();
+    let temp = tempfile::tempdir().unwrap();
+    let file = LastExitFile::in_dir(temp.path());
+    last_exit::install_panic_hook(file.clone());
+
+    let result = std::thread::spawn(|| panic!("simulated failure")).join();
+    assert!(result.is_err());
+
+    let exit = file.read().unwrap().expect("record written by the hook");
+    assert_eq!(exit.cause, ExitCause::Panic);
+    assert!(
+        exit.detail.starts_with("simulated failure at "),
+        "{}",
+        exit.detail
+    );
+    assert!(exit.detail.contains("last_exit_test.rs"));
+    assert!(exit.daemon_state.is_some());
+    assert!(!exit.graceful);
+    // Reported on the next start, since the daemon died with it.
+    assert_eq!(
+        last_exit::unexpected_exit(Some(exit), None).map(|exit| exit.cause),
+        Some(ExitCause::Panic)
+    );
+}
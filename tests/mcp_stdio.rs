@@ -1,3 +1,5 @@
+#![cfg(feature = "mcp")]
+
 use std::process::Stdio;
 use std::time::Duration;
 
@@ -1,9 +1,12 @@
 use std::path::Path;
 
-use palingenesis::monitor::classifier::StopReason;
+use palingenesis::monitor::classifier::{StopReason, StopReasonClassifier};
 use palingenesis::monitor::core::{Monitor, MonitorConfig};
+use palingenesis::monitor::deletion::{DeletionHandler, DeletionOutcome};
 use palingenesis::monitor::events::{MonitorEvent, WatchEvent};
+use palingenesis::monitor::incident::IncidentStore;
 use palingenesis::monitor::process::{ProcessEvent, ProcessInfo};
+use palingenesis::state::{CurrentSession, OrphanedSession, SessionStatus, StateStore, timeline};
 use tempfile::tempdir;
 use tokio::sync::mpsc;
 use tokio::time::{Duration, sleep, timeout};
@@ -74,7 +77,9 @@ async fn emits_session_stopped_after_process_stop() {
         channel_capacity: 10,
         ..MonitorConfig::default()
     };
-    let monitor = Monitor::with_config(config).expect("monitor");
+    let monitor = Monitor::with_config(config)
+        .expect("monitor")
+        .with_incident_store(IncidentStore::new(&temp.path().join("state")));
     let cancel = CancellationToken::new();
     let mut event_rx = monitor
         .run_with_receivers(cancel.clone(), watch_rx, Some(process_rx))
@@ -104,14 +109,17 @@ async fn emits_session_stopped_after_process_stop() {
         .await
         .expect("send process event");
 
-    let (reason, session) = timeout(Duration::from_millis(200), async {
+    let (reason, session, incident) = timeout(Duration::from_millis(200), async {
         loop {
             let event = event_rx.recv().await.expect("event value");
             if let MonitorEvent::SessionStopped {
-                reason, session, ..
+                reason,
+                session,
+                incident,
+                ..
             } = event
             {
-                return (reason, session);
+                return (reason, session, incident);
             }
         }
     })
@@ -120,6 +128,13 @@ async fn emits_session_stopped_after_process_stop() {
 
     assert!(matches!(reason, StopReason::UserExit(_)));
     assert!(session.is_some());
+    let incident = incident.expect("incident snapshot");
+    assert!(incident.starts_with(temp.path().join("state").join("incidents")));
+    let (_, analyzed) = StopReasonClassifier::default().classify_with_tail(&path, Some(130));
+    assert_eq!(
+        std::fs::read_to_string(&incident).expect("read snapshot"),
+        analyzed.expect("tail")
+    );
 
     cancel.cancel();
 }
@@ -165,3 +180,324 @@ async fn drops_events_when_channel_is_full() {
 
     cancel.cancel();
 }
+
+#[tokio::test]
+async fn orphaned_session_is_not_tracked_as_current() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("session.md");
+    write_session(&path);
+    let file = std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .expect("open session");
+    file.set_modified(std::time::SystemTime::now() - Duration::from_secs(60))
+        .expect("set mtime");
+
+    let store = StateStore::with_path(temp.path().join("state.json"));
+    let mut state = store.load();
+    state.record_orphan(OrphanedSession::new(path.clone(), "state store error"));
+    store.save(&state).expect("save state");
+
+    let (watch_tx, watch_rx) = mpsc::channel(4);
+    let (process_tx, process_rx) = mpsc::channel(4);
+
+    let config = MonitorConfig {
+        session_dir: temp.path().to_path_buf(),
+        channel_capacity: 10,
+        ..MonitorConfig::default()
+    };
+    let monitor = Monitor::with_config(config)
+        .expect("monitor")
+        .with_state_store(StateStore::with_path(temp.path().join("state.json")));
+    let cancel = CancellationToken::new();
+    let mut event_rx = monitor
+        .run_with_receivers(cancel.clone(), watch_rx, Some(process_rx))
+        .await;
+
+    watch_tx
+        .send(WatchEvent::FileModified(path.clone()))
+        .await
+        .expect("send watch event");
+    let _ = timeout(Duration::from_millis(200), event_rx.recv())
+        .await
+        .expect("event")
+        .expect("event value");
+
+    process_tx
+        .send(ProcessEvent::ProcessStopped {
+            info: ProcessInfo {
+                pid: 42,
+                command_line: vec!["opencode".to_string()],
+                start_time: None,
+                working_dir: None,
+            },
+            exit_code: Some(130),
+        })
+        .await
+        .expect("send process event");
+
+    let session = timeout(Duration::from_millis(200), async {
+        loop {
+            let event = event_rx.recv().await.expect("event value");
+            if let MonitorEvent::SessionStopped { session, .. } = event {
+                return session;
+            }
+        }
+    })
+    .await
+    .expect("session stopped");
+
+    assert!(session.is_none());
+    assert!(store.load().orphan(&path).is_some());
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn deleted_current_session_is_held_for_attention() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("session.md");
+    write_session(&path);
+
+    let store = StateStore::with_path(temp.path().join("state.json"));
+    let mut state = store.load();
+    state.current_session = Some(CurrentSession {
+        path: path.clone(),
+        steps_completed: vec![1],
+        last_step: 1,
+        ..CurrentSession::default()
+    });
+    store.save(&state).expect("save state");
+
+    let (watch_tx, watch_rx) = mpsc::channel(4);
+    let (_process_tx, process_rx) = mpsc::channel(4);
+
+    let config = MonitorConfig {
+        session_dir: temp.path().to_path_buf(),
+        channel_capacity: 10,
+        ..MonitorConfig::default()
+    };
+    let handler = DeletionHandler::new(StateStore::with_path(temp.path().join("state.json")))
+        .with_grace_period(Duration::from_millis(20));
+    let monitor = Monitor::with_config(config)
+        .expect("monitor")
+        .with_deletion_handler(handler);
+    let cancel = CancellationToken::new();
+    let mut event_rx = monitor
+        .run_with_receivers(cancel.clone(), watch_rx, Some(process_rx))
+        .await;
+
+    watch_tx
+        .send(WatchEvent::FileModified(path.clone()))
+        .await
+        .expect("send watch event");
+    let _ = timeout(Duration::from_millis(200), event_rx.recv())
+        .await
+        .expect("event")
+        .expect("event value");
+
+    std::fs::remove_file(&path).expect("delete session");
+    watch_tx
+        .send(WatchEvent::FileDeleted(path.clone()))
+        .await
+        .expect("send watch event");
+
+    let outcome = timeout(Duration::from_millis(500), async {
+        loop {
+            let event = event_rx.recv().await.expect("event value");
+            if let MonitorEvent::SessionDeleted { outcome, .. } = event {
+                return outcome;
+            }
+        }
+    })
+    .await
+    .expect("session deleted");
+
+    assert_eq!(outcome, DeletionOutcome::NeedsAttention);
+    let session = store.load().current_session.expect("current session");
+    assert_eq!(session.status, SessionStatus::NeedsAttention);
+    assert_eq!(session.steps_completed, vec![1]);
+
+    write_session(&path);
+    watch_tx
+        .send(WatchEvent::FileCreated(path.clone()))
+        .await
+        .expect("send watch event");
+    let _ = timeout(Duration::from_millis(200), event_rx.recv())
+        .await
+        .expect("event")
+        .expect("event value");
+    assert_eq!(
+        store
+            .load()
+            .current_session
+            .expect("current session")
+            .status,
+        SessionStatus::Active
+    );
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn step_completions_are_timestamped_after_first_sighting() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("session.md");
+    write_session(&path);
+
+    let (watch_tx, watch_rx) = mpsc::channel(4);
+    let (_process_tx, process_rx) = mpsc::channel(4);
+
+    let config = MonitorConfig {
+        session_dir: temp.path().to_path_buf(),
+        channel_capacity: 10,
+        ..MonitorConfig::default()
+    };
+    let store = StateStore::with_path(temp.path().join("state.json"));
+    let monitor = Monitor::with_config(config)
+        .expect("monitor")
+        .with_state_store(StateStore::with_path(temp.path().join("state.json")));
+    let cancel = CancellationToken::new();
+    let mut event_rx = monitor
+        .run_with_receivers(cancel.clone(), watch_rx, Some(process_rx))
+        .await;
+
+    for steps in ["[1]", "[1, 2]", "[1, 2, 3]"] {
+        std::fs::write(
+            &path,
+            format!("---\nstepsCompleted: {steps}\nstatus: in-progress\n---\n\nbody\n"),
+        )
+        .expect("write session file");
+        watch_tx
+            .send(WatchEvent::FileModified(path.clone()))
+            .await
+            .expect("send watch event");
+        let event = timeout(Duration::from_millis(200), event_rx.recv())
+            .await
+            .expect("event")
+            .expect("event value");
+        assert!(matches!(event, MonitorEvent::SessionChanged { .. }));
+    }
+
+    let state = store.load();
+    let view = timeline::build(&state, &path).expect("timeline");
+    let steps: Vec<_> = view
+        .rows
+        .iter()
+        .map(|row| (row.step, row.completed_at.is_some()))
+        .collect();
+    assert_eq!(steps, vec![(1, false), (2, true), (3, true)]);
+    assert_eq!(view.rows[1].duration_secs, None);
+    assert!(view.rows[2].duration_secs.is_some_and(|secs| secs >= 0));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn reopened_completed_session_is_classified_again() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("session.md");
+    let completed =
+        "---\nstepsCompleted: [1, 2]\nlastStep: 2\nstatus: complete\n---\n\nAll done.\n";
+    std::fs::write(&path, completed).expect("write session file");
+
+    let (watch_tx, watch_rx) = mpsc::channel(4);
+    let (process_tx, process_rx) = mpsc::channel(4);
+    let config = MonitorConfig {
+        session_dir: temp.path().to_path_buf(),
+        channel_capacity: 10,
+        ..MonitorConfig::default()
+    };
+    let store = StateStore::with_path(temp.path().join("state.json"));
+    let monitor = Monitor::with_config(config)
+        .expect("monitor")
+        .with_state_store(StateStore::with_path(temp.path().join("state.json")));
+    let cancel = CancellationToken::new();
+    let mut event_rx = monitor
+        .run_with_receivers(cancel.clone(), watch_rx, Some(process_rx))
+        .await;
+
+    let (reason, reopened) = stop_session(
+        &path,
+        completed.to_string(),
+        &watch_tx,
+        &process_tx,
+        &mut event_rx,
+    )
+    .await;
+    assert_eq!(reason, StopReason::Completed);
+    assert!(reopened.is_none());
+    assert!(store.load().completed_session(&path).is_some());
+
+    // A short rate-limit line on an otherwise unchanged session does not reopen it.
+    let (reason, reopened) = stop_session(
+        &path,
+        format!("{completed}\nrate limit reached\n"),
+        &watch_tx,
+        &process_tx,
+        &mut event_rx,
+    )
+    .await;
+    assert_eq!(reason, StopReason::Completed);
+    assert!(reopened.is_none());
+
+    // The user keeps working in the same file until a rate limit kills it.
+    let continued = format!(
+        "---\nstepsCompleted: [1, 2]\nlastStep: 2\nstatus: in-progress\n---\n\nAll done.\n\n{}\nError: rate limit reached. Retry-After: 60\n",
+        "More work on the follow-up request.\n".repeat(20)
+    );
+    let (reason, reopened) =
+        stop_session(&path, continued, &watch_tx, &process_tx, &mut event_rx).await;
+    let changes = reopened.expect("session reopened event");
+    assert!(changes.iter().any(|change| change.contains("in-progress")));
+    assert!(matches!(reason, StopReason::RateLimit(_)));
+    assert!(reason.should_auto_resume());
+    assert!(store.load().completed_session(&path).is_none());
+
+    cancel.cancel();
+}
+
+/// Write and parse the session, stop the process, and return the stop reason
+/// with the changes of any `SessionReopened` event before it.
+async fn stop_session(
+    path: &Path,
+    contents: String,
+    watch_tx: &mpsc::Sender<WatchEvent>,
+    process_tx: &mpsc::Sender<ProcessEvent>,
+    event_rx: &mut mpsc::Receiver<MonitorEvent>,
+) -> (StopReason, Option<Vec<String>>) {
+    std::fs::write(path, contents).expect("write session file");
+    watch_tx
+        .send(WatchEvent::FileModified(path.to_path_buf()))
+        .await
+        .expect("send watch event");
+    let changed = timeout(Duration::from_millis(200), event_rx.recv())
+        .await
+        .expect("event")
+        .expect("event value");
+    assert!(matches!(changed, MonitorEvent::SessionChanged { .. }));
+    process_tx
+        .send(ProcessEvent::ProcessStopped {
+            info: ProcessInfo {
+                pid: 42,
+                command_line: vec!["opencode".to_string()],
+                start_time: None,
+                working_dir: None,
+            },
+            exit_code: None,
+        })
+        .await
+        .expect("send process event");
+    timeout(Duration::from_millis(500), async {
+        let mut reopened = None;
+        loop {
+            match event_rx.recv().await.expect("event value") {
+                MonitorEvent::SessionReopened { changes, .. } => reopened = Some(changes),
+                MonitorEvent::SessionStopped { reason, .. } => return (reason, reopened),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("session stopped")
+}
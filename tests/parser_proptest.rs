@@ -0,0 +1,174 @@
+//! Property tests: parsers fed untrusted input must never panic.
+
+use std::path::Path;
+
+use palingenesis::ipc::protocol::IpcCommand;
+use palingenesis::ipc::socket::{MAX_COMMAND_LINE_BYTES, decode_command_line};
+use palingenesis::monitor::classifier::has_completion_marker;
+use palingenesis::monitor::frontmatter::{
+    MAX_FRONTMATTER_BYTES, ParseError, parse_frontmatter, parse_session_bytes,
+};
+use palingenesis::monitor::session::{Session, SessionState, StepValue};
+use palingenesis::resume::ProgressSummary;
+use palingenesis::resume::next_step;
+use palingenesis::resume::progress::MAX_LISTED_STEPS;
+use proptest::prelude::*;
+
+/// YAML-ish frontmatter lines built from the keys the session parser knows.
+fn frontmatter_line() -> impl Strategy<Value = String> {
+    let key = prop_oneof![
+        Just("stepsCompleted".to_string()),
+        Just("lastStep".to_string()),
+        Just("totalSteps".to_string()),
+        Just("status".to_string()),
+        Just("workflowType".to_string()),
+        Just("inputDocuments".to_string()),
+        Just("model".to_string()),
+        "[a-z&*!|>-]{1,8}",
+    ];
+    let value = prop_oneof![
+        any::<i64>().prop_map(|n| n.to_string()),
+        "\\[[0-9, -]{0,20}\\]",
+        "[&*][a-z]{1,4}( \\[[a-z*, ]{0,12}\\])?",
+        "[ -~]{0,24}",
+        Just("{".repeat(64)),
+    ];
+    (key, value, 0usize..6)
+        .prop_map(|(key, value, indent)| format!("{}{}: {}", " ".repeat(indent), key, value))
+}
+
+fn session_with(steps: Vec<i64>, last: Option<i64>, total: Option<i64>) -> Session {
+    Session {
+        path: "/tmp/session.md".into(),
+        state: SessionState {
+            steps_completed: steps.into_iter().map(StepValue::Integer).collect(),
+            last_step: last,
+            total_steps: total,
+            status: None,
+            workflow_type: None,
+            project_name: None,
+            input_documents: Vec::new(),
+            model: None,
+            input_tokens: None,
+            output_tokens: None,
+        },
+    }
+}
+
+proptest! {
+    #[test]
+    fn ipc_parse_never_panics(line in ".*") {
+        let _ = IpcCommand::parse(&line);
+    }
+
+    #[test]
+    fn ipc_decode_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..2048)) {
+        match decode_command_line(&bytes) {
+            Ok(cmd) => prop_assert_eq!(
+                Some(cmd),
+                IpcCommand::parse(std::str::from_utf8(&bytes).unwrap())
+            ),
+            Err(message) => prop_assert!(!message.is_empty()),
+        }
+    }
+
+    #[test]
+    fn ipc_decode_rejects_unterminated_long_lines(fill in any::<u8>()) {
+        let line = vec![fill; MAX_COMMAND_LINE_BYTES];
+        prop_assert!(decode_command_line(&line).is_err());
+    }
+
+    #[test]
+    fn session_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..4096)) {
+        let _ = parse_session_bytes(Path::new("fuzz.md"), &bytes);
+    }
+
+    #[test]
+    fn structured_frontmatter_never_panics(
+        lines in proptest::collection::vec(frontmatter_line(), 0..24)
+    ) {
+        let contents = format!("---\n{}\n---\nbody\n", lines.join("\n"));
+        if let Ok(session) = parse_session_bytes(Path::new("fuzz.md"), contents.as_bytes()) {
+            // Downstream step math must cope with whatever numbers got through.
+            let summary = ProgressSummary::from_session(&session);
+            prop_assert!(summary.next_step >= 1);
+            let _ = summary.percent();
+            let _ = summary.describe();
+        }
+    }
+
+    #[test]
+    fn progress_handles_extreme_step_numbers(
+        steps in proptest::collection::vec(any::<i64>(), 0..32),
+        last in proptest::option::of(any::<i64>()),
+        total in proptest::option::of(any::<i64>()),
+    ) {
+        let summary = ProgressSummary::from_session(&session_with(steps, last, total));
+        prop_assert!(summary.percent().is_none_or(|percent| percent <= 100));
+        prop_assert!(summary.remaining_steps().len() <= MAX_LISTED_STEPS as usize);
+        let _ = summary.render_next_step("Continue");
+    }
+
+    #[test]
+    fn next_step_parse_never_panics(content in ".{0,512}", truncated in any::<bool>()) {
+        if let Ok(info) = next_step::parse(&content, truncated) {
+            prop_assert_eq!(info.truncated, truncated);
+        }
+    }
+
+    #[test]
+    fn next_step_truncate_respects_char_boundaries(content in ".{0,256}", max in 0usize..300) {
+        let (text, cut) = next_step::truncate(&content, max);
+        prop_assert_eq!(cut, content.len() > max);
+        if !cut {
+            prop_assert_eq!(text, content);
+        }
+    }
+
+    #[test]
+    fn completion_marker_scan_never_panics(content in "(?s).{0,1024}") {
+        let _ = has_completion_marker(&content);
+    }
+}
+
+#[test]
+fn alias_bomb_is_rejected_before_parsing() {
+    let mut yaml = String::from("a: &a [\"lol\", \"lol\", \"lol\", \"lol\"]\n");
+    for level in 0..9u8 {
+        let prev = (b'a' + level) as char;
+        let next = (b'a' + level + 1) as char;
+        yaml.push_str(&format!(
+            "{next}: &{next} [*{prev}, *{prev}, *{prev}, *{prev}]\n"
+        ));
+    }
+
+    assert!(matches!(
+        parse_frontmatter(&yaml),
+        Err(ParseError::TooManyAliases { .. })
+    ));
+}
+
+#[test]
+fn oversized_frontmatter_is_rejected() {
+    let line = format!("status: {}\n", "x".repeat(1024));
+    let contents = format!(
+        "---\n{}---\n",
+        line.repeat(MAX_FRONTMATTER_BYTES / 1024 + 1)
+    );
+    assert!(matches!(
+        parse_session_bytes(Path::new("big.md"), contents.as_bytes()),
+        Err(ParseError::TooLarge { .. })
+    ));
+
+    let unterminated = format!("---\n{}", "y".repeat(MAX_FRONTMATTER_BYTES * 2));
+    assert!(matches!(
+        parse_session_bytes(Path::new("big.md"), unterminated.as_bytes()),
+        Err(ParseError::TooLarge { .. })
+    ));
+}
+
+#[test]
+fn interior_nul_and_invalid_utf8_do_not_panic() {
+    let _ = parse_session_bytes(Path::new("nul.md"), b"---\nstatus: a\0b\n---\n");
+    assert!(parse_session_bytes(Path::new("bad.md"), b"---\nstatus: \xff\n---\n").is_err());
+}
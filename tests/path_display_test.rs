@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use palingenesis::config::schema::{Config, PathDisplayMode};
+use palingenesis::i18n::Locale;
+use palingenesis::notify::NotificationEvent;
+use palingenesis::notify::message::format_event_message;
+use palingenesis::state::{CurrentSession, StateFile, StateStore};
+use palingenesis::util::path_display::{self, PathDisplay};
+
+const SESSION: &str = "/home/alice/work/client-acme/session.md";
+
+fn display(mode: PathDisplayMode) -> PathDisplay {
+    PathDisplay::new(mode)
+        .with_home(Some(PathBuf::from("/home/alice")))
+        .with_roots(vec![PathBuf::from("/home/alice/work")])
+        .with_redact_patterns(&["client-*".to_string()])
+}
+
+fn failed() -> NotificationEvent {
+    NotificationEvent::ResumeFailed {
+        timestamp: Utc::now(),
+        session_path: PathBuf::from(SESSION),
+        strategy: "new_session".to_string(),
+        error: "exit 1".to_string(),
+        progress: None,
+        percent: None,
+        incident: Some(PathBuf::from("/home/alice/.local/state/incidents/1.json")),
+        ack: None,
+    }
+}
+
+fn session_line(event: &NotificationEvent) -> String {
+    format_event_message(event, Locale::En)
+        .lines()
+        .find(|line| line.starts_with("Session: "))
+        .expect("session line")
+        .to_string()
+}
+
+#[test]
+fn notification_text_follows_each_mode() {
+    let event = failed();
+    let cases = [
+        (PathDisplayMode::Absolute, SESSION),
+        (PathDisplayMode::Relative, "client-acme/session.md"),
+        (PathDisplayMode::Basename, "session.md"),
+        (PathDisplayMode::Redacted, "~/work/[redacted]/session.md"),
+    ];
+    for (mode, expected) in cases {
+        let rendered = event.with_display_paths(&display(mode));
+        assert_eq!(
+            session_line(&rendered),
+            format!("Session: {expected}"),
+            "{mode:?}"
+        );
+    }
+
+    let redacted = event.with_display_paths(&display(PathDisplayMode::Redacted));
+    let NotificationEvent::ResumeFailed { incident, .. } = redacted.as_ref() else {
+        panic!("unexpected event");
+    };
+    assert_eq!(
+        incident.as_deref(),
+        Some(Path::new("~/.local/state/incidents/1.json"))
+    );
+}
+
+#[test]
+fn json_keeps_full_paths_unless_configured() {
+    let event = failed();
+    let relative = display(PathDisplayMode::Relative);
+
+    let json = serde_json::to_value(event.with_display_paths(&relative.for_json())).unwrap();
+    assert_eq!(json["session_path"], SESSION);
+
+    let redacting = relative.with_redact_json(true);
+    let json = serde_json::to_value(event.with_display_paths(&redacting.for_json())).unwrap();
+    assert_eq!(json["session_path"], "client-acme/session.md");
+}
+
+#[test]
+fn stored_state_and_events_keep_full_paths() {
+    let mut config = Config::default();
+    config.daemon.path_display = PathDisplayMode::Redacted;
+    config.daemon.redact_patterns = vec!["*".to_string()];
+    path_display::set_global(PathDisplay::from_config(&config));
+
+    let event = failed();
+    let rendered = event.with_display_paths(&path_display::global());
+    assert_ne!(rendered.session_path(), event.session_path());
+    assert_eq!(event.session_path(), Some(Path::new(SESSION)));
+
+    let temp = tempfile::tempdir().unwrap();
+    let store = StateStore::with_path(temp.path().join("state.json"));
+    let state = StateFile {
+        current_session: Some(CurrentSession {
+            path: PathBuf::from(SESSION),
+            ..CurrentSession::default()
+        }),
+        ..StateFile::default()
+    };
+    store.save(&state).unwrap();
+
+    let loaded = store.load();
+    assert_eq!(
+        loaded.current_session.map(|session| session.path),
+        Some(PathBuf::from(SESSION))
+    );
+}
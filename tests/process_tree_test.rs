@@ -0,0 +1,136 @@
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use palingenesis::monitor::classifier::StopReason;
+use palingenesis::resume::{CommandStrategy, ResumeContext, ResumeError, ResumeStrategy};
+use palingenesis::util::process::{self, ManagedChild, Termination};
+use tokio::process::Command;
+
+/// Starts a sleeping grandchild, records its PID and waits on it. With
+/// `stubborn`, the grandchild ignores SIGTERM.
+fn spawner(dir: &Path, stubborn: bool) -> Command {
+    let trap = if stubborn { "trap '' TERM; " } else { "" };
+    let script = format!(
+        "({trap}exec sleep 60) & echo $! > {}/grandchild.pid; echo started; wait",
+        dir.display()
+    );
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(script).stdout(Stdio::piped());
+    command
+}
+
+fn grandchild(dir: &Path) -> i32 {
+    std::fs::read_to_string(dir.join("grandchild.pid"))
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+/// Alive and not a zombie waiting for its new parent to reap it.
+fn is_running(pid: i32) -> bool {
+    match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+        Ok(stat) => stat
+            .rsplit_once(')')
+            .is_some_and(|(_, rest)| !rest.trim_start().starts_with('Z')),
+        Err(_) => nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).is_ok(),
+    }
+}
+
+async fn wait_until_gone(pid: i32) -> bool {
+    for _ in 0..40 {
+        if !is_running(pid) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn timeout_stops_the_grandchild_too() {
+    let dir = tempfile::tempdir().unwrap();
+    let child = ManagedChild::spawn(&mut spawner(dir.path(), false))
+        .unwrap()
+        .with_grace(Duration::from_secs(2));
+
+    let finished = child
+        .wait_with_output(Some(Duration::from_millis(300)))
+        .await
+        .unwrap();
+
+    assert!(finished.timed_out);
+    assert_eq!(finished.stopped, Some(Termination::Terminated));
+    assert_eq!(finished.output.stdout, b"started\n");
+    assert!(wait_until_gone(grandchild(dir.path())).await);
+}
+
+#[tokio::test]
+async fn grandchild_ignoring_sigterm_is_killed() {
+    let dir = tempfile::tempdir().unwrap();
+    let child = ManagedChild::spawn(&mut spawner(dir.path(), true))
+        .unwrap()
+        .with_grace(Duration::from_millis(300));
+
+    let finished = child
+        .wait_with_output(Some(Duration::from_millis(300)))
+        .await
+        .unwrap();
+
+    assert_eq!(finished.stopped, Some(Termination::Killed));
+    assert!(finished.stopped.unwrap().escalated());
+    assert!(wait_until_gone(grandchild(dir.path())).await);
+}
+
+#[tokio::test]
+async fn dropping_a_running_child_kills_its_tree() {
+    let dir = tempfile::tempdir().unwrap();
+    let waiting = tokio::spawn({
+        let mut command = spawner(dir.path(), true);
+        async move { process::output(&mut command, None).await }
+    });
+    let pid_file = dir.path().join("grandchild.pid");
+    while std::fs::metadata(&pid_file).map_or(true, |meta| meta.len() == 0) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // A cancelled resume drops the future waiting on its command.
+    waiting.abort();
+    let _ = waiting.await;
+
+    assert!(wait_until_gone(grandchild(dir.path())).await);
+}
+
+#[tokio::test]
+async fn custom_strategy_timeout_reports_how_the_tree_was_stopped() {
+    let dir = tempfile::tempdir().unwrap();
+    let session = dir.path().join("session.md");
+    std::fs::write(&session, "# Session\n").unwrap();
+    let strategy = CommandStrategy::new(
+        "tmux",
+        format!(
+            "(exec sleep 60) & echo $! > {}/grandchild.pid; wait",
+            dir.path().display()
+        ),
+    )
+    .with_timeout(Duration::from_millis(300));
+
+    let result = strategy
+        .execute(&ResumeContext::new(
+            session,
+            StopReason::ContextExhausted(None),
+        ))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ResumeError::Timeout {
+            stopped: Some(Termination::Terminated),
+            ..
+        })
+    ));
+    assert!(wait_until_gone(grandchild(dir.path())).await);
+}
@@ -0,0 +1,75 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use palingenesis::cli::commands::quickstart::{
+    ASSISTANT_DETECTED, CONFIG_LOADED, DAEMON_STARTED, Quickstart, SESSION_DETECTED,
+    WATCHER_STARTED,
+};
+use palingenesis::cli::progress::{Checklist, MilestoneState};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn quickstart_reports_milestones_until_session_appears() {
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().join("home");
+    std::fs::create_dir_all(&home).unwrap();
+    let config_path = temp.path().join("config").join("config.toml");
+    unsafe {
+        std::env::set_var("HOME", &home);
+        std::env::set_var("PALINGENESIS_CONFIG", &config_path);
+        std::env::set_var("PALINGENESIS_STATE", temp.path().join("state"));
+        std::env::set_var("PALINGENESIS_RUNTIME", temp.path().join("run"));
+    }
+
+    // The default config watches ~/.opencode; a session shows up mid-run.
+    let session_dir = home.join(".opencode");
+    let writer = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        std::fs::create_dir_all(&session_dir).unwrap();
+        let session = session_dir.join("session.md");
+        for step in 0..50 {
+            std::fs::write(&session, format!("---\nstepsCompleted: [{step}]\n---\n")).unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    });
+
+    let mut checklist = Checklist::new(Vec::new());
+    let session = Quickstart::new()
+        .with_timeout(Duration::from_secs(20))
+        .run(&mut checklist)
+        .await
+        .expect("quickstart runs");
+    writer.abort();
+
+    assert!(config_path.exists(), "default config is created");
+    assert_eq!(
+        session.and_then(|path| path.file_name().map(|name| name.to_owned())),
+        Some("session.md".into())
+    );
+
+    // Detection depends on the host's processes, so only its presence is checked.
+    let milestones: Vec<_> = checklist
+        .transitions()
+        .iter()
+        .filter(|transition| transition.milestone != ASSISTANT_DETECTED)
+        .map(|transition| (transition.milestone.as_str(), transition.state))
+        .collect();
+    assert_eq!(
+        milestones,
+        vec![
+            (CONFIG_LOADED, MilestoneState::Pending),
+            (CONFIG_LOADED, MilestoneState::Done),
+            (DAEMON_STARTED, MilestoneState::Pending),
+            (DAEMON_STARTED, MilestoneState::Done),
+            (WATCHER_STARTED, MilestoneState::Pending),
+            (WATCHER_STARTED, MilestoneState::Done),
+            (SESSION_DETECTED, MilestoneState::Pending),
+            (SESSION_DETECTED, MilestoneState::Done),
+        ]
+    );
+    assert!(checklist.state(ASSISTANT_DETECTED).is_some());
+
+    let output = String::from_utf8(checklist.into_inner()).unwrap();
+    assert!(output.contains("✓ Session detected ("));
+    assert!(!temp.path().join("run").join("palingenesis.pid").exists());
+}
@@ -0,0 +1,301 @@
+use std::path::Path;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use palingenesis::cli::commands::reconcile::reconcile;
+use palingenesis::config::schema::{MissingSessionPolicy, ReconcileConfig};
+use palingenesis::daemon::reconcile::{
+    CLOSED_NOTE, CorrectionKind, REPORT_FILE, ReconcileReport, Reconciler, reconcile_state,
+};
+use palingenesis::http::EventBroadcaster;
+use palingenesis::notify::events::{EventSeverity, NotificationEvent};
+use palingenesis::state::{
+    CompletedSession, CurrentSession, EscalationRecord, EscalationResolution, SessionStatus,
+    StateFile, StateHandle, StateStore,
+};
+use serde_json::Value;
+
+fn now() -> DateTime<Utc> {
+    "2026-03-20T12:00:00Z".parse().unwrap()
+}
+
+fn reconciler(missing_session: MissingSessionPolicy) -> Reconciler {
+    Reconciler::new(&ReconcileConfig {
+        missing_session,
+        ..ReconcileConfig::default()
+    })
+    .with_now(now())
+}
+
+fn current(path: &Path) -> Option<CurrentSession> {
+    Some(CurrentSession {
+        path: path.to_path_buf(),
+        ..CurrentSession::default()
+    })
+}
+
+fn kinds(report: &ReconcileReport) -> Vec<CorrectionKind> {
+    report
+        .corrections
+        .iter()
+        .map(|correction| correction.kind)
+        .collect()
+}
+
+/// State left by an unclean shutdown: the current session's file is gone,
+/// a deleted session still has records, an incident and a loop hold are
+/// two days old, and saves were counted twice.
+fn diverged_state(sessions: &Path) -> StateFile {
+    let gone = sessions.join("gone.md");
+    let deleted = sessions.join("deleted.md");
+    let kept = sessions.join("kept.md");
+    std::fs::write(&kept, "---\nstatus: in-progress\n---\n").unwrap();
+
+    let mut state = StateFile {
+        current_session: current(&gone),
+        ..StateFile::default()
+    };
+    state.resume_history_mut(&deleted).resumed_at.push(now());
+    state.record_completed_session(CompletedSession {
+        path: deleted.clone(),
+        determined_at: now(),
+        content_len: 10,
+        steps_completed: 1,
+        last_step: None,
+        status: None,
+    });
+    state.resume_history_mut(&kept).loop_suspected_at = Some(now() - TimeDelta::days(2));
+    state.record_escalation(EscalationRecord::new(
+        "inc-old",
+        kept.clone(),
+        now() - TimeDelta::days(2),
+    ));
+    state.stats.total_resumes = 3;
+    state.stats.saves_count = 6;
+    state
+}
+
+#[test]
+fn missing_current_session_is_flagged_or_cleared_per_policy() {
+    let sessions = tempfile::tempdir().unwrap();
+    let gone = sessions.path().join("gone.md");
+
+    let mut flagged = StateFile {
+        current_session: current(&gone),
+        ..StateFile::default()
+    };
+    let report = reconciler(MissingSessionPolicy::Flag).apply(&mut flagged);
+    assert_eq!(kinds(&report), [CorrectionKind::MissingSessionFlagged]);
+    assert_eq!(
+        flagged.current_session.unwrap().status,
+        SessionStatus::NeedsAttention
+    );
+
+    let mut cleared = StateFile {
+        current_session: current(&gone),
+        ..StateFile::default()
+    };
+    let report = reconciler(MissingSessionPolicy::Clear).apply(&mut cleared);
+    assert_eq!(kinds(&report), [CorrectionKind::MissingSessionCleared]);
+    assert!(cleared.current_session.is_none());
+}
+
+#[test]
+fn reappeared_session_is_monitored_again() {
+    let sessions = tempfile::tempdir().unwrap();
+    let back = sessions.path().join("back.md");
+    std::fs::write(&back, "---\n---\n").unwrap();
+    let mut state = StateFile {
+        current_session: current(&back),
+        ..StateFile::default()
+    };
+    state.current_session.as_mut().unwrap().status = SessionStatus::Missing;
+
+    let report = reconciler(MissingSessionPolicy::Flag).apply(&mut state);
+    assert_eq!(kinds(&report), [CorrectionKind::SessionReappeared]);
+    assert!(state.current_session.unwrap().status.is_active());
+}
+
+#[test]
+fn records_of_deleted_sessions_move_to_the_orphan_list() {
+    let sessions = tempfile::tempdir().unwrap();
+    let mut state = diverged_state(sessions.path());
+    let deleted = sessions.path().join("deleted.md");
+
+    let report = reconciler(MissingSessionPolicy::Flag).apply(&mut state);
+    let orphaned: Vec<_> = report
+        .corrections
+        .iter()
+        .filter(|correction| correction.kind == CorrectionKind::RecordOrphaned)
+        .collect();
+    assert_eq!(orphaned.len(), 1);
+    assert_eq!(orphaned[0].session_path.as_deref(), Some(deleted.as_path()));
+
+    assert!(state.resume_history(&deleted).is_none());
+    assert!(state.completed_session(&deleted).is_none());
+    let orphan = state.orphan(&deleted).expect("orphan recorded");
+    assert!(orphan.reason.contains("reconciliation"));
+    // The current session is flagged rather than orphaned.
+    assert!(state.orphan(&sessions.path().join("gone.md")).is_none());
+}
+
+#[test]
+fn stale_incidents_and_loop_holds_are_closed() {
+    let sessions = tempfile::tempdir().unwrap();
+    let mut state = diverged_state(sessions.path());
+    let kept = sessions.path().join("kept.md");
+
+    let report = reconciler(MissingSessionPolicy::Flag).apply(&mut state);
+    let incident = report
+        .corrections
+        .iter()
+        .find(|correction| correction.kind == CorrectionKind::IncidentClosed)
+        .expect("incident closed");
+    assert_eq!(incident.incident_id.as_deref(), Some("inc-old"));
+    assert!(kinds(&report).contains(&CorrectionKind::LoopHoldLifted));
+
+    let record = &state.escalations[0];
+    assert_eq!(record.resolved_at, Some(now()));
+    assert_eq!(record.resolution, Some(EscalationResolution::Reconciled));
+    assert!(
+        state
+            .resume_history(&kept)
+            .unwrap()
+            .loop_suspected_at
+            .is_none()
+    );
+}
+
+#[test]
+fn double_counted_stats_are_repaired() {
+    let mut state = StateFile::default();
+    state.stats.total_resumes = 3;
+    state.stats.saves_count = 6;
+    state.stats.time_saved_seconds = -20.0;
+    state.stats.last_resume = Some(now() + TimeDelta::days(1));
+
+    let report = reconciler(MissingSessionPolicy::Flag).apply(&mut state);
+    assert_eq!(kinds(&report), [CorrectionKind::StatsRepaired]);
+    assert_eq!(state.stats.saves_count, 3);
+    assert_eq!(state.stats.time_saved_seconds, 0.0);
+    assert_eq!(state.stats.last_resume, Some(now()));
+
+    // A consistent state needs nothing.
+    assert!(
+        reconciler(MissingSessionPolicy::Flag)
+            .apply(&mut state)
+            .is_empty()
+    );
+}
+
+#[test]
+fn dry_run_changes_nothing() {
+    let sessions = tempfile::tempdir().unwrap();
+    let state_dir = tempfile::tempdir().unwrap();
+    let state_file = state_dir.path().join("state.json");
+    let store = StateStore::with_path(state_file.clone());
+    store.save(&diverged_state(sessions.path())).unwrap();
+    let before = std::fs::read(&state_file).unwrap();
+
+    let reconciler = reconciler(MissingSessionPolicy::Clear);
+    let plan = reconcile(&store, state_dir.path(), &reconciler, true).unwrap();
+    assert!(plan.dry_run);
+    assert_eq!(plan.corrections.len(), 5);
+    assert_eq!(std::fs::read(&state_file).unwrap(), before);
+    assert!(!state_dir.path().join(REPORT_FILE).exists());
+    assert!(!state_dir.path().join("audit.jsonl").exists());
+
+    // The real run makes exactly the planned corrections.
+    let applied = reconcile(&store, state_dir.path(), &reconciler, false).unwrap();
+    assert!(!applied.dry_run);
+    assert_eq!(applied.corrections, plan.corrections);
+    assert_ne!(std::fs::read(&state_file).unwrap(), before);
+    assert!(store.load().current_session.is_none());
+    assert!(state_dir.path().join(REPORT_FILE).exists());
+}
+
+#[test]
+fn dry_run_of_a_missing_state_file_does_not_create_it() {
+    let state_dir = tempfile::tempdir().unwrap();
+    let state_file = state_dir.path().join("state.json");
+    let store = StateStore::with_path(state_file.clone());
+
+    let plan = reconcile(
+        &store,
+        state_dir.path(),
+        &reconciler(MissingSessionPolicy::Flag),
+        true,
+    )
+    .unwrap();
+    assert!(plan.is_empty());
+    assert!(!state_file.exists());
+}
+
+#[tokio::test]
+async fn startup_reconciliation_reports_audits_and_notifies_once() {
+    let sessions = tempfile::tempdir().unwrap();
+    let state_dir = tempfile::tempdir().unwrap();
+    let store = StateHandle::new(StateStore::with_path(state_dir.path().join("state.json")));
+    store.update(|state| *state = diverged_state(sessions.path()));
+    let broadcaster = EventBroadcaster::new(16);
+    let mut events = broadcaster.subscribe();
+
+    let report = reconcile_state(
+        &store,
+        &reconciler(MissingSessionPolicy::Flag),
+        state_dir.path(),
+        &broadcaster,
+    )
+    .unwrap();
+    assert_eq!(report.corrections.len(), 5);
+    assert_eq!(
+        store.snapshot().current_session.unwrap().status,
+        SessionStatus::NeedsAttention
+    );
+
+    let event = events.try_recv().expect("state_reconciled event");
+    assert_eq!(event.severity(), EventSeverity::Info);
+    let NotificationEvent::StateReconciled {
+        corrections,
+        report: report_path,
+        ..
+    } = event
+    else {
+        panic!("expected state_reconciled, got {event:?}");
+    };
+    assert_eq!(corrections.len(), 5);
+    assert!(events.try_recv().is_err());
+
+    let saved: ReconcileReport =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(saved, report);
+    assert_eq!(report_path, state_dir.path().join(REPORT_FILE));
+
+    let audit = std::fs::read_to_string(state_dir.path().join("audit.jsonl")).unwrap();
+    let entries: Vec<Value> = audit
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 5);
+    assert!(
+        entries
+            .iter()
+            .all(|entry| entry["event_type"] == "state_reconciled")
+    );
+    let closed = entries
+        .iter()
+        .find(|entry| entry["metadata"]["correction"] == "incident_closed")
+        .unwrap();
+    assert_eq!(closed["metadata"]["note"], CLOSED_NOTE);
+    assert_eq!(closed["metadata"]["incident_id"], "inc-old");
+
+    // A second start finds nothing left to correct and stays quiet.
+    let again = reconcile_state(
+        &store,
+        &reconciler(MissingSessionPolicy::Flag),
+        state_dir.path(),
+        &broadcaster,
+    )
+    .unwrap();
+    assert!(again.is_empty());
+    assert!(events.try_recv().is_err());
+}
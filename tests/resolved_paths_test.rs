@@ -0,0 +1,150 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use palingenesis::config::{PathEnv, PathFlags, PathSettings, Paths, ResolvedPaths};
+use palingenesis::daemon::Daemon;
+use palingenesis::daemon::pid::PidFile;
+use palingenesis::ipc::socket::IpcServer;
+use palingenesis::state::{StateFile, StateStore};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+const PATH_VARS: [&str; 6] = [
+    "PALINGENESIS_CONFIG",
+    "PALINGENESIS_STATE",
+    "PALINGENESIS_RUNTIME",
+    "PALINGENESIS_PID_FILE",
+    "PALINGENESIS_SOCKET_PATH",
+    "PALINGENESIS_LOG_FILE",
+];
+
+fn set_env_var(key: &str, value: impl AsRef<std::ffi::OsStr>) {
+    unsafe {
+        env::set_var(key, value);
+    }
+}
+
+fn clear_path_vars() {
+    for key in PATH_VARS {
+        unsafe {
+            env::remove_var(key);
+        }
+    }
+}
+
+/// A config file under `root` setting the state and runtime dirs and the log file.
+fn write_config(root: &Path) -> PathBuf {
+    let config = root.join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[daemon]\nstate_dir = \"{root}/config-state\"\nruntime_dir = \"{root}/config-run\"\n\
+             log_file = \"{root}/config-logs/daemon.log\"\n",
+            root = root.display()
+        ),
+    )
+    .unwrap();
+    config
+}
+
+#[test]
+fn config_settings_apply_when_no_env_is_set() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    clear_path_vars();
+    let temp = tempfile::tempdir().unwrap();
+    set_env_var("PALINGENESIS_CONFIG", write_config(temp.path()));
+
+    let paths = ResolvedPaths::load();
+    assert_eq!(paths.state_dir(), temp.path().join("config-state"));
+    assert_eq!(paths.runtime_dir(), temp.path().join("config-run"));
+    assert_eq!(
+        paths.socket_file(),
+        temp.path().join("config-run/palingenesis.sock")
+    );
+    assert_eq!(
+        paths.pid_file(),
+        temp.path().join("config-run/palingenesis.pid")
+    );
+    assert_eq!(paths.log_file(), temp.path().join("config-logs/daemon.log"));
+
+    clear_path_vars();
+}
+
+#[test]
+fn env_overrides_config_for_each_path_kind() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    clear_path_vars();
+    let temp = tempfile::tempdir().unwrap();
+    set_env_var("PALINGENESIS_CONFIG", write_config(temp.path()));
+    let env_dir = temp.path().join("env");
+    set_env_var("PALINGENESIS_STATE", env_dir.join("state"));
+    set_env_var("PALINGENESIS_RUNTIME", env_dir.join("run"));
+    set_env_var("PALINGENESIS_SOCKET_PATH", env_dir.join("run/custom.sock"));
+    set_env_var("PALINGENESIS_PID_FILE", env_dir.join("run/custom.pid"));
+    set_env_var("PALINGENESIS_LOG_FILE", env_dir.join("daemon.log"));
+
+    let paths = ResolvedPaths::load();
+    assert_eq!(paths.state_dir(), env_dir.join("state"));
+    assert_eq!(paths.runtime_dir(), env_dir.join("run"));
+    assert_eq!(paths.socket_file(), env_dir.join("run/custom.sock"));
+    assert_eq!(paths.pid_file(), env_dir.join("run/custom.pid"));
+    assert_eq!(paths.log_file(), env_dir.join("daemon.log"));
+
+    // The config's directories are reported as overridden.
+    let settings = PathSettings::read(paths.config_file());
+    let fields: Vec<String> = paths
+        .warnings(&settings)
+        .into_iter()
+        .map(|warning| warning.field)
+        .collect();
+    assert_eq!(
+        fields,
+        vec!["daemon.state_dir", "daemon.runtime_dir", "daemon.log_file"]
+    );
+
+    // A flag beats both.
+    let flags = PathFlags {
+        state_dir: Some(temp.path().join("flag-state")),
+        runtime_dir: None,
+    };
+    let flagged = ResolvedPaths::resolve(&settings, &PathEnv::from_process(), &flags, None);
+    assert_eq!(flagged.state_dir(), temp.path().join("flag-state"));
+    assert_eq!(flagged.runtime_dir(), env_dir.join("run"));
+
+    clear_path_vars();
+}
+
+#[test]
+fn daemon_and_cli_resolve_the_same_paths() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    clear_path_vars();
+    let temp = tempfile::tempdir().unwrap();
+    set_env_var("PALINGENESIS_CONFIG", write_config(temp.path()));
+    set_env_var("PALINGENESIS_RUNTIME", temp.path().join("env-run"));
+
+    let daemon = Daemon::new();
+    let cli = ResolvedPaths::load();
+    assert_eq!(daemon.paths(), &cli);
+    assert_eq!(daemon.paths().state_dir(), temp.path().join("config-state"));
+    assert_eq!(
+        daemon.paths().socket_file(),
+        temp.path().join("env-run/palingenesis.sock")
+    );
+
+    // Components built without the daemon land in the same places.
+    assert_eq!(Paths::socket_file(), cli.socket_file());
+    assert_eq!(IpcServer::new().path(), cli.socket_file());
+    assert_eq!(PidFile::new().path(), cli.pid_file());
+    assert_eq!(Paths::state_dir(), cli.state_dir());
+
+    let mut pid_file = PidFile::new();
+    pid_file.acquire().unwrap();
+    assert!(cli.pid_file().exists());
+    pid_file.release().unwrap();
+
+    StateStore::new().save(&StateFile::default()).unwrap();
+    assert!(cli.state_file().exists());
+
+    clear_path_vars();
+}
@@ -28,10 +28,14 @@ fn resume_context_builders_update_fields() {
         state: SessionState {
             steps_completed: Vec::new(),
             last_step: None,
+            total_steps: None,
             status: None,
             workflow_type: None,
             project_name: None,
             input_documents: Vec::new(),
+            model: None,
+            input_tokens: None,
+            output_tokens: None,
         },
     };
 
@@ -1,9 +1,18 @@
+use std::path::Path;
 use std::time::Duration;
 
+use palingenesis::config::schema::DaemonMode;
+use palingenesis::http::EventBroadcaster;
 use palingenesis::monitor::classifier::{
     RateLimitInfo, RetryAfterSource, StopReason, UserExitInfo, UserExitType,
 };
-use palingenesis::resume::{StrategySelector, UnknownStrategy};
+use palingenesis::notify::events::NotificationEvent;
+use palingenesis::resume::{
+    MaintenanceSchedule, ModeSwitch, ResumeContext, ResumeOutcome, Selection, SessionExclusions,
+    StrategySelector, UnknownStrategy,
+};
+use palingenesis::state::AuditLogger;
+use tokio_util::sync::CancellationToken;
 
 #[test]
 fn strategy_selector_maps_rate_limit_to_same_session() {
@@ -27,6 +36,15 @@ fn strategy_selector_maps_context_exhausted_to_new_session() {
     assert_eq!(strategy.name(), "NewSessionStrategy");
 }
 
+#[test]
+fn strategy_selector_maps_quota_exhausted_to_same_session() {
+    let selector = StrategySelector::new();
+    let reason = StopReason::QuotaExhausted { resets_at: None };
+
+    let strategy = selector.select(&reason).expect("strategy");
+    assert_eq!(strategy.name(), "SameSessionStrategy");
+}
+
 #[test]
 fn strategy_selector_skips_user_exit() {
     let selector = StrategySelector::new();
@@ -47,3 +65,204 @@ fn strategy_selector_allows_unknown_default_override() {
     let strategy = selector.select(&reason).expect("strategy");
     assert_eq!(strategy.name(), "SameSessionStrategy");
 }
+
+#[test]
+fn strategy_selector_rejects_excluded_sessions_before_selecting() {
+    let selector = StrategySelector::new().with_exclusions(SessionExclusions::new(&[
+        "**/experiments/**",
+        "!**/experiments/keep/**",
+    ]));
+    let reason = StopReason::ContextExhausted(None);
+
+    match selector.select_for(Path::new("/work/experiments/limits/session.md"), &reason) {
+        Selection::Excluded { pattern } => assert_eq!(pattern, "**/experiments/**"),
+        _ => panic!("expected excluded session"),
+    }
+    assert!(matches!(
+        selector.select_for(Path::new("/work/experiments/keep/session.md"), &reason),
+        Selection::Resume(_)
+    ));
+    assert!(matches!(
+        selector.select_for(Path::new("/work/other/session.md"), &reason),
+        Selection::Resume(_)
+    ));
+}
+
+#[test]
+fn strategy_selector_select_for_skips_completed_sessions() {
+    let selector = StrategySelector::new();
+
+    assert!(matches!(
+        selector.select_for(Path::new("/work/session.md"), &StopReason::Completed),
+        Selection::Skip
+    ));
+}
+
+#[test]
+fn strategy_selector_wraps_strategies_in_maintenance_deferral() {
+    let schedule = MaintenanceSchedule::parse(&["Tue 02:00-04:00 UTC"]).expect("schedule");
+    let selector = StrategySelector::new().with_maintenance(schedule);
+
+    let strategy = selector
+        .select(&StopReason::ContextExhausted(None))
+        .expect("strategy");
+    // The deferral keeps the inner strategy's name for logging.
+    assert_eq!(strategy.name(), "NewSessionStrategy");
+    assert!(selector.select(&StopReason::Completed).is_none());
+}
+
+#[tokio::test]
+async fn strategy_selector_cancels_maintenance_wait_on_shutdown() {
+    let now = chrono::Utc::now();
+    let window = format!(
+        "{}/{}",
+        (now - chrono::TimeDelta::hours(1)).to_rfc3339(),
+        (now + chrono::TimeDelta::hours(1)).to_rfc3339()
+    );
+    let schedule = MaintenanceSchedule::parse(&[window]).expect("schedule");
+    let shutdown = CancellationToken::new();
+    shutdown.cancel();
+    let selector = StrategySelector::new()
+        .with_maintenance(schedule)
+        .with_cancellation(shutdown);
+
+    let strategy = selector
+        .select(&StopReason::ContextExhausted(None))
+        .expect("strategy");
+    let ctx = ResumeContext::new(
+        Path::new("/work/session.md").to_path_buf(),
+        StopReason::ContextExhausted(None),
+    );
+    let outcome = tokio::time::timeout(Duration::from_secs(5), strategy.execute(&ctx))
+        .await
+        .expect("wait abandoned")
+        .expect("outcome");
+    assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+}
+
+#[test]
+fn strategy_selector_honors_sidecar_strategy_preference() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let session = temp.path().join("tests.md");
+    std::fs::write(&session, "").unwrap();
+    std::fs::write(
+        temp.path().join("tests.palingenesis.toml"),
+        "strategy = \"new_session\"\n",
+    )
+    .unwrap();
+    let selector = StrategySelector::with_unknown_default(UnknownStrategy::SameSession);
+    let reason = StopReason::Unknown("mystery".to_string());
+
+    match selector.select_for(&session, &reason) {
+        Selection::Resume(strategy) => assert_eq!(strategy.name(), "NewSessionStrategy"),
+        _ => panic!("expected a resume"),
+    }
+    match selector.select_for(&temp.path().join("other.md"), &reason) {
+        Selection::Resume(strategy) => assert_eq!(strategy.name(), "SameSessionStrategy"),
+        _ => panic!("expected a resume"),
+    }
+}
+
+#[test]
+fn strategy_selector_reports_malformed_sidecar_and_falls_back() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let session = temp.path().join("tests.md");
+    std::fs::write(&session, "").unwrap();
+    let sidecar = temp.path().join("tests.palingenesis.toml");
+    std::fs::write(&sidecar, "strategy = [\n").unwrap();
+    let events = EventBroadcaster::default();
+    let mut rx = events.subscribe();
+    let selector = StrategySelector::with_unknown_default(UnknownStrategy::SameSession)
+        .with_event_broadcaster(events);
+
+    match selector.select_for(&session, &StopReason::Unknown("mystery".to_string())) {
+        Selection::Resume(strategy) => assert_eq!(strategy.name(), "SameSessionStrategy"),
+        _ => panic!("expected a resume"),
+    }
+    match rx.try_recv().expect("sidecar warning") {
+        NotificationEvent::ResumeSidecarInvalid {
+            session_path,
+            sidecar_path,
+            ..
+        } => {
+            assert_eq!(session_path, session);
+            assert_eq!(sidecar_path, sidecar);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+}
+
+/// Every file under `dir` with its contents.
+fn snapshot(dir: &Path) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(snapshot(&path));
+        } else {
+            files.push((path.clone(), std::fs::read(&path).unwrap()));
+        }
+    }
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn observe_mode_stop_has_no_side_effects() {
+    let workspace = tempfile::tempdir().expect("tempdir");
+    let state = tempfile::tempdir().expect("tempdir");
+    let session = workspace.path().join("session.md");
+    std::fs::write(
+        &session,
+        "---\nstepsCompleted: [1, 2]\nlastStep: 2\n---\n# Session\n",
+    )
+    .unwrap();
+    std::fs::write(workspace.path().join("next-step.md"), "Step 3: finish\n").unwrap();
+    let before = snapshot(workspace.path());
+
+    let audit = AuditLogger::new(state.path());
+    let events = EventBroadcaster::default();
+    let mut rx = events.subscribe();
+    let selector = StrategySelector::new()
+        .with_mode(ModeSwitch::new(DaemonMode::Observe))
+        .with_audit(audit.clone())
+        .with_event_broadcaster(events);
+
+    for reason in [
+        StopReason::ContextExhausted(None),
+        StopReason::RateLimit(RateLimitInfo {
+            retry_after: Duration::from_secs(1),
+            source: RetryAfterSource::Header,
+            message: None,
+        }),
+    ] {
+        let Selection::Resume(strategy) = selector.select_for(&session, &reason) else {
+            panic!("expected a resume");
+        };
+        let outcome = strategy
+            .execute(&ResumeContext::new(session.clone(), reason))
+            .await
+            .expect("observed");
+        assert!(matches!(outcome, ResumeOutcome::Skipped { .. }));
+    }
+
+    assert_eq!(snapshot(workspace.path()), before);
+    let actions: Vec<String> = audit
+        .query()
+        .execute()
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.action_taken)
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            "OBSERVE: would run NewSessionStrategy resume",
+            "OBSERVE: would run SameSessionStrategy resume",
+        ]
+    );
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(NotificationEvent::ActionObserved { .. })
+    ));
+}
@@ -19,7 +19,7 @@ impl ResumeStrategy for MockStrategy {
         ))
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "MockStrategy"
     }
 }
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -26,6 +26,7 @@ impl ResumeTrigger for TestTrigger {
             return Err(ResumeError::CommandFailed {
                 command: "test".to_string(),
                 stderr: "fail".to_string(),
+                postmortem: None,
             });
         }
         Ok(())
@@ -49,8 +50,10 @@ async fn same_session_waits_for_retry_after() {
     };
     let ctx = ResumeContext::new(PathBuf::from("/tmp/session.md"), rate_limit_reason())
         .with_retry_after(Duration::from_secs(60));
-    let mut config = SameSessionConfig::default();
-    config.backoff_jitter = false;
+    let config = SameSessionConfig {
+        backoff_jitter: false,
+        ..SameSessionConfig::default()
+    };
     let strategy = SameSessionStrategy::with_config(config).with_trigger(trigger);
 
     let handle = tokio::spawn(async move { strategy.execute(&ctx).await });
@@ -113,8 +116,8 @@ async fn same_session_cancels_wait() {
 
 #[tokio::test]
 async fn same_session_updates_state_on_success() {
-    static ENV_LOCK: Mutex<()> = Mutex::new(());
-    let _lock = ENV_LOCK.lock().unwrap();
+    static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+    let _lock = ENV_LOCK.lock().await;
     let temp = tempfile::tempdir().unwrap();
     let state_dir = temp.path().join("state");
     unsafe {
@@ -132,10 +135,14 @@ async fn same_session_updates_state_on_success() {
         state: SessionState {
             steps_completed: vec![StepValue::Integer(1), StepValue::String("2".to_string())],
             last_step: Some(2),
+            total_steps: None,
             status: None,
             workflow_type: None,
             project_name: None,
             input_documents: Vec::new(),
+            model: None,
+            input_tokens: None,
+            output_tokens: None,
         },
     };
     let ctx = ResumeContext::new(session_path.clone(), rate_limit_reason())
@@ -171,8 +178,10 @@ async fn same_session_returns_delayed_on_trigger_failure() {
     };
     let ctx = ResumeContext::new(PathBuf::from("/tmp/session.md"), rate_limit_reason());
 
-    let mut config = SameSessionConfig::default();
-    config.max_retries = 2;
+    let config = SameSessionConfig {
+        max_retries: 2,
+        ..SameSessionConfig::default()
+    };
     let strategy = SameSessionStrategy::with_config(config).with_trigger(trigger);
 
     let outcome = strategy.execute(&ctx).await.expect("outcome");
@@ -190,8 +199,10 @@ async fn same_session_respects_max_retries() {
     let mut ctx = ResumeContext::new(PathBuf::from("/tmp/session.md"), rate_limit_reason());
     ctx.attempt_number = 3;
 
-    let mut config = SameSessionConfig::default();
-    config.max_retries = 2;
+    let config = SameSessionConfig {
+        max_retries: 2,
+        ..SameSessionConfig::default()
+    };
     let strategy = SameSessionStrategy::with_config(config).with_trigger(trigger);
 
     let outcome = strategy.execute(&ctx).await.expect("outcome");
@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use palingenesis::config::schema::{Config, ScheduleConfig, ScheduleTimezone};
+use palingenesis::daemon::schedules::Scheduler;
+use palingenesis::daemon::state::DaemonState;
+use palingenesis::daemon::suspend::Clock;
+use palingenesis::http::EventBroadcaster;
+use palingenesis::notify::events::NotificationEvent;
+use palingenesis::resume::{ResumeError, SessionCreator};
+use palingenesis::state::{
+    AuditLogger, CurrentSession, ScheduleRun, ScheduleRunOutcome, StateHandle, StateStore,
+};
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Wall time that only moves when told to.
+struct FakeClock {
+    wall: Mutex<DateTime<Utc>>,
+}
+
+impl FakeClock {
+    fn set(&self, at: &str) {
+        *self.wall.lock().unwrap() = at.parse().unwrap();
+    }
+}
+
+impl Clock for FakeClock {
+    fn monotonic(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::from(*self.wall.lock().unwrap())
+    }
+}
+
+/// Starts sessions at `<workdir>/created.md`, remembering each prompt.
+#[derive(Default)]
+struct MockCreator {
+    prompts: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl SessionCreator for MockCreator {
+    async fn create(&self, prompt: &str, session_dir: &Path) -> Result<PathBuf, ResumeError> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        Ok(session_dir.join("created.md"))
+    }
+}
+
+struct Harness {
+    state: DaemonState,
+    scheduler: Arc<Scheduler>,
+    store: StateHandle,
+    clock: Arc<FakeClock>,
+    creator: Arc<MockCreator>,
+    events: broadcast::Receiver<NotificationEvent>,
+    sessions: tempfile::TempDir,
+    state_dir: tempfile::TempDir,
+}
+
+fn harness(catch_up: bool) -> Harness {
+    let sessions = tempfile::tempdir().unwrap();
+    std::fs::create_dir(sessions.path().join("ci")).unwrap();
+    let state_dir = tempfile::tempdir().unwrap();
+
+    let mut config = Config::default();
+    config.monitoring.session_dir = sessions.path().to_path_buf();
+    config.schedules.push(ScheduleConfig {
+        name: "ci-triage".to_string(),
+        cron: None,
+        daily: Some("06:00".to_string()),
+        weekdays: None,
+        timezone: ScheduleTimezone::Utc,
+        workdir: PathBuf::from("ci"),
+        prompt: "Triage CI for {date}".to_string(),
+        assistant: None,
+        model: None,
+        enabled: true,
+        skip_if_active: true,
+        catch_up,
+    });
+    let state = DaemonState::new_without_auto_detection().with_config(config);
+    let creator = Arc::new(MockCreator::default());
+    state.set_session_creator(Arc::clone(&creator) as Arc<dyn SessionCreator>);
+
+    let store = StateHandle::new(StateStore::with_path(state_dir.path().join("state.json")));
+    let clock = Arc::new(FakeClock {
+        wall: Mutex::new("2026-03-20T05:59:30Z".parse().unwrap()),
+    });
+    let broadcaster = EventBroadcaster::new(16);
+    let events = broadcaster.subscribe();
+    let scheduler = Arc::new(
+        Scheduler::new(store.clone())
+            .with_clock(Arc::clone(&clock) as Arc<dyn Clock>)
+            .with_event_broadcaster(broadcaster)
+            .with_audit(AuditLogger::new(state_dir.path())),
+    );
+    Harness {
+        state,
+        scheduler,
+        store,
+        clock,
+        creator,
+        events,
+        sessions,
+        state_dir,
+    }
+}
+
+/// The next `scheduled_run` event as (outcome, session path, detail).
+async fn next_run_event(
+    events: &mut broadcast::Receiver<NotificationEvent>,
+) -> (String, Option<PathBuf>, Option<String>) {
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("scheduled_run event")
+            .unwrap();
+        if let NotificationEvent::ScheduledRun {
+            outcome,
+            session_path,
+            detail,
+            ..
+        } = event
+        {
+            return (outcome, session_path, detail);
+        }
+    }
+}
+
+fn last_run(store: &StateHandle) -> ScheduleRun {
+    store
+        .snapshot()
+        .last_schedule_run("ci-triage")
+        .cloned()
+        .expect("run recorded")
+}
+
+#[tokio::test]
+async fn due_schedules_start_a_session() {
+    let mut harness = harness(false);
+    assert!(harness.scheduler.tick(&harness.state).is_empty());
+
+    harness.clock.set("2026-03-20T06:00:10Z");
+    let runs = harness.scheduler.tick(&harness.state);
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].outcome, ScheduleRunOutcome::Started);
+    assert_eq!(runs[0].trigger, "scheduled");
+    assert_eq!(
+        runs[0].fired_at,
+        "2026-03-20T06:00:00Z".parse::<DateTime<Utc>>().unwrap()
+    );
+
+    let workdir = harness.sessions.path().canonicalize().unwrap().join("ci");
+    let (outcome, session, _) = next_run_event(&mut harness.events).await;
+    assert_eq!(outcome, "succeeded");
+    assert_eq!(session, Some(workdir.join("created.md")));
+    assert_eq!(
+        *harness.creator.prompts.lock().unwrap(),
+        ["Triage CI for 2026-03-20"]
+    );
+
+    let recorded = last_run(&harness.store);
+    assert_eq!(recorded.outcome, ScheduleRunOutcome::Succeeded);
+    assert_eq!(recorded.job_id, runs[0].job_id);
+    assert_eq!(harness.store.snapshot().schedule_runs.len(), 1);
+
+    let audit = std::fs::read_to_string(harness.state_dir.path().join("audit.jsonl")).unwrap();
+    let entry: Value = serde_json::from_str(audit.lines().last().unwrap()).unwrap();
+    assert_eq!(entry["event_type"], "scheduled_run");
+    assert_eq!(entry["outcome"], "success");
+    assert_eq!(entry["metadata"]["trigger"], "scheduled");
+
+    // The same occurrence does not fire twice.
+    harness.clock.set("2026-03-20T06:00:50Z");
+    assert!(harness.scheduler.tick(&harness.state).is_empty());
+}
+
+#[tokio::test]
+async fn firings_are_skipped_while_the_workdir_has_an_active_session() {
+    let mut harness = harness(false);
+    let active = harness.sessions.path().join("ci").join("active.md");
+    std::fs::write(&active, "---\nstatus: in-progress\n---\n").unwrap();
+    harness.store.update(|state| {
+        state.current_session = Some(CurrentSession {
+            path: active.clone(),
+            ..CurrentSession::default()
+        });
+    });
+
+    harness.scheduler.tick(&harness.state);
+    harness.clock.set("2026-03-20T06:00:10Z");
+    let runs = harness.scheduler.tick(&harness.state);
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].outcome, ScheduleRunOutcome::Skipped);
+    assert!(runs[0].job_id.is_none());
+
+    let (outcome, _, detail) = next_run_event(&mut harness.events).await;
+    assert_eq!(outcome, "skipped");
+    assert!(detail.unwrap().contains("still active"));
+    assert!(harness.creator.prompts.lock().unwrap().is_empty());
+    assert_eq!(
+        last_run(&harness.store).outcome,
+        ScheduleRunOutcome::Skipped
+    );
+}
+
+#[tokio::test]
+async fn catch_up_runs_only_the_most_recent_missed_firing() {
+    let mut harness = harness(true);
+    harness.store.update(|state| {
+        state.record_schedule_run(ScheduleRun {
+            schedule: "ci-triage".to_string(),
+            fired_at: "2026-03-17T06:00:00Z".parse().unwrap(),
+            trigger: "scheduled".to_string(),
+            outcome: ScheduleRunOutcome::Succeeded,
+            job_id: None,
+            session_path: None,
+            detail: None,
+        });
+    });
+    // Down since the 17th; the 18th, 19th and 20th were missed.
+    harness.clock.set("2026-03-20T09:00:00Z");
+
+    let runs = harness.scheduler.catch_up(&harness.state);
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].trigger, "catch_up");
+    assert_eq!(
+        runs[0].fired_at,
+        "2026-03-20T06:00:00Z".parse::<DateTime<Utc>>().unwrap()
+    );
+    let (outcome, _, _) = next_run_event(&mut harness.events).await;
+    assert_eq!(outcome, "succeeded");
+    assert_eq!(harness.creator.prompts.lock().unwrap().len(), 1);
+
+    // Ticks continue from startup rather than replaying the misses.
+    harness.clock.set("2026-03-20T09:01:00Z");
+    assert!(harness.scheduler.tick(&harness.state).is_empty());
+}
+
+#[tokio::test]
+async fn missed_firings_are_dropped_without_catch_up() {
+    let harness = harness(false);
+    harness.store.update(|state| {
+        state.record_schedule_run(ScheduleRun {
+            schedule: "ci-triage".to_string(),
+            fired_at: "2026-03-17T06:00:00Z".parse().unwrap(),
+            trigger: "scheduled".to_string(),
+            outcome: ScheduleRunOutcome::Succeeded,
+            job_id: None,
+            session_path: None,
+            detail: None,
+        });
+    });
+    harness.clock.set("2026-03-20T09:00:00Z");
+    assert!(harness.scheduler.catch_up(&harness.state).is_empty());
+    assert!(harness.creator.prompts.lock().unwrap().is_empty());
+}
@@ -60,6 +60,9 @@ async fn backup_prunes_when_exceeding_limit() {
         .await
         .expect("first backup");
     sleep(Duration::from_millis(1100)).await;
+    tokio::fs::write(&session, "session content, next step")
+        .await
+        .expect("session write");
     let second = backupper
         .create_backup(&session)
         .await
@@ -92,3 +95,139 @@ async fn concurrent_backups_succeed_for_multiple_sessions() {
     assert!(backup_b.exists());
     assert_ne!(backup_a, backup_b);
 }
+
+#[cfg(unix)]
+#[tokio::test]
+async fn backup_creates_private_backup_dir_from_template() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    let workspace = temp.path().join("proj");
+    tokio::fs::create_dir_all(&workspace)
+        .await
+        .expect("workspace");
+    let session = workspace.join("session.md");
+    tokio::fs::write(&session, "session content")
+        .await
+        .expect("session write");
+    let backup_dir = temp.path().join("local").join("backups");
+
+    let backupper = SessionBackup::with_config(BackupConfig {
+        backup_dir: Some(backup_dir.clone()),
+        filename_template: "{workspace}/{date}/{stem}-{timestamp}.{ext}".to_string(),
+        ..BackupConfig::default()
+    });
+    let backup_path = backupper.create_backup(&session).await.expect("backup");
+
+    let date = backup_path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str())
+        .expect("date dir");
+    assert_eq!(date.len(), 10);
+    assert!(backup_path.starts_with(backup_dir.join("proj")));
+    let mode = std::fs::metadata(&backup_dir)
+        .expect("backup dir")
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o700);
+    assert_eq!(
+        tokio::fs::read_to_string(&backup_path)
+            .await
+            .expect("read backup"),
+        "session content"
+    );
+
+    let mut entries = tokio::fs::read_dir(&workspace).await.expect("read dir");
+    let mut count = 0;
+    while entries.next_entry().await.expect("entry").is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 1, "nothing is written next to the session");
+}
+
+#[tokio::test]
+async fn identical_session_reuses_newest_backup() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let session = temp.path().join("session.md");
+    tokio::fs::write(&session, "session content")
+        .await
+        .expect("session write");
+
+    let backupper = SessionBackup::default();
+    let first = backupper.create_backup(&session).await.expect("first");
+    sleep(Duration::from_millis(1100)).await;
+    let second = backupper.create_backup(&session).await.expect("second");
+
+    assert_eq!(first, second);
+    let backups = backupper.list_backups(&session).await.expect("list");
+    assert_eq!(backups.len(), 1);
+    assert!(temp.path().join(".backup-index.json").exists());
+}
+
+#[tokio::test]
+async fn missing_or_corrupt_index_is_rebuilt() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let session = temp.path().join("session.md");
+    let index = temp.path().join(".backup-index.json");
+    tokio::fs::write(&session, "session content")
+        .await
+        .expect("session write");
+
+    let backupper = SessionBackup::default();
+    let first = backupper.create_backup(&session).await.expect("first");
+    tokio::fs::remove_file(&index).await.expect("remove index");
+    sleep(Duration::from_millis(1100)).await;
+    assert_eq!(
+        backupper.create_backup(&session).await.expect("second"),
+        first
+    );
+    assert!(index.exists());
+
+    tokio::fs::write(&index, "not json").await.expect("corrupt");
+    sleep(Duration::from_millis(1100)).await;
+    assert_eq!(
+        backupper.create_backup(&session).await.expect("third"),
+        first
+    );
+    let rebuilt = tokio::fs::read_to_string(&index).await.expect("index");
+    assert!(rebuilt.contains(&first.to_string_lossy().into_owned()));
+}
+
+#[tokio::test]
+async fn modified_session_or_disabled_dedupe_gets_fresh_backup() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let session = temp.path().join("session.md");
+    tokio::fs::write(&session, "session content")
+        .await
+        .expect("session write");
+
+    let backupper = SessionBackup::default();
+    let first = backupper.create_backup(&session).await.expect("first");
+    sleep(Duration::from_millis(1100)).await;
+    tokio::fs::write(&session, "session content, step 2 done")
+        .await
+        .expect("session write");
+    let second = backupper.create_backup(&session).await.expect("second");
+    assert_ne!(first, second);
+    assert_eq!(
+        tokio::fs::read_to_string(&second).await.expect("read"),
+        "session content, step 2 done"
+    );
+
+    let without_dedupe = SessionBackup::with_config(BackupConfig {
+        dedupe: false,
+        ..BackupConfig::default()
+    });
+    sleep(Duration::from_millis(1100)).await;
+    let third = without_dedupe.create_backup(&session).await.expect("third");
+    assert_ne!(second, third);
+    assert_eq!(
+        without_dedupe
+            .list_backups(&session)
+            .await
+            .expect("list")
+            .len(),
+        3
+    );
+}
@@ -5,8 +5,25 @@ use palingenesis::monitor::frontmatter::{
     ParseError, SessionParser, extract_frontmatter, parse_session,
 };
 use palingenesis::monitor::session::StepValue;
+use palingenesis::util::encoding::{EncodingFixups, SourceEncoding};
 use tempfile::tempdir;
 
+static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Route audit entries for rejected paths into a temporary state dir.
+fn with_temp_state<T>(f: impl FnOnce(&Path) -> T) -> T {
+    let _lock = ENV_LOCK.lock().unwrap();
+    let state = tempdir().unwrap();
+    unsafe {
+        std::env::set_var("PALINGENESIS_STATE", state.path());
+    }
+    let result = f(state.path());
+    unsafe {
+        std::env::remove_var("PALINGENESIS_STATE");
+    }
+    result
+}
+
 fn fixture_path(name: &str) -> PathBuf {
     Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("tests/fixtures")
@@ -107,3 +124,107 @@ body
         _ => panic!("expected SessionChanged event"),
     }
 }
+
+#[cfg(unix)]
+#[test]
+fn test_session_parser_rejects_symlink_outside_root() {
+    let temp = tempdir().unwrap();
+    let outside = tempdir().unwrap();
+    let secret = outside.path().join("passwd");
+    std::fs::write(&secret, "---\nstatus: complete\n---\n").unwrap();
+    let link = temp.path().join("session.md");
+    std::os::unix::fs::symlink(&secret, &link).unwrap();
+
+    let mut parser = SessionParser::new().with_root(temp.path().to_path_buf(), false);
+    let (event, audit) = with_temp_state(|state_dir| {
+        let event = parser.handle_event(WatchEvent::FileModified(link));
+        let audit = std::fs::read_to_string(state_dir.join("audit.jsonl")).unwrap();
+        (event, audit)
+    });
+
+    match event.expect("expected monitor event") {
+        MonitorEvent::Error { source, .. } => assert_eq!(source, "path_guard"),
+        _ => panic!("expected path_guard error"),
+    }
+    assert!(audit.contains("path_rejected"));
+}
+
+#[test]
+fn test_session_parser_rejects_dot_dot_path() {
+    let temp = tempdir().unwrap();
+    let nested = temp.path().join("sessions");
+    std::fs::create_dir_all(&nested).unwrap();
+    let escaped = nested.join("..").join("session.md");
+    std::fs::write(
+        temp.path().join("session.md"),
+        "---\nstatus: complete\n---\n",
+    )
+    .unwrap();
+
+    let mut parser = SessionParser::new().with_root(nested, true);
+    let event = with_temp_state(|_| parser.handle_event(WatchEvent::FileCreated(escaped)))
+        .expect("expected monitor event");
+
+    assert!(matches!(event, MonitorEvent::Error { source, .. } if source == "path_guard"));
+}
+
+#[test]
+fn test_session_parser_accepts_nested_path_under_root() {
+    let temp = tempdir().unwrap();
+    let nested = temp.path().join("project").join("sessions");
+    std::fs::create_dir_all(&nested).unwrap();
+    let path = nested.join("session.md");
+    std::fs::write(&path, "---\nstatus: in-progress\n---\n").unwrap();
+
+    let mut parser = SessionParser::new().with_root(temp.path().to_path_buf(), false);
+    let event = parser
+        .handle_event(WatchEvent::FileModified(path.clone()))
+        .expect("expected monitor event");
+
+    assert!(matches!(event, MonitorEvent::SessionChanged { session, .. } if session.path == path));
+}
+
+#[test]
+fn test_bom_utf16_and_crlf_sessions_parse_and_warn_once() {
+    let fixtures = [
+        ("session_utf8_bom.md", Some(SourceEncoding::Utf8Bom)),
+        ("session_utf16le.md", Some(SourceEncoding::Utf16Le)),
+        ("session_crlf.md", None),
+    ];
+    for (name, _) in fixtures {
+        let path = fixture_path(name);
+        let session = parse_session(&path).expect(name);
+        assert_eq!(session.state.steps_completed.len(), 3, "{name}");
+        assert_eq!(session.state.last_step, Some(3), "{name}");
+        assert_eq!(
+            session.state.status.as_deref(),
+            Some("in-progress"),
+            "{name}"
+        );
+        parse_session(&path).expect(name);
+    }
+
+    let pending = EncodingFixups::shared().take_pending();
+    for (name, encoding) in fixtures {
+        let path = fixture_path(name);
+        let warnings: Vec<_> = pending.iter().filter(|fixup| fixup.path == path).collect();
+        match encoding {
+            Some(encoding) => {
+                assert_eq!(warnings.len(), 1, "{name}");
+                assert_eq!(warnings[0].encoding, encoding, "{name}");
+            }
+            None => assert!(warnings.is_empty(), "{name}"),
+        }
+    }
+}
+
+#[test]
+fn test_malformed_utf16_session_names_the_encoding() {
+    let temp = tempdir().unwrap();
+    let path = temp.path().join("session.md");
+    std::fs::write(&path, [0xFE, 0xFF, 0xDC, 0x00, 0x00, 0x2D]).unwrap();
+
+    let error = parse_session(&path).expect_err("expected encoding error");
+    assert!(matches!(error, ParseError::Encoding(_)));
+    assert!(error.to_string().contains("UTF-16BE"));
+}
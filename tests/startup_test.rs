@@ -0,0 +1,149 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use palingenesis::daemon::events::{DaemonEventLoop, EventSources};
+use palingenesis::daemon::restart_correlation::CorrelationSettings;
+use palingenesis::daemon::{Daemon, DaemonState, Startup, Subsystem};
+use palingenesis::ipc::client::IpcClient;
+use palingenesis::monitor::classifier::{
+    ClassificationResult, RateLimitInfo, RetryAfterSource, StopReason,
+};
+use palingenesis::monitor::events::MonitorEvent;
+use palingenesis::monitor::session::{Session, SessionState};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+fn set_env_var(key: &str, value: impl AsRef<std::ffi::OsStr>) {
+    unsafe {
+        env::set_var(key, value);
+    }
+}
+
+fn isolate(root: &Path) {
+    let config = root.join("config.toml");
+    std::fs::write(&config, "[daemon]\nhttp_enabled = false\n").unwrap();
+    set_env_var("PALINGENESIS_CONFIG", &config);
+    set_env_var("PALINGENESIS_STATE", root.join("state"));
+    set_env_var("PALINGENESIS_RUNTIME", root.join("run"));
+}
+
+fn rate_limited_stop(path: &str) -> MonitorEvent {
+    let reason = StopReason::RateLimit(RateLimitInfo {
+        retry_after: Duration::from_secs(30),
+        source: RetryAfterSource::ConfigDefault,
+        message: None,
+    });
+    MonitorEvent::SessionStopped {
+        session: Some(Session {
+            path: PathBuf::from(path),
+            state: SessionState {
+                steps_completed: Vec::new(),
+                last_step: None,
+                total_steps: None,
+                status: Some("in-progress".to_string()),
+                workflow_type: None,
+                project_name: None,
+                input_documents: Vec::new(),
+                model: None,
+                input_tokens: None,
+                output_tokens: None,
+            },
+        }),
+        classification: ClassificationResult {
+            reason: reason.clone(),
+            confidence: 0.9,
+            evidence: Vec::new(),
+        },
+        reason,
+        process_info: None,
+        incident: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_answers_while_a_subsystem_is_still_initializing() {
+    let temp = tempfile::tempdir().unwrap();
+    isolate(temp.path());
+
+    let (release, released) = oneshot::channel::<()>();
+    let mut daemon = Daemon::new().with_subsystem_init(Subsystem::Classifier, async move {
+        let _ = released.await;
+    });
+    let socket = daemon.paths().socket_file().to_path_buf();
+    let shutdown = daemon.shutdown_token();
+    let started = Instant::now();
+    let running = tokio::spawn(async move { daemon.run().await });
+
+    let status = loop {
+        if let Ok(status) = IpcClient::status_at(socket.clone()).await {
+            break status;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(3),
+            "STATUS did not answer while the classifier was initializing"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(status.state, "monitoring");
+    assert!(!running.is_finished());
+
+    release.send(()).unwrap();
+    shutdown.cancel();
+    running.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn stops_queue_until_the_classifier_is_ready() {
+    let state = Arc::new(DaemonState::new_without_auto_detection());
+    let startup = Startup::new();
+    for subsystem in [
+        Subsystem::Metrics,
+        Subsystem::Notifications,
+        Subsystem::Http,
+    ] {
+        startup.mark_ready(subsystem, Duration::ZERO);
+    }
+    let (_signal_tx, signals) = mpsc::channel(4);
+    let (monitor_tx, monitor) = mpsc::channel(4);
+    let (dispatch_tx, mut dispatched) = mpsc::channel(4);
+    let event_loop = DaemonEventLoop::new(Arc::clone(&state))
+        .with_startup(startup.clone())
+        .with_monitor_dispatch(dispatch_tx)
+        .with_correlation_settings(CorrelationSettings {
+            settle: Duration::ZERO,
+            restart_timeout: Duration::from_secs(120),
+            grace: Duration::from_secs(60),
+        });
+    let cancel = CancellationToken::new();
+    let sources = EventSources {
+        signals,
+        opencode: None,
+        monitor: Some(monitor),
+        control: None,
+    };
+    let handle = tokio::spawn(event_loop.run(sources, cancel.clone()));
+
+    monitor_tx
+        .send(rate_limited_stop("/w/session.md"))
+        .await
+        .unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), dispatched.recv())
+            .await
+            .is_err(),
+        "stop dispatched before the classifier was ready"
+    );
+
+    startup.mark_ready(Subsystem::Classifier, Duration::from_millis(250));
+    let event = tokio::time::timeout(Duration::from_secs(5), dispatched.recv())
+        .await
+        .expect("queued stop dispatched once the classifier is ready")
+        .unwrap();
+    assert!(matches!(event, MonitorEvent::SessionStopped { .. }));
+    assert!(startup.is_complete());
+
+    cancel.cancel();
+    handle.await.unwrap();
+}
@@ -0,0 +1,284 @@
+//! Both state file formats must round-trip any state, and msgpack has to
+//! stay worth keeping next to JSON.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use palingenesis::state::format::{decode, encode};
+use palingenesis::state::{
+    CompletedSession, CurrentSession, DaemonState, OpenCodeVersionRecord, OrphanedSession,
+    ResumeHistory, SessionStatus, StateFile, StateFormat, StateStore, Stats, StepCompletion,
+    StepTimeline, WorktreeRecord,
+};
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+
+fn timestamp() -> impl Strategy<Value = DateTime<Utc>> {
+    (0i64..4_102_444_800, 0u32..1_000_000_000)
+        .prop_map(|(secs, nanos)| Utc.timestamp_opt(secs, nanos).unwrap())
+}
+
+fn path() -> impl Strategy<Value = PathBuf> {
+    "/[a-z0-9_.-]{1,12}(/[a-z0-9 _.-]{1,12}){0,3}".prop_map(PathBuf::from)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,40}"
+}
+
+fn current_session() -> impl Strategy<Value = CurrentSession> {
+    (
+        path(),
+        prop::collection::vec(any::<u32>(), 0..8),
+        any::<u32>(),
+        any::<u32>(),
+        prop_oneof![
+            Just(SessionStatus::Active),
+            Just(SessionStatus::Missing),
+            Just(SessionStatus::NeedsAttention),
+        ],
+        prop::option::of(text()),
+    )
+        .prop_map(
+            |(path, steps_completed, last_step, total_steps, status, model)| CurrentSession {
+                path,
+                steps_completed,
+                last_step,
+                total_steps,
+                status,
+                model,
+            },
+        )
+}
+
+fn stats() -> impl Strategy<Value = Stats> {
+    (
+        any::<u64>(),
+        any::<u64>(),
+        prop::option::of(timestamp()),
+        // Any finite value; JSON has no NaN or infinity.
+        prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO,
+    )
+        .prop_map(
+            |(saves_count, total_resumes, last_resume, time_saved_seconds)| Stats {
+                saves_count,
+                total_resumes,
+                last_resume,
+                time_saved_seconds,
+                ..Stats::default()
+            },
+        )
+}
+
+fn resume_history() -> impl Strategy<Value = ResumeHistory> {
+    (
+        path(),
+        prop::collection::vec(timestamp(), 0..6),
+        prop::option::of(timestamp()),
+        prop::option::of(path()),
+        prop::option::of(timestamp()),
+        prop::option::of(timestamp()),
+    )
+        .prop_map(
+            |(path, resumed_at, loop_suspected_at, last_incident, acknowledged_at, paused)| {
+                let mut history = ResumeHistory::new(path);
+                history.resumed_at = resumed_at;
+                history.loop_suspected_at = loop_suspected_at;
+                history.last_incident = last_incident;
+                history.acknowledged_at = acknowledged_at;
+                history.resumes_paused_at = paused;
+                history
+            },
+        )
+}
+
+fn step_timeline() -> impl Strategy<Value = StepTimeline> {
+    let step = (any::<u32>(), prop::option::of(timestamp()))
+        .prop_map(|(step, completed_at)| StepCompletion { step, completed_at });
+    (path(), prop::collection::vec(step, 0..6))
+        .prop_map(|(path, steps)| StepTimeline { path, steps })
+}
+
+fn completed_session() -> impl Strategy<Value = CompletedSession> {
+    (
+        path(),
+        timestamp(),
+        any::<u64>(),
+        any::<usize>(),
+        prop::option::of(any::<i64>()),
+        prop::option::of(text()),
+    )
+        .prop_map(
+            |(path, determined_at, content_len, steps_completed, last_step, status)| {
+                CompletedSession {
+                    path,
+                    determined_at,
+                    content_len,
+                    steps_completed,
+                    last_step,
+                    status,
+                }
+            },
+        )
+}
+
+fn state_file() -> impl Strategy<Value = StateFile> {
+    let orphan =
+        (path(), text(), timestamp()).prop_map(|(path, reason, recorded_at)| OrphanedSession {
+            path,
+            reason,
+            recorded_at,
+        });
+    let worktree = (path(), path(), text(), path(), path(), timestamp()).prop_map(
+        |(workspace, worktree, branch, source_session, session, created_at)| WorktreeRecord {
+            workspace,
+            worktree,
+            branch,
+            source_session,
+            session,
+            created_at,
+        },
+    );
+    let version = (text(), timestamp(), prop::collection::vec(text(), 0..3)).prop_map(
+        |(version, detected_at, problems)| OpenCodeVersionRecord {
+            version,
+            detected_at,
+            problems,
+        },
+    );
+    (
+        prop_oneof![
+            Just(DaemonState::Monitoring),
+            Just(DaemonState::Paused),
+            Just(DaemonState::Stopped),
+        ],
+        prop::option::of(current_session()),
+        stats(),
+        prop::collection::vec(orphan, 0..3),
+        prop::collection::vec(worktree, 0..3),
+        prop::collection::vec(resume_history(), 0..4),
+        prop::option::of(version),
+        prop::collection::vec(step_timeline(), 0..3),
+        prop::collection::vec(completed_session(), 0..3),
+    )
+        .prop_map(
+            |(
+                daemon_state,
+                current_session,
+                stats,
+                orphaned_sessions,
+                worktrees,
+                resume_history,
+                opencode_version,
+                step_timelines,
+                completed_sessions,
+            )| StateFile {
+                daemon_state,
+                current_session,
+                stats,
+                orphaned_sessions,
+                worktrees,
+                resume_history,
+                opencode_version,
+                step_timelines,
+                completed_sessions,
+                ..StateFile::default()
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn state_round_trips_through_both_formats(state in state_file()) {
+        for format in [StateFormat::Json, StateFormat::Msgpack] {
+            let bytes = encode(&state, format).unwrap();
+            prop_assert_eq!(decode(&bytes).unwrap(), (state.clone(), format));
+        }
+    }
+
+    #[test]
+    fn decoding_garbage_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = decode(&bytes);
+    }
+}
+
+#[test]
+fn store_round_trips_after_switching_formats() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("state.json");
+    let mut runner = proptest::test_runner::TestRunner::deterministic();
+    for _ in 0..16 {
+        let state = state_file().new_tree(&mut runner).unwrap().current();
+        for format in [
+            StateFormat::Msgpack,
+            StateFormat::Json,
+            StateFormat::Msgpack,
+        ] {
+            let store = StateStore::with_path(path.clone()).with_format(format);
+            store.save(&state).unwrap();
+            assert_eq!(store.inspect().unwrap(), (state.clone(), format));
+        }
+    }
+}
+
+/// A state as large as a long-running daemon keeps: every capped list full.
+fn busy_state() -> StateFile {
+    let at = Utc.with_ymd_and_hms(2026, 1, 6, 12, 0, 0).unwrap();
+    let session = |i: usize| PathBuf::from(format!("/home/dev/project-{i}/.sessions/session.md"));
+    let mut state = StateFile::default();
+    for i in 0..32 {
+        let mut history = ResumeHistory::new(session(i));
+        history.resumed_at = (0..10).map(|n| at + chrono::Duration::minutes(n)).collect();
+        state.resume_history.push(history);
+        state.completed_sessions.push(CompletedSession {
+            path: session(i),
+            determined_at: at,
+            content_len: 48_000,
+            steps_completed: 12,
+            last_step: Some(12),
+            status: Some("complete".to_string()),
+        });
+    }
+    for i in 0..16 {
+        let mut timeline = StepTimeline::new(session(i));
+        timeline.steps = (0..512)
+            .map(|step| StepCompletion {
+                step,
+                completed_at: Some(at + chrono::Duration::seconds(i64::from(step))),
+            })
+            .collect();
+        state.step_timelines.push(timeline);
+    }
+    state
+}
+
+fn time_per_round(format: StateFormat, state: &StateFile, rounds: u32) -> Duration {
+    let start = Instant::now();
+    for _ in 0..rounds {
+        let bytes = encode(state, format).unwrap();
+        std::hint::black_box(decode(&bytes).unwrap());
+    }
+    start.elapsed() / rounds
+}
+
+/// Documents why both formats are kept; run with `--nocapture` to see the
+/// numbers. Only the size is asserted, timings vary too much between hosts.
+#[test]
+fn msgpack_is_smaller_than_json_for_a_busy_state() {
+    let state = busy_state();
+    let json = encode(&state, StateFormat::Json).unwrap().len();
+    let msgpack = encode(&state, StateFormat::Msgpack).unwrap().len();
+    let json_time = time_per_round(StateFormat::Json, &state, 5);
+    let msgpack_time = time_per_round(StateFormat::Msgpack, &state, 5);
+    println!(
+        "busy state: json {json} bytes, {json_time:?} per save+load; \
+         msgpack {msgpack} bytes, {msgpack_time:?} per save+load"
+    );
+    assert!(
+        msgpack * 2 < json,
+        "msgpack ({msgpack} bytes) should be well under half of json ({json} bytes)"
+    );
+}
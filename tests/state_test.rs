@@ -56,8 +56,10 @@ fn test_state_persists_across_restarts() {
     set_env_var("PALINGENESIS_STATE", &state_dir);
 
     let store = StateStore::new();
-    let mut state = StateFile::default();
-    state.daemon_state = DaemonState::Monitoring;
+    let mut state = StateFile {
+        daemon_state: DaemonState::Monitoring,
+        ..Default::default()
+    };
     state.stats.saves_count = 42;
     store.save(&state).unwrap();
 
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use palingenesis::state::{
+    STATE_VERSION, StateError, StateFile, StateFormat, StateHandle, StateStore,
+};
+use serde_json::{Value, json};
+
+const NEWER: u32 = STATE_VERSION + 1;
+
+fn write_state(path: &Path, value: &Value) -> Vec<u8> {
+    let bytes = serde_json::to_vec_pretty(value).unwrap();
+    std::fs::write(path, &bytes).unwrap();
+    bytes
+}
+
+fn newer_state(dir: &Path) -> (PathBuf, Vec<u8>) {
+    let path = dir.join("state.json");
+    let bytes = write_state(
+        &path,
+        &json!({
+            "version": NEWER,
+            "daemon_state": "monitoring",
+            "current_session": null,
+            "stats": {"saves_count": 7, "total_resumes": 3},
+            "resume_budget": {"remaining": 12},
+        }),
+    );
+    (path, bytes)
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn newer_state_file_is_loaded_read_only_and_left_untouched() {
+    let temp = tempfile::tempdir().unwrap();
+    let (path, original) = newer_state(temp.path());
+
+    let store = StateStore::with_path(path.clone());
+    let state = store.load();
+    assert_eq!(state.stats.saves_count, 7);
+    assert_eq!(store.newer_version(), Some(NEWER));
+
+    let err = store.save(&StateFile::default()).unwrap_err();
+    assert!(matches!(err, StateError::NewerVersion { found, supported }
+            if found == NEWER && supported == STATE_VERSION));
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+    assert!(
+        !temp
+            .path()
+            .join(format!("state.v{NEWER}.downgraded.bak"))
+            .exists()
+    );
+}
+
+#[test]
+fn undecodable_newer_state_file_is_read_only_not_corrupted() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("state.json");
+    // A newer schema that changed the type of a field this build knows.
+    let original = write_state(
+        &path,
+        &json!({
+            "version": 99,
+            "daemon_state": "monitoring",
+            "current_session": null,
+            "stats": "moved to stats.json",
+        }),
+    );
+
+    let store = StateStore::with_path(path.clone());
+    assert!(matches!(
+        store.inspect(),
+        Err(StateError::NewerVersion { found: 99, .. })
+    ));
+    assert_eq!(store.load(), StateFile::default());
+    assert_eq!(store.newer_version(), Some(99));
+    assert!(matches!(
+        store.save(&StateFile::default()),
+        Err(StateError::NewerVersion { found: 99, .. })
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+    assert!(!temp.path().join("state.json.bak").exists());
+}
+
+#[test]
+fn read_only_handle_keeps_changes_in_memory() {
+    let temp = tempfile::tempdir().unwrap();
+    let (path, original) = newer_state(temp.path());
+
+    let handle = StateHandle::new(StateStore::with_path(path.clone()));
+    assert_eq!(handle.newer_version(), Some(NEWER));
+    let total = handle
+        .update_critical(|state| {
+            state.stats.total_resumes += 1;
+            state.stats.total_resumes
+        })
+        .expect("kept in memory");
+    assert_eq!(total, 4);
+    handle.update(|state| state.stats.saves_count += 1);
+    handle.flush().expect("nothing to write");
+
+    assert_eq!(handle.snapshot().stats.saves_count, 8);
+    assert!(!handle.is_dirty());
+    drop(handle);
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn force_downgrade_backs_up_then_rewrites() {
+    let temp = tempfile::tempdir().unwrap();
+    let (path, original) = newer_state(temp.path());
+
+    let store = StateStore::with_path(path.clone()).with_force_downgrade(true);
+    let state = store.load();
+
+    assert_eq!(store.newer_version(), None);
+    assert_eq!(state.version, STATE_VERSION);
+    let backup = temp.path().join(format!("state.v{NEWER}.downgraded.bak"));
+    assert_eq!(std::fs::read(&backup).unwrap(), original);
+
+    let rewritten = read_json(&path);
+    assert_eq!(rewritten["version"], STATE_VERSION);
+    assert_eq!(rewritten["stats"]["saves_count"], 7);
+    assert!(rewritten.get("resume_budget").is_none());
+    store.save(&state).expect("writable after downgrade");
+}
+
+#[test]
+fn unknown_fields_survive_a_save() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("state.json");
+    write_state(
+        &path,
+        &json!({
+            "version": STATE_VERSION,
+            "daemon_state": "stopped",
+            "current_session": null,
+            "stats": {"saves_count": 0, "total_resumes": 0},
+            "resume_budget": {"remaining": 12},
+        }),
+    );
+
+    let store = StateStore::with_path(path.clone());
+    let mut state = store.load();
+    state.stats.saves_count = 5;
+    store.save(&state).unwrap();
+
+    let saved = read_json(&path);
+    assert_eq!(saved["resume_budget"], json!({"remaining": 12}));
+    assert_eq!(saved["stats"]["saves_count"], 5);
+
+    let msgpack = StateStore::with_path(path.clone()).with_format(StateFormat::Msgpack);
+    let converted = msgpack.load();
+    assert_eq!(converted.extra["resume_budget"], json!({"remaining": 12}));
+    assert_eq!(
+        StateStore::with_path(path).load().extra,
+        converted.extra,
+        "msgpack keeps unknown fields too"
+    );
+}
+
+#[test]
+fn older_state_file_is_migrated() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("state.json");
+    write_state(
+        &path,
+        &json!({
+            "version": 0,
+            "daemon_state": "stopped",
+            "current_session": null,
+            "stats": {"saves_count": 2, "total_resumes": 0},
+        }),
+    );
+
+    let store = StateStore::with_path(path.clone());
+    let state = store.load();
+
+    assert_eq!(state.version, STATE_VERSION);
+    assert_eq!(store.newer_version(), None);
+    assert_eq!(read_json(&path)["version"], STATE_VERSION);
+}
@@ -0,0 +1,97 @@
+//! Lives in its own test binary: recording a sleep bumps a process-wide
+//! counter that every pending same-session resume observes.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+
+use palingenesis::daemon::suspend;
+use palingenesis::monitor::classifier::{RateLimitInfo, RetryAfterSource, StopReason};
+use palingenesis::resume::{
+    ResumeContext, ResumeError, ResumeOutcome, ResumeStrategy, ResumeTrigger, SameSessionStrategy,
+};
+use palingenesis::state::StateStore;
+
+struct CountingTrigger {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl ResumeTrigger for CountingTrigger {
+    async fn trigger(&self, _ctx: &ResumeContext) -> Result<(), ResumeError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn rate_limit() -> StopReason {
+    StopReason::RateLimit(RateLimitInfo {
+        retry_after: Duration::from_secs(300),
+        source: RetryAfterSource::Header,
+        message: None,
+    })
+}
+
+/// Run a resume that waits five minutes, recording a sleep halfway through
+/// and applying `while_asleep` to the session file.
+async fn resume_across_sleep(
+    session: &std::path::Path,
+    state: &std::path::Path,
+    while_asleep: impl FnOnce(),
+) -> (ResumeOutcome, usize) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let strategy = SameSessionStrategy::new()
+        .with_trigger(CountingTrigger {
+            calls: Arc::clone(&calls),
+        })
+        .with_state_store(StateStore::with_path(state.to_path_buf()));
+    let ctx = ResumeContext::new(session.to_path_buf(), rate_limit())
+        .with_retry_after(Duration::from_secs(300));
+    let handle = tokio::spawn(async move { strategy.execute(&ctx).await });
+
+    tokio::time::advance(Duration::from_secs(150)).await;
+    tokio::task::yield_now().await;
+    while_asleep();
+    suspend::record_suspend(Duration::from_secs(8 * 3600), None);
+    tokio::time::advance(Duration::from_secs(150)).await;
+
+    let outcome = handle.await.expect("task").expect("outcome");
+    (outcome, calls.load(Ordering::SeqCst))
+}
+
+#[tokio::test(start_paused = true)]
+async fn pending_resume_is_rechecked_after_sleep() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let state = temp.path().join("state.json");
+    let session = temp.path().join("session.md");
+    std::fs::write(&session, "Error: 429 Too Many Requests\n").expect("session");
+    let stopped_at = SystemTime::now() - Duration::from_secs(60);
+    std::fs::File::options()
+        .write(true)
+        .open(&session)
+        .and_then(|file| file.set_modified(stopped_at))
+        .expect("backdate session");
+
+    // Untouched session: the resume still fires.
+    let (outcome, calls) = resume_across_sleep(&session, &state, || {}).await;
+    assert!(outcome.is_success(), "unexpected outcome: {outcome:?}");
+    assert_eq!(calls, 1);
+
+    // Session worked on manually while the laptop slept: the resume is dropped.
+    let (outcome, calls) = resume_across_sleep(&session, &state, || {
+        std::fs::write(&session, "Done by hand.\n").expect("edit session");
+        std::fs::File::options()
+            .write(true)
+            .open(&session)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(1)))
+            .expect("touch session");
+    })
+    .await;
+    match outcome {
+        ResumeOutcome::Skipped { reason } => assert!(reason.contains("changed"), "{reason}"),
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+    assert_eq!(calls, 0);
+}
@@ -0,0 +1,43 @@
+#![cfg(unix)]
+
+use palingenesis::cli::commands::verify_install::{CheckOutcome, Verification};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn verify_install_passes_on_clean_environment() {
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().join("home");
+    std::fs::create_dir_all(&home).unwrap();
+    let state_dir = temp.path().join("state");
+    unsafe {
+        std::env::set_var("HOME", &home);
+        std::env::set_var("PALINGENESIS_CONFIG", temp.path().join("config.toml"));
+        std::env::set_var("PALINGENESIS_STATE", &state_dir);
+        std::env::set_var("PALINGENESIS_RUNTIME", temp.path().join("run"));
+    }
+
+    let report = Verification::new().run().await.expect("verification runs");
+
+    let outcomes: Vec<_> = report
+        .checks
+        .iter()
+        .map(|check| (check.name, check.outcome))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("config", CheckOutcome::Passed),
+            ("classifier", CheckOutcome::Passed),
+            ("backoff", CheckOutcome::Passed),
+            ("backup", CheckOutcome::Passed),
+            ("state", CheckOutcome::Passed),
+            ("ipc", CheckOutcome::Passed),
+            ("notifications", CheckOutcome::Skipped),
+        ],
+        "{report:#?}"
+    );
+    assert!(report.passed());
+    assert!(
+        !state_dir.join("state.json").exists(),
+        "the real state file is left alone"
+    );
+}